@@ -0,0 +1,282 @@
+//! This pod's side of the internal control plane (`roomler-ai-control`):
+//! what other pods may ask of it, and [`Control::peer`] to ask them.
+//! Started by `main` when `control.listen_addr` is set.
+
+use std::net::SocketAddr;
+
+use bson::oid::ObjectId;
+use mediasoup::prelude::{MediaKind, ProducerId};
+use roomler_ai_config::ControlSettings;
+use roomler_ai_control::{ControlPlane, ControlPlaneClient, ControlTls, proto};
+use tonic::transport::Channel;
+use tonic::{Request, Response, Status};
+use tracing::{info, warn};
+
+use crate::state::AppState;
+use crate::ws::conference_registry::Ownership;
+
+/// This pod's control plane: where it listens, its mTLS identity, and
+/// where the other pods listen.
+pub struct Control {
+    addr: SocketAddr,
+    tls: ControlTls,
+    peer_port: u16,
+}
+
+impl Control {
+    /// From `control.*`. `None` when `listen_addr` is empty; a
+    /// misconfiguration is logged and leaves the pod without one too.
+    pub fn from_settings(settings: &ControlSettings) -> Option<Self> {
+        if settings.listen_addr.is_empty() {
+            return None;
+        }
+        let addr: SocketAddr = match settings.listen_addr.parse() {
+            Ok(addr) => addr,
+            Err(e) => {
+                warn!(addr = %settings.listen_addr, %e, "Invalid control.listen_addr; control plane off");
+                return None;
+            }
+        };
+        let tls = match ControlTls::from_pem_files(
+            &settings.cert_path,
+            &settings.key_path,
+            &settings.ca_path,
+        ) {
+            Ok(tls) => tls,
+            Err(e) => {
+                warn!(%e, "Control plane needs control.cert_path, key_path and ca_path; control plane off");
+                return None;
+            }
+        };
+        let peer_port = match settings.peer_port {
+            0 => addr.port(),
+            port => port,
+        };
+        Some(Self {
+            addr,
+            tls,
+            peer_port,
+        })
+    }
+
+    /// Connect to the control plane of the pod at `instance_url`, its
+    /// `app.instance_url`.
+    pub async fn peer(&self, instance_url: &str) -> anyhow::Result<ControlPlaneClient<Channel>> {
+        let url = peer_url(instance_url, self.peer_port)
+            .ok_or_else(|| anyhow::anyhow!("Invalid instance URL {}", instance_url))?;
+        Ok(roomler_ai_control::connect(url, &self.tls).await?)
+    }
+}
+
+/// The control endpoint at the host of `instance_url`.
+fn peer_url(instance_url: &str, port: u16) -> Option<String> {
+    let url = reqwest::Url::parse(instance_url).ok()?;
+    // IPv6 hosts come in brackets
+    Some(format!("https://{}:{}", url.host_str()?, port))
+}
+
+/// `audio` or `video`, as the control messages carry it.
+pub fn kind_name(kind: MediaKind) -> &'static str {
+    match kind {
+        MediaKind::Audio => "audio",
+        MediaKind::Video => "video",
+    }
+}
+
+pub fn parse_kind(name: &str) -> Option<MediaKind> {
+    match name {
+        "audio" => Some(MediaKind::Audio),
+        "video" => Some(MediaKind::Video),
+        _ => None,
+    }
+}
+
+pub struct ControlService {
+    state: AppState,
+}
+
+impl ControlService {
+    pub fn new(state: AppState) -> Self {
+        Self { state }
+    }
+
+    /// This pod's base URL as other pods know it.
+    fn instance_url(&self) -> String {
+        self.state
+            .settings
+            .app
+            .instance_url
+            .clone()
+            .unwrap_or_default()
+    }
+}
+
+/// Run the control server until the process exits. Without a usable
+/// `control.*` configuration the pod has none.
+pub fn spawn(state: AppState) {
+    let Some(control) = state.control.clone() else {
+        return;
+    };
+    let (addr, tls) = (control.addr, control.tls.clone());
+    tokio::spawn(async move {
+        info!(%addr, "Control plane listening");
+        if let Err(e) = roomler_ai_control::serve(
+            ControlService::new(state),
+            addr,
+            &tls,
+            std::future::pending(),
+        )
+        .await
+        {
+            warn!(%e, "Control plane stopped");
+        }
+    });
+}
+
+fn object_id(raw: &str, what: &str) -> Result<ObjectId, Status> {
+    ObjectId::parse_str(raw).map_err(|_| Status::invalid_argument(format!("Invalid {}", what)))
+}
+
+#[tonic::async_trait]
+impl ControlPlane for ControlService {
+    async fn place_room(
+        &self,
+        request: Request<proto::PlaceRoomRequest>,
+    ) -> Result<Response<proto::PlaceRoomResponse>, Status> {
+        let room_id = object_id(&request.get_ref().room_id, "room ID")?;
+        if let Some(registry) = &self.state.conference_registry {
+            let ownership = registry
+                .claim(&room_id)
+                .await
+                .map_err(|e| Status::unavailable(format!("Conference registry: {}", e)))?;
+            if let Ownership::Remote(owner_url) = ownership {
+                return Ok(Response::new(proto::PlaceRoomResponse {
+                    owner_url,
+                    local: false,
+                }));
+            }
+        }
+        self.state
+            .room_manager
+            .create_room(room_id)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::PlaceRoomResponse {
+            owner_url: self.instance_url(),
+            local: true,
+        }))
+    }
+
+    async fn setup_pipe(
+        &self,
+        request: Request<proto::SetupPipeRequest>,
+    ) -> Result<Response<proto::SetupPipeResponse>, Status> {
+        let request = request.into_inner();
+        let room_id = object_id(&request.room_id, "room ID")?;
+        let producer_id = request
+            .producer_id
+            .parse::<ProducerId>()
+            .map_err(|_| Status::invalid_argument("Invalid producer ID"))?;
+        let ip = request
+            .ip
+            .parse()
+            .map_err(|_| Status::invalid_argument("Invalid pipe address"))?;
+        let port = u16::try_from(request.port)
+            .map_err(|_| Status::invalid_argument("Invalid pipe address"))?;
+        if !self.state.room_manager.has_room(&room_id) {
+            return Err(Status::not_found("Room not found"));
+        }
+        let piped = self
+            .state
+            .room_manager
+            .pipe_producer(&room_id, producer_id, SocketAddr::new(ip, port))
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        let rtp_parameters_json = serde_json::to_string(&piped.rtp_parameters)
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::SetupPipeResponse {
+            ip: piped.ip,
+            port: piped.port.into(),
+            kind: kind_name(piped.kind).to_string(),
+            rtp_parameters_json,
+        }))
+    }
+
+    async fn list_producers(
+        &self,
+        request: Request<proto::ListProducersRequest>,
+    ) -> Result<Response<proto::ListProducersResponse>, Status> {
+        let room_id = object_id(&request.get_ref().room_id, "room ID")?;
+        // An edge only relays the call; listing its producers would pipe
+        // them back to where they came from
+        if !self.state.room_manager.has_room(&room_id) || self.state.room_manager.is_edge(&room_id)
+        {
+            return Err(Status::not_found("Room not found"));
+        }
+        let producers = self
+            .state
+            .room_manager
+            .get_producer_ids(&room_id, "")
+            .into_iter()
+            .map(
+                |(user_id, connection_id, producer_id, kind, source)| proto::ProducerInfo {
+                    producer_id: producer_id.to_string(),
+                    user_id: user_id.to_hex(),
+                    connection_id,
+                    kind: kind_name(kind).to_string(),
+                    source,
+                },
+            )
+            .collect();
+        Ok(Response::new(proto::ListProducersResponse { producers }))
+    }
+
+    async fn migrate_participant(
+        &self,
+        request: Request<proto::MigrateParticipantRequest>,
+    ) -> Result<Response<proto::MigrateParticipantResponse>, Status> {
+        let request = request.into_inner();
+        let room_id = object_id(&request.room_id, "room ID")?;
+        let user_id = object_id(&request.user_id, "user ID")?;
+        if request.target_url.is_empty() {
+            return Err(Status::invalid_argument("target_url is required"));
+        }
+        // The client reconnects to the target like after a `media:join`
+        // redirect; the media it leaves here goes through the usual
+        // reconnect grace when its socket drops.
+        let msg = serde_json::json!({
+            "type": "media:redirect",
+            "data": { "room_id": room_id.to_hex(), "url": request.target_url }
+        });
+        let connections = self
+            .state
+            .room_manager
+            .get_user_connection_ids(&room_id, &user_id);
+        for connection_id in &connections {
+            crate::ws::dispatcher::send_to_connection(&self.state.ws_storage, connection_id, &msg)
+                .await;
+        }
+        info!(?room_id, ?user_id, url = %request.target_url, count = connections.len(), "Participant migrated");
+        Ok(Response::new(proto::MigrateParticipantResponse {
+            connections: connections.len() as u32,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn peers_are_reached_at_their_instance_host() {
+        assert_eq!(
+            peer_url("https://pod-2.roomler.svc:3000/", 7443).as_deref(),
+            Some("https://pod-2.roomler.svc:7443")
+        );
+        assert_eq!(
+            peer_url("http://[fd00::2]:3000", 7443).as_deref(),
+            Some("https://[fd00::2]:7443")
+        );
+        assert_eq!(peer_url("not a url", 7443), None);
+    }
+}
@@ -1,11 +1,14 @@
 use axum::{
     Json,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
     response::{IntoResponse, Response},
 };
 use roomler_ai_services::auth::AuthError;
 use roomler_ai_services::dao::base::DaoError;
 use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::middleware::storage_quota::QuotaExceeded;
 
 #[derive(Debug)]
 pub enum ApiError {
@@ -16,6 +19,19 @@ pub enum ApiError {
     Conflict(String),
     Internal(String),
     Validation(String),
+    /// 402; the tenant's plan doesn't allow it.
+    PlanLimit(String),
+    /// 403 `room_read_only`; nobody may post in the room.
+    RoomReadOnly(String),
+    /// 403 `announcement_only`; only organizers may post in the room.
+    AnnouncementOnly(String),
+    /// 409 `room_full`; the call is at its participant cap.
+    RoomFull(String),
+    /// 507 `storage_quota`; the upload doesn't fit the tenant's storage
+    /// quota. The numbers go out as the body's `details`.
+    StorageQuota(QuotaExceeded),
+    /// 429; the payload is the `Retry-After` delay in seconds.
+    TooManyRequests(u64),
 }
 
 impl std::fmt::Display for ApiError {
@@ -28,14 +44,26 @@ impl std::fmt::Display for ApiError {
             ApiError::Conflict(msg) => write!(f, "Conflict: {msg}"),
             ApiError::Internal(msg) => write!(f, "Internal error: {msg}"),
             ApiError::Validation(msg) => write!(f, "Validation: {msg}"),
+            ApiError::PlanLimit(msg) => write!(f, "Plan limit: {msg}"),
+            ApiError::RoomReadOnly(msg) => write!(f, "Room read-only: {msg}"),
+            ApiError::AnnouncementOnly(msg) => write!(f, "Announcement only: {msg}"),
+            ApiError::RoomFull(msg) => write!(f, "Room full: {msg}"),
+            ApiError::StorageQuota(exceeded) => write!(f, "{exceeded}"),
+            ApiError::TooManyRequests(secs) => write!(f, "Too many requests: retry in {secs}s"),
         }
     }
 }
 
-#[derive(Serialize)]
-struct ErrorResponse {
+/// Body of every error response.
+#[derive(Serialize, ToSchema)]
+pub(crate) struct ErrorResponse {
+    /// Machine-readable kind, e.g. `not_found` or `validation`.
     error: String,
     message: String,
+    /// Structured context of some errors, e.g. the numbers of a
+    /// `storage_quota` refusal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<serde_json::Value>,
 }
 
 impl IntoResponse for ApiError {
@@ -51,6 +79,14 @@ impl IntoResponse for ApiError {
         if let ApiError::Internal(msg) = &self {
             tracing::error!(message = %msg, "ApiError::Internal -> 500");
         }
+        let retry_after = match &self {
+            ApiError::TooManyRequests(secs) => Some(*secs),
+            _ => None,
+        };
+        let details = match &self {
+            ApiError::StorageQuota(exceeded) => serde_json::to_value(exceeded).ok(),
+            _ => None,
+        };
         let (status, error_type, message) = match self {
             ApiError::NotFound(msg) => (StatusCode::NOT_FOUND, "not_found", msg),
             ApiError::BadRequest(msg) => (StatusCode::BAD_REQUEST, "bad_request", msg),
@@ -59,14 +95,35 @@ impl IntoResponse for ApiError {
             ApiError::Conflict(msg) => (StatusCode::CONFLICT, "conflict", msg),
             ApiError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, "internal", msg),
             ApiError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, "validation", msg),
+            ApiError::PlanLimit(msg) => (StatusCode::PAYMENT_REQUIRED, "plan_limit", msg),
+            ApiError::RoomReadOnly(msg) => (StatusCode::FORBIDDEN, "room_read_only", msg),
+            ApiError::AnnouncementOnly(msg) => (StatusCode::FORBIDDEN, "announcement_only", msg),
+            ApiError::RoomFull(msg) => (StatusCode::CONFLICT, "room_full", msg),
+            ApiError::StorageQuota(exceeded) => (
+                StatusCode::INSUFFICIENT_STORAGE,
+                "storage_quota",
+                exceeded.to_string(),
+            ),
+            ApiError::TooManyRequests(secs) => (
+                StatusCode::TOO_MANY_REQUESTS,
+                "rate_limited",
+                format!("Too many requests, retry in {secs}s"),
+            ),
         };
 
         let body = ErrorResponse {
             error: error_type.to_string(),
             message,
+            details,
         };
 
-        (status, Json(body)).into_response()
+        let mut response = (status, Json(body)).into_response();
+        if let Some(secs) = retry_after {
+            response
+                .headers_mut()
+                .insert(header::RETRY_AFTER, HeaderValue::from(secs));
+        }
+        response
     }
 }
 
@@ -80,6 +137,7 @@ impl From<DaoError> for ApiError {
             DaoError::Mongo(e) => ApiError::Internal(e.to_string()),
             DaoError::BsonSer(e) => ApiError::Internal(e.to_string()),
             DaoError::BsonDe(e) => ApiError::Internal(e.to_string()),
+            DaoError::Unscoped(msg) => ApiError::Internal(msg),
         }
     }
 }
@@ -109,6 +167,25 @@ impl From<roomler_ai_services::oauth::OAuthError> for ApiError {
             roomler_ai_services::oauth::OAuthError::InvalidState => {
                 ApiError::BadRequest("Invalid OAuth state".to_string())
             }
+            roomler_ai_services::oauth::OAuthError::UnverifiedEmail => {
+                ApiError::BadRequest(err.to_string())
+            }
+            other => ApiError::Internal(other.to_string()),
+        }
+    }
+}
+
+impl From<roomler_ai_services::calendar::CalendarError> for ApiError {
+    fn from(err: roomler_ai_services::calendar::CalendarError) -> Self {
+        use roomler_ai_services::calendar::CalendarError;
+        match err {
+            CalendarError::NotConfigured(msg) => {
+                ApiError::BadRequest(format!("Calendar provider not configured: {msg}"))
+            }
+            CalendarError::UnknownProvider(msg) => {
+                ApiError::BadRequest(format!("Unknown calendar provider: {msg}"))
+            }
+            CalendarError::Dao(e) => e.into(),
             other => ApiError::Internal(other.to_string()),
         }
     }
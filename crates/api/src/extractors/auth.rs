@@ -1,9 +1,9 @@
 use axum::{
-    extract::FromRequestParts,
-    http::{header, request::Parts},
+    extract::{FromRequestParts, OriginalUri},
+    http::{Method, header, request::Parts},
 };
 use bson::oid::ObjectId;
-use roomler_ai_services::auth::Claims;
+use roomler_ai_services::auth::{BotClaims, Claims, TokenType};
 
 use crate::{error::ApiError, state::AppState};
 
@@ -15,6 +15,16 @@ pub struct AuthUser {
     pub email: String,
     pub username: String,
     pub claims: Claims,
+    /// Set when the caller is a bot token: the only tenant and rooms the
+    /// request may touch.
+    pub bot: Option<BotScope>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BotScope {
+    pub tenant_id: ObjectId,
+    pub token_id: ObjectId,
+    pub room_ids: Vec<ObjectId>,
 }
 
 impl<S> FromRequestParts<S> for AuthUser
@@ -49,7 +59,15 @@ where
             })
             .ok_or_else(|| ApiError::Unauthorized("No token provided".to_string()))?;
 
-        let claims = app_state.auth.verify_access_token(&token)?;
+        let claims = match app_state.auth.verify_access_token(&token) {
+            Ok(claims) => claims,
+            Err(err) => {
+                return match app_state.auth.verify_bot_token(&token) {
+                    Ok(bot) => authenticate_bot(&app_state, parts, bot).await,
+                    Err(_) => Err(err.into()),
+                };
+            }
+        };
 
         let user_id = ObjectId::parse_str(&claims.sub)
             .map_err(|_| ApiError::Unauthorized("Invalid user ID in token".to_string()))?;
@@ -59,10 +77,94 @@ where
             email: claims.email.clone(),
             username: claims.username.clone(),
             claims,
+            bot: None,
         })
     }
 }
 
+/// Resolve a bot token: it must still be active, and the request must be one
+/// of the room read/post endpoints for a room in its scope.
+async fn authenticate_bot(
+    state: &AppState,
+    parts: &Parts,
+    bot: BotClaims,
+) -> Result<AuthUser, ApiError> {
+    let parse = |s: &str| {
+        ObjectId::parse_str(s).map_err(|_| ApiError::Unauthorized("Invalid bot token".to_string()))
+    };
+    let (bot_id, tenant_id, token_id) =
+        (parse(&bot.sub)?, parse(&bot.tenant_id)?, parse(&bot.jti)?);
+
+    let token = state
+        .bot_tokens
+        .find_active(tenant_id, bot_id, token_id)
+        .await
+        .map_err(|_| ApiError::Unauthorized("Bot token revoked".to_string()))?;
+
+    // Nested routers see a stripped `uri`; scope checks need the full path.
+    let path = parts
+        .extensions
+        .get::<OriginalUri>()
+        .map(|u| u.path().to_string())
+        .unwrap_or_else(|| parts.uri.path().to_string());
+    if !bot_may_access(&parts.method, &path, tenant_id, &token.room_ids) {
+        return Err(ApiError::Forbidden(
+            "Bot token not allowed for this endpoint".to_string(),
+        ));
+    }
+
+    let user = state
+        .users
+        .base
+        .find_by_id(bot_id)
+        .await
+        .ok()
+        .filter(|u| u.is_bot && u.deleted_at.is_none())
+        .ok_or_else(|| ApiError::Unauthorized("Bot no longer exists".to_string()))?;
+
+    Ok(AuthUser {
+        user_id: bot_id,
+        email: user.email.clone(),
+        username: user.username.clone(),
+        claims: Claims {
+            sub: bot.sub,
+            email: user.email,
+            username: user.username,
+            iat: bot.iat,
+            exp: bot.exp,
+            iss: bot.iss,
+            token_type: TokenType::Bot,
+        },
+        bot: Some(BotScope {
+            tenant_id,
+            token_id,
+            room_ids: token.room_ids,
+        }),
+    })
+}
+
+/// Bots may list rooms and members, read a scoped room and its members, and
+/// use everything under a scoped room's `/message` (read, post, edit, react,
+/// mark read). Calls, files, admin and account endpoints are off limits.
+fn bot_may_access(method: &Method, path: &str, tenant_id: ObjectId, room_ids: &[ObjectId]) -> bool {
+    let Some(rest) = path.strip_prefix(&format!("/api/tenant/{}", tenant_id.to_hex())) else {
+        return false;
+    };
+    let segments: Vec<&str> = rest.trim_end_matches('/').split('/').skip(1).collect();
+    let in_scope = |rid: &str| {
+        ObjectId::parse_str(rid)
+            .map(|rid| room_ids.contains(&rid))
+            .unwrap_or(false)
+    };
+    let read = method == Method::GET;
+    match segments.as_slice() {
+        ["member"] | ["room"] => read,
+        ["room", rid] | ["room", rid, "member"] => read && in_scope(rid),
+        ["room", rid, "message", ..] => in_scope(rid),
+        _ => false,
+    }
+}
+
 /// Optional auth extractor — returns `Option<AuthUser>`, never rejects.
 /// Use for endpoints that behave differently for authenticated vs unauthenticated users.
 pub struct OptionalAuthUser(pub Option<AuthUser>);
@@ -91,3 +193,38 @@ impl FromRef<AppState> for AppState {
         input.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bot_access_is_limited_to_scoped_room_messages() {
+        let tenant = ObjectId::new();
+        let room = ObjectId::new();
+        let other = ObjectId::new();
+        let scope = [room];
+        let base = format!("/api/tenant/{}", tenant.to_hex());
+        let ok = |m: Method, p: String| bot_may_access(&m, &p, tenant, &scope);
+
+        assert!(ok(Method::GET, format!("{base}/room")));
+        assert!(ok(Method::GET, format!("{base}/room/{room}")));
+        assert!(ok(Method::POST, format!("{base}/room/{room}/message")));
+        assert!(ok(Method::POST, format!("{base}/room/{room}/message/")));
+        assert!(ok(
+            Method::DELETE,
+            format!("{base}/room/{room}/message/{other}/reaction/x")
+        ));
+
+        assert!(!ok(Method::PUT, format!("{base}/room/{room}")));
+        assert!(!ok(Method::POST, format!("{base}/room/{other}/message")));
+        assert!(!ok(Method::POST, format!("{base}/room/{room}/call/join")));
+        assert!(!ok(Method::GET, format!("{base}/room/explore")));
+        assert!(!ok(Method::GET, format!("{base}/audit")));
+        assert!(!ok(
+            Method::GET,
+            format!("/api/tenant/{}/room", ObjectId::new().to_hex())
+        ));
+        assert!(!ok(Method::GET, "/api/auth/me".to_string()));
+    }
+}
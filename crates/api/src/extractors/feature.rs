@@ -0,0 +1,84 @@
+use std::sync::Arc;
+
+use axum::extract::FromRequestParts;
+use axum::http::request::Parts;
+use bson::oid::ObjectId;
+use roomler_ai_config::RuntimeSettings;
+use roomler_ai_db::models::FeatureFlag;
+use roomler_ai_services::feature_flags;
+
+use super::auth::{AuthUser, FromRef};
+use super::tenant::TenantId;
+use crate::{error::ApiError, state::AppState};
+
+/// The caller's feature flags in the tenant of the `:tenant_id` path
+/// parameter. WebSocket handlers, which have no extractors, use
+/// [`Features::load`].
+#[derive(Debug, Clone)]
+pub struct Features {
+    user_id: ObjectId,
+    flags: Vec<FeatureFlag>,
+    runtime: Arc<RuntimeSettings>,
+}
+
+impl Features {
+    pub async fn load(
+        state: &AppState,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> Result<Self, ApiError> {
+        Ok(Self {
+            user_id,
+            flags: state.feature_flags.list_for_tenant(tenant_id).await?,
+            runtime: state.runtime.load(),
+        })
+    }
+
+    /// The tenant's setting for `key`, if it has one.
+    pub fn flag(&self, key: &str) -> Option<&FeatureFlag> {
+        self.flags.iter().find(|f| f.key == key)
+    }
+
+    /// The deployment's default for `key`, which the tenant's `default`
+    /// mode follows.
+    pub fn deployment_default(&self, key: &str) -> bool {
+        feature_flags::deployment_default(key, &self.runtime.features)
+    }
+
+    /// Whether `key` is on for the caller.
+    pub fn enabled(&self, key: &str) -> bool {
+        self.enabled_for(key, &self.user_id)
+    }
+
+    /// Whether `key` is on for `subject`, for flags decided per room
+    /// rather than per user.
+    pub fn enabled_for(&self, key: &str, subject: &ObjectId) -> bool {
+        feature_flags::is_enabled(key, self.flag(key), &self.runtime.features, subject)
+    }
+
+    /// 403 unless `key` is on for the caller.
+    pub fn require(&self, key: &str) -> Result<(), ApiError> {
+        if self.enabled(key) {
+            Ok(())
+        } else {
+            Err(ApiError::Forbidden(format!(
+                "The {} feature is not enabled",
+                key
+            )))
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Features
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let TenantId(tenant_id) = TenantId::from_request_parts(parts, state).await?;
+        let auth = AuthUser::from_request_parts(parts, state).await?;
+        Features::load(&AppState::from_ref(state), tenant_id, auth.user_id).await
+    }
+}
@@ -0,0 +1,400 @@
+use axum::extract::{FromRequestParts, Query};
+use axum::http::request::Parts;
+use bson::{Bson, Document, doc, oid::ObjectId};
+use roomler_ai_services::dao::base::{ListOptions, PaginationParams};
+use utoipa::IntoParams;
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn, ParameterStyle};
+use utoipa::openapi::{ObjectBuilder, Required, Type};
+
+use crate::error::ApiError;
+
+/// How a filter value from the query string is converted before it reaches
+/// MongoDB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldKind {
+    String,
+    ObjectId,
+    /// RFC 3339 timestamp.
+    DateTime,
+    Bool,
+    Int,
+}
+
+/// A field a list endpoint allows clients to filter on.
+#[derive(Debug, Clone, Copy)]
+pub struct FilterField {
+    /// Name used in the query string (`filter[<name>]`).
+    pub name: &'static str,
+    /// Document path in the collection.
+    pub field: &'static str,
+    pub kind: FieldKind,
+}
+
+impl FilterField {
+    pub const fn new(name: &'static str, field: &'static str, kind: FieldKind) -> Self {
+        Self { name, field, kind }
+    }
+}
+
+/// Per-endpoint whitelist of sortable and filterable fields. Anything not
+/// listed is rejected with 400 rather than passed through to the database.
+#[derive(Debug, Clone, Copy)]
+pub struct ListSpec {
+    /// `(query name, document path)` pairs accepted in `sort`.
+    pub sort: &'static [(&'static str, &'static str)],
+    pub filters: &'static [FilterField],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FilterOp {
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    In,
+    Contains,
+    Exists,
+}
+
+impl FilterOp {
+    fn parse(op: &str) -> Option<Self> {
+        Some(match op {
+            "eq" => Self::Eq,
+            "ne" => Self::Ne,
+            "gt" => Self::Gt,
+            "gte" => Self::Gte,
+            "lt" => Self::Lt,
+            "lte" => Self::Lte,
+            "in" => Self::In,
+            "contains" => Self::Contains,
+            "exists" => Self::Exists,
+            _ => return None,
+        })
+    }
+}
+
+/// Shared query parameters for list endpoints.
+///
+/// Pagination: `page` + `per_page` (offset mode) or `cursor` + `per_page`
+/// (keyset mode; an empty `cursor=` fetches the first page, later pages pass
+/// the previous page's `next_cursor`).
+/// Sorting: `sort=-created_at,filename` (`-` = descending).
+/// Filtering: `filter[field]=value` or `filter[field][op]=value` with `op` one
+/// of `eq ne gt gte lt lte in contains exists` (`in` takes a comma list).
+#[derive(Debug, Clone, Default)]
+pub struct ListQuery {
+    pub pagination: PaginationParams,
+    sort: Option<String>,
+    filters: Vec<(String, String, String)>,
+}
+
+impl<S> FromRequestParts<S> for ListQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let Query(pairs): Query<Vec<(String, String)>> = Query::from_request_parts(parts, state)
+            .await
+            .map_err(|_| ApiError::BadRequest("Invalid query string".to_string()))?;
+        Self::from_pairs(pairs)
+    }
+}
+
+/// The pagination parameters plus `sort` and the `filter[...]` deep object.
+/// Which fields each endpoint accepts is listed in `docs/api.md`.
+impl IntoParams for ListQuery {
+    fn into_params(parameter_in_provider: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        let mut params = PaginationParams::into_params(&parameter_in_provider);
+        params.push(
+            ParameterBuilder::new()
+                .name("sort")
+                .parameter_in(ParameterIn::Query)
+                .required(Required::False)
+                .description(Some(
+                    "Comma-separated sort fields, `-` prefix for descending",
+                ))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::String)))
+                .example(Some("-created_at".into()))
+                .build(),
+        );
+        params.push(
+            ParameterBuilder::new()
+                .name("filter")
+                .parameter_in(ParameterIn::Query)
+                .required(Required::False)
+                .description(Some(
+                    "`filter[field]=value` or `filter[field][op]=value`, op one of \
+                     `eq ne gt gte lt lte in contains exists`",
+                ))
+                .schema(Some(ObjectBuilder::new().schema_type(Type::Object)))
+                .style(Some(ParameterStyle::DeepObject))
+                .explode(Some(true))
+                .build(),
+        );
+        params
+    }
+}
+
+impl ListQuery {
+    fn from_pairs(pairs: Vec<(String, String)>) -> Result<Self, ApiError> {
+        let mut query = ListQuery::default();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "page" => query.pagination.page = parse_u64("page", &value)?,
+                "per_page" => query.pagination.per_page = parse_u64("per_page", &value)?,
+                "before" => query.pagination.before = Some(value),
+                "cursor" => query.pagination.cursor = Some(value),
+                "sort" => query.sort = Some(value),
+                _ => {
+                    if let Some((field, op)) = parse_filter_key(&key) {
+                        query.filters.push((field, op, value));
+                    }
+                    // Other keys belong to the endpoint (e.g. `q`) — ignore.
+                }
+            }
+        }
+        Ok(query)
+    }
+
+    /// Validate sort and filters against `spec` and build the DAO options.
+    pub fn options(&self, spec: &ListSpec) -> Result<ListOptions, ApiError> {
+        let sort = match self.sort.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(raw) => {
+                let mut sort = Document::new();
+                for key in raw.split(',').map(str::trim).filter(|k| !k.is_empty()) {
+                    let (name, dir) = match key.strip_prefix('-') {
+                        Some(name) => (name, -1),
+                        None => (key.strip_prefix('+').unwrap_or(key), 1),
+                    };
+                    let field = spec
+                        .sort
+                        .iter()
+                        .find(|(n, _)| *n == name)
+                        .map(|(_, f)| *f)
+                        .ok_or_else(|| ApiError::BadRequest(format!("Cannot sort by '{name}'")))?;
+                    sort.insert(field, dir);
+                }
+                Some(sort)
+            }
+        };
+
+        let mut filter = Document::new();
+        for (name, op, value) in &self.filters {
+            let field = spec
+                .filters
+                .iter()
+                .find(|f| f.name == name)
+                .ok_or_else(|| ApiError::BadRequest(format!("Cannot filter by '{name}'")))?;
+            let op = FilterOp::parse(op)
+                .ok_or_else(|| ApiError::BadRequest(format!("Unknown filter operator '{op}'")))?;
+            let clause = build_clause(field, op, value)?;
+            merge_clause(&mut filter, field.field, clause);
+        }
+
+        Ok(ListOptions { filter, sort })
+    }
+}
+
+fn parse_u64(name: &str, value: &str) -> Result<u64, ApiError> {
+    value
+        .parse()
+        .map_err(|_| ApiError::BadRequest(format!("Invalid {name}")))
+}
+
+/// `filter[name]` → `(name, "eq")`, `filter[name][op]` → `(name, op)`.
+fn parse_filter_key(key: &str) -> Option<(String, String)> {
+    let rest = key.strip_prefix("filter[")?;
+    let (name, rest) = rest.split_once(']')?;
+    if name.is_empty() {
+        return None;
+    }
+    if rest.is_empty() {
+        return Some((name.to_string(), "eq".to_string()));
+    }
+    let op = rest.strip_prefix('[')?.strip_suffix(']')?;
+    Some((name.to_string(), op.to_string()))
+}
+
+fn convert(field: &FilterField, value: &str) -> Result<Bson, ApiError> {
+    let invalid = || ApiError::BadRequest(format!("Invalid value for filter '{}'", field.name));
+    Ok(match field.kind {
+        FieldKind::String => Bson::String(value.to_string()),
+        FieldKind::ObjectId => Bson::ObjectId(ObjectId::parse_str(value).map_err(|_| invalid())?),
+        FieldKind::DateTime => {
+            Bson::DateTime(bson::DateTime::parse_rfc3339_str(value).map_err(|_| invalid())?)
+        }
+        FieldKind::Bool => Bson::Boolean(value.parse().map_err(|_| invalid())?),
+        FieldKind::Int => Bson::Int64(value.parse().map_err(|_| invalid())?),
+    })
+}
+
+fn build_clause(field: &FilterField, op: FilterOp, value: &str) -> Result<Bson, ApiError> {
+    let cmp = |mongo_op: &str| -> Result<Bson, ApiError> {
+        Ok(Bson::Document(doc! { mongo_op: convert(field, value)? }))
+    };
+    match op {
+        FilterOp::Eq => convert(field, value),
+        FilterOp::Ne => cmp("$ne"),
+        FilterOp::Gt => cmp("$gt"),
+        FilterOp::Gte => cmp("$gte"),
+        FilterOp::Lt => cmp("$lt"),
+        FilterOp::Lte => cmp("$lte"),
+        FilterOp::In => {
+            let values = value
+                .split(',')
+                .map(|v| convert(field, v.trim()))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Bson::Document(doc! { "$in": values }))
+        }
+        FilterOp::Contains => {
+            if field.kind != FieldKind::String {
+                return Err(ApiError::BadRequest(format!(
+                    "Filter '{}' does not support 'contains'",
+                    field.name
+                )));
+            }
+            Ok(Bson::Document(
+                doc! { "$regex": escape_regex(value), "$options": "i" },
+            ))
+        }
+        FilterOp::Exists => {
+            let exists: bool = value.parse().map_err(|_| {
+                ApiError::BadRequest(format!("Invalid value for filter '{}'", field.name))
+            })?;
+            // Optional fields are stored as explicit nulls, so "exists"
+            // means "has a non-null value".
+            Ok(Bson::Document(if exists {
+                doc! { "$ne": null }
+            } else {
+                doc! { "$eq": null }
+            }))
+        }
+    }
+}
+
+/// Combine several clauses on one field (`gte` + `lt` for a range) into a
+/// single operator document. An equality clause replaces whatever was there.
+fn merge_clause(filter: &mut Document, field: &str, clause: Bson) {
+    match (filter.get_mut(field), clause) {
+        (Some(Bson::Document(existing)), Bson::Document(ops))
+            if existing.keys().all(|k| k.starts_with('$')) =>
+        {
+            existing.extend(ops);
+        }
+        (_, clause) => {
+            filter.insert(field, clause);
+        }
+    }
+}
+
+fn escape_regex(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: ListSpec = ListSpec {
+        sort: &[("created_at", "created_at"), ("name", "filename")],
+        filters: &[
+            FilterField::new("name", "filename", FieldKind::String),
+            FilterField::new("size", "size", FieldKind::Int),
+            FilterField::new("uploaded_by", "uploaded_by", FieldKind::ObjectId),
+            FilterField::new("pinned", "is_pinned", FieldKind::Bool),
+        ],
+    };
+
+    fn query(pairs: &[(&str, &str)]) -> ListQuery {
+        ListQuery::from_pairs(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn parses_pagination_and_ignores_unrelated_keys() {
+        let q = query(&[
+            ("page", "2"),
+            ("per_page", "10"),
+            ("cursor", "abc"),
+            ("q", "x"),
+        ]);
+        assert_eq!(q.pagination.page, 2);
+        assert_eq!(q.pagination.per_page, 10);
+        assert_eq!(q.pagination.cursor.as_deref(), Some("abc"));
+        assert!(q.options(&SPEC).unwrap().filter.is_empty());
+    }
+
+    #[test]
+    fn maps_sort_to_document_fields() {
+        let opts = query(&[("sort", "-name,created_at")])
+            .options(&SPEC)
+            .unwrap();
+        assert_eq!(opts.sort, Some(doc! { "filename": -1, "created_at": 1 }));
+    }
+
+    #[test]
+    fn rejects_non_whitelisted_sort_and_filter() {
+        assert!(query(&[("sort", "password")]).options(&SPEC).is_err());
+        assert!(query(&[("filter[password]", "x")]).options(&SPEC).is_err());
+        assert!(
+            query(&[("filter[name][where]", "x")])
+                .options(&SPEC)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn builds_typed_filter_clauses() {
+        let uid = ObjectId::new();
+        let opts = query(&[
+            ("filter[size][gte]", "10"),
+            ("filter[size][lt]", "20"),
+            ("filter[uploaded_by]", &uid.to_hex()),
+            ("filter[pinned]", "true"),
+            ("filter[name][contains]", "a.b"),
+        ])
+        .options(&SPEC)
+        .unwrap();
+        assert_eq!(
+            opts.filter,
+            doc! {
+                "size": { "$gte": 10_i64, "$lt": 20_i64 },
+                "uploaded_by": uid,
+                "is_pinned": true,
+                "filename": { "$regex": "a\\.b", "$options": "i" },
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_badly_typed_values() {
+        assert!(query(&[("filter[size]", "ten")]).options(&SPEC).is_err());
+        assert!(
+            query(&[("filter[uploaded_by]", "nope")])
+                .options(&SPEC)
+                .is_err()
+        );
+        assert!(
+            query(&[("filter[size][contains]", "1")])
+                .options(&SPEC)
+                .is_err()
+        );
+    }
+}
@@ -1,2 +1,4 @@
 pub mod auth;
+pub mod feature;
+pub mod list_query;
 pub mod tenant;
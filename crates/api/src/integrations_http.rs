@@ -0,0 +1,130 @@
+//! The HTTP client outgoing webhooks and slash commands POST through.
+//! Their URLs are set by tenant admins, but the requests leave from inside
+//! the server's network, so the client only talks to public addresses:
+//! host names are resolved at connect time and loopback, private,
+//! link-local (cloud metadata), unique-local and shared addresses are
+//! dropped, and [`check_url`] refuses such IP literals, which are never
+//! resolved. Redirects aren't followed, so a public endpoint can't bounce the
+//! request inwards.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use reqwest::Url;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+
+/// The client for integration calls. `allow_private_networks` turns the
+/// address checks off.
+pub fn client(allow_private_networks: bool) -> reqwest::Result<reqwest::Client> {
+    let builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .redirect(reqwest::redirect::Policy::none());
+    if allow_private_networks {
+        builder.build()
+    } else {
+        builder.dns_resolver(Arc::new(PublicResolver)).build()
+    }
+}
+
+/// Why `url` can't be an integration endpoint, if it can't: it must be an
+/// absolute http(s) URL, and unless `allow_private_networks` its host can't
+/// be a non-public IP. Host names are checked when they are resolved, on
+/// every request.
+pub fn check_url(url: &str, allow_private_networks: bool) -> Result<(), &'static str> {
+    let url = match Url::parse(url) {
+        Ok(u) if matches!(u.scheme(), "http" | "https") => u,
+        _ => return Err("url must be an absolute http(s) URL"),
+    };
+    if allow_private_networks {
+        return Ok(());
+    }
+    // IPv6 hosts come bracketed; IPv4 ones in any notation come normalized
+    let host = url
+        .host_str()
+        .unwrap_or_default()
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(ip) if !is_public(ip) => Err("url must not point at a local or private network address"),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `ip` is reachable on the public internet.
+fn is_public(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_private()
+                // 169.254.0.0/16, where cloud metadata services live
+                || ip.is_link_local()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || ip.is_multicast()
+                || a == 0
+                // 100.64.0.0/10, carrier-grade NAT and some metadata services
+                || (a == 100 && b & 0xc0 == 64))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(v4) = ip.to_ipv4_mapped() {
+                return is_public(IpAddr::V4(v4));
+            }
+            let first = ip.segments()[0];
+            !(ip.is_unspecified()
+                || ip.is_loopback()
+                || ip.is_multicast()
+                // fc00::/7, unique local
+                || first & 0xfe00 == 0xfc00
+                // fe80::/10, link-local
+                || first & 0xffc0 == 0xfe80)
+        }
+    }
+}
+
+/// Resolves with the system resolver, keeping only public addresses.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn local_and_private_urls_are_refused() {
+        for url in [
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data/",
+            "http://10.0.0.5/",
+            "http://192.168.1.1/",
+            "http://100.100.100.200/",
+            "http://[::1]/",
+            "http://[fd00:ec2::254]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://0.0.0.0/",
+            "http://2130706433/",
+        ] {
+            assert!(check_url(url, false).is_err(), "{url}");
+            assert!(check_url(url, true).is_ok(), "{url}");
+        }
+        assert!(check_url("https://hooks.example.com/x", false).is_ok());
+        assert!(check_url("http://93.184.216.34/", false).is_ok());
+        assert!(check_url("file:///etc/passwd", true).is_err());
+    }
+}
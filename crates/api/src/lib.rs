@@ -1,8 +1,13 @@
+pub mod control;
 pub mod error;
 pub mod extractors;
+pub mod integrations_http;
 pub mod middleware;
+pub mod openapi;
 pub mod routes;
+pub mod runtime;
 pub mod state;
+pub mod telemetry;
 pub mod ws;
 
 use axum::{
@@ -18,6 +23,8 @@ use tower_http::{
     cors::{Any, CorsLayer},
     trace::TraceLayer,
 };
+use utoipa::OpenApi;
+use utoipa_swagger_ui::SwaggerUi;
 
 fn build_cors_layer(origins: &[String]) -> CorsLayer {
     if origins.is_empty() || origins.iter().any(|o| o == "*") {
@@ -53,27 +60,39 @@ pub fn build_router(state: AppState) -> Router {
         config: governor_conf.into(),
     };
 
-    // Auth routes (no tenant prefix)
-    let auth_routes = Router::new()
+    // Auth routes (no tenant prefix). The credential-taking ones are
+    // limited per client IP on top of the governor.
+    let credential_routes = Router::new()
         .route("/register", post(routes::auth::register))
         .route("/login", post(routes::auth::login))
-        .route("/logout", post(routes::auth::logout))
         .route("/refresh", post(routes::auth::refresh))
         .route("/activate", post(routes::auth::activate))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::rate_limit::limit_auth,
+        ));
+    let auth_routes = Router::new()
+        .merge(credential_routes)
+        .route("/logout", post(routes::auth::logout))
         .route("/me", get(routes::auth::me))
-        .route("/me", put(routes::auth::me));
+        .route("/me", put(routes::auth::me))
+        .route("/ws-ticket", post(routes::auth::ws_ticket));
 
     // Tenant routes
     let tenant_routes = Router::new()
         .route("/", get(routes::tenant::list))
         .route("/", post(routes::tenant::create))
+        .route("/joinable", get(routes::tenant_domain::joinable))
         .route("/{tenant_id}", get(routes::tenant::get));
 
     // Member routes (under tenant)
-    let member_routes = Router::new().route(
-        "/",
-        get(routes::user::list_members).post(routes::invite::add_member),
-    );
+    let member_routes = Router::new()
+        .route(
+            "/",
+            get(routes::user::list_members).post(routes::invite::add_member),
+        )
+        .route("/search", get(routes::user::search_members))
+        .route("/{user_id}", delete(routes::user::remove_member));
 
     // Room routes (under tenant) — replaces channel + conference
     let room_routes = Router::new()
@@ -83,9 +102,15 @@ pub fn build_router(state: AppState) -> Router {
         .route("/{room_id}", get(routes::room::get))
         .route("/{room_id}", put(routes::room::update))
         .route("/{room_id}", delete(routes::room::delete))
+        .route("/{room_id}/restore", post(routes::room::restore))
         .route("/{room_id}/join", post(routes::room::join))
         .route("/{room_id}/leave", post(routes::room::leave))
         .route("/{room_id}/member", get(routes::room::members))
+        .route("/{room_id}/command", post(routes::slash_command::invoke))
+        .route(
+            "/{room_id}/permission",
+            get(routes::room::get_permissions).put(routes::room::set_permissions),
+        )
         // Call endpoints
         .route("/{room_id}/call/start", post(routes::room::call_start))
         .route("/{room_id}/call/join", post(routes::room::call_join))
@@ -95,9 +120,93 @@ pub fn build_router(state: AppState) -> Router {
             "/{room_id}/call/participant",
             get(routes::room::participants),
         )
+        .route(
+            "/{room_id}/call/participant/{user_id}/mute",
+            put(routes::call_audio::mute),
+        )
+        .route(
+            "/{room_id}/call/push-to-talk",
+            put(routes::call_audio::push_to_talk),
+        )
+        .route(
+            "/{room_id}/call/transcription",
+            put(routes::call_transcription::opt_out),
+        )
+        .route(
+            "/{room_id}/call/transcript",
+            post(routes::call_transcription::append),
+        )
+        .route(
+            "/{room_id}/call/schedule",
+            get(routes::call_schedule::get)
+                .put(routes::call_schedule::set)
+                .delete(routes::call_schedule::delete),
+        )
+        .route(
+            "/{room_id}/call/schedule/sync",
+            post(routes::call_schedule::sync),
+        )
+        .route("/{room_id}/call/webinar", get(routes::webinar::get))
+        .route(
+            "/{room_id}/call/speaker/{user_id}",
+            put(routes::webinar::promote).delete(routes::webinar::demote),
+        )
+        .route("/{room_id}/call/history", get(routes::room::call_history))
+        .route("/{room_id}/call/debug", get(routes::call_debug::timeline))
+        .route("/{room_id}/ice", get(routes::room::ice_servers))
+        .route(
+            "/{room_id}/call/breakout",
+            get(routes::breakout::list)
+                .post(routes::breakout::create)
+                .delete(routes::breakout::close),
+        )
+        .route(
+            "/{room_id}/call/breakout/{breakout_id}/participant",
+            put(routes::breakout::assign),
+        )
         .route(
             "/{room_id}/call/message",
             get(routes::room::call_messages).post(routes::room::create_call_message),
+        )
+        .route(
+            "/{room_id}/call/poll",
+            get(routes::poll::list).post(routes::poll::create),
+        )
+        .route("/{room_id}/call/poll/{poll_id}", put(routes::poll::update))
+        .route(
+            "/{room_id}/call/poll/{poll_id}/vote",
+            post(routes::poll::vote),
+        )
+        .route(
+            "/{room_id}/call/poll/{poll_id}/close",
+            post(routes::poll::close),
+        )
+        .route(
+            "/{room_id}/call/question",
+            get(routes::question::list).post(routes::question::create),
+        )
+        .route(
+            "/{room_id}/call/question/{question_id}/upvote",
+            post(routes::question::upvote).delete(routes::question::remove_upvote),
+        )
+        .route(
+            "/{room_id}/call/question/{question_id}/answer",
+            post(routes::question::answer),
+        )
+        .route(
+            "/{room_id}/legal-hold",
+            put(routes::retention::set_legal_hold),
+        )
+        .route("/{room_id}/sidebar", put(routes::sidebar::update_room))
+        .route(
+            "/{room_id}/notification-preferences",
+            get(routes::notification::get_room_preferences)
+                .put(routes::notification::update_room_preferences),
+        )
+        .route("/{room_id}/whiteboard", get(routes::whiteboard::get))
+        .route(
+            "/{room_id}/whiteboard/export",
+            post(routes::whiteboard::export),
         );
 
     // Message routes (under tenant/room)
@@ -105,10 +214,21 @@ pub fn build_router(state: AppState) -> Router {
         .route("/", get(routes::message::list))
         .route("/", post(routes::message::create))
         .route("/pin", get(routes::message::pinned))
+        .route("/scheduled", get(routes::scheduled_message::list))
+        .route(
+            "/scheduled/{scheduled_id}",
+            delete(routes::scheduled_message::cancel),
+        )
         .route("/{message_id}", put(routes::message::update))
         .route("/{message_id}", delete(routes::message::delete))
+        .route("/{message_id}/restore", post(routes::message::restore))
         .route("/{message_id}/pin", put(routes::message::toggle_pin))
+        .route("/{message_id}/history", get(routes::message::history))
         .route("/{message_id}/thread", get(routes::message::thread_replies))
+        .route(
+            "/{message_id}/thread/follow",
+            put(routes::message::follow_thread).delete(routes::message::unfollow_thread),
+        )
         .route("/{message_id}/reaction", post(routes::reaction::add))
         .route(
             "/{message_id}/reaction/{emoji}",
@@ -121,7 +241,18 @@ pub fn build_router(state: AppState) -> Router {
     let recording_routes = Router::new()
         .route("/", get(routes::recording::list))
         .route("/", post(routes::recording::create))
-        .route("/{recording_id}", delete(routes::recording::delete));
+        .route("/{recording_id}", delete(routes::recording::delete))
+        .route("/{recording_id}/file", put(routes::recording::upload_file))
+        .route("/{recording_id}/stream", get(routes::recording::stream))
+        .route("/{recording_id}/captions", get(routes::recording::captions))
+        .route(
+            "/{recording_id}/transcript",
+            get(routes::recording::transcript).put(routes::recording::set_transcript),
+        )
+        .route(
+            "/{recording_id}/consent",
+            get(routes::recording_consent::list).post(routes::recording_consent::respond),
+        );
 
     // Room file routes (100 MB body limit for audio uploads)
     let room_file_routes = Router::new()
@@ -135,6 +266,8 @@ pub fn build_router(state: AppState) -> Router {
         .route("/upload", post(routes::file::upload))
         .route("/{file_id}", get(routes::file::get))
         .route("/{file_id}/download", get(routes::file::download))
+        .route("/{file_id}/scan", put(routes::file::override_scan))
+        .route("/{file_id}/preview/{page}", get(routes::file::preview))
         .route("/{file_id}", delete(routes::file::delete))
         .route(
             "/{file_id}/recognize",
@@ -142,6 +275,26 @@ pub fn build_router(state: AppState) -> Router {
         )
         .layer(DefaultBodyLimit::max(100 * 1024 * 1024));
 
+    // Tenant asset library (virtual backgrounds, custom emojis); the limit
+    // leaves room for multipart framing around the largest accepted image.
+    let asset_routes = Router::new()
+        .route(
+            "/background",
+            get(routes::asset::list_backgrounds).post(routes::asset::upload_background),
+        )
+        .route(
+            "/background/{file_id}",
+            delete(routes::asset::delete_background),
+        )
+        .route(
+            "/emoji",
+            get(routes::asset::list_emojis).post(routes::asset::upload_emoji),
+        )
+        .route("/emoji/{emoji_id}", delete(routes::asset::delete_emoji))
+        .layer(DefaultBodyLimit::max(
+            routes::asset::MAX_BACKGROUND_SIZE + 1024 * 1024,
+        ));
+
     // Background task routes (under tenant)
     let task_routes = Router::new()
         .route("/", get(routes::background_task::list))
@@ -154,6 +307,7 @@ pub fn build_router(state: AppState) -> Router {
     // Export routes (under tenant)
     let export_routes = Router::new()
         .route("/conversation", post(routes::export::export_conversation))
+        .route("/room/{room_id}", post(routes::export::export_room))
         .route(
             "/conversation-pdf",
             post(routes::integration::export_conversation_pdf),
@@ -185,9 +339,20 @@ pub fn build_router(state: AppState) -> Router {
 
     // OAuth routes (no auth required)
     let oauth_routes = Router::new()
+        .route("/providers", get(routes::oauth::providers))
         .route("/{provider}", get(routes::oauth::oauth_redirect))
         .route("/callback/{provider}", get(routes::oauth::oauth_callback));
 
+    // Calendar connections (user-scoped; the callback needs no auth)
+    let calendar_routes = Router::new()
+        .route("/connection", get(routes::calendar::list))
+        .route(
+            "/connection/{provider}",
+            delete(routes::calendar::disconnect),
+        )
+        .route("/{provider}/connect", post(routes::calendar::connect))
+        .route("/callback/{provider}", get(routes::calendar::callback));
+
     // Stripe routes
     let stripe_routes = Router::new()
         .route("/plans", get(routes::stripe::get_plans))
@@ -204,7 +369,8 @@ pub fn build_router(state: AppState) -> Router {
     let push_routes = Router::new()
         .route("/config", get(routes::push::config))
         .route("/subscribe", post(routes::push::subscribe))
-        .route("/unsubscribe", post(routes::push::unsubscribe));
+        .route("/unsubscribe", post(routes::push::unsubscribe))
+        .route("/subscription", put(routes::push::set_device_preferences));
 
     // Notification routes (user-scoped, no tenant prefix)
     let notification_routes = Router::new()
@@ -215,12 +381,30 @@ pub fn build_router(state: AppState) -> Router {
             "/{notification_id}/read",
             put(routes::notification::mark_read),
         )
-        .route("/read-all", post(routes::notification::mark_all_read));
+        .route("/read-all", post(routes::notification::mark_all_read))
+        .route(
+            "/preferences",
+            get(routes::notification::get_preferences)
+                .put(routes::notification::update_preferences),
+        );
 
-    // User profile routes
+    // User profile routes; the limit leaves room for multipart framing
+    // around the largest accepted avatar.
     let user_routes = Router::new()
         .route("/me", put(routes::user::update_profile))
-        .route("/{user_id}", get(routes::user::get_profile));
+        .route(
+            "/me/status",
+            put(routes::user::set_status).delete(routes::user::clear_status),
+        )
+        .route(
+            "/me/avatar",
+            post(routes::user::upload_avatar).delete(routes::user::delete_avatar),
+        )
+        .route("/{user_id}", get(routes::user::get_profile))
+        .route("/{user_id}/avatar/{key}", get(routes::user::avatar))
+        .layer(DefaultBodyLimit::max(
+            routes::user::MAX_AVATAR_SIZE + 1024 * 1024,
+        ));
 
     // rc.58 — browser console log batch ingest. User-authed (the
     // controller user's JWT). The body MUST include an explicit
@@ -231,6 +415,75 @@ pub fn build_router(state: AppState) -> Router {
     // Search routes (under tenant)
     let search_routes = Router::new().route("/", get(routes::search::search));
 
+    // Retention policy (under tenant; updates need MANAGE_TENANT)
+    let retention_routes = Router::new().route(
+        "/",
+        get(routes::retention::get).put(routes::retention::update),
+    );
+
+    // The caller's own sidebar (under tenant)
+    let sidebar_routes = Router::new()
+        .route("/", get(routes::sidebar::get))
+        .route("/order", put(routes::sidebar::set_order));
+
+    // Usage report (under tenant, MANAGE_TENANT)
+    let usage_routes = Router::new().route("/", get(routes::usage::get));
+
+    // Audit log (under tenant, MANAGE_TENANT)
+    let audit_routes = Router::new().route("/", get(routes::admin::list_audit));
+
+    // Integrations (under tenant, MANAGE_TENANT)
+    let webhook_routes = Router::new()
+        .route(
+            "/",
+            get(routes::webhook::list).post(routes::webhook::create),
+        )
+        .route(
+            "/{webhook_id}",
+            put(routes::webhook::update).delete(routes::webhook::delete),
+        );
+    let command_routes = Router::new()
+        .route(
+            "/",
+            get(routes::slash_command::list).post(routes::slash_command::create),
+        )
+        .route("/{command_id}", delete(routes::slash_command::delete));
+    let bot_routes = Router::new()
+        .route("/", get(routes::bot::list).post(routes::bot::create))
+        .route("/{bot_id}", delete(routes::bot::delete))
+        .route(
+            "/{bot_id}/token",
+            get(routes::bot::list_tokens).post(routes::bot::create_token),
+        )
+        .route(
+            "/{bot_id}/token/{token_id}",
+            delete(routes::bot::revoke_token),
+        );
+
+    // Feature flags (under tenant; listing is for members, setting needs
+    // MANAGE_TENANT)
+    let feature_flag_routes = Router::new()
+        .route("/", get(routes::feature_flag::list))
+        .route("/{key}", put(routes::feature_flag::update));
+
+    // Email domains (under tenant, MANAGE_TENANT; joining is for any user
+    // with a verified address at the domain)
+    let domain_routes = Router::new()
+        .route(
+            "/",
+            get(routes::tenant_domain::list).post(routes::tenant_domain::create),
+        )
+        .route("/join", post(routes::tenant_domain::join))
+        .route(
+            "/{domain_id}",
+            put(routes::tenant_domain::update).delete(routes::tenant_domain::delete),
+        )
+        .route("/{domain_id}/verify", post(routes::tenant_domain::verify));
+
+    // Incoming webhooks (no auth — the URL token is the credential)
+    let public_hook_routes =
+        Router::new().route("/{webhook_id}/{token}", post(routes::webhook::incoming));
+
     // Remote-control agent routes (tenant-scoped)
     let agent_routes = Router::new()
         .route("/", get(routes::remote_control::list_agents))
@@ -398,18 +651,35 @@ pub fn build_router(state: AppState) -> Router {
         );
 
     // TURN credentials (user-scoped, no tenant prefix)
-    let turn_routes = Router::new().route(
-        "/credentials",
-        get(routes::remote_control::turn_credentials),
-    );
+    let turn_routes = Router::new()
+        .route(
+            "/credentials",
+            get(routes::remote_control::turn_credentials),
+        )
+        .route("/regions", get(routes::remote_control::turn_regions));
+
+    let ws_routes = Router::new().route("/stats", get(routes::ws::stats));
+
+    // Deployment-wide operator endpoints (`app.admin_emails`)
+    let admin_routes = Router::new()
+        .route(
+            "/settings",
+            get(routes::admin::get_settings).put(routes::admin::update_settings),
+        )
+        .route(
+            "/tenant/{tenant_id}/storage-quota",
+            put(routes::admin::update_storage_quota),
+        );
 
     // Compose API
     let api = Router::new()
         .nest("/auth", auth_routes)
         .nest("/user", user_routes)
         .nest("/oauth", oauth_routes)
+        .nest("/calendar", calendar_routes)
         .nest("/stripe", stripe_routes)
         .nest("/invite", public_invite_routes)
+        .nest("/hook", public_hook_routes)
         .nest("/giphy", giphy_routes)
         .nest("/push", push_routes)
         .nest("/notification", notification_routes)
@@ -419,12 +689,23 @@ pub fn build_router(state: AppState) -> Router {
         .nest("/tunnel", public_tunnel_release_routes)
         .nest("/setup", public_setup_routes)
         .nest("/turn", turn_routes)
+        .nest("/ws", ws_routes)
+        .nest("/admin", admin_routes)
         .nest("/log", log_routes)
         .nest("/tenant", tenant_routes)
         .nest("/tenant/{tenant_id}/member", member_routes)
         .nest("/tenant/{tenant_id}/role", role_routes)
         .nest("/tenant/{tenant_id}/invite", tenant_invite_routes)
+        .nest("/tenant/{tenant_id}/domain", domain_routes)
+        .nest("/tenant/{tenant_id}/feature-flag", feature_flag_routes)
         .nest("/tenant/{tenant_id}/search", search_routes)
+        .nest("/tenant/{tenant_id}/audit", audit_routes)
+        .nest("/tenant/{tenant_id}/retention", retention_routes)
+        .nest("/tenant/{tenant_id}/usage", usage_routes)
+        .nest("/tenant/{tenant_id}/sidebar", sidebar_routes)
+        .nest("/tenant/{tenant_id}/webhook", webhook_routes)
+        .nest("/tenant/{tenant_id}/command", command_routes)
+        .nest("/tenant/{tenant_id}/bot", bot_routes)
         .nest("/tenant/{tenant_id}/room", room_routes)
         .nest("/tenant/{tenant_id}/room/{room_id}/message", message_routes)
         .nest(
@@ -433,6 +714,7 @@ pub fn build_router(state: AppState) -> Router {
         )
         .nest("/tenant/{tenant_id}/room/{room_id}/file", room_file_routes)
         .nest("/tenant/{tenant_id}/file", file_by_id_routes)
+        .nest("/tenant/{tenant_id}/asset", asset_routes)
         .nest("/tenant/{tenant_id}/task", task_routes)
         .nest("/tenant/{tenant_id}/export", export_routes)
         .nest("/tenant/{tenant_id}/agent", agent_routes)
@@ -442,8 +724,14 @@ pub fn build_router(state: AppState) -> Router {
         .nest("/tenant/{tenant_id}/magic-dns", magic_dns_routes)
         .nest("/tenant/{tenant_id}/session", remote_session_routes);
 
-    // Health check
-    let health = Router::new().route("/health", get(health_check));
+    // Health, liveness and readiness probes
+    let health = Router::new()
+        .route("/health", get(routes::health::health))
+        .route("/health/live", get(routes::health::live))
+        .route("/health/ready", get(routes::health::ready));
+
+    // OpenAPI document and Swagger UI
+    let docs = SwaggerUi::new("/api/docs").url("/api/openapi.json", openapi::ApiDoc::openapi());
 
     // Apply rate limiting only to API routes (not health/ws which need unrestricted access)
     let rate_limited_api = Router::new().nest("/api", api).layer(governor_layer);
@@ -451,16 +739,10 @@ pub fn build_router(state: AppState) -> Router {
     Router::new()
         .merge(rate_limited_api)
         .merge(health)
+        .merge(docs)
         .route("/ws", get(ws::handler::ws_upgrade))
         .route("/derp", get(ws::derp::derp_upgrade))
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(telemetry::http_span))
         .layer(cors)
         .with_state(state)
 }
-
-async fn health_check() -> axum::Json<serde_json::Value> {
-    axum::Json(serde_json::json!({
-        "status": "ok",
-        "version": env!("CARGO_PKG_VERSION"),
-    }))
-}
@@ -1,31 +1,25 @@
 use bson::oid::ObjectId;
 use roomler_ai_api::{
-    build_router,
+    build_router, control,
     state::AppState,
+    telemetry,
     ws::{dispatcher, redis_pubsub::RedisPubSub},
 };
 use roomler_ai_config::Settings;
-use roomler_ai_db::{connect, indexes::ensure_indexes};
+use roomler_ai_db::{connect, migrations::migrate};
 use tracing::{error, info};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Load .env file (silently ignore if missing)
     dotenvy::dotenv().ok();
 
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
-            "roomler_ai_api=debug,roomler_ai_services=debug,roomler_ai_db=debug,tower_http=debug"
-                .into()
-        }))
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
     // Load config
     let settings = Settings::load()?;
 
+    // Initialize logging and trace export
+    let telemetry = telemetry::init(&settings.telemetry)?;
+
     // Loud, non-fatal warning if the JWT secret is still the built-in
     // default ("change-me-in-production"). The default is fine for
     // `cargo run` against localhost, but any reachable deployment must
@@ -56,8 +50,8 @@ async fn main() -> anyhow::Result<()> {
     // Connect to MongoDB
     let db = connect(&settings).await?;
 
-    // Ensure indexes
-    ensure_indexes(&db).await?;
+    // Ensure indexes and apply pending migrations
+    migrate(&db).await?;
 
     // Build app state (async: spawns mediasoup workers)
     let app_state = AppState::new(db.clone(), settings.clone()).await?;
@@ -89,7 +83,7 @@ async fn main() -> anyhow::Result<()> {
         // Count replies per thread parent and rebuild metadata
         use futures::TryStreamExt;
         let pipeline = vec![
-            bson::doc! { "$match": { "thread_id": { "$ne": null } } },
+            bson::doc! { "$match": { "thread_id": { "$ne": null }, "deleted_at": null } },
             bson::doc! { "$group": {
                 "_id": "$thread_id",
                 "reply_count": { "$sum": 1 },
@@ -113,13 +107,19 @@ async fn main() -> anyhow::Result<()> {
                                 "last_reply_at": doc.get("last_reply_at"),
                                 "last_reply_user_id": doc.get("last_reply_user_id"),
                                 "participant_ids": doc.get("participant_ids"),
+                                "follower_ids": doc.get("participant_ids"),
                             },
                         },
                     };
                     if msgs_coll
-                        .update_one(bson::doc! { "_id": parent_id }, update)
+                        // Only roots still missing metadata — a full
+                        // rebuild would drop follower_ids on every start.
+                        .update_one(
+                            bson::doc! { "_id": parent_id, "thread_metadata": null },
+                            update,
+                        )
                         .await
-                        .is_ok()
+                        .is_ok_and(|r| r.modified_count > 0)
                     {
                         fixed += 1;
                     }
@@ -138,6 +138,7 @@ async fn main() -> anyhow::Result<()> {
     if app_state.redis_pubsub.is_some() {
         let (redis_tx, _) = tokio::sync::broadcast::channel::<String>(1024);
         let ws_storage = app_state.ws_storage.clone();
+        let event_log = app_state.event_log.clone();
         let mut redis_rx = redis_tx.subscribe();
 
         // Start the Redis subscriber (spawns a background task internally)
@@ -155,6 +156,15 @@ async fn main() -> anyhow::Result<()> {
                             .iter()
                             .filter_map(|v| v.as_str().and_then(|s| ObjectId::parse_str(s).ok()))
                             .collect();
+                        // Keep room events from other pods for `sync` replay
+                        if let (Some(room_id), Some(seq)) = (
+                            message["room_id"]
+                                .as_str()
+                                .and_then(|r| ObjectId::parse_str(r).ok()),
+                            message["seq"].as_u64(),
+                        ) {
+                            event_log.record(room_id, seq, ids.clone(), message.clone());
+                        }
                         // Deliver to local connections only (no re-publish to Redis)
                         dispatcher::broadcast(&ws_storage, &ids, message).await;
                     }
@@ -165,6 +175,9 @@ async fn main() -> anyhow::Result<()> {
         }
     }
 
+    // Internal control plane for the other pods
+    control::spawn(app_state.clone());
+
     // Build router
     let app = build_router(app_state);
 
@@ -173,7 +186,9 @@ async fn main() -> anyhow::Result<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     info!("Listening on {}", addr);
 
-    axum::serve(listener, app).await?;
+    let served = axum::serve(listener, app).await;
+    telemetry.shutdown();
+    served?;
 
     Ok(())
 }
@@ -0,0 +1,210 @@
+//! Audit trail for tenant admin actions.
+//!
+//! Handlers that change tenant-wide state (room delete, member remove, role
+//! changes, invite revoke, recording delete, exports) take an [`AuditContext`]
+//! and call [`record`] after the change succeeded. Snapshots are the API
+//! response DTOs (hex ids, RFC 3339 timestamps) so `changes` reads the same as
+//! the resources clients already know. Recording never fails the request —
+//! a write error is logged and dropped.
+
+use std::net::SocketAddr;
+
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::{header, request::Parts};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{
+    AuditLog,
+    audit_log::{ActorType, AuditChange, AuditMetadata},
+};
+use serde::Serialize;
+
+use super::rate_limit::client_ip;
+use crate::state::AppState;
+
+/// Request metadata stored with each audit entry.
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let ip = client_ip(
+            &parts.headers,
+            parts.extensions.get::<ConnectInfo<SocketAddr>>(),
+        );
+        Ok(Self {
+            ip: Some(ip).filter(|ip| ip != "unknown"),
+            user_agent: parts
+                .headers
+                .get(header::USER_AGENT)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// One admin action, built by the handler that performed it.
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub tenant_id: ObjectId,
+    pub actor_id: ObjectId,
+    /// Dotted `<target>.<verb>`, e.g. `room.delete`, `role.assign`.
+    pub action: &'static str,
+    pub target_type: &'static str,
+    pub target_id: Option<ObjectId>,
+    pub before: Option<serde_json::Value>,
+    pub after: Option<serde_json::Value>,
+    pub reason: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(
+        tenant_id: ObjectId,
+        actor_id: ObjectId,
+        action: &'static str,
+        target_type: &'static str,
+        target_id: Option<ObjectId>,
+    ) -> Self {
+        Self {
+            tenant_id,
+            actor_id,
+            action,
+            target_type,
+            target_id,
+            before: None,
+            after: None,
+            reason: None,
+        }
+    }
+
+    pub fn before(mut self, snapshot: &impl Serialize) -> Self {
+        self.before = serde_json::to_value(snapshot).ok();
+        self
+    }
+
+    pub fn after(mut self, snapshot: &impl Serialize) -> Self {
+        self.after = serde_json::to_value(snapshot).ok();
+        self
+    }
+
+    pub fn reason(mut self, reason: Option<String>) -> Self {
+        self.reason = reason;
+        self
+    }
+}
+
+/// Persist `entry`. Best effort: the action already happened, so a failed
+/// audit write is logged rather than surfaced to the caller.
+pub async fn record(state: &AppState, ctx: &AuditContext, entry: AuditEntry) {
+    let log = AuditLog {
+        id: None,
+        tenant_id: entry.tenant_id,
+        actor_id: Some(entry.actor_id),
+        actor_type: ActorType::User,
+        action: entry.action.to_string(),
+        target_type: entry.target_type.to_string(),
+        target_id: entry.target_id,
+        changes: diff(entry.before.as_ref(), entry.after.as_ref()),
+        metadata: AuditMetadata {
+            ip: ctx.ip.clone(),
+            user_agent: ctx.user_agent.clone(),
+            reason: entry.reason,
+        },
+        created_at: bson::DateTime::now(),
+    };
+    if let Err(e) = state.audit_logs.append(&log).await {
+        tracing::warn!(
+            action = entry.action,
+            tenant_id = %entry.tenant_id,
+            %e,
+            "audit log write failed"
+        );
+    }
+}
+
+/// Persist an action the server took on its own (e.g. a retention purge):
+/// no actor, `actor_type: system`, and `after` recorded as the changes.
+pub async fn record_system(
+    state: &AppState,
+    tenant_id: ObjectId,
+    action: &'static str,
+    target_type: &'static str,
+    target_id: Option<ObjectId>,
+    after: &impl Serialize,
+) {
+    let after = serde_json::to_value(after).ok();
+    let log = AuditLog {
+        id: None,
+        tenant_id,
+        actor_id: None,
+        actor_type: ActorType::System,
+        action: action.to_string(),
+        target_type: target_type.to_string(),
+        target_id,
+        changes: diff(None, after.as_ref()),
+        metadata: AuditMetadata::default(),
+        created_at: bson::DateTime::now(),
+    };
+    if let Err(e) = state.audit_logs.append(&log).await {
+        tracing::warn!(action, tenant_id = %tenant_id, %e, "audit log write failed");
+    }
+}
+
+/// Top-level field changes between two object snapshots. Fields equal on
+/// both sides are omitted; a missing snapshot (create/delete) records every
+/// field of the other side.
+fn diff(before: Option<&serde_json::Value>, after: Option<&serde_json::Value>) -> Vec<AuditChange> {
+    let empty = serde_json::Map::new();
+    let old = before.and_then(|v| v.as_object()).unwrap_or(&empty);
+    let new = after.and_then(|v| v.as_object()).unwrap_or(&empty);
+
+    let mut fields: Vec<&String> = old.keys().chain(new.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let (o, n) = (old.get(field), new.get(field));
+            (o != n).then(|| AuditChange {
+                field: field.clone(),
+                old_value: o.cloned(),
+                new_value: n.cloned(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diff_reports_only_changed_fields() {
+        let before = json!({ "name": "ops", "permissions": 1, "position": 5 });
+        let after = json!({ "name": "ops", "permissions": 3, "color": 7 });
+        let changes = diff(Some(&before), Some(&after));
+        let fields: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["color", "permissions", "position"]);
+        assert_eq!(changes[1].old_value, Some(json!(1)));
+        assert_eq!(changes[1].new_value, Some(json!(3)));
+        assert_eq!(changes[2].new_value, None);
+    }
+
+    #[test]
+    fn diff_of_delete_records_every_field() {
+        let before = json!({ "id": "abc", "name": "general" });
+        let changes = diff(Some(&before), None);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|c| c.new_value.is_none()));
+        assert!(diff(None, None).is_empty());
+    }
+}
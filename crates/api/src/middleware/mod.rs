@@ -1 +1,5 @@
+pub mod audit;
 pub mod auth;
+pub mod plan_limits;
+pub mod rate_limit;
+pub mod storage_quota;
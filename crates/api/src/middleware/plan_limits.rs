@@ -0,0 +1,164 @@
+//! Plan limit checks, called by the handlers where a tenant grows:
+//! [`check_members`] on invite accept, [`check_call_join`] on call join,
+//! [`check_recording`] on recording start and [`check_storage`] on upload.
+//!
+//! Only enforced with `stripe.enforce_limits`. A refused request is a `402`
+//! (`plan_limit`), and the user gets a `billing:limit_reached` event so the
+//! UI can offer an upgrade.
+//!
+//! Plans come from [`PlanCache`], which the billing worker clears whenever it
+//! applies a Stripe event, so a check never waits on Stripe.
+
+use std::time::{Duration, Instant};
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_db::models::Plan;
+use roomler_ai_services::plan_limits::{self, Limit};
+
+use crate::error::ApiError;
+use crate::state::AppState;
+use crate::ws;
+
+/// How long a tenant's plan is cached; other pods see a plan change within
+/// this window.
+const PLAN_TTL: Duration = Duration::from_secs(60);
+
+/// Each tenant's plan, as last read from the tenant document.
+#[derive(Default)]
+pub struct PlanCache {
+    plans: DashMap<ObjectId, (Plan, Instant)>,
+}
+
+impl PlanCache {
+    async fn plan(&self, state: &AppState, tenant_id: ObjectId) -> Result<Plan, ApiError> {
+        if let Some(entry) = self.plans.get(&tenant_id)
+            && entry.1.elapsed() < PLAN_TTL
+        {
+            return Ok(entry.0.clone());
+        }
+        let plan = state.tenants.base.find_by_id(tenant_id).await?.plan;
+        self.plans.insert(tenant_id, (plan.clone(), Instant::now()));
+        Ok(plan)
+    }
+
+    /// Forget every cached plan, e.g. after a billing change.
+    pub fn clear(&self) {
+        self.plans.clear();
+    }
+}
+
+/// One more member.
+pub async fn check_members(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !state.settings.stripe.enforce_limits {
+        return Ok(());
+    }
+    let current = state.tenants.member_count(tenant_id).await?;
+    enforce(state, tenant_id, user_id, Limit::Members, current, 1).await
+}
+
+/// One more participant in the room's call; rejoining is free.
+pub async fn check_call_join(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !state.settings.stripe.enforce_limits {
+        return Ok(());
+    }
+    if state.rooms.is_in_call(room_id, user_id).await? {
+        return Ok(());
+    }
+    let room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(tenant_id, room_id)
+        .await?;
+    let current = u64::from(room.participant_count);
+    enforce(
+        state,
+        tenant_id,
+        user_id,
+        Limit::CallParticipants,
+        current,
+        1,
+    )
+    .await
+}
+
+/// A new recording needs at least a minute left.
+pub async fn check_recording(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !state.settings.stripe.enforce_limits {
+        return Ok(());
+    }
+    let current = state.recordings.recorded_secs(tenant_id).await? / 60;
+    enforce(
+        state,
+        tenant_id,
+        user_id,
+        Limit::RecordingMinutes,
+        current,
+        1,
+    )
+    .await
+}
+
+/// `bytes` more of files; recordings count towards the quota too.
+pub async fn check_storage(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    bytes: u64,
+) -> Result<(), ApiError> {
+    if !state.settings.stripe.enforce_limits {
+        return Ok(());
+    }
+    let current = state.files.storage_used(tenant_id).await?
+        + state.recordings.storage_used(tenant_id).await?;
+    enforce(state, tenant_id, user_id, Limit::Storage, current, bytes).await
+}
+
+async fn enforce(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    limit: Limit,
+    current: u64,
+    adding: u64,
+) -> Result<(), ApiError> {
+    let plan = state.plan_cache.plan(state, tenant_id).await?;
+    let Err(reached) = plan_limits::check(&plan, limit, current, adding) else {
+        return Ok(());
+    };
+
+    let event = serde_json::json!({
+        "type": "billing:limit_reached",
+        "tenant_id": tenant_id.to_hex(),
+        "data": {
+            "tenant_id": tenant_id.to_hex(),
+            "limit": reached.limit,
+            "plan": reached.plan,
+            "max": reached.max,
+            "current": reached.current,
+            "message": reached.to_string(),
+        }
+    });
+    ws::dispatcher::send_to_user_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &user_id,
+        &event,
+    )
+    .await;
+
+    Err(ApiError::PlanLimit(reached.to_string()))
+}
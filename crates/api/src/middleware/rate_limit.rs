@@ -0,0 +1,295 @@
+//! Token-bucket rate limiting keyed by user, tenant and client IP.
+//!
+//! - Auth endpoints: per client IP (`rate_limit.auth_per_min`), applied as a
+//!   layer on the credential-taking auth routes.
+//! - Message create / file upload: per member per tenant, at the tenant's
+//!   plan rate (`Plan::rate_limits`, overridable from Stripe price metadata),
+//!   checked by the handlers via [`check_message`] / [`check_upload`].
+//! - WebSocket: per connection (`rate_limit.ws_messages_per_sec`), see
+//!   [`WsThrottle`].
+//!
+//! Buckets are process-local, like the per-IP governor on `/api`; with N pods
+//! a client gets at most N times the budget.
+//!
+//! The `rate_limit` settings are read per check from [`crate::runtime`], so
+//! an operator's change applies to the next request (WebSocket throttles to
+//! the next connection).
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::extract::{ConnectInfo, Request, State};
+use axum::http::HeaderMap;
+use axum::middleware::Next;
+use axum::response::Response;
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_config::RateLimitSettings;
+use roomler_ai_db::models::{Plan, RateLimits};
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+/// How long a tenant's resolved limits are cached; plan changes take effect
+/// within this window.
+const LIMITS_TTL: Duration = Duration::from_secs(60);
+/// Per-minute buckets refill completely within a minute, so anything idle
+/// longer than this is full and can be dropped.
+const IDLE_EVICT: Duration = Duration::from_secs(120);
+const SWEEP_EVERY: u64 = 4096;
+/// WS bursts allowed, in seconds' worth of `ws_messages_per_sec`.
+const WS_BURST_SECS: f64 = 4.0;
+
+/// `capacity` tokens, refilled continuously at `per_sec`.
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    tokens: f64,
+    last: Instant,
+}
+
+impl TokenBucket {
+    pub fn full(capacity: f64) -> Self {
+        Self {
+            tokens: capacity,
+            last: Instant::now(),
+        }
+    }
+
+    /// Take one token, or return how long until one is available.
+    pub fn take(&mut self, capacity: f64, per_sec: f64, now: Instant) -> Result<(), Duration> {
+        let elapsed = now.saturating_duration_since(self.last).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_sec).min(capacity);
+        self.last = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64((1.0 - self.tokens) / per_sec))
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RateLimiter {
+    buckets: DashMap<String, TokenBucket>,
+    tenant_limits: DashMap<ObjectId, (RateLimits, Instant)>,
+    checks: AtomicU64,
+}
+
+impl RateLimiter {
+    /// Take one token from `key`'s bucket, which holds `per_min` tokens and
+    /// refills them over a minute.
+    pub fn check(&self, key: &str, per_min: u32) -> Result<(), ApiError> {
+        let capacity = f64::from(per_min.max(1));
+        let now = Instant::now();
+        if self
+            .checks
+            .fetch_add(1, Ordering::Relaxed)
+            .is_multiple_of(SWEEP_EVERY)
+        {
+            self.buckets
+                .retain(|_, b| now.saturating_duration_since(b.last) < IDLE_EVICT);
+        }
+        let mut bucket = self
+            .buckets
+            .entry(key.to_string())
+            .or_insert_with(|| TokenBucket::full(capacity));
+        bucket
+            .take(capacity, capacity / 60.0, now)
+            .map_err(|wait| ApiError::TooManyRequests(wait.as_secs_f64().ceil().max(1.0) as u64))
+    }
+
+    /// Drop every cached tenant limit, e.g. after a billing change.
+    pub fn forget_tenant_limits(&self) {
+        self.tenant_limits.clear();
+    }
+
+    async fn limits_for(&self, state: &AppState, tenant_id: ObjectId) -> RateLimits {
+        if let Some(entry) = self.tenant_limits.get(&tenant_id)
+            && entry.1.elapsed() < LIMITS_TTL
+        {
+            return entry.0;
+        }
+        let limits = match state.tenants.base.find_by_id(tenant_id).await {
+            Ok(tenant) => tenant.rate_limits(),
+            // The handler reports the missing tenant; don't fail here.
+            Err(_) => Plan::Free.rate_limits(),
+        };
+        self.tenant_limits
+            .insert(tenant_id, (limits, Instant::now()));
+        limits
+    }
+}
+
+/// Charge one message against the member's allowance in the tenant.
+pub async fn check_message(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !state.runtime.load().rate_limit.enabled {
+        return Ok(());
+    }
+    let limits = state.rate_limiter.limits_for(state, tenant_id).await;
+    state.rate_limiter.check(
+        &format!("msg:{tenant_id}:{user_id}"),
+        limits.messages_per_min,
+    )
+}
+
+/// Charge one file upload against the member's allowance in the tenant.
+pub async fn check_upload(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    if !state.runtime.load().rate_limit.enabled {
+        return Ok(());
+    }
+    let limits = state.rate_limiter.limits_for(state, tenant_id).await;
+    state.rate_limiter.check(
+        &format!("upload:{tenant_id}:{user_id}"),
+        limits.uploads_per_min,
+    )
+}
+
+/// Layer for the auth routes: limits credential attempts per client IP.
+pub async fn limit_auth(
+    State(state): State<AppState>,
+    req: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let runtime = state.runtime.load();
+    if runtime.rate_limit.enabled {
+        let ip = client_ip(
+            req.headers(),
+            req.extensions().get::<ConnectInfo<SocketAddr>>(),
+        );
+        state
+            .rate_limiter
+            .check(&format!("auth:{ip}"), runtime.rate_limit.auth_per_min)?;
+    }
+    Ok(next.run(req).await)
+}
+
+/// Client IP the same way the `/api` governor sees it: the ingress-set
+/// `X-Forwarded-For` / `X-Real-IP`, else the peer address.
+pub(crate) fn client_ip(headers: &HeaderMap, peer: Option<&ConnectInfo<SocketAddr>>) -> String {
+    headers
+        .get("x-forwarded-for")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .or_else(|| headers.get("x-real-ip").and_then(|v| v.to_str().ok()))
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .or_else(|| peer.map(|ConnectInfo(addr)| addr.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Per-connection budget for client → server WebSocket messages.
+pub struct WsThrottle {
+    bucket: TokenBucket,
+    capacity: f64,
+    per_sec: f64,
+    notified: bool,
+}
+
+impl WsThrottle {
+    /// `None` when rate limiting is disabled.
+    pub fn new(settings: &RateLimitSettings) -> Option<Self> {
+        if !settings.enabled {
+            return None;
+        }
+        let per_sec = f64::from(settings.ws_messages_per_sec.max(1));
+        let capacity = per_sec * WS_BURST_SECS;
+        Some(Self {
+            bucket: TokenBucket::full(capacity),
+            capacity,
+            per_sec,
+            notified: false,
+        })
+    }
+
+    /// `Ok` to process the message. Otherwise the message is dropped; the
+    /// first drop of a run carries the retry delay (ms) so the caller can
+    /// tell the client once instead of once per dropped message.
+    pub fn admit(&mut self) -> Result<(), Option<u64>> {
+        match self
+            .bucket
+            .take(self.capacity, self.per_sec, Instant::now())
+        {
+            Ok(()) => {
+                self.notified = false;
+                Ok(())
+            }
+            Err(wait) if !self.notified => {
+                self.notified = true;
+                Err(Some(wait.as_millis() as u64))
+            }
+            Err(_) => Err(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_burst_then_reports_wait() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::full(2.0);
+        assert!(bucket.take(2.0, 1.0, start).is_ok());
+        assert!(bucket.take(2.0, 1.0, start).is_ok());
+        let wait = bucket.take(2.0, 1.0, start).unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        assert!(
+            bucket
+                .take(2.0, 1.0, start + Duration::from_secs(1))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn limiter_keys_are_independent() {
+        let limiter = RateLimiter::default();
+        assert!(limiter.check("a", 1).is_ok());
+        assert!(matches!(
+            limiter.check("a", 1),
+            Err(ApiError::TooManyRequests(secs)) if secs >= 1
+        ));
+        assert!(limiter.check("b", 1).is_ok());
+    }
+
+    #[test]
+    fn ws_throttle_notifies_once_per_run() {
+        let settings = RateLimitSettings {
+            enabled: true,
+            auth_per_min: 1,
+            ws_messages_per_sec: 1,
+        };
+        let mut throttle = WsThrottle::new(&settings).unwrap();
+        for _ in 0..4 {
+            assert!(throttle.admit().is_ok());
+        }
+        assert!(matches!(throttle.admit(), Err(Some(_))));
+        assert_eq!(throttle.admit(), Err(None));
+        assert!(
+            WsThrottle::new(&RateLimitSettings {
+                enabled: false,
+                ..settings
+            })
+            .is_none()
+        );
+    }
+
+    #[test]
+    fn client_ip_prefers_forwarded_header() {
+        let mut headers = HeaderMap::new();
+        let peer = ConnectInfo(SocketAddr::from(([10, 0, 0, 1], 1234)));
+        assert_eq!(client_ip(&headers, Some(&peer)), "10.0.0.1");
+        headers.insert("x-forwarded-for", "203.0.113.7, 10.0.0.1".parse().unwrap());
+        assert_eq!(client_ip(&headers, Some(&peer)), "203.0.113.7");
+    }
+}
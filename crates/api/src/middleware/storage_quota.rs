@@ -0,0 +1,187 @@
+//! Per-tenant storage quotas. Every upload, file or recording, goes through
+//! [`upload`]; every delete reports what it freed.
+//!
+//! A tenant's quota is its own `settings.storage_quota_bytes`, or
+//! `storage.default_quota_bytes`; 0 is unlimited. Usage is the tenant's
+//! `storage_used_bytes` counter over files and recordings, reserved before
+//! every upload is stored (and given back if storing fails), lowered on every
+//! delete and reset to the measured total by the hourly storage meter.
+//!
+//! With `storage.quota_mode = block` an upload past the quota is refused
+//! with `507 storage_quota`; with `warn` it is stored and the uploader gets
+//! a `storage:quota_warning` event. Unlike the plan's storage limit, this
+//! applies without `stripe.enforce_limits`.
+
+use bson::oid::ObjectId;
+use roomler_ai_config::StorageQuotaMode;
+use roomler_ai_db::models::Tenant;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::error::ApiError;
+use crate::middleware::plan_limits;
+use crate::state::AppState;
+use crate::ws;
+
+pub use roomler_ai_types::tenant::StorageUsage;
+
+/// An upload that doesn't fit the quota.
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct QuotaExceeded {
+    pub used_bytes: u64,
+    pub quota_bytes: u64,
+    /// Size of the refused upload.
+    pub adding_bytes: u64,
+}
+
+impl std::fmt::Display for QuotaExceeded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Storage quota exceeded: {} of {} bytes used, upload is {} bytes",
+            self.used_bytes, self.quota_bytes, self.adding_bytes
+        )
+    }
+}
+
+/// The tenant's usage against its effective quota.
+pub fn usage(state: &AppState, tenant: &Tenant) -> StorageUsage {
+    let quota = tenant
+        .settings
+        .storage_quota_bytes
+        .unwrap_or(state.settings.storage.default_quota_bytes);
+    StorageUsage {
+        used_bytes: tenant.storage_used_bytes,
+        quota_bytes: (quota > 0).then_some(quota),
+    }
+}
+
+/// Store an upload of `bytes`: refuse it past the plan's storage limit or
+/// the quota, else reserve the bytes, run `store`, and give them back if it
+/// fails. The reservation is one conditional update, so concurrent uploads
+/// can't together go past a blocking quota.
+pub async fn upload<T>(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    bytes: u64,
+    store: impl Future<Output = Result<T, ApiError>>,
+) -> Result<T, ApiError> {
+    plan_limits::check_storage(state, tenant_id, user_id, bytes).await?;
+    reserve(state, tenant_id, user_id, bytes).await?;
+    match store.await {
+        Ok(stored) => Ok(stored),
+        Err(e) => {
+            add(state, tenant_id, -(bytes as i64)).await;
+            Err(e)
+        }
+    }
+}
+
+/// Count `bytes` more against the quota, if they may be.
+async fn reserve(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    bytes: u64,
+) -> Result<(), ApiError> {
+    let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+    let quota = usage(state, &tenant).quota_bytes;
+    let limit = match state.settings.storage.quota_mode {
+        StorageQuotaMode::Block => quota,
+        StorageQuotaMode::Warn => None,
+    };
+
+    let Some(after) = state
+        .tenants
+        .reserve_storage(tenant_id, bytes, limit)
+        .await?
+    else {
+        // Only a blocking quota refuses; report the usage it refused against
+        let tenant = state.tenants.base.find_by_id(tenant_id).await?;
+        return Err(ApiError::StorageQuota(QuotaExceeded {
+            used_bytes: tenant.storage_used_bytes,
+            quota_bytes: quota.unwrap_or_default(),
+            adding_bytes: bytes,
+        }));
+    };
+
+    let before = StorageUsage {
+        used_bytes: after.storage_used_bytes.saturating_sub(bytes),
+        quota_bytes: quota,
+    };
+    if let Err(exceeded) = fits(&before, bytes) {
+        tracing::warn!(%tenant_id, %exceeded, "Upload past the storage quota");
+        let event = serde_json::json!({
+            "type": "storage:quota_warning",
+            "tenant_id": tenant_id.to_hex(),
+            "data": {
+                "tenant_id": tenant_id.to_hex(),
+                "used_bytes": exceeded.used_bytes,
+                "quota_bytes": exceeded.quota_bytes,
+                "adding_bytes": exceeded.adding_bytes,
+                "message": exceeded.to_string(),
+            }
+        });
+        ws::dispatcher::send_to_user_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &user_id,
+            &event,
+        )
+        .await;
+    }
+    Ok(())
+}
+
+/// Count `bytes` freed by a delete.
+pub async fn record_delete(state: &AppState, tenant_id: ObjectId, bytes: u64) {
+    if bytes > 0 {
+        add(state, tenant_id, -(bytes as i64)).await;
+    }
+}
+
+/// Bookkeeping never fails the request; the meter corrects a missed update
+/// within the hour.
+async fn add(state: &AppState, tenant_id: ObjectId, delta: i64) {
+    if let Err(e) = state.tenants.add_storage_used(tenant_id, delta).await {
+        tracing::warn!(%tenant_id, delta, %e, "Failed to update storage usage");
+    }
+}
+
+fn fits(usage: &StorageUsage, adding: u64) -> Result<(), QuotaExceeded> {
+    match usage.quota_bytes {
+        Some(quota) if usage.used_bytes.saturating_add(adding) > quota => Err(QuotaExceeded {
+            used_bytes: usage.used_bytes,
+            quota_bytes: quota,
+            adding_bytes: adding,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(used_bytes: u64, quota_bytes: Option<u64>) -> StorageUsage {
+        StorageUsage {
+            used_bytes,
+            quota_bytes,
+        }
+    }
+
+    #[test]
+    fn uploads_fit_up_to_the_quota() {
+        assert!(fits(&usage(60, Some(100)), 40).is_ok());
+        let exceeded = fits(&usage(60, Some(100)), 41).unwrap_err();
+        assert_eq!(exceeded.used_bytes, 60);
+        assert_eq!(exceeded.quota_bytes, 100);
+        assert_eq!(exceeded.adding_bytes, 41);
+    }
+
+    #[test]
+    fn no_quota_is_unlimited() {
+        assert!(fits(&usage(u64::MAX, None), u64::MAX).is_ok());
+    }
+}
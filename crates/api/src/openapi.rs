@@ -0,0 +1,452 @@
+//! OpenAPI document for the REST API, served as JSON at `/api/openapi.json`
+//! with Swagger UI at `/api/docs`. Handlers carry their own
+//! `#[utoipa::path]` annotations; this module only collects them.
+//!
+//! The WebSocket protocol has no OpenAPI representation, so it is described
+//! under the top-level `x-websocket` extension (see `docs/real-time.md`).
+
+use std::collections::HashSet;
+
+use serde_json::json;
+use utoipa::openapi::extensions::ExtensionsBuilder;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
+
+use crate::routes;
+
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Roomler AI API",
+        description = "REST API of the Roomler AI server. Authenticated routes take a \
+                       JWT access token (or a bot token) as `Authorization: Bearer`; the \
+                       browser app sends the `access_token` cookie instead. Errors are \
+                       returned as `ErrorResponse`."
+    ),
+    paths(
+        routes::auth::logout,
+        routes::auth::me,
+        routes::auth::register,
+        routes::auth::login,
+        routes::auth::refresh,
+        routes::auth::activate,
+        routes::auth::ws_ticket,
+        routes::user::update_profile,
+        routes::user::get_profile,
+        routes::user::set_status,
+        routes::user::clear_status,
+        routes::user::upload_avatar,
+        routes::user::delete_avatar,
+        routes::user::avatar,
+        routes::oauth::providers,
+        routes::oauth::oauth_redirect,
+        routes::oauth::oauth_callback,
+        routes::calendar::list,
+        routes::calendar::connect,
+        routes::calendar::callback,
+        routes::calendar::disconnect,
+        routes::stripe::get_plans,
+        routes::stripe::create_checkout,
+        routes::stripe::create_portal,
+        routes::stripe::webhook,
+        routes::invite::get_invite_info,
+        routes::invite::accept_invite,
+        routes::webhook::incoming,
+        routes::giphy::search,
+        routes::giphy::trending,
+        routes::push::config,
+        routes::push::subscribe,
+        routes::push::unsubscribe,
+        routes::push::set_device_preferences,
+        routes::notification::list,
+        routes::notification::unread,
+        routes::notification::unread_count,
+        routes::notification::mark_read,
+        routes::notification::mark_all_read,
+        routes::notification::get_preferences,
+        routes::notification::update_preferences,
+        routes::notification::get_room_preferences,
+        routes::notification::update_room_preferences,
+        routes::remote_control::enroll_agent,
+        routes::agent_release::latest_release,
+        routes::agent_release::installer_health,
+        routes::agent_release::installer_proxy,
+        routes::agent_crash::ingest,
+        routes::consent::approve_consent,
+        routes::consent::deny_consent,
+        routes::tunnel::enroll_tunnel_client,
+        routes::tunnel::list_tenant_agents,
+        routes::tunnel_release::latest_release,
+        routes::tunnel_release::installer_health,
+        routes::tunnel_release::installer_proxy,
+        routes::setup_release::setup_latest_release,
+        routes::setup_release::install_script_sh,
+        routes::setup_release::install_script_ps1,
+        routes::setup_release::setup_installer_health,
+        routes::setup_release::setup_installer_proxy,
+        routes::remote_control::turn_credentials,
+        routes::remote_control::turn_regions,
+        routes::ws::stats,
+        routes::agent_log::ingest_browser,
+        routes::tenant::list,
+        routes::tenant::create,
+        routes::tenant::get,
+        routes::tenant_domain::list,
+        routes::tenant_domain::create,
+        routes::tenant_domain::verify,
+        routes::tenant_domain::update,
+        routes::tenant_domain::delete,
+        routes::tenant_domain::joinable,
+        routes::tenant_domain::join,
+        routes::feature_flag::list,
+        routes::feature_flag::update,
+        routes::user::list_members,
+        routes::user::search_members,
+        routes::invite::add_member,
+        routes::user::remove_member,
+        routes::role::list,
+        routes::role::create,
+        routes::role::update,
+        routes::role::delete,
+        routes::role::assign,
+        routes::role::unassign,
+        routes::invite::list_invites,
+        routes::invite::create_invite,
+        routes::invite::batch_create_invite,
+        routes::invite::revoke_invite,
+        routes::search::search,
+        routes::admin::list_audit,
+        routes::admin::get_settings,
+        routes::admin::update_settings,
+        routes::admin::update_storage_quota,
+        routes::webhook::list,
+        routes::webhook::create,
+        routes::webhook::update,
+        routes::webhook::delete,
+        routes::slash_command::list,
+        routes::slash_command::create,
+        routes::slash_command::delete,
+        routes::bot::list,
+        routes::bot::create,
+        routes::bot::delete,
+        routes::bot::list_tokens,
+        routes::bot::create_token,
+        routes::bot::revoke_token,
+        routes::room::list,
+        routes::room::create,
+        routes::room::explore,
+        routes::room::get,
+        routes::room::update,
+        routes::room::delete,
+        routes::room::restore,
+        routes::room::join,
+        routes::room::leave,
+        routes::room::members,
+        routes::slash_command::invoke,
+        routes::room::get_permissions,
+        routes::room::set_permissions,
+        routes::room::call_start,
+        routes::room::call_join,
+        routes::room::call_leave,
+        routes::room::call_end,
+        routes::room::participants,
+        routes::room::call_history,
+        routes::call_debug::timeline,
+        routes::room::ice_servers,
+        routes::call_audio::mute,
+        routes::call_audio::push_to_talk,
+        routes::call_transcription::opt_out,
+        routes::call_transcription::append,
+        routes::call_schedule::get,
+        routes::call_schedule::set,
+        routes::call_schedule::delete,
+        routes::call_schedule::sync,
+        routes::webinar::get,
+        routes::webinar::promote,
+        routes::webinar::demote,
+        routes::breakout::create,
+        routes::breakout::list,
+        routes::breakout::assign,
+        routes::breakout::close,
+        routes::whiteboard::get,
+        routes::whiteboard::export,
+        routes::poll::create,
+        routes::poll::list,
+        routes::poll::vote,
+        routes::poll::update,
+        routes::poll::close,
+        routes::question::list,
+        routes::question::create,
+        routes::question::upvote,
+        routes::question::remove_upvote,
+        routes::question::answer,
+        routes::room::call_messages,
+        routes::room::create_call_message,
+        routes::message::list,
+        routes::message::create,
+        routes::message::pinned,
+        routes::scheduled_message::list,
+        routes::scheduled_message::cancel,
+        routes::message::update,
+        routes::message::delete,
+        routes::message::restore,
+        routes::message::toggle_pin,
+        routes::message::history,
+        routes::message::thread_replies,
+        routes::message::follow_thread,
+        routes::message::unfollow_thread,
+        routes::reaction::add,
+        routes::reaction::remove,
+        routes::message::mark_read,
+        routes::message::unread_count,
+        routes::recording::list,
+        routes::recording::create,
+        routes::recording::delete,
+        routes::recording::upload_file,
+        routes::recording::stream,
+        routes::recording::captions,
+        routes::recording::transcript,
+        routes::recording::set_transcript,
+        routes::recording_consent::respond,
+        routes::recording_consent::list,
+        routes::file::list,
+        routes::file::upload_room,
+        routes::file::list_tenant_files,
+        routes::file::upload,
+        routes::file::get,
+        routes::file::download,
+        routes::file::override_scan,
+        routes::file::preview,
+        routes::file::delete,
+        routes::asset::list_backgrounds,
+        routes::asset::upload_background,
+        routes::asset::delete_background,
+        routes::asset::list_emojis,
+        routes::asset::upload_emoji,
+        routes::asset::delete_emoji,
+        routes::integration::recognize_file,
+        routes::background_task::list,
+        routes::background_task::get,
+        routes::background_task::download,
+        routes::export::export_conversation,
+        routes::export::export_room,
+        routes::integration::export_conversation_pdf,
+        routes::retention::get,
+        routes::retention::update,
+        routes::retention::set_legal_hold,
+        routes::usage::get,
+        routes::sidebar::get,
+        routes::sidebar::update_room,
+        routes::sidebar::set_order,
+        routes::remote_control::list_agents,
+        routes::remote_control::issue_enrollment_token,
+        routes::remote_control::get_agent,
+        routes::remote_control::update_agent,
+        routes::remote_control::delete_agent,
+        routes::agent_crash::list_for_agent,
+        routes::agent_log::ingest_agent,
+        routes::agent_log::list_for_agent,
+        routes::tunnel::list_tunnel_clients,
+        routes::tunnel::issue_tunnel_enrollment_token,
+        routes::tunnel::list_tunnel_policies,
+        routes::tunnel::create_tunnel_policy,
+        routes::tunnel::get_tunnel_policy,
+        routes::tunnel::update_tunnel_policy,
+        routes::tunnel::delete_tunnel_policy,
+        routes::overlay_route::list_overlay_nodes,
+        routes::overlay_route::set_approved_routes,
+        routes::overlay_route::set_exit_node,
+        routes::overlay_route::get_magic_dns,
+        routes::overlay_route::set_magic_dns,
+        routes::remote_control::get_session,
+        routes::remote_control::terminate_session,
+        routes::remote_control::session_audit,
+    ),
+    components(schemas(crate::error::ErrorResponse)),
+    modifiers(&BearerAuth, &OperationIds, &WebSocketProtocol),
+    security(("bearer" = []))
+)]
+pub struct ApiDoc;
+
+/// Registers the `bearer` scheme referenced by `security(...)`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// Handler names repeat across modules (`list`, `create`, ...), so prefix
+/// each operation id with its tag to keep them unique for client generators.
+/// A handler mounted for several methods also gets the method appended.
+struct OperationIds;
+
+impl Modify for OperationIds {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let mut seen = HashSet::new();
+        for item in openapi.paths.paths.values_mut() {
+            let operations = [
+                ("get", &mut item.get),
+                ("put", &mut item.put),
+                ("post", &mut item.post),
+                ("delete", &mut item.delete),
+                ("patch", &mut item.patch),
+            ];
+            for (method, op) in operations {
+                let Some(op) = op else { continue };
+                let tag = op.tags.as_ref().and_then(|tags| tags.first());
+                let (Some(tag), Some(id)) = (tag, &op.operation_id) else {
+                    continue;
+                };
+                let mut id = format!("{tag}_{id}");
+                if !seen.insert(id.clone()) {
+                    id = format!("{id}_{method}");
+                }
+                op.operation_id = Some(id);
+            }
+        }
+    }
+}
+
+/// Describes `GET /ws` and its message envelope under `x-websocket`.
+struct WebSocketProtocol;
+
+impl Modify for WebSocketProtocol {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        // Split up to stay under `json!`'s macro recursion limit.
+        let client_messages = json!({
+            "ping": "{}",
+            "sync": "{ room_id, last_seq }",
+            "typing:start": "{ room_id }",
+            "typing:stop": "{ room_id }",
+            "presence:update": "{ presence }",
+            "presence:subscribe": "{ room_id }",
+            "presence:unsubscribe": "{ room_id }",
+            "tenant:subscribe": "{ tenant_ids }",
+            "tenant:unsubscribe": "{ tenant_ids }",
+            "media:join": "{ room_id }",
+            "media:connect_transport": "{ room_id, transport_id, dtls_parameters }",
+            "media:restart_ice": "{ room_id, transport_id }",
+            "media:rejoin": "{ resume_token }",
+            "media:produce": "{ room_id, kind, rtp_parameters, source }",
+            "media:consume": "{ room_id, producer_id, rtp_capabilities }",
+            "media:producer_close": "{ room_id, producer_id }",
+            "media:leave": "{ room_id }",
+            "media:key_rotate": "{ room_id }",
+            "media:key_distribute": "{ room_id, epoch, keys: [{ connection_id, payload }] }",
+            "media:play_audio": "{ room_id, file_id }",
+            "media:stop_audio": "{ room_id, playback_id }",
+            "media:effects_state": "{ room_id, background: none|blur|image, asset_id? }",
+            "media:ptt_active": "{ room_id, active }",
+            "call:accept": "{ room_id }",
+            "call:decline": "{ room_id }",
+            "whiteboard:op": "{ room_id, op, client_op_id? }",
+            "whiteboard:sync": "{ room_id }",
+        });
+        let mut server_messages = json!({
+            "connected": "{ user_id, tenant_ids }",
+            "tenant:subscribed": "{ tenant_ids }",
+            "pong": "{}",
+            "rate_limited": "{ retry_after_ms }",
+            "sync:done": "{ room_id, seq }",
+            "sync:resync_required": "{ room_id, seq }",
+            "typing:start": "{ room_id, user_id }",
+            "typing:stop": "{ room_id, user_id }",
+            "presence:update": "{ user_id, presence }",
+            "presence:snapshot": "{ room_id, users: [{ user_id, presence }], typing }",
+            "message:create": "MessageResponse",
+            "message:ack": "{ room_id, nonce, id, created_at }",
+            "message:update": "MessageResponse",
+            "message:delete": "{ id, room_id }",
+            "message:restore": "MessageResponse",
+            "message:reaction": "{ action, message_id, room_id, user_id, emoji, custom_emoji_id? }",
+            "notification:new": "{ id, title, body, link, notification_type, created_at }",
+            "billing:limit_reached": "{ tenant_id, limit, plan, max, current, message }",
+            "room:call_started": "{ room_id, room_name, started_by }",
+            "room:call_updated": "{ room_id, participant_count, conference_status }",
+            "room:call_ended": "{ room_id }",
+            "call:ring": "{ room_id, tenant_id, room_name, caller_id, caller_name, timeout_secs }",
+            "call:ring_update": "{ room_id, user_id, status: accepted|declined|missed }",
+            "call:ring_cancelled": "{ room_id, reason: accepted|declined|timeout|ended }",
+            "call:message:create": "{ room_id, message }",
+            "call:breakout_assigned": "{ room_id, breakout_id, name }",
+            "call:breakout_ended": "{ room_id }",
+            "call:poll:create": "{ room_id, poll }",
+            "call:poll:update": "{ room_id, poll }",
+            "call:question:create": "{ room_id, question }",
+            "call:question:update": "{ room_id, question }",
+            "whiteboard:op": "{ room_id, seq, user_id, client_op_id, op }",
+            "whiteboard:snapshot": "{ room_id, seq, elements }",
+            "whiteboard:error": "{ room_id, client_op_id, message }",
+        });
+        let media_server_messages = json!({
+            "media:router_capabilities": "{ rtp_capabilities }",
+            "media:transport_created":
+                "{ send_transport, recv_transport, ice_servers, force_relay, e2ee, resume_token, overflow }",
+            "media:room_full": "{ room_id, max_participants }",
+            "media:produce_result": "{ id }",
+            "media:consumer_created": "{ id, producer_id, kind, rtp_parameters }",
+            "media:ice_restarted": "{ room_id, transport_id, ice_parameters }",
+            "media:rejoined": "{ room_id, resume_token, previous_connection_id }",
+            "media:peer_reconnected": "{ room_id, user_id, connection_id, previous_connection_id }",
+            "media:new_producer": "{ producer_id, user_id, connection_id, kind, source }",
+            "media:producer_closed": "{ producer_id, user_id }",
+            "media:peer_left": "{ room_id, user_id, connection_id }",
+            "media:redirect": "{ room_id, url }",
+            "media:key_rotate": "{ room_id, epoch, reason, participants }",
+            "media:key_distribute":
+                "{ room_id, epoch, from_user_id, from_connection_id, payload }",
+            "media:audio_playback":
+                "{ action, room_id, playback_id, file_id, file_url, filename }",
+            "media:room_closed": "{ room_id }",
+            "media:effects_state": "{ room_id, user_id, connection_id, background, asset_id }",
+            "media:audio_state":
+                "{ room_id, user_id, connection_id, force_muted, ptt_active, silenced }",
+            "media:push_to_talk": "{ room_id, enabled }",
+            "media:speaker_update":
+                "{ room_id, user_id, speaker, speaker_count, attendee_count }",
+            "media:webinar_state":
+                "{ room_id, speaker, speakers, speaker_count, attendee_count }",
+            "media:webinar_counts": "{ room_id, speaker_count, attendee_count }",
+            "media:consumer_paused": "{ room_id, consumer_id, reason: bandwidth }",
+            "media:consumer_resumed": "{ room_id, consumer_id, reason: bandwidth }",
+            "media:error": "{ message }",
+        });
+        if let (Some(all), serde_json::Value::Object(media)) =
+            (server_messages.as_object_mut(), media_server_messages)
+        {
+            all.extend(media);
+        }
+        let protocol = json!({
+            "path": "/ws",
+            "query": {
+                "token": "Access token, bot token, agent token or tunnel-client token",
+                "ticket": "One-time ticket from `POST /api/auth/ws-ticket`, instead of `token` for users",
+                "tenants": "Comma-separated tenant ids; only those tenants' events are delivered (all when omitted)",
+                "batch": "`true` to receive every text frame as a JSON array of envelopes",
+                "role": "`agent` or `tunnel-client` for those connections; omit otherwise",
+            },
+            "envelope": "{ type, data }; `connected` carries `user_id` and `tenant_ids` at the top level; tenant events carry `tenant_id`, replayable room events also `room_id` and `seq` (see `sync`); with `batch=true` frames are arrays of these",
+            "client_messages": client_messages,
+            "server_messages": server_messages,
+            "docs": "docs/real-time.md",
+        });
+        let extensions = ExtensionsBuilder::new()
+            .add("x-websocket", protocol)
+            .build();
+        match openapi.extensions.as_mut() {
+            Some(existing) => existing.merge(extensions),
+            None => openapi.extensions = Some(extensions),
+        }
+    }
+}
@@ -1,4 +1,250 @@
-// Admin routes - Phase 2+
-// GET /api/tenant/:tid/admin/audit-log
-// GET /api/tenant/:tid/admin/stats
-// Admin-only access controlled by RBAC.
+use std::collections::BTreeMap;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_config::masked;
+use roomler_ai_db::models::role::permissions;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    middleware::storage_quota::{self, StorageUsage},
+    state::AppState,
+};
+use roomler_ai_services::dao::base::PaginatedResult;
+use utoipa::ToSchema;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditChangeResponse {
+    pub field: String,
+    pub old_value: Option<serde_json::Value>,
+    pub new_value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AuditLogResponse {
+    pub id: String,
+    pub actor_id: Option<String>,
+    pub actor_type: String,
+    pub action: String,
+    pub target_type: String,
+    pub target_id: Option<String>,
+    pub changes: Vec<AuditChangeResponse>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub reason: Option<String>,
+    pub created_at: String,
+}
+
+const AUDIT_SPEC: ListSpec = ListSpec {
+    sort: &[("created_at", "created_at")],
+    filters: &[
+        FilterField::new("action", "action", FieldKind::String),
+        FilterField::new("actor_id", "actor_id", FieldKind::ObjectId),
+        FilterField::new("target_type", "target_type", FieldKind::String),
+        FilterField::new("target_id", "target_id", FieldKind::ObjectId),
+        FilterField::new("created_at", "created_at", FieldKind::DateTime),
+    ],
+};
+
+/// GET /api/tenant/{tenant_id}/audit — the tenant's audit trail, newest
+/// first. Requires `MANAGE_TENANT`.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/audit",
+    tag = "admin",
+    params(("tenant_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<AuditLogResponse>))
+)]
+pub async fn list_audit(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<AuditLogResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    let options = query.options(&AUDIT_SPEC)?;
+    let result = state
+        .audit_logs
+        .list_for_tenant(tid, &query.pagination, &options)
+        .await?;
+
+    Ok(Json(result.map(to_response)))
+}
+
+fn to_response(e: roomler_ai_db::models::AuditLog) -> AuditLogResponse {
+    AuditLogResponse {
+        id: e.id.map(|id| id.to_hex()).unwrap_or_default(),
+        actor_id: e.actor_id.map(|id| id.to_hex()),
+        actor_type: format!("{:?}", e.actor_type).to_lowercase(),
+        action: e.action,
+        target_type: e.target_type,
+        target_id: e.target_id.map(|id| id.to_hex()),
+        changes: e
+            .changes
+            .into_iter()
+            .map(|c| AuditChangeResponse {
+                field: c.field,
+                old_value: c.old_value,
+                new_value: c.new_value,
+            })
+            .collect(),
+        ip: e.metadata.ip,
+        user_agent: e.metadata.user_agent,
+        reason: e.metadata.reason,
+        created_at: e.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RuntimeSettingsResponse {
+    /// Every runtime-tunable value in effect, by dotted key (`turn.url`,
+    /// `rate_limit.auth_per_min`, `features.<name>`). Secrets are masked.
+    #[schema(value_type = Object)]
+    pub values: BTreeMap<String, serde_json::Value>,
+    /// The keys among them that an operator overrode, with their values.
+    #[schema(value_type = Object)]
+    pub overrides: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateRuntimeSettingsRequest {
+    /// Keys to override, by dotted key. `null` drops a key's override so the
+    /// config files apply again.
+    #[schema(value_type = Object)]
+    pub values: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateStorageQuotaRequest {
+    /// The tenant's file storage quota in bytes; 0 is unlimited and `null`
+    /// falls back to `storage.default_quota_bytes`.
+    pub quota_bytes: Option<u64>,
+}
+
+/// GET /api/admin/settings — runtime-tunable settings in effect on this
+/// pod. Operators only (`app.admin_emails`).
+#[utoipa::path(
+    get,
+    path = "/api/admin/settings",
+    tag = "admin",
+    responses((status = 200, body = RuntimeSettingsResponse))
+)]
+pub async fn get_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<RuntimeSettingsResponse>, ApiError> {
+    require_operator(&state, &auth)?;
+    Ok(Json(settings_response(&state)))
+}
+
+/// PUT /api/admin/settings — override runtime-tunable settings for every
+/// pod. Applies here at once and on the other pods at their next reload.
+/// Operators only.
+#[utoipa::path(
+    put,
+    path = "/api/admin/settings",
+    tag = "admin",
+    request_body = UpdateRuntimeSettingsRequest,
+    responses((status = 200, body = RuntimeSettingsResponse))
+)]
+pub async fn update_settings(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<UpdateRuntimeSettingsRequest>,
+) -> Result<Json<RuntimeSettingsResponse>, ApiError> {
+    require_operator(&state, &auth)?;
+
+    state
+        .runtime
+        .check_changes(&body.values)
+        .map_err(ApiError::Validation)?;
+    // Store first, merging per key so a change made meanwhile on another pod
+    // survives, then apply what was stored
+    let _writes = state.runtime.lock_writes().await;
+    let overrides = state
+        .settings_overrides
+        .merge(&body.values, auth.user_id)
+        .await?;
+    state.runtime.apply_stored(overrides);
+    tracing::info!(user_id = ?auth.user_id, keys = ?body.values.keys(), "Runtime settings overridden");
+
+    Ok(Json(settings_response(&state)))
+}
+
+/// PUT /api/admin/tenant/{tenant_id}/storage-quota — set a tenant's own
+/// file storage quota. Operators only.
+#[utoipa::path(
+    put,
+    path = "/api/admin/tenant/{tenant_id}/storage-quota",
+    tag = "admin",
+    params(("tenant_id" = String, Path)),
+    request_body = UpdateStorageQuotaRequest,
+    responses((status = 200, body = StorageUsage))
+)]
+pub async fn update_storage_quota(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<UpdateStorageQuotaRequest>,
+) -> Result<Json<StorageUsage>, ApiError> {
+    require_operator(&state, &auth)?;
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let tenant = state
+        .tenants
+        .set_storage_quota(tid, body.quota_bytes)
+        .await?;
+    tracing::info!(user_id = ?auth.user_id, tenant_id = %tid, quota_bytes = ?body.quota_bytes, "Storage quota set");
+
+    Ok(Json(storage_quota::usage(&state, &tenant)))
+}
+
+/// Operators are the users listed in `app.admin_emails`; bot tokens never
+/// are.
+fn require_operator(state: &AppState, auth: &AuthUser) -> Result<(), ApiError> {
+    let listed = state
+        .settings
+        .app
+        .admin_emails
+        .split(',')
+        .map(str::trim)
+        .any(|email| !email.is_empty() && email.eq_ignore_ascii_case(&auth.email));
+    if !listed || auth.bot.is_some() {
+        return Err(ApiError::Forbidden("Operators only".to_string()));
+    }
+    Ok(())
+}
+
+fn settings_response(state: &AppState) -> RuntimeSettingsResponse {
+    RuntimeSettingsResponse {
+        values: state.runtime.load().tunables(),
+        overrides: state
+            .runtime
+            .overrides()
+            .into_iter()
+            .map(|(key, value)| {
+                let value = masked(&key, value);
+                (key, value)
+            })
+            .collect(),
+    }
+}
@@ -23,6 +23,7 @@ use bson::oid::ObjectId;
 use roomler_ai_remote_control::models::{AgentCrashPayload, AgentCrashRecord};
 use serde::Serialize;
 use serde_json::json;
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
@@ -48,6 +49,13 @@ const MAX_FUTURE_SECS: i64 = 60 * 60; // 1h tolerance for clock skew
 ///   422 — payload validation failed (log_tail too large, summary
 ///         empty, crashed_at_unix outside plausibility window).
 ///   500 — DB write failure.
+#[utoipa::path(
+    post,
+    path = "/api/agent/crash",
+    tag = "agent_crash",
+    request_body = serde_json::Value,
+    responses((status = 201, body = serde_json::Value))
+)]
 pub async fn ingest(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -79,6 +87,13 @@ pub async fn ingest(
 /// GET `/api/tenant/{tenant_id}/agent/{agent_id}/crash` — list the
 /// most-recent 50 crash reports for the agent. Auth: standard user
 /// JWT + tenant membership.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}/crash",
+    tag = "agent_crash",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path)),
+    responses((status = 200, body = AgentCrashListResponse))
+)]
 pub async fn list_for_agent(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -103,7 +118,7 @@ pub async fn list_for_agent(
     Ok(Json(AgentCrashListResponse { items }))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AgentCrashListResponse {
     pub items: Vec<AgentCrashView>,
 }
@@ -111,12 +126,13 @@ pub struct AgentCrashListResponse {
 /// View-model for an admin-UI crash row. Mirrors the payload's
 /// camelCase shape PLUS the server-side `_id` (hex string) and
 /// `reportedAt` (RFC 3339).
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentCrashView {
     pub id: String,
     pub reported_at: String,
     #[serde(flatten)]
+    #[schema(value_type = Object)]
     pub payload: AgentCrashPayload,
 }
 
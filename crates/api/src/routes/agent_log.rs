@@ -26,13 +26,15 @@ use bson::{DateTime, oid::ObjectId};
 use roomler_ai_db::models::{AgentLogBatch, LogLine, LogSource};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
 /// Body shape POSTed by an uploader. `source` must NOT be `Browser`
 /// on the agent-authed route; route handler enforces this.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LogBatchPayload {
+    #[schema(value_type = String)]
     pub source: LogSource,
     #[serde(default)]
     pub session_id: Option<String>,
@@ -40,10 +42,12 @@ pub struct LogBatchPayload {
     pub host_id_hash: Option<String>,
     #[serde(default)]
     pub agent_version: Option<String>,
+    #[schema(value_type = Vec<Object>)]
     pub lines: Vec<LogLine>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ListLogsQuery {
     #[serde(default = "default_limit")]
     pub limit: i64,
@@ -53,7 +57,7 @@ fn default_limit() -> i64 {
     50
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct LogsListResponse {
     pub batches: Vec<AgentLogBatchView>,
 }
@@ -61,7 +65,7 @@ pub struct LogsListResponse {
 /// View-model for an admin-UI log batch row. Mirrors the wire shape
 /// but exposes `id` as hex string + `created_at` as RFC 3339 so the
 /// SPA doesn't need to handle BSON dates.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AgentLogBatchView {
     pub id: String,
@@ -73,6 +77,7 @@ pub struct AgentLogBatchView {
     pub agent_version: Option<String>,
     pub line_count: u32,
     pub created_at: String,
+    #[schema(value_type = Vec<Object>)]
     pub lines: Vec<LogLine>,
 }
 
@@ -105,6 +110,14 @@ impl From<AgentLogBatch> for AgentLogBatchView {
 ///   422 — payload validation failed (too many lines, oversized msg,
 ///         body too large, browser source on agent route).
 ///   500 — DB write failure.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}/logs",
+    tag = "agent_log",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path)),
+    request_body = LogBatchPayload,
+    responses((status = 201, body = serde_json::Value))
+)]
 pub async fn ingest_agent(
     State(state): State<AppState>,
     Path((tenant_id, agent_id)): Path<(String, String)>,
@@ -161,6 +174,13 @@ pub async fn ingest_agent(
 /// from a `tenant_id` query / form param (not yet wired — punted to
 /// rc.59 where the browser-side composable lands). For now the body
 /// must include `tenant_id`.
+#[utoipa::path(
+    post,
+    path = "/api/log/browser",
+    tag = "agent_log",
+    request_body = BrowserLogBatchPayload,
+    responses((status = 201, body = serde_json::Value))
+)]
 pub async fn ingest_browser(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -192,7 +212,7 @@ pub async fn ingest_browser(
 
 /// Browser-route body — wraps a [`LogBatchPayload`] with an explicit
 /// `tenant_id` since the user JWT alone doesn't pin a tenant.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BrowserLogBatchPayload {
     pub tenant_id: String,
     #[serde(flatten)]
@@ -202,6 +222,13 @@ pub struct BrowserLogBatchPayload {
 /// GET `/api/tenant/{tenant_id}/agent/{agent_id}/logs?limit=N` —
 /// list the most-recent N batches. Auth: standard user JWT + tenant
 /// membership. Default limit: 50.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}/logs",
+    tag = "agent_log",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path), ListLogsQuery),
+    responses((status = 200, body = LogsListResponse))
+)]
 pub async fn list_for_agent(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -36,6 +36,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{error::ApiError, state::AppState};
 
@@ -56,7 +57,7 @@ const RELEASES_PER_PAGE: usize = 30;
 /// Subset of GitHub's release JSON the agent actually consults. We
 /// don't need authors, body, html_url, or hundreds of bytes of CI
 /// metadata. Slimming the response also makes the cache cheap.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct AgentReleaseAsset {
     pub name: String,
     pub browser_download_url: String,
@@ -73,7 +74,7 @@ pub struct AgentReleaseAsset {
     pub digest: Option<String>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, ToSchema)]
 pub struct AgentRelease {
     pub tag_name: String,
     #[serde(default)]
@@ -112,6 +113,13 @@ impl LatestReleaseCache {
 /// Response shape: `Vec<AgentRelease>`, mimicking the agent's
 /// existing GitHub-shape parser so the agent-side code change is
 /// just a URL swap.
+#[utoipa::path(
+    get,
+    path = "/api/agent/latest-release",
+    tag = "agent_release",
+    responses((status = 200, body = Vec<AgentRelease>)),
+    security(())
+)]
 pub async fn latest_release(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<AgentRelease>>, ApiError> {
@@ -199,7 +207,8 @@ pub(crate) fn filter_component_releases(
 /// Query parameter for both `/installer/{flavour}` and
 /// `/installer/{flavour}/health`. `version=latest` (default) picks
 /// the most recent non-prerelease tag; an explicit tag name pins.
-#[derive(Clone, Debug, Deserialize)]
+#[derive(Clone, Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct InstallerQuery {
     #[serde(default = "default_version_latest")]
     pub version: String,
@@ -212,7 +221,7 @@ fn default_version_latest() -> String {
 /// JSON returned by `/installer/{flavour}/health`. The wizard uses
 /// `size` to render a download progress bar and `digest` to verify
 /// the MSI bytes match the advertised hash before launching msiexec.
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, Serialize, ToSchema)]
 pub struct InstallerHealth {
     /// Resolved tag, e.g. `agent-v0.3.0-rc.27`.
     pub tag: String,
@@ -233,6 +242,14 @@ pub struct InstallerHealth {
 }
 
 /// `GET /api/agent/installer/{flavour}/health`.
+#[utoipa::path(
+    get,
+    path = "/api/agent/installer/{flavour}/health",
+    tag = "agent_release",
+    params(("flavour" = String, Path), InstallerQuery),
+    responses((status = 200, body = InstallerHealth)),
+    security(())
+)]
 pub async fn installer_health(
     State(state): State<AppState>,
     Path(flavour): Path<String>,
@@ -263,6 +280,16 @@ pub async fn installer_health(
 }
 
 /// `GET /api/agent/installer/{flavour}` — streams the MSI bytes.
+#[utoipa::path(
+    get,
+    path = "/api/agent/installer/{flavour}",
+    tag = "agent_release",
+    params(("flavour" = String, Path), InstallerQuery),
+    responses(
+        (status = 200, description = "Installer binary", content_type = "application/octet-stream"),
+    ),
+    security(())
+)]
 pub async fn installer_proxy(
     State(state): State<AppState>,
     Path(flavour): Path<String>,
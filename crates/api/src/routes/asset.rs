@@ -0,0 +1,446 @@
+use axum::{
+    Json,
+    extract::{Multipart, Path, State},
+};
+use bson::{DateTime, oid::ObjectId};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::file::FileResponse;
+use crate::{
+    error::ApiError, extractors::auth::AuthUser, middleware::storage_quota, state::AppState,
+};
+use roomler_ai_db::models::{CustomEmoji, FileContext, FileContextType, role::permissions};
+
+/// Largest background image accepted.
+pub const MAX_BACKGROUND_SIZE: usize = 10 * 1024 * 1024;
+/// Backgrounds kept per tenant.
+const MAX_BACKGROUNDS: usize = 50;
+
+/// Largest custom emoji image accepted.
+pub const MAX_EMOJI_SIZE: usize = 256 * 1024;
+/// Custom emojis kept per tenant.
+const MAX_EMOJIS: u64 = 500;
+
+/// Multipart body of the background upload, for the OpenAPI document only.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct BackgroundUploadForm {
+    /// PNG, JPEG or WebP image.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+/// The tenant's virtual background images, newest first.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/asset/background",
+    tag = "asset",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<FileResponse>))
+)]
+pub async fn list_backgrounds(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<FileResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let files = state.files.find_backgrounds(tid).await?;
+    Ok(Json(
+        files.into_iter().map(super::file::to_response).collect(),
+    ))
+}
+
+/// Add an image to the tenant's background library (MANAGE_TENANT).
+/// Download it through the regular file download endpoint.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/asset/background",
+    tag = "asset",
+    params(("tenant_id" = String, Path)),
+    request_body(content = BackgroundUploadForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = FileResponse))
+)]
+pub async fn upload_background(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<FileResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+    crate::middleware::rate_limit::check_upload(&state, tid, auth.user_id).await?;
+
+    let mut file_data: Option<(String, Vec<u8>)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Multipart error: {}", e)))?
+    {
+        if field.name() == Some("file") {
+            let filename = field.file_name().unwrap_or("background").to_string();
+            let bytes = field
+                .bytes()
+                .await
+                .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {}", e)))?;
+            file_data = Some((filename, bytes.to_vec()));
+        }
+    }
+    let (filename, bytes) =
+        file_data.ok_or_else(|| ApiError::BadRequest("Missing 'file' field".to_string()))?;
+
+    if bytes.len() > MAX_BACKGROUND_SIZE {
+        return Err(ApiError::Validation(format!(
+            "Backgrounds are limited to {} MB",
+            MAX_BACKGROUND_SIZE / (1024 * 1024)
+        )));
+    }
+    // Trust the bytes, not the part's Content-Type.
+    let content_type = image_type(&bytes).ok_or_else(|| {
+        ApiError::Validation("Backgrounds must be PNG, JPEG or WebP images".to_string())
+    })?;
+    if state.files.find_backgrounds(tid).await?.len() >= MAX_BACKGROUNDS {
+        return Err(ApiError::Conflict(format!(
+            "A tenant can keep at most {} backgrounds",
+            MAX_BACKGROUNDS
+        )));
+    }
+
+    let context = FileContext {
+        context_type: FileContextType::Background,
+        entity_id: tid,
+        room_id: None,
+    };
+    let resp = super::file::store(
+        &state,
+        tid,
+        auth.user_id,
+        context,
+        "background",
+        (filename, content_type.to_string(), bytes),
+    )
+    .await?;
+    Ok(Json(resp))
+}
+
+/// Remove an image from the background library (MANAGE_TENANT).
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/asset/background/{file_id}",
+    tag = "asset",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete_background(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, file_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let fid = ObjectId::parse_str(&file_id)
+        .map_err(|_| ApiError::BadRequest("Invalid file_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    if state.files.find_background(tid, fid).await?.is_none() {
+        return Err(ApiError::NotFound("Background not found".to_string()));
+    }
+    let freed = state.files.soft_delete(tid, fid).await?;
+    storage_quota::record_delete(&state, tid, freed).await;
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// Multipart body of the emoji upload, for the OpenAPI document only.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct EmojiUploadForm {
+    /// 2-32 lowercase letters, digits or underscores; used as `:name:`.
+    name: String,
+    /// PNG, JPEG, WebP or GIF image.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CustomEmojiResponse {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub is_animated: bool,
+    pub created_at: String,
+}
+
+fn to_emoji_response(e: CustomEmoji) -> CustomEmojiResponse {
+    CustomEmojiResponse {
+        id: e.id.unwrap().to_hex(),
+        name: e.name,
+        url: e.image_url,
+        is_animated: e.is_animated,
+        created_at: e.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// The tenant's custom emoji pack, by name, for the composer and reactions.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/asset/emoji",
+    tag = "asset",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<CustomEmojiResponse>))
+)]
+pub async fn list_emojis(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<CustomEmojiResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let emojis = state.custom_emojis.list_by_tenant(tid).await?;
+    Ok(Json(emojis.into_iter().map(to_emoji_response).collect()))
+}
+
+/// Add a custom emoji to the tenant's pack (MANAGE_TENANT). Members use it
+/// as `:name:` in messages and reactions.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/asset/emoji",
+    tag = "asset",
+    params(("tenant_id" = String, Path)),
+    request_body(content = EmojiUploadForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = CustomEmojiResponse))
+)]
+pub async fn upload_emoji(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<CustomEmojiResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+    crate::middleware::rate_limit::check_upload(&state, tid, auth.user_id).await?;
+
+    let mut name: Option<String> = None;
+    let mut file_data: Option<(String, Vec<u8>)> = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Multipart error: {}", e)))?
+    {
+        match field.name() {
+            Some("name") => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Failed to read name: {}", e)))?;
+                name = Some(text.trim().to_string());
+            }
+            Some("file") => {
+                let filename = field.file_name().unwrap_or("emoji").to_string();
+                let bytes = field
+                    .bytes()
+                    .await
+                    .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {}", e)))?;
+                file_data = Some((filename, bytes.to_vec()));
+            }
+            _ => {}
+        }
+    }
+    let name = name.ok_or_else(|| ApiError::BadRequest("Missing 'name' field".to_string()))?;
+    let (filename, bytes) =
+        file_data.ok_or_else(|| ApiError::BadRequest("Missing 'file' field".to_string()))?;
+
+    if !is_valid_emoji_name(&name) {
+        return Err(ApiError::Validation(
+            "Emoji names are 2-32 lowercase letters, digits or underscores".to_string(),
+        ));
+    }
+    if bytes.len() > MAX_EMOJI_SIZE {
+        return Err(ApiError::Validation(format!(
+            "Emojis are limited to {} KB",
+            MAX_EMOJI_SIZE / 1024
+        )));
+    }
+    let content_type = emoji_type(&bytes).ok_or_else(|| {
+        ApiError::Validation("Emojis must be PNG, JPEG, WebP or GIF images".to_string())
+    })?;
+    if state
+        .custom_emojis
+        .find_by_name(tid, &name)
+        .await?
+        .is_some()
+    {
+        return Err(ApiError::Conflict(format!(
+            "Emoji :{}: already exists",
+            name
+        )));
+    }
+    if state.custom_emojis.count(tid).await? >= MAX_EMOJIS {
+        return Err(ApiError::Conflict(format!(
+            "A tenant can keep at most {} emojis",
+            MAX_EMOJIS
+        )));
+    }
+
+    let context = FileContext {
+        context_type: FileContextType::Emoji,
+        entity_id: tid,
+        room_id: None,
+    };
+    let file = super::file::store(
+        &state,
+        tid,
+        auth.user_id,
+        context,
+        "emoji",
+        (filename, content_type.to_string(), bytes),
+    )
+    .await?;
+
+    let now = DateTime::now();
+    let emoji = state
+        .custom_emojis
+        .create(&CustomEmoji {
+            id: None,
+            tenant_id: tid,
+            name,
+            image_url: file.url,
+            file_id: ObjectId::parse_str(&file.id).ok(),
+            is_animated: content_type == "image/gif",
+            creator_id: auth.user_id,
+            allowed_role_ids: None,
+            created_at: now,
+            updated_at: now,
+        })
+        .await?;
+    Ok(Json(to_emoji_response(emoji)))
+}
+
+/// Remove a custom emoji (MANAGE_TENANT). Existing reactions keep their
+/// `:name:` but no longer resolve to an image.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/asset/emoji/{emoji_id}",
+    tag = "asset",
+    params(("tenant_id" = String, Path), ("emoji_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete_emoji(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, emoji_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let eid = ObjectId::parse_str(&emoji_id)
+        .map_err(|_| ApiError::BadRequest("Invalid emoji_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let emoji = state
+        .custom_emojis
+        .find_in_tenant(tid, eid)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Emoji not found".to_string()))?;
+    state.custom_emojis.delete(tid, eid).await?;
+    if let Some(fid) = emoji.file_id {
+        let freed = state.files.soft_delete(tid, fid).await?;
+        storage_quota::record_delete(&state, tid, freed).await;
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// The asset library is tenant branding: `MANAGE_TENANT`.
+async fn require_manage_tenant(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    let perms = state
+        .tenants
+        .get_member_permissions(tenant_id, user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Content type of a PNG, JPEG or WebP image, from its magic bytes.
+fn image_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(b"\xff\xd8\xff") {
+        Some("image/jpeg")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+/// Like [`image_type`], plus GIF (animated emojis).
+fn emoji_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else {
+        image_type(bytes)
+    }
+}
+
+/// 2-32 of `[a-z0-9_]`, so `:name:` is unambiguous in text.
+pub fn is_valid_emoji_name(name: &str) -> bool {
+    (2..=32).contains(&name.len())
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{emoji_type, image_type, is_valid_emoji_name};
+
+    #[test]
+    fn detects_supported_images_by_magic_bytes() {
+        assert_eq!(image_type(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(image_type(b"\xff\xd8\xff\xe0...."), Some("image/jpeg"));
+        assert_eq!(image_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(image_type(b"<svg xmlns=\"...\">"), None);
+        assert_eq!(image_type(b"RIFF"), None);
+    }
+
+    #[test]
+    fn emojis_also_accept_gif() {
+        assert_eq!(emoji_type(b"GIF89a...."), Some("image/gif"));
+        assert_eq!(emoji_type(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(emoji_type(b"GIF8"), None);
+    }
+
+    #[test]
+    fn emoji_names() {
+        assert!(is_valid_emoji_name("party_parrot"));
+        assert!(is_valid_emoji_name("a1"));
+        assert!(!is_valid_emoji_name("x"));
+        assert!(!is_valid_emoji_name("Party"));
+        assert!(!is_valid_emoji_name("no-dash"));
+        assert!(!is_valid_emoji_name(":colon:"));
+        assert!(!is_valid_emoji_name(&"a".repeat(33)));
+    }
+}
@@ -0,0 +1,148 @@
+//! The meeting assistant, an optional participant that answers questions
+//! about the call. It listens to the call's final transcript lines, which
+//! the external transcriber posts to `call/transcript` (see
+//! [`super::call_transcription::append`]), and answers `/assistant ask
+//! <question>` in the call chat with a message of its own; `/assistant say
+//! <question>` also speaks the answer into the call (see `ws::speech`).
+//!
+//! The language model is pluggable (`roomler_ai_services::assistant`) and
+//! tenants turn the assistant on with the `meeting_assistant` feature flag.
+//! Every answer is kept in the call's notes, `CallSession::assistant_notes`.
+//! The transcript it listens to is kept on the call session too, so it
+//! survives a restart and any pod can answer.
+
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{AssistantNote, AuthorType, CallSession};
+use roomler_ai_services::assistant::{self, Command, Line};
+use roomler_ai_services::feature_flags;
+use tracing::{info, warn};
+
+use crate::{error::ApiError, extractors::feature::Features, state::AppState};
+
+/// Display name of the assistant's chat messages.
+pub const NAME: &str = "Assistant";
+
+/// The running call `command` would be answered in. `403` when the
+/// assistant is off for the room, `409` when no model is configured, no
+/// call is running, or `say` has no text-to-speech.
+pub async fn check(
+    state: &AppState,
+    features: &Features,
+    room_id: ObjectId,
+    command: &Command,
+) -> Result<CallSession, ApiError> {
+    if !features.enabled_for(feature_flags::MEETING_ASSISTANT, &room_id) {
+        return Err(ApiError::Forbidden(
+            "The meeting assistant is not enabled".to_string(),
+        ));
+    }
+    if state.assistant.is_none() {
+        return Err(ApiError::Conflict(
+            "The meeting assistant is not configured".to_string(),
+        ));
+    }
+    if matches!(command, Command::Say(_)) && state.tts.is_none() {
+        return Err(ApiError::Conflict(
+            "Text-to-speech is not configured".to_string(),
+        ));
+    }
+    state
+        .call_sessions
+        .find_active(room_id)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))
+}
+
+/// Answer `command` from `asked_by` in the background: post the answer to
+/// the call chat, note it on `session`, and speak it for `say`.
+pub fn answer(
+    state: &AppState,
+    session: &CallSession,
+    asked_by: ObjectId,
+    asker_name: String,
+    command: Command,
+) {
+    let (Some(model), Some(session_id)) = (state.assistant.clone(), session.id) else {
+        return;
+    };
+    let state = state.clone();
+    let (tenant_id, room_id) = (session.tenant_id, session.room_id);
+    let transcript: Vec<Line> = session
+        .transcript
+        .iter()
+        .map(|seg| Line {
+            speaker_name: seg.speaker_name.clone(),
+            text: seg.text.clone(),
+        })
+        .collect();
+    tokio::spawn(async move {
+        let (question, speak) = match command {
+            Command::Ask(q) => (q, false),
+            Command::Say(q) => (q, true),
+        };
+        let prompt = assistant::prompt(&transcript, &asker_name, &question);
+
+        let answer = match model.complete(prompt).await {
+            Ok(answer) if !answer.trim().is_empty() => answer.trim().to_string(),
+            Ok(_) => {
+                warn!(%room_id, backend = model.name(), "Meeting assistant gave no answer");
+                return post_failure(&state, tenant_id, room_id, session_id).await;
+            }
+            Err(e) => {
+                warn!(%room_id, backend = model.name(), %e, "Meeting assistant failed");
+                return post_failure(&state, tenant_id, room_id, session_id).await;
+            }
+        };
+
+        if let Err(e) = super::room::post_call_message(
+            &state,
+            tenant_id,
+            room_id,
+            session_id,
+            AuthorType::Bot,
+            NAME.to_string(),
+            answer.clone(),
+        )
+        .await
+        {
+            warn!(%room_id, %e, "Failed to post the meeting assistant's answer");
+        }
+        let note = AssistantNote {
+            asked_by,
+            question,
+            answer: answer.clone(),
+            asked_at: DateTime::now(),
+        };
+        if let Err(e) = state.call_sessions.add_assistant_note(room_id, &note).await {
+            warn!(%room_id, %e, "Failed to note the meeting assistant's answer");
+        }
+        if speak {
+            let text = answer
+                .chars()
+                .take(crate::ws::speech::MAX_SPEECH_CHARS)
+                .collect();
+            if let Err(e) = crate::ws::speech::speak(&state, room_id, asked_by, text, None) {
+                warn!(%room_id, error = e, "Meeting assistant couldn't speak its answer");
+            }
+        }
+        info!(%room_id, speak, "Meeting assistant answered");
+    });
+}
+
+async fn post_failure(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    author_id: ObjectId,
+) {
+    let _ = super::room::post_call_message(
+        state,
+        tenant_id,
+        room_id,
+        author_id,
+        AuthorType::Bot,
+        NAME.to_string(),
+        "Sorry, I couldn't answer that right now.".to_string(),
+    )
+    .await;
+}
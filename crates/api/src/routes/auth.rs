@@ -6,79 +6,27 @@ use axum::{
 use nanoid::nanoid;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
-#[derive(Debug, Deserialize)]
-pub struct RegisterRequest {
-    pub email: String,
-    pub username: String,
-    pub display_name: String,
-    pub password: String,
-    pub tenant_name: Option<String>,
-    pub tenant_slug: Option<String>,
-    pub invite_code: Option<String>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct AuthResponse {
-    pub access_token: String,
-    pub refresh_token: String,
-    pub expires_in: u64,
-    pub user: UserResponse,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub invite_tenant: Option<InviteTenantResponse>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct InviteTenantResponse {
-    pub tenant_id: String,
-    pub tenant_name: String,
-    pub tenant_slug: String,
-}
-
-#[derive(Debug, Serialize)]
-pub struct UserResponse {
-    pub id: String,
-    pub email: String,
-    pub username: String,
-    pub display_name: String,
-    pub avatar: Option<String>,
-}
+pub use roomler_ai_types::auth::{
+    AuthResponse, InviteTenantResponse, RegisterRequest, RegisterResponse, UserResponse,
+};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ActivateRequest {
     pub user_id: String,
     pub token: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = AuthMessageResponse)]
 pub struct MessageResponse {
     pub message: String,
 }
 
-/// Response shape for `POST /auth/register`. Always carries a
-/// `message`; when `ROOMLER__AUTH__AUTO_VERIFY=true` (e2e overlay)
-/// also returns access/refresh tokens + the user record so test
-/// helpers can chain register → authenticated API calls without
-/// an explicit login step. Production (auto_verify=false) returns
-/// only `message` — clients still call `/auth/login` after the
-/// email-link activation. Token fields skip-serialize when None
-/// so the prod payload stays a single `{ "message": "..." }`.
-#[derive(Debug, Serialize)]
-pub struct RegisterResponse {
-    pub message: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub access_token: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub refresh_token: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expires_in: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub user: Option<UserResponse>,
-}
-
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct LoginRequest {
     #[serde(default)]
     pub username: Option<String>,
@@ -87,11 +35,19 @@ pub struct LoginRequest {
     pub password: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct RefreshRequest {
     pub refresh_token: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/register",
+    tag = "auth",
+    request_body = RegisterRequest,
+    responses((status = 201, body = RegisterResponse)),
+    security(())
+)]
 pub async fn register(
     State(state): State<AppState>,
     Json(body): Json<RegisterRequest>,
@@ -186,6 +142,12 @@ pub async fn register(
         }
     }
 
+    // A verified address may already qualify for tenants with an `auto`
+    // domain join; otherwise that happens on activation.
+    if state.settings.auth.auto_verify {
+        super::tenant_domain::auto_join(&state, user_id, &user.email).await;
+    }
+
     // E2E auto-verify path: skip the email-link round-trip by
     // returning tokens directly. Test helpers (`registerUserViaApi`)
     // expect `{ access_token, user }` in the body; without this they
@@ -225,6 +187,14 @@ pub async fn register(
     ))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/login",
+    tag = "auth",
+    request_body = LoginRequest,
+    responses((status = 200, body = AuthResponse)),
+    security(())
+)]
 pub async fn login(
     State(state): State<AppState>,
     Json(body): Json<LoginRequest>,
@@ -285,6 +255,13 @@ pub async fn login(
     Ok((headers, Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/logout",
+    tag = "auth",
+    responses((status = 200, description = "Clears the `access_token` cookie")),
+    security(())
+)]
 pub async fn logout() -> Result<HeaderMap, ApiError> {
     let mut headers = HeaderMap::new();
     let cookie = "access_token=; HttpOnly; Path=/; SameSite=Lax; Max-Age=0";
@@ -292,6 +269,12 @@ pub async fn logout() -> Result<HeaderMap, ApiError> {
     Ok(headers)
 }
 
+#[utoipa::path(
+    method(get, put),
+    path = "/api/auth/me",
+    tag = "auth",
+    responses((status = 200, body = UserResponse))
+)]
 pub async fn me(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -307,6 +290,68 @@ pub async fn me(
     }))
 }
 
+#[derive(Debug, Default, Deserialize, ToSchema)]
+pub struct WsTicketRequest {
+    /// Limit the connection's media signaling to these rooms; omit for
+    /// every room.
+    #[serde(default)]
+    pub media_rooms: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WsTicketResponse {
+    /// Pass as `/ws?ticket=`; opens one connection.
+    pub ticket: String,
+    pub expires_in: u64,
+    pub media_rooms: Option<Vec<String>>,
+}
+
+/// Trade the access token for a short-lived one-time WebSocket ticket, so
+/// the token itself never goes into a URL.
+#[utoipa::path(
+    post,
+    path = "/api/auth/ws-ticket",
+    tag = "auth",
+    request_body = WsTicketRequest,
+    responses((status = 200, body = WsTicketResponse))
+)]
+pub async fn ws_ticket(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    body: Option<Json<WsTicketRequest>>,
+) -> Result<Json<WsTicketResponse>, ApiError> {
+    let Json(body) = body.unwrap_or_default();
+    let media_rooms = body
+        .media_rooms
+        .map(|ids| {
+            ids.iter()
+                .map(bson::oid::ObjectId::parse_str)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid room id in media_rooms".to_string()))?;
+
+    let ttl = state.settings.ws.ticket_ttl_secs;
+    let ticket =
+        state
+            .auth
+            .issue_ws_ticket(auth.user_id, &auth.username, media_rooms.as_deref(), ttl)?;
+
+    Ok(Json(WsTicketResponse {
+        ticket,
+        expires_in: ttl,
+        media_rooms: media_rooms.map(|ids| ids.iter().map(|id| id.to_hex()).collect()),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/auth/refresh",
+    tag = "auth",
+    request_body = RefreshRequest,
+    responses((status = 200, body = AuthResponse)),
+    security(())
+)]
 pub async fn refresh(
     State(state): State<AppState>,
     Json(body): Json<RefreshRequest>,
@@ -346,6 +391,14 @@ pub async fn refresh(
     Ok((headers, Json(response)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/auth/activate",
+    tag = "auth",
+    request_body = ActivateRequest,
+    responses((status = 200, body = MessageResponse)),
+    security(())
+)]
 pub async fn activate(
     State(state): State<AppState>,
     Json(body): Json<ActivateRequest>,
@@ -371,16 +424,18 @@ pub async fn activate(
     // Delete used activation code
     let _ = state.activation_codes.delete_for_user(user_id).await;
 
+    let user = state
+        .users
+        .base
+        .find_by_id(user_id)
+        .await
+        .map_err(|e| ApiError::Internal(format!("User not found: {}", e)))?;
+    super::tenant_domain::auto_join(&state, user_id, &user.email).await;
+
     // Send success email — fire-and-forget so SMTP latency doesn't
     // block the activate response. Same reasoning as the send_activation
     // call in register above.
     if let Some(email_svc) = state.email.clone() {
-        let user = state
-            .users
-            .base
-            .find_by_id(user_id)
-            .await
-            .map_err(|e| ApiError::Internal(format!("User not found: {}", e)))?;
         let login_url = format!("{}/auth/login", state.settings.app.frontend_url);
         let to_email = user.email.clone();
         let display_name = user.display_name.clone();
@@ -422,6 +477,8 @@ async fn auto_accept_invite(
         ));
     }
 
+    crate::middleware::plan_limits::check_members(state, invite.tenant_id, user_id).await?;
+
     // Determine roles
     let role_ids = if invite.assign_role_ids.is_empty() {
         let member_role = state
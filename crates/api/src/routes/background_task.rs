@@ -1,17 +1,23 @@
 use axum::{
     Json,
     body::Body,
-    extract::{Path, Query, State},
+    extract::{Path, State},
     response::Response,
 };
 use bson::oid::ObjectId;
 use serde::Serialize;
 use tokio::io::AsyncReadExt;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_services::dao::base::PaginationParams;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    state::AppState,
+};
+use roomler_ai_services::dao::base::PaginatedResult;
+use utoipa::ToSchema;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TaskResponse {
     pub id: String,
     pub task_type: String,
@@ -23,44 +29,56 @@ pub struct TaskResponse {
     pub created_at: String,
 }
 
+const LIST_SPEC: ListSpec = ListSpec {
+    sort: &[("created_at", "created_at"), ("updated_at", "updated_at")],
+    filters: &[
+        FilterField::new("status", "status", FieldKind::String),
+        FilterField::new("task_type", "task_type", FieldKind::String),
+        FilterField::new("created_at", "created_at", FieldKind::DateTime),
+    ],
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/task",
+    tag = "background_task",
+    params(("tenant_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<TaskResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(tenant_id): Path<String>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<TaskResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
 
+    let options = query.options(&LIST_SPEC)?;
     let result = state
         .tasks
-        .list_user_tasks(tid, auth.user_id, &params)
+        .list_user_tasks(tid, auth.user_id, &query.pagination, &options)
         .await?;
 
-    let items: Vec<TaskResponse> = result
-        .items
-        .into_iter()
-        .map(|t| TaskResponse {
-            id: t.id.unwrap().to_hex(),
-            task_type: t.task_type,
-            status: format!("{:?}", t.status),
-            progress: t.progress,
-            logs: t.logs,
-            file_name: t.file_name,
-            error: t.error,
-            created_at: t.created_at.try_to_rfc3339_string().unwrap_or_default(),
-        })
-        .collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
+    Ok(Json(result.map(|t| TaskResponse {
+        id: t.id.unwrap().to_hex(),
+        task_type: t.task_type,
+        status: format!("{:?}", t.status),
+        progress: t.progress,
+        logs: t.logs,
+        file_name: t.file_name,
+        error: t.error,
+        created_at: t.created_at.try_to_rfc3339_string().unwrap_or_default(),
     })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/task/{task_id}",
+    tag = "background_task",
+    params(("tenant_id" = String, Path), ("task_id" = String, Path)),
+    responses((status = 200, body = TaskResponse))
+)]
 pub async fn get(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -87,6 +105,15 @@ pub async fn get(
     }))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/task/{task_id}/download",
+    tag = "background_task",
+    params(("tenant_id" = String, Path), ("task_id" = String, Path)),
+    responses(
+        (status = 200, description = "Task output", content_type = "application/octet-stream"),
+    )
+)]
 pub async fn download(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -119,6 +146,8 @@ pub async fn download(
         "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
     } else if file_name.ends_with(".pdf") {
         "application/pdf"
+    } else if file_name.ends_with(".zip") {
+        "application/zip"
     } else {
         "application/octet-stream"
     };
@@ -0,0 +1,384 @@
+//! Tenant bot accounts. A bot is a `User` with `is_bot` that joins the tenant
+//! with the `member` role; it authenticates only with bot tokens, each scoped
+//! to the rooms it may read and post in (enforced by the [`AuthUser`]
+//! extractor). Issuing a token joins the bot to its rooms so it receives
+//! their WS events.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::{doc, oid::ObjectId};
+use roomler_ai_db::models::{BotToken, User};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::webhook::require_manage_integrations;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+
+/// Default and maximum token lifetime.
+const DEFAULT_TOKEN_DAYS: u64 = 365;
+const MAX_TOKEN_DAYS: u64 = 5 * 365;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBotRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBotTokenRequest {
+    pub name: String,
+    pub room_ids: Vec<String>,
+    pub expires_in_days: Option<u64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BotResponse {
+    pub id: String,
+    pub username: String,
+    pub display_name: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BotTokenResponse {
+    pub id: String,
+    pub name: String,
+    pub room_ids: Vec<String>,
+    pub expires_at: String,
+    pub revoked_at: Option<String>,
+    pub created_at: String,
+    /// The bearer token. Only returned when the token is issued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+fn parse_oid(s: &str, what: &str) -> Result<ObjectId, ApiError> {
+    ObjectId::parse_str(s).map_err(|_| ApiError::BadRequest(format!("Invalid {what}")))
+}
+
+/// The bot `bot_id`, which must be a member of the tenant.
+async fn find_bot(
+    state: &AppState,
+    tenant_id: ObjectId,
+    bot_id: ObjectId,
+) -> Result<User, ApiError> {
+    let bot = state
+        .users
+        .base
+        .find_by_id(bot_id)
+        .await
+        .ok()
+        .filter(|u| u.is_bot && u.deleted_at.is_none());
+    match bot {
+        Some(bot) if state.tenants.is_member(tenant_id, bot_id).await? => Ok(bot),
+        _ => Err(ApiError::NotFound("Bot not found".to_string())),
+    }
+}
+
+/// GET /api/tenant/{tenant_id}/bot
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/bot",
+    tag = "bot",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<BotResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<BotResponse>>, ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let member_ids: Vec<ObjectId> = state
+        .tenants
+        .members
+        .find_many(doc! { "tenant_id": tid }, None)
+        .await?
+        .into_iter()
+        .map(|m| m.user_id)
+        .collect();
+    let bots = state
+        .users
+        .base
+        .find_many(
+            doc! { "_id": { "$in": member_ids }, "is_bot": true, "deleted_at": null },
+            Some(doc! { "created_at": 1 }),
+        )
+        .await?;
+    Ok(Json(bots.into_iter().map(to_response).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/bot — create a bot and add it to the tenant
+/// with the `member` role.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/bot",
+    tag = "bot",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateBotRequest,
+    responses((status = 201, body = BotResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateBotRequest>,
+) -> Result<(StatusCode, Json<BotResponse>), ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let name = body.name.trim();
+    if name.is_empty() {
+        return Err(ApiError::Validation("name is required".to_string()));
+    }
+
+    let bot = state.users.create_bot(name).await?;
+    let bot_id = bot.id.unwrap();
+    let member_role = state.tenants.get_role_by_name(tid, "member").await?;
+    state
+        .tenants
+        .add_member(
+            tid,
+            bot_id,
+            vec![member_role.id.unwrap()],
+            Some(auth.user_id),
+        )
+        .await?;
+
+    let response = to_response(bot);
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "bot.create", "bot", Some(bot_id)).after(&response),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/bot/{bot_id} — revoke the bot's tokens,
+/// remove it from the tenant and its rooms, and delete the account.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/bot/{bot_id}",
+    tag = "bot",
+    params(("tenant_id" = String, Path), ("bot_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, bot_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    let bid = parse_oid(&bot_id, "bot_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+    let bot = find_bot(&state, tid, bid).await?;
+
+    state.bot_tokens.revoke_all(tid, bid).await?;
+    for room in state.rooms.find_user_rooms(tid, bid).await? {
+        if let Some(rid) = room.id {
+            state.rooms.leave(tid, rid, bid).await?;
+        }
+    }
+    state.tenants.remove_member(tid, bid).await?;
+    state.users.base.soft_delete(bid).await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "bot.delete", "bot", Some(bid))
+            .before(&to_response(bot)),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// GET /api/tenant/{tenant_id}/bot/{bot_id}/token
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/bot/{bot_id}/token",
+    tag = "bot",
+    params(("tenant_id" = String, Path), ("bot_id" = String, Path)),
+    responses((status = 200, body = Vec<BotTokenResponse>))
+)]
+pub async fn list_tokens(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, bot_id)): Path<(String, String)>,
+) -> Result<Json<Vec<BotTokenResponse>>, ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    let bid = parse_oid(&bot_id, "bot_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+    find_bot(&state, tid, bid).await?;
+
+    let tokens = state.bot_tokens.list_for_bot(tid, bid).await?;
+    Ok(Json(
+        tokens
+            .into_iter()
+            .map(|t| token_to_response(t, None))
+            .collect(),
+    ))
+}
+
+/// POST /api/tenant/{tenant_id}/bot/{bot_id}/token — issue a token scoped to
+/// `room_ids`. The bearer token is only in this response.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/bot/{bot_id}/token",
+    tag = "bot",
+    params(("tenant_id" = String, Path), ("bot_id" = String, Path)),
+    request_body = CreateBotTokenRequest,
+    responses((status = 201, body = BotTokenResponse))
+)]
+pub async fn create_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, bot_id)): Path<(String, String)>,
+    Json(body): Json<CreateBotTokenRequest>,
+) -> Result<(StatusCode, Json<BotTokenResponse>), ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    let bid = parse_oid(&bot_id, "bot_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+    find_bot(&state, tid, bid).await?;
+
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::Validation("name is required".to_string()));
+    }
+    if body.room_ids.is_empty() {
+        return Err(ApiError::Validation(
+            "a bot token needs at least one room".to_string(),
+        ));
+    }
+    let days = body.expires_in_days.unwrap_or(DEFAULT_TOKEN_DAYS);
+    if days == 0 || days > MAX_TOKEN_DAYS {
+        return Err(ApiError::Validation(format!(
+            "expires_in_days must be between 1 and {MAX_TOKEN_DAYS}"
+        )));
+    }
+
+    let mut room_ids = Vec::with_capacity(body.room_ids.len());
+    for raw in &body.room_ids {
+        let rid = parse_oid(raw, "room_id")?;
+        state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+        room_ids.push(rid);
+    }
+    // Join the scoped rooms so the bot passes membership checks and gets
+    // their WS events.
+    for &rid in &room_ids {
+        let joined = state
+            .rooms
+            .members
+            .find_one(doc! { "room_id": rid, "user_id": bid })
+            .await?
+            .is_some();
+        if !joined {
+            state.rooms.join(tid, rid, bid).await?;
+        }
+    }
+
+    let ttl_secs = days * 24 * 60 * 60;
+    let token = state
+        .bot_tokens
+        .create(tid, bid, auth.user_id, name, room_ids, ttl_secs)
+        .await?;
+    let token_id = token.id.unwrap();
+    let jwt = state.auth.issue_bot_token(bid, tid, token_id, ttl_secs)?;
+
+    let response = token_to_response(token, Some(jwt));
+    let mut snapshot = serde_json::to_value(&response).unwrap_or_default();
+    if let Some(obj) = snapshot.as_object_mut() {
+        obj.remove("token");
+    }
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(
+            tid,
+            auth.user_id,
+            "bot_token.create",
+            "bot_token",
+            Some(token_id),
+        )
+        .after(&snapshot),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/bot/{bot_id}/token/{token_id}
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/bot/{bot_id}/token/{token_id}",
+    tag = "bot",
+    params(("tenant_id" = String, Path), ("bot_id" = String, Path), ("token_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn revoke_token(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, bot_id, token_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = parse_oid(&tenant_id, "tenant_id")?;
+    let bid = parse_oid(&bot_id, "bot_id")?;
+    let token_oid = parse_oid(&token_id, "token_id")?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    if !state.bot_tokens.revoke(tid, bid, token_oid).await? {
+        return Err(ApiError::NotFound("Bot token not found".to_string()));
+    }
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(
+            tid,
+            auth.user_id,
+            "bot_token.revoke",
+            "bot_token",
+            Some(token_oid),
+        ),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "revoked": true })))
+}
+
+fn to_response(u: User) -> BotResponse {
+    BotResponse {
+        id: u.id.map(|id| id.to_hex()).unwrap_or_default(),
+        username: u.username,
+        display_name: u.display_name,
+        created_at: u.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+fn token_to_response(t: BotToken, token: Option<String>) -> BotTokenResponse {
+    BotTokenResponse {
+        id: t.id.map(|id| id.to_hex()).unwrap_or_default(),
+        name: t.name,
+        room_ids: t.room_ids.iter().map(|r| r.to_hex()).collect(),
+        expires_at: t.expires_at.try_to_rfc3339_string().unwrap_or_default(),
+        revoked_at: t
+            .revoked_at
+            .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
+        created_at: t.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        token,
+    }
+}
@@ -0,0 +1,374 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApiError,
+    extractors::{auth::AuthUser, feature::Features},
+    state::AppState,
+};
+use roomler_ai_db::models::{BreakoutRoom, role::permissions};
+use roomler_ai_services::feature_flags;
+
+/// Upper bound on breakout rooms per call; each one is a mediasoup Router.
+const MAX_BREAKOUTS: usize = 20;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateBreakoutsRequest {
+    /// Open this many rooms and spread the call's participants across them
+    /// (everyone but the caller, in join order). Ignored when `rooms` is set.
+    pub count: Option<usize>,
+    /// Open these rooms with the given members (manual assignment).
+    pub rooms: Option<Vec<BreakoutSpec>>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct BreakoutSpec {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub user_ids: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct AssignBreakoutRequest {
+    pub user_id: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct BreakoutResponse {
+    pub id: String,
+    pub name: String,
+    pub user_ids: Vec<String>,
+    pub opened_at: String,
+}
+
+/// Split an active call into breakout rooms, each on its own media Router.
+/// Assigned participants receive `call:breakout_assigned` and move their
+/// media with `media:join { room_id: <breakout id> }`.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/breakout",
+    tag = "breakout",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreateBreakoutsRequest,
+    responses((status = 201, body = Vec<BreakoutResponse>))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    features: Features,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<CreateBreakoutsRequest>,
+) -> Result<(StatusCode, Json<Vec<BreakoutResponse>>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+    features.require(feature_flags::BREAKOUT_ROOMS)?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.conference_status.as_deref() != Some("in_progress") {
+        return Err(ApiError::Conflict("No call in progress".to_string()));
+    }
+
+    let specs = match (body.rooms, body.count) {
+        (Some(rooms), _) => rooms,
+        (None, Some(count)) => auto_assign(&state, rid, auth.user_id, count).await?,
+        (None, None) => {
+            return Err(ApiError::Validation(
+                "Either count or rooms is required".to_string(),
+            ));
+        }
+    };
+    if specs.is_empty() || specs.len() > MAX_BREAKOUTS {
+        return Err(ApiError::Validation(format!(
+            "Between 1 and {} breakout rooms are allowed",
+            MAX_BREAKOUTS
+        )));
+    }
+
+    let now = DateTime::now();
+    let mut seen = HashSet::new();
+    let mut breakouts = Vec::with_capacity(specs.len());
+    for (i, spec) in specs.into_iter().enumerate() {
+        let mut user_ids = Vec::with_capacity(spec.user_ids.len());
+        for uid in &spec.user_ids {
+            let uid = ObjectId::parse_str(uid)
+                .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+            if !seen.insert(uid) {
+                return Err(ApiError::Validation(format!(
+                    "User {} is assigned to more than one breakout room",
+                    uid.to_hex()
+                )));
+            }
+            user_ids.push(uid);
+        }
+        let name = spec
+            .name
+            .map(|n| n.trim().to_string())
+            .filter(|n| !n.is_empty())
+            .unwrap_or_else(|| format!("Breakout {}", i + 1));
+        breakouts.push(BreakoutRoom {
+            id: ObjectId::new(),
+            name,
+            user_ids,
+            opened_at: now,
+            closed_at: None,
+        });
+    }
+
+    if !state.call_sessions.open_breakouts(rid, &breakouts).await? {
+        return Err(ApiError::Conflict(
+            "Breakout rooms are already open".to_string(),
+        ));
+    }
+
+    let ids: Vec<ObjectId> = breakouts.iter().map(|b| b.id).collect();
+    // Same decision as when the call started.
+    let e2ee = room.media_settings.as_ref().is_some_and(|m| m.e2ee_enabled)
+        && features.enabled_for(feature_flags::E2EE, &rid);
+    if let Err(e) = state.room_manager.create_breakouts(rid, &ids, e2ee).await {
+        state.room_manager.remove_breakouts(&rid);
+        state.call_sessions.close_breakouts(rid).await?;
+        return Err(ApiError::Internal(format!(
+            "Failed to create media room: {}",
+            e
+        )));
+    }
+    // Claim the new Routers so `media:join` on other pods redirects here.
+    if let Some(registry) = &state.conference_registry {
+        for id in &ids {
+            registry
+                .claim(id)
+                .await
+                .map_err(|e| ApiError::Internal(format!("Conference registry: {}", e)))?;
+        }
+    }
+
+    for breakout in &breakouts {
+        notify_assigned(&state, tid, rid, breakout, &breakout.user_ids).await;
+    }
+
+    Ok((
+        StatusCode::CREATED,
+        Json(breakouts.into_iter().map(to_response).collect()),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/breakout",
+    tag = "breakout",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<BreakoutResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<BreakoutResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let breakouts = state.call_sessions.find_open_breakouts(rid).await?;
+    Ok(Json(breakouts.into_iter().map(to_response).collect()))
+}
+
+/// Move a participant into a breakout room (out of any other one).
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/breakout/{breakout_id}/participant",
+    tag = "breakout",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("breakout_id" = String, Path),
+    ),
+    request_body = AssignBreakoutRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn assign(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, breakout_id)): Path<(String, String, String)>,
+    Json(body): Json<AssignBreakoutRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let bid = ObjectId::parse_str(&breakout_id)
+        .map_err(|_| ApiError::BadRequest("Invalid breakout_id".to_string()))?;
+    let uid = ObjectId::parse_str(&body.user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    if !state.call_sessions.assign_breakout(rid, bid, uid).await? {
+        return Err(ApiError::NotFound("Breakout room not found".to_string()));
+    }
+
+    if let Some(breakout) = state
+        .call_sessions
+        .find_open_breakouts(rid)
+        .await?
+        .into_iter()
+        .find(|b| b.id == bid)
+    {
+        notify_assigned(&state, tid, rid, &breakout, &[uid]).await;
+    }
+
+    Ok(Json(serde_json::json!({ "assigned": true })))
+}
+
+/// Close every breakout room and send all participants back to the main
+/// call (`call:breakout_ended`).
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/breakout",
+    tag = "breakout",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn close(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let closed = close_all(&state, tid, rid).await?;
+    Ok(Json(serde_json::json!({ "closed": closed })))
+}
+
+/// Close the call's open breakout rooms, tear down their Routers and tell
+/// the room to return to the main call. Returns how many were closed.
+pub(crate) async fn close_all(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+) -> Result<usize, ApiError> {
+    let closed = state.call_sessions.close_breakouts(room_id).await?;
+    state.room_manager.remove_breakouts(&room_id);
+    for breakout in &closed {
+        super::room::release_conference(state, &breakout.id).await;
+    }
+    if closed.is_empty() {
+        return Ok(0);
+    }
+
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .unwrap_or_default();
+    if !member_ids.is_empty() {
+        let event = serde_json::json!({
+            "type": "call:breakout_ended",
+            "tenant_id": tenant_id.to_hex(),
+            "data": { "room_id": room_id.to_hex() }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &member_ids,
+            &event,
+        )
+        .await;
+    }
+    Ok(closed.len())
+}
+
+/// Build `count` rooms and deal the call's participants (minus `organizer`)
+/// into them round-robin, in join order.
+async fn auto_assign(
+    state: &AppState,
+    room_id: ObjectId,
+    organizer: ObjectId,
+    count: usize,
+) -> Result<Vec<BreakoutSpec>, ApiError> {
+    // Out-of-range counts are rejected by the caller; don't build them.
+    if count == 0 || count > MAX_BREAKOUTS {
+        return Ok(Vec::new());
+    }
+    let mut specs: Vec<BreakoutSpec> = (0..count)
+        .map(|_| BreakoutSpec {
+            name: None,
+            user_ids: Vec::new(),
+        })
+        .collect();
+    let participants = state.rooms.list_participants(room_id).await?;
+    let user_ids = participants
+        .iter()
+        .filter_map(|p| p.user_id)
+        .filter(|u| *u != organizer);
+    for (i, uid) in user_ids.enumerate() {
+        specs[i % count].user_ids.push(uid.to_hex());
+    }
+    Ok(specs)
+}
+
+async fn notify_assigned(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    breakout: &BreakoutRoom,
+    user_ids: &[ObjectId],
+) {
+    if user_ids.is_empty() {
+        return;
+    }
+    let event = serde_json::json!({
+        "type": "call:breakout_assigned",
+        "tenant_id": tenant_id.to_hex(),
+        "data": {
+            "room_id": room_id.to_hex(),
+            "breakout_id": breakout.id.to_hex(),
+            "name": breakout.name,
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        user_ids,
+        &event,
+    )
+    .await;
+}
+
+fn to_response(b: BreakoutRoom) -> BreakoutResponse {
+    BreakoutResponse {
+        id: b.id.to_hex(),
+        name: b.name,
+        user_ids: b.user_ids.iter().map(|u| u.to_hex()).collect(),
+        opened_at: b.opened_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
@@ -0,0 +1,200 @@
+//! Connecting a user's Google or Microsoft calendar, so the calls they
+//! schedule (see [`super::call_schedule`]) are written there as events.
+//! The consent flow carries the user in a short-lived signed `state`, as
+//! the provider's callback arrives without the user's token. The state
+//! also holds a nonce that `connect` sets as a cookie, so only the browser
+//! that started the flow can finish it: someone else's connect URL can't
+//! attach your calendar to their account.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Redirect, Response},
+};
+use bson::oid::ObjectId;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::oauth::CallbackQuery;
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+/// How long the consent screen may take.
+const STATE_TTL_SECS: u64 = 600;
+
+/// The cookie holding the state's nonce while `provider`'s consent runs.
+fn nonce_cookie(provider: &str) -> String {
+    format!("calendar_nonce_{}", provider)
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarConnectionResponse {
+    pub provider: String,
+    pub account_email: String,
+    pub connected_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarConnectResponse {
+    /// The provider's consent screen; open it in the browser.
+    pub url: String,
+}
+
+/// The calendars you connected.
+#[utoipa::path(
+    get,
+    path = "/api/calendar/connection",
+    tag = "calendar",
+    responses((status = 200, body = Vec<CalendarConnectionResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<CalendarConnectionResponse>>, ApiError> {
+    let connections = state
+        .calendar_connections
+        .list_for_user(auth.user_id)
+        .await?;
+    Ok(Json(
+        connections
+            .into_iter()
+            .map(|c| CalendarConnectionResponse {
+                provider: c.provider,
+                account_email: c.account_email,
+                connected_at: c.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+            })
+            .collect(),
+    ))
+}
+
+/// Start connecting your calendar at `provider` (`google` or `microsoft`).
+/// Sets the nonce cookie the callback checks.
+#[utoipa::path(
+    post,
+    path = "/api/calendar/{provider}/connect",
+    tag = "calendar",
+    params(("provider" = String, Path)),
+    responses((status = 200, body = CalendarConnectResponse))
+)]
+pub async fn connect(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(provider): Path<String>,
+) -> Result<Response, ApiError> {
+    let calendar = state
+        .calendar
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Calendar sync not configured".to_string()))?;
+    let nonce = uuid::Uuid::new_v4().simple().to_string();
+    let csrf_state =
+        state
+            .auth
+            .issue_calendar_state(auth.user_id, &provider, &nonce, STATE_TTL_SECS)?;
+    let url = calendar.auth_url(&provider, &csrf_state)?;
+    let cookie = format!(
+        "{}={}; HttpOnly; Path=/api/calendar/callback; SameSite=Lax; Max-Age={}",
+        nonce_cookie(&provider),
+        nonce,
+        STATE_TTL_SECS
+    );
+    Ok((
+        [(header::SET_COOKIE, cookie)],
+        Json(CalendarConnectResponse { url }),
+    )
+        .into_response())
+}
+
+/// The provider's redirect after consent; stores the connection and sends
+/// the browser back to the profile page.
+#[utoipa::path(
+    get,
+    path = "/api/calendar/callback/{provider}",
+    tag = "calendar",
+    params(("provider" = String, Path), CallbackQuery),
+    responses((status = 303, description = "Redirects to the profile page")),
+    security(())
+)]
+pub async fn callback(
+    State(state): State<AppState>,
+    Path(provider): Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<CallbackQuery>,
+) -> Result<Response, ApiError> {
+    let calendar = state
+        .calendar
+        .as_ref()
+        .ok_or_else(|| ApiError::BadRequest("Calendar sync not configured".to_string()))?;
+    let claims = state.auth.verify_calendar_state(&params.state)?;
+    let cookie_name = nonce_cookie(&provider);
+    let nonce = headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .find_map(|cookie| {
+            let (name, value) = cookie.trim().split_once('=')?;
+            (name == cookie_name).then_some(value)
+        });
+    if claims.provider != provider || nonce != Some(claims.nonce.as_str()) {
+        return Err(ApiError::BadRequest("Invalid OAuth state".to_string()));
+    }
+    let user_id = ObjectId::parse_str(&claims.sub)
+        .map_err(|_| ApiError::BadRequest("Invalid OAuth state".to_string()))?;
+
+    let tokens = calendar.exchange_code(&provider, &params.code).await?;
+    // Without one the connection would stop working within the hour.
+    let refresh_token = tokens.refresh_token.ok_or_else(|| {
+        ApiError::BadRequest("The provider granted no offline access".to_string())
+    })?;
+    let account_email = calendar
+        .account_email(&provider, &tokens.access_token)
+        .await?;
+    state
+        .calendar_connections
+        .connect(
+            user_id,
+            &provider,
+            account_email,
+            tokens.access_token,
+            refresh_token,
+            tokens.expires_at,
+        )
+        .await?;
+    tracing::info!(%user_id, %provider, "Calendar connected");
+
+    let clear = format!(
+        "{}=; HttpOnly; Path=/api/calendar/callback; SameSite=Lax; Max-Age=0",
+        cookie_name
+    );
+    Ok((
+        [(header::SET_COOKIE, clear)],
+        Redirect::to(&format!(
+            "{}/profile/edit?calendar={}",
+            state.settings.app.frontend_url, provider
+        )),
+    )
+        .into_response())
+}
+
+/// Disconnect your calendar at `provider`. Events already written stay.
+#[utoipa::path(
+    delete,
+    path = "/api/calendar/connection/{provider}",
+    tag = "calendar",
+    params(("provider" = String, Path)),
+    responses((status = 204))
+)]
+pub async fn disconnect(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(provider): Path<String>,
+) -> Result<StatusCode, ApiError> {
+    if !state
+        .calendar_connections
+        .disconnect(auth.user_id, &provider)
+        .await?
+    {
+        return Err(ApiError::NotFound("No calendar connected".to_string()));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,103 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use serde::Deserialize;
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::role::permissions;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct MuteParticipantRequest {
+    pub muted: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PushToTalkRequest {
+    pub enabled: bool,
+}
+
+/// Mute or unmute a participant server-side: their audio producers are
+/// paused on every connection, including ones they join with later, until
+/// an organizer unmutes them.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/participant/{user_id}/mute",
+    tag = "room",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("user_id" = String, Path)
+    ),
+    request_body = MuteParticipantRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn mute(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+    Json(body): Json<MuteParticipantRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let uid = ObjectId::parse_str(&user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let states = state
+        .room_manager
+        .set_user_muted(&rid, &uid, body.muted)
+        .await
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+    crate::ws::audio::announce_user(&state, &rid, &uid, &states).await;
+
+    Ok(Json(serde_json::json!({
+        "muted": body.muted,
+        "connections": states.len(),
+    })))
+}
+
+/// Turn push-to-talk on or off for the running call.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/push-to-talk",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = PushToTalkRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn push_to_talk(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<PushToTalkRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    if !state
+        .room_manager
+        .set_push_to_talk(&rid, body.enabled)
+        .await
+    {
+        return Err(ApiError::Conflict("No call in progress".to_string()));
+    }
+    crate::ws::audio::announce_push_to_talk(&state, &rid, body.enabled).await;
+
+    Ok(Json(serde_json::json!({ "push_to_talk": body.enabled })))
+}
@@ -0,0 +1,83 @@
+//! A room's media signaling timeline (see `ws::call_debug`), for tenant
+//! admins debugging a participant's call.
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{CallDebugEvent, CallDebugKind};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use super::retention::require_manage_tenant;
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+const DEFAULT_LIMIT: i64 = 500;
+const MAX_LIMIT: i64 = 2000;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct CallDebugQuery {
+    /// Only this participant's events.
+    pub user_id: Option<String>,
+    /// How many of the latest events to return; 500 by default, at most
+    /// 2000.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CallDebugEventResponse {
+    pub user_id: String,
+    pub connection_id: String,
+    /// `join`, `transport_connect`, `ice_restart`, `ice_state`, `dtls_state`,
+    /// `produce`, `consume`, `rejoin`, `leave` or `error`.
+    #[schema(value_type = String)]
+    pub kind: CallDebugKind,
+    pub detail: String,
+    pub at: String,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/call/debug
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/debug",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), CallDebugQuery),
+    responses((status = 200, body = Vec<CallDebugEventResponse>))
+)]
+pub async fn timeline(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Query(query): Query<CallDebugQuery>,
+) -> Result<Json<Vec<CallDebugEventResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let user_id = query
+        .user_id
+        .as_deref()
+        .map(ObjectId::parse_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let events = state
+        .call_debug_events
+        .timeline(tid, rid, user_id, limit)
+        .await?;
+    Ok(Json(events.into_iter().map(to_response).collect()))
+}
+
+fn to_response(e: CallDebugEvent) -> CallDebugEventResponse {
+    CallDebugEventResponse {
+        user_id: e.user_id.to_hex(),
+        connection_id: e.connection_id,
+        kind: e.kind,
+        detail: e.detail,
+        at: e.at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
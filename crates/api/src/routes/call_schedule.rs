@@ -0,0 +1,564 @@
+//! Scheduled calls. A moderator sets the room's call time and invitees;
+//! with `calendar` it also goes to their connected calendar (see
+//! [`super::calendar`]) as an event with the join link and dial-in, inviting
+//! the invitees. The invitees' responses come back from that event: on
+//! `call/schedule/sync` and every `calendar.sync_interval_secs` until the
+//! call's end. A failed calendar write never fails the schedule; it is
+//! kept on the event link and retried by the next sync.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{
+    CalendarEventLink, ConferenceSettings, Invitee, InviteeResponse, Room, role::permissions,
+};
+use roomler_ai_services::calendar::{CalendarError, CalendarService, EventDetails};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+const MAX_INVITEES: usize = 500;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScheduleRequest {
+    /// RFC 3339.
+    pub scheduled_start: String,
+    /// RFC 3339; after `scheduled_start`.
+    pub scheduled_end: String,
+    /// IANA zone, e.g. `Europe/Vienna`; how calendars show the times.
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub invitees: Vec<String>,
+    /// Put the call in your calendar at this provider (`google` or
+    /// `microsoft`), inviting the invitees. Without it an earlier event is
+    /// cancelled.
+    pub calendar: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduledInviteeResponse {
+    pub email: String,
+    pub user_id: Option<String>,
+    /// `needs_action`, `accepted`, `tentative` or `declined`.
+    #[schema(value_type = String)]
+    pub response: InviteeResponse,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CalendarEventResponse {
+    pub provider: String,
+    /// Whose calendar holds the event.
+    pub organizer_id: String,
+    pub event_id: Option<String>,
+    pub synced_at: Option<String>,
+    /// Why the last sync failed; it is retried.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ScheduleResponse {
+    pub scheduled_start: Option<String>,
+    pub scheduled_end: Option<String>,
+    pub timezone: Option<String>,
+    pub join_url: Option<String>,
+    pub invitees: Vec<ScheduledInviteeResponse>,
+    pub calendar_event: Option<CalendarEventResponse>,
+}
+
+fn schedule_response(state: &AppState, room: &Room) -> ScheduleResponse {
+    let settings = room.conference_settings.as_ref();
+    let time = |t: Option<DateTime>| t.map(|t| t.try_to_rfc3339_string().unwrap_or_default());
+    ScheduleResponse {
+        scheduled_start: time(settings.and_then(|s| s.scheduled_start)),
+        scheduled_end: time(settings.and_then(|s| s.scheduled_end)),
+        timezone: settings.and_then(|s| s.timezone.clone()),
+        join_url: join_url(state, room),
+        invitees: settings
+            .map(|s| s.invitees.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|i| ScheduledInviteeResponse {
+                email: i.email.clone(),
+                user_id: i.user_id.map(|u| u.to_hex()),
+                response: i.response,
+            })
+            .collect(),
+        calendar_event: settings.and_then(|s| s.calendar_event.as_ref()).map(|e| {
+            CalendarEventResponse {
+                provider: e.provider.clone(),
+                organizer_id: e.user_id.to_hex(),
+                event_id: e.event_id.clone(),
+                synced_at: time(e.synced_at),
+                error: e.error.clone(),
+            }
+        }),
+    }
+}
+
+/// The room's join link as invitees open it.
+fn join_url(state: &AppState, room: &Room) -> Option<String> {
+    room.join_url
+        .as_ref()
+        .map(|path| format!("{}{}", state.settings.app.frontend_url, path))
+}
+
+async fn load(
+    state: &AppState,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: ObjectId,
+    permission: Option<u64>,
+) -> Result<Room, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    match permission {
+        Some(permission) => {
+            state
+                .permissions
+                .require_room(tid, rid, user_id, permission)
+                .await?;
+        }
+        None => {
+            if !state.tenants.is_member(tid, user_id).await? {
+                return Err(ApiError::Forbidden("Not a member".to_string()));
+            }
+        }
+    }
+    Ok(state.rooms.base.find_by_id_in_tenant(tid, rid).await?)
+}
+
+/// The room's scheduled call.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/schedule",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = ScheduleResponse))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<ScheduleResponse>, ApiError> {
+    let room = load(&state, &tenant_id, &room_id, auth.user_id, None).await?;
+    Ok(Json(schedule_response(&state, &room)))
+}
+
+/// Schedule (or reschedule) the room's call. Needs MANAGE_MEETINGS.
+/// Invitees keep their responses across reschedules.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/schedule",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = ScheduleRequest,
+    responses((status = 200, body = ScheduleResponse))
+)]
+pub async fn set(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<ScheduleRequest>,
+) -> Result<Json<ScheduleResponse>, ApiError> {
+    let room = load(
+        &state,
+        &tenant_id,
+        &room_id,
+        auth.user_id,
+        Some(permissions::MANAGE_MEETINGS),
+    )
+    .await?;
+    let room_id = room.id.unwrap_or_default();
+
+    let start = parse_time("scheduled_start", &body.scheduled_start)?;
+    let end = parse_time("scheduled_end", &body.scheduled_end)?;
+    if end <= start {
+        return Err(ApiError::Validation(
+            "scheduled_end must be after scheduled_start".to_string(),
+        ));
+    }
+    let emails = invitee_emails(&body.invitees)?;
+
+    if let Some(provider) = &body.calendar {
+        let calendar = state
+            .calendar
+            .as_ref()
+            .ok_or_else(|| ApiError::Conflict("Calendar sync not configured".to_string()))?;
+        if !calendar.providers().contains(&provider.as_str()) {
+            return Err(ApiError::BadRequest(format!(
+                "Unknown calendar provider: {provider}"
+            )));
+        }
+        if state
+            .calendar_connections
+            .find(auth.user_id, provider)
+            .await?
+            .is_none()
+        {
+            return Err(ApiError::Conflict(format!(
+                "Connect your {provider} calendar first"
+            )));
+        }
+    }
+
+    let previous = room.conference_settings.clone().unwrap_or_default();
+    let mut invitees = Vec::with_capacity(emails.len());
+    for email in emails {
+        let kept = previous.invitees.iter().find(|i| i.email == email);
+        let user_id = match kept.and_then(|i| i.user_id) {
+            Some(user_id) => Some(user_id),
+            None => state
+                .users
+                .find_by_email(&email)
+                .await
+                .ok()
+                .and_then(|u| u.id),
+        };
+        invitees.push(Invitee {
+            email,
+            user_id,
+            response: kept.map(|i| i.response).unwrap_or_default(),
+        });
+    }
+
+    // The event stays where it is unless the calendar changes.
+    let calendar_event = match (&previous.calendar_event, &body.calendar) {
+        (Some(old), Some(provider)) if old.provider == *provider && old.user_id == auth.user_id => {
+            Some(old.clone())
+        }
+        (_, provider) => {
+            if let Some(old) = &previous.calendar_event {
+                cancel_event(&state, old).await;
+            }
+            provider.as_ref().map(|provider| CalendarEventLink {
+                provider: provider.clone(),
+                user_id: auth.user_id,
+                event_id: None,
+                synced_at: None,
+                error: None,
+            })
+        }
+    };
+    let settings = ConferenceSettings {
+        scheduled_start: Some(start),
+        scheduled_end: Some(end),
+        timezone: body.timezone,
+        invitees,
+        calendar_event,
+        ..previous
+    };
+    state
+        .rooms
+        .set_conference_settings(room.tenant_id, room_id, Some(&settings))
+        .await?;
+
+    let mut room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(room.tenant_id, room_id)
+        .await?;
+    if settings.calendar_event.is_some() {
+        sync_room(&state, &room, true).await;
+        room = state
+            .rooms
+            .base
+            .find_by_id_in_tenant(room.tenant_id, room_id)
+            .await?;
+    }
+    Ok(Json(schedule_response(&state, &room)))
+}
+
+/// Unschedule the room's call, cancelling its calendar event. Needs
+/// MANAGE_MEETINGS.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/schedule",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 204))
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<StatusCode, ApiError> {
+    let room = load(
+        &state,
+        &tenant_id,
+        &room_id,
+        auth.user_id,
+        Some(permissions::MANAGE_MEETINGS),
+    )
+    .await?;
+    let Some(previous) = room.conference_settings.clone() else {
+        return Err(ApiError::NotFound("No call scheduled".to_string()));
+    };
+    if let Some(old) = &previous.calendar_event {
+        cancel_event(&state, old).await;
+    }
+    let settings = ConferenceSettings {
+        scheduled_start: None,
+        scheduled_end: None,
+        invitees: Vec::new(),
+        calendar_event: None,
+        ..previous
+    };
+    state
+        .rooms
+        .set_conference_settings(room.tenant_id, room.id.unwrap_or_default(), Some(&settings))
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Read the invitees' responses from the calendar now, rather than at the
+/// next periodic sync. Needs MANAGE_MEETINGS.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/schedule/sync",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = ScheduleResponse))
+)]
+pub async fn sync(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<ScheduleResponse>, ApiError> {
+    let room = load(
+        &state,
+        &tenant_id,
+        &room_id,
+        auth.user_id,
+        Some(permissions::MANAGE_MEETINGS),
+    )
+    .await?;
+    if room
+        .conference_settings
+        .as_ref()
+        .and_then(|s| s.calendar_event.as_ref())
+        .is_none()
+    {
+        return Err(ApiError::Conflict(
+            "The call is not in a calendar".to_string(),
+        ));
+    }
+    sync_room(&state, &room, false).await;
+    let room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(room.tenant_id, room.id.unwrap_or_default())
+        .await?;
+    Ok(Json(schedule_response(&state, &room)))
+}
+
+fn parse_time(field: &str, raw: &str) -> Result<DateTime, ApiError> {
+    DateTime::parse_rfc3339_str(raw)
+        .map_err(|_| ApiError::Validation(format!("{field} must be an RFC 3339 timestamp")))
+}
+
+/// Lowercased and deduplicated, in the order given.
+fn invitee_emails(raw: &[String]) -> Result<Vec<String>, ApiError> {
+    if raw.len() > MAX_INVITEES {
+        return Err(ApiError::Validation(format!(
+            "At most {MAX_INVITEES} invitees"
+        )));
+    }
+    let mut seen = HashSet::new();
+    let mut emails = Vec::with_capacity(raw.len());
+    for email in raw {
+        let email = email.trim().to_lowercase();
+        if !email.contains('@') {
+            return Err(ApiError::Validation(format!(
+                "Invalid invitee email: {email}"
+            )));
+        }
+        if seen.insert(email.clone()) {
+            emails.push(email);
+        }
+    }
+    Ok(emails)
+}
+
+/// Write the room's scheduled call to its calendar event (when `push`, or
+/// when the event is missing or the last sync failed) and read the
+/// invitees' responses back. The outcome is stored on the room; a failure
+/// is kept on the event link for the next sync to retry.
+pub(crate) async fn sync_room(state: &AppState, room: &Room, push: bool) {
+    let (Some(calendar), Some(room_id)) = (state.calendar.as_ref(), room.id) else {
+        return;
+    };
+    let Some(settings) = room.conference_settings.as_ref() else {
+        return;
+    };
+    let Some(mut link) = settings.calendar_event.clone() else {
+        return;
+    };
+    let mut invitees = settings.invitees.clone();
+
+    let push = push || link.event_id.is_none() || link.error.is_some();
+    match sync_event(state, calendar, room, &mut link, &mut invitees, push).await {
+        Ok(()) => {
+            link.synced_at = Some(DateTime::now());
+            link.error = None;
+        }
+        Err(e) => {
+            tracing::warn!(%room_id, provider = %link.provider, %e, "Calendar sync failed");
+            link.error = Some(e.to_string());
+        }
+    }
+    if let Err(e) = state
+        .rooms
+        .set_calendar_sync(room.tenant_id, room_id, &link, &invitees)
+        .await
+    {
+        tracing::warn!(%room_id, %e, "Failed to store the calendar sync");
+    }
+}
+
+async fn sync_event(
+    state: &AppState,
+    calendar: &CalendarService,
+    room: &Room,
+    link: &mut CalendarEventLink,
+    invitees: &mut [Invitee],
+    push: bool,
+) -> Result<(), CalendarError> {
+    let connection = state
+        .calendar_connections
+        .find(link.user_id, &link.provider)
+        .await?
+        .ok_or(CalendarError::Disconnected)?;
+    let token = calendar
+        .access_token(&state.calendar_connections, &connection)
+        .await?;
+
+    if push {
+        let event = event_details(state, calendar, room, invitees);
+        match &link.event_id {
+            Some(event_id) => {
+                calendar
+                    .update_event(&link.provider, &token, event_id, &event)
+                    .await?
+            }
+            None => {
+                link.event_id = Some(
+                    calendar
+                        .create_event(&link.provider, &token, &event)
+                        .await?,
+                );
+            }
+        }
+    }
+
+    if let Some(event_id) = &link.event_id {
+        let responses = calendar
+            .attendee_responses(&link.provider, &token, event_id)
+            .await?;
+        for invitee in invitees.iter_mut() {
+            if let Some((_, response)) = responses.iter().find(|(e, _)| *e == invitee.email) {
+                invitee.response = *response;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn event_details(
+    state: &AppState,
+    calendar: &CalendarService,
+    room: &Room,
+    invitees: &[Invitee],
+) -> EventDetails {
+    let settings = room.conference_settings.clone().unwrap_or_default();
+    let location = join_url(state, room).unwrap_or_default();
+    EventDetails {
+        title: room.name.clone(),
+        description: calendar.description(&location, room.meeting_code.as_deref()),
+        location,
+        start: settings
+            .scheduled_start
+            .unwrap_or_else(DateTime::now)
+            .to_chrono(),
+        end: settings
+            .scheduled_end
+            .unwrap_or_else(DateTime::now)
+            .to_chrono(),
+        timezone: settings.timezone,
+        attendees: invitees.iter().map(|i| i.email.clone()).collect(),
+    }
+}
+
+/// Best-effort cancel of a calendar event the call no longer has.
+async fn cancel_event(state: &AppState, link: &CalendarEventLink) {
+    let (Some(calendar), Some(event_id)) = (state.calendar.as_ref(), &link.event_id) else {
+        return;
+    };
+    let result = async {
+        let connection = state
+            .calendar_connections
+            .find(link.user_id, &link.provider)
+            .await?
+            .ok_or(CalendarError::Disconnected)?;
+        let token = calendar
+            .access_token(&state.calendar_connections, &connection)
+            .await?;
+        calendar
+            .delete_event(&link.provider, &token, event_id)
+            .await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!(provider = %link.provider, %e, "Failed to cancel a calendar event");
+    }
+}
+
+/// Keep calendar-linked calls in sync until they end, every
+/// `calendar.sync_interval_secs` (0 disables).
+pub(crate) fn spawn_calendar_sync(state: AppState) {
+    let interval = state.settings.calendar.sync_interval_secs;
+    if interval == 0 || state.calendar.is_none() {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            tick.tick().await;
+            let rooms = match state.rooms.find_calendar_linked(DateTime::now()).await {
+                Ok(rooms) => rooms,
+                Err(e) => {
+                    tracing::warn!(%e, "Failed to list calendar-linked calls");
+                    continue;
+                }
+            };
+            for room in &rooms {
+                sync_room(&state, room, false).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invitee_emails_are_normalized() {
+        let emails = invitee_emails(&[
+            " Ana@Example.com".to_string(),
+            "ben@example.com".to_string(),
+            "ana@example.com".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(emails, vec!["ana@example.com", "ben@example.com"]);
+        assert!(invitee_emails(&["not-an-email".to_string()]).is_err());
+    }
+}
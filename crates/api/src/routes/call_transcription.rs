@@ -0,0 +1,193 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{TranscriptSegment, role::permissions};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::recording::{TranscriptSegmentRequest, redact_opt_outs};
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TranscriptionOptOutRequest {
+    pub opted_out: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscriptionOptOutResponse {
+    /// Everyone in the call who opted out of transcription.
+    pub opted_out_user_ids: Vec<String>,
+}
+
+/// Opt yourself out of (or back into) the call's transcription: your
+/// transcript segments read "[not transcribed]".
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/transcription",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = TranscriptionOptOutRequest,
+    responses((status = 200, body = TranscriptionOptOutResponse))
+)]
+pub async fn opt_out(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<TranscriptionOptOutRequest>,
+) -> Result<Json<TranscriptionOptOutResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::CONNECT_VOICE)
+        .await?;
+
+    let session = state
+        .call_sessions
+        .set_transcription_opt_out(rid, auth.user_id, body.opted_out)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+    state
+        .room_manager
+        .set_user_transcribed(&rid, &auth.user_id, !body.opted_out);
+
+    let opted_out_user_ids: Vec<String> = session
+        .transcription_opt_outs
+        .iter()
+        .map(|u| u.to_hex())
+        .collect();
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await
+        .unwrap_or_default();
+    if !member_ids.is_empty() {
+        let event = serde_json::json!({
+            "type": "call:transcription_opt_out",
+            "tenant_id": tid.to_hex(),
+            "data": {
+                "room_id": rid.to_hex(),
+                "user_id": auth.user_id.to_hex(),
+                "opted_out": body.opted_out,
+                "opted_out_user_ids": &opted_out_user_ids,
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &member_ids,
+            &event,
+        )
+        .await;
+    }
+
+    Ok(Json(TranscriptionOptOutResponse { opted_out_user_ids }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LiveTranscriptRequest {
+    pub segments: Vec<TranscriptSegmentRequest>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct LiveTranscriptResponse {
+    /// Segments relayed; those of participants who opted out read
+    /// "[not transcribed]".
+    pub accepted: usize,
+}
+
+/// Final transcript lines from the call's transcriber. The server doesn't
+/// recognize speech itself: a transcriber outside it, running as a member
+/// with MANAGE_MEETINGS, posts each final line here. Each one goes to the
+/// call as `media:transcript` and onto the call session's `transcript`,
+/// which the meeting assistant reads, so any pod can answer.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/transcript",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = LiveTranscriptRequest,
+    responses((status = 200, body = LiveTranscriptResponse))
+)]
+pub async fn append(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<LiveTranscriptRequest>,
+) -> Result<Json<LiveTranscriptResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+    let session = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+
+    let mut segments = Vec::with_capacity(body.segments.len());
+    for seg in body.segments {
+        if !seg.start_time.is_finite()
+            || !seg.end_time.is_finite()
+            || seg.start_time < 0.0
+            || seg.end_time < seg.start_time
+        {
+            return Err(ApiError::Validation(
+                "Segment times must satisfy 0 <= start_time <= end_time".to_string(),
+            ));
+        }
+        let user_id = match &seg.user_id {
+            Some(id) => Some(
+                ObjectId::parse_str(id)
+                    .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?,
+            ),
+            None => None,
+        };
+        segments.push(TranscriptSegment {
+            user_id,
+            speaker_name: seg.speaker_name,
+            text: seg.text,
+            start_time: seg.start_time,
+            end_time: seg.end_time,
+        });
+    }
+    redact_opt_outs(&mut segments, &session.transcription_opt_outs);
+
+    let connections = state.room_manager.get_other_connection_ids(&rid, "");
+    for seg in &segments {
+        let msg = serde_json::json!({
+            "type": "media:transcript",
+            "data": {
+                "is_final": true,
+                "user_id": seg.user_id.map(|u| u.to_hex()).unwrap_or_default(),
+                "speaker_name": seg.speaker_name,
+                "text": seg.text,
+                "language": null,
+                "confidence": null,
+                "start_time": seg.start_time,
+                "end_time": seg.end_time,
+            }
+        });
+        for conn_id in &connections {
+            crate::ws::dispatcher::send_to_connection(&state.ws_storage, conn_id, &msg).await;
+        }
+    }
+
+    state
+        .call_sessions
+        .append_transcript(rid, &segments, state.settings.assistant.context_lines)
+        .await?;
+    Ok(Json(LiveTranscriptResponse {
+        accepted: segments.len(),
+    }))
+}
@@ -15,6 +15,14 @@ use roomler_ai_db::models::consent_request::ConsentRequestStatus;
 use crate::{error::ApiError, state::AppState};
 
 /// `POST /api/consent/{token}/approve`
+#[utoipa::path(
+    post,
+    path = "/api/consent/{token}/approve",
+    tag = "consent",
+    params(("token" = String, Path)),
+    responses((status = 200, body = serde_json::Value)),
+    security(())
+)]
 pub async fn approve_consent(
     State(state): State<AppState>,
     Path(token): Path<String>,
@@ -23,6 +31,14 @@ pub async fn approve_consent(
 }
 
 /// `POST /api/consent/{token}/deny`
+#[utoipa::path(
+    post,
+    path = "/api/consent/{token}/deny",
+    tag = "consent",
+    params(("token" = String, Path)),
+    responses((status = 200, body = serde_json::Value)),
+    security(())
+)]
 pub async fn deny_consent(
     State(state): State<AppState>,
     Path(token): Path<String>,
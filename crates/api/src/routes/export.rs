@@ -7,18 +7,33 @@ use serde::Deserialize;
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::TaskCategory;
-use roomler_ai_services::dao::base::PaginationParams;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+use roomler_ai_db::models::{TaskCategory, role::permissions};
+use roomler_ai_services::dao::base::{ListOptions, PaginationParams};
+use utoipa::ToSchema;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ExportConversationRequest {
     pub room_id: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/export/conversation",
+    tag = "export",
+    params(("tenant_id" = String, Path)),
+    request_body = ExportConversationRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn export_conversation(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path(tenant_id): Path<String>,
     Json(body): Json<ExportConversationRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
@@ -45,6 +60,14 @@ pub async fn export_conversation(
 
     let task_id = task.id.unwrap();
 
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "export.conversation", "room", Some(rid))
+            .after(&serde_json::json!({ "task_id": task_id.to_hex(), "format": "xlsx" })),
+    )
+    .await;
+
     // Spawn async export work
     let messages_dao = Arc::clone(&state.messages);
     let users_dao = Arc::clone(&state.users);
@@ -56,9 +79,10 @@ pub async fn export_conversation(
             page: 1,
             per_page: 10000,
             before: None,
+            cursor: None,
         };
         let result = messages_dao
-            .find_in_room(rid, &params)
+            .find_in_room(rid, false, &params, &ListOptions::default())
             .await
             .map_err(|e| format!("Failed to fetch messages: {}", e))?;
 
@@ -124,3 +148,214 @@ pub async fn export_conversation(
         "status": "pending",
     })))
 }
+
+/// Archive a whole room as a zip (MANAGE_TENANT): messages as JSON and HTML
+/// (thread replies and deleted messages included), the room's files, call
+/// history and in-call chat transcripts. Runs as a background task; the zip
+/// is downloaded from the task routes.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/export/room/{room_id}",
+    tag = "export",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn export_room(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    // Archives cover rooms the caller may not be in: an admin operation.
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let exporter = state.users.base.find_by_id(auth.user_id).await?;
+
+    let task = state
+        .tasks
+        .create_task(
+            tid,
+            auth.user_id,
+            "export_room".to_string(),
+            TaskCategory::Export,
+            serde_json::json!({ "room_id": room_id }),
+        )
+        .await?;
+
+    let task_id = task.id.unwrap();
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "export.room", "room", Some(rid))
+            .after(&serde_json::json!({ "task_id": task_id.to_hex(), "format": "zip" })),
+    )
+    .await;
+
+    let bg = state.clone();
+    let task_store = Arc::clone(state.tasks.store());
+
+    state.tasks.spawn_task(task_id, async move {
+        use roomler_ai_services::export::archive;
+
+        let progress = |pct: u8, step: &str| {
+            let task_store = Arc::clone(&task_store);
+            let step = step.to_string();
+            async move {
+                task_store
+                    .update_progress(task_id, pct, Some(step))
+                    .await
+                    .map_err(|e| format!("Failed to update progress: {}", e))
+            }
+        };
+
+        let messages = bg
+            .messages
+            .find_all_in_room(rid)
+            .await
+            .map_err(|e| format!("Failed to fetch messages: {}", e))?;
+        let author_ids: Vec<ObjectId> = messages
+            .iter()
+            .map(|m| m.author_id)
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        let user_map: HashMap<_, _> = bg
+            .users
+            .base
+            .find_by_ids(&author_ids)
+            .await
+            .map_err(|e| format!("Failed to fetch users: {}", e))?
+            .into_iter()
+            .filter_map(|u| u.id.map(|id| (id, u)))
+            .collect();
+        progress(10, "Fetched messages").await?;
+
+        // Attachments, plus files uploaded to the room without a message.
+        let mut files = Vec::new();
+        let mut seen = std::collections::HashSet::new();
+        for attachment in messages.iter().flat_map(|m| &m.attachments) {
+            if seen.insert(attachment.file_id) {
+                files.push((attachment.file_id, attachment.filename.clone()));
+            }
+        }
+        let params = PaginationParams {
+            page: 1,
+            per_page: 10000,
+            before: None,
+            cursor: None,
+        };
+        let room_files = bg
+            .files
+            .find_by_room(tid, rid, &params, &ListOptions::default())
+            .await
+            .map_err(|e| format!("Failed to fetch files: {}", e))?;
+        for file in room_files.items {
+            if let Some(id) = file.id
+                && seen.insert(id)
+            {
+                files.push((id, file.filename));
+            }
+        }
+
+        let upload_dir = std::path::PathBuf::from(
+            std::env::var("ROOMLER_UPLOAD_DIR")
+                .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string()),
+        );
+        let export_dir = upload_dir.join("exports");
+        tokio::fs::create_dir_all(&export_dir)
+            .await
+            .map_err(|e| format!("Failed to create export dir: {}", e))?;
+        let file_name = format!("room-archive-{}.zip", task_id.to_hex());
+        let file_path = export_dir.join(&file_name);
+        let out = std::fs::File::create(&file_path)
+            .map_err(|e| format!("Failed to create export file: {}", e))?;
+
+        let zip_err = |e: std::io::Error| format!("Failed to write archive: {}", e);
+        let mut zip = archive::RoomArchive::new(out);
+        zip.add_json("room.json", &archive::room_json(&room, &exporter))
+            .map_err(zip_err)?;
+        zip.add_json(
+            "messages.json",
+            &archive::messages_json(&messages, &user_map),
+        )
+        .map_err(zip_err)?;
+        zip.add(
+            "messages.html",
+            archive::messages_html(&room.name, &messages, &user_map).as_bytes(),
+        )
+        .map_err(zip_err)?;
+        progress(20, "Wrote messages").await?;
+
+        let total = files.len().max(1);
+        for (i, (file_id, filename)) in files.iter().enumerate() {
+            // A file gone from the database or the disk, or quarantined by the
+            // virus scan, leaves a gap, not a failed archive.
+            let Ok(file) = bg.files.base.find_by_id_in_tenant(tid, *file_id).await else {
+                continue;
+            };
+            if !file.scan_status.is_downloadable() {
+                continue;
+            }
+            let Ok(bytes) = tokio::fs::read(upload_dir.join(&file.storage_key)).await else {
+                continue;
+            };
+            zip.add(&archive::attachment_path(*file_id, filename), &bytes)
+                .map_err(zip_err)?;
+            progress(
+                20 + (60 * (i + 1) / total) as u8,
+                &format!("Archived file {}/{}", i + 1, files.len()),
+            )
+            .await?;
+        }
+
+        let calls = bg
+            .call_sessions
+            .find_all_for_room(tid, rid)
+            .await
+            .map_err(|e| format!("Failed to fetch call history: {}", e))?;
+        zip.add_json("calls.json", &archive::calls_json(&calls))
+            .map_err(zip_err)?;
+        let chat = bg
+            .rooms
+            .find_all_chat_messages(rid)
+            .await
+            .map_err(|e| format!("Failed to fetch call chat: {}", e))?;
+        zip.add(
+            "transcripts/call-chat.txt",
+            archive::call_chat_transcript(&chat).as_bytes(),
+        )
+        .map_err(zip_err)?;
+        zip.finish().map_err(zip_err)?;
+        progress(95, "Wrote call history").await?;
+
+        task_store
+            .complete(
+                task_id,
+                Some(file_path.to_string_lossy().to_string()),
+                Some(file_name),
+            )
+            .await
+            .map_err(|e| format!("Failed to complete task: {}", e))?;
+
+        Ok(())
+    });
+
+    Ok(Json(serde_json::json!({
+        "task_id": task_id.to_hex(),
+        "status": "pending",
+    })))
+}
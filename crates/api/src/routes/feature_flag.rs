@@ -0,0 +1,158 @@
+//! Per-tenant feature flags (see `roomler_ai_services::feature_flags`).
+//! Members list the flags with whether each is on for them; MANAGE_TENANT
+//! sets a flag to `default`, `on`, `off` or a `percentage` rollout.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{FeatureFlag, FlagMode};
+use roomler_ai_services::feature_flags::{self, FLAGS, FlagInfo};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::retention::require_manage_tenant;
+use crate::{
+    error::ApiError,
+    extractors::{auth::AuthUser, feature::Features},
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct FeatureFlagResponse {
+    pub key: String,
+    pub description: String,
+    /// `default`, `on`, `off` or `percentage`.
+    #[schema(value_type = String)]
+    pub mode: FlagMode,
+    pub percentage: u8,
+    /// What `default` means in this deployment.
+    pub default: bool,
+    /// Whether the flag is on for the caller.
+    pub enabled: bool,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateFeatureFlagRequest {
+    #[schema(value_type = String)]
+    pub mode: FlagMode,
+    /// 0-100; required for `percentage`.
+    pub percentage: Option<u8>,
+}
+
+/// GET /api/tenant/{tenant_id}/feature-flag
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/feature-flag",
+    tag = "tenant",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<FeatureFlagResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<FeatureFlagResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let features = Features::load(&state, tid, auth.user_id).await?;
+    Ok(Json(
+        FLAGS
+            .iter()
+            .map(|info| to_response(info, &features))
+            .collect(),
+    ))
+}
+
+/// PUT /api/tenant/{tenant_id}/feature-flag/{key}
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/feature-flag/{key}",
+    tag = "tenant",
+    params(("tenant_id" = String, Path), ("key" = String, Path)),
+    request_body = UpdateFeatureFlagRequest,
+    responses((status = 200, body = FeatureFlagResponse))
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, key)): Path<(String, String)>,
+    Json(body): Json<UpdateFeatureFlagRequest>,
+) -> Result<Json<FeatureFlagResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let info = feature_flags::info(&key)
+        .ok_or_else(|| ApiError::NotFound("Feature flag not found".to_string()))?;
+    let percentage = match (body.mode, body.percentage) {
+        (FlagMode::Percentage, None) => {
+            return Err(ApiError::Validation(
+                "percentage is required for a percentage rollout".to_string(),
+            ));
+        }
+        (_, Some(p)) if p > 100 => {
+            return Err(ApiError::Validation(
+                "percentage must be between 0 and 100".to_string(),
+            ));
+        }
+        (FlagMode::Percentage, Some(p)) => p,
+        _ => 0,
+    };
+
+    let before = Features::load(&state, tid, auth.user_id)
+        .await?
+        .flag(info.key)
+        .map(audit_view);
+    let flag = state
+        .feature_flags
+        .set(tid, info.key, body.mode, percentage, auth.user_id)
+        .await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(
+            tid,
+            auth.user_id,
+            "tenant.feature_flag_update",
+            "feature_flag",
+            flag.id,
+        )
+        .before(&before)
+        .after(&audit_view(&flag)),
+    )
+    .await;
+
+    let features = Features::load(&state, tid, auth.user_id).await?;
+    Ok(Json(to_response(info, &features)))
+}
+
+fn to_response(info: &FlagInfo, features: &Features) -> FeatureFlagResponse {
+    let flag = features.flag(info.key);
+    FeatureFlagResponse {
+        key: info.key.to_string(),
+        description: info.description.to_string(),
+        mode: flag.map(|f| f.mode).unwrap_or_default(),
+        percentage: flag.map(|f| f.percentage).unwrap_or(0),
+        default: features.deployment_default(info.key),
+        enabled: features.enabled(info.key),
+        updated_at: flag.map(|f| f.updated_at.try_to_rfc3339_string().unwrap_or_default()),
+    }
+}
+
+fn audit_view(flag: &FeatureFlag) -> serde_json::Value {
+    serde_json::json!({
+        "key": flag.key,
+        "mode": flag.mode,
+        "percentage": flag.percentage,
+    })
+}
@@ -1,35 +1,36 @@
 use axum::{
     Json,
     body::Body,
-    extract::{Multipart, Path, Query, State},
+    extract::{Multipart, Path, State},
     response::Response,
 };
 use bson::oid::ObjectId;
-use serde::Serialize;
+use serde::Deserialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::io::AsyncReadExt;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::{FileContext, FileContextType};
-use roomler_ai_services::dao::base::PaginationParams;
-
-#[derive(Debug, Serialize)]
-pub struct FileResponse {
-    pub id: String,
-    pub filename: String,
-    pub content_type: String,
-    pub size: u64,
-    pub url: String,
-    pub uploaded_by: String,
-    pub created_at: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub room_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub room_name: Option<String>,
-}
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    middleware::audit::{self, AuditContext, AuditEntry},
+    middleware::storage_quota,
+    state::AppState,
+};
+use roomler_ai_db::models::{
+    FileContext, FileContextType, ScanResult, ScanStatus, TaskCategory, Thumbnail,
+    role::permissions,
+};
+use roomler_ai_services::PreviewService;
+use roomler_ai_services::dao::base::PaginatedResult;
+use roomler_ai_services::scan::Verdict;
+use utoipa::ToSchema;
 
-fn to_response(f: roomler_ai_db::models::File) -> FileResponse {
+pub use roomler_ai_types::file::{FileResponse, ThumbnailResponse};
+
+pub(crate) fn to_response(f: roomler_ai_db::models::File) -> FileResponse {
     let room_id = f.context.room_id.map(|rid| rid.to_hex());
     FileResponse {
         id: f.id.unwrap().to_hex(),
@@ -38,19 +39,63 @@ fn to_response(f: roomler_ai_db::models::File) -> FileResponse {
         size: f.size,
         url: f.url,
         uploaded_by: f.uploaded_by.to_hex(),
+        scan_status: f.scan_status.as_str().to_string(),
+        thumbnails: f
+            .thumbnails
+            .into_iter()
+            .map(|t| ThumbnailResponse {
+                size: t.size,
+                url: t.url,
+                width: t.width,
+                height: t.height,
+            })
+            .collect(),
         created_at: f.created_at.try_to_rfc3339_string().unwrap_or_default(),
         room_id,
         room_name: None,
     }
 }
 
+/// Multipart body of the upload endpoints, for the OpenAPI document only;
+/// the handlers read the parts one by one.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct UploadForm {
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Tenant uploads only: the room the file belongs to.
+    room_id: Option<String>,
+}
+
+const LIST_SPEC: ListSpec = ListSpec {
+    sort: &[
+        ("created_at", "created_at"),
+        ("filename", "filename"),
+        ("size", "size"),
+    ],
+    filters: &[
+        FilterField::new("filename", "filename", FieldKind::String),
+        FilterField::new("content_type", "content_type", FieldKind::String),
+        FilterField::new("uploaded_by", "uploaded_by", FieldKind::ObjectId),
+        FilterField::new("size", "size", FieldKind::Int),
+        FilterField::new("created_at", "created_at", FieldKind::DateTime),
+    ],
+};
+
 /// List files for a room.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/file",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<FileResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((tenant_id, room_id)): Path<(String, String)>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<FileResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
@@ -60,26 +105,29 @@ pub async fn list(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    let result = state.files.find_by_room(tid, rid, &params).await?;
-
-    let items: Vec<FileResponse> = result.items.into_iter().map(to_response).collect();
+    let options = query.options(&LIST_SPEC)?;
+    let result = state
+        .files
+        .find_by_room(tid, rid, &query.pagination, &options)
+        .await?;
 
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+    Ok(Json(result.map(to_response)))
 }
 
 /// List all files across all rooms in a tenant.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/file",
+    tag = "file",
+    params(("tenant_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<FileResponse>))
+)]
 pub async fn list_tenant_files(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(tenant_id): Path<String>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<FileResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
 
@@ -87,7 +135,11 @@ pub async fn list_tenant_files(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    let result = state.files.find_by_tenant(tid, &params).await?;
+    let options = query.options(&LIST_SPEC)?;
+    let result = state
+        .files
+        .find_by_tenant(tid, &query.pagination, &options)
+        .await?;
 
     // Collect unique room IDs and look up room names
     let room_ids: Vec<ObjectId> = result
@@ -105,81 +157,85 @@ pub async fn list_tenant_files(
         }
     }
 
-    let items: Vec<FileResponse> = result
-        .items
-        .into_iter()
-        .map(|f| {
-            let mut resp = to_response(f.clone());
-            if let Some(rid) = f.context.room_id {
-                resp.room_name = room_names.get(&rid).cloned();
-            }
-            resp
-        })
-        .collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
+    Ok(Json(result.map(|f| {
+        let mut resp = to_response(f.clone());
+        if let Some(rid) = f.context.room_id {
+            resp.room_name = room_names.get(&rid).cloned();
+        }
+        resp
     })))
 }
 
 /// Shared upload logic used by both `upload` and `upload_room`.
-async fn do_upload(
+pub(crate) async fn do_upload(
     state: &AppState,
     tid: ObjectId,
     rid: ObjectId,
     user_id: ObjectId,
     file_data: (String, String, Vec<u8>),
 ) -> Result<FileResponse, ApiError> {
-    let (filename, content_type, bytes) = file_data;
-    let size = bytes.len() as u64;
-
-    let upload_dir = upload_dir();
-    tokio::fs::create_dir_all(&upload_dir)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to create upload dir: {}", e)))?;
-
-    let storage_key = format!(
-        "{}/room/{}/{}",
-        tid.to_hex(),
-        rid.to_hex(),
-        uuid::Uuid::new_v4()
-    );
-    let file_path = upload_dir.join(&storage_key);
-
-    if let Some(parent) = file_path.parent() {
-        tokio::fs::create_dir_all(parent)
-            .await
-            .map_err(|e| ApiError::Internal(format!("Failed to create dirs: {}", e)))?;
-    }
-
-    tokio::fs::write(&file_path, &bytes)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to write file: {}", e)))?;
-
     let context = FileContext {
         context_type: FileContextType::Room,
         entity_id: rid,
         room_id: Some(rid),
     };
+    let prefix = format!("room/{}", rid.to_hex());
+    store(state, tid, user_id, context, &prefix, file_data).await
+}
 
-    let file = state
-        .files
-        .create(
-            tid,
-            user_id,
-            context,
-            filename,
-            content_type,
-            size,
-            "local".to_string(),
-            storage_key,
-            String::new(),
-        )
-        .await?;
+/// Write the bytes under `{tenant}/{prefix}/` in the upload dir and record
+/// the file with the given context.
+pub(crate) async fn store(
+    state: &AppState,
+    tid: ObjectId,
+    user_id: ObjectId,
+    context: FileContext,
+    prefix: &str,
+    file_data: (String, String, Vec<u8>),
+) -> Result<FileResponse, ApiError> {
+    let (filename, content_type, bytes) = file_data;
+    let size = bytes.len() as u64;
+    let file = storage_quota::upload(state, tid, user_id, size, async {
+        let upload_dir = upload_dir();
+        tokio::fs::create_dir_all(&upload_dir)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to create upload dir: {}", e)))?;
+
+        let storage_key = format!("{}/{}/{}", tid.to_hex(), prefix, uuid::Uuid::new_v4());
+        let file_path = upload_dir.join(&storage_key);
+
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| ApiError::Internal(format!("Failed to create dirs: {}", e)))?;
+        }
+
+        tokio::fs::write(&file_path, &bytes)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to write file: {}", e)))?;
+
+        let file = state
+            .files
+            .create(
+                tid,
+                user_id,
+                context,
+                filename,
+                content_type,
+                size,
+                "local".to_string(),
+                storage_key,
+                String::new(),
+                if state.scanner.is_some() {
+                    ScanStatus::Pending
+                } else {
+                    ScanStatus::Skipped
+                },
+            )
+            .await?;
+        Ok(file)
+    })
+    .await?;
 
     let file_id_hex = file.id.unwrap().to_hex();
     let url = format!("/api/tenant/{}/file/{}/download", tid.to_hex(), file_id_hex);
@@ -192,13 +248,325 @@ async fn do_upload(
         )
         .await?;
 
+    if state.scanner.is_some() {
+        spawn_scan(state, &file).await?;
+    } else {
+        spawn_preview(state, &file).await?;
+    }
     let mut resp = to_response(file);
     resp.url = url;
     Ok(resp)
 }
 
+/// Queue the virus scan of a new upload. Until it reports clean the file
+/// stays quarantined; a failed scan leaves it so for an admin to decide.
+async fn spawn_scan(state: &AppState, file: &roomler_ai_db::models::File) -> Result<(), ApiError> {
+    let Some(scanner) = state.scanner.clone() else {
+        return Ok(());
+    };
+    let fid = file.id.unwrap();
+    let task = state
+        .tasks
+        .create_task(
+            file.tenant_id,
+            file.uploaded_by,
+            "file_scan".to_string(),
+            TaskCategory::Scan,
+            serde_json::json!({ "file_id": fid.to_hex() }),
+        )
+        .await?;
+
+    let task_id = task.id.unwrap();
+    let file_path = upload_dir().join(&file.storage_key);
+    let tasks = Arc::clone(&state.tasks);
+    let task_store = Arc::clone(state.tasks.store());
+    let state = state.clone();
+
+    tasks.spawn_task(task_id, async move {
+        let bytes = tokio::fs::read(&file_path)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let verdict = scanner.scan(&bytes).await?;
+
+        let (status, signature) = match verdict {
+            Verdict::Clean => (ScanStatus::Clean, None),
+            Verdict::Infected(signature) => {
+                tracing::warn!(?fid, %signature, "Upload quarantined: malware found");
+                (ScanStatus::Malware, Some(signature))
+            }
+        };
+        let result = ScanResult {
+            engine: scanner.engine().to_string(),
+            signature,
+            overridden_by: None,
+            scanned_at: bson::DateTime::now(),
+        };
+        state
+            .files
+            .set_scan_result(fid, status, &result)
+            .await
+            .map_err(|e| format!("Failed to store scan result: {}", e))?;
+        let file = state
+            .files
+            .base
+            .find_by_id(fid)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        notify_scan_result(&state, &file).await;
+        if matches!(file.scan_status, ScanStatus::Clean)
+            && let Err(e) = spawn_preview(&state, &file).await
+        {
+            tracing::warn!(?fid, %e, "Failed to queue preview after scan");
+        }
+
+        task_store
+            .complete(task_id, None, None)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        Ok(())
+    });
+    Ok(())
+}
+
+/// The uploader and, for a room file, the room's members.
+async fn file_audience(state: &AppState, file: &roomler_ai_db::models::File) -> Vec<ObjectId> {
+    let mut user_ids = vec![file.uploaded_by];
+    if let Some(rid) = file.context.room_id {
+        match state.rooms.find_member_user_ids(rid).await {
+            Ok(members) => user_ids.extend(members.into_iter().filter(|u| *u != file.uploaded_by)),
+            Err(e) => tracing::warn!(?rid, %e, "File event: room members lookup failed"),
+        }
+    }
+    user_ids
+}
+
+/// `file:scan_result` to the uploader and, for a room file, the room.
+async fn notify_scan_result(state: &AppState, file: &roomler_ai_db::models::File) {
+    let user_ids = file_audience(state, file).await;
+    let msg = serde_json::json!({
+        "type": "file:scan_result",
+        "tenant_id": file.tenant_id.to_hex(),
+        "data": {
+            "file_id": file.id.map(|id| id.to_hex()),
+            "room_id": file.context.room_id.map(|id| id.to_hex()),
+            "status": file.scan_status.as_str(),
+            "signature": file.scan_result.as_ref().and_then(|r| r.signature.clone()),
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &user_ids,
+        &msg,
+    )
+    .await;
+}
+
+/// Directory holding the rendered pages of a file.
+fn preview_dir(file: &roomler_ai_db::models::File) -> PathBuf {
+    upload_dir().join(format!("{}.preview", file.storage_key))
+}
+
+/// Queue page previews for a PDF or Word upload, once it may be served.
+/// Does nothing without a conversion worker or for other file types.
+async fn spawn_preview(
+    state: &AppState,
+    file: &roomler_ai_db::models::File,
+) -> Result<(), ApiError> {
+    let Some(previews) = state.previews.clone() else {
+        return Ok(());
+    };
+    if !PreviewService::supports(&file.content_type) || !file.scan_status.is_downloadable() {
+        return Ok(());
+    }
+    let fid = file.id.unwrap();
+    let task = state
+        .tasks
+        .create_task(
+            file.tenant_id,
+            file.uploaded_by,
+            "file_preview".to_string(),
+            TaskCategory::Preview,
+            serde_json::json!({ "file_id": fid.to_hex() }),
+        )
+        .await?;
+
+    let task_id = task.id.unwrap();
+    let file = file.clone();
+    let tasks = Arc::clone(&state.tasks);
+    let task_store = Arc::clone(state.tasks.store());
+    let state = state.clone();
+
+    tasks.spawn_task(task_id, async move {
+        let bytes = tokio::fs::read(upload_dir().join(&file.storage_key))
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let pages = previews.render(&bytes, &file.content_type).await?;
+        task_store
+            .update_progress(task_id, 60, Some("Storing page previews".to_string()))
+            .await
+            .map_err(|e| format!("{}", e))?;
+
+        let dir = preview_dir(&file);
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .map_err(|e| format!("Failed to create preview dir: {}", e))?;
+        let mut thumbnails = Vec::with_capacity(pages.len());
+        for (i, page) in pages.into_iter().enumerate() {
+            let n = i + 1;
+            tokio::fs::write(dir.join(format!("{}.png", n)), &page.png)
+                .await
+                .map_err(|e| format!("Failed to write preview: {}", e))?;
+            thumbnails.push(Thumbnail {
+                size: format!("page-{}", n),
+                url: format!(
+                    "/api/tenant/{}/file/{}/preview/{}",
+                    file.tenant_id.to_hex(),
+                    fid.to_hex(),
+                    n
+                ),
+                width: page.width,
+                height: page.height,
+            });
+        }
+
+        state
+            .files
+            .set_thumbnails(fid, &thumbnails)
+            .await
+            .map_err(|e| format!("Failed to store previews: {}", e))?;
+        if let Some(first) = thumbnails.first() {
+            state
+                .messages
+                .set_attachment_thumbnail(fid, &first.url)
+                .await
+                .map_err(|e| format!("Failed to update attachments: {}", e))?;
+        }
+
+        let user_ids = file_audience(&state, &file).await;
+        let msg = serde_json::json!({
+            "type": "file:preview_ready",
+            "tenant_id": file.tenant_id.to_hex(),
+            "data": {
+                "file_id": fid.to_hex(),
+                "room_id": file.context.room_id.map(|id| id.to_hex()),
+                "thumbnails": thumbnails,
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &user_ids,
+            &msg,
+        )
+        .await;
+
+        task_store
+            .complete(task_id, None, None)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        Ok(())
+    });
+    Ok(())
+}
+
+/// Refuse quarantined files: `409` while the scan is pending, `403` once
+/// malware was found.
+pub(crate) fn require_downloadable(file: &roomler_ai_db::models::File) -> Result<(), ApiError> {
+    match file.scan_status {
+        ScanStatus::Pending => Err(ApiError::Conflict(
+            "File is quarantined until its virus scan completes".to_string(),
+        )),
+        ScanStatus::Malware => Err(ApiError::Forbidden(
+            "File is quarantined: malware detected".to_string(),
+        )),
+        ScanStatus::Clean | ScanStatus::Skipped => Ok(()),
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ScanOverrideRequest {
+    /// `clean` releases the file, `malware` quarantines it.
+    pub status: String,
+}
+
+/// Set a file's scan status by hand, e.g. to release a false positive.
+/// Needs `MANAGE_TENANT`; audited as `file.scan_override`.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/file/{file_id}/scan",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    request_body = ScanOverrideRequest,
+    responses((status = 200, body = FileResponse))
+)]
+pub async fn override_scan(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, file_id)): Path<(String, String)>,
+    Json(body): Json<ScanOverrideRequest>,
+) -> Result<Json<FileResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let fid = ObjectId::parse_str(&file_id)
+        .map_err(|_| ApiError::BadRequest("Invalid file_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    let status = match body.status.as_str() {
+        "clean" => ScanStatus::Clean,
+        "malware" => ScanStatus::Malware,
+        _ => {
+            return Err(ApiError::Validation(
+                "status must be clean or malware".to_string(),
+            ));
+        }
+    };
+
+    let before = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    let result = ScanResult {
+        engine: "override".to_string(),
+        signature: None,
+        overridden_by: Some(auth.user_id),
+        scanned_at: bson::DateTime::now(),
+    };
+    state.files.set_scan_result(fid, status, &result).await?;
+    let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    notify_scan_result(&state, &file).await;
+    if file.thumbnails.is_empty() {
+        spawn_preview(&state, &file).await?;
+    }
+
+    let after = to_response(file);
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "file.scan_override", "file", Some(fid))
+            .before(&to_response(before))
+            .after(&after),
+    )
+    .await;
+    Ok(Json(after))
+}
+
 /// Upload a file via multipart form data.
 /// Fields: `file` (binary), `room_id` (text)
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/file/upload",
+    tag = "file",
+    params(("tenant_id" = String, Path)),
+    request_body(content = UploadForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = FileResponse))
+)]
 pub async fn upload(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -211,6 +579,7 @@ pub async fn upload(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    crate::middleware::rate_limit::check_upload(&state, tid, auth.user_id).await?;
 
     let mut file_data: Option<(String, String, Vec<u8>)> = None;
     let mut room_id_str: Option<String> = None;
@@ -256,6 +625,13 @@ pub async fn upload(
     Ok(Json(resp))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/file/{file_id}",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    responses((status = 200, body = FileResponse))
+)]
 pub async fn get(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -274,6 +650,15 @@ pub async fn get(
     Ok(Json(to_response(file)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/file/{file_id}/download",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    responses(
+        (status = 200, description = "File contents", content_type = "application/octet-stream"),
+    )
+)]
 pub async fn download(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -289,6 +674,7 @@ pub async fn download(
     }
 
     let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    require_downloadable(&file)?;
     let file_path = upload_dir().join(&file.storage_key);
 
     let mut contents = Vec::new();
@@ -319,6 +705,48 @@ pub async fn download(
         .unwrap())
 }
 
+/// A rendered page of a PDF or Word document, as PNG. `page` counts from 1.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/file/{file_id}/preview/{page}",
+    tag = "file",
+    params(
+        ("tenant_id" = String, Path),
+        ("file_id" = String, Path),
+        ("page" = u32, Path)
+    ),
+    responses((status = 200, description = "Page image", content_type = "image/png"))
+)]
+pub async fn preview(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, file_id, page)): Path<(String, String, u32)>,
+) -> Result<Response, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let fid = ObjectId::parse_str(&file_id)
+        .map_err(|_| ApiError::BadRequest("Invalid file_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    require_downloadable(&file)?;
+    if page == 0 || page as usize > file.thumbnails.len() {
+        return Err(ApiError::NotFound("Preview not found".to_string()));
+    }
+    let png = tokio::fs::read(preview_dir(&file).join(format!("{}.png", page)))
+        .await
+        .map_err(|_| ApiError::NotFound("Preview not found on disk".to_string()))?;
+
+    Ok(Response::builder()
+        .header("Content-Type", "image/png")
+        .header("Cache-Control", "private, max-age=86400")
+        .body(Body::from(png))
+        .unwrap())
+}
+
 /// Sanitize a stored filename for the `Content-Disposition` header.
 /// Returns `(ascii_quoted, rfc5987_encoded)`:
 /// - `ascii_quoted` is safe to place inside `filename="…"` — control
@@ -410,6 +838,13 @@ mod content_disposition_tests {
     }
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/file/{file_id}",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -424,11 +859,20 @@ pub async fn delete(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    state.files.soft_delete(tid, fid).await?;
+    let freed = state.files.soft_delete(tid, fid).await?;
+    storage_quota::record_delete(&state, tid, freed).await;
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
 /// Upload a file attached to a room (with 100MB body limit).
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/file/upload",
+    tag = "file",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body(content = UploadForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = FileResponse))
+)]
 pub async fn upload_room(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -443,6 +887,7 @@ pub async fn upload_room(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    crate::middleware::rate_limit::check_upload(&state, tid, auth.user_id).await?;
 
     let mut file_data: Option<(String, String, Vec<u8>)> = None;
 
@@ -473,7 +918,7 @@ pub async fn upload_room(
     Ok(Json(resp))
 }
 
-fn upload_dir() -> PathBuf {
+pub(crate) fn upload_dir() -> PathBuf {
     let dir = std::env::var("ROOMLER_UPLOAD_DIR")
         .unwrap_or_else(|_| "/tmp/roomler-ai-uploads".to_string());
     PathBuf::from(dir)
@@ -3,10 +3,12 @@ use axum::{
     extract::{Query, State},
 };
 use serde::Deserialize;
+use utoipa::IntoParams;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchQuery {
     pub q: String,
     #[serde(default = "default_limit")]
@@ -15,7 +17,8 @@ pub struct SearchQuery {
     pub offset: u32,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct TrendingQuery {
     #[serde(default = "default_limit")]
     pub limit: u32,
@@ -27,6 +30,13 @@ fn default_limit() -> u32 {
     25
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/giphy/search",
+    tag = "giphy",
+    params(SearchQuery),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn search(
     State(state): State<AppState>,
     _auth: AuthUser,
@@ -45,6 +55,13 @@ pub async fn search(
     Ok(Json(serde_json::to_value(result).unwrap()))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/giphy/trending",
+    tag = "giphy",
+    params(TrendingQuery),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn trending(
     State(state): State<AppState>,
     _auth: AuthUser,
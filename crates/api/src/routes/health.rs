@@ -0,0 +1,192 @@
+//! Health probes for the orchestrator.
+//!
+//! - `/health` — the process answers HTTP.
+//! - `/health/live` — the process isn't wedged: the watchdog below still
+//!   runs, and every mediasoup worker still answers it. Failing means
+//!   restart the pod.
+//! - `/health/ready` — the pod can serve: MongoDB answers, Redis answers if
+//!   this pod uses it, and at least one mediasoup worker runs. Failing
+//!   means take the pod out of the Service until it passes again.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use axum::{Json, extract::State, http::StatusCode};
+use serde_json::{Value, json};
+
+use crate::state::AppState;
+
+/// Watchdog pings in a row a worker may miss before it counts as stuck.
+const MISSED_LIMIT: u32 = 3;
+
+/// Time allowed for each dependency check of `/health/ready`.
+const CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Results of the mediasoup worker watchdog (see [`spawn_watchdog`]).
+#[derive(Default)]
+pub struct Watchdog {
+    inner: Mutex<Rounds>,
+}
+
+#[derive(Default)]
+struct Rounds {
+    last: Option<Instant>,
+    /// Consecutive missed pings, per worker.
+    missed: Vec<u32>,
+}
+
+impl Watchdog {
+    fn record(&self, answered: &[bool]) {
+        let mut rounds = self.inner.lock().unwrap();
+        rounds.missed.resize(answered.len(), 0);
+        for (missed, ok) in rounds.missed.iter_mut().zip(answered) {
+            *missed = if *ok { 0 } else { *missed + 1 };
+        }
+        rounds.last = Some(Instant::now());
+    }
+
+    /// Time since the last finished round (`None` before the first), and
+    /// how many workers missed `MISSED_LIMIT` pings in a row.
+    fn status(&self) -> (Option<Duration>, usize) {
+        let rounds = self.inner.lock().unwrap();
+        let stuck = rounds.missed.iter().filter(|m| **m >= MISSED_LIMIT).count();
+        (rounds.last.map(|at| at.elapsed()), stuck)
+    }
+}
+
+/// Ping every mediasoup worker each `mediasoup.watchdog_secs` (0 disables).
+pub(crate) fn spawn_watchdog(state: AppState) {
+    let interval = state.settings.mediasoup.watchdog_secs;
+    if interval == 0 {
+        return;
+    }
+    let interval = Duration::from_secs(interval);
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(interval);
+        loop {
+            tick.tick().await;
+            let answered = state.room_manager.ping_workers(interval).await;
+            let before = state.watchdog.status().1;
+            state.watchdog.record(&answered);
+            let stuck = state.watchdog.status().1;
+            if stuck > before {
+                tracing::error!(stuck, "mediasoup worker stopped answering the watchdog");
+            }
+        }
+    });
+}
+
+/// GET /health
+pub async fn health() -> Json<Value> {
+    Json(json!({
+        "status": "ok",
+        "version": env!("CARGO_PKG_VERSION"),
+    }))
+}
+
+/// GET /health/live
+pub async fn live(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let interval = state.settings.mediasoup.watchdog_secs;
+    let (age, stuck_workers) = state.watchdog.status();
+    // A round takes at most two intervals (the wait plus the ping timeout).
+    let stale_after = Duration::from_secs(2 * u64::from(MISSED_LIMIT) * interval);
+    let stalled = interval > 0 && age.is_some_and(|age| age > stale_after);
+    let ok = !stalled && stuck_workers == 0;
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(json!({
+            "status": if ok { "ok" } else { "fail" },
+            "watchdog": match (interval, stalled) {
+                (0, _) => "disabled",
+                (_, true) => "stalled",
+                _ => "ok",
+            },
+            "last_round_ms": age.map(|age| age.as_millis() as u64),
+            "stuck_workers": stuck_workers,
+        })),
+    )
+}
+
+/// GET /health/ready
+pub async fn ready(State(state): State<AppState>) -> (StatusCode, Json<Value>) {
+    let mongo = timed(async {
+        state
+            .db
+            .run_command(bson::doc! { "ping": 1 })
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    })
+    .await;
+
+    // Without Redis at startup the pod runs on its own, so it has nothing
+    // to lose if Redis is down now.
+    let redis = match &state.redis_pubsub {
+        Some(redis) => timed(async { redis.ping().await.map_err(|e| e.to_string()) }).await,
+        None => json!({ "status": "skipped" }),
+    };
+
+    let (live_workers, workers) = state.room_manager.worker_counts();
+    let mediasoup = json!({
+        "status": if live_workers > 0 { "ok" } else { "fail" },
+        "live_workers": live_workers,
+        "workers": workers,
+    });
+
+    // No speech recognition backend is built into this server yet.
+    let asr = json!({ "status": "skipped" });
+
+    let ok = [&mongo, &redis, &mediasoup, &asr]
+        .iter()
+        .all(|check| check["status"] != "fail");
+    let status = if ok {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (
+        status,
+        Json(json!({
+            "status": if ok { "ready" } else { "not_ready" },
+            "checks": { "mongo": mongo, "redis": redis, "mediasoup": mediasoup, "asr": asr },
+        })),
+    )
+}
+
+/// Run a dependency check within `CHECK_TIMEOUT`, reporting its latency.
+async fn timed(check: impl Future<Output = Result<(), String>>) -> Value {
+    let started = Instant::now();
+    match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(Ok(())) => json!({
+            "status": "ok",
+            "latency_ms": started.elapsed().as_millis() as u64,
+        }),
+        Ok(Err(error)) => json!({ "status": "fail", "error": error }),
+        Err(_) => json!({ "status": "fail", "error": "timed out" }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn workers_are_stuck_after_missing_the_limit_in_a_row() {
+        let watchdog = Watchdog::default();
+        assert_eq!(watchdog.status(), (None, 0));
+        watchdog.record(&[true, false]);
+        watchdog.record(&[true, false]);
+        assert_eq!(watchdog.status().1, 0);
+        watchdog.record(&[false, false]);
+        assert_eq!(watchdog.status().1, 1);
+        // An answer clears the count.
+        watchdog.record(&[true, true]);
+        assert!(watchdog.status().0.is_some());
+        assert_eq!(watchdog.status().1, 0);
+    }
+}
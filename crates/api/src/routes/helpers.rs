@@ -1,5 +1,8 @@
-use bson::oid::ObjectId;
+use std::collections::HashMap;
+
+use bson::{doc, oid::ObjectId};
 use roomler_ai_db::models::{NotificationSource, NotificationType};
+use roomler_ai_services::notification_prefs::{self, NotifyKind};
 
 use crate::state::AppState;
 use crate::ws;
@@ -37,6 +40,7 @@ async fn create_and_send_notification(
         Ok(notification) => {
             let notif_event = serde_json::json!({
                 "type": "notification:new",
+                "tenant_id": params.tenant_id.to_hex(),
                 "data": {
                     "id": notification.id.unwrap().to_hex(),
                     "title": notification.title,
@@ -67,10 +71,102 @@ async fn create_and_send_notification(
     }
 }
 
+/// How a recipient may be alerted outside the app.
+#[derive(Debug, Clone, Copy)]
+struct Alerts {
+    push: bool,
+    email: bool,
+}
+
+/// The users of `user_ids`, other than `actor_id`, whose notification
+/// preferences let a `kind` notification from the room through, with how
+/// they may be alerted right now.
+async fn recipients(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_ids: &[ObjectId],
+    actor_id: ObjectId,
+    kind: NotifyKind,
+) -> Vec<(ObjectId, Alerts)> {
+    let ids: Vec<ObjectId> = user_ids
+        .iter()
+        .copied()
+        .filter(|id| *id != actor_id)
+        .collect();
+    if ids.is_empty() {
+        return Vec::new();
+    }
+
+    let users: HashMap<_, _> = state
+        .users
+        .base
+        .find_by_ids(&ids)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|u| Some((u.id?, u.notification_preferences)))
+        .collect();
+    let room_members: HashMap<_, _> = state
+        .rooms
+        .find_memberships_of(room_id, &ids)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|m| Some((m.user_id?, m)))
+        .collect();
+    let tenant_members: HashMap<_, _> = state
+        .tenants
+        .members
+        .find_many(
+            doc! { "tenant_id": tenant_id, "user_id": { "$in": ids.as_slice() } },
+            None,
+        )
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|m| (m.user_id, m))
+        .collect();
+    let tenant_default = state
+        .tenants
+        .base
+        .find_by_id(tenant_id)
+        .await
+        .map(|t| t.settings.default_message_notifications)
+        .unwrap_or_default();
+
+    let now = chrono::Utc::now();
+    ids.into_iter()
+        .filter_map(|id| {
+            let prefs = users.get(&id).cloned().unwrap_or_default();
+            let level = notification_prefs::room_level(
+                &prefs,
+                room_members.get(&id),
+                tenant_members.get(&id),
+                &tenant_default,
+            );
+            if !notification_prefs::allows(&level, kind) {
+                return None;
+            }
+            let quiet = prefs
+                .quiet_hours
+                .as_ref()
+                .is_some_and(|q| notification_prefs::in_quiet_hours(q, now));
+            let alerts = Alerts {
+                push: prefs.push && !quiet,
+                email: prefs.email && !quiet,
+            };
+            Some((id, alerts))
+        })
+        .collect()
+}
+
 /// Send push notifications for a list of offline user IDs (spawns a background task).
+/// Devices whose own notification level doesn't allow `kind` are skipped.
 fn spawn_push_for_offline(
     state: &AppState,
     offline_user_ids: Vec<ObjectId>,
+    kind: NotifyKind,
     title: String,
     body: String,
     link: String,
@@ -83,7 +179,12 @@ fn spawn_push_for_offline(
         let subs_dao = state.push_subscriptions.clone();
         tokio::spawn(async move {
             if let Ok(subs) = subs_dao.find_by_users(&offline_user_ids).await {
-                for sub in subs {
+                let wanted = subs.into_iter().filter(|s| {
+                    s.notification_level
+                        .as_ref()
+                        .is_none_or(|level| notification_prefs::allows(level, kind))
+                });
+                for sub in wanted {
                     let _ = push
                         .send(
                             &sub.endpoint,
@@ -140,7 +241,7 @@ fn spawn_mention_email(
 pub async fn notify_mentions(
     state: &AppState,
     tenant_id: ObjectId,
-    _room_id: ObjectId,
+    room_id: ObjectId,
     message_id: ObjectId,
     author_id: ObjectId,
     mentioned_user_ids: &[ObjectId],
@@ -169,38 +270,112 @@ pub async fn notify_mentions(
         ws_type_label: "mention",
     };
 
+    let to_notify = recipients(
+        state,
+        tenant_id,
+        room_id,
+        mentioned_user_ids,
+        author_id,
+        NotifyKind::Mention,
+    )
+    .await;
     let mut offline_ids = Vec::new();
 
-    for user_id in mentioned_user_ids {
-        if *user_id == author_id {
+    for (user_id, alerts) in to_notify {
+        create_and_send_notification(state, &params, user_id).await;
+
+        if state.ws_storage.is_connected_to(&user_id, &tenant_id) {
             continue;
         }
-
-        create_and_send_notification(state, &params, *user_id).await;
-
-        if !state.ws_storage.is_connected(user_id) {
+        if alerts.email {
             spawn_mention_email(
                 state,
-                *user_id,
+                user_id,
                 mentioner_name.to_string(),
                 room_name.to_string(),
                 params.body.clone(),
                 tenant_id_str,
                 room_id_str,
             );
-            offline_ids.push(*user_id);
+        }
+        if alerts.push {
+            offline_ids.push(user_id);
         }
     }
 
     spawn_push_for_offline(
         state,
         offline_ids,
+        NotifyKind::Mention,
         params.title,
         params.body,
         format!("/tenant/{}/room/{}", tenant_id_str, room_id_str),
     );
 }
 
+/// Create thread-reply notifications for a thread's followers and send push
+/// to offline ones.
+#[allow(clippy::too_many_arguments)]
+pub async fn notify_thread_followers(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    thread_id: ObjectId,
+    author_id: ObjectId,
+    follower_ids: &[ObjectId],
+    replier_name: &str,
+    content_preview: &str,
+    tenant_id_str: &str,
+    room_id_str: &str,
+) {
+    let params = NotifyParams {
+        tenant_id,
+        notification_type: NotificationType::ThreadReply,
+        title: format!("{} replied in a thread", replier_name),
+        body: content_preview.chars().take(200).collect(),
+        link: format!(
+            "/tenant/{}/room/{}?thread={}",
+            tenant_id_str,
+            room_id_str,
+            thread_id.to_hex()
+        ),
+        source: NotificationSource {
+            entity_type: "thread".to_string(),
+            entity_id: thread_id,
+            actor_id: Some(author_id),
+        },
+        ws_type_label: "thread_reply",
+    };
+
+    let to_notify = recipients(
+        state,
+        tenant_id,
+        room_id,
+        follower_ids,
+        author_id,
+        NotifyKind::Activity,
+    )
+    .await;
+    let mut offline_ids = Vec::new();
+
+    for (uid, alerts) in to_notify {
+        create_and_send_notification(state, &params, uid).await;
+
+        if alerts.push && !state.ws_storage.is_connected_to(&uid, &tenant_id) {
+            offline_ids.push(uid);
+        }
+    }
+
+    spawn_push_for_offline(
+        state,
+        offline_ids,
+        NotifyKind::Activity,
+        params.title,
+        params.body,
+        params.link,
+    );
+}
+
 /// Create call-started notifications for room members and send push to offline users.
 #[allow(clippy::too_many_arguments)]
 pub async fn notify_call_started(
@@ -228,19 +403,89 @@ pub async fn notify_call_started(
         ws_type_label: "call",
     };
 
+    let to_notify = recipients(
+        state,
+        tenant_id,
+        room_id,
+        member_ids,
+        caller_id,
+        NotifyKind::Activity,
+    )
+    .await;
     let mut offline_ids = Vec::new();
 
-    for uid in member_ids {
-        if *uid == caller_id {
-            continue;
+    for (uid, alerts) in to_notify {
+        create_and_send_notification(state, &params, uid).await;
+
+        if alerts.push && !state.ws_storage.is_connected_to(&uid, &tenant_id) {
+            offline_ids.push(uid);
         }
+    }
+
+    spawn_push_for_offline(
+        state,
+        offline_ids,
+        NotifyKind::Activity,
+        params.title,
+        params.body,
+        params.link,
+    );
+}
+
+/// Notify rung members who didn't pick up (see `ws::ring`). Targeted like a
+/// mention, so only `nothing` or a muted room holds it back.
+pub async fn notify_missed_call(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    caller_id: ObjectId,
+    user_ids: &[ObjectId],
+    room_name: &str,
+    caller_name: &str,
+) {
+    let params = NotifyParams {
+        tenant_id,
+        notification_type: NotificationType::MissedCall,
+        title: format!("Missed call in #{}", room_name),
+        body: format!("You missed a call from {}", caller_name),
+        link: format!(
+            "/tenant/{}/room/{}/call",
+            tenant_id.to_hex(),
+            room_id.to_hex()
+        ),
+        source: NotificationSource {
+            entity_type: "room".to_string(),
+            entity_id: room_id,
+            actor_id: Some(caller_id),
+        },
+        ws_type_label: "missed_call",
+    };
+
+    let to_notify = recipients(
+        state,
+        tenant_id,
+        room_id,
+        user_ids,
+        caller_id,
+        NotifyKind::Mention,
+    )
+    .await;
+    let mut offline_ids = Vec::new();
 
-        create_and_send_notification(state, &params, *uid).await;
+    for (uid, alerts) in to_notify {
+        create_and_send_notification(state, &params, uid).await;
 
-        if !state.ws_storage.is_connected(uid) {
-            offline_ids.push(*uid);
+        if alerts.push && !state.ws_storage.is_connected_to(&uid, &tenant_id) {
+            offline_ids.push(uid);
         }
     }
 
-    spawn_push_for_offline(state, offline_ids, params.title, params.body, params.link);
+    spawn_push_for_offline(
+        state,
+        offline_ids,
+        NotifyKind::Mention,
+        params.title,
+        params.body,
+        params.link,
+    );
 }
@@ -6,11 +6,24 @@ use bson::oid::ObjectId;
 use serde::Deserialize;
 use std::sync::Arc;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
 use roomler_ai_db::models::TaskCategory;
+use utoipa::ToSchema;
 
 /// POST /api/tenant/:tid/file/:fid/recognize
 /// Trigger AI document recognition for an uploaded file.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/file/{file_id}/recognize",
+    tag = "integration",
+    params(("tenant_id" = String, Path), ("file_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn recognize_file(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -32,6 +45,7 @@ pub async fn recognize_file(
     }
 
     let file = state.files.base.find_by_id_in_tenant(tid, fid).await?;
+    super::file::require_downloadable(&file)?;
 
     // Create background task
     let task = state
@@ -114,14 +128,23 @@ pub async fn recognize_file(
 
 /// POST /api/tenant/:tid/export/conversation-pdf
 /// Export conversation as PDF (background task).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct ExportPdfRequest {
     pub room_id: String,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/export/conversation-pdf",
+    tag = "integration",
+    params(("tenant_id" = String, Path)),
+    request_body = ExportPdfRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn export_conversation_pdf(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path(tenant_id): Path<String>,
     Json(body): Json<ExportPdfRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
@@ -146,6 +169,15 @@ pub async fn export_conversation_pdf(
         .await?;
 
     let task_id = task.id.unwrap();
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "export.conversation", "room", Some(rid))
+            .after(&serde_json::json!({ "task_id": task_id.to_hex(), "format": "pdf" })),
+    )
+    .await;
+
     let messages_dao = Arc::clone(&state.messages);
     let users_dao = Arc::clone(&state.users);
     let task_store = Arc::clone(state.tasks.store());
@@ -155,9 +187,15 @@ pub async fn export_conversation_pdf(
             page: 1,
             per_page: 10000,
             before: None,
+            cursor: None,
         };
         let result = messages_dao
-            .find_in_room(rid, &params)
+            .find_in_room(
+                rid,
+                false,
+                &params,
+                &roomler_ai_services::dao::base::ListOptions::default(),
+            )
             .await
             .map_err(|e| format!("Failed to fetch messages: {}", e))?;
 
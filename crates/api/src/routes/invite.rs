@@ -1,6 +1,6 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    extract::{Path, State},
     http::StatusCode,
 };
 use bson::oid::ObjectId;
@@ -9,14 +9,17 @@ use serde::{Deserialize, Serialize};
 use crate::{
     error::ApiError,
     extractors::auth::{AuthUser, OptionalAuthUser},
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    middleware::audit::{self, AuditContext, AuditEntry},
     state::AppState,
 };
 use roomler_ai_db::models::role::permissions;
-use roomler_ai_services::dao::{base::PaginationParams, invite::CreateInviteParams};
+use roomler_ai_services::dao::{base::PaginatedResult, invite::CreateInviteParams};
+use utoipa::ToSchema;
 
 // ─── Response types ──────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InviteInfoResponse {
     pub code: String,
     pub tenant_name: String,
@@ -28,12 +31,13 @@ pub struct InviteInfoResponse {
     pub already_member: Option<bool>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct InviteResponse {
     pub id: String,
     pub code: String,
     pub tenant_id: String,
     pub inviter_id: String,
+    pub room_id: Option<String>,
     pub target_email: Option<String>,
     pub max_uses: Option<u32>,
     pub use_count: u32,
@@ -43,7 +47,7 @@ pub struct InviteResponse {
     pub created_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AcceptInviteResponse {
     pub tenant_id: String,
     pub tenant_name: String,
@@ -52,28 +56,31 @@ pub struct AcceptInviteResponse {
 
 // ─── Request types ──────────────────────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateInviteRequest {
     pub target_email: Option<String>,
     pub max_uses: Option<u32>,
     pub expires_in_hours: Option<u64>,
     #[serde(default)]
     pub assign_role_ids: Vec<String>,
+    /// Also join the invitee to this room. Checked against the inviter's
+    /// permissions in that room rather than tenant-wide.
+    pub room_id: Option<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddMemberRequest {
     pub user_id: String,
     #[serde(default)]
     pub role_ids: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct BatchCreateInviteRequest {
     pub invites: Vec<CreateInviteRequest>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchInviteResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub invite: Option<InviteResponse>,
@@ -83,7 +90,7 @@ pub struct BatchInviteResult {
     pub target_email: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct BatchCreateInviteResponse {
     pub results: Vec<BatchInviteResult>,
     pub created: usize,
@@ -93,6 +100,14 @@ pub struct BatchCreateInviteResponse {
 // ─── Public handlers ────────────────────────────────────────────
 
 /// GET /api/invite/{code} — public invite info
+#[utoipa::path(
+    get,
+    path = "/api/invite/{code}",
+    tag = "invite",
+    params(("code" = String, Path)),
+    responses((status = 200, body = InviteInfoResponse)),
+    security((), ("bearer" = []))
+)]
 pub async fn get_invite_info(
     State(state): State<AppState>,
     optional_auth: OptionalAuthUser,
@@ -134,6 +149,13 @@ pub async fn get_invite_info(
 }
 
 /// POST /api/invite/{code}/accept — accept invite (requires auth)
+#[utoipa::path(
+    post,
+    path = "/api/invite/{code}/accept",
+    tag = "invite",
+    params(("code" = String, Path)),
+    responses((status = 200, body = AcceptInviteResponse))
+)]
 pub async fn accept_invite(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -166,6 +188,7 @@ pub async fn accept_invite(
             "Already a member of this tenant".to_string(),
         ));
     }
+    crate::middleware::plan_limits::check_members(&state, invite.tenant_id, auth.user_id).await?;
 
     // Determine roles to assign (default to "member" role if none specified)
     let role_ids = if invite.assign_role_ids.is_empty() {
@@ -189,6 +212,13 @@ pub async fn accept_invite(
         )
         .await?;
 
+    if let Some(rid) = invite.room_id {
+        state
+            .rooms
+            .join(invite.tenant_id, rid, auth.user_id)
+            .await?;
+    }
+
     // Atomically increment the use count
     state
         .invites
@@ -207,30 +237,56 @@ pub async fn accept_invite(
 
 // ─── Tenant-scoped handlers (require INVITE_MEMBERS) ───────────
 
+const INVITES_SPEC: ListSpec = ListSpec {
+    sort: &[
+        ("created_at", "created_at"),
+        ("expires_at", "expires_at"),
+        ("use_count", "use_count"),
+    ],
+    filters: &[
+        FilterField::new("status", "status", FieldKind::String),
+        FilterField::new("inviter_id", "inviter_id", FieldKind::ObjectId),
+        FilterField::new("room_id", "room_id", FieldKind::ObjectId),
+        FilterField::new("target_email", "target_email", FieldKind::String),
+        FilterField::new("expires_at", "expires_at", FieldKind::DateTime),
+    ],
+};
+
 /// GET /api/tenant/{tenant_id}/invite — list tenant invites
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/invite",
+    tag = "invite",
+    params(("tenant_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<InviteResponse>))
+)]
 pub async fn list_invites(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(tenant_id): Path<String>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<InviteResponse>>, ApiError> {
     let tid = parse_oid(&tenant_id)?;
     require_invite_permission(&state, tid, auth.user_id).await?;
 
-    let result = state.invites.list_by_tenant(tid, &params).await?;
-
-    let items: Vec<InviteResponse> = result.items.into_iter().map(invite_to_response).collect();
+    let options = query.options(&INVITES_SPEC)?;
+    let result = state
+        .invites
+        .list_by_tenant(tid, &query.pagination, &options)
+        .await?;
 
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+    Ok(Json(result.map(invite_to_response)))
 }
 
 /// POST /api/tenant/{tenant_id}/invite — create invite
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/invite",
+    tag = "invite",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateInviteRequest,
+    responses((status = 201, body = InviteResponse))
+)]
 pub async fn create_invite(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -238,7 +294,15 @@ pub async fn create_invite(
     Json(body): Json<CreateInviteRequest>,
 ) -> Result<(StatusCode, Json<InviteResponse>), ApiError> {
     let tid = parse_oid(&tenant_id)?;
-    require_invite_permission(&state, tid, auth.user_id).await?;
+    let room_id = match body.room_id.as_deref() {
+        Some(room_id) => {
+            Some(require_room_invite_permission(&state, tid, auth.user_id, room_id).await?)
+        }
+        None => {
+            require_invite_permission(&state, tid, auth.user_id).await?;
+            None
+        }
+    };
 
     let assign_role_ids: Vec<ObjectId> = body
         .assign_role_ids
@@ -260,6 +324,7 @@ pub async fn create_invite(
                 max_uses: body.max_uses,
                 expires_in_hours,
                 assign_role_ids,
+                room_id,
             },
         )
         .await?;
@@ -288,6 +353,14 @@ pub async fn create_invite(
 }
 
 /// POST /api/tenant/{tenant_id}/invite/batch — create multiple invites
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/invite/batch",
+    tag = "invite",
+    params(("tenant_id" = String, Path)),
+    request_body = BatchCreateInviteRequest,
+    responses((status = 201, body = BatchCreateInviteResponse))
+)]
 pub async fn batch_create_invite(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -311,9 +384,15 @@ pub async fn batch_create_invite(
     for item in body.invites {
         let assign_role_ids: Result<Vec<ObjectId>, _> =
             item.assign_role_ids.iter().map(|s| parse_oid(s)).collect();
+        let room_id = match item.room_id.as_deref() {
+            Some(room_id) => require_room_invite_permission(&state, tid, auth.user_id, room_id)
+                .await
+                .map(Some),
+            None => Ok(None),
+        };
 
-        match assign_role_ids {
-            Ok(role_ids) => {
+        match (assign_role_ids, room_id) {
+            (Ok(role_ids), Ok(room_id)) => {
                 let expires_in_hours = item.expires_in_hours.or(Some(168));
                 match state
                     .invites
@@ -325,6 +404,7 @@ pub async fn batch_create_invite(
                             max_uses: item.max_uses,
                             expires_in_hours,
                             assign_role_ids: role_ids,
+                            room_id,
                         },
                     )
                     .await
@@ -341,7 +421,7 @@ pub async fn batch_create_invite(
                     }),
                 }
             }
-            Err(e) => results.push(BatchInviteResult {
+            (Err(e), _) | (_, Err(e)) => results.push(BatchInviteResult {
                 invite: None,
                 error: Some(e.to_string()),
                 target_email: item.target_email,
@@ -363,21 +443,48 @@ pub async fn batch_create_invite(
 }
 
 /// DELETE /api/tenant/{tenant_id}/invite/{invite_id} — revoke invite
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/invite/{invite_id}",
+    tag = "invite",
+    params(("tenant_id" = String, Path), ("invite_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn revoke_invite(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, invite_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = parse_oid(&tenant_id)?;
     let iid = parse_oid(&invite_id)?;
     require_invite_permission(&state, tid, auth.user_id).await?;
 
+    let before = invite_to_response(state.invites.base.find_by_id_in_tenant(tid, iid).await?);
     state.invites.revoke(iid, tid).await?;
+    let after = invite_to_response(state.invites.base.find_by_id_in_tenant(tid, iid).await?);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "invite.revoke", "invite", Some(iid))
+            .before(&before)
+            .after(&after),
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "revoked": true })))
 }
 
 /// POST /api/tenant/{tenant_id}/member — direct add member
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/member",
+    tag = "invite",
+    params(("tenant_id" = String, Path)),
+    request_body = AddMemberRequest,
+    responses((status = 201, body = serde_json::Value))
+)]
 pub async fn add_member(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -393,6 +500,7 @@ pub async fn add_member(
     if state.tenants.is_member(tid, user_id).await? {
         return Err(ApiError::Conflict("User is already a member".to_string()));
     }
+    crate::middleware::plan_limits::check_members(&state, tid, auth.user_id).await?;
 
     let role_ids: Vec<ObjectId> = if body.role_ids.is_empty() {
         let member_role = state.tenants.get_role_by_name(tid, "member").await?;
@@ -431,8 +539,8 @@ async fn require_invite_permission(
     user_id: ObjectId,
 ) -> Result<(), ApiError> {
     let perms = state
-        .tenants
-        .get_member_permissions(tenant_id, user_id)
+        .permissions
+        .tenant_permissions(tenant_id, user_id)
         .await?;
     if !permissions::has(perms, permissions::INVITE_MEMBERS) {
         return Err(ApiError::Forbidden(
@@ -442,12 +550,29 @@ async fn require_invite_permission(
     Ok(())
 }
 
+/// Room-scoped invite: `INVITE_MEMBERS` resolved in the target room, so a
+/// room overwrite can grant (or withhold) it independently of the tenant.
+async fn require_room_invite_permission(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+    room_id: &str,
+) -> Result<ObjectId, ApiError> {
+    let rid = parse_oid(room_id)?;
+    state
+        .permissions
+        .require_room(tenant_id, rid, user_id, permissions::INVITE_MEMBERS)
+        .await?;
+    Ok(rid)
+}
+
 fn invite_to_response(invite: roomler_ai_db::models::Invite) -> InviteResponse {
     InviteResponse {
         id: invite.id.unwrap().to_hex(),
         code: invite.code,
         tenant_id: invite.tenant_id.to_hex(),
         inviter_id: invite.inviter_id.to_hex(),
+        room_id: invite.room_id.map(|id| id.to_hex()),
         target_email: invite.target_email,
         max_uses: invite.max_uses,
         use_count: invite.use_count,
@@ -1,138 +1,155 @@
 use axum::{
     Json,
     extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
 };
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::{Mentions, MessageAttachment};
-use roomler_ai_services::dao::base::PaginationParams;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    routes::retention,
+    state::AppState,
+};
+use roomler_ai_db::models::{
+    AuthorType, Mentions, MessageAttachment, ScheduledMessage, role::permissions,
+};
+use roomler_ai_services::dao::{
+    base::{PaginatedResult, PaginationParams},
+    message::CreateMessageParams,
+    user::UserCard,
+};
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Deserialize)]
-pub struct MentionRequest {
-    #[serde(default)]
-    pub users: Vec<String>,
-    #[serde(default)]
-    pub everyone: bool,
-    #[serde(default)]
-    pub here: bool,
-}
+pub use roomler_ai_types::message::{
+    AttachmentResponse, CreateMessageRequest, MentionRequest, MessageResponse,
+    ReactionSummaryResponse,
+};
 
-#[derive(Debug, Deserialize)]
-pub struct CreateMessageRequest {
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateMessageRequest {
     pub content: String,
-    pub thread_id: Option<String>,
-    pub referenced_message_id: Option<String>,
-    pub nonce: Option<String>,
-    pub mentions: Option<MentionRequest>,
-    #[serde(default)]
-    pub attachment_ids: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct UpdateMessageRequest {
-    pub content: String,
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ListMessagesParams {
+    /// Also return soft-deleted messages. Requires `MANAGE_MESSAGES`.
+    #[serde(default)]
+    pub include_deleted: bool,
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct AttachmentResponse {
-    pub file_id: String,
-    pub filename: String,
-    pub content_type: String,
-    pub size: u64,
-    pub url: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub thumbnail_url: Option<String>,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageEditResponse {
+    pub content: String,
+    pub edited_at: String,
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct MessageResponse {
-    pub id: String,
-    pub room_id: String,
-    pub author_id: String,
-    pub author_name: String,
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MessageHistoryResponse {
+    pub message_id: String,
     pub content: String,
-    pub message_type: String,
-    pub is_pinned: bool,
-    pub is_edited: bool,
-    pub is_thread_root: bool,
-    pub thread_id: Option<String>,
-    pub referenced_message_id: Option<String>,
-    pub reaction_summary: Vec<ReactionSummaryResponse>,
-    pub attachments: Vec<AttachmentResponse>,
-    pub is_read: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub reply_count: Option<u32>,
+    pub edits: Vec<MessageEditResponse>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_reply_at: Option<String>,
+    pub deleted_at: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub last_reply_user_id: Option<String>,
-    pub created_at: String,
-    pub updated_at: String,
+    pub deleted_by: Option<String>,
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct ReactionSummaryResponse {
-    pub emoji: String,
-    pub count: u32,
-}
+const LIST_SPEC: ListSpec = ListSpec {
+    sort: &[("created_at", "created_at"), ("updated_at", "updated_at")],
+    filters: &[
+        FilterField::new("author_id", "author_id", FieldKind::ObjectId),
+        FilterField::new("message_type", "message_type", FieldKind::String),
+        FilterField::new("is_pinned", "is_pinned", FieldKind::Bool),
+        FilterField::new("is_edited", "is_edited", FieldKind::Bool),
+        FilterField::new("content", "content", FieldKind::String),
+        FilterField::new("created_at", "created_at", FieldKind::DateTime),
+    ],
+};
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message",
+    tag = "message",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ListQuery,
+        ListMessagesParams,
+    ),
+    responses((status = 200, body = PaginatedResult<MessageResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((tenant_id, room_id)): Path<(String, String)>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    Query(params): Query<ListMessagesParams>,
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<MessageResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
+    if params.include_deleted {
+        state
+            .permissions
+            .require_room(tid, rid, auth.user_id, permissions::MANAGE_MESSAGES)
+            .await?;
+    } else if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    let result = state.messages.find_in_room(rid, &params).await?;
+    let options = query.options(&LIST_SPEC)?;
+    let result = state
+        .messages
+        .find_in_room(rid, params.include_deleted, &query.pagination, &options)
+        .await?;
 
     let author_ids = collect_author_ids(&result.items);
-    let names = state
+    let authors = state
         .users
-        .find_display_names(&author_ids)
+        .find_cards(&author_ids)
         .await
         .unwrap_or_default();
     let viewer_id = Some(auth.user_id);
 
-    let items: Vec<MessageResponse> = result
-        .items
-        .into_iter()
-        .map(|m| to_response(m, &names, viewer_id))
-        .collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+    Ok(Json(result.map(|m| to_response(m, &authors, viewer_id))))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreateMessageRequest,
+    responses(
+        (status = 200, body = MessageResponse),
+        (
+            status = 202,
+            description = "Scheduled for `send_at`",
+            body = super::scheduled_message::ScheduledMessageResponse
+        ),
+    )
+)]
 pub async fn create(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((tenant_id, room_id)): Path<(String, String)>,
     Json(body): Json<CreateMessageRequest>,
-) -> Result<Json<MessageResponse>, ApiError> {
+) -> Result<Response, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    require_can_post(&state, tid, rid, auth.user_id, body.thread_id.is_some()).await?;
+    crate::middleware::rate_limit::check_message(&state, tid, auth.user_id).await?;
 
     let thread_id = body
         .thread_id
@@ -140,6 +157,11 @@ pub async fn create(
         .map(ObjectId::parse_str)
         .transpose()
         .map_err(|_| ApiError::BadRequest("Invalid thread_id".to_string()))?;
+    if let Some(parent_id) = thread_id
+        && state.messages.is_archived(parent_id).await?
+    {
+        return Err(ApiError::Conflict("Thread is archived".to_string()));
+    }
 
     let ref_msg_id = body
         .referenced_message_id
@@ -148,68 +170,165 @@ pub async fn create(
         .transpose()
         .map_err(|_| ApiError::BadRequest("Invalid referenced_message_id".to_string()))?;
 
+    let send_at = parse_send_at(body.send_at.as_deref())?;
+
     // Parse mentions from request
-    let mentions = if let Some(ref mention_req) = body.mentions {
-        let user_ids: Vec<ObjectId> = mention_req
+    let mentions = body.mentions.as_ref().map(|mention_req| Mentions {
+        users: mention_req
             .users
             .iter()
             .filter_map(|s| ObjectId::parse_str(s).ok())
-            .collect();
-        Some(Mentions {
-            users: user_ids,
-            roles: Vec::new(),
-            rooms: Vec::new(),
-            everyone: mention_req.everyone,
-            here: mention_req.here,
-        })
-    } else {
-        None
-    };
+            .collect(),
+        roles: Vec::new(),
+        rooms: Vec::new(),
+        everyone: mention_req.everyone,
+        here: mention_req.here,
+    });
 
     // Fetch file records for attachments (tenant-scoped to prevent cross-tenant access)
-    let attachments = if !body.attachment_ids.is_empty() {
-        let mut att = Vec::new();
-        for file_id_str in &body.attachment_ids {
-            if let Ok(fid) = ObjectId::parse_str(file_id_str)
-                && let Ok(file) = state.files.base.find_by_id_in_tenant(tid, fid).await
-            {
-                att.push(MessageAttachment {
-                    file_id: file.id.unwrap(),
-                    filename: file.filename,
-                    content_type: file.content_type,
-                    size: file.size,
-                    url: file.url,
-                    thumbnail_url: file.thumbnails.first().map(|t| t.url.clone()),
-                    is_spoiler: false,
-                });
-            }
+    let mut attachments = Vec::new();
+    for file_id_str in &body.attachment_ids {
+        if let Ok(fid) = ObjectId::parse_str(file_id_str)
+            && let Ok(file) = state.files.base.find_by_id_in_tenant(tid, fid).await
+        {
+            attachments.push(MessageAttachment {
+                file_id: file.id.unwrap(),
+                filename: file.filename,
+                content_type: file.content_type,
+                size: file.size,
+                url: file.url,
+                thumbnail_url: file.thumbnails.first().map(|t| t.url.clone()),
+                is_spoiler: false,
+            });
         }
-        att
-    } else {
-        Vec::new()
-    };
+    }
 
-    let message = state
-        .messages
-        .create_with_attachments(
-            tid,
-            rid,
-            auth.user_id,
-            body.content.clone(),
+    if let Some(send_at) = send_at {
+        let now = bson::DateTime::now();
+        let scheduled = state
+            .scheduled_messages
+            .create(&ScheduledMessage {
+                id: None,
+                tenant_id: tid,
+                room_id: rid,
+                author_id: auth.user_id,
+                content: body.content,
+                thread_id,
+                referenced_message_id: ref_msg_id,
+                mentions: mentions.unwrap_or_default(),
+                attachments,
+                is_silent: body.silent,
+                send_at,
+                created_at: now,
+            })
+            .await?;
+        let response = super::scheduled_message::to_response(scheduled);
+        return Ok((StatusCode::ACCEPTED, Json(response)).into_response());
+    }
+
+    let response = deliver(
+        &state,
+        CreateMessageParams {
+            tenant_id: tid,
+            room_id: rid,
+            author_id: auth.user_id,
+            author_type: if auth.bot.is_some() {
+                AuthorType::Bot
+            } else {
+                AuthorType::User
+            },
+            author_name: None,
+            content: body.content,
             thread_id,
-            ref_msg_id,
-            body.nonce,
+            referenced_message_id: ref_msg_id,
+            nonce: body.nonce,
             mentions,
             attachments,
-        )
+            is_silent: body.silent,
+        },
+    )
+    .await?;
+
+    Ok(Json(response).into_response())
+}
+
+/// `Ok` when the member may post in the room: they hold the send permission
+/// and the room takes posts from them. Nobody posts in a read-only room; an
+/// announcement room only takes posts from members with `MANAGE_CHANNELS`.
+pub(crate) async fn require_can_post(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    user_id: ObjectId,
+    is_thread_reply: bool,
+) -> Result<(), ApiError> {
+    let perms = state
+        .permissions
+        .require_room(tid, rid, user_id, send_permission(is_thread_reply))
         .await?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.is_read_only {
+        return Err(ApiError::RoomReadOnly("This room is read-only".to_string()));
+    }
+    if room.is_announcement && !permissions::has(perms, permissions::MANAGE_CHANNELS) {
+        return Err(ApiError::AnnouncementOnly(
+            "Only organizers can post in this announcement room".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// `SEND_THREADS` for thread replies, `SEND_MESSAGES` otherwise.
+fn send_permission(is_thread_reply: bool) -> u64 {
+    if is_thread_reply {
+        permissions::SEND_THREADS
+    } else {
+        permissions::SEND_MESSAGES
+    }
+}
+
+/// `Some` only for a time far enough ahead to be worth scheduling; a past or
+/// imminent `send_at` posts right away.
+fn parse_send_at(send_at: Option<&str>) -> Result<Option<bson::DateTime>, ApiError> {
+    let Some(raw) = send_at else {
+        return Ok(None);
+    };
+    let at = bson::DateTime::parse_rfc3339_str(raw)
+        .map_err(|_| ApiError::Validation("send_at must be an RFC 3339 timestamp".to_string()))?;
+    let now = bson::DateTime::now().timestamp_millis();
+    if at.timestamp_millis() - now > MAX_SCHEDULE_AHEAD_MS {
+        return Err(ApiError::Validation(
+            "send_at can be at most one year ahead".to_string(),
+        ));
+    }
+    Ok((at.timestamp_millis() > now + 1000).then_some(at))
+}
+
+const MAX_SCHEDULE_AHEAD_MS: i64 = 365 * 24 * 60 * 60 * 1000;
+
+/// Persist a message and fan it out: WS `message:create`, the thread parent's
+/// `message:update`, and — unless silent — follower and mention
+/// notifications. Messages from users and bots also go to outgoing webhooks.
+pub(crate) async fn deliver(
+    state: &AppState,
+    params: CreateMessageParams,
+) -> Result<MessageResponse, ApiError> {
+    let (tid, rid, author_id) = (params.tenant_id, params.room_id, params.author_id);
+    let (tenant_id, room_id) = (tid.to_hex(), rid.to_hex());
+    let content = params.content.clone();
+    let thread_id = params.thread_id;
+    let mentions = params.mentions.clone();
+    let is_silent = params.is_silent;
+    let author_type = params.author_type.clone();
+
+    let message = state.messages.create(params).await?;
 
     let message_id = message.id.unwrap();
 
-    // Fetch author display name for the response
-    let names = state
+    // Fetch the author's name, avatar and status for the response
+    let authors = state
         .users
-        .find_display_names(&[auth.user_id])
+        .find_cards(&[author_id])
         .await
         .unwrap_or_default();
 
@@ -217,64 +336,99 @@ pub async fn create(
     let all_member_ids = state.rooms.find_member_user_ids(rid).await?;
     let member_ids_excluding_sender: Vec<ObjectId> = all_member_ids
         .iter()
-        .filter(|id| **id != auth.user_id)
+        .filter(|id| **id != author_id)
         .copied()
         .collect();
 
-    // Broadcast via WebSocket to room members (exclude sender)
-    let response = to_response(message, &names, Some(auth.user_id));
+    // Confirm the nonce to the author, then broadcast to the whole room. The
+    // author's other devices pick the message up from `message:create`; the
+    // sending tab already has it from the ack (and the HTTP response).
+    let response = to_response(message, &authors, Some(author_id));
+    if let Some(nonce) = &response.nonce {
+        let ack = serde_json::json!({
+            "type": "message:ack",
+            "data": {
+                "room_id": room_id,
+                "nonce": nonce,
+                "id": response.id,
+                "created_at": response.created_at,
+            }
+        });
+        crate::ws::dispatcher::send_to_user_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &author_id,
+            &ack,
+        )
+        .await;
+    }
     let event = serde_json::json!({
         "type": "message:create",
         "data": &response,
     });
-    crate::ws::dispatcher::broadcast_with_redis(
-        &state.ws_storage,
-        &state.redis_pubsub,
-        &member_ids_excluding_sender,
-        &event,
-    )
-    .await;
+    crate::ws::event_log::publish(state, tid, rid, &all_member_ids, event).await;
+
+    // Mentioned users: @everyone means all room members except the sender
+    let mentioned_user_ids: Vec<ObjectId> = match mentions {
+        Some(ref m) if m.everyone => member_ids_excluding_sender.clone(),
+        Some(ref m) => m
+            .users
+            .iter()
+            .filter(|id| **id != author_id)
+            .copied()
+            .collect(),
+        None => Vec::new(),
+    };
+    let author_name = response.author_name.clone();
 
     // If this was a thread reply, broadcast an update for the parent message
-    // so other users see the updated is_thread_root + reply_count
+    // so other users see the updated is_thread_root + reply_count, and notify
+    // the thread's followers
     if let Some(parent_id) = thread_id
         && let Ok(parent_msg) = state.messages.base.find_by_id(parent_id).await
     {
+        if !is_silent {
+            // Mentioned users get the mention notification instead
+            let follower_ids: Vec<ObjectId> = parent_msg
+                .thread_metadata
+                .as_ref()
+                .map(|tm| tm.follower_ids.clone())
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|id| !mentioned_user_ids.contains(id) && all_member_ids.contains(id))
+                .collect();
+            super::helpers::notify_thread_followers(
+                state,
+                tid,
+                rid,
+                parent_id,
+                author_id,
+                &follower_ids,
+                &author_name,
+                &content,
+                &tenant_id,
+                &room_id,
+            )
+            .await;
+        }
+
         let parent_author_ids = vec![parent_msg.author_id];
-        let parent_names = state
+        let parent_authors = state
             .users
-            .find_display_names(&parent_author_ids)
+            .find_cards(&parent_author_ids)
             .await
             .unwrap_or_default();
-        let parent_response = to_response(parent_msg, &parent_names, None);
+        let parent_response = to_response(parent_msg, &parent_authors, None);
         let parent_event = serde_json::json!({
             "type": "message:update",
             "data": &parent_response,
         });
         // Broadcast to ALL members (including sender, so sender's UI also updates)
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &all_member_ids,
-            &parent_event,
-        )
-        .await;
+        crate::ws::event_log::publish(state, tid, rid, &all_member_ids, parent_event).await;
     }
 
     // Create notifications for mentioned users via helper
-    if let Some(ref mention_req) = body.mentions {
-        let mentioned_user_ids: Vec<ObjectId> = if mention_req.everyone {
-            // @everyone: notify all room members except sender
-            member_ids_excluding_sender.clone()
-        } else {
-            mention_req
-                .users
-                .iter()
-                .filter_map(|s| ObjectId::parse_str(s).ok())
-                .filter(|id| *id != auth.user_id)
-                .collect()
-        };
-
+    if !is_silent && !mentioned_user_ids.is_empty() {
         let room_name = state
             .rooms
             .base
@@ -283,30 +437,39 @@ pub async fn create(
             .map(|r| r.name)
             .unwrap_or_default();
 
-        let mentioner_name = names
-            .get(&auth.user_id)
-            .cloned()
-            .unwrap_or_else(|| auth.user_id.to_hex());
-
         super::helpers::notify_mentions(
-            &state,
+            state,
             tid,
             rid,
             message_id,
-            auth.user_id,
+            author_id,
             &mentioned_user_ids,
             &room_name,
-            &body.content,
-            &mentioner_name,
+            &content,
+            &author_name,
             &tenant_id,
             &room_id,
         )
         .await;
     }
 
-    Ok(Json(response))
+    // Webhook-authored messages are not echoed back out, so a pair of hooks
+    // can't loop
+    if !matches!(author_type, AuthorType::Webhook) {
+        super::webhook::dispatch_outgoing(state, tid, rid, &response);
+    }
+
+    Ok(response)
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    request_body = UpdateMessageRequest,
+    responses((status = 200, body = MessageResponse))
+)]
 pub async fn update(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -331,12 +494,12 @@ pub async fn update(
 
     // Re-fetch the updated message for the full response
     let updated = state.messages.base.find_by_id(mid).await?;
-    let names = state
+    let authors = state
         .users
-        .find_display_names(&[updated.author_id])
+        .find_cards(&[updated.author_id])
         .await
         .unwrap_or_default();
-    let response = to_response(updated, &names, Some(auth.user_id));
+    let response = to_response(updated, &authors, Some(auth.user_id));
 
     // Broadcast full message to room members (exclude sender)
     let member_ids: Vec<ObjectId> = state
@@ -350,17 +513,18 @@ pub async fn update(
         "type": "message:update",
         "data": &response,
     });
-    crate::ws::dispatcher::broadcast_with_redis(
-        &state.ws_storage,
-        &state.redis_pubsub,
-        &member_ids,
-        &event,
-    )
-    .await;
+    crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
 
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -373,19 +537,26 @@ pub async fn delete(
     let mid = ObjectId::parse_str(&message_id)
         .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    let perms = state
+        .permissions
+        .room_permissions(tid, rid, auth.user_id)
+        .await?;
 
-    // Verify ownership: only the author can delete their message (tenant-scoped)
+    // Authors can delete their own messages; anyone else needs MANAGE_MESSAGES
+    // in the room (tenant-scoped lookup).
     let message = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
-    if message.author_id != auth.user_id {
+    if message.author_id != auth.user_id && !permissions::has(perms, permissions::MANAGE_MESSAGES) {
         return Err(ApiError::Forbidden(
-            "Only the author can delete this message".to_string(),
+            "Only the author or a member with MANAGE_MESSAGES can delete this message".to_string(),
         ));
     }
 
-    state.messages.base.soft_delete_in_tenant(tid, mid).await?;
+    state.messages.soft_delete(tid, mid, auth.user_id).await?;
+    if let Some(parent_id) = message.thread_id
+        && let Err(e) = state.messages.refresh_thread_metadata(parent_id).await
+    {
+        tracing::warn!(%parent_id, %e, "Failed to refresh thread metadata");
+    }
 
     let member_ids: Vec<ObjectId> = state
         .rooms
@@ -401,17 +572,92 @@ pub async fn delete(
             "room_id": room_id,
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
-        &state.ws_storage,
-        &state.redis_pubsub,
-        &member_ids,
-        &event,
-    )
-    .await;
+    crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Undo a message's delete within `retention.deleted_grace_days`. Authors can
+/// restore what they deleted themselves; anything else needs `MANAGE_MESSAGES`.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/restore",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    responses((status = 200, body = MessageResponse))
+)]
+pub async fn restore(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mid = ObjectId::parse_str(&message_id)
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+
+    let perms = state
+        .permissions
+        .room_permissions(tid, rid, auth.user_id)
+        .await?;
+
+    let message = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
+    if message.room_id != rid {
+        return Err(ApiError::NotFound("Message not found".to_string()));
+    }
+    let Some(deleted_at) = message.deleted_at else {
+        return Err(ApiError::Conflict("Message is not deleted".to_string()));
+    };
+    let own = message.author_id == auth.user_id && message.deleted_by == Some(auth.user_id);
+    if !own && !permissions::has(perms, permissions::MANAGE_MESSAGES) {
+        return Err(ApiError::Forbidden(
+            "Only the author or a member with MANAGE_MESSAGES can restore this message".to_string(),
+        ));
+    }
+    if !retention::restorable(&state, deleted_at) {
+        return Err(ApiError::Conflict("Restore window has passed".to_string()));
+    }
+
+    state.messages.restore(tid, mid).await?;
+    if let Some(parent_id) = message.thread_id
+        && let Err(e) = state.messages.refresh_thread_metadata(parent_id).await
+    {
+        tracing::warn!(%parent_id, %e, "Failed to refresh thread metadata");
+    }
+
+    let restored = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
+    let authors = state
+        .users
+        .find_cards(&[restored.author_id])
+        .await
+        .unwrap_or_default();
+    let response = to_response(restored, &authors, Some(auth.user_id));
+
+    let member_ids: Vec<ObjectId> = state
+        .rooms
+        .find_member_user_ids(rid)
+        .await?
+        .into_iter()
+        .filter(|id| *id != auth.user_id)
+        .collect();
+    let event = serde_json::json!({
+        "type": "message:restore",
+        "data": &response,
+    });
+    crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
+
+    Ok(Json(response))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/pin",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<MessageResponse>))
+)]
 pub async fn pinned(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -428,24 +674,87 @@ pub async fn pinned(
 
     let messages = state.messages.find_pinned(rid).await?;
     let author_ids = collect_author_ids(&messages);
-    let names = state
+    let authors = state
         .users
-        .find_display_names(&author_ids)
+        .find_cards(&author_ids)
         .await
         .unwrap_or_default();
     let response: Vec<MessageResponse> = messages
         .into_iter()
-        .map(|m| to_response(m, &names, Some(auth.user_id)))
+        .map(|m| to_response(m, &authors, Some(auth.user_id)))
         .collect();
 
     Ok(Json(response))
 }
 
-#[derive(Debug, Deserialize)]
+/// GET /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/history —
+/// the message's previous versions. Visible to the author and to members with
+/// `MANAGE_MESSAGES`; once deleted, to the latter only.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/history",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    responses((status = 200, body = MessageHistoryResponse))
+)]
+pub async fn history(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<MessageHistoryResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mid = ObjectId::parse_str(&message_id)
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+
+    let perms = state
+        .permissions
+        .room_permissions(tid, rid, auth.user_id)
+        .await?;
+    let message = state.messages.find_any_in_tenant(tid, mid).await?;
+    if message.room_id != rid {
+        return Err(ApiError::NotFound("Message not found".to_string()));
+    }
+    let is_moderator = permissions::has(perms, permissions::MANAGE_MESSAGES);
+    let is_author = message.author_id == auth.user_id && message.deleted_at.is_none();
+    if !is_moderator && !is_author {
+        return Err(ApiError::Forbidden(
+            "Only the author or a member with MANAGE_MESSAGES can view edit history".to_string(),
+        ));
+    }
+
+    let rfc3339 = |d: bson::DateTime| d.try_to_rfc3339_string().unwrap_or_default();
+    Ok(Json(MessageHistoryResponse {
+        message_id,
+        content: message.content,
+        edits: message
+            .edit_history
+            .into_iter()
+            .map(|e| MessageEditResponse {
+                content: e.content,
+                edited_at: rfc3339(e.edited_at),
+            })
+            .collect(),
+        deleted_at: message.deleted_at.map(rfc3339),
+        deleted_by: message.deleted_by.map(|u| u.to_hex()),
+    }))
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TogglePinRequest {
     pub pinned: bool,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/pin",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    request_body = TogglePinRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn toggle_pin(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -459,9 +768,10 @@ pub async fn toggle_pin(
     let mid = ObjectId::parse_str(&message_id)
         .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MESSAGES)
+        .await?;
 
     state.messages.toggle_pin(tid, mid, body.pinned).await?;
 
@@ -474,17 +784,23 @@ pub async fn toggle_pin(
             "pinned": body.pinned,
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
-        &state.ws_storage,
-        &state.redis_pubsub,
-        &member_ids,
-        &event,
-    )
-    .await;
+    crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
 
     Ok(Json(serde_json::json!({ "pinned": body.pinned })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread",
+    tag = "message",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("message_id" = String, Path),
+        PaginationParams,
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn thread_replies(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -503,9 +819,9 @@ pub async fn thread_replies(
     let result = state.messages.find_thread_replies(mid, &params).await?;
 
     let author_ids = collect_author_ids(&result.items);
-    let names = state
+    let authors = state
         .users
-        .find_display_names(&author_ids)
+        .find_cards(&author_ids)
         .await
         .unwrap_or_default();
     let viewer_id = Some(auth.user_id);
@@ -513,7 +829,7 @@ pub async fn thread_replies(
     let items: Vec<MessageResponse> = result
         .items
         .into_iter()
-        .map(|m| to_response(m, &names, viewer_id))
+        .map(|m| to_response(m, &authors, viewer_id))
         .collect();
 
     Ok(Json(serde_json::json!({
@@ -525,38 +841,118 @@ pub async fn thread_replies(
     })))
 }
 
+/// PUT/DELETE /api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/follow
+/// — follow or unfollow a thread root for reply notifications.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/follow",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn follow_thread(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_thread_follow(&state, &auth, &tenant_id, &room_id, &message_id, true).await
+}
+
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/follow",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn unfollow_thread(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, message_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_thread_follow(&state, &auth, &tenant_id, &room_id, &message_id, false).await
+}
+
+async fn set_thread_follow(
+    state: &AppState,
+    auth: &AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+    message_id: &str,
+    follow: bool,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mid = ObjectId::parse_str(message_id)
+        .map_err(|_| ApiError::BadRequest("Invalid message_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::VIEW_CHANNELS)
+        .await?;
+
+    let message = state.messages.base.find_by_id_in_tenant(tid, mid).await?;
+    if message.room_id != rid {
+        return Err(ApiError::NotFound("Message not found".to_string()));
+    }
+    if message.thread_id.is_some() {
+        return Err(ApiError::Validation(
+            "Only thread roots can be followed".to_string(),
+        ));
+    }
+
+    state
+        .messages
+        .set_thread_follow(tid, mid, auth.user_id, follow)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "following": follow })))
+}
+
 fn to_response(
     m: roomler_ai_db::models::Message,
-    names: &HashMap<ObjectId, String>,
+    authors: &HashMap<ObjectId, UserCard>,
     viewer_id: Option<ObjectId>,
 ) -> MessageResponse {
-    let author_name = names
-        .get(&m.author_id)
-        .cloned()
+    let card = authors.get(&m.author_id);
+    let author_name = m
+        .author_name
+        .clone()
+        .or_else(|| card.map(|c| c.display_name.clone()))
         .unwrap_or_else(|| m.author_id.to_hex());
     let is_read = viewer_id.is_some_and(|uid| m.readby.iter().any(|r| r == &uid));
-    let (reply_count, last_reply_at, last_reply_user_id) = match &m.thread_metadata {
+    let (reply_count, last_reply_at, last_reply_user_id, is_following) = match &m.thread_metadata {
         Some(tm) => (
             Some(tm.reply_count),
             tm.last_reply_at
                 .as_ref()
                 .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
             tm.last_reply_user_id.map(|u| u.to_hex()),
+            viewer_id.map(|uid| tm.follower_ids.contains(&uid)),
         ),
-        None => (None, None, None),
+        None => (None, None, None, None),
     };
     MessageResponse {
         id: m.id.unwrap().to_hex(),
         room_id: m.room_id.to_hex(),
         author_id: m.author_id.to_hex(),
         author_name,
+        author_avatar: card.and_then(|c| c.avatar.clone()),
+        author_status: card
+            .and_then(|c| c.status.as_ref())
+            .map(super::user::status_response),
+        author_type: format!("{:?}", m.author_type).to_lowercase(),
         content: m.content,
         message_type: format!("{:?}", m.message_type),
         is_pinned: m.is_pinned,
+        is_silent: m.is_silent,
         is_edited: m.is_edited,
         is_thread_root: m.is_thread_root,
         thread_id: m.thread_id.map(|t| t.to_hex()),
         referenced_message_id: m.referenced_message_id.map(|r| r.to_hex()),
+        nonce: m.nonce,
         reaction_summary: m
             .reaction_summary
             .into_iter()
@@ -581,6 +977,11 @@ fn to_response(
         reply_count,
         last_reply_at,
         last_reply_user_id,
+        is_following,
+        deleted_at: m
+            .deleted_at
+            .map(|d| d.try_to_rfc3339_string().unwrap_or_default()),
+        deleted_by: m.deleted_by.map(|u| u.to_hex()),
         created_at: m.created_at.try_to_rfc3339_string().unwrap_or_default(),
         updated_at: m.updated_at.try_to_rfc3339_string().unwrap_or_default(),
     }
@@ -594,11 +995,19 @@ fn collect_author_ids(messages: &[roomler_ai_db::models::Message]) -> Vec<Object
     ids
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct MarkReadRequest {
     pub message_ids: Vec<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/read",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = MarkReadRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn mark_read(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -628,6 +1037,13 @@ pub async fn mark_read(
     Ok(Json(serde_json::json!({ "marked": modified })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/unread-count",
+    tag = "message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn unread_count(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -2,12 +2,23 @@ pub mod admin;
 pub mod agent_crash;
 pub mod agent_log;
 pub mod agent_release;
+pub mod asset;
+pub mod assistant;
 pub mod auth;
 pub mod background_task;
+pub mod bot;
+pub mod breakout;
+pub mod calendar;
+pub mod call_audio;
+pub mod call_debug;
+pub mod call_schedule;
+pub mod call_transcription;
 pub mod consent;
 pub mod export;
+pub mod feature_flag;
 pub mod file;
 pub mod giphy;
+pub mod health;
 pub(crate) mod helpers;
 pub mod integration;
 pub mod invite;
@@ -15,17 +26,30 @@ pub mod message;
 pub mod notification;
 pub mod oauth;
 pub mod overlay_route;
+pub mod poll;
 pub mod push;
+pub mod question;
 pub mod reaction;
 pub mod recording;
+pub mod recording_consent;
 pub mod remote_control;
+pub mod retention;
 pub mod role;
 pub mod room;
+pub mod scheduled_message;
 pub mod setup_release;
+pub mod sidebar;
+pub mod slash_command;
 pub mod stripe;
 pub mod tenant;
+pub mod tenant_domain;
 pub mod tunnel;
 pub mod tunnel_release;
+pub mod usage;
+pub mod webhook;
+pub mod webinar;
+pub mod whiteboard;
+pub mod ws;
 
 pub mod search;
 pub mod user;
@@ -1,66 +1,115 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    extract::{Path, State},
 };
 use bson::oid::ObjectId;
-use serde::Serialize;
-
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_services::dao::base::PaginationParams;
-
-#[derive(Debug, Serialize)]
-pub struct NotificationResponse {
-    pub id: String,
-    pub notification_type: String,
-    pub title: String,
-    pub body: String,
-    pub link: Option<String>,
-    pub is_read: bool,
-    pub created_at: String,
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    state::AppState,
+};
+use roomler_ai_db::models::{NotificationLevel, NotificationPrefs, QuietHours};
+use roomler_ai_services::dao::base::PaginatedResult;
+use roomler_ai_services::notification_prefs;
+use utoipa::ToSchema;
+
+pub use roomler_ai_types::notification::NotificationResponse;
+
+/// The user's notification preferences, replaced as a whole on update.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct NotificationPreferencesBody {
+    pub email: bool,
+    pub push: bool,
+    pub desktop: bool,
+    /// No notifications at all.
+    pub mute_all: bool,
+    /// No push or email inside this daily window.
+    pub quiet_hours: Option<QuietHoursBody>,
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct QuietHoursBody {
+    /// `HH:MM` local time.
+    pub start: String,
+    /// `HH:MM` local time; earlier than `start` wraps past midnight.
+    pub end: String,
+    /// The user's offset from UTC, -840 to 840.
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
 }
 
+/// The caller's notification settings for one room.
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct RoomNotificationPreferences {
+    /// `all`, `mentions` or `nothing`; `null` follows the tenant-wide setting.
+    #[schema(value_type = Option<String>)]
+    pub level: Option<NotificationLevel>,
+    /// No notifications from the room.
+    #[serde(default)]
+    pub muted: bool,
+}
+
+const LIST_SPEC: ListSpec = ListSpec {
+    sort: &[("created_at", "created_at")],
+    filters: &[
+        FilterField::new("notification_type", "notification_type", FieldKind::String),
+        FilterField::new("is_read", "is_read", FieldKind::Bool),
+        FilterField::new("tenant_id", "tenant_id", FieldKind::ObjectId),
+        FilterField::new("created_at", "created_at", FieldKind::DateTime),
+    ],
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/notification",
+    tag = "notification",
+    params(ListQuery),
+    responses((status = 200, body = PaginatedResult<NotificationResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<NotificationResponse>>, ApiError> {
+    let options = query.options(&LIST_SPEC)?;
     let result = state
         .notifications
-        .find_for_user(auth.user_id, &params)
+        .find_for_user(auth.user_id, &query.pagination, &options)
         .await?;
 
-    let items: Vec<NotificationResponse> = result.items.into_iter().map(to_response).collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+    Ok(Json(result.map(to_response)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/notification/unread",
+    tag = "notification",
+    params(ListQuery),
+    responses((status = 200, body = PaginatedResult<NotificationResponse>))
+)]
 pub async fn unread(
     State(state): State<AppState>,
     auth: AuthUser,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<NotificationResponse>>, ApiError> {
+    let options = query.options(&LIST_SPEC)?;
     let result = state
         .notifications
-        .find_unread_for_user(auth.user_id, &params)
+        .find_unread_for_user(auth.user_id, &query.pagination, &options)
         .await?;
 
-    let items: Vec<NotificationResponse> = result.items.into_iter().map(to_response).collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+    Ok(Json(result.map(to_response)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/notification/unread-count",
+    tag = "notification",
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn unread_count(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -69,6 +118,13 @@ pub async fn unread_count(
     Ok(Json(serde_json::json!({ "count": count })))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/notification/{notification_id}/read",
+    tag = "notification",
+    params(("notification_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn mark_read(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -82,6 +138,12 @@ pub async fn mark_read(
     Ok(Json(serde_json::json!({ "read": true })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/notification/read-all",
+    tag = "notification",
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn mark_all_read(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -90,6 +152,147 @@ pub async fn mark_all_read(
     Ok(Json(serde_json::json!({ "marked": count })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/notification/preferences",
+    tag = "notification",
+    responses((status = 200, body = NotificationPreferencesBody))
+)]
+pub async fn get_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<NotificationPreferencesBody>, ApiError> {
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    Ok(Json(user.notification_preferences.into()))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/notification/preferences",
+    tag = "notification",
+    request_body = NotificationPreferencesBody,
+    responses((status = 200, body = NotificationPreferencesBody))
+)]
+pub async fn update_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<NotificationPreferencesBody>,
+) -> Result<Json<NotificationPreferencesBody>, ApiError> {
+    if let Some(quiet) = &body.quiet_hours {
+        for time in [&quiet.start, &quiet.end] {
+            if notification_prefs::parse_time(time).is_none() {
+                return Err(ApiError::Validation(format!(
+                    "Invalid quiet hours time '{time}', expected HH:MM"
+                )));
+            }
+        }
+        if !(-840..=840).contains(&quiet.utc_offset_minutes) {
+            return Err(ApiError::Validation(
+                "utc_offset_minutes must be between -840 and 840".to_string(),
+            ));
+        }
+    }
+
+    let prefs = NotificationPrefs {
+        email: body.email,
+        push: body.push,
+        desktop: body.desktop,
+        mute_all: body.mute_all,
+        quiet_hours: body.quiet_hours.map(|q| QuietHours {
+            start: q.start,
+            end: q.end,
+            utc_offset_minutes: q.utc_offset_minutes,
+        }),
+    };
+    state
+        .users
+        .set_notification_preferences(auth.user_id, &prefs)
+        .await?;
+
+    Ok(Json(prefs.into()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/notification-preferences",
+    tag = "notification",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = RoomNotificationPreferences))
+)]
+pub async fn get_room_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomNotificationPreferences>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let member = state
+        .rooms
+        .find_membership(tid, rid, auth.user_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Not a member of this room".to_string()))?;
+
+    Ok(Json(RoomNotificationPreferences {
+        level: member.notification_override,
+        muted: member.is_muted,
+    }))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/notification-preferences",
+    tag = "notification",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = RoomNotificationPreferences,
+    responses((status = 200, body = RoomNotificationPreferences))
+)]
+pub async fn update_room_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<RoomNotificationPreferences>,
+) -> Result<Json<RoomNotificationPreferences>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let updated = state
+        .rooms
+        .set_notification_preferences(
+            tid,
+            rid,
+            auth.user_id,
+            Some(body.level.clone()),
+            Some(body.muted),
+        )
+        .await?;
+    if !updated {
+        return Err(ApiError::NotFound("Not a member of this room".to_string()));
+    }
+
+    Ok(Json(body))
+}
+
+impl From<NotificationPrefs> for NotificationPreferencesBody {
+    fn from(p: NotificationPrefs) -> Self {
+        Self {
+            email: p.email,
+            push: p.push,
+            desktop: p.desktop,
+            mute_all: p.mute_all,
+            quiet_hours: p.quiet_hours.map(|q| QuietHoursBody {
+                start: q.start,
+                end: q.end,
+                utc_offset_minutes: q.utc_offset_minutes,
+            }),
+        }
+    }
+}
+
 fn to_response(n: roomler_ai_db::models::Notification) -> NotificationResponse {
     NotificationResponse {
         id: n.id.unwrap().to_hex(),
@@ -1,19 +1,60 @@
 use axum::{
+    Json,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode, header},
     response::{IntoResponse, Redirect, Response},
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 
 use crate::{error::ApiError, state::AppState};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct CallbackQuery {
     pub code: String,
     pub state: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct OAuthProviderResponse {
+    pub name: String,
+    pub label: String,
+}
+
+/// Lists the providers with credentials configured, so the login page can
+/// render a button per provider — including the generic `custom` OIDC one.
+#[utoipa::path(
+    get,
+    path = "/api/oauth/providers",
+    tag = "oauth",
+    responses((status = 200, body = Vec<OAuthProviderResponse>)),
+    security(())
+)]
+pub async fn providers(State(state): State<AppState>) -> Json<Vec<OAuthProviderResponse>> {
+    let providers = state
+        .oauth
+        .as_ref()
+        .map(|oauth| oauth.providers())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|p| OAuthProviderResponse {
+            name: p.name,
+            label: p.label,
+        })
+        .collect();
+    Json(providers)
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/oauth/{provider}",
+    tag = "oauth",
+    params(("provider" = String, Path)),
+    responses((status = 307, description = "Redirect to the provider's consent screen")),
+    security(())
+)]
 pub async fn oauth_redirect(
     State(state): State<AppState>,
     Path(provider): Path<String>,
@@ -29,11 +70,20 @@ pub async fn oauth_redirect(
 
     let auth_url = oauth
         .build_auth_url(&provider, &csrf_state)
+        .await
         .map_err(|e| ApiError::BadRequest(e.to_string()))?;
 
     Ok(Redirect::temporary(&auth_url).into_response())
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/oauth/callback/{provider}",
+    tag = "oauth",
+    params(("provider" = String, Path), CallbackQuery),
+    responses((status = 302, description = "Sets the session cookies and redirects to the app")),
+    security(())
+)]
 pub async fn oauth_callback(
     State(state): State<AppState>,
     Path(provider): Path<String>,
@@ -13,10 +13,11 @@ use axum::{
 use bson::oid::ObjectId;
 use roomler_ai_remote_control::models::{AgentStatus, DEFAULT_ROUTE_V4, NodeRef, OverlayNode};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct OverlayNodeResponse {
     pub id: String,
     pub name: String,
@@ -67,13 +68,20 @@ impl From<OverlayNode> for OverlayNodeResponse {
     }
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetApprovedRoutesRequest {
     pub approved_routes: Vec<String>,
 }
 
 /// GET /api/tenant/{tenant_id}/overlay-node — list the tenant's overlay nodes
 /// with their advertised + approved subnet routes (the subnet-router admin view).
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/overlay-node",
+    tag = "overlay_route",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn list_overlay_nodes(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -99,6 +107,14 @@ pub async fn list_overlay_nodes(
 /// PUT /api/tenant/{tenant_id}/overlay-node/{node_id}/approved-routes — set the
 /// admin-approved subset of a node's advertised routes. Only routes the node
 /// actually advertised may be approved; the change is re-fanned to peers.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/overlay-node/{node_id}/approved-routes",
+    tag = "overlay_route",
+    params(("tenant_id" = String, Path), ("node_id" = String, Path)),
+    request_body = SetApprovedRoutesRequest,
+    responses((status = 200, body = OverlayNodeResponse))
+)]
 pub async fn set_approved_routes(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -164,7 +180,7 @@ pub async fn set_approved_routes(
     Ok(Json(updated.into()))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetExitNodeRequest {
     pub enabled: bool,
 }
@@ -173,6 +189,14 @@ pub struct SetExitNodeRequest {
 /// un-designate) a node as an exit node (P5). Enabling requires the node to have
 /// advertised `0.0.0.0/0`; it sets `is_exit_node` and adds `/0` to the node's
 /// `approved_routes` (the data-plane signal). Re-fanned to peers immediately.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/overlay-node/{node_id}/exit-node",
+    tag = "overlay_route",
+    params(("tenant_id" = String, Path), ("node_id" = String, Path)),
+    request_body = SetExitNodeRequest,
+    responses((status = 200, body = OverlayNodeResponse))
+)]
 pub async fn set_exit_node(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -217,7 +241,7 @@ pub async fn set_exit_node(
 // Phase 2 MagicDNS — tenant DNS settings
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MagicDnsResponse {
     /// Overlay DNS suffix (`None` = MagicDNS off).
     pub magic_dns_domain: Option<String>,
@@ -225,7 +249,7 @@ pub struct MagicDnsResponse {
     pub magic_dns_nameservers: Vec<String>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SetMagicDnsRequest {
     #[serde(default)]
     pub magic_dns_domain: Option<String>,
@@ -234,6 +258,13 @@ pub struct SetMagicDnsRequest {
 }
 
 /// GET /api/tenant/{tenant_id}/magic-dns — the tenant's current MagicDNS config.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/magic-dns",
+    tag = "overlay_route",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = MagicDnsResponse))
+)]
 pub async fn get_magic_dns(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -253,6 +284,14 @@ pub async fn get_magic_dns(
 
 /// PUT /api/tenant/{tenant_id}/magic-dns — set the MagicDNS domain + upstreams.
 /// An empty domain disables MagicDNS.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/magic-dns",
+    tag = "overlay_route",
+    params(("tenant_id" = String, Path)),
+    request_body = SetMagicDnsRequest,
+    responses((status = 200, body = MagicDnsResponse))
+)]
 pub async fn set_magic_dns(
     State(state): State<AppState>,
     auth: AuthUser,
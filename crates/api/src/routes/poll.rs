@@ -0,0 +1,411 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::{CallPoll, role::permissions};
+
+const MAX_OPTIONS: usize = 10;
+const MAX_QUESTION_LEN: usize = 500;
+const MAX_OPTION_LEN: usize = 200;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreatePollRequest {
+    pub question: String,
+    /// Between 2 and 10 choices.
+    pub options: Vec<String>,
+    #[serde(default)]
+    pub allow_multiple: bool,
+    /// Show tallies to participants while the poll is open.
+    #[serde(default)]
+    pub results_visible: bool,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VoteRequest {
+    /// Indexes into the poll's `options`; exactly one unless the poll
+    /// allows multiple choices.
+    pub option_ids: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdatePollRequest {
+    pub results_visible: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PollResponse {
+    pub id: String,
+    pub room_id: String,
+    pub call_session_id: String,
+    pub created_by: String,
+    pub question: String,
+    pub options: Vec<PollOptionResponse>,
+    pub allow_multiple: bool,
+    pub results_visible: bool,
+    pub total_votes: u32,
+    pub closed: bool,
+    /// Whether the requesting user has voted; absent in WS events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voted: Option<bool>,
+    pub created_at: String,
+    pub closed_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PollOptionResponse {
+    pub id: u32,
+    pub text: String,
+    /// `null` while results are hidden from the viewer.
+    pub votes: Option<u32>,
+}
+
+/// Start a poll in the room's current call.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/poll",
+    tag = "poll",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreatePollRequest,
+    responses((status = 201, body = PollResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<CreatePollRequest>,
+) -> Result<(StatusCode, Json<PollResponse>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let question = body.question.trim().to_string();
+    if question.is_empty() || question.chars().count() > MAX_QUESTION_LEN {
+        return Err(ApiError::Validation(format!(
+            "Question must be 1-{} characters",
+            MAX_QUESTION_LEN
+        )));
+    }
+    let options: Vec<String> = body.options.iter().map(|o| o.trim().to_string()).collect();
+    if options.len() < 2 || options.len() > MAX_OPTIONS {
+        return Err(ApiError::Validation(format!(
+            "A poll needs 2-{} options",
+            MAX_OPTIONS
+        )));
+    }
+    if options
+        .iter()
+        .any(|o| o.is_empty() || o.chars().count() > MAX_OPTION_LEN)
+    {
+        return Err(ApiError::Validation(format!(
+            "Options must be 1-{} characters",
+            MAX_OPTION_LEN
+        )));
+    }
+
+    let session = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .filter(|s| s.tenant_id == tid)
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+
+    let poll = state
+        .call_polls
+        .create(
+            tid,
+            rid,
+            session.id.unwrap(),
+            auth.user_id,
+            question,
+            options,
+            body.allow_multiple,
+            body.results_visible,
+        )
+        .await?;
+
+    broadcast(&state, "call:poll:create", &poll).await;
+    Ok((
+        StatusCode::CREATED,
+        Json(to_response(&poll, true, Some(auth.user_id))),
+    ))
+}
+
+/// Polls of the room's current call. Tallies of open polls are hidden unless
+/// the poll shows them or the caller has MANAGE_MEETINGS.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/poll",
+    tag = "poll",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<PollResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<PollResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let Some(session) = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .filter(|s| s.tenant_id == tid)
+    else {
+        return Ok(Json(Vec::new()));
+    };
+    let moderator = is_moderator(&state, tid, rid, auth.user_id).await?;
+    let polls = state
+        .call_polls
+        .list_for_session(session.id.unwrap())
+        .await?;
+    Ok(Json(
+        polls
+            .iter()
+            .map(|p| to_response(p, moderator, Some(auth.user_id)))
+            .collect(),
+    ))
+}
+
+/// Vote once in an open poll.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/poll/{poll_id}/vote",
+    tag = "poll",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("poll_id" = String, Path)
+    ),
+    request_body = VoteRequest,
+    responses((status = 200, body = PollResponse))
+)]
+pub async fn vote(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, poll_id)): Path<(String, String, String)>,
+    Json(body): Json<VoteRequest>,
+) -> Result<Json<PollResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let pid = ObjectId::parse_str(&poll_id)
+        .map_err(|_| ApiError::BadRequest("Invalid poll_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let poll = state.call_polls.find_in_room(rid, pid).await?;
+    if poll.tenant_id != tid {
+        return Err(ApiError::NotFound("Poll not found".to_string()));
+    }
+    let unique: HashSet<u32> = body.option_ids.iter().copied().collect();
+    if body.option_ids.is_empty()
+        || unique.len() != body.option_ids.len()
+        || (!poll.allow_multiple && body.option_ids.len() > 1)
+        || body
+            .option_ids
+            .iter()
+            .any(|&i| i as usize >= poll.options.len())
+    {
+        return Err(ApiError::Validation("Invalid option_ids".to_string()));
+    }
+
+    let poll = state
+        .call_polls
+        .vote(rid, pid, auth.user_id, body.option_ids)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Poll is closed or already voted".to_string()))?;
+
+    broadcast(&state, "call:poll:update", &poll).await;
+    let moderator = is_moderator(&state, tid, rid, auth.user_id).await?;
+    Ok(Json(to_response(&poll, moderator, Some(auth.user_id))))
+}
+
+/// Show or hide an open poll's results to participants.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/poll/{poll_id}",
+    tag = "poll",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("poll_id" = String, Path)
+    ),
+    request_body = UpdatePollRequest,
+    responses((status = 200, body = PollResponse))
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, poll_id)): Path<(String, String, String)>,
+    Json(body): Json<UpdatePollRequest>,
+) -> Result<Json<PollResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let pid = ObjectId::parse_str(&poll_id)
+        .map_err(|_| ApiError::BadRequest("Invalid poll_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let poll = state
+        .call_polls
+        .set_results_visible(rid, pid, body.results_visible)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Poll is closed".to_string()))?;
+
+    broadcast(&state, "call:poll:update", &poll).await;
+    Ok(Json(to_response(&poll, true, Some(auth.user_id))))
+}
+
+/// Close a poll; its results become visible to everyone.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/poll/{poll_id}/close",
+    tag = "poll",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("poll_id" = String, Path)
+    ),
+    responses((status = 200, body = PollResponse))
+)]
+pub async fn close(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, poll_id)): Path<(String, String, String)>,
+) -> Result<Json<PollResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let pid = ObjectId::parse_str(&poll_id)
+        .map_err(|_| ApiError::BadRequest("Invalid poll_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let poll = state
+        .call_polls
+        .close(rid, pid)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("Poll is already closed".to_string()))?;
+
+    broadcast(&state, "call:poll:update", &poll).await;
+    Ok(Json(to_response(&poll, true, Some(auth.user_id))))
+}
+
+/// Close the current call's open polls, when the call ends.
+pub(crate) async fn close_all(state: &AppState, room_id: ObjectId) -> Result<(), ApiError> {
+    if let Some(session) = state.call_sessions.find_active(room_id).await? {
+        state
+            .call_polls
+            .close_all_for_session(session.id.unwrap())
+            .await?;
+    }
+    Ok(())
+}
+
+async fn is_moderator(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    uid: ObjectId,
+) -> Result<bool, ApiError> {
+    let perms = state.permissions.room_permissions(tid, rid, uid).await?;
+    Ok(permissions::has(perms, permissions::MANAGE_MEETINGS))
+}
+
+/// Send a poll event to the room's members. The poll's creator gets the
+/// tallies; everyone else only when the poll reveals them.
+async fn broadcast(state: &AppState, event_type: &str, poll: &CallPoll) {
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(poll.room_id)
+        .await
+        .unwrap_or_default();
+    let (creator, others): (Vec<ObjectId>, Vec<ObjectId>) = member_ids
+        .into_iter()
+        .partition(|id| *id == poll.created_by);
+
+    for (recipients, reveal) in [(creator, true), (others, false)] {
+        if recipients.is_empty() {
+            continue;
+        }
+        let event = serde_json::json!({
+            "type": event_type,
+            "tenant_id": poll.tenant_id.to_hex(),
+            "data": {
+                "room_id": poll.room_id.to_hex(),
+                "poll": to_response(poll, reveal, None),
+            }
+        });
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &recipients,
+            &event,
+        )
+        .await;
+    }
+}
+
+/// `reveal` shows tallies of an open, hidden-results poll (moderators);
+/// `viewer` fills in `voted`.
+pub(crate) fn to_response(p: &CallPoll, reveal: bool, viewer: Option<ObjectId>) -> PollResponse {
+    let closed = p.closed_at.is_some();
+    let show = reveal || closed || p.results_visible;
+    PollResponse {
+        id: p.id.map(|i| i.to_hex()).unwrap_or_default(),
+        room_id: p.room_id.to_hex(),
+        call_session_id: p.call_session_id.to_hex(),
+        created_by: p.created_by.to_hex(),
+        question: p.question.clone(),
+        options: p
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, o)| PollOptionResponse {
+                id: i as u32,
+                text: o.text.clone(),
+                votes: show.then_some(o.votes),
+            })
+            .collect(),
+        allow_multiple: p.allow_multiple,
+        results_visible: p.results_visible,
+        total_votes: p.total_votes,
+        closed,
+        voted: viewer.map(|uid| p.votes.iter().any(|v| v.user_id == uid)),
+        created_at: p.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        closed_at: p.closed_at.and_then(|d| d.try_to_rfc3339_string().ok()),
+    }
+}
@@ -1,31 +1,49 @@
 use axum::{Json, extract::State};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::NotificationLevel;
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct SubscribeRequest {
     pub endpoint: String,
     pub keys: PushKeysRequest,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct PushKeysRequest {
     pub auth: String,
     pub p256dh: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UnsubscribeRequest {
     pub endpoint: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct DevicePreferencesRequest {
+    pub endpoint: String,
+    /// `all`, `mentions` or `nothing` for this device; `null` follows the
+    /// user's preferences.
+    #[schema(value_type = Option<String>)]
+    pub notification_level: Option<NotificationLevel>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PushConfigResponse {
     pub vapid_public_key: String,
 }
 
 /// GET /push/config — returns the VAPID public key for client-side subscription
+#[utoipa::path(
+    get,
+    path = "/api/push/config",
+    tag = "push",
+    responses((status = 200, body = PushConfigResponse)),
+    security(())
+)]
 pub async fn config(State(state): State<AppState>) -> Result<Json<PushConfigResponse>, ApiError> {
     Ok(Json(PushConfigResponse {
         vapid_public_key: state.settings.push.vapid_public_key.clone(),
@@ -33,6 +51,13 @@ pub async fn config(State(state): State<AppState>) -> Result<Json<PushConfigResp
 }
 
 /// POST /push/subscribe — register a push subscription for the authenticated user
+#[utoipa::path(
+    post,
+    path = "/api/push/subscribe",
+    tag = "push",
+    request_body = SubscribeRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn subscribe(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -53,6 +78,13 @@ pub async fn subscribe(
 }
 
 /// POST /push/unsubscribe — remove a push subscription
+#[utoipa::path(
+    post,
+    path = "/api/push/unsubscribe",
+    tag = "push",
+    request_body = UnsubscribeRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn unsubscribe(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -66,3 +98,30 @@ pub async fn unsubscribe(
 
     Ok(Json(serde_json::json!({ "ok": true })))
 }
+
+/// PUT /push/subscription — set or clear one device's notification level
+#[utoipa::path(
+    put,
+    path = "/api/push/subscription",
+    tag = "push",
+    request_body = DevicePreferencesRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn set_device_preferences(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<DevicePreferencesRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let found = state
+        .push_subscriptions
+        .set_notification_level(auth.user_id, &body.endpoint, body.notification_level)
+        .await
+        .map_err(|e| ApiError::Internal(e.to_string()))?;
+    if !found {
+        return Err(ApiError::NotFound(
+            "Push subscription not found".to_string(),
+        ));
+    }
+
+    Ok(Json(serde_json::json!({ "ok": true })))
+}
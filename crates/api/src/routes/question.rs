@@ -0,0 +1,287 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::{CallQuestion, role::permissions};
+
+const MAX_CONTENT_LEN: usize = 1000;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateQuestionRequest {
+    pub content: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct QuestionResponse {
+    pub id: String,
+    pub room_id: String,
+    pub call_session_id: String,
+    pub author_id: String,
+    pub display_name: String,
+    pub content: String,
+    pub upvotes: u32,
+    /// Whether the requesting user has upvoted; absent in WS events.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upvoted: Option<bool>,
+    pub answered: bool,
+    pub created_at: String,
+    pub answered_at: Option<String>,
+}
+
+/// Questions of the room's current call, most upvoted first.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/question",
+    tag = "question",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<QuestionResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<QuestionResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let Some(session) = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .filter(|s| s.tenant_id == tid)
+    else {
+        return Ok(Json(Vec::new()));
+    };
+    let questions = state
+        .call_questions
+        .list_for_session(session.id.unwrap())
+        .await?;
+    Ok(Json(
+        questions
+            .iter()
+            .map(|q| to_response(q, Some(auth.user_id)))
+            .collect(),
+    ))
+}
+
+/// Ask a question in the room's current call.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/question",
+    tag = "question",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreateQuestionRequest,
+    responses((status = 201, body = QuestionResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<CreateQuestionRequest>,
+) -> Result<(StatusCode, Json<QuestionResponse>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let content = body.content.trim().to_string();
+    if content.is_empty() || content.chars().count() > MAX_CONTENT_LEN {
+        return Err(ApiError::Validation(format!(
+            "Question must be 1-{} characters",
+            MAX_CONTENT_LEN
+        )));
+    }
+
+    let session = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .filter(|s| s.tenant_id == tid)
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    let question = state
+        .call_questions
+        .create(
+            tid,
+            rid,
+            session.id.unwrap(),
+            auth.user_id,
+            user.display_name,
+            content,
+        )
+        .await?;
+
+    broadcast(&state, "call:question:create", &question).await;
+    Ok((
+        StatusCode::CREATED,
+        Json(to_response(&question, Some(auth.user_id))),
+    ))
+}
+
+/// Upvote a question. Upvoting twice is a no-op.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/question/{question_id}/upvote",
+    tag = "question",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("question_id" = String, Path)
+    ),
+    responses((status = 200, body = QuestionResponse))
+)]
+pub async fn upvote(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, question_id)): Path<(String, String, String)>,
+) -> Result<Json<QuestionResponse>, ApiError> {
+    set_upvote(state, auth, tenant_id, room_id, question_id, true).await
+}
+
+/// Withdraw an upvote.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/question/{question_id}/upvote",
+    tag = "question",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("question_id" = String, Path)
+    ),
+    responses((status = 200, body = QuestionResponse))
+)]
+pub async fn remove_upvote(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, question_id)): Path<(String, String, String)>,
+) -> Result<Json<QuestionResponse>, ApiError> {
+    set_upvote(state, auth, tenant_id, room_id, question_id, false).await
+}
+
+async fn set_upvote(
+    state: AppState,
+    auth: AuthUser,
+    tenant_id: String,
+    room_id: String,
+    question_id: String,
+    upvote: bool,
+) -> Result<Json<QuestionResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let qid = ObjectId::parse_str(&question_id)
+        .map_err(|_| ApiError::BadRequest("Invalid question_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let before = state.call_questions.base.find_by_id(qid).await?;
+    if before.tenant_id != tid || before.room_id != rid {
+        return Err(ApiError::NotFound("Question not found".to_string()));
+    }
+    let question = state
+        .call_questions
+        .set_upvote(rid, qid, auth.user_id, upvote)
+        .await?;
+
+    if question.upvotes != before.upvotes {
+        broadcast(&state, "call:question:update", &question).await;
+    }
+    Ok(Json(to_response(&question, Some(auth.user_id))))
+}
+
+/// Mark a question as answered.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/question/{question_id}/answer",
+    tag = "question",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("question_id" = String, Path)
+    ),
+    responses((status = 200, body = QuestionResponse))
+)]
+pub async fn answer(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, question_id)): Path<(String, String, String)>,
+) -> Result<Json<QuestionResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let qid = ObjectId::parse_str(&question_id)
+        .map_err(|_| ApiError::BadRequest("Invalid question_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+
+    let question = state.call_questions.mark_answered(rid, qid).await?;
+    broadcast(&state, "call:question:update", &question).await;
+    Ok(Json(to_response(&question, Some(auth.user_id))))
+}
+
+async fn broadcast(state: &AppState, event_type: &str, question: &CallQuestion) {
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(question.room_id)
+        .await
+        .unwrap_or_default();
+    if member_ids.is_empty() {
+        return;
+    }
+    let event = serde_json::json!({
+        "type": event_type,
+        "data": {
+            "room_id": question.room_id.to_hex(),
+            "question": to_response(question, None),
+        }
+    });
+    crate::ws::event_log::publish(
+        state,
+        question.tenant_id,
+        question.room_id,
+        &member_ids,
+        event,
+    )
+    .await;
+}
+
+/// `viewer` fills in `upvoted`.
+pub(crate) fn to_response(q: &CallQuestion, viewer: Option<ObjectId>) -> QuestionResponse {
+    QuestionResponse {
+        id: q.id.map(|i| i.to_hex()).unwrap_or_default(),
+        room_id: q.room_id.to_hex(),
+        call_session_id: q.call_session_id.to_hex(),
+        author_id: q.author_id.to_hex(),
+        display_name: q.display_name.clone(),
+        content: q.content.clone(),
+        upvotes: q.upvotes,
+        upvoted: viewer.map(|uid| q.upvoter_ids.contains(&uid)),
+        answered: q.answered_at.is_some(),
+        created_at: q.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        answered_at: q.answered_at.and_then(|d| d.try_to_rfc3339_string().ok()),
+    }
+}
@@ -4,14 +4,28 @@ use axum::{
 };
 use bson::oid::ObjectId;
 use serde::Deserialize;
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::{EmojiRef, EmojiType};
 
-#[derive(Debug, Deserialize)]
+/// React with a unicode emoji, or with one of the tenant's custom emojis
+/// given as `:name:` in `emoji` or by `custom_emoji_id`.
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct AddReactionRequest {
+    #[serde(default)]
     pub emoji: String,
+    pub custom_emoji_id: Option<String>,
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/reaction",
+    tag = "reaction",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ("message_id" = String, Path)),
+    request_body = AddReactionRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn add(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -29,9 +43,10 @@ pub async fn add(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
+    let emoji = resolve_emoji(&state, tid, body).await?;
     let reaction = state
         .reactions
-        .add_and_update_summary(&state.messages, tid, rid, mid, auth.user_id, body.emoji)
+        .add_and_update_summary(&state.messages, tid, rid, mid, auth.user_id, emoji)
         .await?;
 
     let member_ids = state.rooms.find_member_user_ids(rid).await?;
@@ -43,19 +58,26 @@ pub async fn add(
             "room_id": room_id,
             "user_id": auth.user_id.to_hex(),
             "emoji": reaction.emoji.value,
+            "custom_emoji_id": reaction.emoji.custom_emoji_id.map(|id| id.to_hex()),
         }
     });
-    crate::ws::dispatcher::broadcast_with_redis(
-        &state.ws_storage,
-        &state.redis_pubsub,
-        &member_ids,
-        &event,
-    )
-    .await;
+    crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
 
     Ok(Json(serde_json::json!({ "added": true })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/reaction/{emoji}",
+    tag = "reaction",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("message_id" = String, Path),
+        ("emoji" = String, Path),
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn remove(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -89,14 +111,48 @@ pub async fn remove(
                 "emoji": emoji,
             }
         });
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &member_ids,
-            &event,
-        )
-        .await;
+        crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
     }
 
     Ok(Json(serde_json::json!({ "removed": removed })))
 }
+
+/// Custom emojis are stored as `:name:`, so summaries and removal work the
+/// same as for unicode ones.
+async fn resolve_emoji(
+    state: &AppState,
+    tenant_id: ObjectId,
+    body: AddReactionRequest,
+) -> Result<EmojiRef, ApiError> {
+    let custom = if let Some(id) = body.custom_emoji_id {
+        let eid = ObjectId::parse_str(&id)
+            .map_err(|_| ApiError::BadRequest("Invalid custom_emoji_id".to_string()))?;
+        Some(state.custom_emojis.find_in_tenant(tenant_id, eid).await?)
+    } else if let Some(name) = body
+        .emoji
+        .strip_prefix(':')
+        .and_then(|e| e.strip_suffix(':'))
+        .filter(|n| super::asset::is_valid_emoji_name(n))
+    {
+        Some(state.custom_emojis.find_by_name(tenant_id, name).await?)
+    } else {
+        None
+    };
+
+    match custom {
+        Some(Some(emoji)) => Ok(EmojiRef {
+            emoji_type: EmojiType::Custom,
+            value: format!(":{}:", emoji.name),
+            custom_emoji_id: emoji.id,
+        }),
+        Some(None) => Err(ApiError::NotFound("Custom emoji not found".to_string())),
+        None if body.emoji.trim().is_empty() => {
+            Err(ApiError::Validation("emoji is required".to_string()))
+        }
+        None => Ok(EmojiRef {
+            emoji_type: EmojiType::Unicode,
+            value: body.emoji,
+            custom_emoji_id: None,
+        }),
+    }
+}
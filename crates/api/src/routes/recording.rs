@@ -1,14 +1,34 @@
 use axum::{
     Json,
+    body::Body,
     extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode, header},
+    response::{IntoResponse, Response},
 };
 use bson::oid::ObjectId;
+use futures::StreamExt;
+use roomler_ai_db::models::TaskCategory;
+use roomler_ai_db::models::recording::{
+    ConsentPolicy, DeclineAction, RecordingStatus, TranscriptSegment,
+};
+use roomler_ai_services::chapters;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::{
+        audit::{self, AuditContext, AuditEntry},
+        storage_quota,
+    },
+    state::AppState,
+};
 use roomler_ai_services::dao::base::PaginationParams;
+use utoipa::{IntoParams, ToSchema};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RecordingResponse {
     pub id: String,
     pub room_id: String,
@@ -17,9 +37,24 @@ pub struct RecordingResponse {
     pub content_type: String,
     pub size: u64,
     pub duration: u32,
+    pub chapters: Vec<ChapterResponse>,
     pub created_at: String,
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ChapterResponse {
+    /// Seconds into the recording.
+    pub start: f64,
+    pub title: String,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording",
+    tag = "recording",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), PaginationParams),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -47,11 +82,30 @@ pub async fn list(
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateRecordingRequest {
     pub recording_type: Option<String>,
+    /// Ask the call's participants for consent before recording starts.
+    pub consent: Option<RecordingConsentOptions>,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RecordingConsentOptions {
+    /// `keep_unrecorded` (default) or `remove`: what happens to a
+    /// participant who declines.
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub on_decline: DeclineAction,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording",
+    tag = "recording",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreateRecordingRequest,
+    responses((status = 200, body = RecordingResponse))
+)]
 pub async fn create(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -66,6 +120,7 @@ pub async fn create(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    crate::middleware::plan_limits::check_recording(&state, tid, auth.user_id).await?;
 
     let recording_type = match body.recording_type.as_deref() {
         Some("audio") => roomler_ai_db::models::recording::RecordingType::Audio,
@@ -73,6 +128,14 @@ pub async fn create(
         _ => roomler_ai_db::models::recording::RecordingType::Video,
     };
 
+    let consent = body.consent.map(|c| ConsentPolicy {
+        on_decline: c.on_decline,
+    });
+    let participants = match consent {
+        Some(_) => super::recording_consent::in_call(&state, tid, rid).await?,
+        None => Vec::new(),
+    };
+
     let now = bson::DateTime::now();
     let storage_file = roomler_ai_db::models::recording::StorageFile {
         storage_provider: roomler_ai_db::models::recording::StorageProvider::Local,
@@ -82,20 +145,45 @@ pub async fn create(
         content_type: "video/webm".to_string(),
         size: 0,
         duration: 0,
+        duration_ms: 0,
         resolution: None,
     };
 
     let recording = state
         .recordings
-        .create(tid, rid, recording_type, storage_file, now, now)
+        .create(tid, rid, recording_type, storage_file, now, now, consent)
         .await?;
+    if let Some(recording_id) = recording.id {
+        state
+            .call_sessions
+            .attach_recording(rid, recording_id)
+            .await?;
+    }
+    let recording = if consent.is_some() {
+        super::recording_consent::request(&state, recording, auth.user_id, &participants).await?
+    } else {
+        super::recording_consent::announce_started(&state, &recording).await?;
+        recording
+    };
 
     Ok(Json(to_response(recording)))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, _room_id, recording_id)): Path<(String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -107,10 +195,629 @@ pub async fn delete(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    state.recordings.soft_delete(tid, rec_id).await?;
+    let recording = state
+        .recordings
+        .base
+        .find_by_id_in_tenant(tid, rec_id)
+        .await?;
+    let size = recording.file.size;
+    let before = to_response(recording);
+    if state.recordings.soft_delete(tid, rec_id).await? {
+        storage_quota::record_delete(&state, tid, size).await;
+    }
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(
+            tid,
+            auth.user_id,
+            "recording.delete",
+            "recording",
+            Some(rec_id),
+        )
+        .before(&before),
+    )
+    .await;
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Size of the chunks a recording is streamed in.
+const STREAM_CHUNK: usize = 64 * 1024;
+
+/// A member's recording in the given room, or 404.
+pub(super) async fn find_in_room(
+    state: &AppState,
+    user_id: ObjectId,
+    ids: &(String, String, String),
+) -> Result<roomler_ai_db::models::Recording, ApiError> {
+    let tid = ObjectId::parse_str(&ids.0)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&ids.1)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let rec_id = ObjectId::parse_str(&ids.2)
+        .map_err(|_| ApiError::BadRequest("Invalid recording_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let recording = state
+        .recordings
+        .base
+        .find_by_id_in_tenant(tid, rec_id)
+        .await?;
+    if recording.room_id != rid || recording.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Recording not found".to_string()));
+    }
+    Ok(recording)
+}
+
+fn file_path(recording: &roomler_ai_db::models::Recording) -> std::path::PathBuf {
+    super::file::upload_dir()
+        .join(&recording.file.bucket)
+        .join(&recording.file.key)
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FinalizeParams {
+    /// Length of the media in ms.
+    pub duration_ms: u64,
+    /// Where the file's first frame sits on the call clock, in ms.
+    pub offset_ms: Option<u64>,
+}
+
+/// Upload the finished file (the raw body) and make the recording available.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/file",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+        FinalizeParams,
+    ),
+    request_body(content = Vec<u8>, content_type = "video/webm"),
+    responses((status = 200, body = RecordingResponse))
+)]
+pub async fn upload_file(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Path(ids): Path<(String, String, String)>,
+    Query(params): Query<FinalizeParams>,
+    body: Body,
+) -> Result<Json<RecordingResponse>, ApiError> {
+    let recording = find_in_room(&state, auth.user_id, &ids).await?;
+    if matches!(recording.status, RecordingStatus::AwaitingConsent) {
+        return Err(ApiError::Conflict(
+            "Recording is waiting for consent".to_string(),
+        ));
+    }
+    if !matches!(recording.status, RecordingStatus::Processing) {
+        return Err(ApiError::Conflict(
+            "Recording already finalized".to_string(),
+        ));
+    }
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or(&recording.file.content_type)
+        .to_string();
+
+    let path = file_path(&recording);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to create dirs: {}", e)))?;
+    }
+    let mut file = tokio::fs::File::create(&path)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to create file: {}", e)))?;
+    let mut size = 0u64;
+    let mut stream = body.into_data_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|e| ApiError::BadRequest(format!("Upload failed: {}", e)))?;
+        size += chunk.len() as u64;
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to write file: {}", e)))?;
+    }
+    file.flush()
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to write file: {}", e)))?;
+
+    let rec_id = recording.id.unwrap();
+    let finalized = storage_quota::upload(&state, recording.tenant_id, auth.user_id, size, async {
+        state
+            .recordings
+            .finalize(
+                rec_id,
+                &content_type,
+                size,
+                params.duration_ms,
+                params.offset_ms.unwrap_or(0),
+            )
+            .await?;
+        Ok(())
+    })
+    .await;
+    if let Err(e) = finalized {
+        let _ = tokio::fs::remove_file(&path).await;
+        return Err(e);
+    }
+
+    let recording = state
+        .recordings
+        .base
+        .find_by_id_in_tenant(recording.tenant_id, rec_id)
+        .await?;
+    spawn_chapters(&state, auth.user_id, &recording).await?;
+    let (tid, rid) = (recording.tenant_id, recording.room_id);
+    let response = to_response(recording);
+    super::webhook::dispatch_event(
+        &state,
+        tid,
+        rid,
+        "recording.ready",
+        serde_json::json!({ "recording": &response }),
+    );
+    Ok(Json(response))
+}
+
+/// Which bytes of a `len`-byte file a `Range` header asks for.
+#[derive(Debug, PartialEq)]
+enum ByteRange {
+    Full,
+    /// Inclusive first and last byte.
+    Partial(u64, u64),
+    Unsatisfiable,
+}
+
+/// Parse a single `bytes=` range. Other units, multiple ranges and
+/// malformed headers are ignored (the whole file is served), as RFC 9110
+/// allows.
+fn byte_range(range: Option<&str>, len: u64) -> ByteRange {
+    let Some(spec) = range.and_then(|r| r.trim().strip_prefix("bytes=")) else {
+        return ByteRange::Full;
+    };
+    let Some((first, last)) = spec.trim().split_once('-') else {
+        return ByteRange::Full;
+    };
+    if spec.contains(',') {
+        return ByteRange::Full;
+    }
+    match (first.parse::<u64>().ok(), last.parse::<u64>().ok()) {
+        // bytes=-n: the last n bytes
+        (None, Some(n)) if first.is_empty() => {
+            if n == 0 || len == 0 {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial(len.saturating_sub(n), len - 1)
+            }
+        }
+        (Some(start), None) if last.is_empty() => {
+            if start >= len {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial(start, len - 1)
+            }
+        }
+        (Some(start), Some(end)) if start <= end => {
+            if start >= len {
+                ByteRange::Unsatisfiable
+            } else {
+                ByteRange::Partial(start, end.min(len - 1))
+            }
+        }
+        _ => ByteRange::Full,
+    }
+}
+
+/// Stream the recording's file; honours `Range` so players can seek.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/stream",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    responses(
+        (status = 200, description = "Whole file", content_type = "video/webm"),
+        (status = 206, description = "Requested byte range", content_type = "video/webm"),
+        (status = 416, description = "Range outside the file"),
+    )
+)]
+pub async fn stream(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Path(ids): Path<(String, String, String)>,
+) -> Result<Response, ApiError> {
+    let recording = find_in_room(&state, auth.user_id, &ids).await?;
+    if !matches!(recording.status, RecordingStatus::Available) {
+        return Err(ApiError::NotFound("Recording file not ready".to_string()));
+    }
+
+    let mut file = tokio::fs::File::open(file_path(&recording))
+        .await
+        .map_err(|_| ApiError::NotFound("Recording file not found".to_string()))?;
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read file: {}", e)))?
+        .len();
+
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+    let (status, start, end) = match byte_range(range, len) {
+        ByteRange::Full => (StatusCode::OK, 0, len.saturating_sub(1)),
+        ByteRange::Partial(start, end) => (StatusCode::PARTIAL_CONTENT, start, end),
+        ByteRange::Unsatisfiable => {
+            return Ok((
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                [(header::CONTENT_RANGE, format!("bytes */{}", len))],
+            )
+                .into_response());
+        }
+    };
+    let length = if len == 0 { 0 } else { end - start + 1 };
+    file.seek(std::io::SeekFrom::Start(start))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to read file: {}", e)))?;
+
+    let body = futures::stream::unfold(file.take(length), |mut reader| async move {
+        let mut buf = vec![0; STREAM_CHUNK];
+        match reader.read(&mut buf).await {
+            Ok(0) => None,
+            Ok(n) => {
+                buf.truncate(n);
+                Some((Ok::<_, std::io::Error>(buf), reader))
+            }
+            Err(e) => Some((Err(e), reader)),
+        }
+    });
+
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, &recording.file.content_type)
+        .header(header::CONTENT_LENGTH, length)
+        .header(header::ACCEPT_RANGES, "bytes");
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(
+            header::CONTENT_RANGE,
+            format!("bytes {}-{}/{}", start, end, len),
+        );
+    }
+    response
+        .body(Body::from_stream(body))
+        .map_err(|e| ApiError::Internal(format!("Failed to build response: {}", e)))
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscriptLine {
+    /// Seconds into the recording.
+    pub start: f64,
+    pub end: f64,
+    pub user_id: Option<String>,
+    pub speaker_name: String,
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TranscriptResponse {
+    pub recording_id: String,
+    /// Length of the recording in seconds; 0 until the file is uploaded.
+    pub duration: f64,
+    pub segments: Vec<TranscriptLine>,
+}
+
+/// Move call-clock segments onto the file's timeline: shift by the file's
+/// offset, drop what falls outside the file and clip what straddles its
+/// ends. A recording without a file yet (`duration_ms` 0) isn't clipped at
+/// the end.
+fn align(segments: &[TranscriptSegment], offset_ms: u64, duration_ms: u64) -> Vec<TranscriptLine> {
+    let offset = offset_ms as f64 / 1000.0;
+    let duration = (duration_ms > 0).then(|| duration_ms as f64 / 1000.0);
+    let ms = |secs: f64| (secs * 1000.0).round() / 1000.0;
+    let mut lines: Vec<TranscriptLine> = segments
+        .iter()
+        .filter_map(|seg| {
+            let start = (seg.start_time - offset).max(0.0);
+            let mut end = seg.end_time - offset;
+            if end <= 0.0 || duration.is_some_and(|d| start >= d) {
+                return None;
+            }
+            if let Some(d) = duration {
+                end = end.min(d);
+            }
+            Some(TranscriptLine {
+                start: ms(start),
+                end: ms(end),
+                user_id: seg.user_id.map(|id| id.to_hex()),
+                speaker_name: seg.speaker_name.clone(),
+                text: seg.text.clone(),
+            })
+        })
+        .collect();
+    lines.sort_by(|a, b| a.start.total_cmp(&b.start));
+    lines
+}
+
+fn to_transcript(r: &roomler_ai_db::models::Recording) -> TranscriptResponse {
+    TranscriptResponse {
+        recording_id: r.id.unwrap().to_hex(),
+        duration: r.file.duration_ms as f64 / 1000.0,
+        segments: align(&r.transcript, r.media_offset_ms, r.file.duration_ms),
+    }
+}
+
+/// The transcript aligned to the recording, for click-to-seek.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/transcript",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    responses((status = 200, body = TranscriptResponse))
+)]
+pub async fn transcript(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(ids): Path<(String, String, String)>,
+) -> Result<Json<TranscriptResponse>, ApiError> {
+    let recording = find_in_room(&state, auth.user_id, &ids).await?;
+    Ok(Json(to_transcript(&recording)))
+}
+
+/// The transcript as WebVTT captions, for players to load as a text
+/// track next to `.../stream`. Built on request from a finished
+/// recording's transcript; nothing is burned in or muxed into a live
+/// stream. 404 unless the room has captions on.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/captions",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    responses((status = 200, description = "WebVTT captions", content_type = "text/vtt"))
+)]
+pub async fn captions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(ids): Path<(String, String, String)>,
+) -> Result<Response, ApiError> {
+    let recording = find_in_room(&state, auth.user_id, &ids).await?;
+    let room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(recording.tenant_id, recording.room_id)
+        .await?;
+    if !room.media_settings.is_some_and(|m| m.captions) {
+        return Err(ApiError::NotFound(
+            "Captions are off for this room".to_string(),
+        ));
+    }
+    let body = webvtt(&to_transcript(&recording).segments);
+    Ok(([(header::CONTENT_TYPE, "text/vtt; charset=utf-8")], body).into_response())
+}
+
+/// A WebVTT file with one cue per line, voiced by its speaker.
+fn webvtt(lines: &[TranscriptLine]) -> String {
+    let escape = |s: &str| {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let mut vtt = String::from("WEBVTT\n");
+    for (i, line) in lines.iter().enumerate() {
+        vtt.push_str(&format!(
+            "\n{}\n{} --> {}\n<v {}>{}\n",
+            i + 1,
+            vtt_time(line.start),
+            vtt_time(line.end),
+            escape(&line.speaker_name),
+            escape(&line.text),
+        ));
+    }
+    vtt
+}
+
+/// `hh:mm:ss.ttt`.
+fn vtt_time(secs: f64) -> String {
+    let ms = (secs * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TranscriptSegmentRequest {
+    pub user_id: Option<String>,
+    pub speaker_name: String,
+    pub text: String,
+    /// Seconds on the call clock, as in `media:transcript`.
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetTranscriptRequest {
+    pub segments: Vec<TranscriptSegmentRequest>,
+}
+
+/// Store the call's transcript segments on the recording, replacing any
+/// earlier ones.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/transcript",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    request_body = SetTranscriptRequest,
+    responses((status = 200, body = TranscriptResponse))
+)]
+pub async fn set_transcript(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(ids): Path<(String, String, String)>,
+    Json(body): Json<SetTranscriptRequest>,
+) -> Result<Json<TranscriptResponse>, ApiError> {
+    let mut recording = find_in_room(&state, auth.user_id, &ids).await?;
+
+    let mut segments = Vec::with_capacity(body.segments.len());
+    for seg in body.segments {
+        if !seg.start_time.is_finite()
+            || !seg.end_time.is_finite()
+            || seg.start_time < 0.0
+            || seg.end_time < seg.start_time
+        {
+            return Err(ApiError::Validation(
+                "Segment times must satisfy 0 <= start_time <= end_time".to_string(),
+            ));
+        }
+        let user_id = match seg.user_id {
+            Some(id) => Some(
+                ObjectId::parse_str(&id)
+                    .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?,
+            ),
+            None => None,
+        };
+        segments.push(TranscriptSegment {
+            user_id,
+            speaker_name: seg.speaker_name,
+            text: seg.text,
+            start_time: seg.start_time,
+            end_time: seg.end_time,
+        });
+    }
+
+    if let Some(session) = state
+        .call_sessions
+        .find_by_recording(recording.tenant_id, recording.id.unwrap())
+        .await?
+    {
+        redact_opt_outs(&mut segments, &session.transcription_opt_outs);
+    }
+    state
+        .recordings
+        .set_transcript(recording.id.unwrap(), &segments)
+        .await?;
+    recording.transcript = segments;
+    spawn_chapters(&state, auth.user_id, &recording).await?;
+    let response = to_transcript(&recording);
+    super::webhook::dispatch_event(
+        &state,
+        recording.tenant_id,
+        recording.room_id,
+        "recording.transcript",
+        serde_json::json!({ "transcript": &response }),
+    );
+    Ok(Json(response))
+}
+
+/// What the segments of users who opted out of transcription say.
+const NOT_TRANSCRIBED: &str = "[not transcribed]";
+
+/// Blank out the speech of users who opted out of the call's transcription,
+/// keeping when they spoke.
+pub(super) fn redact_opt_outs(segments: &mut [TranscriptSegment], opt_outs: &[ObjectId]) {
+    for seg in segments
+        .iter_mut()
+        .filter(|s| s.user_id.is_some_and(|u| opt_outs.contains(&u)))
+    {
+        seg.text = NOT_TRANSCRIBED.to_string();
+    }
+}
+
+/// Chapter a recording in the background once it has both its file and a
+/// transcript; a new transcript re-chapters it.
+async fn spawn_chapters(
+    state: &AppState,
+    user_id: ObjectId,
+    recording: &roomler_ai_db::models::Recording,
+) -> Result<(), ApiError> {
+    if !matches!(recording.status, RecordingStatus::Available) || recording.transcript.is_empty() {
+        return Ok(());
+    }
+    let rec_id = recording.id.unwrap();
+    let task = state
+        .tasks
+        .create_task(
+            recording.tenant_id,
+            user_id,
+            "recording_chapters".to_string(),
+            TaskCategory::Recording,
+            serde_json::json!({ "recording_id": rec_id.to_hex() }),
+        )
+        .await?;
+
+    let task_id = task.id.unwrap();
+    let lines: Vec<chapters::Line> = align(
+        &recording.transcript,
+        recording.media_offset_ms,
+        recording.file.duration_ms,
+    )
+    .into_iter()
+    .map(|l| chapters::Line {
+        start: l.start,
+        speaker_name: l.speaker_name,
+        text: l.text,
+    })
+    .collect();
+    let recognition = state.recognition.clone();
+    let recordings = Arc::clone(&state.recordings);
+    let task_store = Arc::clone(state.tasks.store());
+
+    state.tasks.spawn_task(task_id, async move {
+        let prompt = chapters::prompt(&lines);
+        let from_model = if recognition.is_available() && prompt.len() <= chapters::MAX_PROMPT_CHARS
+        {
+            task_store
+                .update_progress(task_id, 20, Some("Asking Claude for chapters".to_string()))
+                .await
+                .map_err(|e| format!("{}", e))?;
+            match recognition.complete(prompt).await {
+                Ok(reply) => chapters::parse_reply(&reply, &lines),
+                Err(e) => {
+                    tracing::warn!(?rec_id, %e, "Chaptering with Claude failed");
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let chapters = from_model.unwrap_or_else(|| chapters::by_topic(&lines));
+
+        recordings
+            .set_chapters(rec_id, &chapters)
+            .await
+            .map_err(|e| format!("Failed to store chapters: {}", e))?;
+        task_store
+            .complete(task_id, None, None)
+            .await
+            .map_err(|e| format!("{}", e))?;
+        Ok(())
+    });
+    Ok(())
+}
+
 fn to_response(r: roomler_ai_db::models::Recording) -> RecordingResponse {
     RecordingResponse {
         id: r.id.unwrap().to_hex(),
@@ -120,6 +827,122 @@ fn to_response(r: roomler_ai_db::models::Recording) -> RecordingResponse {
         content_type: r.file.content_type,
         size: r.file.size,
         duration: r.file.duration,
+        chapters: r
+            .chapters
+            .into_iter()
+            .map(|c| ChapterResponse {
+                start: c.start,
+                title: c.title,
+            })
+            .collect(),
         created_at: r.created_at.try_to_rfc3339_string().unwrap_or_default(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn byte_ranges() {
+        assert_eq!(byte_range(None, 100), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=0-9"), 100), ByteRange::Partial(0, 9));
+        assert_eq!(
+            byte_range(Some("bytes=90-"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            byte_range(Some("bytes=-10"), 100),
+            ByteRange::Partial(90, 99)
+        );
+        assert_eq!(
+            byte_range(Some("bytes=50-500"), 100),
+            ByteRange::Partial(50, 99)
+        );
+        assert_eq!(
+            byte_range(Some("bytes=-500"), 100),
+            ByteRange::Partial(0, 99)
+        );
+        assert_eq!(
+            byte_range(Some("bytes=100-"), 100),
+            ByteRange::Unsatisfiable
+        );
+        assert_eq!(byte_range(Some("bytes=-0"), 100), ByteRange::Unsatisfiable);
+        assert_eq!(byte_range(Some("bytes=9-0"), 100), ByteRange::Full);
+        assert_eq!(byte_range(Some("bytes=0-1,5-6"), 100), ByteRange::Full);
+        assert_eq!(byte_range(Some("items=0-1"), 100), ByteRange::Full);
+    }
+
+    fn seg(text: &str, start_time: f64, end_time: f64) -> TranscriptSegment {
+        TranscriptSegment {
+            user_id: None,
+            speaker_name: "Ann".to_string(),
+            text: text.to_string(),
+            start_time,
+            end_time,
+        }
+    }
+
+    #[test]
+    fn segments_are_shifted_onto_the_file_and_clipped() {
+        let segments = [
+            seg("late", 70.0, 75.0),
+            seg("before", 1.0, 4.0),
+            seg("straddles start", 8.0, 12.5),
+            seg("inside", 20.25, 22.0),
+            seg("straddles end", 68.0, 72.0),
+        ];
+        let lines = align(&segments, 10_000, 60_000);
+        let got: Vec<_> = lines
+            .iter()
+            .map(|l| (l.text.as_str(), l.start, l.end))
+            .collect();
+        assert_eq!(
+            got,
+            [
+                ("straddles start", 0.0, 2.5),
+                ("inside", 10.25, 12.0),
+                ("straddles end", 58.0, 60.0),
+            ]
+        );
+        assert_eq!(align(&segments, 0, 0).len(), 5);
+    }
+
+    #[test]
+    fn captions_are_webvtt_cues() {
+        assert_eq!(vtt_time(0.0), "00:00:00.000");
+        assert_eq!(vtt_time(3723.4567), "01:02:03.457");
+
+        let mut lines = align(&[seg("a < b & c", 1.5, 4.0)], 0, 0);
+        lines.push(TranscriptLine {
+            start: 61.0,
+            end: 62.25,
+            user_id: None,
+            speaker_name: "Bob".to_string(),
+            text: "bye".to_string(),
+        });
+        assert_eq!(
+            webvtt(&lines),
+            "WEBVTT\n\
+             \n1\n00:00:01.500 --> 00:00:04.000\n<v Ann>a &lt; b &amp; c\n\
+             \n2\n00:01:01.000 --> 00:01:02.250\n<v Bob>bye\n"
+        );
+        assert_eq!(webvtt(&[]), "WEBVTT\n");
+    }
+
+    #[test]
+    fn opted_out_speech_is_redacted() {
+        let (ann, bob) = (ObjectId::new(), ObjectId::new());
+        let mut segments = [
+            seg("hello", 0.0, 1.0),
+            seg("secret", 1.0, 2.0),
+            seg("bye", 2.0, 3.0),
+        ];
+        segments[0].user_id = Some(ann);
+        segments[1].user_id = Some(bob);
+        redact_opt_outs(&mut segments, &[bob]);
+        let texts: Vec<_> = segments.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["hello", NOT_TRANSCRIBED, "bye"]);
+        assert_eq!((segments[1].start_time, segments[1].end_time), (1.0, 2.0));
+    }
+}
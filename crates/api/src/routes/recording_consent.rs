@@ -0,0 +1,329 @@
+//! Consent to a call recording. A recording created with a `consent`
+//! policy waits in `awaiting_consent` while every other participant of the
+//! call answers `call:recording_consent_request`; it starts once nobody is
+//! left to answer. Participants who decline, or don't answer within
+//! [`CONSENT_TIMEOUT_SECS`], are removed from the call or kept out of the
+//! recording, as the policy says. Every answer is kept for compliance.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::recording::{DeclineAction, Recording};
+use roomler_ai_db::models::{ConsentDecision, RecordingConsent, role::permissions};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+/// How long participants have to answer before they count as declining.
+pub const CONSENT_TIMEOUT_SECS: u64 = 60;
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConsentRequest {
+    pub accept: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct ConsentResponse {
+    pub user_id: String,
+    /// `pending`, `accepted` or `declined`.
+    pub decision: String,
+    /// Declined because no answer came in time.
+    pub timed_out: bool,
+    pub decided_at: Option<String>,
+}
+
+/// Users currently in the room's call, or 409 if there is no call.
+pub(crate) async fn in_call(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+) -> Result<Vec<ObjectId>, ApiError> {
+    let session = state
+        .call_sessions
+        .find_active(rid)
+        .await?
+        .filter(|s| s.tenant_id == tid)
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+    let mut user_ids: Vec<ObjectId> = session
+        .participants
+        .iter()
+        .filter(|p| p.left_at.is_none())
+        .map(|p| p.user_id)
+        .collect();
+    user_ids.sort();
+    user_ids.dedup();
+    Ok(user_ids)
+}
+
+/// Ask `participants` to consent to a recording just created in
+/// `awaiting_consent`. Returns the recording, started if nobody but the
+/// requester had to answer.
+pub(crate) async fn request(
+    state: &AppState,
+    recording: Recording,
+    requested_by: ObjectId,
+    participants: &[ObjectId],
+) -> Result<Recording, ApiError> {
+    let rec_id = recording.id.unwrap();
+    let on_decline = recording.consent.unwrap_or_default().on_decline;
+    state
+        .recording_consents
+        .request(
+            recording.tenant_id,
+            recording.room_id,
+            rec_id,
+            requested_by,
+            participants,
+        )
+        .await?;
+
+    let asked: Vec<ObjectId> = participants
+        .iter()
+        .copied()
+        .filter(|id| *id != requested_by)
+        .collect();
+    if asked.is_empty() {
+        return Ok(try_start(state, &recording).await?.unwrap_or(recording));
+    }
+    let event = serde_json::json!({
+        "type": "call:recording_consent_request",
+        "tenant_id": recording.tenant_id.to_hex(),
+        "data": {
+            "room_id": recording.room_id.to_hex(),
+            "recording_id": rec_id.to_hex(),
+            "recording_type": format!("{:?}", recording.recording_type),
+            "requested_by": requested_by.to_hex(),
+            "on_decline": on_decline,
+            "timeout_secs": CONSENT_TIMEOUT_SECS,
+        }
+    });
+    crate::ws::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &asked,
+        &event,
+    )
+    .await;
+
+    let state = state.clone();
+    let pending = recording.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_secs(CONSENT_TIMEOUT_SECS)).await;
+        if let Err(e) = time_out(&state, &pending).await {
+            tracing::warn!(recording_id = %rec_id, %e, "Failed to time out recording consent");
+        }
+    });
+    Ok(recording)
+}
+
+/// Decline for everyone who hasn't answered, then start the recording.
+async fn time_out(state: &AppState, recording: &Recording) -> Result<(), ApiError> {
+    let declined = state
+        .recording_consents
+        .time_out(recording.id.unwrap())
+        .await?;
+    for user_id in declined {
+        announce(state, recording, user_id, false, true).await;
+        apply_decline(state, recording, user_id).await;
+    }
+    try_start(state, recording).await?;
+    Ok(())
+}
+
+/// Answer a recording's consent request.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/consent",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    request_body = ConsentRequest,
+    responses((status = 200, body = ConsentResponse))
+)]
+pub async fn respond(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(ids): Path<(String, String, String)>,
+    Json(body): Json<ConsentRequest>,
+) -> Result<Json<ConsentResponse>, ApiError> {
+    let recording = super::recording::find_in_room(&state, auth.user_id, &ids).await?;
+    let consent = state
+        .recording_consents
+        .decide(recording.id.unwrap(), auth.user_id, body.accept)
+        .await?
+        .ok_or_else(|| ApiError::Conflict("No consent pending for this recording".to_string()))?;
+
+    announce(&state, &recording, auth.user_id, body.accept, false).await;
+    if !body.accept {
+        apply_decline(&state, &recording, auth.user_id).await;
+    }
+    try_start(&state, &recording).await?;
+    Ok(Json(to_response(consent)))
+}
+
+/// Every participant's answer to the recording's consent request.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/recording/{recording_id}/consent",
+    tag = "recording",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("recording_id" = String, Path),
+    ),
+    responses((status = 200, body = Vec<ConsentResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(ids): Path<(String, String, String)>,
+) -> Result<Json<Vec<ConsentResponse>>, ApiError> {
+    let recording = super::recording::find_in_room(&state, auth.user_id, &ids).await?;
+    state
+        .permissions
+        .require_room(
+            recording.tenant_id,
+            recording.room_id,
+            auth.user_id,
+            permissions::MANAGE_MEETINGS,
+        )
+        .await?;
+
+    let consents = state
+        .recording_consents
+        .list_for_recording(recording.tenant_id, recording.id.unwrap())
+        .await?;
+    Ok(Json(consents.into_iter().map(to_response).collect()))
+}
+
+/// Tell the call about one participant's answer.
+async fn announce(
+    state: &AppState,
+    recording: &Recording,
+    user_id: ObjectId,
+    accepted: bool,
+    timed_out: bool,
+) {
+    let event = serde_json::json!({
+        "type": "call:recording_consent",
+        "tenant_id": recording.tenant_id.to_hex(),
+        "data": {
+            "room_id": recording.room_id.to_hex(),
+            "recording_id": recording.id.unwrap().to_hex(),
+            "user_id": user_id.to_hex(),
+            "accepted": accepted,
+            "timed_out": timed_out,
+        }
+    });
+    broadcast_to_call(state, recording, &event).await;
+}
+
+async fn apply_decline(state: &AppState, recording: &Recording, user_id: ObjectId) {
+    let rid = recording.room_id;
+    match recording.consent.unwrap_or_default().on_decline {
+        DeclineAction::KeepUnrecorded => {
+            state.room_manager.set_user_unrecorded(&rid, &user_id);
+        }
+        DeclineAction::Remove => {
+            let event = serde_json::json!({
+                "type": "call:recording_removed",
+                "tenant_id": recording.tenant_id.to_hex(),
+                "data": {
+                    "room_id": rid.to_hex(),
+                    "recording_id": recording.id.unwrap().to_hex(),
+                }
+            });
+            crate::ws::dispatcher::send_to_user_with_redis(
+                &state.ws_storage,
+                &state.redis_pubsub,
+                &user_id,
+                &event,
+            )
+            .await;
+            if let Err(e) = super::room::leave_call(state, recording.tenant_id, rid, user_id).await
+            {
+                tracing::warn!(%rid, %user_id, %e, "Failed to remove declining participant");
+            }
+        }
+    }
+}
+
+/// Start the recording once nobody is left to answer, and tell the call.
+/// Returns the started recording, or `None` if it isn't starting (yet).
+async fn try_start(state: &AppState, recording: &Recording) -> Result<Option<Recording>, ApiError> {
+    let rec_id = recording.id.unwrap();
+    if state.recording_consents.pending_count(rec_id).await? > 0 {
+        return Ok(None);
+    }
+    let Some(started) = state.recordings.start_after_consent(rec_id).await? else {
+        return Ok(None);
+    };
+    announce_started(state, &started).await?;
+    Ok(Some(started))
+}
+
+/// Broadcast `call:recording_started` to the call, with who is kept out
+/// of the recording so clients can badge them.
+pub(crate) async fn announce_started(
+    state: &AppState,
+    recording: &Recording,
+) -> Result<(), ApiError> {
+    let unrecorded: Vec<String> = match recording.consent {
+        Some(policy) if policy.on_decline == DeclineAction::KeepUnrecorded => state
+            .recording_consents
+            .list_for_recording(recording.tenant_id, recording.id.unwrap())
+            .await?
+            .into_iter()
+            .filter(|c| c.decision == ConsentDecision::Declined)
+            .map(|c| c.user_id.to_hex())
+            .collect(),
+        _ => Vec::new(),
+    };
+    let event = serde_json::json!({
+        "type": "call:recording_started",
+        "tenant_id": recording.tenant_id.to_hex(),
+        "data": {
+            "room_id": recording.room_id.to_hex(),
+            "recording_id": recording.id.unwrap().to_hex(),
+            "recording_type": format!("{:?}", recording.recording_type),
+            "unrecorded_user_ids": unrecorded,
+        }
+    });
+    broadcast_to_call(state, recording, &event).await;
+    Ok(())
+}
+
+async fn broadcast_to_call(state: &AppState, recording: &Recording, event: &serde_json::Value) {
+    let Ok(user_ids) = in_call(state, recording.tenant_id, recording.room_id).await else {
+        return;
+    };
+    if !user_ids.is_empty() {
+        crate::ws::dispatcher::broadcast_with_redis(
+            &state.ws_storage,
+            &state.redis_pubsub,
+            &user_ids,
+            event,
+        )
+        .await;
+    }
+}
+
+fn to_response(c: RecordingConsent) -> ConsentResponse {
+    ConsentResponse {
+        user_id: c.user_id.to_hex(),
+        decision: match c.decision {
+            ConsentDecision::Pending => "pending",
+            ConsentDecision::Accepted => "accepted",
+            ConsentDecision::Declined => "declined",
+        }
+        .to_string(),
+        timed_out: c.timed_out,
+        decided_at: c.decided_at.and_then(|d| d.try_to_rfc3339_string().ok()),
+    }
+}
@@ -17,7 +17,9 @@ use roomler_ai_remote_control::{
     turn_creds::ice_servers_for,
 };
 use roomler_ai_services::dao::base::PaginationParams;
+use roomler_ai_services::turn::TurnService;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
@@ -47,7 +49,7 @@ async fn require_permission(
 // Agent enrollment
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EnrollmentTokenResponse {
     pub enrollment_token: String,
     pub expires_in: u64,
@@ -57,6 +59,13 @@ pub struct EnrollmentTokenResponse {
 /// POST /api/tenant/{tenant_id}/agent/enroll-token — admin issues an enrollment
 /// token that a new agent binary exchanges (once, within 10 min) for a
 /// long-lived agent token.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/agent/enroll-token",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = EnrollmentTokenResponse))
+)]
 pub async fn issue_enrollment_token(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -85,16 +94,17 @@ pub async fn issue_enrollment_token(
     }))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct EnrollRequest {
     pub enrollment_token: String,
     pub machine_id: String,
     pub machine_name: String,
+    #[schema(value_type = String)]
     pub os: OsKind,
     pub agent_version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct EnrollResponse {
     pub agent_id: String,
     pub tenant_id: String,
@@ -104,6 +114,14 @@ pub struct EnrollResponse {
 /// POST /api/agent/enroll — public (no user JWT); authenticates via the
 /// enrollment token instead. Creates or rehydrates the Agent row and returns
 /// a long-lived agent JWT.
+#[utoipa::path(
+    post,
+    path = "/api/agent/enroll",
+    tag = "remote_control",
+    request_body = EnrollRequest,
+    responses((status = 200, body = EnrollResponse)),
+    security(())
+)]
 pub async fn enroll_agent(
     State(state): State<AppState>,
     Json(body): Json<EnrollRequest>,
@@ -164,20 +182,23 @@ pub async fn enroll_agent(
 // Agent CRUD
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AgentResponse {
     pub id: String,
     pub tenant_id: String,
     pub owner_user_id: String,
     pub name: String,
     pub machine_id: String,
+    #[schema(value_type = String)]
     pub os: OsKind,
     pub agent_version: String,
+    #[schema(value_type = String)]
     pub status: AgentStatus,
     /// Live `true` when the Hub holds an active WS to this agent, independent
     /// of the persisted `status` field (which can drift across restarts).
     pub is_online: bool,
     pub last_seen_at: String,
+    #[schema(value_type = Object)]
     pub access_policy: AccessPolicy,
     /// Subnet-router CIDRs this agent advertises for the mesh (Phase 2). The
     /// `roomler-tunnel socks5` mesh longest-prefix-matches a LAN target IP
@@ -191,9 +212,17 @@ pub struct AgentResponse {
     /// Codec + HW backend availability advertised by the agent in its
     /// most recent rc:agent.hello. Default empty for pre-2A.1 agents
     /// that haven't reconnected since the schema change.
+    #[schema(value_type = Object)]
     pub capabilities: roomler_ai_remote_control::models::AgentCaps,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/agent",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), PaginationParams),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn list_agents(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -223,6 +252,13 @@ pub async fn list_agents(
     })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path)),
+    responses((status = 200, body = AgentResponse))
+)]
 pub async fn get_agent(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -241,9 +277,10 @@ pub async fn get_agent(
     Ok(Json(to_agent_response(&state, agent)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateAgentRequest {
     pub name: Option<String>,
+    #[schema(value_type = Option<Object>)]
     pub access_policy: Option<AccessPolicy>,
     /// Reassign the device owner (hex user id). `MANAGE_AGENTS` only.
     pub owner_user_id: Option<String>,
@@ -253,6 +290,14 @@ pub struct UpdateAgentRequest {
     pub routes: Option<Vec<String>>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path)),
+    request_body = UpdateAgentRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn update_agent(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -292,6 +337,13 @@ pub async fn update_agent(
     Ok(Json(serde_json::json!({ "updated": true })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/agent/{agent_id}",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("agent_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete_agent(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -324,19 +376,28 @@ pub async fn delete_agent(
 // Sessions
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct SessionResponse {
     pub id: String,
     pub agent_id: String,
     pub tenant_id: String,
     pub controller_user_id: String,
+    #[schema(value_type = String, example = "VIEW | INPUT")]
     pub permissions: Permissions,
+    #[schema(value_type = String)]
     pub phase: roomler_ai_remote_control::models::SessionPhase,
     pub created_at: String,
     pub started_at: Option<String>,
     pub ended_at: Option<String>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/session/{session_id}",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("session_id" = String, Path)),
+    responses((status = 200, body = SessionResponse))
+)]
 pub async fn get_session(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -355,6 +416,13 @@ pub async fn get_session(
     Ok(Json(to_session_response(session)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/session/{session_id}/terminate",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("session_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn terminate_session(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -377,8 +445,9 @@ pub async fn terminate_session(
     Ok(Json(serde_json::json!({ "terminated": true })))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct AuditListResponse {
+    #[schema(value_type = Vec<Object>)]
     pub items: Vec<RemoteAuditEvent>,
     pub total: u64,
     pub page: u64,
@@ -386,6 +455,13 @@ pub struct AuditListResponse {
     pub total_pages: u64,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/session/{session_id}/audit",
+    tag = "remote_control",
+    params(("tenant_id" = String, Path), ("session_id" = String, Path), PaginationParams),
+    responses((status = 200, body = AuditListResponse))
+)]
 pub async fn session_audit(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -423,14 +499,21 @@ pub async fn session_audit(
 // TURN credentials
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TurnCredentialsResponse {
+    #[schema(value_type = Vec<Object>)]
     pub ice_servers: Vec<IceServer>,
 }
 
 /// GET /api/turn/credentials — user-scoped, returns short-lived (10 min) TURN
 /// creds plus a STUN fallback. Used by the browser controller and by the
 /// native agent when it needs to trickle ICE.
+#[utoipa::path(
+    get,
+    path = "/api/turn/credentials",
+    tag = "remote_control",
+    responses((status = 200, body = TurnCredentialsResponse))
+)]
 pub async fn turn_credentials(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -439,11 +522,53 @@ pub async fn turn_credentials(
     // `state::build_turn_config` (a former inline duplicate). This route is
     // session-less (a pre-fetch), so it issues the generic URL list; the
     // per-session same-worker affinity happens on the Hub's issuance paths.
-    let turn_cfg = crate::state::build_turn_config(&state.settings.turn);
+    let turn_cfg = crate::state::build_turn_config(&state.runtime.load().turn);
     let ice_servers = ice_servers_for(&auth.user_id.to_hex(), turn_cfg.as_ref());
     Ok(Json(TurnCredentialsResponse { ice_servers }))
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct TurnRegionResponse {
+    pub name: String,
+    pub url: String,
+    pub countries: Vec<String>,
+    /// `media:join`s on this pod that got the region as their primary relay.
+    pub primary_joins: u64,
+    /// In rotation: not missed the last two health probes from this pod.
+    pub healthy: bool,
+    /// Round trip of the last answered health probe from this pod.
+    pub rtt_ms: Option<u64>,
+}
+
+/// GET /api/turn/regions — configured conference TURN regions with this
+/// pod's per-region relay-pinning counters and probed health.
+#[utoipa::path(
+    get,
+    path = "/api/turn/regions",
+    tag = "remote_control",
+    responses((status = 200, body = Vec<TurnRegionResponse>))
+)]
+pub async fn turn_regions(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+) -> Result<Json<Vec<TurnRegionResponse>>, ApiError> {
+    let counts: std::collections::HashMap<String, u64> =
+        state.turn_region_stats.snapshot().into_iter().collect();
+    let regions = TurnService::new(&state.runtime.load().turn)
+        .regions()
+        .into_iter()
+        .map(|r| TurnRegionResponse {
+            primary_joins: counts.get(&r.name).copied().unwrap_or(0),
+            healthy: state.turn_region_stats.is_healthy(&r.name),
+            rtt_ms: state.turn_region_stats.rtt_ms(&r.name),
+            name: r.name,
+            url: r.url,
+            countries: r.countries,
+        })
+        .collect();
+    Ok(Json(regions))
+}
+
 // ────────────────────────────────────────────────────────────────────────────
 // Helpers
 // ────────────────────────────────────────────────────────────────────────────
@@ -0,0 +1,502 @@
+//! Tenant data retention.
+//!
+//! A tenant's `settings.retention` says how many days messages, recordings
+//! and in-call chat are kept. A background reaper sweeps every
+//! `retention.sweep_interval_secs`: messages and call chat past the limit
+//! are deleted, recordings are soft-deleted. Pinned messages and everything
+//! in a room under legal hold are skipped. Each purge that removed something
+//! is written to the audit log as a system action.
+//!
+//! The same sweep purges rooms and messages of every tenant that were
+//! soft-deleted more than `retention.deleted_grace_days` ago; until then
+//! they can be restored. Purging a message also removes its reactions and
+//! notifications and soft-deletes attachments no other message uses;
+//! purging a room removes everything in it.
+//!
+//! Separately, an archiver sweeps every `archive.sweep_interval_secs` and
+//! moves threads older than `archive_days` (or `archive.message_days`) from
+//! the `messages` collection to `messages_archive`. Listings merge the two,
+//! so archiving only changes where a message lives; archived threads are
+//! read-only. Retention limits apply to both collections.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    middleware::storage_quota,
+    state::AppState,
+};
+use roomler_ai_db::models::{RetentionPolicy, Tenant, role::permissions};
+use roomler_ai_services::dao::base::DaoResult;
+
+/// Longest retention limit accepted (100 years).
+pub const MAX_RETENTION_DAYS: u32 = 36_500;
+/// Messages deleted per round trip.
+const PURGE_BATCH: i64 = 500;
+/// Threads archived per round trip.
+const ARCHIVE_BATCH: i64 = 200;
+
+/// Days to keep each kind of content; `null` keeps it forever.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RetentionBody {
+    pub message_days: Option<u32>,
+    pub recording_days: Option<u32>,
+    /// In-call chat.
+    pub transcript_days: Option<u32>,
+    /// Days before messages move to the archive collection; `null` uses the
+    /// server default. Archived messages stay readable.
+    #[serde(default)]
+    pub archive_days: Option<u32>,
+}
+
+impl From<RetentionPolicy> for RetentionBody {
+    fn from(p: RetentionPolicy) -> Self {
+        Self {
+            message_days: p.message_days,
+            recording_days: p.recording_days,
+            transcript_days: p.transcript_days,
+            archive_days: p.archive_days,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct LegalHoldRequest {
+    pub enabled: bool,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/retention",
+    tag = "retention",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = RetentionBody))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<RetentionBody>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(tenant.settings.retention.into()))
+}
+
+/// Replace the tenant's retention policy (MANAGE_TENANT).
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/retention",
+    tag = "retention",
+    params(("tenant_id" = String, Path)),
+    request_body = RetentionBody,
+    responses((status = 200, body = RetentionBody))
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<RetentionBody>,
+) -> Result<Json<RetentionBody>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    for days in [
+        body.message_days,
+        body.recording_days,
+        body.transcript_days,
+        body.archive_days,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+            return Err(ApiError::Validation(format!(
+                "Retention must be between 1 and {} days",
+                MAX_RETENTION_DAYS
+            )));
+        }
+    }
+
+    let before = state.tenants.base.find_by_id(tid).await?.settings.retention;
+    let policy = RetentionPolicy {
+        message_days: body.message_days,
+        recording_days: body.recording_days,
+        transcript_days: body.transcript_days,
+        archive_days: body.archive_days,
+    };
+    let tenant = state.tenants.set_retention(tid, &policy).await?;
+    let after = RetentionBody::from(tenant.settings.retention);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(
+            tid,
+            auth.user_id,
+            "tenant.retention_update",
+            "tenant",
+            Some(tid),
+        )
+        .before(&RetentionBody::from(before))
+        .after(&after),
+    )
+    .await;
+
+    Ok(Json(after))
+}
+
+/// Place or lift a legal hold on a room (MANAGE_TENANT). A held room's
+/// content is never purged by retention.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/legal-hold",
+    tag = "retention",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = LegalHoldRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn set_legal_hold(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<LegalHoldRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    state.rooms.set_legal_hold(tid, rid, body.enabled).await?;
+
+    if room.legal_hold != body.enabled {
+        audit::record(
+            &state,
+            &ctx,
+            AuditEntry::new(tid, auth.user_id, "room.legal_hold", "room", Some(rid))
+                .before(&serde_json::json!({ "legal_hold": room.legal_hold }))
+                .after(&serde_json::json!({ "legal_hold": body.enabled })),
+        )
+        .await;
+    }
+
+    Ok(Json(serde_json::json!({ "legal_hold": body.enabled })))
+}
+
+pub(crate) async fn require_manage_tenant(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    let perms = state
+        .tenants
+        .get_member_permissions(tenant_id, user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Apply every tenant's retention policy, and purge deletions past
+/// `retention.deleted_grace_days`, every `retention.sweep_interval_secs`
+/// (0 disables).
+pub(crate) fn spawn_reaper(state: AppState) {
+    let interval = state.settings.retention.sweep_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(interval));
+        loop {
+            tick.tick().await;
+            let tenants = match state.tenants.find_with_retention().await {
+                Ok(tenants) => tenants,
+                Err(e) => {
+                    tracing::warn!(%e, "Failed to load tenants for retention");
+                    continue;
+                }
+            };
+            for tenant in tenants {
+                if let Err(e) = purge_tenant(&state, &tenant).await {
+                    tracing::warn!(tenant_id = ?tenant.id, %e, "Retention purge failed");
+                }
+            }
+
+            let grace_days = state.settings.retention.deleted_grace_days;
+            if grace_days == 0 {
+                continue;
+            }
+            let tenants = match state.tenants.find_live().await {
+                Ok(tenants) => tenants,
+                Err(e) => {
+                    tracing::warn!(%e, "Failed to load tenants for deleted-content purge");
+                    continue;
+                }
+            };
+            for tenant in tenants {
+                if let Err(e) = purge_deleted(&state, &tenant, grace_days).await {
+                    tracing::warn!(tenant_id = ?tenant.id, %e, "Deleted-content purge failed");
+                }
+            }
+        }
+    });
+}
+
+async fn purge_tenant(state: &AppState, tenant: &Tenant) -> DaoResult<()> {
+    let Some(tid) = tenant.id else {
+        return Ok(());
+    };
+    let policy = &tenant.settings.retention;
+    let held = state.rooms.find_held_ids(tid).await?;
+    let now = DateTime::now();
+
+    if let Some(days) = policy.message_days {
+        let before = cutoff(now, days);
+        let mut purged = 0;
+        loop {
+            let ids = state
+                .messages
+                .find_expired_ids(tid, before, &held, PURGE_BATCH)
+                .await?;
+            if ids.is_empty() {
+                break;
+            }
+            purged += purge_messages(state, tid, &ids).await?;
+        }
+        record_purge(state, tid, "message", purged, days, before).await;
+    }
+
+    if let Some(days) = policy.recording_days {
+        let before = cutoff(now, days);
+        let purged = state
+            .recordings
+            .soft_delete_expired(tid, before, &held)
+            .await?;
+        record_purge(state, tid, "recording", purged, days, before).await;
+    }
+
+    if let Some(days) = policy.transcript_days {
+        let before = cutoff(now, days);
+        let purged = state
+            .rooms
+            .delete_chat_messages_before(tid, before, &held)
+            .await?;
+        record_purge(state, tid, "call_chat_message", purged, days, before).await;
+    }
+
+    Ok(())
+}
+
+/// Move old threads to the archive collection every
+/// `archive.sweep_interval_secs` (0 disables).
+pub(crate) fn spawn_archiver(state: AppState) {
+    let interval = state.settings.archive.sweep_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(interval));
+        loop {
+            tick.tick().await;
+            let tenants = if state.settings.archive.message_days > 0 {
+                state.tenants.find_live().await
+            } else {
+                state.tenants.find_with_archive_days().await
+            };
+            let tenants = match tenants {
+                Ok(tenants) => tenants,
+                Err(e) => {
+                    tracing::warn!(%e, "Failed to load tenants for archiving");
+                    continue;
+                }
+            };
+            for tenant in tenants {
+                if let Err(e) = archive_tenant(&state, &tenant).await {
+                    tracing::warn!(tenant_id = ?tenant.id, %e, "Message archiving failed");
+                }
+            }
+        }
+    });
+}
+
+async fn archive_tenant(state: &AppState, tenant: &Tenant) -> DaoResult<()> {
+    let Some(tid) = tenant.id else {
+        return Ok(());
+    };
+    let days = match tenant.settings.retention.archive_days {
+        Some(days) => days,
+        None => state.settings.archive.message_days,
+    };
+    if days == 0 {
+        return Ok(());
+    }
+
+    let before = cutoff(DateTime::now(), days);
+    let mut archived = 0;
+    loop {
+        let moved = state
+            .messages
+            .archive_before(tid, before, ARCHIVE_BATCH)
+            .await?;
+        if moved == 0 {
+            break;
+        }
+        archived += moved;
+    }
+    if archived > 0 {
+        tracing::info!(%tid, archived, "Archived messages");
+    }
+    Ok(())
+}
+
+/// Purge what was soft-deleted more than `grace_days` ago. Legal holds are
+/// honored as for retention.
+async fn purge_deleted(state: &AppState, tenant: &Tenant, grace_days: u32) -> DaoResult<()> {
+    let Some(tid) = tenant.id else {
+        return Ok(());
+    };
+    let held = state.rooms.find_held_ids(tid).await?;
+    let before = cutoff(DateTime::now(), grace_days);
+
+    let mut purged = 0;
+    loop {
+        let ids = state
+            .messages
+            .find_deleted_ids(tid, before, &held, PURGE_BATCH)
+            .await?;
+        if ids.is_empty() {
+            break;
+        }
+        purged += purge_messages(state, tid, &ids).await?;
+    }
+    record_purge(state, tid, "deleted_message", purged, grace_days, before).await;
+
+    let rooms = state.rooms.find_deleted_ids(tid, before).await?;
+    for &rid in &rooms {
+        purge_room(state, tid, rid).await?;
+    }
+    record_purge(
+        state,
+        tid,
+        "deleted_room",
+        rooms.len() as u64,
+        grace_days,
+        before,
+    )
+    .await;
+
+    Ok(())
+}
+
+/// Hard-delete messages with their reactions and notifications, and
+/// soft-delete attachments no remaining message uses.
+async fn purge_messages(state: &AppState, tenant_id: ObjectId, ids: &[ObjectId]) -> DaoResult<u64> {
+    let files = state.messages.attachment_file_ids(tenant_id, ids).await?;
+    state.reactions.delete_for_messages(ids).await?;
+    state
+        .notifications
+        .delete_for_entities(tenant_id, ids)
+        .await?;
+    let purged = state.messages.delete_by_ids(tenant_id, ids).await?;
+
+    if !files.is_empty() {
+        let in_use = state.messages.attached_file_ids(tenant_id, &files).await?;
+        let orphaned: Vec<ObjectId> = files.into_iter().filter(|f| !in_use.contains(f)).collect();
+        let freed = state.files.soft_delete_many(tenant_id, &orphaned).await?;
+        storage_quota::record_delete(state, tenant_id, freed).await;
+    }
+    Ok(purged)
+}
+
+/// Hard-delete a room: its messages as in [`purge_messages`], then
+/// everything else it holds.
+async fn purge_room(state: &AppState, tenant_id: ObjectId, room_id: ObjectId) -> DaoResult<()> {
+    loop {
+        let ids = state
+            .messages
+            .find_room_ids(tenant_id, room_id, PURGE_BATCH)
+            .await?;
+        if ids.is_empty() {
+            break;
+        }
+        purge_messages(state, tenant_id, &ids).await?;
+    }
+    state
+        .notifications
+        .delete_for_entities(tenant_id, &[room_id])
+        .await?;
+    state.rooms.cascade_delete(tenant_id, room_id).await
+}
+
+async fn record_purge(
+    state: &AppState,
+    tenant_id: ObjectId,
+    target_type: &'static str,
+    count: u64,
+    days: u32,
+    before: DateTime,
+) {
+    if count == 0 {
+        return;
+    }
+    tracing::info!(%tenant_id, target_type, count, "Retention purge");
+    audit::record_system(
+        state,
+        tenant_id,
+        "retention.purge",
+        target_type,
+        None,
+        &serde_json::json!({
+            "count": count,
+            "retention_days": days,
+            "before": before.try_to_rfc3339_string().unwrap_or_default(),
+        }),
+    )
+    .await;
+}
+
+/// Whether something soft-deleted at `deleted_at` may still be restored.
+pub(crate) fn restorable(state: &AppState, deleted_at: DateTime) -> bool {
+    let grace_days = state.settings.retention.deleted_grace_days;
+    grace_days == 0 || deleted_at >= cutoff(DateTime::now(), grace_days)
+}
+
+/// `days` before `now`.
+fn cutoff(now: DateTime, days: u32) -> DateTime {
+    DateTime::from_millis(now.timestamp_millis() - i64::from(days) * 86_400_000)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cutoff_is_whole_days_back() {
+        let now = DateTime::from_millis(10 * 86_400_000 + 5);
+        assert_eq!(cutoff(now, 3).timestamp_millis(), 7 * 86_400_000 + 5);
+        assert_eq!(cutoff(now, 0), now);
+    }
+}
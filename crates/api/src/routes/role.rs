@@ -4,9 +4,16 @@ use axum::{
 };
 use bson::oid::ObjectId;
 use roomler_ai_db::models::role::permissions;
+use roomler_ai_services::RoleChange;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
 
 /// Require `MANAGE_ROLES` for role administration. Doubles as the membership
 /// check — `get_member_permissions` returns `Forbidden` for a non-member;
@@ -28,7 +35,19 @@ async fn require_manage_roles(
     Ok(())
 }
 
-#[derive(Debug, Serialize)]
+/// Reject bits outside the defined permission set so a typo'd mask can't
+/// silently grant flags added later.
+fn validate_permissions(perms: Option<u64>) -> Result<(), ApiError> {
+    match perms {
+        Some(p) if p & !permissions::ALL != 0 => Err(ApiError::Validation(format!(
+            "Unknown permission bits: {:#x}",
+            p & !permissions::ALL
+        ))),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct RoleResponse {
     pub id: String,
     pub tenant_id: String,
@@ -42,7 +61,7 @@ pub struct RoleResponse {
     pub is_mentionable: bool,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateRoleRequest {
     pub name: String,
     pub description: Option<String>,
@@ -51,7 +70,7 @@ pub struct CreateRoleRequest {
     pub position: Option<u32>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRoleRequest {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -60,6 +79,13 @@ pub struct UpdateRoleRequest {
     pub position: Option<u32>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/role",
+    tag = "role",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<RoleResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -78,9 +104,18 @@ pub async fn list(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/role",
+    tag = "role",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateRoleRequest,
+    responses((status = 200, body = RoleResponse))
+)]
 pub async fn create(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path(tenant_id): Path<String>,
     Json(body): Json<CreateRoleRequest>,
 ) -> Result<Json<RoleResponse>, ApiError> {
@@ -88,6 +123,7 @@ pub async fn create(
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
 
     require_manage_roles(&state, tid, auth.user_id).await?;
+    validate_permissions(body.permissions)?;
 
     let role = state
         .roles
@@ -102,13 +138,31 @@ pub async fn create(
             body.position.unwrap_or(100),
         )
         .await?;
+    let role_id = role.id;
+    let response = to_response(role);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "role.create", "role", role_id).after(&response),
+    )
+    .await;
 
-    Ok(Json(to_response(role)))
+    Ok(Json(response))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/role/{role_id}",
+    tag = "role",
+    params(("tenant_id" = String, Path), ("role_id" = String, Path)),
+    request_body = UpdateRoleRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn update(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, role_id)): Path<(String, String)>,
     Json(body): Json<UpdateRoleRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
@@ -118,7 +172,21 @@ pub async fn update(
         .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
 
     require_manage_roles(&state, tid, auth.user_id).await?;
+    validate_permissions(body.permissions)?;
 
+    let before = to_response(state.roles.base.find_by_id_in_tenant(tid, rid).await?);
+    if let Some(perms) = body.permissions {
+        state
+            .permissions
+            .ensure_admin_remains(
+                tid,
+                RoleChange::SetPermissions {
+                    role_id: rid,
+                    permissions: perms,
+                },
+            )
+            .await?;
+    }
     state
         .roles
         .update(
@@ -131,13 +199,31 @@ pub async fn update(
             body.position,
         )
         .await?;
+    let after = to_response(state.roles.base.find_by_id_in_tenant(tid, rid).await?);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "role.update", "role", Some(rid))
+            .before(&before)
+            .after(&after),
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "updated": true })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/role/{role_id}",
+    tag = "role",
+    params(("tenant_id" = String, Path), ("role_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, role_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -147,14 +233,34 @@ pub async fn delete(
 
     require_manage_roles(&state, tid, auth.user_id).await?;
 
+    let before = to_response(state.roles.base.find_by_id_in_tenant(tid, rid).await?);
+    state
+        .permissions
+        .ensure_admin_remains(tid, RoleChange::DeleteRole(rid))
+        .await?;
     state.roles.delete(rid, tid).await?;
 
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "role.delete", "role", Some(rid)).before(&before),
+    )
+    .await;
+
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/role/{role_id}/assign/{user_id}",
+    tag = "role",
+    params(("tenant_id" = String, Path), ("role_id" = String, Path), ("user_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn assign(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, role_id, user_id)): Path<(String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -166,14 +272,24 @@ pub async fn assign(
 
     require_manage_roles(&state, tid, auth.user_id).await?;
 
+    let before = state.tenants.member_role_ids(tid, uid).await?;
     state.tenants.assign_role(tid, uid, rid).await?;
+    record_member_roles(&state, &ctx, tid, auth.user_id, uid, "role.assign", before).await;
 
     Ok(Json(serde_json::json!({ "assigned": true })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/role/{role_id}/assign/{user_id}",
+    tag = "role",
+    params(("tenant_id" = String, Path), ("role_id" = String, Path), ("user_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn unassign(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, role_id, user_id)): Path<(String, String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -185,11 +301,59 @@ pub async fn unassign(
 
     require_manage_roles(&state, tid, auth.user_id).await?;
 
+    state
+        .permissions
+        .ensure_admin_remains(
+            tid,
+            RoleChange::Unassign {
+                user_id: uid,
+                role_id: rid,
+            },
+        )
+        .await?;
+
+    let before = state.tenants.member_role_ids(tid, uid).await?;
     state.tenants.remove_role(tid, uid, rid).await?;
+    record_member_roles(
+        &state,
+        &ctx,
+        tid,
+        auth.user_id,
+        uid,
+        "role.unassign",
+        before,
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "removed": true })))
 }
 
+/// Audit a member's role set change as a `role_ids` before/after diff.
+async fn record_member_roles(
+    state: &AppState,
+    ctx: &AuditContext,
+    tenant_id: ObjectId,
+    actor_id: ObjectId,
+    user_id: ObjectId,
+    action: &'static str,
+    before: Vec<ObjectId>,
+) {
+    let hex = |ids: Vec<ObjectId>| ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>();
+    let after = state
+        .tenants
+        .member_role_ids(tenant_id, user_id)
+        .await
+        .unwrap_or_default();
+    audit::record(
+        state,
+        ctx,
+        AuditEntry::new(tenant_id, actor_id, action, "member", Some(user_id))
+            .before(&serde_json::json!({ "role_ids": hex(before) }))
+            .after(&serde_json::json!({ "role_ids": hex(after) })),
+    )
+    .await;
+}
+
 fn to_response(r: roomler_ai_db::models::Role) -> RoleResponse {
     RoleResponse {
         id: r.id.unwrap().to_hex(),
@@ -1,38 +1,42 @@
 use axum::{
     Json,
     extract::{Path, Query, State},
+    http::HeaderMap,
 };
 use bson::oid::ObjectId;
 use serde::{Deserialize, Serialize};
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_db::models::MediaSettings;
-use roomler_ai_services::dao::base::PaginationParams;
-
-#[derive(Debug, Deserialize)]
-pub struct CreateRoomRequest {
-    pub name: String,
-    pub parent_id: Option<String>,
-    #[serde(default)]
-    pub is_open: bool,
-    pub media_settings: Option<MediaSettings>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct RoomResponse {
-    pub id: String,
-    pub name: String,
-    pub path: String,
-    pub parent_id: Option<String>,
-    pub is_open: bool,
-    pub member_count: u32,
-    pub message_count: u64,
-    pub has_media: bool,
-    pub conference_status: Option<String>,
-    pub meeting_code: Option<String>,
-    pub participant_count: u32,
-}
-
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::feature::Features,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    middleware::audit::{self, AuditContext, AuditEntry},
+    routes::retention,
+    state::AppState,
+    ws::conference_registry::Ownership,
+};
+use roomler_ai_db::models::{
+    AuthorType, CallChatMessage, CallSession, PermissionOverwrite, role::permissions,
+};
+use roomler_ai_services::dao::base::{PaginatedResult, PaginationParams};
+use roomler_ai_services::permissions::{OVERWRITE_EVERYONE, OVERWRITE_MEMBER, OVERWRITE_ROLE};
+use roomler_ai_services::turn::{CREDENTIAL_TTL_SECS, TurnService};
+use roomler_ai_services::{assistant, feature_flags};
+use utoipa::{IntoParams, ToSchema};
+
+pub use roomler_ai_types::room::{CallStartResponse, CreateRoomRequest, RoomResponse};
+
+/// Lowest per-transport bitrate cap a room may set, in bps.
+const MIN_BITRATE_CAP: u32 = 100_000;
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room",
+    tag = "room",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<RoomResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -51,6 +55,14 @@ pub async fn list(
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room",
+    tag = "room",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateRoomRequest,
+    responses((status = 200, body = RoomResponse))
+)]
 pub async fn create(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -70,6 +82,20 @@ pub async fn create(
         .map(ObjectId::parse_str)
         .transpose()
         .map_err(|_| ApiError::BadRequest("Invalid parent_id".to_string()))?;
+    if let Some(media) = &body.media_settings {
+        let caps = [media.max_incoming_bitrate, media.max_outgoing_bitrate];
+        if caps.into_iter().flatten().any(|bps| bps < MIN_BITRATE_CAP) {
+            return Err(ApiError::Validation(format!(
+                "Bitrate caps must be at least {} bps",
+                MIN_BITRATE_CAP
+            )));
+        }
+        if media.max_participants == Some(0) {
+            return Err(ApiError::Validation(
+                "max_participants must be at least 1".to_string(),
+            ));
+        }
+    }
 
     let room = state
         .rooms
@@ -87,6 +113,13 @@ pub async fn create(
     Ok(Json(to_response(room)))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/join",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn join(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -102,6 +135,13 @@ pub async fn join(
     Ok(Json(serde_json::json!({ "joined": true })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/leave",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn leave(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -117,6 +157,13 @@ pub async fn leave(
     Ok(Json(serde_json::json!({ "left": true })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = RoomResponse))
+)]
 pub async fn get(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -132,11 +179,14 @@ pub async fn get(
     }
 
     let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    if room.deleted_at.is_some() {
+        return Err(ApiError::NotFound("Room not found".to_string()));
+    }
 
     Ok(Json(to_response(room)))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateRoomRequest {
     pub name: Option<String>,
     pub topic: Option<String>,
@@ -144,8 +194,18 @@ pub struct UpdateRoomRequest {
     pub is_open: Option<bool>,
     pub is_archived: Option<bool>,
     pub is_read_only: Option<bool>,
+    /// Only members with `MANAGE_CHANNELS` may post.
+    pub is_announcement: Option<bool>,
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = UpdateRoomRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn update(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -157,9 +217,10 @@ pub async fn update(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_CHANNELS)
+        .await?;
 
     state
         .rooms
@@ -172,15 +233,24 @@ pub async fn update(
             body.is_open,
             body.is_archived,
             body.is_read_only,
+            body.is_announcement,
         )
         .await?;
 
     Ok(Json(serde_json::json!({ "updated": true })))
 }
 
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn delete(
     State(state): State<AppState>,
     auth: AuthUser,
+    ctx: AuditContext,
     Path((tenant_id, room_id)): Path<(String, String)>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
@@ -188,21 +258,253 @@ pub async fn delete(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_CHANNELS)
+        .await?;
 
-    state.rooms.cascade_delete(tid, rid).await?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    state.rooms.soft_delete(tid, rid).await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "room.delete", "room", Some(rid))
+            .before(&to_response(room)),
+    )
+    .await;
 
     Ok(Json(serde_json::json!({ "deleted": true })))
 }
 
+/// Bring back a deleted room within `retention.deleted_grace_days`. Needs
+/// `MANAGE_CHANNELS` at the tenant level, since the room's own overwrites
+/// are not consulted while it is deleted.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/restore",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = RoomResponse))
+)]
+pub async fn restore(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let perms = state
+        .permissions
+        .tenant_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_CHANNELS) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_CHANNELS permission".to_string(),
+        ));
+    }
+
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    let Some(deleted_at) = room.deleted_at else {
+        return Err(ApiError::Conflict("Room is not deleted".to_string()));
+    };
+    if !retention::restorable(&state, deleted_at) {
+        return Err(ApiError::Conflict("Restore window has passed".to_string()));
+    }
+
+    state.rooms.restore(tid, rid).await?;
+    let response = to_response(state.rooms.base.find_by_id_in_tenant(tid, rid).await?);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "room.restore", "room", Some(rid)).after(&response),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct OverwriteDto {
+    /// `everyone`, `role` or `member`.
+    pub target_type: String,
+    /// Role or user id; ignored for `everyone`.
+    #[serde(default)]
+    pub target_id: Option<String>,
+    #[serde(default)]
+    pub allow: u64,
+    #[serde(default)]
+    pub deny: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct RoomPermissionsResponse {
+    /// The caller's effective permissions in this room.
+    pub permissions: u64,
+    pub overwrites: Vec<OverwriteDto>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetOverwritesRequest {
+    pub overwrites: Vec<OverwriteDto>,
+}
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/permission — the caller's
+/// effective permissions and the room's overwrites.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/permission",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = RoomPermissionsResponse))
+)]
+pub async fn get_permissions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<RoomPermissionsResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let perms = state
+        .permissions
+        .room_permissions(tid, rid, auth.user_id)
+        .await?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    Ok(Json(RoomPermissionsResponse {
+        permissions: perms,
+        overwrites: room
+            .permission_overwrites
+            .into_iter()
+            .map(overwrite_to_dto)
+            .collect(),
+    }))
+}
+
+/// PUT /api/tenant/{tenant_id}/room/{room_id}/permission — replace the
+/// room's overwrites. Requires `MANAGE_ROLES` in the room.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/permission",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = SetOverwritesRequest,
+    responses((status = 200, body = Vec<OverwriteDto>))
+)]
+pub async fn set_permissions(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<SetOverwritesRequest>,
+) -> Result<Json<Vec<OverwriteDto>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_ROLES)
+        .await?;
+
+    let overwrites = body
+        .overwrites
+        .into_iter()
+        .map(|o| overwrite_from_dto(tid, o))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let before = state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    state
+        .rooms
+        .set_permission_overwrites(tid, rid, &overwrites)
+        .await?;
+
+    let before: Vec<OverwriteDto> = before
+        .permission_overwrites
+        .into_iter()
+        .map(overwrite_to_dto)
+        .collect();
+    let after: Vec<OverwriteDto> = overwrites.into_iter().map(overwrite_to_dto).collect();
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "room.permissions", "room", Some(rid))
+            .before(&serde_json::json!({ "overwrites": before }))
+            .after(&serde_json::json!({ "overwrites": after })),
+    )
+    .await;
+
+    Ok(Json(after))
+}
+
+fn overwrite_from_dto(
+    tenant_id: ObjectId,
+    o: OverwriteDto,
+) -> Result<PermissionOverwrite, ApiError> {
+    let target_id = match o.target_type.as_str() {
+        // The tenant id stands in for "everyone", like Discord's @everyone
+        // role sharing the guild id.
+        OVERWRITE_EVERYONE => tenant_id,
+        OVERWRITE_ROLE | OVERWRITE_MEMBER => o
+            .target_id
+            .as_deref()
+            .and_then(|id| ObjectId::parse_str(id).ok())
+            .ok_or_else(|| ApiError::Validation("Overwrite needs a valid target_id".to_string()))?,
+        other => {
+            return Err(ApiError::Validation(format!(
+                "Unknown overwrite target_type '{}'",
+                other
+            )));
+        }
+    };
+    Ok(PermissionOverwrite {
+        target_id,
+        target_type: o.target_type,
+        allow: o.allow,
+        deny: o.deny,
+    })
+}
+
+fn overwrite_to_dto(o: PermissionOverwrite) -> OverwriteDto {
+    OverwriteDto {
+        target_id: (o.target_type != OVERWRITE_EVERYONE).then(|| o.target_id.to_hex()),
+        target_type: o.target_type,
+        allow: o.allow,
+        deny: o.deny,
+    }
+}
+
+const MEMBERS_SPEC: ListSpec = ListSpec {
+    sort: &[("joined_at", "joined_at")],
+    filters: &[
+        FilterField::new("user_id", "user_id", FieldKind::ObjectId),
+        FilterField::new("is_muted", "is_muted", FieldKind::Bool),
+        FilterField::new("joined_at", "joined_at", FieldKind::DateTime),
+    ],
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/member",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<serde_json::Value>))
+)]
 pub async fn members(
     State(state): State<AppState>,
     auth: AuthUser,
     Path((tenant_id, room_id)): Path<(String, String)>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<serde_json::Value>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
@@ -212,7 +514,11 @@ pub async fn members(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
-    let result = state.rooms.list_members(rid, &params).await?;
+    let options = query.options(&MEMBERS_SPEC)?;
+    let result = state
+        .rooms
+        .list_members(rid, &query.pagination, &options)
+        .await?;
 
     // Batch-fetch user details (username, avatar) for member user IDs
     let user_ids: Vec<ObjectId> = result.items.iter().filter_map(|m| m.user_id).collect();
@@ -227,7 +533,10 @@ pub async fn members(
         let mut map = std::collections::HashMap::new();
         for user in users {
             if let Some(uid) = user.id {
-                map.insert(uid, (user.username, user.avatar, user.display_name));
+                map.insert(
+                    uid,
+                    (user.username, user.avatar, user.display_name, user.is_bot),
+                );
             }
         }
         map
@@ -235,39 +544,36 @@ pub async fn members(
         std::collections::HashMap::new()
     };
 
-    let items: Vec<serde_json::Value> = result
-        .items
-        .iter()
-        .map(|m| {
-            let user_info = m.user_id.and_then(|uid| user_map.get(&uid));
-            serde_json::json!({
-                "id": m.id.unwrap().to_hex(),
-                "user_id": m.user_id.map(|u| u.to_hex()),
-                "room_id": m.room_id.to_hex(),
-                "display_name": user_info.map(|u| u.2.clone()).or_else(|| m.display_name.clone()).unwrap_or_default(),
-                "username": user_info.map(|u| u.0.clone()),
-                "avatar": user_info.and_then(|u| u.1.clone()),
-                "joined_at": m.joined_at.try_to_rfc3339_string().unwrap_or_default(),
-                "unread_count": m.unread_count,
-                "is_muted": m.is_muted,
-            })
+    Ok(Json(result.map(|m| {
+        let user_info = m.user_id.and_then(|uid| user_map.get(&uid));
+        serde_json::json!({
+            "id": m.id.unwrap().to_hex(),
+            "user_id": m.user_id.map(|u| u.to_hex()),
+            "room_id": m.room_id.to_hex(),
+            "display_name": user_info.map(|u| u.2.clone()).or_else(|| m.display_name.clone()).unwrap_or_default(),
+            "username": user_info.map(|u| u.0.clone()),
+            "avatar": user_info.and_then(|u| u.1.clone()),
+            "is_bot": user_info.is_some_and(|u| u.3),
+            "joined_at": m.joined_at.try_to_rfc3339_string().unwrap_or_default(),
+            "unread_count": m.unread_count,
+            "is_muted": m.is_muted,
         })
-        .collect();
-
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
     })))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct ExploreQuery {
     pub q: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/explore",
+    tag = "room",
+    params(("tenant_id" = String, Path), ExploreQuery),
+    responses((status = 200, body = Vec<RoomResponse>))
+)]
 pub async fn explore(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -289,26 +595,110 @@ pub async fn explore(
 
 // ── Call endpoints ──────────────────────────────────────────────
 
+/// Who to ring. Without either field members only get `room:call_started`.
+#[derive(Debug, Default, Deserialize, ToSchema)]
+#[serde(default)]
+pub struct CallStartRequest {
+    /// Members to ring with `call:ring`; ids not in the room are ignored.
+    pub ring: Vec<String>,
+    /// Ring every member of the room but the caller.
+    pub ring_all: bool,
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/start",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CallStartRequest,
+    responses((status = 200, body = CallStartResponse))
+)]
 pub async fn call_start(
     State(state): State<AppState>,
     auth: AuthUser,
+    features: Features,
     Path((tenant_id, room_id)): Path<(String, String)>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    body: Option<Json<CallStartRequest>>,
+) -> Result<Json<CallStartResponse>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let body = body.map(|Json(b)| b).unwrap_or_default();
+    let ring = body
+        .ring
+        .iter()
+        .map(ObjectId::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::BadRequest("Invalid user id in ring".to_string()))?;
 
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
     state.rooms.start_call(rid).await?;
-    let rtp_capabilities = state
-        .room_manager
-        .create_room(rid)
-        .await
-        .map_err(|e| ApiError::Internal(format!("Failed to create media room: {}", e)))?;
+    state.call_sessions.start(tid, rid, auth.user_id).await?;
+    let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
+    let media_settings = room.as_ref().and_then(|r| r.media_settings.as_ref());
+    // E2EE rolls out per room, so everyone in a call gets the same answer.
+    let e2ee = media_settings.is_some_and(|m| m.e2ee_enabled)
+        && features.enabled_for(feature_flags::E2EE, &rid);
+    let push_to_talk = media_settings.is_some_and(|m| m.push_to_talk);
+    let webinar = media_settings.is_some_and(|m| m.webinar);
+    let max_participants = media_settings.and_then(|m| m.max_participants);
+    let overflow = media_settings.map(|m| m.overflow).unwrap_or_default();
+    let bitrate_caps = media_settings
+        .map(|m| (m.max_incoming_bitrate, m.max_outgoing_bitrate))
+        .unwrap_or_default();
+
+    // Multi-pod: the Router lives on whichever pod claimed the conference
+    // first. If that's another pod, don't spin up a second Router here —
+    // hand the client the owner's URL instead.
+    let media_url = match &state.conference_registry {
+        Some(registry) => match registry
+            .claim(&rid)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Conference registry: {}", e)))?
+        {
+            Ownership::Local => None,
+            Ownership::Remote(url) => Some(url),
+        },
+        None => None,
+    };
+    let rtp_capabilities = if media_url.is_none() {
+        let caps = state
+            .room_manager
+            .create_room(rid)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to create media room: {}", e)))?;
+        state.room_manager.set_e2ee(&rid, e2ee);
+        state
+            .room_manager
+            .set_bitrate_caps(&rid, bitrate_caps.0, bitrate_caps.1);
+        state
+            .room_manager
+            .set_push_to_talk(&rid, push_to_talk)
+            .await;
+        // The host of a webinar starts out as its first speaker.
+        state.room_manager.set_webinar(&rid, webinar);
+        if webinar {
+            state.room_manager.set_speaker(&rid, &auth.user_id, true);
+        }
+        state
+            .room_manager
+            .set_capacity(&rid, max_participants, overflow);
+        caps
+    } else {
+        serde_json::Value::Null
+    };
+
+    super::webhook::dispatch_event(
+        &state,
+        tid,
+        rid,
+        "call.started",
+        serde_json::json!({ "started_by": auth.user_id.to_hex() }),
+    );
 
     // Notify all room members about the call
     let member_ids = state
@@ -317,7 +707,6 @@ pub async fn call_start(
         .await
         .unwrap_or_default();
     if !member_ids.is_empty() {
-        let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
         let room_name = room.map(|r| r.name).unwrap_or_default();
         let event = serde_json::json!({
             "type": "room:call_started",
@@ -327,13 +716,18 @@ pub async fn call_start(
                 "started_by": auth.user_id.to_hex(),
             }
         });
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &member_ids,
-            &event,
-        )
-        .await;
+        crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
+
+        let rung: Vec<ObjectId> = member_ids
+            .iter()
+            .copied()
+            .filter(|id| *id != auth.user_id && (body.ring_all || ring.contains(id)))
+            .collect();
+        let not_rung: Vec<ObjectId> = member_ids
+            .iter()
+            .copied()
+            .filter(|id| !rung.contains(id))
+            .collect();
 
         // Create persistent call notifications + push for offline members via helper
         let caller_names = state
@@ -351,21 +745,31 @@ pub async fn call_start(
             tid,
             rid,
             auth.user_id,
-            &member_ids,
+            &not_rung,
             &room_name,
             &caller_name,
             &tenant_id,
             &room_id,
         )
         .await;
+        crate::ws::ring::start(&state, tid, rid, auth.user_id, caller_name, room_name, rung).await;
     }
 
-    Ok(Json(serde_json::json!({
-        "started": true,
-        "rtp_capabilities": rtp_capabilities,
-    })))
+    Ok(Json(CallStartResponse {
+        started: true,
+        rtp_capabilities,
+        media_url,
+        e2ee,
+    }))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/join",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn call_join(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -379,13 +783,31 @@ pub async fn call_join(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    crate::middleware::plan_limits::check_call_join(&state, tid, rid, auth.user_id).await?;
+    // The cap is enforced by the pod hosting the call's media; elsewhere
+    // `media:join` is redirected there and checked on arrival.
+    let overflow = state
+        .room_manager
+        .admission(&rid, &auth.user_id)
+        .map_err(|full| ApiError::RoomFull(full.to_string()))?;
 
     let user = state.users.base.find_by_id(auth.user_id).await?;
 
     let member = state
         .rooms
-        .join_participant(tid, rid, auth.user_id, user.display_name, "web".to_string())
+        .join_participant(
+            tid,
+            rid,
+            auth.user_id,
+            user.display_name.clone(),
+            "web".to_string(),
+        )
+        .await?;
+    state
+        .call_sessions
+        .record_join(rid, auth.user_id, user.display_name, "web".to_string())
         .await?;
+    crate::ws::ring::answer(&state, auth.user_id, rid, "accepted").await;
 
     // Notify room members about updated participant count
     let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
@@ -404,21 +826,75 @@ pub async fn call_join(
                 "conference_status": "in_progress",
             }
         });
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &member_ids,
-            &event,
-        )
-        .await;
+        crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
     }
 
     Ok(Json(serde_json::json!({
         "member_id": member.id.unwrap().to_hex(),
         "joined": true,
+        "overflow": overflow,
     })))
 }
 
+#[derive(Debug, Serialize, ToSchema)]
+pub struct IceServersResponse {
+    /// RTCIceServer entries, nearest TURN region first.
+    #[schema(value_type = Vec<Object>)]
+    pub ice_servers: Vec<serde_json::Value>,
+    /// Clients should set `iceTransportPolicy: "relay"`.
+    pub force_relay: bool,
+    /// Lifetime of the minted credentials; `None` for static ones.
+    pub ttl_secs: Option<u64>,
+}
+
+/// ICE servers for calls in the room, the same `media:join` hands out, for
+/// native clients and pre-call device tests that need them before the
+/// WebSocket is open.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/ice",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = IceServersResponse))
+)]
+pub async fn ice_servers(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<IceServersResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    let runtime = state.runtime.load();
+    let turn = &runtime.turn;
+    let country = crate::ws::turn_regions::client_country(&headers);
+    let ice =
+        TurnService::new(turn).ice_servers(&auth.user_id.to_hex(), country.as_deref(), |name| {
+            state.turn_region_stats.is_healthy(name)
+        });
+
+    Ok(Json(IceServersResponse {
+        ice_servers: ice.servers,
+        force_relay: turn.force_relay.unwrap_or(false),
+        ttl_secs: turn.shared_secret.as_ref().map(|_| CREDENTIAL_TTL_SECS),
+    }))
+}
+
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/leave",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn call_leave(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -433,10 +909,21 @@ pub async fn call_leave(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
+    leave_call(&state, tid, rid, auth.user_id).await?;
+    Ok(Json(serde_json::json!({ "left": true })))
+}
+
+/// Take `user_id` out of the call: close their media, tell the others and
+/// end the call if they were the last one in it.
+pub(crate) async fn leave_call(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
     // Clean up media before DB leave
-    state
-        .room_manager
-        .close_participant_by_user(&rid, &auth.user_id);
+    state.room_manager.close_participant_by_user(&rid, &user_id);
+    crate::ws::e2ee::rotate_and_announce(state, &rid, "leave").await;
 
     // Broadcast peer_left to remaining participants
     let remaining = state.room_manager.get_participant_user_ids(&rid);
@@ -445,7 +932,7 @@ pub async fn call_leave(
             "type": "media:peer_left",
             "data": {
                 "room_id": rid.to_hex(),
-                "user_id": auth.user_id.to_hex(),
+                "user_id": user_id.to_hex(),
             }
         });
         crate::ws::dispatcher::broadcast_with_redis(
@@ -457,7 +944,9 @@ pub async fn call_leave(
         .await;
     }
 
-    state.rooms.leave_participant(rid, auth.user_id).await?;
+    state.rooms.leave_participant(rid, user_id).await?;
+    let call_secs = state.call_sessions.record_leave(rid, user_id).await?;
+    super::usage::book_call(state, tid, call_secs).await;
 
     // Check if this was the last participant — if so, auto-end the call
     let room = state.rooms.base.find_by_id_in_tenant(tid, rid).await.ok();
@@ -466,7 +955,23 @@ pub async fn call_leave(
         && room.conference_status.as_deref() == Some("in_progress")
     {
         state.rooms.end_call(rid).await?;
+        crate::ws::ring::cancel(state, rid).await;
+        crate::ws::playback::cancel(state, &rid);
+        crate::ws::speech::cancel(state, &rid);
+        super::breakout::close_all(state, tid, rid).await?;
+        super::poll::close_all(state, rid).await?;
+        let call_secs = state.call_sessions.end(rid).await?;
+        super::usage::book_call(state, tid, call_secs).await;
+        crate::ws::whiteboard::finish(state, rid, user_id).await;
         state.room_manager.remove_room(&rid);
+        release_conference(state, &rid).await;
+        super::webhook::dispatch_event(
+            state,
+            tid,
+            rid,
+            "call.ended",
+            serde_json::json!({ "ended_by": user_id.to_hex() }),
+        );
 
         // Notify all room members that the call has ended
         let member_ids = state
@@ -481,19 +986,20 @@ pub async fn call_leave(
                     "room_id": rid.to_hex(),
                 }
             });
-            crate::ws::dispatcher::broadcast_with_redis(
-                &state.ws_storage,
-                &state.redis_pubsub,
-                &member_ids,
-                &event,
-            )
-            .await;
+            crate::ws::event_log::publish(state, tid, rid, &member_ids, event).await;
         }
     }
 
-    Ok(Json(serde_json::json!({ "left": true })))
+    Ok(())
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/end",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn call_end(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -504,12 +1010,29 @@ pub async fn call_end(
     let rid = ObjectId::parse_str(&room_id)
         .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
 
-    if !state.tenants.is_member(tid, auth.user_id).await? {
-        return Err(ApiError::Forbidden("Not a member".to_string()));
-    }
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
 
     state.rooms.end_call(rid).await?;
+    crate::ws::ring::cancel(&state, rid).await;
+    crate::ws::playback::cancel(&state, &rid);
+    crate::ws::speech::cancel(&state, &rid);
+    super::breakout::close_all(&state, tid, rid).await?;
+    super::poll::close_all(&state, rid).await?;
+    let call_secs = state.call_sessions.end(rid).await?;
+    super::usage::book_call(&state, tid, call_secs).await;
+    crate::ws::whiteboard::finish(&state, rid, auth.user_id).await;
     state.room_manager.remove_room(&rid);
+    release_conference(&state, &rid).await;
+    super::webhook::dispatch_event(
+        &state,
+        tid,
+        rid,
+        "call.ended",
+        serde_json::json!({ "ended_by": auth.user_id.to_hex() }),
+    );
 
     let remaining = state.room_manager.get_participant_user_ids(&rid);
     if !remaining.is_empty() {
@@ -539,18 +1062,29 @@ pub async fn call_end(
                 "room_id": rid.to_hex(),
             }
         });
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &member_ids,
-            &event,
-        )
-        .await;
+        crate::ws::event_log::publish(&state, tid, rid, &member_ids, event).await;
     }
 
     Ok(Json(serde_json::json!({ "ended": true })))
 }
 
+/// Drop this pod's conference-registry claim once its Router is gone, so the
+/// next `call:start` can land on any pod.
+pub(crate) async fn release_conference(state: &AppState, room_id: &ObjectId) {
+    if let Some(registry) = &state.conference_registry
+        && let Err(e) = registry.release(room_id).await
+    {
+        tracing::warn!(%room_id, %e, "Failed to release conference ownership");
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/participant",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<serde_json::Value>))
+)]
 pub async fn participants(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -585,13 +1119,163 @@ pub async fn participants(
     Ok(Json(items))
 }
 
+// ── Call history ────────────────────────────────────────────
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CallSessionResponse {
+    pub id: String,
+    pub room_id: String,
+    pub started_by: String,
+    pub started_at: String,
+    /// `None` while the call is in progress.
+    pub ended_at: Option<String>,
+    /// Seconds from start to end; `None` while in progress.
+    pub duration: Option<i64>,
+    pub participant_count: u32,
+    pub peak_participants: u32,
+    pub participants: Vec<CallParticipantResponse>,
+    pub recording_ids: Vec<String>,
+    /// Participants who opted out of transcription.
+    pub transcription_opt_outs: Vec<String>,
+    /// Polls run during the call, oldest first.
+    pub polls: Vec<super::poll::PollResponse>,
+    /// Q&A questions, most upvoted first.
+    pub questions: Vec<super::question::QuestionResponse>,
+    /// Questions the meeting assistant answered, oldest first.
+    pub assistant_notes: Vec<AssistantNoteResponse>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct AssistantNoteResponse {
+    pub asked_by: String,
+    pub question: String,
+    pub answer: String,
+    pub asked_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CallParticipantResponse {
+    pub user_id: String,
+    pub display_name: String,
+    pub device_type: String,
+    pub joined_at: String,
+    pub left_at: Option<String>,
+    pub duration: Option<i64>,
+}
+
+/// Past calls in the room (and the current one, if any), newest first.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/history",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), PaginationParams),
+    responses((status = 200, body = PaginatedResult<CallSessionResponse>))
+)]
+pub async fn call_history(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Query(params): Query<PaginationParams>,
+) -> Result<Json<PaginatedResult<CallSessionResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let result = state.call_sessions.list_for_room(tid, rid, &params).await?;
+    let perms = state
+        .permissions
+        .room_permissions(tid, rid, auth.user_id)
+        .await?;
+    let moderator = permissions::has(perms, permissions::MANAGE_MEETINGS);
+
+    let mut result = result.map(to_call_session_response);
+    for session in &mut result.items {
+        let Ok(sid) = ObjectId::parse_str(&session.id) else {
+            continue;
+        };
+        session.polls = state
+            .call_polls
+            .list_for_session(sid)
+            .await?
+            .iter()
+            .map(|p| super::poll::to_response(p, moderator, Some(auth.user_id)))
+            .collect();
+        session.questions = state
+            .call_questions
+            .list_for_session(sid)
+            .await?
+            .iter()
+            .map(|q| super::question::to_response(q, Some(auth.user_id)))
+            .collect();
+    }
+    Ok(Json(result))
+}
+
+fn to_call_session_response(s: CallSession) -> CallSessionResponse {
+    let rfc3339 = |d: bson::DateTime| d.try_to_rfc3339_string().unwrap_or_default();
+    CallSessionResponse {
+        id: s.id.map(|i| i.to_hex()).unwrap_or_default(),
+        room_id: s.room_id.to_hex(),
+        started_by: s.started_by.to_hex(),
+        started_at: rfc3339(s.started_at),
+        ended_at: s.ended_at.map(rfc3339),
+        duration: s
+            .ended_at
+            .map(|e| (e.timestamp_millis() - s.started_at.timestamp_millis()) / 1000),
+        participant_count: s.participant_count,
+        peak_participants: s.peak_participants,
+        participants: s
+            .participants
+            .into_iter()
+            .map(|p| CallParticipantResponse {
+                user_id: p.user_id.to_hex(),
+                display_name: p.display_name,
+                device_type: p.device_type,
+                joined_at: rfc3339(p.joined_at),
+                left_at: p.left_at.map(rfc3339),
+                duration: p.duration,
+            })
+            .collect(),
+        recording_ids: s.recording_ids.iter().map(|r| r.to_hex()).collect(),
+        transcription_opt_outs: s
+            .transcription_opt_outs
+            .iter()
+            .map(|u| u.to_hex())
+            .collect(),
+        polls: Vec::new(),
+        questions: Vec::new(),
+        assistant_notes: s
+            .assistant_notes
+            .into_iter()
+            .map(|n| AssistantNoteResponse {
+                asked_by: n.asked_by.to_hex(),
+                question: n.question,
+                answer: n.answer,
+                asked_at: rfc3339(n.asked_at),
+            })
+            .collect(),
+    }
+}
+
 // ── Call chat message endpoints ─────────────────────────────
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateCallMessageRequest {
     pub content: String,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/message",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path), PaginationParams),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn call_messages(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -608,20 +1292,7 @@ pub async fn call_messages(
     }
 
     let result = state.rooms.find_chat_messages(rid, &params).await?;
-    let items: Vec<serde_json::Value> = result
-        .items
-        .iter()
-        .map(|m| {
-            serde_json::json!({
-                "id": m.id.unwrap().to_hex(),
-                "room_id": m.room_id.to_hex(),
-                "author_id": m.author_id.to_hex(),
-                "display_name": m.display_name,
-                "content": m.content,
-                "created_at": m.created_at.try_to_rfc3339_string().unwrap_or_default(),
-            })
-        })
-        .collect();
+    let items: Vec<serde_json::Value> = result.items.iter().map(call_message_json).collect();
 
     Ok(Json(serde_json::json!({
         "items": items,
@@ -632,9 +1303,18 @@ pub async fn call_messages(
     })))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/message",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = CreateCallMessageRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn create_call_message(
     State(state): State<AppState>,
     auth: AuthUser,
+    features: Features,
     Path((tenant_id, room_id)): Path<(String, String)>,
     Json(body): Json<CreateCallMessageRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
@@ -646,27 +1326,60 @@ pub async fn create_call_message(
     if !state.tenants.is_member(tid, auth.user_id).await? {
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
+    let command = match assistant::parse_command(&body.content) {
+        Some(Ok(command)) => {
+            let session = super::assistant::check(&state, &features, rid, &command).await?;
+            Some((session, command))
+        }
+        Some(Err(usage)) => return Err(ApiError::Validation(usage.to_string())),
+        None => None,
+    };
 
     let user = state.users.base.find_by_id(auth.user_id).await?;
+    let response = post_call_message(
+        &state,
+        tid,
+        rid,
+        auth.user_id,
+        AuthorType::User,
+        user.display_name.clone(),
+        body.content,
+    )
+    .await?;
+    if let Some((session, command)) = command {
+        super::assistant::answer(&state, &session, auth.user_id, user.display_name, command);
+    }
+
+    Ok(Json(response))
+}
+
+fn call_message_json(m: &CallChatMessage) -> serde_json::Value {
+    serde_json::json!({
+        "id": m.id.map(|i| i.to_hex()).unwrap_or_default(),
+        "room_id": m.room_id.to_hex(),
+        "author_id": m.author_id.to_hex(),
+        "author_type": m.author_type,
+        "display_name": m.display_name,
+        "content": m.content,
+        "created_at": m.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    })
+}
+
+/// Store an in-call chat message and send it to the room's members.
+pub(super) async fn post_call_message(
+    state: &AppState,
+    tid: ObjectId,
+    rid: ObjectId,
+    author_id: ObjectId,
+    author_type: AuthorType,
+    display_name: String,
+    content: String,
+) -> Result<serde_json::Value, ApiError> {
     let msg = state
         .rooms
-        .create_chat_message(
-            tid,
-            rid,
-            auth.user_id,
-            user.display_name.clone(),
-            body.content,
-        )
+        .create_chat_message(tid, rid, author_id, author_type, display_name, content)
         .await?;
-
-    let response = serde_json::json!({
-        "id": msg.id.unwrap().to_hex(),
-        "room_id": msg.room_id.to_hex(),
-        "author_id": msg.author_id.to_hex(),
-        "display_name": msg.display_name,
-        "content": msg.content,
-        "created_at": msg.created_at.try_to_rfc3339_string().unwrap_or_default(),
-    });
+    let response = call_message_json(&msg);
 
     // Broadcast to other room members via WS
     let member_ids = state
@@ -679,19 +1392,12 @@ pub async fn create_call_message(
             "type": "call:message:create",
             "data": &response,
         });
-        crate::ws::dispatcher::broadcast_with_redis(
-            &state.ws_storage,
-            &state.redis_pubsub,
-            &member_ids,
-            &event,
-        )
-        .await;
+        crate::ws::event_log::publish(state, tid, rid, &member_ids, event).await;
     }
-
-    Ok(Json(response))
+    Ok(response)
 }
 
-fn to_response(r: roomler_ai_db::models::Room) -> RoomResponse {
+pub(crate) fn to_response(r: roomler_ai_db::models::Room) -> RoomResponse {
     // `r.id.unwrap()` previously panicked when a Mongo document
     // somehow lacked `_id` (or arrived stripped through a custom
     // projection in the future). Any panic inside Axum's handler
@@ -708,11 +1414,15 @@ fn to_response(r: roomler_ai_db::models::Room) -> RoomResponse {
         path: r.path,
         parent_id: r.parent_id.map(|p| p.to_hex()),
         is_open: r.is_open,
+        is_read_only: r.is_read_only,
+        is_announcement: r.is_announcement,
         member_count: r.member_count,
         message_count: r.message_count,
         has_media: r.media_settings.is_some(),
+        e2ee_enabled: r.media_settings.as_ref().is_some_and(|m| m.e2ee_enabled),
         conference_status: r.conference_status,
         meeting_code: r.meeting_code,
         participant_count: r.participant_count,
+        legal_hold: r.legal_hold,
     }
 }
@@ -0,0 +1,162 @@
+//! "Send later": messages created with a future `send_at` wait in
+//! `scheduled_messages` until [`spawn_scheduler`]'s loop claims and posts them
+//! through the same [`message::deliver`] path as an immediate send.
+
+use std::time::Duration;
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{AuthorType, ScheduledMessage};
+use roomler_ai_services::dao::message::CreateMessageParams;
+
+use super::message;
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+pub use roomler_ai_types::message::ScheduledMessageResponse;
+
+/// How often the scheduler looks for due messages.
+const SCHEDULER_TICK: Duration = Duration::from_secs(1);
+
+/// GET /api/tenant/{tenant_id}/room/{room_id}/message/scheduled — the
+/// caller's pending messages in the room, soonest first.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/scheduled",
+    tag = "scheduled_message",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = Vec<ScheduledMessageResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<Vec<ScheduledMessageResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let items = state
+        .scheduled_messages
+        .find_for_author(rid, auth.user_id)
+        .await?;
+    Ok(Json(items.into_iter().map(to_response).collect()))
+}
+
+/// DELETE /api/tenant/{tenant_id}/room/{room_id}/message/scheduled/{id} —
+/// cancel one of the caller's pending messages.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/message/scheduled/{scheduled_id}",
+    tag = "scheduled_message",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("scheduled_id" = String, Path),
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn cancel(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, _room_id, scheduled_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let sid = ObjectId::parse_str(&scheduled_id)
+        .map_err(|_| ApiError::BadRequest("Invalid scheduled message id".to_string()))?;
+
+    if !state
+        .scheduled_messages
+        .cancel(tid, sid, auth.user_id)
+        .await?
+    {
+        return Err(ApiError::NotFound(
+            "Scheduled message not found".to_string(),
+        ));
+    }
+    Ok(Json(serde_json::json!({ "cancelled": true })))
+}
+
+/// Spawn the delivery loop. Each tick drains every due message; claiming
+/// deletes the row, so with several instances each message is posted once.
+pub(crate) fn spawn_scheduler(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(SCHEDULER_TICK);
+        loop {
+            tick.tick().await;
+            loop {
+                match state
+                    .scheduled_messages
+                    .claim_due(bson::DateTime::now())
+                    .await
+                {
+                    Ok(Some(scheduled)) => deliver_scheduled(&state, scheduled).await,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::warn!(%e, "Failed to claim scheduled messages");
+                        break;
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// Post a claimed message, re-checking that the author may still send in the
+/// room. A message that can't be posted is dropped with a warning.
+async fn deliver_scheduled(state: &AppState, scheduled: ScheduledMessage) {
+    let (tid, rid, author_id) = (scheduled.tenant_id, scheduled.room_id, scheduled.author_id);
+    let is_thread_reply = scheduled.thread_id.is_some();
+    let allowed = message::require_can_post(state, tid, rid, author_id, is_thread_reply).await;
+    let result = match allowed {
+        Ok(()) => {
+            message::deliver(
+                state,
+                CreateMessageParams {
+                    tenant_id: tid,
+                    room_id: rid,
+                    author_id,
+                    author_type: AuthorType::User,
+                    author_name: None,
+                    content: scheduled.content,
+                    thread_id: scheduled.thread_id,
+                    referenced_message_id: scheduled.referenced_message_id,
+                    nonce: None,
+                    mentions: Some(scheduled.mentions),
+                    attachments: scheduled.attachments,
+                    is_silent: scheduled.is_silent,
+                },
+            )
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!(
+            scheduled_id = ?scheduled.id,
+            %rid,
+            ?e,
+            "Dropped scheduled message"
+        );
+    }
+}
+
+pub(crate) fn to_response(s: ScheduledMessage) -> ScheduledMessageResponse {
+    ScheduledMessageResponse {
+        id: s.id.map(|id| id.to_hex()).unwrap_or_default(),
+        room_id: s.room_id.to_hex(),
+        content: s.content,
+        thread_id: s.thread_id.map(|t| t.to_hex()),
+        is_silent: s.is_silent,
+        send_at: s.send_at.try_to_rfc3339_string().unwrap_or_default(),
+        created_at: s.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
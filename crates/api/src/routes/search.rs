@@ -5,12 +5,14 @@ use axum::{
 use bson::{doc, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::error::ApiError;
 use crate::extractors::auth::AuthUser;
 use crate::state::AppState;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct SearchQuery {
     pub q: String,
     #[serde(default = "default_limit")]
@@ -21,7 +23,7 @@ fn default_limit() -> u64 {
     20
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SearchMessageResult {
     pub id: String,
     pub room_id: String,
@@ -32,7 +34,7 @@ pub struct SearchMessageResult {
     pub created_at: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SearchRoomResult {
     pub id: String,
     pub name: String,
@@ -41,7 +43,7 @@ pub struct SearchRoomResult {
     pub member_count: u32,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SearchUserResult {
     pub id: String,
     pub display_name: String,
@@ -50,13 +52,20 @@ pub struct SearchUserResult {
     pub avatar: Option<String>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct SearchResults {
     pub messages: Vec<SearchMessageResult>,
     pub rooms: Vec<SearchRoomResult>,
     pub users: Vec<SearchUserResult>,
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/search",
+    tag = "search",
+    params(("tenant_id" = String, Path), SearchQuery),
+    responses((status = 200, body = SearchResults))
+)]
 pub async fn search(
     State(state): State<AppState>,
     Path(tenant_id): Path<String>,
@@ -89,8 +98,7 @@ pub async fn search(
     };
     let messages = state
         .messages
-        .base
-        .text_search(q, msg_filter, limit)
+        .search(q, msg_filter, limit)
         .await
         .unwrap_or_default();
 
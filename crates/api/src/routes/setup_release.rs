@@ -26,6 +26,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     error::ApiError,
@@ -67,7 +68,8 @@ impl LatestSetupReleaseCache {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct InstallerQuery {
     #[serde(default = "default_version_latest")]
     pub version: String,
@@ -77,7 +79,8 @@ fn default_version_latest() -> String {
     "latest".to_string()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = SetupInstallerHealth)]
 pub struct InstallerHealth {
     pub tag: String,
     pub platform: String,
@@ -92,6 +95,13 @@ pub struct InstallerHealth {
 
 /// `GET /api/setup/latest-release` — recent `setup-v*` releases ONLY
 /// (filtered server-side from the mixed tag list the cache holds).
+#[utoipa::path(
+    get,
+    path = "/api/setup/latest-release",
+    tag = "setup_release",
+    responses((status = 200, body = Vec<AgentRelease>)),
+    security(())
+)]
 pub async fn setup_latest_release(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<AgentRelease>>, ApiError> {
@@ -106,6 +116,14 @@ pub async fn setup_latest_release(
 /// `GET /api/setup/{platform}/health` — manifest for the unified
 /// wizard EXE matching the requested platform + version. 404s until
 /// P4c tags the first `setup-v*` release.
+#[utoipa::path(
+    get,
+    path = "/api/setup/{platform}/health",
+    tag = "setup_release",
+    params(("platform" = String, Path), InstallerQuery),
+    responses((status = 200, body = InstallerHealth)),
+    security(())
+)]
 pub async fn setup_installer_health(
     State(state): State<AppState>,
     Path(platform): Path<String>,
@@ -136,6 +154,16 @@ pub async fn setup_installer_health(
 }
 
 /// `GET /api/setup/{platform}` — streams the unified wizard bytes.
+#[utoipa::path(
+    get,
+    path = "/api/setup/{platform}",
+    tag = "setup_release",
+    params(("platform" = String, Path), InstallerQuery),
+    responses(
+        (status = 200, description = "Installer binary", content_type = "application/octet-stream"),
+    ),
+    security(())
+)]
 pub async fn setup_installer_proxy(
     State(state): State<AppState>,
     Path(platform): Path<String>,
@@ -211,11 +239,27 @@ fn script_response(body: &'static str, content_type: &'static str) -> Response {
 }
 
 /// `GET /api/setup/install.sh` — the Linux/macOS terminal installer.
+#[utoipa::path(
+    get,
+    path = "/api/setup/install.sh",
+    tag = "setup_release",
+    responses(
+        (status = 200, description = "POSIX shell installer", content_type = "text/x-shellscript"),
+    ),
+    security(())
+)]
 pub async fn install_script_sh() -> Response {
     script_response(INSTALL_SH, "text/x-shellscript; charset=utf-8")
 }
 
 /// `GET /api/setup/install.ps1` — the Windows terminal installer.
+#[utoipa::path(
+    get,
+    path = "/api/setup/install.ps1",
+    tag = "setup_release",
+    responses((status = 200, description = "PowerShell installer", content_type = "text/plain")),
+    security(())
+)]
 pub async fn install_script_ps1() -> Response {
     script_response(INSTALL_PS1, "text/plain; charset=utf-8")
 }
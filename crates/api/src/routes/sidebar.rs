@@ -0,0 +1,223 @@
+//! Per-user sidebar.
+//!
+//! Pins, favorites and the custom order live on the user's `room_members`
+//! entries, so every member arranges their own sidebar. `GET .../sidebar`
+//! hands the client everything it needs at startup in one call: the joined
+//! rooms grouped into pinned, favorites and the rest, each with its unread
+//! count, plus the user's unread notification count.
+
+use std::collections::{HashMap, HashSet};
+
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::{doc, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::room::{RoomResponse, to_response};
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::{Room, RoomMember};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SidebarRoom {
+    pub room: RoomResponse,
+    pub is_pinned: bool,
+    pub is_favorite: bool,
+    pub is_muted: bool,
+    /// The user's custom position; `null` for rooms never reordered.
+    pub position: Option<i32>,
+    pub unread_count: u64,
+}
+
+/// Each joined room appears once: pinned wins over favorite. Every section is
+/// in the user's order, unordered rooms last by name.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SidebarResponse {
+    pub pinned: Vec<SidebarRoom>,
+    pub favorites: Vec<SidebarRoom>,
+    pub rooms: Vec<SidebarRoom>,
+    pub notification_unread_count: u64,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateSidebarRoomRequest {
+    pub is_pinned: Option<bool>,
+    pub is_favorite: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SidebarOrderRequest {
+    /// Joined rooms in sidebar order; rooms left out lose their position.
+    pub room_ids: Vec<String>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/sidebar",
+    tag = "sidebar",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = SidebarResponse))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<SidebarResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let memberships = state.rooms.find_memberships(tid, auth.user_id).await?;
+    let room_ids: Vec<ObjectId> = memberships.iter().map(|m| m.room_id).collect();
+    let rooms = if room_ids.is_empty() {
+        Vec::new()
+    } else {
+        state
+            .rooms
+            .base
+            .find_many(
+                doc! { "_id": { "$in": room_ids.clone() }, "deleted_at": null },
+                None,
+            )
+            .await?
+    };
+    let unread: HashMap<ObjectId, u64> = if room_ids.is_empty() {
+        HashMap::new()
+    } else {
+        state
+            .messages
+            .unread_counts_by_room(&room_ids, auth.user_id)
+            .await?
+            .into_iter()
+            .collect()
+    };
+    let notification_unread_count = state.notifications.unread_count(auth.user_id).await?;
+
+    Ok(Json(build_sidebar(
+        rooms,
+        memberships,
+        &unread,
+        notification_unread_count,
+    )))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/sidebar",
+    tag = "sidebar",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = UpdateSidebarRoomRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn update_room(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<UpdateSidebarRoomRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    let updated = state
+        .rooms
+        .set_sidebar_flags(tid, rid, auth.user_id, body.is_pinned, body.is_favorite)
+        .await?;
+    if !updated {
+        return Err(ApiError::NotFound("Not a member of this room".to_string()));
+    }
+
+    Ok(Json(serde_json::json!({ "updated": true })))
+}
+
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/sidebar/order",
+    tag = "sidebar",
+    params(("tenant_id" = String, Path)),
+    request_body = SidebarOrderRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn set_order(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<SidebarOrderRequest>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+
+    let room_ids = body
+        .room_ids
+        .iter()
+        .map(ObjectId::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let mut seen = HashSet::new();
+    if !room_ids.iter().all(|id| seen.insert(*id)) {
+        return Err(ApiError::Validation("room_ids has duplicates".to_string()));
+    }
+
+    state
+        .rooms
+        .set_sidebar_order(tid, auth.user_id, &room_ids)
+        .await?;
+
+    Ok(Json(serde_json::json!({ "updated": true })))
+}
+
+/// Group the joined rooms into sections and sort each one.
+fn build_sidebar(
+    rooms: Vec<Room>,
+    memberships: Vec<RoomMember>,
+    unread: &HashMap<ObjectId, u64>,
+    notification_unread_count: u64,
+) -> SidebarResponse {
+    let mut by_room: HashMap<ObjectId, RoomMember> =
+        memberships.into_iter().map(|m| (m.room_id, m)).collect();
+    let mut sidebar = SidebarResponse {
+        pinned: Vec::new(),
+        favorites: Vec::new(),
+        rooms: Vec::new(),
+        notification_unread_count,
+    };
+    for room in rooms {
+        let Some(member) = room.id.and_then(|id| by_room.remove(&id)) else {
+            continue;
+        };
+        let entry = SidebarRoom {
+            unread_count: unread.get(&member.room_id).copied().unwrap_or(0),
+            room: to_response(room),
+            is_pinned: member.is_pinned,
+            is_favorite: member.is_favorite,
+            is_muted: member.is_muted,
+            position: member.sidebar_position,
+        };
+        if entry.is_pinned {
+            sidebar.pinned.push(entry);
+        } else if entry.is_favorite {
+            sidebar.favorites.push(entry);
+        } else {
+            sidebar.rooms.push(entry);
+        }
+    }
+    for section in [
+        &mut sidebar.pinned,
+        &mut sidebar.favorites,
+        &mut sidebar.rooms,
+    ] {
+        section
+            .sort_by_cached_key(|r| (r.position.is_none(), r.position, r.room.name.to_lowercase()));
+    }
+    sidebar
+}
@@ -0,0 +1,349 @@
+//! Slash commands: `/name args` typed in a room is forwarded, signed like an
+//! outgoing webhook (see [`super::webhook`]), to the command's URL. The reply
+//! is either shown only to the caller (`ephemeral`, the default) or posted to
+//! the room as a bot message (`in_channel`).
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{AuthorType, SlashCommand, role::permissions};
+use roomler_ai_services::dao::message::CreateMessageParams;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use super::message::{self, MessageResponse};
+use super::webhook::{require_manage_integrations, signed_post, validate_url};
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateCommandRequest {
+    /// With or without the leading `/`.
+    pub command: String,
+    pub description: Option<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct InvokeCommandRequest {
+    /// The full input, e.g. `/weather berlin`.
+    pub text: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct SlashCommandResponse {
+    pub id: String,
+    pub command: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub secret: String,
+    pub created_by: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CommandResponseType {
+    #[default]
+    Ephemeral,
+    InChannel,
+}
+
+/// What the command's endpoint answers with.
+#[derive(Debug, Deserialize)]
+struct CommandReply {
+    text: String,
+    #[serde(default)]
+    response_type: CommandResponseType,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct CommandResultResponse {
+    pub response_type: CommandResponseType,
+    pub text: String,
+    /// The posted message for `in_channel` replies.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<MessageResponse>,
+}
+
+/// Lowercase, strip a leading `/`, and allow only `[a-z0-9_-]{1,32}`.
+fn normalize_command(raw: &str) -> Result<String, ApiError> {
+    let name = raw.trim().trim_start_matches('/').to_lowercase();
+    let valid = (1..=32).contains(&name.len())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !valid {
+        return Err(ApiError::Validation(
+            "command must be 1-32 characters of a-z, 0-9, _ or -".to_string(),
+        ));
+    }
+    Ok(name)
+}
+
+/// Split `/name rest of line` into the normalised name and its arguments.
+fn parse_invocation(text: &str) -> Result<(String, String), ApiError> {
+    let text = text.trim();
+    if !text.starts_with('/') {
+        return Err(ApiError::Validation(
+            "text must start with a /command".to_string(),
+        ));
+    }
+    let (name, args) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    Ok((normalize_command(name)?, args.trim().to_string()))
+}
+
+/// GET /api/tenant/{tenant_id}/command
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/command",
+    tag = "slash_command",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<SlashCommandResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<SlashCommandResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let commands = state.slash_commands.list_for_tenant(tid).await?;
+    Ok(Json(commands.into_iter().map(to_response).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/command
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/command",
+    tag = "slash_command",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateCommandRequest,
+    responses((status = 201, body = SlashCommandResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateCommandRequest>,
+) -> Result<(StatusCode, Json<SlashCommandResponse>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let command = normalize_command(&body.command)?;
+    validate_url(&state, &body.url)?;
+
+    let created = state
+        .slash_commands
+        .create(tid, auth.user_id, command, body.description, body.url)
+        .await?;
+    let cid = created.id;
+    let response = to_response(created);
+
+    let mut snapshot = serde_json::to_value(&response).unwrap_or_default();
+    if let Some(obj) = snapshot.as_object_mut() {
+        obj.remove("secret");
+    }
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "command.create", "command", cid).after(&snapshot),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// DELETE /api/tenant/{tenant_id}/command/{command_id}
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/command/{command_id}",
+    tag = "slash_command",
+    params(("tenant_id" = String, Path), ("command_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, command_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let cid = ObjectId::parse_str(&command_id)
+        .map_err(|_| ApiError::BadRequest("Invalid command_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let existing = state
+        .slash_commands
+        .base
+        .find_by_id_in_tenant(tid, cid)
+        .await?;
+    state.slash_commands.delete(tid, cid).await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "command.delete", "command", Some(cid))
+            .before(&serde_json::json!({ "command": existing.command, "url": existing.url })),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// POST /api/tenant/{tenant_id}/room/{room_id}/command — run a slash
+/// command. Needs `SEND_MESSAGES` in the room. An endpoint that fails or
+/// answers garbage yields an ephemeral error text rather than an API error.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/command",
+    tag = "slash_command",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    request_body = InvokeCommandRequest,
+    responses((status = 200, body = CommandResultResponse))
+)]
+pub async fn invoke(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+    Json(body): Json<InvokeCommandRequest>,
+) -> Result<Json<CommandResultResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::SEND_MESSAGES)
+        .await?;
+    crate::middleware::rate_limit::check_message(&state, tid, auth.user_id).await?;
+
+    let (name, args) = parse_invocation(&body.text)?;
+    let command = state
+        .slash_commands
+        .find_by_command(tid, &name)
+        .await
+        .map_err(|_| ApiError::NotFound(format!("Unknown command /{name}")))?;
+
+    let user_name = state
+        .users
+        .find_display_names(&[auth.user_id])
+        .await
+        .unwrap_or_default()
+        .remove(&auth.user_id)
+        .unwrap_or_default();
+    let payload = serde_json::json!({
+        "command": format!("/{name}"),
+        "text": args,
+        "tenant_id": tenant_id,
+        "room_id": room_id,
+        "user_id": auth.user_id.to_hex(),
+        "user_name": user_name,
+    });
+
+    if let Err(e) = crate::integrations_http::check_url(
+        &command.url,
+        state.settings.integrations.allow_private_networks,
+    ) {
+        tracing::warn!(command = %name, %e, "Slash command URL refused");
+        return Ok(Json(CommandResultResponse {
+            response_type: CommandResponseType::Ephemeral,
+            text: format!("/{name} did not respond"),
+            message: None,
+        }));
+    }
+    let reply = match signed_post(
+        &state.integrations_http,
+        &command.url,
+        &command.secret,
+        "command.invoke",
+        payload.to_string(),
+    )
+    .await
+    .and_then(|r| r.error_for_status())
+    {
+        Ok(resp) => resp.json::<CommandReply>().await.ok(),
+        Err(e) => {
+            tracing::warn!(command = %name, %e, "Slash command endpoint failed");
+            None
+        }
+    };
+    let Some(reply) = reply else {
+        return Ok(Json(CommandResultResponse {
+            response_type: CommandResponseType::Ephemeral,
+            text: format!("/{name} did not respond"),
+            message: None,
+        }));
+    };
+
+    let message = match reply.response_type {
+        CommandResponseType::Ephemeral => None,
+        CommandResponseType::InChannel => Some(
+            message::deliver(
+                &state,
+                CreateMessageParams {
+                    tenant_id: tid,
+                    room_id: rid,
+                    author_id: command.id.unwrap(),
+                    author_type: AuthorType::Bot,
+                    author_name: Some(format!("/{name}")),
+                    content: reply.text.clone(),
+                    thread_id: None,
+                    referenced_message_id: None,
+                    nonce: None,
+                    mentions: None,
+                    attachments: Vec::new(),
+                    is_silent: false,
+                },
+            )
+            .await?,
+        ),
+    };
+
+    Ok(Json(CommandResultResponse {
+        response_type: reply.response_type,
+        text: reply.text,
+        message,
+    }))
+}
+
+fn to_response(c: SlashCommand) -> SlashCommandResponse {
+    SlashCommandResponse {
+        id: c.id.map(|id| id.to_hex()).unwrap_or_default(),
+        command: c.command,
+        description: c.description,
+        url: c.url,
+        secret: c.secret,
+        created_by: c.created_by.to_hex(),
+        created_at: c.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_command_and_arguments() {
+        let (name, args) = parse_invocation("  /Weather  berlin today ").unwrap();
+        assert_eq!(name, "weather");
+        assert_eq!(args, "berlin today");
+        assert_eq!(parse_invocation("/ping").unwrap().1, "");
+        assert!(parse_invocation("weather").is_err());
+        assert!(parse_invocation("/we@ther").is_err());
+        assert!(normalize_command("/").is_err());
+    }
+}
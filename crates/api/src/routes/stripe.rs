@@ -1,19 +1,30 @@
+//! Stripe checkout, billing portal and webhooks.
+//!
+//! A webhook is verified (signature, and a signed timestamp within
+//! `stripe.webhook_tolerance_secs`), stored in `stripe_events` keyed by its
+//! event id and acknowledged. Redeliveries of a stored event are
+//! acknowledged without being stored again. The billing worker applies
+//! queued events to the tenants, retrying failures with backoff.
+
+use std::time::Duration;
+
 use axum::{
     Json,
     body::Bytes,
     extract::State,
     http::{HeaderMap, StatusCode},
 };
-use bson::oid::ObjectId;
+use bson::{DateTime, oid::ObjectId};
 use serde::Deserialize;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 use roomler_ai_db::models::role::permissions;
 use roomler_ai_services::stripe::{StripeEvent, StripeService};
+use utoipa::ToSchema;
 
 // ---- Request types -------------------------------------------------------
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CheckoutRequest {
     pub tenant_id: String,
     pub plan: String,
@@ -21,7 +32,7 @@ pub struct CheckoutRequest {
     pub cancel_url: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct PortalRequest {
     pub tenant_id: String,
     pub return_url: String,
@@ -29,12 +40,26 @@ pub struct PortalRequest {
 
 // ---- GET /api/stripe/plans (public) --------------------------------------
 
+#[utoipa::path(
+    get,
+    path = "/api/stripe/plans",
+    tag = "stripe",
+    responses((status = 200, body = Vec<roomler_ai_services::stripe::PlanInfo>)),
+    security(())
+)]
 pub async fn get_plans() -> Json<Vec<roomler_ai_services::stripe::PlanInfo>> {
     Json(StripeService::get_plans())
 }
 
 // ---- POST /api/stripe/checkout (authenticated, MANAGE_TENANT) ------------
 
+#[utoipa::path(
+    post,
+    path = "/api/stripe/checkout",
+    tag = "stripe",
+    request_body = CheckoutRequest,
+    responses((status = 200, body = roomler_ai_services::stripe::CheckoutResponse))
+)]
 pub async fn create_checkout(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -61,6 +86,13 @@ pub async fn create_checkout(
 
 // ---- POST /api/stripe/portal (authenticated, MANAGE_TENANT) --------------
 
+#[utoipa::path(
+    post,
+    path = "/api/stripe/portal",
+    tag = "stripe",
+    request_body = PortalRequest,
+    responses((status = 200, body = roomler_ai_services::stripe::PortalResponse))
+)]
 pub async fn create_portal(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -80,6 +112,18 @@ pub async fn create_portal(
 
 // ---- POST /api/stripe/webhook (no auth, raw body) ------------------------
 
+#[utoipa::path(
+    post,
+    path = "/api/stripe/webhook",
+    tag = "stripe",
+    request_body(
+        content = String,
+        description = "Raw Stripe event, signed in the `Stripe-Signature` header",
+        content_type = "application/json"
+    ),
+    responses((status = 200, description = "Event queued, or already received")),
+    security(())
+)]
 pub async fn webhook(
     State(state): State<AppState>,
     headers: HeaderMap,
@@ -90,24 +134,124 @@ pub async fn webhook(
         .and_then(|v| v.to_str().ok())
         .ok_or_else(|| ApiError::BadRequest("Missing Stripe-Signature header".to_string()))?;
 
-    // Verify signature
-    StripeService::verify_signature(&state.settings.stripe.webhook_secret, &body, sig_header)
-        .map_err(|_| ApiError::Unauthorized("Invalid webhook signature".to_string()))?;
+    StripeService::verify_signature(
+        &state.settings.stripe.webhook_secret,
+        &body,
+        sig_header,
+        chrono::Utc::now().timestamp(),
+        state.settings.stripe.webhook_tolerance_secs,
+    )
+    .map_err(stripe_err)?;
 
-    // Parse event
     let event: StripeEvent = serde_json::from_slice(&body)
         .map_err(|e| ApiError::BadRequest(format!("Invalid event payload: {e}")))?;
+    let payload = String::from_utf8(body.to_vec())
+        .map_err(|_| ApiError::BadRequest("Event payload is not UTF-8".to_string()))?;
 
-    // Process event
-    let stripe = StripeService::new(&state.settings.stripe);
-    stripe
-        .handle_webhook_event(&state.db, &event)
-        .await
-        .map_err(stripe_err)?;
+    let queued = state
+        .stripe_events
+        .enqueue(
+            &event.id,
+            &event.event_type,
+            DateTime::from_millis(event.created.saturating_mul(1000)),
+            payload,
+        )
+        .await?;
+    if queued {
+        state.stripe_wakeup.notify_one();
+    } else {
+        tracing::debug!(event_id = %event.id, "Duplicate Stripe event ignored");
+    }
 
     Ok(StatusCode::OK)
 }
 
+// ---- Billing worker ------------------------------------------------------
+
+/// Queued events are also picked up this often, for retries and for events
+/// queued by another pod.
+const POLL_EVERY: Duration = Duration::from_secs(5);
+/// How long a claimed event is reserved for the worker processing it.
+const LEASE: Duration = Duration::from_secs(60);
+/// Attempts before an event is marked `failed`.
+const MAX_ATTEMPTS: u32 = 12;
+
+/// Apply queued Stripe events as they arrive.
+pub(crate) fn spawn_event_worker(state: AppState) {
+    tokio::spawn(async move {
+        loop {
+            process_due_events(&state).await;
+            tokio::select! {
+                _ = state.stripe_wakeup.notified() => {}
+                _ = tokio::time::sleep(POLL_EVERY) => {}
+            }
+        }
+    });
+}
+
+async fn process_due_events(state: &AppState) {
+    let stripe = StripeService::new(&state.settings.stripe);
+    loop {
+        let record = match state.stripe_events.claim_due(after(LEASE)).await {
+            Ok(Some(record)) => record,
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!(%e, "Failed to claim Stripe event");
+                return;
+            }
+        };
+        let Some(id) = record.id else { continue };
+
+        let result = match serde_json::from_str::<StripeEvent>(&record.payload) {
+            Ok(event) => stripe
+                .handle_webhook_event(&state.db, &event)
+                .await
+                .map_err(|e| e.to_string()),
+            Err(e) => Err(format!("Invalid event payload: {e}")),
+        };
+
+        let marked = match result {
+            Ok(()) => {
+                // Plan or limits may have changed.
+                state.plan_cache.clear();
+                state.rate_limiter.forget_tenant_limits();
+                state.stripe_events.mark_processed(id).await
+            }
+            Err(error) => {
+                let retry_at = retry_delay(record.attempts).map(after);
+                tracing::warn!(
+                    event_id = %record.event_id,
+                    attempts = record.attempts,
+                    will_retry = retry_at.is_some(),
+                    %error,
+                    "Stripe event failed"
+                );
+                state
+                    .stripe_events
+                    .mark_attempt_failed(id, &error, retry_at)
+                    .await
+            }
+        };
+        if let Err(e) = marked {
+            tracing::warn!(event_id = %record.event_id, %e, "Failed to update Stripe event");
+        }
+    }
+}
+
+/// Backoff after the `attempts`th failure: 10 s doubling up to an hour, or
+/// `None` once the attempts are used up.
+fn retry_delay(attempts: u32) -> Option<Duration> {
+    if attempts >= MAX_ATTEMPTS {
+        return None;
+    }
+    let secs = 10u64 << attempts.saturating_sub(1).min(16);
+    Some(Duration::from_secs(secs.min(3600)))
+}
+
+fn after(delay: Duration) -> DateTime {
+    DateTime::from_millis(DateTime::now().timestamp_millis() + delay.as_millis() as i64)
+}
+
 // ---- Helpers -------------------------------------------------------------
 
 fn parse_oid(s: &str) -> Result<ObjectId, ApiError> {
@@ -149,7 +293,27 @@ fn stripe_err(e: roomler_ai_services::stripe::StripeError) -> ApiError {
         StripeError::InvalidSignature => {
             ApiError::Unauthorized("Invalid webhook signature".to_string())
         }
+        StripeError::StaleSignature => {
+            ApiError::Unauthorized("Webhook timestamp outside the tolerance".to_string())
+        }
         StripeError::ApiError(msg) => ApiError::Internal(format!("Stripe API error: {msg}")),
         StripeError::Mongo(e) => ApiError::Internal(e.to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_back_off_then_give_up() {
+        assert_eq!(retry_delay(1), Some(Duration::from_secs(10)));
+        assert_eq!(retry_delay(2), Some(Duration::from_secs(20)));
+        assert_eq!(retry_delay(10), Some(Duration::from_secs(3600)));
+        assert_eq!(
+            retry_delay(MAX_ATTEMPTS - 1),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(retry_delay(MAX_ATTEMPTS), None);
+    }
+}
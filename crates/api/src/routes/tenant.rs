@@ -1,24 +1,45 @@
 use axum::{Json, extract::State};
 use bson::oid::ObjectId;
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
+use utoipa::ToSchema;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use crate::{
+    error::ApiError, extractors::auth::AuthUser, middleware::storage_quota, state::AppState,
+};
+use roomler_ai_db::models::Tenant;
 
-#[derive(Debug, Deserialize)]
+pub use roomler_ai_types::tenant::TenantResponse;
+
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct CreateTenantRequest {
     pub name: String,
     pub slug: String,
 }
 
-#[derive(Debug, Serialize)]
-pub struct TenantResponse {
-    pub id: String,
-    pub name: String,
-    pub slug: String,
-    pub owner_id: String,
-    pub plan: String,
+fn tenant_response(state: &AppState, tenant: Tenant) -> TenantResponse {
+    let storage = storage_quota::usage(state, &tenant);
+    TenantResponse {
+        id: tenant.id.unwrap().to_hex(),
+        name: tenant.name,
+        slug: tenant.slug,
+        owner_id: tenant.owner_id.to_hex(),
+        // Plan enum is `#[serde(rename_all = "snake_case")]` so it
+        // serializes as "free"/"pro"/"business"/"enterprise" via
+        // serde. The frontend's plan cards (and Stripe /plans
+        // response) use lowercase ids — Debug-formatting gives
+        // "Free"/"Pro" which doesn't match, breaking the
+        // currentPlan comparison.
+        plan: format!("{:?}", tenant.plan).to_lowercase(),
+        storage,
+    }
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant",
+    tag = "tenant",
+    responses((status = 200, body = Vec<TenantResponse>))
+)]
 pub async fn list(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -27,24 +48,19 @@ pub async fn list(
 
     let response: Vec<TenantResponse> = tenants
         .into_iter()
-        .map(|t| TenantResponse {
-            id: t.id.unwrap().to_hex(),
-            name: t.name,
-            slug: t.slug,
-            owner_id: t.owner_id.to_hex(),
-            // Plan enum is `#[serde(rename_all = "snake_case")]` so it
-            // serializes as "free"/"pro"/"business"/"enterprise" via
-            // serde. The frontend's plan cards (and Stripe /plans
-            // response) use lowercase ids — Debug-formatting gives
-            // "Free"/"Pro" which doesn't match, breaking the
-            // currentPlan comparison.
-            plan: format!("{:?}", t.plan).to_lowercase(),
-        })
+        .map(|t| tenant_response(&state, t))
         .collect();
 
     Ok(Json(response))
 }
 
+#[utoipa::path(
+    post,
+    path = "/api/tenant",
+    tag = "tenant",
+    request_body = CreateTenantRequest,
+    responses((status = 200, body = TenantResponse))
+)]
 pub async fn create(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -55,15 +71,16 @@ pub async fn create(
         .create(body.name, body.slug, auth.user_id)
         .await?;
 
-    Ok(Json(TenantResponse {
-        id: tenant.id.unwrap().to_hex(),
-        name: tenant.name,
-        slug: tenant.slug,
-        owner_id: tenant.owner_id.to_hex(),
-        plan: format!("{:?}", tenant.plan).to_lowercase(),
-    }))
+    Ok(Json(tenant_response(&state, tenant)))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}",
+    tag = "tenant",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = TenantResponse))
+)]
 pub async fn get(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -79,11 +96,5 @@ pub async fn get(
 
     let tenant = state.tenants.base.find_by_id(tid).await?;
 
-    Ok(Json(TenantResponse {
-        id: tenant.id.unwrap().to_hex(),
-        name: tenant.name,
-        slug: tenant.slug,
-        owner_id: tenant.owner_id.to_hex(),
-        plan: format!("{:?}", tenant.plan).to_lowercase(),
-    }))
+    Ok(Json(tenant_response(&state, tenant)))
 }
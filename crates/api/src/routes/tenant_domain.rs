@@ -0,0 +1,485 @@
+//! Tenant email domains. A tenant claims a domain, publishes the issued
+//! token in a DNS TXT record and verifies it; from then on users whose
+//! verified email is at the domain can join without an invite. In `offer`
+//! mode the tenant is listed to them under `GET /api/tenant/joinable`; in
+//! `auto` mode they are added as soon as their email is verified. Either
+//! way they get the domain's role, or `member`.
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{DomainJoinMode, TenantDomain, role::permissions};
+use roomler_ai_services::{dao::base::DaoError, domain_verify};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use utoipa::ToSchema;
+
+use super::invite::AcceptInviteResponse;
+use super::retention::require_manage_tenant;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateDomainRequest {
+    pub domain: String,
+    /// `offer` (default) or `auto`.
+    #[serde(default)]
+    #[schema(value_type = String)]
+    pub join_mode: DomainJoinMode,
+    /// Role granted on joining; omitted is `member`.
+    pub role_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateDomainRequest {
+    #[schema(value_type = String)]
+    pub join_mode: DomainJoinMode,
+    pub role_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct DomainResponse {
+    pub id: String,
+    pub domain: String,
+    pub verified: bool,
+    pub verified_at: Option<String>,
+    #[schema(value_type = String)]
+    pub join_mode: DomainJoinMode,
+    pub role_id: Option<String>,
+    /// Name of the TXT record to publish.
+    pub txt_name: String,
+    /// Value of the TXT record to publish.
+    pub txt_value: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct JoinableTenantResponse {
+    pub tenant_id: String,
+    pub tenant_name: String,
+    pub tenant_slug: String,
+    pub domain: String,
+}
+
+/// GET /api/tenant/{tenant_id}/domain
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/domain",
+    tag = "tenant",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<DomainResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<DomainResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let claims = state.tenant_domains.list_for_tenant(tid).await?;
+    Ok(Json(claims.into_iter().map(to_response).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/domain — claim a domain. It does nothing
+/// until verified.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/domain",
+    tag = "tenant",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateDomainRequest,
+    responses((status = 201, body = DomainResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateDomainRequest>,
+) -> Result<(StatusCode, Json<DomainResponse>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let domain = domain_verify::normalize_domain(&body.domain)
+        .ok_or_else(|| ApiError::Validation("Invalid domain".to_string()))?;
+    let role_id = parse_role(&state, tid, body.role_id.as_deref()).await?;
+
+    let claim = state
+        .tenant_domains
+        .create(tid, domain, body.join_mode, role_id, auth.user_id)
+        .await?;
+    let did = claim.id;
+    let response = to_response(claim);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "domain.create", "domain", did).after(&response),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// POST /api/tenant/{tenant_id}/domain/{domain_id}/verify — look for the
+/// TXT record; `422` while it is missing.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/domain/{domain_id}/verify",
+    tag = "tenant",
+    params(("tenant_id" = String, Path), ("domain_id" = String, Path)),
+    responses((status = 200, body = DomainResponse))
+)]
+pub async fn verify(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, domain_id)): Path<(String, String)>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let did = ObjectId::parse_str(&domain_id)
+        .map_err(|_| ApiError::BadRequest("Invalid domain_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let claim = state
+        .tenant_domains
+        .base
+        .find_by_id_in_tenant(tid, did)
+        .await?;
+    if claim.verified_at.is_some() {
+        return Ok(Json(to_response(claim)));
+    }
+
+    let verifier = state
+        .domain_verifier
+        .as_ref()
+        .ok_or_else(|| ApiError::Internal("DNS resolver unavailable".to_string()))?;
+    let found = verifier
+        .has_token(&claim.domain, &claim.verification_token)
+        .await
+        .map_err(|e| ApiError::Internal(format!("DNS lookup failed: {e}")))?;
+    if !found {
+        return Err(ApiError::Validation(format!(
+            "TXT record {} with value {} not found",
+            domain_verify::record_name(&claim.domain),
+            domain_verify::record_value(&claim.verification_token)
+        )));
+    }
+
+    state.tenant_domains.mark_verified(tid, did).await?;
+    let response = to_response(
+        state
+            .tenant_domains
+            .base
+            .find_by_id_in_tenant(tid, did)
+            .await?,
+    );
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "domain.verify", "domain", Some(did)).after(&response),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+/// PUT /api/tenant/{tenant_id}/domain/{domain_id} — replace the join mode
+/// and role.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/domain/{domain_id}",
+    tag = "tenant",
+    params(("tenant_id" = String, Path), ("domain_id" = String, Path)),
+    request_body = UpdateDomainRequest,
+    responses((status = 200, body = DomainResponse))
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, domain_id)): Path<(String, String)>,
+    Json(body): Json<UpdateDomainRequest>,
+) -> Result<Json<DomainResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let did = ObjectId::parse_str(&domain_id)
+        .map_err(|_| ApiError::BadRequest("Invalid domain_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let before = to_response(
+        state
+            .tenant_domains
+            .base
+            .find_by_id_in_tenant(tid, did)
+            .await?,
+    );
+    let role_id = parse_role(&state, tid, body.role_id.as_deref()).await?;
+    let response = to_response(
+        state
+            .tenant_domains
+            .set_policy(tid, did, body.join_mode, role_id)
+            .await?,
+    );
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "domain.update", "domain", Some(did))
+            .before(&before)
+            .after(&response),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+/// DELETE /api/tenant/{tenant_id}/domain/{domain_id} — members who joined
+/// through the domain stay.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/domain/{domain_id}",
+    tag = "tenant",
+    params(("tenant_id" = String, Path), ("domain_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, domain_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let did = ObjectId::parse_str(&domain_id)
+        .map_err(|_| ApiError::BadRequest("Invalid domain_id".to_string()))?;
+    require_manage_tenant(&state, tid, auth.user_id).await?;
+
+    let before = to_response(
+        state
+            .tenant_domains
+            .base
+            .find_by_id_in_tenant(tid, did)
+            .await?,
+    );
+    state.tenant_domains.delete(tid, did).await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "domain.delete", "domain", Some(did)).before(&before),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// GET /api/tenant/joinable — tenants the caller can join through their
+/// verified email domain.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/joinable",
+    tag = "tenant",
+    responses((status = 200, body = Vec<JoinableTenantResponse>))
+)]
+pub async fn joinable(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<Vec<JoinableTenantResponse>>, ApiError> {
+    let mut joinable = Vec::new();
+    for claim in open_claims(&state, auth.user_id, &auth.email).await? {
+        let tenant = state.tenants.base.find_by_id(claim.tenant_id).await?;
+        joinable.push(JoinableTenantResponse {
+            tenant_id: claim.tenant_id.to_hex(),
+            tenant_name: tenant.name,
+            tenant_slug: tenant.slug,
+            domain: claim.domain,
+        });
+    }
+    Ok(Json(joinable))
+}
+
+/// POST /api/tenant/{tenant_id}/domain/join — join through a verified
+/// domain of the caller's email.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/domain/join",
+    tag = "tenant",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = AcceptInviteResponse))
+)]
+pub async fn join(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<AcceptInviteResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Conflict(
+            "Already a member of this tenant".to_string(),
+        ));
+    }
+    let claim = open_claims(&state, auth.user_id, &auth.email)
+        .await?
+        .into_iter()
+        .find(|c| c.tenant_id == tid)
+        .ok_or_else(|| {
+            ApiError::Forbidden("Your email domain is not verified for this tenant".to_string())
+        })?;
+    join_through(&state, &claim, auth.user_id).await?;
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    Ok(Json(AcceptInviteResponse {
+        tenant_id,
+        tenant_name: tenant.name,
+        tenant_slug: tenant.slug,
+    }))
+}
+
+/// Add a user whose email was just verified to every tenant with an `auto`
+/// claim on their domain. A join that fails (a plan limit, say) is logged
+/// and skipped.
+pub(crate) async fn auto_join(state: &AppState, user_id: ObjectId, email: &str) {
+    let claims = match open_claims(state, user_id, email).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            warn!(%user_id, "Failed to look up domain joins: {:?}", e);
+            return;
+        }
+    };
+    for claim in claims {
+        if claim.join_mode != DomainJoinMode::Auto {
+            continue;
+        }
+        if let Err(e) = join_through(state, &claim, user_id).await {
+            warn!(%user_id, tenant_id = %claim.tenant_id, "Domain auto-join failed: {:?}", e);
+        }
+    }
+}
+
+/// Verified claims on the user's email domain in live tenants they are not
+/// yet a member of, one per tenant. Empty until the email is verified.
+async fn open_claims(
+    state: &AppState,
+    user_id: ObjectId,
+    email: &str,
+) -> Result<Vec<TenantDomain>, ApiError> {
+    let Some(domain) = domain_verify::email_domain(email) else {
+        return Ok(Vec::new());
+    };
+    let user = state.users.base.find_by_id(user_id).await?;
+    if !user.is_verified || user.is_bot {
+        return Ok(Vec::new());
+    }
+
+    let mut open: Vec<TenantDomain> = Vec::new();
+    for claim in state.tenant_domains.find_verified(&domain).await? {
+        if open.iter().any(|c| c.tenant_id == claim.tenant_id)
+            || state.tenants.is_member(claim.tenant_id, user_id).await?
+        {
+            continue;
+        }
+        match state.tenants.base.find_by_id(claim.tenant_id).await {
+            Ok(tenant) if tenant.deleted_at.is_none() => open.push(claim),
+            Ok(_) | Err(DaoError::NotFound) => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(open)
+}
+
+async fn join_through(
+    state: &AppState,
+    claim: &TenantDomain,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    crate::middleware::plan_limits::check_members(state, claim.tenant_id, user_id).await?;
+
+    // A role deleted since it was picked falls back to `member`.
+    let role = match claim.role_id {
+        Some(rid) => {
+            state
+                .tenants
+                .roles
+                .find_one(bson::doc! { "_id": rid, "tenant_id": claim.tenant_id })
+                .await?
+        }
+        None => None,
+    };
+    let role = match role {
+        Some(role) => role,
+        None => {
+            state
+                .tenants
+                .get_role_by_name(claim.tenant_id, "member")
+                .await?
+        }
+    };
+
+    state
+        .tenants
+        .add_member(
+            claim.tenant_id,
+            user_id,
+            role.id.into_iter().collect(),
+            None,
+        )
+        .await?;
+    Ok(())
+}
+
+/// Parse `role_id` and check it is a role of the tenant that doesn't carry
+/// `ADMINISTRATOR`.
+async fn parse_role(
+    state: &AppState,
+    tenant_id: ObjectId,
+    role_id: Option<&str>,
+) -> Result<Option<ObjectId>, ApiError> {
+    let Some(raw) = role_id else {
+        return Ok(None);
+    };
+    let rid = ObjectId::parse_str(raw)
+        .map_err(|_| ApiError::BadRequest("Invalid role_id".to_string()))?;
+    let role = state
+        .tenants
+        .roles
+        .find_one(bson::doc! { "_id": rid, "tenant_id": tenant_id })
+        .await?
+        .ok_or_else(|| ApiError::Validation("Unknown role_id".to_string()))?;
+    if role.permissions & permissions::ADMINISTRATOR != 0 {
+        return Err(ApiError::Validation(
+            "Domain joins cannot grant ADMINISTRATOR".to_string(),
+        ));
+    }
+    Ok(Some(rid))
+}
+
+fn to_response(d: TenantDomain) -> DomainResponse {
+    DomainResponse {
+        id: d.id.map(|id| id.to_hex()).unwrap_or_default(),
+        txt_name: domain_verify::record_name(&d.domain),
+        txt_value: domain_verify::record_value(&d.verification_token),
+        domain: d.domain,
+        verified: d.verified_at.is_some(),
+        verified_at: d
+            .verified_at
+            .map(|at| at.try_to_rfc3339_string().unwrap_or_default()),
+        join_mode: d.join_mode,
+        role_id: d.role_id.map(|id| id.to_hex()),
+        created_at: d.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
@@ -20,6 +20,7 @@ use roomler_ai_remote_control::models::{
 };
 use roomler_ai_services::dao::base::PaginationParams;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
 
@@ -32,7 +33,7 @@ const TUNNEL_ENROLLMENT_TTL_SECS: u64 = 600;
 // Tunnel-client enrollment
 // ────────────────────────────────────────────────────────────────────────────
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TunnelEnrollmentTokenResponse {
     pub enrollment_token: String,
     pub expires_in: u64,
@@ -43,6 +44,13 @@ pub struct TunnelEnrollmentTokenResponse {
 /// admin/member issues a single-use token the operator pastes into
 /// `roomler-tunnel enroll`. Mirrors `issue_enrollment_token` for
 /// agents.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/tunnel-client/enroll-token",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = TunnelEnrollmentTokenResponse))
+)]
 pub async fn issue_tunnel_enrollment_token(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -67,16 +75,17 @@ pub async fn issue_tunnel_enrollment_token(
     }))
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TunnelEnrollRequest {
     pub enrollment_token: String,
     pub machine_id: String,
     pub machine_name: String,
+    #[schema(value_type = String)]
     pub os: OsKind,
     pub client_version: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TunnelEnrollResponse {
     pub tunnel_client_id: String,
     pub tenant_id: String,
@@ -94,6 +103,14 @@ pub struct TunnelEnrollResponse {
 /// admin's identity rather than the operator's. v1.1 can extend the
 /// enrollment request with an operator user-id once we have the
 /// admin-UI flow to enter it.
+#[utoipa::path(
+    post,
+    path = "/api/tunnel-client/enroll",
+    tag = "tunnel",
+    request_body = TunnelEnrollRequest,
+    responses((status = 200, body = TunnelEnrollResponse)),
+    security(())
+)]
 pub async fn enroll_tunnel_client(
     State(state): State<AppState>,
     Json(body): Json<TunnelEnrollRequest>,
@@ -154,7 +171,7 @@ pub async fn enroll_tunnel_client(
 
 /// One agent in the tenant, for the SOCKS mesh's name → agent-id routing
 /// and (Phase 2) subnet-router CIDR → agent-id routing.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TunnelAgentInfo {
     pub agent_id: String,
     pub name: String,
@@ -168,6 +185,12 @@ pub struct TunnelAgentInfo {
 /// `roomler-tunnel socks5` (mesh mode) can route a CONNECT by friendly agent
 /// name instead of the raw 24-hex id. Authenticated by the caller's TunnelClient
 /// JWT (`Authorization: Bearer <token>`) and scoped to that token's tenant.
+#[utoipa::path(
+    get,
+    path = "/api/tunnel-client/agents",
+    tag = "tunnel",
+    responses((status = 200, body = Vec<TunnelAgentInfo>))
+)]
 pub async fn list_tenant_agents(
     State(state): State<AppState>,
     headers: axum::http::HeaderMap,
@@ -234,6 +257,13 @@ pub struct TunnelClientResponse {
 /// GET /api/tenant/{tenant_id}/tunnel-client — paginated list of
 /// enrolled tunnel clients for the tenant. Mirrors `list_agents`.
 /// T2 extends with the WS-live `is_online` derivation.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/tunnel-client",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path), PaginationParams),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn list_tunnel_clients(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -281,11 +311,14 @@ pub async fn list_tunnel_clients(
 /// `allowlist` re-use the BSON serde representation of the
 /// `roomler_ai_remote_control::models` types — adjacently-tagged
 /// (`{kind: "...", id: "..."}` / `{kind: "...", value: "..."}`).
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TunnelPolicyCreateRequest {
     pub name: String,
+    #[schema(value_type = Vec<Object>)]
     pub subjects: Vec<PolicySubject>,
+    #[schema(value_type = Vec<Object>)]
     pub targets: Vec<PolicyTarget>,
+    #[schema(value_type = Vec<Object>)]
     pub allowlist: Vec<DestinationRule>,
     #[serde(default)]
     pub max_concurrent_flows: Option<u32>,
@@ -293,15 +326,18 @@ pub struct TunnelPolicyCreateRequest {
     pub max_bytes_per_session: Option<u64>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct TunnelPolicyUpdateRequest {
     #[serde(default)]
     pub name: Option<String>,
     #[serde(default)]
+    #[schema(value_type = Option<Vec<Object>>)]
     pub subjects: Option<Vec<PolicySubject>>,
     #[serde(default)]
+    #[schema(value_type = Option<Vec<Object>>)]
     pub targets: Option<Vec<PolicyTarget>>,
     #[serde(default)]
+    #[schema(value_type = Option<Vec<Object>>)]
     pub allowlist: Option<Vec<DestinationRule>>,
     /// `Some(None)` clears the ceiling; `Some(Some(v))` sets it;
     /// absent leaves it unchanged. Two-level Option matches the
@@ -329,13 +365,16 @@ where
     Ok(Some(v))
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct TunnelPolicyResponse {
     pub id: String,
     pub tenant_id: String,
     pub name: String,
+    #[schema(value_type = Vec<Object>)]
     pub subjects: Vec<PolicySubject>,
+    #[schema(value_type = Vec<Object>)]
     pub targets: Vec<PolicyTarget>,
+    #[schema(value_type = Vec<Object>)]
     pub allowlist: Vec<DestinationRule>,
     pub max_concurrent_flows: Option<u32>,
     pub max_bytes_per_session: Option<u64>,
@@ -398,6 +437,14 @@ fn validate_policy_input(
 }
 
 /// POST /api/tenant/{tenant_id}/tunnel-policy — create a new policy.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/tunnel-policy",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path)),
+    request_body = TunnelPolicyCreateRequest,
+    responses((status = 201, body = TunnelPolicyResponse))
+)]
 pub async fn create_tunnel_policy(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -428,6 +475,13 @@ pub async fn create_tunnel_policy(
 
 /// GET /api/tenant/{tenant_id}/tunnel-policy — paginated list of live
 /// policies for the tenant. Soft-deleted rows are excluded.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/tunnel-policy",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path), PaginationParams),
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn list_tunnel_policies(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -451,6 +505,13 @@ pub async fn list_tunnel_policies(
 }
 
 /// GET /api/tenant/{tenant_id}/tunnel-policy/{policy_id} — fetch one.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/tunnel-policy/{policy_id}",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path), ("policy_id" = String, Path)),
+    responses((status = 200, body = TunnelPolicyResponse))
+)]
 pub async fn get_tunnel_policy(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -469,6 +530,14 @@ pub async fn get_tunnel_policy(
 
 /// PUT /api/tenant/{tenant_id}/tunnel-policy/{policy_id} — partial
 /// update. Any field omitted from the body stays unchanged.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/tunnel-policy/{policy_id}",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path), ("policy_id" = String, Path)),
+    request_body = TunnelPolicyUpdateRequest,
+    responses((status = 200, body = TunnelPolicyResponse))
+)]
 pub async fn update_tunnel_policy(
     State(state): State<AppState>,
     auth: AuthUser,
@@ -542,6 +611,13 @@ pub async fn update_tunnel_policy(
 /// `TcpForwardRequest`s using only this policy will start being
 /// denied at the next policy fetch (every request — there's no
 /// cache, see `list_active_for_tenant`).
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/tunnel-policy/{policy_id}",
+    tag = "tunnel",
+    params(("tenant_id" = String, Path), ("policy_id" = String, Path)),
+    responses((status = 204, description = "Policy deleted"))
+)]
 pub async fn delete_tunnel_policy(
     State(state): State<AppState>,
     auth: AuthUser,
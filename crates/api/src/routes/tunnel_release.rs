@@ -19,6 +19,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
+use utoipa::{IntoParams, ToSchema};
 
 use crate::{
     error::ApiError,
@@ -53,7 +54,8 @@ impl LatestTunnelReleaseCache {
     }
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct InstallerQuery {
     #[serde(default = "default_version_latest")]
     pub version: String,
@@ -63,7 +65,8 @@ fn default_version_latest() -> String {
     "latest".to_string()
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
+#[schema(as = TunnelInstallerHealth)]
 pub struct InstallerHealth {
     pub tag: String,
     pub platform: String,
@@ -83,6 +86,13 @@ pub struct InstallerHealth {
 /// UNRELATED release — e.g. a `vendored-ffmpeg-*` helper published for the
 /// agent's HEVC libs — was served as the latest tunnel, so self-update reported
 /// a bogus version and failed the SHA-256 check against the wrong asset.
+#[utoipa::path(
+    get,
+    path = "/api/tunnel/latest-release",
+    tag = "tunnel_release",
+    responses((status = 200, body = Vec<AgentRelease>)),
+    security(())
+)]
 pub async fn latest_release(
     State(state): State<AppState>,
 ) -> Result<Json<Vec<AgentRelease>>, ApiError> {
@@ -95,6 +105,14 @@ pub async fn latest_release(
 /// `GET /api/tunnel/installer/{platform}/health` — manifest (tag,
 /// filename, size, digest, download URI) for the asset matching the
 /// requested platform + version.
+#[utoipa::path(
+    get,
+    path = "/api/tunnel/installer/{platform}/health",
+    tag = "tunnel_release",
+    params(("platform" = String, Path), InstallerQuery),
+    responses((status = 200, body = InstallerHealth)),
+    security(())
+)]
 pub async fn installer_health(
     State(state): State<AppState>,
     Path(platform): Path<String>,
@@ -126,6 +144,16 @@ pub async fn installer_health(
 
 /// `GET /api/tunnel/installer/{platform}` — streams the archive
 /// bytes. Content-Type derived from filename suffix.
+#[utoipa::path(
+    get,
+    path = "/api/tunnel/installer/{platform}",
+    tag = "tunnel_release",
+    params(("platform" = String, Path), InstallerQuery),
+    responses(
+        (status = 200, description = "Installer binary", content_type = "application/octet-stream"),
+    ),
+    security(())
+)]
 pub async fn installer_proxy(
     State(state): State<AppState>,
     Path(platform): Path<String>,
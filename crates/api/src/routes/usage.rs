@@ -0,0 +1,368 @@
+//! Usage metering.
+//!
+//! Billable usage is booked per tenant per UTC day (`usage_days`): call
+//! participant-seconds when a participant leaves or the call ends, media
+//! streamed and transcribed when the meter drains the room manager, and the
+//! largest storage footprint measured that day. Usage is booked on the day
+//! it ends. Measuring storage also resets each tenant's file storage quota
+//! counter to the measured total.
+//!
+//! With `stripe.report_usage`, finished days of tenants with a Stripe
+//! customer are reported as meter events and marked reported.
+
+use std::time::{Duration, Instant};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+};
+use bson::{DateTime, oid::ObjectId};
+use chrono::{Datelike, NaiveDate};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    middleware::storage_quota::{self, StorageUsage},
+    state::AppState,
+};
+use roomler_ai_db::models::{UsageDay, role::permissions};
+use roomler_ai_services::dao::usage::{UsageDelta, usage_date};
+use roomler_ai_services::stripe::StripeService;
+
+/// Storage is measured, and finished days reported, this often.
+const STORAGE_EVERY: Duration = Duration::from_secs(3600);
+/// Longest range one usage request may cover.
+const MAX_RANGE_DAYS: i64 = 366;
+const GB: u64 = 1024 * 1024 * 1024;
+
+/// Stripe meter event names, one per billed quantity.
+pub const METER_CALL_MINUTES: &str = "roomler_call_minutes";
+pub const METER_TRANSCRIPTION_MINUTES: &str = "roomler_transcription_minutes";
+pub const METER_STREAMED_MINUTES: &str = "roomler_streamed_minutes";
+pub const METER_STORAGE_GB: &str = "roomler_storage_gb";
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UsageQuery {
+    /// First day, `YYYY-MM-DD` (UTC). Defaults to the first of this month.
+    pub from: Option<String>,
+    /// Last day, inclusive. Defaults to today.
+    pub to: Option<String>,
+}
+
+/// One day of usage. Minutes are rounded up.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageDayResponse {
+    pub date: String,
+    pub call_minutes: u64,
+    pub transcription_minutes: u64,
+    pub streamed_minutes: u64,
+    pub storage_bytes: u64,
+    pub reported: bool,
+}
+
+impl From<&UsageDay> for UsageDayResponse {
+    fn from(d: &UsageDay) -> Self {
+        Self {
+            date: d.date.clone(),
+            call_minutes: minutes(d.call_secs),
+            transcription_minutes: minutes(d.transcription_secs),
+            streamed_minutes: minutes(d.streamed_secs),
+            storage_bytes: d.storage_bytes,
+            reported: d.reported_at.is_some(),
+        }
+    }
+}
+
+/// The range's totals; minutes are rounded up once, over the whole range.
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageTotals {
+    pub call_minutes: u64,
+    pub transcription_minutes: u64,
+    pub streamed_minutes: u64,
+    pub peak_storage_bytes: u64,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct UsageResponse {
+    pub from: String,
+    pub to: String,
+    pub days: Vec<UsageDayResponse>,
+    pub totals: UsageTotals,
+    /// File storage now, against the tenant's storage quota.
+    pub storage: StorageUsage,
+}
+
+/// The tenant's daily usage over a range (MANAGE_TENANT).
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/usage",
+    tag = "usage",
+    params(("tenant_id" = String, Path), UsageQuery),
+    responses((status = 200, body = UsageResponse))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<UsageResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+
+    let today = DateTime::now().to_chrono().date_naive();
+    let from = match query.from.as_deref() {
+        Some(s) => parse_day(s)?,
+        None => today.with_day(1).unwrap_or(today),
+    };
+    let to = match query.to.as_deref() {
+        Some(s) => parse_day(s)?,
+        None => today,
+    };
+    if from > to {
+        return Err(ApiError::Validation("from is after to".to_string()));
+    }
+    if (to - from).num_days() >= MAX_RANGE_DAYS {
+        return Err(ApiError::Validation(format!(
+            "The range may cover at most {} days",
+            MAX_RANGE_DAYS
+        )));
+    }
+
+    let (from, to) = (from.to_string(), to.to_string());
+    let days = state.usage.find_range(tid, &from, &to).await?;
+    let totals = UsageTotals {
+        call_minutes: minutes(days.iter().map(|d| d.call_secs).sum()),
+        transcription_minutes: minutes(days.iter().map(|d| d.transcription_secs).sum()),
+        streamed_minutes: minutes(days.iter().map(|d| d.streamed_secs).sum()),
+        peak_storage_bytes: days.iter().map(|d| d.storage_bytes).max().unwrap_or(0),
+    };
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+
+    Ok(Json(UsageResponse {
+        from,
+        to,
+        days: days.iter().map(UsageDayResponse::from).collect(),
+        totals,
+        storage: storage_quota::usage(&state, &tenant),
+    }))
+}
+
+fn parse_day(s: &str) -> Result<NaiveDate, ApiError> {
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map_err(|_| ApiError::BadRequest(format!("Invalid date '{}', expected YYYY-MM-DD", s)))
+}
+
+/// Book the participant-seconds of a call leave or end against the room's
+/// tenant. Metering never fails the request.
+pub(crate) async fn book_call(state: &AppState, tenant_id: ObjectId, call_secs: u64) {
+    let delta = UsageDelta {
+        call_secs,
+        ..Default::default()
+    };
+    if let Err(e) = state.usage.add(tenant_id, delta).await {
+        tracing::warn!(%tenant_id, %e, "Failed to book call usage");
+    }
+}
+
+/// Drain media usage every `usage.meter_interval_secs` (0 disables); measure
+/// storage and report finished days to Stripe every hour.
+pub(crate) fn spawn_meter(state: AppState) {
+    let interval = state.settings.usage.meter_interval_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(interval));
+        let mut last_hourly: Option<Instant> = None;
+        loop {
+            tick.tick().await;
+            book_media(&state).await;
+            if last_hourly.is_some_and(|t| t.elapsed() < STORAGE_EVERY) {
+                continue;
+            }
+            last_hourly = Some(Instant::now());
+            measure_storage(&state).await;
+            if state.settings.stripe.report_usage {
+                report_to_stripe(&state).await;
+            }
+        }
+    });
+}
+
+async fn book_media(state: &AppState) {
+    for (room_id, media) in state.room_manager.take_usage() {
+        // Only to learn whose usage it is; no user is asking
+        let tenant_id = match state.rooms.base.find_by_id_unscoped(room_id).await {
+            Ok(room) => room.tenant_id,
+            Err(e) => {
+                tracing::warn!(%room_id, %e, "Dropping media usage of unknown room");
+                continue;
+            }
+        };
+        let delta = UsageDelta {
+            streamed_secs: (media.streamed_ms + 500) / 1000,
+            transcription_secs: (media.transcription_ms + 500) / 1000,
+            ..Default::default()
+        };
+        if let Err(e) = state.usage.add(tenant_id, delta).await {
+            tracing::warn!(%tenant_id, %e, "Failed to book media usage");
+        }
+    }
+}
+
+async fn measure_storage(state: &AppState) {
+    let tenants = match state.tenants.find_live().await {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            tracing::warn!(%e, "Failed to load tenants for storage metering");
+            return;
+        }
+    };
+    for tenant in &tenants {
+        let Some(tid) = tenant.id else { continue };
+        let (files, recordings) = match (
+            state.files.storage_used(tid).await,
+            state.recordings.storage_used(tid).await,
+        ) {
+            (Ok(files), Ok(recordings)) => (files, recordings),
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::warn!(tenant_id = %tid, %e, "Failed to measure storage");
+                continue;
+            }
+        };
+        let used = files + recordings;
+        // Correct any drift of the quota counter from missed updates.
+        if used != tenant.storage_used_bytes
+            && let Err(e) = state.tenants.set_storage_used(tid, used).await
+        {
+            tracing::warn!(tenant_id = %tid, %e, "Failed to reconcile storage usage");
+        }
+        if used > 0
+            && let Err(e) = state.usage.record_storage(tid, used).await
+        {
+            tracing::warn!(tenant_id = %tid, %e, "Failed to book storage usage");
+        }
+    }
+}
+
+async fn report_to_stripe(state: &AppState) {
+    let tenants = match state.tenants.find_live().await {
+        Ok(tenants) => tenants,
+        Err(e) => {
+            tracing::warn!(%e, "Failed to load tenants for usage reporting");
+            return;
+        }
+    };
+    let stripe = StripeService::new(&state.settings.stripe);
+    let today = usage_date(DateTime::now());
+    for tenant in tenants {
+        let (Some(tid), Some(customer_id)) =
+            (tenant.id, tenant.billing.and_then(|b| b.customer_id))
+        else {
+            continue;
+        };
+        let days = match state.usage.find_unreported(tid, &today).await {
+            Ok(days) => days,
+            Err(e) => {
+                tracing::warn!(tenant_id = %tid, %e, "Failed to load unreported usage");
+                continue;
+            }
+        };
+        for day in days {
+            if let Err(e) = report_day(&stripe, &customer_id, &day).await {
+                tracing::warn!(tenant_id = %tid, date = %day.date, %e, "Usage report failed");
+                break;
+            }
+            if let Some(id) = day.id
+                && let Err(e) = state.usage.mark_reported(id).await
+            {
+                tracing::warn!(tenant_id = %tid, %e, "Failed to mark usage reported");
+            }
+        }
+    }
+}
+
+/// Send one day's meter events. Identifiers are derived from the day, so a
+/// day reported again after a partial failure is not double-counted.
+async fn report_day(
+    stripe: &StripeService,
+    customer_id: &str,
+    day: &UsageDay,
+) -> Result<(), roomler_ai_services::stripe::StripeError> {
+    let timestamp = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(12, 0, 0))
+        .map(|t| t.and_utc().timestamp())
+        .unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let day_id = day.id.map(|id| id.to_hex()).unwrap_or_default();
+
+    for (event, value) in meter_values(day) {
+        if value == 0 {
+            continue;
+        }
+        let identifier = format!("{}-{}", day_id, event);
+        stripe
+            .report_meter_event(event, customer_id, value, timestamp, &identifier)
+            .await?;
+    }
+    Ok(())
+}
+
+/// The quantities billed for a day, per meter event.
+fn meter_values(day: &UsageDay) -> [(&'static str, u64); 4] {
+    [
+        (METER_CALL_MINUTES, minutes(day.call_secs)),
+        (METER_TRANSCRIPTION_MINUTES, minutes(day.transcription_secs)),
+        (METER_STREAMED_MINUTES, minutes(day.streamed_secs)),
+        (METER_STORAGE_GB, day.storage_bytes.div_ceil(GB)),
+    ]
+}
+
+fn minutes(secs: u64) -> u64 {
+    secs.div_ceil(60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn billed_quantities_round_up() {
+        let now = DateTime::now();
+        let day = UsageDay {
+            id: None,
+            tenant_id: ObjectId::new(),
+            date: "2026-03-01".to_string(),
+            call_secs: 61,
+            transcription_secs: 0,
+            streamed_secs: 3600,
+            storage_bytes: GB + 1,
+            reported_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        assert_eq!(
+            meter_values(&day),
+            [
+                (METER_CALL_MINUTES, 2),
+                (METER_TRANSCRIPTION_MINUTES, 0),
+                (METER_STREAMED_MINUTES, 60),
+                (METER_STORAGE_GB, 2),
+            ]
+        );
+    }
+}
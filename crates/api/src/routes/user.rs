@@ -1,14 +1,43 @@
 use axum::{
     Json,
-    extract::{Path, Query, State},
+    body::Body,
+    extract::{Multipart, Path, Query, State},
+    response::Response,
 };
-use bson::{doc, oid::ObjectId};
+use bson::{DateTime, doc, oid::ObjectId};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
-use roomler_ai_services::dao::base::PaginationParams;
+use super::file::upload_dir;
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    extractors::list_query::{FieldKind, FilterField, ListQuery, ListSpec},
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+use roomler_ai_db::models::{User, UserStatusInfo, role::permissions};
+use roomler_ai_services::{
+    RoleChange,
+    avatar::{self, Crop},
+    dao::base::PaginatedResult,
+};
+use utoipa::{IntoParams, ToSchema};
+
+pub use roomler_ai_types::user::UserStatusResponse;
+
+/// Largest avatar upload accepted, before cropping.
+pub const MAX_AVATAR_SIZE: usize = 5 * 1024 * 1024;
+
+/// Results of a member search, by default and at most.
+const SEARCH_LIMIT: i64 = 10;
+const MAX_SEARCH_LIMIT: i64 = 25;
+
+const MAX_STATUS_TEXT: usize = 100;
+const MAX_STATUS_EMOJI: usize = 64;
+const MAX_PRONOUNS: usize = 40;
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct MemberResponse {
     pub id: String,
     pub user_id: String,
@@ -16,36 +45,122 @@ pub struct MemberResponse {
     /// The user's display name (falls back to username), resolved from the users
     /// collection — so member pickers can show a name, not a raw id.
     pub display_name: String,
+    /// Bot accounts get a badge in member lists.
+    pub is_bot: bool,
+    pub avatar: Option<String>,
+    pub pronouns: Option<String>,
+    /// Custom status, omitted when unset or expired.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<UserStatusResponse>,
     pub role_ids: Vec<String>,
     pub joined_at: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct MemberSearchQuery {
+    /// Start of a display name, a word of it, a username or an email.
+    pub q: String,
+    /// Defaults to 10, at most 25.
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MemberSearchResult {
+    pub user_id: String,
+    pub username: String,
+    pub display_name: String,
+    pub nickname: Option<String>,
+    pub avatar: Option<String>,
+    /// `online`, `idle`, `dnd` or `offline`; invisible users show offline.
+    pub presence: String,
+    pub is_bot: bool,
+    pub role_ids: Vec<String>,
+    /// Names of `role_ids`, highest first.
+    pub roles: Vec<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
 pub struct ProfileResponse {
     pub id: String,
     pub username: String,
     pub display_name: String,
     pub avatar: Option<String>,
     pub bio: Option<String>,
+    pub pronouns: Option<String>,
+    pub timezone: String,
+    /// Custom status, omitted when unset or expired.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<UserStatusResponse>,
     pub presence: String,
     pub created_at: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ToSchema)]
 pub struct UpdateProfileRequest {
     pub display_name: Option<String>,
     pub bio: Option<String>,
     pub avatar: Option<String>,
+    /// Free text up to 40 characters, e.g. `she/her`.
+    pub pronouns: Option<String>,
     pub locale: Option<String>,
+    /// IANA zone name, e.g. `Europe/Vienna`.
     pub timezone: Option<String>,
 }
 
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct SetStatusRequest {
+    /// Up to 100 characters.
+    pub text: Option<String>,
+    pub emoji: Option<String>,
+    /// RFC 3339; the status clears itself after this. Omit to keep it.
+    pub expires_at: Option<String>,
+}
+
+/// Multipart body of the avatar upload, for the OpenAPI document only.
+#[derive(ToSchema)]
+#[allow(dead_code)]
+pub struct AvatarUploadForm {
+    /// PNG, JPEG or WebP image.
+    #[schema(value_type = String, format = Binary)]
+    file: Vec<u8>,
+    /// Square to keep, in source pixels; all three or none. Without them
+    /// the largest centered square is used.
+    crop_x: Option<u32>,
+    crop_y: Option<u32>,
+    crop_size: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct RemoveMemberQuery {
+    /// Free-text justification, kept in the audit log.
+    pub reason: Option<String>,
+}
+
+const MEMBERS_SPEC: ListSpec = ListSpec {
+    sort: &[("joined_at", "joined_at"), ("nickname", "nickname")],
+    filters: &[
+        FilterField::new("user_id", "user_id", FieldKind::ObjectId),
+        FilterField::new("role_id", "role_ids", FieldKind::ObjectId),
+        FilterField::new("nickname", "nickname", FieldKind::String),
+        FilterField::new("joined_at", "joined_at", FieldKind::DateTime),
+    ],
+};
+
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/member",
+    tag = "user",
+    params(("tenant_id" = String, Path), ListQuery),
+    responses((status = 200, body = PaginatedResult<MemberResponse>))
+)]
 pub async fn list_members(
     State(state): State<AppState>,
     auth: AuthUser,
     Path(tenant_id): Path<String>,
-    Query(params): Query<PaginationParams>,
-) -> Result<Json<serde_json::Value>, ApiError> {
+    query: ListQuery,
+) -> Result<Json<PaginatedResult<MemberResponse>>, ApiError> {
     let tid = ObjectId::parse_str(&tenant_id)
         .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
 
@@ -53,46 +168,211 @@ pub async fn list_members(
         return Err(ApiError::Forbidden("Not a member".to_string()));
     }
 
+    let options = query.options(&MEMBERS_SPEC)?;
     let result = state
         .tenants
         .members
-        .find_paginated(
+        .find_listed(
             doc! { "tenant_id": tid },
             Some(doc! { "joined_at": 1 }),
-            &params,
+            &query.pagination,
+            &options,
         )
         .await?;
 
-    // Resolve display names in one batch so the response carries names, not just
-    // ids (used by member pickers, e.g. the agent owner-reassign dialog).
+    // Resolve names, avatars and statuses in one batch so the response carries
+    // them, not just ids (used by member pickers, e.g. the agent owner-reassign
+    // dialog).
     let user_ids: Vec<ObjectId> = result.items.iter().map(|m| m.user_id).collect();
-    let names = state
-        .users
-        .find_display_names(&user_ids)
-        .await
-        .unwrap_or_default();
-    let items: Vec<MemberResponse> = result
-        .items
-        .into_iter()
-        .map(|m| MemberResponse {
+    let mut cards = state.users.find_cards(&user_ids).await.unwrap_or_default();
+    Ok(Json(result.map(|m| {
+        let card = cards.remove(&m.user_id);
+        MemberResponse {
             id: m.id.unwrap().to_hex(),
             user_id: m.user_id.to_hex(),
             nickname: m.nickname,
-            display_name: names.get(&m.user_id).cloned().unwrap_or_default(),
+            display_name: card
+                .as_ref()
+                .map(|c| c.display_name.clone())
+                .unwrap_or_default(),
+            is_bot: card.as_ref().is_some_and(|c| c.is_bot),
+            avatar: card.as_ref().and_then(|c| c.avatar.clone()),
+            pronouns: card.as_ref().and_then(|c| c.pronouns.clone()),
+            status: card.and_then(|c| c.status).map(|s| status_response(&s)),
             role_ids: m.role_ids.iter().map(|r| r.to_hex()).collect(),
             joined_at: m.joined_at.try_to_rfc3339_string().unwrap_or_default(),
+        }
+    })))
+}
+
+/// GET /api/tenant/{tenant_id}/member/search?q= — typeahead for mentions
+/// and DMs: members matching a prefix, with presence and roles, by name.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/member/search",
+    tag = "user",
+    params(("tenant_id" = String, Path), MemberSearchQuery),
+    responses((status = 200, body = Vec<MemberSearchResult>))
+)]
+pub async fn search_members(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<MemberSearchQuery>,
+) -> Result<Json<Vec<MemberSearchResult>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    let q = query.q.trim();
+    if q.is_empty() || q.chars().count() > 64 {
+        return Err(ApiError::Validation(
+            "q must be 1-64 characters".to_string(),
+        ));
+    }
+    let limit = query
+        .limit
+        .unwrap_or(SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+
+    let members = state.tenants.search_members(tid, q, limit).await?;
+    let user_ids: Vec<ObjectId> = members.iter().map(|m| m.user_id).collect();
+    let mut cards = state.users.find_cards(&user_ids).await?;
+    let presence = state.users.find_visible_presence(&user_ids).await?;
+    let roles = state
+        .tenants
+        .roles
+        .find_many(doc! { "tenant_id": tid }, Some(doc! { "position": 1 }))
+        .await?;
+
+    let mut results: Vec<MemberSearchResult> = members
+        .into_iter()
+        .filter_map(|m| {
+            // Deleted users have no card.
+            let card = cards.remove(&m.user_id)?;
+            Some(MemberSearchResult {
+                user_id: m.user_id.to_hex(),
+                username: card.username,
+                display_name: card.display_name,
+                nickname: m.nickname,
+                avatar: card.avatar,
+                presence: presence
+                    .get(&m.user_id)
+                    .map(|p| format!("{:?}", p).to_lowercase())
+                    .unwrap_or_else(|| "offline".to_string()),
+                is_bot: card.is_bot,
+                role_ids: m.role_ids.iter().map(|r| r.to_hex()).collect(),
+                roles: roles
+                    .iter()
+                    .filter(|r| r.id.is_some_and(|id| m.role_ids.contains(&id)))
+                    .map(|r| r.name.clone())
+                    .collect(),
+            })
         })
         .collect();
+    results.sort_by_key(|r| r.display_name.to_lowercase());
+    Ok(Json(results))
+}
 
-    Ok(Json(serde_json::json!({
-        "items": items,
-        "total": result.total,
-        "page": result.page,
-        "per_page": result.per_page,
-        "total_pages": result.total_pages,
-    })))
+/// DELETE /api/tenant/{tenant_id}/member/{user_id} — remove a member from the
+/// tenant and all of its rooms. Requires `KICK_MEMBERS`; the owner can't be
+/// removed. An optional `?reason=` is recorded in the audit log.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/member/{user_id}",
+    tag = "user",
+    params(("tenant_id" = String, Path), ("user_id" = String, Path), RemoveMemberQuery),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn remove_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, user_id)): Path<(String, String)>,
+    Query(query): Query<RemoveMemberQuery>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let uid = ObjectId::parse_str(&user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let perms = state
+        .tenants
+        .get_member_permissions(tid, auth.user_id)
+        .await?;
+    if !permissions::has(perms, permissions::KICK_MEMBERS) {
+        return Err(ApiError::Forbidden(
+            "Missing KICK_MEMBERS permission".to_string(),
+        ));
+    }
+
+    let tenant = state.tenants.base.find_by_id(tid).await?;
+    if tenant.owner_id == uid {
+        return Err(ApiError::Forbidden(
+            "The tenant owner cannot be removed".to_string(),
+        ));
+    }
+
+    let member = state
+        .tenants
+        .members
+        .find_one(doc! { "tenant_id": tid, "user_id": uid })
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Member not found".to_string()))?;
+    state
+        .permissions
+        .ensure_admin_remains(tid, RoleChange::RemoveMember(uid))
+        .await?;
+
+    for room in state.rooms.find_user_rooms(tid, uid).await? {
+        if let Some(rid) = room.id {
+            state.rooms.leave(tid, rid, uid).await?;
+        }
+    }
+    state.tenants.remove_member(tid, uid).await?;
+
+    let card = state
+        .users
+        .find_cards(&[uid])
+        .await
+        .unwrap_or_default()
+        .remove(&uid);
+    let before = MemberResponse {
+        id: member.id.map(|id| id.to_hex()).unwrap_or_default(),
+        user_id: uid.to_hex(),
+        nickname: member.nickname,
+        display_name: card
+            .as_ref()
+            .map(|c| c.display_name.clone())
+            .unwrap_or_default(),
+        is_bot: card.as_ref().is_some_and(|c| c.is_bot),
+        avatar: card.as_ref().and_then(|c| c.avatar.clone()),
+        pronouns: card.as_ref().and_then(|c| c.pronouns.clone()),
+        status: None,
+        role_ids: member.role_ids.iter().map(|r| r.to_hex()).collect(),
+        joined_at: member.joined_at.try_to_rfc3339_string().unwrap_or_default(),
+    };
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "member.remove", "member", Some(uid))
+            .before(&before)
+            .reason(query.reason),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "removed": true })))
 }
 
+#[utoipa::path(
+    get,
+    path = "/api/user/{user_id}",
+    tag = "user",
+    params(("user_id" = String, Path)),
+    responses((status = 200, body = ProfileResponse))
+)]
 pub async fn get_profile(
     State(state): State<AppState>,
     _auth: AuthUser,
@@ -102,23 +382,41 @@ pub async fn get_profile(
         .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
 
     let user = state.users.base.find_by_id(uid).await?;
-
-    Ok(Json(ProfileResponse {
-        id: user.id.unwrap().to_hex(),
-        username: user.username,
-        display_name: user.display_name,
-        avatar: user.avatar,
-        bio: user.bio,
-        presence: format!("{:?}", user.presence).to_lowercase(),
-        created_at: user.created_at.try_to_rfc3339_string().unwrap_or_default(),
-    }))
+    Ok(Json(to_profile(user)))
 }
 
+#[utoipa::path(
+    put,
+    path = "/api/user/me",
+    tag = "user",
+    request_body = UpdateProfileRequest,
+    responses((status = 200, body = serde_json::Value))
+)]
 pub async fn update_profile(
     State(state): State<AppState>,
     auth: AuthUser,
     Json(body): Json<UpdateProfileRequest>,
 ) -> Result<Json<serde_json::Value>, ApiError> {
+    let pronouns = body.pronouns.map(|p| p.trim().to_string());
+    if pronouns
+        .as_ref()
+        .is_some_and(|p| p.chars().count() > MAX_PRONOUNS)
+    {
+        return Err(ApiError::Validation(format!(
+            "Pronouns are limited to {} characters",
+            MAX_PRONOUNS
+        )));
+    }
+    if body
+        .timezone
+        .as_deref()
+        .is_some_and(|tz| !is_valid_timezone(tz))
+    {
+        return Err(ApiError::Validation(
+            "Timezone must be an IANA zone name like Europe/Vienna".to_string(),
+        ));
+    }
+
     state
         .users
         .update_profile(
@@ -126,6 +424,7 @@ pub async fn update_profile(
             body.display_name,
             body.bio,
             body.avatar,
+            pronouns,
             body.locale,
             body.timezone,
         )
@@ -133,3 +432,307 @@ pub async fn update_profile(
 
     Ok(Json(serde_json::json!({ "updated": true })))
 }
+
+/// PUT /api/user/me/status — set a custom status, optionally clearing itself
+/// at `expires_at`.
+#[utoipa::path(
+    put,
+    path = "/api/user/me/status",
+    tag = "user",
+    request_body = SetStatusRequest,
+    responses((status = 200, body = ProfileResponse))
+)]
+pub async fn set_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(body): Json<SetStatusRequest>,
+) -> Result<Json<ProfileResponse>, ApiError> {
+    let text = body
+        .text
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let emoji = body
+        .emoji
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+    if text.is_none() && emoji.is_none() {
+        return Err(ApiError::Validation(
+            "A status needs text or an emoji".to_string(),
+        ));
+    }
+    if text
+        .as_ref()
+        .is_some_and(|t| t.chars().count() > MAX_STATUS_TEXT)
+    {
+        return Err(ApiError::Validation(format!(
+            "Status text is limited to {} characters",
+            MAX_STATUS_TEXT
+        )));
+    }
+    if emoji.as_ref().is_some_and(|e| e.len() > MAX_STATUS_EMOJI) {
+        return Err(ApiError::Validation("Invalid status emoji".to_string()));
+    }
+    let expires_at = body
+        .expires_at
+        .as_deref()
+        .map(DateTime::parse_rfc3339_str)
+        .transpose()
+        .map_err(|_| ApiError::BadRequest("Invalid expires_at".to_string()))?;
+    if expires_at.is_some_and(|at| at <= DateTime::now()) {
+        return Err(ApiError::Validation(
+            "expires_at must be in the future".to_string(),
+        ));
+    }
+
+    let status = UserStatusInfo {
+        text,
+        emoji,
+        expires_at,
+    };
+    state.users.set_status(auth.user_id, &status).await?;
+
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    Ok(Json(to_profile(user)))
+}
+
+/// DELETE /api/user/me/status
+#[utoipa::path(
+    delete,
+    path = "/api/user/me/status",
+    tag = "user",
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn clear_status(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    state
+        .users
+        .set_status(auth.user_id, &UserStatusInfo::default())
+        .await?;
+    Ok(Json(serde_json::json!({ "cleared": true })))
+}
+
+/// POST /api/user/me/avatar — upload a picture. It is cut to a square
+/// (`crop_*`, or the centered one), scaled to 256 px and stored as PNG.
+#[utoipa::path(
+    post,
+    path = "/api/user/me/avatar",
+    tag = "user",
+    request_body(content = AvatarUploadForm, content_type = "multipart/form-data"),
+    responses((status = 200, body = ProfileResponse))
+)]
+pub async fn upload_avatar(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    mut multipart: Multipart,
+) -> Result<Json<ProfileResponse>, ApiError> {
+    let mut bytes: Option<Vec<u8>> = None;
+    let (mut crop_x, mut crop_y, mut crop_size) = (None, None, None);
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ApiError::BadRequest(format!("Multipart error: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| ApiError::BadRequest(format!("Failed to read file: {}", e)))?;
+            bytes = Some(data.to_vec());
+            continue;
+        }
+        let slot = match name.as_str() {
+            "crop_x" => &mut crop_x,
+            "crop_y" => &mut crop_y,
+            "crop_size" => &mut crop_size,
+            _ => continue,
+        };
+        let text = field
+            .text()
+            .await
+            .map_err(|e| ApiError::BadRequest(format!("Failed to read {}: {}", name, e)))?;
+        *slot = Some(
+            text.trim()
+                .parse::<u32>()
+                .map_err(|_| ApiError::BadRequest(format!("Invalid {}", name)))?,
+        );
+    }
+    let bytes = bytes.ok_or_else(|| ApiError::BadRequest("Missing 'file' field".to_string()))?;
+    if bytes.len() > MAX_AVATAR_SIZE {
+        return Err(ApiError::Validation(format!(
+            "Avatars are limited to {} MB",
+            MAX_AVATAR_SIZE / (1024 * 1024)
+        )));
+    }
+    let crop = match (crop_x, crop_y, crop_size) {
+        (Some(x), Some(y), Some(size)) => Some(Crop { x, y, size }),
+        (None, None, None) => None,
+        _ => {
+            return Err(ApiError::Validation(
+                "Give crop_x, crop_y and crop_size together".to_string(),
+            ));
+        }
+    };
+
+    let png = tokio::task::spawn_blocking(move || avatar::render(&bytes, crop))
+        .await
+        .map_err(|e| ApiError::Internal(format!("Avatar task failed: {}", e)))?
+        .map_err(ApiError::Validation)?;
+
+    let key = uuid::Uuid::new_v4().simple().to_string();
+    let path = avatar_path(auth.user_id, &key);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| ApiError::Internal(format!("Failed to create dirs: {}", e)))?;
+    }
+    tokio::fs::write(&path, &png)
+        .await
+        .map_err(|e| ApiError::Internal(format!("Failed to write avatar: {}", e)))?;
+
+    let previous = state.users.base.find_by_id(auth.user_id).await?.avatar_key;
+    let url = format!("/api/user/{}/avatar/{}", auth.user_id.to_hex(), key);
+    state
+        .users
+        .set_avatar(auth.user_id, Some(url), Some(key))
+        .await?;
+    if let Some(old) = previous {
+        let _ = tokio::fs::remove_file(avatar_path(auth.user_id, &old)).await;
+    }
+
+    let user = state.users.base.find_by_id(auth.user_id).await?;
+    Ok(Json(to_profile(user)))
+}
+
+/// DELETE /api/user/me/avatar — remove the avatar, uploaded or linked.
+#[utoipa::path(
+    delete,
+    path = "/api/user/me/avatar",
+    tag = "user",
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete_avatar(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let previous = state.users.base.find_by_id(auth.user_id).await?.avatar_key;
+    state.users.set_avatar(auth.user_id, None, None).await?;
+    if let Some(old) = previous {
+        let _ = tokio::fs::remove_file(avatar_path(auth.user_id, &old)).await;
+    }
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// GET /api/user/{user_id}/avatar/{key} — an uploaded avatar. The key
+/// changes with every upload, so the image can be cached for good.
+#[utoipa::path(
+    get,
+    path = "/api/user/{user_id}/avatar/{key}",
+    tag = "user",
+    params(("user_id" = String, Path), ("key" = String, Path)),
+    responses((status = 200, description = "Avatar image", content_type = "image/png"))
+)]
+pub async fn avatar(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+    Path((user_id, key)): Path<(String, String)>,
+) -> Result<Response, ApiError> {
+    let uid = ObjectId::parse_str(&user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    let user = state.users.base.find_by_id(uid).await?;
+    if user.avatar_key.as_deref() != Some(key.as_str()) {
+        return Err(ApiError::NotFound("Avatar not found".to_string()));
+    }
+    let png = tokio::fs::read(avatar_path(uid, &key))
+        .await
+        .map_err(|_| ApiError::NotFound("Avatar not found on disk".to_string()))?;
+
+    Ok(Response::builder()
+        .header("Content-Type", "image/png")
+        .header("Cache-Control", "private, max-age=31536000, immutable")
+        .body(Body::from(png))
+        .unwrap())
+}
+
+/// The status as shown to others; callers drop expired ones.
+pub(crate) fn status_response(status: &UserStatusInfo) -> UserStatusResponse {
+    UserStatusResponse {
+        text: status.text.clone(),
+        emoji: status.emoji.clone(),
+        expires_at: status
+            .expires_at
+            .map(|at| at.try_to_rfc3339_string().unwrap_or_default()),
+    }
+}
+
+fn to_profile(user: User) -> ProfileResponse {
+    let status = user
+        .status
+        .is_active(DateTime::now())
+        .then(|| status_response(&user.status));
+    ProfileResponse {
+        id: user.id.unwrap().to_hex(),
+        username: user.username,
+        display_name: user.display_name,
+        avatar: user.avatar,
+        bio: user.bio,
+        pronouns: user.pronouns,
+        timezone: user.timezone,
+        status,
+        presence: format!("{:?}", user.presence).to_lowercase(),
+        created_at: user.created_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+/// Where an uploaded avatar lives. Keys are generated here, never taken
+/// from a request unchecked, so they are safe in a path.
+fn avatar_path(user_id: ObjectId, key: &str) -> PathBuf {
+    upload_dir()
+        .join("avatars")
+        .join(user_id.to_hex())
+        .join(format!("{}.png", key))
+}
+
+/// `UTC` or an `Area/Location` style IANA name. Not checked against the zone
+/// database; clients pick from their own list.
+fn is_valid_timezone(tz: &str) -> bool {
+    tz == "UTC"
+        || (tz.len() <= 64
+            && tz.contains('/')
+            && !tz.starts_with('/')
+            && !tz.ends_with('/')
+            && !tz.contains("//")
+            && tz
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'/' | b'_' | b'-' | b'+')))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_valid_timezone;
+
+    #[test]
+    fn accepts_iana_style_zone_names() {
+        for tz in [
+            "UTC",
+            "Europe/Vienna",
+            "America/Argentina/Buenos_Aires",
+            "Etc/GMT+5",
+        ] {
+            assert!(is_valid_timezone(tz), "{tz}");
+        }
+        for tz in [
+            "",
+            "Vienna",
+            "/Europe",
+            "Europe/",
+            "Europe//Vienna",
+            "Europe/Vienna;",
+        ] {
+            assert!(!is_valid_timezone(tz), "{tz}");
+        }
+    }
+}
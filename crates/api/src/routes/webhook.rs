@@ -0,0 +1,642 @@
+//! Tenant webhooks. Outgoing hooks receive the events they subscribe to
+//! (matching messages, call start and end, recordings and their
+//! transcripts) as signed POSTs; incoming hooks let an external system post
+//! into one room through a secret URL, shown under the webhook's name.
+//!
+//! Signatures follow the Stripe scheme: `X-Roomler-Signature` is
+//! `sha256=<hex HMAC-SHA256 of "{X-Roomler-Timestamp}.{body}">` keyed with
+//! the integration's secret, so receivers can reject replays by timestamp.
+//! A delivery that fails with a network error, `429` or `5xx` is retried
+//! after each of [`RETRY_DELAYS`].
+
+use axum::{
+    Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use bson::oid::ObjectId;
+use hmac::{Hmac, Mac};
+use roomler_ai_db::models::{AuthorType, WEBHOOK_EVENTS, Webhook, WebhookKind, role::permissions};
+use roomler_ai_services::dao::{
+    message::CreateMessageParams,
+    webhook::{CreateWebhookParams, UpdateWebhookParams},
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+use super::message::{self, MessageResponse};
+use crate::{
+    error::ApiError,
+    extractors::auth::AuthUser,
+    integrations_http,
+    middleware::audit::{self, AuditContext, AuditEntry},
+    state::AppState,
+};
+
+/// Waits before each retry of a failed delivery.
+const RETRY_DELAYS: [Duration; 2] = [Duration::from_secs(1), Duration::from_secs(5)];
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    #[schema(value_type = String)]
+    pub kind: WebhookKind,
+    #[serde(default)]
+    pub room_ids: Vec<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Outgoing only: `message.create`, `call.started`, `call.ended`,
+    /// `recording.ready`, `recording.transcript` (empty = `message.create`).
+    #[serde(default)]
+    pub events: Vec<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UpdateWebhookRequest {
+    pub name: Option<String>,
+    pub room_ids: Option<Vec<String>>,
+    pub keywords: Option<Vec<String>>,
+    pub events: Option<Vec<String>>,
+    pub url: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct IncomingWebhookRequest {
+    pub content: String,
+    /// Overrides the webhook's name as the shown author.
+    pub username: Option<String>,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebhookResponse {
+    pub id: String,
+    pub name: String,
+    #[schema(value_type = String)]
+    pub kind: WebhookKind,
+    pub room_ids: Vec<String>,
+    pub keywords: Vec<String>,
+    pub events: Vec<String>,
+    pub url: Option<String>,
+    /// Signing key (outgoing) or URL token (incoming).
+    pub secret: String,
+    /// Incoming only: the path external systems POST to.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hook_path: Option<String>,
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// Webhooks and slash commands are tenant configuration: `MANAGE_TENANT`.
+pub(crate) async fn require_manage_integrations(
+    state: &AppState,
+    tenant_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<(), ApiError> {
+    let perms = state
+        .tenants
+        .get_member_permissions(tenant_id, user_id)
+        .await?;
+    if !permissions::has(perms, permissions::MANAGE_TENANT) {
+        return Err(ApiError::Forbidden(
+            "Missing MANAGE_TENANT permission".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Integration endpoints must be absolute http(s) URLs, outside the
+/// server's own networks (see [`integrations_http`]).
+pub(crate) fn validate_url(state: &AppState, url: &str) -> Result<(), ApiError> {
+    integrations_http::check_url(url, state.settings.integrations.allow_private_networks)
+        .map_err(|e| ApiError::Validation(e.to_string()))
+}
+
+/// Only known events; duplicates are dropped.
+fn validate_events(events: Vec<String>) -> Result<Vec<String>, ApiError> {
+    let mut valid: Vec<String> = Vec::with_capacity(events.len());
+    for event in events {
+        if !WEBHOOK_EVENTS.contains(&event.as_str()) {
+            return Err(ApiError::Validation(format!(
+                "Unknown event '{}'; expected one of {}",
+                event,
+                WEBHOOK_EVENTS.join(", ")
+            )));
+        }
+        if !valid.contains(&event) {
+            valid.push(event);
+        }
+    }
+    Ok(valid)
+}
+
+/// Parse `room_ids` and check each belongs to the tenant.
+async fn parse_rooms(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_ids: &[String],
+) -> Result<Vec<ObjectId>, ApiError> {
+    let mut ids = Vec::with_capacity(room_ids.len());
+    for raw in room_ids {
+        let rid = ObjectId::parse_str(raw)
+            .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+        state
+            .rooms
+            .base
+            .find_by_id_in_tenant(tenant_id, rid)
+            .await?;
+        ids.push(rid);
+    }
+    Ok(ids)
+}
+
+/// GET /api/tenant/{tenant_id}/webhook
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/webhook",
+    tag = "webhook",
+    params(("tenant_id" = String, Path)),
+    responses((status = 200, body = Vec<WebhookResponse>))
+)]
+pub async fn list(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<Vec<WebhookResponse>>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let hooks = state.webhooks.list_for_tenant(tid).await?;
+    Ok(Json(hooks.into_iter().map(to_response).collect()))
+}
+
+/// POST /api/tenant/{tenant_id}/webhook — an outgoing webhook needs a `url`;
+/// an incoming one exactly one room.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/webhook",
+    tag = "webhook",
+    params(("tenant_id" = String, Path)),
+    request_body = CreateWebhookRequest,
+    responses((status = 201, body = WebhookResponse))
+)]
+pub async fn create(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path(tenant_id): Path<String>,
+    Json(body): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, Json<WebhookResponse>), ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let name = body.name.trim().to_string();
+    if name.is_empty() {
+        return Err(ApiError::Validation("name is required".to_string()));
+    }
+    match body.kind {
+        WebhookKind::Outgoing => {
+            let url = body
+                .url
+                .as_deref()
+                .ok_or_else(|| ApiError::Validation("url is required".to_string()))?;
+            validate_url(&state, url)?;
+        }
+        WebhookKind::Incoming => {
+            if body.room_ids.len() != 1 {
+                return Err(ApiError::Validation(
+                    "an incoming webhook posts to exactly one room".to_string(),
+                ));
+            }
+        }
+    }
+    let room_ids = parse_rooms(&state, tid, &body.room_ids).await?;
+    let events = match body.kind {
+        WebhookKind::Outgoing => validate_events(body.events)?,
+        WebhookKind::Incoming => Vec::new(),
+    };
+
+    let hook = state
+        .webhooks
+        .create(
+            tid,
+            auth.user_id,
+            CreateWebhookParams {
+                name,
+                kind: body.kind,
+                room_ids,
+                keywords: body.keywords,
+                events,
+                url: body.url.filter(|_| body.kind == WebhookKind::Outgoing),
+            },
+        )
+        .await?;
+    let wid = hook.id;
+    let response = to_response(hook);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "webhook.create", "webhook", wid)
+            .after(&redacted(&response)),
+    )
+    .await;
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// PUT /api/tenant/{tenant_id}/webhook/{webhook_id}
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/webhook/{webhook_id}",
+    tag = "webhook",
+    params(("tenant_id" = String, Path), ("webhook_id" = String, Path)),
+    request_body = UpdateWebhookRequest,
+    responses((status = 200, body = WebhookResponse))
+)]
+pub async fn update(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, webhook_id)): Path<(String, String)>,
+    Json(body): Json<UpdateWebhookRequest>,
+) -> Result<Json<WebhookResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let wid = ObjectId::parse_str(&webhook_id)
+        .map_err(|_| ApiError::BadRequest("Invalid webhook_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let existing = state.webhooks.base.find_by_id_in_tenant(tid, wid).await?;
+    if let Some(url) = body.url.as_deref() {
+        if existing.kind == WebhookKind::Incoming {
+            return Err(ApiError::Validation(
+                "incoming webhooks have no url".to_string(),
+            ));
+        }
+        validate_url(&state, url)?;
+    }
+    let room_ids = match body.room_ids {
+        Some(ref ids) => {
+            if existing.kind == WebhookKind::Incoming && ids.len() != 1 {
+                return Err(ApiError::Validation(
+                    "an incoming webhook posts to exactly one room".to_string(),
+                ));
+            }
+            Some(parse_rooms(&state, tid, ids).await?)
+        }
+        None => None,
+    };
+    let name = body.name.map(|n| n.trim().to_string());
+    if name.as_deref() == Some("") {
+        return Err(ApiError::Validation("name is required".to_string()));
+    }
+    let events = match body.events {
+        Some(_) if existing.kind == WebhookKind::Incoming => {
+            return Err(ApiError::Validation(
+                "incoming webhooks have no events".to_string(),
+            ));
+        }
+        Some(events) => Some(validate_events(events)?),
+        None => None,
+    };
+
+    let before = to_response(existing);
+    let updated = state
+        .webhooks
+        .update(
+            tid,
+            wid,
+            UpdateWebhookParams {
+                name,
+                room_ids,
+                keywords: body.keywords,
+                events,
+                url: body.url,
+                is_active: body.is_active,
+            },
+        )
+        .await?;
+    let response = to_response(updated);
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "webhook.update", "webhook", Some(wid))
+            .before(&redacted(&before))
+            .after(&redacted(&response)),
+    )
+    .await;
+
+    Ok(Json(response))
+}
+
+/// DELETE /api/tenant/{tenant_id}/webhook/{webhook_id}
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/webhook/{webhook_id}",
+    tag = "webhook",
+    params(("tenant_id" = String, Path), ("webhook_id" = String, Path)),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn delete(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    ctx: AuditContext,
+    Path((tenant_id, webhook_id)): Path<(String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let wid = ObjectId::parse_str(&webhook_id)
+        .map_err(|_| ApiError::BadRequest("Invalid webhook_id".to_string()))?;
+    require_manage_integrations(&state, tid, auth.user_id).await?;
+
+    let before = to_response(state.webhooks.base.find_by_id_in_tenant(tid, wid).await?);
+    state.webhooks.delete(tid, wid).await?;
+
+    audit::record(
+        &state,
+        &ctx,
+        AuditEntry::new(tid, auth.user_id, "webhook.delete", "webhook", Some(wid))
+            .before(&redacted(&before)),
+    )
+    .await;
+
+    Ok(Json(serde_json::json!({ "deleted": true })))
+}
+
+/// POST /api/hook/{webhook_id}/{token} — public entry point of an incoming
+/// webhook. The token is the credential; the message is authored by the
+/// webhook, not a user.
+#[utoipa::path(
+    post,
+    path = "/api/hook/{webhook_id}/{token}",
+    tag = "webhook",
+    params(("webhook_id" = String, Path), ("token" = String, Path)),
+    request_body = IncomingWebhookRequest,
+    responses((status = 200, body = MessageResponse)),
+    security(())
+)]
+pub async fn incoming(
+    State(state): State<AppState>,
+    Path((webhook_id, token)): Path<(String, String)>,
+    Json(body): Json<IncomingWebhookRequest>,
+) -> Result<Json<MessageResponse>, ApiError> {
+    let wid = ObjectId::parse_str(&webhook_id)
+        .map_err(|_| ApiError::NotFound("Webhook not found".to_string()))?;
+    let hook = state
+        .webhooks
+        .find_incoming(wid, &token)
+        .await
+        .map_err(|_| ApiError::NotFound("Webhook not found".to_string()))?;
+
+    if body.content.trim().is_empty() {
+        return Err(ApiError::Validation("content is required".to_string()));
+    }
+    let room_id = *hook
+        .room_ids
+        .first()
+        .ok_or_else(|| ApiError::NotFound("Webhook room not found".to_string()))?;
+    state
+        .rooms
+        .base
+        .find_by_id_in_tenant(hook.tenant_id, room_id)
+        .await?;
+    crate::middleware::rate_limit::check_message(&state, hook.tenant_id, wid).await?;
+
+    let author_name = body
+        .username
+        .map(|u| u.trim().to_string())
+        .filter(|u| !u.is_empty())
+        .unwrap_or(hook.name);
+    let response = message::deliver(
+        &state,
+        CreateMessageParams {
+            tenant_id: hook.tenant_id,
+            room_id,
+            author_id: wid,
+            author_type: AuthorType::Webhook,
+            author_name: Some(author_name),
+            content: body.content,
+            thread_id: None,
+            referenced_message_id: None,
+            nonce: None,
+            mentions: None,
+            attachments: Vec::new(),
+            is_silent: false,
+        },
+    )
+    .await?;
+
+    Ok(Json(response))
+}
+
+/// Send a new message to the tenant's matching outgoing webhooks in the
+/// background.
+pub(crate) fn dispatch_outgoing(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    message: &MessageResponse,
+) {
+    let webhooks = state.webhooks.clone();
+    let http = state.integrations_http.clone();
+    let allow_private = state.settings.integrations.allow_private_networks;
+    let content = message.content.clone();
+    let payload = serde_json::json!({
+        "event": "message.create",
+        "tenant_id": tenant_id.to_hex(),
+        "room_id": room_id.to_hex(),
+        "message": message,
+    });
+
+    tokio::spawn(async move {
+        let hooks = match webhooks
+            .find_outgoing_matches(tenant_id, room_id, &content)
+            .await
+        {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                tracing::warn!(%tenant_id, %e, "Failed to load outgoing webhooks");
+                return;
+            }
+        };
+        deliver_all(
+            &http,
+            allow_private,
+            hooks,
+            "message.create",
+            payload.to_string(),
+        )
+        .await;
+    });
+}
+
+/// Send a call or recording event to the outgoing webhooks subscribed to
+/// it, in the background. `fields` (an object) is merged into the
+/// `{ event, tenant_id, room_id }` envelope.
+pub(crate) fn dispatch_event(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    event: &'static str,
+    fields: serde_json::Value,
+) {
+    let webhooks = state.webhooks.clone();
+    let http = state.integrations_http.clone();
+    let allow_private = state.settings.integrations.allow_private_networks;
+    let mut payload = serde_json::json!({
+        "event": event,
+        "tenant_id": tenant_id.to_hex(),
+        "room_id": room_id.to_hex(),
+    });
+    if let (Some(envelope), serde_json::Value::Object(fields)) = (payload.as_object_mut(), fields) {
+        envelope.extend(fields);
+    }
+
+    tokio::spawn(async move {
+        let hooks = match webhooks.find_subscribers(tenant_id, room_id, event).await {
+            Ok(hooks) => hooks,
+            Err(e) => {
+                tracing::warn!(%tenant_id, %e, "Failed to load outgoing webhooks");
+                return;
+            }
+        };
+        deliver_all(&http, allow_private, hooks, event, payload.to_string()).await;
+    });
+}
+
+/// Deliver one event to each hook concurrently, so a hook being retried
+/// doesn't hold up the others. Hooks whose URL points into a private
+/// network (e.g. saved before that was refused) are skipped.
+async fn deliver_all(
+    http: &reqwest::Client,
+    allow_private: bool,
+    hooks: Vec<Webhook>,
+    event: &str,
+    body: String,
+) {
+    let deliveries = hooks.iter().filter_map(|hook| {
+        let url = hook.url.as_deref()?;
+        if let Err(e) = integrations_http::check_url(url, allow_private) {
+            tracing::warn!(webhook_id = ?hook.id, %e, "Outgoing webhook URL refused");
+            return None;
+        }
+        let body = body.clone();
+        Some(async move {
+            if let Err(e) = deliver(http, url, &hook.secret, event, body).await {
+                tracing::warn!(webhook_id = ?hook.id, %event, %e, "Outgoing webhook delivery failed");
+            }
+        })
+    });
+    futures::future::join_all(deliveries).await;
+}
+
+/// [`signed_post`], retried after each of [`RETRY_DELAYS`] on network
+/// errors, `429` and `5xx`. Each attempt is signed afresh.
+async fn deliver(
+    http: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    event: &str,
+    body: String,
+) -> reqwest::Result<()> {
+    let mut delays = RETRY_DELAYS.iter();
+    loop {
+        let result = signed_post(http, url, secret, event, body.clone())
+            .await
+            .and_then(|r| r.error_for_status());
+        let retryable = match &result {
+            Ok(_) => return Ok(()),
+            Err(e) => e
+                .status()
+                .is_none_or(|s| s.is_server_error() || s.as_u16() == 429),
+        };
+        match delays.next() {
+            Some(delay) if retryable => tokio::time::sleep(*delay).await,
+            _ => return result.map(|_| ()),
+        }
+    }
+}
+
+/// POST `body` as JSON with the integration signature headers.
+pub(crate) async fn signed_post(
+    http: &reqwest::Client,
+    url: &str,
+    secret: &str,
+    event: &str,
+    body: String,
+) -> reqwest::Result<reqwest::Response> {
+    let timestamp = chrono::Utc::now().timestamp();
+    http.post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Roomler-Event", event)
+        .header("X-Roomler-Timestamp", timestamp.to_string())
+        .header("X-Roomler-Signature", sign(secret, timestamp, &body))
+        .body(body)
+        .send()
+        .await
+}
+
+/// `sha256=<hex>` of `"{timestamp}.{body}"` keyed with `secret`.
+pub(crate) fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take key of any size");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Audit snapshots never carry the secret.
+fn redacted(response: &WebhookResponse) -> serde_json::Value {
+    let mut value = serde_json::to_value(response).unwrap_or_default();
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("secret");
+        obj.remove("hook_path");
+    }
+    value
+}
+
+fn to_response(w: Webhook) -> WebhookResponse {
+    let id = w.id.map(|id| id.to_hex()).unwrap_or_default();
+    let hook_path =
+        (w.kind == WebhookKind::Incoming).then(|| format!("/api/hook/{}/{}", id, w.secret));
+    WebhookResponse {
+        id,
+        name: w.name,
+        kind: w.kind,
+        room_ids: w.room_ids.iter().map(|r| r.to_hex()).collect(),
+        keywords: w.keywords,
+        events: w.events,
+        url: w.url,
+        secret: w.secret,
+        hook_path,
+        is_active: w.is_active,
+        created_by: w.created_by.to_hex(),
+        created_at: w.created_at.try_to_rfc3339_string().unwrap_or_default(),
+        updated_at: w.updated_at.try_to_rfc3339_string().unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        assert_eq!(
+            sign("secret", 1_700_000_000, r#"{"a":1}"#),
+            "sha256=49f24e537407743fa4a0242bb63b94b9a47ee99cbbe071ccd8a22550ae411686"
+        );
+        assert_ne!(
+            sign("secret", 1_700_000_001, r#"{"a":1}"#),
+            sign("secret", 1_700_000_000, r#"{"a":1}"#)
+        );
+    }
+}
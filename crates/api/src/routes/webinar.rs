@@ -0,0 +1,137 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+use roomler_ai_db::models::role::permissions;
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WebinarResponse {
+    pub enabled: bool,
+    /// Promoted speakers, connected or not.
+    pub speakers: Vec<String>,
+    /// Distinct connected users by role.
+    pub speaker_count: usize,
+    pub attendee_count: usize,
+}
+
+/// The running call's webinar roles and head counts.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/webinar",
+    tag = "room",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = WebinarResponse))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<WebinarResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    if !state.room_manager.has_room(&rid) {
+        return Err(ApiError::Conflict("No call in progress".to_string()));
+    }
+
+    let (speaker_count, attendee_count) = state.room_manager.webinar_counts(&rid);
+    Ok(Json(WebinarResponse {
+        enabled: state.room_manager.is_webinar(&rid),
+        speakers: state
+            .room_manager
+            .speakers(&rid)
+            .iter()
+            .map(|s| s.to_hex())
+            .collect(),
+        speaker_count,
+        attendee_count,
+    }))
+}
+
+/// Let a user send media in the webinar.
+#[utoipa::path(
+    put,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/speaker/{user_id}",
+    tag = "room",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("user_id" = String, Path)
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn promote(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_role(&state, auth, &tenant_id, &room_id, &user_id, true).await
+}
+
+/// Make a speaker an attendee again; their producers are closed.
+#[utoipa::path(
+    delete,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/call/speaker/{user_id}",
+    tag = "room",
+    params(
+        ("tenant_id" = String, Path),
+        ("room_id" = String, Path),
+        ("user_id" = String, Path)
+    ),
+    responses((status = 200, body = serde_json::Value))
+)]
+pub async fn demote(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id, user_id)): Path<(String, String, String)>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    set_role(&state, auth, &tenant_id, &room_id, &user_id, false).await
+}
+
+async fn set_role(
+    state: &AppState,
+    auth: AuthUser,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: &str,
+    speaker: bool,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let tid = ObjectId::parse_str(tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+    let uid = ObjectId::parse_str(user_id)
+        .map_err(|_| ApiError::BadRequest("Invalid user_id".to_string()))?;
+
+    state
+        .permissions
+        .require_room(tid, rid, auth.user_id, permissions::MANAGE_MEETINGS)
+        .await?;
+    if !state.room_manager.has_room(&rid) {
+        return Err(ApiError::Conflict("No call in progress".to_string()));
+    }
+    if !state.room_manager.is_webinar(&rid) {
+        return Err(ApiError::Conflict("The call isn't a webinar".to_string()));
+    }
+    if !state.tenants.is_member(tid, uid).await? {
+        return Err(ApiError::NotFound("User not found".to_string()));
+    }
+
+    let closed = state
+        .room_manager
+        .set_speaker(&rid, &uid, speaker)
+        .ok_or_else(|| ApiError::Conflict("No call in progress".to_string()))?;
+    crate::ws::webinar::announce_role(state, &rid, &uid, speaker, &closed).await;
+
+    Ok(Json(serde_json::json!({ "speaker": speaker })))
+}
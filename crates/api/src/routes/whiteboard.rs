@@ -0,0 +1,125 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+};
+use bson::oid::ObjectId;
+use roomler_ai_services::export::whiteboard::render_svg;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use super::file::{FileResponse, do_upload};
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WhiteboardResponse {
+    pub room_id: String,
+    /// Sequence number of the last op applied.
+    pub seq: i64,
+    /// Elements in z-order.
+    pub elements: Vec<serde_json::Value>,
+    /// File holding the latest image export, if any.
+    pub export_file_id: Option<String>,
+}
+
+/// The room's whiteboard, including ops not yet snapshotted.
+#[utoipa::path(
+    get,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/whiteboard",
+    tag = "whiteboard",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = WhiteboardResponse))
+)]
+pub async fn get(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<WhiteboardResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+
+    let board = crate::ws::whiteboard::current(&state, rid).await?;
+    let export_file_id = state
+        .whiteboards
+        .find_by_room(rid)
+        .await?
+        .and_then(|w| w.export_file_id)
+        .map(|id| id.to_hex());
+    Ok(Json(WhiteboardResponse {
+        room_id: rid.to_hex(),
+        seq: board.seq,
+        elements: board.elements,
+        export_file_id,
+    }))
+}
+
+/// Render the board to an SVG image and attach it to the room as a file.
+#[utoipa::path(
+    post,
+    path = "/api/tenant/{tenant_id}/room/{room_id}/whiteboard/export",
+    tag = "whiteboard",
+    params(("tenant_id" = String, Path), ("room_id" = String, Path)),
+    responses((status = 200, body = FileResponse))
+)]
+pub async fn export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((tenant_id, room_id)): Path<(String, String)>,
+) -> Result<Json<FileResponse>, ApiError> {
+    let tid = ObjectId::parse_str(&tenant_id)
+        .map_err(|_| ApiError::BadRequest("Invalid tenant_id".to_string()))?;
+    let rid = ObjectId::parse_str(&room_id)
+        .map_err(|_| ApiError::BadRequest("Invalid room_id".to_string()))?;
+
+    if !state.tenants.is_member(tid, auth.user_id).await? {
+        return Err(ApiError::Forbidden("Not a member".to_string()));
+    }
+    state.rooms.base.find_by_id_in_tenant(tid, rid).await?;
+    crate::middleware::rate_limit::check_upload(&state, tid, auth.user_id).await?;
+
+    Ok(Json(export_board(&state, tid, rid, auth.user_id).await?))
+}
+
+/// Save the board, render it and store the image as a room file uploaded by
+/// `user_id`.
+pub(crate) async fn export_board(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_id: ObjectId,
+) -> Result<FileResponse, ApiError> {
+    crate::ws::whiteboard::flush(state, room_id).await?;
+    let room = state
+        .rooms
+        .base
+        .find_by_id_in_tenant(tenant_id, room_id)
+        .await?;
+    let board = crate::ws::whiteboard::current(state, room_id).await?;
+    if board.seq == 0 {
+        return Err(ApiError::NotFound("Whiteboard is empty".to_string()));
+    }
+
+    let filename = format!(
+        "whiteboard-{}.svg",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S")
+    );
+    let svg = render_svg(&board.elements).into_bytes();
+    let file = do_upload(
+        state,
+        room.tenant_id,
+        room_id,
+        user_id,
+        (filename, "image/svg+xml".to_string(), svg),
+    )
+    .await?;
+    if let Ok(file_id) = ObjectId::parse_str(&file.id) {
+        state.whiteboards.set_export(room_id, file_id).await?;
+    }
+    Ok(file)
+}
@@ -0,0 +1,57 @@
+use axum::{Json, extract::State};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+use crate::{error::ApiError, extractors::auth::AuthUser, state::AppState};
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct WsStatsResponse {
+    /// Open WebSocket connections on this pod.
+    pub connections: usize,
+    /// Connections dropped because a ping went unanswered.
+    pub reaped_missed_pong: u64,
+    /// Connections dropped for sending no message for `ws.idle_timeout_secs`.
+    pub reaped_idle: u64,
+    /// Connections dropped for falling `ws.outbound_queue_size` messages
+    /// behind.
+    pub reaped_queue_overflow: u64,
+    /// Connections dropped because a write took longer than
+    /// `ws.send_timeout_secs`.
+    pub reaped_send_stalled: u64,
+    /// High-frequency events dropped from full outbound queues.
+    pub dropped_messages: u64,
+    /// Queued events replaced by a newer one, such as a partial transcript
+    /// superseded by the next.
+    pub coalesced_messages: u64,
+    /// Messages waiting in all outbound queues.
+    pub queued_messages: usize,
+    /// The deepest outbound queue.
+    pub max_queue_depth: usize,
+}
+
+/// GET /api/ws/stats — this pod's WebSocket connection, keepalive and
+/// outbound queue counters.
+#[utoipa::path(
+    get,
+    path = "/api/ws/stats",
+    tag = "ws",
+    responses((status = 200, body = WsStatsResponse))
+)]
+pub async fn stats(
+    State(state): State<AppState>,
+    _auth: AuthUser,
+) -> Result<Json<WsStatsResponse>, ApiError> {
+    let stats = state.ws_stats.snapshot();
+    let (queued_messages, max_queue_depth) = state.ws_storage.queue_depths();
+    Ok(Json(WsStatsResponse {
+        connections: state.ws_storage.connection_count(),
+        reaped_missed_pong: stats.reaped_missed_pong,
+        reaped_idle: stats.reaped_idle,
+        reaped_queue_overflow: stats.reaped_queue_overflow,
+        reaped_send_stalled: stats.reaped_send_stalled,
+        dropped_messages: stats.dropped_messages,
+        coalesced_messages: stats.coalesced_messages,
+        queued_messages,
+        max_queue_depth,
+    }))
+}
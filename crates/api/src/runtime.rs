@@ -0,0 +1,204 @@
+//! Settings that change without a restart.
+//!
+//! [`RuntimeSettings`] (TURN servers, rate limits, feature switches) are
+//! the config files and environment with the operator overrides of
+//! `PUT /api/admin/settings` on top. The current value sits behind an
+//! `ArcSwap`: readers take a snapshot with [`Runtime::load`] per use, and a
+//! change is visible to the next request or connection without locking.
+//!
+//! Every `app.settings_reload_secs` each pod re-reads its config files and
+//! the stored overrides, so file edits (e.g. a re-rendered ConfigMap or
+//! consul-template output) and overrides saved on another pod arrive
+//! without a restart. Environment variables are fixed for the life of the
+//! process.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use arc_swap::ArcSwap;
+use roomler_ai_config::{RuntimeSettings, Settings};
+use serde_json::Value;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+pub struct Runtime {
+    current: ArcSwap<RuntimeSettings>,
+    /// Values from the config files and environment, and the overrides.
+    sources: Mutex<(RuntimeSettings, BTreeMap<String, Value>)>,
+    /// Held across storing and applying an operator change, so this pod
+    /// applies changes in the order they were stored.
+    writes: tokio::sync::Mutex<()>,
+}
+
+impl Runtime {
+    pub fn new(base: RuntimeSettings) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(base.clone()),
+            sources: Mutex::new((base, BTreeMap::new())),
+            writes: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    /// The values in effect now.
+    pub fn load(&self) -> Arc<RuntimeSettings> {
+        self.current.load_full()
+    }
+
+    pub fn overrides(&self) -> BTreeMap<String, Value> {
+        self.sources.lock().unwrap().1.clone()
+    }
+
+    /// Replace the overrides. Nothing changes unless all of them apply.
+    pub fn set_overrides(&self, overrides: BTreeMap<String, Value>) -> Result<(), String> {
+        let mut sources = self.sources.lock().unwrap();
+        let next = sources.0.with_overrides(&overrides)?;
+        sources.1 = overrides;
+        self.swap(next);
+        Ok(())
+    }
+
+    /// Whether `changes` (`null` drops a key) would apply, without applying
+    /// them.
+    pub fn check_changes(&self, changes: &BTreeMap<String, Value>) -> Result<(), String> {
+        let set: BTreeMap<String, Value> = changes
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        let sources = self.sources.lock().unwrap();
+        sources.0.with_overrides(&set)?;
+        Ok(())
+    }
+
+    /// Serialize an operator change: hold the guard while the change is
+    /// stored and then applied with [`Runtime::apply_stored`].
+    pub async fn lock_writes(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.writes.lock().await
+    }
+
+    /// Replace the overrides with the stored ones, keeping the config file
+    /// values. Overrides that no longer apply are skipped.
+    pub fn apply_stored(&self, overrides: BTreeMap<String, Value>) {
+        let mut sources = self.sources.lock().unwrap();
+        let base = sources.0.clone();
+        self.apply(&mut sources, base, overrides);
+    }
+
+    /// Replace both sources, as read back from the config files and the
+    /// database. Overrides that no longer apply are skipped.
+    pub fn reload(&self, base: RuntimeSettings, overrides: BTreeMap<String, Value>) {
+        let mut sources = self.sources.lock().unwrap();
+        self.apply(&mut sources, base, overrides);
+    }
+
+    fn apply(
+        &self,
+        sources: &mut (RuntimeSettings, BTreeMap<String, Value>),
+        base: RuntimeSettings,
+        overrides: BTreeMap<String, Value>,
+    ) {
+        let mut next = base.clone();
+        for (key, value) in &overrides {
+            let single = BTreeMap::from([(key.clone(), value.clone())]);
+            match next.with_overrides(&single) {
+                Ok(applied) => next = applied,
+                Err(e) => warn!(key, %e, "Skipping stored settings override"),
+            }
+        }
+        *sources = (base, overrides);
+        self.swap(next);
+    }
+
+    fn swap(&self, next: RuntimeSettings) {
+        let previous = self.current.swap(Arc::new(next));
+        let (before, after) = (previous.tunables(), self.current.load().tunables());
+        let changed: BTreeSet<&String> = after
+            .keys()
+            .chain(before.keys())
+            .filter(|key| before.get(*key) != after.get(*key))
+            .collect();
+        if !changed.is_empty() {
+            info!(?changed, "Runtime settings changed");
+        }
+    }
+}
+
+/// Re-read the config files and stored overrides every
+/// `app.settings_reload_secs` (0 disables).
+pub(crate) fn spawn_reloader(state: AppState) {
+    let interval = state.settings.app.settings_reload_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(std::time::Duration::from_secs(interval));
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let base = match Settings::load() {
+                Ok(settings) => RuntimeSettings::from_settings(&settings),
+                Err(e) => {
+                    warn!(%e, "Failed to re-read settings; keeping the current ones");
+                    continue;
+                }
+            };
+            let overrides = match state.settings_overrides.get().await {
+                Ok(stored) => stored.map(|o| o.to_map()).unwrap_or_default(),
+                Err(e) => {
+                    warn!(%e, "Failed to load settings overrides; keeping the current ones");
+                    state.runtime.overrides()
+                }
+            };
+            state.runtime.reload(base, overrides);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base() -> RuntimeSettings {
+        RuntimeSettings {
+            turn: serde_json::from_value(json!({ "health_check_secs": 0 })).unwrap(),
+            rate_limit: Default::default(),
+            features: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn overrides_apply_all_or_nothing() {
+        let runtime = Runtime::new(base());
+        let good = BTreeMap::from([("rate_limit.auth_per_min".to_string(), json!(5))]);
+        runtime.set_overrides(good.clone()).unwrap();
+        assert_eq!(runtime.load().rate_limit.auth_per_min, 5);
+
+        let mut bad = good.clone();
+        bad.insert("rate_limit.enabled".to_string(), json!(false));
+        bad.insert("jwt.secret".to_string(), json!("x"));
+        assert!(runtime.set_overrides(bad).is_err());
+        assert!(runtime.load().rate_limit.enabled);
+        assert_eq!(runtime.overrides(), good);
+    }
+
+    #[test]
+    fn reload_keeps_overrides_on_the_new_base() {
+        let runtime = Runtime::new(base());
+        runtime
+            .set_overrides(BTreeMap::from([("features.beta".to_string(), json!(true))]))
+            .unwrap();
+        let mut file = base();
+        file.rate_limit.ws_messages_per_sec = 7;
+        let stored = BTreeMap::from([
+            ("features.beta".to_string(), json!(true)),
+            ("turn.shared_secret".to_string(), json!("skipped")),
+        ]);
+        runtime.reload(file, stored);
+        let now = runtime.load();
+        assert_eq!(now.rate_limit.ws_messages_per_sec, 7);
+        assert!(now.feature("beta"));
+        assert_eq!(now.turn.shared_secret, None);
+    }
+}
@@ -3,31 +3,51 @@ use std::collections::HashSet;
 use bson::oid::ObjectId;
 use dashmap::DashMap;
 use mongodb::Database;
-use roomler_ai_config::Settings;
+use roomler_ai_config::{RuntimeSettings, Settings};
 use roomler_ai_remote_control::{
     Hub, audit::AuditSink, hub::ConsentEvent, models::ConsentMode, signaling::ServerMsg,
     turn_creds::TurnConfig,
 };
 use roomler_ai_services::{
-    AuthService, EmailService, GiphyService, OAuthService, PushService, RecognitionService,
-    TaskService,
+    AssistantModel, AuthService, CalendarService, DomainVerifier, EmailService, GiphyService,
+    OAuthService, PermissionService, PreviewService, PushService, RecognitionService, ScanService,
+    SpeechSynthesizer, TaskService,
     dao::{
-        activation_code::ActivationCodeDao, agent::AgentDao, consent_request::ConsentRequestDao,
-        file::FileDao, invite::InviteDao, message::MessageDao, notification::NotificationDao,
-        overlay_network::OverlayNetworkDao, overlay_node::OverlayNodeDao,
-        push_subscription::PushSubscriptionDao, reaction::ReactionDao, recording::RecordingDao,
+        activation_code::ActivationCodeDao, agent::AgentDao, audit_log::AuditLogDao, base,
+        bot_token::BotTokenDao, calendar_connection::CalendarConnectionDao,
+        call_debug_event::CallDebugEventDao, call_poll::CallPollDao,
+        call_question::CallQuestionDao, call_session::CallSessionDao,
+        consent_request::ConsentRequestDao, custom_emoji::CustomEmojiDao,
+        feature_flag::FeatureFlagDao, file::FileDao, invite::InviteDao, message::MessageDao,
+        notification::NotificationDao, overlay_network::OverlayNetworkDao,
+        overlay_node::OverlayNodeDao, push_subscription::PushSubscriptionDao,
+        reaction::ReactionDao, recording::RecordingDao, recording_consent::RecordingConsentDao,
         remote_audit::RemoteAuditDao, remote_session::RemoteSessionDao, role::RoleDao,
-        room::RoomDao, tenant::TenantDao, tunnel_audit::TunnelAuditDao,
-        tunnel_client::TunnelClientDao, tunnel_policy::TunnelPolicyDao, user::UserDao,
+        room::RoomDao, scheduled_message::ScheduledMessageDao,
+        settings_override::SettingsOverrideDao, slash_command::SlashCommandDao,
+        stripe_event::StripeEventDao, tenant::TenantDao, tenant_domain::TenantDomainDao,
+        tunnel_audit::TunnelAuditDao, tunnel_client::TunnelClientDao,
+        tunnel_policy::TunnelPolicyDao, usage::UsageDao, user::UserDao, webhook::WebhookDao,
+        whiteboard::WhiteboardDao,
     },
-    media::{room_manager::RoomManager, worker_pool::WorkerPool},
+    media::{backend, room_manager::RoomManager},
 };
 use tokio::sync::mpsc;
 
 use std::sync::Arc;
 
+use crate::middleware::plan_limits::PlanCache;
+use crate::middleware::rate_limit::RateLimiter;
+use crate::routes::health::Watchdog;
+use crate::runtime::Runtime;
+use crate::ws::call_debug::CallDebugLog;
+use crate::ws::conference_registry::ConferenceRegistry;
+use crate::ws::event_log::EventLog;
+use crate::ws::keepalive::WsHealthStats;
+use crate::ws::presence::PresenceHub;
 use crate::ws::redis_pubsub::RedisPubSub;
 use crate::ws::storage::WsStorage;
+use crate::ws::turn_regions::TurnRegionStats;
 
 /// Outbound channel for a connected `roomler-tunnel` client, keyed by
 /// the `tunnel_session_id` issued on `rc:tunnel.open`. The tunnel WS
@@ -53,12 +73,37 @@ pub struct AppState {
     pub tenants: Arc<TenantDao>,
     pub rooms: Arc<RoomDao>,
     pub invites: Arc<InviteDao>,
+    /// Email domains tenants claim for invite-free joining (see
+    /// `routes::tenant_domain`).
+    pub tenant_domains: Arc<TenantDomainDao>,
+    /// Per-tenant feature flag settings (see `extractors::feature`).
+    pub feature_flags: Arc<FeatureFlagDao>,
     pub messages: Arc<MessageDao>,
+    pub scheduled_messages: Arc<ScheduledMessageDao>,
+    pub webhooks: Arc<WebhookDao>,
+    pub slash_commands: Arc<SlashCommandDao>,
+    pub bot_tokens: Arc<BotTokenDao>,
+    pub calendar_connections: Arc<CalendarConnectionDao>,
+    /// Shared client for outgoing webhooks and slash commands, with a short
+    /// timeout so a slow integration can't hold a request open.
+    pub integrations_http: reqwest::Client,
     pub notifications: Arc<NotificationDao>,
     pub reactions: Arc<ReactionDao>,
+    pub custom_emojis: Arc<CustomEmojiDao>,
     pub roles: Arc<RoleDao>,
     pub files: Arc<FileDao>,
     pub recordings: Arc<RecordingDao>,
+    pub recording_consents: Arc<RecordingConsentDao>,
+    pub call_sessions: Arc<CallSessionDao>,
+    pub call_debug_events: Arc<CallDebugEventDao>,
+    /// Media signaling timeline of each call (see `ws::call_debug`).
+    pub call_debug: CallDebugLog,
+    pub usage: Arc<UsageDao>,
+    pub call_polls: Arc<CallPollDao>,
+    pub call_questions: Arc<CallQuestionDao>,
+    pub whiteboards: Arc<WhiteboardDao>,
+    pub audit_logs: Arc<AuditLogDao>,
+    pub permissions: Arc<PermissionService>,
 
     pub tasks: Arc<TaskService>,
     pub room_manager: Arc<RoomManager>,
@@ -67,9 +112,69 @@ pub struct AppState {
     pub oauth: Option<Arc<OAuthService>>,
     pub giphy: Option<Arc<GiphyService>>,
     pub email: Option<Arc<EmailService>>,
+    /// Antivirus scanner for uploads; `None` stores them unscanned.
+    pub scanner: Option<ScanService>,
+    /// Page previews of PDF and Word uploads; `None` skips them.
+    pub previews: Option<PreviewService>,
+    /// Text-to-speech for `media:speak`; `None` turns it off.
+    pub tts: Option<Arc<dyn SpeechSynthesizer>>,
+    /// Language model of the meeting assistant; `None` turns it off.
+    pub assistant: Option<Arc<dyn AssistantModel>>,
+    /// Google/Microsoft calendar connectors for scheduled calls; `None`
+    /// when neither OAuth client is configured.
+    pub calendar: Option<Arc<CalendarService>>,
+    /// DNS lookups for domain verification; `None` when the host's
+    /// resolver configuration could not be read.
+    pub domain_verifier: Option<DomainVerifier>,
     pub push: Option<Arc<PushService>>,
     pub push_subscriptions: Arc<PushSubscriptionDao>,
     pub redis_pubsub: Option<Arc<RedisPubSub>>,
+    /// Which pod hosts each conference's Router. `None` when
+    /// `app.instance_url` is unset (single-pod) or Redis is unreachable.
+    pub conference_registry: Option<Arc<ConferenceRegistry>>,
+    /// The internal control plane to and from other pods; `None` unless
+    /// `control.*` is configured.
+    pub control: Option<Arc<crate::control::Control>>,
+    /// Edge Routers on this pod, by room, with the base URL of the pod
+    /// hosting the call (see `ws::edge`).
+    pub edges: Arc<DashMap<ObjectId, String>>,
+    /// Per-region counts of `media:join`s pinned to each TURN region.
+    pub turn_region_stats: Arc<TurnRegionStats>,
+    /// Connections this pod dropped for missing pongs or idling.
+    pub ws_stats: Arc<WsHealthStats>,
+    /// mediasoup worker watchdog behind `/health/live` (see
+    /// `routes::health`).
+    pub watchdog: Arc<Watchdog>,
+    /// Presence subscriptions, leases and typing indicators (see
+    /// `ws::presence`).
+    pub presence: Arc<PresenceHub>,
+    /// Recent room events for `sync` replay (see `ws::event_log`).
+    pub event_log: Arc<EventLog>,
+    /// Whiteboards in use, by room id (see `ws::whiteboard`).
+    pub live_whiteboards: Arc<DashMap<ObjectId, crate::ws::whiteboard::LiveBoard>>,
+    /// Calls still ringing members, by room id (see `ws::ring`).
+    pub rings: Arc<DashMap<ObjectId, crate::ws::ring::Ring>>,
+    /// Shared audio playing in each call (see `ws::playback`).
+    pub playbacks: Arc<DashMap<ObjectId, crate::ws::playback::Playback>>,
+    /// Text-to-speech announcements playing in each call (see
+    /// `ws::speech`).
+    pub speeches: Arc<DashMap<ObjectId, crate::ws::speech::Speech>>,
+    /// Redeemed WebSocket tickets by `jti`, with their expiry (see
+    /// `ws::ticket`).
+    pub ws_tickets: Arc<DashMap<String, i64>>,
+    /// Per-user / per-tenant / per-IP token buckets (see `middleware::rate_limit`).
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Tenants' plans for the limit checks (see `middleware::plan_limits`).
+    pub plan_cache: Arc<PlanCache>,
+    /// Verified Stripe webhook events, deduplicated and queued for the
+    /// billing worker (see `routes::stripe`).
+    pub stripe_events: Arc<StripeEventDao>,
+    /// TURN servers, rate limits and feature switches in effect now; read
+    /// these here rather than from `settings` (see `runtime`).
+    pub runtime: Arc<Runtime>,
+    pub settings_overrides: Arc<SettingsOverrideDao>,
+    /// Wakes the billing worker when a new event is queued.
+    pub stripe_wakeup: Arc<tokio::sync::Notify>,
 
     // Remote-control subsystem
     pub agents: Arc<AgentDao>,
@@ -135,22 +240,58 @@ pub struct AppState {
 
 impl AppState {
     pub async fn new(db: Database, settings: Settings) -> anyhow::Result<Self> {
+        base::set_tenant_audit(base::TenantAudit::parse(&settings.database.tenant_audit));
         let auth = Arc::new(AuthService::new(settings.jwt.clone()));
         let users = Arc::new(UserDao::new(&db));
         let activation_codes = Arc::new(ActivationCodeDao::new(&db));
         let tenants = Arc::new(TenantDao::new(&db));
         let rooms = Arc::new(RoomDao::new(&db));
         let invites = Arc::new(InviteDao::new(&db));
+        let tenant_domains = Arc::new(TenantDomainDao::new(&db));
+        let feature_flags = Arc::new(FeatureFlagDao::new(&db));
         let messages = Arc::new(MessageDao::new(&db));
+        let scheduled_messages = Arc::new(ScheduledMessageDao::new(&db));
+        let webhooks = Arc::new(WebhookDao::new(&db));
+        let slash_commands = Arc::new(SlashCommandDao::new(&db));
+        let bot_tokens = Arc::new(BotTokenDao::new(&db));
+        let calendar_connections = Arc::new(CalendarConnectionDao::new(&db));
+        let integrations_http =
+            crate::integrations_http::client(settings.integrations.allow_private_networks)?;
         let notifications = Arc::new(NotificationDao::new(&db));
         let reactions = Arc::new(ReactionDao::new(&db));
+        let custom_emojis = Arc::new(CustomEmojiDao::new(&db));
         let roles = Arc::new(RoleDao::new(&db));
         let files = Arc::new(FileDao::new(&db));
         let recordings = Arc::new(RecordingDao::new(&db));
+        let recording_consents = Arc::new(RecordingConsentDao::new(&db));
+        let call_sessions = Arc::new(CallSessionDao::new(&db));
+        let usage = Arc::new(UsageDao::new(&db));
+        let stripe_events = Arc::new(StripeEventDao::new(&db));
+        let settings_overrides = Arc::new(SettingsOverrideDao::new(&db));
+        let runtime = Arc::new(Runtime::new(RuntimeSettings::from_settings(&settings)));
+        match settings_overrides.get().await {
+            Ok(Some(stored)) => {
+                runtime.reload(RuntimeSettings::from_settings(&settings), stored.to_map())
+            }
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Failed to load settings overrides: {}", e),
+        }
+        let call_polls = Arc::new(CallPollDao::new(&db));
+        let call_questions = Arc::new(CallQuestionDao::new(&db));
+        let whiteboards = Arc::new(WhiteboardDao::new(&db));
+        let audit_logs = Arc::new(AuditLogDao::new(&db));
+        let permissions = Arc::new(PermissionService::new(tenants.clone(), rooms.clone()));
         let tasks = Arc::new(TaskService::new(&db));
 
-        let worker_pool = Arc::new(WorkerPool::new(&settings.mediasoup).await?);
-        let room_manager = Arc::new(RoomManager::new(worker_pool, &settings.mediasoup));
+        let room_manager = Arc::new(RoomManager::new(
+            backend::from_settings(&settings.mediasoup).await?,
+        ));
+        let call_debug_events = Arc::new(CallDebugEventDao::new(&db));
+        let call_debug = CallDebugLog::spawn(rooms.clone(), call_debug_events.clone());
+        room_manager.observe_transport_states({
+            let call_debug = call_debug.clone();
+            move |change| call_debug.transport_state(change)
+        });
 
         let ws_storage = Arc::new(WsStorage::new());
         let recognition = RecognitionService::new(
@@ -164,6 +305,7 @@ impl AppState {
             || !settings.oauth.github.client_id.is_empty()
             || !settings.oauth.linkedin.client_id.is_empty()
             || !settings.oauth.microsoft.client_id.is_empty()
+            || !settings.oauth.custom.client_id.is_empty()
         {
             Some(Arc::new(OAuthService::new(settings.oauth.clone())))
         } else {
@@ -174,6 +316,23 @@ impl AppState {
         // (prod), SMTP when `email.smtp_host` + `email.smtp_port` are
         // set (e2e Mailpit), or returns None otherwise (dev / no email).
         let email = EmailService::from_settings(&settings.email).map(Arc::new);
+        let scanner = ScanService::from_settings(&settings.scan);
+        let previews = PreviewService::from_settings(&settings.preview);
+        let tts = roomler_ai_services::tts::from_settings(&settings.tts);
+        let assistant =
+            roomler_ai_services::assistant::from_settings(&settings.assistant, &recognition);
+        let calendar =
+            CalendarService::from_settings(&settings.calendar, &settings.oauth).map(Arc::new);
+        let domain_verifier = match DomainVerifier::from_system() {
+            Ok(verifier) => Some(verifier),
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to initialize DNS resolver: {} — domain verification disabled",
+                    e
+                );
+                None
+            }
+        };
 
         let push_subscriptions = Arc::new(PushSubscriptionDao::new(&db));
         let push = if !settings.push.vapid_private_key.is_empty() {
@@ -191,6 +350,8 @@ impl AppState {
             None
         };
 
+        let control = crate::control::Control::from_settings(&settings.control).map(Arc::new);
+
         let redis_pubsub = match RedisPubSub::new(&settings.redis.url).await {
             Ok(ps) => Some(Arc::new(ps)),
             Err(e) => {
@@ -202,6 +363,25 @@ impl AppState {
             }
         };
 
+        let conference_registry = match &settings.app.instance_url {
+            Some(instance_url) => {
+                match ConferenceRegistry::new(&settings.redis.url, instance_url.clone()).await {
+                    Ok(registry) => {
+                        registry.spawn_refresher(room_manager.clone());
+                        Some(Arc::new(registry))
+                    }
+                    Err(e) => {
+                        tracing::warn!(
+                            "Failed to initialize conference registry: {} — media:join redirects disabled",
+                            e
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
         let giphy = if !settings.giphy.api_key.is_empty() {
             Some(Arc::new(GiphyService::new(settings.giphy.api_key.clone())))
         } else {
@@ -253,7 +433,7 @@ impl AppState {
         let overlay_networks = Arc::new(OverlayNetworkDao::new(&db));
         let overlay_nodes = Arc::new(OverlayNodeDao::new(&db));
 
-        Ok(Self {
+        let state = Self {
             db,
             settings,
             auth,
@@ -262,12 +442,31 @@ impl AppState {
             tenants,
             rooms,
             invites,
+            tenant_domains,
+            feature_flags,
             messages,
+            scheduled_messages,
+            webhooks,
+            slash_commands,
+            bot_tokens,
+            calendar_connections,
+            integrations_http,
             notifications,
             reactions,
+            custom_emojis,
             roles,
             files,
             recordings,
+            recording_consents,
+            call_sessions,
+            call_debug_events,
+            call_debug,
+            usage,
+            call_polls,
+            call_questions,
+            whiteboards,
+            audit_logs,
+            permissions,
 
             tasks,
             room_manager,
@@ -276,9 +475,34 @@ impl AppState {
             oauth,
             giphy,
             email,
+            scanner,
+            previews,
+            tts,
+            assistant,
+            calendar,
+            domain_verifier,
             push,
             push_subscriptions,
             redis_pubsub,
+            conference_registry,
+            control,
+            edges: Arc::new(DashMap::new()),
+            turn_region_stats: Arc::new(TurnRegionStats::default()),
+            ws_stats: Arc::new(WsHealthStats::default()),
+            watchdog: Arc::new(Watchdog::default()),
+            presence: Arc::new(PresenceHub::default()),
+            event_log: Arc::new(EventLog::default()),
+            live_whiteboards: Arc::new(DashMap::new()),
+            rings: Arc::new(DashMap::new()),
+            playbacks: Arc::new(DashMap::new()),
+            speeches: Arc::new(DashMap::new()),
+            ws_tickets: Arc::new(DashMap::new()),
+            rate_limiter: Arc::new(RateLimiter::default()),
+            plan_cache: Arc::new(PlanCache::default()),
+            stripe_events,
+            runtime,
+            settings_overrides,
+            stripe_wakeup: Arc::new(tokio::sync::Notify::new()),
             agents,
             remote_sessions,
             remote_audit,
@@ -299,7 +523,23 @@ impl AppState {
             latest_release_cache: crate::routes::agent_release::LatestReleaseCache::new(),
             tunnel_release_cache: crate::routes::tunnel_release::LatestTunnelReleaseCache::new(),
             setup_release_cache: crate::routes::setup_release::LatestSetupReleaseCache::new(),
-        })
+        };
+        crate::routes::scheduled_message::spawn_scheduler(state.clone());
+        crate::ws::whiteboard::spawn_snapshotter(state.clone());
+        crate::ws::bandwidth::spawn_downlink_policy(state.clone());
+        crate::ws::quality::spawn_reporter(state.clone());
+        crate::ws::webinar::spawn_counter(state.clone());
+        crate::ws::playback::spawn_ducking(state.clone());
+        crate::ws::presence::spawn_expiry(state.clone());
+        crate::routes::retention::spawn_reaper(state.clone());
+        crate::routes::retention::spawn_archiver(state.clone());
+        crate::routes::usage::spawn_meter(state.clone());
+        crate::routes::call_schedule::spawn_calendar_sync(state.clone());
+        crate::routes::stripe::spawn_event_worker(state.clone());
+        crate::ws::turn_regions::spawn_health_checks(state.clone());
+        crate::routes::health::spawn_watchdog(state.clone());
+        crate::runtime::spawn_reloader(state.clone());
+        Ok(state)
     }
 }
 
@@ -398,7 +638,11 @@ fn spawn_consent_consumer(mut rx: mpsc::Receiver<ConsentEvent>, deps: ConsentCon
 async fn handle_consent_event(deps: &ConsentConsumerDeps, ev: &ConsentEvent) -> anyhow::Result<()> {
     // Resolve the device owner + display name (the Hub is DB-agnostic, so it
     // only knows the agent_id).
-    let agent = deps.agents.base.find_by_id(ev.agent_id).await?;
+    let agent = deps
+        .agents
+        .base
+        .find_by_id_in_tenant(ev.tenant_id, ev.agent_id)
+        .await?;
     let owner_id = agent.owner_user_id;
     let device_name = agent.name.clone();
 
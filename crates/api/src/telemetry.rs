@@ -0,0 +1,219 @@
+//! Logging and OpenTelemetry tracing.
+//!
+//! Every HTTP request, WebSocket message and media operation runs in a
+//! `tracing` span. With `telemetry.otlp_endpoint` set, those spans are also
+//! exported over OTLP, so a slow `media:consume` shows up as one trace with
+//! the WebSocket message, the RoomManager call and the mediasoup worker that
+//! served it.
+//!
+//! Traces cross process boundaries in W3C Trace Context: an HTTP request's
+//! `traceparent` header, or a `traceparent` field next to `type` in a
+//! WebSocket message, makes the server's span a child of the caller's.
+
+use axum::http::{HeaderMap, Request};
+use opentelemetry::propagation::{Extractor, TextMapPropagator};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{Sampler, SdkTracerProvider};
+use roomler_ai_config::TelemetrySettings;
+use tracing::{Span, field};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+
+const DEFAULT_FILTER: &str =
+    "roomler_ai_api=debug,roomler_ai_services=debug,roomler_ai_db=debug,tower_http=debug";
+
+/// Keeps the trace exporter running; [`Telemetry::shutdown`] flushes it.
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Telemetry {
+    /// Send the spans still buffered. Call before the process exits.
+    pub fn shutdown(self) {
+        if let Some(provider) = self.provider
+            && let Err(e) = provider.shutdown()
+        {
+            tracing::warn!(error = %e, "Failed to flush traces");
+        }
+    }
+}
+
+/// Install the global subscriber: `RUST_LOG` (or the default filter), the
+/// console log, and the OTLP exporter when configured.
+pub fn init(settings: &TelemetrySettings) -> anyhow::Result<Telemetry> {
+    let provider = if settings.otlp_endpoint.is_empty() {
+        None
+    } else {
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(&settings.otlp_endpoint)
+            .build()?;
+        Some(
+            SdkTracerProvider::builder()
+                .with_batch_exporter(exporter)
+                .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                    settings.sample_ratio.clamp(0.0, 1.0),
+                ))))
+                .with_resource(
+                    Resource::builder()
+                        .with_service_name(settings.service_name.clone())
+                        .build(),
+                )
+                .build(),
+        )
+    };
+    let otel = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer("roomler-ai-api"))
+    });
+
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| DEFAULT_FILTER.into()),
+        )
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel)
+        .init();
+
+    if provider.is_some() {
+        tracing::info!(endpoint = %settings.otlp_endpoint, "Exporting traces over OTLP");
+    }
+    Ok(Telemetry { provider })
+}
+
+/// The span of an HTTP request (for `TraceLayer::make_span_with`),
+/// continuing the caller's trace if it sent a `traceparent`. Only the path
+/// is recorded: query strings may carry tickets.
+pub fn http_span<B>(request: &Request<B>) -> Span {
+    let span = tracing::info_span!(
+        "request",
+        otel.name = %format!("{} {}", request.method(), request.uri().path()),
+        otel.kind = "server",
+        method = %request.method(),
+        path = %request.uri().path(),
+        version = ?request.version(),
+    );
+    continue_trace(&span, &HeaderExtractor(request.headers()));
+    span
+}
+
+/// The span of one incoming WebSocket message, continuing the client's
+/// trace if the message carries a `traceparent`. Its type is recorded once
+/// the message is parsed; see [`record_ws_message`].
+pub fn ws_span(user_id: &bson::oid::ObjectId, connection_id: &str, text: &str) -> Span {
+    let span = tracing::info_span!(
+        "ws.message",
+        otel.name = "ws",
+        otel.kind = "server",
+        msg_type = field::Empty,
+        %user_id,
+        %connection_id,
+    );
+    // The parent has to be set before the span is entered, so only messages
+    // that mention a `traceparent` are parsed for it here.
+    if text.contains("\"traceparent\"")
+        && let Ok(Traced {
+            traceparent: Some(traceparent),
+        }) = serde_json::from_str::<Traced>(text)
+    {
+        continue_trace(&span, &JsonExtractor(&traceparent));
+    }
+    span
+}
+
+/// Name the current WebSocket message span after the message type.
+pub fn record_ws_message(msg_type: &str) {
+    let span = Span::current();
+    span.record("otel.name", format!("ws {}", msg_type));
+    span.record("msg_type", msg_type);
+}
+
+#[derive(serde::Deserialize)]
+struct Traced {
+    traceparent: Option<String>,
+}
+
+fn continue_trace(span: &Span, carrier: &dyn Extractor) {
+    let parent = TraceContextPropagator::new().extract(carrier);
+    // Without a valid `traceparent` the extracted context is empty and
+    // the span stays a root.
+    let _ = span.set_parent(parent);
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|v| v.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|k| k.as_str()).collect()
+    }
+}
+
+/// A WebSocket message's `traceparent` field.
+struct JsonExtractor<'a>(&'a str);
+
+impl Extractor for JsonExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        (key == "traceparent").then_some(self.0)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        vec!["traceparent"]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::TraceContextExt;
+
+    const TRACE_ID: &str = "4bf92f3577b34da6a3ce929d0e0e4736";
+
+    fn traced<T>(f: impl FnOnce() -> T) -> T {
+        let provider = SdkTracerProvider::builder().build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        tracing::subscriber::with_default(subscriber, f)
+    }
+
+    fn trace_id(span: &Span) -> String {
+        span.context().span().span_context().trace_id().to_string()
+    }
+
+    #[test]
+    fn http_requests_continue_the_callers_trace() {
+        traced(|| {
+            let request = Request::get("/api/tenant?ticket=secret")
+                .header(
+                    "traceparent",
+                    format!("00-{}-00f067aa0ba902b7-01", TRACE_ID),
+                )
+                .body(())
+                .unwrap();
+            assert_eq!(trace_id(&http_span(&request)), TRACE_ID);
+
+            let untraced = Request::get("/api/tenant").body(()).unwrap();
+            assert_ne!(trace_id(&http_span(&untraced)), TRACE_ID);
+        });
+    }
+
+    #[test]
+    fn ws_messages_continue_the_callers_trace() {
+        traced(|| {
+            let text = serde_json::json!({
+                "type": "media:consume",
+                "traceparent": format!("00-{}-00f067aa0ba902b7-01", TRACE_ID),
+            })
+            .to_string();
+            let span = ws_span(&bson::oid::ObjectId::new(), "conn", &text);
+            span.in_scope(|| record_ws_message("media:consume"));
+            assert_eq!(trace_id(&span), TRACE_ID);
+        });
+    }
+}
@@ -0,0 +1,126 @@
+//! Server-enforced audio: organizer mute and push-to-talk.
+//!
+//! The SFU pauses a connection's audio producers while it is silenced, so a
+//! client can't talk past a mute by recreating its producer: the state lives
+//! on the connection's media (see `AudioState`), and a user muted by an
+//! organizer stays muted on connections they join with later. In a
+//! push-to-talk room audio only flows while the client holds
+//! `media:ptt_active { room_id, active: true }`.
+//!
+//! Every change goes to all connections in the media room as
+//! `media:audio_state { room_id, user_id, connection_id, force_muted,
+//! ptt_active, silenced }`; turning push-to-talk on or off as
+//! `media:push_to_talk { room_id, enabled }`. Joiners get the room's mode and
+//! everyone's non-default state right after the existing producers.
+
+use bson::oid::ObjectId;
+use roomler_ai_services::media::room_manager::AudioState;
+use tracing::debug;
+
+use crate::state::AppState;
+
+fn event(
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    connection_id: &str,
+    audio: &AudioState,
+    push_to_talk: bool,
+) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:audio_state",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "force_muted": audio.force_muted,
+            "ptt_active": audio.ptt_active,
+            "silenced": audio.silenced(push_to_talk),
+        }
+    })
+}
+
+/// Send `msg` to every connection in the media room.
+async fn send_to_room(state: &AppState, room_id: &ObjectId, msg: &serde_json::Value) {
+    for (_, conn_id, _) in state.room_manager.audio_states(room_id) {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, msg).await;
+    }
+}
+
+/// Tell the room about a user's connections after an organizer (un)muted
+/// them.
+pub async fn announce_user(
+    state: &AppState,
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    states: &[(String, AudioState)],
+) {
+    let push_to_talk = state.room_manager.is_push_to_talk(room_id);
+    for (conn_id, audio) in states {
+        let msg = event(room_id, user_id, conn_id, audio, push_to_talk);
+        send_to_room(state, room_id, &msg).await;
+    }
+}
+
+/// Tell the room push-to-talk was turned on or off.
+pub async fn announce_push_to_talk(state: &AppState, room_id: &ObjectId, enabled: bool) {
+    let msg = serde_json::json!({
+        "type": "media:push_to_talk",
+        "data": { "room_id": room_id.to_hex(), "enabled": enabled }
+    });
+    send_to_room(state, room_id, &msg).await;
+}
+
+/// `media:ptt_active`: the client pressed or released push-to-talk.
+pub async fn handle_ptt_active(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = data
+        .get("room_id")
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        return;
+    };
+    let active = data
+        .get("active")
+        .and_then(|a| a.as_bool())
+        .unwrap_or(false);
+    let Some(audio) = state
+        .room_manager
+        .set_ptt_active(&rid, connection_id, active)
+        .await
+    else {
+        return;
+    };
+    debug!(?rid, %connection_id, active, "Push-to-talk state updated");
+
+    let push_to_talk = state.room_manager.is_push_to_talk(&rid);
+    let msg = event(&rid, user_id, connection_id, &audio, push_to_talk);
+    send_to_room(state, &rid, &msg).await;
+}
+
+/// Send a joining connection the room's mode and the audio state of
+/// everyone, itself included, that isn't the default.
+pub async fn replay_to(state: &AppState, room_id: &ObjectId, connection_id: &str) {
+    let push_to_talk = state.room_manager.is_push_to_talk(room_id);
+    if push_to_talk {
+        let msg = serde_json::json!({
+            "type": "media:push_to_talk",
+            "data": { "room_id": room_id.to_hex(), "enabled": true }
+        });
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    }
+    for (uid, conn_id, audio) in state.room_manager.audio_states(room_id) {
+        if audio == AudioState::default() {
+            continue;
+        }
+        let msg = event(room_id, &uid, &conn_id, &audio, push_to_talk);
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    }
+}
@@ -0,0 +1,46 @@
+//! Server-side downlink adaptation.
+//!
+//! Every [`POLICY_INTERVAL`] the room manager compares each participant's
+//! transport-cc downlink estimate with the video it consumes and pauses the
+//! lowest-priority streams (cameras before screen shares) or resumes them
+//! once the estimate recovers. The consuming connection is told with
+//! `media:consumer_paused` / `media:consumer_resumed { room_id, consumer_id,
+//! reason: "bandwidth" }` so it can show a placeholder instead of a frozen
+//! frame.
+
+use std::time::Duration;
+
+use crate::state::AppState;
+
+const POLICY_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Spawn the loop that applies the downlink policy to every live room.
+pub(crate) fn spawn_downlink_policy(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(POLICY_INTERVAL);
+        loop {
+            tick.tick().await;
+            for toggle in state.room_manager.adapt_downlinks().await {
+                let msg_type = if toggle.paused {
+                    "media:consumer_paused"
+                } else {
+                    "media:consumer_resumed"
+                };
+                let msg = serde_json::json!({
+                    "type": msg_type,
+                    "data": {
+                        "room_id": toggle.room_id.to_hex(),
+                        "consumer_id": toggle.consumer_id,
+                        "reason": "bandwidth",
+                    }
+                });
+                super::dispatcher::send_to_connection(
+                    &state.ws_storage,
+                    &toggle.connection_id,
+                    &msg,
+                )
+                .await;
+            }
+        }
+    });
+}
@@ -0,0 +1,170 @@
+//! Media signaling timeline of each call, for debugging a participant's
+//! failed call after the fact.
+//!
+//! Joins, transport connects, ICE restarts, produce/consume, ICE and DTLS
+//! state changes, rejoins, leaves and signaling errors are recorded per
+//! connection in `call_debug_events` (kept for 3 days) and served to tenant
+//! admins by `GET /api/tenant/{tenant_id}/room/{room_id}/call/debug`.
+//!
+//! Recording must never hold up signaling: events go through a bounded
+//! channel to a background flusher, which looks up each room's tenant and
+//! writes them in batches. When the channel is full the event is dropped.
+//! Only rooms are recorded; events of breakout rooms and device tests, which
+//! have no room document, are dropped by the flusher.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use bson::{DateTime, oid::ObjectId};
+use roomler_ai_db::models::{CallDebugEvent, CallDebugKind};
+use roomler_ai_services::dao::{call_debug_event::CallDebugEventDao, room::RoomDao};
+use roomler_ai_services::media::room_manager::{TransportLayer, TransportStateChange};
+use tokio::sync::mpsc;
+use tracing::{error, warn};
+
+const BUFFER: usize = 4096;
+const BATCH: usize = 64;
+/// Room -> tenant lookups kept by the flusher before it starts over.
+const TENANT_CACHE: usize = 1024;
+
+struct Pending {
+    room_id: ObjectId,
+    user_id: ObjectId,
+    connection_id: String,
+    kind: CallDebugKind,
+    detail: String,
+    at: DateTime,
+}
+
+#[derive(Clone)]
+pub struct CallDebugLog {
+    tx: mpsc::Sender<Pending>,
+}
+
+impl CallDebugLog {
+    /// Spawns the background flusher.
+    pub fn spawn(rooms: Arc<RoomDao>, events: Arc<CallDebugEventDao>) -> Self {
+        let (tx, mut rx) = mpsc::channel::<Pending>(BUFFER);
+
+        tokio::spawn(async move {
+            let mut tenants = HashMap::new();
+            let mut buf = Vec::with_capacity(BATCH);
+            loop {
+                let timeout = tokio::time::sleep(Duration::from_millis(200));
+                tokio::pin!(timeout);
+
+                tokio::select! {
+                    maybe = rx.recv() => match maybe {
+                        Some(ev) => {
+                            buf.push(ev);
+                            while buf.len() < BATCH
+                                && let Ok(more) = rx.try_recv()
+                            {
+                                buf.push(more);
+                            }
+                            if buf.len() >= BATCH {
+                                flush(&rooms, &events, &mut tenants, &mut buf).await;
+                            }
+                        }
+                        None => {
+                            flush(&rooms, &events, &mut tenants, &mut buf).await;
+                            break;
+                        }
+                    },
+                    _ = &mut timeout => {
+                        flush(&rooms, &events, &mut tenants, &mut buf).await;
+                    }
+                }
+            }
+        });
+
+        Self { tx }
+    }
+
+    pub fn record(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        connection_id: &str,
+        kind: CallDebugKind,
+        detail: impl Into<String>,
+    ) {
+        let ev = Pending {
+            room_id,
+            user_id,
+            connection_id: connection_id.to_string(),
+            kind,
+            detail: detail.into(),
+            at: DateTime::now(),
+        };
+        if let Err(e) = self.tx.try_send(ev) {
+            warn!("call debug channel full, dropping event: {e}");
+        }
+    }
+
+    /// Record a transport state change from
+    /// [`RoomManager::observe_transport_states`](roomler_ai_services::media::room_manager::RoomManager::observe_transport_states).
+    pub fn transport_state(&self, change: TransportStateChange) {
+        let kind = match change.layer {
+            TransportLayer::Ice => CallDebugKind::IceState,
+            TransportLayer::Dtls => CallDebugKind::DtlsState,
+        };
+        self.record(
+            change.room_id,
+            change.user_id,
+            &change.connection_id,
+            kind,
+            format!("{}: {}", change.direction, change.state),
+        );
+    }
+}
+
+async fn flush(
+    rooms: &RoomDao,
+    events: &CallDebugEventDao,
+    tenants: &mut HashMap<ObjectId, Option<ObjectId>>,
+    buf: &mut Vec<Pending>,
+) {
+    if buf.is_empty() {
+        return;
+    }
+    if tenants.len() > TENANT_CACHE {
+        tenants.clear();
+    }
+    let mut batch = Vec::with_capacity(buf.len());
+    for ev in buf.drain(..) {
+        let tenant_id = match tenants.get(&ev.room_id) {
+            Some(tenant_id) => *tenant_id,
+            None => {
+                let tenant_id = rooms
+                    .base
+                    .find_by_id_unscoped(ev.room_id)
+                    .await
+                    .ok()
+                    .map(|room| room.tenant_id);
+                tenants.insert(ev.room_id, tenant_id);
+                tenant_id
+            }
+        };
+        let Some(tenant_id) = tenant_id else {
+            continue;
+        };
+        batch.push(CallDebugEvent {
+            id: None,
+            tenant_id,
+            room_id: ev.room_id,
+            user_id: ev.user_id,
+            connection_id: ev.connection_id,
+            kind: ev.kind,
+            detail: ev.detail,
+            at: ev.at,
+        });
+    }
+    if batch.is_empty() {
+        return;
+    }
+    if let Err(e) = events.insert_many(batch).await {
+        error!("call debug insert_many failed: {e}");
+    }
+}
@@ -0,0 +1,156 @@
+use bson::oid::ObjectId;
+use redis::aio::ConnectionManager;
+use tracing::{debug, info};
+
+const KEY_PREFIX: &str = "roomler:conference:owner:";
+
+/// How long an ownership claim survives without a refresh. The refresher
+/// spawned by [`ConferenceRegistry::spawn_refresher`] re-arms every claim at a
+/// third of this, so a pod that dies leaves its conferences claimable again
+/// within one TTL.
+pub const OWNER_TTL_SECS: u64 = 30;
+
+/// Releases a claim only if it still belongs to the caller, so a pod that lost
+/// its claim (TTL lapse + another pod re-claimed) can't delete the new owner's
+/// key on its way out.
+const RELEASE_SCRIPT: &str = r#"
+if redis.call("GET", KEYS[1]) == ARGV[1] then
+    return redis.call("DEL", KEYS[1])
+end
+return 0
+"#;
+
+/// Result of [`ConferenceRegistry::claim`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Ownership {
+    /// This pod owns (or just claimed) the conference's Router.
+    Local,
+    /// Another pod owns it; signaling must go to this base URL.
+    Remote(String),
+}
+
+/// Redis-backed registry of which API pod hosts each conference's mediasoup
+/// Router.
+///
+/// mediasoup state is process-local, so with N pods behind a load balancer a
+/// `media:join` that lands on a pod without the Router can't be served. The
+/// pod that runs `call:start` claims `roomler:conference:owner:{room_id}` with
+/// its own `app.instance_url`; every other pod answers `media:join` for that
+/// room with `media:redirect { url }` so the client reconnects its WS there.
+#[derive(Clone)]
+pub struct ConferenceRegistry {
+    conn: ConnectionManager,
+    instance_url: String,
+}
+
+impl ConferenceRegistry {
+    pub async fn new(redis_url: &str, instance_url: String) -> Result<Self, redis::RedisError> {
+        let client = redis::Client::open(redis_url)?;
+        let conn = ConnectionManager::new(client).await?;
+        info!(%instance_url, "Conference registry connected");
+        Ok(Self { conn, instance_url })
+    }
+
+    pub fn instance_url(&self) -> &str {
+        &self.instance_url
+    }
+
+    fn key(room_id: &ObjectId) -> String {
+        format!("{KEY_PREFIX}{}", room_id.to_hex())
+    }
+
+    /// Claim the conference for this pod, or report the pod that already owns
+    /// it. Claiming is `SET NX EX`, so two pods racing on `call:start` resolve
+    /// to exactly one owner.
+    pub async fn claim(&self, room_id: &ObjectId) -> Result<Ownership, redis::RedisError> {
+        let mut conn = self.conn.clone();
+        let key = Self::key(room_id);
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg(&self.instance_url)
+            .arg("NX")
+            .arg("EX")
+            .arg(OWNER_TTL_SECS)
+            .query_async(&mut conn)
+            .await?;
+        if claimed.is_some() {
+            debug!(%room_id, "conference ownership claimed");
+            return Ok(Ownership::Local);
+        }
+        match self.owner(room_id).await? {
+            // Key expired between SET NX and GET — retrying would recurse
+            // without bound under churn, so treat the gap as "ours" and let
+            // the refresher re-arm it.
+            None => Ok(Ownership::Local),
+            Some(url) if url == self.instance_url => Ok(Ownership::Local),
+            Some(url) => Ok(Ownership::Remote(url)),
+        }
+    }
+
+    /// The owning pod's base URL, if any pod currently holds the conference.
+    pub async fn owner(&self, room_id: &ObjectId) -> Result<Option<String>, redis::RedisError> {
+        let mut conn = self.conn.clone();
+        redis::cmd("GET")
+            .arg(Self::key(room_id))
+            .query_async(&mut conn)
+            .await
+    }
+
+    /// Drop this pod's claim (call ended / Router removed). A claim held by a
+    /// different pod is left untouched.
+    pub async fn release(&self, room_id: &ObjectId) -> Result<(), redis::RedisError> {
+        let mut conn = self.conn.clone();
+        redis::Script::new(RELEASE_SCRIPT)
+            .key(Self::key(room_id))
+            .arg(&self.instance_url)
+            .invoke_async::<i64>(&mut conn)
+            .await?;
+        debug!(%room_id, "conference ownership released");
+        Ok(())
+    }
+
+    /// Re-arm the TTL on every claim for the given rooms. Claims that were
+    /// lost in the meantime are re-taken when still free.
+    pub async fn refresh(&self, room_ids: &[ObjectId]) -> Result<(), redis::RedisError> {
+        for room_id in room_ids {
+            if let Ownership::Local = self.claim(room_id).await? {
+                let mut conn = self.conn.clone();
+                redis::cmd("EXPIRE")
+                    .arg(Self::key(room_id))
+                    .arg(OWNER_TTL_SECS)
+                    .query_async::<()>(&mut conn)
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Spawn the process-lifetime task that keeps this pod's claims alive for
+    /// as long as the Routers exist in `room_manager`.
+    pub fn spawn_refresher(
+        &self,
+        room_manager: std::sync::Arc<roomler_ai_services::media::room_manager::RoomManager>,
+    ) {
+        let registry = self.clone();
+        tokio::spawn(async move {
+            let mut tick =
+                tokio::time::interval(std::time::Duration::from_secs(OWNER_TTL_SECS / 3));
+            loop {
+                tick.tick().await;
+                // An edge Router relays another pod's conference; it never
+                // claims one
+                let rooms: Vec<ObjectId> = room_manager
+                    .room_ids()
+                    .into_iter()
+                    .filter(|id| !room_manager.is_edge(id))
+                    .collect();
+                if rooms.is_empty() {
+                    continue;
+                }
+                if let Err(e) = registry.refresh(&rooms).await {
+                    tracing::warn!(%e, "conference ownership refresh failed");
+                }
+            }
+        });
+    }
+}
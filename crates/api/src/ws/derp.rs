@@ -91,7 +91,8 @@ pub async fn derp_upgrade(
     Query(params): Query<WsParams>,
     ws: WebSocketUpgrade,
 ) -> Response {
-    let claims = match state.auth.verify_agent_token(&params.token) {
+    let token = params.token.unwrap_or_default();
+    let claims = match state.auth.verify_agent_token(&token) {
         Ok(c) => c,
         Err(_) => {
             return Response::builder()
@@ -1,26 +1,30 @@
 use axum::extract::ws::Message;
 use bson::oid::ObjectId;
-use futures::SinkExt;
 use std::sync::Arc;
-use tracing::{debug, warn};
 
 use super::redis_pubsub::RedisPubSub;
 use super::storage::WsStorage;
 
+/// The tenant an event belongs to: its top-level `tenant_id`, if any.
+pub fn event_tenant(message: &serde_json::Value) -> Option<ObjectId> {
+    message
+        .get("tenant_id")
+        .and_then(|t| t.as_str())
+        .and_then(|t| ObjectId::parse_str(t).ok())
+}
+
 /// Broadcasts a JSON message to all connections of the specified users.
+/// An event carrying a `tenant_id` only reaches connections subscribed to
+/// that tenant (or not scoped to any). Messages are queued per connection
+/// (see [`super::outbound`]), so a slow client doesn't hold up the others.
 pub async fn broadcast(ws_storage: &WsStorage, user_ids: &[ObjectId], message: &serde_json::Value) {
-    let text = serde_json::to_string(message).unwrap_or_default();
+    let text = Message::text(serde_json::to_string(message).unwrap_or_default());
+    let tenant_id = event_tenant(message);
+    let class = super::outbound::classify(message);
 
     for user_id in user_ids {
-        let senders = ws_storage.get_senders(user_id);
-        for sender in senders {
-            let text = text.clone();
-            let mut guard = sender.lock().await;
-            if let Err(e) = guard.send(Message::text(text)).await {
-                warn!(?user_id, %e, "Failed to send WS message");
-            } else {
-                debug!(?user_id, "WS message sent");
-            }
+        for sender in ws_storage.get_senders_in(user_id, tenant_id.as_ref()) {
+            sender.push(text.clone(), class.clone());
         }
     }
 }
@@ -72,10 +76,6 @@ pub async fn send_to_connection(
     message: &serde_json::Value,
 ) {
     if let Some(sender) = ws_storage.get_sender_by_connection(connection_id) {
-        let text = serde_json::to_string(message).unwrap_or_default();
-        let mut guard = sender.lock().await;
-        if let Err(e) = guard.send(Message::text(text)).await {
-            warn!(%connection_id, %e, "Failed to send WS message to connection");
-        }
+        sender.push_json(message);
     }
 }
@@ -0,0 +1,126 @@
+//! Key-epoch signaling for end-to-end encrypted conferences (SFrame /
+//! insertable streams).
+//!
+//! The server never sees media keys. It only:
+//! - bumps the room's key epoch whenever membership changes (or a participant
+//!   asks for a rotation) and tells every participant via `media:key_rotate`;
+//! - relays opaque, client-encrypted key envelopes between participants via
+//!   `media:key_distribute`, dropping envelopes for a stale epoch.
+//!
+//! On `media:key_rotate` each participant generates a fresh sender key for the
+//! announced epoch and sends it to every other participant (encrypted to that
+//! participant) in one `media:key_distribute`.
+
+use bson::oid::ObjectId;
+use tracing::debug;
+
+use crate::state::AppState;
+
+/// Upper bound on a single relayed key envelope. A wrapped SFrame key plus
+/// metadata is a few hundred bytes; this leaves room for PQ-sized wrapping.
+const MAX_ENVELOPE_LEN: usize = 16 * 1024;
+
+/// Advance the room's key epoch and announce it to every participant still in
+/// the media room. No-op for rooms without E2EE.
+pub async fn rotate_and_announce(state: &AppState, room_id: &ObjectId, reason: &str) {
+    let Some(epoch) = state.room_manager.rotate_key_epoch(room_id) else {
+        return;
+    };
+    // Connection ids are UUIDs, so excluding "" yields every participant.
+    let participants = state.room_manager.get_other_connection_ids(room_id, "");
+    debug!(
+        ?room_id,
+        epoch,
+        reason,
+        participants = participants.len(),
+        "E2EE key epoch rotated"
+    );
+    let event = serde_json::json!({
+        "type": "media:key_rotate",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "epoch": epoch,
+            "reason": reason,
+            "participants": participants,
+        }
+    });
+    for conn_id in &participants {
+        super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
+    }
+}
+
+/// Client-requested rotation (`media:key_rotate { room_id }`), e.g. on a
+/// periodic re-key timer. Only participants of the media room may ask.
+pub async fn handle_key_rotate(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = room_id_of(data) else {
+        return;
+    };
+    if !state.room_manager.is_participant(&rid, connection_id) {
+        return;
+    }
+    rotate_and_announce(state, &rid, "requested").await;
+}
+
+/// Relay `media:key_distribute { room_id, epoch, keys: [{ connection_id,
+/// payload }] }` from one participant to the others. Each recipient gets only
+/// its own envelope, tagged with the sender.
+pub async fn handle_key_distribute(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = room_id_of(data) else {
+        return;
+    };
+    if !state.room_manager.is_participant(&rid, connection_id) {
+        return;
+    }
+    let Some(current) = state.room_manager.key_epoch(&rid) else {
+        return;
+    };
+    let epoch = data.and_then(|d| d.get("epoch")).and_then(|e| e.as_u64());
+    if epoch != Some(current) {
+        debug!(?rid, %connection_id, ?epoch, current, "dropping stale E2EE key envelope");
+        return;
+    }
+    let Some(keys) = data.and_then(|d| d.get("keys")).and_then(|k| k.as_array()) else {
+        return;
+    };
+
+    for entry in keys {
+        let (Some(target), Some(payload)) = (
+            entry.get("connection_id").and_then(|c| c.as_str()),
+            entry.get("payload").and_then(|p| p.as_str()),
+        ) else {
+            continue;
+        };
+        if target == connection_id
+            || payload.len() > MAX_ENVELOPE_LEN
+            || !state.room_manager.is_participant(&rid, target)
+        {
+            continue;
+        }
+        let event = serde_json::json!({
+            "type": "media:key_distribute",
+            "data": {
+                "room_id": rid.to_hex(),
+                "epoch": current,
+                "from_user_id": user_id.to_hex(),
+                "from_connection_id": connection_id,
+                "payload": payload,
+            }
+        });
+        super::dispatcher::send_to_connection(&state.ws_storage, target, &event).await;
+    }
+}
+
+fn room_id_of(data: Option<&serde_json::Value>) -> Option<ObjectId> {
+    data.and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+}
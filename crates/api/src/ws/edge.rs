@@ -0,0 +1,179 @@
+//! Edge Routers: listening to a call whose Router is on another pod
+//! without being redirected there. A `media:join` with `listen_only` on a
+//! pod without the Router opens an edge Router for the room, fed with the
+//! owner's producers over pipe transports (`ListProducers`, then
+//! `SetupPipe` per producer on the owner's control plane). The edge
+//! follows the owner every [`SYNC_INTERVAL`], announcing producers as they
+//! come and go, and closes once the owner drops the call or its last
+//! listener leaves.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use roomler_ai_control::proto;
+use roomler_ai_services::media::room_manager::{PipedProducer, RemoteProducer};
+use tracing::{debug, info, warn};
+
+use crate::control::{kind_name, parse_kind};
+use crate::state::AppState;
+
+/// How often an edge catches up with the producers on the owning pod.
+pub const SYNC_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Open an edge Router for `rid`, hosted by the pod at `owner_url`, with
+/// the producers it has now. A no-op if the edge is already open.
+pub async fn open(state: &AppState, rid: ObjectId, owner_url: String) -> anyhow::Result<()> {
+    if state.room_manager.has_room(&rid) {
+        return Ok(());
+    }
+    if state.edges.insert(rid, owner_url.clone()).is_some() {
+        return Ok(());
+    }
+    let opened = async {
+        state.room_manager.create_edge(rid).await?;
+        sync(state, &rid, &owner_url).await
+    }
+    .await;
+    if let Err(e) = opened {
+        state.room_manager.remove_room(&rid);
+        state.edges.remove(&rid);
+        return Err(e);
+    }
+    info!(?rid, %owner_url, "Edge Router opened");
+    tokio::spawn(follow(state.clone(), rid, owner_url));
+    Ok(())
+}
+
+/// Bring the edge in line with the owner's producers: pipe in the new
+/// ones, drop the closed ones, and tell the listeners either way.
+async fn sync(state: &AppState, rid: &ObjectId, owner_url: &str) -> anyhow::Result<()> {
+    let control = state
+        .control
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("Control plane not configured"))?;
+    let mut client = control.peer(owner_url).await?;
+    let producers = client
+        .list_producers(proto::ListProducersRequest {
+            room_id: rid.to_hex(),
+        })
+        .await?
+        .into_inner()
+        .producers;
+
+    let piped = state.room_manager.piped_in_ids(rid);
+    for producer_id in &piped {
+        if producers.iter().any(|p| &p.producer_id == producer_id) {
+            continue;
+        }
+        if let Some(remote) = state.room_manager.remove_piped_in(rid, producer_id) {
+            let msg = serde_json::json!({
+                "type": "media:producer_closed",
+                "data": {
+                    "producer_id": producer_id,
+                    "user_id": remote.user_id.to_hex(),
+                }
+            });
+            for conn_id in state.room_manager.get_other_connection_ids(rid, "") {
+                super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &msg).await;
+            }
+        }
+    }
+
+    for info in producers {
+        if piped.contains(&info.producer_id) {
+            continue;
+        }
+        let (Ok(producer_id), Ok(user_id), Some(kind)) = (
+            info.producer_id.parse(),
+            ObjectId::parse_str(&info.user_id),
+            parse_kind(&info.kind),
+        ) else {
+            warn!(?rid, producer_id = %info.producer_id, "Owner listed an invalid producer");
+            continue;
+        };
+        let remote = RemoteProducer {
+            producer_id,
+            user_id,
+            connection_id: info.connection_id.clone(),
+            source: info.source.clone(),
+        };
+        let mut client = client.clone();
+        let piped_id = info.producer_id.clone();
+        let setup = |ip: String, port: u16| async move {
+            let far = client
+                .setup_pipe(proto::SetupPipeRequest {
+                    room_id: rid.to_hex(),
+                    producer_id: piped_id,
+                    ip,
+                    port: port.into(),
+                })
+                .await?
+                .into_inner();
+            anyhow::Ok(PipedProducer {
+                ip: far.ip,
+                port: u16::try_from(far.port)?,
+                kind: parse_kind(&far.kind)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid pipe kind {}", far.kind))?,
+                rtp_parameters: serde_json::from_str(&far.rtp_parameters_json)?,
+            })
+        };
+        if let Err(e) = state.room_manager.pipe_in(rid, remote, setup).await {
+            // The producer may have closed meanwhile; the next sync retries
+            warn!(?rid, %producer_id, %e, "Failed to pipe in producer");
+            continue;
+        }
+        let msg = serde_json::json!({
+            "type": "media:new_producer",
+            "data": {
+                "producer_id": info.producer_id,
+                "user_id": info.user_id,
+                "connection_id": info.connection_id,
+                "kind": kind_name(kind),
+                "source": info.source,
+            }
+        });
+        for conn_id in state.room_manager.get_other_connection_ids(rid, "") {
+            if super::handler::receives(state, rid, &conn_id, kind) {
+                super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &msg).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Keep an edge in sync until the owner no longer has the call or nobody
+/// listens here, then close it.
+async fn follow(state: AppState, rid: ObjectId, owner_url: String) {
+    let mut tick = tokio::time::interval(SYNC_INTERVAL);
+    // The first tick is immediate; the joiner that opened the edge has
+    // yet to get its transports
+    tick.tick().await;
+    loop {
+        tick.tick().await;
+        if !state.room_manager.is_edge(&rid) {
+            break;
+        }
+        if state
+            .room_manager
+            .get_other_connection_ids(&rid, "")
+            .is_empty()
+        {
+            debug!(?rid, "Edge has no listeners left");
+            break;
+        }
+        if let Err(e) = sync(&state, &rid, &owner_url).await {
+            if e.downcast_ref::<tonic::Status>()
+                .is_some_and(|s| s.code() == tonic::Code::NotFound)
+            {
+                debug!(?rid, "Owner no longer hosts the call");
+                break;
+            }
+            warn!(?rid, %owner_url, %e, "Edge sync failed");
+        }
+    }
+    if state.room_manager.is_edge(&rid) {
+        state.room_manager.remove_room(&rid);
+    }
+    state.edges.remove(&rid);
+    info!(?rid, "Edge Router closed");
+}
@@ -0,0 +1,128 @@
+//! Camera effects signaling (virtual backgrounds and blur).
+//!
+//! Effects run in the sender's browser; the server only keeps each media
+//! participant's reported state so that the others, and any participant
+//! recording the call, can label or re-render that video. A client sends
+//! `media:effects_state { room_id, background, asset_id? }` whenever it
+//! changes effects; the other connections in the media room get
+//! `media:effects_state { room_id, user_id, connection_id, background,
+//! asset_id }`. Joiners receive the current state of everyone with an effect
+//! on, right after the existing producers.
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::FileContextType;
+use roomler_ai_services::media::room_manager::{BackgroundEffect, VideoEffects};
+use tracing::debug;
+
+use crate::state::AppState;
+
+fn event(
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    connection_id: &str,
+    effects: &VideoEffects,
+) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:effects_state",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "background": effects.background,
+            "asset_id": effects.asset_id,
+        }
+    })
+}
+
+/// Whether `asset_id` is a live background in a tenant the user belongs to.
+async fn is_usable_background(state: &AppState, user_id: &ObjectId, asset_id: &str) -> bool {
+    let Ok(fid) = ObjectId::parse_str(asset_id) else {
+        return false;
+    };
+    let Ok(file) = state.files.base.find_by_id_unscoped(fid).await else {
+        return false;
+    };
+    matches!(file.context.context_type, FileContextType::Background)
+        && file.deleted_at.is_none()
+        && state
+            .tenants
+            .is_member(file.tenant_id, *user_id)
+            .await
+            .unwrap_or(false)
+}
+
+/// Record a participant's effects and relay them to the rest of the room.
+pub async fn handle_effects_state(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = data
+        .get("room_id")
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        return;
+    };
+    if !state.room_manager.is_participant(&rid, connection_id) {
+        return;
+    }
+    let effects: VideoEffects = match serde_json::from_value(data.clone()) {
+        Ok(e) => e,
+        Err(e) => {
+            super::handler::send_media_error(
+                state,
+                user_id,
+                &format!("Invalid effects state: {}", e),
+            )
+            .await;
+            return;
+        }
+    };
+    let effects = match effects.background {
+        BackgroundEffect::Image => {
+            let usable = match effects.asset_id.as_deref() {
+                Some(aid) => is_usable_background(state, user_id, aid).await,
+                None => false,
+            };
+            if !usable {
+                super::handler::send_media_error(state, user_id, "Unknown background asset").await;
+                return;
+            }
+            effects
+        }
+        // An asset only means something with an image background.
+        _ => VideoEffects {
+            asset_id: None,
+            ..effects
+        },
+    };
+
+    if !state
+        .room_manager
+        .set_effects(&rid, connection_id, effects.clone())
+    {
+        return;
+    }
+    debug!(?rid, %connection_id, background = ?effects.background, "Effects state updated");
+
+    let msg = event(&rid, user_id, connection_id, &effects);
+    let others = state
+        .room_manager
+        .get_other_connection_ids(&rid, connection_id);
+    for conn_id in &others {
+        super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &msg).await;
+    }
+}
+
+/// Send a joining connection the effects of everyone already in the room.
+pub async fn replay_to(state: &AppState, room_id: &ObjectId, connection_id: &str) {
+    for (uid, conn_id, effects) in state.room_manager.get_effects(room_id, connection_id) {
+        let msg = event(room_id, &uid, &conn_id, &effects);
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    }
+}
@@ -0,0 +1,303 @@
+//! Missed-event recovery for room events.
+//!
+//! Room-wide events (messages, reactions, pins, call lifecycle, Q&A) are sent
+//! through [`publish`], which stamps them with the room's `tenant_id` and
+//! `room_id` and a per-room, monotonically increasing `seq` at the top level
+//! of the message, and keeps the last [`CAPACITY`] of them per room. Sequence
+//! numbers come from Redis when it is configured, so they stay ordered across
+//! pods, and every pod records the events it relays from Redis as well as its
+//! own.
+//!
+//! A client that reconnects sends `sync { room_id, last_seq }` with the
+//! highest `seq` it saw for the room. It gets the events it was a recipient
+//! of since then, in order, followed by `sync:done { room_id, seq }`; if the
+//! gap is no longer buffered it gets `sync:resync_required { room_id, seq }`
+//! instead and should reload the room over REST. A user's `seq`s increase but
+//! aren't contiguous: events they weren't sent (the `message:update` for
+//! their own edit, for one) still take a number.
+
+use std::collections::VecDeque;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// Events kept per room.
+pub const CAPACITY: usize = 200;
+
+struct LoggedEvent {
+    seq: u64,
+    recipients: Vec<ObjectId>,
+    message: serde_json::Value,
+}
+
+#[derive(Default)]
+struct RoomLog {
+    /// Highest `seq` seen for the room.
+    newest: u64,
+    /// Ordered by `seq`.
+    events: VecDeque<LoggedEvent>,
+}
+
+impl RoomLog {
+    fn insert(&mut self, event: LoggedEvent) {
+        // Events relayed from other pods can arrive out of order, and this
+        // pod's own events come back from Redis too.
+        let pos = self.events.partition_point(|e| e.seq < event.seq);
+        if self.events.get(pos).is_some_and(|e| e.seq == event.seq) {
+            return;
+        }
+        self.newest = self.newest.max(event.seq);
+        self.events.insert(pos, event);
+        while self.events.len() > CAPACITY {
+            self.events.pop_front();
+        }
+    }
+}
+
+/// What a client that last saw `last_seq` needs.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Gap {
+    /// Everything after `last_seq` is still buffered (possibly nothing).
+    Replay,
+    /// Events after `last_seq` were evicted, or `last_seq` is from a log this
+    /// pod never saw (e.g. before a restart).
+    Resync,
+}
+
+/// `oldest` is the lowest buffered `seq` and `newest` the highest seen.
+pub fn gap(oldest: Option<u64>, newest: u64, last_seq: u64) -> Gap {
+    if last_seq > newest {
+        return Gap::Resync;
+    }
+    if last_seq == newest {
+        return Gap::Replay;
+    }
+    match oldest {
+        Some(oldest) if last_seq + 1 >= oldest => Gap::Replay,
+        _ => Gap::Resync,
+    }
+}
+
+/// Recent room events, by room id.
+#[derive(Default)]
+pub struct EventLog {
+    rooms: DashMap<ObjectId, RoomLog>,
+}
+
+impl EventLog {
+    /// Record an event with an already assigned `seq`.
+    pub fn record(
+        &self,
+        room_id: ObjectId,
+        seq: u64,
+        recipients: Vec<ObjectId>,
+        message: serde_json::Value,
+    ) {
+        self.rooms.entry(room_id).or_default().insert(LoggedEvent {
+            seq,
+            recipients,
+            message,
+        });
+    }
+
+    /// Record an event under the next local `seq` and return it.
+    fn record_next(
+        &self,
+        room_id: ObjectId,
+        recipients: Vec<ObjectId>,
+        message: &mut serde_json::Value,
+    ) -> u64 {
+        let mut log = self.rooms.entry(room_id).or_default();
+        let seq = log.newest + 1;
+        message["seq"] = seq.into();
+        log.insert(LoggedEvent {
+            seq,
+            recipients,
+            message: message.clone(),
+        });
+        seq
+    }
+
+    /// The room's highest `seq`, and the events addressed to `user_id` after
+    /// `last_seq`, or `None` when the gap can't be filled.
+    pub fn since(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+        last_seq: u64,
+    ) -> (u64, Option<Vec<serde_json::Value>>) {
+        let Some(log) = self.rooms.get(room_id) else {
+            return match gap(None, 0, last_seq) {
+                Gap::Replay => (0, Some(Vec::new())),
+                Gap::Resync => (0, None),
+            };
+        };
+        let oldest = log.events.front().map(|e| e.seq);
+        match gap(oldest, log.newest, last_seq) {
+            Gap::Replay => {
+                let events = log
+                    .events
+                    .iter()
+                    .filter(|e| e.seq > last_seq && e.recipients.contains(user_id))
+                    .map(|e| e.message.clone())
+                    .collect();
+                (log.newest, Some(events))
+            }
+            Gap::Resync => (log.newest, None),
+        }
+    }
+}
+
+/// Send a room event to `user_ids` (on every pod), stamped with `tenant_id`,
+/// `room_id` and the room's next `seq`, and keep it for [`handle_sync`].
+pub async fn publish(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    user_ids: &[ObjectId],
+    mut message: serde_json::Value,
+) {
+    message["tenant_id"] = tenant_id.to_hex().into();
+    message["room_id"] = room_id.to_hex().into();
+
+    let shared_seq = match &state.redis_pubsub {
+        Some(pubsub) => match pubsub.next_seq(&room_id.to_hex()).await {
+            Ok(seq) => Some(seq),
+            Err(e) => {
+                warn!(?room_id, %e, "Failed to allocate room event seq from Redis");
+                None
+            }
+        },
+        None => None,
+    };
+    match shared_seq {
+        Some(seq) => {
+            message["seq"] = seq.into();
+            state
+                .event_log
+                .record(room_id, seq, user_ids.to_vec(), message.clone());
+        }
+        None => {
+            state
+                .event_log
+                .record_next(room_id, user_ids.to_vec(), &mut message);
+        }
+    }
+
+    super::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        user_ids,
+        &message,
+    )
+    .await;
+}
+
+/// `sync { room_id, last_seq }`: replay what the connection missed.
+pub async fn handle_sync(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(room_id) = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+    else {
+        return;
+    };
+    let last_seq = data
+        .and_then(|d| d.get("last_seq"))
+        .and_then(|s| s.as_u64())
+        .unwrap_or(0);
+
+    let is_member = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .map(|ids| ids.contains(user_id))
+        .unwrap_or(false);
+    if !is_member {
+        warn!(?user_id, ?room_id, "sync for a room the user isn't in");
+        return;
+    }
+
+    let (seq, events) = state.event_log.since(&room_id, user_id, last_seq);
+    let done_type = match events {
+        Some(events) => {
+            for event in &events {
+                super::dispatcher::send_to_connection(&state.ws_storage, connection_id, event)
+                    .await;
+            }
+            "sync:done"
+        }
+        None => "sync:resync_required",
+    };
+    let msg = serde_json::json!({
+        "type": done_type,
+        "data": {
+            "room_id": room_id.to_hex(),
+            "seq": seq,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gap_decisions() {
+        // Up to date, including a client that never saw an event.
+        assert_eq!(gap(Some(1), 5, 5), Gap::Replay);
+        assert_eq!(gap(None, 0, 0), Gap::Replay);
+        // Everything after last_seq is still buffered.
+        assert_eq!(gap(Some(4), 10, 3), Gap::Replay);
+        assert_eq!(gap(Some(1), 10, 0), Gap::Replay);
+        // Evicted, or ahead of this log.
+        assert_eq!(gap(Some(5), 10, 3), Gap::Resync);
+        assert_eq!(gap(None, 4, 2), Gap::Resync);
+        assert_eq!(gap(Some(1), 5, 9), Gap::Resync);
+    }
+
+    #[test]
+    fn replays_only_the_users_events_in_order() {
+        let log = EventLog::default();
+        let room = ObjectId::new();
+        let (alice, bob) = (ObjectId::new(), ObjectId::new());
+        log.record(room, 2, vec![alice], serde_json::json!({ "n": 2 }));
+        log.record(room, 1, vec![alice, bob], serde_json::json!({ "n": 1 }));
+        log.record(room, 3, vec![bob], serde_json::json!({ "n": 3 }));
+        // Relayed back from Redis: ignored.
+        log.record(room, 2, vec![alice], serde_json::json!({ "n": 2 }));
+
+        let (seq, events) = log.since(&room, &alice, 0);
+        assert_eq!(seq, 3);
+        assert_eq!(
+            events.unwrap(),
+            vec![serde_json::json!({ "n": 1 }), serde_json::json!({ "n": 2 })]
+        );
+        let (_, events) = log.since(&room, &bob, 1);
+        assert_eq!(events.unwrap(), vec![serde_json::json!({ "n": 3 })]);
+    }
+
+    #[test]
+    fn evicted_gap_requires_resync() {
+        let log = EventLog::default();
+        let room = ObjectId::new();
+        let user = ObjectId::new();
+        for _ in 0..CAPACITY + 5 {
+            log.record_next(room, vec![user], &mut serde_json::json!({}));
+        }
+        assert!(log.since(&room, &user, 2).1.is_none());
+        let (seq, events) = log.since(&room, &user, 5);
+        assert_eq!(seq, (CAPACITY + 5) as u64);
+        assert_eq!(events.unwrap().len(), CAPACITY);
+        assert!(log.since(&room, &user, seq + 1).1.is_none());
+    }
+}
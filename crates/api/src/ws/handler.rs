@@ -3,26 +3,41 @@ use axum::{
         Query, State, WebSocketUpgrade,
         ws::{CloseFrame, Message, WebSocket},
     },
+    http::HeaderMap,
     response::Response,
 };
-use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
 use bson::oid::ObjectId;
-use futures::{SinkExt, StreamExt};
-use hmac::{Hmac, Mac};
+use futures::StreamExt;
 use mediasoup::prelude::*;
+use roomler_ai_db::models::{CallDebugKind, room::OverflowMode};
+use roomler_ai_services::media::room_manager::RoomFull;
 use serde::Deserialize;
-use sha1::Sha1;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
-use tokio::sync::Mutex;
-use tracing::{debug, info, warn};
+use std::time::Duration;
+use tokio::time::{Instant, MissedTickBehavior};
+use tracing::{Instrument, debug, info, warn};
 use uuid::Uuid;
 
+use super::keepalive::{Action as KeepaliveAction, Keepalive};
+use super::outbound::{Class, Outbound, spawn_writer};
+use crate::middleware::rate_limit::WsThrottle;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
 pub struct WsParams {
-    pub token: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    /// One-time ticket from `POST /api/auth/ws-ticket`, in place of `token`
+    /// for user connections (see `ws::ticket`).
+    #[serde(default)]
+    pub ticket: Option<String>,
+    /// Comma-separated tenant ids whose events this connection wants; all of
+    /// the user's tenants when absent (see `ws::tenant_scope`).
+    #[serde(default)]
+    pub tenants: Option<String>,
+    /// `true` to get events as JSON array frames (see `ws::outbound`).
+    #[serde(default)]
+    pub batch: bool,
     /// Optional connection role. Defaults to `"user"` to preserve existing
     /// browser behaviour. Set to `"agent"` by the native remote-control agent.
     #[serde(default)]
@@ -32,38 +47,122 @@ pub struct WsParams {
 pub async fn ws_upgrade(
     State(state): State<AppState>,
     Query(params): Query<WsParams>,
+    headers: HeaderMap,
     ws: WebSocketUpgrade,
 ) -> Response {
-    match params.role.as_deref() {
-        Some("agent") => ws_upgrade_agent(state, params.token, ws),
-        Some("tunnel-client") => ws_upgrade_tunnel_client(state, params.token, ws),
-        _ => ws_upgrade_user(state, params.token, ws),
-    }
-}
-
-fn ws_upgrade_user(state: AppState, token: String, ws: WebSocketUpgrade) -> Response {
-    let claims = match state.auth.verify_access_token(&token) {
-        Ok(c) => c,
-        Err(_) => {
+    let role = params.role.as_deref();
+    let tenants = match params
+        .tenants
+        .as_deref()
+        .map(super::tenant_scope::parse_ids)
+    {
+        Some(Some(ids)) => Some(ids),
+        Some(None) => {
             return Response::builder()
-                .status(401)
-                .body("Unauthorized".into())
+                .status(400)
+                .body("Invalid tenant ID".into())
                 .unwrap();
         }
+        None => None,
     };
-
-    let user_id = match ObjectId::parse_str(&claims.sub) {
-        Ok(id) => id,
-        Err(_) => {
+    if let (None | Some("user"), Some(ticket)) = (role, &params.ticket) {
+        let Some(redeemed) = super::ticket::redeem(&state, ticket).await else {
             return Response::builder()
-                .status(400)
-                .body("Invalid user ID".into())
+                .status(401)
+                .body("Invalid or used ticket".into())
                 .unwrap();
+        };
+        let client_country = super::turn_regions::client_country(&headers);
+        return ws.on_upgrade(move |socket| {
+            handle_socket(
+                socket,
+                state,
+                redeemed.user_id,
+                redeemed.username,
+                false,
+                redeemed.media_rooms,
+                tenants,
+                client_country,
+                params.batch,
+            )
+        });
+    }
+    let Some(token) = params.token else {
+        return Response::builder()
+            .status(401)
+            .body("Unauthorized".into())
+            .unwrap();
+    };
+    match role {
+        Some("agent") => ws_upgrade_agent(state, token, ws),
+        Some("tunnel-client") => ws_upgrade_tunnel_client(state, token, ws),
+        _ => {
+            // Used to pin TURN regions for this connection's media:join.
+            let client_country = super::turn_regions::client_country(&headers);
+            ws_upgrade_user(state, token, tenants, client_country, params.batch, ws).await
         }
+    }
+}
+
+async fn ws_upgrade_user(
+    state: AppState,
+    token: String,
+    tenants: Option<Vec<ObjectId>>,
+    client_country: Option<String>,
+    batch: bool,
+    ws: WebSocketUpgrade,
+) -> Response {
+    let (user_id, username, tenants, is_bot) = match state.auth.verify_access_token(&token) {
+        Ok(claims) => match ObjectId::parse_str(&claims.sub) {
+            Ok(id) => (id, claims.username, tenants, false),
+            Err(_) => {
+                return Response::builder()
+                    .status(400)
+                    .body("Invalid user ID".into())
+                    .unwrap();
+            }
+        },
+        Err(_) => match resolve_bot(&state, &token).await {
+            // A bot only ever hears its own tenant.
+            Some((id, username, tenant_id)) => (id, username, Some(vec![tenant_id]), true),
+            None => {
+                return Response::builder()
+                    .status(401)
+                    .body("Unauthorized".into())
+                    .unwrap();
+            }
+        },
     };
-    let username = claims.username.clone();
 
-    ws.on_upgrade(move |socket| handle_socket(socket, state, user_id, username))
+    ws.on_upgrade(move |socket| {
+        handle_socket(
+            socket,
+            state,
+            user_id,
+            username,
+            is_bot,
+            None,
+            tenants,
+            client_country,
+            batch,
+        )
+    })
+}
+
+/// A bot token may open the socket to receive events for the rooms it has
+/// joined, but not media or remote control (see [`handle_client_message`]).
+async fn resolve_bot(state: &AppState, token: &str) -> Option<(ObjectId, String, ObjectId)> {
+    let claims = state.auth.verify_bot_token(token).ok()?;
+    let bot_id = ObjectId::parse_str(&claims.sub).ok()?;
+    let tenant_id = ObjectId::parse_str(&claims.tenant_id).ok()?;
+    let token_id = ObjectId::parse_str(&claims.jti).ok()?;
+    state
+        .bot_tokens
+        .find_active(tenant_id, bot_id, token_id)
+        .await
+        .ok()?;
+    let bot = state.users.base.find_by_id(bot_id).await.ok()?;
+    (bot.is_bot && bot.deleted_at.is_none()).then_some((bot_id, bot.username, tenant_id))
 }
 
 fn ws_upgrade_tunnel_client(state: AppState, token: String, ws: WebSocketUpgrade) -> Response {
@@ -272,12 +371,42 @@ async fn send_goodbye_and_close<M: serde::Serialize>(
     // returned.
 }
 
-async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, username: String) {
+#[allow(clippy::too_many_arguments)]
+async fn handle_socket(
+    socket: WebSocket,
+    state: AppState,
+    user_id: ObjectId,
+    username: String,
+    is_bot: bool,
+    media_rooms: Option<Vec<ObjectId>>,
+    tenants: Option<Vec<ObjectId>>,
+    client_country: Option<String>,
+    batch: bool,
+) {
     let connection_id = Uuid::new_v4().to_string();
     info!(?user_id, %connection_id, "WebSocket connected");
 
-    let (sender, mut receiver) = socket.split();
-    let sender = Arc::new(Mutex::new(sender));
+    let (sink, mut receiver) = socket.split();
+    let sender = Arc::new(Outbound::new(
+        state.settings.ws.outbound_queue_size,
+        state.ws_stats.clone(),
+    ));
+    let mut writer = spawn_writer(
+        sink,
+        sender.clone(),
+        Duration::from_secs(state.settings.ws.send_timeout_secs.max(1)),
+        batch.then(|| Duration::from_millis(state.settings.ws.batch_window_ms)),
+    );
+
+    // Scope the connection before it is registered, so no other tenant's
+    // event slips through in between.
+    let tenant_ids = match tenants {
+        Some(ids) if is_bot => Some(state.ws_storage.subscribe(&connection_id, &ids)),
+        Some(ids) => {
+            Some(super::tenant_scope::subscribe(&state, &user_id, &connection_id, &ids).await)
+        }
+        None => None,
+    };
 
     state
         .ws_storage
@@ -287,38 +416,83 @@ async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, us
     // Each browser tab gets its own controller tx; the Hub routes by tx, not
     // by user id, so multiple tabs don't cross signals.
     let (rc_controller_tx, rc_controller_rx) = state.rc_hub.register_controller(user_id);
-    let rc_pump = tokio::spawn(crate::ws::remote_control::pump_server_messages(
+    let rc_pump = tokio::spawn(crate::ws::remote_control::pump_server_messages_to(
         rc_controller_rx,
         sender.clone(),
     ));
 
-    {
-        let msg = serde_json::json!({
-            "type": "connected",
-            "user_id": user_id.to_hex(),
-        });
-        let mut guard = sender.lock().await;
-        let _ = guard
-            .send(Message::text(serde_json::to_string(&msg).unwrap()))
-            .await;
-    }
-
-    while let Some(msg) = receiver.next().await {
+    sender.push_json(&serde_json::json!({
+        "type": "connected",
+        "user_id": user_id.to_hex(),
+        "tenant_ids": tenant_ids.as_ref().map(super::tenant_scope::to_hex),
+    }));
+
+    let mut throttle = WsThrottle::new(&state.runtime.load().rate_limit);
+    let mut keepalive = Keepalive::new(&state.settings.ws, Instant::now());
+    let mut keepalive_tick = tokio::time::interval(keepalive.tick_period());
+    keepalive_tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        let msg = tokio::select! {
+            msg = receiver.next() => match msg {
+                Some(msg) => msg,
+                None => break,
+            },
+            reason = &mut writer => {
+                if let Ok(Some(reason)) = reason {
+                    info!(?user_id, %connection_id, ?reason, "Dropping slow WebSocket consumer");
+                    state.ws_stats.record_reaped(reason);
+                }
+                break;
+            }
+            _ = keepalive_tick.tick() => {
+                match keepalive.poll(Instant::now()) {
+                    KeepaliveAction::Wait => {}
+                    KeepaliveAction::Ping => {
+                        sender.push(Message::Ping(Default::default()), Class::default());
+                    }
+                    KeepaliveAction::Reap(reason) => {
+                        info!(?user_id, %connection_id, ?reason, "Dropping unresponsive WebSocket");
+                        state.ws_stats.record_reaped(reason);
+                        break;
+                    }
+                }
+                continue;
+            }
+        };
+        keepalive.on_frame(
+            Instant::now(),
+            matches!(msg, Ok(Message::Text(_) | Message::Binary(_))),
+        );
         match msg {
             Ok(Message::Text(text)) => {
+                if let Some(throttle) = throttle.as_mut()
+                    && let Err(notify) = throttle.admit()
+                {
+                    if let Some(retry_after_ms) = notify {
+                        debug!(?user_id, %connection_id, "WS message rate limit hit");
+                        sender.push_json(&serde_json::json!({
+                            "type": "rate_limited",
+                            "data": { "retry_after_ms": retry_after_ms }
+                        }));
+                    }
+                    continue;
+                }
                 handle_client_message(
                     &state,
                     &user_id,
                     &connection_id,
                     &username,
+                    is_bot,
+                    media_rooms.as_deref(),
+                    client_country.as_deref(),
                     &rc_controller_tx,
                     &text,
                 )
+                .instrument(crate::telemetry::ws_span(&user_id, &connection_id, &text))
                 .await;
             }
             Ok(Message::Ping(data)) => {
-                let mut guard = sender.lock().await;
-                let _ = guard.send(Message::Pong(data)).await;
+                sender.push(Message::Pong(data), Class::default());
             }
             Ok(Message::Close(_)) => {
                 break;
@@ -337,39 +511,38 @@ async fn handle_socket(socket: WebSocket, state: AppState, user_id: ObjectId, us
         .unregister_controller(user_id, &rc_controller_tx);
     rc_pump.abort();
     state.ws_storage.remove(&user_id, &connection_id, &sender);
+    // Stops the writer, which drops the socket.
+    sender.close();
+    state.presence.drop_connection(&connection_id);
 
     if let Some(room_id) = state.room_manager.get_connection_room(&connection_id) {
-        let remaining_conns = state
-            .room_manager
-            .get_other_connection_ids(&room_id, &connection_id);
-
-        state
-            .room_manager
-            .close_participant(&room_id, &connection_id);
-
-        if !remaining_conns.is_empty() {
-            let event = serde_json::json!({
-                "type": "media:peer_left",
-                "data": {
-                    "user_id": user_id.to_hex(),
-                    "connection_id": connection_id,
-                    "room_id": room_id.to_hex(),
-                }
-            });
-            for conn_id in &remaining_conns {
-                super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
-            }
+        if state.room_manager.is_loopback(&room_id) {
+            // Nothing to come back to in a device test.
+            state.room_manager.remove_room(&room_id);
+        } else {
+            state.call_debug.record(
+                room_id,
+                user_id,
+                &connection_id,
+                CallDebugKind::Leave,
+                "WebSocket closed",
+            );
+            super::reconnect::on_disconnect(&state, room_id, user_id, &connection_id).await;
         }
     }
 
     info!(?user_id, %connection_id, "WebSocket disconnected");
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_client_message(
     state: &AppState,
     user_id: &ObjectId,
     connection_id: &str,
     username: &str,
+    is_bot: bool,
+    media_rooms: Option<&[ObjectId]>,
+    client_country: Option<&str>,
     rc_controller_tx: &roomler_ai_remote_control::session::ClientTx,
     text: &str,
 ) {
@@ -377,6 +550,9 @@ async fn handle_client_message(
     // Peek at the raw JSON before full parse so we don't pay the cost on
     // every media/presence message.
     if text.contains("\"rc:") {
+        if is_bot {
+            return;
+        }
         // Authorization + consent-mode gate for `rc:session.request`
         // (self-control / admin / REMOTE_CONTROL + per-device allowlist +
         // quarantine). A non-request rc:* message resolves to `Ok(Prompt)` and
@@ -419,63 +595,55 @@ async fn handle_client_message(
     let msg_type = parsed.get("type").and_then(|t| t.as_str()).unwrap_or("");
     let data = parsed.get("data");
 
+    crate::telemetry::record_ws_message(msg_type);
     debug!(?user_id, %connection_id, msg_type, "WS message received");
 
+    super::presence::touch(state, user_id);
+
+    if is_bot && msg_type.starts_with("media:") {
+        send_media_error(state, user_id, "Bots can't use media").await;
+        return;
+    }
+    if msg_type.starts_with("media:") && !super::ticket::allows_media(media_rooms, msg_type, data) {
+        let msg = serde_json::json!({
+            "type": "media:error",
+            "data": { "message": "This connection's ticket doesn't cover that room" }
+        });
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+        return;
+    }
+
     match msg_type {
         "ping" => {
             let pong = serde_json::json!({ "type": "pong" });
             super::dispatcher::send_to_user(&state.ws_storage, user_id, &pong).await;
         }
+        "sync" => {
+            super::event_log::handle_sync(state, user_id, connection_id, data).await;
+        }
         "typing:start" | "typing:stop" => {
-            if let Some(room_id_str) = data.and_then(|d| d.get("room_id")).and_then(|c| c.as_str())
-                && let Ok(rid) = ObjectId::parse_str(room_id_str)
-                && let Ok(member_ids) = state.rooms.find_member_user_ids(rid).await
-            {
-                let recipients: Vec<ObjectId> =
-                    member_ids.into_iter().filter(|id| id != user_id).collect();
-                let event = serde_json::json!({
-                    "type": msg_type,
-                    "data": {
-                        "room_id": room_id_str,
-                        "user_id": user_id.to_hex(),
-                    }
-                });
-                super::dispatcher::broadcast_with_redis(
-                    &state.ws_storage,
-                    &state.redis_pubsub,
-                    &recipients,
-                    &event,
-                )
-                .await;
-            }
+            super::presence::handle_typing(state, user_id, data, msg_type == "typing:start").await;
         }
         "presence:update" => {
-            if let Some(presence) = data
-                .and_then(|d| d.get("presence"))
-                .and_then(|p| p.as_str())
-            {
-                let all_users = state.ws_storage.all_user_ids();
-                let event = serde_json::json!({
-                    "type": "presence:update",
-                    "data": {
-                        "user_id": user_id.to_hex(),
-                        "presence": presence,
-                    }
-                });
-                super::dispatcher::broadcast_with_redis(
-                    &state.ws_storage,
-                    &state.redis_pubsub,
-                    &all_users,
-                    &event,
-                )
-                .await;
-            }
+            super::presence::handle_update(state, user_id, data).await;
+        }
+        "presence:subscribe" => {
+            super::presence::handle_subscribe(state, user_id, connection_id, data).await;
+        }
+        "presence:unsubscribe" => {
+            super::presence::handle_unsubscribe(state, connection_id, data);
+        }
+        "tenant:subscribe" if !is_bot => {
+            super::tenant_scope::handle_subscribe(state, user_id, connection_id, data).await;
+        }
+        "tenant:unsubscribe" if !is_bot => {
+            super::tenant_scope::handle_unsubscribe(state, connection_id, data).await;
         }
         "media:join" => {
-            handle_media_join(state, user_id, connection_id, data).await;
+            handle_media_join(state, user_id, connection_id, client_country, data).await;
         }
         "media:connect_transport" => {
-            handle_media_connect_transport(state, connection_id, data).await;
+            handle_media_connect_transport(state, user_id, connection_id, data).await;
         }
         "media:produce" => {
             handle_media_produce(state, user_id, connection_id, data).await;
@@ -483,17 +651,66 @@ async fn handle_client_message(
         "media:consume" => {
             handle_media_consume(state, user_id, connection_id, data).await;
         }
+        "media:restart_ice" => {
+            handle_media_restart_ice(state, user_id, connection_id, data).await;
+        }
         "media:producer_close" => {
             handle_media_producer_close(state, user_id, connection_id, data).await;
         }
+        "media:rejoin" => {
+            super::reconnect::handle_rejoin(state, user_id, connection_id, data).await;
+        }
         "media:leave" => {
             handle_media_leave(state, user_id, connection_id, data).await;
         }
+        "media:test_join" => {
+            super::test_call::handle_join(state, user_id, connection_id, client_country, data)
+                .await;
+        }
+        "media:test_ping" => {
+            super::test_call::handle_ping(state, connection_id, data).await;
+        }
+        "media:test_stats" => {
+            super::test_call::handle_stats(state, user_id, connection_id, data).await;
+        }
+        "media:test_leave" => {
+            super::test_call::handle_leave(state, connection_id, data).await;
+        }
+        "media:key_rotate" => {
+            super::e2ee::handle_key_rotate(state, connection_id, data).await;
+        }
+        "media:key_distribute" => {
+            super::e2ee::handle_key_distribute(state, user_id, connection_id, data).await;
+        }
         "media:play_audio" => {
-            handle_play_audio(state, user_id, connection_id, data).await;
+            super::playback::handle_play_audio(state, user_id, connection_id, data).await;
         }
         "media:stop_audio" => {
-            handle_stop_audio(state, user_id, connection_id, data).await;
+            super::playback::handle_stop_audio(state, connection_id, data).await;
+        }
+        "media:set_playback_volume" => {
+            super::playback::handle_set_volume(state, connection_id, data).await;
+        }
+        "media:speak" => {
+            super::speech::handle_speak(state, user_id, connection_id, data).await;
+        }
+        "media:effects_state" => {
+            super::effects::handle_effects_state(state, user_id, connection_id, data).await;
+        }
+        "media:ptt_active" => {
+            super::audio::handle_ptt_active(state, user_id, connection_id, data).await;
+        }
+        "call:accept" => {
+            super::ring::handle_accept(state, user_id, data).await;
+        }
+        "call:decline" => {
+            super::ring::handle_decline(state, user_id, data).await;
+        }
+        "whiteboard:op" => {
+            super::whiteboard::handle_op(state, user_id, connection_id, data).await;
+        }
+        "whiteboard:sync" => {
+            super::whiteboard::handle_sync(state, user_id, connection_id, data).await;
         }
         _ => {
             debug!(?user_id, msg_type, "Unknown WS message type");
@@ -501,7 +718,7 @@ async fn handle_client_message(
     }
 }
 
-async fn send_media_error(state: &AppState, user_id: &ObjectId, message: &str) {
+pub(super) async fn send_media_error(state: &AppState, user_id: &ObjectId, message: &str) {
     let msg = serde_json::json!({
         "type": "media:error",
         "data": { "message": message }
@@ -513,6 +730,7 @@ async fn handle_media_join(
     state: &AppState,
     user_id: &ObjectId,
     connection_id: &str,
+    client_country: Option<&str>,
     data: Option<&serde_json::Value>,
 ) {
     let room_id_str = match data.and_then(|d| d.get("room_id")).and_then(|c| c.as_str()) {
@@ -531,11 +749,52 @@ async fn handle_media_join(
         }
     };
 
+    let listen_only = data
+        .and_then(|d| d.get("listen_only"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+
     let room_exists = state.room_manager.has_room(&rid);
     debug!(?user_id, %connection_id, ?rid, room_exists, "media:join room check");
     if !room_exists {
-        send_media_error(state, user_id, "Room does not exist").await;
-        return;
+        // Multi-pod: the Router may live on another pod. Listeners get its
+        // media piped to an edge Router here; anyone else is pointed at it
+        // instead of failing the join.
+        if let Some(registry) = &state.conference_registry {
+            match registry.owner(&rid).await {
+                Ok(Some(url)) if url != registry.instance_url() => {
+                    let edge = listen_only
+                        && state.control.is_some()
+                        && match super::edge::open(state, rid, url.clone()).await {
+                            Ok(()) => true,
+                            Err(e) => {
+                                warn!(?rid, %url, %e, "Failed to open edge Router");
+                                false
+                            }
+                        };
+                    if !edge {
+                        info!(%connection_id, ?rid, %url, "media:join redirected to owning pod");
+                        let msg = serde_json::json!({
+                            "type": "media:redirect",
+                            "data": { "room_id": room_id_str, "url": url }
+                        });
+                        super::dispatcher::send_to_connection(
+                            &state.ws_storage,
+                            connection_id,
+                            &msg,
+                        )
+                        .await;
+                        return;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => warn!(?rid, %e, "conference registry lookup failed"),
+            }
+        }
+        if !state.room_manager.has_room(&rid) {
+            send_media_error(state, user_id, "Room does not exist").await;
+            return;
+        }
     }
 
     let transport_pair = match state
@@ -545,18 +804,45 @@ async fn handle_media_join(
     {
         Ok(tp) => tp,
         Err(e) => {
-            send_media_error(
-                state,
-                user_id,
-                &format!("Failed to create transports: {}", e),
-            )
-            .await;
+            if let Some(full) = e.downcast_ref::<RoomFull>() {
+                info!(%connection_id, ?rid, "media:join refused, room full");
+                state.call_debug.record(
+                    rid,
+                    *user_id,
+                    connection_id,
+                    CallDebugKind::Error,
+                    "Room full",
+                );
+                let msg = serde_json::json!({
+                    "type": "media:room_full",
+                    "data": {
+                        "room_id": room_id_str,
+                        "max_participants": full.max_participants,
+                    }
+                });
+                super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+                return;
+            }
+            let message = format!("Failed to create transports: {}", e);
+            state
+                .call_debug
+                .record(rid, *user_id, connection_id, CallDebugKind::Error, &message);
+            send_media_error(state, user_id, &message).await;
             return;
         }
     };
+    state.call_debug.record(
+        rid,
+        *user_id,
+        connection_id,
+        CallDebugKind::Join,
+        format!(
+            "send transport {}, recv transport {}",
+            transport_pair.send_transport.id, transport_pair.recv_transport.id
+        ),
+    );
 
-    if let Some(room) = state.room_manager.rooms_ref().get(&rid) {
-        let caps = serde_json::to_value(room.router.rtp_capabilities()).unwrap_or_default();
+    if let Some(caps) = state.room_manager.rtp_capabilities(&rid) {
         let msg = serde_json::json!({
             "type": "media:router_capabilities",
             "data": { "rtp_capabilities": caps }
@@ -564,62 +850,15 @@ async fn handle_media_join(
         super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
     }
 
-    let ice_servers: Vec<serde_json::Value> = if let Some(ref url) = state.settings.turn.url {
-        let (turn_username, turn_credential) =
-            if let Some(ref secret) = state.settings.turn.shared_secret {
-                let expiry = SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs()
-                    + 86400;
-                let username = format!("{}:{}", expiry, user_id.to_hex());
-                let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes())
-                    .expect("HMAC key length is valid");
-                mac.update(username.as_bytes());
-                let credential = BASE64.encode(mac.finalize().into_bytes());
-                debug!(%username, "Generated TURN ephemeral credentials");
-                (username, credential)
-            } else {
-                (
-                    state
-                        .settings
-                        .turn
-                        .username
-                        .as_deref()
-                        .unwrap_or("")
-                        .to_string(),
-                    state
-                        .settings
-                        .turn
-                        .password
-                        .as_deref()
-                        .unwrap_or("")
-                        .to_string(),
-                )
-            };
-        // Build TURN URLs with multiple transport variants.
-        // UDP TURN often fails behind NAT/firewalls, so include TCP and TLS
-        // fallbacks. Also emit `turn:HOST:443?transport=udp` because many
-        // corporate firewalls allow UDP/443 (QUIC) but block UDP/3478.
-        let mut urls: Vec<String> = vec![url.clone()];
-        if url.starts_with("turn:") && !url.contains("?transport=") {
-            let turn_443 = url.replace(":3478", ":443");
-            urls.push(format!("{}?transport=udp", turn_443));
-            urls.push(format!("{}?transport=tcp", url));
-            // Derive TURNS (TLS) URL on port 5349
-            let turns_url = url.replacen("turn:", "turns:", 1).replace(":3478", ":5349");
-            urls.push(format!("{}?transport=tcp", turns_url));
-        }
-        vec![serde_json::json!({
-            "urls": urls,
-            "username": turn_username,
-            "credential": turn_credential,
-        })]
-    } else {
-        vec![]
-    };
+    let runtime = state.runtime.load();
+    let ice_servers = super::turn_regions::media_ice_servers(
+        &runtime.turn,
+        &state.turn_region_stats,
+        &user_id.to_hex(),
+        client_country,
+    );
 
-    let force_relay = state.settings.turn.force_relay.unwrap_or(false);
+    let force_relay = runtime.turn.force_relay.unwrap_or(false);
 
     if force_relay {
         info!("force_relay=true — clients will use iceTransportPolicy='relay' via TURN server");
@@ -629,7 +868,7 @@ async fn handle_media_join(
         %connection_id,
         force_relay,
         announced_ip = %state.settings.mediasoup.announced_ip,
-        turn_url = ?state.settings.turn.url,
+        turn_url = ?runtime.turn.url,
         send_ice_candidates = %transport_pair.send_transport.ice_candidates,
         recv_ice_candidates = %transport_pair.recv_transport.ice_candidates,
         "media:join transport_created ICE diagnostics"
@@ -642,12 +881,29 @@ async fn handle_media_join(
             "recv_transport": transport_pair.recv_transport,
             "ice_servers": ice_servers,
             "force_relay": force_relay,
+            "e2ee": state.room_manager.key_epoch(&rid).is_some(),
+            "resume_token": transport_pair.resume_token,
+            "overflow": state.room_manager.overflow(&rid, connection_id),
         }
     });
     super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
 
-    let producers = state.room_manager.get_producer_ids(&rid, connection_id);
+    // New member: everyone (the joiner included) moves to a fresh key epoch.
+    super::e2ee::rotate_and_announce(state, &rid, "join").await;
+
+    replay_room_state(state, &rid, connection_id).await;
+    super::webinar::replay_to(state, &rid, user_id, connection_id).await;
+}
+
+/// Send a connection everyone else's producers as `media:new_producer`,
+/// followed by their camera effects, the room's audio state and any
+/// shared audio or announcement playing.
+pub(super) async fn replay_room_state(state: &AppState, rid: &ObjectId, connection_id: &str) {
+    let producers = state.room_manager.get_producer_ids(rid, connection_id);
     for (uid, conn_id, pid, kind, source) in producers {
+        if !receives(state, rid, connection_id, kind) {
+            continue;
+        }
         let msg = serde_json::json!({
             "type": "media:new_producer",
             "data": {
@@ -660,10 +916,27 @@ async fn handle_media_join(
         });
         super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
     }
+    super::effects::replay_to(state, rid, connection_id).await;
+    super::audio::replay_to(state, rid, connection_id).await;
+    super::playback::replay_to(state, rid, connection_id).await;
+    super::speech::replay_to(state, rid, connection_id).await;
+}
+
+/// Whether a connection takes media of `kind`: audio-only overflow
+/// participants aren't told about video producers.
+pub(super) fn receives(
+    state: &AppState,
+    rid: &ObjectId,
+    connection_id: &str,
+    kind: MediaKind,
+) -> bool {
+    kind == MediaKind::Audio
+        || state.room_manager.overflow(rid, connection_id) != Some(OverflowMode::AudioOnly)
 }
 
 async fn handle_media_connect_transport(
     state: &AppState,
+    user_id: &ObjectId,
     connection_id: &str,
     data: Option<&serde_json::Value>,
 ) {
@@ -693,12 +966,97 @@ async fn handle_media_connect_transport(
         Err(_) => return,
     };
 
-    if let Err(e) = state
+    match state
         .room_manager
         .connect_transport(&rid, connection_id, transport_id, dtls_parameters)
         .await
     {
-        warn!(%connection_id, %e, "connect_transport failed");
+        Ok(()) => state.call_debug.record(
+            rid,
+            *user_id,
+            connection_id,
+            CallDebugKind::TransportConnect,
+            transport_id,
+        ),
+        Err(e) => {
+            warn!(%connection_id, %e, "connect_transport failed");
+            state.call_debug.record(
+                rid,
+                *user_id,
+                connection_id,
+                CallDebugKind::Error,
+                format!("connect_transport failed: {}", e),
+            );
+        }
+    }
+}
+
+async fn handle_media_restart_ice(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let data = match data {
+        Some(d) => d,
+        None => {
+            send_media_error(state, user_id, "Missing data").await;
+            return;
+        }
+    };
+
+    let room_id_str = match data.get("room_id").and_then(|v| v.as_str()) {
+        Some(s) => s,
+        None => {
+            send_media_error(state, user_id, "Missing room_id").await;
+            return;
+        }
+    };
+    let transport_id = match data.get("transport_id").and_then(|v| v.as_str()) {
+        Some(s) => s,
+        None => {
+            send_media_error(state, user_id, "Missing transport_id").await;
+            return;
+        }
+    };
+    let rid = match ObjectId::parse_str(room_id_str) {
+        Ok(id) => id,
+        Err(_) => {
+            send_media_error(state, user_id, "Invalid room_id").await;
+            return;
+        }
+    };
+
+    match state
+        .room_manager
+        .restart_ice(&rid, connection_id, transport_id)
+        .await
+    {
+        Ok(ice_parameters) => {
+            state.call_debug.record(
+                rid,
+                *user_id,
+                connection_id,
+                CallDebugKind::IceRestart,
+                transport_id,
+            );
+            let msg = serde_json::json!({
+                "type": "media:ice_restarted",
+                "data": {
+                    "room_id": room_id_str,
+                    "transport_id": transport_id,
+                    "ice_parameters": ice_parameters,
+                }
+            });
+            super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+        }
+        Err(e) => {
+            let message = format!("restart_ice failed: {}", e);
+            state
+                .call_debug
+                .record(rid, *user_id, connection_id, CallDebugKind::Error, &message);
+            send_media_error(state, user_id, &message).await;
+        }
     }
 }
 
@@ -766,6 +1124,13 @@ async fn handle_media_produce(
         .await
     {
         Ok(producer_id) => {
+            state.call_debug.record(
+                rid,
+                *user_id,
+                connection_id,
+                CallDebugKind::Produce,
+                format!("{} producer {}", source, producer_id),
+            );
             let result_msg = serde_json::json!({
                 "type": "media:produce_result",
                 "data": { "id": producer_id.to_string() }
@@ -789,12 +1154,19 @@ async fn handle_media_produce(
                     }
                 });
                 for conn_id in &other_conns {
-                    super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event).await;
+                    if receives(state, &rid, conn_id, kind) {
+                        super::dispatcher::send_to_connection(&state.ws_storage, conn_id, &event)
+                            .await;
+                    }
                 }
             }
         }
         Err(e) => {
-            send_media_error(state, user_id, &format!("produce failed: {}", e)).await;
+            let message = format!("produce failed: {}", e);
+            state
+                .call_debug
+                .record(rid, *user_id, connection_id, CallDebugKind::Error, &message);
+            send_media_error(state, user_id, &message).await;
         }
     }
 }
@@ -860,6 +1232,16 @@ async fn handle_media_consume(
         .await
     {
         Ok(consumer_info) => {
+            state.call_debug.record(
+                rid,
+                *user_id,
+                connection_id,
+                CallDebugKind::Consume,
+                format!(
+                    "{} consumer {} of producer {}",
+                    consumer_info.kind, consumer_info.id, consumer_info.producer_id
+                ),
+            );
             let msg = serde_json::json!({
                 "type": "media:consumer_created",
                 "data": {
@@ -872,7 +1254,11 @@ async fn handle_media_consume(
             super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
         }
         Err(e) => {
-            send_media_error(state, user_id, &format!("consume failed: {}", e)).await;
+            let message = format!("consume failed: {}", e);
+            state
+                .call_debug
+                .record(rid, *user_id, connection_id, CallDebugKind::Error, &message);
+            send_media_error(state, user_id, &message).await;
         }
     }
 }
@@ -955,6 +1341,10 @@ async fn handle_media_leave(
         .get_other_connection_ids(&rid, connection_id);
 
     state.room_manager.close_participant(&rid, connection_id);
+    state
+        .call_debug
+        .record(rid, *user_id, connection_id, CallDebugKind::Leave, "Left");
+    super::e2ee::rotate_and_announce(state, &rid, "leave").await;
 
     if !other_conns.is_empty() {
         let event = serde_json::json!({
@@ -970,129 +1360,3 @@ async fn handle_media_leave(
         }
     }
 }
-
-async fn handle_play_audio(
-    state: &AppState,
-    _user_id: &ObjectId,
-    connection_id: &str,
-    data: Option<&serde_json::Value>,
-) {
-    let data = match data {
-        Some(d) => d,
-        None => return,
-    };
-
-    let room_id_str = match data.get("room_id").and_then(|v| v.as_str()) {
-        Some(s) => s,
-        None => return,
-    };
-    let file_id = match data.get("file_id").and_then(|v| v.as_str()) {
-        Some(s) => s,
-        None => return,
-    };
-
-    let rid = match ObjectId::parse_str(room_id_str) {
-        Ok(id) => id,
-        Err(_) => return,
-    };
-    let fid = match ObjectId::parse_str(file_id) {
-        Ok(id) => id,
-        Err(_) => return,
-    };
-
-    // Look up the room to get tenant_id
-    let room = match state.rooms.base.find_by_id(rid).await {
-        Ok(r) => r,
-        Err(e) => {
-            warn!(%e, "Failed to find room for file playback");
-            return;
-        }
-    };
-
-    let file = match state
-        .files
-        .base
-        .find_by_id_in_tenant(room.tenant_id, fid)
-        .await
-    {
-        Ok(f) => f,
-        Err(e) => {
-            warn!(%e, "Failed to find file for playback");
-            return;
-        }
-    };
-
-    let playback_id = String::new();
-
-    let file_url = format!(
-        "/api/tenant/{}/file/{}/download",
-        room.tenant_id.to_hex(),
-        fid.to_hex(),
-    );
-    let msg = serde_json::json!({
-        "type": "media:audio_playback",
-        "data": {
-            "action": "start",
-            "file_url": file_url,
-            "file_id": file_id,
-            "filename": file.filename,
-            "playback_id": playback_id,
-            "room_id": room_id_str,
-        }
-    });
-
-    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
-    let other_conns = state
-        .room_manager
-        .get_other_connection_ids(&rid, connection_id);
-    for cid in &other_conns {
-        super::dispatcher::send_to_connection(&state.ws_storage, cid, &msg).await;
-    }
-
-    info!(%rid, %file_id, %playback_id, "Audio playback started");
-}
-
-async fn handle_stop_audio(
-    state: &AppState,
-    _user_id: &ObjectId,
-    connection_id: &str,
-    data: Option<&serde_json::Value>,
-) {
-    let data = match data {
-        Some(d) => d,
-        None => return,
-    };
-
-    let room_id_str = match data.get("room_id").and_then(|v| v.as_str()) {
-        Some(s) => s,
-        None => return,
-    };
-    let playback_id = match data.get("playback_id").and_then(|v| v.as_str()) {
-        Some(s) => s,
-        None => return,
-    };
-
-    let rid = match ObjectId::parse_str(room_id_str) {
-        Ok(id) => id,
-        Err(_) => return,
-    };
-
-    let msg = serde_json::json!({
-        "type": "media:audio_playback",
-        "data": {
-            "action": "stop",
-            "playback_id": playback_id,
-            "room_id": room_id_str,
-        }
-    });
-
-    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
-    let other_conns = state
-        .room_manager
-        .get_other_connection_ids(&rid, connection_id);
-    for cid in &other_conns {
-        super::dispatcher::send_to_connection(&state.ws_storage, cid, &msg).await;
-    }
-
-    info!(%rid, %playback_id, "Audio playback stopped");
-}
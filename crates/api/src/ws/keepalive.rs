@@ -0,0 +1,202 @@
+//! Server-side WebSocket keepalive.
+//!
+//! A half-open TCP connection (laptop lid closed, NAT entry dropped) never
+//! delivers a Close, so without this the socket, and any media participant
+//! on it, lives until the kernel gives up on TCP. [`Keepalive`] pings a
+//! connection after `ws.ping_interval_secs` of inbound silence and drops it
+//! when nothing arrives within `ws.pong_timeout_secs`, or when the client
+//! sends no message for `ws.idle_timeout_secs`. Dropped connections are
+//! counted in [`WsHealthStats`], along with the slow consumers dropped by
+//! [`super::outbound`].
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use roomler_ai_config::WsSettings;
+use tokio::time::Instant;
+
+/// Why a connection was dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReapReason {
+    /// A ping went unanswered.
+    MissedPong,
+    /// The client stopped sending messages.
+    Idle,
+    /// The client fell `ws.outbound_queue_size` messages behind.
+    QueueOverflow,
+    /// A write to the client took longer than `ws.send_timeout_secs`.
+    SendStalled,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Action {
+    Wait,
+    Ping,
+    Reap(ReapReason),
+}
+
+/// Liveness bookkeeping for one connection.
+pub struct Keepalive {
+    ping_interval: Duration,
+    pong_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    last_frame: Instant,
+    last_message: Instant,
+    ping_sent: Option<Instant>,
+}
+
+impl Keepalive {
+    pub fn new(settings: &WsSettings, now: Instant) -> Self {
+        Self {
+            ping_interval: Duration::from_secs(settings.ping_interval_secs.max(1)),
+            pong_timeout: Duration::from_secs(settings.pong_timeout_secs.max(1)),
+            idle_timeout: (settings.idle_timeout_secs > 0)
+                .then(|| Duration::from_secs(settings.idle_timeout_secs)),
+            last_frame: now,
+            last_message: now,
+            ping_sent: None,
+        }
+    }
+
+    /// How often [`Keepalive::poll`] should run.
+    pub fn tick_period(&self) -> Duration {
+        self.ping_interval.min(self.pong_timeout) / 2
+    }
+
+    /// Any inbound frame proves the peer is alive; only text and binary
+    /// frames count as the client being active.
+    pub fn on_frame(&mut self, now: Instant, is_message: bool) {
+        self.last_frame = now;
+        self.ping_sent = None;
+        if is_message {
+            self.last_message = now;
+        }
+    }
+
+    pub fn poll(&mut self, now: Instant) -> Action {
+        if let Some(idle) = self.idle_timeout
+            && now.duration_since(self.last_message) >= idle
+        {
+            return Action::Reap(ReapReason::Idle);
+        }
+        match self.ping_sent {
+            Some(sent) if now.duration_since(sent) >= self.pong_timeout => {
+                Action::Reap(ReapReason::MissedPong)
+            }
+            Some(_) => Action::Wait,
+            None if now.duration_since(self.last_frame) >= self.ping_interval => {
+                self.ping_sent = Some(now);
+                Action::Ping
+            }
+            None => Action::Wait,
+        }
+    }
+}
+
+/// Per-pod counters of dropped connections and dropped or coalesced
+/// outbound events.
+#[derive(Default)]
+pub struct WsHealthStats {
+    reaped_missed_pong: AtomicU64,
+    reaped_idle: AtomicU64,
+    reaped_queue_overflow: AtomicU64,
+    reaped_send_stalled: AtomicU64,
+    dropped_messages: AtomicU64,
+    coalesced_messages: AtomicU64,
+}
+
+/// A point-in-time read of [`WsHealthStats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WsHealthSnapshot {
+    pub reaped_missed_pong: u64,
+    pub reaped_idle: u64,
+    pub reaped_queue_overflow: u64,
+    pub reaped_send_stalled: u64,
+    pub dropped_messages: u64,
+    pub coalesced_messages: u64,
+}
+
+impl WsHealthStats {
+    pub fn record_reaped(&self, reason: ReapReason) {
+        let counter = match reason {
+            ReapReason::MissedPong => &self.reaped_missed_pong,
+            ReapReason::Idle => &self.reaped_idle,
+            ReapReason::QueueOverflow => &self.reaped_queue_overflow,
+            ReapReason::SendStalled => &self.reaped_send_stalled,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A high-frequency event dropped from a full outbound queue.
+    pub fn record_dropped(&self) {
+        self.dropped_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A queued event replaced by a newer one that supersedes it.
+    pub fn record_coalesced(&self) {
+        self.coalesced_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> WsHealthSnapshot {
+        WsHealthSnapshot {
+            reaped_missed_pong: self.reaped_missed_pong.load(Ordering::Relaxed),
+            reaped_idle: self.reaped_idle.load(Ordering::Relaxed),
+            reaped_queue_overflow: self.reaped_queue_overflow.load(Ordering::Relaxed),
+            reaped_send_stalled: self.reaped_send_stalled.load(Ordering::Relaxed),
+            dropped_messages: self.dropped_messages.load(Ordering::Relaxed),
+            coalesced_messages: self.coalesced_messages.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(ping: u64, pong: u64, idle: u64) -> WsSettings {
+        WsSettings {
+            ping_interval_secs: ping,
+            pong_timeout_secs: pong,
+            idle_timeout_secs: idle,
+            ..WsSettings::default()
+        }
+    }
+
+    #[test]
+    fn pings_after_silence_and_reaps_without_answer() {
+        let t0 = Instant::now();
+        let mut k = Keepalive::new(&settings(20, 10, 0), t0);
+        assert_eq!(k.poll(t0 + Duration::from_secs(19)), Action::Wait);
+        assert_eq!(k.poll(t0 + Duration::from_secs(20)), Action::Ping);
+        assert_eq!(k.poll(t0 + Duration::from_secs(25)), Action::Wait);
+        assert_eq!(
+            k.poll(t0 + Duration::from_secs(30)),
+            Action::Reap(ReapReason::MissedPong)
+        );
+    }
+
+    #[test]
+    fn any_frame_answers_a_ping() {
+        let t0 = Instant::now();
+        let mut k = Keepalive::new(&settings(20, 10, 0), t0);
+        assert_eq!(k.poll(t0 + Duration::from_secs(20)), Action::Ping);
+        k.on_frame(t0 + Duration::from_secs(21), false);
+        assert_eq!(k.poll(t0 + Duration::from_secs(35)), Action::Wait);
+        assert_eq!(k.poll(t0 + Duration::from_secs(41)), Action::Ping);
+    }
+
+    #[test]
+    fn reaps_idle_clients_even_if_they_answer_pings() {
+        let t0 = Instant::now();
+        let mut k = Keepalive::new(&settings(20, 10, 60), t0);
+        k.on_frame(t0 + Duration::from_secs(50), false);
+        assert_eq!(
+            k.poll(t0 + Duration::from_secs(60)),
+            Action::Reap(ReapReason::Idle)
+        );
+
+        let mut k = Keepalive::new(&settings(20, 10, 60), t0);
+        k.on_frame(t0 + Duration::from_secs(50), true);
+        assert_eq!(k.poll(t0 + Duration::from_secs(60)), Action::Wait);
+    }
+}
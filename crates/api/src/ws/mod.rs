@@ -1,8 +1,30 @@
+pub mod audio;
+pub mod bandwidth;
+pub mod call_debug;
+pub mod conference_registry;
 pub mod derp;
 pub mod dispatcher;
+pub mod e2ee;
+pub mod edge;
+pub mod effects;
+pub mod event_log;
 pub mod handler;
+pub mod keepalive;
+pub mod outbound;
 pub mod overlay;
+pub mod playback;
+pub mod presence;
+pub mod quality;
+pub mod reconnect;
 pub mod redis_pubsub;
 pub mod remote_control;
+pub mod ring;
+pub mod speech;
 pub mod storage;
+pub mod tenant_scope;
+pub mod test_call;
+pub mod ticket;
 pub mod tunnel;
+pub mod turn_regions;
+pub mod webinar;
+pub mod whiteboard;
@@ -0,0 +1,392 @@
+//! Per-connection outbound queue.
+//!
+//! Senders never touch the socket: [`Outbound::push`] appends to a bounded
+//! queue and returns, and one writer task per connection
+//! ([`spawn_writer`]) drains it into the sink. A slow client therefore only
+//! backs up its own queue, never a broadcast loop over a whole room.
+//!
+//! When the queue holds `ws.outbound_queue_size` messages, the oldest
+//! high-frequency event still queued (see [`classify`]) makes room for the
+//! new one; those are superseded by the next update anyway. A new
+//! high-frequency event is dropped if nothing older can go. Anything else
+//! overflowing means the client has fallen behind for good: the queue is
+//! discarded and the writer closes the socket with code
+//! [`SLOW_CONSUMER_CODE`]. A single write that takes longer than
+//! `ws.send_timeout_secs` drops the connection too. Both are counted in
+//! [`WsHealthStats`], with the dropped events.
+//!
+//! A partial `media:transcript` replaces the queued partial of the same
+//! `segment_id` instead of queueing behind it. A connection opened with
+//! `batch=1` gets events as JSON array frames: the writer sends whatever
+//! text is queued in one frame, and after a high-frequency event first
+//! waits `ws.batch_window_ms` for more to arrive.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use axum::extract::ws::{CloseFrame, Message, Utf8Bytes, WebSocket};
+use futures::SinkExt;
+use futures::stream::SplitSink;
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+use super::keepalive::{ReapReason, WsHealthStats};
+
+/// Close code sent to a client dropped for not keeping up.
+pub const SLOW_CONSUMER_CODE: u16 = 4008;
+
+/// Most events in one batch frame.
+const MAX_BATCH: usize = 64;
+
+/// Event types sent often enough that a newer one makes the older
+/// redundant.
+const DROPPABLE: &[&str] = &[
+    "media:connection_quality",
+    "media:webinar_counts",
+    "typing:start",
+    "typing:stop",
+];
+
+/// How the queue treats a message.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Class {
+    /// May be dropped when the connection falls behind, and opens a batch
+    /// window.
+    pub droppable: bool,
+    /// Replaces a queued message with the same key.
+    pub supersedes: Option<String>,
+}
+
+/// Classify a JSON event.
+pub fn classify(message: &serde_json::Value) -> Class {
+    let msg_type = message.get("type").and_then(|t| t.as_str());
+    if msg_type == Some("media:transcript") {
+        let data = &message["data"];
+        let partial = data.get("is_final").and_then(|f| f.as_bool()) == Some(false);
+        let segment = data.get("segment_id").and_then(|s| s.as_str());
+        return match segment {
+            Some(segment) if partial => Class {
+                droppable: true,
+                supersedes: Some(format!("transcript:{}", segment)),
+            },
+            _ => Class::default(),
+        };
+    }
+    Class {
+        droppable: msg_type.is_some_and(|t| DROPPABLE.contains(&t)),
+        supersedes: None,
+    }
+}
+
+struct Item {
+    message: Message,
+    class: Class,
+}
+
+#[derive(Default)]
+struct Queue {
+    items: VecDeque<Item>,
+    closed: Option<Closed>,
+}
+
+#[derive(Clone, Copy)]
+enum Closed {
+    Overflow,
+    Done,
+}
+
+enum Next {
+    Send(Item),
+    Overflow,
+    Done,
+}
+
+/// The outbound side of one connection.
+pub struct Outbound {
+    queue: Mutex<Queue>,
+    ready: Notify,
+    capacity: usize,
+    stats: Arc<WsHealthStats>,
+}
+
+impl Outbound {
+    pub fn new(capacity: usize, stats: Arc<WsHealthStats>) -> Self {
+        Self {
+            queue: Mutex::new(Queue::default()),
+            ready: Notify::new(),
+            capacity: capacity.max(1),
+            stats,
+        }
+    }
+
+    /// Queue a message for the writer. Never waits on the client.
+    pub fn push(&self, message: Message, class: Class) {
+        let mut queue = self.queue.lock().unwrap();
+        if queue.closed.is_some() {
+            return;
+        }
+        if let Some(key) = &class.supersedes
+            && let Some(stale) = queue
+                .items
+                .iter_mut()
+                .find(|item| item.class.supersedes.as_ref() == Some(key))
+        {
+            stale.message = message;
+            self.stats.record_coalesced();
+            return;
+        }
+        if queue.items.len() >= self.capacity {
+            if let Some(oldest) = queue.items.iter().position(|item| item.class.droppable) {
+                queue.items.remove(oldest);
+                self.stats.record_dropped();
+            } else if class.droppable {
+                self.stats.record_dropped();
+                return;
+            } else {
+                queue.items.clear();
+                queue.closed = Some(Closed::Overflow);
+                drop(queue);
+                self.ready.notify_one();
+                return;
+            }
+        }
+        queue.items.push_back(Item { message, class });
+        drop(queue);
+        self.ready.notify_one();
+    }
+
+    /// Queue a JSON event.
+    pub fn push_json(&self, message: &serde_json::Value) {
+        let text = serde_json::to_string(message).unwrap_or_default();
+        self.push(Message::text(text), classify(message));
+    }
+
+    /// Stop the writer; anything still queued is discarded.
+    pub fn close(&self) {
+        let mut queue = self.queue.lock().unwrap();
+        queue.closed.get_or_insert(Closed::Done);
+        queue.items.clear();
+        drop(queue);
+        self.ready.notify_one();
+    }
+
+    /// Messages waiting to be written.
+    pub fn depth(&self) -> usize {
+        self.queue.lock().unwrap().items.len()
+    }
+
+    async fn next(&self) -> Next {
+        loop {
+            {
+                let mut queue = self.queue.lock().unwrap();
+                match queue.closed {
+                    Some(Closed::Overflow) => return Next::Overflow,
+                    Some(Closed::Done) => return Next::Done,
+                    None => {}
+                }
+                if let Some(item) = queue.items.pop_front() {
+                    return Next::Send(item);
+                }
+            }
+            // `notify_one` keeps a permit when nobody waits, so a push
+            // between the check above and here isn't lost.
+            self.ready.notified().await;
+        }
+    }
+
+    /// Take up to `max` text messages from the front of the queue.
+    fn take_text(&self, max: usize) -> Vec<Utf8Bytes> {
+        let mut queue = self.queue.lock().unwrap();
+        let mut texts = Vec::new();
+        while texts.len() < max
+            && let Some(Item {
+                message: Message::Text(_),
+                ..
+            }) = queue.items.front()
+        {
+            if let Some(Item {
+                message: Message::Text(text),
+                ..
+            }) = queue.items.pop_front()
+            {
+                texts.push(text);
+            }
+        }
+        texts
+    }
+}
+
+/// Join JSON texts into one array frame.
+fn batch_frame(texts: &[Utf8Bytes]) -> Message {
+    let mut frame = String::with_capacity(texts.iter().map(|t| t.len() + 1).sum::<usize>() + 1);
+    frame.push('[');
+    for (i, text) in texts.iter().enumerate() {
+        if i > 0 {
+            frame.push(',');
+        }
+        frame.push_str(text.as_str());
+    }
+    frame.push(']');
+    Message::text(frame)
+}
+
+/// Drain `outbound` into the socket until it is closed or the client is
+/// dropped, batching text frames when `batch_window` is set. Resolves to
+/// the reason when the client was dropped for being slow; `None` when the
+/// connection ended otherwise.
+pub fn spawn_writer(
+    mut sink: SplitSink<WebSocket, Message>,
+    outbound: Arc<Outbound>,
+    send_timeout: Duration,
+    batch_window: Option<Duration>,
+) -> JoinHandle<Option<ReapReason>> {
+    tokio::spawn(async move {
+        loop {
+            match outbound.next().await {
+                Next::Send(item) => {
+                    let message = match (batch_window, item.message) {
+                        (Some(window), Message::Text(first)) => {
+                            if item.class.droppable {
+                                tokio::time::sleep(window).await;
+                            }
+                            let mut texts = vec![first];
+                            texts.extend(outbound.take_text(MAX_BATCH - 1));
+                            batch_frame(&texts)
+                        }
+                        (_, message) => message,
+                    };
+                    match tokio::time::timeout(send_timeout, sink.send(message)).await {
+                        Ok(Ok(())) => {}
+                        // The reader sees the broken socket too.
+                        Ok(Err(_)) => return None,
+                        Err(_) => return Some(ReapReason::SendStalled),
+                    }
+                }
+                Next::Overflow => {
+                    let close = Message::Close(Some(CloseFrame {
+                        code: SLOW_CONSUMER_CODE,
+                        reason: "slow consumer".into(),
+                    }));
+                    let _ = tokio::time::timeout(send_timeout, sink.send(close)).await;
+                    return Some(ReapReason::QueueOverflow);
+                }
+                Next::Done => return None,
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn regular() -> Class {
+        Class::default()
+    }
+
+    fn frequent() -> Class {
+        Class {
+            droppable: true,
+            supersedes: None,
+        }
+    }
+
+    fn queued(outbound: &Outbound) -> Vec<String> {
+        let queue = outbound.queue.lock().unwrap();
+        queue
+            .items
+            .iter()
+            .map(|item| item.message.to_text().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn full_queue_drops_the_oldest_high_frequency_event() {
+        let stats = Arc::new(WsHealthStats::default());
+        let outbound = Outbound::new(3, stats.clone());
+        outbound.push(Message::text("a"), regular());
+        outbound.push(Message::text("q1"), frequent());
+        outbound.push(Message::text("q2"), frequent());
+        outbound.push(Message::text("b"), regular());
+        assert_eq!(queued(&outbound), ["a", "q2", "b"]);
+        outbound.push(Message::text("q3"), frequent());
+        assert_eq!(queued(&outbound), ["a", "b", "q3"]);
+        assert_eq!(stats.snapshot().dropped_messages, 2);
+    }
+
+    #[test]
+    fn full_queue_of_regular_events_overflows() {
+        let stats = Arc::new(WsHealthStats::default());
+        let outbound = Outbound::new(2, stats.clone());
+        outbound.push(Message::text("a"), regular());
+        outbound.push(Message::text("b"), regular());
+        // Nothing older can go, so the new quality update is dropped.
+        outbound.push(Message::text("q"), frequent());
+        assert_eq!(queued(&outbound), ["a", "b"]);
+        outbound.push(Message::text("c"), regular());
+        assert_eq!(outbound.depth(), 0);
+        assert!(matches!(
+            outbound.queue.lock().unwrap().closed,
+            Some(Closed::Overflow)
+        ));
+        // A closed queue takes nothing more.
+        outbound.push(Message::text("d"), regular());
+        assert_eq!(outbound.depth(), 0);
+        assert_eq!(stats.snapshot().dropped_messages, 1);
+    }
+
+    #[test]
+    fn partial_transcripts_replace_their_queued_predecessor() {
+        let stats = Arc::new(WsHealthStats::default());
+        let outbound = Outbound::new(8, stats.clone());
+        let transcript = |segment: &str, text: &str, is_final: bool| {
+            serde_json::json!({
+                "type": "media:transcript",
+                "data": { "segment_id": segment, "text": text, "is_final": is_final }
+            })
+        };
+        outbound.push_json(&transcript("s1", "hel", false));
+        outbound.push_json(&transcript("s2", "yes", false));
+        outbound.push_json(&transcript("s1", "hello", false));
+        outbound.push_json(&transcript("s1", "hello there", true));
+        let texts: Vec<String> = queued(&outbound)
+            .iter()
+            .map(|t| {
+                serde_json::from_str::<serde_json::Value>(t).unwrap()["data"]["text"].to_string()
+            })
+            .collect();
+        assert_eq!(texts, ["\"hello\"", "\"yes\"", "\"hello there\""]);
+        assert_eq!(stats.snapshot().coalesced_messages, 1);
+    }
+
+    #[test]
+    fn batches_leading_text_frames() {
+        let outbound = Outbound::new(8, Arc::new(WsHealthStats::default()));
+        outbound.push_json(&serde_json::json!({ "type": "a" }));
+        outbound.push_json(&serde_json::json!({ "type": "b" }));
+        outbound.push(Message::Ping(Default::default()), regular());
+        outbound.push_json(&serde_json::json!({ "type": "c" }));
+        let texts = outbound.take_text(MAX_BATCH);
+        assert_eq!(
+            batch_frame(&texts).to_text().unwrap(),
+            r#"[{"type":"a"},{"type":"b"}]"#
+        );
+        assert_eq!(outbound.depth(), 2);
+    }
+
+    #[test]
+    fn classifies_events() {
+        assert!(classify(&serde_json::json!({ "type": "media:connection_quality" })).droppable);
+        assert_eq!(
+            classify(&serde_json::json!({ "type": "message:create" })),
+            regular()
+        );
+        assert_eq!(classify(&serde_json::json!({ "data": {} })), regular());
+        let final_transcript = serde_json::json!({
+            "type": "media:transcript",
+            "data": { "segment_id": "s1", "is_final": true }
+        });
+        assert_eq!(classify(&final_transcript), regular());
+    }
+}
@@ -957,7 +957,7 @@ fn next_epoch() -> u64 {
 /// to the hostname-based servers (pre-fix behaviour) with no TURN config or on
 /// DNS failure.
 async fn overlay_ice_servers(state: &AppState, pair_key: &str) -> Vec<IceServer> {
-    let Some(turn_cfg) = build_turn_config(&state.settings.turn) else {
+    let Some(turn_cfg) = build_turn_config(&state.runtime.load().turn) else {
         return turn_creds::ice_servers_for(pair_key, None);
     };
     let servers = turn_creds::ice_servers_for(pair_key, Some(&turn_cfg));
@@ -1030,7 +1030,7 @@ fn turn_url_host_port(u: &str) -> Option<String> {
 /// `?transport=tcp` variants are skipped — plain STUN is UDP. Deduped. Empty
 /// when TURN isn't configured (dev), which leaves the srflx tier inert.
 fn stun_urls_from_turn(state: &AppState) -> Vec<String> {
-    match build_turn_config(&state.settings.turn) {
+    match build_turn_config(&state.runtime.load().turn) {
         Some(cfg) => stun_urls_from_turn_urls(&cfg.urls),
         None => Vec::new(),
     }
@@ -0,0 +1,447 @@
+//! Shared audio playback in a call: intro music, a language lesson's
+//! recording. `media:play_audio { room_id, file_id, volume?, ducking? }`
+//! plays the file into the media room as a server-side producer (see
+//! `roomler_ai_services::media::file_audio`), so everyone consumes the same
+//! stream in sync and recordings pick it up. The gain is applied to the
+//! stream itself, so everyone, recordings included, hears the file at the
+//! same level.
+//!
+//! With `ducking` on (the default) the music drops to [`DUCK_GAIN`] of its
+//! volume while anyone in the call is heard and comes back once everyone
+//! is quiet. Speech is what the router's audio level observer hears on the
+//! participants' audio producers (see
+//! [`RoomManager::observe_speech`](roomler_ai_services::media::room_manager::RoomManager::observe_speech)).
+//! `media:set_playback_volume { room_id, playback_id, volume, ducking? }`
+//! changes it while it plays. Every change goes out as
+//! `media:audio_playback { action: "gain", .., gain }` for the clients to
+//! show.
+//!
+//! One playback runs per room; starting another replaces it. It ends with
+//! `media:audio_playback { action: "stop", .., reason }` when stopped, when
+//! the file is over, or when it can't be played. Like the media room, it
+//! lives in memory on the pod hosting the call.
+
+use bson::oid::ObjectId;
+use mediasoup::prelude::MediaKind;
+use roomler_ai_services::media::file_audio;
+use tokio::sync::{mpsc, watch};
+use tokio::task::AbortHandle;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Share of its volume the music keeps while someone speaks.
+pub const DUCK_GAIN: f64 = 0.2;
+
+/// The room's current playback.
+pub struct Playback {
+    playback_id: String,
+    /// The server-side producer playing the file.
+    producer_id: String,
+    file_id: ObjectId,
+    filename: String,
+    /// 0.0 to 1.0.
+    volume: f64,
+    ducking: bool,
+    /// Someone in the room is heard.
+    heard: bool,
+    /// The gain the stream plays at.
+    gain: watch::Sender<f64>,
+    /// The task feeding the producer; stopped with the playback.
+    pump: Option<AbortHandle>,
+}
+
+impl Playback {
+    fn ducked(&self) -> bool {
+        self.ducking && self.heard
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        if let Some(pump) = self.pump.take() {
+            pump.abort();
+        }
+    }
+}
+
+fn gain(volume: f64, ducked: bool) -> f64 {
+    if ducked { volume * DUCK_GAIN } else { volume }
+}
+
+/// A volume from the client, clamped to 0.0-1.0.
+fn parse_volume(data: &serde_json::Value) -> Option<f64> {
+    data.get("volume")
+        .and_then(|v| v.as_f64())
+        .filter(|v| v.is_finite())
+        .map(|v| v.clamp(0.0, 1.0))
+}
+
+fn room_id(data: &serde_json::Value) -> Option<ObjectId> {
+    data.get("room_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+}
+
+/// Send `msg` to `connection_id` and every other connection in the media
+/// room.
+async fn send_to_room(
+    state: &AppState,
+    room_id: &ObjectId,
+    connection_id: &str,
+    msg: &serde_json::Value,
+) {
+    if !connection_id.is_empty() {
+        super::dispatcher::send_to_connection(&state.ws_storage, connection_id, msg).await;
+    }
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(room_id, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, msg).await;
+    }
+}
+
+fn gain_event(room_id: &ObjectId, playback: &Playback) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:audio_playback",
+        "data": {
+            "action": "gain",
+            "room_id": room_id.to_hex(),
+            "playback_id": playback.playback_id,
+            "volume": playback.volume,
+            "ducking": playback.ducking,
+            "ducked": playback.ducked(),
+            "gain": *playback.gain.borrow(),
+        }
+    })
+}
+
+fn start_event(room_id: &ObjectId, playback: &Playback) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:audio_playback",
+        "data": {
+            "action": "start",
+            "room_id": room_id.to_hex(),
+            "playback_id": playback.playback_id,
+            "producer_id": playback.producer_id,
+            "file_id": playback.file_id.to_hex(),
+            "filename": playback.filename,
+            "volume": playback.volume,
+            "ducking": playback.ducking,
+            "ducked": playback.ducked(),
+            "gain": *playback.gain.borrow(),
+        }
+    })
+}
+
+fn stop_event(room_id: &ObjectId, playback_id: &str, reason: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:audio_playback",
+        "data": {
+            "action": "stop",
+            "room_id": room_id.to_hex(),
+            "playback_id": playback_id,
+            "reason": reason,
+        }
+    })
+}
+
+/// Take the room's playback if it is `playback_id`, closing its producer.
+fn take(state: &AppState, room_id: &ObjectId, playback_id: &str) -> Option<Playback> {
+    let (_, playback) = state
+        .playbacks
+        .remove_if(room_id, |_, p| p.playback_id == playback_id)?;
+    state
+        .room_manager
+        .remove_injected(room_id, &playback.producer_id);
+    Some(playback)
+}
+
+/// Stop the room's playback, if any, without telling the clients; for
+/// when the call ends.
+pub fn cancel(state: &AppState, room_id: &ObjectId) {
+    if let Some((_, playback)) = state.playbacks.remove(room_id) {
+        state
+            .room_manager
+            .remove_injected(room_id, &playback.producer_id);
+    }
+}
+
+/// Work out the playback's gain again and hand it to the stream. Returns
+/// the event to send if it changed, or always when `force`d.
+fn regain(state: &AppState, room_id: &ObjectId, force: bool) -> Option<serde_json::Value> {
+    let playback = state.playbacks.get(room_id)?;
+    let new_gain = gain(playback.volume, playback.ducked());
+    let changed = playback.gain.send_if_modified(|g| {
+        let changed = *g != new_gain;
+        *g = new_gain;
+        changed
+    });
+    (changed || force).then(|| gain_event(room_id, &playback))
+}
+
+/// `media:play_audio`: play a tenant file to the whole call.
+pub async fn handle_play_audio(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = room_id(data) else {
+        return;
+    };
+    let Some(fid) = data
+        .get("file_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+    else {
+        return;
+    };
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return;
+    }
+
+    // Look up the room to get tenant_id, then hold the user to it
+    let room = match state.rooms.base.find_by_id_unscoped(rid).await {
+        Ok(r) => r,
+        Err(e) => {
+            warn!(%e, "Failed to find room for file playback");
+            return;
+        }
+    };
+    if !state
+        .tenants
+        .is_member(room.tenant_id, *user_id)
+        .await
+        .unwrap_or(false)
+    {
+        return;
+    }
+
+    let file = match state
+        .files
+        .base
+        .find_by_id_in_tenant(room.tenant_id, fid)
+        .await
+    {
+        Ok(f) => f,
+        Err(e) => {
+            warn!(%e, "Failed to find file for playback");
+            return;
+        }
+    };
+    // Only what the user could download themselves: not deleted, not
+    // quarantined, and from a room they belong to
+    if file.deleted_at.is_some() || crate::routes::file::require_downloadable(&file).is_err() {
+        return;
+    }
+    if let Some(file_room) = file.context.room_id {
+        match state
+            .rooms
+            .find_membership(room.tenant_id, file_room, *user_id)
+            .await
+        {
+            Ok(Some(_)) => {}
+            Ok(None) => return,
+            Err(e) => {
+                warn!(%e, "Failed to check file room membership for playback");
+                return;
+            }
+        }
+    }
+
+    let (ssrc, rtp_parameters) = match file_audio::rtp_parameters() {
+        Ok(p) => p,
+        Err(e) => {
+            warn!(%e, "Failed to build playback RTP parameters");
+            return;
+        }
+    };
+    let (producer_id, tx) = match state
+        .room_manager
+        .inject_producer(&rid, MediaKind::Audio, rtp_parameters)
+        .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            warn!(%rid, %e, "Failed to create playback producer");
+            return;
+        }
+    };
+
+    let volume = parse_volume(data).unwrap_or(1.0);
+    let ducking = data
+        .get("ducking")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(true);
+    let heard = state.room_manager.is_speech_heard(&rid);
+    let (gain_tx, gain_rx) = watch::channel(gain(volume, ducking && heard));
+    let playback = Playback {
+        playback_id: uuid::Uuid::new_v4().to_string(),
+        producer_id: producer_id.to_string(),
+        file_id: fid,
+        filename: file.filename.clone(),
+        volume,
+        ducking,
+        heard,
+        gain: gain_tx,
+        pump: None,
+    };
+    let msg = start_event(&rid, &playback);
+    let playback_id = playback.playback_id.clone();
+    if let Some(replaced) = state.playbacks.insert(rid, playback) {
+        state
+            .room_manager
+            .remove_injected(&rid, &replaced.producer_id);
+    }
+    send_to_room(state, &rid, connection_id, &msg).await;
+
+    let pump = {
+        let state = state.clone();
+        let ffmpeg = state.settings.mediasoup.ffmpeg_path.clone();
+        let source =
+            file_audio::Source::File(crate::routes::file::upload_dir().join(&file.storage_key));
+        let playback_id = playback_id.clone();
+        tokio::spawn(async move {
+            let reason = match file_audio::stream(&ffmpeg, source, ssrc, gain_rx, tx).await {
+                Ok(true) => "ended",
+                // Nothing takes the RTP: the producer is gone
+                Ok(false) => return,
+                Err(e) => {
+                    warn!(%rid, %playback_id, %e, "Audio playback failed");
+                    "failed"
+                }
+            };
+            if let Some(mut playback) = take(&state, &rid, &playback_id) {
+                // This task is the pump; don't abort it before the event is out
+                playback.pump = None;
+                drop(playback);
+                send_to_room(&state, &rid, "", &stop_event(&rid, &playback_id, reason)).await;
+                info!(%rid, %playback_id, reason, "Audio playback finished");
+            }
+        })
+        .abort_handle()
+    };
+    match state.playbacks.get_mut(&rid) {
+        Some(mut playback) if playback.playback_id == playback_id => playback.pump = Some(pump),
+        // Already over
+        _ => pump.abort(),
+    }
+    info!(%rid, %fid, %playback_id, %producer_id, "Audio playback started");
+}
+
+/// Send a connection joining the media room the playback in progress.
+pub async fn replay_to(state: &AppState, room_id: &ObjectId, connection_id: &str) {
+    let msg = match state.playbacks.get(room_id) {
+        Some(playback) => start_event(room_id, &playback),
+        None => return,
+    };
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// `media:stop_audio`: stop the room's playback.
+pub async fn handle_stop_audio(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = room_id(data) else {
+        return;
+    };
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return;
+    }
+    let Some(playback_id) = data.get("playback_id").and_then(|v| v.as_str()) else {
+        return;
+    };
+    if take(state, &rid, playback_id).is_none() {
+        return;
+    }
+
+    let msg = stop_event(&rid, playback_id, "stopped");
+    send_to_room(state, &rid, connection_id, &msg).await;
+    info!(%rid, %playback_id, "Audio playback stopped");
+}
+
+/// `media:set_playback_volume`: change the playback's volume, and
+/// optionally turn ducking on or off.
+pub async fn handle_set_volume(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = room_id(data) else {
+        return;
+    };
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return;
+    }
+    let playback_id = data.get("playback_id").and_then(|v| v.as_str());
+    {
+        let Some(mut playback) = state.playbacks.get_mut(&rid) else {
+            return;
+        };
+        if playback_id != Some(playback.playback_id.as_str()) {
+            return;
+        }
+        if let Some(volume) = parse_volume(data) {
+            playback.volume = volume;
+        }
+        if let Some(ducking) = data.get("ducking").and_then(|v| v.as_bool()) {
+            playback.ducking = ducking;
+        }
+    }
+    if let Some(msg) = regain(state, &rid, true) {
+        send_to_room(state, &rid, "", &msg).await;
+    }
+}
+
+/// Duck and restore the rooms' playbacks as speech starts and stops, as
+/// the routers' audio level observers hear it.
+pub fn spawn_ducking(state: AppState) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    state.room_manager.observe_speech(move |room_id, heard| {
+        let _ = tx.send((room_id, heard));
+    });
+    tokio::spawn(async move {
+        while let Some((room_id, heard)) = rx.recv().await {
+            match state.playbacks.get_mut(&room_id) {
+                Some(mut playback) => playback.heard = heard,
+                None => continue,
+            }
+            if let Some(msg) = regain(&state, &room_id, false) {
+                send_to_room(&state, &room_id, "", &msg).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speech_ducks_the_music() {
+        assert_eq!(gain(0.5, false), 0.5);
+        assert!((gain(0.5, true) - 0.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn volumes_are_clamped() {
+        let volume = |v: serde_json::Value| parse_volume(&serde_json::json!({ "volume": v }));
+        assert_eq!(volume(serde_json::json!(0.3)), Some(0.3));
+        assert_eq!(volume(serde_json::json!(7)), Some(1.0));
+        assert_eq!(volume(serde_json::json!(-1)), Some(0.0));
+        assert_eq!(volume(serde_json::json!("loud")), None);
+    }
+}
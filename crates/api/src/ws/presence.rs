@@ -0,0 +1,423 @@
+//! Room-scoped presence and typing indicators.
+//!
+//! A connection watches the presence of a room's members by sending
+//! `presence:subscribe { room_id }` when it opens the room (and
+//! `presence:unsubscribe` when it leaves). It gets a `presence:snapshot` of
+//! the members who are online, idle or dnd and who is typing, and from then
+//! on a `presence:update` whenever one of them changes; users never hear
+//! about anyone they don't share a watched room with.
+//!
+//! A user's presence is kept in the `users` collection (so snapshots see
+//! users on every pod) and leased for `ws.presence_ttl_secs`: any message
+//! from the user renews the lease, and when it runs out they go offline.
+//! Typing indicators lapse the same way: `typing:start` is relayed once and
+//! a `typing:stop` follows after [`TYPING_TTL`] unless the client renews or
+//! stops it first.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+use roomler_ai_db::models::Presence;
+use tokio::time::Instant;
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// How long a `typing:start` lasts without being renewed.
+pub const TYPING_TTL: Duration = Duration::from_secs(6);
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct RoomWatch {
+    /// The room's members when the latest watcher subscribed.
+    members: HashSet<ObjectId>,
+    /// Watching connection → its user.
+    connections: HashMap<String, ObjectId>,
+}
+
+/// This pod's presence subscriptions, leases and typing indicators.
+#[derive(Default)]
+pub struct PresenceHub {
+    rooms: DashMap<ObjectId, RoomWatch>,
+    /// Rooms each connection watches, for cleanup on disconnect.
+    by_connection: DashMap<String, HashSet<ObjectId>>,
+    /// When each user's presence lapses.
+    leases: DashMap<ObjectId, Instant>,
+    /// When each (room, user) typing indicator lapses.
+    typing: DashMap<(ObjectId, ObjectId), Instant>,
+}
+
+impl PresenceHub {
+    pub fn subscribe(
+        &self,
+        room_id: ObjectId,
+        connection_id: &str,
+        user_id: ObjectId,
+        members: HashSet<ObjectId>,
+    ) {
+        let mut watch = self.rooms.entry(room_id).or_default();
+        watch.members = members;
+        watch.connections.insert(connection_id.to_string(), user_id);
+        self.by_connection
+            .entry(connection_id.to_string())
+            .or_default()
+            .insert(room_id);
+    }
+
+    pub fn unsubscribe(&self, room_id: &ObjectId, connection_id: &str) {
+        self.rooms.remove_if_mut(room_id, |_, watch| {
+            watch.connections.remove(connection_id);
+            watch.connections.is_empty()
+        });
+        if let Some(mut rooms) = self.by_connection.get_mut(connection_id) {
+            rooms.remove(room_id);
+        }
+    }
+
+    /// Forget everything a closed connection watched.
+    pub fn drop_connection(&self, connection_id: &str) {
+        if let Some((_, rooms)) = self.by_connection.remove(connection_id) {
+            for room_id in rooms {
+                self.unsubscribe(&room_id, connection_id);
+            }
+        }
+    }
+
+    /// Connections watching a room `user_id` is a member of, each once.
+    pub fn watchers_of(&self, user_id: &ObjectId) -> HashSet<String> {
+        self.rooms
+            .iter()
+            .filter(|watch| watch.members.contains(user_id))
+            .flat_map(|watch| watch.connections.keys().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Start or renew a user's lease.
+    pub fn lease(&self, user_id: ObjectId, expires: Instant) {
+        self.leases.insert(user_id, expires);
+    }
+
+    /// Renew a user's lease if they have one.
+    pub fn touch(&self, user_id: &ObjectId, expires: Instant) {
+        if let Some(mut lease) = self.leases.get_mut(user_id) {
+            *lease = expires;
+        }
+    }
+
+    pub fn release(&self, user_id: &ObjectId) {
+        self.leases.remove(user_id);
+    }
+
+    /// Remove and return the users whose lease ran out.
+    pub fn expired_leases(&self, now: Instant) -> Vec<ObjectId> {
+        let expired: Vec<ObjectId> = self
+            .leases
+            .iter()
+            .filter(|lease| *lease.value() <= now)
+            .map(|lease| *lease.key())
+            .collect();
+        expired
+            .into_iter()
+            .filter(|user_id| self.leases.remove_if(user_id, |_, e| *e <= now).is_some())
+            .collect()
+    }
+
+    /// Returns whether the user wasn't already typing there.
+    pub fn typing_start(&self, room_id: ObjectId, user_id: ObjectId, expires: Instant) -> bool {
+        self.typing.insert((room_id, user_id), expires).is_none()
+    }
+
+    /// Returns whether the user was typing there.
+    pub fn typing_stop(&self, room_id: ObjectId, user_id: ObjectId) -> bool {
+        self.typing.remove(&(room_id, user_id)).is_some()
+    }
+
+    /// Who is typing in a room.
+    pub fn typing_in(&self, room_id: &ObjectId) -> Vec<ObjectId> {
+        self.typing
+            .iter()
+            .filter(|t| t.key().0 == *room_id)
+            .map(|t| t.key().1)
+            .collect()
+    }
+
+    /// Remove and return the typing indicators that ran out.
+    pub fn expired_typing(&self, now: Instant) -> Vec<(ObjectId, ObjectId)> {
+        let expired: Vec<(ObjectId, ObjectId)> = self
+            .typing
+            .iter()
+            .filter(|t| *t.value() <= now)
+            .map(|t| *t.key())
+            .collect();
+        expired
+            .into_iter()
+            .filter(|key| self.typing.remove_if(key, |_, e| *e <= now).is_some())
+            .collect()
+    }
+}
+
+fn ttl(state: &AppState) -> Duration {
+    Duration::from_secs(state.settings.ws.presence_ttl_secs.max(1))
+}
+
+/// Any message from a user keeps their presence alive.
+pub fn touch(state: &AppState, user_id: &ObjectId) {
+    state.presence.touch(user_id, Instant::now() + ttl(state));
+}
+
+/// What others see: invisible users show as offline.
+fn visible(presence: &Presence) -> Presence {
+    match presence {
+        Presence::Invisible => Presence::Offline,
+        p => p.clone(),
+    }
+}
+
+/// Store a user's presence and tell the connections watching them.
+async fn set(state: &AppState, user_id: &ObjectId, presence: Presence) {
+    if let Err(e) = state
+        .users
+        .update_presence(*user_id, presence.clone())
+        .await
+    {
+        warn!(?user_id, %e, "Failed to store presence");
+    }
+    let event = serde_json::json!({
+        "type": "presence:update",
+        "data": {
+            "user_id": user_id.to_hex(),
+            "presence": visible(&presence),
+        }
+    });
+    for connection_id in state.presence.watchers_of(user_id) {
+        super::dispatcher::send_to_connection(&state.ws_storage, &connection_id, &event).await;
+    }
+}
+
+/// `presence:update { presence }`.
+pub async fn handle_update(state: &AppState, user_id: &ObjectId, data: Option<&serde_json::Value>) {
+    let Some(presence) = data
+        .and_then(|d| d.get("presence"))
+        .and_then(|p| serde_json::from_value::<Presence>(p.clone()).ok())
+    else {
+        return;
+    };
+    if presence == Presence::Offline {
+        state.presence.release(user_id);
+    } else {
+        state.presence.lease(*user_id, Instant::now() + ttl(state));
+    }
+    set(state, user_id, presence).await;
+}
+
+/// `presence:subscribe { room_id }`: watch the room's members and get a
+/// snapshot of them.
+pub async fn handle_subscribe(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(room_id) = room_id(data) else {
+        return;
+    };
+    let member_ids = state
+        .rooms
+        .find_member_user_ids(room_id)
+        .await
+        .unwrap_or_default();
+    if !member_ids.contains(user_id) {
+        warn!(
+            ?user_id,
+            ?room_id,
+            "presence:subscribe for a room the user isn't in"
+        );
+        return;
+    }
+
+    let presence = state
+        .users
+        .find_visible_presence(&member_ids)
+        .await
+        .unwrap_or_default();
+    state.presence.subscribe(
+        room_id,
+        connection_id,
+        *user_id,
+        member_ids.into_iter().collect(),
+    );
+
+    let users: Vec<serde_json::Value> = presence
+        .into_iter()
+        .map(|(id, p)| serde_json::json!({ "user_id": id.to_hex(), "presence": p }))
+        .collect();
+    let typing: Vec<String> = state
+        .presence
+        .typing_in(&room_id)
+        .into_iter()
+        .filter(|id| id != user_id)
+        .map(|id| id.to_hex())
+        .collect();
+    let msg = serde_json::json!({
+        "type": "presence:snapshot",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "users": users,
+            "typing": typing,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// `presence:unsubscribe { room_id }`.
+pub fn handle_unsubscribe(state: &AppState, connection_id: &str, data: Option<&serde_json::Value>) {
+    if let Some(room_id) = room_id(data) {
+        state.presence.unsubscribe(&room_id, connection_id);
+    }
+}
+
+/// `typing:start` / `typing:stop { room_id }`.
+pub async fn handle_typing(
+    state: &AppState,
+    user_id: &ObjectId,
+    data: Option<&serde_json::Value>,
+    started: bool,
+) {
+    let Some(room_id) = room_id(data) else {
+        return;
+    };
+    let Ok(member_ids) = state.rooms.find_member_user_ids(room_id).await else {
+        return;
+    };
+    if !member_ids.contains(user_id) {
+        return;
+    }
+    let changed = if started {
+        state
+            .presence
+            .typing_start(room_id, *user_id, Instant::now() + TYPING_TTL)
+    } else {
+        state.presence.typing_stop(room_id, *user_id)
+    };
+    // Renewals only push the expiry out.
+    if changed {
+        relay_typing(state, room_id, user_id, member_ids, started).await;
+    }
+}
+
+async fn relay_typing(
+    state: &AppState,
+    room_id: ObjectId,
+    user_id: &ObjectId,
+    member_ids: Vec<ObjectId>,
+    started: bool,
+) {
+    let Ok(room) = state.rooms.base.find_by_id_unscoped(room_id).await else {
+        return;
+    };
+    let recipients: Vec<ObjectId> = member_ids.into_iter().filter(|id| id != user_id).collect();
+    let event = serde_json::json!({
+        "type": if started { "typing:start" } else { "typing:stop" },
+        "tenant_id": room.tenant_id.to_hex(),
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+        }
+    });
+    super::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &recipients,
+        &event,
+    )
+    .await;
+}
+
+fn room_id(data: Option<&serde_json::Value>) -> Option<ObjectId> {
+    data.and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+}
+
+/// Spawn the loop that lapses expired presence leases and typing indicators.
+pub(crate) fn spawn_expiry(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            tick.tick().await;
+            let now = Instant::now();
+            for (room_id, user_id) in state.presence.expired_typing(now) {
+                if let Ok(member_ids) = state.rooms.find_member_user_ids(room_id).await {
+                    relay_typing(&state, room_id, &user_id, member_ids, false).await;
+                }
+            }
+            for user_id in state.presence.expired_leases(now) {
+                set(&state, &user_id, Presence::Offline).await;
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchers_are_scoped_to_rooms_the_user_is_in() {
+        let hub = PresenceHub::default();
+        let (room_a, room_b) = (ObjectId::new(), ObjectId::new());
+        let (alice, bob, carol) = (ObjectId::new(), ObjectId::new(), ObjectId::new());
+        hub.subscribe(room_a, "bob-1", bob, HashSet::from([alice, bob]));
+        hub.subscribe(room_b, "bob-1", bob, HashSet::from([alice, bob, carol]));
+        hub.subscribe(room_b, "carol-1", carol, HashSet::from([alice, bob, carol]));
+
+        assert_eq!(
+            hub.watchers_of(&alice),
+            HashSet::from(["bob-1".to_string(), "carol-1".to_string()])
+        );
+        hub.unsubscribe(&room_b, "carol-1");
+        assert_eq!(
+            hub.watchers_of(&carol),
+            HashSet::from(["bob-1".to_string()])
+        );
+        hub.drop_connection("bob-1");
+        assert!(hub.watchers_of(&alice).is_empty());
+        assert!(hub.rooms.is_empty());
+    }
+
+    #[test]
+    fn leases_lapse_unless_touched() {
+        let hub = PresenceHub::default();
+        let t0 = Instant::now();
+        let (alice, bob) = (ObjectId::new(), ObjectId::new());
+        hub.lease(alice, t0 + Duration::from_secs(10));
+        hub.lease(bob, t0 + Duration::from_secs(10));
+        hub.touch(&bob, t0 + Duration::from_secs(20));
+        // No lease, nothing to renew.
+        hub.touch(&ObjectId::new(), t0 + Duration::from_secs(20));
+
+        assert!(hub.expired_leases(t0 + Duration::from_secs(5)).is_empty());
+        assert_eq!(
+            hub.expired_leases(t0 + Duration::from_secs(10)),
+            vec![alice]
+        );
+        assert!(hub.expired_leases(t0 + Duration::from_secs(15)).is_empty());
+        assert_eq!(hub.expired_leases(t0 + Duration::from_secs(20)), vec![bob]);
+    }
+
+    #[test]
+    fn typing_is_relayed_once_and_lapses() {
+        let hub = PresenceHub::default();
+        let t0 = Instant::now();
+        let (room, alice) = (ObjectId::new(), ObjectId::new());
+        assert!(hub.typing_start(room, alice, t0 + TYPING_TTL));
+        assert!(!hub.typing_start(room, alice, t0 + 2 * TYPING_TTL));
+        assert_eq!(hub.typing_in(&room), vec![alice]);
+        assert!(hub.expired_typing(t0 + TYPING_TTL).is_empty());
+        assert_eq!(hub.expired_typing(t0 + 2 * TYPING_TTL), vec![(room, alice)]);
+        assert!(!hub.typing_stop(room, alice));
+    }
+}
@@ -0,0 +1,48 @@
+//! Connection quality indicators.
+//!
+//! Every [`QUALITY_INTERVAL`] each call with at least two participants gets
+//! `media:connection_quality { room_id, participants: [{ user_id,
+//! connection_id, score, rtt, loss }] }`, rated on the server from the
+//! participants' transport stats, so clients can show quality bars for
+//! remote peers without polling `getStats()` for every consumer.
+
+use std::time::Duration;
+
+use crate::state::AppState;
+
+const QUALITY_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Spawn the loop that reports connection quality to every live call.
+pub(crate) fn spawn_reporter(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(QUALITY_INTERVAL);
+        loop {
+            tick.tick().await;
+            for (room_id, qualities) in state.room_manager.connection_quality().await {
+                let participants: Vec<serde_json::Value> = qualities
+                    .iter()
+                    .map(|q| {
+                        serde_json::json!({
+                            "user_id": q.user_id.to_hex(),
+                            "connection_id": q.connection_id,
+                            "score": q.score,
+                            "rtt": q.rtt,
+                            "loss": q.loss,
+                        })
+                    })
+                    .collect();
+                let msg = serde_json::json!({
+                    "type": "media:connection_quality",
+                    "data": {
+                        "room_id": room_id.to_hex(),
+                        "participants": participants,
+                    }
+                });
+                // Every connection in the media room, none excluded.
+                for conn_id in state.room_manager.get_other_connection_ids(&room_id, "") {
+                    super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &msg).await;
+                }
+            }
+        }
+    });
+}
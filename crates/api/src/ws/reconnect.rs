@@ -0,0 +1,149 @@
+//! Reconnection grace period for media connections.
+//!
+//! When a WebSocket in a media room drops, its transports, producers and
+//! consumers are kept for `mediasoup.reconnect_grace_secs` instead of being
+//! closed. A new connection of the same user can take them over with
+//! `media:rejoin { resume_token }` (the token comes with
+//! `media:transport_created` and every `media:rejoined`); it gets back
+//! `media:rejoined { room_id, resume_token, previous_connection_id }` and the
+//! others get `media:peer_reconnected`. Peers only see `media:peer_left` if
+//! nobody reclaims the media before the timer runs out.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::CallDebugKind;
+use tracing::debug;
+
+use crate::state::AppState;
+
+/// A media connection's WebSocket closed: suspend its media for the grace
+/// period, or close it right away when the grace period is off.
+pub async fn on_disconnect(
+    state: &AppState,
+    room_id: ObjectId,
+    user_id: ObjectId,
+    connection_id: &str,
+) {
+    let grace = Duration::from_secs(state.settings.mediasoup.reconnect_grace_secs);
+    if grace.is_zero()
+        || !state
+            .room_manager
+            .suspend_participant(&room_id, connection_id)
+    {
+        close(state, &room_id, &user_id, connection_id).await;
+        return;
+    }
+
+    let state = state.clone();
+    let connection_id = connection_id.to_string();
+    tokio::spawn(async move {
+        tokio::time::sleep(grace).await;
+        if state
+            .room_manager
+            .expire_suspended(&room_id, &connection_id)
+        {
+            debug!(?room_id, %connection_id, "Reconnect grace period expired");
+            announce_left(&state, &room_id, &user_id, &connection_id).await;
+        }
+    });
+}
+
+/// Close a connection's media now and tell the rest of the room.
+async fn close(state: &AppState, room_id: &ObjectId, user_id: &ObjectId, connection_id: &str) {
+    state.room_manager.close_participant(room_id, connection_id);
+    announce_left(state, room_id, user_id, connection_id).await;
+}
+
+async fn announce_left(
+    state: &AppState,
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    connection_id: &str,
+) {
+    super::e2ee::rotate_and_announce(state, room_id, "leave").await;
+
+    let event = serde_json::json!({
+        "type": "media:peer_left",
+        "data": {
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "room_id": room_id.to_hex(),
+        }
+    });
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(room_id, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+}
+
+/// Take over the suspended media a `resume_token` was issued for.
+pub async fn handle_rejoin(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(resume_token) = data
+        .and_then(|d| d.get("resume_token"))
+        .and_then(|t| t.as_str())
+    else {
+        super::handler::send_media_error(state, user_id, "Missing resume_token").await;
+        return;
+    };
+    if state
+        .room_manager
+        .get_connection_room(connection_id)
+        .is_some()
+    {
+        super::handler::send_media_error(state, user_id, "Connection already has media").await;
+        return;
+    }
+    let Some((room_id, previous, resume_token)) =
+        state
+            .room_manager
+            .reclaim_participant(resume_token, user_id, connection_id)
+    else {
+        super::handler::send_media_error(state, user_id, "Unknown or expired resume_token").await;
+        return;
+    };
+    debug!(?room_id, %previous, %connection_id, "Media connection resumed");
+    state.call_debug.record(
+        room_id,
+        *user_id,
+        connection_id,
+        CallDebugKind::Rejoin,
+        format!("Resumed from {}", previous),
+    );
+
+    let msg = serde_json::json!({
+        "type": "media:rejoined",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "resume_token": resume_token,
+            "previous_connection_id": previous,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+
+    let event = serde_json::json!({
+        "type": "media:peer_reconnected",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+            "connection_id": connection_id,
+            "previous_connection_id": previous,
+        }
+    });
+    for conn_id in state
+        .room_manager
+        .get_other_connection_ids(&room_id, connection_id)
+    {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, &event).await;
+    }
+
+    // Producers announced while the connection was down were lost with it.
+    super::handler::replay_room_state(state, &room_id, connection_id).await;
+}
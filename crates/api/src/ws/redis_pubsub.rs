@@ -3,6 +3,7 @@ use tokio::sync::broadcast;
 use tracing::{error, info};
 
 const CHANNEL_NAME: &str = "roomler:ws";
+const SEQ_KEY_PREFIX: &str = "roomler:ws:seq:";
 
 /// Manages Redis Pub/Sub for cross-instance WebSocket event distribution.
 ///
@@ -37,6 +38,37 @@ impl RedisPubSub {
         Ok(())
     }
 
+    /// Round trip to Redis, for the readiness probe.
+    pub async fn ping(&self) -> Result<(), redis::RedisError> {
+        let mut conn = self.publisher.clone();
+        redis::cmd("PING").query_async::<()>(&mut conn).await
+    }
+
+    /// Allocate the next event sequence number of a room, shared by all
+    /// instances (see `ws::event_log`).
+    pub async fn next_seq(&self, room_id: &str) -> Result<u64, redis::RedisError> {
+        let mut conn = self.publisher.clone();
+        redis::cmd("INCR")
+            .arg(format!("{SEQ_KEY_PREFIX}{room_id}"))
+            .query_async::<u64>(&mut conn)
+            .await
+    }
+
+    /// Set `key` unless it already exists, expiring it after `ttl_secs`.
+    /// True when this call set it, i.e. on every instance only once.
+    pub async fn set_once(&self, key: &str, ttl_secs: u64) -> Result<bool, redis::RedisError> {
+        let mut conn = self.publisher.clone();
+        let set: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(1)
+            .arg("NX")
+            .arg("EX")
+            .arg(ttl_secs.max(1))
+            .query_async(&mut conn)
+            .await?;
+        Ok(set.is_some())
+    }
+
     /// Start a subscriber that listens on the Redis channel and forwards
     /// messages into a tokio broadcast channel. Returns immediately after
     /// spawning the background listener task.
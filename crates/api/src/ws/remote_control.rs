@@ -20,6 +20,8 @@ use tokio::sync::{Mutex, mpsc};
 use tracing::{debug, info, warn};
 
 use crate::state::AppState;
+use crate::ws::outbound::Class;
+use crate::ws::storage::WsSender;
 
 /// Handle a socket that authenticated as an agent.
 ///
@@ -305,6 +307,17 @@ pub async fn pump_server_messages(
     }
 }
 
+/// Forwards [`ServerMsg`] values to a browser tab's outbound queue. Exits
+/// when the channel closes.
+pub async fn pump_server_messages_to(mut rx: mpsc::Receiver<ServerMsg>, outbound: WsSender) {
+    while let Some(msg) = rx.recv().await {
+        match serde_json::to_string(&msg) {
+            Ok(json) => outbound.push(Message::text(json), Class::default()),
+            Err(e) => warn!(%e, "serializing ServerMsg failed"),
+        }
+    }
+}
+
 /// Route a parsed `rc:*` message coming from a controller browser tab.
 /// Returns `true` if the message was handled, `false` if it wasn't rc:*.
 pub fn dispatch_controller_rc(
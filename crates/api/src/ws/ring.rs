@@ -0,0 +1,212 @@
+//! Incoming-call ringing.
+//!
+//! `POST .../call/start` with `ring` or `ring_all` sends `call:ring` to the
+//! chosen members and keeps a [`Ring`] in [`AppState::rings`] until each of
+//! them has answered. Callees answer with `call:accept` or `call:decline`
+//! (joining the call counts as accepting); the caller sees every answer as
+//! `call:ring_update` and the callee's other devices stop ringing on
+//! `call:ring_cancelled`. Whoever hasn't answered after
+//! `ws.ring_timeout_secs`, or when the call ends before they pick up, gets a
+//! missed-call notification.
+//!
+//! Rings live in memory on the pod that started the call, like the media
+//! Router; answers that reach another pod are ignored and those callees end
+//! up with a missed call.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use tracing::debug;
+
+use crate::state::AppState;
+
+/// A call that is still ringing some of the room's members.
+pub struct Ring {
+    /// Tells this ring's timeout apart from a later ring in the same room.
+    id: ObjectId,
+    tenant_id: ObjectId,
+    caller_id: ObjectId,
+    caller_name: String,
+    room_name: String,
+    /// Callees who haven't answered yet.
+    pending: HashSet<ObjectId>,
+}
+
+/// Ring `targets` for the call just started in the room. Replaces a ring
+/// still running there.
+pub async fn start(
+    state: &AppState,
+    tenant_id: ObjectId,
+    room_id: ObjectId,
+    caller_id: ObjectId,
+    caller_name: String,
+    room_name: String,
+    targets: Vec<ObjectId>,
+) {
+    if targets.is_empty() {
+        return;
+    }
+    let timeout_secs = state.settings.ws.ring_timeout_secs.max(1);
+    let msg = serde_json::json!({
+        "type": "call:ring",
+        "tenant_id": tenant_id.to_hex(),
+        "data": {
+            "room_id": room_id.to_hex(),
+            "tenant_id": tenant_id.to_hex(),
+            "room_name": room_name,
+            "caller_id": caller_id.to_hex(),
+            "caller_name": caller_name,
+            "timeout_secs": timeout_secs,
+        }
+    });
+
+    let id = ObjectId::new();
+    state.rings.insert(
+        room_id,
+        Ring {
+            id,
+            tenant_id,
+            caller_id,
+            caller_name,
+            room_name,
+            pending: targets.iter().copied().collect(),
+        },
+    );
+    super::dispatcher::broadcast_with_redis(&state.ws_storage, &state.redis_pubsub, &targets, &msg)
+        .await;
+
+    let state = state.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(timeout_secs)).await;
+        if let Some((_, ring)) = state.rings.remove_if(&room_id, |_, r| r.id == id) {
+            debug!(?room_id, missed = ring.pending.len(), "Ring timed out");
+            finish(&state, room_id, ring, "timeout").await;
+        }
+    });
+}
+
+/// Stop ringing because the call ended; nobody left can pick up anymore.
+pub async fn cancel(state: &AppState, room_id: ObjectId) {
+    if let Some((_, ring)) = state.rings.remove(&room_id) {
+        finish(state, room_id, ring, "ended").await;
+    }
+}
+
+/// Record the user's answer. Returns false when the room isn't ringing them.
+pub async fn answer(state: &AppState, user_id: ObjectId, room_id: ObjectId, status: &str) -> bool {
+    let (tenant_id, caller_id) = {
+        let Some(mut ring) = state.rings.get_mut(&room_id) else {
+            return false;
+        };
+        if !ring.pending.remove(&user_id) {
+            return false;
+        }
+        (ring.tenant_id, ring.caller_id)
+    };
+    state.rings.remove_if(&room_id, |_, r| r.pending.is_empty());
+
+    let update = serde_json::json!({
+        "type": "call:ring_update",
+        "tenant_id": tenant_id.to_hex(),
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+            "status": status,
+        }
+    });
+    super::dispatcher::send_to_user_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &caller_id,
+        &update,
+    )
+    .await;
+    let cancelled = serde_json::json!({
+        "type": "call:ring_cancelled",
+        "tenant_id": tenant_id.to_hex(),
+        "data": { "room_id": room_id.to_hex(), "reason": status }
+    });
+    super::dispatcher::send_to_user_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &user_id,
+        &cancelled,
+    )
+    .await;
+    true
+}
+
+pub async fn handle_accept(state: &AppState, user_id: &ObjectId, data: Option<&serde_json::Value>) {
+    if let Some(rid) = parse_room_id(data) {
+        answer(state, *user_id, rid, "accepted").await;
+    }
+}
+
+pub async fn handle_decline(
+    state: &AppState,
+    user_id: &ObjectId,
+    data: Option<&serde_json::Value>,
+) {
+    if let Some(rid) = parse_room_id(data) {
+        answer(state, *user_id, rid, "declined").await;
+    }
+}
+
+fn parse_room_id(data: Option<&serde_json::Value>) -> Option<ObjectId> {
+    data.and_then(|d| d.get("room_id"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+}
+
+/// Stop the ring for everyone still pending and tell them they missed it.
+async fn finish(state: &AppState, room_id: ObjectId, ring: Ring, reason: &str) {
+    let missed: Vec<ObjectId> = ring.pending.into_iter().collect();
+    if missed.is_empty() {
+        return;
+    }
+    let cancelled = serde_json::json!({
+        "type": "call:ring_cancelled",
+        "tenant_id": ring.tenant_id.to_hex(),
+        "data": { "room_id": room_id.to_hex(), "reason": reason }
+    });
+    super::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &missed,
+        &cancelled,
+    )
+    .await;
+
+    if reason == "timeout" {
+        for user_id in &missed {
+            let update = serde_json::json!({
+                "type": "call:ring_update",
+                "tenant_id": ring.tenant_id.to_hex(),
+                "data": {
+                    "room_id": room_id.to_hex(),
+                    "user_id": user_id.to_hex(),
+                    "status": "missed",
+                }
+            });
+            super::dispatcher::send_to_user_with_redis(
+                &state.ws_storage,
+                &state.redis_pubsub,
+                &ring.caller_id,
+                &update,
+            )
+            .await;
+        }
+    }
+
+    crate::routes::helpers::notify_missed_call(
+        state,
+        ring.tenant_id,
+        room_id,
+        ring.caller_id,
+        &missed,
+        &ring.room_name,
+        &ring.caller_name,
+    )
+    .await;
+}
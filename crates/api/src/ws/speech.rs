@@ -0,0 +1,290 @@
+//! Spoken announcements in a call. `media:speak { room_id, text, voice? }`
+//! synthesizes `text` with the configured text-to-speech backend (see
+//! `roomler_ai_services::tts`) and plays it into the media room the same
+//! way as shared audio: a server-side producer fed by
+//! `roomler_ai_services::media::file_audio`, so everyone hears it in sync
+//! and recordings pick it up. Used for announcements ("five minutes
+//! left"), accessibility (typed text spoken for participants who can't
+//! talk) and translated voice-over.
+//!
+//! The room gets `media:speech { action: "start", speech_id, producer_id,
+//! user_id, text, .. }` once the audio is ready, and `{ action: "end", ..,
+//! reason }` when it is over or couldn't be played. One announcement plays
+//! per room at a time. Synthesis failures go back to the speaker as
+//! `media:error`.
+
+use bson::oid::ObjectId;
+use mediasoup::prelude::MediaKind;
+use roomler_ai_services::media::file_audio;
+use tokio::sync::watch;
+use tokio::task::AbortHandle;
+use tracing::{info, warn};
+
+use crate::state::AppState;
+
+/// Longest text one announcement may speak.
+pub const MAX_SPEECH_CHARS: usize = 1000;
+
+/// The room's current announcement.
+pub struct Speech {
+    speech_id: String,
+    user_id: ObjectId,
+    text: String,
+    voice: Option<String>,
+    /// Set once the audio is synthesized and playing.
+    producer_id: Option<String>,
+    /// Synthesizes and plays the speech; stopped with it.
+    task: Option<AbortHandle>,
+}
+
+impl Drop for Speech {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.take() {
+            task.abort();
+        }
+    }
+}
+
+fn start_event(room_id: &ObjectId, speech: &Speech, producer_id: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:speech",
+        "data": {
+            "action": "start",
+            "room_id": room_id.to_hex(),
+            "speech_id": speech.speech_id,
+            "producer_id": producer_id,
+            "user_id": speech.user_id.to_hex(),
+            "text": speech.text,
+            "voice": speech.voice,
+        }
+    })
+}
+
+fn end_event(room_id: &ObjectId, speech_id: &str, reason: &str) -> serde_json::Value {
+    serde_json::json!({
+        "type": "media:speech",
+        "data": {
+            "action": "end",
+            "room_id": room_id.to_hex(),
+            "speech_id": speech_id,
+            "reason": reason,
+        }
+    })
+}
+
+async fn send_to_room(state: &AppState, room_id: &ObjectId, msg: &serde_json::Value) {
+    for conn_id in state.room_manager.get_other_connection_ids(room_id, "") {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, msg).await;
+    }
+}
+
+/// Take the room's announcement if it is `speech_id`, closing its
+/// producer. Called from its own task, so the task is left running.
+fn finish(state: &AppState, room_id: &ObjectId, speech_id: &str) -> Option<Speech> {
+    let (_, mut speech) = state
+        .speeches
+        .remove_if(room_id, |_, s| s.speech_id == speech_id)?;
+    speech.task = None;
+    if let Some(producer_id) = &speech.producer_id {
+        state.room_manager.remove_injected(room_id, producer_id);
+    }
+    Some(speech)
+}
+
+/// Stop the room's announcement, if any, without telling the clients; for
+/// when the call ends.
+pub fn cancel(state: &AppState, room_id: &ObjectId) {
+    if let Some((_, speech)) = state.speeches.remove(room_id)
+        && let Some(producer_id) = &speech.producer_id
+    {
+        state.room_manager.remove_injected(room_id, producer_id);
+    }
+}
+
+/// Send a connection joining the media room the announcement in progress.
+pub async fn replay_to(state: &AppState, room_id: &ObjectId, connection_id: &str) {
+    let msg = match state.speeches.get(room_id) {
+        Some(speech) => match &speech.producer_id {
+            Some(producer_id) => start_event(room_id, &speech, producer_id),
+            // Still synthesizing; the start goes to the whole room
+            None => return,
+        },
+        None => return,
+    };
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// `media:speak`: speak text to the whole call.
+pub async fn handle_speak(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(data) = data else {
+        return;
+    };
+    let Some(rid) = data
+        .get("room_id")
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+    else {
+        return;
+    };
+    if state.room_manager.get_connection_room(connection_id) != Some(rid) {
+        return;
+    }
+    let text = data
+        .get("text")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .trim()
+        .to_string();
+    if text.is_empty() {
+        return;
+    }
+    if text.chars().count() > MAX_SPEECH_CHARS {
+        let message = format!(
+            "Announcements are limited to {} characters",
+            MAX_SPEECH_CHARS
+        );
+        super::handler::send_media_error(state, user_id, &message).await;
+        return;
+    }
+
+    // Look up the room to get tenant_id, then hold the user to it
+    let room = match state.rooms.base.find_by_id_unscoped(rid).await {
+        Ok(r) => r,
+        Err(e) => {
+            warn!(%e, "Failed to find room for announcement");
+            return;
+        }
+    };
+    if !state
+        .tenants
+        .is_member(room.tenant_id, *user_id)
+        .await
+        .unwrap_or(false)
+    {
+        return;
+    }
+
+    let voice = data
+        .get("voice")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .map(str::to_string);
+    if let Err(message) = speak(state, rid, *user_id, text, voice) {
+        super::handler::send_media_error(state, user_id, message).await;
+    }
+}
+
+/// Speak `text` to the call in `rid` on behalf of `user_id`, in the
+/// background. Fails when text-to-speech is off or another announcement is
+/// playing; synthesis failures go to `user_id` as `media:error`.
+pub fn speak(
+    state: &AppState,
+    rid: ObjectId,
+    user_id: ObjectId,
+    text: String,
+    voice: Option<String>,
+) -> Result<(), &'static str> {
+    let Some(tts) = state.tts.clone() else {
+        return Err("Text-to-speech is not configured");
+    };
+    let speech_id = uuid::Uuid::new_v4().to_string();
+    let busy = match state.speeches.entry(rid) {
+        dashmap::mapref::entry::Entry::Occupied(_) => true,
+        dashmap::mapref::entry::Entry::Vacant(v) => {
+            v.insert(Speech {
+                speech_id: speech_id.clone(),
+                user_id,
+                text: text.clone(),
+                voice: voice.clone(),
+                producer_id: None,
+                task: None,
+            });
+            false
+        }
+    };
+    if busy {
+        return Err("An announcement is already playing");
+    }
+
+    let task = {
+        let state = state.clone();
+        let speech_id = speech_id.clone();
+        tokio::spawn(async move {
+            let audio = match tts.synthesize(&text, voice.as_deref()).await {
+                Ok(audio) => audio,
+                Err(e) => {
+                    warn!(%rid, %speech_id, backend = tts.name(), %e, "Speech synthesis failed");
+                    if finish(&state, &rid, &speech_id).is_some() {
+                        super::handler::send_media_error(
+                            &state,
+                            &user_id,
+                            "Failed to synthesize the announcement",
+                        )
+                        .await;
+                    }
+                    return;
+                }
+            };
+
+            let injected = async {
+                let (ssrc, rtp_parameters) = file_audio::rtp_parameters()?;
+                let (producer_id, tx) = state
+                    .room_manager
+                    .inject_producer(&rid, MediaKind::Audio, rtp_parameters)
+                    .await?;
+                anyhow::Ok((ssrc, producer_id.to_string(), tx))
+            };
+            let (ssrc, producer_id, tx) = match injected.await {
+                Ok(p) => p,
+                Err(e) => {
+                    warn!(%rid, %speech_id, %e, "Failed to create announcement producer");
+                    finish(&state, &rid, &speech_id);
+                    return;
+                }
+            };
+            let msg = match state.speeches.get_mut(&rid) {
+                Some(mut speech) if speech.speech_id == speech_id => {
+                    speech.producer_id = Some(producer_id.clone());
+                    start_event(&rid, &speech, &producer_id)
+                }
+                // Cancelled while synthesizing
+                _ => {
+                    state.room_manager.remove_injected(&rid, &producer_id);
+                    return;
+                }
+            };
+            send_to_room(&state, &rid, &msg).await;
+            info!(%rid, %speech_id, %producer_id, "Announcement started");
+
+            let ffmpeg = state.settings.mediasoup.ffmpeg_path.clone();
+            let source = file_audio::Source::Bytes(audio);
+            // Announcements aren't ducked: they play at their own volume
+            let (_, gain) = watch::channel(1.0);
+            let reason = match file_audio::stream(&ffmpeg, source, ssrc, gain, tx).await {
+                Ok(true) => "ended",
+                // Nothing takes the RTP: the producer is gone
+                Ok(false) => "stopped",
+                Err(e) => {
+                    warn!(%rid, %speech_id, %e, "Announcement failed");
+                    "failed"
+                }
+            };
+            if finish(&state, &rid, &speech_id).is_some() {
+                send_to_room(&state, &rid, &end_event(&rid, &speech_id, reason)).await;
+                info!(%rid, %speech_id, reason, "Announcement finished");
+            }
+        })
+        .abort_handle()
+    };
+    match state.speeches.get_mut(&rid) {
+        Some(mut speech) if speech.speech_id == speech_id => speech.task = Some(task),
+        // Already over
+        _ => task.abort(),
+    }
+    Ok(())
+}
@@ -1,19 +1,24 @@
-use axum::extract::ws::{Message, WebSocket};
 use bson::oid::ObjectId;
 use dashmap::DashMap;
-use futures::stream::SplitSink;
+use std::collections::HashSet;
 use std::sync::Arc;
-use tokio::sync::Mutex;
 
-pub type WsSender = Arc<Mutex<SplitSink<WebSocket, Message>>>;
+use super::outbound::Outbound;
+
+/// A connection's outbound queue; see [`super::outbound`].
+pub type WsSender = Arc<Outbound>;
 
 /// Tracks all active WebSocket connections by user ID and connection ID.
-/// Each user can have multiple connections (multiple tabs/devices).
+/// Each user can have multiple connections (multiple tabs/devices), and each
+/// connection may be scoped to some of the user's tenants.
 pub struct WsStorage {
-    /// user_id -> Vec of senders (for user-level broadcasts)
-    connections: DashMap<ObjectId, Vec<WsSender>>,
+    /// user_id -> Vec of (connection_id, sender) (for user-level broadcasts)
+    connections: DashMap<ObjectId, Vec<(String, WsSender)>>,
     /// connection_id -> (user_id, sender) for connection-targeted sends
     connection_map: DashMap<String, (ObjectId, WsSender)>,
+    /// connection_id -> tenants it subscribed to. A connection that never
+    /// subscribed has no entry and gets every tenant's events.
+    tenants: DashMap<String, HashSet<ObjectId>>,
 }
 
 impl WsStorage {
@@ -21,6 +26,7 @@ impl WsStorage {
         Self {
             connections: DashMap::new(),
             connection_map: DashMap::new(),
+            tenants: DashMap::new(),
         }
     }
 
@@ -28,28 +34,60 @@ impl WsStorage {
         self.connections
             .entry(user_id)
             .or_default()
-            .push(sender.clone());
+            .push((connection_id.clone(), sender.clone()));
         self.connection_map.insert(connection_id, (user_id, sender));
     }
 
     pub fn remove(&self, user_id: &ObjectId, connection_id: &str, sender: &WsSender) {
         if let Some(mut senders) = self.connections.get_mut(user_id) {
-            senders.retain(|s| !Arc::ptr_eq(s, sender));
+            senders.retain(|(_, s)| !Arc::ptr_eq(s, sender));
             if senders.is_empty() {
                 drop(senders);
                 self.connections.remove(user_id);
             }
         }
         self.connection_map.remove(connection_id);
+        self.tenants.remove(connection_id);
+    }
+
+    /// Add tenants to a connection's subscription, scoping it if it wasn't
+    /// yet. Returns the tenants it is now subscribed to.
+    pub fn subscribe(&self, connection_id: &str, tenant_ids: &[ObjectId]) -> HashSet<ObjectId> {
+        let mut entry = self.tenants.entry(connection_id.to_string()).or_default();
+        entry.extend(tenant_ids.iter().copied());
+        entry.value().clone()
+    }
+
+    /// Drop tenants from a connection's subscription. The connection stays
+    /// scoped even when none are left.
+    pub fn unsubscribe(&self, connection_id: &str, tenant_ids: &[ObjectId]) -> HashSet<ObjectId> {
+        let mut entry = self.tenants.entry(connection_id.to_string()).or_default();
+        entry.retain(|t| !tenant_ids.contains(t));
+        entry.value().clone()
     }
 
-    pub fn get_senders(&self, user_id: &ObjectId) -> Vec<WsSender> {
+    /// Senders of the user's connections that take events of `tenant_id`;
+    /// all of them for events that belong to no tenant.
+    pub fn get_senders_in(
+        &self,
+        user_id: &ObjectId,
+        tenant_id: Option<&ObjectId>,
+    ) -> Vec<WsSender> {
         self.connections
             .get(user_id)
-            .map(|s| s.clone())
+            .map(|s| {
+                s.iter()
+                    .filter(|(cid, _)| self.receives(cid, tenant_id))
+                    .map(|(_, sender)| sender.clone())
+                    .collect()
+            })
             .unwrap_or_default()
     }
 
+    fn receives(&self, connection_id: &str, tenant_id: Option<&ObjectId>) -> bool {
+        receives(self.tenants.get(connection_id).as_deref(), tenant_id)
+    }
+
     /// Get the sender for a specific connection ID.
     pub fn get_sender_by_connection(&self, connection_id: &str) -> Option<WsSender> {
         self.connection_map
@@ -57,12 +95,11 @@ impl WsStorage {
             .map(|entry| entry.value().1.clone())
     }
 
-    /// Check if a user has any active WebSocket connections.
-    pub fn is_connected(&self, user_id: &ObjectId) -> bool {
+    /// Check if a user has a connection that takes `tenant_id`'s events.
+    pub fn is_connected_to(&self, user_id: &ObjectId, tenant_id: &ObjectId) -> bool {
         self.connections
             .get(user_id)
-            .map(|s| !s.is_empty())
-            .unwrap_or(false)
+            .is_some_and(|s| s.iter().any(|(cid, _)| self.receives(cid, Some(tenant_id))))
     }
 
     pub fn all_user_ids(&self) -> Vec<ObjectId> {
@@ -72,6 +109,16 @@ impl WsStorage {
     pub fn connection_count(&self) -> usize {
         self.connections.iter().map(|r| r.value().len()).sum()
     }
+
+    /// (total, deepest) outbound queue depth over all connections.
+    pub fn queue_depths(&self) -> (usize, usize) {
+        self.connection_map
+            .iter()
+            .map(|r| r.value().1.depth())
+            .fold((0, 0), |(total, max), depth| {
+                (total + depth, max.max(depth))
+            })
+    }
 }
 
 impl Default for WsStorage {
@@ -79,3 +126,28 @@ impl Default for WsStorage {
         Self::new()
     }
 }
+
+/// Whether a connection with this subscription gets an event of `tenant_id`.
+fn receives(subscription: Option<&HashSet<ObjectId>>, tenant_id: Option<&ObjectId>) -> bool {
+    match (subscription, tenant_id) {
+        (Some(tenants), Some(tenant_id)) => tenants.contains(tenant_id),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_connections_only_get_their_tenants() {
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        let only_a = HashSet::from([a]);
+        assert!(receives(Some(&only_a), Some(&a)));
+        assert!(!receives(Some(&only_a), Some(&b)));
+        assert!(!receives(Some(&HashSet::new()), Some(&a)));
+        // Unscoped connections and tenant-less events.
+        assert!(receives(None, Some(&b)));
+        assert!(receives(Some(&only_a), None));
+    }
+}
@@ -0,0 +1,126 @@
+//! Tenant scoping of a connection's events.
+//!
+//! Every event that belongs to a tenant carries its `tenant_id` at the top
+//! level, next to `type`. A user in several tenants keeps one socket and
+//! picks the tenants it listens to: `/ws?tenants=<id>,<id>` when connecting,
+//! then `tenant:subscribe { tenant_ids }` and `tenant:unsubscribe
+//! { tenant_ids }`, each answered with `tenant:subscribed { tenant_ids }`
+//! listing the connection's tenants. The dispatcher drops a tenant's events
+//! for connections not subscribed to it (see [`WsStorage`]). Tenants the
+//! user isn't a member of are ignored.
+//!
+//! A connection that never subscribes gets every tenant's events, as before.
+//! A bot connection is scoped to its token's tenant and can't change that.
+//! Events without a tenant (`connected`, `pong`, errors, and the presence
+//! and media signaling addressed to single connections) always go through.
+//!
+//! [`WsStorage`]: super::storage::WsStorage
+
+use std::collections::HashSet;
+
+use bson::oid::ObjectId;
+use tracing::warn;
+
+use crate::state::AppState;
+
+/// Parse the comma-separated `tenants` query parameter. `None` if any id is
+/// malformed.
+pub fn parse_ids(raw: &str) -> Option<Vec<ObjectId>> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| ObjectId::parse_str(id).ok())
+        .collect()
+}
+
+/// Subscribe the connection to those of `tenant_ids` the user is a member
+/// of. Returns the connection's tenants.
+pub async fn subscribe(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    tenant_ids: &[ObjectId],
+) -> HashSet<ObjectId> {
+    let allowed: Vec<ObjectId> = match state.tenants.find_user_tenants(*user_id).await {
+        Ok(tenants) => {
+            let member_of: HashSet<ObjectId> = tenants.iter().filter_map(|t| t.id).collect();
+            tenant_ids
+                .iter()
+                .copied()
+                .filter(|t| member_of.contains(t))
+                .collect()
+        }
+        Err(e) => {
+            warn!(?user_id, %e, "Tenant lookup for WS subscription failed");
+            Vec::new()
+        }
+    };
+    state.ws_storage.subscribe(connection_id, &allowed)
+}
+
+/// `tenant:subscribe { tenant_ids }`.
+pub async fn handle_subscribe(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let tenant_ids = tenant_ids(data);
+    let subscribed = subscribe(state, user_id, connection_id, &tenant_ids).await;
+    reply(state, connection_id, &subscribed).await;
+}
+
+/// `tenant:unsubscribe { tenant_ids }`.
+pub async fn handle_unsubscribe(
+    state: &AppState,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let subscribed = state
+        .ws_storage
+        .unsubscribe(connection_id, &tenant_ids(data));
+    reply(state, connection_id, &subscribed).await;
+}
+
+/// The connection's tenants as sorted hex ids.
+pub fn to_hex(tenant_ids: &HashSet<ObjectId>) -> Vec<String> {
+    let mut ids: Vec<String> = tenant_ids.iter().map(|t| t.to_hex()).collect();
+    ids.sort();
+    ids
+}
+
+async fn reply(state: &AppState, connection_id: &str, subscribed: &HashSet<ObjectId>) {
+    let msg = serde_json::json!({
+        "type": "tenant:subscribed",
+        "data": { "tenant_ids": to_hex(subscribed) }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+fn tenant_ids(data: Option<&serde_json::Value>) -> Vec<ObjectId> {
+    data.and_then(|d| d.get("tenant_ids"))
+        .and_then(|ids| ids.as_array())
+        .map(|ids| {
+            ids.iter()
+                .filter_map(|id| id.as_str())
+                .filter_map(|id| ObjectId::parse_str(id).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_tenants_parameter() {
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+        assert_eq!(
+            parse_ids(&format!("{}, {},", a.to_hex(), b.to_hex())),
+            Some(vec![a, b])
+        );
+        assert_eq!(parse_ids(""), Some(Vec::new()));
+        assert_eq!(parse_ids(&format!("{},nope", a.to_hex())), None);
+    }
+}
@@ -0,0 +1,203 @@
+//! Pre-call device test ("check your mic and network").
+//!
+//! `media:test_join { duration_secs? }` opens a loopback Router for the
+//! connection and answers `media:test_ready { test_id, rtp_capabilities,
+//! send_transport, recv_transport, ice_servers, force_relay, duration_secs }`.
+//! The client then uses the usual `media:connect_transport`,
+//! `media:produce` and `media:consume` with `room_id: test_id`, consuming
+//! its own producers to hear and see itself as the others would.
+//!
+//! While the test runs, `media:test_ping { seq }` is answered right away
+//! with `media:test_pong { seq, server_time }` for the signaling round trip,
+//! and `media:test_stats { test_id }` with the server's view of the media
+//! path (`uplink_bps`, `downlink_bps`, `downlink_estimate_bps` and packet
+//! loss both ways). The test ends with `media:test_leave { test_id }`, when
+//! the socket closes, or after `duration_secs`; the last sends
+//! `media:test_ended { test_id, reason: "expired" }`. Test media isn't
+//! metered.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use roomler_ai_services::turn::TurnService;
+use tracing::{debug, warn};
+
+use super::handler::send_media_error;
+use crate::state::AppState;
+
+/// Length of a test when the client doesn't ask for one.
+const DEFAULT_DURATION: Duration = Duration::from_secs(30);
+/// Longest test a client may ask for.
+const MAX_DURATION: Duration = Duration::from_secs(120);
+
+/// The test length a client asked for, clamped to [1 s, MAX_DURATION].
+fn requested_duration(data: Option<&serde_json::Value>) -> Duration {
+    data.and_then(|d| d.get("duration_secs"))
+        .and_then(|v| v.as_u64())
+        .map(|secs| Duration::from_secs(secs.max(1)).min(MAX_DURATION))
+        .unwrap_or(DEFAULT_DURATION)
+}
+
+fn test_id(data: Option<&serde_json::Value>) -> Option<ObjectId> {
+    data.and_then(|d| d.get("test_id"))
+        .and_then(|v| v.as_str())
+        .and_then(|s| ObjectId::parse_str(s).ok())
+}
+
+pub async fn handle_join(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    client_country: Option<&str>,
+    data: Option<&serde_json::Value>,
+) {
+    if state
+        .room_manager
+        .get_connection_room(connection_id)
+        .is_some()
+    {
+        send_media_error(state, user_id, "Leave the call before a device test").await;
+        return;
+    }
+
+    let duration = requested_duration(data);
+    let test_id = ObjectId::new();
+    let rtp_capabilities = match state.room_manager.create_loopback(test_id).await {
+        Ok(caps) => caps,
+        Err(e) => {
+            send_media_error(state, user_id, &format!("Device test failed: {}", e)).await;
+            return;
+        }
+    };
+    let transports = match state
+        .room_manager
+        .create_transports(test_id, *user_id, connection_id.to_string())
+        .await
+    {
+        Ok(tp) => tp,
+        Err(e) => {
+            state.room_manager.remove_room(&test_id);
+            send_media_error(state, user_id, &format!("Device test failed: {}", e)).await;
+            return;
+        }
+    };
+
+    let runtime = state.runtime.load();
+    let turn = &runtime.turn;
+    let ice = TurnService::new(turn).ice_servers(&user_id.to_hex(), client_country, |name| {
+        state.turn_region_stats.is_healthy(name)
+    });
+    let msg = serde_json::json!({
+        "type": "media:test_ready",
+        "data": {
+            "test_id": test_id.to_hex(),
+            "rtp_capabilities": rtp_capabilities,
+            "send_transport": transports.send_transport,
+            "recv_transport": transports.recv_transport,
+            "ice_servers": ice.servers,
+            "force_relay": turn.force_relay.unwrap_or(false),
+            "duration_secs": duration.as_secs(),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+    debug!(?user_id, %connection_id, %test_id, "Device test started");
+
+    let state = state.clone();
+    let connection_id = connection_id.to_string();
+    tokio::spawn(async move {
+        tokio::time::sleep(duration).await;
+        if state.room_manager.is_loopback(&test_id) && state.room_manager.remove_room(&test_id) {
+            let msg = serde_json::json!({
+                "type": "media:test_ended",
+                "data": { "test_id": test_id.to_hex(), "reason": "expired" }
+            });
+            super::dispatcher::send_to_connection(&state.ws_storage, &connection_id, &msg).await;
+        }
+    });
+}
+
+pub async fn handle_ping(state: &AppState, connection_id: &str, data: Option<&serde_json::Value>) {
+    let msg = serde_json::json!({
+        "type": "media:test_pong",
+        "data": {
+            "seq": data.and_then(|d| d.get("seq")).cloned(),
+            "server_time": chrono::Utc::now().timestamp_millis(),
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+pub async fn handle_stats(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(test_id) = test_id(data) else {
+        send_media_error(state, user_id, "Invalid test_id").await;
+        return;
+    };
+    match state
+        .room_manager
+        .loopback_stats(&test_id, connection_id)
+        .await
+    {
+        Ok(stats) => {
+            let msg = serde_json::json!({
+                "type": "media:test_stats",
+                "data": {
+                    "test_id": test_id.to_hex(),
+                    "uplink_bps": stats.uplink_bps,
+                    "downlink_bps": stats.downlink_bps,
+                    "downlink_estimate_bps": stats.downlink_estimate_bps,
+                    "uplink_packet_loss": stats.uplink_packet_loss,
+                    "downlink_packet_loss": stats.downlink_packet_loss,
+                }
+            });
+            super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+        }
+        Err(e) => {
+            warn!(%connection_id, %test_id, %e, "Device test stats failed");
+            send_media_error(state, user_id, &format!("test_stats failed: {}", e)).await;
+        }
+    }
+}
+
+pub async fn handle_leave(state: &AppState, connection_id: &str, data: Option<&serde_json::Value>) {
+    let Some(test_id) = test_id(data) else {
+        return;
+    };
+    if state.room_manager.is_loopback(&test_id)
+        && state.room_manager.is_participant(&test_id, connection_id)
+    {
+        state.room_manager.remove_room(&test_id);
+        debug!(%connection_id, %test_id, "Device test ended");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn duration_defaults_and_is_clamped() {
+        assert_eq!(requested_duration(None), DEFAULT_DURATION);
+        let asked = |v: serde_json::Value| requested_duration(Some(&v));
+        assert_eq!(
+            asked(serde_json::json!({ "duration_secs": 10 })),
+            Duration::from_secs(10)
+        );
+        assert_eq!(
+            asked(serde_json::json!({ "duration_secs": 0 })),
+            Duration::from_secs(1)
+        );
+        assert_eq!(
+            asked(serde_json::json!({ "duration_secs": 3600 })),
+            MAX_DURATION
+        );
+        assert_eq!(
+            asked(serde_json::json!({ "duration_secs": "long" })),
+            DEFAULT_DURATION
+        );
+    }
+}
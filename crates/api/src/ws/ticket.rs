@@ -0,0 +1,121 @@
+//! One-time WebSocket tickets.
+//!
+//! `GET /ws?token=` puts the access token in the URL, where proxies and
+//! load balancers log it. A client can instead trade its token for a ticket
+//! over `POST /api/auth/ws-ticket` and connect with `/ws?ticket=`. A ticket
+//! expires after `ws.ticket_ttl_secs` and opens one connection only: its
+//! `jti` is recorded on redemption, in Redis when configured so a replay
+//! fails on every instance, otherwise in [`AppState::ws_tickets`].
+//!
+//! A ticket may name `media_rooms`; the connection it opens can then only
+//! send `media:*` messages carrying one of those `room_id`s (device tests
+//! excepted). Other traffic is unaffected.
+
+use bson::oid::ObjectId;
+use tracing::warn;
+
+use crate::state::AppState;
+
+const USED_KEY_PREFIX: &str = "roomler:ws:ticket:";
+
+/// Who a redeemed ticket authenticates and what media it allows.
+pub struct Redeemed {
+    pub user_id: ObjectId,
+    pub username: String,
+    /// `None` allows media in every room.
+    pub media_rooms: Option<Vec<ObjectId>>,
+}
+
+/// Verify a ticket and mark it used. `None` if it is invalid, expired or
+/// was redeemed before.
+pub async fn redeem(state: &AppState, ticket: &str) -> Option<Redeemed> {
+    let claims = state.auth.verify_ws_ticket(ticket).ok()?;
+    let user_id = ObjectId::parse_str(&claims.sub).ok()?;
+    let media_rooms = match claims.media_rooms {
+        Some(ids) => Some(
+            ids.iter()
+                .map(|id| ObjectId::parse_str(id).ok())
+                .collect::<Option<Vec<_>>>()?,
+        ),
+        None => None,
+    };
+
+    let now = chrono::Utc::now().timestamp();
+    let first_use = match &state.redis_pubsub {
+        Some(redis) => {
+            let ttl = u64::try_from(claims.exp - now).unwrap_or(0);
+            match redis
+                .set_once(&format!("{USED_KEY_PREFIX}{}", claims.jti), ttl)
+                .await
+            {
+                Ok(first) => first,
+                Err(e) => {
+                    warn!(%e, "ws ticket redemption check failed, using local record");
+                    mark_used(state, &claims.jti, claims.exp, now)
+                }
+            }
+        }
+        None => mark_used(state, &claims.jti, claims.exp, now),
+    };
+    first_use.then_some(Redeemed {
+        user_id,
+        username: claims.username,
+        media_rooms,
+    })
+}
+
+/// Record `jti` locally, dropping records of tickets that have expired
+/// anyway. True if it wasn't recorded yet.
+fn mark_used(state: &AppState, jti: &str, exp: i64, now: i64) -> bool {
+    state.ws_tickets.retain(|_, e| *e >= now);
+    match state.ws_tickets.entry(jti.to_string()) {
+        dashmap::mapref::entry::Entry::Occupied(_) => false,
+        dashmap::mapref::entry::Entry::Vacant(v) => {
+            v.insert(exp);
+            true
+        }
+    }
+}
+
+/// Whether a connection limited to `media_rooms` may send this `media:*`
+/// message.
+pub fn allows_media(
+    media_rooms: Option<&[ObjectId]>,
+    msg_type: &str,
+    data: Option<&serde_json::Value>,
+) -> bool {
+    let Some(rooms) = media_rooms else {
+        return true;
+    };
+    if msg_type.starts_with("media:test_") {
+        return true;
+    }
+    data.and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())
+        .is_some_and(|rid| rooms.contains(&rid))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_connections_only_signal_their_rooms() {
+        let (room, other) = (ObjectId::new(), ObjectId::new());
+        let scope = [room];
+        let join = |rid: &ObjectId| serde_json::json!({ "room_id": rid.to_hex() });
+
+        assert!(allows_media(None, "media:join", Some(&join(&other))));
+        assert!(allows_media(Some(&scope), "media:join", Some(&join(&room))));
+        assert!(!allows_media(
+            Some(&scope),
+            "media:join",
+            Some(&join(&other))
+        ));
+        // A rejoin has to name its room.
+        let rejoin = serde_json::json!({ "resume_token": "t" });
+        assert!(!allows_media(Some(&scope), "media:rejoin", Some(&rejoin)));
+        assert!(allows_media(Some(&scope), "media:test_join", None));
+    }
+}
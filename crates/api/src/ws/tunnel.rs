@@ -676,7 +676,7 @@ async fn handle_tunnel_open(
     // both peers derive identical, session-scoped creds.
     let quic_ice_servers = roomler_ai_remote_control::turn_creds::ice_servers_for(
         &tunnel_session_id.to_hex(),
-        crate::state::build_turn_config(&state.settings.turn).as_ref(),
+        crate::state::build_turn_config(&state.runtime.load().turn).as_ref(),
     );
 
     // For quic-v1, tell the agent to stand up its endpoint + authorize
@@ -0,0 +1,240 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+use dashmap::DashMap;
+use roomler_ai_config::TurnSettings;
+use roomler_ai_services::turn::TurnService;
+use tokio::net::{TcpStream, UdpSocket};
+
+use crate::state::AppState;
+
+/// Country headers set by the edge (Cloudflare, or an ingress running the
+/// GeoIP2 module against the connecting IP), checked in order. The API pod
+/// itself only ever sees the ingress IP, so the lookup has to happen there.
+const COUNTRY_HEADERS: &[&str] = &["cf-ipcountry", "x-geoip-country", "x-country-code"];
+
+/// Consecutive failed probes after which a region is left out of joins.
+const FAILURES_TO_DROP: u32 = 2;
+/// How long a probe waits for the region to answer.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+const STUN_MAGIC_COOKIE: [u8; 4] = [0x21, 0x12, 0xa4, 0x42];
+
+/// The client's country as reported by the edge, if any.
+pub fn client_country(headers: &HeaderMap) -> Option<String> {
+    COUNTRY_HEADERS.iter().find_map(|h| {
+        headers
+            .get(*h)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_ascii_uppercase())
+            // Cloudflare uses XX (unknown) and T1 (Tor).
+            .filter(|v| v.len() == 2 && v != "XX" && v != "T1")
+    })
+}
+
+/// Per-region counters of media joins handed a region as their primary
+/// relay, and the regions' health as probed from this pod. Process-local;
+/// surfaced by `GET /api/turn/regions`.
+#[derive(Default)]
+pub struct TurnRegionStats {
+    primary: DashMap<String, AtomicU64>,
+    health: DashMap<String, RegionHealth>,
+}
+
+impl TurnRegionStats {
+    pub fn record_primary(&self, region: &str) {
+        self.primary
+            .entry(region.to_string())
+            .or_default()
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> Vec<(String, u64)> {
+        let mut out: Vec<(String, u64)> = self
+            .primary
+            .iter()
+            .map(|e| (e.key().clone(), e.value().load(Ordering::Relaxed)))
+            .collect();
+        out.sort();
+        out
+    }
+
+    /// Book a health probe: `Some(rtt)` if the region answered.
+    pub fn record_probe(&self, region: &str, rtt: Option<Duration>) {
+        let mut health = self.health.entry(region.to_string()).or_default();
+        match rtt {
+            Some(rtt) => {
+                health.failures = 0;
+                health.rtt_ms = Some(rtt.as_millis() as u64);
+            }
+            None => {
+                health.failures += 1;
+                health.rtt_ms = None;
+            }
+        }
+    }
+
+    /// Whether the region is in rotation. Regions not probed yet are.
+    pub fn is_healthy(&self, region: &str) -> bool {
+        self.health
+            .get(region)
+            .is_none_or(|h| h.failures < FAILURES_TO_DROP)
+    }
+
+    /// Round trip of the region's last answered probe from this pod.
+    pub fn rtt_ms(&self, region: &str) -> Option<u64> {
+        self.health.get(region).and_then(|h| h.rtt_ms)
+    }
+}
+
+#[derive(Default)]
+struct RegionHealth {
+    failures: u32,
+    rtt_ms: Option<u64>,
+}
+
+/// Build the `ice_servers` list for a `media:join`, booking the nearest
+/// region handed out as the join's primary relay.
+pub fn media_ice_servers(
+    turn: &TurnSettings,
+    stats: &TurnRegionStats,
+    user_hex: &str,
+    country: Option<&str>,
+) -> Vec<serde_json::Value> {
+    let ice = TurnService::new(turn).ice_servers(user_hex, country, |name| stats.is_healthy(name));
+    if let Some(primary) = &ice.primary_region {
+        stats.record_primary(primary);
+    }
+    ice.servers
+}
+
+/// Probe every region each `turn.health_check_secs` (0 disables). The
+/// regions are read each round, as they can change at runtime.
+pub(crate) fn spawn_health_checks(state: AppState) {
+    let interval = state.settings.turn.health_check_secs;
+    if interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(Duration::from_secs(interval));
+        loop {
+            tick.tick().await;
+            let regions = TurnService::new(&state.runtime.load().turn).regions();
+            let probes = regions.iter().map(|r| probe(&r.url));
+            let results = futures::future::join_all(probes).await;
+            for (region, rtt) in regions.iter().zip(results) {
+                let was_healthy = state.turn_region_stats.is_healthy(&region.name);
+                state.turn_region_stats.record_probe(&region.name, rtt);
+                let healthy = state.turn_region_stats.is_healthy(&region.name);
+                if was_healthy && !healthy {
+                    tracing::warn!(
+                        region = %region.name,
+                        url = %region.url,
+                        "TURN region down, dropped from rotation"
+                    );
+                } else if !was_healthy && healthy {
+                    tracing::info!(region = %region.name, "TURN region back in rotation");
+                }
+            }
+        }
+    });
+}
+
+/// Probe a TURN server: a STUN Binding request for `turn:` (coturn answers
+/// it without credentials), a TCP connect for `turns:`. Returns the round
+/// trip, or `None` if the server didn't answer in time.
+async fn probe(url: &str) -> Option<Duration> {
+    let (tls, host_port) = match url.split_once(':')? {
+        ("turns", rest) => (true, rest),
+        (_, rest) => (false, rest),
+    };
+    let host_port = host_port.split('?').next().unwrap_or(host_port);
+    let addr = if host_port
+        .rsplit_once(':')
+        .is_some_and(|(_, p)| p.parse::<u16>().is_ok())
+    {
+        host_port.to_string()
+    } else {
+        format!("{}:{}", host_port, if tls { 5349 } else { 3478 })
+    };
+
+    let started = Instant::now();
+    let answered = tokio::time::timeout(PROBE_TIMEOUT, async {
+        let target = tokio::net::lookup_host(&addr).await.ok()?.next()?;
+        if tls {
+            return TcpStream::connect(target).await.ok().map(|_| ());
+        }
+        let bind = if target.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(bind).await.ok()?;
+        let request = stun_binding_request();
+        socket.send_to(&request, target).await.ok()?;
+        let mut buf = [0u8; 512];
+        loop {
+            let (n, from) = socket.recv_from(&mut buf).await.ok()?;
+            if from == target && is_binding_success(&buf[..n], &request[8..20]) {
+                return Some(());
+            }
+        }
+    })
+    .await;
+    matches!(answered, Ok(Some(()))).then(|| started.elapsed())
+}
+
+/// A 20-byte STUN Binding request (RFC 5389) with a random transaction id.
+fn stun_binding_request() -> [u8; 20] {
+    let mut msg = [0u8; 20];
+    msg[0..2].copy_from_slice(&[0x00, 0x01]);
+    msg[4..8].copy_from_slice(&STUN_MAGIC_COOKIE);
+    msg[8..20].copy_from_slice(&uuid::Uuid::new_v4().as_bytes()[..12]);
+    msg
+}
+
+fn is_binding_success(msg: &[u8], transaction_id: &[u8]) -> bool {
+    msg.len() >= 20
+        && msg[0..2] == [0x01, 0x01]
+        && msg[4..8] == STUN_MAGIC_COOKIE
+        && msg[8..20] == *transaction_id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_country_from_edge_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("cf-ipcountry", "xx".parse().unwrap());
+        headers.insert("x-geoip-country", "de".parse().unwrap());
+        assert_eq!(client_country(&headers).as_deref(), Some("DE"));
+        assert_eq!(client_country(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn two_missed_probes_drop_a_region() {
+        let stats = TurnRegionStats::default();
+        assert!(stats.is_healthy("eu"), "unprobed regions are in rotation");
+        stats.record_probe("eu", None);
+        assert!(stats.is_healthy("eu"), "one missed probe keeps a region");
+        stats.record_probe("eu", None);
+        assert!(!stats.is_healthy("eu"));
+
+        stats.record_probe("eu", Some(Duration::from_millis(12)));
+        assert!(stats.is_healthy("eu"));
+        assert_eq!(stats.rtt_ms("eu"), Some(12));
+    }
+
+    #[test]
+    fn recognises_the_matching_binding_response() {
+        let request = stun_binding_request();
+        let mut response = request;
+        response[0..2].copy_from_slice(&[0x01, 0x01]);
+        assert!(is_binding_success(&response, &request[8..20]));
+        assert!(!is_binding_success(&request, &request[8..20]));
+        let other = stun_binding_request();
+        assert!(!is_binding_success(&response, &other[8..20]));
+    }
+}
@@ -0,0 +1,134 @@
+//! Webinar mode signaling.
+//!
+//! In a room with `media_settings.webinar` only speakers may produce:
+//! `RoomManager::produce` refuses everyone else, so attendees can't send
+//! media whatever their client does. Organizers promote and demote speakers
+//! over REST; demoting closes the user's producers. Attendees never appear as
+//! peers, so clients show them as a count rather than in the grid.
+//!
+//! Role changes go to every connection in the media room as
+//! `media:speaker_update { room_id, user_id, speaker, speaker_count,
+//! attendee_count }`. A connection joining a webinar gets
+//! `media:webinar_state { room_id, speaker, speakers, speaker_count,
+//! attendee_count }` after the existing producers. Attendees coming and
+//! going aren't announced one by one: every [`COUNT_INTERVAL`] a webinar
+//! whose counts changed gets `media:webinar_counts { room_id,
+//! speaker_count, attendee_count }`.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use mediasoup::prelude::ProducerId;
+
+use crate::state::AppState;
+
+const COUNT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Send `msg` to every connection in the media room.
+async fn send_to_room(state: &AppState, room_id: &ObjectId, msg: &serde_json::Value) {
+    for conn_id in state.room_manager.get_other_connection_ids(room_id, "") {
+        super::dispatcher::send_to_connection(&state.ws_storage, &conn_id, msg).await;
+    }
+}
+
+/// Tell the room a user was promoted or demoted, and the others that the
+/// demoted user's producers are gone.
+pub async fn announce_role(
+    state: &AppState,
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    speaker: bool,
+    closed: &[(String, ProducerId)],
+) {
+    for (conn_id, producer_id) in closed {
+        state
+            .room_manager
+            .remove_rtp_tap(room_id, &producer_id.to_string());
+        let msg = serde_json::json!({
+            "type": "media:producer_closed",
+            "data": {
+                "producer_id": producer_id.to_string(),
+                "user_id": user_id.to_hex(),
+            }
+        });
+        for other in state
+            .room_manager
+            .get_other_connection_ids(room_id, conn_id)
+        {
+            super::dispatcher::send_to_connection(&state.ws_storage, &other, &msg).await;
+        }
+    }
+
+    let (speaker_count, attendee_count) = state.room_manager.webinar_counts(room_id);
+    let msg = serde_json::json!({
+        "type": "media:speaker_update",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "user_id": user_id.to_hex(),
+            "speaker": speaker,
+            "speaker_count": speaker_count,
+            "attendee_count": attendee_count,
+        }
+    });
+    send_to_room(state, room_id, &msg).await;
+}
+
+/// Send a connection joining a webinar its role and the current speakers.
+pub async fn replay_to(
+    state: &AppState,
+    room_id: &ObjectId,
+    user_id: &ObjectId,
+    connection_id: &str,
+) {
+    if !state.room_manager.is_webinar(room_id) {
+        return;
+    }
+    let speakers = state.room_manager.speakers(room_id);
+    let (speaker_count, attendee_count) = state.room_manager.webinar_counts(room_id);
+    let msg = serde_json::json!({
+        "type": "media:webinar_state",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "speaker": speakers.contains(user_id),
+            "speakers": speakers.iter().map(|s| s.to_hex()).collect::<Vec<_>>(),
+            "speaker_count": speaker_count,
+            "attendee_count": attendee_count,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// Spawn the loop that sends webinars their changed speaker and attendee
+/// counts.
+pub(crate) fn spawn_counter(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(COUNT_INTERVAL);
+        let mut last: HashMap<ObjectId, (usize, usize)> = HashMap::new();
+        loop {
+            tick.tick().await;
+            let webinars: Vec<ObjectId> = state
+                .room_manager
+                .room_ids()
+                .into_iter()
+                .filter(|id| state.room_manager.is_webinar(id))
+                .collect();
+            last.retain(|id, _| webinars.contains(id));
+            for room_id in webinars {
+                let counts = state.room_manager.webinar_counts(&room_id);
+                if last.insert(room_id, counts) == Some(counts) {
+                    continue;
+                }
+                let msg = serde_json::json!({
+                    "type": "media:webinar_counts",
+                    "data": {
+                        "room_id": room_id.to_hex(),
+                        "speaker_count": counts.0,
+                        "attendee_count": counts.1,
+                    }
+                });
+                send_to_room(&state, &room_id, &msg).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,283 @@
+//! In-call whiteboard sync.
+//!
+//! Each room's board lives in [`AppState::live_whiteboards`] while it is in use,
+//! loaded from the `whiteboards` collection on first touch. Clients send
+//! `whiteboard:op { room_id, op, client_op_id? }`; ops are applied in arrival
+//! order and relayed to the room's members as `whiteboard:op` with a
+//! server-assigned `seq` (the sender's own op comes back too, which is its
+//! acknowledgement). A client that joins late, or sees a gap in `seq`, sends
+//! `whiteboard:sync { room_id }` and gets a `whiteboard:snapshot`.
+//!
+//! Dirty boards are written back every [`SNAPSHOT_INTERVAL`]; ending the call
+//! flushes the board, exports it as an image attached to the room and drops
+//! it from memory.
+
+use std::time::Duration;
+
+use bson::oid::ObjectId;
+use roomler_ai_services::whiteboard::{Board, WhiteboardOp};
+use tracing::{debug, warn};
+
+use crate::error::ApiError;
+use crate::state::AppState;
+
+pub const SNAPSHOT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Upper bound on a single serialized op.
+const MAX_OP_LEN: usize = 64 * 1024;
+
+/// A board held in memory, with whether it has ops not yet persisted.
+pub struct LiveBoard {
+    pub tenant_id: ObjectId,
+    pub board: Board,
+    pub dirty: bool,
+}
+
+/// Load the room's board into memory if it isn't there yet.
+async fn ensure_loaded(state: &AppState, room_id: ObjectId) -> Result<(), ApiError> {
+    if state.live_whiteboards.contains_key(&room_id) {
+        return Ok(());
+    }
+    // Callers have checked the user is a member (`member_room`); the room is
+    // read for its tenant
+    let room = state.rooms.base.find_by_id_unscoped(room_id).await?;
+    let board = match state.whiteboards.find_by_room(room_id).await? {
+        Some(saved) => Board::new(saved.seq, saved.elements),
+        None => Board::default(),
+    };
+    // Another task may have loaded it meanwhile; keep whichever came first.
+    state.live_whiteboards.entry(room_id).or_insert(LiveBoard {
+        tenant_id: room.tenant_id,
+        board,
+        dirty: false,
+    });
+    Ok(())
+}
+
+/// The room's current board: the live copy if loaded, else the saved one.
+pub async fn current(state: &AppState, room_id: ObjectId) -> Result<Board, ApiError> {
+    if let Some(live) = state.live_whiteboards.get(&room_id) {
+        return Ok(live.board.clone());
+    }
+    Ok(state
+        .whiteboards
+        .find_by_room(room_id)
+        .await?
+        .map(|saved| Board::new(saved.seq, saved.elements))
+        .unwrap_or_default())
+}
+
+/// Room id of a `whiteboard:*` message, if the sender is a member of the room.
+async fn member_room(
+    state: &AppState,
+    user_id: &ObjectId,
+    data: Option<&serde_json::Value>,
+) -> Option<ObjectId> {
+    let rid = data
+        .and_then(|d| d.get("room_id"))
+        .and_then(|r| r.as_str())
+        .and_then(|r| ObjectId::parse_str(r).ok())?;
+    let members = state.rooms.find_member_user_ids(rid).await.ok()?;
+    members.contains(user_id).then_some(rid)
+}
+
+async fn send_error(
+    state: &AppState,
+    connection_id: &str,
+    room_id: &ObjectId,
+    client_op_id: Option<&serde_json::Value>,
+    message: &str,
+) {
+    let msg = serde_json::json!({
+        "type": "whiteboard:error",
+        "data": {
+            "room_id": room_id.to_hex(),
+            "client_op_id": client_op_id,
+            "message": message,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// Apply a client's op and relay it to the room with its sequence number.
+pub async fn handle_op(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = member_room(state, user_id, data).await else {
+        return;
+    };
+    let client_op_id = data.and_then(|d| d.get("client_op_id"));
+    let Some(raw) = data.and_then(|d| d.get("op")) else {
+        send_error(state, connection_id, &rid, client_op_id, "Missing op").await;
+        return;
+    };
+    if raw.to_string().len() > MAX_OP_LEN {
+        send_error(state, connection_id, &rid, client_op_id, "Op too large").await;
+        return;
+    }
+    let op: WhiteboardOp = match serde_json::from_value(raw.clone()) {
+        Ok(op) => op,
+        Err(e) => {
+            let message = format!("Invalid op: {}", e);
+            send_error(state, connection_id, &rid, client_op_id, &message).await;
+            return;
+        }
+    };
+    if let Err(e) = ensure_loaded(state, rid).await {
+        warn!(?rid, %e, "Failed to load whiteboard");
+        send_error(
+            state,
+            connection_id,
+            &rid,
+            client_op_id,
+            "Whiteboard unavailable",
+        )
+        .await;
+        return;
+    }
+
+    // Apply and stamp under the entry lock so seq order matches apply order.
+    let result = match state.live_whiteboards.get_mut(&rid) {
+        Some(mut live) => {
+            let result = live.board.apply(&op);
+            live.dirty |= result.is_ok();
+            result.map(|seq| (live.tenant_id, seq))
+        }
+        None => Err("Whiteboard unavailable".to_string()),
+    };
+    let (tenant_id, seq) = match result {
+        Ok(applied) => applied,
+        Err(message) => {
+            send_error(state, connection_id, &rid, client_op_id, &message).await;
+            return;
+        }
+    };
+
+    let Ok(recipients) = state.rooms.find_member_user_ids(rid).await else {
+        return;
+    };
+    let event = serde_json::json!({
+        "type": "whiteboard:op",
+        "tenant_id": tenant_id.to_hex(),
+        "data": {
+            "room_id": rid.to_hex(),
+            "seq": seq,
+            "user_id": user_id.to_hex(),
+            "client_op_id": client_op_id,
+            "op": raw,
+        }
+    });
+    super::dispatcher::broadcast_with_redis(
+        &state.ws_storage,
+        &state.redis_pubsub,
+        &recipients,
+        &event,
+    )
+    .await;
+}
+
+/// Send the requesting connection the full board.
+pub async fn handle_sync(
+    state: &AppState,
+    user_id: &ObjectId,
+    connection_id: &str,
+    data: Option<&serde_json::Value>,
+) {
+    let Some(rid) = member_room(state, user_id, data).await else {
+        return;
+    };
+    if let Err(e) = ensure_loaded(state, rid).await {
+        warn!(?rid, %e, "Failed to load whiteboard");
+        send_error(state, connection_id, &rid, None, "Whiteboard unavailable").await;
+        return;
+    }
+    let Some((seq, elements)) = state
+        .live_whiteboards
+        .get(&rid)
+        .map(|live| (live.board.seq, live.board.elements.clone()))
+    else {
+        return;
+    };
+    let msg = serde_json::json!({
+        "type": "whiteboard:snapshot",
+        "data": {
+            "room_id": rid.to_hex(),
+            "seq": seq,
+            "elements": elements,
+        }
+    });
+    super::dispatcher::send_to_connection(&state.ws_storage, connection_id, &msg).await;
+}
+
+/// Persist the room's board if it has unsaved ops.
+pub async fn flush(state: &AppState, room_id: ObjectId) -> Result<(), ApiError> {
+    let Some((tenant_id, board)) = state
+        .live_whiteboards
+        .get(&room_id)
+        .filter(|live| live.dirty)
+        .map(|live| (live.tenant_id, live.board.clone()))
+    else {
+        return Ok(());
+    };
+    state
+        .whiteboards
+        .save_snapshot(tenant_id, room_id, board.seq, &board.elements)
+        .await?;
+    // Ops applied while saving keep the board dirty for the next round.
+    if let Some(mut live) = state.live_whiteboards.get_mut(&room_id)
+        && live.board.seq == board.seq
+    {
+        live.dirty = false;
+    }
+    debug!(?room_id, seq = board.seq, "Whiteboard snapshot saved");
+    Ok(())
+}
+
+/// Spawn the loop that periodically persists dirty boards.
+pub(crate) fn spawn_snapshotter(state: AppState) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(SNAPSHOT_INTERVAL);
+        loop {
+            tick.tick().await;
+            let dirty: Vec<ObjectId> = state
+                .live_whiteboards
+                .iter()
+                .filter(|live| live.dirty)
+                .map(|live| *live.key())
+                .collect();
+            for room_id in dirty {
+                if let Err(e) = flush(&state, room_id).await {
+                    warn!(?room_id, %e, "Failed to save whiteboard snapshot");
+                }
+            }
+        }
+    });
+}
+
+/// At call end: save the board, export it to the room if anything was drawn,
+/// and release it from memory. Failures are logged; they never block ending
+/// the call.
+pub(crate) async fn finish(state: &AppState, room_id: ObjectId, ended_by: ObjectId) {
+    if !state.live_whiteboards.contains_key(&room_id) {
+        return;
+    }
+    if let Err(e) = flush(state, room_id).await {
+        warn!(?room_id, %e, "Failed to save whiteboard at call end");
+        return;
+    }
+    let drawn_in = state
+        .live_whiteboards
+        .get(&room_id)
+        .filter(|live| !live.board.elements.is_empty())
+        .map(|live| live.tenant_id);
+    if let Some(tenant_id) = drawn_in
+        && let Err(e) =
+            crate::routes::whiteboard::export_board(state, tenant_id, room_id, ended_by).await
+    {
+        warn!(?room_id, %e, "Failed to export whiteboard at call end");
+    }
+    state.live_whiteboards.remove(&room_id);
+}
@@ -0,0 +1,36 @@
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ClientError {
+    /// Non-2xx response. `message` is the `message` field when the
+    /// body is the standard API error envelope, otherwise the raw body.
+    #[error("API error {status}: {message}")]
+    Api { status: u16, message: String },
+
+    #[error("Not authenticated")]
+    NotAuthenticated,
+
+    #[error("HTTP error: {0}")]
+    Http(#[from] reqwest::Error),
+
+    #[error("WebSocket error: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+
+    #[error("WebSocket closed")]
+    WsClosed,
+
+    #[error("Serialization error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+impl ClientError {
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::Api { status, .. } => Some(*status),
+            ClientError::Http(e) => e.status().map(|s| s.as_u16()),
+            _ => None,
+        }
+    }
+}
+
+pub type ClientResult<T> = Result<T, ClientError>;
@@ -0,0 +1,35 @@
+//! Rust client for the Roomler REST and WebSocket APIs.
+//!
+//! [`Client`] wraps the REST endpoints with typed requests/responses and
+//! keeps the access/refresh token pair fresh; [`WsClient`] holds a
+//! reconnecting `/ws` connection and exposes the media signaling messages.
+//! Bots and integration tests should use these instead of hand-rolling JSON.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), roomler_ai_client::ClientError> {
+//! use roomler_ai_client::{Client, ListParams};
+//!
+//! let client = Client::new("https://roomler.ai");
+//! client.login("bot", "secret").await?;
+//! let tenants = client.tenants().await?;
+//! let rooms = client.rooms(&tenants[0].id).await?;
+//! let page = client.messages(&tenants[0].id, &rooms[0].id, &ListParams::cursor()).await?;
+//! let mut ws = client.connect_ws().await?;
+//! ws.media_join(&rooms[0].id)?;
+//! while let Some(event) = ws.next_event().await {
+//!     println!("{} {}", event.kind, event.data);
+//! }
+//! # let _ = page;
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod error;
+pub mod rest;
+pub mod types;
+pub mod ws;
+
+pub use error::{ClientError, ClientResult};
+pub use rest::Client;
+pub use types::*;
+pub use ws::{WsClient, WsEvent};
@@ -0,0 +1,431 @@
+use std::sync::{Arc, RwLock};
+
+use reqwest::{Method, RequestBuilder, Response, StatusCode, multipart};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tracing::debug;
+
+use crate::error::{ClientError, ClientResult};
+use crate::types::*;
+use crate::ws::WsClient;
+
+#[derive(Debug, Clone)]
+struct Tokens {
+    access: String,
+    refresh: Option<String>,
+}
+
+/// REST client. Cheap to clone; clones share the token pair, so a refresh
+/// done by one is seen by all (including a [`WsClient`] reconnecting).
+#[derive(Clone)]
+pub struct Client {
+    http: reqwest::Client,
+    base_url: String,
+    tokens: Arc<RwLock<Option<Tokens>>>,
+}
+
+impl Client {
+    /// `base_url` is the server origin, e.g. `https://roomler.ai`.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self::with_http(base_url, reqwest::Client::new())
+    }
+
+    pub fn with_http(base_url: impl Into<String>, http: reqwest::Client) -> Self {
+        Self {
+            http,
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            tokens: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Use an existing token pair (e.g. a bot token from configuration).
+    /// Without a refresh token, an expired access token surfaces as a 401.
+    pub fn set_tokens(&self, access_token: impl Into<String>, refresh_token: Option<String>) {
+        *self.tokens.write().unwrap() = Some(Tokens {
+            access: access_token.into(),
+            refresh: refresh_token,
+        });
+    }
+
+    pub fn access_token(&self) -> Option<String> {
+        self.tokens
+            .read()
+            .unwrap()
+            .as_ref()
+            .map(|t| t.access.clone())
+    }
+
+    // ── Auth ─────────────────────────────────────────────────
+
+    /// Register an account. When the server auto-verifies, the returned
+    /// tokens are stored and the client is logged in.
+    pub async fn register(&self, req: &RegisterRequest) -> ClientResult<RegisterResponse> {
+        let resp: RegisterResponse = decode(
+            self.http
+                .post(self.url("/api/auth/register"))
+                .json(req)
+                .send()
+                .await?,
+        )
+        .await?;
+        if let Some(access) = &resp.access_token {
+            self.set_tokens(access.clone(), resp.refresh_token.clone());
+        }
+        Ok(resp)
+    }
+
+    /// Log in by username, or by email when `login` contains an `@`.
+    pub async fn login(&self, login: &str, password: &str) -> ClientResult<AuthResponse> {
+        let body = if login.contains('@') {
+            serde_json::json!({ "email": login, "password": password })
+        } else {
+            serde_json::json!({ "username": login, "password": password })
+        };
+        let resp: AuthResponse = decode(
+            self.http
+                .post(self.url("/api/auth/login"))
+                .json(&body)
+                .send()
+                .await?,
+        )
+        .await?;
+        self.store(&resp);
+        Ok(resp)
+    }
+
+    /// Exchange the refresh token for a new pair. Called automatically when
+    /// a request comes back 401.
+    pub async fn refresh(&self) -> ClientResult<AuthResponse> {
+        let refresh_token = self
+            .tokens
+            .read()
+            .unwrap()
+            .as_ref()
+            .and_then(|t| t.refresh.clone())
+            .ok_or(ClientError::NotAuthenticated)?;
+        let resp: AuthResponse = decode(
+            self.http
+                .post(self.url("/api/auth/refresh"))
+                .json(&serde_json::json!({ "refresh_token": refresh_token }))
+                .send()
+                .await?,
+        )
+        .await?;
+        debug!(user_id = %resp.user.id, "access token refreshed");
+        self.store(&resp);
+        Ok(resp)
+    }
+
+    pub async fn logout(&self) -> ClientResult<()> {
+        let resp = self.http.post(self.url("/api/auth/logout")).send().await;
+        *self.tokens.write().unwrap() = None;
+        check(resp?).await
+    }
+
+    pub async fn me(&self) -> ClientResult<UserResponse> {
+        self.get("/api/auth/me").await
+    }
+
+    fn store(&self, resp: &AuthResponse) {
+        self.set_tokens(resp.access_token.clone(), Some(resp.refresh_token.clone()));
+    }
+
+    // ── Tenants & rooms ──────────────────────────────────────
+
+    pub async fn tenants(&self) -> ClientResult<Vec<TenantResponse>> {
+        self.get("/api/tenant").await
+    }
+
+    pub async fn tenant(&self, tenant_id: &str) -> ClientResult<TenantResponse> {
+        self.get(&format!("/api/tenant/{tenant_id}")).await
+    }
+
+    pub async fn rooms(&self, tenant_id: &str) -> ClientResult<Vec<RoomResponse>> {
+        self.get(&format!("/api/tenant/{tenant_id}/room")).await
+    }
+
+    pub async fn room(&self, tenant_id: &str, room_id: &str) -> ClientResult<RoomResponse> {
+        self.get(&format!("/api/tenant/{tenant_id}/room/{room_id}"))
+            .await
+    }
+
+    pub async fn create_room(
+        &self,
+        tenant_id: &str,
+        req: &CreateRoomRequest,
+    ) -> ClientResult<RoomResponse> {
+        self.post(&format!("/api/tenant/{tenant_id}/room"), req)
+            .await
+    }
+
+    pub async fn join_room(&self, tenant_id: &str, room_id: &str) -> ClientResult<()> {
+        self.room_action(tenant_id, room_id, "join").await
+    }
+
+    pub async fn leave_room(&self, tenant_id: &str, room_id: &str) -> ClientResult<()> {
+        self.room_action(tenant_id, room_id, "leave").await
+    }
+
+    // ── Calls ────────────────────────────────────────────────
+
+    pub async fn call_start(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+    ) -> ClientResult<CallStartResponse> {
+        self.post(
+            &format!("/api/tenant/{tenant_id}/room/{room_id}/call/start"),
+            &serde_json::json!({}),
+        )
+        .await
+    }
+
+    pub async fn call_join(&self, tenant_id: &str, room_id: &str) -> ClientResult<()> {
+        self.room_action(tenant_id, room_id, "call/join").await
+    }
+
+    pub async fn call_leave(&self, tenant_id: &str, room_id: &str) -> ClientResult<()> {
+        self.room_action(tenant_id, room_id, "call/leave").await
+    }
+
+    pub async fn call_end(&self, tenant_id: &str, room_id: &str) -> ClientResult<()> {
+        self.room_action(tenant_id, room_id, "call/end").await
+    }
+
+    async fn room_action(&self, tenant_id: &str, room_id: &str, action: &str) -> ClientResult<()> {
+        let req = self
+            .http
+            .post(self.url(&format!("/api/tenant/{tenant_id}/room/{room_id}/{action}")))
+            .json(&serde_json::json!({}));
+        let _: serde_json::Value = self.execute(req).await?;
+        Ok(())
+    }
+
+    // ── Messages ─────────────────────────────────────────────
+
+    pub async fn messages(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        params: &ListParams,
+    ) -> ClientResult<Page<MessageResponse>> {
+        self.list(
+            &format!("/api/tenant/{tenant_id}/room/{room_id}/message"),
+            params,
+        )
+        .await
+    }
+
+    pub async fn send_message(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        req: &CreateMessageRequest,
+    ) -> ClientResult<MessageResponse> {
+        self.post(
+            &format!("/api/tenant/{tenant_id}/room/{room_id}/message"),
+            req,
+        )
+        .await
+    }
+
+    /// Post `req` at `send_at` (RFC 3339) instead of now.
+    pub async fn schedule_message(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        req: &CreateMessageRequest,
+        send_at: &str,
+    ) -> ClientResult<ScheduledMessageResponse> {
+        let mut body = serde_json::to_value(req)?;
+        body["send_at"] = send_at.into();
+        self.post(
+            &format!("/api/tenant/{tenant_id}/room/{room_id}/message"),
+            &body,
+        )
+        .await
+    }
+
+    /// Follow (`true`) or unfollow a thread root for reply notifications.
+    pub async fn follow_thread(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        message_id: &str,
+        follow: bool,
+    ) -> ClientResult<()> {
+        let url = self.url(&format!(
+            "/api/tenant/{tenant_id}/room/{room_id}/message/{message_id}/thread/follow"
+        ));
+        let req = if follow {
+            self.http.put(url)
+        } else {
+            self.http.delete(url)
+        };
+        let _: serde_json::Value = self.execute(req).await?;
+        Ok(())
+    }
+
+    // ── Files ────────────────────────────────────────────────
+
+    pub async fn files(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        params: &ListParams,
+    ) -> ClientResult<Page<FileResponse>> {
+        self.list(
+            &format!("/api/tenant/{tenant_id}/room/{room_id}/file"),
+            params,
+        )
+        .await
+    }
+
+    /// Upload a file into a room. Multipart bodies can't be replayed, so a
+    /// 401 here is returned as-is; call [`Client::refresh`] and retry.
+    pub async fn upload_file(
+        &self,
+        tenant_id: &str,
+        room_id: &str,
+        filename: &str,
+        content_type: &str,
+        bytes: Vec<u8>,
+    ) -> ClientResult<FileResponse> {
+        let part = multipart::Part::bytes(bytes)
+            .file_name(filename.to_string())
+            .mime_str(content_type)?;
+        let req = self
+            .http
+            .post(self.url(&format!(
+                "/api/tenant/{tenant_id}/room/{room_id}/file/upload"
+            )))
+            .multipart(multipart::Form::new().part("file", part));
+        self.execute(req).await
+    }
+
+    // ── Notifications ────────────────────────────────────────
+
+    pub async fn notifications(
+        &self,
+        params: &ListParams,
+    ) -> ClientResult<Page<NotificationResponse>> {
+        self.list("/api/notification", params).await
+    }
+
+    pub async fn unread_notifications(
+        &self,
+        params: &ListParams,
+    ) -> ClientResult<Page<NotificationResponse>> {
+        self.list("/api/notification/unread", params).await
+    }
+
+    // ── WebSocket ────────────────────────────────────────────
+
+    /// Open a reconnecting `/ws` connection authenticated as this client.
+    pub async fn connect_ws(&self) -> ClientResult<WsClient> {
+        WsClient::connect(self.clone()).await
+    }
+
+    // ── Plumbing ─────────────────────────────────────────────
+
+    /// Escape hatch for endpoints without a typed wrapper.
+    pub async fn request<T: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: &str,
+        body: Option<&serde_json::Value>,
+    ) -> ClientResult<T> {
+        let mut req = self.http.request(method, self.url(path));
+        if let Some(body) = body {
+            req = req.json(body);
+        }
+        self.execute(req).await
+    }
+
+    pub(crate) fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> ClientResult<T> {
+        self.execute(self.http.get(self.url(path))).await
+    }
+
+    async fn post<B: Serialize + ?Sized, T: DeserializeOwned>(
+        &self,
+        path: &str,
+        body: &B,
+    ) -> ClientResult<T> {
+        self.execute(self.http.post(self.url(path)).json(body))
+            .await
+    }
+
+    async fn list<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        params: &ListParams,
+    ) -> ClientResult<Page<T>> {
+        self.execute(self.http.get(self.url(path)).query(&params.to_query()))
+            .await
+    }
+
+    /// Send with the current access token; on 401, refresh once and replay
+    /// (when the body is replayable and a refresh token is held).
+    async fn execute<T: DeserializeOwned>(&self, req: RequestBuilder) -> ClientResult<T> {
+        let replay = req.try_clone();
+        let resp = self.authorize(req)?.send().await?;
+        if resp.status() == StatusCode::UNAUTHORIZED
+            && let Some(replay) = replay
+            && self.has_refresh_token()
+        {
+            self.refresh().await?;
+            return decode(self.authorize(replay)?.send().await?).await;
+        }
+        decode(resp).await
+    }
+
+    fn authorize(&self, req: RequestBuilder) -> ClientResult<RequestBuilder> {
+        let access = self.access_token().ok_or(ClientError::NotAuthenticated)?;
+        Ok(req.bearer_auth(access))
+    }
+
+    fn has_refresh_token(&self) -> bool {
+        self.tokens
+            .read()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|t| t.refresh.is_some())
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct ErrorBody {
+    message: String,
+}
+
+async fn check(resp: Response) -> ClientResult<()> {
+    if resp.status().is_success() {
+        return Ok(());
+    }
+    Err(api_error(resp).await)
+}
+
+async fn decode<T: DeserializeOwned>(resp: Response) -> ClientResult<T> {
+    if !resp.status().is_success() {
+        return Err(api_error(resp).await);
+    }
+    let bytes = resp.bytes().await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+async fn api_error(resp: Response) -> ClientError {
+    let status = resp.status().as_u16();
+    let body = resp.text().await.unwrap_or_default();
+    let message = serde_json::from_str::<ErrorBody>(&body)
+        .map(|e| e.message)
+        .unwrap_or(body);
+    ClientError::Api { status, message }
+}
@@ -0,0 +1,123 @@
+//! Wire types for the REST API.
+//!
+//! Request and response bodies are the server's own DTOs, re-exported from
+//! `roomler-ai-types`, and room settings persisted as-is (e.g.
+//! [`MediaSettings`]) come straight from the model crate, so both sides
+//! serialize the same structs.
+
+pub use roomler_ai_db::models::MediaSettings;
+pub use roomler_ai_types::PaginatedResult;
+pub use roomler_ai_types::auth::{
+    AuthResponse, InviteTenantResponse, RegisterRequest, RegisterResponse, UserResponse,
+};
+pub use roomler_ai_types::file::{FileResponse, ThumbnailResponse};
+pub use roomler_ai_types::message::{
+    AttachmentResponse, CreateMessageRequest, MentionRequest, MessageResponse,
+    ReactionSummaryResponse, ScheduledMessageResponse,
+};
+pub use roomler_ai_types::notification::NotificationResponse;
+pub use roomler_ai_types::room::{CallStartResponse, CreateRoomRequest, RoomResponse};
+pub use roomler_ai_types::tenant::{StorageUsage, TenantResponse};
+pub use roomler_ai_types::user::UserStatusResponse;
+
+/// Envelope returned by every list endpoint. Pass its `next_cursor` back via
+/// [`ListParams::after`] to fetch the next page (cursor mode only).
+pub type Page<T> = PaginatedResult<T>;
+
+/// Query parameters shared by list endpoints: offset or cursor pagination,
+/// `sort`, and `filter[field][op]` clauses. What may be sorted/filtered is
+/// endpoint-specific; the server rejects anything else with 400.
+#[derive(Debug, Clone, Default)]
+pub struct ListParams {
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+    pub cursor: Option<String>,
+    pub sort: Option<String>,
+    pub filters: Vec<(String, String, String)>,
+}
+
+impl ListParams {
+    /// First page in cursor (keyset) mode.
+    pub fn cursor() -> Self {
+        Self {
+            cursor: Some(String::new()),
+            ..Default::default()
+        }
+    }
+
+    /// The page after the one that returned `next_cursor`.
+    pub fn after(mut self, next_cursor: impl Into<String>) -> Self {
+        self.cursor = Some(next_cursor.into());
+        self
+    }
+
+    pub fn page(mut self, page: u64) -> Self {
+        self.page = Some(page);
+        self
+    }
+
+    pub fn per_page(mut self, per_page: u64) -> Self {
+        self.per_page = Some(per_page);
+        self
+    }
+
+    /// `-field` sorts descending; several keys are comma-separated.
+    pub fn sort(mut self, sort: impl Into<String>) -> Self {
+        self.sort = Some(sort.into());
+        self
+    }
+
+    /// `op` is one of `eq ne gt gte lt lte in contains exists`.
+    pub fn filter(
+        mut self,
+        field: impl Into<String>,
+        op: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Self {
+        self.filters.push((field.into(), op.into(), value.into()));
+        self
+    }
+
+    pub(crate) fn to_query(&self) -> Vec<(String, String)> {
+        let mut query = Vec::new();
+        if let Some(page) = self.page {
+            query.push(("page".to_string(), page.to_string()));
+        }
+        if let Some(per_page) = self.per_page {
+            query.push(("per_page".to_string(), per_page.to_string()));
+        }
+        if let Some(cursor) = &self.cursor {
+            query.push(("cursor".to_string(), cursor.clone()));
+        }
+        if let Some(sort) = &self.sort {
+            query.push(("sort".to_string(), sort.clone()));
+        }
+        for (field, op, value) in &self.filters {
+            query.push((format!("filter[{field}][{op}]"), value.clone()));
+        }
+        query
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_params_build_server_query_syntax() {
+        let query = ListParams::cursor()
+            .per_page(20)
+            .sort("-created_at")
+            .filter("author_id", "eq", "abc")
+            .to_query();
+        assert_eq!(
+            query,
+            vec![
+                ("per_page".to_string(), "20".to_string()),
+                ("cursor".to_string(), String::new()),
+                ("sort".to_string(), "-created_at".to_string()),
+                ("filter[author_id][eq]".to_string(), "abc".to_string()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,351 @@
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde::Deserialize;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+use tracing::{debug, warn};
+
+use crate::error::{ClientError, ClientResult};
+use crate::rest::Client;
+
+type Socket = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const BACKOFF_START: Duration = Duration::from_millis(500);
+const BACKOFF_MAX: Duration = Duration::from_secs(30);
+
+/// One server → client message (`{ "type": ..., "data": ... }`).
+#[derive(Debug, Clone, Deserialize)]
+pub struct WsEvent {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub data: serde_json::Value,
+}
+
+/// A `/ws` connection that survives drops.
+///
+/// A background task owns the socket. When it drops, the task reconnects
+/// with exponential backoff (refreshing the access token if the upgrade is
+/// rejected with 401) and replays the last `media:join`, so callers see a
+/// fresh `connected` followed by a fresh `media:transport_created` and must
+/// rebuild their transports. `media:redirect` is followed the same way: the
+/// task reconnects to the pod that owns the conference and replays the join.
+///
+/// Dropping the `WsClient` closes the connection.
+pub struct WsClient {
+    outgoing: mpsc::UnboundedSender<serde_json::Value>,
+    events: mpsc::UnboundedReceiver<WsEvent>,
+}
+
+impl WsClient {
+    pub(crate) async fn connect(client: Client) -> ClientResult<Self> {
+        let base = client.base_url().to_string();
+        let socket = match open(&client, &base).await {
+            Err(e) if is_unauthorized(&e) => {
+                client.refresh().await?;
+                open(&client, &base).await?
+            }
+            other => other?,
+        };
+        let (out_tx, out_rx) = mpsc::unbounded_channel();
+        let (ev_tx, ev_rx) = mpsc::unbounded_channel();
+        tokio::spawn(run(client, base, socket, out_rx, ev_tx));
+        Ok(Self {
+            outgoing: out_tx,
+            events: ev_rx,
+        })
+    }
+
+    /// Next event from the server. `None` once the connection is closed for
+    /// good (reconnect gave up because the session can't be refreshed).
+    pub async fn next_event(&mut self) -> Option<WsEvent> {
+        self.events.recv().await
+    }
+
+    /// Wait for the next event of the given type, discarding others.
+    pub async fn wait_for(&mut self, kind: &str) -> Option<WsEvent> {
+        while let Some(event) = self.next_event().await {
+            if event.kind == kind {
+                return Some(event);
+            }
+        }
+        None
+    }
+
+    /// Send `{ "type": kind, "data": data }`. Messages sent while the
+    /// connection is down are queued until it is back.
+    pub fn send(&self, kind: &str, data: serde_json::Value) -> ClientResult<()> {
+        self.outgoing
+            .send(serde_json::json!({ "type": kind, "data": data }))
+            .map_err(|_| ClientError::WsClosed)
+    }
+
+    // ── Chat ─────────────────────────────────────────────────
+
+    pub fn typing_start(&self, room_id: &str) -> ClientResult<()> {
+        self.send("typing:start", serde_json::json!({ "room_id": room_id }))
+    }
+
+    pub fn typing_stop(&self, room_id: &str) -> ClientResult<()> {
+        self.send("typing:stop", serde_json::json!({ "room_id": room_id }))
+    }
+
+    // ── Media signaling ──────────────────────────────────────
+
+    /// Join the conference; answered by `media:transport_created` (or
+    /// `media:redirect`, which this client follows on its own).
+    pub fn media_join(&self, room_id: &str) -> ClientResult<()> {
+        self.send("media:join", serde_json::json!({ "room_id": room_id }))
+    }
+
+    pub fn media_leave(&self, room_id: &str) -> ClientResult<()> {
+        self.send("media:leave", serde_json::json!({ "room_id": room_id }))
+    }
+
+    pub fn connect_transport(
+        &self,
+        room_id: &str,
+        transport_id: &str,
+        dtls_parameters: serde_json::Value,
+    ) -> ClientResult<()> {
+        self.send(
+            "media:connect_transport",
+            serde_json::json!({
+                "room_id": room_id,
+                "transport_id": transport_id,
+                "dtls_parameters": dtls_parameters,
+            }),
+        )
+    }
+
+    /// `kind` is `audio` or `video`; `source` e.g. `camera` or `screen`.
+    pub fn produce(
+        &self,
+        room_id: &str,
+        kind: &str,
+        rtp_parameters: serde_json::Value,
+        source: Option<&str>,
+    ) -> ClientResult<()> {
+        let mut data = serde_json::json!({
+            "room_id": room_id,
+            "kind": kind,
+            "rtp_parameters": rtp_parameters,
+        });
+        if let Some(source) = source {
+            data["source"] = source.into();
+        }
+        self.send("media:produce", data)
+    }
+
+    pub fn consume(
+        &self,
+        room_id: &str,
+        producer_id: &str,
+        rtp_capabilities: serde_json::Value,
+    ) -> ClientResult<()> {
+        self.send(
+            "media:consume",
+            serde_json::json!({
+                "room_id": room_id,
+                "producer_id": producer_id,
+                "rtp_capabilities": rtp_capabilities,
+            }),
+        )
+    }
+
+    pub fn producer_close(&self, room_id: &str, producer_id: &str) -> ClientResult<()> {
+        self.send(
+            "media:producer_close",
+            serde_json::json!({ "room_id": room_id, "producer_id": producer_id }),
+        )
+    }
+
+    /// Ask the server to start a new E2EE key epoch.
+    pub fn key_rotate(&self, room_id: &str) -> ClientResult<()> {
+        self.send(
+            "media:key_rotate",
+            serde_json::json!({ "room_id": room_id }),
+        )
+    }
+
+    /// Hand out this participant's key for `epoch`: one opaque envelope per
+    /// `(connection_id, payload)` recipient.
+    pub fn key_distribute(
+        &self,
+        room_id: &str,
+        epoch: u64,
+        keys: &[(String, String)],
+    ) -> ClientResult<()> {
+        let keys: Vec<serde_json::Value> = keys
+            .iter()
+            .map(|(conn, payload)| serde_json::json!({ "connection_id": conn, "payload": payload }))
+            .collect();
+        self.send(
+            "media:key_distribute",
+            serde_json::json!({ "room_id": room_id, "epoch": epoch, "keys": keys }),
+        )
+    }
+}
+
+/// `http(s)://host` → `ws(s)://host/ws?token=...`.
+fn ws_url(base: &str, token: &str) -> String {
+    let base = base.trim_end_matches('/');
+    let base = if let Some(rest) = base.strip_prefix("https://") {
+        format!("wss://{rest}")
+    } else if let Some(rest) = base.strip_prefix("http://") {
+        format!("ws://{rest}")
+    } else {
+        base.to_string()
+    };
+    format!("{base}/ws?token={}", urlencoding::encode(token))
+}
+
+async fn open(client: &Client, base: &str) -> ClientResult<Socket> {
+    let token = client.access_token().ok_or(ClientError::NotAuthenticated)?;
+    let (socket, _) = tokio_tungstenite::connect_async(ws_url(base, &token)).await?;
+    Ok(socket)
+}
+
+fn is_unauthorized(e: &ClientError) -> bool {
+    matches!(
+        e,
+        ClientError::WebSocket(tungstenite::Error::Http(resp))
+            if resp.status() == tungstenite::http::StatusCode::UNAUTHORIZED
+    )
+}
+
+enum Stop {
+    /// The `WsClient` was dropped.
+    Closed,
+    Disconnected,
+    Redirect(String),
+}
+
+async fn run(
+    client: Client,
+    mut base: String,
+    mut socket: Socket,
+    mut outgoing: mpsc::UnboundedReceiver<serde_json::Value>,
+    events: mpsc::UnboundedSender<WsEvent>,
+) {
+    let mut join: Option<serde_json::Value> = None;
+    loop {
+        match pump(&mut socket, &mut outgoing, &events, &mut join).await {
+            Stop::Closed => {
+                let _ = socket.close(None).await;
+                return;
+            }
+            Stop::Disconnected => debug!("websocket dropped, reconnecting"),
+            Stop::Redirect(url) => {
+                debug!(%url, "media:redirect, moving websocket to owning pod");
+                let _ = socket.close(None).await;
+                base = url;
+            }
+        }
+        socket = match reconnect(&client, &base, &events).await {
+            Some(socket) => socket,
+            None => return,
+        };
+        if let Some(join) = &join
+            && socket
+                .send(Message::Text(join.to_string().into()))
+                .await
+                .is_err()
+        {
+            warn!("failed to replay media:join after reconnect");
+        }
+    }
+}
+
+async fn pump(
+    socket: &mut Socket,
+    outgoing: &mut mpsc::UnboundedReceiver<serde_json::Value>,
+    events: &mpsc::UnboundedSender<WsEvent>,
+    join: &mut Option<serde_json::Value>,
+) -> Stop {
+    loop {
+        tokio::select! {
+            out = outgoing.recv() => {
+                let Some(msg) = out else {
+                    return Stop::Closed;
+                };
+                // Remember the conference we're in so a reconnect can rejoin.
+                match msg["type"].as_str() {
+                    Some("media:join") => *join = Some(msg.clone()),
+                    Some("media:leave") => *join = None,
+                    _ => {}
+                }
+                if socket.send(Message::Text(msg.to_string().into())).await.is_err() {
+                    return Stop::Disconnected;
+                }
+            }
+            incoming = socket.next() => match incoming {
+                Some(Ok(Message::Text(text))) => {
+                    let Ok(event) = serde_json::from_str::<WsEvent>(&text) else {
+                        continue;
+                    };
+                    let redirect = (event.kind == "media:redirect")
+                        .then(|| event.data["url"].as_str().map(str::to_string))
+                        .flatten();
+                    if events.send(event).is_err() {
+                        return Stop::Closed;
+                    }
+                    if let Some(url) = redirect {
+                        return Stop::Redirect(url);
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return Stop::Disconnected,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Reconnect with exponential backoff. Gives up (returns `None`) when the
+/// caller is gone or the session can no longer be refreshed.
+async fn reconnect(
+    client: &Client,
+    base: &str,
+    events: &mpsc::UnboundedSender<WsEvent>,
+) -> Option<Socket> {
+    let mut backoff = BACKOFF_START;
+    loop {
+        if events.is_closed() {
+            return None;
+        }
+        match open(client, base).await {
+            Ok(socket) => return Some(socket),
+            Err(e) if is_unauthorized(&e) => {
+                if let Err(e) = client.refresh().await {
+                    warn!(%e, "websocket rejected and token refresh failed, giving up");
+                    return None;
+                }
+                continue;
+            }
+            Err(ClientError::NotAuthenticated) => return None,
+            Err(e) => debug!(%e, ?backoff, "websocket reconnect failed"),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(BACKOFF_MAX);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_ws_url_from_http_base() {
+        assert_eq!(
+            ws_url("https://roomler.ai/", "a b"),
+            "wss://roomler.ai/ws?token=a%20b"
+        );
+        assert_eq!(
+            ws_url("http://127.0.0.1:3000", "t"),
+            "ws://127.0.0.1:3000/ws?token=t"
+        );
+    }
+}
@@ -1,3 +1,5 @@
+mod runtime;
 mod settings;
 
+pub use runtime::*;
 pub use settings::*;
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{RateLimitSettings, Settings, TurnSettings};
+
+/// Keys operators may change while the server runs, besides `features.*`.
+pub const TUNABLE_KEYS: &[&str] = &[
+    "turn.url",
+    "turn.worker_urls",
+    "turn.regions",
+    "turn.max_regions",
+    "turn.username",
+    "turn.password",
+    "turn.force_relay",
+    "rate_limit.enabled",
+    "rate_limit.auth_per_min",
+    "rate_limit.ws_messages_per_sec",
+];
+
+/// Keys whose values are never shown back.
+const SECRET_KEYS: &[&str] = &["turn.password"];
+
+/// The part of [`Settings`] that can change without a restart: the TURN
+/// servers handed to clients, the per-user and per-IP rate limits, and the
+/// feature switches. The server keeps the current value behind a swappable
+/// handle; readers take a snapshot per use instead of caching it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeSettings {
+    pub turn: TurnSettings,
+    pub rate_limit: RateLimitSettings,
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+}
+
+impl RuntimeSettings {
+    pub fn from_settings(settings: &Settings) -> Self {
+        Self {
+            turn: settings.turn.clone(),
+            rate_limit: settings.rate_limit.clone(),
+            features: settings.features.clone(),
+        }
+    }
+
+    /// Whether the named feature switch is on. Unknown switches are off.
+    pub fn feature(&self, name: &str) -> bool {
+        self.features.get(name).copied().unwrap_or(false)
+    }
+
+    /// A copy with `overrides` (dotted keys, e.g. `turn.url`) applied. Fails
+    /// on the first key that isn't tunable or whose value doesn't fit.
+    pub fn with_overrides(&self, overrides: &BTreeMap<String, Value>) -> Result<Self, String> {
+        let mut tree = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        for (key, value) in overrides {
+            check_key(key, value)?;
+            let (section, field) = key.split_once('.').unwrap_or_default();
+            tree[section][field] = value.clone();
+            serde_json::from_value::<Self>(tree.clone())
+                .map_err(|_| format!("Invalid value for {}", key))?;
+        }
+        serde_json::from_value(tree).map_err(|e| e.to_string())
+    }
+
+    /// The tunable values by dotted key, with secrets masked.
+    pub fn tunables(&self) -> BTreeMap<String, Value> {
+        let tree = serde_json::to_value(self).unwrap_or_default();
+        let mut values: BTreeMap<String, Value> = TUNABLE_KEYS
+            .iter()
+            .map(|key| {
+                let (section, field) = key.split_once('.').unwrap_or_default();
+                (key.to_string(), masked(key, tree[section][field].clone()))
+            })
+            .collect();
+        for (name, on) in &self.features {
+            values.insert(format!("features.{}", name), Value::Bool(*on));
+        }
+        values
+    }
+}
+
+/// Whether `key` may be overridden with `value` at all.
+pub fn check_key(key: &str, value: &Value) -> Result<(), String> {
+    match key.strip_prefix("features.") {
+        Some(name) if !name.is_empty() && !name.contains('.') => {
+            if value.is_boolean() {
+                Ok(())
+            } else {
+                Err(format!("Invalid value for {}", key))
+            }
+        }
+        _ if TUNABLE_KEYS.contains(&key) => Ok(()),
+        _ => Err(format!("{} can't be changed at runtime", key)),
+    }
+}
+
+/// `value` of `key` as it may be shown back: secrets are masked.
+pub fn masked(key: &str, value: Value) -> Value {
+    if SECRET_KEYS.contains(&key) && !value.is_null() {
+        Value::String("********".to_string())
+    } else {
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn base() -> RuntimeSettings {
+        RuntimeSettings {
+            turn: TurnSettings {
+                url: Some("turn:a.example:3478".to_string()),
+                worker_urls: None,
+                regions: None,
+                max_regions: None,
+                health_check_secs: 30,
+                username: None,
+                password: Some("secret".to_string()),
+                shared_secret: Some("key".to_string()),
+                force_relay: None,
+            },
+            rate_limit: RateLimitSettings::default(),
+            features: BTreeMap::new(),
+        }
+    }
+
+    fn overrides(pairs: &[(&str, Value)]) -> BTreeMap<String, Value> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn applies_tunable_overrides() {
+        let tuned = base()
+            .with_overrides(&overrides(&[
+                ("turn.url", json!("turn:b.example:3478")),
+                ("rate_limit.auth_per_min", json!(5)),
+                ("features.new_composer", json!(true)),
+            ]))
+            .unwrap();
+        assert_eq!(tuned.turn.url.as_deref(), Some("turn:b.example:3478"));
+        assert_eq!(tuned.rate_limit.auth_per_min, 5);
+        assert!(tuned.feature("new_composer"));
+        assert!(!tuned.feature("other"));
+        // Untouched values stay.
+        assert_eq!(tuned.turn.shared_secret.as_deref(), Some("key"));
+    }
+
+    #[test]
+    fn refuses_other_keys_and_bad_values() {
+        let settings = base();
+        for (key, value) in [
+            ("turn.shared_secret", json!("x")),
+            ("jwt.secret", json!("x")),
+            ("features", json!(true)),
+            ("features.a.b", json!(true)),
+            ("features.flag", json!("yes")),
+            ("rate_limit.auth_per_min", json!("many")),
+        ] {
+            assert!(
+                settings
+                    .with_overrides(&overrides(&[(key, value)]))
+                    .is_err(),
+                "{key}"
+            );
+        }
+    }
+
+    #[test]
+    fn masks_secrets() {
+        let values = base().tunables();
+        assert_eq!(values["turn.password"], "********");
+        assert_eq!(values["turn.url"], "turn:a.example:3478");
+        assert!(!values.contains_key("turn.shared_secret"));
+    }
+}
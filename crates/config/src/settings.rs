@@ -1,5 +1,6 @@
 use config::{Config, ConfigError, Environment, File};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -17,6 +18,381 @@ pub struct Settings {
     pub email: EmailSettings,
     pub push: PushSettings,
     pub auth: AuthSettings,
+    pub rate_limit: RateLimitSettings,
+    pub ws: WsSettings,
+    pub retention: RetentionSettings,
+    pub archive: ArchiveSettings,
+    pub usage: UsageSettings,
+    pub scan: ScanSettings,
+    pub preview: PreviewSettings,
+    pub tts: TtsSettings,
+    pub assistant: AssistantSettings,
+    pub calendar: CalendarSettings,
+    pub control: ControlSettings,
+    pub telemetry: TelemetrySettings,
+    pub storage: StorageSettings,
+    pub integrations: IntegrationsSettings,
+    /// Named on/off switches, tunable at runtime (see [`crate::RuntimeSettings`]).
+    #[serde(default)]
+    pub features: BTreeMap<String, bool>,
+}
+
+/// Per-user / per-tenant request limits, on top of the per-IP governor on
+/// `/api`. Message and upload rates come from the tenant's plan (see
+/// `Plan::rate_limits`); these cover what isn't tenant-scoped.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RateLimitSettings {
+    /// Master switch. The e2e overlay turns it off.
+    pub enabled: bool,
+    /// Login / register / refresh / activate attempts per client IP per
+    /// minute (also the burst).
+    pub auth_per_min: u32,
+    /// Messages per WebSocket connection per second. Bursts of four
+    /// seconds' worth are allowed so joining a large call (one
+    /// `media:consume` per producer) isn't throttled.
+    pub ws_messages_per_sec: u32,
+}
+
+impl Default for RateLimitSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            auth_per_min: 30,
+            ws_messages_per_sec: 50,
+        }
+    }
+}
+
+/// WebSocket keepalive. The server pings every connection and drops the
+/// ones that stop answering or go quiet, so a half-open TCP connection
+/// doesn't linger as a ghost participant until TCP gives up. Connections
+/// that can't keep up with their outbound messages are dropped too.
+#[derive(Debug, Deserialize, Clone)]
+pub struct WsSettings {
+    /// Seconds of inbound silence before the server sends a ping.
+    pub ping_interval_secs: u64,
+    /// Seconds to wait for any frame after a ping before dropping the
+    /// connection.
+    pub pong_timeout_secs: u64,
+    /// Seconds without a client message before dropping the connection
+    /// (the web client sends `ping` every 30 seconds). 0 disables.
+    pub idle_timeout_secs: u64,
+    /// Seconds a user's presence stays set without any message from them
+    /// before it lapses to offline.
+    pub presence_ttl_secs: u64,
+    /// Seconds an incoming call rings before it counts as missed.
+    pub ring_timeout_secs: u64,
+    /// Seconds a one-time WebSocket ticket stays redeemable.
+    pub ticket_ttl_secs: u64,
+    /// Messages waiting to be written to one connection before it counts as
+    /// a slow consumer.
+    pub outbound_queue_size: usize,
+    /// Seconds a single write to a connection may take before it is
+    /// dropped as stalled.
+    pub send_timeout_secs: u64,
+    /// Milliseconds a batching connection's writer waits after a
+    /// high-frequency event for more to send in the same frame.
+    pub batch_window_ms: u64,
+}
+
+impl Default for WsSettings {
+    fn default() -> Self {
+        Self {
+            ping_interval_secs: 20,
+            pong_timeout_secs: 10,
+            idle_timeout_secs: 120,
+            presence_ttl_secs: 90,
+            ring_timeout_secs: 30,
+            ticket_ttl_secs: 30,
+            outbound_queue_size: 256,
+            send_timeout_secs: 10,
+            batch_window_ms: 20,
+        }
+    }
+}
+
+/// The reaper that applies each tenant's retention policy.
+#[derive(Debug, Deserialize, Clone)]
+pub struct RetentionSettings {
+    /// Seconds between sweeps. 0 disables the reaper.
+    pub sweep_interval_secs: u64,
+    /// Days a soft-deleted room or message can be restored before the
+    /// reaper purges it. 0 keeps deleted content (and restorable) forever.
+    pub deleted_grace_days: u32,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            sweep_interval_secs: 3600,
+            deleted_grace_days: 30,
+        }
+    }
+}
+
+/// The sweeper that moves old messages to the `messages_archive` collection.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ArchiveSettings {
+    /// Seconds between sweeps. 0 disables archiving.
+    pub sweep_interval_secs: u64,
+    /// Days a message stays hot unless the tenant's retention policy sets
+    /// `archive_days`. 0 archives only tenants that set it.
+    pub message_days: u32,
+}
+
+impl Default for ArchiveSettings {
+    fn default() -> Self {
+        Self {
+            sweep_interval_secs: 3600,
+            message_days: 0,
+        }
+    }
+}
+
+/// The meter that books media usage and storage into each tenant's daily
+/// usage and reports finished days to Stripe.
+#[derive(Debug, Deserialize, Clone)]
+pub struct UsageSettings {
+    /// Seconds between meter runs. 0 disables metering of media and
+    /// storage; call minutes are still booked when a call is left.
+    pub meter_interval_secs: u64,
+}
+
+impl Default for UsageSettings {
+    fn default() -> Self {
+        Self {
+            meter_interval_secs: 60,
+        }
+    }
+}
+
+/// Antivirus scanning of uploads. While a file waits for its scan, or was
+/// found infected, it can't be downloaded.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ScanSettings {
+    /// `clamav` (a clamd daemon), `http` (an external scanning API) or
+    /// empty to store uploads unscanned.
+    pub provider: String,
+    /// `host:port` of clamd's TCP socket.
+    pub clamd_addr: String,
+    /// Endpoint of the external API; it gets the file as the POST body.
+    pub api_url: String,
+    /// Bearer token for the external API.
+    pub api_key: String,
+    /// Seconds a single scan may take before it fails.
+    pub timeout_secs: u64,
+}
+
+impl Default for ScanSettings {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            clamd_addr: "127.0.0.1:3310".to_string(),
+            api_url: String::new(),
+            api_key: String::new(),
+            timeout_secs: 60,
+        }
+    }
+}
+
+/// Page previews of PDF and Word attachments, rendered by an external
+/// conversion worker.
+#[derive(Debug, Deserialize, Clone)]
+pub struct PreviewSettings {
+    /// Endpoint of the conversion worker; empty turns previews off.
+    pub worker_url: String,
+    /// Bearer token for the worker.
+    pub api_key: String,
+    /// Pages rendered from the start of each document.
+    pub max_pages: u32,
+    /// Width in pixels of the rendered pages.
+    pub width: u32,
+    /// Seconds a single conversion may take before it fails.
+    pub timeout_secs: u64,
+}
+
+impl Default for PreviewSettings {
+    fn default() -> Self {
+        Self {
+            worker_url: String::new(),
+            api_key: String::new(),
+            max_pages: 3,
+            width: 480,
+            timeout_secs: 120,
+        }
+    }
+}
+
+/// Text-to-speech for `media:speak`, spoken into calls.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TtsSettings {
+    /// `piper` (a local Piper binary and ONNX voice), `http` (an
+    /// OpenAI-compatible speech API) or empty to turn speech off.
+    pub provider: String,
+    /// Piper binary.
+    pub piper_path: String,
+    /// Piper voice model (`.onnx`).
+    pub piper_model: String,
+    /// Endpoint of the speech API, e.g. `https://api.openai.com/v1/audio/speech`.
+    pub api_url: String,
+    /// Bearer token for the speech API.
+    pub api_key: String,
+    /// Model the speech API is asked for.
+    pub model: String,
+    /// Voice used when `media:speak` names none.
+    pub voice: String,
+    /// Seconds a single synthesis may take before it fails.
+    pub timeout_secs: u64,
+}
+
+impl Default for TtsSettings {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            piper_path: "piper".to_string(),
+            piper_model: String::new(),
+            api_url: String::new(),
+            api_key: String::new(),
+            model: "tts-1".to_string(),
+            voice: "alloy".to_string(),
+            timeout_secs: 30,
+        }
+    }
+}
+
+/// The meeting assistant answering `/assistant` in call chat. Tenants turn
+/// it on with the `meeting_assistant` feature flag.
+#[derive(Debug, Deserialize, Clone)]
+pub struct AssistantSettings {
+    /// `claude` (the Claude API, with `claude.api_key`), `http` (an
+    /// OpenAI-compatible chat completions API) or empty to turn the
+    /// assistant off.
+    pub provider: String,
+    /// Endpoint of the chat API, e.g.
+    /// `https://api.openai.com/v1/chat/completions`.
+    pub api_url: String,
+    /// Bearer token for the chat API.
+    pub api_key: String,
+    /// Model the chat API is asked for.
+    pub model: String,
+    /// Final transcript lines of the call kept as the assistant's context.
+    pub context_lines: usize,
+    /// Seconds an answer may take before it fails.
+    pub timeout_secs: u64,
+}
+
+impl Default for AssistantSettings {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            api_url: String::new(),
+            api_key: String::new(),
+            model: "gpt-4o-mini".to_string(),
+            context_lines: 200,
+            timeout_secs: 60,
+        }
+    }
+}
+
+/// Google and Microsoft calendar connectors for scheduled calls. They sign
+/// in with the `oauth.google` / `oauth.microsoft` clients; a provider
+/// without a client id can't be connected.
+#[derive(Debug, Deserialize, Clone)]
+pub struct CalendarSettings {
+    /// Google APIs root, for the Calendar API and userinfo.
+    pub google_api_url: String,
+    pub google_token_url: String,
+    /// Microsoft Graph root.
+    pub microsoft_api_url: String,
+    pub microsoft_token_url: String,
+    /// Phone number put in calendar events, with the meeting code as the
+    /// PIN; empty leaves dial-in out.
+    pub dial_in_number: String,
+    /// Seconds between pulls of attendee responses; 0 turns the sync off.
+    pub sync_interval_secs: u64,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            google_api_url: "https://www.googleapis.com".to_string(),
+            google_token_url: "https://oauth2.googleapis.com/token".to_string(),
+            microsoft_api_url: "https://graph.microsoft.com/v1.0".to_string(),
+            microsoft_token_url: "https://login.microsoftonline.com/common/oauth2/v2.0/token"
+                .to_string(),
+            dial_in_number: String::new(),
+            sync_interval_secs: 300,
+        }
+    }
+}
+
+/// The internal gRPC control plane other pods call for room placement,
+/// media pipes and participant migration. Always mutual TLS: the pod's
+/// certificate and key, and the CA every pod's certificate is signed by.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ControlSettings {
+    /// Address the control server listens on; empty turns it off.
+    pub listen_addr: String,
+    pub cert_path: String,
+    pub key_path: String,
+    pub ca_path: String,
+    /// Port other pods' control planes listen on, at the host of their
+    /// `app.instance_url`; 0 is the port of `listen_addr`.
+    pub peer_port: u16,
+}
+
+/// OpenTelemetry trace export: spans of HTTP requests, WebSocket messages
+/// and media operations go to an OTLP collector over gRPC.
+#[derive(Debug, Deserialize, Clone)]
+pub struct TelemetrySettings {
+    /// OTLP gRPC endpoint, e.g. `http://otel-collector:4317`; empty turns
+    /// export off.
+    pub otlp_endpoint: String,
+    /// The `service.name` traces are reported under.
+    pub service_name: String,
+    /// Share of new traces kept, 0.0-1.0. Traces continued from a caller's
+    /// `traceparent` follow the caller's sampling decision.
+    pub sample_ratio: f64,
+}
+
+impl Default for TelemetrySettings {
+    fn default() -> Self {
+        Self {
+            otlp_endpoint: String::new(),
+            service_name: "roomler-ai".to_string(),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+/// Per-tenant storage quotas on file uploads, on top of the plan's storage
+/// limit. A tenant's own `settings.storage_quota_bytes` overrides the default.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StorageSettings {
+    /// Quota of tenants without their own, in bytes; 0 is unlimited.
+    pub default_quota_bytes: u64,
+    /// What an upload past the quota gets.
+    pub quota_mode: StorageQuotaMode,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageQuotaMode {
+    /// Refused with `507 storage_quota`.
+    #[default]
+    Block,
+    /// Stored anyway; the uploader gets a `storage:quota_warning` event.
+    Warn,
+}
+
+/// Outgoing webhooks and slash commands, which POST to tenant-configured
+/// URLs from inside the server's network.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct IntegrationsSettings {
+    /// Let integration URLs reach loopback, private, link-local and
+    /// metadata addresses. Off by default; only for setups whose
+    /// integrations run next to the server.
+    pub allow_private_networks: bool,
 }
 
 #[derive(Debug, Deserialize, Clone, Default)]
@@ -40,6 +416,7 @@ pub struct OAuthSettings {
     pub github: OAuthProviderSettings,
     pub linkedin: OAuthProviderSettings,
     pub microsoft: OAuthProviderSettings,
+    pub custom: OidcProviderSettings,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -48,6 +425,22 @@ pub struct OAuthProviderSettings {
     pub client_secret: String,
 }
 
+/// Generic OpenID Connect provider (Keycloak, Authentik, Zitadel, ...),
+/// exposed as the `custom` provider. Endpoints are resolved through OIDC
+/// discovery at `{issuer_url}/.well-known/openid-configuration`, so only
+/// the issuer and client credentials are needed. Empty `client_id`
+/// disables it.
+#[derive(Debug, Deserialize, Clone)]
+pub struct OidcProviderSettings {
+    pub issuer_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    /// Space-separated scopes requested at the authorization endpoint.
+    pub scopes: String,
+    /// Button label the login page shows for this provider.
+    pub label: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct AppSettings {
     pub host: String,
@@ -65,6 +458,22 @@ pub struct AppSettings {
     /// per-second refill above. Bumped in e2e for the same reason.
     #[serde(default = "default_rate_limit_burst")]
     pub rate_limit_burst: u32,
+    /// Externally reachable base URL of THIS pod (e.g.
+    /// `wss://pod-2.api.roomler.ai`). When set, the pod claims ownership of
+    /// every conference whose mediasoup Router it hosts in the Redis
+    /// conference registry, and a `media:join` landing on a different pod is
+    /// answered with `media:redirect` pointing here. Unset = single-pod
+    /// deployment, no registry traffic.
+    #[serde(default)]
+    pub instance_url: Option<String>,
+    /// Comma-separated emails of the operators who may read and change
+    /// runtime settings (`/api/admin/settings`). Empty = nobody.
+    #[serde(default)]
+    pub admin_emails: String,
+    /// Seconds between re-reads of the config files and the stored
+    /// overrides of runtime settings. 0 disables.
+    #[serde(default = "default_settings_reload_secs")]
+    pub settings_reload_secs: u64,
 }
 
 fn default_rate_limit_per_sec() -> u64 {
@@ -73,6 +482,9 @@ fn default_rate_limit_per_sec() -> u64 {
 fn default_rate_limit_burst() -> u32 {
     60
 }
+fn default_settings_reload_secs() -> u64 {
+    30
+}
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct DatabaseSettings {
@@ -80,6 +492,11 @@ pub struct DatabaseSettings {
     pub name: String,
     pub max_pool_size: Option<u32>,
     pub min_pool_size: Option<u32>,
+    /// Queries on tenant-owned collections without a `tenant_id` filter:
+    /// `warn` logs the first per collection and operation, `deny` fails
+    /// them, empty ignores them. For dev and test.
+    #[serde(default)]
+    pub tenant_audit: String,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -111,9 +528,31 @@ pub struct MediasoupSettings {
     pub announced_ip: String,
     pub rtc_min_port: u16,
     pub rtc_max_port: u16,
+    /// Seconds a dropped WebSocket's media stays alive for `media:rejoin`
+    /// before it is cleaned up. 0 cleans up immediately.
+    pub reconnect_grace_secs: u64,
+    /// Seconds between watchdog pings of every worker (0 disables). A
+    /// worker that misses three in a row fails `/health/live`.
+    pub watchdog_secs: u64,
+    /// What runs the media: mediasoup workers, or an in-memory mock for
+    /// hermetic tests.
+    #[serde(default)]
+    pub backend: MediaBackendKind,
+    /// FFmpeg binary that encodes files played to a call.
+    pub ffmpeg_path: String,
 }
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaBackendKind {
+    #[default]
+    Mediasoup,
+    /// No worker process and no UDP ports: routers, transports, producers
+    /// and consumers only exist in memory and no RTP flows.
+    Mock,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct TurnSettings {
     pub url: Option<String>,
     /// Same-worker TURN affinity: comma-separated per-worker base URLs, one
@@ -122,6 +561,18 @@ pub struct TurnSettings {
     /// Each is expanded into transport variants like `url`. Unset = no
     /// affinity (single-hostname DNS round-robin behaviour).
     pub worker_urls: Option<String>,
+    /// Region-pinned TURN servers for conferences: comma-separated
+    /// `name=turn:host:port@CC|CC` entries (ISO country codes served by the
+    /// region), e.g. "eu=turn:eu.turn.roomler.ai:3478@DE|AT,us=turn:us.turn.roomler.ai:3478@US".
+    /// `media:join` orders them nearest-first by the client's edge-reported
+    /// country; the first entry is the fallback. Unset = `url` only.
+    pub regions: Option<String>,
+    /// Most regions handed to one `media:join`, nearest first. Unset = all.
+    pub max_regions: Option<usize>,
+    /// Seconds between STUN health probes of each region; a region that
+    /// misses two probes in a row is left out of `media:join` until it
+    /// answers again. 0 disables the probes.
+    pub health_check_secs: u64,
     pub username: Option<String>,
     pub password: Option<String>,
     pub shared_secret: Option<String>,
@@ -140,8 +591,17 @@ pub struct StripeSettings {
     pub secret_key: String,
     pub publishable_key: String,
     pub webhook_secret: String,
+    /// Seconds a webhook's signed timestamp may be off from the server
+    /// clock before it is rejected as a replay. 0 disables the check.
+    pub webhook_tolerance_secs: u64,
     pub price_pro: String,
     pub price_business: String,
+    /// Enforce the tenant's plan limits (members, call participants,
+    /// recording minutes, storage). Off for deployments that don't sell plans.
+    pub enforce_limits: bool,
+    /// Report each tenant's finished usage days to Stripe as meter events,
+    /// for usage-based prices. Tenants without a Stripe customer are skipped.
+    pub report_usage: bool,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -187,8 +647,12 @@ impl Settings {
             .set_default("app.frontend_url", "http://localhost:5173")?
             .set_default("app.rate_limit_per_sec", 1)?
             .set_default("app.rate_limit_burst", 60)?
+            .set_default("app.instance_url", None::<String>)?
+            .set_default("app.admin_emails", "")?
+            .set_default("app.settings_reload_secs", 30)?
             .set_default("database.url", "mongodb://localhost:27019")?
             .set_default("database.name", "roomler-ai")?
+            .set_default("database.tenant_audit", "")?
             .set_default("jwt.secret", "change-me-in-production")?
             // Access token: 1 week (was 1 hour). The shorter TTL was
             // partnered with a much longer refresh, but operators
@@ -212,8 +676,15 @@ impl Settings {
             .set_default("mediasoup.announced_ip", "127.0.0.1")?
             .set_default("mediasoup.rtc_min_port", 40000)?
             .set_default("mediasoup.rtc_max_port", 49999)?
+            .set_default("mediasoup.reconnect_grace_secs", 15)?
+            .set_default("mediasoup.watchdog_secs", 10)?
+            .set_default("mediasoup.backend", "mediasoup")?
+            .set_default("mediasoup.ffmpeg_path", "ffmpeg")?
             .set_default("turn.url", None::<String>)?
             .set_default("turn.worker_urls", None::<String>)?
+            .set_default("turn.regions", None::<String>)?
+            .set_default("turn.max_regions", None::<u64>)?
+            .set_default("turn.health_check_secs", 30)?
             .set_default("turn.username", None::<String>)?
             .set_default("turn.password", None::<String>)?
             .set_default("turn.force_relay", false)?
@@ -230,11 +701,19 @@ impl Settings {
             .set_default("oauth.linkedin.client_secret", "")?
             .set_default("oauth.microsoft.client_id", "")?
             .set_default("oauth.microsoft.client_secret", "")?
+            .set_default("oauth.custom.issuer_url", "")?
+            .set_default("oauth.custom.client_id", "")?
+            .set_default("oauth.custom.client_secret", "")?
+            .set_default("oauth.custom.scopes", "openid email profile")?
+            .set_default("oauth.custom.label", "SSO")?
             .set_default("stripe.secret_key", "")?
             .set_default("stripe.publishable_key", "")?
             .set_default("stripe.webhook_secret", "")?
+            .set_default("stripe.webhook_tolerance_secs", 300)?
             .set_default("stripe.price_pro", "")?
             .set_default("stripe.price_business", "")?
+            .set_default("stripe.enforce_limits", false)?
+            .set_default("stripe.report_usage", false)?
             .set_default("giphy.api_key", "")?
             .set_default("email.api_key", "")?
             .set_default("email.from_email", "noreply@roomler.ai")?
@@ -246,6 +725,73 @@ impl Settings {
             .set_default("push.vapid_private_key", "")?
             .set_default("push.contact", "mailto:noreply@roomler.ai")?
             .set_default("auth.auto_verify", false)?
+            .set_default("rate_limit.enabled", true)?
+            .set_default("rate_limit.auth_per_min", 30)?
+            .set_default("rate_limit.ws_messages_per_sec", 50)?
+            .set_default("ws.ping_interval_secs", 20)?
+            .set_default("ws.pong_timeout_secs", 10)?
+            .set_default("ws.idle_timeout_secs", 120)?
+            .set_default("ws.presence_ttl_secs", 90)?
+            .set_default("ws.ring_timeout_secs", 30)?
+            .set_default("ws.ticket_ttl_secs", 30)?
+            .set_default("ws.outbound_queue_size", 256)?
+            .set_default("ws.send_timeout_secs", 10)?
+            .set_default("ws.batch_window_ms", 20)?
+            .set_default("retention.sweep_interval_secs", 3600)?
+            .set_default("retention.deleted_grace_days", 30)?
+            .set_default("archive.sweep_interval_secs", 3600)?
+            .set_default("archive.message_days", 0)?
+            .set_default("usage.meter_interval_secs", 60)?
+            .set_default("scan.provider", "")?
+            .set_default("scan.clamd_addr", "127.0.0.1:3310")?
+            .set_default("scan.api_url", "")?
+            .set_default("scan.api_key", "")?
+            .set_default("scan.timeout_secs", 60)?
+            .set_default("preview.worker_url", "")?
+            .set_default("preview.api_key", "")?
+            .set_default("preview.max_pages", 3)?
+            .set_default("preview.width", 480)?
+            .set_default("preview.timeout_secs", 120)?
+            .set_default("tts.provider", "")?
+            .set_default("tts.piper_path", "piper")?
+            .set_default("tts.piper_model", "")?
+            .set_default("tts.api_url", "")?
+            .set_default("tts.api_key", "")?
+            .set_default("tts.model", "tts-1")?
+            .set_default("tts.voice", "alloy")?
+            .set_default("tts.timeout_secs", 30)?
+            .set_default("assistant.provider", "")?
+            .set_default("assistant.api_url", "")?
+            .set_default("assistant.api_key", "")?
+            .set_default("assistant.model", "gpt-4o-mini")?
+            .set_default("assistant.context_lines", 200)?
+            .set_default("assistant.timeout_secs", 60)?
+            .set_default("calendar.google_api_url", "https://www.googleapis.com")?
+            .set_default(
+                "calendar.google_token_url",
+                "https://oauth2.googleapis.com/token",
+            )?
+            .set_default(
+                "calendar.microsoft_api_url",
+                "https://graph.microsoft.com/v1.0",
+            )?
+            .set_default(
+                "calendar.microsoft_token_url",
+                "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            )?
+            .set_default("calendar.dial_in_number", "")?
+            .set_default("calendar.sync_interval_secs", 300)?
+            .set_default("control.listen_addr", "")?
+            .set_default("control.cert_path", "")?
+            .set_default("control.key_path", "")?
+            .set_default("control.ca_path", "")?
+            .set_default("control.peer_port", 0)?
+            .set_default("telemetry.otlp_endpoint", "")?
+            .set_default("telemetry.service_name", "roomler-ai")?
+            .set_default("telemetry.sample_ratio", 1.0)?
+            .set_default("storage.default_quota_bytes", 0)?
+            .set_default("storage.quota_mode", "block")?
+            .set_default("integrations.allow_private_networks", false)?
             .build()?;
 
         config.try_deserialize()
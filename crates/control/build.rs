@@ -0,0 +1,52 @@
+use tonic_build::manual::{Method, Service};
+
+fn method(name: &str, route: &str, input: &str, output: &str, comment: &str) -> Method {
+    Method::builder()
+        .name(name)
+        .route_name(route)
+        .comment(comment)
+        .input_type(format!("crate::proto::{input}"))
+        .output_type(format!("crate::proto::{output}"))
+        .codec_path("tonic_prost::ProstCodec")
+        .build()
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let control_plane = Service::builder()
+        .name("ControlPlane")
+        .package("roomler.control.v1")
+        .comment("Requests one API pod makes of another.")
+        .method(method(
+            "place_room",
+            "PlaceRoom",
+            "PlaceRoomRequest",
+            "PlaceRoomResponse",
+            "Host a room's Router on the called pod, unless another pod already does.",
+        ))
+        .method(method(
+            "setup_pipe",
+            "SetupPipe",
+            "SetupPipeRequest",
+            "SetupPipeResponse",
+            "Pipe one of the called pod's producers to the caller's pipe transport.",
+        ))
+        .method(method(
+            "list_producers",
+            "ListProducers",
+            "ListProducersRequest",
+            "ListProducersResponse",
+            "The participants' producers on the called pod's Router for a room.",
+        ))
+        .method(method(
+            "migrate_participant",
+            "MigrateParticipant",
+            "MigrateParticipantRequest",
+            "MigrateParticipantResponse",
+            "Move a user's media connections in a room to another pod.",
+        ))
+        .build();
+
+    tonic_build::manual::Builder::new().compile(&[control_plane]);
+}
@@ -0,0 +1,168 @@
+//! `roomler-ai-control` — internal gRPC control plane between API pods.
+//!
+//! Cross-pod media needs request/response calls between pods: host this
+//! room's Router, what does it produce, pipe me that producer, move this
+//! participant over.
+//! Redis pub/sub fans events out but has no replies, deadlines or errors,
+//! so those calls go over the `roomler.control.v1.ControlPlane` service
+//! defined here instead, with mutual TLS between pods.
+//!
+//! Module map:
+//!
+//! - [`proto`] — request and response messages
+//! - [`tls`]   — mTLS identities for server and client
+//!
+//! The API crate implements [`ControlPlane`] and runs it with [`serve`];
+//! pods call each other through [`connect`].
+
+use std::future::Future;
+use std::net::SocketAddr;
+
+use tonic::transport::{Channel, Endpoint, Server};
+
+pub mod proto;
+pub mod tls;
+
+mod generated {
+    include!(concat!(
+        env!("OUT_DIR"),
+        "/roomler.control.v1.ControlPlane.rs"
+    ));
+}
+
+pub use generated::control_plane_client::ControlPlaneClient;
+pub use generated::control_plane_server::{ControlPlane, ControlPlaneServer};
+pub use tls::ControlTls;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("control plane TLS: {0}")]
+    Tls(String),
+    #[error("control plane transport: {0}")]
+    Transport(#[from] tonic::transport::Error),
+}
+
+/// Serve `service` on `addr` until `shutdown` resolves.
+pub async fn serve<S: ControlPlane>(
+    service: S,
+    addr: SocketAddr,
+    tls: &ControlTls,
+    shutdown: impl Future<Output = ()>,
+) -> Result<(), Error> {
+    Server::builder()
+        .tls_config(tls.server_config())?
+        .add_service(ControlPlaneServer::new(service))
+        .serve_with_shutdown(addr, shutdown)
+        .await?;
+    Ok(())
+}
+
+/// Connect to a peer pod's control endpoint (`https://host:port`).
+pub async fn connect(url: String, tls: &ControlTls) -> Result<ControlPlaneClient<Channel>, Error> {
+    let channel = Endpoint::from_shared(url)?
+        .tls_config(tls.client_config())?
+        .connect()
+        .await?;
+    Ok(ControlPlaneClient::new(channel))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rcgen::{BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+    use tonic::{Request, Response, Status};
+
+    struct Placer;
+
+    #[tonic::async_trait]
+    impl ControlPlane for Placer {
+        async fn place_room(
+            &self,
+            request: Request<proto::PlaceRoomRequest>,
+        ) -> Result<Response<proto::PlaceRoomResponse>, Status> {
+            Ok(Response::new(proto::PlaceRoomResponse {
+                owner_url: format!("https://pod-1/{}", request.into_inner().room_id),
+                local: true,
+            }))
+        }
+
+        async fn setup_pipe(
+            &self,
+            _: Request<proto::SetupPipeRequest>,
+        ) -> Result<Response<proto::SetupPipeResponse>, Status> {
+            Err(Status::unimplemented("setup_pipe"))
+        }
+
+        async fn list_producers(
+            &self,
+            _: Request<proto::ListProducersRequest>,
+        ) -> Result<Response<proto::ListProducersResponse>, Status> {
+            Err(Status::unimplemented("list_producers"))
+        }
+
+        async fn migrate_participant(
+            &self,
+            _: Request<proto::MigrateParticipantRequest>,
+        ) -> Result<Response<proto::MigrateParticipantResponse>, Status> {
+            Err(Status::unimplemented("migrate_participant"))
+        }
+    }
+
+    /// A CA and a `localhost` certificate it signed for both ends.
+    fn pki() -> ControlTls {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca = CertificateParams::new(Vec::new()).unwrap();
+        ca.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca_cert = ca.self_signed(&ca_key).unwrap();
+
+        let key = KeyPair::generate().unwrap();
+        let mut pod = CertificateParams::new(vec!["localhost".to_string()]).unwrap();
+        pod.extended_key_usages = vec![
+            ExtendedKeyUsagePurpose::ServerAuth,
+            ExtendedKeyUsagePurpose::ClientAuth,
+        ];
+        let cert = pod.signed_by(&key, &ca_cert, &ca_key).unwrap();
+        ControlTls::from_pem(cert.pem(), key.serialize_pem(), ca_cert.pem())
+    }
+
+    #[tokio::test]
+    async fn peers_with_the_same_ca_can_call_each_other() {
+        let server_tls = pki();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let incoming = tonic::transport::server::TcpIncoming::from(listener);
+        let tls = server_tls.clone();
+        tokio::spawn(async move {
+            Server::builder()
+                .tls_config(tls.server_config())
+                .unwrap()
+                .add_service(ControlPlaneServer::new(Placer))
+                .serve_with_incoming(incoming)
+                .await
+                .unwrap();
+        });
+
+        let url = format!("https://localhost:{port}");
+        let mut client = connect(url.clone(), &server_tls).await.unwrap();
+        let placed = client
+            .place_room(proto::PlaceRoomRequest {
+                room_id: "r1".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(placed.owner_url, "https://pod-1/r1");
+        assert!(placed.local);
+
+        // A certificate from another CA doesn't get through.
+        let stranger = pki();
+        let refused = match connect(url, &stranger).await {
+            Ok(mut client) => client
+                .place_room(proto::PlaceRoomRequest::default())
+                .await
+                .is_err(),
+            Err(_) => true,
+        };
+        assert!(refused);
+    }
+}
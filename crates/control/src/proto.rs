@@ -0,0 +1,100 @@
+//! Control-plane messages. Ids are hex `ObjectId`s and mediasoup ids as
+//! their strings; field tags are the wire contract, so never reuse one.
+
+/// `PlaceRoom` request.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PlaceRoomRequest {
+    #[prost(string, tag = "1")]
+    pub room_id: String,
+}
+
+/// Where the room's Router lives after `PlaceRoom`.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PlaceRoomResponse {
+    /// Base URL of the owning pod (its `app.instance_url`).
+    #[prost(string, tag = "1")]
+    pub owner_url: String,
+    /// The called pod owns the Router, either already or from this call.
+    #[prost(bool, tag = "2")]
+    pub local: bool,
+}
+
+/// `SetupPipe` request: the caller has opened a pipe transport on its own
+/// Router for the room and wants `producer_id` sent to it.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetupPipeRequest {
+    #[prost(string, tag = "1")]
+    pub room_id: String,
+    #[prost(string, tag = "2")]
+    pub producer_id: String,
+    /// Address of the caller's pipe transport.
+    #[prost(string, tag = "3")]
+    pub ip: String,
+    #[prost(uint32, tag = "4")]
+    pub port: u32,
+}
+
+/// The called pod's end of the pipe, for the caller to connect its pipe
+/// transport to and produce from.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SetupPipeResponse {
+    #[prost(string, tag = "1")]
+    pub ip: String,
+    #[prost(uint32, tag = "2")]
+    pub port: u32,
+    /// `audio` or `video`.
+    #[prost(string, tag = "3")]
+    pub kind: String,
+    /// mediasoup `RtpParameters` of the pipe consumer, as JSON.
+    #[prost(string, tag = "4")]
+    pub rtp_parameters_json: String,
+}
+
+/// `ListProducers` request.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListProducersRequest {
+    #[prost(string, tag = "1")]
+    pub room_id: String,
+}
+
+/// A participant's producer on the called pod's Router.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ProducerInfo {
+    #[prost(string, tag = "1")]
+    pub producer_id: String,
+    #[prost(string, tag = "2")]
+    pub user_id: String,
+    #[prost(string, tag = "3")]
+    pub connection_id: String,
+    /// `audio` or `video`.
+    #[prost(string, tag = "4")]
+    pub kind: String,
+    /// e.g. `audio`, `camera` or `screen`.
+    #[prost(string, tag = "5")]
+    pub source: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ListProducersResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub producers: Vec<ProducerInfo>,
+}
+
+/// `MigrateParticipant` request.
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MigrateParticipantRequest {
+    #[prost(string, tag = "1")]
+    pub room_id: String,
+    #[prost(string, tag = "2")]
+    pub user_id: String,
+    /// Base URL of the pod the participant should reconnect to.
+    #[prost(string, tag = "3")]
+    pub target_url: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MigrateParticipantResponse {
+    /// Connections told to move.
+    #[prost(uint32, tag = "1")]
+    pub connections: u32,
+}
@@ -0,0 +1,65 @@
+//! Mutual TLS for the control plane. Every pod has a certificate signed by
+//! the same internal CA and presents it both as server and as client; a
+//! peer without one is refused at the handshake.
+
+use std::path::Path;
+
+use tonic::transport::{Certificate, ClientTlsConfig, Identity, ServerTlsConfig};
+
+use crate::Error;
+
+/// A pod's certificate and key plus the CA its peers' certificates are
+/// signed by, all PEM.
+#[derive(Clone)]
+pub struct ControlTls {
+    cert: Vec<u8>,
+    key: Vec<u8>,
+    ca: Vec<u8>,
+}
+
+impl ControlTls {
+    pub fn from_pem(
+        cert: impl Into<Vec<u8>>,
+        key: impl Into<Vec<u8>>,
+        ca: impl Into<Vec<u8>>,
+    ) -> Self {
+        Self {
+            cert: cert.into(),
+            key: key.into(),
+            ca: ca.into(),
+        }
+    }
+
+    pub fn from_pem_files(
+        cert: impl AsRef<Path>,
+        key: impl AsRef<Path>,
+        ca: impl AsRef<Path>,
+    ) -> Result<Self, Error> {
+        let read = |path: &Path| {
+            std::fs::read(path).map_err(|e| Error::Tls(format!("{}: {}", path.display(), e)))
+        };
+        Ok(Self::from_pem(
+            read(cert.as_ref())?,
+            read(key.as_ref())?,
+            read(ca.as_ref())?,
+        ))
+    }
+
+    fn identity(&self) -> Identity {
+        Identity::from_pem(&self.cert, &self.key)
+    }
+
+    /// Server side: client certificates are required.
+    pub fn server_config(&self) -> ServerTlsConfig {
+        ServerTlsConfig::new()
+            .identity(self.identity())
+            .client_ca_root(Certificate::from_pem(&self.ca))
+    }
+
+    /// Client side: the server must present a certificate from the CA.
+    pub fn client_config(&self) -> ClientTlsConfig {
+        ClientTlsConfig::new()
+            .ca_certificate(Certificate::from_pem(&self.ca))
+            .identity(self.identity())
+    }
+}
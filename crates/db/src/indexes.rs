@@ -32,6 +32,18 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
         vec![
             index_unique(bson::doc! { "tenant_id": 1, "user_id": 1 }),
             index(bson::doc! { "user_id": 1 }),
+            index(bson::doc! { "tenant_id": 1, "search_keys": 1 }),
+        ],
+    )
+    .await?;
+
+    // Tenant Domains
+    create_indexes(
+        db,
+        "tenant_domains",
+        vec![
+            index_unique(bson::doc! { "tenant_id": 1, "domain": 1 }),
+            index(bson::doc! { "domain": 1, "verified_at": 1 }),
         ],
     )
     .await?;
@@ -57,6 +69,8 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
             index(bson::doc! { "tenant_id": 1, "name": 1 }),
             index(bson::doc! { "tenant_id": 1, "is_default": 1 }),
             index_unique_sparse(bson::doc! { "meeting_code": 1 }),
+            // Calendar sync polls upcoming scheduled calls
+            index(bson::doc! { "conference_settings.scheduled_end": 1 }),
             index_text(bson::doc! { "name": "text", "purpose": "text", "tags": "text" }),
         ],
     )
@@ -88,6 +102,78 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
     )
     .await?;
 
+    // Archived messages — read by room and thread like the hot ones, and
+    // swept per tenant by retention.
+    create_indexes(
+        db,
+        "messages_archive",
+        vec![
+            index(bson::doc! { "room_id": 1, "created_at": -1 }),
+            index(bson::doc! { "thread_id": 1, "created_at": 1 }),
+            index(bson::doc! { "tenant_id": 1, "created_at": 1 }),
+            index_text(bson::doc! { "content": "text" }),
+        ],
+    )
+    .await?;
+
+    // Scheduled messages — the scheduler polls by `send_at`; authors list
+    // their own per room.
+    create_indexes(
+        db,
+        "scheduled_messages",
+        vec![
+            index(bson::doc! { "send_at": 1 }),
+            index(bson::doc! { "room_id": 1, "author_id": 1, "send_at": 1 }),
+        ],
+    )
+    .await?;
+
+    // Integrations: outgoing webhooks are looked up per tenant on every
+    // message; command names are unique per tenant.
+    create_indexes(
+        db,
+        "webhooks",
+        vec![index(
+            bson::doc! { "tenant_id": 1, "kind": 1, "is_active": 1 },
+        )],
+    )
+    .await?;
+    create_indexes(
+        db,
+        "slash_commands",
+        vec![index_unique(bson::doc! { "tenant_id": 1, "command": 1 })],
+    )
+    .await?;
+
+    // Feature flags — one setting per tenant and flag, all loaded at once.
+    create_indexes(
+        db,
+        "feature_flags",
+        vec![index_unique(bson::doc! { "tenant_id": 1, "key": 1 })],
+    )
+    .await?;
+
+    // Call debug timelines — read per room in order, kept for 3 days.
+    create_indexes(
+        db,
+        "call_debug_events",
+        vec![
+            index(bson::doc! { "tenant_id": 1, "room_id": 1, "at": 1 }),
+            index_ttl(bson::doc! { "at": 1 }, 3 * 24 * 60 * 60),
+        ],
+    )
+    .await?;
+
+    // Bot tokens — looked up by id on every bot request; listed per bot.
+    create_indexes(
+        db,
+        "bot_tokens",
+        vec![index(
+            bson::doc! { "tenant_id": 1, "bot_id": 1, "created_at": 1 },
+        )],
+    )
+    .await?;
+
     // Reactions
     create_indexes(
         db,
@@ -98,6 +184,49 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
     )
     .await?;
 
+    // Call history — one active session per room, listed newest first.
+    create_indexes(
+        db,
+        "call_sessions",
+        vec![
+            index(bson::doc! { "room_id": 1, "ended_at": 1 }),
+            index(bson::doc! { "tenant_id": 1, "room_id": 1, "started_at": -1 }),
+        ],
+    )
+    .await?;
+
+    // In-call polls and Q&A, listed per call session
+    create_indexes(
+        db,
+        "call_polls",
+        vec![index(bson::doc! { "call_session_id": 1, "created_at": 1 })],
+    )
+    .await?;
+    create_indexes(
+        db,
+        "call_questions",
+        vec![index(
+            bson::doc! { "call_session_id": 1, "upvotes": -1, "created_at": 1 },
+        )],
+    )
+    .await?;
+
+    // Calendar connections — one per user and provider
+    create_indexes(
+        db,
+        "calendar_connections",
+        vec![index_unique(bson::doc! { "user_id": 1, "provider": 1 })],
+    )
+    .await?;
+
+    // Whiteboards: one per room
+    create_indexes(
+        db,
+        "whiteboards",
+        vec![index_unique(bson::doc! { "room_id": 1 })],
+    )
+    .await?;
+
     // Recordings
     create_indexes(
         db,
@@ -108,6 +237,15 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
         ],
     )
     .await?;
+    create_indexes(
+        db,
+        "recording_consents",
+        vec![
+            index_unique(bson::doc! { "recording_id": 1, "user_id": 1 }),
+            index(bson::doc! { "tenant_id": 1, "room_id": 1, "created_at": -1 }),
+        ],
+    )
+    .await?;
 
     // Files
     create_indexes(
@@ -191,6 +329,30 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
     )
     .await?;
 
+    // Usage metering: one document per tenant per day
+    create_indexes(
+        db,
+        "usage_days",
+        vec![
+            index_unique(bson::doc! { "tenant_id": 1, "date": 1 }),
+            index(bson::doc! { "tenant_id": 1, "reported_at": 1, "date": 1 }),
+        ],
+    )
+    .await?;
+
+    // Stripe webhook events: dedupe by event id, queue by next attempt.
+    // Kept 30 days, well past Stripe's 3-day retry window.
+    create_indexes(
+        db,
+        "stripe_events",
+        vec![
+            index_unique(bson::doc! { "event_id": 1 }),
+            index(bson::doc! { "status": 1, "next_attempt_at": 1 }),
+            index_ttl(bson::doc! { "created_at": 1 }, 30 * 24 * 60 * 60),
+        ],
+    )
+    .await?;
+
     // Activation Codes
     create_indexes(
         db,
@@ -357,7 +519,7 @@ pub async fn ensure_indexes(db: &Database) -> Result<(), mongodb::error::Error>
     Ok(())
 }
 
-fn index(keys: bson::Document) -> IndexModel {
+pub(crate) fn index(keys: bson::Document) -> IndexModel {
     IndexModel::builder().keys(keys).build()
 }
 
@@ -368,7 +530,7 @@ fn index_unique(keys: bson::Document) -> IndexModel {
         .build()
 }
 
-fn index_ttl(keys: bson::Document, expire_after_secs: u64) -> IndexModel {
+pub(crate) fn index_ttl(keys: bson::Document, expire_after_secs: u64) -> IndexModel {
     IndexModel::builder()
         .keys(keys)
         .options(
@@ -405,7 +567,7 @@ fn index_unique_partial(keys: bson::Document, filter: bson::Document) -> IndexMo
         .build()
 }
 
-async fn create_indexes(
+pub(crate) async fn create_indexes(
     db: &Database,
     collection: &str,
     indexes: Vec<IndexModel>,
@@ -0,0 +1,216 @@
+//! Versioned schema changes, applied once per database at startup.
+//!
+//! [`migrate`] first creates the indexes declared in [`crate::indexes`],
+//! which is a no-op for indexes that already exist, then runs every entry of
+//! [`MIGRATIONS`] not yet recorded in `schema_migrations`, in version order,
+//! recording each one as it completes. Pods starting together may run the
+//! same step twice, so a step must be safe to repeat.
+//!
+//! New indexes on a collection can simply be declared in `indexes.rs`; add a
+//! migration for anything that changes what an existing deployment already
+//! has (TTLs on existing fields, replacing an index, backfilling data).
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+
+use bson::{DateTime, doc};
+use mongodb::Database;
+use tracing::info;
+
+use crate::indexes::{create_indexes, ensure_indexes, index, index_ttl};
+use crate::models::{File, Tenant, TenantMember, User};
+
+/// Collection recording applied migrations, keyed by version.
+pub const COLLECTION: &str = "schema_migrations";
+
+type StepFuture<'a> = Pin<Box<dyn Future<Output = Result<(), mongodb::error::Error>> + Send + 'a>>;
+
+pub struct Migration {
+    pub version: u32,
+    pub name: &'static str,
+    apply: for<'a> fn(&'a Database) -> StepFuture<'a>,
+}
+
+/// Every migration, oldest first. Versions are never reused or reordered.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "message_tenant_room_index",
+        apply: |db| Box::pin(message_tenant_room_index(db)),
+    },
+    Migration {
+        version: 2,
+        name: "invite_expiry_ttl",
+        apply: |db| Box::pin(invite_expiry_ttl(db)),
+    },
+    Migration {
+        version: 3,
+        name: "notification_ttl",
+        apply: |db| Box::pin(notification_ttl(db)),
+    },
+    Migration {
+        version: 4,
+        name: "member_search_keys",
+        apply: |db| Box::pin(member_search_keys(db)),
+    },
+    Migration {
+        version: 5,
+        name: "tenant_storage_used",
+        apply: |db| Box::pin(tenant_storage_used(db)),
+    },
+];
+
+/// Bring `db` up to date. Returns the versions applied by this call.
+pub async fn migrate(db: &Database) -> Result<Vec<u32>, mongodb::error::Error> {
+    ensure_indexes(db).await?;
+
+    let applied = db.collection::<bson::Document>(COLLECTION);
+    let done: HashSet<i64> = applied
+        .distinct("_id", doc! {})
+        .await?
+        .iter()
+        .filter_map(|v| v.as_i64())
+        .collect();
+
+    let mut ran = Vec::new();
+    for migration in MIGRATIONS {
+        if done.contains(&i64::from(migration.version)) {
+            continue;
+        }
+        info!(
+            version = migration.version,
+            name = migration.name,
+            "Applying migration"
+        );
+        (migration.apply)(db).await?;
+        applied
+            .update_one(
+                doc! { "_id": i64::from(migration.version) },
+                doc! { "$setOnInsert": {
+                    "name": migration.name,
+                    "applied_at": DateTime::now(),
+                }},
+            )
+            .upsert(true)
+            .await?;
+        ran.push(migration.version);
+    }
+    Ok(ran)
+}
+
+/// Tenant-wide message scans (exports, retention, archiving) narrowed to a
+/// room, newest first.
+async fn message_tenant_room_index(db: &Database) -> Result<(), mongodb::error::Error> {
+    for collection in ["messages", "messages_archive"] {
+        create_indexes(
+            db,
+            collection,
+            vec![index(
+                doc! { "tenant_id": 1, "room_id": 1, "created_at": -1 },
+            )],
+        )
+        .await?;
+    }
+    Ok(())
+}
+
+/// Drop invites 30 days after they expire; invites without `expires_at` are
+/// kept.
+async fn invite_expiry_ttl(db: &Database) -> Result<(), mongodb::error::Error> {
+    create_indexes(
+        db,
+        "invites",
+        vec![index_ttl(doc! { "expires_at": 1 }, 30 * 24 * 60 * 60)],
+    )
+    .await
+}
+
+/// Drop notifications after 90 days, read or not.
+async fn notification_ttl(db: &Database) -> Result<(), mongodb::error::Error> {
+    create_indexes(
+        db,
+        "notifications",
+        vec![index_ttl(doc! { "created_at": 1 }, 90 * 24 * 60 * 60)],
+    )
+    .await
+}
+
+/// Fill `search_keys` on memberships created before member search.
+async fn member_search_keys(db: &Database) -> Result<(), mongodb::error::Error> {
+    let members = db.collection::<bson::Document>(TenantMember::COLLECTION);
+    let users = db.collection::<bson::Document>(User::COLLECTION);
+    let missing = doc! { "search_keys": { "$exists": false } };
+    for user_id in members.distinct("user_id", missing.clone()).await? {
+        let Some(user) = users.find_one(doc! { "_id": &user_id }).await? else {
+            continue;
+        };
+        let keys = TenantMember::search_keys(
+            user.get_str("display_name").unwrap_or_default(),
+            user.get_str("username").unwrap_or_default(),
+            user.get_str("email").unwrap_or_default(),
+        );
+        let mut filter = missing.clone();
+        filter.insert("user_id", user_id);
+        members
+            .update_many(filter, doc! { "$set": { "search_keys": keys } })
+            .await?;
+    }
+    Ok(())
+}
+
+/// Fill `storage_used_bytes`, the storage quota counter, from the tenants'
+/// live files.
+async fn tenant_storage_used(db: &Database) -> Result<(), mongodb::error::Error> {
+    let tenants = db.collection::<bson::Document>(Tenant::COLLECTION);
+    let files = db.collection::<bson::Document>(File::COLLECTION);
+    let mut totals = files
+        .aggregate(vec![
+            doc! { "$match": { "deleted_at": null } },
+            doc! { "$group": { "_id": "$tenant_id", "bytes": { "$sum": "$size" } } },
+        ])
+        .await?;
+    let mut measured = Vec::new();
+    while totals.advance().await? {
+        let total = totals.deserialize_current()?;
+        let Ok(tenant_id) = total.get_object_id("_id") else {
+            continue;
+        };
+        let bytes = match total.get("bytes") {
+            Some(bson::Bson::Int32(n)) => i64::from(*n),
+            Some(bson::Bson::Int64(n)) => *n,
+            Some(bson::Bson::Double(n)) => *n as i64,
+            _ => 0,
+        };
+        tenants
+            .update_one(
+                doc! { "_id": tenant_id },
+                doc! { "$set": { "storage_used_bytes": bytes.max(0) } },
+            )
+            .await?;
+        measured.push(tenant_id);
+    }
+    tenants
+        .update_many(
+            doc! { "_id": { "$nin": measured } },
+            doc! { "$set": { "storage_used_bytes": 0_i64 } },
+        )
+        .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_increase_and_names_are_unique() {
+        let mut names = HashSet::new();
+        for pair in MIGRATIONS.windows(2) {
+            assert!(pair[0].version < pair[1].version, "{}", pair[1].name);
+        }
+        for migration in MIGRATIONS {
+            assert!(names.insert(migration.name), "{}", migration.name);
+        }
+    }
+}
@@ -34,6 +34,8 @@ pub enum TaskCategory {
     Export,
     Import,
     Recognition,
+    Scan,
+    Preview,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
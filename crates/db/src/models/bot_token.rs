@@ -0,0 +1,23 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A long-lived API token issued to a bot account. The JWT only names this
+/// row (`jti`); the room scope and revocation are checked on every request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotToken {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub bot_id: ObjectId,
+    pub name: String,
+    /// Rooms the token may read and post in.
+    pub room_ids: Vec<ObjectId>,
+    pub created_by: ObjectId,
+    pub expires_at: DateTime,
+    pub revoked_at: Option<DateTime>,
+    pub created_at: DateTime,
+}
+
+impl BotToken {
+    pub const COLLECTION: &'static str = "bot_tokens";
+}
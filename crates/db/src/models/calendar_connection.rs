@@ -0,0 +1,25 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A user's Google or Microsoft calendar, connected through OAuth so
+/// scheduled calls they organize show up there. One per user and provider.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarConnection {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub user_id: ObjectId,
+    /// `google` or `microsoft`.
+    pub provider: String,
+    /// Account the calendar belongs to, as the provider reports it.
+    pub account_email: String,
+    pub access_token: String,
+    /// Used to get a new access token once `expires_at` passes.
+    pub refresh_token: String,
+    pub expires_at: DateTime,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl CalendarConnection {
+    pub const COLLECTION: &'static str = "calendar_connections";
+}
@@ -1,13 +1,18 @@
 use bson::{DateTime, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
+use super::message::AuthorType;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallChatMessage {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
     pub id: Option<ObjectId>,
     pub tenant_id: ObjectId,
     pub room_id: ObjectId,
+    /// For the meeting assistant (`bot`), the call session it answers in.
     pub author_id: ObjectId,
+    #[serde(default)]
+    pub author_type: AuthorType,
     pub display_name: String,
     pub content: String,
     pub created_at: DateTime,
@@ -0,0 +1,39 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// One step of a connection's media signaling, kept for a few days so an
+/// admin can see where a participant's call went wrong.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallDebugEvent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    pub connection_id: String,
+    pub kind: CallDebugKind,
+    /// What happened, e.g. `send: connected` or the error message.
+    #[serde(default)]
+    pub detail: String,
+    pub at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallDebugKind {
+    Join,
+    TransportConnect,
+    IceRestart,
+    /// ICE state of the send or recv transport, as mediasoup reports it.
+    IceState,
+    DtlsState,
+    Produce,
+    Consume,
+    Rejoin,
+    Leave,
+    Error,
+}
+
+impl CallDebugEvent {
+    pub const COLLECTION: &'static str = "call_debug_events";
+}
@@ -0,0 +1,48 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A poll run during a call. Votes are anonymous in responses; `votes` only
+/// exists to stop a user voting twice.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallPoll {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub call_session_id: ObjectId,
+    pub created_by: ObjectId,
+    pub question: String,
+    pub options: Vec<PollOption>,
+    #[serde(default)]
+    pub allow_multiple: bool,
+    /// Whether participants see the tallies before the poll closes.
+    /// Closed polls always show them.
+    #[serde(default)]
+    pub results_visible: bool,
+    #[serde(default)]
+    pub votes: Vec<PollVote>,
+    #[serde(default)]
+    pub total_votes: u32,
+    pub closed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOption {
+    pub text: String,
+    #[serde(default)]
+    pub votes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollVote {
+    pub user_id: ObjectId,
+    /// Indexes into `options`.
+    pub option_ids: Vec<u32>,
+    pub voted_at: DateTime,
+}
+
+impl CallPoll {
+    pub const COLLECTION: &'static str = "call_polls";
+}
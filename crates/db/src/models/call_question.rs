@@ -0,0 +1,26 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A question asked in a call's Q&A.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallQuestion {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub call_session_id: ObjectId,
+    pub author_id: ObjectId,
+    pub display_name: String,
+    pub content: String,
+    #[serde(default)]
+    pub upvoter_ids: Vec<ObjectId>,
+    #[serde(default)]
+    pub upvotes: u32,
+    pub answered_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl CallQuestion {
+    pub const COLLECTION: &'static str = "call_questions";
+}
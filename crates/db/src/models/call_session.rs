@@ -0,0 +1,84 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+use super::recording::TranscriptSegment;
+
+/// One call in a room, from `call/start` to `call/end` (or the last
+/// participant leaving). The room itself only tracks the live call; this is
+/// the history that survives the next one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallSession {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub started_by: ObjectId,
+    pub started_at: DateTime,
+    /// `None` while the call is in progress.
+    pub ended_at: Option<DateTime>,
+    /// Participants currently in the call.
+    #[serde(default)]
+    pub participant_count: u32,
+    #[serde(default)]
+    pub peak_participants: u32,
+    /// One entry per join; a user who drops and rejoins has several.
+    #[serde(default)]
+    pub participants: Vec<CallParticipantSession>,
+    #[serde(default)]
+    pub recording_ids: Vec<ObjectId>,
+    /// Participants who opted out of transcription: their transcript
+    /// segments read "[not transcribed]".
+    #[serde(default)]
+    pub transcription_opt_outs: Vec<ObjectId>,
+    /// Breakout rooms opened during the call; the open ones have no
+    /// `closed_at`.
+    #[serde(default)]
+    pub breakouts: Vec<BreakoutRoom>,
+    /// Questions put to the meeting assistant during the call and its
+    /// answers, oldest first: the call's notes.
+    #[serde(default)]
+    pub assistant_notes: Vec<AssistantNote>,
+    /// The last `assistant.context_lines` final transcript lines posted to
+    /// `call/transcript`, oldest first: what the meeting assistant has
+    /// heard. Lines of participants who opted out read "[not transcribed]".
+    #[serde(default)]
+    pub transcript: Vec<TranscriptSegment>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl CallSession {
+    pub const COLLECTION: &'static str = "call_sessions";
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallParticipantSession {
+    pub user_id: ObjectId,
+    pub display_name: String,
+    pub device_type: String,
+    pub joined_at: DateTime,
+    pub left_at: Option<DateTime>,
+    /// Seconds in the call, set when the entry is closed.
+    pub duration: Option<i64>,
+}
+
+/// A sub-room of a call with its own media Router. Clients join its media
+/// with `media:join { room_id: <breakout id> }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BreakoutRoom {
+    pub id: ObjectId,
+    pub name: String,
+    #[serde(default)]
+    pub user_ids: Vec<ObjectId>,
+    pub opened_at: DateTime,
+    pub closed_at: Option<DateTime>,
+}
+
+/// One `/assistant` question and the answer it got.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AssistantNote {
+    pub asked_by: ObjectId,
+    pub question: String,
+    pub answer: String,
+    pub asked_at: DateTime,
+}
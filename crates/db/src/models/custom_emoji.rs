@@ -8,6 +8,9 @@ pub struct CustomEmoji {
     pub tenant_id: ObjectId,
     pub name: String,
     pub image_url: String,
+    /// Stored image, when uploaded through the asset library.
+    #[serde(default)]
+    pub file_id: Option<ObjectId>,
     #[serde(default)]
     pub is_animated: bool,
     pub creator_id: ObjectId,
@@ -0,0 +1,39 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A tenant's setting for one feature flag. Flags without a document follow
+/// the deployment default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub key: String,
+    #[serde(default)]
+    pub mode: FlagMode,
+    /// Share of users (0-100) the flag is on for; only read in
+    /// [`FlagMode::Percentage`].
+    #[serde(default)]
+    pub percentage: u8,
+    pub updated_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagMode {
+    /// Whatever the deployment sets (`features.<key>`, else the flag's
+    /// built-in default).
+    #[default]
+    Default,
+    On,
+    Off,
+    /// On for a stable `percentage` of users (or rooms, for room-wide
+    /// flags).
+    Percentage,
+}
+
+impl FeatureFlag {
+    pub const COLLECTION: &'static str = "feature_flags";
+}
@@ -32,6 +32,7 @@ pub struct File {
     pub external_source: Option<ExternalSource>,
     #[serde(default)]
     pub scan_status: ScanStatus,
+    pub scan_result: Option<ScanResult>,
     #[serde(default)]
     pub visibility: Visibility,
     pub recognized_content: Option<RecognizedContent>,
@@ -54,6 +55,10 @@ pub enum FileContextType {
     Document,
     Profile,
     Room,
+    /// Tenant-wide virtual background image; `entity_id` is the tenant.
+    Background,
+    /// Tenant custom emoji image; `entity_id` is the tenant.
+    Emoji,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -108,6 +113,34 @@ pub enum ScanStatus {
     Skipped,
 }
 
+impl ScanStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ScanStatus::Pending => "pending",
+            ScanStatus::Clean => "clean",
+            ScanStatus::Malware => "malware",
+            ScanStatus::Skipped => "skipped",
+        }
+    }
+
+    /// Only `clean` and `skipped` (scanning off) files can be downloaded;
+    /// the rest are quarantined.
+    pub fn is_downloadable(&self) -> bool {
+        matches!(self, ScanStatus::Clean | ScanStatus::Skipped)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanResult {
+    /// `clamav`, `http` or `override`.
+    pub engine: String,
+    /// What the engine found, for `malware`.
+    pub signature: Option<String>,
+    /// The admin who set the status by hand.
+    pub overridden_by: Option<ObjectId>,
+    pub scanned_at: DateTime,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RecognizedContent {
     pub raw_text: String,
@@ -14,6 +14,10 @@ pub struct Message {
     pub author_id: ObjectId,
     #[serde(default)]
     pub author_type: AuthorType,
+    /// Display name for bot and webhook authors, whose `author_id` is not a
+    /// user.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_name: Option<String>,
     pub content: String,
     #[serde(default)]
     pub content_type: ContentType,
@@ -30,15 +34,32 @@ pub struct Message {
     pub referenced_message_id: Option<ObjectId>,
     #[serde(default)]
     pub is_pinned: bool,
+    /// Posted without notifications and without counting as unread.
+    #[serde(default)]
+    pub is_silent: bool,
     #[serde(default)]
     pub is_edited: bool,
     pub edited_at: Option<DateTime>,
+    /// Previous versions of `content`, oldest first.
+    #[serde(default)]
+    pub edit_history: Vec<MessageEdit>,
     pub nonce: Option<String>,
     #[serde(default)]
     pub readby: Vec<ObjectId>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
+    /// Who soft-deleted the message — the author or a moderator.
+    #[serde(default)]
+    pub deleted_by: Option<ObjectId>,
+}
+
+/// A superseded version of a message's content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEdit {
+    pub content: String,
+    /// When this version was replaced.
+    pub edited_at: DateTime,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +70,10 @@ pub struct ThreadMetadata {
     pub last_reply_user_id: Option<ObjectId>,
     #[serde(default)]
     pub participant_ids: Vec<ObjectId>,
+    /// Users notified of new replies. The root author and every replier
+    /// follow automatically; anyone can follow/unfollow explicitly.
+    #[serde(default)]
+    pub follower_ids: Vec<ObjectId>,
     #[serde(default)]
     pub is_locked: bool,
     #[serde(default)]
@@ -132,4 +157,6 @@ pub struct ReactionSummary {
 
 impl Message {
     pub const COLLECTION: &'static str = "messages";
+    /// Cold storage for old messages (see `MessageDao::archive_before`).
+    pub const ARCHIVE_COLLECTION: &'static str = "messages_archive";
 }
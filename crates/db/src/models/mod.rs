@@ -1,7 +1,14 @@
 pub mod audit_log;
 pub mod background_task;
+pub mod bot_token;
+pub mod calendar_connection;
 pub mod call_chat_message;
+pub mod call_debug_event;
+pub mod call_poll;
+pub mod call_question;
+pub mod call_session;
 pub mod custom_emoji;
+pub mod feature_flag;
 pub mod file;
 pub mod invite;
 pub mod message;
@@ -9,18 +16,34 @@ pub mod notification;
 pub mod push_subscription;
 pub mod reaction;
 pub mod recording;
+pub mod recording_consent;
 pub mod role;
 pub mod room;
 pub mod room_member;
+pub mod scheduled_message;
+pub mod settings_override;
+pub mod slash_command;
+pub mod stripe_event;
 pub mod tenant;
+pub mod tenant_domain;
 pub mod tenant_member;
+pub mod usage;
+pub mod webhook;
+pub mod whiteboard;
 
 pub mod user;
 
 pub use audit_log::*;
 pub use background_task::*;
+pub use bot_token::*;
+pub use calendar_connection::*;
 pub use call_chat_message::*;
+pub use call_debug_event::*;
+pub use call_poll::*;
+pub use call_question::*;
+pub use call_session::*;
 pub use custom_emoji::*;
+pub use feature_flag::*;
 pub use file::*;
 pub use invite::*;
 pub use message::*;
@@ -28,11 +51,20 @@ pub use notification::*;
 pub use push_subscription::*;
 pub use reaction::*;
 pub use recording::*;
+pub use recording_consent::*;
 pub use role::*;
 pub use room::*;
 pub use room_member::*;
+pub use scheduled_message::*;
+pub use settings_override::*;
+pub use slash_command::*;
+pub use stripe_event::*;
 pub use tenant::*;
+pub use tenant_domain::*;
 pub use tenant_member::*;
+pub use usage::*;
+pub use webhook::*;
+pub use whiteboard::*;
 
 pub use user::*;
 
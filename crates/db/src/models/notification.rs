@@ -23,9 +23,13 @@ pub struct Notification {
 pub enum NotificationType {
     Message,
     Mention,
+    /// A new reply in a thread the user follows.
+    ThreadReply,
     Reaction,
     Invite,
     Call,
+    /// A call rang the user and they didn't pick up.
+    MissedCall,
     TaskComplete,
     /// A remote-control session is awaiting the device owner's approval
     /// (Phase 4 owner-consent). `link` points at the in-app consent page.
@@ -1,6 +1,8 @@
 use bson::{DateTime, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
+use super::tenant::NotificationLevel;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PushSubscription {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -8,6 +10,9 @@ pub struct PushSubscription {
     pub user_id: ObjectId,
     pub endpoint: String,
     pub keys: PushKeys,
+    /// Per-device override: this device only gets pushes the level allows.
+    #[serde(default)]
+    pub notification_level: Option<NotificationLevel>,
     pub created_at: DateTime,
 }
 
@@ -17,6 +17,21 @@ pub struct Recording {
     #[serde(default = "bool_true")]
     pub allow_download: bool,
     pub expires_at: Option<DateTime>,
+    /// Where the file's first frame sits on the call clock, in ms; set when
+    /// the file is finalized.
+    #[serde(default)]
+    pub media_offset_ms: u64,
+    /// Transcript segments on the call clock, as the live transcript sent
+    /// them; aligned to the file when read.
+    #[serde(default)]
+    pub transcript: Vec<TranscriptSegment>,
+    /// Chapter markers, from the background chaptering task.
+    #[serde(default)]
+    pub chapters: Vec<RecordingChapter>,
+    /// Set when the call's participants were asked to consent before the
+    /// recording started (see `recording_consents`).
+    #[serde(default)]
+    pub consent: Option<ConsentPolicy>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
@@ -35,6 +50,9 @@ pub enum RecordingType {
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum RecordingStatus {
+    /// Asked the participants for consent; recording starts once everyone
+    /// answered or the request timed out.
+    AwaitingConsent,
     #[default]
     Processing,
     Available,
@@ -42,6 +60,23 @@ pub enum RecordingStatus {
     Deleted,
 }
 
+/// Consent the call's participants must give before the recording starts.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default)]
+pub struct ConsentPolicy {
+    pub on_decline: DeclineAction,
+}
+
+/// What happens to a participant who declines being recorded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeclineAction {
+    /// Removed from the call.
+    Remove,
+    /// Stays in the call; their media is left out of the recording.
+    #[default]
+    KeepUnrecorded,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StorageFile {
     pub storage_provider: StorageProvider,
@@ -51,9 +86,29 @@ pub struct StorageFile {
     pub content_type: String,
     pub size: u64,
     pub duration: u32,
+    /// Exact media length in ms (`duration` is whole seconds).
+    #[serde(default)]
+    pub duration_ms: u64,
     pub resolution: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingChapter {
+    /// Seconds into the recording.
+    pub start: f64,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    pub user_id: Option<ObjectId>,
+    pub speaker_name: String,
+    pub text: String,
+    /// Seconds on the call clock.
+    pub start_time: f64,
+    pub end_time: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum StorageProvider {
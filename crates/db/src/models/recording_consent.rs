@@ -0,0 +1,34 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A call participant's answer to a recording's consent request. Kept when
+/// the recording is deleted, as the record of who agreed to be recorded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingConsent {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub recording_id: ObjectId,
+    pub user_id: ObjectId,
+    pub decision: ConsentDecision,
+    /// Declined by the request timing out, not by the participant.
+    #[serde(default)]
+    pub timed_out: bool,
+    pub decided_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsentDecision {
+    #[default]
+    Pending,
+    Accepted,
+    Declined,
+}
+
+impl RecordingConsent {
+    pub const COLLECTION: &'static str = "recording_consents";
+}
@@ -92,6 +92,15 @@ pub mod permissions {
         | REMOTE_CONTROL
         | VIEW_REMOTE_AUDIT;
 
+    /// Granted on top of role/overwrite permissions to a room's organizer
+    /// and co-organizers (the creator, when no organizer is set).
+    pub const ORGANIZER: u64 = MANAGE_CHANNELS
+        | MANAGE_MESSAGES
+        | MANAGE_MEETINGS
+        | MUTE_MEMBERS
+        | DEAFEN_MEMBERS
+        | MOVE_MEMBERS;
+
     /// Owner permissions (everything). Bump the mask whenever a new bit is
     /// added above so `ALL` literally contains every defined permission (owner
     /// also passes via the `ADMINISTRATOR` bypass in `has`, but keep this exact).
@@ -100,6 +109,40 @@ pub mod permissions {
     pub fn has(permissions: u64, flag: u64) -> bool {
         permissions & ADMINISTRATOR != 0 || permissions & flag == flag
     }
+
+    /// Constant name of a single permission bit, for error messages.
+    pub fn name(flag: u64) -> &'static str {
+        match flag {
+            VIEW_CHANNELS => "VIEW_CHANNELS",
+            MANAGE_CHANNELS => "MANAGE_CHANNELS",
+            MANAGE_ROLES => "MANAGE_ROLES",
+            MANAGE_TENANT => "MANAGE_TENANT",
+            KICK_MEMBERS => "KICK_MEMBERS",
+            BAN_MEMBERS => "BAN_MEMBERS",
+            INVITE_MEMBERS => "INVITE_MEMBERS",
+            SEND_MESSAGES => "SEND_MESSAGES",
+            SEND_THREADS => "SEND_THREADS",
+            EMBED_LINKS => "EMBED_LINKS",
+            ATTACH_FILES => "ATTACH_FILES",
+            READ_HISTORY => "READ_HISTORY",
+            MENTION_EVERYONE => "MENTION_EVERYONE",
+            MANAGE_MESSAGES => "MANAGE_MESSAGES",
+            ADD_REACTIONS => "ADD_REACTIONS",
+            CONNECT_VOICE => "CONNECT_VOICE",
+            SPEAK => "SPEAK",
+            STREAM_VIDEO => "STREAM_VIDEO",
+            MUTE_MEMBERS => "MUTE_MEMBERS",
+            DEAFEN_MEMBERS => "DEAFEN_MEMBERS",
+            MOVE_MEMBERS => "MOVE_MEMBERS",
+            MANAGE_MEETINGS => "MANAGE_MEETINGS",
+            MANAGE_DOCUMENTS => "MANAGE_DOCUMENTS",
+            ADMINISTRATOR => "ADMINISTRATOR",
+            MANAGE_AGENTS => "MANAGE_AGENTS",
+            REMOTE_CONTROL => "REMOTE_CONTROL",
+            VIEW_REMOTE_AUDIT => "VIEW_REMOTE_AUDIT",
+            _ => "required",
+        }
+    }
 }
 
 impl Role {
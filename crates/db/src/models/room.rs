@@ -21,8 +21,16 @@ pub struct Room {
     pub is_archived: bool,
     #[serde(default)]
     pub is_read_only: bool,
+    /// Announcement channel: only members who can manage the channel
+    /// (organizers, admins) post; everyone else reads.
+    #[serde(default)]
+    pub is_announcement: bool,
     #[serde(default)]
     pub is_default: bool,
+    /// Exempts the room's messages, recordings and call chat from the
+    /// tenant's retention policy.
+    #[serde(default)]
+    pub legal_hold: bool,
     #[serde(default)]
     pub permission_overwrites: Vec<PermissionOverwrite>,
     #[serde(default)]
@@ -79,10 +87,50 @@ pub struct MediaSettings {
     pub screen_share_enabled: bool,
     #[serde(default)]
     pub recording_enabled: bool,
+    /// Distinct users the call's media takes in full; `None` is uncapped.
     pub max_participants: Option<u32>,
+    /// What happens to users joining once `max_participants` is reached.
+    #[serde(default)]
+    pub overflow: OverflowMode,
+    /// End-to-end encrypted media (SFrame / insertable streams). Clients
+    /// exchange keys among themselves; the server only coordinates epochs.
+    #[serde(default)]
+    pub e2ee_enabled: bool,
+    /// Cap in bps on what each participant sends to the SFU
+    /// (`setMaxIncomingBitrate` on their transports). `None` is uncapped.
+    #[serde(default)]
+    pub max_incoming_bitrate: Option<u32>,
+    /// Cap in bps on what the SFU sends each participant.
+    #[serde(default)]
+    pub max_outgoing_bitrate: Option<u32>,
+    /// Calls start in push-to-talk mode: audio only flows while a
+    /// participant holds `media:ptt_active`.
+    #[serde(default)]
+    pub push_to_talk: bool,
+    /// Calls run as webinars: only promoted speakers send media, everyone
+    /// else watches.
+    #[serde(default)]
+    pub webinar: bool,
+    /// Finished recordings get a WebVTT caption track built from their
+    /// transcript. Live calls and streams don't carry captions.
+    #[serde(default)]
+    pub captions: bool,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// How a call treats joiners past its `max_participants`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowMode {
+    /// Refuse the join with `room_full`.
+    #[default]
+    Reject,
+    /// Join without video: audio is sent and received, video neither.
+    AudioOnly,
+    /// Join receive-only: the room's media comes in, nothing goes out.
+    ListenOnly,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ConferenceSettings {
     pub scheduled_start: Option<DateTime>,
     pub scheduled_end: Option<DateTime>,
@@ -92,4 +140,45 @@ pub struct ConferenceSettings {
     pub lobby_enabled: bool,
     #[serde(default)]
     pub auto_record: bool,
+    /// Who the call was scheduled for, with their calendar responses.
+    #[serde(default)]
+    pub invitees: Vec<Invitee>,
+    /// The organizer's calendar event for the call, if it has one.
+    #[serde(default)]
+    pub calendar_event: Option<CalendarEventLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitee {
+    pub email: String,
+    /// The user with that email, if there is one.
+    pub user_id: Option<ObjectId>,
+    #[serde(default)]
+    pub response: InviteeResponse,
+}
+
+/// An invitee's answer to the calendar invitation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InviteeResponse {
+    #[default]
+    NeedsAction,
+    Accepted,
+    Tentative,
+    Declined,
+}
+
+/// A scheduled call's event in a connected calendar.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEventLink {
+    /// `google` or `microsoft`.
+    pub provider: String,
+    /// Whose calendar holds the event.
+    pub user_id: ObjectId,
+    /// `None` until the event is created.
+    pub event_id: Option<String>,
+    /// Last successful sync with the calendar.
+    pub synced_at: Option<DateTime>,
+    /// Why the last sync failed; retried on the next one.
+    pub error: Option<String>,
 }
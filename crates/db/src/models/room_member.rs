@@ -1,6 +1,8 @@
 use bson::{DateTime, oid::ObjectId};
 use serde::{Deserialize, Serialize};
 
+use super::tenant::NotificationLevel;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoomMember {
     #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
@@ -22,11 +24,19 @@ pub struct RoomMember {
     pub unread_count: i64,
     #[serde(default)]
     pub mention_count: i64,
-    pub notification_override: Option<String>,
+    /// The member's notification level for this room; `None` follows their
+    /// tenant-wide setting.
+    pub notification_override: Option<NotificationLevel>,
     #[serde(default)]
     pub is_muted: bool,
+    /// Pinned to the top of the member's sidebar.
     #[serde(default)]
     pub is_pinned: bool,
+    /// Listed in the member's favorites section of the sidebar.
+    #[serde(default)]
+    pub is_favorite: bool,
+    /// The member's own sidebar order; rooms without one follow, by name.
+    pub sidebar_position: Option<i32>,
     #[serde(default)]
     pub is_video_on: bool,
     #[serde(default)]
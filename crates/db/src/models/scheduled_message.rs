@@ -0,0 +1,30 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+use super::message::{Mentions, MessageAttachment};
+
+/// A message waiting for its `send_at`. The scheduler claims it (deleting the
+/// row) and posts it as a regular [`super::Message`] at that time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    pub author_id: ObjectId,
+    pub content: String,
+    pub thread_id: Option<ObjectId>,
+    pub referenced_message_id: Option<ObjectId>,
+    #[serde(default)]
+    pub mentions: Mentions,
+    #[serde(default)]
+    pub attachments: Vec<MessageAttachment>,
+    #[serde(default)]
+    pub is_silent: bool,
+    pub send_at: DateTime,
+    pub created_at: DateTime,
+}
+
+impl ScheduledMessage {
+    pub const COLLECTION: &'static str = "scheduled_messages";
+}
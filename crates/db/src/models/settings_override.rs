@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Operator overrides of the runtime-tunable settings, set with
+/// `PUT /api/admin/settings`. There is one document for the deployment, so
+/// every pod applies the same values on top of its config files.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsOverrides {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// Overridden values by dotted key (`turn.url`, `features.x`). Stored as
+    /// a list because the keys contain dots.
+    #[serde(default)]
+    pub values: Vec<SettingOverride>,
+    pub updated_by: Option<ObjectId>,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SettingOverride {
+    pub key: String,
+    pub value: serde_json::Value,
+}
+
+impl SettingsOverrides {
+    pub const COLLECTION: &'static str = "settings_overrides";
+
+    /// The overridden values by key.
+    pub fn to_map(&self) -> BTreeMap<String, serde_json::Value> {
+        self.values
+            .iter()
+            .map(|o| (o.key.clone(), o.value.clone()))
+            .collect()
+    }
+}
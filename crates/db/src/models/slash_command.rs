@@ -0,0 +1,24 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A tenant-registered `/command`. Invocations are forwarded, signed with
+/// `secret`, to `url`, and the reply is shown to the caller or posted to the
+/// room.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlashCommand {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    /// Lowercase name without the leading `/`, unique per tenant.
+    pub command: String,
+    pub description: Option<String>,
+    pub url: String,
+    pub secret: String,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl SlashCommand {
+    pub const COLLECTION: &'static str = "slash_commands";
+}
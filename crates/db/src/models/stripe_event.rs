@@ -0,0 +1,42 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// A verified Stripe webhook event, kept for deduplication and processed
+/// from this collection by the billing worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StripeEventRecord {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    /// Stripe's `evt_...` id; unique.
+    pub event_id: String,
+    pub event_type: String,
+    /// When Stripe created the event.
+    pub event_created: DateTime,
+    /// The raw event body.
+    pub payload: String,
+    #[serde(default)]
+    pub status: StripeEventStatus,
+    #[serde(default)]
+    pub attempts: u32,
+    /// Earliest time of the next processing attempt; a claimed event is
+    /// leased by pushing this forward.
+    pub next_attempt_at: DateTime,
+    pub last_error: Option<String>,
+    pub processed_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StripeEventStatus {
+    #[default]
+    Pending,
+    Processed,
+    /// Gave up after the last retry.
+    Failed,
+}
+
+impl StripeEventRecord {
+    pub const COLLECTION: &'static str = "stripe_events";
+}
@@ -17,6 +17,10 @@ pub struct Tenant {
     pub integrations: Option<IntegrationSettings>,
     #[serde(default)]
     pub is_archived: bool,
+    /// Bytes of the tenant's live files, kept up to date on upload and
+    /// delete and reconciled by the hourly storage meter.
+    #[serde(default)]
+    pub storage_used_bytes: u64,
     pub created_at: DateTime,
     pub updated_at: DateTime,
     pub deleted_at: Option<DateTime>,
@@ -57,6 +61,25 @@ pub struct TenantSettings {
     /// existing system resolvers as the fallback.
     #[serde(default)]
     pub magic_dns_nameservers: Vec<String>,
+    #[serde(default)]
+    pub retention: RetentionPolicy,
+    /// File storage quota in bytes; `None` uses `storage.default_quota_bytes`.
+    #[serde(default)]
+    pub storage_quota_bytes: Option<u64>,
+}
+
+/// How long the tenant keeps content, in days; `None` keeps it forever.
+/// Pinned messages and everything in a room under legal hold are exempt.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct RetentionPolicy {
+    pub message_days: Option<u32>,
+    pub recording_days: Option<u32>,
+    /// In-call chat (`call_chat_messages`).
+    pub transcript_days: Option<u32>,
+    /// Days messages stay in the hot collection before moving to the
+    /// archive; `None` uses `archive.message_days`. Not a deletion limit.
+    #[serde(default)]
+    pub archive_days: Option<u32>,
 }
 
 impl Default for TenantSettings {
@@ -70,6 +93,8 @@ impl Default for TenantSettings {
             file_upload_limit: default_file_upload_limit(),
             magic_dns_domain: None,
             magic_dns_nameservers: Vec::new(),
+            retention: RetentionPolicy::default(),
+            storage_quota_bytes: None,
         }
     }
 }
@@ -86,7 +111,7 @@ fn default_file_upload_limit() -> u64 {
     10 * 1024 * 1024 // 10 MB
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum NotificationLevel {
     #[default]
@@ -104,6 +129,14 @@ pub struct BillingInfo {
     pub status: SubscriptionStatus,
     #[serde(default)]
     pub cancel_at_period_end: bool,
+    /// Per-tenant overrides of the plan's rate limits, taken from the
+    /// subscription's Stripe price metadata.
+    #[serde(default)]
+    pub rate_limits: RateLimitOverrides,
+    /// `created` of the last Stripe event applied; older events arriving
+    /// late are ignored.
+    #[serde(default)]
+    pub synced_at: Option<DateTime>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -133,6 +166,15 @@ pub struct OAuthCredential {
 
 impl Tenant {
     pub const COLLECTION: &'static str = "tenants";
+
+    /// Effective rate limits: the plan's, with any billing overrides applied.
+    pub fn rate_limits(&self) -> RateLimits {
+        let base = self.plan.rate_limits();
+        match &self.billing {
+            Some(billing) => base.with_overrides(&billing.rate_limits),
+            None => base,
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -145,6 +187,35 @@ pub struct PlanLimits {
     pub cloud_integrations: bool,
     pub ai_recognition: bool,
     pub recordings: bool,
+    /// Total minutes of stored recordings.
+    pub recording_minutes: u64,
+    pub rate_limits: RateLimits,
+}
+
+/// Request rates the API allows each member of a tenant.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RateLimits {
+    pub messages_per_min: u32,
+    pub uploads_per_min: u32,
+}
+
+/// Stripe price metadata `rate_limit_messages_per_min` /
+/// `rate_limit_uploads_per_min`; unset fields keep the plan default.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub struct RateLimitOverrides {
+    #[serde(default)]
+    pub messages_per_min: Option<u32>,
+    #[serde(default)]
+    pub uploads_per_min: Option<u32>,
+}
+
+impl RateLimits {
+    pub fn with_overrides(self, overrides: &RateLimitOverrides) -> Self {
+        Self {
+            messages_per_min: overrides.messages_per_min.unwrap_or(self.messages_per_min),
+            uploads_per_min: overrides.uploads_per_min.unwrap_or(self.uploads_per_min),
+        }
+    }
 }
 
 impl Plan {
@@ -159,6 +230,8 @@ impl Plan {
                 cloud_integrations: false,
                 ai_recognition: false,
                 recordings: false,
+                recording_minutes: 0,
+                rate_limits: self.rate_limits(),
             },
             Plan::Pro => PlanLimits {
                 max_members: u32::MAX,
@@ -169,6 +242,8 @@ impl Plan {
                 cloud_integrations: true,
                 ai_recognition: false,
                 recordings: false,
+                recording_minutes: 0,
+                rate_limits: self.rate_limits(),
             },
             Plan::Business | Plan::Enterprise => PlanLimits {
                 max_members: u32::MAX,
@@ -179,6 +254,25 @@ impl Plan {
                 cloud_integrations: true,
                 ai_recognition: true,
                 recordings: true,
+                recording_minutes: 3_000,
+                rate_limits: self.rate_limits(),
+            },
+        }
+    }
+
+    pub fn rate_limits(&self) -> RateLimits {
+        match self {
+            Plan::Free => RateLimits {
+                messages_per_min: 60,
+                uploads_per_min: 10,
+            },
+            Plan::Pro => RateLimits {
+                messages_per_min: 300,
+                uploads_per_min: 60,
+            },
+            Plan::Business | Plan::Enterprise => RateLimits {
+                messages_per_min: 1200,
+                uploads_per_min: 240,
             },
         }
     }
@@ -0,0 +1,39 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// An email domain claimed by a tenant. Once a DNS TXT record proves the
+/// tenant controls it, users with a verified address at the domain can join
+/// without an invite.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantDomain {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    /// Lowercase, without a trailing dot.
+    pub domain: String,
+    /// Expected in the `_roomler-verify.<domain>` TXT record.
+    pub verification_token: String,
+    pub verified_at: Option<DateTime>,
+    #[serde(default)]
+    pub join_mode: DomainJoinMode,
+    /// Role granted on joining through the domain; `None` is the `member`
+    /// role.
+    pub role_id: Option<ObjectId>,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DomainJoinMode {
+    /// Listed to matching users, who join themselves.
+    #[default]
+    Offer,
+    /// Matching users are added as soon as their email is verified.
+    Auto,
+}
+
+impl TenantDomain {
+    pub const COLLECTION: &'static str = "tenant_domains";
+}
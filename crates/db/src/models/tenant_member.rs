@@ -20,10 +20,52 @@ pub struct TenantMember {
     pub notification_override: Option<NotificationLevel>,
     pub invited_by: Option<ObjectId>,
     pub last_seen_at: Option<DateTime>,
+    /// Lowercased name, username and email prefixes for member search; see
+    /// [`TenantMember::search_keys`]. Kept in step with the user.
+    #[serde(default)]
+    pub search_keys: Vec<String>,
     pub created_at: DateTime,
     pub updated_at: DateTime,
 }
 
 impl TenantMember {
     pub const COLLECTION: &'static str = "tenant_members";
+
+    /// What a member search prefix-matches against: the display name, each
+    /// word of it (so "jov" finds "Goran Jovanov"), the username and the
+    /// email, all lowercased.
+    pub fn search_keys(display_name: &str, username: &str, email: &str) -> Vec<String> {
+        let name = display_name.trim().to_lowercase();
+        let mut keys: Vec<String> = Vec::new();
+        for key in std::iter::once(name.as_str())
+            .chain(name.split_whitespace())
+            .chain([username, email])
+        {
+            let key = key.to_lowercase();
+            if !key.is_empty() && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TenantMember;
+
+    #[test]
+    fn search_keys_cover_name_words_username_and_email() {
+        assert_eq!(
+            TenantMember::search_keys(" Goran Jovanov ", "GJovanov", "goran@Example.com"),
+            vec![
+                "goran jovanov",
+                "goran",
+                "jovanov",
+                "gjovanov",
+                "goran@example.com"
+            ]
+        );
+        assert_eq!(TenantMember::search_keys("Ana", "ana", ""), vec!["ana"]);
+    }
 }
@@ -0,0 +1,32 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Billable usage of one tenant on one UTC day. Counters only grow during
+/// the day; `storage_bytes` is the largest footprint measured that day.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageDay {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    /// `YYYY-MM-DD`, UTC.
+    pub date: String,
+    /// Participant-seconds in calls: each participant counts separately.
+    #[serde(default)]
+    pub call_secs: u64,
+    #[serde(default)]
+    pub transcription_secs: u64,
+    /// Seconds of media published into calls (camera, microphone, screen).
+    #[serde(default)]
+    pub streamed_secs: u64,
+    #[serde(default)]
+    pub storage_bytes: u64,
+    /// When the day was reported to Stripe; reported days are final.
+    #[serde(default)]
+    pub reported_at: Option<DateTime>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl UsageDay {
+    pub const COLLECTION: &'static str = "usage_days";
+}
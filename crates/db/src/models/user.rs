@@ -9,7 +9,13 @@ pub struct User {
     pub username: String,
     pub display_name: String,
     pub avatar: Option<String>,
+    /// Set while `avatar` is an uploaded picture, served from
+    /// `/api/user/{id}/avatar/{avatar_key}`.
+    #[serde(default)]
+    pub avatar_key: Option<String>,
     pub bio: Option<String>,
+    #[serde(default)]
+    pub pronouns: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password_hash: Option<String>,
     #[serde(default)]
@@ -24,6 +30,9 @@ pub struct User {
     pub is_verified: bool,
     #[serde(default)]
     pub is_mfa_enabled: bool,
+    /// Tenant bot account: no password, authenticates with bot tokens only.
+    #[serde(default)]
+    pub is_bot: bool,
     pub last_active_at: Option<DateTime>,
     #[serde(default)]
     pub oauth_providers: Vec<OAuthProvider>,
@@ -41,6 +50,13 @@ pub struct UserStatusInfo {
     pub expires_at: Option<DateTime>,
 }
 
+impl UserStatusInfo {
+    /// Has a text or emoji that hasn't expired by `now`.
+    pub fn is_active(&self, now: DateTime) -> bool {
+        (self.text.is_some() || self.emoji.is_some()) && self.expires_at.is_none_or(|at| at > now)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Presence {
@@ -70,6 +86,19 @@ pub struct NotificationPrefs {
     pub desktop: bool,
     #[serde(default)]
     pub mute_all: bool,
+    /// No push or email while it's quiet; in-app notifications still arrive.
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+/// A daily window, `start` to `end` (`HH:MM`, wrapping past midnight when
+/// `end` is earlier), in the user's local time at `utc_offset_minutes`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+    #[serde(default)]
+    pub utc_offset_minutes: i32,
 }
 
 impl Default for NotificationPrefs {
@@ -79,6 +108,7 @@ impl Default for NotificationPrefs {
             push: true,
             desktop: true,
             mute_all: false,
+            quiet_hours: None,
         }
     }
 }
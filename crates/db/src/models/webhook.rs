@@ -0,0 +1,66 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// Events an outgoing webhook can subscribe to.
+pub const WEBHOOK_EVENTS: &[&str] = &[
+    "message.create",
+    "call.started",
+    "call.ended",
+    "recording.ready",
+    "recording.transcript",
+];
+
+/// A tenant-configured webhook. Outgoing webhooks receive signed events
+/// (see [`WEBHOOK_EVENTS`]); incoming webhooks accept posts into one room.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub name: String,
+    pub kind: WebhookKind,
+    /// Outgoing: rooms whose messages are sent (empty = every room).
+    /// Incoming: the single room posts go to.
+    #[serde(default)]
+    pub room_ids: Vec<ObjectId>,
+    /// Outgoing only: send a message only if it contains one of these,
+    /// case-insensitively (empty = every message).
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// Outgoing only: the events sent (empty = `message.create` only, as
+    /// before events could be picked).
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Outgoing only: where events are POSTed.
+    pub url: Option<String>,
+    /// Outgoing: HMAC-SHA256 signing key. Incoming: the token in the hook URL.
+    pub secret: String,
+    #[serde(default = "default_true")]
+    pub is_active: bool,
+    pub created_by: ObjectId,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookKind {
+    Incoming,
+    Outgoing,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Webhook {
+    pub const COLLECTION: &'static str = "webhooks";
+
+    pub fn subscribes_to(&self, event: &str) -> bool {
+        if self.events.is_empty() {
+            event == "message.create"
+        } else {
+            self.events.iter().any(|e| e == event)
+        }
+    }
+}
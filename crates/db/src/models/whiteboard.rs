@@ -0,0 +1,26 @@
+use bson::{DateTime, oid::ObjectId};
+use serde::{Deserialize, Serialize};
+
+/// The persisted state of a room's in-call whiteboard. Live edits are applied
+/// in memory and written here as periodic snapshots; `seq` is the server
+/// sequence number of the last op included.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Whiteboard {
+    #[serde(rename = "_id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<ObjectId>,
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    #[serde(default)]
+    pub seq: i64,
+    /// Board elements in z-order, as sent by clients (each has a string `id`).
+    #[serde(default)]
+    pub elements: Vec<serde_json::Value>,
+    /// The most recent image export, attached to the room as a file.
+    pub export_file_id: Option<ObjectId>,
+    pub created_at: DateTime,
+    pub updated_at: DateTime,
+}
+
+impl Whiteboard {
+    pub const COLLECTION: &'static str = "whiteboards";
+}
@@ -0,0 +1,229 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use roomler_ai_config::AssistantSettings;
+
+use crate::RecognitionService;
+
+/// The language model behind the meeting assistant, picked from
+/// `AssistantSettings::provider`:
+///   - [`Claude`]: the Claude API, with the `claude.*` key and model.
+///   - [`HttpChat`]: an OpenAI-compatible `/v1/chat/completions` API.
+#[async_trait]
+pub trait AssistantModel: Send + Sync {
+    /// Name of the backend, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Answer a single-turn prompt.
+    async fn complete(&self, prompt: String) -> Result<String, String>;
+}
+
+/// The model `settings.provider` asks for; `None` when the assistant is off.
+pub fn from_settings(
+    settings: &AssistantSettings,
+    recognition: &RecognitionService,
+) -> Option<Arc<dyn AssistantModel>> {
+    let timeout = Duration::from_secs(settings.timeout_secs.max(1));
+    match settings.provider.as_str() {
+        "claude" if recognition.is_available() => Some(Arc::new(Claude {
+            recognition: recognition.clone(),
+            timeout,
+        })),
+        "http" if !settings.api_url.is_empty() => Some(Arc::new(HttpChat {
+            client: reqwest::Client::new(),
+            url: settings.api_url.clone(),
+            api_key: settings.api_key.clone(),
+            model: settings.model.clone(),
+            timeout,
+        })),
+        _ => None,
+    }
+}
+
+/// The Claude API, through the client document recognition uses.
+pub struct Claude {
+    recognition: RecognitionService,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl AssistantModel for Claude {
+    fn name(&self) -> &'static str {
+        "claude"
+    }
+
+    async fn complete(&self, prompt: String) -> Result<String, String> {
+        tokio::time::timeout(self.timeout, self.recognition.complete(prompt))
+            .await
+            .map_err(|_| "The assistant timed out".to_string())?
+    }
+}
+
+/// An OpenAI-compatible chat API: POSTs `{ model, messages }` with the
+/// prompt as the one user message and reads `choices[0].message.content`.
+pub struct HttpChat {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl AssistantModel for HttpChat {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn complete(&self, prompt: String) -> Result<String, String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "messages": [{ "role": "user", "content": prompt }],
+        });
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&body);
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| format!("Chat API request failed: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("Chat API error {}", resp.status()));
+        }
+        let reply: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse chat API response: {}", e))?;
+        reply["choices"][0]["message"]["content"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| "No text in chat API response".to_string())
+    }
+}
+
+/// What an `/assistant` message asks for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `/assistant ask <question>`: answer in the call chat.
+    Ask(String),
+    /// `/assistant say <question>`: answer in the call chat and speak the
+    /// answer into the call.
+    Say(String),
+}
+
+pub const USAGE: &str = "Use /assistant ask <question> or /assistant say <question>";
+
+/// Parse a call chat message. `None` when it isn't for the assistant,
+/// `Some(Err(USAGE))` when it is but can't be understood.
+pub fn parse_command(content: &str) -> Option<Result<Command, &'static str>> {
+    let rest = content.trim().strip_prefix("/assistant")?;
+    if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+        // Some other command, e.g. `/assistants`
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (verb, question) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    let question = question.trim().to_string();
+    let command = match verb.to_lowercase().as_str() {
+        _ if question.is_empty() => return Some(Err(USAGE)),
+        "ask" => Command::Ask(question),
+        "say" => Command::Say(question),
+        _ => return Some(Err(USAGE)),
+    };
+    Some(Ok(command))
+}
+
+/// One final line of the call's live transcript.
+#[derive(Debug, Clone)]
+pub struct Line {
+    pub speaker_name: String,
+    pub text: String,
+}
+
+/// The prompt for a question asked by `asker`, with the call's transcript
+/// so far as context.
+pub fn prompt(transcript: &[Line], asker: &str, question: &str) -> String {
+    let mut prompt = String::from(
+        "You are the meeting assistant in a video call. Answer the participant's \
+         question in a few sentences of plain text, using the call transcript \
+         below where it is relevant. If the transcript doesn't cover it, say so \
+         rather than guessing.\n\n<transcript>\n",
+    );
+    if transcript.is_empty() {
+        prompt.push_str("(nothing has been transcribed yet)\n");
+    }
+    for line in transcript {
+        prompt.push_str(&format!("{}: {}\n", line.speaker_name, line.text));
+    }
+    prompt.push_str(&format!(
+        "</transcript>\n\nQuestion from {}: {}",
+        asker, question
+    ));
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!(
+            parse_command("/assistant ask what did we decide?"),
+            Some(Ok(Command::Ask("what did we decide?".to_string())))
+        );
+        assert_eq!(
+            parse_command("  /assistant SAY  summarize  "),
+            Some(Ok(Command::Say("summarize".to_string())))
+        );
+        assert_eq!(parse_command("/assistant"), Some(Err(USAGE)));
+        assert_eq!(parse_command("/assistant ask"), Some(Err(USAGE)));
+        assert_eq!(parse_command("/assistant sing a song"), Some(Err(USAGE)));
+        assert_eq!(parse_command("/assistants ask x"), None);
+        assert_eq!(parse_command("hello /assistant ask x"), None);
+    }
+
+    #[test]
+    fn prompt_carries_the_transcript_and_question() {
+        let line = |speaker: &str, text: &str| Line {
+            speaker_name: speaker.to_string(),
+            text: text.to_string(),
+        };
+        let transcript = [line("Ana", "Let's ship on Friday"), line("Ben", "Agreed")];
+        let prompt = prompt(&transcript, "Cleo", "When do we ship?");
+        assert!(
+            prompt.contains("<transcript>\nAna: Let's ship on Friday\nBen: Agreed\n</transcript>")
+        );
+        assert!(prompt.ends_with("Question from Cleo: When do we ship?"));
+
+        assert!(super::prompt(&[], "Cleo", "Anything?").contains("nothing has been transcribed"));
+    }
+
+    #[test]
+    fn provider_selects_backend() {
+        let recognition = RecognitionService::new(None, "model".to_string(), 1024);
+        let mut settings = AssistantSettings::default();
+        assert!(from_settings(&settings, &recognition).is_none());
+        settings.provider = "claude".to_string();
+        assert!(from_settings(&settings, &recognition).is_none());
+        let recognition =
+            RecognitionService::new(Some("key".to_string()), "model".to_string(), 1024);
+        assert_eq!(
+            from_settings(&settings, &recognition).unwrap().name(),
+            "claude"
+        );
+        settings.provider = "http".to_string();
+        assert!(from_settings(&settings, &recognition).is_none());
+        settings.api_url = "http://llm.local/v1/chat/completions".to_string();
+        assert_eq!(
+            from_settings(&settings, &recognition).unwrap().name(),
+            "http"
+        );
+    }
+}
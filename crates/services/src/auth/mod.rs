@@ -47,6 +47,15 @@ pub enum TokenType {
     /// on its WebSocket connection (`role=tunnel-client`). Audience
     /// distinct from `Agent` — agents serve forwards, clients open them.
     TunnelClient,
+    /// Long-lived token issued to a tenant bot account. `jti` names the
+    /// `bot_tokens` row that holds its room scope and revocation state.
+    Bot,
+    /// Single-use, seconds-long ticket that opens a user's WebSocket in
+    /// place of the access token, so the token never lands in proxy logs.
+    WsTicket,
+    /// Minutes-long OAuth `state` of a calendar connection, naming the
+    /// user it is for.
+    CalendarState,
 }
 
 /// Claims carried by a remote-control enrollment token (aud = enroll).
@@ -107,6 +116,55 @@ pub struct TunnelEnrollmentClaims {
     pub jti: String,
 }
 
+/// Claims carried by a bot token. The room scope lives server-side in the
+/// `bot_tokens` row named by `jti`, so it can change without reissuing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BotClaims {
+    /// Bot user id hex.
+    pub sub: String,
+    pub tenant_id: String,
+    /// `bot_tokens._id` hex.
+    pub jti: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub token_type: TokenType,
+}
+
+/// Claims carried by a WebSocket ticket.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WsTicketClaims {
+    /// User id hex.
+    pub sub: String,
+    pub username: String,
+    /// Rooms (hex ids) the connection may signal media for; `None` allows
+    /// any room, like an access token.
+    #[serde(default)]
+    pub media_rooms: Option<Vec<String>>,
+    /// Unique id; the WS handler records it to refuse a second use.
+    pub jti: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub token_type: TokenType,
+}
+
+/// Claims carried by the OAuth `state` of a calendar connection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarStateClaims {
+    /// User id hex.
+    pub sub: String,
+    /// `google` or `microsoft`.
+    pub provider: String,
+    /// Also set as a cookie in the browser that asked, so the consent can't
+    /// be finished in another one.
+    pub nonce: String,
+    pub iat: i64,
+    pub exp: i64,
+    pub iss: String,
+    pub token_type: TokenType,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TokenPair {
     pub access_token: String,
@@ -388,6 +446,135 @@ impl AuthService {
         }
         Ok(data.claims)
     }
+
+    // ─── Bot tokens ───────────────────────────────────────────────────
+
+    /// Mint a bot token for the `bot_tokens` row `token_id`.
+    pub fn issue_bot_token(
+        &self,
+        bot_id: ObjectId,
+        tenant_id: ObjectId,
+        token_id: ObjectId,
+        ttl_secs: u64,
+    ) -> Result<String, AuthError> {
+        let now = Utc::now();
+        let claims = BotClaims {
+            sub: bot_id.to_hex(),
+            tenant_id: tenant_id.to_hex(),
+            jti: token_id.to_hex(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
+            iss: self.jwt_settings.issuer.clone(),
+            token_type: TokenType::Bot,
+        };
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    pub fn verify_bot_token(&self, token: &str) -> Result<BotClaims, AuthError> {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.jwt_settings.issuer]);
+        let data =
+            decode::<BotClaims>(token, &self.decoding_key, &validation).map_err(|e| {
+                match e.kind() {
+                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                    _ => AuthError::InvalidToken(e.to_string()),
+                }
+            })?;
+        if data.claims.token_type != TokenType::Bot {
+            return Err(AuthError::InvalidToken("Not a bot token".to_string()));
+        }
+        Ok(data.claims)
+    }
+
+    // ─── WebSocket tickets ────────────────────────────────────────────
+
+    /// Mint a WebSocket ticket for a user, optionally limited to media
+    /// signaling in `media_rooms`.
+    pub fn issue_ws_ticket(
+        &self,
+        user_id: ObjectId,
+        username: &str,
+        media_rooms: Option<&[ObjectId]>,
+        ttl_secs: u64,
+    ) -> Result<String, AuthError> {
+        let now = Utc::now();
+        let claims = WsTicketClaims {
+            sub: user_id.to_hex(),
+            username: username.to_string(),
+            media_rooms: media_rooms.map(|ids| ids.iter().map(|id| id.to_hex()).collect()),
+            jti: uuid_v4_hex(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
+            iss: self.jwt_settings.issuer.clone(),
+            token_type: TokenType::WsTicket,
+        };
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    /// Check a ticket's signature and expiry. Single use is up to the
+    /// caller, by `jti`.
+    pub fn verify_ws_ticket(&self, ticket: &str) -> Result<WsTicketClaims, AuthError> {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.jwt_settings.issuer]);
+        // Tickets live for seconds; the default minute of leeway would
+        // multiply that.
+        validation.leeway = 0;
+        let data = decode::<WsTicketClaims>(ticket, &self.decoding_key, &validation).map_err(
+            |e| match e.kind() {
+                jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                _ => AuthError::InvalidToken(e.to_string()),
+            },
+        )?;
+        if data.claims.token_type != TokenType::WsTicket {
+            return Err(AuthError::InvalidToken(
+                "Not a WebSocket ticket".to_string(),
+            ));
+        }
+        Ok(data.claims)
+    }
+
+    // ─── Calendar connections ─────────────────────────────────────────
+
+    /// Mint the OAuth `state` for connecting `user_id`'s calendar, so the
+    /// callback knows whose it is.
+    pub fn issue_calendar_state(
+        &self,
+        user_id: ObjectId,
+        provider: &str,
+        nonce: &str,
+        ttl_secs: u64,
+    ) -> Result<String, AuthError> {
+        let now = Utc::now();
+        let claims = CalendarStateClaims {
+            sub: user_id.to_hex(),
+            provider: provider.to_string(),
+            nonce: nonce.to_string(),
+            iat: now.timestamp(),
+            exp: (now + Duration::seconds(ttl_secs as i64)).timestamp(),
+            iss: self.jwt_settings.issuer.clone(),
+            token_type: TokenType::CalendarState,
+        };
+        encode(&Header::default(), &claims, &self.encoding_key)
+            .map_err(|e| AuthError::InvalidToken(e.to_string()))
+    }
+
+    pub fn verify_calendar_state(&self, state: &str) -> Result<CalendarStateClaims, AuthError> {
+        let mut validation = Validation::default();
+        validation.set_issuer(&[&self.jwt_settings.issuer]);
+        let data =
+            decode::<CalendarStateClaims>(state, &self.decoding_key, &validation).map_err(|e| {
+                match e.kind() {
+                    jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
+                    _ => AuthError::InvalidToken(e.to_string()),
+                }
+            })?;
+        if data.claims.token_type != TokenType::CalendarState {
+            return Err(AuthError::InvalidToken("Not a calendar state".to_string()));
+        }
+        Ok(data.claims)
+    }
 }
 
 fn uuid_v4_hex() -> String {
@@ -646,4 +833,68 @@ mod tests {
         let err = s.verify_access_token(&t).unwrap_err();
         assert!(matches!(err, AuthError::InvalidToken(_)));
     }
+
+    #[test]
+    fn bot_token_round_trips_and_is_not_an_access_token() {
+        let s = svc();
+        let (bot, token_id) = (ObjectId::new(), ObjectId::new());
+        let t = s
+            .issue_bot_token(bot, ObjectId::new(), token_id, 60)
+            .unwrap();
+        let claims = s.verify_bot_token(&t).unwrap();
+        assert_eq!(claims.sub, bot.to_hex());
+        assert_eq!(claims.jti, token_id.to_hex());
+        assert!(matches!(
+            s.verify_access_token(&t).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+        let t = s
+            .issue_agent_token(ObjectId::new(), ObjectId::new(), Some(60))
+            .unwrap();
+        assert!(matches!(
+            s.verify_bot_token(&t).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+    }
+
+    #[test]
+    fn ws_ticket_round_trips_and_is_not_an_access_token() {
+        let s = svc();
+        let (user, room) = (ObjectId::new(), ObjectId::new());
+        let t = s.issue_ws_ticket(user, "alice", Some(&[room]), 30).unwrap();
+        let claims = s.verify_ws_ticket(&t).unwrap();
+        assert_eq!(claims.sub, user.to_hex());
+        assert_eq!(claims.media_rooms, Some(vec![room.to_hex()]));
+        assert!(matches!(
+            s.verify_access_token(&t).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+        let pair = s.generate_tokens(user, "a@b.c", "alice").unwrap();
+        assert!(matches!(
+            s.verify_ws_ticket(&pair.access_token).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+    }
+
+    #[test]
+    fn calendar_state_round_trips_and_is_not_an_access_token() {
+        let s = svc();
+        let user = ObjectId::new();
+        let state = s
+            .issue_calendar_state(user, "google", "n0nce", 600)
+            .unwrap();
+        let claims = s.verify_calendar_state(&state).unwrap();
+        assert_eq!(claims.sub, user.to_hex());
+        assert_eq!(claims.provider, "google");
+        assert_eq!(claims.nonce, "n0nce");
+        assert!(matches!(
+            s.verify_access_token(&state).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+        let pair = s.generate_tokens(user, "a@b.c", "alice").unwrap();
+        assert!(matches!(
+            s.verify_calendar_state(&pair.access_token).unwrap_err(),
+            AuthError::InvalidToken(_)
+        ));
+    }
 }
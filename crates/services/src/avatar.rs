@@ -0,0 +1,145 @@
+//! Profile pictures. The upload is decoded, cut to a square and scaled to
+//! [`AVATAR_SIZE`], then re-encoded as PNG, so what is served never carries
+//! the original's metadata.
+
+use std::io::Cursor;
+
+use image::{DynamicImage, ImageFormat, ImageReader, Limits, imageops::FilterType};
+
+/// Width and height of a stored avatar.
+pub const AVATAR_SIZE: u32 = 256;
+
+/// Larger sources are refused before decoding.
+const MAX_SOURCE_SIDE: u32 = 8192;
+
+/// A square of the source image, in pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Crop {
+    pub x: u32,
+    pub y: u32,
+    pub size: u32,
+}
+
+/// The square actually cut from a `width` x `height` image: the requested
+/// one moved and shrunk to fit, or the largest centered square.
+pub fn crop_rect(width: u32, height: u32, crop: Option<Crop>) -> Crop {
+    let side = width.min(height);
+    match crop {
+        Some(c) => {
+            let size = c.size.clamp(1, side);
+            Crop {
+                x: c.x.min(width - size),
+                y: c.y.min(height - size),
+                size,
+            }
+        }
+        None => Crop {
+            x: (width - side) / 2,
+            y: (height - side) / 2,
+            size: side,
+        },
+    }
+}
+
+/// Decode `bytes` (PNG, JPEG or WebP), cut the square and return the avatar
+/// as PNG.
+pub fn render(bytes: &[u8], crop: Option<Crop>) -> Result<Vec<u8>, String> {
+    let mut reader = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()
+        .map_err(|e| format!("Unreadable image: {e}"))?;
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_SOURCE_SIDE);
+    limits.max_image_height = Some(MAX_SOURCE_SIDE);
+    reader.limits(limits);
+    let source = reader
+        .decode()
+        .map_err(|e| format!("Unreadable image: {e}"))?;
+    if source.width() == 0 || source.height() == 0 {
+        return Err("Empty image".to_string());
+    }
+
+    let r = crop_rect(source.width(), source.height(), crop);
+    let avatar = source
+        .crop_imm(r.x, r.y, r.size, r.size)
+        .resize_exact(AVATAR_SIZE, AVATAR_SIZE, FilterType::Lanczos3)
+        .to_rgba8();
+
+    let mut out = Cursor::new(Vec::new());
+    DynamicImage::ImageRgba8(avatar)
+        .write_to(&mut out, ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode avatar: {e}"))?;
+    Ok(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_crop_is_the_centered_square() {
+        let c = crop_rect(300, 200, None);
+        assert_eq!(
+            c,
+            Crop {
+                x: 50,
+                y: 0,
+                size: 200
+            }
+        );
+    }
+
+    #[test]
+    fn requested_crop_is_kept_inside_the_image() {
+        let c = crop_rect(
+            300,
+            200,
+            Some(Crop {
+                x: 250,
+                y: 150,
+                size: 500,
+            }),
+        );
+        assert_eq!(
+            c,
+            Crop {
+                x: 100,
+                y: 0,
+                size: 200
+            }
+        );
+        let c = crop_rect(
+            300,
+            200,
+            Some(Crop {
+                x: 10,
+                y: 20,
+                size: 0,
+            }),
+        );
+        assert_eq!(
+            c,
+            Crop {
+                x: 10,
+                y: 20,
+                size: 1
+            }
+        );
+    }
+
+    #[test]
+    fn renders_a_square_png() {
+        let mut source = Cursor::new(Vec::new());
+        DynamicImage::new_rgb8(300, 120)
+            .write_to(&mut source, ImageFormat::Jpeg)
+            .unwrap();
+        let png = render(source.get_ref(), None).unwrap();
+        let avatar = image::load_from_memory(&png).unwrap();
+        assert_eq!(
+            (avatar.width(), avatar.height()),
+            (AVATAR_SIZE, AVATAR_SIZE)
+        );
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+
+        assert!(render(b"not an image", None).is_err());
+    }
+}
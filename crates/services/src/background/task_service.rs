@@ -3,7 +3,7 @@ use mongodb::Database;
 use roomler_ai_db::models::{BackgroundTask, TaskCategory, TaskStatus};
 use std::sync::Arc;
 
-use crate::dao::base::{DaoResult, PaginatedResult, PaginationParams};
+use crate::dao::base::{DaoResult, ListOptions, PaginatedResult, PaginationParams};
 
 use super::task_store::TaskStore;
 
@@ -65,13 +65,15 @@ impl TaskService {
         tenant_id: ObjectId,
         user_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<BackgroundTask>> {
         self.store
             .db_dao
-            .find_paginated(
+            .find_listed(
                 doc! { "tenant_id": tenant_id, "user_id": user_id },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
             )
             .await
     }
@@ -14,7 +14,7 @@ pub struct TaskStore {
 impl TaskStore {
     pub fn new(db: &Database) -> Self {
         Self {
-            db_dao: BaseDao::new(db, BackgroundTask::COLLECTION),
+            db_dao: BaseDao::new(db, BackgroundTask::COLLECTION).tenant_scoped(),
             cache: DashMap::new(),
         }
     }
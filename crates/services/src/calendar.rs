@@ -0,0 +1,536 @@
+use std::time::Duration;
+
+use bson::DateTime;
+use chrono::{SecondsFormat, Utc};
+use roomler_ai_config::{CalendarSettings, OAuthSettings};
+use roomler_ai_db::models::{CalendarConnection, InviteeResponse};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::dao::{base::DaoError, calendar_connection::CalendarConnectionDao};
+
+/// Calendar providers a user can connect, in display order.
+pub const PROVIDERS: [&str; 2] = ["google", "microsoft"];
+
+const GOOGLE_AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
+const GOOGLE_SCOPES: &str = "openid email https://www.googleapis.com/auth/calendar.events";
+const MICROSOFT_AUTH_URL: &str = "https://login.microsoftonline.com/common/oauth2/v2.0/authorize";
+const MICROSOFT_SCOPES: &str = "offline_access User.Read Calendars.ReadWrite";
+
+/// An access token this close to expiry is refreshed before use.
+const REFRESH_MARGIN_MS: i64 = 60_000;
+
+const TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Error)]
+pub enum CalendarError {
+    #[error("Calendar provider not configured: {0}")]
+    NotConfigured(String),
+    #[error("Unknown calendar provider: {0}")]
+    UnknownProvider(String),
+    #[error("The calendar was disconnected")]
+    Disconnected,
+    #[error("Token request failed: {0}")]
+    Token(String),
+    #[error("Calendar API request failed: {0}")]
+    Api(String),
+    #[error(transparent)]
+    Dao(#[from] DaoError),
+}
+
+/// Tokens from an authorization code or a refresh.
+#[derive(Debug, Clone)]
+pub struct Tokens {
+    pub access_token: String,
+    /// Not every refresh issues a new one.
+    pub refresh_token: Option<String>,
+    pub expires_at: DateTime,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+}
+
+/// A scheduled call as a calendar event.
+#[derive(Debug, Clone)]
+pub struct EventDetails {
+    pub title: String,
+    pub description: String,
+    /// The call's join URL.
+    pub location: String,
+    pub start: chrono::DateTime<Utc>,
+    pub end: chrono::DateTime<Utc>,
+    /// IANA zone the organizer scheduled in, for how calendars show it.
+    pub timezone: Option<String>,
+    pub attendees: Vec<String>,
+}
+
+/// Calendar connectors for Google Calendar and Microsoft Outlook (Graph).
+/// Users connect their calendar with OAuth (offline access, so the server
+/// can refresh the token); scheduled calls they organize are then written
+/// there as events with the join URL and dial-in, and the attendees'
+/// responses are read back.
+///
+/// `from_settings` returns `None` when neither `oauth.google` nor
+/// `oauth.microsoft` has a client id.
+pub struct CalendarService {
+    client: reqwest::Client,
+    settings: CalendarSettings,
+    oauth: OAuthSettings,
+}
+
+impl CalendarService {
+    pub fn from_settings(settings: &CalendarSettings, oauth: &OAuthSettings) -> Option<Self> {
+        let service = Self {
+            client: reqwest::Client::new(),
+            settings: settings.clone(),
+            oauth: oauth.clone(),
+        };
+        (!service.providers().is_empty()).then_some(service)
+    }
+
+    /// Providers with an OAuth client configured.
+    pub fn providers(&self) -> Vec<&'static str> {
+        PROVIDERS
+            .into_iter()
+            .filter(|p| self.client_credentials(p).is_ok())
+            .collect()
+    }
+
+    fn client_credentials(&self, provider: &str) -> Result<(&str, &str), CalendarError> {
+        let client = match provider {
+            "google" => &self.oauth.google,
+            "microsoft" => &self.oauth.microsoft,
+            _ => return Err(CalendarError::UnknownProvider(provider.to_string())),
+        };
+        if client.client_id.is_empty() {
+            return Err(CalendarError::NotConfigured(provider.to_string()));
+        }
+        Ok((&client.client_id, &client.client_secret))
+    }
+
+    fn callback_url(&self, provider: &str) -> String {
+        format!("{}/api/calendar/callback/{}", self.oauth.base_url, provider)
+    }
+
+    fn token_url(&self, provider: &str) -> &str {
+        match provider {
+            "google" => &self.settings.google_token_url,
+            _ => &self.settings.microsoft_token_url,
+        }
+    }
+
+    /// The provider's consent screen, asking for calendar access.
+    pub fn auth_url(&self, provider: &str, state: &str) -> Result<String, CalendarError> {
+        let (client_id, _) = self.client_credentials(provider)?;
+        let redirect_uri = self.callback_url(provider);
+        let url = match provider {
+            "google" => format!(
+                "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent&state={}",
+                GOOGLE_AUTH_URL,
+                urlencoding::encode(client_id),
+                urlencoding::encode(&redirect_uri),
+                urlencoding::encode(GOOGLE_SCOPES),
+                urlencoding::encode(state)
+            ),
+            _ => format!(
+                "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&state={}",
+                MICROSOFT_AUTH_URL,
+                urlencoding::encode(client_id),
+                urlencoding::encode(&redirect_uri),
+                urlencoding::encode(MICROSOFT_SCOPES),
+                urlencoding::encode(state)
+            ),
+        };
+        Ok(url)
+    }
+
+    /// Exchange the callback's code for tokens.
+    pub async fn exchange_code(&self, provider: &str, code: &str) -> Result<Tokens, CalendarError> {
+        let redirect_uri = self.callback_url(provider);
+        self.token_request(
+            provider,
+            &[
+                ("grant_type", "authorization_code"),
+                ("code", code),
+                ("redirect_uri", &redirect_uri),
+            ],
+        )
+        .await
+    }
+
+    async fn refresh(&self, provider: &str, refresh_token: &str) -> Result<Tokens, CalendarError> {
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+        ];
+        if provider == "microsoft" {
+            form.push(("scope", MICROSOFT_SCOPES));
+        }
+        self.token_request(provider, &form).await
+    }
+
+    async fn token_request(
+        &self,
+        provider: &str,
+        form: &[(&str, &str)],
+    ) -> Result<Tokens, CalendarError> {
+        let (client_id, client_secret) = self.client_credentials(provider)?;
+        let mut form = form.to_vec();
+        form.push(("client_id", client_id));
+        form.push(("client_secret", client_secret));
+        let resp = self
+            .client
+            .post(self.token_url(provider))
+            .timeout(TIMEOUT)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|e| CalendarError::Token(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(CalendarError::Token(format!("status {}", resp.status())));
+        }
+        let body: TokenResponse = resp
+            .json()
+            .await
+            .map_err(|e| CalendarError::Token(e.to_string()))?;
+        let expires_in = body.expires_in.unwrap_or(3600);
+        Ok(Tokens {
+            access_token: body.access_token,
+            refresh_token: body.refresh_token,
+            expires_at: DateTime::from_millis(
+                DateTime::now().timestamp_millis() + expires_in * 1000,
+            ),
+        })
+    }
+
+    /// A usable access token for the connection, refreshed (and stored)
+    /// when it is about to expire.
+    pub async fn access_token(
+        &self,
+        connections: &CalendarConnectionDao,
+        connection: &CalendarConnection,
+    ) -> Result<String, CalendarError> {
+        let now = DateTime::now().timestamp_millis();
+        if connection.expires_at.timestamp_millis() - now > REFRESH_MARGIN_MS {
+            return Ok(connection.access_token.clone());
+        }
+        let tokens = self
+            .refresh(&connection.provider, &connection.refresh_token)
+            .await?;
+        if let Some(id) = connection.id {
+            connections
+                .set_tokens(
+                    id,
+                    &tokens.access_token,
+                    tokens.refresh_token.as_deref(),
+                    tokens.expires_at,
+                )
+                .await?;
+        }
+        Ok(tokens.access_token)
+    }
+
+    /// The email of the account the token belongs to.
+    pub async fn account_email(
+        &self,
+        provider: &str,
+        access_token: &str,
+    ) -> Result<String, CalendarError> {
+        let url = match provider {
+            "google" => format!("{}/oauth2/v2/userinfo", self.settings.google_api_url),
+            _ => format!("{}/me", self.settings.microsoft_api_url),
+        };
+        let body = self
+            .send(self.client.get(url), access_token)
+            .await?
+            .unwrap_or_default();
+        ["email", "mail", "userPrincipalName"]
+            .iter()
+            .find_map(|k| body[k].as_str().filter(|e| !e.is_empty()))
+            .map(str::to_string)
+            .ok_or_else(|| CalendarError::Api("No email for the calendar account".to_string()))
+    }
+
+    fn events_url(&self, provider: &str) -> String {
+        match provider {
+            "google" => format!(
+                "{}/calendar/v3/calendars/primary/events",
+                self.settings.google_api_url
+            ),
+            _ => format!("{}/me/events", self.settings.microsoft_api_url),
+        }
+    }
+
+    /// Create the event and invite its attendees; returns its id.
+    pub async fn create_event(
+        &self,
+        provider: &str,
+        access_token: &str,
+        event: &EventDetails,
+    ) -> Result<String, CalendarError> {
+        let request = self
+            .client
+            .post(self.events_url(provider))
+            .query(&send_updates(provider))
+            .json(&event_body(provider, event));
+        let body = self.send(request, access_token).await?.unwrap_or_default();
+        body["id"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| CalendarError::Api("No id for the created event".to_string()))
+    }
+
+    /// Replace the event's time, details and attendees.
+    pub async fn update_event(
+        &self,
+        provider: &str,
+        access_token: &str,
+        event_id: &str,
+        event: &EventDetails,
+    ) -> Result<(), CalendarError> {
+        let url = format!("{}/{}", self.events_url(provider), event_id);
+        let request = self
+            .client
+            .patch(url)
+            .query(&send_updates(provider))
+            .json(&event_body(provider, event));
+        self.send(request, access_token).await?;
+        Ok(())
+    }
+
+    /// Cancel the event; one that is already gone counts as cancelled.
+    pub async fn delete_event(
+        &self,
+        provider: &str,
+        access_token: &str,
+        event_id: &str,
+    ) -> Result<(), CalendarError> {
+        let url = format!("{}/{}", self.events_url(provider), event_id);
+        let request = self.client.delete(url).query(&send_updates(provider));
+        self.send(request, access_token).await?;
+        Ok(())
+    }
+
+    /// The attendees' responses to the event, by lowercased email.
+    pub async fn attendee_responses(
+        &self,
+        provider: &str,
+        access_token: &str,
+        event_id: &str,
+    ) -> Result<Vec<(String, InviteeResponse)>, CalendarError> {
+        let url = format!("{}/{}", self.events_url(provider), event_id);
+        let body = self
+            .send(self.client.get(url), access_token)
+            .await?
+            .unwrap_or_default();
+        Ok(parse_responses(provider, &body))
+    }
+
+    /// Send an authorized request; `None` for a 404/410 (gone) or an empty
+    /// body.
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        access_token: &str,
+    ) -> Result<Option<serde_json::Value>, CalendarError> {
+        let resp = request
+            .bearer_auth(access_token)
+            .timeout(TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| CalendarError::Api(e.to_string()))?;
+        let status = resp.status();
+        if status == reqwest::StatusCode::NOT_FOUND || status == reqwest::StatusCode::GONE {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(CalendarError::Api(format!("status {}", status)));
+        }
+        let bytes = resp
+            .bytes()
+            .await
+            .map_err(|e| CalendarError::Api(e.to_string()))?;
+        if bytes.is_empty() {
+            return Ok(None);
+        }
+        serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| CalendarError::Api(e.to_string()))
+    }
+
+    /// The event's description: the join link and, when configured, the
+    /// dial-in number with the meeting code as its PIN.
+    pub fn description(&self, join_url: &str, meeting_code: Option<&str>) -> String {
+        let mut description = format!("Join the call: {}", join_url);
+        if let (false, Some(code)) = (self.settings.dial_in_number.is_empty(), meeting_code) {
+            description.push_str(&format!(
+                "\nDial in: {} (PIN {})",
+                self.settings.dial_in_number, code
+            ));
+        }
+        description
+    }
+}
+
+/// Google only emails attendees about changes when asked to.
+fn send_updates(provider: &str) -> Vec<(&'static str, &'static str)> {
+    match provider {
+        "google" => vec![("sendUpdates", "all")],
+        _ => Vec::new(),
+    }
+}
+
+fn event_body(provider: &str, event: &EventDetails) -> serde_json::Value {
+    match provider {
+        "google" => {
+            let time = |t: &chrono::DateTime<Utc>| {
+                let mut time = serde_json::json!({
+                    "dateTime": t.to_rfc3339_opts(SecondsFormat::Secs, true),
+                });
+                if let Some(tz) = &event.timezone {
+                    time["timeZone"] = tz.as_str().into();
+                }
+                time
+            };
+            serde_json::json!({
+                "summary": event.title,
+                "description": event.description,
+                "location": event.location,
+                "start": time(&event.start),
+                "end": time(&event.end),
+                "attendees": event
+                    .attendees
+                    .iter()
+                    .map(|email| serde_json::json!({ "email": email }))
+                    .collect::<Vec<_>>(),
+            })
+        }
+        _ => {
+            // Graph takes a zone-less local time with its zone; UTC keeps it
+            // independent of Windows zone names.
+            let time = |t: &chrono::DateTime<Utc>| {
+                serde_json::json!({
+                    "dateTime": t.format("%Y-%m-%dT%H:%M:%S").to_string(),
+                    "timeZone": "UTC",
+                })
+            };
+            serde_json::json!({
+                "subject": event.title,
+                "body": { "contentType": "text", "content": event.description },
+                "location": { "displayName": event.location },
+                "start": time(&event.start),
+                "end": time(&event.end),
+                "attendees": event
+                    .attendees
+                    .iter()
+                    .map(|email| serde_json::json!({
+                        "emailAddress": { "address": email },
+                        "type": "required",
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        }
+    }
+}
+
+fn parse_responses(provider: &str, event: &serde_json::Value) -> Vec<(String, InviteeResponse)> {
+    let Some(attendees) = event["attendees"].as_array() else {
+        return Vec::new();
+    };
+    attendees
+        .iter()
+        .filter_map(|a| {
+            let (email, status) = match provider {
+                "google" => (a["email"].as_str()?, a["responseStatus"].as_str()?),
+                _ => (
+                    a["emailAddress"]["address"].as_str()?,
+                    a["status"]["response"].as_str()?,
+                ),
+            };
+            let response = match status {
+                "accepted" | "organizer" => InviteeResponse::Accepted,
+                "tentative" | "tentativelyAccepted" => InviteeResponse::Tentative,
+                "declined" => InviteeResponse::Declined,
+                _ => InviteeResponse::NeedsAction,
+            };
+            Some((email.to_lowercase(), response))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn event() -> EventDetails {
+        EventDetails {
+            title: "Planning".to_string(),
+            description: "Join the call: https://app/join/abc".to_string(),
+            location: "https://app/join/abc".to_string(),
+            start: Utc.with_ymd_and_hms(2026, 11, 2, 15, 0, 0).unwrap(),
+            end: Utc.with_ymd_and_hms(2026, 11, 2, 15, 30, 0).unwrap(),
+            timezone: Some("Europe/Vienna".to_string()),
+            attendees: vec!["ana@example.com".to_string()],
+        }
+    }
+
+    #[test]
+    fn events_are_written_for_each_provider() {
+        let google = event_body("google", &event());
+        assert_eq!(google["summary"], "Planning");
+        assert_eq!(google["start"]["dateTime"], "2026-11-02T15:00:00Z");
+        assert_eq!(google["start"]["timeZone"], "Europe/Vienna");
+        assert_eq!(google["attendees"][0]["email"], "ana@example.com");
+
+        let microsoft = event_body("microsoft", &event());
+        assert_eq!(microsoft["subject"], "Planning");
+        assert_eq!(microsoft["end"]["dateTime"], "2026-11-02T15:30:00");
+        assert_eq!(microsoft["end"]["timeZone"], "UTC");
+        assert_eq!(
+            microsoft["attendees"][0]["emailAddress"]["address"],
+            "ana@example.com"
+        );
+        assert_eq!(microsoft["location"]["displayName"], "https://app/join/abc");
+    }
+
+    #[test]
+    fn responses_are_read_for_each_provider() {
+        let google = serde_json::json!({ "attendees": [
+            { "email": "Ana@Example.com", "responseStatus": "accepted" },
+            { "email": "ben@example.com", "responseStatus": "tentative" },
+            { "email": "cleo@example.com", "responseStatus": "needsAction" },
+        ]});
+        assert_eq!(
+            parse_responses("google", &google),
+            vec![
+                ("ana@example.com".to_string(), InviteeResponse::Accepted),
+                ("ben@example.com".to_string(), InviteeResponse::Tentative),
+                ("cleo@example.com".to_string(), InviteeResponse::NeedsAction),
+            ]
+        );
+
+        let microsoft = serde_json::json!({ "attendees": [
+            {
+                "emailAddress": { "address": "ana@example.com" },
+                "status": { "response": "declined" },
+            },
+            {
+                "emailAddress": { "address": "ben@example.com" },
+                "status": { "response": "tentativelyAccepted" },
+            },
+        ]});
+        assert_eq!(
+            parse_responses("microsoft", &microsoft),
+            vec![
+                ("ana@example.com".to_string(), InviteeResponse::Declined),
+                ("ben@example.com".to_string(), InviteeResponse::Tentative),
+            ]
+        );
+        assert!(parse_responses("google", &serde_json::json!({})).is_empty());
+    }
+}
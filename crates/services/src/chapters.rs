@@ -0,0 +1,262 @@
+//! Recording chapters from the transcript.
+//!
+//! With a Claude API key the transcript goes to the model, which picks the
+//! lines where the topic changes and titles each chapter ([`prompt`] and
+//! [`parse_reply`]). Without one, or when the reply is unusable,
+//! [`by_topic`] finds the shifts itself: it compares the words of the lines
+//! before and after each gap (TextTiling) and cuts where they share the
+//! least. Chapter starts are seconds into the recording; the first chapter
+//! always starts at 0.
+
+use std::collections::HashMap;
+
+use roomler_ai_db::models::recording::RecordingChapter;
+
+/// Shortest chapter, in seconds.
+const MIN_CHAPTER_SECS: f64 = 60.0;
+/// Lines compared on each side of a gap.
+const WINDOW: usize = 3;
+/// Words per chapter title.
+const TITLE_WORDS: usize = 3;
+/// Longest transcript sent to the model; longer ones use [`by_topic`].
+pub const MAX_PROMPT_CHARS: usize = 100_000;
+
+const STOPWORDS: &[&str] = &[
+    "about", "after", "again", "all", "also", "and", "any", "are", "back", "because", "been",
+    "but", "can", "could", "did", "does", "doing", "done", "for", "from", "get", "going", "got",
+    "had", "has", "have", "her", "here", "him", "his", "how", "into", "its", "just", "know", "let",
+    "like", "look", "make", "maybe", "more", "need", "not", "now", "okay", "one", "our", "out",
+    "really", "right", "say", "see", "she", "should", "some", "that", "the", "their", "them",
+    "then", "there", "they", "think", "this", "those", "want", "was", "way", "well", "were",
+    "what", "when", "where", "which", "who", "will", "with", "would", "yeah", "yes", "you", "your",
+];
+
+/// One line of the transcript, aligned to the recording.
+#[derive(Debug, Clone)]
+pub struct Line {
+    /// Seconds into the recording.
+    pub start: f64,
+    pub speaker_name: String,
+    pub text: String,
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|w| w.chars().count() >= 3 && !STOPWORDS.contains(&w.as_str()))
+}
+
+fn bag<'a>(lines: impl Iterator<Item = &'a Line>) -> HashMap<String, f64> {
+    let mut counts = HashMap::new();
+    for line in lines {
+        for word in words(&line.text) {
+            *counts.entry(word).or_insert(0.0) += 1.0;
+        }
+    }
+    counts
+}
+
+fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let dot: f64 = a.iter().filter_map(|(w, x)| b.get(w).map(|y| x * y)).sum();
+    let norm = |m: &HashMap<String, f64>| m.values().map(|x| x * x).sum::<f64>().sqrt();
+    let (na, nb) = (norm(a), norm(b));
+    if na == 0.0 || nb == 0.0 {
+        0.0
+    } else {
+        dot / (na * nb)
+    }
+}
+
+/// The most frequent words of a chapter, first-seen first on ties.
+fn title(lines: &[Line]) -> String {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for word in lines.iter().flat_map(|l| words(&l.text)) {
+        match counts.iter_mut().find(|(w, _)| *w == word) {
+            Some((_, n)) => *n += 1,
+            None => counts.push((word, 1)),
+        }
+    }
+    // Stable sort keeps first-seen order among equal counts
+    counts.sort_by_key(|c| std::cmp::Reverse(c.1));
+    let title = counts
+        .into_iter()
+        .take(TITLE_WORDS)
+        .map(|(w, _)| w)
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut chars = title.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => "Untitled".to_string(),
+    }
+}
+
+/// Split the transcript where the vocabulary shifts.
+///
+/// Each gap between lines is scored by how similar the words of the
+/// [`WINDOW`] lines before it are to those after. A gap becomes a chapter
+/// boundary when it is a local minimum, below the mean by half a standard
+/// deviation, and at least [`MIN_CHAPTER_SECS`] after the previous one.
+pub fn by_topic(lines: &[Line]) -> Vec<RecordingChapter> {
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let sims: Vec<f64> = (1..lines.len())
+        .map(|gap| {
+            let before = bag(lines[gap.saturating_sub(WINDOW)..gap].iter());
+            let after = bag(lines[gap..(gap + WINDOW).min(lines.len())].iter());
+            cosine(&before, &after)
+        })
+        .collect();
+
+    let mut starts = vec![0];
+    if !sims.is_empty() {
+        let mean = sims.iter().sum::<f64>() / sims.len() as f64;
+        let var = sims.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / sims.len() as f64;
+        let cutoff = mean - var.sqrt() / 2.0;
+        for (i, &sim) in sims.iter().enumerate() {
+            let is_min =
+                (i == 0 || sim <= sims[i - 1]) && sims.get(i + 1).is_none_or(|&n| sim <= n);
+            let gap = i + 1;
+            let since = lines[gap].start - lines[*starts.last().unwrap()].start;
+            if is_min && sim < cutoff && since >= MIN_CHAPTER_SECS {
+                starts.push(gap);
+            }
+        }
+    }
+
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &first)| {
+            let end = starts.get(n + 1).copied().unwrap_or(lines.len());
+            RecordingChapter {
+                start: if n == 0 { 0.0 } else { lines[first].start },
+                title: title(&lines[first..end]),
+            }
+        })
+        .collect()
+}
+
+fn timestamp(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// The request for the model: the numbered transcript and the answer format.
+pub fn prompt(lines: &[Line]) -> String {
+    let mut prompt = String::from(concat!(
+        "Split this meeting transcript into chapters where the topic changes. ",
+        "Each line is `[number] (time) speaker: text`. ",
+        "Return ONLY a JSON array, no markdown fences, of objects with ",
+        "\"line\" (the number of the chapter's first line) and \"title\" ",
+        "(a short title, at most 6 words). The first chapter starts at line 0.\n\n",
+    ));
+    for (i, line) in lines.iter().enumerate() {
+        prompt.push_str(&format!(
+            "[{}] ({}) {}: {}\n",
+            i,
+            timestamp(line.start),
+            line.speaker_name,
+            line.text
+        ));
+    }
+    prompt
+}
+
+/// Chapters from the model's reply, or `None` when it isn't usable.
+/// Unknown line numbers are dropped, duplicates merged and the first
+/// chapter moved to 0.
+pub fn parse_reply(reply: &str, lines: &[Line]) -> Option<Vec<RecordingChapter>> {
+    let json = reply
+        .trim()
+        .trim_start_matches("```json")
+        .trim_start_matches("```")
+        .trim_end_matches("```");
+    let items: Vec<serde_json::Value> = serde_json::from_str(json.trim()).ok()?;
+    let mut chapters: Vec<RecordingChapter> = items
+        .iter()
+        .filter_map(|item| {
+            let line = lines.get(item["line"].as_u64()? as usize)?;
+            let title = item["title"].as_str()?.trim();
+            (!title.is_empty()).then(|| RecordingChapter {
+                start: line.start,
+                title: title.to_string(),
+            })
+        })
+        .collect();
+    chapters.sort_by(|a, b| a.start.total_cmp(&b.start));
+    chapters.dedup_by(|b, a| a.start == b.start);
+    chapters.first_mut()?.start = 0.0;
+    Some(chapters)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(start: f64, text: &str) -> Line {
+        Line {
+            start,
+            speaker_name: "Ann".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn meeting() -> Vec<Line> {
+        vec![
+            line(2.0, "The budget for the quarter is tight"),
+            line(20.0, "Budget cuts hit the travel costs"),
+            line(40.0, "Travel costs are over budget again"),
+            line(60.0, "Let's freeze the budget for travel"),
+            line(80.0, "Hiring: two engineers start in May"),
+            line(100.0, "Interview engineers next week for hiring"),
+            line(120.0, "The hiring panel needs engineers"),
+            line(140.0, "Engineers onboarding after hiring"),
+        ]
+    }
+
+    #[test]
+    fn vocabulary_shift_starts_a_chapter() {
+        let chapters = by_topic(&meeting());
+        assert_eq!(chapters.len(), 2);
+        assert_eq!(chapters[0].start, 0.0);
+        assert!(chapters[0].title.starts_with("Budget"));
+        assert_eq!(chapters[1].start, 80.0);
+        assert!(chapters[1].title.starts_with("Hiring"));
+    }
+
+    #[test]
+    fn short_meetings_are_one_chapter() {
+        let mut lines = meeting();
+        for (i, l) in lines.iter_mut().enumerate() {
+            l.start = i as f64 * 5.0;
+        }
+        assert_eq!(by_topic(&lines).len(), 1);
+        assert!(by_topic(&[]).is_empty());
+    }
+
+    #[test]
+    fn model_reply_is_mapped_onto_lines() {
+        let lines = meeting();
+        let reply = r#"```json
+[{"line": 4, "title": "Hiring plan"}, {"line": 1, "title": "Budget"},
+ {"line": 99, "title": "Nowhere"}, {"line": 4, "title": "Again"}]
+```"#;
+        let chapters = parse_reply(reply, &lines).unwrap();
+        let got: Vec<_> = chapters
+            .iter()
+            .map(|c| (c.start, c.title.as_str()))
+            .collect();
+        assert_eq!(got, [(0.0, "Budget"), (80.0, "Hiring plan")]);
+        assert!(parse_reply("no chapters here", &lines).is_none());
+        assert!(parse_reply("[]", &lines).is_none());
+    }
+
+    #[test]
+    fn prompt_numbers_and_times_lines() {
+        let p = prompt(&meeting()[..2]);
+        assert!(p.contains("[0] (00:00:02) Ann: The budget for the quarter is tight\n"));
+        assert!(p.contains("[1] (00:00:20) Ann: Budget cuts"));
+    }
+}
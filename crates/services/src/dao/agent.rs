@@ -13,7 +13,7 @@ pub struct AgentDao {
 impl AgentDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Agent::COLLECTION),
+            base: BaseDao::new(db, Agent::COLLECTION).tenant_scoped(),
         }
     }
 
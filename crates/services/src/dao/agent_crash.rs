@@ -28,7 +28,7 @@ pub struct AgentCrashDao {
 impl AgentCrashDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, AgentCrashRecord::COLLECTION),
+            base: BaseDao::new(db, AgentCrashRecord::COLLECTION).tenant_scoped(),
         }
     }
 
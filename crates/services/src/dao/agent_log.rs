@@ -23,7 +23,7 @@ pub struct AgentLogDao {
 impl AgentLogDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, AgentLogBatch::COLLECTION),
+            base: BaseDao::new(db, AgentLogBatch::COLLECTION).tenant_scoped(),
         }
     }
 
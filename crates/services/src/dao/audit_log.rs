@@ -0,0 +1,42 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::AuditLog;
+
+use super::base::{BaseDao, DaoResult, ListOptions, PaginatedResult, PaginationParams};
+
+pub struct AuditLogDao {
+    pub base: BaseDao<AuditLog>,
+}
+
+impl AuditLogDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, AuditLog::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Append one entry. Append-only — rows are never edited; the 90 d TTL
+    /// index on `created_at` handles cleanup.
+    pub async fn append(&self, entry: &AuditLog) -> DaoResult<ObjectId> {
+        self.base.insert_one(entry).await
+    }
+
+    /// A tenant's entries, newest-first by default. Backed by the
+    /// `(tenant_id, created_at)` / `(tenant_id, action, created_at)` /
+    /// `(tenant_id, actor_id, created_at)` indexes.
+    pub async fn list_for_tenant(
+        &self,
+        tenant_id: ObjectId,
+        params: &PaginationParams,
+        options: &ListOptions,
+    ) -> DaoResult<PaginatedResult<AuditLog>> {
+        self.base
+            .find_listed(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "created_at": -1 }),
+                params,
+                options,
+            )
+            .await
+    }
+}
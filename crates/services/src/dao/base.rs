@@ -1,8 +1,15 @@
-use bson::{Document, doc, oid::ObjectId};
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use bson::{Bson, Document, doc, oid::ObjectId};
 use mongodb::{Collection, Database};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tracing::debug;
+use tracing::{debug, warn};
+use utoipa::IntoParams;
 
 #[derive(Debug, Error)]
 pub enum DaoError {
@@ -20,11 +27,16 @@ pub enum DaoError {
     Forbidden(String),
     #[error("Validation: {0}")]
     Validation(String),
+    /// A tenant-owned collection was queried without a `tenant_id` filter
+    /// while [`TenantAudit::Deny`] is on.
+    #[error("Unscoped query: {0}")]
+    Unscoped(String),
 }
 
 pub type DaoResult<T> = Result<T, DaoError>;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct PaginationParams {
     #[serde(default = "default_page")]
     pub page: u64,
@@ -33,6 +45,11 @@ pub struct PaginationParams {
     /// ISO 8601 timestamp — return only items created before this date
     #[serde(default)]
     pub before: Option<String>,
+    /// Opaque keyset cursor from a previous page's `next_cursor`. When set,
+    /// `page` is ignored and the page starts right after the cursor's item;
+    /// an empty cursor starts cursor mode at the first item.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 impl Default for PaginationParams {
@@ -41,6 +58,7 @@ impl Default for PaginationParams {
             page: default_page(),
             per_page: default_per_page(),
             before: None,
+            cursor: None,
         }
     }
 }
@@ -62,17 +80,183 @@ impl PaginationParams {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PaginatedResult<T> {
-    pub items: Vec<T>,
-    pub total: u64,
-    pub page: u64,
-    pub per_page: u64,
-    pub total_pages: u64,
+pub use roomler_ai_types::PaginatedResult;
+
+/// Caller-supplied narrowing of a list query: extra filter clauses and an
+/// optional sort overriding the DAO's default. Built from validated query
+/// parameters by the API layer; the DAO's own scoping filter always applies.
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    pub filter: Document,
+    pub sort: Option<Document>,
+}
+
+impl ListOptions {
+    /// `scope` narrowed by the caller's filter. The two are combined with
+    /// `$and` rather than merged, so a caller clause on a scoping key
+    /// (e.g. `tenant_id`) can only narrow the result, never replace it.
+    fn narrow(&self, scope: Document) -> Document {
+        if self.filter.is_empty() {
+            scope
+        } else {
+            doc! { "$and": [scope, self.filter.clone()] }
+        }
+    }
+}
+
+/// Append `_id` as a tie-breaker so the sort is total: pages never skip or
+/// repeat items that share a sort value, in either pagination mode.
+fn with_tiebreak(mut sort: Document) -> Document {
+    if !sort.contains_key("_id") {
+        let dir = sort
+            .iter()
+            .next()
+            .map(|(_, v)| v.clone())
+            .unwrap_or(Bson::Int32(-1));
+        sort.insert("_id", dir);
+    }
+    sort
+}
+
+pub(super) fn sort_direction(v: &Bson) -> i64 {
+    match v {
+        Bson::Int32(n) => *n as i64,
+        Bson::Int64(n) => *n,
+        Bson::Double(n) => *n as i64,
+        _ => 1,
+    }
+}
+
+/// Look up a (possibly dotted) field path in a serialized document.
+fn lookup_path(doc: &Document, path: &str) -> Bson {
+    let mut current = doc;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        match current.get(part) {
+            Some(Bson::Document(inner)) if parts.peek().is_some() => current = inner,
+            Some(v) if parts.peek().is_none() => return v.clone(),
+            _ => return Bson::Null,
+        }
+    }
+    Bson::Null
+}
+
+fn encode_cursor(values: Vec<Bson>) -> Option<String> {
+    let bytes = bson::to_vec(&doc! { "k": values }).ok()?;
+    Some(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+fn decode_cursor(cursor: &str, key_count: usize) -> DaoResult<Vec<Bson>> {
+    let invalid = || DaoError::Validation("Invalid cursor".to_string());
+    let bytes = URL_SAFE_NO_PAD.decode(cursor).map_err(|_| invalid())?;
+    let doc: Document = bson::from_slice(&bytes).map_err(|_| invalid())?;
+    let values = doc.get_array("k").map_err(|_| invalid())?.clone();
+    if values.len() != key_count {
+        return Err(invalid());
+    }
+    Ok(values)
+}
+
+/// Filter selecting the items strictly after `values` in `sort` order:
+/// `(k1 > v1) OR (k1 = v1 AND k2 > v2) OR ...`, with `<` for descending keys.
+fn keyset_filter(sort: &Document, values: &[Bson]) -> Document {
+    let keys: Vec<(&String, &Bson)> = sort.iter().collect();
+    let branches: Vec<Bson> = (0..keys.len())
+        .map(|i| {
+            let mut branch = Document::new();
+            for (j, (key, _)) in keys.iter().enumerate().take(i) {
+                branch.insert(key.as_str(), values[j].clone());
+            }
+            let (key, dir) = keys[i];
+            let op = if sort_direction(dir) < 0 {
+                "$lt"
+            } else {
+                "$gt"
+            };
+            branch.insert(key.as_str(), doc! { op: values[i].clone() });
+            Bson::Document(branch)
+        })
+        .collect();
+    doc! { "$or": branches }
+}
+
+/// How [`BaseDao`] treats a query on a tenant-owned collection whose filter
+/// doesn't include `tenant_id`. A dev/test guard against cross-tenant reads;
+/// production runs with it off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TenantAudit {
+    Off,
+    /// Log the first offending query from each call site, with a backtrace
+    /// to it.
+    Warn,
+    /// Fail the query with [`DaoError::Unscoped`].
+    Deny,
+}
+
+impl TenantAudit {
+    /// From `database.tenant_audit`: `warn`, `deny`, anything else is off.
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "warn" => Self::Warn,
+            "deny" => Self::Deny,
+            _ => Self::Off,
+        }
+    }
+}
+
+static TENANT_AUDIT: AtomicU8 = AtomicU8::new(0);
+/// Hashes of the call sites already reported in [`TenantAudit::Warn`].
+static REPORTED_UNSCOPED: LazyLock<Mutex<HashSet<u64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Set the process-wide [`TenantAudit`] mode.
+pub fn set_tenant_audit(mode: TenantAudit) {
+    TENANT_AUDIT.store(mode as u8, Ordering::Relaxed);
+}
+
+fn tenant_audit() -> TenantAudit {
+    match TENANT_AUDIT.load(Ordering::Relaxed) {
+        1 => TenantAudit::Warn,
+        2 => TenantAudit::Deny,
+        _ => TenantAudit::Off,
+    }
+}
+
+/// The workspace frames of a rendered backtrace: its source locations
+/// outside the standard library and dependencies (the async runtime's
+/// frames differ between polls of the same call). The whole backtrace when
+/// it has no locations, as without debug info.
+fn call_site(backtrace: &str) -> String {
+    let ours: Vec<&str> = backtrace
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("at "))
+        .filter(|loc| !loc.starts_with("/rustc/") && !loc.contains("/.cargo/"))
+        .collect();
+    if ours.is_empty() {
+        backtrace.to_string()
+    } else {
+        ours.join("\n")
+    }
+}
+
+/// Whether every document `filter` can match is pinned to one tenant: a
+/// top-level `tenant_id`, any `$and` clause that is, or all `$or` branches.
+fn is_tenant_scoped(filter: &Document) -> bool {
+    let clauses = |key| {
+        filter
+            .get_array(key)
+            .map(|a| a.iter().filter_map(Bson::as_document).collect::<Vec<_>>())
+            .unwrap_or_default()
+    };
+    filter.contains_key("tenant_id") || clauses("$and").into_iter().any(is_tenant_scoped) || {
+        let branches = clauses("$or");
+        !branches.is_empty() && branches.into_iter().all(is_tenant_scoped)
+    }
 }
 
 pub struct BaseDao<T: Send + Sync> {
     collection: Collection<T>,
+    tenant_scoped: bool,
 }
 
 impl<T> BaseDao<T>
@@ -82,14 +266,64 @@ where
     pub fn new(db: &Database, collection_name: &str) -> Self {
         Self {
             collection: db.collection::<T>(collection_name),
+            tenant_scoped: false,
         }
     }
 
+    /// Mark the collection as tenant-owned, so [`TenantAudit`] checks that
+    /// queries through this DAO filter on `tenant_id`.
+    pub fn tenant_scoped(mut self) -> Self {
+        self.tenant_scoped = true;
+        self
+    }
+
     pub fn collection(&self) -> &Collection<T> {
         &self.collection
     }
 
+    /// Apply [`TenantAudit`] to a query about to run with `filter`.
+    fn audit_scope(&self, op: &str, filter: &Document) -> DaoResult<()> {
+        if !self.tenant_scoped || is_tenant_scoped(filter) {
+            return Ok(());
+        }
+        let collection = self.collection.name();
+        match tenant_audit() {
+            TenantAudit::Off => Ok(()),
+            TenantAudit::Warn => {
+                let backtrace = std::backtrace::Backtrace::force_capture().to_string();
+                let site = {
+                    let mut hasher = std::hash::DefaultHasher::new();
+                    (collection, op, call_site(&backtrace)).hash(&mut hasher);
+                    hasher.finish()
+                };
+                let first = REPORTED_UNSCOPED
+                    .lock()
+                    .map(|mut seen| seen.insert(site))
+                    .unwrap_or(false);
+                if first {
+                    warn!(collection, op, %backtrace, "Query without a tenant_id filter");
+                }
+                Ok(())
+            }
+            TenantAudit::Deny => Err(DaoError::Unscoped(format!(
+                "{op} on {collection} without a tenant_id filter"
+            ))),
+        }
+    }
+
     pub async fn find_by_id(&self, id: ObjectId) -> DaoResult<T> {
+        let filter = doc! { "_id": id };
+        self.audit_scope("find_by_id", &filter)?;
+        self.collection
+            .find_one(filter)
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    /// Look up a document by id alone, to learn which tenant it belongs to.
+    /// Exempt from [`TenantAudit`]: the caller must then check the user's
+    /// access to that tenant.
+    pub async fn find_by_id_unscoped(&self, id: ObjectId) -> DaoResult<T> {
         self.collection
             .find_one(doc! { "_id": id })
             .await?
@@ -104,6 +338,7 @@ where
     }
 
     pub async fn find_one(&self, filter: Document) -> DaoResult<Option<T>> {
+        self.audit_scope("find_one", &filter)?;
         Ok(self.collection.find_one(filter).await?)
     }
 
@@ -113,6 +348,7 @@ where
     }
 
     pub async fn find_many(&self, filter: Document, sort: Option<Document>) -> DaoResult<Vec<T>> {
+        self.audit_scope("find_many", &filter)?;
         let mut cursor = if let Some(sort) = sort {
             self.collection.find(filter).sort(sort).await?
         } else {
@@ -132,13 +368,82 @@ where
         filter: Document,
         sort: Option<Document>,
         params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<T>> {
+        self.find_listed(filter, sort, params, &ListOptions::default())
+            .await
+    }
+
+    /// Paginated find narrowed by caller [`ListOptions`]. Offset mode uses
+    /// `page`; when `params.cursor` is set the page is selected by keyset
+    /// instead, which stays stable while items are inserted.
+    pub async fn find_listed(
+        &self,
+        filter: Document,
+        default_sort: Option<Document>,
+        params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<T>> {
         let per_page = params.clamped_per_page();
+
+        let filter = options.narrow(filter);
+        self.audit_scope("find_listed", &filter)?;
         let total = self.collection.count_documents(filter.clone()).await?;
-        let skip = (params.page - 1) * per_page;
 
-        let sort = sort.unwrap_or_else(|| doc! { "created_at": -1 });
+        let sort = with_tiebreak(
+            options
+                .sort
+                .clone()
+                .or(default_sort)
+                .unwrap_or_else(|| doc! { "created_at": -1 }),
+        );
+
+        let total_pages = if per_page > 0 {
+            total.div_ceil(per_page)
+        } else {
+            0
+        };
+
+        use futures::TryStreamExt;
+
+        if let Some(ref cursor) = params.cursor {
+            // An empty cursor starts cursor mode at the first item.
+            let filter = if cursor.is_empty() {
+                filter
+            } else {
+                let values = decode_cursor(cursor, sort.len())?;
+                doc! { "$and": [filter, keyset_filter(&sort, &values)] }
+            };
+
+            // One extra item tells us whether another page exists.
+            let mut cursor = self
+                .collection
+                .find(filter)
+                .sort(sort.clone())
+                .limit(per_page as i64 + 1)
+                .await?;
+            let mut items = Vec::new();
+            while let Some(doc) = cursor.try_next().await? {
+                items.push(doc);
+            }
+            let has_more = items.len() as u64 > per_page;
+            items.truncate(per_page as usize);
+            let next_cursor = if has_more {
+                self.cursor_for(items.last(), &sort)?
+            } else {
+                None
+            };
+
+            return Ok(PaginatedResult {
+                items,
+                total,
+                page: params.page,
+                per_page,
+                total_pages,
+                next_cursor,
+            });
+        }
 
+        let skip = (params.page.max(1) - 1) * per_page;
         let mut cursor = self
             .collection
             .find(filter)
@@ -148,16 +453,102 @@ where
             .await?;
 
         let mut items = Vec::new();
-        use futures::TryStreamExt;
         while let Some(doc) = cursor.try_next().await? {
             items.push(doc);
         }
 
+        Ok(PaginatedResult {
+            items,
+            total,
+            page: params.page,
+            per_page,
+            total_pages,
+            next_cursor: None,
+        })
+    }
+
+    /// [`Self::find_listed`] over this collection followed by `tail`, whose
+    /// items all sort after this collection's (e.g. an archive of older
+    /// documents). Pages, totals and cursors run across both as one list.
+    pub async fn find_listed_then(
+        &self,
+        tail: &BaseDao<T>,
+        filter: Document,
+        default_sort: Option<Document>,
+        params: &PaginationParams,
+        options: &ListOptions,
+    ) -> DaoResult<PaginatedResult<T>> {
+        let head = self
+            .find_listed(filter.clone(), default_sort.clone(), params, options)
+            .await?;
+
+        let filter = options.narrow(filter);
+        let tail_total = tail.collection.count_documents(filter.clone()).await?;
+        if tail_total == 0 {
+            return Ok(head);
+        }
+
+        let per_page = params.clamped_per_page();
+        let sort = with_tiebreak(
+            options
+                .sort
+                .clone()
+                .or(default_sort)
+                .unwrap_or_else(|| doc! { "created_at": -1 }),
+        );
+        let total = head.total + tail_total;
         let total_pages = if per_page > 0 {
             total.div_ceil(per_page)
         } else {
             0
         };
+        let mut items = head.items;
+        let mut next_cursor = head.next_cursor;
+
+        use futures::TryStreamExt;
+
+        if let Some(ref cursor) = params.cursor {
+            // The head ran out on this page: carry on into the tail from the
+            // same position, again with one extra item to detect more.
+            if next_cursor.is_none() {
+                let filter = if cursor.is_empty() {
+                    filter
+                } else {
+                    let values = decode_cursor(cursor, sort.len())?;
+                    doc! { "$and": [filter, keyset_filter(&sort, &values)] }
+                };
+                let want = per_page - items.len() as u64 + 1;
+                let mut cursor = tail
+                    .collection
+                    .find(filter)
+                    .sort(sort.clone())
+                    .limit(want as i64)
+                    .await?;
+                while let Some(doc) = cursor.try_next().await? {
+                    items.push(doc);
+                }
+                let has_more = items.len() as u64 > per_page;
+                items.truncate(per_page as usize);
+                if has_more {
+                    next_cursor = self.cursor_for(items.last(), &sort)?;
+                }
+            }
+        } else {
+            let want = per_page.saturating_sub(items.len() as u64);
+            if want > 0 {
+                let skip = (params.page.max(1) - 1) * per_page;
+                let mut cursor = tail
+                    .collection
+                    .find(filter)
+                    .sort(sort)
+                    .skip(skip.saturating_sub(head.total))
+                    .limit(want as i64)
+                    .await?;
+                while let Some(doc) = cursor.try_next().await? {
+                    items.push(doc);
+                }
+            }
+        }
 
         Ok(PaginatedResult {
             items,
@@ -165,9 +556,20 @@ where
             page: params.page,
             per_page,
             total_pages,
+            next_cursor,
         })
     }
 
+    /// Encode the keyset position of `item` under `sort`.
+    fn cursor_for(&self, item: Option<&T>, sort: &Document) -> DaoResult<Option<String>> {
+        let Some(item) = item else {
+            return Ok(None);
+        };
+        let doc = bson::to_document(item)?;
+        let values = sort.keys().map(|k| lookup_path(&doc, k)).collect();
+        Ok(encode_cursor(values))
+    }
+
     pub async fn text_search(
         &self,
         query: &str,
@@ -178,6 +580,7 @@ where
         for (k, v) in additional_filter.iter() {
             filter.insert(k, v.clone());
         }
+        self.audit_scope("text_search", &filter)?;
 
         let mut cursor = self
             .collection
@@ -215,6 +618,7 @@ where
     }
 
     pub async fn update_one(&self, filter: Document, update: Document) -> DaoResult<bool> {
+        self.audit_scope("update_one", &filter)?;
         let update_with_timestamp = doc! {
             "$set": {
                 "updated_at": bson::DateTime::now(),
@@ -273,11 +677,84 @@ where
     }
 
     pub async fn hard_delete(&self, filter: Document) -> DaoResult<u64> {
+        self.audit_scope("hard_delete", &filter)?;
         let result = self.collection.delete_many(filter).await?;
         Ok(result.deleted_count)
     }
 
     pub async fn count(&self, filter: Document) -> DaoResult<u64> {
+        self.audit_scope("count", &filter)?;
         Ok(self.collection.count_documents(filter).await?)
     }
+
+    /// Sum of a numeric `field` over the matching documents (0 if none match).
+    pub async fn sum(&self, filter: Document, field: &str) -> DaoResult<u64> {
+        use futures::TryStreamExt;
+
+        self.audit_scope("sum", &filter)?;
+        let pipeline = vec![
+            doc! { "$match": filter },
+            doc! { "$group": { "_id": null, "total": { "$sum": format!("${field}") } } },
+        ];
+        let mut cursor = self.collection.aggregate(pipeline).await?;
+        let total = match cursor.try_next().await? {
+            Some(doc) => match doc.get("total") {
+                Some(Bson::Int32(n)) => (*n).max(0) as u64,
+                Some(Bson::Int64(n)) => (*n).max(0) as u64,
+                Some(Bson::Double(n)) => n.max(0.0) as u64,
+                _ => 0,
+            },
+            None => 0,
+        };
+        Ok(total)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tenant_scope_follows_and_or_clauses() {
+        let tid = ObjectId::new();
+        assert!(is_tenant_scoped(&doc! { "_id": 1, "tenant_id": tid }));
+        assert!(!is_tenant_scoped(&doc! { "_id": 1 }));
+        assert!(is_tenant_scoped(
+            &doc! { "$and": [{ "room_id": 1 }, { "tenant_id": tid }] }
+        ));
+        assert!(is_tenant_scoped(
+            &doc! { "$or": [{ "tenant_id": tid }, { "tenant_id": tid, "x": 1 }] }
+        ));
+        assert!(!is_tenant_scoped(
+            &doc! { "$or": [{ "tenant_id": tid }, { "room_id": 1 }] }
+        ));
+    }
+
+    #[test]
+    fn tenant_audit_parses_config_values() {
+        assert_eq!(TenantAudit::parse("warn"), TenantAudit::Warn);
+        assert_eq!(TenantAudit::parse("deny"), TenantAudit::Deny);
+        assert_eq!(TenantAudit::parse(""), TenantAudit::Off);
+        assert_eq!(TenantAudit::parse("yes"), TenantAudit::Off);
+    }
+
+    #[test]
+    fn call_sites_are_the_workspace_frames() {
+        let trace = |caller: &str, poll: u32| {
+            format!(
+                "   0: std::backtrace::Backtrace::force_capture
+             at /rustc/abc/library/std/src/backtrace.rs:310:9
+   1: roomler_ai_services::dao::base::BaseDao<T>::find_by_id
+             at ./crates/services/src/dao/base.rs:330:9
+   2: roomler_ai_api::routes::{caller}
+             at ./crates/api/src/routes/{caller}.rs:12:5
+   3: tokio::runtime::task::harness::poll
+             at /root/.cargo/registry/src/tokio-1.0/src/runtime/task/harness.rs:{poll}:1
+"
+            )
+        };
+        assert_eq!(call_site(&trace("file", 1)), call_site(&trace("file", 2)));
+        assert_ne!(call_site(&trace("file", 1)), call_site(&trace("room", 1)));
+        assert_eq!(call_site("<unsupported>"), "<unsupported>");
+    }
 }
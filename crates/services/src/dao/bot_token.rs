@@ -0,0 +1,109 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::BotToken;
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct BotTokenDao {
+    pub base: BaseDao<BotToken>,
+}
+
+impl BotTokenDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, BotToken::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        bot_id: ObjectId,
+        created_by: ObjectId,
+        name: String,
+        room_ids: Vec<ObjectId>,
+        ttl_secs: u64,
+    ) -> DaoResult<BotToken> {
+        let now = DateTime::now();
+        let token = BotToken {
+            id: None,
+            tenant_id,
+            bot_id,
+            name,
+            room_ids,
+            created_by,
+            expires_at: DateTime::from_millis(now.timestamp_millis() + ttl_secs as i64 * 1000),
+            revoked_at: None,
+            created_at: now,
+        };
+        let id = self.base.insert_one(&token).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn list_for_bot(
+        &self,
+        tenant_id: ObjectId,
+        bot_id: ObjectId,
+    ) -> DaoResult<Vec<BotToken>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "bot_id": bot_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// The unrevoked token `id` belonging to `bot_id` in `tenant_id`.
+    pub async fn find_active(
+        &self,
+        tenant_id: ObjectId,
+        bot_id: ObjectId,
+        id: ObjectId,
+    ) -> DaoResult<BotToken> {
+        self.base
+            .find_one(doc! {
+                "_id": id,
+                "tenant_id": tenant_id,
+                "bot_id": bot_id,
+                "revoked_at": null,
+            })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    /// Revoke one token. Returns whether it was still active.
+    pub async fn revoke(
+        &self,
+        tenant_id: ObjectId,
+        bot_id: ObjectId,
+        id: ObjectId,
+    ) -> DaoResult<bool> {
+        let result = self
+            .base
+            .collection()
+            .update_one(
+                doc! {
+                    "_id": id,
+                    "tenant_id": tenant_id,
+                    "bot_id": bot_id,
+                    "revoked_at": null,
+                },
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+            )
+            .await?;
+        Ok(result.modified_count > 0)
+    }
+
+    /// Revoke every token of a bot, e.g. when the bot is deleted.
+    pub async fn revoke_all(&self, tenant_id: ObjectId, bot_id: ObjectId) -> DaoResult<u64> {
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! { "tenant_id": tenant_id, "bot_id": bot_id, "revoked_at": null },
+                doc! { "$set": { "revoked_at": DateTime::now() } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+}
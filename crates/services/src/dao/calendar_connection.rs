@@ -0,0 +1,91 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::CalendarConnection;
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct CalendarConnectionDao {
+    pub base: BaseDao<CalendarConnection>,
+}
+
+impl CalendarConnectionDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CalendarConnection::COLLECTION),
+        }
+    }
+
+    /// Connect `user_id`'s calendar at `provider`, replacing an earlier
+    /// connection there.
+    pub async fn connect(
+        &self,
+        user_id: ObjectId,
+        provider: &str,
+        account_email: String,
+        access_token: String,
+        refresh_token: String,
+        expires_at: DateTime,
+    ) -> DaoResult<CalendarConnection> {
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .update_one(
+                doc! { "user_id": user_id, "provider": provider },
+                doc! {
+                    "$set": {
+                        "account_email": account_email,
+                        "access_token": access_token,
+                        "refresh_token": refresh_token,
+                        "expires_at": expires_at,
+                        "updated_at": now,
+                    },
+                    "$setOnInsert": { "created_at": now },
+                },
+            )
+            .upsert(true)
+            .await?;
+        self.find(user_id, provider)
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    pub async fn find(
+        &self,
+        user_id: ObjectId,
+        provider: &str,
+    ) -> DaoResult<Option<CalendarConnection>> {
+        self.base
+            .find_one(doc! { "user_id": user_id, "provider": provider })
+            .await
+    }
+
+    pub async fn list_for_user(&self, user_id: ObjectId) -> DaoResult<Vec<CalendarConnection>> {
+        self.base
+            .find_many(doc! { "user_id": user_id }, Some(doc! { "provider": 1 }))
+            .await
+    }
+
+    /// Store refreshed tokens; `refresh_token` is kept when the provider
+    /// didn't issue a new one.
+    pub async fn set_tokens(
+        &self,
+        id: ObjectId,
+        access_token: &str,
+        refresh_token: Option<&str>,
+        expires_at: DateTime,
+    ) -> DaoResult<bool> {
+        let mut set = doc! { "access_token": access_token, "expires_at": expires_at };
+        if let Some(refresh_token) = refresh_token {
+            set.insert("refresh_token", refresh_token);
+        }
+        self.base.update_by_id(id, doc! { "$set": set }).await
+    }
+
+    pub async fn disconnect(&self, user_id: ObjectId, provider: &str) -> DaoResult<bool> {
+        let count = self
+            .base
+            .hard_delete(doc! { "user_id": user_id, "provider": provider })
+            .await?;
+        Ok(count > 0)
+    }
+}
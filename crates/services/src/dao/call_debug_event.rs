@@ -0,0 +1,49 @@
+use bson::{doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::CallDebugEvent;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct CallDebugEventDao {
+    pub base: BaseDao<CallDebugEvent>,
+}
+
+impl CallDebugEventDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CallDebugEvent::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn insert_many(&self, events: Vec<CallDebugEvent>) -> DaoResult<()> {
+        self.base.collection().insert_many(events).await?;
+        Ok(())
+    }
+
+    /// The room's latest `limit` events, oldest first; only `user_id`'s
+    /// when given.
+    pub async fn timeline(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: Option<ObjectId>,
+        limit: i64,
+    ) -> DaoResult<Vec<CallDebugEvent>> {
+        let mut filter = doc! { "tenant_id": tenant_id, "room_id": room_id };
+        if let Some(uid) = user_id {
+            filter.insert("user_id", uid);
+        }
+        let mut events: Vec<CallDebugEvent> = self
+            .base
+            .collection()
+            .find(filter)
+            .sort(doc! { "at": -1, "_id": -1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+        events.reverse();
+        Ok(events)
+    }
+}
@@ -0,0 +1,158 @@
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::ReturnDocument;
+use roomler_ai_db::models::{CallPoll, PollOption, PollVote};
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct CallPollDao {
+    pub base: BaseDao<CallPoll>,
+}
+
+impl CallPollDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CallPoll::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        call_session_id: ObjectId,
+        created_by: ObjectId,
+        question: String,
+        options: Vec<String>,
+        allow_multiple: bool,
+        results_visible: bool,
+    ) -> DaoResult<CallPoll> {
+        let now = DateTime::now();
+        let poll = CallPoll {
+            id: None,
+            tenant_id,
+            room_id,
+            call_session_id,
+            created_by,
+            question,
+            options: options
+                .into_iter()
+                .map(|text| PollOption { text, votes: 0 })
+                .collect(),
+            allow_multiple,
+            results_visible,
+            votes: Vec::new(),
+            total_votes: 0,
+            closed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&poll).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn find_in_room(&self, room_id: ObjectId, poll_id: ObjectId) -> DaoResult<CallPoll> {
+        self.base
+            .find_one(doc! { "_id": poll_id, "room_id": room_id })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    /// Polls of one call, oldest first.
+    pub async fn list_for_session(&self, call_session_id: ObjectId) -> DaoResult<Vec<CallPoll>> {
+        self.base
+            .find_many(
+                doc! { "call_session_id": call_session_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Record a user's vote. Returns `None` when the poll is closed or the
+    /// user has already voted.
+    pub async fn vote(
+        &self,
+        room_id: ObjectId,
+        poll_id: ObjectId,
+        user_id: ObjectId,
+        option_ids: Vec<u32>,
+    ) -> DaoResult<Option<CallPoll>> {
+        let now = DateTime::now();
+        let mut inc = Document::new();
+        for i in &option_ids {
+            inc.insert(format!("options.{}.votes", i), 1);
+        }
+        inc.insert("total_votes", 1);
+        let vote = PollVote {
+            user_id,
+            option_ids,
+            voted_at: now,
+        };
+
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "_id": poll_id,
+                    "room_id": room_id,
+                    "closed_at": null,
+                    "votes.user_id": { "$ne": user_id },
+                },
+                doc! {
+                    "$push": { "votes": bson::to_bson(&vote)? },
+                    "$inc": inc,
+                    "$set": { "updated_at": now },
+                },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Show or hide tallies of an open poll. Returns `None` if it's closed.
+    pub async fn set_results_visible(
+        &self,
+        room_id: ObjectId,
+        poll_id: ObjectId,
+        visible: bool,
+    ) -> DaoResult<Option<CallPoll>> {
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! { "_id": poll_id, "room_id": room_id, "closed_at": null },
+                doc! { "$set": { "results_visible": visible, "updated_at": DateTime::now() } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Close an open poll. Returns `None` if it was already closed.
+    pub async fn close(&self, room_id: ObjectId, poll_id: ObjectId) -> DaoResult<Option<CallPoll>> {
+        let now = DateTime::now();
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! { "_id": poll_id, "room_id": room_id, "closed_at": null },
+                doc! { "$set": { "closed_at": now, "updated_at": now } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Close every poll still open in a call, when the call ends.
+    pub async fn close_all_for_session(&self, call_session_id: ObjectId) -> DaoResult<u64> {
+        let now = DateTime::now();
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! { "call_session_id": call_session_id, "closed_at": null },
+                doc! { "$set": { "closed_at": now, "updated_at": now } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
+}
@@ -0,0 +1,121 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::ReturnDocument;
+use roomler_ai_db::models::CallQuestion;
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct CallQuestionDao {
+    pub base: BaseDao<CallQuestion>,
+}
+
+impl CallQuestionDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CallQuestion::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        call_session_id: ObjectId,
+        author_id: ObjectId,
+        display_name: String,
+        content: String,
+    ) -> DaoResult<CallQuestion> {
+        let now = DateTime::now();
+        let question = CallQuestion {
+            id: None,
+            tenant_id,
+            room_id,
+            call_session_id,
+            author_id,
+            display_name,
+            content,
+            upvoter_ids: Vec::new(),
+            upvotes: 0,
+            answered_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&question).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// Questions of one call, most upvoted first.
+    pub async fn list_for_session(
+        &self,
+        call_session_id: ObjectId,
+    ) -> DaoResult<Vec<CallQuestion>> {
+        self.base
+            .find_many(
+                doc! { "call_session_id": call_session_id },
+                Some(doc! { "upvotes": -1, "created_at": 1 }),
+            )
+            .await
+    }
+
+    /// Add or remove the user's upvote. Upvoting twice (or removing an upvote
+    /// that isn't there) leaves the question unchanged.
+    pub async fn set_upvote(
+        &self,
+        room_id: ObjectId,
+        question_id: ObjectId,
+        user_id: ObjectId,
+        upvote: bool,
+    ) -> DaoResult<CallQuestion> {
+        let now = DateTime::now();
+        let (filter, update) = if upvote {
+            (
+                doc! { "_id": question_id, "room_id": room_id, "upvoter_ids": { "$ne": user_id } },
+                doc! {
+                    "$push": { "upvoter_ids": user_id },
+                    "$inc": { "upvotes": 1 },
+                    "$set": { "updated_at": now },
+                },
+            )
+        } else {
+            (
+                doc! { "_id": question_id, "room_id": room_id, "upvoter_ids": user_id },
+                doc! {
+                    "$pull": { "upvoter_ids": user_id },
+                    "$inc": { "upvotes": -1 },
+                    "$set": { "updated_at": now },
+                },
+            )
+        };
+        let updated = self
+            .base
+            .collection()
+            .find_one_and_update(filter, update)
+            .return_document(ReturnDocument::After)
+            .await?;
+        match updated {
+            Some(q) => Ok(q),
+            None => self
+                .base
+                .find_one(doc! { "_id": question_id, "room_id": room_id })
+                .await?
+                .ok_or(DaoError::NotFound),
+        }
+    }
+
+    pub async fn mark_answered(
+        &self,
+        room_id: ObjectId,
+        question_id: ObjectId,
+    ) -> DaoResult<CallQuestion> {
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .find_one_and_update(
+                doc! { "_id": question_id, "room_id": room_id },
+                doc! { "$set": { "answered_at": now, "updated_at": now } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+}
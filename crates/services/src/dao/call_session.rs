@@ -0,0 +1,404 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::{ReturnDocument, UpdateOptions};
+use roomler_ai_db::models::{
+    AssistantNote, BreakoutRoom, CallParticipantSession, CallSession, TranscriptSegment,
+};
+
+use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+
+pub struct CallSessionDao {
+    pub base: BaseDao<CallSession>,
+}
+
+impl CallSessionDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CallSession::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// The room's in-progress call, if any.
+    pub async fn find_active(&self, room_id: ObjectId) -> DaoResult<Option<CallSession>> {
+        self.base
+            .find_one(doc! { "room_id": room_id, "ended_at": null })
+            .await
+    }
+
+    /// Open a session for a new call. Starting a call that is already running
+    /// returns the existing session.
+    pub async fn start(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        started_by: ObjectId,
+    ) -> DaoResult<CallSession> {
+        if let Some(active) = self.find_active(room_id).await? {
+            return Ok(active);
+        }
+
+        let now = DateTime::now();
+        let session = CallSession {
+            id: None,
+            tenant_id,
+            room_id,
+            started_by,
+            started_at: now,
+            ended_at: None,
+            participant_count: 0,
+            peak_participants: 0,
+            participants: Vec::new(),
+            recording_ids: Vec::new(),
+            transcription_opt_outs: Vec::new(),
+            breakouts: Vec::new(),
+            assistant_notes: Vec::new(),
+            transcript: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&session).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// Add a participant entry to the active session and raise the peak.
+    /// A user who is already in the call is not counted twice.
+    pub async fn record_join(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        display_name: String,
+        device_type: String,
+    ) -> DaoResult<()> {
+        let now = DateTime::now();
+        let entry = CallParticipantSession {
+            user_id,
+            display_name,
+            device_type,
+            joined_at: now,
+            left_at: None,
+            duration: None,
+        };
+
+        let updated = self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "room_id": room_id,
+                    "ended_at": null,
+                    "participants": {
+                        "$not": { "$elemMatch": { "user_id": user_id, "left_at": null } }
+                    },
+                },
+                doc! {
+                    "$push": { "participants": bson::to_bson(&entry)? },
+                    "$inc": { "participant_count": 1 },
+                    "$set": { "updated_at": now },
+                },
+            )
+            .return_document(ReturnDocument::After)
+            .await?;
+
+        if let Some(session) = updated {
+            self.base
+                .collection()
+                .update_one(
+                    doc! { "_id": session.id },
+                    doc! { "$max": { "peak_participants": session.participant_count } },
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Close the user's open entry on the active session. Returns the
+    /// seconds the entry was open (0 if there was none).
+    pub async fn record_leave(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<u64> {
+        let Some(session) = self.find_active(room_id).await? else {
+            return Ok(0);
+        };
+        let Some(open) = session
+            .participants
+            .iter()
+            .find(|p| p.user_id == user_id && p.left_at.is_none())
+        else {
+            return Ok(0);
+        };
+
+        let now = DateTime::now();
+        let duration = seconds_between(open.joined_at, now);
+        let opts = UpdateOptions::builder()
+            .array_filters(vec![doc! { "elem.user_id": user_id, "elem.left_at": null }])
+            .build();
+        // Matching on the open entry makes a duplicate leave a no-op, so the
+        // count can't be decremented twice.
+        let result = self
+            .base
+            .collection()
+            .update_one(
+                doc! {
+                    "_id": session.id,
+                    "participants": { "$elemMatch": { "user_id": user_id, "left_at": null } },
+                },
+                doc! {
+                    "$set": {
+                        "participants.$[elem].left_at": now,
+                        "participants.$[elem].duration": duration,
+                        "updated_at": now,
+                    },
+                    "$inc": { "participant_count": -1 },
+                },
+            )
+            .with_options(opts)
+            .await?;
+        if result.modified_count == 0 {
+            return Ok(0);
+        }
+        Ok(duration.max(0) as u64)
+    }
+
+    /// End the active session, closing every entry still open. Returns the
+    /// participant-seconds of the entries it closed.
+    pub async fn end(&self, room_id: ObjectId) -> DaoResult<u64> {
+        let Some(mut session) = self.find_active(room_id).await? else {
+            return Ok(0);
+        };
+
+        let now = DateTime::now();
+        let mut closed = 0;
+        for p in session
+            .participants
+            .iter_mut()
+            .filter(|p| p.left_at.is_none())
+        {
+            let duration = seconds_between(p.joined_at, now);
+            p.left_at = Some(now);
+            p.duration = Some(duration);
+            closed += duration.max(0) as u64;
+        }
+        let ended = self
+            .base
+            .update_one(
+                doc! { "_id": session.id, "ended_at": null },
+                doc! {
+                    "$set": {
+                        "ended_at": now,
+                        "participant_count": 0,
+                        "participants": bson::to_bson(&session.participants)?,
+                    }
+                },
+            )
+            .await?;
+        // A concurrent end already closed (and counted) the entries.
+        Ok(if ended { closed } else { 0 })
+    }
+
+    /// Link a recording to the room's in-progress call. Returns whether a
+    /// call was running.
+    pub async fn attach_recording(
+        &self,
+        room_id: ObjectId,
+        recording_id: ObjectId,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "room_id": room_id, "ended_at": null },
+                doc! { "$addToSet": { "recording_ids": recording_id } },
+            )
+            .await
+    }
+
+    /// Opt a user out of (or back into) transcription on the active call.
+    /// Returns `None` when there is no call in progress.
+    pub async fn set_transcription_opt_out(
+        &self,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        opted_out: bool,
+    ) -> DaoResult<Option<CallSession>> {
+        let op = if opted_out { "$addToSet" } else { "$pull" };
+        let update = doc! {
+            op: { "transcription_opt_outs": user_id },
+            "$set": { "updated_at": DateTime::now() },
+        };
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(doc! { "room_id": room_id, "ended_at": null }, update)
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Add a meeting assistant answer to the active call's notes. Returns
+    /// whether a call was running.
+    pub async fn add_assistant_note(
+        &self,
+        room_id: ObjectId,
+        note: &AssistantNote,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "room_id": room_id, "ended_at": null },
+                doc! { "$push": { "assistant_notes": bson::to_bson(note)? } },
+            )
+            .await
+    }
+
+    /// Add final lines to the active call's transcript, keeping the last
+    /// `keep`. Returns whether a call was running.
+    pub async fn append_transcript(
+        &self,
+        room_id: ObjectId,
+        segments: &[TranscriptSegment],
+        keep: usize,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "room_id": room_id, "ended_at": null },
+                doc! { "$push": { "transcript": {
+                    "$each": bson::to_bson(segments)?,
+                    "$slice": -(keep as i64),
+                } } },
+            )
+            .await
+    }
+
+    /// The call a recording was made in.
+    pub async fn find_by_recording(
+        &self,
+        tenant_id: ObjectId,
+        recording_id: ObjectId,
+    ) -> DaoResult<Option<CallSession>> {
+        self.base
+            .find_one(doc! { "tenant_id": tenant_id, "recording_ids": recording_id })
+            .await
+    }
+
+    /// Open breakout rooms on the active call. Returns `false` when there is
+    /// no call in progress or it already has open breakouts.
+    pub async fn open_breakouts(
+        &self,
+        room_id: ObjectId,
+        breakouts: &[BreakoutRoom],
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! {
+                    "room_id": room_id,
+                    "ended_at": null,
+                    "breakouts": { "$not": { "$elemMatch": { "closed_at": null } } },
+                },
+                doc! { "$push": { "breakouts": { "$each": bson::to_bson(breakouts)? } } },
+            )
+            .await
+    }
+
+    /// The active call's open breakout rooms.
+    pub async fn find_open_breakouts(&self, room_id: ObjectId) -> DaoResult<Vec<BreakoutRoom>> {
+        Ok(self
+            .find_active(room_id)
+            .await?
+            .map(|s| {
+                s.breakouts
+                    .into_iter()
+                    .filter(|b| b.closed_at.is_none())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Move a user into an open breakout room, out of any other. Returns
+    /// `false` when the breakout isn't open.
+    pub async fn assign_breakout(
+        &self,
+        room_id: ObjectId,
+        breakout_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<bool> {
+        let filter = doc! {
+            "room_id": room_id,
+            "ended_at": null,
+            "breakouts": { "$elemMatch": { "id": breakout_id, "closed_at": null } },
+        };
+        if self.base.count(filter.clone()).await? == 0 {
+            return Ok(false);
+        }
+        self.base
+            .collection()
+            .update_one(
+                filter.clone(),
+                doc! { "$pull": { "breakouts.$[].user_ids": user_id } },
+            )
+            .await?;
+        let opts = UpdateOptions::builder()
+            .array_filters(vec![doc! { "b.id": breakout_id }])
+            .build();
+        self.base
+            .collection()
+            .update_one(
+                filter,
+                doc! {
+                    "$addToSet": { "breakouts.$[b].user_ids": user_id },
+                    "$set": { "updated_at": DateTime::now() },
+                },
+            )
+            .with_options(opts)
+            .await?;
+        Ok(true)
+    }
+
+    /// Close the active call's open breakout rooms and return them.
+    pub async fn close_breakouts(&self, room_id: ObjectId) -> DaoResult<Vec<BreakoutRoom>> {
+        let open = self.find_open_breakouts(room_id).await?;
+        if open.is_empty() {
+            return Ok(open);
+        }
+        let now = DateTime::now();
+        let opts = UpdateOptions::builder()
+            .array_filters(vec![doc! { "b.closed_at": null }])
+            .build();
+        self.base
+            .collection()
+            .update_one(
+                doc! { "room_id": room_id, "ended_at": null },
+                doc! { "$set": { "breakouts.$[b].closed_at": now, "updated_at": now } },
+            )
+            .with_options(opts)
+            .await?;
+        Ok(open)
+    }
+
+    /// Past and current calls in a room, newest first.
+    pub async fn list_for_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        params: &PaginationParams,
+    ) -> DaoResult<PaginatedResult<CallSession>> {
+        self.base
+            .find_paginated(
+                doc! { "tenant_id": tenant_id, "room_id": room_id },
+                Some(doc! { "started_at": -1 }),
+                params,
+            )
+            .await
+    }
+
+    /// Every call in a room, newest first.
+    pub async fn find_all_for_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+    ) -> DaoResult<Vec<CallSession>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "room_id": room_id },
+                Some(doc! { "started_at": -1 }),
+            )
+            .await
+    }
+}
+
+fn seconds_between(from: DateTime, to: DateTime) -> i64 {
+    (to.timestamp_millis() - from.timestamp_millis()) / 1000
+}
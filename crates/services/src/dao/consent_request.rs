@@ -11,7 +11,7 @@ pub struct ConsentRequestDao {
 impl ConsentRequestDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, ConsentRequest::COLLECTION),
+            base: BaseDao::new(db, ConsentRequest::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -0,0 +1,62 @@
+use bson::{doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::CustomEmoji;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct CustomEmojiDao {
+    pub base: BaseDao<CustomEmoji>,
+}
+
+impl CustomEmojiDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, CustomEmoji::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Insert an emoji; a taken name is a `DuplicateKey`.
+    pub async fn create(&self, emoji: &CustomEmoji) -> DaoResult<CustomEmoji> {
+        let id = self.base.insert_one(emoji).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// The tenant's emoji pack, by name.
+    pub async fn list_by_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<CustomEmoji>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id }, Some(doc! { "name": 1 }))
+            .await
+    }
+
+    pub async fn find_by_name(
+        &self,
+        tenant_id: ObjectId,
+        name: &str,
+    ) -> DaoResult<Option<CustomEmoji>> {
+        self.base
+            .find_one(doc! { "tenant_id": tenant_id, "name": name })
+            .await
+    }
+
+    pub async fn find_in_tenant(
+        &self,
+        tenant_id: ObjectId,
+        emoji_id: ObjectId,
+    ) -> DaoResult<Option<CustomEmoji>> {
+        self.base
+            .find_one(doc! { "_id": emoji_id, "tenant_id": tenant_id })
+            .await
+    }
+
+    pub async fn count(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base.count(doc! { "tenant_id": tenant_id }).await
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, emoji_id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": emoji_id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,54 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::ReturnDocument;
+use roomler_ai_db::models::{FeatureFlag, FlagMode};
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct FeatureFlagDao {
+    pub base: BaseDao<FeatureFlag>,
+}
+
+impl FeatureFlagDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, FeatureFlag::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<FeatureFlag>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id }, Some(doc! { "key": 1 }))
+            .await
+    }
+
+    /// Set the tenant's mode for `key`, creating the flag on first use.
+    pub async fn set(
+        &self,
+        tenant_id: ObjectId,
+        key: &str,
+        mode: FlagMode,
+        percentage: u8,
+        updated_by: ObjectId,
+    ) -> DaoResult<FeatureFlag> {
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .find_one_and_update(
+                doc! { "tenant_id": tenant_id, "key": key },
+                doc! {
+                    "$set": {
+                        "mode": bson::to_bson(&mode)?,
+                        "percentage": i32::from(percentage),
+                        "updated_by": updated_by,
+                        "updated_at": now,
+                    },
+                    "$setOnInsert": { "created_at": now },
+                },
+            )
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+}
@@ -1,9 +1,9 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::recording::{StorageProvider, Visibility};
-use roomler_ai_db::models::{self, FileContext, ScanStatus};
+use roomler_ai_db::models::{self, FileContext, ScanResult, ScanStatus, Thumbnail};
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoResult, ListOptions, PaginatedResult, PaginationParams};
 
 pub struct FileDao {
     pub base: BaseDao<models::File>,
@@ -12,7 +12,7 @@ pub struct FileDao {
 impl FileDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, models::File::COLLECTION),
+            base: BaseDao::new(db, models::File::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -28,6 +28,7 @@ impl FileDao {
         storage_bucket: String,
         storage_key: String,
         url: String,
+        scan_status: ScanStatus,
     ) -> DaoResult<models::File> {
         let now = DateTime::now();
         let file = models::File {
@@ -52,7 +53,8 @@ impl FileDao {
             previous_version_id: None,
             is_current_version: true,
             external_source: None,
-            scan_status: ScanStatus::Pending,
+            scan_status,
+            scan_result: None,
             visibility: Visibility::Private,
             recognized_content: None,
             created_at: now,
@@ -69,9 +71,10 @@ impl FileDao {
         tenant_id: ObjectId,
         room_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<models::File>> {
         self.base
-            .find_paginated(
+            .find_listed(
                 doc! {
                     "tenant_id": tenant_id,
                     "context.room_id": room_id,
@@ -79,6 +82,7 @@ impl FileDao {
                 },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
             )
             .await
     }
@@ -106,20 +110,113 @@ impl FileDao {
         &self,
         tenant_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<models::File>> {
         self.base
-            .find_paginated(
+            .find_listed(
                 doc! {
                     "tenant_id": tenant_id,
+                    "context.context_type": { "$nin": ["background", "emoji"] },
                     "deleted_at": null,
                 },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
+            )
+            .await
+    }
+
+    /// Bytes of the tenant's live files, for the plan's storage quota.
+    pub async fn storage_used(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base
+            .sum(doc! { "tenant_id": tenant_id, "deleted_at": null }, "size")
+            .await
+    }
+
+    /// The tenant's virtual background library, newest first.
+    pub async fn find_backgrounds(&self, tenant_id: ObjectId) -> DaoResult<Vec<models::File>> {
+        self.base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "context.context_type": "background",
+                    "deleted_at": null,
+                },
+                Some(doc! { "created_at": -1 }),
+            )
+            .await
+    }
+
+    pub async fn find_background(
+        &self,
+        tenant_id: ObjectId,
+        file_id: ObjectId,
+    ) -> DaoResult<Option<models::File>> {
+        self.base
+            .find_one(doc! {
+                "_id": file_id,
+                "tenant_id": tenant_id,
+                "context.context_type": "background",
+                "deleted_at": null,
+            })
+            .await
+    }
+
+    pub async fn set_scan_result(
+        &self,
+        id: ObjectId,
+        status: ScanStatus,
+        result: &ScanResult,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": {
+                    "scan_status": bson::to_bson(&status)?,
+                    "scan_result": bson::to_bson(result)?,
+                    "updated_at": DateTime::now(),
+                } },
+            )
+            .await
+    }
+
+    pub async fn set_thumbnails(&self, id: ObjectId, thumbnails: &[Thumbnail]) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": { "thumbnails": bson::to_bson(thumbnails)? } },
             )
             .await
     }
 
-    pub async fn soft_delete(&self, tenant_id: ObjectId, file_id: ObjectId) -> DaoResult<bool> {
-        self.base.soft_delete_in_tenant(tenant_id, file_id).await
+    /// Soft-delete a file. Returns the bytes freed: its size, or 0 if it was
+    /// already deleted.
+    pub async fn soft_delete(&self, tenant_id: ObjectId, file_id: ObjectId) -> DaoResult<u64> {
+        let now = DateTime::now();
+        let deleted = self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! { "_id": file_id, "tenant_id": tenant_id, "deleted_at": null },
+                doc! { "$set": { "deleted_at": now, "updated_at": now } },
+            )
+            .await?;
+        Ok(deleted.map_or(0, |f| f.size))
+    }
+
+    /// Soft-delete several files at once, e.g. attachments of purged messages.
+    /// Returns the bytes freed.
+    pub async fn soft_delete_many(&self, tenant_id: ObjectId, ids: &[ObjectId]) -> DaoResult<u64> {
+        let filter = doc! { "_id": { "$in": ids }, "tenant_id": tenant_id, "deleted_at": null };
+        let freed = self.base.sum(filter.clone(), "size").await?;
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .update_many(
+                filter,
+                doc! { "$set": { "deleted_at": now, "updated_at": now } },
+            )
+            .await?;
+        Ok(freed)
     }
 }
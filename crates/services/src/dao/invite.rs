@@ -2,7 +2,7 @@ use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::{Invite, InviteStatus};
 
-use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoError, DaoResult, ListOptions, PaginatedResult, PaginationParams};
 
 pub struct InviteDao {
     pub base: BaseDao<Invite>,
@@ -13,12 +13,14 @@ pub struct CreateInviteParams {
     pub max_uses: Option<u32>,
     pub expires_in_hours: Option<u64>,
     pub assign_role_ids: Vec<ObjectId>,
+    /// Room the invitee joins on acceptance, in addition to the tenant.
+    pub room_id: Option<ObjectId>,
 }
 
 impl InviteDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Invite::COLLECTION),
+            base: BaseDao::new(db, Invite::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -45,7 +47,7 @@ impl InviteDao {
         let invite = Invite {
             id: None,
             tenant_id,
-            room_id: None,
+            room_id: params.room_id,
             code,
             inviter_id,
             target_email: params.target_email,
@@ -74,12 +76,14 @@ impl InviteDao {
         &self,
         tenant_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<Invite>> {
         self.base
-            .find_paginated(
+            .find_listed(
                 doc! { "tenant_id": tenant_id },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
             )
             .await
     }
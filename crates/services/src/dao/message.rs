@@ -1,61 +1,66 @@
+use std::collections::HashSet;
+
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::{
     AuthorType, ContentType, Mentions, Message, MessageAttachment, MessageType, ReactionSummary,
 };
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{
+    BaseDao, DaoError, DaoResult, ListOptions, PaginatedResult, PaginationParams, sort_direction,
+};
+
+/// Oldest edits are dropped past this many stored versions.
+const MAX_EDIT_HISTORY: i32 = 50;
+
+/// A message to post, from a user request, the scheduler or an integration.
+#[derive(Debug, Clone)]
+pub struct CreateMessageParams {
+    pub tenant_id: ObjectId,
+    pub room_id: ObjectId,
+    /// The user, bot, webhook or slash command posting the message.
+    pub author_id: ObjectId,
+    pub author_type: AuthorType,
+    pub author_name: Option<String>,
+    pub content: String,
+    pub thread_id: Option<ObjectId>,
+    pub referenced_message_id: Option<ObjectId>,
+    pub nonce: Option<String>,
+    pub mentions: Option<Mentions>,
+    pub attachments: Vec<MessageAttachment>,
+    pub is_silent: bool,
+}
 
 pub struct MessageDao {
     pub base: BaseDao<Message>,
+    /// Cold storage: threads whose last activity is older than the tenant's
+    /// archive threshold. Read-only, and merged after `base` in listings.
+    pub archive: BaseDao<Message>,
 }
 
 impl MessageDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Message::COLLECTION),
+            base: BaseDao::new(db, Message::COLLECTION).tenant_scoped(),
+            archive: BaseDao::new(db, Message::ARCHIVE_COLLECTION).tenant_scoped(),
         }
     }
 
-    #[allow(clippy::too_many_arguments)]
-    pub async fn create(
-        &self,
-        tenant_id: ObjectId,
-        room_id: ObjectId,
-        author_id: ObjectId,
-        content: String,
-        thread_id: Option<ObjectId>,
-        referenced_message_id: Option<ObjectId>,
-        nonce: Option<String>,
-        mentions: Option<Mentions>,
-    ) -> DaoResult<Message> {
-        self.create_with_attachments(
+    pub async fn create(&self, params: CreateMessageParams) -> DaoResult<Message> {
+        let CreateMessageParams {
             tenant_id,
             room_id,
             author_id,
+            author_type,
+            author_name,
             content,
             thread_id,
             referenced_message_id,
             nonce,
             mentions,
-            Vec::new(),
-        )
-        .await
-    }
-
-    #[allow(clippy::too_many_arguments)]
-    pub async fn create_with_attachments(
-        &self,
-        tenant_id: ObjectId,
-        room_id: ObjectId,
-        author_id: ObjectId,
-        content: String,
-        thread_id: Option<ObjectId>,
-        referenced_message_id: Option<ObjectId>,
-        nonce: Option<String>,
-        mentions: Option<Mentions>,
-        attachments: Vec<MessageAttachment>,
-    ) -> DaoResult<Message> {
+            attachments,
+            is_silent,
+        } = params;
         let now = DateTime::now();
         let message_type = if referenced_message_id.is_some() {
             MessageType::Reply
@@ -71,7 +76,8 @@ impl MessageDao {
             is_thread_root: false,
             thread_metadata: None,
             author_id,
-            author_type: AuthorType::User,
+            author_type,
+            author_name,
             content,
             content_type: ContentType::Markdown,
             message_type,
@@ -81,13 +87,16 @@ impl MessageDao {
             reaction_summary: Vec::new(),
             referenced_message_id,
             is_pinned: false,
+            is_silent,
             is_edited: false,
             edited_at: None,
+            edit_history: Vec::new(),
             nonce,
             readby: vec![author_id], // Author has read their own message
             created_at: now,
             updated_at: now,
             deleted_at: None,
+            deleted_by: None,
         };
 
         let id = self.base.insert_one(&message).await?;
@@ -100,12 +109,19 @@ impl MessageDao {
         self.base.find_by_id(id).await
     }
 
+    /// Top-level messages in a room, newest first. `include_deleted` also
+    /// returns soft-deleted ones (moderator view).
     pub async fn find_in_room(
         &self,
         room_id: ObjectId,
+        include_deleted: bool,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<Message>> {
-        let mut filter = doc! { "room_id": room_id, "deleted_at": null, "thread_id": null };
+        let mut filter = doc! { "room_id": room_id, "thread_id": null };
+        if !include_deleted {
+            filter.insert("deleted_at", bson::Bson::Null);
+        }
 
         // Support cursor-based pagination via `before` timestamp
         if let Some(ref before) = params.before
@@ -114,8 +130,288 @@ impl MessageDao {
             filter.insert("created_at", doc! { "$lt": dt });
         }
 
+        // Every archived top-level message is older than every hot one, so
+        // the archive follows the hot collection newest first and precedes
+        // it oldest first.
+        let ascending = options
+            .sort
+            .as_ref()
+            .and_then(|s| s.values().next())
+            .is_some_and(|v| sort_direction(v) > 0);
+        let sort = Some(doc! { "created_at": -1 });
+        if ascending {
+            self.archive
+                .find_listed_then(&self.base, filter, sort, params, options)
+                .await
+        } else {
+            self.base
+                .find_listed_then(&self.archive, filter, sort, params, options)
+                .await
+        }
+    }
+
+    /// Every message of a room, thread replies and deleted ones included,
+    /// oldest first. For archival exports.
+    pub async fn find_all_in_room(&self, room_id: ObjectId) -> DaoResult<Vec<Message>> {
+        let filter = doc! { "room_id": room_id };
+        let sort = Some(doc! { "created_at": 1 });
+        let mut messages = self.archive.find_many(filter.clone(), sort.clone()).await?;
+        messages.extend(self.base.find_many(filter, sort).await?);
+        Ok(messages)
+    }
+
+    /// Full-text search over top-level messages, hot ones ranked first and
+    /// the archive filling any remaining slots.
+    pub async fn search(
+        &self,
+        query: &str,
+        filter: bson::Document,
+        limit: i64,
+    ) -> DaoResult<Vec<Message>> {
+        let mut found = self.base.text_search(query, filter.clone(), limit).await?;
+        let remaining = limit - found.len() as i64;
+        if remaining > 0 {
+            found.extend(self.archive.text_search(query, filter, remaining).await?);
+        }
+        Ok(found)
+    }
+
+    /// Whether `id` has been moved to the archive. Archived threads take no
+    /// new replies.
+    pub async fn is_archived(&self, id: ObjectId) -> DaoResult<bool> {
+        Ok(self.archive.count(doc! { "_id": id }).await? > 0)
+    }
+
+    /// A message of the tenant, hot or archived.
+    pub async fn find_any_in_tenant(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+    ) -> DaoResult<Message> {
+        match self.base.find_by_id_in_tenant(tenant_id, id).await {
+            Err(DaoError::NotFound) => self.archive.find_by_id_in_tenant(tenant_id, id).await,
+            other => other,
+        }
+    }
+
+    /// Move up to `limit` of the tenant's threads (root and replies) created
+    /// before `cutoff` into the archive. Returns how many messages moved.
+    pub async fn archive_before(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+        limit: i64,
+    ) -> DaoResult<u64> {
+        use futures::TryStreamExt;
+
+        let hot = self.base.collection().clone_with_type::<bson::Document>();
+        let cold = self
+            .archive
+            .collection()
+            .clone_with_type::<bson::Document>();
+
+        // A thread still being replied to stays hot, and so does every newer
+        // one: the hot collection always holds a tenant's newest top-level
+        // messages, which keeps merged listings in order.
+        let held = hot
+            .find_one(doc! {
+                "tenant_id": tenant_id,
+                "thread_id": null,
+                "created_at": { "$lt": cutoff },
+                "thread_metadata.last_reply_at": { "$gte": cutoff },
+            })
+            .sort(doc! { "created_at": 1 })
+            .projection(doc! { "created_at": 1 })
+            .await?;
+        let cutoff = held
+            .and_then(|d| d.get_datetime("created_at").ok().copied())
+            .unwrap_or(cutoff);
+
+        let roots: Vec<bson::Document> = hot
+            .find(doc! {
+                "tenant_id": tenant_id,
+                "thread_id": null,
+                "created_at": { "$lt": cutoff },
+            })
+            .projection(doc! { "_id": 1 })
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+        let root_ids: Vec<ObjectId> = roots
+            .iter()
+            .filter_map(|d| d.get_object_id("_id").ok())
+            .collect();
+        if root_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let docs: Vec<bson::Document> = hot
+            .find(doc! { "$or": [
+                { "_id": { "$in": &root_ids[..] } },
+                { "thread_id": { "$in": &root_ids[..] } },
+            ]})
+            .await?
+            .try_collect()
+            .await?;
+        let ids: Vec<ObjectId> = docs
+            .iter()
+            .filter_map(|d| d.get_object_id("_id").ok())
+            .collect();
+
+        // A sweep interrupted between copy and delete leaves copies behind;
+        // skip those rather than fail the insert on duplicate ids.
+        let copied: HashSet<ObjectId> = cold
+            .find(doc! { "_id": { "$in": &ids[..] } })
+            .projection(doc! { "_id": 1 })
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?
+            .iter()
+            .filter_map(|d| d.get_object_id("_id").ok())
+            .collect();
+        let fresh: Vec<bson::Document> = docs
+            .into_iter()
+            .filter(|d| d.get_object_id("_id").is_ok_and(|id| !copied.contains(&id)))
+            .collect();
+        if !fresh.is_empty() {
+            cold.insert_many(fresh).await?;
+        }
+
         self.base
-            .find_paginated(filter, Some(doc! { "created_at": -1 }), params)
+            .hard_delete(doc! { "_id": { "$in": &ids[..] } })
+            .await
+    }
+
+    /// Ids of up to `limit` messages created before `cutoff` that retention
+    /// may purge: not pinned and not in one of `held_room_ids`.
+    pub async fn find_expired_ids(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+        held_room_ids: &[ObjectId],
+        limit: i64,
+    ) -> DaoResult<Vec<ObjectId>> {
+        self.find_ids(
+            doc! {
+                "tenant_id": tenant_id,
+                "created_at": { "$lt": cutoff },
+                "is_pinned": { "$ne": true },
+                "room_id": { "$nin": held_room_ids },
+            },
+            limit,
+        )
+        .await
+    }
+
+    /// Ids of up to `limit` messages soft-deleted before `cutoff`, outside
+    /// `held_room_ids`: past their restore window.
+    pub async fn find_deleted_ids(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+        held_room_ids: &[ObjectId],
+        limit: i64,
+    ) -> DaoResult<Vec<ObjectId>> {
+        self.find_ids(
+            doc! {
+                "tenant_id": tenant_id,
+                "deleted_at": { "$lt": cutoff },
+                "room_id": { "$nin": held_room_ids },
+            },
+            limit,
+        )
+        .await
+    }
+
+    /// Ids of up to `limit` messages of a room, for purging the room.
+    pub async fn find_room_ids(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        limit: i64,
+    ) -> DaoResult<Vec<ObjectId>> {
+        self.find_ids(doc! { "tenant_id": tenant_id, "room_id": room_id }, limit)
+            .await
+    }
+
+    async fn find_ids(&self, filter: bson::Document, limit: i64) -> DaoResult<Vec<ObjectId>> {
+        use futures::TryStreamExt;
+
+        let mut ids = Vec::new();
+        // Hot first; the archive only once the hot backlog is cleared.
+        for dao in [&self.base, &self.archive] {
+            let docs: Vec<bson::Document> = dao
+                .collection()
+                .clone_with_type::<bson::Document>()
+                .find(filter.clone())
+                .projection(doc! { "_id": 1 })
+                .limit(limit)
+                .await?
+                .try_collect()
+                .await?;
+            ids.extend(docs.iter().filter_map(|d| d.get_object_id("_id").ok()));
+            if !ids.is_empty() {
+                break;
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Permanently delete messages (retention purge), hot or archived.
+    pub async fn delete_by_ids(&self, tenant_id: ObjectId, ids: &[ObjectId]) -> DaoResult<u64> {
+        let filter = doc! { "tenant_id": tenant_id, "_id": { "$in": ids } };
+        let hot = self.base.hard_delete(filter.clone()).await?;
+        Ok(hot + self.archive.hard_delete(filter).await?)
+    }
+
+    /// Files attached to any of `ids`.
+    pub async fn attachment_file_ids(
+        &self,
+        tenant_id: ObjectId,
+        ids: &[ObjectId],
+    ) -> DaoResult<Vec<ObjectId>> {
+        self.distinct_file_ids(doc! { "tenant_id": tenant_id, "_id": { "$in": ids } })
+            .await
+    }
+
+    /// Those of `file_ids` still attached to some message.
+    pub async fn attached_file_ids(
+        &self,
+        tenant_id: ObjectId,
+        file_ids: &[ObjectId],
+    ) -> DaoResult<HashSet<ObjectId>> {
+        let ids = self
+            .distinct_file_ids(doc! {
+                "tenant_id": tenant_id,
+                "attachments.file_id": { "$in": file_ids },
+            })
+            .await?;
+        let wanted: HashSet<&ObjectId> = file_ids.iter().collect();
+        Ok(ids.into_iter().filter(|id| wanted.contains(id)).collect())
+    }
+
+    async fn distinct_file_ids(&self, filter: bson::Document) -> DaoResult<Vec<ObjectId>> {
+        let mut ids = Vec::new();
+        for dao in [&self.base, &self.archive] {
+            let values = dao
+                .collection()
+                .distinct("attachments.file_id", filter.clone())
+                .await?;
+            ids.extend(values.iter().filter_map(|v| v.as_object_id()));
+        }
+        ids.sort();
+        ids.dedup();
+        Ok(ids)
+    }
+
+    /// Undo a soft delete.
+    pub async fn restore(&self, tenant_id: ObjectId, message_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": message_id, "tenant_id": tenant_id, "deleted_at": { "$ne": null } },
+                doc! { "$set": { "deleted_at": null, "deleted_by": null } },
+            )
             .await
     }
 
@@ -124,24 +420,30 @@ impl MessageDao {
         thread_id: ObjectId,
         params: &PaginationParams,
     ) -> DaoResult<PaginatedResult<Message>> {
-        self.base
-            .find_paginated(
+        // Replies are archived with their root, so an archived thread's
+        // replies all precede any that raced the move into the hot collection.
+        self.archive
+            .find_listed_then(
+                &self.base,
                 doc! { "thread_id": thread_id, "deleted_at": null },
                 Some(doc! { "created_at": 1 }),
                 params,
+                &ListOptions::default(),
             )
             .await
     }
 
     pub async fn find_pinned(&self, room_id: ObjectId) -> DaoResult<Vec<Message>> {
-        self.base
-            .find_many(
-                doc! { "room_id": room_id, "is_pinned": true, "deleted_at": null },
-                Some(doc! { "created_at": -1 }),
-            )
-            .await
+        let filter = doc! { "room_id": room_id, "is_pinned": true, "deleted_at": null };
+        let sort = Some(doc! { "created_at": -1 });
+        let mut messages = self.base.find_many(filter.clone(), sort.clone()).await?;
+        messages.extend(self.archive.find_many(filter, sort).await?);
+        Ok(messages)
     }
 
+    /// Replace an author's message content, pushing the previous version onto
+    /// `edit_history`. The update is conditional on the content read, so a
+    /// concurrent edit can't record the wrong previous version.
     pub async fn update_content(
         &self,
         tenant_id: ObjectId,
@@ -149,25 +451,60 @@ impl MessageDao {
         author_id: ObjectId,
         content: String,
     ) -> DaoResult<bool> {
+        let filter = doc! {
+            "_id": message_id,
+            "tenant_id": tenant_id,
+            "author_id": author_id,
+            "deleted_at": null,
+        };
+        let Some(current) = self.base.find_one(filter.clone()).await? else {
+            return Ok(false);
+        };
+        if current.content == content {
+            return Ok(true);
+        }
+
+        let now = DateTime::now();
+        let mut filter = filter;
+        filter.insert("content", current.content.as_str());
         self.base
             .update_one(
-                doc! {
-                    "_id": message_id,
-                    "tenant_id": tenant_id,
-                    "author_id": author_id,
-                    "deleted_at": null,
-                },
+                filter,
                 doc! {
                     "$set": {
                         "content": content,
                         "is_edited": true,
-                        "edited_at": DateTime::now(),
-                    }
+                        "edited_at": now,
+                    },
+                    "$push": {
+                        "edit_history": {
+                            "$each": [{ "content": current.content, "edited_at": now }],
+                            "$slice": -MAX_EDIT_HISTORY,
+                        },
+                    },
                 },
             )
             .await
     }
 
+    /// Soft-delete a message, recording who deleted it.
+    pub async fn soft_delete(
+        &self,
+        tenant_id: ObjectId,
+        message_id: ObjectId,
+        deleted_by: ObjectId,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": message_id, "tenant_id": tenant_id, "deleted_at": null },
+                doc! { "$set": {
+                    "deleted_at": DateTime::now(),
+                    "deleted_by": deleted_by,
+                }},
+            )
+            .await
+    }
+
     pub async fn toggle_pin(
         &self,
         tenant_id: ObjectId,
@@ -189,22 +526,7 @@ impl MessageDao {
         reply_author_id: ObjectId,
     ) -> DaoResult<bool> {
         let now = DateTime::now();
-        // First, ensure thread_metadata is not null (MongoDB $inc/$addToSet fail on null subdocs)
-        let _ = self
-            .base
-            .collection()
-            .update_one(
-                doc! { "_id": parent_id, "thread_metadata": null },
-                doc! { "$set": {
-                    "thread_metadata": {
-                        "reply_count": 0_i32,
-                        "last_reply_at": null,
-                        "last_reply_user_id": null,
-                        "participant_ids": [],
-                    },
-                }},
-            )
-            .await;
+        self.ensure_thread_metadata(parent_id).await;
         // Now safely increment/update the nested fields
         self.base
             .update_one(
@@ -220,12 +542,84 @@ impl MessageDao {
                     },
                     "$addToSet": {
                         "thread_metadata.participant_ids": reply_author_id,
+                        "thread_metadata.follower_ids": reply_author_id,
                     },
                 },
             )
             .await
     }
 
+    /// Initialise a null `thread_metadata` (MongoDB `$inc`/`$addToSet` fail on
+    /// null subdocs). The root author starts out following the thread.
+    async fn ensure_thread_metadata(&self, parent_id: ObjectId) {
+        let Ok(parent) = self.base.find_by_id(parent_id).await else {
+            return;
+        };
+        if parent.thread_metadata.is_some() {
+            return;
+        }
+        let _ = self
+            .base
+            .collection()
+            .update_one(
+                doc! { "_id": parent_id, "thread_metadata": null },
+                doc! { "$set": {
+                    "thread_metadata": {
+                        "reply_count": 0_i32,
+                        "last_reply_at": null,
+                        "last_reply_user_id": null,
+                        "participant_ids": [],
+                        "follower_ids": [parent.author_id],
+                    },
+                }},
+            )
+            .await;
+    }
+
+    /// Recompute `reply_count` and the last-reply fields from the thread's
+    /// remaining replies, after one was deleted.
+    pub async fn refresh_thread_metadata(&self, parent_id: ObjectId) -> DaoResult<bool> {
+        let filter = doc! { "thread_id": parent_id, "deleted_at": null };
+        let reply_count = self.base.count(filter.clone()).await?;
+        let last = self
+            .base
+            .collection()
+            .find_one(filter)
+            .sort(doc! { "created_at": -1 })
+            .await?;
+
+        self.base
+            .update_one(
+                doc! { "_id": parent_id, "thread_metadata": { "$ne": null } },
+                doc! { "$set": {
+                    "thread_metadata.reply_count": reply_count as i64,
+                    "thread_metadata.last_reply_at": last.as_ref().map(|m| m.created_at),
+                    "thread_metadata.last_reply_user_id": last.map(|m| m.author_id),
+                }},
+            )
+            .await
+    }
+
+    /// Follow or unfollow a thread root. Followers get a notification for
+    /// every new reply.
+    pub async fn set_thread_follow(
+        &self,
+        tenant_id: ObjectId,
+        parent_id: ObjectId,
+        user_id: ObjectId,
+        follow: bool,
+    ) -> DaoResult<bool> {
+        self.ensure_thread_metadata(parent_id).await;
+        let update = if follow {
+            doc! { "$addToSet": { "thread_metadata.follower_ids": user_id } }
+        } else {
+            doc! { "$pull": { "thread_metadata.follower_ids": user_id } }
+        };
+        self.base
+            .update_one(doc! { "_id": parent_id, "tenant_id": tenant_id }, update)
+            .await
+    }
+
     /// Mark messages in a room as read by a user
     pub async fn mark_read(
         &self,
@@ -248,6 +642,21 @@ impl MessageDao {
         Ok(result.modified_count)
     }
 
+    /// Point the attachments of `file_id` at its preview, for messages
+    /// sent before the preview was rendered.
+    pub async fn set_attachment_thumbnail(&self, file_id: ObjectId, url: &str) -> DaoResult<u64> {
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! { "attachments.file_id": file_id },
+                doc! { "$set": { "attachments.$[a].thumbnail_url": url } },
+            )
+            .array_filters(vec![doc! { "a.file_id": file_id }])
+            .await?;
+        Ok(result.modified_count)
+    }
+
     /// Count unread messages for a user in a room
     pub async fn unread_count(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<u64> {
         let count = self
@@ -257,6 +666,7 @@ impl MessageDao {
                 "room_id": room_id,
                 "deleted_at": null,
                 "thread_id": null,
+                "is_silent": { "$ne": true },
                 "readby": { "$ne": user_id },
             })
             .await?;
@@ -277,6 +687,7 @@ impl MessageDao {
                 "room_id": { "$in": room_ids.iter().map(|id| Bson::ObjectId(*id)).collect::<Vec<_>>() },
                 "deleted_at": null,
                 "thread_id": null,
+                "is_silent": { "$ne": true },
                 "readby": { "$ne": user_id },
             }},
             doc! { "$group": {
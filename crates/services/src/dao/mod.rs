@@ -1,8 +1,17 @@
 pub mod agent;
 pub mod agent_crash;
 pub mod agent_log;
+pub mod audit_log;
 pub mod base;
+pub mod bot_token;
+pub mod calendar_connection;
+pub mod call_debug_event;
+pub mod call_poll;
+pub mod call_question;
+pub mod call_session;
 pub mod consent_request;
+pub mod custom_emoji;
+pub mod feature_flag;
 pub mod file;
 pub mod invite;
 pub mod message;
@@ -12,14 +21,23 @@ pub mod overlay_node;
 pub mod push_subscription;
 pub mod reaction;
 pub mod recording;
+pub mod recording_consent;
 pub mod remote_audit;
 pub mod remote_session;
 pub mod role;
 pub mod room;
+pub mod scheduled_message;
+pub mod settings_override;
+pub mod slash_command;
+pub mod stripe_event;
 pub mod tenant;
+pub mod tenant_domain;
 pub mod tunnel_audit;
 pub mod tunnel_client;
 pub mod tunnel_policy;
+pub mod usage;
+pub mod webhook;
+pub mod whiteboard;
 
 pub mod activation_code;
 pub mod user;
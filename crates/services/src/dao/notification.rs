@@ -2,7 +2,7 @@ use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use roomler_ai_db::models::{Notification, NotificationSource, NotificationType};
 
-use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoResult, ListOptions, PaginatedResult, PaginationParams};
 
 pub struct NotificationDao {
     pub base: BaseDao<Notification>,
@@ -11,7 +11,7 @@ pub struct NotificationDao {
 impl NotificationDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Notification::COLLECTION),
+            base: BaseDao::new(db, Notification::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -47,12 +47,14 @@ impl NotificationDao {
         &self,
         user_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<Notification>> {
         self.base
-            .find_paginated(
+            .find_listed(
                 doc! { "user_id": user_id },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
             )
             .await
     }
@@ -61,12 +63,14 @@ impl NotificationDao {
         &self,
         user_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<Notification>> {
         self.base
-            .find_paginated(
+            .find_listed(
                 doc! { "user_id": user_id, "is_read": false },
                 Some(doc! { "created_at": -1 }),
                 params,
+                options,
             )
             .await
     }
@@ -99,4 +103,19 @@ impl NotificationDao {
             .await?;
         Ok(result.modified_count)
     }
+
+    /// Delete notifications about any of `entity_ids` (purged messages,
+    /// threads or rooms).
+    pub async fn delete_for_entities(
+        &self,
+        tenant_id: ObjectId,
+        entity_ids: &[ObjectId],
+    ) -> DaoResult<u64> {
+        self.base
+            .hard_delete(doc! {
+                "tenant_id": tenant_id,
+                "source.entity_id": { "$in": entity_ids },
+            })
+            .await
+    }
 }
@@ -15,7 +15,7 @@ pub struct OverlayNetworkDao {
 impl OverlayNetworkDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, OverlayNetwork::COLLECTION),
+            base: BaseDao::new(db, OverlayNetwork::COLLECTION).tenant_scoped(),
         }
     }
 
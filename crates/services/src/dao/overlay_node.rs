@@ -14,7 +14,7 @@ pub struct OverlayNodeDao {
 impl OverlayNodeDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, OverlayNode::COLLECTION),
+            base: BaseDao::new(db, OverlayNode::COLLECTION).tenant_scoped(),
         }
     }
 
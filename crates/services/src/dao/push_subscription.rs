@@ -1,6 +1,6 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::PushSubscription;
+use roomler_ai_db::models::{NotificationLevel, PushSubscription};
 
 use super::base::{BaseDao, DaoResult};
 
@@ -36,6 +36,7 @@ impl PushSubscriptionDao {
             user_id,
             endpoint,
             keys: roomler_ai_db::models::PushKeys { auth, p256dh },
+            notification_level: None,
             created_at: DateTime::now(),
         };
 
@@ -51,6 +52,25 @@ impl PushSubscriptionDao {
         Ok(count > 0)
     }
 
+    /// Set or clear one device's notification level. `false` when the user
+    /// has no subscription with that endpoint.
+    pub async fn set_notification_level(
+        &self,
+        user_id: ObjectId,
+        endpoint: &str,
+        level: Option<NotificationLevel>,
+    ) -> DaoResult<bool> {
+        let result = self
+            .base
+            .collection()
+            .update_one(
+                doc! { "user_id": user_id, "endpoint": endpoint },
+                doc! { "$set": { "notification_level": bson::to_bson(&level)? } },
+            )
+            .await?;
+        Ok(result.matched_count > 0)
+    }
+
     pub async fn find_by_user(&self, user_id: ObjectId) -> DaoResult<Vec<PushSubscription>> {
         self.base.find_many(doc! { "user_id": user_id }, None).await
     }
@@ -1,6 +1,6 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::{EmojiRef, EmojiType, Reaction, ReactionSummary};
+use roomler_ai_db::models::{EmojiRef, Reaction, ReactionSummary};
 
 use super::base::{BaseDao, DaoError, DaoResult};
 use super::message::MessageDao;
@@ -12,7 +12,7 @@ pub struct ReactionDao {
 impl ReactionDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Reaction::COLLECTION),
+            base: BaseDao::new(db, Reaction::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -22,7 +22,7 @@ impl ReactionDao {
         room_id: ObjectId,
         message_id: ObjectId,
         user_id: ObjectId,
-        emoji: String,
+        emoji: EmojiRef,
     ) -> DaoResult<Reaction> {
         // Check if already reacted with same emoji
         let existing = self
@@ -30,7 +30,7 @@ impl ReactionDao {
             .find_one(doc! {
                 "message_id": message_id,
                 "user_id": user_id,
-                "emoji.value": &emoji,
+                "emoji.value": &emoji.value,
             })
             .await?;
 
@@ -46,11 +46,7 @@ impl ReactionDao {
             room_id,
             message_id,
             user_id,
-            emoji: EmojiRef {
-                emoji_type: EmojiType::Unicode,
-                value: emoji,
-                custom_emoji_id: None,
-            },
+            emoji,
             created_at: DateTime::now(),
         };
 
@@ -75,6 +71,13 @@ impl ReactionDao {
         Ok(deleted > 0)
     }
 
+    /// Drop the reactions of deleted messages.
+    pub async fn delete_for_messages(&self, message_ids: &[ObjectId]) -> DaoResult<u64> {
+        self.base
+            .hard_delete(doc! { "message_id": { "$in": message_ids } })
+            .await
+    }
+
     pub async fn get_summary(&self, message_id: ObjectId) -> DaoResult<Vec<ReactionSummary>> {
         use futures::TryStreamExt;
 
@@ -108,7 +111,7 @@ impl ReactionDao {
         room_id: ObjectId,
         message_id: ObjectId,
         user_id: ObjectId,
-        emoji: String,
+        emoji: EmojiRef,
     ) -> DaoResult<Reaction> {
         let reaction = self
             .add(tenant_id, room_id, message_id, user_id, emoji)
@@ -1,5 +1,6 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
+use mongodb::options::ReturnDocument;
 use roomler_ai_db::models::{self, recording::*};
 
 use super::base::{BaseDao, DaoResult, PaginatedResult, PaginationParams};
@@ -11,10 +12,13 @@ pub struct RecordingDao {
 impl RecordingDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, models::Recording::COLLECTION),
+            base: BaseDao::new(db, models::Recording::COLLECTION).tenant_scoped(),
         }
     }
 
+    /// A recording with a `consent` policy waits in `awaiting_consent`
+    /// until [`Self::start_after_consent`].
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         tenant_id: ObjectId,
@@ -23,6 +27,7 @@ impl RecordingDao {
         storage_file: StorageFile,
         started_at: DateTime,
         ended_at: DateTime,
+        consent: Option<ConsentPolicy>,
     ) -> DaoResult<models::Recording> {
         let now = DateTime::now();
         let recording = models::Recording {
@@ -30,13 +35,21 @@ impl RecordingDao {
             tenant_id,
             room_id,
             recording_type,
-            status: RecordingStatus::Processing,
+            status: if consent.is_some() {
+                RecordingStatus::AwaitingConsent
+            } else {
+                RecordingStatus::Processing
+            },
             file: storage_file,
             started_at,
             ended_at,
             visibility: Visibility::Private,
             allow_download: true,
             expires_at: None,
+            media_offset_ms: 0,
+            transcript: Vec::new(),
+            chapters: Vec::new(),
+            consent,
             created_at: now,
             updated_at: now,
             deleted_at: None,
@@ -60,6 +73,26 @@ impl RecordingDao {
             .await
     }
 
+    /// Bytes of the tenant's live recordings, for the plan's storage quota.
+    pub async fn storage_used(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base
+            .sum(
+                doc! { "tenant_id": tenant_id, "deleted_at": null },
+                "file.size",
+            )
+            .await
+    }
+
+    /// Seconds of the tenant's live recordings.
+    pub async fn recorded_secs(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.base
+            .sum(
+                doc! { "tenant_id": tenant_id, "deleted_at": null },
+                "file.duration",
+            )
+            .await
+    }
+
     pub async fn update_status(&self, id: ObjectId, status: RecordingStatus) -> DaoResult<bool> {
         self.base
             .update_by_id(
@@ -69,7 +102,110 @@ impl RecordingDao {
             .await
     }
 
+    /// Start a recording that was waiting for consent. Returns `None` if it
+    /// already started (or was deleted), so only one caller announces it.
+    pub async fn start_after_consent(&self, id: ObjectId) -> DaoResult<Option<models::Recording>> {
+        let now = DateTime::now();
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "_id": id,
+                    "status": bson::to_bson(&RecordingStatus::AwaitingConsent)?,
+                    "deleted_at": null,
+                },
+                doc! { "$set": {
+                    "status": bson::to_bson(&RecordingStatus::Processing)?,
+                    "started_at": now,
+                    "updated_at": now,
+                } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Record the written file and make the recording available.
+    pub async fn finalize(
+        &self,
+        id: ObjectId,
+        content_type: &str,
+        size: u64,
+        duration_ms: u64,
+        media_offset_ms: u64,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": {
+                    "status": bson::to_bson(&RecordingStatus::Available)?,
+                    "file.content_type": content_type,
+                    "file.size": size as i64,
+                    "file.duration": duration_ms.div_ceil(1000) as i64,
+                    "file.duration_ms": duration_ms as i64,
+                    "media_offset_ms": media_offset_ms as i64,
+                    "ended_at": DateTime::now(),
+                } },
+            )
+            .await
+    }
+
+    pub async fn set_transcript(
+        &self,
+        id: ObjectId,
+        segments: &[TranscriptSegment],
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": { "transcript": bson::to_bson(segments)? } },
+            )
+            .await
+    }
+
+    pub async fn set_chapters(
+        &self,
+        id: ObjectId,
+        chapters: &[RecordingChapter],
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                id,
+                doc! { "$set": { "chapters": bson::to_bson(chapters)? } },
+            )
+            .await
+    }
+
     pub async fn soft_delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
         self.base.soft_delete_in_tenant(tenant_id, id).await
     }
+
+    /// Soft-delete recordings created before `cutoff` outside
+    /// `held_room_ids` (retention purge).
+    pub async fn soft_delete_expired(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+        held_room_ids: &[ObjectId],
+    ) -> DaoResult<u64> {
+        let now = DateTime::now();
+        let result = self
+            .base
+            .collection()
+            .update_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "created_at": { "$lt": cutoff },
+                    "room_id": { "$nin": held_room_ids },
+                    "deleted_at": null,
+                },
+                doc! { "$set": {
+                    "status": bson::to_bson(&RecordingStatus::Deleted)?,
+                    "deleted_at": now,
+                    "updated_at": now,
+                } },
+            )
+            .await?;
+        Ok(result.modified_count)
+    }
 }
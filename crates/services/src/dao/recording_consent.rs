@@ -0,0 +1,152 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::ReturnDocument;
+use roomler_ai_db::models::{ConsentDecision, RecordingConsent};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct RecordingConsentDao {
+    pub base: BaseDao<RecordingConsent>,
+}
+
+impl RecordingConsentDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, RecordingConsent::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Ask `user_ids` for consent to `recording_id`. The requester has
+    /// consented by asking.
+    pub async fn request(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        recording_id: ObjectId,
+        requested_by: ObjectId,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<()> {
+        let now = DateTime::now();
+        let consents: Vec<RecordingConsent> = user_ids
+            .iter()
+            .map(|&user_id| {
+                let asked = user_id != requested_by;
+                RecordingConsent {
+                    id: None,
+                    tenant_id,
+                    room_id,
+                    recording_id,
+                    user_id,
+                    decision: if asked {
+                        ConsentDecision::Pending
+                    } else {
+                        ConsentDecision::Accepted
+                    },
+                    timed_out: false,
+                    decided_at: (!asked).then_some(now),
+                    created_at: now,
+                    updated_at: now,
+                }
+            })
+            .collect();
+        if !consents.is_empty() {
+            self.base.collection().insert_many(consents).await?;
+        }
+        Ok(())
+    }
+
+    /// Record a participant's answer. Returns `None` unless they had one
+    /// pending.
+    pub async fn decide(
+        &self,
+        recording_id: ObjectId,
+        user_id: ObjectId,
+        accepted: bool,
+    ) -> DaoResult<Option<RecordingConsent>> {
+        let decision = if accepted {
+            ConsentDecision::Accepted
+        } else {
+            ConsentDecision::Declined
+        };
+        let now = DateTime::now();
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "recording_id": recording_id,
+                    "user_id": user_id,
+                    "decision": bson::to_bson(&ConsentDecision::Pending)?,
+                },
+                doc! { "$set": {
+                    "decision": bson::to_bson(&decision)?,
+                    "decided_at": now,
+                    "updated_at": now,
+                } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Decline every answer still pending, as timed out. Returns the users
+    /// it declined for; one answering at the same moment keeps their answer.
+    pub async fn time_out(&self, recording_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
+        let pending = doc! {
+            "recording_id": recording_id,
+            "decision": bson::to_bson(&ConsentDecision::Pending)?,
+        };
+        let consents = self
+            .base
+            .collection()
+            .distinct("_id", pending.clone())
+            .await?;
+        let now = DateTime::now();
+        let mut declined = Vec::new();
+        for id in consents.iter().filter_map(|v| v.as_object_id()) {
+            let mut filter = pending.clone();
+            filter.insert("_id", id);
+            let consent = self
+                .base
+                .collection()
+                .find_one_and_update(
+                    filter,
+                    doc! { "$set": {
+                        "decision": bson::to_bson(&ConsentDecision::Declined)?,
+                        "timed_out": true,
+                        "decided_at": now,
+                        "updated_at": now,
+                    } },
+                )
+                .await?;
+            if let Some(consent) = consent {
+                declined.push(consent.user_id);
+            }
+        }
+        Ok(declined)
+    }
+
+    pub async fn pending_count(&self, recording_id: ObjectId) -> DaoResult<u64> {
+        Ok(self
+            .base
+            .collection()
+            .count_documents(doc! {
+                "recording_id": recording_id,
+                "decision": bson::to_bson(&ConsentDecision::Pending)?,
+            })
+            .await?)
+    }
+
+    /// Every answer to the recording's request, oldest first.
+    pub async fn list_for_recording(
+        &self,
+        tenant_id: ObjectId,
+        recording_id: ObjectId,
+    ) -> DaoResult<Vec<RecordingConsent>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "recording_id": recording_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+}
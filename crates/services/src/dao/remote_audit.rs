@@ -11,7 +11,7 @@ pub struct RemoteAuditDao {
 impl RemoteAuditDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, RemoteAuditEvent::COLLECTION),
+            base: BaseDao::new(db, RemoteAuditEvent::COLLECTION).tenant_scoped(),
         }
     }
 
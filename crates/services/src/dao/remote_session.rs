@@ -12,7 +12,7 @@ pub struct RemoteSessionDao {
 impl RemoteSessionDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, RemoteSession::COLLECTION),
+            base: BaseDao::new(db, RemoteSession::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -12,7 +12,7 @@ pub struct RoleDao {
 impl RoleDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Role::COLLECTION),
+            base: BaseDao::new(db, Role::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -2,11 +2,11 @@ use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
 use rand::Rng;
 use roomler_ai_db::models::{
-    CallChatMessage, ConferenceSettings, MediaSettings, ParticipantRole, ParticipantSession, Room,
-    RoomMember,
+    AuthorType, CalendarEventLink, CallChatMessage, ConferenceSettings, Invitee, MediaSettings,
+    NotificationLevel, ParticipantRole, ParticipantSession, PermissionOverwrite, Room, RoomMember,
 };
 
-use super::base::{BaseDao, DaoError, DaoResult, PaginatedResult, PaginationParams};
+use super::base::{BaseDao, DaoError, DaoResult, ListOptions, PaginatedResult, PaginationParams};
 
 pub struct RoomDao {
     pub base: BaseDao<Room>,
@@ -18,9 +18,9 @@ pub struct RoomDao {
 impl RoomDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, Room::COLLECTION),
-            members: BaseDao::new(db, RoomMember::COLLECTION),
-            chat_messages: BaseDao::new(db, CallChatMessage::COLLECTION),
+            base: BaseDao::new(db, Room::COLLECTION).tenant_scoped(),
+            members: BaseDao::new(db, RoomMember::COLLECTION).tenant_scoped(),
+            chat_messages: BaseDao::new(db, CallChatMessage::COLLECTION).tenant_scoped(),
             db: db.clone(),
         }
     }
@@ -69,7 +69,9 @@ impl RoomDao {
             is_open,
             is_archived: false,
             is_read_only: false,
+            is_announcement: false,
             is_default: false,
+            legal_hold: false,
             permission_overwrites: Vec::new(),
             tags: Vec::new(),
             media_settings,
@@ -145,6 +147,7 @@ impl RoomDao {
         is_open: Option<bool>,
         is_archived: Option<bool>,
         is_read_only: Option<bool>,
+        is_announcement: Option<bool>,
     ) -> DaoResult<bool> {
         let mut set_doc = doc! {};
 
@@ -166,6 +169,9 @@ impl RoomDao {
         if let Some(is_read_only) = is_read_only {
             set_doc.insert("is_read_only", is_read_only);
         }
+        if let Some(is_announcement) = is_announcement {
+            set_doc.insert("is_announcement", is_announcement);
+        }
 
         if set_doc.is_empty() {
             return Ok(false);
@@ -179,18 +185,73 @@ impl RoomDao {
             .await
     }
 
+    /// Replace the room's permission overwrites wholesale.
+    pub async fn set_permission_overwrites(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        overwrites: &[PermissionOverwrite],
+    ) -> DaoResult<bool> {
+        let overwrites = bson::to_bson(overwrites)
+            .map_err(|e| DaoError::Validation(format!("Invalid overwrites: {}", e)))?;
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! {
+                    "$set": {
+                        "permission_overwrites": overwrites,
+                        "updated_at": DateTime::now(),
+                    }
+                },
+            )
+            .await
+    }
+
     pub async fn soft_delete(&self, tenant_id: ObjectId, room_id: ObjectId) -> DaoResult<bool> {
         self.base.soft_delete_in_tenant(tenant_id, room_id).await
     }
 
+    /// Undo a soft delete.
+    pub async fn restore(&self, tenant_id: ObjectId, room_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id, "deleted_at": { "$ne": null } },
+                doc! { "$set": { "deleted_at": null } },
+            )
+            .await
+    }
+
+    /// Rooms soft-deleted before `cutoff` and not under legal hold: past
+    /// their restore window.
+    pub async fn find_deleted_ids(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+    ) -> DaoResult<Vec<ObjectId>> {
+        let rooms = self
+            .base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "deleted_at": { "$lt": cutoff },
+                    "legal_hold": { "$ne": true },
+                },
+                None,
+            )
+            .await?;
+        Ok(rooms.into_iter().filter_map(|r| r.id).collect())
+    }
+
     /// Hard-delete a room and cascade to all related resources:
     /// messages, reactions, room_members, call_chat_messages, files (soft), recordings.
     pub async fn cascade_delete(&self, tenant_id: ObjectId, room_id: ObjectId) -> DaoResult<()> {
-        // 1. Delete all messages in the room
-        let msg_coll = self.db.collection::<bson::Document>("messages");
-        msg_coll
-            .delete_many(doc! { "room_id": room_id, "tenant_id": tenant_id })
-            .await?;
+        // 1. Delete all messages in the room, archived ones included
+        for collection in ["messages", "messages_archive"] {
+            self.db
+                .collection::<bson::Document>(collection)
+                .delete_many(doc! { "room_id": room_id, "tenant_id": tenant_id })
+                .await?;
+        }
 
         // 2. Delete all reactions in the room
         let react_coll = self.db.collection::<bson::Document>("reactions");
@@ -316,6 +377,8 @@ impl RoomDao {
             notification_override: None,
             is_muted: false,
             is_pinned: false,
+            is_favorite: false,
+            sidebar_position: None,
             is_video_on: false,
             is_screen_sharing: false,
             is_hand_raised: false,
@@ -361,12 +424,218 @@ impl RoomDao {
         &self,
         room_id: ObjectId,
         params: &PaginationParams,
+        options: &ListOptions,
     ) -> DaoResult<PaginatedResult<RoomMember>> {
         self.members
-            .find_paginated(
+            .find_listed(
                 doc! { "room_id": room_id },
                 Some(doc! { "joined_at": 1 }),
                 params,
+                options,
+            )
+            .await
+    }
+
+    /// Place or lift a legal hold on a room.
+    pub async fn set_legal_hold(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        legal_hold: bool,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "legal_hold": legal_hold } },
+            )
+            .await
+    }
+
+    /// Set or clear the room's scheduled call. A room scheduled for the
+    /// first time gets a meeting code, as conference rooms do on create.
+    pub async fn set_conference_settings(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        settings: Option<&ConferenceSettings>,
+    ) -> DaoResult<bool> {
+        let updated = self
+            .base
+            .update_one(
+                doc! { "_id": room_id, "tenant_id": tenant_id },
+                doc! { "$set": { "conference_settings": bson::to_bson(&settings)? } },
+            )
+            .await?;
+        if updated && settings.is_some() {
+            let code = generate_meeting_code();
+            self.base
+                .update_one(
+                    doc! { "_id": room_id, "tenant_id": tenant_id, "meeting_code": null },
+                    doc! { "$set": { "join_url": format!("/join/{}", code), "meeting_code": code } },
+                )
+                .await?;
+        }
+        Ok(updated)
+    }
+
+    /// Record the outcome of a calendar sync of the room's scheduled call.
+    pub async fn set_calendar_sync(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        link: &CalendarEventLink,
+        invitees: &[Invitee],
+    ) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! {
+                    "_id": room_id,
+                    "tenant_id": tenant_id,
+                    "conference_settings.calendar_event.provider": &link.provider,
+                },
+                doc! { "$set": {
+                    "conference_settings.calendar_event": bson::to_bson(link)?,
+                    "conference_settings.invitees": bson::to_bson(invitees)?,
+                } },
+            )
+            .await
+    }
+
+    /// Scheduled calls in every tenant that have a calendar event and
+    /// haven't ended by `now`, for the calendar sync.
+    pub async fn find_calendar_linked(&self, now: DateTime) -> DaoResult<Vec<Room>> {
+        use futures::TryStreamExt;
+        let cursor = self
+            .base
+            .collection()
+            .find(doc! {
+                "conference_settings.scheduled_end": { "$gt": now },
+                "conference_settings.calendar_event": { "$ne": null },
+                "deleted_at": null,
+            })
+            .await?;
+        Ok(cursor.try_collect().await?)
+    }
+
+    /// Rooms of the tenant under legal hold.
+    pub async fn find_held_ids(&self, tenant_id: ObjectId) -> DaoResult<Vec<ObjectId>> {
+        let rooms = self
+            .base
+            .find_many(doc! { "tenant_id": tenant_id, "legal_hold": true }, None)
+            .await?;
+        Ok(rooms.into_iter().filter_map(|r| r.id).collect())
+    }
+
+    /// The user's memberships across the tenant's rooms.
+    pub async fn find_memberships(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Vec<RoomMember>> {
+        self.members
+            .find_many(doc! { "tenant_id": tenant_id, "user_id": user_id }, None)
+            .await
+    }
+
+    /// Pin or favorite a room in the member's sidebar. `false` when the user
+    /// isn't a member of the room.
+    pub async fn set_sidebar_flags(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        is_pinned: Option<bool>,
+        is_favorite: Option<bool>,
+    ) -> DaoResult<bool> {
+        let mut set_doc = doc! {};
+        if let Some(is_pinned) = is_pinned {
+            set_doc.insert("is_pinned", is_pinned);
+        }
+        if let Some(is_favorite) = is_favorite {
+            set_doc.insert("is_favorite", is_favorite);
+        }
+        self.members
+            .update_one(
+                doc! { "tenant_id": tenant_id, "room_id": room_id, "user_id": user_id },
+                doc! { "$set": set_doc },
+            )
+            .await
+    }
+
+    /// Replace the member's sidebar order: `room_ids` get positions in the
+    /// given order, the user's other rooms lose theirs.
+    pub async fn set_sidebar_order(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+        room_ids: &[ObjectId],
+    ) -> DaoResult<()> {
+        self.members
+            .collection()
+            .update_many(
+                doc! { "tenant_id": tenant_id, "user_id": user_id },
+                doc! { "$set": { "sidebar_position": null } },
+            )
+            .await?;
+        for (position, room_id) in room_ids.iter().enumerate() {
+            self.members
+                .update_one(
+                    doc! { "tenant_id": tenant_id, "room_id": room_id, "user_id": user_id },
+                    doc! { "$set": { "sidebar_position": position as i32 } },
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// The user's membership of the room, if any.
+    pub async fn find_membership(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<Option<RoomMember>> {
+        self.members
+            .find_one(doc! { "tenant_id": tenant_id, "room_id": room_id, "user_id": user_id })
+            .await
+    }
+
+    /// Memberships of the room held by any of `user_ids`.
+    pub async fn find_memberships_of(
+        &self,
+        room_id: ObjectId,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<Vec<RoomMember>> {
+        self.members
+            .find_many(
+                doc! { "room_id": room_id, "user_id": { "$in": user_ids } },
+                None,
+            )
+            .await
+    }
+
+    /// Set the member's notification level for the room (`Some(None)` goes
+    /// back to their tenant-wide setting) and whether the room is muted.
+    /// `false` when the user isn't a member of the room.
+    pub async fn set_notification_preferences(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        level: Option<Option<NotificationLevel>>,
+        is_muted: Option<bool>,
+    ) -> DaoResult<bool> {
+        let mut set_doc = doc! {};
+        if let Some(level) = level {
+            set_doc.insert("notification_override", bson::to_bson(&level)?);
+        }
+        if let Some(is_muted) = is_muted {
+            set_doc.insert("is_muted", is_muted);
+        }
+        self.members
+            .update_one(
+                doc! { "tenant_id": tenant_id, "room_id": room_id, "user_id": user_id },
+                doc! { "$set": set_doc },
             )
             .await
     }
@@ -523,6 +792,8 @@ impl RoomDao {
             notification_override: None,
             is_muted: false,
             is_pinned: false,
+            is_favorite: false,
+            sidebar_position: None,
             is_video_on: true,
             is_screen_sharing: false,
             is_hand_raised: false,
@@ -584,6 +855,19 @@ impl RoomDao {
         Ok(true)
     }
 
+    /// Whether the user has an open call session in the room.
+    pub async fn is_in_call(&self, room_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
+        let count = self
+            .members
+            .count(doc! {
+                "room_id": room_id,
+                "user_id": user_id,
+                "sessions": { "$elemMatch": { "left_at": null } },
+            })
+            .await?;
+        Ok(count > 0)
+    }
+
     pub async fn list_participants(&self, room_id: ObjectId) -> DaoResult<Vec<RoomMember>> {
         self.members
             .find_many(
@@ -644,6 +928,7 @@ impl RoomDao {
         tenant_id: ObjectId,
         room_id: ObjectId,
         author_id: ObjectId,
+        author_type: AuthorType,
         display_name: String,
         content: String,
     ) -> DaoResult<CallChatMessage> {
@@ -652,6 +937,7 @@ impl RoomDao {
             tenant_id,
             room_id,
             author_id,
+            author_type,
             display_name,
             content,
             created_at: DateTime::now(),
@@ -660,6 +946,33 @@ impl RoomDao {
         self.chat_messages.find_by_id(id).await
     }
 
+    /// Delete in-call chat sent before `cutoff` outside `held_room_ids`
+    /// (retention purge).
+    pub async fn delete_chat_messages_before(
+        &self,
+        tenant_id: ObjectId,
+        cutoff: DateTime,
+        held_room_ids: &[ObjectId],
+    ) -> DaoResult<u64> {
+        self.chat_messages
+            .hard_delete(doc! {
+                "tenant_id": tenant_id,
+                "created_at": { "$lt": cutoff },
+                "room_id": { "$nin": held_room_ids },
+            })
+            .await
+    }
+
+    /// A room's whole in-call chat, oldest first.
+    pub async fn find_all_chat_messages(
+        &self,
+        room_id: ObjectId,
+    ) -> DaoResult<Vec<CallChatMessage>> {
+        self.chat_messages
+            .find_many(doc! { "room_id": room_id }, Some(doc! { "created_at": 1 }))
+            .await
+    }
+
     pub async fn find_chat_messages(
         &self,
         room_id: ObjectId,
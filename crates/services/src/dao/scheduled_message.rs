@@ -0,0 +1,62 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::ScheduledMessage;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct ScheduledMessageDao {
+    pub base: BaseDao<ScheduledMessage>,
+}
+
+impl ScheduledMessageDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, ScheduledMessage::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn create(&self, message: &ScheduledMessage) -> DaoResult<ScheduledMessage> {
+        let id = self.base.insert_one(message).await?;
+        self.base.find_by_id(id).await
+    }
+
+    /// The author's pending messages in a room, soonest first.
+    pub async fn find_for_author(
+        &self,
+        room_id: ObjectId,
+        author_id: ObjectId,
+    ) -> DaoResult<Vec<ScheduledMessage>> {
+        self.base
+            .find_many(
+                doc! { "room_id": room_id, "author_id": author_id },
+                Some(doc! { "send_at": 1 }),
+            )
+            .await
+    }
+
+    /// Cancel one of the author's pending messages. Returns whether it was
+    /// still pending.
+    pub async fn cancel(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        author_id: ObjectId,
+    ) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id, "author_id": author_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    /// Atomically take the oldest message that is due, removing it so no
+    /// other instance delivers it too.
+    pub async fn claim_due(&self, now: DateTime) -> DaoResult<Option<ScheduledMessage>> {
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_delete(doc! { "send_at": { "$lte": now } })
+            .sort(doc! { "send_at": 1 })
+            .await?)
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::BTreeMap;
+
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::{Database, options::ReturnDocument};
+use roomler_ai_db::models::{SettingOverride, SettingsOverrides};
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct SettingsOverrideDao {
+    pub base: BaseDao<SettingsOverrides>,
+}
+
+impl SettingsOverrideDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, SettingsOverrides::COLLECTION),
+        }
+    }
+
+    /// The deployment's overrides, if any were ever set.
+    pub async fn get(&self) -> DaoResult<Option<SettingsOverrides>> {
+        self.base.find_one(doc! {}).await
+    }
+
+    /// Set or, for `null`, drop the `changes` in one update, leaving other
+    /// keys as they are stored (another pod may have just changed them), and
+    /// create the document on first save. Returns the stored overrides.
+    pub async fn merge(
+        &self,
+        changes: &BTreeMap<String, serde_json::Value>,
+        updated_by: ObjectId,
+    ) -> DaoResult<BTreeMap<String, serde_json::Value>> {
+        let keys: Vec<&String> = changes.keys().collect();
+        let set: Vec<SettingOverride> = changes
+            .iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| SettingOverride {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect();
+        let pipeline = vec![doc! { "$set": {
+            "values": { "$concatArrays": [
+                { "$filter": {
+                    "input": { "$ifNull": ["$values", []] },
+                    "cond": { "$not": [{ "$in": ["$$this.key", bson::to_bson(&keys)?] }] },
+                } },
+                // Values are data, not expressions
+                { "$literal": bson::to_bson(&set)? },
+            ] },
+            "updated_by": updated_by,
+            "updated_at": DateTime::now(),
+        } }];
+        let stored = self
+            .base
+            .collection()
+            .find_one_and_update(doc! {}, pipeline)
+            .upsert(true)
+            .return_document(ReturnDocument::After)
+            .await?;
+        Ok(stored.map(|o| o.to_map()).unwrap_or_default())
+    }
+}
@@ -0,0 +1,68 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::SlashCommand;
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct SlashCommandDao {
+    pub base: BaseDao<SlashCommand>,
+}
+
+impl SlashCommandDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, SlashCommand::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Register `command` (already normalised). A name taken in the tenant
+    /// fails with `DuplicateKey`.
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        created_by: ObjectId,
+        command: String,
+        description: Option<String>,
+        url: String,
+    ) -> DaoResult<SlashCommand> {
+        let now = DateTime::now();
+        let cmd = SlashCommand {
+            id: None,
+            tenant_id,
+            command,
+            description,
+            url,
+            secret: nanoid::nanoid!(32),
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&cmd).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<SlashCommand>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id }, Some(doc! { "command": 1 }))
+            .await
+    }
+
+    pub async fn find_by_command(
+        &self,
+        tenant_id: ObjectId,
+        command: &str,
+    ) -> DaoResult<SlashCommand> {
+        self.base
+            .find_one(doc! { "tenant_id": tenant_id, "command": command })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+}
@@ -0,0 +1,109 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::ReturnDocument;
+use roomler_ai_db::models::{StripeEventRecord, StripeEventStatus};
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct StripeEventDao {
+    pub base: BaseDao<StripeEventRecord>,
+}
+
+impl StripeEventDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, StripeEventRecord::COLLECTION),
+        }
+    }
+
+    /// Queue a verified event. Returns false if the event id was seen before.
+    pub async fn enqueue(
+        &self,
+        event_id: &str,
+        event_type: &str,
+        event_created: DateTime,
+        payload: String,
+    ) -> DaoResult<bool> {
+        let now = DateTime::now();
+        let record = StripeEventRecord {
+            id: None,
+            event_id: event_id.to_string(),
+            event_type: event_type.to_string(),
+            event_created,
+            payload,
+            status: StripeEventStatus::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            processed_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        match self.base.insert_one(&record).await {
+            Ok(_) => Ok(true),
+            Err(DaoError::DuplicateKey(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Take the oldest due event, leasing it until `lease_until` so another
+    /// worker doesn't pick it up meanwhile. Counts the attempt.
+    pub async fn claim_due(&self, lease_until: DateTime) -> DaoResult<Option<StripeEventRecord>> {
+        let now = DateTime::now();
+        let claimed = self
+            .base
+            .collection()
+            .find_one_and_update(
+                doc! {
+                    "status": bson::to_bson(&StripeEventStatus::Pending)?,
+                    "next_attempt_at": { "$lte": now },
+                },
+                doc! {
+                    "$set": { "next_attempt_at": lease_until, "updated_at": now },
+                    "$inc": { "attempts": 1 },
+                },
+            )
+            .sort(doc! { "event_created": 1 })
+            .return_document(ReturnDocument::After)
+            .await?;
+        Ok(claimed)
+    }
+
+    pub async fn mark_processed(&self, id: ObjectId) -> DaoResult<()> {
+        let now = DateTime::now();
+        self.base
+            .update_by_id(
+                id,
+                doc! {
+                    "$set": {
+                        "status": bson::to_bson(&StripeEventStatus::Processed)?,
+                        "processed_at": now,
+                        "last_error": null,
+                    }
+                },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Record a failed attempt; retried at `retry_at`, or given up on when
+    /// that is `None`.
+    pub async fn mark_attempt_failed(
+        &self,
+        id: ObjectId,
+        error: &str,
+        retry_at: Option<DateTime>,
+    ) -> DaoResult<()> {
+        let update = match retry_at {
+            Some(at) => doc! { "$set": { "last_error": error, "next_attempt_at": at } },
+            None => doc! {
+                "$set": {
+                    "last_error": error,
+                    "status": bson::to_bson(&StripeEventStatus::Failed)?,
+                }
+            },
+        };
+        self.base.update_by_id(id, update).await?;
+        Ok(())
+    }
+}
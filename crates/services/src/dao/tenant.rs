@@ -1,6 +1,8 @@
 use bson::{DateTime, doc, oid::ObjectId};
-use mongodb::Database;
-use roomler_ai_db::models::{Plan, Role, Tenant, TenantMember, TenantSettings, role::permissions};
+use mongodb::{Database, options::ReturnDocument};
+use roomler_ai_db::models::{
+    Plan, RetentionPolicy, Role, Tenant, TenantMember, TenantSettings, User, role::permissions,
+};
 
 use super::base::{BaseDao, DaoError, DaoResult};
 
@@ -8,14 +10,17 @@ pub struct TenantDao {
     pub base: BaseDao<Tenant>,
     pub members: BaseDao<TenantMember>,
     pub roles: BaseDao<Role>,
+    /// Read for the search keys of new members.
+    users: BaseDao<User>,
 }
 
 impl TenantDao {
     pub fn new(db: &Database) -> Self {
         Self {
             base: BaseDao::new(db, Tenant::COLLECTION),
-            members: BaseDao::new(db, TenantMember::COLLECTION),
-            roles: BaseDao::new(db, Role::COLLECTION),
+            members: BaseDao::new(db, TenantMember::COLLECTION).tenant_scoped(),
+            roles: BaseDao::new(db, Role::COLLECTION).tenant_scoped(),
+            users: BaseDao::new(db, User::COLLECTION),
         }
     }
 
@@ -40,6 +45,130 @@ impl TenantDao {
         self.base.find_by_id(tenant_id).await
     }
 
+    /// Replace the tenant's retention policy. Returns the updated tenant.
+    pub async fn set_retention(
+        &self,
+        tenant_id: ObjectId,
+        retention: &RetentionPolicy,
+    ) -> DaoResult<Tenant> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.retention": bson::to_bson(retention)? } },
+            )
+            .await?;
+        self.base.find_by_id(tenant_id).await
+    }
+
+    /// Set the tenant's own file storage quota (`None` falls back to the
+    /// default). Returns the updated tenant.
+    pub async fn set_storage_quota(
+        &self,
+        tenant_id: ObjectId,
+        quota_bytes: Option<u64>,
+    ) -> DaoResult<Tenant> {
+        self.base
+            .update_by_id(
+                tenant_id,
+                doc! { "$set": { "settings.storage_quota_bytes": quota_bytes.map(|b| b as i64) } },
+            )
+            .await?;
+        self.base.find_by_id(tenant_id).await
+    }
+
+    /// Move the tenant's file storage counter by `delta` bytes, never below 0.
+    pub async fn add_storage_used(&self, tenant_id: ObjectId, delta: i64) -> DaoResult<()> {
+        let pipeline = vec![doc! { "$set": {
+            "storage_used_bytes": { "$max": [
+                0_i64,
+                { "$add": [{ "$ifNull": ["$storage_used_bytes", 0_i64] }, delta] },
+            ] },
+        } }];
+        self.base
+            .collection()
+            .update_one(doc! { "_id": tenant_id }, pipeline)
+            .await?;
+        Ok(())
+    }
+
+    /// Add `bytes` to the tenant's file storage counter if it stays within
+    /// `limit` (`None` is unlimited), in one conditional update so concurrent
+    /// uploads can't both fit. Returns the tenant after the update, or `None`
+    /// when it wouldn't fit.
+    pub async fn reserve_storage(
+        &self,
+        tenant_id: ObjectId,
+        bytes: u64,
+        limit: Option<u64>,
+    ) -> DaoResult<Option<Tenant>> {
+        let mut filter = doc! { "_id": tenant_id };
+        if let Some(limit) = limit {
+            let Some(room) = limit.checked_sub(bytes) else {
+                return Ok(None);
+            };
+            filter.insert(
+                "$expr",
+                doc! { "$lte": [{ "$ifNull": ["$storage_used_bytes", 0_i64] }, room as i64] },
+            );
+        }
+        Ok(self
+            .base
+            .collection()
+            .find_one_and_update(
+                filter,
+                doc! { "$inc": { "storage_used_bytes": bytes as i64 } },
+            )
+            .return_document(ReturnDocument::After)
+            .await?)
+    }
+
+    /// Overwrite the tenant's file storage counter with a measured total.
+    pub async fn set_storage_used(&self, tenant_id: ObjectId, bytes: u64) -> DaoResult<()> {
+        self.base
+            .collection()
+            .update_one(
+                doc! { "_id": tenant_id },
+                doc! { "$set": { "storage_used_bytes": bytes as i64 } },
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Every live tenant.
+    pub async fn find_live(&self) -> DaoResult<Vec<Tenant>> {
+        self.base.find_many(doc! { "deleted_at": null }, None).await
+    }
+
+    /// Live tenants with at least one retention limit set.
+    pub async fn find_with_retention(&self) -> DaoResult<Vec<Tenant>> {
+        self.base
+            .find_many(
+                doc! {
+                    "deleted_at": null,
+                    "$or": [
+                        { "settings.retention.message_days": { "$ne": null } },
+                        { "settings.retention.recording_days": { "$ne": null } },
+                        { "settings.retention.transcript_days": { "$ne": null } },
+                    ],
+                },
+                None,
+            )
+            .await
+    }
+
+    /// Live tenants with their own archive threshold.
+    pub async fn find_with_archive_days(&self) -> DaoResult<Vec<Tenant>> {
+        self.base
+            .find_many(
+                doc! {
+                    "deleted_at": null,
+                    "settings.retention.archive_days": { "$ne": null },
+                },
+                None,
+            )
+            .await
+    }
+
     pub async fn create(
         &self,
         name: String,
@@ -60,6 +189,7 @@ impl TenantDao {
             billing: None,
             integrations: None,
             is_archived: false,
+            storage_used_bytes: 0,
             created_at: now,
             updated_at: now,
             deleted_at: None,
@@ -184,6 +314,12 @@ impl TenantDao {
         role_ids: Vec<ObjectId>,
         invited_by: Option<ObjectId>,
     ) -> DaoResult<TenantMember> {
+        let search_keys = self
+            .users
+            .find_one(doc! { "_id": user_id })
+            .await?
+            .map(|u| TenantMember::search_keys(&u.display_name, &u.username, &u.email))
+            .unwrap_or_default();
         let now = DateTime::now();
         let member = TenantMember {
             id: None,
@@ -197,6 +333,7 @@ impl TenantDao {
             notification_override: None,
             invited_by,
             last_seen_at: None,
+            search_keys,
             created_at: now,
             updated_at: now,
         };
@@ -205,6 +342,43 @@ impl TenantDao {
         self.members.find_by_id(id).await
     }
 
+    /// Up to `limit` members whose name, a word of it, username or email
+    /// starts with `prefix` (case-insensitive). The anchored regex on the
+    /// lowercased keys runs as a range scan of the `{ tenant_id, search_keys }`
+    /// index.
+    pub async fn search_members(
+        &self,
+        tenant_id: ObjectId,
+        prefix: &str,
+        limit: i64,
+    ) -> DaoResult<Vec<TenantMember>> {
+        let escaped: String = prefix
+            .to_lowercase()
+            .chars()
+            .flat_map(|c| {
+                if ".*+?^${}()|[]\\".contains(c) {
+                    vec!['\\', c]
+                } else {
+                    vec![c]
+                }
+            })
+            .collect();
+        let filter = doc! {
+            "tenant_id": tenant_id,
+            "search_keys": { "$regex": format!("^{}", escaped) },
+        };
+        use futures::TryStreamExt;
+        let members = self
+            .members
+            .collection()
+            .find(filter)
+            .limit(limit)
+            .await?
+            .try_collect()
+            .await?;
+        Ok(members)
+    }
+
     pub async fn find_by_slug(&self, slug: &str) -> DaoResult<Tenant> {
         self.base
             .find_one(doc! { "slug": slug, "deleted_at": null })
@@ -240,6 +414,20 @@ impl TenantDao {
         Ok(count > 0)
     }
 
+    pub async fn member_count(&self, tenant_id: ObjectId) -> DaoResult<u64> {
+        self.members.count(doc! { "tenant_id": tenant_id }).await
+    }
+
+    /// Drop a user's tenant membership. Room memberships are the caller's
+    /// concern. Returns whether a membership was removed.
+    pub async fn remove_member(&self, tenant_id: ObjectId, user_id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .members
+            .hard_delete(doc! { "tenant_id": tenant_id, "user_id": user_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
     pub async fn assign_role(
         &self,
         tenant_id: ObjectId,
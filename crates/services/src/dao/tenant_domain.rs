@@ -0,0 +1,106 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use futures::TryStreamExt;
+use mongodb::Database;
+use roomler_ai_db::models::{DomainJoinMode, TenantDomain};
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct TenantDomainDao {
+    pub base: BaseDao<TenantDomain>,
+}
+
+impl TenantDomainDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, TenantDomain::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Claim `domain` (already normalized) for the tenant, unverified.
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        domain: String,
+        join_mode: DomainJoinMode,
+        role_id: Option<ObjectId>,
+        created_by: ObjectId,
+    ) -> DaoResult<TenantDomain> {
+        let now = DateTime::now();
+        let claim = TenantDomain {
+            id: None,
+            tenant_id,
+            domain,
+            verification_token: nanoid::nanoid!(32),
+            verified_at: None,
+            join_mode,
+            role_id,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&claim).await?;
+        self.base.find_by_id_in_tenant(tenant_id, id).await
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<TenantDomain>> {
+        self.base
+            .find_many(doc! { "tenant_id": tenant_id }, Some(doc! { "domain": 1 }))
+            .await
+    }
+
+    /// Replace how users join through the domain.
+    pub async fn set_policy(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        join_mode: DomainJoinMode,
+        role_id: Option<ObjectId>,
+    ) -> DaoResult<TenantDomain> {
+        let mode = bson::to_bson(&join_mode)?;
+        if !self
+            .base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": {
+                    "join_mode": mode,
+                    "role_id": role_id,
+                    "updated_at": DateTime::now(),
+                }},
+            )
+            .await?
+        {
+            return Err(DaoError::NotFound);
+        }
+        self.base.find_by_id_in_tenant(tenant_id, id).await
+    }
+
+    pub async fn mark_verified(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let now = DateTime::now();
+        self.base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": { "verified_at": now, "updated_at": now } },
+            )
+            .await
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    /// Verified claims on `domain` across all tenants. The lookup is by
+    /// design not tenant-scoped: it is how a user finds tenants to join.
+    pub async fn find_verified(&self, domain: &str) -> DaoResult<Vec<TenantDomain>> {
+        Ok(self
+            .base
+            .collection()
+            .find(doc! { "domain": domain, "verified_at": { "$ne": null } })
+            .await?
+            .try_collect()
+            .await?)
+    }
+}
@@ -11,7 +11,7 @@ pub struct TunnelAuditDao {
 impl TunnelAuditDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, TunnelAuditEvent::COLLECTION),
+            base: BaseDao::new(db, TunnelAuditEvent::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -11,7 +11,7 @@ pub struct TunnelClientDao {
 impl TunnelClientDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, TunnelClient::COLLECTION),
+            base: BaseDao::new(db, TunnelClient::COLLECTION).tenant_scoped(),
         }
     }
 
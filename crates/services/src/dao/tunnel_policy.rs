@@ -13,7 +13,7 @@ pub struct TunnelPolicyDao {
 impl TunnelPolicyDao {
     pub fn new(db: &Database) -> Self {
         Self {
-            base: BaseDao::new(db, TunnelPolicy::COLLECTION),
+            base: BaseDao::new(db, TunnelPolicy::COLLECTION).tenant_scoped(),
         }
     }
 
@@ -0,0 +1,115 @@
+use bson::{DateTime, Document, doc, oid::ObjectId};
+use mongodb::Database;
+use mongodb::options::UpdateOptions;
+use roomler_ai_db::models::UsageDay;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct UsageDao {
+    pub base: BaseDao<UsageDay>,
+}
+
+/// Seconds of usage to add to a day.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageDelta {
+    pub call_secs: u64,
+    pub transcription_secs: u64,
+    pub streamed_secs: u64,
+}
+
+impl UsageDelta {
+    pub fn is_empty(&self) -> bool {
+        self.call_secs == 0 && self.transcription_secs == 0 && self.streamed_secs == 0
+    }
+}
+
+/// The UTC day `now` falls on, as stored in `UsageDay::date`.
+pub fn usage_date(now: DateTime) -> String {
+    now.to_chrono().format("%Y-%m-%d").to_string()
+}
+
+impl UsageDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, UsageDay::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    /// Add `delta` to the tenant's usage for today.
+    pub async fn add(&self, tenant_id: ObjectId, delta: UsageDelta) -> DaoResult<()> {
+        if delta.is_empty() {
+            return Ok(());
+        }
+        self.upsert_today(
+            tenant_id,
+            doc! {
+                "$inc": {
+                    "call_secs": delta.call_secs as i64,
+                    "transcription_secs": delta.transcription_secs as i64,
+                    "streamed_secs": delta.streamed_secs as i64,
+                },
+            },
+        )
+        .await
+    }
+
+    /// Record the tenant's storage footprint; the day keeps its largest.
+    pub async fn record_storage(&self, tenant_id: ObjectId, bytes: u64) -> DaoResult<()> {
+        self.upsert_today(
+            tenant_id,
+            doc! { "$max": { "storage_bytes": bytes as i64 } },
+        )
+        .await
+    }
+
+    /// The tenant's days from `from` to `to` (inclusive, `YYYY-MM-DD`).
+    pub async fn find_range(
+        &self,
+        tenant_id: ObjectId,
+        from: &str,
+        to: &str,
+    ) -> DaoResult<Vec<UsageDay>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "date": { "$gte": from, "$lte": to } },
+                Some(doc! { "date": 1 }),
+            )
+            .await
+    }
+
+    /// The tenant's days before `before` not yet reported to Stripe.
+    pub async fn find_unreported(
+        &self,
+        tenant_id: ObjectId,
+        before: &str,
+    ) -> DaoResult<Vec<UsageDay>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id, "reported_at": null, "date": { "$lt": before } },
+                Some(doc! { "date": 1 }),
+            )
+            .await
+    }
+
+    pub async fn mark_reported(&self, id: ObjectId) -> DaoResult<()> {
+        self.base
+            .update_by_id(id, doc! { "$set": { "reported_at": DateTime::now() } })
+            .await?;
+        Ok(())
+    }
+
+    async fn upsert_today(&self, tenant_id: ObjectId, mut update: Document) -> DaoResult<()> {
+        let now = DateTime::now();
+        update.insert("$set", doc! { "updated_at": now });
+        update.insert("$setOnInsert", doc! { "created_at": now });
+        self.base
+            .collection()
+            .update_one(
+                doc! { "tenant_id": tenant_id, "date": usage_date(now) },
+                update,
+            )
+            .with_options(UpdateOptions::builder().upsert(true).build())
+            .await?;
+        Ok(())
+    }
+}
@@ -1,17 +1,34 @@
 use bson::{DateTime, doc, oid::ObjectId};
 use mongodb::Database;
-use roomler_ai_db::models::{NotificationPrefs, OAuthProvider, Presence, User, UserStatusInfo};
+use roomler_ai_db::models::{
+    NotificationPrefs, OAuthProvider, Presence, TenantMember, User, UserStatusInfo,
+};
 
 use super::base::{BaseDao, DaoError, DaoResult};
 
+/// What member lists and message authors show of a user.
+#[derive(Debug, Clone)]
+pub struct UserCard {
+    pub username: String,
+    pub display_name: String,
+    pub avatar: Option<String>,
+    pub pronouns: Option<String>,
+    /// Only while it hasn't expired.
+    pub status: Option<UserStatusInfo>,
+    pub is_bot: bool,
+}
+
 pub struct UserDao {
     pub base: BaseDao<User>,
+    /// Memberships in every tenant, whose search keys follow the profile.
+    members: BaseDao<TenantMember>,
 }
 
 impl UserDao {
     pub fn new(db: &Database) -> Self {
         Self {
             base: BaseDao::new(db, User::COLLECTION),
+            members: BaseDao::new(db, TenantMember::COLLECTION),
         }
     }
 
@@ -29,7 +46,9 @@ impl UserDao {
             username,
             display_name,
             avatar: None,
+            avatar_key: None,
             bio: None,
+            pronouns: None,
             password_hash: Some(password_hash),
             status: UserStatusInfo::default(),
             presence: Presence::Offline,
@@ -37,6 +56,7 @@ impl UserDao {
             timezone: "UTC".to_string(),
             is_verified: false,
             is_mfa_enabled: false,
+            is_bot: false,
             last_active_at: None,
             oauth_providers: Vec::new(),
             notification_preferences: NotificationPrefs::default(),
@@ -138,7 +158,9 @@ impl UserDao {
             username: uname,
             display_name: display_name.to_string(),
             avatar: avatar_url.map(|s| s.to_string()),
+            avatar_key: None,
             bio: None,
+            pronouns: None,
             password_hash: None,
             status: UserStatusInfo::default(),
             presence: Presence::Offline,
@@ -146,6 +168,7 @@ impl UserDao {
             timezone: "UTC".to_string(),
             is_verified: true,
             is_mfa_enabled: false,
+            is_bot: false,
             last_active_at: None,
             oauth_providers: vec![OAuthProvider {
                 provider: provider.to_string(),
@@ -218,24 +241,210 @@ impl UserDao {
         Ok(result)
     }
 
+    /// Batch-fetch what member lists and message authors show of each user:
+    /// name (falling back to username, like [`Self::find_display_names`]),
+    /// avatar and a status that hasn't expired.
+    pub async fn find_cards(
+        &self,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<std::collections::HashMap<ObjectId, UserCard>> {
+        if user_ids.is_empty() {
+            return Ok(Default::default());
+        }
+        let users = self
+            .base
+            .find_many(
+                doc! { "_id": { "$in": user_ids }, "deleted_at": null },
+                None,
+            )
+            .await?;
+        let now = DateTime::now();
+        Ok(users
+            .into_iter()
+            .filter_map(|u| {
+                let id = u.id?;
+                let display_name = if u.display_name.is_empty() {
+                    u.username.clone()
+                } else {
+                    u.display_name
+                };
+                let status = u.status.is_active(now).then_some(u.status);
+                Some((
+                    id,
+                    UserCard {
+                        username: u.username,
+                        display_name,
+                        avatar: u.avatar,
+                        pronouns: u.pronouns,
+                        status,
+                        is_bot: u.is_bot,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// Presence of those of `user_ids` who show as online, idle or dnd.
+    pub async fn find_visible_presence(
+        &self,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<std::collections::HashMap<ObjectId, Presence>> {
+        if user_ids.is_empty() {
+            return Ok(Default::default());
+        }
+        let users = self
+            .base
+            .find_many(
+                doc! {
+                    "_id": { "$in": user_ids },
+                    "presence": { "$in": ["online", "idle", "dnd"] },
+                    "deleted_at": null,
+                },
+                None,
+            )
+            .await?;
+        Ok(users
+            .into_iter()
+            .filter_map(|u| u.id.map(|id| (id, u.presence)))
+            .collect())
+    }
+
+    /// Create a bot account. Bots have no password and an unroutable email;
+    /// the username gets a random suffix like OAuth sign-ups.
+    pub async fn create_bot(&self, display_name: &str) -> DaoResult<User> {
+        let now = DateTime::now();
+        let base_username: String = display_name
+            .to_lowercase()
+            .replace(' ', "_")
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '_')
+            .collect();
+
+        for _ in 0..5 {
+            let username = format!("{}_bot_{}", base_username, &ObjectId::new().to_hex()[..6]);
+            let user = User {
+                id: None,
+                email: format!("{}@bots.invalid", username),
+                username,
+                display_name: display_name.to_string(),
+                avatar: None,
+                avatar_key: None,
+                bio: None,
+                pronouns: None,
+                password_hash: None,
+                status: UserStatusInfo::default(),
+                presence: Presence::Offline,
+                locale: "en-US".to_string(),
+                timezone: "UTC".to_string(),
+                is_verified: true,
+                is_mfa_enabled: false,
+                is_bot: true,
+                last_active_at: None,
+                oauth_providers: Vec::new(),
+                notification_preferences: NotificationPrefs::default(),
+                created_at: now,
+                updated_at: now,
+                deleted_at: None,
+            };
+            match self.base.insert_one(&user).await {
+                Ok(id) => return self.base.find_by_id(id).await,
+                Err(DaoError::DuplicateKey(_)) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(DaoError::DuplicateKey(
+            "Failed to generate unique bot username after retries".to_string(),
+        ))
+    }
+
+    /// The subset of `user_ids` that are bot accounts.
+    pub async fn find_bot_ids(
+        &self,
+        user_ids: &[ObjectId],
+    ) -> DaoResult<std::collections::HashSet<ObjectId>> {
+        if user_ids.is_empty() {
+            return Ok(Default::default());
+        }
+        let bots = self
+            .base
+            .find_many(doc! { "_id": { "$in": user_ids }, "is_bot": true }, None)
+            .await?;
+        Ok(bots.into_iter().filter_map(|u| u.id).collect())
+    }
+
+    /// Replace the user's notification preferences.
+    pub async fn set_notification_preferences(
+        &self,
+        user_id: ObjectId,
+        prefs: &NotificationPrefs,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                user_id,
+                doc! { "$set": { "notification_preferences": bson::to_bson(prefs)? } },
+            )
+            .await
+    }
+
+    /// Replace the custom status; a default (empty) one clears it.
+    pub async fn set_status(&self, user_id: ObjectId, status: &UserStatusInfo) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                user_id,
+                doc! { "$set": {
+                    "status": bson::to_bson(status)?,
+                    "updated_at": DateTime::now(),
+                } },
+            )
+            .await
+    }
+
+    /// Point `avatar` at an uploaded picture (`key` set) or clear it.
+    pub async fn set_avatar(
+        &self,
+        user_id: ObjectId,
+        avatar: Option<String>,
+        key: Option<String>,
+    ) -> DaoResult<bool> {
+        self.base
+            .update_by_id(
+                user_id,
+                doc! { "$set": {
+                    "avatar": avatar,
+                    "avatar_key": key,
+                    "updated_at": DateTime::now(),
+                } },
+            )
+            .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
     pub async fn update_profile(
         &self,
         user_id: ObjectId,
         display_name: Option<String>,
         bio: Option<String>,
         avatar: Option<String>,
+        pronouns: Option<String>,
         locale: Option<String>,
         timezone: Option<String>,
     ) -> DaoResult<bool> {
         let mut update = bson::Document::new();
+        let renamed = display_name.is_some();
         if let Some(name) = display_name {
             update.insert("display_name", name);
         }
         if let Some(b) = bio {
             update.insert("bio", b);
         }
+        // A URL set by hand replaces any uploaded picture.
         if let Some(av) = avatar {
             update.insert("avatar", av);
+            update.insert("avatar_key", bson::Bson::Null);
+        }
+        if let Some(p) = pronouns {
+            update.insert("pronouns", p);
         }
         if let Some(loc) = locale {
             update.insert("locale", loc);
@@ -250,8 +459,27 @@ impl UserDao {
 
         update.insert("updated_at", DateTime::now());
 
-        self.base
+        let updated = self
+            .base
             .update_by_id(user_id, doc! { "$set": update })
-            .await
+            .await?;
+        if renamed {
+            self.refresh_search_keys(user_id).await?;
+        }
+        Ok(updated)
+    }
+
+    /// Recompute the user's member search keys in every tenant.
+    async fn refresh_search_keys(&self, user_id: ObjectId) -> DaoResult<()> {
+        let user = self.base.find_by_id(user_id).await?;
+        let keys = TenantMember::search_keys(&user.display_name, &user.username, &user.email);
+        self.members
+            .collection()
+            .update_many(
+                doc! { "user_id": user_id },
+                doc! { "$set": { "search_keys": keys } },
+            )
+            .await?;
+        Ok(())
     }
 }
@@ -0,0 +1,181 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::{Webhook, WebhookKind};
+
+use super::base::{BaseDao, DaoError, DaoResult};
+
+pub struct WebhookDao {
+    pub base: BaseDao<Webhook>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CreateWebhookParams {
+    pub name: String,
+    pub kind: WebhookKind,
+    pub room_ids: Vec<ObjectId>,
+    pub keywords: Vec<String>,
+    pub events: Vec<String>,
+    pub url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct UpdateWebhookParams {
+    pub name: Option<String>,
+    pub room_ids: Option<Vec<ObjectId>>,
+    pub keywords: Option<Vec<String>>,
+    pub events: Option<Vec<String>>,
+    pub url: Option<String>,
+    pub is_active: Option<bool>,
+}
+
+impl WebhookDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, Webhook::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn create(
+        &self,
+        tenant_id: ObjectId,
+        created_by: ObjectId,
+        params: CreateWebhookParams,
+    ) -> DaoResult<Webhook> {
+        let now = DateTime::now();
+        let webhook = Webhook {
+            id: None,
+            tenant_id,
+            name: params.name,
+            kind: params.kind,
+            room_ids: params.room_ids,
+            keywords: params
+                .keywords
+                .into_iter()
+                .map(|k| k.to_lowercase())
+                .collect(),
+            events: params.events,
+            url: params.url,
+            secret: nanoid::nanoid!(32),
+            is_active: true,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        let id = self.base.insert_one(&webhook).await?;
+        self.base.find_by_id(id).await
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: ObjectId) -> DaoResult<Vec<Webhook>> {
+        self.base
+            .find_many(
+                doc! { "tenant_id": tenant_id },
+                Some(doc! { "created_at": 1 }),
+            )
+            .await
+    }
+
+    pub async fn update(
+        &self,
+        tenant_id: ObjectId,
+        id: ObjectId,
+        params: UpdateWebhookParams,
+    ) -> DaoResult<Webhook> {
+        let mut set_doc = doc! {};
+        if let Some(name) = params.name {
+            set_doc.insert("name", name);
+        }
+        if let Some(room_ids) = params.room_ids {
+            set_doc.insert("room_ids", room_ids);
+        }
+        if let Some(keywords) = params.keywords {
+            let keywords: Vec<String> = keywords.into_iter().map(|k| k.to_lowercase()).collect();
+            set_doc.insert("keywords", keywords);
+        }
+        if let Some(events) = params.events {
+            set_doc.insert("events", events);
+        }
+        if let Some(url) = params.url {
+            set_doc.insert("url", url);
+        }
+        if let Some(is_active) = params.is_active {
+            set_doc.insert("is_active", is_active);
+        }
+
+        if !self
+            .base
+            .update_one(
+                doc! { "_id": id, "tenant_id": tenant_id },
+                doc! { "$set": set_doc },
+            )
+            .await?
+        {
+            return Err(DaoError::NotFound);
+        }
+        self.base.find_by_id_in_tenant(tenant_id, id).await
+    }
+
+    pub async fn delete(&self, tenant_id: ObjectId, id: ObjectId) -> DaoResult<bool> {
+        let deleted = self
+            .base
+            .hard_delete(doc! { "_id": id, "tenant_id": tenant_id })
+            .await?;
+        Ok(deleted > 0)
+    }
+
+    /// Active outgoing webhooks subscribed to `event` whose room filter
+    /// matches.
+    pub async fn find_subscribers(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        event: &str,
+    ) -> DaoResult<Vec<Webhook>> {
+        let hooks = self
+            .base
+            .find_many(
+                doc! {
+                    "tenant_id": tenant_id,
+                    "kind": "outgoing",
+                    "is_active": true,
+                    "$or": [{ "room_ids": { "$size": 0 } }, { "room_ids": room_id }],
+                },
+                None,
+            )
+            .await?;
+        Ok(hooks
+            .into_iter()
+            .filter(|h| h.subscribes_to(event))
+            .collect())
+    }
+
+    /// Active outgoing webhooks whose room and keyword filters match a
+    /// message.
+    pub async fn find_outgoing_matches(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        content: &str,
+    ) -> DaoResult<Vec<Webhook>> {
+        let hooks = self
+            .find_subscribers(tenant_id, room_id, "message.create")
+            .await?;
+        let content = content.to_lowercase();
+        Ok(hooks
+            .into_iter()
+            .filter(|h| h.keywords.is_empty() || h.keywords.iter().any(|k| content.contains(k)))
+            .collect())
+    }
+
+    /// The active incoming webhook `id` if `token` matches its secret.
+    pub async fn find_incoming(&self, id: ObjectId, token: &str) -> DaoResult<Webhook> {
+        self.base
+            .find_one(doc! {
+                "_id": id,
+                "kind": "incoming",
+                "secret": token,
+                "is_active": true,
+            })
+            .await?
+            .ok_or(DaoError::NotFound)
+    }
+}
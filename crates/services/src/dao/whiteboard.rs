@@ -0,0 +1,63 @@
+use bson::{DateTime, doc, oid::ObjectId};
+use mongodb::Database;
+use roomler_ai_db::models::Whiteboard;
+
+use super::base::{BaseDao, DaoResult};
+
+pub struct WhiteboardDao {
+    pub base: BaseDao<Whiteboard>,
+}
+
+impl WhiteboardDao {
+    pub fn new(db: &Database) -> Self {
+        Self {
+            base: BaseDao::new(db, Whiteboard::COLLECTION).tenant_scoped(),
+        }
+    }
+
+    pub async fn find_by_room(&self, room_id: ObjectId) -> DaoResult<Option<Whiteboard>> {
+        self.base.find_one(doc! { "room_id": room_id }).await
+    }
+
+    /// Persist a snapshot of the room's board, creating the document on first
+    /// save.
+    pub async fn save_snapshot(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        seq: i64,
+        elements: &[serde_json::Value],
+    ) -> DaoResult<()> {
+        let now = DateTime::now();
+        self.base
+            .collection()
+            .update_one(
+                doc! { "room_id": room_id },
+                doc! {
+                    "$set": {
+                        "seq": seq,
+                        "elements": bson::to_bson(elements)?,
+                        "updated_at": now,
+                    },
+                    "$setOnInsert": {
+                        "tenant_id": tenant_id,
+                        "export_file_id": null,
+                        "created_at": now,
+                    },
+                },
+            )
+            .upsert(true)
+            .await?;
+        Ok(())
+    }
+
+    /// Record the file holding the board's latest image export.
+    pub async fn set_export(&self, room_id: ObjectId, file_id: ObjectId) -> DaoResult<bool> {
+        self.base
+            .update_one(
+                doc! { "room_id": room_id },
+                doc! { "$set": { "export_file_id": file_id } },
+            )
+            .await
+    }
+}
@@ -78,11 +78,6 @@ impl RecognitionService {
         file_bytes: &[u8],
         content_type: &str,
     ) -> Result<RecognitionResult, String> {
-        let api_key = self
-            .api_key
-            .as_ref()
-            .ok_or_else(|| "Claude API key not configured".to_string())?;
-
         let b64 = base64::engine::general_purpose::STANDARD.encode(file_bytes);
 
         let media_type = match content_type {
@@ -96,34 +91,70 @@ impl RecognitionService {
             }
         };
 
+        let text = self
+            .send(vec![
+                ClaudeContent::Image {
+                    source: ImageSource {
+                        source_type: "base64".to_string(),
+                        media_type,
+                        data: b64,
+                    },
+                },
+                ClaudeContent::Text {
+                    text: concat!(
+                        "Extract all text and structured data from this document. ",
+                        "Identify the document type (invoice, receipt, bank statement, ",
+                        "contract, letter, form, report, etc). ",
+                        "Return a JSON object with these fields:\n",
+                        "- \"raw_text\": all extracted text\n",
+                        "- \"document_type\": the identified type\n",
+                        "- \"structured_data\": key-value pairs of important fields\n",
+                        "- \"confidence\": 0.0-1.0 confidence score\n",
+                        "Return ONLY the JSON, no markdown fences."
+                    )
+                    .to_string(),
+                },
+            ])
+            .await?;
+
+        // Parse the JSON response
+        match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(json) => Ok(RecognitionResult {
+                raw_text: json["raw_text"].as_str().unwrap_or("").to_string(),
+                structured_data: json.get("structured_data").cloned(),
+                document_type: json["document_type"].as_str().map(|s| s.to_string()),
+                confidence: json["confidence"].as_f64().unwrap_or(0.5),
+            }),
+            Err(_) => {
+                // If Claude didn't return valid JSON, use the raw text
+                Ok(RecognitionResult {
+                    raw_text: text,
+                    structured_data: None,
+                    document_type: None,
+                    confidence: 0.3,
+                })
+            }
+        }
+    }
+
+    /// Answer a text-only prompt.
+    pub async fn complete(&self, prompt: String) -> Result<String, String> {
+        self.send(vec![ClaudeContent::Text { text: prompt }]).await
+    }
+
+    /// Send one user message and return the text of the reply.
+    async fn send(&self, content: Vec<ClaudeContent>) -> Result<String, String> {
+        let api_key = self
+            .api_key
+            .as_ref()
+            .ok_or_else(|| "Claude API key not configured".to_string())?;
+
         let request = ClaudeRequest {
             model: self.model.clone(),
             max_tokens: self.max_tokens,
             messages: vec![ClaudeMessage {
                 role: "user".to_string(),
-                content: vec![
-                    ClaudeContent::Image {
-                        source: ImageSource {
-                            source_type: "base64".to_string(),
-                            media_type,
-                            data: b64,
-                        },
-                    },
-                    ClaudeContent::Text {
-                        text: concat!(
-                            "Extract all text and structured data from this document. ",
-                            "Identify the document type (invoice, receipt, bank statement, ",
-                            "contract, letter, form, report, etc). ",
-                            "Return a JSON object with these fields:\n",
-                            "- \"raw_text\": all extracted text\n",
-                            "- \"document_type\": the identified type\n",
-                            "- \"structured_data\": key-value pairs of important fields\n",
-                            "- \"confidence\": 0.0-1.0 confidence score\n",
-                            "Return ONLY the JSON, no markdown fences."
-                        )
-                        .to_string(),
-                    },
-                ],
+                content,
             }],
         };
 
@@ -149,29 +180,11 @@ impl RecognitionService {
             .await
             .map_err(|e| format!("Failed to parse Claude response: {}", e))?;
 
-        let text = claude_resp
+        claude_resp
             .content
-            .first()
-            .and_then(|c| c.text.as_ref())
-            .ok_or_else(|| "No text in Claude response".to_string())?;
-
-        // Parse the JSON response
-        match serde_json::from_str::<serde_json::Value>(text) {
-            Ok(json) => Ok(RecognitionResult {
-                raw_text: json["raw_text"].as_str().unwrap_or("").to_string(),
-                structured_data: json.get("structured_data").cloned(),
-                document_type: json["document_type"].as_str().map(|s| s.to_string()),
-                confidence: json["confidence"].as_f64().unwrap_or(0.5),
-            }),
-            Err(_) => {
-                // If Claude didn't return valid JSON, use the raw text
-                Ok(RecognitionResult {
-                    raw_text: text.clone(),
-                    structured_data: None,
-                    document_type: None,
-                    confidence: 0.3,
-                })
-            }
-        }
+            .into_iter()
+            .next()
+            .and_then(|c| c.text)
+            .ok_or_else(|| "No text in Claude response".to_string())
     }
 }
@@ -0,0 +1,130 @@
+//! DNS proof that a tenant controls an email domain: a TXT record at
+//! `_roomler-verify.<domain>` holding `roomler-verify=<token>`, with the
+//! token issued when the domain is claimed.
+
+use hickory_resolver::{TokioResolver, proto::rr::RData};
+
+const RECORD_LABEL: &str = "_roomler-verify";
+const VALUE_PREFIX: &str = "roomler-verify=";
+
+/// Where the TXT record for `domain` goes.
+pub fn record_name(domain: &str) -> String {
+    format!("{RECORD_LABEL}.{domain}")
+}
+
+/// What the TXT record must hold.
+pub fn record_value(token: &str) -> String {
+    format!("{VALUE_PREFIX}{token}")
+}
+
+/// `input` lowercased, without surrounding whitespace or a trailing dot,
+/// if it is a host name of at least two labels made of letters, digits and
+/// inner hyphens.
+pub fn normalize_domain(input: &str) -> Option<String> {
+    let domain = input.trim().trim_end_matches('.').to_ascii_lowercase();
+    let labels: Vec<&str> = domain.split('.').collect();
+    let valid = domain.len() <= 253
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        });
+    valid.then_some(domain)
+}
+
+/// The normalized domain of an email address.
+pub fn email_domain(email: &str) -> Option<String> {
+    email
+        .rsplit_once('@')
+        .and_then(|(_, domain)| normalize_domain(domain))
+}
+
+/// A TXT record's character strings joined, as long values are split
+/// into 255-byte pieces.
+fn txt_string(parts: &[Box<[u8]>]) -> String {
+    parts.iter().map(|p| String::from_utf8_lossy(p)).collect()
+}
+
+/// Looks up verification records through the host's resolvers.
+#[derive(Clone)]
+pub struct DomainVerifier {
+    resolver: TokioResolver,
+}
+
+impl DomainVerifier {
+    pub fn from_system() -> Result<Self, String> {
+        let resolver = TokioResolver::builder_tokio()
+            .and_then(|builder| builder.build())
+            .map_err(|e| e.to_string())?;
+        Ok(Self { resolver })
+    }
+
+    /// Whether `domain` publishes the record for `token`. A name without
+    /// TXT records is `Ok(false)`; `Err` means DNS could not be asked.
+    pub async fn has_token(&self, domain: &str, token: &str) -> Result<bool, String> {
+        let lookup = match self
+            .resolver
+            .txt_lookup(format!("{}.", record_name(domain)))
+            .await
+        {
+            Ok(lookup) => lookup,
+            Err(e) if e.is_no_records_found() => return Ok(false),
+            Err(e) => return Err(e.to_string()),
+        };
+        let expected = record_value(token);
+        Ok(lookup.answers().iter().any(|record| match &record.data {
+            RData::TXT(txt) => txt_string(&txt.txt_data) == expected,
+            _ => false,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn domains_are_normalized_and_checked() {
+        assert_eq!(
+            normalize_domain(" Example.COM. ").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            normalize_domain("mail.my-corp.co.uk").as_deref(),
+            Some("mail.my-corp.co.uk")
+        );
+        for bad in [
+            "",
+            "localhost",
+            "a..com",
+            "-corp.com",
+            "corp-.com",
+            "co rp.com",
+            "x@y.com",
+        ] {
+            assert_eq!(normalize_domain(bad), None, "{bad}");
+        }
+    }
+
+    #[test]
+    fn email_domain_takes_the_last_at() {
+        assert_eq!(
+            email_domain("Jane.Doe@Corp.example").as_deref(),
+            Some("corp.example")
+        );
+        assert_eq!(email_domain("no-at-sign"), None);
+    }
+
+    #[test]
+    fn split_txt_records_are_joined() {
+        let parts: Vec<Box<[u8]>> = vec![
+            b"roomler-verify=".to_vec().into_boxed_slice(),
+            b"abc".to_vec().into_boxed_slice(),
+        ];
+        assert_eq!(txt_string(&parts), record_value("abc"));
+        assert_eq!(record_name("corp.example"), "_roomler-verify.corp.example");
+    }
+}
@@ -0,0 +1,266 @@
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{CallChatMessage, CallSession, Message, Room, User};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{self, Seek, Write};
+use zip::{ZipWriter, write::SimpleFileOptions};
+
+/// A room archive being written as a zip:
+///
+/// ```text
+/// room.json                      room metadata, export time and exporter
+/// messages.json                  every message, thread replies and deleted ones included
+/// messages.html                  the same, readable in a browser
+/// attachments/<file_id>-<name>   attachment contents
+/// calls.json                     call history
+/// transcripts/call-chat.txt      in-call chat, one line per message
+/// ```
+pub struct RoomArchive<W: Write + Seek> {
+    zip: ZipWriter<W>,
+}
+
+impl<W: Write + Seek> RoomArchive<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            zip: ZipWriter::new(writer),
+        }
+    }
+
+    pub fn add(&mut self, path: &str, bytes: &[u8]) -> io::Result<()> {
+        self.zip
+            .start_file(path, SimpleFileOptions::default().large_file(true))?;
+        self.zip.write_all(bytes)?;
+        Ok(())
+    }
+
+    pub fn add_json(&mut self, path: &str, value: &Value) -> io::Result<()> {
+        let bytes = serde_json::to_vec_pretty(value).unwrap_or_default();
+        self.add(path, &bytes)
+    }
+
+    pub fn finish(self) -> io::Result<W> {
+        Ok(self.zip.finish()?)
+    }
+}
+
+/// Path of an attachment inside the archive. The file id keeps names unique;
+/// the original name is reduced to characters safe in any unzip tool.
+pub fn attachment_path(file_id: ObjectId, filename: &str) -> String {
+    let name: String = filename
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let name = name.trim_start_matches('.');
+    format!("attachments/{}-{}", file_id.to_hex(), name)
+}
+
+pub fn room_json(room: &Room, exported_by: &User) -> Value {
+    json!({
+        "id": room.id.map(|id| id.to_hex()),
+        "name": room.name,
+        "path": room.path,
+        "created_at": rfc3339(room.created_at),
+        "exported_at": rfc3339(bson::DateTime::now()),
+        "exported_by": {
+            "id": exported_by.id.map(|id| id.to_hex()),
+            "display_name": exported_by.display_name,
+        },
+    })
+}
+
+/// `messages` oldest first.
+pub fn messages_json(messages: &[Message], users: &HashMap<ObjectId, User>) -> Value {
+    Value::Array(
+        messages
+            .iter()
+            .map(|m| {
+                json!({
+                    "id": m.id.map(|id| id.to_hex()),
+                    "thread_id": m.thread_id.map(|id| id.to_hex()),
+                    "author_id": m.author_id.to_hex(),
+                    "author_name": author_name(m, users),
+                    "content": m.content,
+                    "attachments": m.attachments.iter().map(|a| json!({
+                        "file_id": a.file_id.to_hex(),
+                        "filename": a.filename,
+                        "content_type": a.content_type,
+                        "size": a.size,
+                        "path": attachment_path(a.file_id, &a.filename),
+                    })).collect::<Vec<_>>(),
+                    "reactions": m.reaction_summary.iter().map(|r| json!({
+                        "emoji": r.emoji,
+                        "count": r.count,
+                    })).collect::<Vec<_>>(),
+                    "is_pinned": m.is_pinned,
+                    "edit_history": m.edit_history.iter().map(|e| json!({
+                        "content": e.content,
+                        "edited_at": rfc3339(e.edited_at),
+                    })).collect::<Vec<_>>(),
+                    "created_at": rfc3339(m.created_at),
+                    "edited_at": m.edited_at.map(rfc3339),
+                    "deleted_at": m.deleted_at.map(rfc3339),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// A standalone HTML page of the conversation, `messages` oldest first.
+pub fn messages_html(
+    room_name: &str,
+    messages: &[Message],
+    users: &HashMap<ObjectId, User>,
+) -> String {
+    let mut html = String::new();
+    let _ = write!(
+        html,
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{}</title>\
+         <style>body{{font-family:sans-serif;max-width:48em;margin:2em auto}}\
+         .msg{{margin:.6em 0}}.meta{{color:#666;font-size:.85em}}\
+         .reply{{margin-left:2em}}.deleted{{color:#999}}</style></head><body>\n<h1>{}</h1>\n",
+        escape(room_name),
+        escape(room_name)
+    );
+    for m in messages {
+        let mut classes = String::from("msg");
+        if m.thread_id.is_some() {
+            classes.push_str(" reply");
+        }
+        if m.deleted_at.is_some() {
+            classes.push_str(" deleted");
+        }
+        let _ = write!(
+            html,
+            "<div class=\"{}\" id=\"m-{}\"><div class=\"meta\"><b>{}</b> {}{}{}</div><div>{}</div>",
+            classes,
+            m.id.map(|id| id.to_hex()).unwrap_or_default(),
+            escape(&author_name(m, users)),
+            rfc3339(m.created_at),
+            if m.is_edited { " (edited)" } else { "" },
+            if m.deleted_at.is_some() {
+                " (deleted)"
+            } else {
+                ""
+            },
+            escape(&m.content).replace('\n', "<br>")
+        );
+        for a in &m.attachments {
+            let _ = write!(
+                html,
+                "<div><a href=\"{}\">{}</a></div>",
+                escape(&attachment_path(a.file_id, &a.filename)),
+                escape(&a.filename)
+            );
+        }
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body></html>\n");
+    html
+}
+
+/// `sessions` newest first, as listed by the call history.
+pub fn calls_json(sessions: &[CallSession]) -> Value {
+    Value::Array(
+        sessions
+            .iter()
+            .map(|s| {
+                json!({
+                    "id": s.id.map(|id| id.to_hex()),
+                    "started_by": s.started_by.to_hex(),
+                    "started_at": rfc3339(s.started_at),
+                    "ended_at": s.ended_at.map(rfc3339),
+                    "peak_participants": s.peak_participants,
+                    "participants": s.participants.iter().map(|p| json!({
+                        "user_id": p.user_id.to_hex(),
+                        "display_name": p.display_name,
+                        "joined_at": rfc3339(p.joined_at),
+                        "left_at": p.left_at.map(rfc3339),
+                        "duration": p.duration,
+                    })).collect::<Vec<_>>(),
+                    "recording_ids": s.recording_ids.iter().map(|id| id.to_hex()).collect::<Vec<_>>(),
+                })
+            })
+            .collect(),
+    )
+}
+
+/// In-call chat as plain text, `[time] name: content` per line, oldest first.
+pub fn call_chat_transcript(messages: &[CallChatMessage]) -> String {
+    let mut text = String::new();
+    for m in messages {
+        let _ = writeln!(
+            text,
+            "[{}] {}: {}",
+            rfc3339(m.created_at),
+            m.display_name,
+            m.content.replace('\n', " ")
+        );
+    }
+    text
+}
+
+fn author_name(message: &Message, users: &HashMap<ObjectId, User>) -> String {
+    message
+        .author_name
+        .clone()
+        .or_else(|| {
+            users
+                .get(&message.author_id)
+                .map(|u| u.display_name.clone())
+        })
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+fn rfc3339(dt: bson::DateTime) -> String {
+    dt.try_to_rfc3339_string().unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn attachment_paths_stay_inside_the_archive() {
+        let id = ObjectId::parse_str("65f000000000000000000001").unwrap();
+        assert_eq!(
+            attachment_path(id, "report v2.pdf"),
+            "attachments/65f000000000000000000001-report_v2.pdf"
+        );
+        assert_eq!(
+            attachment_path(id, "../../etc/passwd"),
+            "attachments/65f000000000000000000001-_.._etc_passwd"
+        );
+    }
+
+    #[test]
+    fn transcript_is_one_line_per_message() {
+        let msg = |content: &str| CallChatMessage {
+            id: None,
+            tenant_id: ObjectId::new(),
+            room_id: ObjectId::new(),
+            author_id: ObjectId::new(),
+            author_type: Default::default(),
+            display_name: "Ana".to_string(),
+            content: content.to_string(),
+            created_at: bson::DateTime::from_millis(0),
+        };
+        assert_eq!(
+            call_chat_transcript(&[msg("hi"), msg("two\nlines")]),
+            "[1970-01-01T00:00:00Z] Ana: hi\n[1970-01-01T00:00:00Z] Ana: two lines\n"
+        );
+    }
+}
@@ -1,2 +1,4 @@
+pub mod archive;
 pub mod excel;
 pub mod pdf;
+pub mod whiteboard;
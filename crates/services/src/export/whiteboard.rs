@@ -0,0 +1,225 @@
+use serde_json::Value;
+use std::fmt::Write;
+
+/// Margin around the drawn content, in board units.
+const PADDING: f64 = 20.0;
+/// Canvas size for an empty board.
+const EMPTY_SIZE: (f64, f64) = (800.0, 600.0);
+
+/// Render whiteboard elements to a standalone SVG image.
+///
+/// Understood kinds: `path` and `line` (`points: [[x, y], ...]`), `rect` and
+/// `ellipse` (`x`, `y`, `width`, `height`, optional `fill`), and `text` (`x`,
+/// `y`, `text`, optional `font_size`). All take `color` and `stroke_width`.
+/// Unknown kinds are skipped. The view box is fitted to the content.
+pub fn render_svg(elements: &[Value]) -> String {
+    let mut body = String::new();
+    let mut bounds = Bounds::default();
+
+    for el in elements {
+        let color = safe_color(el, "color").unwrap_or("#000000");
+        let stroke_width = num(el, "stroke_width").unwrap_or(2.0);
+        match el.get("kind").and_then(|k| k.as_str()) {
+            Some("path") | Some("line") => {
+                let points = points(el);
+                if points.is_empty() {
+                    continue;
+                }
+                let coords: Vec<String> = points
+                    .iter()
+                    .map(|(x, y)| {
+                        bounds.add(*x, *y);
+                        format!("{},{}", x, y)
+                    })
+                    .collect();
+                let _ = writeln!(
+                    body,
+                    r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+                    coords.join(" "),
+                    color,
+                    stroke_width
+                );
+            }
+            Some(kind @ ("rect" | "ellipse")) => {
+                let (Some(x), Some(y), Some(w), Some(h)) = (
+                    num(el, "x"),
+                    num(el, "y"),
+                    num(el, "width"),
+                    num(el, "height"),
+                ) else {
+                    continue;
+                };
+                // Shapes drawn right-to-left or bottom-to-top have negative sizes.
+                let (x, w) = if w < 0.0 { (x + w, -w) } else { (x, w) };
+                let (y, h) = if h < 0.0 { (y + h, -h) } else { (y, h) };
+                bounds.add(x, y);
+                bounds.add(x + w, y + h);
+                let fill = safe_color(el, "fill").unwrap_or("none");
+                if kind == "rect" {
+                    let _ = writeln!(
+                        body,
+                        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                        x, y, w, h, fill, color, stroke_width
+                    );
+                } else {
+                    let _ = writeln!(
+                        body,
+                        r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}" fill="{}" stroke="{}" stroke-width="{}"/>"#,
+                        x + w / 2.0,
+                        y + h / 2.0,
+                        w / 2.0,
+                        h / 2.0,
+                        fill,
+                        color,
+                        stroke_width
+                    );
+                }
+            }
+            Some("text") => {
+                let (Some(x), Some(y), Some(text)) = (
+                    num(el, "x"),
+                    num(el, "y"),
+                    el.get("text").and_then(|t| t.as_str()),
+                ) else {
+                    continue;
+                };
+                let font_size = num(el, "font_size").unwrap_or(16.0);
+                // Rough extent so text near the edge isn't clipped.
+                bounds.add(x, y - font_size);
+                bounds.add(x + text.chars().count() as f64 * font_size * 0.6, y);
+                let _ = writeln!(
+                    body,
+                    r#"<text x="{}" y="{}" font-family="sans-serif" font-size="{}" fill="{}">{}</text>"#,
+                    x,
+                    y,
+                    font_size,
+                    color,
+                    escape(text)
+                );
+            }
+            _ => {}
+        }
+    }
+
+    let (min_x, min_y, width, height) = match bounds.rect() {
+        Some((x0, y0, x1, y1)) => (
+            x0 - PADDING,
+            y0 - PADDING,
+            x1 - x0 + 2.0 * PADDING,
+            y1 - y0 + 2.0 * PADDING,
+        ),
+        None => (0.0, 0.0, EMPTY_SIZE.0, EMPTY_SIZE.1),
+    };
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="{x} {y} {w} {h}">"#,
+            "\n",
+            r##"<rect x="{x}" y="{y}" width="{w}" height="{h}" fill="#ffffff"/>"##,
+            "\n{body}</svg>\n"
+        ),
+        x = min_x,
+        y = min_y,
+        w = width,
+        h = height,
+        body = body
+    )
+}
+
+#[derive(Default)]
+struct Bounds(Option<(f64, f64, f64, f64)>);
+
+impl Bounds {
+    fn add(&mut self, x: f64, y: f64) {
+        self.0 = Some(match self.0 {
+            Some((x0, y0, x1, y1)) => (x0.min(x), y0.min(y), x1.max(x), y1.max(y)),
+            None => (x, y, x, y),
+        });
+    }
+
+    fn rect(&self) -> Option<(f64, f64, f64, f64)> {
+        self.0
+    }
+}
+
+fn num(el: &Value, key: &str) -> Option<f64> {
+    el.get(key)
+        .and_then(|v| v.as_f64())
+        .filter(|v| v.is_finite())
+}
+
+fn points(el: &Value) -> Vec<(f64, f64)> {
+    el.get("points")
+        .and_then(|p| p.as_array())
+        .map(|pts| {
+            pts.iter()
+                .filter_map(|p| {
+                    let p = p.as_array()?;
+                    let x = p.first()?.as_f64().filter(|v| v.is_finite())?;
+                    let y = p.get(1)?.as_f64().filter(|v| v.is_finite())?;
+                    Some((x, y))
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// A colour attribute, accepted only as `#hex` or a bare colour name so
+/// client data can't break out of the attribute.
+fn safe_color<'a>(el: &'a Value, key: &str) -> Option<&'a str> {
+    el.get(key).and_then(|v| v.as_str()).filter(|c| {
+        let hex = c.strip_prefix('#').is_some_and(|h| {
+            matches!(h.len(), 3 | 4 | 6 | 8) && h.chars().all(|ch| ch.is_ascii_hexdigit())
+        });
+        hex || (!c.is_empty() && c.len() <= 32 && c.chars().all(|ch| ch.is_ascii_alphabetic()))
+    })
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn renders_known_kinds_and_fits_the_view_box() {
+        let svg = render_svg(&[
+            json!({ "id": "1", "kind": "rect", "x": 10, "y": 10, "width": 100, "height": 50, "color": "#ff0000" }),
+            json!({ "id": "2", "kind": "path", "points": [[0, 0], [200, 100]] }),
+            json!({ "id": "3", "kind": "ellipse", "x": 0, "y": 0, "width": -40, "height": 20, "fill": "blue" }),
+            json!({ "id": "4", "kind": "sticker" }),
+        ]);
+        assert!(svg.contains(
+            r##"<rect x="10" y="10" width="100" height="50" fill="none" stroke="#ff0000""##
+        ));
+        assert!(svg.contains(r#"points="0,0 200,100""#));
+        assert!(svg.contains(r#"<ellipse cx="-20" cy="10" rx="20" ry="10" fill="blue""#));
+        assert!(svg.contains(r#"viewBox="-60 -20 280 140""#));
+    }
+
+    #[test]
+    fn escapes_text_and_rejects_unsafe_colours() {
+        let svg = render_svg(&[json!({
+            "id": "t",
+            "kind": "text",
+            "x": 0,
+            "y": 20,
+            "text": "<b>&\"",
+            "color": "red\" onload=\"x",
+        })]);
+        assert!(svg.contains("&lt;b&gt;&amp;&quot;</text>"));
+        assert!(!svg.contains("onload"));
+        assert!(svg.contains(r##"fill="#000000""##));
+    }
+
+    #[test]
+    fn empty_board_renders_a_blank_canvas() {
+        let svg = render_svg(&[]);
+        assert!(svg.contains(r#"viewBox="0 0 800 600""#));
+    }
+}
@@ -0,0 +1,148 @@
+//! Per-tenant feature flags, for shipping risky features gradually.
+//!
+//! Every flag is declared in [`FLAGS`] with a built-in default. A
+//! deployment may change the default with `features.<key>` (see the runtime
+//! settings), and a tenant may pin the flag on or off, or roll it out to a
+//! percentage of users. A percentage rollout hashes the flag key with the
+//! subject's id, so the same user (or room, for room-wide flags) keeps the
+//! same answer as the percentage grows.
+
+use std::collections::BTreeMap;
+
+use bson::oid::ObjectId;
+use roomler_ai_db::models::{FeatureFlag, FlagMode};
+use sha2::{Digest, Sha256};
+
+/// End-to-end encrypted calls; decided per room when its call starts.
+pub const E2EE: &str = "e2ee";
+/// Splitting a call into breakout rooms; decided per user.
+pub const BREAKOUT_ROOMS: &str = "breakout_rooms";
+/// The AI meeting assistant answering `/assistant` in call chat; decided
+/// per room.
+pub const MEETING_ASSISTANT: &str = "meeting_assistant";
+
+pub struct FlagInfo {
+    pub key: &'static str,
+    pub description: &'static str,
+    /// Used when neither the tenant nor the deployment sets the flag.
+    pub default: bool,
+}
+
+pub const FLAGS: &[FlagInfo] = &[
+    FlagInfo {
+        key: BREAKOUT_ROOMS,
+        description: "Split a call into breakout rooms",
+        default: true,
+    },
+    FlagInfo {
+        key: E2EE,
+        description: "End-to-end encrypted calls in rooms that ask for them",
+        default: true,
+    },
+    FlagInfo {
+        key: MEETING_ASSISTANT,
+        description: "AI meeting assistant answering /assistant in call chat",
+        default: false,
+    },
+];
+
+pub fn info(key: &str) -> Option<&'static FlagInfo> {
+    FLAGS.iter().find(|f| f.key == key)
+}
+
+/// The deployment's default for `key`: `features.<key>` if set, else the
+/// built-in one. Unknown flags are off.
+pub fn deployment_default(key: &str, features: &BTreeMap<String, bool>) -> bool {
+    features
+        .get(key)
+        .copied()
+        .or_else(|| info(key).map(|f| f.default))
+        .unwrap_or(false)
+}
+
+/// Where `subject` falls in a percentage rollout of `key`, 0-99.
+pub fn bucket(key: &str, subject: &ObjectId) -> u8 {
+    let digest = Sha256::new()
+        .chain_update(key.as_bytes())
+        .chain_update(b":")
+        .chain_update(subject.bytes())
+        .finalize();
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as u8
+}
+
+/// Whether `key` is on for `subject`, given the tenant's setting (if any).
+pub fn is_enabled(
+    key: &str,
+    flag: Option<&FeatureFlag>,
+    features: &BTreeMap<String, bool>,
+    subject: &ObjectId,
+) -> bool {
+    match flag.map(|f| (f.mode, f.percentage)) {
+        None | Some((FlagMode::Default, _)) => deployment_default(key, features),
+        Some((FlagMode::On, _)) => true,
+        Some((FlagMode::Off, _)) => false,
+        Some((FlagMode::Percentage, percentage)) => bucket(key, subject) < percentage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::DateTime;
+
+    fn flag(mode: FlagMode, percentage: u8) -> FeatureFlag {
+        FeatureFlag {
+            id: None,
+            tenant_id: ObjectId::new(),
+            key: E2EE.to_string(),
+            mode,
+            percentage,
+            updated_by: ObjectId::new(),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn defaults_come_from_the_deployment_then_the_registry() {
+        let user = ObjectId::new();
+        let mut features = BTreeMap::new();
+        assert!(is_enabled(E2EE, None, &features, &user));
+        assert!(!is_enabled("unknown", None, &features, &user));
+        features.insert(E2EE.to_string(), false);
+        assert!(!is_enabled(E2EE, None, &features, &user));
+        let pinned = flag(FlagMode::Default, 0);
+        assert!(!is_enabled(E2EE, Some(&pinned), &features, &user));
+        assert!(is_enabled(
+            E2EE,
+            Some(&flag(FlagMode::On, 0)),
+            &features,
+            &user
+        ));
+        assert!(!is_enabled(
+            E2EE,
+            Some(&flag(FlagMode::Off, 0)),
+            &BTreeMap::new(),
+            &user
+        ));
+    }
+
+    #[test]
+    fn percentage_rollout_is_stable_and_grows() {
+        let users: Vec<ObjectId> = (0..1000).map(|_| ObjectId::new()).collect();
+        let on = |percentage| {
+            let flag = flag(FlagMode::Percentage, percentage);
+            users
+                .iter()
+                .filter(|u| is_enabled(E2EE, Some(&flag), &BTreeMap::new(), u))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+        assert!(on(0).is_empty());
+        assert_eq!(on(100).len(), users.len());
+        let (ten, fifty) = (on(10), on(50));
+        assert!((50..200).contains(&ten.len()), "{}", ten.len());
+        assert!(ten.iter().all(|u| fifty.contains(u)));
+        assert_eq!(on(10), ten);
+    }
+}
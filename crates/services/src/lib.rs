@@ -1,22 +1,44 @@
+pub mod assistant;
 pub mod auth;
+pub mod avatar;
 pub mod background;
+pub mod calendar;
+pub mod chapters;
 pub mod cloud_storage;
 pub mod dao;
 pub mod document_recognition;
+pub mod domain_verify;
 pub mod email;
 pub mod export;
+pub mod feature_flags;
 pub mod giphy;
 pub mod media;
+pub mod notification_prefs;
 pub mod oauth;
+pub mod permissions;
+pub mod plan_limits;
+pub mod preview;
 pub mod push;
+pub mod scan;
 pub mod stripe;
+pub mod tts;
+pub mod turn;
+pub mod whiteboard;
 
+pub use assistant::AssistantModel;
 pub use auth::AuthService;
 pub use background::TaskService;
+pub use calendar::CalendarService;
 pub use dao::*;
 pub use document_recognition::RecognitionService;
+pub use domain_verify::DomainVerifier;
 pub use email::EmailService;
 pub use giphy::GiphyService;
 pub use oauth::OAuthService;
+pub use permissions::{PermissionService, RoleChange};
+pub use preview::PreviewService;
 pub use push::PushService;
+pub use scan::ScanService;
 pub use stripe::StripeService;
+pub use tts::SpeechSynthesizer;
+pub use turn::TurnService;
@@ -0,0 +1,582 @@
+//! [`MediaBackend`] on mediasoup workers.
+
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZero;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mediasoup::pipe_transport::{
+    PipeTransport, PipeTransportOptions, PipeTransportRemoteParameters,
+};
+use mediasoup::prelude::*;
+use mediasoup::transport::{TransportTraceEventData, TransportTraceEventType};
+use mediasoup::webrtc_transport::{
+    WebRtcTransportListenInfos, WebRtcTransportOptions, WebRtcTransportRemoteParameters,
+};
+use roomler_ai_config::MediasoupSettings;
+use tokio::sync::mpsc;
+
+use super::{
+    AudioLevels, LevelListener, MediaBackend, MediaConsumer, MediaHandle, MediaPipe, MediaProducer,
+    MediaRouter, MediaTransport, StateListener, TransportStats,
+};
+use crate::media::room_manager::{PipedProducer, TransportLayer, TransportOptions};
+use crate::media::worker_pool::WorkerPool;
+
+/// Average volume, in dBov, above which a producer counts as heard.
+const SPEECH_THRESHOLD: i8 = -50;
+
+/// How often the audio levels are checked, in ms.
+const LEVEL_INTERVAL: u16 = 300;
+
+/// Where transports listen.
+#[derive(Clone)]
+struct Listen {
+    ip: IpAddr,
+    announced_ip: Option<String>,
+}
+
+impl Listen {
+    fn info(&self, protocol: Protocol) -> ListenInfo {
+        ListenInfo {
+            protocol,
+            ip: self.ip,
+            announced_address: self.announced_ip.clone(),
+            port: None,
+            port_range: None,
+            flags: None,
+            send_buffer_size: None,
+            recv_buffer_size: None,
+            expose_internal_ip: false,
+        }
+    }
+}
+
+pub struct MediasoupBackend {
+    worker_pool: WorkerPool,
+    listen: Listen,
+}
+
+impl MediasoupBackend {
+    /// Starts `settings.num_workers` workers.
+    pub async fn new(settings: &MediasoupSettings) -> anyhow::Result<Self> {
+        let ip: IpAddr = settings
+            .listen_ip
+            .parse()
+            .unwrap_or_else(|_| "0.0.0.0".parse().unwrap());
+
+        let announced_ip = if settings.announced_ip.is_empty() {
+            None
+        } else {
+            Some(settings.announced_ip.clone())
+        };
+
+        Ok(Self {
+            worker_pool: WorkerPool::new(settings).await?,
+            listen: Listen { ip, announced_ip },
+        })
+    }
+}
+
+#[async_trait]
+impl MediaBackend for MediasoupBackend {
+    async fn create_router(&self) -> anyhow::Result<Arc<dyn MediaRouter>> {
+        let worker = self.worker_pool.get_worker();
+        let router = worker
+            .create_router(RouterOptions::new(media_codecs()))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create router: {}", e))?;
+        Ok(Arc::new(SoupRouter {
+            router,
+            worker_id: worker.id().to_string(),
+            listen: self.listen.clone(),
+        }))
+    }
+
+    fn worker_counts(&self) -> (usize, usize) {
+        (
+            self.worker_pool.live_count(),
+            self.worker_pool.worker_count(),
+        )
+    }
+
+    async fn ping_workers(&self, timeout: Duration) -> Vec<bool> {
+        self.worker_pool.ping(timeout).await
+    }
+}
+
+struct SoupRouter {
+    router: Router,
+    worker_id: String,
+    listen: Listen,
+}
+
+#[async_trait]
+impl MediaRouter for SoupRouter {
+    fn worker_id(&self) -> String {
+        self.worker_id.clone()
+    }
+
+    fn rtp_capabilities(&self) -> serde_json::Value {
+        serde_json::to_value(self.router.rtp_capabilities()).unwrap_or_default()
+    }
+
+    fn can_consume(&self, producer_id: &ProducerId, rtp_capabilities: &RtpCapabilities) -> bool {
+        self.router.can_consume(producer_id, rtp_capabilities)
+    }
+
+    async fn create_webrtc_transport(
+        &self,
+        (max_incoming, max_outgoing): (Option<u32>, Option<u32>),
+    ) -> anyhow::Result<Arc<dyn MediaTransport>> {
+        let udp_info = self.listen.info(Protocol::Udp);
+
+        // TCP fallback — essential when wsl-vpnkit or similar networking
+        // intercepts UDP but TCP localhost forwarding still works.
+        let tcp_info = self.listen.info(Protocol::Tcp);
+
+        let listen_infos = WebRtcTransportListenInfos::new(udp_info).insert(tcp_info);
+        let mut transport_options = WebRtcTransportOptions::new(listen_infos);
+        transport_options.enable_udp = true;
+        transport_options.enable_tcp = true;
+        transport_options.prefer_udp = true;
+
+        let transport = self
+            .router
+            .create_webrtc_transport(transport_options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create WebRtcTransport: {}", e))?;
+
+        if let Some(bps) = max_incoming {
+            transport
+                .set_max_incoming_bitrate(bps)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to cap incoming bitrate: {}", e))?;
+        }
+        if let Some(bps) = max_outgoing {
+            transport
+                .set_max_outgoing_bitrate(bps)
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to cap outgoing bitrate: {}", e))?;
+        }
+
+        Ok(Arc::new(SoupTransport(transport)))
+    }
+
+    async fn tap_rtp(
+        &self,
+        producer_id: ProducerId,
+    ) -> anyhow::Result<(MediaHandle, mpsc::Receiver<Vec<u8>>)> {
+        let direct_transport = self
+            .router
+            .create_direct_transport(DirectTransportOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create DirectTransport: {}", e))?;
+
+        // Convert RtpCapabilitiesFinalized → RtpCapabilities via serde (same JSON schema)
+        let rtp_capabilities: RtpCapabilities = serde_json::from_value(self.rtp_capabilities())
+            .map_err(|e| anyhow::anyhow!("Failed to deserialize capabilities: {}", e))?;
+
+        let consumer = direct_transport
+            .consume(ConsumerOptions::new(producer_id, rtp_capabilities))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to consume on DirectTransport: {}", e))?;
+
+        // mediasoup consumers are created paused — must resume to receive RTP
+        consumer
+            .resume()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to resume DirectTransport consumer: {}", e))?;
+
+        let (tx, rx) = mpsc::channel(512);
+
+        // Register RTP callback; detach so it lives as long as the Consumer
+        consumer
+            .on_rtp(move |data: &[u8]| {
+                let _ = tx.try_send(data.to_vec());
+            })
+            .detach();
+
+        Ok((Box::new((direct_transport, consumer)), rx))
+    }
+
+    async fn inject_rtp(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<(Arc<dyn MediaProducer>, mpsc::Sender<Vec<u8>>)> {
+        let direct_transport = self
+            .router
+            .create_direct_transport(DirectTransportOptions::default())
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create DirectTransport: {}", e))?;
+
+        let producer = direct_transport
+            .produce(ProducerOptions::new(kind, rtp_parameters))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to produce on DirectTransport: {}", e))?;
+        let Producer::Direct(direct_producer) = producer.clone() else {
+            anyhow::bail!("DirectTransport returned a regular producer");
+        };
+
+        let (tx, mut rx) = mpsc::channel::<Vec<u8>>(64);
+
+        // The transport stays open until the sender is dropped
+        tokio::spawn(async move {
+            let _transport = direct_transport;
+            while let Some(packet) = rx.recv().await {
+                if direct_producer.send(packet).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok((Arc::new(SoupProducer(producer)), tx))
+    }
+
+    async fn pipe_to(
+        &self,
+        producer_id: ProducerId,
+        remote: SocketAddr,
+    ) -> anyhow::Result<(MediaHandle, String, PipedProducer)> {
+        let transport = self
+            .router
+            .create_pipe_transport(PipeTransportOptions::new(self.listen.info(Protocol::Udp)))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create PipeTransport: {}", e))?;
+        transport
+            .connect(PipeTransportRemoteParameters {
+                ip: remote.ip(),
+                port: remote.port(),
+                srtp_parameters: None,
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect PipeTransport: {}", e))?;
+
+        // A pipe consumer forwards the producer's streams as they are, so
+        // the capabilities are not used.
+        let consumer = transport
+            .consume(ConsumerOptions::new(
+                producer_id,
+                RtpCapabilities::default(),
+            ))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to consume on PipeTransport: {}", e))?;
+
+        let tuple = transport.tuple();
+        let piped = PipedProducer {
+            ip: tuple.local_address().to_string(),
+            port: tuple.local_port(),
+            kind: consumer.kind(),
+            rtp_parameters: consumer.rtp_parameters().clone(),
+        };
+        let consumer_id = consumer.id().to_string();
+        Ok((Box::new((transport, consumer)), consumer_id, piped))
+    }
+
+    async fn open_pipe(&self) -> anyhow::Result<Arc<dyn MediaPipe>> {
+        let transport = self
+            .router
+            .create_pipe_transport(PipeTransportOptions::new(self.listen.info(Protocol::Udp)))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create PipeTransport: {}", e))?;
+        Ok(Arc::new(SoupPipe(transport)))
+    }
+
+    async fn observe_audio_levels(
+        &self,
+        listener: LevelListener,
+    ) -> anyhow::Result<Arc<dyn AudioLevels>> {
+        let mut options = AudioLevelObserverOptions::default();
+        options.threshold = SPEECH_THRESHOLD;
+        options.interval = LEVEL_INTERVAL;
+        let observer = self
+            .router
+            .create_audio_level_observer(options)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create AudioLevelObserver: {}", e))?;
+
+        // `volumes` repeats every interval while anyone is loud; only the
+        // first one after a silence is passed on
+        let heard = Arc::new(AtomicBool::new(false));
+        observer
+            .on_volumes({
+                let heard = heard.clone();
+                let listener = listener.clone();
+                move |_| {
+                    if !heard.swap(true, Ordering::Relaxed) {
+                        listener(true);
+                    }
+                }
+            })
+            .detach();
+        observer
+            .on_silence(move || {
+                if heard.swap(false, Ordering::Relaxed) {
+                    listener(false);
+                }
+            })
+            .detach();
+        Ok(Arc::new(SoupAudioLevels(observer)))
+    }
+}
+
+struct SoupPipe(PipeTransport);
+
+#[async_trait]
+impl MediaPipe for SoupPipe {
+    fn local(&self) -> (String, u16) {
+        let tuple = self.0.tuple();
+        (tuple.local_address().to_string(), tuple.local_port())
+    }
+
+    async fn produce(
+        &self,
+        remote: SocketAddr,
+        producer_id: ProducerId,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>> {
+        self.0
+            .connect(PipeTransportRemoteParameters {
+                ip: remote.ip(),
+                port: remote.port(),
+                srtp_parameters: None,
+            })
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to connect PipeTransport: {}", e))?;
+        let producer = self
+            .0
+            .produce(ProducerOptions::new_pipe_transport(
+                producer_id,
+                kind,
+                rtp_parameters,
+            ))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to produce on PipeTransport: {}", e))?;
+        Ok(Arc::new(SoupProducer(producer)))
+    }
+}
+
+struct SoupAudioLevels(AudioLevelObserver);
+
+#[async_trait]
+impl AudioLevels for SoupAudioLevels {
+    async fn add(&self, producer_id: ProducerId) -> anyhow::Result<()> {
+        self.0
+            .add_producer(RtpObserverAddProducerOptions::new(producer_id))
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to observe producer: {}", e))
+    }
+}
+
+struct SoupTransport(WebRtcTransport);
+
+#[async_trait]
+impl MediaTransport for SoupTransport {
+    fn id(&self) -> String {
+        self.0.id().to_string()
+    }
+
+    fn options(&self) -> TransportOptions {
+        TransportOptions {
+            id: self.0.id().to_string(),
+            ice_parameters: serde_json::to_value(self.0.ice_parameters()).unwrap_or_default(),
+            ice_candidates: serde_json::to_value(self.0.ice_candidates()).unwrap_or_default(),
+            dtls_parameters: serde_json::to_value(self.0.dtls_parameters()).unwrap_or_default(),
+        }
+    }
+
+    async fn connect(&self, dtls_parameters: DtlsParameters) -> anyhow::Result<()> {
+        self.0
+            .connect(WebRtcTransportRemoteParameters { dtls_parameters })
+            .await?;
+        Ok(())
+    }
+
+    async fn restart_ice(&self) -> anyhow::Result<serde_json::Value> {
+        let ice_parameters = self.0.restart_ice().await?;
+        Ok(serde_json::to_value(ice_parameters)?)
+    }
+
+    async fn produce(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>> {
+        let producer = self
+            .0
+            .produce(ProducerOptions::new(kind, rtp_parameters))
+            .await?;
+        Ok(Arc::new(SoupProducer(producer)))
+    }
+
+    async fn consume(
+        &self,
+        producer_id: ProducerId,
+        rtp_capabilities: RtpCapabilities,
+    ) -> anyhow::Result<Arc<dyn MediaConsumer>> {
+        let consumer = self
+            .0
+            .consume(ConsumerOptions::new(producer_id, rtp_capabilities))
+            .await?;
+        Ok(Arc::new(SoupConsumer(consumer)))
+    }
+
+    async fn stats(&self) -> anyhow::Result<Option<TransportStats>> {
+        let stats = self.0.get_stats().await?;
+        Ok(stats.first().map(|s| TransportStats {
+            recv_bitrate: s.recv_bitrate,
+            send_bitrate: s.send_bitrate,
+            packet_loss_received: s.rtp_packet_loss_received,
+            packet_loss_sent: s.rtp_packet_loss_sent,
+        }))
+    }
+
+    async fn on_bandwidth_estimate(
+        &self,
+        listener: Box<dyn Fn(u32) + Send + Sync>,
+    ) -> anyhow::Result<()> {
+        self.0
+            .enable_trace_event(vec![TransportTraceEventType::Bwe])
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to enable BWE trace: {}", e))?;
+        self.0
+            .on_trace(Arc::new(move |data: &TransportTraceEventData| {
+                if let TransportTraceEventData::Bwe { info, .. } = data {
+                    listener(info.available_bitrate);
+                }
+            }))
+            .detach();
+        Ok(())
+    }
+
+    fn on_state_change(&self, listener: StateListener) {
+        let ice_listener = listener.clone();
+        self.0
+            .on_ice_state_change(move |state| {
+                ice_listener(TransportLayer::Ice, format!("{:?}", state).to_lowercase())
+            })
+            .detach();
+        self.0
+            .on_dtls_state_change(move |state| {
+                listener(TransportLayer::Dtls, format!("{:?}", state).to_lowercase())
+            })
+            .detach();
+    }
+}
+
+struct SoupProducer(Producer);
+
+#[async_trait]
+impl MediaProducer for SoupProducer {
+    fn id(&self) -> ProducerId {
+        self.0.id()
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.0.kind()
+    }
+
+    async fn pause(&self) -> anyhow::Result<()> {
+        Ok(self.0.pause().await?)
+    }
+
+    async fn resume(&self) -> anyhow::Result<()> {
+        Ok(self.0.resume().await?)
+    }
+
+    async fn round_trip_times(&self) -> anyhow::Result<Vec<f64>> {
+        let stats = self.0.get_stats().await?;
+        Ok(stats
+            .iter()
+            .filter_map(|s| s.round_trip_time.map(f64::from))
+            .collect())
+    }
+}
+
+struct SoupConsumer(Consumer);
+
+#[async_trait]
+impl MediaConsumer for SoupConsumer {
+    fn id(&self) -> String {
+        self.0.id().to_string()
+    }
+
+    fn producer_id(&self) -> ProducerId {
+        self.0.producer_id()
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.0.kind()
+    }
+
+    fn rtp_parameters(&self) -> RtpParameters {
+        self.0.rtp_parameters().clone()
+    }
+
+    fn paused(&self) -> bool {
+        self.0.paused()
+    }
+
+    fn closed(&self) -> bool {
+        self.0.closed()
+    }
+
+    async fn pause(&self) -> anyhow::Result<()> {
+        Ok(self.0.pause().await?)
+    }
+
+    async fn resume(&self) -> anyhow::Result<()> {
+        Ok(self.0.resume().await?)
+    }
+}
+
+/// Standard SFU media codecs: opus audio + VP8/H264 video.
+fn media_codecs() -> Vec<RtpCodecCapability> {
+    vec![
+        // Opus audio
+        RtpCodecCapability::Audio {
+            mime_type: MimeTypeAudio::Opus,
+            preferred_payload_type: Some(111),
+            clock_rate: NonZero::new(48000).unwrap(),
+            channels: NonZero::new(2).unwrap(),
+            parameters: RtpCodecParametersParameters::default(),
+            rtcp_feedback: vec![RtcpFeedback::TransportCc],
+        },
+        // VP8 video
+        RtpCodecCapability::Video {
+            mime_type: MimeTypeVideo::Vp8,
+            preferred_payload_type: Some(96),
+            clock_rate: NonZero::new(90000).unwrap(),
+            parameters: RtpCodecParametersParameters::default(),
+            rtcp_feedback: vec![
+                RtcpFeedback::Nack,
+                RtcpFeedback::NackPli,
+                RtcpFeedback::CcmFir,
+                RtcpFeedback::GoogRemb,
+                RtcpFeedback::TransportCc,
+            ],
+        },
+        // H264 video
+        RtpCodecCapability::Video {
+            mime_type: MimeTypeVideo::H264,
+            preferred_payload_type: Some(125),
+            clock_rate: NonZero::new(90000).unwrap(),
+            parameters: RtpCodecParametersParameters::from([
+                ("level-asymmetry-allowed", 1_u32.into()),
+                ("packetization-mode", 1_u32.into()),
+                ("profile-level-id", "42e01f".into()),
+            ]),
+            rtcp_feedback: vec![
+                RtcpFeedback::Nack,
+                RtcpFeedback::NackPli,
+                RtcpFeedback::CcmFir,
+                RtcpFeedback::GoogRemb,
+                RtcpFeedback::TransportCc,
+            ],
+        },
+    ]
+}
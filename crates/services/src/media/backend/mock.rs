@@ -0,0 +1,555 @@
+//! In-memory [`MediaBackend`] for tests: no worker process, no ports, no RTP.
+//!
+//! Signaling behaves like mediasoup's: ids are fresh UUIDs, consumers start
+//! paused, consuming needs a live producer on the same Router, a transport
+//! connects once and then reports ICE and DTLS `connected`, and dropping a
+//! producer closes its consumers. Without RTP no producer is ever heard by
+//! an audio level observer until a test says so with [`set_heard`].
+
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use dashmap::DashMap;
+use mediasoup::prelude::{DtlsParameters, MediaKind, ProducerId, RtpCapabilities, RtpParameters};
+use serde_json::json;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use super::{
+    AudioLevels, LevelListener, MediaBackend, MediaConsumer, MediaHandle, MediaPipe, MediaProducer,
+    MediaRouter, MediaTransport, StateListener, TransportStats,
+};
+use crate::media::room_manager::{PipedProducer, TransportLayer, TransportOptions};
+
+/// Live producers of a Router.
+type Producers = Arc<DashMap<ProducerId, (MediaKind, RtpParameters)>>;
+
+/// The observer each observed producer was added to, across all Routers.
+static OBSERVED: LazyLock<DashMap<ProducerId, Arc<MockAudioLevels>>> = LazyLock::new(DashMap::new);
+
+/// Make an observed producer heard or quiet, as its RTP would on
+/// mediasoup. Returns false if no audio level observer has the producer.
+pub fn set_heard(producer_id: &str, heard: bool) -> bool {
+    let Ok(producer_id) = producer_id.parse::<ProducerId>() else {
+        return false;
+    };
+    let Some(levels) = OBSERVED.get(&producer_id).map(|l| l.value().clone()) else {
+        return false;
+    };
+    levels.set_heard(producer_id, heard);
+    true
+}
+
+#[derive(Default)]
+pub struct MockBackend;
+
+#[async_trait]
+impl MediaBackend for MockBackend {
+    async fn create_router(&self) -> anyhow::Result<Arc<dyn MediaRouter>> {
+        Ok(Arc::new(MockRouter::default()))
+    }
+
+    fn worker_counts(&self) -> (usize, usize) {
+        (1, 1)
+    }
+
+    async fn ping_workers(&self, _timeout: Duration) -> Vec<bool> {
+        vec![true]
+    }
+}
+
+#[derive(Default)]
+struct MockRouter {
+    producers: Producers,
+}
+
+fn new_producer_id() -> ProducerId {
+    Uuid::new_v4()
+        .to_string()
+        .parse()
+        .expect("a UUID is a valid producer id")
+}
+
+#[async_trait]
+impl MediaRouter for MockRouter {
+    fn worker_id(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn rtp_capabilities(&self) -> serde_json::Value {
+        json!({
+            "codecs": [
+                {
+                    "kind": "audio",
+                    "mimeType": "audio/opus",
+                    "preferredPayloadType": 111,
+                    "clockRate": 48000,
+                    "channels": 2,
+                    "parameters": {},
+                    "rtcpFeedback": [{ "type": "transport-cc", "parameter": "" }],
+                },
+                {
+                    "kind": "video",
+                    "mimeType": "video/VP8",
+                    "preferredPayloadType": 96,
+                    "clockRate": 90000,
+                    "parameters": {},
+                    "rtcpFeedback": [
+                        { "type": "nack", "parameter": "" },
+                        { "type": "nack", "parameter": "pli" },
+                        { "type": "ccm", "parameter": "fir" },
+                        { "type": "goog-remb", "parameter": "" },
+                        { "type": "transport-cc", "parameter": "" },
+                    ],
+                },
+            ],
+            "headerExtensions": [],
+        })
+    }
+
+    fn can_consume(&self, producer_id: &ProducerId, _rtp_capabilities: &RtpCapabilities) -> bool {
+        self.producers.contains_key(producer_id)
+    }
+
+    async fn create_webrtc_transport(
+        &self,
+        _max_bitrates: (Option<u32>, Option<u32>),
+    ) -> anyhow::Result<Arc<dyn MediaTransport>> {
+        Ok(Arc::new(MockTransport {
+            id: Uuid::new_v4().to_string(),
+            producers: self.producers.clone(),
+            connected: AtomicBool::new(false),
+            listeners: Mutex::new(Vec::new()),
+        }))
+    }
+
+    async fn tap_rtp(
+        &self,
+        producer_id: ProducerId,
+    ) -> anyhow::Result<(MediaHandle, mpsc::Receiver<Vec<u8>>)> {
+        if !self.producers.contains_key(&producer_id) {
+            return Err(anyhow::anyhow!("Producer {} not found", producer_id));
+        }
+        // The tap stays open, and silent, while the handle is kept.
+        let (tx, rx) = mpsc::channel(1);
+        Ok((Box::new(tx), rx))
+    }
+
+    async fn inject_rtp(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<(Arc<dyn MediaProducer>, mpsc::Sender<Vec<u8>>)> {
+        let id = new_producer_id();
+        self.producers.insert(id, (kind, rtp_parameters));
+        let producer = Arc::new(MockProducer {
+            id,
+            kind,
+            producers: self.producers.clone(),
+        });
+        // No RTP flows: the channel is closed from the start.
+        let (tx, _) = mpsc::channel(1);
+        Ok((producer, tx))
+    }
+
+    async fn pipe_to(
+        &self,
+        producer_id: ProducerId,
+        _remote: SocketAddr,
+    ) -> anyhow::Result<(MediaHandle, String, PipedProducer)> {
+        let (kind, rtp_parameters) = self
+            .producers
+            .get(&producer_id)
+            .map(|p| p.value().clone())
+            .ok_or_else(|| anyhow::anyhow!("Producer {} not found", producer_id))?;
+        let piped = PipedProducer {
+            ip: "127.0.0.1".to_string(),
+            port: 40000,
+            kind,
+            rtp_parameters,
+        };
+        Ok((Box::new(()), Uuid::new_v4().to_string(), piped))
+    }
+
+    async fn open_pipe(&self) -> anyhow::Result<Arc<dyn MediaPipe>> {
+        Ok(Arc::new(MockPipe {
+            producers: self.producers.clone(),
+        }))
+    }
+
+    async fn observe_audio_levels(
+        &self,
+        listener: LevelListener,
+    ) -> anyhow::Result<Arc<dyn AudioLevels>> {
+        Ok(Arc::new(MockObserver(Arc::new(MockAudioLevels {
+            producers: self.producers.clone(),
+            listener,
+            heard: Mutex::new(HashSet::new()),
+        }))))
+    }
+}
+
+struct MockPipe {
+    producers: Producers,
+}
+
+#[async_trait]
+impl MediaPipe for MockPipe {
+    fn local(&self) -> (String, u16) {
+        ("127.0.0.1".to_string(), 40001)
+    }
+
+    async fn produce(
+        &self,
+        _remote: SocketAddr,
+        producer_id: ProducerId,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>> {
+        if self.producers.contains_key(&producer_id) {
+            anyhow::bail!("Producer {} already exists", producer_id);
+        }
+        self.producers.insert(producer_id, (kind, rtp_parameters));
+        Ok(Arc::new(MockProducer {
+            id: producer_id,
+            kind,
+            producers: self.producers.clone(),
+        }))
+    }
+}
+
+struct MockAudioLevels {
+    producers: Producers,
+    listener: LevelListener,
+    /// Observed producers currently heard.
+    heard: Mutex<HashSet<ProducerId>>,
+}
+
+impl MockAudioLevels {
+    fn set_heard(&self, producer_id: ProducerId, heard: bool) {
+        let (before, after) = {
+            let mut set = self.heard.lock().unwrap();
+            let before = !set.is_empty();
+            if heard {
+                set.insert(producer_id);
+            } else {
+                set.remove(&producer_id);
+            }
+            (before, !set.is_empty())
+        };
+        if before != after {
+            (self.listener)(after);
+        }
+    }
+}
+
+struct MockObserver(Arc<MockAudioLevels>);
+
+#[async_trait]
+impl AudioLevels for MockObserver {
+    async fn add(&self, producer_id: ProducerId) -> anyhow::Result<()> {
+        match self.0.producers.get(&producer_id).map(|p| p.0) {
+            Some(MediaKind::Audio) => {}
+            Some(MediaKind::Video) => anyhow::bail!("Producer {} is not audio", producer_id),
+            None => anyhow::bail!("Producer {} not found", producer_id),
+        }
+        OBSERVED.insert(producer_id, self.0.clone());
+        Ok(())
+    }
+}
+
+struct MockTransport {
+    id: String,
+    producers: Producers,
+    connected: AtomicBool,
+    listeners: Mutex<Vec<StateListener>>,
+}
+
+#[async_trait]
+impl MediaTransport for MockTransport {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn options(&self) -> TransportOptions {
+        TransportOptions {
+            id: self.id.clone(),
+            ice_parameters: ice_parameters(),
+            ice_candidates: json!([{
+                "foundation": "udpcandidate",
+                "priority": 1076302079,
+                "address": "127.0.0.1",
+                "ip": "127.0.0.1",
+                "protocol": "udp",
+                "port": 40000,
+                "type": "host",
+            }]),
+            dtls_parameters: json!({
+                "role": "auto",
+                "fingerprints": [{
+                    "algorithm": "sha-256",
+                    "value": "00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF:00:11:22:33:44:55:66:77:88:99:AA:BB:CC:DD:EE:FF",
+                }],
+            }),
+        }
+    }
+
+    async fn connect(&self, _dtls_parameters: DtlsParameters) -> anyhow::Result<()> {
+        if self.connected.swap(true, Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("connect() already called"));
+        }
+        let listeners = self.listeners.lock().unwrap().clone();
+        for listener in listeners {
+            listener(TransportLayer::Ice, "connected".to_string());
+            listener(TransportLayer::Dtls, "connected".to_string());
+        }
+        Ok(())
+    }
+
+    async fn restart_ice(&self) -> anyhow::Result<serde_json::Value> {
+        Ok(ice_parameters())
+    }
+
+    async fn produce(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>> {
+        let id = new_producer_id();
+        self.producers.insert(id, (kind, rtp_parameters));
+        Ok(Arc::new(MockProducer {
+            id,
+            kind,
+            producers: self.producers.clone(),
+        }))
+    }
+
+    async fn consume(
+        &self,
+        producer_id: ProducerId,
+        _rtp_capabilities: RtpCapabilities,
+    ) -> anyhow::Result<Arc<dyn MediaConsumer>> {
+        let (kind, rtp_parameters) = self
+            .producers
+            .get(&producer_id)
+            .map(|p| p.value().clone())
+            .ok_or_else(|| anyhow::anyhow!("Producer {} not found", producer_id))?;
+        Ok(Arc::new(MockConsumer {
+            id: Uuid::new_v4().to_string(),
+            producer_id,
+            kind,
+            rtp_parameters,
+            paused: AtomicBool::new(true),
+            producers: self.producers.clone(),
+        }))
+    }
+
+    async fn stats(&self) -> anyhow::Result<Option<TransportStats>> {
+        Ok(Some(TransportStats::default()))
+    }
+
+    async fn on_bandwidth_estimate(
+        &self,
+        _listener: Box<dyn Fn(u32) + Send + Sync>,
+    ) -> anyhow::Result<()> {
+        // No RTP, no estimates.
+        Ok(())
+    }
+
+    fn on_state_change(&self, listener: StateListener) {
+        self.listeners.lock().unwrap().push(listener);
+    }
+}
+
+/// Fresh ICE credentials, as on creation and every ICE restart.
+fn ice_parameters() -> serde_json::Value {
+    json!({
+        "usernameFragment": Uuid::new_v4().simple().to_string(),
+        "password": Uuid::new_v4().simple().to_string(),
+        "iceLite": true,
+    })
+}
+
+struct MockProducer {
+    id: ProducerId,
+    kind: MediaKind,
+    producers: Producers,
+}
+
+impl Drop for MockProducer {
+    fn drop(&mut self) {
+        self.producers.remove(&self.id);
+        // A closed producer leaves its observer, and is no longer heard
+        if let Some((_, levels)) = OBSERVED.remove(&self.id) {
+            levels.set_heard(self.id, false);
+        }
+    }
+}
+
+#[async_trait]
+impl MediaProducer for MockProducer {
+    fn id(&self) -> ProducerId {
+        self.id
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.kind
+    }
+
+    async fn pause(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn resume(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    async fn round_trip_times(&self) -> anyhow::Result<Vec<f64>> {
+        Ok(Vec::new())
+    }
+}
+
+struct MockConsumer {
+    id: String,
+    producer_id: ProducerId,
+    kind: MediaKind,
+    rtp_parameters: RtpParameters,
+    paused: AtomicBool,
+    producers: Producers,
+}
+
+#[async_trait]
+impl MediaConsumer for MockConsumer {
+    fn id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn producer_id(&self) -> ProducerId {
+        self.producer_id
+    }
+
+    fn kind(&self) -> MediaKind {
+        self.kind
+    }
+
+    fn rtp_parameters(&self) -> RtpParameters {
+        self.rtp_parameters.clone()
+    }
+
+    fn paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    fn closed(&self) -> bool {
+        !self.producers.contains_key(&self.producer_id)
+    }
+
+    async fn pause(&self) -> anyhow::Result<()> {
+        self.paused.store(true, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn resume(&self) -> anyhow::Result<()> {
+        self.paused.store(false, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn opus() -> RtpParameters {
+        serde_json::from_value(json!({
+            "mid": "0",
+            "codecs": [{
+                "mimeType": "audio/opus",
+                "clockRate": 48000,
+                "channels": 2,
+                "payloadType": 111,
+                "parameters": {},
+                "rtcpFeedback": [],
+            }],
+            "headerExtensions": [],
+            "encodings": [{ "ssrc": 4242 }],
+            "rtcp": { "cname": "mock" },
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn consumers_follow_their_producer() {
+        let router = MockBackend.create_router().await.unwrap();
+        let caps: RtpCapabilities = serde_json::from_value(router.rtp_capabilities()).unwrap();
+        let send = router.create_webrtc_transport((None, None)).await.unwrap();
+        let recv = router.create_webrtc_transport((None, None)).await.unwrap();
+
+        let producer = send.produce(MediaKind::Audio, opus()).await.unwrap();
+        assert!(router.can_consume(&producer.id(), &caps));
+        let consumer = recv.consume(producer.id(), caps.clone()).await.unwrap();
+        assert!(consumer.paused());
+        assert!(!consumer.closed());
+        assert_eq!(consumer.kind(), MediaKind::Audio);
+
+        let id = producer.id();
+        drop(producer);
+        assert!(consumer.closed());
+        assert!(!router.can_consume(&id, &caps));
+        assert!(recv.consume(id, caps).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_reports_states_once() {
+        let router = MockBackend.create_router().await.unwrap();
+        let transport = router.create_webrtc_transport((None, None)).await.unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        transport.on_state_change(Arc::new(move |layer: TransportLayer, state: String| {
+            sink.lock().unwrap().push((layer, state))
+        }));
+
+        let dtls: DtlsParameters = serde_json::from_value(json!({
+            "role": "client",
+            "fingerprints": transport.options().dtls_parameters["fingerprints"],
+        }))
+        .unwrap();
+        transport.connect(dtls.clone()).await.unwrap();
+        assert!(transport.connect(dtls).await.is_err());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            [
+                (TransportLayer::Ice, "connected".to_string()),
+                (TransportLayer::Dtls, "connected".to_string()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn observers_hear_until_everyone_is_quiet() {
+        let router = MockBackend.create_router().await.unwrap();
+        let send = router.create_webrtc_transport((None, None)).await.unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let levels = router
+            .observe_audio_levels(Arc::new(move |heard| sink.lock().unwrap().push(heard)))
+            .await
+            .unwrap();
+
+        let first = send.produce(MediaKind::Audio, opus()).await.unwrap();
+        let second = send.produce(MediaKind::Audio, opus()).await.unwrap();
+        assert!(!set_heard(&first.id().to_string(), true));
+        levels.add(first.id()).await.unwrap();
+        levels.add(second.id()).await.unwrap();
+
+        assert!(set_heard(&first.id().to_string(), true));
+        assert!(set_heard(&second.id().to_string(), true));
+        assert!(set_heard(&first.id().to_string(), false));
+        // The last one heard closing ends the speech
+        drop(second);
+        assert_eq!(*seen.lock().unwrap(), [true, false]);
+    }
+}
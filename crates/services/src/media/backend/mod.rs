@@ -0,0 +1,206 @@
+//! The media layer under [`RoomManager`](super::room_manager::RoomManager):
+//! routers, transports, producers and consumers.
+//!
+//! [`MediasoupBackend`] runs them on mediasoup workers. [`MockBackend`]
+//! keeps them in memory, with no worker process, UDP port or RTP, so that
+//! signaling and WebSocket flows can be tested hermetically. The setting
+//! `mediasoup.backend` picks one.
+//!
+//! Everything is handed out as `Arc<dyn ...>`; dropping the last handle of
+//! an object closes it, as with mediasoup's own types.
+
+pub mod mediasoup_backend;
+pub mod mock;
+
+use std::any::Any;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use mediasoup::prelude::{DtlsParameters, MediaKind, ProducerId, RtpCapabilities, RtpParameters};
+use roomler_ai_config::{MediaBackendKind, MediasoupSettings};
+use tokio::sync::mpsc;
+
+use super::room_manager::{PipedProducer, TransportLayer, TransportOptions};
+
+pub use mediasoup_backend::MediasoupBackend;
+pub use mock::MockBackend;
+
+/// Keeps server-side media objects (an RTP tap, a pipe) alive; dropping it
+/// closes them.
+pub type MediaHandle = Box<dyn Any + Send + Sync>;
+
+/// Called with the layer and the new state name, e.g. `connected`.
+pub type StateListener = Arc<dyn Fn(TransportLayer, String) + Send + Sync>;
+
+/// Called with `true` when an observed producer starts being heard and
+/// `false` once all of them are quiet again.
+pub type LevelListener = Arc<dyn Fn(bool) + Send + Sync>;
+
+/// The backend `settings.backend` asks for.
+pub async fn from_settings(settings: &MediasoupSettings) -> anyhow::Result<Arc<dyn MediaBackend>> {
+    Ok(match settings.backend {
+        MediaBackendKind::Mediasoup => Arc::new(MediasoupBackend::new(settings).await?),
+        MediaBackendKind::Mock => Arc::new(MockBackend),
+    })
+}
+
+#[async_trait]
+pub trait MediaBackend: Send + Sync {
+    /// A Router with the SFU codecs, on the next worker.
+    async fn create_router(&self) -> anyhow::Result<Arc<dyn MediaRouter>>;
+
+    /// (running, total) workers.
+    fn worker_counts(&self) -> (usize, usize);
+
+    /// `true` for each worker that answered within `timeout`, in pool order.
+    async fn ping_workers(&self, timeout: Duration) -> Vec<bool>;
+}
+
+#[async_trait]
+pub trait MediaRouter: Send + Sync {
+    /// The worker running the router, for traces.
+    fn worker_id(&self) -> String;
+
+    /// RTP capabilities as sent to clients.
+    fn rtp_capabilities(&self) -> serde_json::Value;
+
+    fn can_consume(&self, producer_id: &ProducerId, rtp_capabilities: &RtpCapabilities) -> bool;
+
+    /// A transport for a client, capped at (incoming, outgoing) bps.
+    async fn create_webrtc_transport(
+        &self,
+        max_bitrates: (Option<u32>, Option<u32>),
+    ) -> anyhow::Result<Arc<dyn MediaTransport>>;
+
+    /// Consume a producer on the server and hand out its raw RTP packets.
+    async fn tap_rtp(
+        &self,
+        producer_id: ProducerId,
+    ) -> anyhow::Result<(MediaHandle, mpsc::Receiver<Vec<u8>>)>;
+
+    /// Produce on the server from raw RTP packets sent into the returned
+    /// channel, as described by `rtp_parameters`.
+    async fn inject_rtp(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<(Arc<dyn MediaProducer>, mpsc::Sender<Vec<u8>>)>;
+
+    /// Pipe a producer to another pod's pipe transport at `remote`. Returns
+    /// the pipe consumer's id with this end of the pipe.
+    async fn pipe_to(
+        &self,
+        producer_id: ProducerId,
+        remote: SocketAddr,
+    ) -> anyhow::Result<(MediaHandle, String, PipedProducer)>;
+
+    /// Open this end of a pipe from another pod's Router, the calling side
+    /// of [`pipe_to`](Self::pipe_to).
+    async fn open_pipe(&self) -> anyhow::Result<Arc<dyn MediaPipe>>;
+
+    /// Watch the audio level of the producers added to the returned
+    /// observer. Closed producers leave it by themselves.
+    async fn observe_audio_levels(
+        &self,
+        listener: LevelListener,
+    ) -> anyhow::Result<Arc<dyn AudioLevels>>;
+}
+
+/// A pipe from [`MediaRouter::open_pipe`]; dropping it before
+/// [`produce`](Self::produce) closes it.
+#[async_trait]
+pub trait MediaPipe: Send + Sync {
+    /// The (ip, port) the other pod pipes the producer to.
+    fn local(&self) -> (String, u16);
+
+    /// Connect to the other pod's end at `remote` and produce what it
+    /// sends, under the id the producer has there. The producer keeps the
+    /// pipe open.
+    async fn produce(
+        &self,
+        remote: SocketAddr,
+        producer_id: ProducerId,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>>;
+}
+
+/// An audio level observer from [`MediaRouter::observe_audio_levels`].
+#[async_trait]
+pub trait AudioLevels: Send + Sync {
+    async fn add(&self, producer_id: ProducerId) -> anyhow::Result<()>;
+}
+
+/// The figures of a transport the call quality and device tests use.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TransportStats {
+    pub recv_bitrate: u32,
+    pub send_bitrate: u32,
+    /// Share of the client's RTP packets lost on the way in (0-1).
+    pub packet_loss_received: Option<f64>,
+    /// Share of RTP packets the client reports lost on the way out (0-1).
+    pub packet_loss_sent: Option<f64>,
+}
+
+#[async_trait]
+pub trait MediaTransport: Send + Sync {
+    fn id(&self) -> String;
+
+    /// What the client needs to set up its side.
+    fn options(&self) -> TransportOptions;
+
+    async fn connect(&self, dtls_parameters: DtlsParameters) -> anyhow::Result<()>;
+
+    /// Restart ICE and return the new ICE parameters.
+    async fn restart_ice(&self) -> anyhow::Result<serde_json::Value>;
+
+    async fn produce(
+        &self,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<Arc<dyn MediaProducer>>;
+
+    async fn consume(
+        &self,
+        producer_id: ProducerId,
+        rtp_capabilities: RtpCapabilities,
+    ) -> anyhow::Result<Arc<dyn MediaConsumer>>;
+
+    /// `None` until the transport has any.
+    async fn stats(&self) -> anyhow::Result<Option<TransportStats>>;
+
+    /// Call `listener` with every transport-cc estimate, in bps, of what
+    /// can be sent to the client.
+    async fn on_bandwidth_estimate(
+        &self,
+        listener: Box<dyn Fn(u32) + Send + Sync>,
+    ) -> anyhow::Result<()>;
+
+    /// Call `listener` on every ICE and DTLS state change.
+    fn on_state_change(&self, listener: StateListener);
+}
+
+#[async_trait]
+pub trait MediaProducer: Send + Sync {
+    fn id(&self) -> ProducerId;
+    fn kind(&self) -> MediaKind;
+    async fn pause(&self) -> anyhow::Result<()>;
+    async fn resume(&self) -> anyhow::Result<()>;
+    /// Round-trip times reported for the producer's RTP streams.
+    async fn round_trip_times(&self) -> anyhow::Result<Vec<f64>>;
+}
+
+#[async_trait]
+pub trait MediaConsumer: Send + Sync {
+    fn id(&self) -> String;
+    fn producer_id(&self) -> ProducerId;
+    fn kind(&self) -> MediaKind;
+    fn rtp_parameters(&self) -> RtpParameters;
+    fn paused(&self) -> bool;
+    /// The producer or the transport is gone.
+    fn closed(&self) -> bool;
+    async fn pause(&self) -> anyhow::Result<()>;
+    async fn resume(&self) -> anyhow::Result<()>;
+}
@@ -0,0 +1,122 @@
+//! Downlink bandwidth policy.
+//!
+//! mediasoup reports each recv transport's transport-cc estimate of what the
+//! participant can take. When that estimate can't carry every video stream
+//! they consume, the lowest-priority ones are paused until it recovers. Audio
+//! is never paused.
+
+/// Assumed bitrate of a consumed camera stream.
+pub const CAMERA_BPS: u32 = 600_000;
+/// Assumed bitrate of a consumed screen share.
+pub const SCREEN_BPS: u32 = 1_500_000;
+/// Kept back from the estimate for audio and RTCP.
+pub const AUDIO_RESERVE_BPS: u32 = 150_000;
+/// A paused stream only resumes once the estimate covers it with this much
+/// to spare (in percent), so a stream doesn't flap around the threshold.
+pub const RESUME_HEADROOM_PCT: u32 = 125;
+
+/// A video consumer of one participant, in the order it was created.
+#[derive(Debug, Clone, Copy)]
+pub struct VideoConsumer<'a> {
+    /// `source` of the producer it consumes ("camera", "screen", ...).
+    pub source: &'a str,
+    pub paused: bool,
+}
+
+fn priority(source: &str) -> u8 {
+    match source {
+        "screen" => 1,
+        _ => 0,
+    }
+}
+
+fn cost(source: &str) -> u32 {
+    match source {
+        "screen" => SCREEN_BPS,
+        _ => CAMERA_BPS,
+    }
+}
+
+/// Decides which video consumers should be running for a downlink estimate
+/// of `available_bps`. Screen shares go before cameras and, within a
+/// priority, earlier consumers before later ones. The top stream is always
+/// kept so that the estimate keeps being probed upwards.
+pub fn select(available_bps: u32, consumers: &[VideoConsumer]) -> Vec<bool> {
+    let mut order: Vec<usize> = (0..consumers.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse(priority(consumers[i].source)));
+
+    let mut budget = available_bps.saturating_sub(AUDIO_RESERVE_BPS) as u64;
+    let mut keep = vec![false; consumers.len()];
+    for (rank, &i) in order.iter().enumerate() {
+        let c = consumers[i];
+        let needed = if c.paused {
+            cost(c.source) as u64 * RESUME_HEADROOM_PCT as u64 / 100
+        } else {
+            cost(c.source) as u64
+        };
+        if rank == 0 || needed <= budget {
+            keep[i] = true;
+            budget = budget.saturating_sub(cost(c.source) as u64);
+        } else {
+            // Nothing below a stream that didn't fit gets to jump the queue.
+            break;
+        }
+    }
+    keep
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cams(paused: &[bool]) -> Vec<VideoConsumer<'static>> {
+        paused
+            .iter()
+            .map(|&paused| VideoConsumer {
+                source: "camera",
+                paused,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn keeps_everything_that_fits() {
+        let consumers = cams(&[false, false, false]);
+        let available = AUDIO_RESERVE_BPS + 3 * CAMERA_BPS;
+        assert_eq!(select(available, &consumers), vec![true, true, true]);
+    }
+
+    #[test]
+    fn pauses_later_cameras_first_but_keeps_one() {
+        let consumers = cams(&[false, false, false]);
+        let available = AUDIO_RESERVE_BPS + 2 * CAMERA_BPS;
+        assert_eq!(select(available, &consumers), vec![true, true, false]);
+        assert_eq!(select(0, &consumers), vec![true, false, false]);
+    }
+
+    #[test]
+    fn screen_share_outranks_cameras() {
+        let consumers = vec![
+            VideoConsumer {
+                source: "camera",
+                paused: false,
+            },
+            VideoConsumer {
+                source: "screen",
+                paused: false,
+            },
+        ];
+        let available = AUDIO_RESERVE_BPS + SCREEN_BPS;
+        assert_eq!(select(available, &consumers), vec![false, true]);
+    }
+
+    #[test]
+    fn paused_streams_need_headroom_to_resume() {
+        let consumers = cams(&[false, true]);
+        let available = AUDIO_RESERVE_BPS + 2 * CAMERA_BPS;
+        assert_eq!(select(available, &consumers), vec![true, false]);
+
+        let available = AUDIO_RESERVE_BPS + CAMERA_BPS + CAMERA_BPS * RESUME_HEADROOM_PCT / 100;
+        assert_eq!(select(available, &consumers), vec![true, true]);
+    }
+}
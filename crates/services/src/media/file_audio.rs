@@ -0,0 +1,377 @@
+//! Files played to a call as a real producer. One FFmpeg decodes the file
+//! to PCM, which is scaled by the playback's gain and fed in real time to
+//! a second FFmpeg that encodes it to 20 ms Opus frames in an Ogg stream;
+//! the frames are demuxed, wrapped in RTP and sent into a producer from
+//! [`RoomManager::inject_producer`](super::room_manager::RoomManager::inject_producer),
+//! so every participant hears the same stream and recordings pick it up.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+
+use mediasoup::prelude::RtpParameters;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::process::Command;
+use tokio::sync::{mpsc, watch};
+
+/// Matches the router's Opus codec.
+pub const PAYLOAD_TYPE: u8 = 111;
+
+/// Each Opus frame FFmpeg is asked for.
+const FRAME: Duration = Duration::from_millis(20);
+
+/// RTP timestamp units (48 kHz) per frame.
+const FRAME_SAMPLES: u32 = 960;
+
+/// Bytes of one frame of 16-bit stereo PCM.
+const PCM_FRAME: usize = FRAME_SAMPLES as usize * 2 * 2;
+
+/// A fresh SSRC, and the RTP parameters of a playback producer sending
+/// with it.
+pub fn rtp_parameters() -> anyhow::Result<(u32, RtpParameters)> {
+    let ssrc: u32 = rand::random();
+    let parameters = serde_json::from_value(serde_json::json!({
+        "codecs": [{
+            "mimeType": "audio/opus",
+            "payloadType": PAYLOAD_TYPE,
+            "clockRate": 48000,
+            "channels": 2,
+            "parameters": {},
+            "rtcpFeedback": [],
+        }],
+        "headerExtensions": [],
+        "encodings": [{ "ssrc": ssrc }],
+        "rtcp": { "reducedSize": true },
+    }))?;
+    Ok((ssrc, parameters))
+}
+
+/// Audio for [`stream`] in any format FFmpeg reads.
+pub enum Source {
+    File(PathBuf),
+    /// Audio in memory, e.g. synthesized speech.
+    Bytes(Vec<u8>),
+}
+
+/// How the decoder hands PCM to the encoder.
+const PCM_FORMAT: [&str; 6] = ["-f", "s16le", "-ac", "2", "-ar", "48000"];
+
+/// An FFmpeg run writing to a piped stdout.
+fn ffmpeg_command(ffmpeg: &str) -> Command {
+    let mut command = Command::new(ffmpeg);
+    command
+        .args(["-loglevel", "error"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    command
+}
+
+/// Play `source` into `tx`, paced in real time, at the volume `gain`
+/// holds (1.0 is the file's own); a change fades in over one frame.
+/// Returns `true` once all of it was sent, `false` if the channel closed
+/// first.
+pub async fn stream(
+    ffmpeg: &str,
+    source: Source,
+    ssrc: u32,
+    gain: watch::Receiver<f64>,
+    tx: mpsc::Sender<Vec<u8>>,
+) -> anyhow::Result<bool> {
+    if tx.is_closed() {
+        return Ok(false);
+    }
+    let (input, bytes) = match source {
+        Source::File(path) => (path.into_os_string(), None),
+        Source::Bytes(bytes) => ("pipe:0".into(), Some(bytes)),
+    };
+    let mut decoder = ffmpeg_command(ffmpeg)
+        .arg("-i")
+        .arg(input)
+        .arg("-vn")
+        .args(PCM_FORMAT)
+        .arg("pipe:1")
+        .stdin(if bytes.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", ffmpeg, e))?;
+    // Small Ogg pages, flushed at once, so a gain change is heard right away
+    let mut encoder = ffmpeg_command(ffmpeg)
+        .args(PCM_FORMAT)
+        .args([
+            "-i",
+            "pipe:0",
+            "-c:a",
+            "libopus",
+            "-b:a",
+            "96k",
+            "-frame_duration",
+            "20",
+            "-application",
+            "audio",
+            "-page_duration",
+            "20000",
+            "-flush_packets",
+            "1",
+            "-f",
+            "ogg",
+            "pipe:1",
+        ])
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", ffmpeg, e))?;
+    let (Some(mut pcm), Some(mut encoder_in), Some(mut ogg)) = (
+        decoder.stdout.take(),
+        encoder.stdin.take(),
+        encoder.stdout.take(),
+    ) else {
+        anyhow::bail!("FFmpeg pipes are missing");
+    };
+    if let (Some(bytes), Some(mut stdin)) = (bytes, decoder.stdin.take()) {
+        // Written alongside the reads so neither pipe fills up
+        tokio::spawn(async move {
+            let _ = stdin.write_all(&bytes).await;
+        });
+    }
+
+    // Paced on the way into the encoder, so the gain applied is never more
+    // than a frame or two ahead of what is sent
+    let feed = async move {
+        let mut ticks = tokio::time::interval(FRAME);
+        let mut frame = vec![0u8; PCM_FRAME];
+        let mut applied = *gain.borrow();
+        loop {
+            match pcm.read_exact(&mut frame).await {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => return Err(e.into()),
+            }
+            let target = *gain.borrow();
+            scale(&mut frame, applied, target);
+            applied = target;
+            ticks.tick().await;
+            encoder_in.write_all(&frame).await?;
+        }
+        // Closing its input lets the encoder finish
+        drop(encoder_in);
+        anyhow::Ok(())
+    };
+    let forward = async move {
+        let mut demuxer = OggOpusDemuxer::default();
+        let mut packetizer = RtpPacketizer::new(ssrc);
+        let mut chunk = vec![0u8; 8192];
+        loop {
+            let n = ogg.read(&mut chunk).await?;
+            if n == 0 {
+                return anyhow::Ok(true);
+            }
+            for frame in demuxer.push(&chunk[..n]) {
+                if tx.send(packetizer.packet(&frame)).await.is_err() {
+                    return Ok(false);
+                }
+            }
+        }
+    };
+    tokio::pin!(feed, forward);
+
+    let mut fed = false;
+    let sent = loop {
+        tokio::select! {
+            result = &mut feed, if !fed => {
+                result?;
+                fed = true;
+            }
+            sent = &mut forward => break sent?,
+        }
+    };
+    if !sent {
+        return Ok(false);
+    }
+    anyhow::ensure!(fed, "FFmpeg stopped encoding early");
+
+    let decoded = decoder.wait().await?;
+    anyhow::ensure!(decoded.success(), "FFmpeg failed to decode: {}", decoded);
+    let encoded = encoder.wait().await?;
+    anyhow::ensure!(encoded.success(), "FFmpeg failed to encode: {}", encoded);
+    Ok(true)
+}
+
+/// Scale a frame of 16-bit stereo PCM, ramping from gain `from` to `to`
+/// across it so the change doesn't click.
+fn scale(frame: &mut [u8], from: f64, to: f64) {
+    if from == 1.0 && to == 1.0 {
+        return;
+    }
+    let samples = frame.len() / 4;
+    for (i, sample) in frame.chunks_exact_mut(4).enumerate() {
+        let gain = from + (to - from) * (i + 1) as f64 / samples as f64;
+        for channel in sample.chunks_exact_mut(2) {
+            let value = i16::from_le_bytes([channel[0], channel[1]]) as f64 * gain;
+            let value = value.round().clamp(i16::MIN as f64, i16::MAX as f64) as i16;
+            channel.copy_from_slice(&value.to_le_bytes());
+        }
+    }
+}
+
+/// Splits an Ogg Opus stream into its Opus packets, leaving out the
+/// `OpusHead` and `OpusTags` headers.
+#[derive(Default)]
+struct OggOpusDemuxer {
+    buf: Vec<u8>,
+    /// A packet continued on the next page.
+    packet: Vec<u8>,
+    headers_seen: u8,
+}
+
+impl OggOpusDemuxer {
+    /// Feed more of the stream; returns the packets it completed.
+    fn push(&mut self, data: &[u8]) -> Vec<Vec<u8>> {
+        self.buf.extend_from_slice(data);
+        let mut packets = Vec::new();
+        while self.buf.len() >= 27 {
+            if !self.buf.starts_with(b"OggS") {
+                // Skip to the next page
+                match self.buf[1..].windows(4).position(|w| w == b"OggS") {
+                    Some(i) => {
+                        self.buf.drain(..=i);
+                        continue;
+                    }
+                    None => {
+                        self.buf.drain(..self.buf.len() - 3);
+                        break;
+                    }
+                }
+            }
+            let header_len = 27 + self.buf[26] as usize;
+            if self.buf.len() < header_len {
+                break;
+            }
+            let body_len: usize = self.buf[27..header_len].iter().map(|&l| l as usize).sum();
+            if self.buf.len() < header_len + body_len {
+                break;
+            }
+
+            let mut offset = header_len;
+            for &lacing in &self.buf[27..header_len] {
+                let end = offset + lacing as usize;
+                self.packet.extend_from_slice(&self.buf[offset..end]);
+                offset = end;
+                // A lacing value under 255 ends the packet
+                if lacing < 255 {
+                    let packet = std::mem::take(&mut self.packet);
+                    if self.headers_seen < 2 {
+                        self.headers_seen += 1;
+                    } else if !packet.is_empty() {
+                        packets.push(packet);
+                    }
+                }
+            }
+            self.buf.drain(..header_len + body_len);
+        }
+        packets
+    }
+}
+
+/// Wraps Opus frames in RTP packets of one stream.
+struct RtpPacketizer {
+    ssrc: u32,
+    sequence: u16,
+    timestamp: u32,
+}
+
+impl RtpPacketizer {
+    fn new(ssrc: u32) -> Self {
+        Self {
+            ssrc,
+            sequence: rand::random(),
+            timestamp: rand::random(),
+        }
+    }
+
+    fn packet(&mut self, frame: &[u8]) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(12 + frame.len());
+        // Version 2, no padding, extension or CSRCs
+        packet.push(0x80);
+        packet.push(PAYLOAD_TYPE);
+        packet.extend_from_slice(&self.sequence.to_be_bytes());
+        packet.extend_from_slice(&self.timestamp.to_be_bytes());
+        packet.extend_from_slice(&self.ssrc.to_be_bytes());
+        packet.extend_from_slice(frame);
+        self.sequence = self.sequence.wrapping_add(1);
+        self.timestamp = self.timestamp.wrapping_add(FRAME_SAMPLES);
+        packet
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(segments: &[u8], body: &[u8]) -> Vec<u8> {
+        let mut page = b"OggS".to_vec();
+        page.extend_from_slice(&[0; 22]);
+        page.push(segments.len() as u8);
+        page.extend_from_slice(segments);
+        page.extend_from_slice(body);
+        page
+    }
+
+    #[test]
+    fn demuxer_skips_headers_and_joins_continued_packets() {
+        let mut stream = page(&[8], b"OpusHead");
+        stream.extend(page(&[8], b"OpusTags"));
+        let long = vec![7u8; 300];
+        // A 300-byte packet split over two pages, then a 2-byte one
+        stream.extend(page(&[255], &long[..255]));
+        let mut rest = long[255..].to_vec();
+        rest.extend_from_slice(&[1, 2]);
+        stream.extend(page(&[45, 2], &rest));
+
+        let mut demuxer = OggOpusDemuxer::default();
+        // Fed in awkward chunks, with junk before the first page
+        let mut packets = demuxer.push(b"junk");
+        for chunk in stream.chunks(13) {
+            packets.extend(demuxer.push(chunk));
+        }
+        assert_eq!(packets, vec![long, vec![1, 2]]);
+    }
+
+    #[test]
+    fn gain_changes_ramp_across_the_frame() {
+        let frame = |value: i16| value.to_le_bytes().repeat(2 * FRAME_SAMPLES as usize);
+        let sample = |pcm: &[u8], i: usize| i16::from_le_bytes([pcm[i * 4], pcm[i * 4 + 1]]);
+
+        let mut pcm = frame(1000);
+        scale(&mut pcm, 1.0, 1.0);
+        assert_eq!(pcm, frame(1000));
+
+        let mut pcm = frame(1000);
+        scale(&mut pcm, 1.0, 0.2);
+        assert!(sample(&pcm, 0) > 990);
+        assert_eq!(sample(&pcm, FRAME_SAMPLES as usize - 1), 200);
+        // Both channels alike
+        assert_eq!(pcm[..2], pcm[2..4]);
+
+        let mut pcm = frame(i16::MAX);
+        scale(&mut pcm, 2.0, 2.0);
+        assert_eq!(pcm, frame(i16::MAX));
+    }
+
+    #[test]
+    fn packets_advance_one_frame_each() {
+        let mut packetizer = RtpPacketizer::new(0xdead_beef);
+        let first = packetizer.packet(&[9, 9]);
+        let second = packetizer.packet(&[9]);
+        assert_eq!(first[..2], [0x80, PAYLOAD_TYPE]);
+        assert_eq!(first[8..12], 0xdead_beef_u32.to_be_bytes());
+        assert_eq!(first[12..], [9, 9]);
+
+        let seq = |p: &[u8]| u16::from_be_bytes([p[2], p[3]]);
+        let ts = |p: &[u8]| u32::from_be_bytes([p[4], p[5], p[6], p[7]]);
+        assert_eq!(seq(&second), seq(&first).wrapping_add(1));
+        assert_eq!(ts(&second), ts(&first).wrapping_add(FRAME_SAMPLES));
+    }
+}
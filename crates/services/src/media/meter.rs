@@ -0,0 +1,109 @@
+//! Media usage metering.
+//!
+//! Every producer and RTP tap holds a [`MeterGuard`]. Dropping it, however
+//! the producer goes away (closed, participant left, room removed), books
+//! its lifetime against the room it was billed to. The usage meter collects
+//! the totals with [`MediaMeter::drain`].
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use bson::oid::ObjectId;
+use dashmap::DashMap;
+
+/// What a guarded lifetime is billed as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeterKind {
+    /// Media published into a call.
+    Streamed,
+    /// Audio tapped for transcription.
+    Transcription,
+}
+
+/// Milliseconds booked against one room since the last drain.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MediaUsage {
+    pub streamed_ms: u64,
+    pub transcription_ms: u64,
+}
+
+impl MediaUsage {
+    fn add(&mut self, kind: MeterKind, ms: u64) {
+        match kind {
+            MeterKind::Streamed => self.streamed_ms += ms,
+            MeterKind::Transcription => self.transcription_ms += ms,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct MediaMeter {
+    totals: DashMap<ObjectId, MediaUsage>,
+}
+
+impl MediaMeter {
+    /// Start metering a lifetime billed to `room_id`.
+    pub fn start(self: &Arc<Self>, room_id: ObjectId, kind: MeterKind) -> MeterGuard {
+        MeterGuard {
+            meter: self.clone(),
+            room_id,
+            kind,
+            started: Instant::now(),
+        }
+    }
+
+    /// Take every room's usage booked so far.
+    pub fn drain(&self) -> Vec<(ObjectId, MediaUsage)> {
+        let room_ids: Vec<ObjectId> = self.totals.iter().map(|e| *e.key()).collect();
+        room_ids
+            .into_iter()
+            .filter_map(|id| self.totals.remove(&id))
+            .collect()
+    }
+
+    fn book(&self, room_id: ObjectId, kind: MeterKind, ms: u64) {
+        self.totals.entry(room_id).or_default().add(kind, ms);
+    }
+}
+
+/// Books the time since it was created when dropped.
+pub struct MeterGuard {
+    meter: Arc<MediaMeter>,
+    room_id: ObjectId,
+    kind: MeterKind,
+    started: Instant,
+}
+
+impl Drop for MeterGuard {
+    fn drop(&mut self) {
+        let ms = self.started.elapsed().as_millis() as u64;
+        self.meter.book(self.room_id, self.kind, ms);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropped_guards_are_booked_per_room_until_drained() {
+        let meter = Arc::new(MediaMeter::default());
+        let (a, b) = (ObjectId::new(), ObjectId::new());
+
+        let camera = meter.start(a, MeterKind::Streamed);
+        let tap = meter.start(a, MeterKind::Transcription);
+        let screen = meter.start(b, MeterKind::Streamed);
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        drop(camera);
+        drop(tap);
+        assert!(meter.drain().iter().all(|(id, _)| *id == a));
+
+        drop(screen);
+        let drained = meter.drain();
+        assert_eq!(drained.len(), 1);
+        assert_eq!(drained[0].0, b);
+        assert!(drained[0].1.streamed_ms >= 20);
+        assert_eq!(drained[0].1.transcription_ms, 0);
+        assert!(meter.drain().is_empty());
+    }
+}
@@ -1,3 +1,8 @@
+pub mod backend;
+pub mod bandwidth;
+pub mod file_audio;
+pub mod meter;
+pub mod quality;
 pub mod room_manager;
 pub mod signaling;
 pub mod worker_pool;
@@ -0,0 +1,51 @@
+//! Connection quality scores.
+//!
+//! Each participant's media path is rated from the server's side of its
+//! transports: the RTP loss on the way in and back out, and the round trip
+//! the SFU measures to the client from RTCP. The score runs from 1 (barely
+//! usable) to 5 (clean), like the bars of a phone signal.
+
+use bson::oid::ObjectId;
+
+/// Loss (0-1) from which a score loses one, two and three bars.
+const LOSS_STEPS: [f64; 3] = [0.02, 0.05, 0.10];
+/// Round trip in ms from which a score loses one and two bars.
+const RTT_STEPS_MS: [f64; 2] = [250.0, 400.0];
+
+/// The quality of one participant's connection.
+#[derive(Debug, Clone)]
+pub struct ParticipantQuality {
+    pub user_id: ObjectId,
+    pub connection_id: String,
+    /// 1 (poor) to 5 (excellent).
+    pub score: u8,
+    /// Round trip to the client in ms, once RTCP has measured it.
+    pub rtt: Option<f64>,
+    /// The worse of uplink and downlink RTP loss (0-1).
+    pub loss: f64,
+}
+
+/// Rate a connection from its RTP loss (0-1) and round trip in ms.
+pub fn score(rtt_ms: Option<f64>, loss: f64) -> u8 {
+    let loss_penalty = LOSS_STEPS.iter().filter(|&&step| loss >= step).count();
+    let rtt_penalty = rtt_ms.map_or(0, |rtt| {
+        RTT_STEPS_MS.iter().filter(|&&step| rtt >= step).count()
+    });
+    5u8.saturating_sub((loss_penalty + rtt_penalty) as u8)
+        .max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scores_drop_with_loss_and_round_trip() {
+        assert_eq!(score(None, 0.0), 5);
+        assert_eq!(score(Some(80.0), 0.01), 5);
+        assert_eq!(score(Some(80.0), 0.03), 4);
+        assert_eq!(score(Some(300.0), 0.03), 3);
+        assert_eq!(score(Some(500.0), 0.06), 1);
+        assert_eq!(score(Some(900.0), 0.5), 1);
+    }
+}
@@ -1,49 +1,280 @@
 use bson::oid::ObjectId;
-use dashmap::DashMap;
-use mediasoup::prelude::*;
-use mediasoup::webrtc_transport::{
-    WebRtcTransportListenInfos, WebRtcTransportOptions, WebRtcTransportRemoteParameters,
+use dashmap::{DashMap, DashSet};
+use mediasoup::prelude::{
+    DtlsParameters, MediaKind, ProducerId, RtpCapabilities, RtpParameters, TransportId,
 };
-use roomler_ai_config::MediasoupSettings;
+use roomler_ai_db::models::room::OverflowMode;
 use serde::{Deserialize, Serialize};
-use std::net::IpAddr;
-use std::num::NonZero;
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::str::FromStr;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
 use tokio::sync::mpsc;
-use tracing::{debug, info};
+use tracing::{Span, debug, field, info, instrument, warn};
 
-use super::worker_pool::WorkerPool;
+use super::backend::{
+    AudioLevels, MediaBackend, MediaConsumer, MediaHandle, MediaProducer, MediaRouter,
+    MediaTransport,
+};
+use super::bandwidth::{self, VideoConsumer};
+use super::meter::{MediaMeter, MediaUsage, MeterGuard, MeterKind};
+use super::quality::{self, ParticipantQuality};
 
-/// Holds the DirectTransport + Consumer for an RTP tap (transcription).
+/// Holds the server-side consumer of an RTP tap (transcription).
 struct RtpTap {
-    _direct_transport: DirectTransport,
-    _consumer: Consumer,
+    _handle: MediaHandle,
+    _meter: MeterGuard,
+}
+
+/// One of this pod's producers piped to another pod's Router.
+struct PipeOut {
+    producer_id: ProducerId,
+    _handle: MediaHandle,
+}
+
+/// A producer on another pod's Router, as listed by that pod, for
+/// [`RoomManager::pipe_in`].
+#[derive(Debug, Clone)]
+pub struct RemoteProducer {
+    pub producer_id: ProducerId,
+    pub user_id: ObjectId,
+    /// The producer's connection on the other pod.
+    pub connection_id: String,
+    pub source: String,
 }
 
-/// A media room backed by a mediasoup Router.
+/// A producer of another pod's Router piped into an edge room.
+struct PipedIn {
+    producer: Arc<dyn MediaProducer>,
+    remote: RemoteProducer,
+}
+
+/// This pod's end of a pipe from [`RoomManager::pipe_producer`]: where the
+/// other pod connects its pipe transport, and what it produces from it.
+pub struct PipedProducer {
+    pub ip: String,
+    pub port: u16,
+    pub kind: MediaKind,
+    pub rtp_parameters: RtpParameters,
+}
+
+/// A producer the server feeds itself, e.g. a file played to the call.
+struct Injected {
+    _producer: Arc<dyn MediaProducer>,
+    _meter: MeterGuard,
+}
+
+/// A media room backed by a Router of the [`MediaBackend`].
 pub struct MediaRoom {
-    pub router: Router,
+    pub router: Arc<dyn MediaRouter>,
+    /// The mediasoup worker running `router`.
+    worker_id: String,
     /// Keyed by connection_id (UUID per WebSocket connection) so the same user
     /// can join from multiple tabs/devices without overwriting state.
     pub participants: DashMap<String, ParticipantMedia>,
     /// RTP taps for transcription, keyed by producer_id string.
     rtp_taps: DashMap<String, RtpTap>,
+    /// Producers piped to other pods, keyed by pipe consumer id.
+    pipes: DashMap<String, PipeOut>,
+    /// Producers piped in from the pod hosting the call, keyed by
+    /// producer_id string; only in an edge room.
+    piped_in: DashMap<String, PipedIn>,
+    /// Server-fed producers, keyed by producer_id string.
+    injected: DashMap<String, Injected>,
+    /// Observes the participants' audio producers (not the injected ones)
+    /// for [`RoomManager::observe_speech`].
+    audio_levels: Option<Arc<dyn AudioLevels>>,
+    /// Someone is heard in the room right now.
+    speech: Arc<AtomicBool>,
+    /// The room its media usage is billed to: itself, or the parent call of
+    /// a breakout.
+    billed_to: ObjectId,
+    /// Insertable-streams E2EE is on for this call (from `MediaSettings`).
+    e2ee_enabled: AtomicBool,
+    /// Current E2EE key epoch. Bumped on every membership change so a new
+    /// participant can't decrypt earlier media and a departed one can't
+    /// decrypt later media. Keys themselves never reach the server.
+    key_epoch: AtomicU64,
+    /// Per-transport bitrate caps in bps from `MediaSettings`; 0 is uncapped.
+    max_incoming_bitrate: AtomicU32,
+    max_outgoing_bitrate: AtomicU32,
+    /// A pre-call device test: one connection consumes its own media, which
+    /// isn't metered.
+    loopback: bool,
+    /// Relays a call hosted by another pod: everyone here only listens.
+    edge: bool,
+    /// Audio only flows while the participant holds `media:ptt_active`.
+    push_to_talk: AtomicBool,
+    /// Users an organizer muted; their connections joining later start
+    /// muted too.
+    muted_users: DashSet<ObjectId>,
+    /// Webinar mode: only `speakers` may produce, everyone else only
+    /// consumes.
+    webinar: AtomicBool,
+    speakers: DashSet<ObjectId>,
+    /// Users who declined consent to the call's recording; their producers
+    /// are never tapped.
+    unrecorded_users: DashSet<ObjectId>,
+    /// Users who opted out of transcription; their audio is never tapped.
+    untranscribed_users: DashSet<ObjectId>,
+    /// Participant cap and what happens to joiners past it.
+    capacity: RwLock<Capacity>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Capacity {
+    max_participants: Option<u32>,
+    overflow: OverflowMode,
+}
+
+/// A join refused because the room is at its participant cap.
+#[derive(Debug, thiserror::Error)]
+#[error("Room is full ({max_participants} participants)")]
+pub struct RoomFull {
+    pub max_participants: u32,
+}
+
+impl MediaRoom {
+    /// Note the room's worker on the current span, so a slow media
+    /// operation can be traced to the worker that served it.
+    fn record_worker(&self) {
+        Span::current().record("worker_id", field::display(&self.worker_id));
+    }
+
+    /// Admit `user_id` in full, in the overflow mode, or refuse them. A user
+    /// already connected keeps the role of their first connection, so extra
+    /// tabs never count twice.
+    fn admit(&self, user_id: &ObjectId) -> Result<Option<OverflowMode>, RoomFull> {
+        if self.edge {
+            return Ok(Some(OverflowMode::ListenOnly));
+        }
+        if let Some(existing) = self.participants.iter().find(|p| p.user_id == *user_id) {
+            return Ok(existing.overflow);
+        }
+        let capacity = *self.capacity.read().unwrap();
+        let Some(max_participants) = capacity.max_participants else {
+            return Ok(None);
+        };
+        let full: HashSet<ObjectId> = self
+            .participants
+            .iter()
+            .filter(|p| p.overflow.is_none())
+            .map(|p| p.user_id)
+            .collect();
+        if full.len() < max_participants as usize {
+            return Ok(None);
+        }
+        match capacity.overflow {
+            OverflowMode::Reject => Err(RoomFull { max_participants }),
+            mode => Ok(Some(mode)),
+        }
+    }
+
+    /// Drop the pipes to other pods of producers that closed.
+    fn close_pipes(&self, producer_ids: &[ProducerId]) {
+        self.pipes
+            .retain(|_, pipe| !producer_ids.contains(&pipe.producer_id));
+    }
+
+    fn producer_user(&self, producer_id: &ProducerId) -> Option<ObjectId> {
+        self.participants.iter().find_map(|p| {
+            p.producers
+                .iter()
+                .any(|pe| pe.producer.id() == *producer_id)
+                .then_some(p.user_id)
+        })
+    }
+
+    fn producer_kind(&self, producer_id: &ProducerId) -> Option<MediaKind> {
+        self.participants
+            .iter()
+            .find_map(|p| {
+                p.producers
+                    .iter()
+                    .find(|pe| pe.producer.id() == *producer_id)
+                    .map(|pe| pe.producer.kind())
+            })
+            .or_else(|| {
+                self.piped_in
+                    .get(&producer_id.to_string())
+                    .map(|piped| piped.producer.kind())
+            })
+    }
 }
 
 /// A producer with its source label (e.g. "camera", "screen", "audio").
 pub struct ProducerEntry {
-    pub producer: Producer,
+    pub producer: Arc<dyn MediaProducer>,
     pub source: String,
+    _meter: Option<MeterGuard>,
 }
 
 /// Media state for a single participant (one WebSocket connection).
 pub struct ParticipantMedia {
     pub user_id: ObjectId,
-    pub send_transport: WebRtcTransport,
-    pub recv_transport: WebRtcTransport,
+    pub send_transport: Arc<dyn MediaTransport>,
+    pub recv_transport: Arc<dyn MediaTransport>,
     pub producers: Vec<ProducerEntry>,
-    pub consumers: Vec<Consumer>,
+    pub consumers: Vec<Arc<dyn MediaConsumer>>,
+    /// Camera effects the client reports applying before it sends video.
+    pub effects: VideoEffects,
+    /// Latest transport-cc estimate of the recv transport in bps, written by
+    /// its trace handler; 0 until the first estimate.
+    pub downlink_bps: Arc<AtomicU32>,
+    /// Secret a new WebSocket connection presents to take this media over
+    /// after the original one dropped. Replaced on every takeover.
+    pub resume_token: String,
+    /// The WebSocket dropped and the media is kept only for a takeover.
+    pub suspended: bool,
+    /// Server-enforced audio state; applies to every audio producer the
+    /// connection has now or creates later.
+    pub audio: AudioState,
+    /// Joined past the room's cap in this degraded mode; `None` is a full
+    /// participant.
+    pub overflow: Option<OverflowMode>,
+}
+
+impl ParticipantMedia {
+    fn producer_ids(&self) -> Vec<ProducerId> {
+        self.producers.iter().map(|pe| pe.producer.id()).collect()
+    }
+}
+
+/// Whether a connection's audio may reach the room.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct AudioState {
+    /// Muted by an organizer; only an organizer can lift it.
+    pub force_muted: bool,
+    /// Holding the push-to-talk key.
+    pub ptt_active: bool,
+}
+
+impl AudioState {
+    /// Whether the connection's audio producers are to be paused.
+    pub fn silenced(&self, push_to_talk: bool) -> bool {
+        self.force_muted || (push_to_talk && !self.ptt_active)
+    }
+}
+
+/// Camera effects a participant applies locally. The server never touches
+/// the video; it only tells the others (and any recorder) what's on.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct VideoEffects {
+    #[serde(default)]
+    pub background: BackgroundEffect,
+    /// Tenant background asset (a file id) when `background` is `image`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asset_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackgroundEffect {
+    #[default]
+    None,
+    Blur,
+    Image,
 }
 
 /// Transport connection details sent to the client.
@@ -60,6 +291,33 @@ pub struct TransportOptions {
 pub struct TransportPair {
     pub send_transport: TransportOptions,
     pub recv_transport: TransportOptions,
+    /// See [`ParticipantMedia::resume_token`].
+    pub resume_token: String,
+}
+
+/// A consumer the downlink policy paused or resumed.
+#[derive(Debug, Clone)]
+pub struct ConsumerToggle {
+    pub room_id: ObjectId,
+    pub connection_id: String,
+    pub consumer_id: String,
+    pub paused: bool,
+}
+
+/// Network figures of a device test, from the server's side of its
+/// transports.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LoopbackStats {
+    /// What the server receives from the client, in bps.
+    pub uplink_bps: u32,
+    /// What the server sends back, in bps.
+    pub downlink_bps: u32,
+    /// Transport-cc estimate of the downlink in bps; 0 until known.
+    pub downlink_estimate_bps: u32,
+    /// Share of the client's RTP packets lost on the way in (0-1).
+    pub uplink_packet_loss: Option<f64>,
+    /// Share of RTP packets the client reports lost on the way back (0-1).
+    pub downlink_packet_loss: Option<f64>,
 }
 
 /// Consumer details sent to the client.
@@ -71,73 +329,183 @@ pub struct ConsumerInfo {
     pub rtp_parameters: serde_json::Value,
 }
 
+/// An ICE or DTLS state change of a participant's transport, for
+/// [`RoomManager::observe_transport_states`].
+#[derive(Debug, Clone)]
+pub struct TransportStateChange {
+    pub room_id: ObjectId,
+    pub user_id: ObjectId,
+    /// The connection the transport was created for.
+    pub connection_id: String,
+    /// `send` or `recv`.
+    pub direction: &'static str,
+    pub layer: TransportLayer,
+    /// mediasoup's state name, e.g. `connected` or `failed`.
+    pub state: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportLayer {
+    Ice,
+    Dtls,
+}
+
+type TransportObserver = Arc<dyn Fn(TransportStateChange) + Send + Sync>;
+
+type SpeechObserver = Arc<dyn Fn(ObjectId, bool) + Send + Sync>;
+
 /// Manages mediasoup rooms and their media state.
 pub struct RoomManager {
     rooms: DashMap<ObjectId, MediaRoom>,
     /// Tracks which room each connection is in (connection_id -> room_id).
     connection_rooms: DashMap<String, ObjectId>,
-    worker_pool: Arc<WorkerPool>,
-    listen_ip: IpAddr,
-    announced_ip: Option<String>,
+    /// Breakout Routers of each call (parent room_id -> breakout ids). They
+    /// are ordinary entries in `rooms`, torn down with their parent.
+    breakouts: DashMap<ObjectId, Vec<ObjectId>>,
+    backend: Arc<dyn MediaBackend>,
+    meter: Arc<MediaMeter>,
+    transport_observer: OnceLock<TransportObserver>,
+    speech_observer: OnceLock<SpeechObserver>,
 }
 
 impl RoomManager {
-    pub fn new(worker_pool: Arc<WorkerPool>, settings: &MediasoupSettings) -> Self {
-        let listen_ip: IpAddr = settings
-            .listen_ip
-            .parse()
-            .unwrap_or_else(|_| "0.0.0.0".parse().unwrap());
-
-        let announced_ip = if settings.announced_ip.is_empty() {
-            None
-        } else {
-            Some(settings.announced_ip.clone())
-        };
-
+    pub fn new(backend: Arc<dyn MediaBackend>) -> Self {
         Self {
             rooms: DashMap::new(),
             connection_rooms: DashMap::new(),
-            worker_pool,
-            listen_ip,
-            announced_ip,
+            breakouts: DashMap::new(),
+            backend,
+            meter: Arc::new(MediaMeter::default()),
+            transport_observer: OnceLock::new(),
+            speech_observer: OnceLock::new(),
         }
     }
 
-    /// Creates a mediasoup Router for a room and stores it.
-    /// Returns the router's RTP capabilities (serialized).
-    pub async fn create_room(&self, room_id: ObjectId) -> anyhow::Result<serde_json::Value> {
-        if self.rooms.contains_key(&room_id) {
-            let room = self.rooms.get(&room_id).unwrap();
-            let caps = room.router.rtp_capabilities().clone();
-            return Ok(serde_json::to_value(caps)?);
+    /// Call `observer` on every ICE and DTLS state change of participants'
+    /// transports created from now on. Only the first observer is kept.
+    pub fn observe_transport_states(
+        &self,
+        observer: impl Fn(TransportStateChange) + Send + Sync + 'static,
+    ) {
+        if self.transport_observer.set(Arc::new(observer)).is_err() {
+            warn!("transport state observer already set");
         }
+    }
 
-        let worker = self.worker_pool.get_worker();
+    /// Call `observer` with the room and `true` when someone in a room
+    /// created from now on starts being heard, by the router's audio level
+    /// observer, and `false` once everyone is quiet. Only the first
+    /// observer is kept.
+    pub fn observe_speech(&self, observer: impl Fn(ObjectId, bool) + Send + Sync + 'static) {
+        if self.speech_observer.set(Arc::new(observer)).is_err() {
+            warn!("speech observer already set");
+        }
+    }
 
-        let media_codecs = media_codecs();
-        let router_options = RouterOptions::new(media_codecs);
-        let router = worker
-            .create_router(router_options)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create router: {}", e))?;
+    /// Whether someone in the room is heard right now.
+    pub fn is_speech_heard(&self, room_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|room| room.speech.load(Ordering::Relaxed))
+    }
+
+    /// Creates a Router for a room and stores it.
+    /// Returns the router's RTP capabilities (serialized).
+    pub async fn create_room(&self, room_id: ObjectId) -> anyhow::Result<serde_json::Value> {
+        if let Some(caps) = self.rtp_capabilities(&room_id) {
+            return Ok(caps);
+        }
 
-        let caps = router.rtp_capabilities().clone();
+        let router = self.backend.create_router().await?;
+
+        let caps = router.rtp_capabilities();
+        let speech = Arc::new(AtomicBool::new(false));
+        let audio_levels = match self.speech_observer.get().cloned() {
+            Some(observer) => {
+                let speech = speech.clone();
+                let listener = Arc::new(move |heard| {
+                    speech.store(heard, Ordering::Relaxed);
+                    observer(room_id, heard);
+                });
+                Some(router.observe_audio_levels(listener).await?)
+            }
+            None => None,
+        };
         info!(?room_id, "mediasoup room created");
 
         self.rooms.insert(
             room_id,
             MediaRoom {
+                worker_id: router.worker_id(),
                 router,
                 participants: DashMap::new(),
                 rtp_taps: DashMap::new(),
+                pipes: DashMap::new(),
+                piped_in: DashMap::new(),
+                injected: DashMap::new(),
+                audio_levels,
+                speech,
+                billed_to: room_id,
+                e2ee_enabled: AtomicBool::new(false),
+                key_epoch: AtomicU64::new(0),
+                max_incoming_bitrate: AtomicU32::new(0),
+                max_outgoing_bitrate: AtomicU32::new(0),
+                loopback: false,
+                edge: false,
+                push_to_talk: AtomicBool::new(false),
+                muted_users: DashSet::new(),
+                webinar: AtomicBool::new(false),
+                speakers: DashSet::new(),
+                unrecorded_users: DashSet::new(),
+                untranscribed_users: DashSet::new(),
+                capacity: RwLock::new(Capacity::default()),
             },
         );
 
-        Ok(serde_json::to_value(caps)?)
+        Ok(caps)
+    }
+
+    /// The RTP capabilities of the room's Router, if it has one here.
+    pub fn rtp_capabilities(&self, room_id: &ObjectId) -> Option<serde_json::Value> {
+        self.rooms
+            .get(room_id)
+            .map(|room| room.router.rtp_capabilities())
+    }
+
+    /// Creates a loopback Router for a pre-call device test, where a single
+    /// connection produces and consumes its own media. Removed like any
+    /// other room. Returns the router's RTP capabilities.
+    pub async fn create_loopback(&self, test_id: ObjectId) -> anyhow::Result<serde_json::Value> {
+        let caps = self.create_room(test_id).await?;
+        if let Some(mut room) = self.rooms.get_mut(&test_id) {
+            room.loopback = true;
+        }
+        Ok(caps)
+    }
+
+    pub fn is_loopback(&self, room_id: &ObjectId) -> bool {
+        self.rooms.get(room_id).is_some_and(|room| room.loopback)
+    }
+
+    /// Creates a Router relaying a call whose Router is on another pod.
+    /// Everyone joining it only listens, to producers brought over with
+    /// [`pipe_in`](Self::pipe_in). Removed like any other room. Returns the
+    /// router's RTP capabilities.
+    pub async fn create_edge(&self, room_id: ObjectId) -> anyhow::Result<serde_json::Value> {
+        let caps = self.create_room(room_id).await?;
+        if let Some(mut room) = self.rooms.get_mut(&room_id) {
+            room.edge = true;
+        }
+        Ok(caps)
+    }
+
+    pub fn is_edge(&self, room_id: &ObjectId) -> bool {
+        self.rooms.get(room_id).is_some_and(|room| room.edge)
     }
 
     /// Removes a room and all its media state.
     pub fn remove_room(&self, room_id: &ObjectId) -> bool {
+        self.remove_breakouts(room_id);
         if let Some((_, room)) = self.rooms.remove(room_id) {
             // Clean up connection_rooms mappings
             let conn_ids: Vec<String> = room
@@ -146,7 +514,9 @@ impl RoomManager {
                 .map(|entry| entry.key().clone())
                 .collect();
             for cid in conn_ids {
-                self.connection_rooms.remove(&cid);
+                // The connection may have moved on to another room.
+                self.connection_rooms
+                    .remove_if(&cid, |_, rid| rid == room_id);
             }
             // Dropping the room closes the router and all transports/producers/consumers
             info!(?room_id, "mediasoup room removed");
@@ -156,6 +526,46 @@ impl RoomManager {
         }
     }
 
+    /// Creates a Router per breakout room of `parent_id`, each with the
+    /// parent's E2EE setting and bitrate caps. They are removed with the
+    /// parent or by [`RoomManager::remove_breakouts`].
+    pub async fn create_breakouts(
+        &self,
+        parent_id: ObjectId,
+        breakout_ids: &[ObjectId],
+        e2ee: bool,
+    ) -> anyhow::Result<()> {
+        let (incoming, outgoing) = self.bitrate_caps(&parent_id);
+        for id in breakout_ids {
+            self.create_room(*id).await?;
+            if let Some(mut room) = self.rooms.get_mut(id) {
+                room.billed_to = parent_id;
+            }
+            self.set_e2ee(id, e2ee);
+            self.set_bitrate_caps(id, incoming, outgoing);
+            self.breakouts.entry(parent_id).or_default().push(*id);
+        }
+        Ok(())
+    }
+
+    /// Removes the breakout Routers of `parent_id`, returning their ids.
+    pub fn remove_breakouts(&self, parent_id: &ObjectId) -> Vec<ObjectId> {
+        let ids = self
+            .breakouts
+            .remove(parent_id)
+            .map(|(_, ids)| ids)
+            .unwrap_or_default();
+        for id in &ids {
+            self.remove_room(id);
+        }
+        ids
+    }
+
+    /// Media usage booked since the last call, per billed room.
+    pub fn take_usage(&self) -> Vec<(ObjectId, MediaUsage)> {
+        self.meter.drain()
+    }
+
     pub fn has_room(&self, room_id: &ObjectId) -> bool {
         self.rooms.contains_key(room_id)
     }
@@ -164,12 +574,305 @@ impl RoomManager {
         self.rooms.len()
     }
 
+    /// (running, total) mediasoup workers.
+    pub fn worker_counts(&self) -> (usize, usize) {
+        self.backend.worker_counts()
+    }
+
+    /// See [`MediaBackend::ping_workers`].
+    pub async fn ping_workers(&self, timeout: std::time::Duration) -> Vec<bool> {
+        self.backend.ping_workers(timeout).await
+    }
+
+    /// IDs of every room whose Router lives in this process.
+    pub fn room_ids(&self) -> Vec<ObjectId> {
+        self.rooms.iter().map(|r| *r.key()).collect()
+    }
+
+    /// Turn E2EE key-epoch coordination on or off for a live room.
+    pub fn set_e2ee(&self, room_id: &ObjectId, enabled: bool) {
+        if let Some(room) = self.rooms.get(room_id) {
+            room.e2ee_enabled.store(enabled, Ordering::Relaxed);
+        }
+    }
+
+    /// Cap the bitrate of transports created from now on in a live room.
+    /// `None` leaves that direction uncapped.
+    pub fn set_bitrate_caps(
+        &self,
+        room_id: &ObjectId,
+        max_incoming: Option<u32>,
+        max_outgoing: Option<u32>,
+    ) {
+        if let Some(room) = self.rooms.get(room_id) {
+            room.max_incoming_bitrate
+                .store(max_incoming.unwrap_or(0), Ordering::Relaxed);
+            room.max_outgoing_bitrate
+                .store(max_outgoing.unwrap_or(0), Ordering::Relaxed);
+        }
+    }
+
+    /// The room's (incoming, outgoing) bitrate caps.
+    pub fn bitrate_caps(&self, room_id: &ObjectId) -> (Option<u32>, Option<u32>) {
+        let Some(room) = self.rooms.get(room_id) else {
+            return (None, None);
+        };
+        let cap = |v: &AtomicU32| Some(v.load(Ordering::Relaxed)).filter(|&bps| bps > 0);
+        (
+            cap(&room.max_incoming_bitrate),
+            cap(&room.max_outgoing_bitrate),
+        )
+    }
+
+    /// Turn push-to-talk on or off for a live room and pause or resume
+    /// everyone's audio to match. Returns false if the room isn't here.
+    pub async fn set_push_to_talk(&self, room_id: &ObjectId, enabled: bool) -> bool {
+        let conn_ids: Vec<String> = {
+            let Some(room) = self.rooms.get(room_id) else {
+                return false;
+            };
+            room.push_to_talk.store(enabled, Ordering::Relaxed);
+            room.participants.iter().map(|e| e.key().clone()).collect()
+        };
+        for cid in &conn_ids {
+            self.apply_audio(room_id, cid).await;
+        }
+        true
+    }
+
+    pub fn is_push_to_talk(&self, room_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|room| room.push_to_talk.load(Ordering::Relaxed))
+    }
+
+    /// Mute or unmute a user on all their connections, and on ones they open
+    /// later. Returns each connection's new state, or `None` if the room
+    /// isn't here.
+    pub async fn set_user_muted(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+        muted: bool,
+    ) -> Option<Vec<(String, AudioState)>> {
+        let conn_ids: Vec<String> = {
+            let room = self.rooms.get(room_id)?;
+            if muted {
+                room.muted_users.insert(*user_id);
+            } else {
+                room.muted_users.remove(user_id);
+            }
+            room.participants
+                .iter_mut()
+                .filter(|e| e.user_id == *user_id)
+                .map(|mut e| {
+                    e.audio.force_muted = muted;
+                    e.key().clone()
+                })
+                .collect()
+        };
+        let mut states = Vec::new();
+        for cid in conn_ids {
+            if let Some(audio) = self.apply_audio(room_id, &cid).await {
+                states.push((cid, audio));
+            }
+        }
+        Some(states)
+    }
+
+    /// Record whether the connection holds push-to-talk. Returns its new
+    /// state, or `None` if it isn't in the room.
+    pub async fn set_ptt_active(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        active: bool,
+    ) -> Option<AudioState> {
+        {
+            let room = self.rooms.get(room_id)?;
+            room.participants.get_mut(connection_id)?.audio.ptt_active = active;
+        }
+        self.apply_audio(room_id, connection_id).await
+    }
+
+    /// Audio state of every connection in the room, as (user_id,
+    /// connection_id, state).
+    pub fn audio_states(&self, room_id: &ObjectId) -> Vec<(ObjectId, String, AudioState)> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.participants
+                    .iter()
+                    .map(|e| (e.value().user_id, e.key().clone(), e.value().audio))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Pause or resume the connection's audio producers to match its state
+    /// and the room's mode. Returns the state applied.
+    async fn apply_audio(&self, room_id: &ObjectId, connection_id: &str) -> Option<AudioState> {
+        let (audio, silenced, producers) = {
+            let room = self.rooms.get(room_id)?;
+            let participant = room.participants.get(connection_id)?;
+            let producers: Vec<Arc<dyn MediaProducer>> = participant
+                .producers
+                .iter()
+                .filter(|pe| pe.producer.kind() == MediaKind::Audio)
+                .map(|pe| pe.producer.clone())
+                .collect();
+            let audio = participant.audio;
+            (
+                audio,
+                audio.silenced(room.push_to_talk.load(Ordering::Relaxed)),
+                producers,
+            )
+        };
+        for producer in producers {
+            let result = if silenced {
+                producer.pause().await
+            } else {
+                producer.resume().await
+            };
+            if let Err(e) = result {
+                warn!(?room_id, %connection_id, producer_id = %producer.id(), "Failed to apply audio state: {}", e);
+            }
+        }
+        Some(audio)
+    }
+
+    /// Turn webinar mode on or off for a live room. Producers already
+    /// running are left alone.
+    pub fn set_webinar(&self, room_id: &ObjectId, enabled: bool) {
+        if let Some(room) = self.rooms.get(room_id) {
+            room.webinar.store(enabled, Ordering::Relaxed);
+        }
+    }
+
+    pub fn is_webinar(&self, room_id: &ObjectId) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|room| room.webinar.load(Ordering::Relaxed))
+    }
+
+    /// Promote a user to speaker or demote them to attendee. Demoting closes
+    /// the producers of all their connections, returned as (connection_id,
+    /// producer_id). `None` if the room isn't here.
+    pub fn set_speaker(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+        speaker: bool,
+    ) -> Option<Vec<(String, ProducerId)>> {
+        let room = self.rooms.get(room_id)?;
+        if speaker {
+            room.speakers.insert(*user_id);
+            return Some(Vec::new());
+        }
+        room.speakers.remove(user_id);
+        let mut closed = Vec::new();
+        for mut entry in room.participants.iter_mut() {
+            if entry.user_id != *user_id {
+                continue;
+            }
+            let conn_id = entry.key().clone();
+            // Dropping the entries closes the producers.
+            for pe in entry.producers.drain(..) {
+                closed.push((conn_id.clone(), pe.producer.id()));
+            }
+        }
+        Some(closed)
+    }
+
+    /// The room's speakers, whether or not they are connected.
+    pub fn speakers(&self, room_id: &ObjectId) -> Vec<ObjectId> {
+        self.rooms
+            .get(room_id)
+            .map(|room| room.speakers.iter().map(|s| *s).collect())
+            .unwrap_or_default()
+    }
+
+    /// Distinct users connected to the room as (speakers, attendees).
+    pub fn webinar_counts(&self, room_id: &ObjectId) -> (usize, usize) {
+        let Some(room) = self.rooms.get(room_id) else {
+            return (0, 0);
+        };
+        let users: HashSet<ObjectId> = room.participants.iter().map(|e| e.user_id).collect();
+        let speakers = users.iter().filter(|u| room.speakers.contains(u)).count();
+        (speakers, users.len() - speakers)
+    }
+
+    /// Set the room's participant cap and overflow behaviour. Connections
+    /// already in keep their role.
+    pub fn set_capacity(
+        &self,
+        room_id: &ObjectId,
+        max_participants: Option<u32>,
+        overflow: OverflowMode,
+    ) {
+        if let Some(room) = self.rooms.get(room_id) {
+            *room.capacity.write().unwrap() = Capacity {
+                max_participants,
+                overflow,
+            };
+        }
+    }
+
+    /// How `user_id` would join the room right now: in full (`None`), in
+    /// an overflow mode, or not at all. Also `None` if the room isn't here.
+    pub fn admission(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+    ) -> Result<Option<OverflowMode>, RoomFull> {
+        match self.rooms.get(room_id) {
+            Some(room) => room.admit(user_id),
+            None => Ok(None),
+        }
+    }
+
+    /// The overflow mode a connection joined in, `None` for a full
+    /// participant.
+    pub fn overflow(&self, room_id: &ObjectId, connection_id: &str) -> Option<OverflowMode> {
+        let room = self.rooms.get(room_id)?;
+        room.participants.get(connection_id)?.overflow
+    }
+
+    /// Current key epoch, or `None` when the room is absent or not E2EE.
+    pub fn key_epoch(&self, room_id: &ObjectId) -> Option<u64> {
+        let room = self.rooms.get(room_id)?;
+        room.e2ee_enabled
+            .load(Ordering::Relaxed)
+            .then(|| room.key_epoch.load(Ordering::Relaxed))
+    }
+
+    /// Advance the key epoch and return the new value, or `None` when the
+    /// room is absent or not E2EE.
+    pub fn rotate_key_epoch(&self, room_id: &ObjectId) -> Option<u64> {
+        let room = self.rooms.get(room_id)?;
+        room.e2ee_enabled
+            .load(Ordering::Relaxed)
+            .then(|| room.key_epoch.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    /// Whether `connection_id` currently has media state in the room.
+    pub fn is_participant(&self, room_id: &ObjectId, connection_id: &str) -> bool {
+        self.rooms
+            .get(room_id)
+            .is_some_and(|room| room.participants.contains_key(connection_id))
+    }
+
     /// Returns a reference to the rooms DashMap (for WS handler to read router capabilities).
     pub fn rooms_ref(&self) -> &DashMap<ObjectId, MediaRoom> {
         &self.rooms
     }
 
     /// Creates send + recv WebRtcTransport pair for a participant.
+    #[instrument(
+        name = "media.create_transports",
+        skip_all,
+        fields(%room_id, %connection_id, worker_id = field::Empty)
+    )]
     pub async fn create_transports(
         &self,
         room_id: ObjectId,
@@ -180,12 +883,37 @@ impl RoomManager {
             .rooms
             .get(&room_id)
             .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
+        let overflow = room.admit(&user_id)?;
+
+        let caps = self.bitrate_caps(&room_id);
+        let send_transport = room.router.create_webrtc_transport(caps).await?;
+        let recv_transport = room.router.create_webrtc_transport(caps).await?;
+
+        // Feed the downlink policy with the recv transport's estimates.
+        let downlink_bps = Arc::new(AtomicU32::new(0));
+        let estimate = downlink_bps.clone();
+        recv_transport
+            .on_bandwidth_estimate(Box::new(move |bps| estimate.store(bps, Ordering::Relaxed)))
+            .await?;
+
+        if let Some(observer) = self.transport_observer.get() {
+            for (direction, transport) in [("send", &send_transport), ("recv", &recv_transport)] {
+                let change = TransportStateChange {
+                    room_id,
+                    user_id,
+                    connection_id: connection_id.clone(),
+                    direction,
+                    layer: TransportLayer::Ice,
+                    state: String::new(),
+                };
+                watch_transport_states(transport.as_ref(), observer, change);
+            }
+        }
 
-        let send_transport = self.create_webrtc_transport(&room.router).await?;
-        let recv_transport = self.create_webrtc_transport(&room.router).await?;
-
-        let send_opts = transport_to_options(&send_transport);
-        let recv_opts = transport_to_options(&recv_transport);
+        let send_opts = send_transport.options();
+        let recv_opts = recv_transport.options();
+        let resume_token = uuid::Uuid::new_v4().to_string();
 
         room.participants.insert(
             connection_id.clone(),
@@ -195,20 +923,35 @@ impl RoomManager {
                 recv_transport,
                 producers: Vec::new(),
                 consumers: Vec::new(),
+                effects: VideoEffects::default(),
+                downlink_bps,
+                resume_token: resume_token.clone(),
+                suspended: false,
+                audio: AudioState {
+                    force_muted: room.muted_users.contains(&user_id),
+                    ptt_active: false,
+                },
+                overflow,
             },
         );
 
         self.connection_rooms.insert(connection_id.clone(), room_id);
 
-        debug!(?room_id, ?user_id, %connection_id, "transports created");
+        debug!(?room_id, ?user_id, %connection_id, ?overflow, "transports created");
 
         Ok(TransportPair {
             send_transport: send_opts,
             recv_transport: recv_opts,
+            resume_token,
         })
     }
 
     /// Connects a transport with remote DTLS parameters.
+    #[instrument(
+        name = "media.connect_transport",
+        skip_all,
+        fields(%room_id, %connection_id, %transport_id, worker_id = field::Empty)
+    )]
     pub async fn connect_transport(
         &self,
         room_id: &ObjectId,
@@ -220,6 +963,7 @@ impl RoomManager {
             .rooms
             .get(room_id)
             .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
 
         let participant = room
             .participants
@@ -227,20 +971,19 @@ impl RoomManager {
             .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
 
         let tid = TransportId::from_str(transport_id)
-            .map_err(|e| anyhow::anyhow!("Invalid transport_id: {}", e))?;
-
-        let remote_params = WebRtcTransportRemoteParameters { dtls_parameters };
+            .map_err(|e| anyhow::anyhow!("Invalid transport_id: {}", e))?
+            .to_string();
 
         if participant.send_transport.id() == tid {
             participant
                 .send_transport
-                .connect(remote_params)
+                .connect(dtls_parameters)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to connect send transport: {}", e))?;
         } else if participant.recv_transport.id() == tid {
             participant
                 .recv_transport
-                .connect(remote_params)
+                .connect(dtls_parameters)
                 .await
                 .map_err(|e| anyhow::anyhow!("Failed to connect recv transport: {}", e))?;
         } else {
@@ -251,7 +994,51 @@ impl RoomManager {
         Ok(())
     }
 
+    /// Restarts ICE on one of the participant's transports, e.g. after the
+    /// client's network changed, and returns the new ICE parameters. DTLS,
+    /// producers and consumers are kept.
+    pub async fn restart_ice(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        transport_id: &str,
+    ) -> anyhow::Result<serde_json::Value> {
+        let room = self
+            .rooms
+            .get(room_id)
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+
+        let participant = room
+            .participants
+            .get(connection_id)
+            .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
+
+        let tid = TransportId::from_str(transport_id)
+            .map_err(|e| anyhow::anyhow!("Invalid transport_id: {}", e))?
+            .to_string();
+
+        let transport = if participant.send_transport.id() == tid {
+            &participant.send_transport
+        } else if participant.recv_transport.id() == tid {
+            &participant.recv_transport
+        } else {
+            return Err(anyhow::anyhow!("Transport not found for this participant"));
+        };
+        let ice_parameters = transport
+            .restart_ice()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to restart ICE: {}", e))?;
+
+        debug!(?room_id, %connection_id, transport_id, "ICE restarted");
+        Ok(ice_parameters)
+    }
+
     /// Creates a Producer on the participant's send transport.
+    #[instrument(
+        name = "media.produce",
+        skip_all,
+        fields(%room_id, %connection_id, ?kind, worker_id = field::Empty)
+    )]
     pub async fn produce(
         &self,
         room_id: &ObjectId,
@@ -264,23 +1051,53 @@ impl RoomManager {
             .rooms
             .get(room_id)
             .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
 
         let mut participant = room
             .participants
             .get_mut(connection_id)
             .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
+        if room.webinar.load(Ordering::Relaxed) && !room.speakers.contains(&participant.user_id) {
+            return Err(anyhow::anyhow!("Only speakers can send media in a webinar"));
+        }
+        match participant.overflow {
+            Some(OverflowMode::ListenOnly) => {
+                return Err(anyhow::anyhow!("Listen-only participants can't send media"));
+            }
+            Some(OverflowMode::AudioOnly) if kind == MediaKind::Video => {
+                return Err(anyhow::anyhow!("Audio-only participants can't send video"));
+            }
+            _ => {}
+        }
 
-        let producer_options = ProducerOptions::new(kind, rtp_parameters);
         let producer = participant
             .send_transport
-            .produce(producer_options)
+            .produce(kind, rtp_parameters)
             .await
             .map_err(|e| anyhow::anyhow!("Failed to produce: {}", e))?;
+        // A muted participant's recreated producer starts out paused.
+        if kind == MediaKind::Audio
+            && participant
+                .audio
+                .silenced(room.push_to_talk.load(Ordering::Relaxed))
+        {
+            producer
+                .pause()
+                .await
+                .map_err(|e| anyhow::anyhow!("Failed to pause producer: {}", e))?;
+        }
 
         let producer_id = producer.id();
+        if kind == MediaKind::Audio
+            && let Some(levels) = &room.audio_levels
+            && let Err(e) = levels.add(producer_id).await
+        {
+            warn!(?room_id, %producer_id, %e, "Failed to observe audio levels");
+        }
         participant.producers.push(ProducerEntry {
             producer,
             source: source.clone(),
+            _meter: (!room.loopback).then(|| self.meter.start(room.billed_to, MeterKind::Streamed)),
         });
 
         debug!(?room_id, %connection_id, %producer_id, ?kind, %source, "producer created");
@@ -288,6 +1105,11 @@ impl RoomManager {
     }
 
     /// Creates a Consumer on the participant's recv transport for a given producer.
+    #[instrument(
+        name = "media.consume",
+        skip_all,
+        fields(%room_id, %connection_id, %producer_id, worker_id = field::Empty)
+    )]
     pub async fn consume(
         &self,
         room_id: &ObjectId,
@@ -299,21 +1121,30 @@ impl RoomManager {
             .rooms
             .get(room_id)
             .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
 
         // Check if the router can consume this producer
         if !room.router.can_consume(&producer_id, rtp_capabilities) {
             return Err(anyhow::anyhow!("Cannot consume: incompatible capabilities"));
         }
+        let audio_only = room
+            .participants
+            .get(connection_id)
+            .is_some_and(|p| p.overflow == Some(OverflowMode::AudioOnly));
+        if audio_only && room.producer_kind(&producer_id) == Some(MediaKind::Video) {
+            return Err(anyhow::anyhow!(
+                "Audio-only participants can't receive video"
+            ));
+        }
 
         let mut participant = room
             .participants
             .get_mut(connection_id)
             .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
 
-        let consumer_options = ConsumerOptions::new(producer_id, rtp_capabilities.clone());
         let consumer = participant
             .recv_transport
-            .consume(consumer_options)
+            .consume(producer_id, rtp_capabilities.clone())
             .await
             .map_err(|e| anyhow::anyhow!("Failed to consume: {}", e))?;
 
@@ -324,7 +1155,7 @@ impl RoomManager {
             .map_err(|e| anyhow::anyhow!("Failed to resume consumer: {}", e))?;
 
         let info = ConsumerInfo {
-            id: consumer.id().to_string(),
+            id: consumer.id(),
             producer_id: consumer.producer_id().to_string(),
             kind: match consumer.kind() {
                 MediaKind::Audio => "audio".to_string(),
@@ -345,6 +1176,48 @@ impl RoomManager {
         Ok(info)
     }
 
+    /// Current network figures of a connection's device test.
+    pub async fn loopback_stats(
+        &self,
+        test_id: &ObjectId,
+        connection_id: &str,
+    ) -> anyhow::Result<LoopbackStats> {
+        let room = self
+            .rooms
+            .get(test_id)
+            .filter(|room| room.loopback)
+            .ok_or_else(|| anyhow::anyhow!("Device test not found"))?;
+        let participant = room
+            .participants
+            .get(connection_id)
+            .ok_or_else(|| anyhow::anyhow!("Participant not found"))?;
+
+        let send = participant
+            .send_transport
+            .stats()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get send transport stats: {}", e))?;
+        let recv = participant
+            .recv_transport
+            .stats()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to get recv transport stats: {}", e))?;
+
+        let mut stats = LoopbackStats {
+            downlink_estimate_bps: participant.downlink_bps.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        if let Some(s) = send {
+            stats.uplink_bps = s.recv_bitrate;
+            stats.uplink_packet_loss = s.packet_loss_received;
+        }
+        if let Some(r) = recv {
+            stats.downlink_bps = r.send_bitrate;
+            stats.downlink_packet_loss = r.packet_loss_sent;
+        }
+        Ok(stats)
+    }
+
     /// Closes a specific producer by ID.
     pub fn close_producer(
         &self,
@@ -359,7 +1232,10 @@ impl RoomManager {
             participant
                 .producers
                 .retain(|pe| &pe.producer.id() != producer_id);
-            return participant.producers.len() < before;
+            let closed = participant.producers.len() < before;
+            drop(participant);
+            room.close_pipes(&[*producer_id]);
+            return closed;
         }
         false
     }
@@ -368,12 +1244,92 @@ impl RoomManager {
     pub fn close_participant(&self, room_id: &ObjectId, connection_id: &str) {
         if let Some(room) = self.rooms.get(room_id) {
             // Dropping the ParticipantMedia closes transports/producers/consumers
-            room.participants.remove(connection_id);
+            if let Some((_, participant)) = room.participants.remove(connection_id) {
+                room.close_pipes(&participant.producer_ids());
+            }
         }
         self.connection_rooms.remove(connection_id);
         debug!(?room_id, %connection_id, "participant media closed");
     }
 
+    /// Keeps a dropped connection's media alive for a takeover by
+    /// [`RoomManager::reclaim_participant`]. Returns false if the connection
+    /// has no media in the room.
+    pub fn suspend_participant(&self, room_id: &ObjectId, connection_id: &str) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
+        };
+        match room.participants.get_mut(connection_id) {
+            Some(mut participant) => {
+                participant.suspended = true;
+                debug!(?room_id, %connection_id, "participant media suspended");
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves the suspended media holding `resume_token` to `connection_id`
+    /// if it belongs to `user_id`. Returns (room_id, previous
+    /// connection_id, new resume token).
+    pub fn reclaim_participant(
+        &self,
+        resume_token: &str,
+        user_id: &ObjectId,
+        connection_id: &str,
+    ) -> Option<(ObjectId, String, String)> {
+        for room in self.rooms.iter() {
+            let Some(previous) = room
+                .participants
+                .iter()
+                .find(|e| {
+                    e.value().suspended
+                        && &e.value().user_id == user_id
+                        && e.value().resume_token == resume_token
+                })
+                .map(|e| e.key().clone())
+            else {
+                continue;
+            };
+            let (_, mut participant) = room.participants.remove(&previous)?;
+            let new_token = uuid::Uuid::new_v4().to_string();
+            participant.suspended = false;
+            participant.resume_token = new_token.clone();
+            room.participants
+                .insert(connection_id.to_string(), participant);
+
+            let room_id = *room.key();
+            self.connection_rooms.remove(&previous);
+            self.connection_rooms
+                .insert(connection_id.to_string(), room_id);
+            debug!(?room_id, %previous, %connection_id, "participant media reclaimed");
+            return Some((room_id, previous, new_token));
+        }
+        None
+    }
+
+    /// Closes a connection's media if it is still suspended. Returns false
+    /// if it was reclaimed or is already gone.
+    pub fn expire_suspended(&self, room_id: &ObjectId, connection_id: &str) -> bool {
+        let removed = self.rooms.get(room_id).is_some_and(|room| {
+            match room
+                .participants
+                .remove_if(connection_id, |_, p| p.suspended)
+            {
+                Some((_, participant)) => {
+                    room.close_pipes(&participant.producer_ids());
+                    true
+                }
+                None => false,
+            }
+        });
+        if removed {
+            self.connection_rooms.remove(connection_id);
+            debug!(?room_id, %connection_id, "suspended participant media expired");
+        }
+        removed
+    }
+
     /// Removes ALL participant entries for a given user_id from a room.
     /// Used by HTTP leave endpoint which doesn't have a connection_id.
     pub fn close_participant_by_user(&self, room_id: &ObjectId, user_id: &ObjectId) {
@@ -385,7 +1341,9 @@ impl RoomManager {
                 .map(|e| e.key().clone())
                 .collect();
             for cid in conn_ids {
-                room.participants.remove(&cid);
+                if let Some((_, participant)) = room.participants.remove(&cid) {
+                    room.close_pipes(&participant.producer_ids());
+                }
                 self.connection_rooms.remove(&cid);
             }
         }
@@ -415,10 +1373,67 @@ impl RoomManager {
                     }
                 }
             }
+            for piped in room.piped_in.iter() {
+                result.push((
+                    piped.remote.user_id,
+                    piped.remote.connection_id.clone(),
+                    piped.producer.id(),
+                    piped.producer.kind(),
+                    piped.remote.source.clone(),
+                ));
+            }
         }
         result
     }
 
+    /// Records a participant's camera effects. Returns false if the
+    /// connection isn't in the room.
+    pub fn set_effects(
+        &self,
+        room_id: &ObjectId,
+        connection_id: &str,
+        effects: VideoEffects,
+    ) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
+        };
+        match room.participants.get_mut(connection_id) {
+            Some(mut participant) => {
+                participant.effects = effects;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the effects of participants other than the given connection
+    /// that have any effect on, as (user_id, connection_id, effects).
+    pub fn get_effects(
+        &self,
+        room_id: &ObjectId,
+        exclude_connection_id: &str,
+    ) -> Vec<(ObjectId, String, VideoEffects)> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.participants
+                    .iter()
+                    .filter(|e| {
+                        e.key() != exclude_connection_id
+                            && e.value().effects != VideoEffects::default()
+                    })
+                    .map(|e| {
+                        (
+                            e.value().user_id,
+                            e.key().clone(),
+                            e.value().effects.clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Returns unique participant user IDs in a room.
     pub fn get_participant_user_ids(&self, room_id: &ObjectId) -> Vec<ObjectId> {
         self.rooms
@@ -478,15 +1493,34 @@ impl RoomManager {
             .unwrap_or_default()
     }
 
+    /// The user's connections with live (not suspended) media in the room.
+    pub fn get_user_connection_ids(&self, room_id: &ObjectId, user_id: &ObjectId) -> Vec<String> {
+        self.rooms
+            .get(room_id)
+            .map(|room| {
+                room.participants
+                    .iter()
+                    .filter(|e| e.user_id == *user_id && !e.suspended)
+                    .map(|e| e.key().clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
     /// Returns the room ID that a connection is currently in, if any.
     pub fn get_connection_room(&self, connection_id: &str) -> Option<ObjectId> {
         self.connection_rooms.get(connection_id).map(|v| *v)
     }
 
-    /// Creates a DirectTransport consumer that taps into a producer's RTP stream.
+    /// Creates a server-side consumer that taps into a producer's RTP stream.
     ///
-    /// Returns an mpsc receiver that yields raw RTP packets. The DirectTransport
-    /// and Consumer are stored internally and cleaned up when the tap is removed.
+    /// Returns an mpsc receiver that yields raw RTP packets. The consumer is
+    /// stored internally and cleaned up when the tap is removed.
+    #[instrument(
+        name = "media.rtp_tap",
+        skip_all,
+        fields(%room_id, %producer_id, worker_id = field::Empty)
+    )]
     pub async fn create_rtp_tap(
         &self,
         room_id: &ObjectId,
@@ -496,48 +1530,26 @@ impl RoomManager {
             .rooms
             .get(room_id)
             .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
+        if let Some(user_id) = room.producer_user(&producer_id) {
+            if room.unrecorded_users.contains(&user_id) {
+                anyhow::bail!("Producer belongs to a user who declined recording");
+            }
+            if room.untranscribed_users.contains(&user_id)
+                && room.producer_kind(&producer_id) == Some(MediaKind::Audio)
+            {
+                anyhow::bail!("Producer belongs to a user who opted out of transcription");
+            }
+        }
 
-        let direct_transport = room
-            .router
-            .create_direct_transport(DirectTransportOptions::default())
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create DirectTransport: {}", e))?;
-
-        // Convert RtpCapabilitiesFinalized → RtpCapabilities via serde (same JSON schema)
-        let caps_finalized = room.router.rtp_capabilities();
-        let rtp_capabilities: RtpCapabilities = serde_json::from_value(
-            serde_json::to_value(caps_finalized)
-                .map_err(|e| anyhow::anyhow!("Failed to serialize capabilities: {}", e))?,
-        )
-        .map_err(|e| anyhow::anyhow!("Failed to deserialize capabilities: {}", e))?;
-
-        let consumer_options = ConsumerOptions::new(producer_id, rtp_capabilities);
-        let consumer = direct_transport
-            .consume(consumer_options)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to consume on DirectTransport: {}", e))?;
-
-        // mediasoup consumers are created paused — must resume to receive RTP
-        consumer
-            .resume()
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to resume DirectTransport consumer: {}", e))?;
-
-        let (tx, rx) = mpsc::channel(512);
-
-        // Register RTP callback; detach so it lives as long as the Consumer
-        consumer
-            .on_rtp(move |data: &[u8]| {
-                let _ = tx.try_send(data.to_vec());
-            })
-            .detach();
+        let (handle, rx) = room.router.tap_rtp(producer_id).await?;
 
         // Store to keep alive
         room.rtp_taps.insert(
             producer_id.to_string(),
             RtpTap {
-                _direct_transport: direct_transport,
-                _consumer: consumer,
+                _handle: handle,
+                _meter: self.meter.start(room.billed_to, MeterKind::Transcription),
             },
         );
 
@@ -545,111 +1557,373 @@ impl RoomManager {
         Ok(rx)
     }
 
-    /// Removes an RTP tap for a producer (stops the DirectTransport consumer).
-    pub fn remove_rtp_tap(&self, room_id: &ObjectId, producer_id: &str) {
-        if let Some(room) = self.rooms.get(room_id)
-            && room.rtp_taps.remove(producer_id).is_some()
-        {
-            debug!(?room_id, %producer_id, "RTP tap removed");
+    /// Pipes a producer to another pod's pipe transport at `remote`. The
+    /// other pod connects back to the returned address and produces the
+    /// media on its own Router with the returned parameters, as
+    /// [`pipe_in`](Self::pipe_in) does. The pipe lives until the producer
+    /// closes or the room is removed.
+    pub async fn pipe_producer(
+        &self,
+        room_id: &ObjectId,
+        producer_id: ProducerId,
+        remote: SocketAddr,
+    ) -> anyhow::Result<PipedProducer> {
+        let room = self
+            .rooms
+            .get(room_id)
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+
+        let (handle, consumer_id, piped) = room.router.pipe_to(producer_id, remote).await?;
+        room.pipes.insert(
+            consumer_id,
+            PipeOut {
+                producer_id,
+                _handle: handle,
+            },
+        );
+
+        debug!(?room_id, %producer_id, %remote, "Producer piped to another pod");
+        Ok(piped)
+    }
+
+    /// Pipes a producer of the pod hosting an edge room's call into the
+    /// edge room, for its listeners to consume like any other. `setup`
+    /// gets this end's (ip, port), asks the other pod to pipe the producer
+    /// there with [`pipe_producer`](Self::pipe_producer) and returns that
+    /// pod's end. The producer stays until
+    /// [`remove_piped_in`](Self::remove_piped_in) or the room is removed.
+    pub async fn pipe_in<F, Fut>(
+        &self,
+        room_id: &ObjectId,
+        remote: RemoteProducer,
+        setup: F,
+    ) -> anyhow::Result<()>
+    where
+        F: FnOnce(String, u16) -> Fut,
+        Fut: Future<Output = anyhow::Result<PipedProducer>>,
+    {
+        // The room isn't held while the other pod is asked
+        let router = match self.rooms.get(room_id) {
+            Some(room) if room.edge => room.router.clone(),
+            Some(_) => anyhow::bail!("Not an edge room"),
+            None => anyhow::bail!("Room not found"),
+        };
+        let pipe = router.open_pipe().await?;
+        let (ip, port) = pipe.local();
+        let far = setup(ip, port).await?;
+        let far_addr = SocketAddr::new(far.ip.parse()?, far.port);
+        let producer = pipe
+            .produce(far_addr, remote.producer_id, far.kind, far.rtp_parameters)
+            .await?;
+
+        let room = self
+            .rooms
+            .get(room_id)
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        debug!(?room_id, producer_id = %remote.producer_id, %far_addr, "Producer piped in from another pod");
+        room.piped_in
+            .insert(remote.producer_id.to_string(), PipedIn { producer, remote });
+        Ok(())
+    }
+
+    /// The ids of the producers piped into an edge room.
+    pub fn piped_in_ids(&self, room_id: &ObjectId) -> Vec<String> {
+        self.rooms
+            .get(room_id)
+            .map(|room| room.piped_in.iter().map(|p| p.key().clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Closes a producer from [`pipe_in`](Self::pipe_in), and with it its
+    /// consumers. Returns whose it was.
+    pub fn remove_piped_in(&self, room_id: &ObjectId, producer_id: &str) -> Option<RemoteProducer> {
+        let room = self.rooms.get(room_id)?;
+        room.piped_in
+            .remove(producer_id)
+            .map(|(_, piped)| piped.remote)
+    }
+
+    /// Adds a producer the server feeds with the RTP packets sent into the
+    /// returned channel, e.g. a file played to the call. Participants
+    /// consume it like any other; it lives until
+    /// [`remove_injected`](Self::remove_injected) or the room is removed.
+    pub async fn inject_producer(
+        &self,
+        room_id: &ObjectId,
+        kind: MediaKind,
+        rtp_parameters: RtpParameters,
+    ) -> anyhow::Result<(ProducerId, mpsc::Sender<Vec<u8>>)> {
+        let room = self
+            .rooms
+            .get(room_id)
+            .ok_or_else(|| anyhow::anyhow!("Room not found"))?;
+        room.record_worker();
+
+        let (producer, tx) = room.router.inject_rtp(kind, rtp_parameters).await?;
+        let producer_id = producer.id();
+        room.injected.insert(
+            producer_id.to_string(),
+            Injected {
+                _producer: producer,
+                _meter: self.meter.start(room.billed_to, MeterKind::Streamed),
+            },
+        );
+
+        debug!(?room_id, %producer_id, ?kind, "Injected producer created");
+        Ok((producer_id, tx))
+    }
+
+    /// Closes a producer from [`inject_producer`](Self::inject_producer).
+    pub fn remove_injected(&self, room_id: &ObjectId, producer_id: &str) -> bool {
+        let removed = self.rooms.get(room_id).is_some_and(|room| {
+            let removed = room.injected.remove(producer_id).is_some();
+            if let Ok(id) = producer_id.parse() {
+                room.close_pipes(&[id]);
+            }
+            removed
+        });
+        if removed {
+            debug!(?room_id, %producer_id, "Injected producer removed");
         }
+        removed
     }
 
-    /// Helper: creates a single WebRtcTransport on the given router.
-    async fn create_webrtc_transport(&self, router: &Router) -> anyhow::Result<WebRtcTransport> {
-        let udp_info = ListenInfo {
-            protocol: Protocol::Udp,
-            ip: self.listen_ip,
-            announced_address: self.announced_ip.clone(),
-            port: None,
-            port_range: None,
-            flags: None,
-            send_buffer_size: None,
-            recv_buffer_size: None,
-            expose_internal_ip: false,
+    /// Keep a user out of the call's recording: drop the taps on their
+    /// producers and refuse new ones. Returns false if the room isn't here.
+    pub fn set_user_unrecorded(&self, room_id: &ObjectId, user_id: &ObjectId) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
         };
+        room.unrecorded_users.insert(*user_id);
+        let producer_ids: Vec<String> = room
+            .participants
+            .iter()
+            .filter(|p| p.user_id == *user_id)
+            .flat_map(|p| {
+                p.producers
+                    .iter()
+                    .map(|pe| pe.producer.id().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for producer_id in producer_ids {
+            if room.rtp_taps.remove(&producer_id).is_some() {
+                debug!(?room_id, %producer_id, "RTP tap of unrecorded user removed");
+            }
+        }
+        true
+    }
 
-        // TCP fallback — essential when wsl-vpnkit or similar networking
-        // intercepts UDP but TCP localhost forwarding still works.
-        let tcp_info = ListenInfo {
-            protocol: Protocol::Tcp,
-            ip: self.listen_ip,
-            announced_address: self.announced_ip.clone(),
-            port: None,
-            port_range: None,
-            flags: None,
-            send_buffer_size: None,
-            recv_buffer_size: None,
-            expose_internal_ip: false,
+    /// Opt a user out of (or back into) transcription: while out, the taps
+    /// on their audio producers are dropped and new ones refused. Returns
+    /// false if the room isn't here.
+    pub fn set_user_transcribed(
+        &self,
+        room_id: &ObjectId,
+        user_id: &ObjectId,
+        transcribed: bool,
+    ) -> bool {
+        let Some(room) = self.rooms.get(room_id) else {
+            return false;
         };
+        if transcribed {
+            room.untranscribed_users.remove(user_id);
+            return true;
+        }
+        room.untranscribed_users.insert(*user_id);
+        let producer_ids: Vec<String> = room
+            .participants
+            .iter()
+            .filter(|p| p.user_id == *user_id)
+            .flat_map(|p| {
+                p.producers
+                    .iter()
+                    .filter(|pe| pe.producer.kind() == MediaKind::Audio)
+                    .map(|pe| pe.producer.id().to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        for producer_id in producer_ids {
+            if room.rtp_taps.remove(&producer_id).is_some() {
+                debug!(?room_id, %producer_id, "RTP tap of untranscribed user removed");
+            }
+        }
+        true
+    }
 
-        let listen_infos = WebRtcTransportListenInfos::new(udp_info).insert(tcp_info);
-        let mut transport_options = WebRtcTransportOptions::new(listen_infos);
-        transport_options.enable_udp = true;
-        transport_options.enable_tcp = true;
-        transport_options.prefer_udp = true;
+    /// Removes an RTP tap for a producer (stops its consumer).
+    pub fn remove_rtp_tap(&self, room_id: &ObjectId, producer_id: &str) {
+        if let Some(room) = self.rooms.get(room_id)
+            && room.rtp_taps.remove(producer_id).is_some()
+        {
+            debug!(?room_id, %producer_id, "RTP tap removed");
+        }
+    }
 
-        let transport = router
-            .create_webrtc_transport(transport_options)
-            .await
-            .map_err(|e| anyhow::anyhow!("Failed to create WebRtcTransport: {}", e))?;
+    /// Rates the connection of every active participant in each call with
+    /// at least two of them. Device tests are left out.
+    pub async fn connection_quality(&self) -> Vec<(ObjectId, Vec<ParticipantQuality>)> {
+        // Clone the handles under the map guards, query mediasoup after.
+        type Probe = (
+            ObjectId,
+            String,
+            Arc<dyn MediaTransport>,
+            Arc<dyn MediaTransport>,
+            Vec<Arc<dyn MediaProducer>>,
+        );
+        let mut probes: Vec<(ObjectId, Vec<Probe>)> = Vec::new();
+        for room in self.rooms.iter() {
+            if room.loopback || room.participants.len() < 2 {
+                continue;
+            }
+            let participants = room
+                .participants
+                .iter()
+                .filter(|p| !p.suspended)
+                .map(|p| {
+                    (
+                        p.user_id,
+                        p.key().clone(),
+                        p.send_transport.clone(),
+                        p.recv_transport.clone(),
+                        p.producers.iter().map(|pe| pe.producer.clone()).collect(),
+                    )
+                })
+                .collect();
+            probes.push((*room.key(), participants));
+        }
 
-        Ok(transport)
+        let mut reports = Vec::new();
+        for (room_id, participants) in probes {
+            let mut qualities = Vec::new();
+            for (user_id, connection_id, send, recv, producers) in participants {
+                let uplink_loss = match send.stats().await {
+                    Ok(stats) => stats.and_then(|s| s.packet_loss_received),
+                    Err(e) => {
+                        debug!(?room_id, %connection_id, %e, "send transport stats failed");
+                        continue;
+                    }
+                };
+                let downlink_loss = match recv.stats().await {
+                    Ok(stats) => stats.and_then(|s| s.packet_loss_sent),
+                    Err(e) => {
+                        debug!(?room_id, %connection_id, %e, "recv transport stats failed");
+                        continue;
+                    }
+                };
+                let mut rtt: Option<f64> = None;
+                for producer in &producers {
+                    if let Ok(times) = producer.round_trip_times().await {
+                        for r in times {
+                            rtt = Some(rtt.map_or(r, |prev| prev.max(r)));
+                        }
+                    }
+                }
+                let loss = uplink_loss.unwrap_or(0.0).max(downlink_loss.unwrap_or(0.0));
+                qualities.push(ParticipantQuality {
+                    user_id,
+                    connection_id,
+                    score: quality::score(rtt, loss),
+                    rtt,
+                    loss,
+                });
+            }
+            if !qualities.is_empty() {
+                reports.push((room_id, qualities));
+            }
+        }
+        reports
     }
-}
 
-/// Extracts transport connection details for the client.
-fn transport_to_options(transport: &WebRtcTransport) -> TransportOptions {
-    TransportOptions {
-        id: transport.id().to_string(),
-        ice_parameters: serde_json::to_value(transport.ice_parameters()).unwrap_or_default(),
-        ice_candidates: serde_json::to_value(transport.ice_candidates()).unwrap_or_default(),
-        dtls_parameters: serde_json::to_value(transport.dtls_parameters()).unwrap_or_default(),
+    /// Pauses or resumes video consumers according to each participant's
+    /// downlink estimate (see [`bandwidth::select`]) and returns what changed.
+    pub async fn adapt_downlinks(&self) -> Vec<ConsumerToggle> {
+        // Decide under the map guards, act on the consumers after.
+        let mut plan: Vec<(ObjectId, String, Arc<dyn MediaConsumer>, bool)> = Vec::new();
+        for room in self.rooms.iter() {
+            let sources: HashMap<ProducerId, String> = room
+                .participants
+                .iter()
+                .flat_map(|p| {
+                    p.producers
+                        .iter()
+                        .map(|pe| (pe.producer.id(), pe.source.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            for participant in room.participants.iter() {
+                let available = participant.downlink_bps.load(Ordering::Relaxed);
+                if available == 0 {
+                    continue;
+                }
+                let video: Vec<&Arc<dyn MediaConsumer>> = participant
+                    .consumers
+                    .iter()
+                    .filter(|c| c.kind() == MediaKind::Video && !c.closed())
+                    .collect();
+                let inputs: Vec<VideoConsumer> = video
+                    .iter()
+                    .map(|c| VideoConsumer {
+                        source: sources
+                            .get(&c.producer_id())
+                            .map(String::as_str)
+                            .unwrap_or("camera"),
+                        paused: c.paused(),
+                    })
+                    .collect();
+                let keep = bandwidth::select(available, &inputs);
+                for (consumer, keep) in video.into_iter().zip(keep) {
+                    if keep == consumer.paused() {
+                        plan.push((
+                            *room.key(),
+                            participant.key().clone(),
+                            consumer.clone(),
+                            keep,
+                        ));
+                    }
+                }
+            }
+        }
+
+        let mut toggles = Vec::new();
+        for (room_id, connection_id, consumer, resume) in plan {
+            let result = if resume {
+                consumer.resume().await
+            } else {
+                consumer.pause().await
+            };
+            match result {
+                Ok(()) => {
+                    debug!(?room_id, %connection_id, consumer_id = %consumer.id(), resume, "downlink policy toggled consumer");
+                    toggles.push(ConsumerToggle {
+                        room_id,
+                        connection_id,
+                        consumer_id: consumer.id(),
+                        paused: !resume,
+                    });
+                }
+                Err(e) => {
+                    warn!(?room_id, %connection_id, %e, "downlink policy failed to toggle consumer")
+                }
+            }
+        }
+        toggles
     }
 }
 
-/// Standard SFU media codecs: opus audio + VP8/H264 video.
-fn media_codecs() -> Vec<RtpCodecCapability> {
-    vec![
-        // Opus audio
-        RtpCodecCapability::Audio {
-            mime_type: MimeTypeAudio::Opus,
-            preferred_payload_type: Some(111),
-            clock_rate: NonZero::new(48000).unwrap(),
-            channels: NonZero::new(2).unwrap(),
-            parameters: RtpCodecParametersParameters::default(),
-            rtcp_feedback: vec![RtcpFeedback::TransportCc],
-        },
-        // VP8 video
-        RtpCodecCapability::Video {
-            mime_type: MimeTypeVideo::Vp8,
-            preferred_payload_type: Some(96),
-            clock_rate: NonZero::new(90000).unwrap(),
-            parameters: RtpCodecParametersParameters::default(),
-            rtcp_feedback: vec![
-                RtcpFeedback::Nack,
-                RtcpFeedback::NackPli,
-                RtcpFeedback::CcmFir,
-                RtcpFeedback::GoogRemb,
-                RtcpFeedback::TransportCc,
-            ],
-        },
-        // H264 video
-        RtpCodecCapability::Video {
-            mime_type: MimeTypeVideo::H264,
-            preferred_payload_type: Some(125),
-            clock_rate: NonZero::new(90000).unwrap(),
-            parameters: RtpCodecParametersParameters::from([
-                ("level-asymmetry-allowed", 1_u32.into()),
-                ("packetization-mode", 1_u32.into()),
-                ("profile-level-id", "42e01f".into()),
-            ]),
-            rtcp_feedback: vec![
-                RtcpFeedback::Nack,
-                RtcpFeedback::NackPli,
-                RtcpFeedback::CcmFir,
-                RtcpFeedback::GoogRemb,
-                RtcpFeedback::TransportCc,
-            ],
-        },
-    ]
+/// Report `transport`'s ICE and DTLS state changes to `observer`, filling
+/// in `change` with the layer and state.
+fn watch_transport_states(
+    transport: &dyn MediaTransport,
+    observer: &TransportObserver,
+    change: TransportStateChange,
+) {
+    let observer = observer.clone();
+    transport.on_state_change(Arc::new(move |layer, state| {
+        observer(TransportStateChange {
+            layer,
+            state,
+            ..change.clone()
+        })
+    }));
 }
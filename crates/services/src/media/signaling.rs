@@ -105,9 +105,14 @@ pub enum ServerSignal {
         user_id: String,
     },
 
-    /// Live transcript segment from ASR
+    /// Live transcript segment from ASR. Partial results (`is_final`
+    /// false) for a `segment_id` are superseded by the next one for it.
     #[serde(rename = "media:transcript")]
     Transcript {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        segment_id: Option<String>,
+        #[serde(default = "default_true")]
+        is_final: bool,
         user_id: String,
         speaker_name: String,
         text: String,
@@ -130,3 +135,7 @@ pub enum ServerSignal {
     #[serde(rename = "media:error")]
     Error { message: String },
 }
+
+fn default_true() -> bool {
+    true
+}
@@ -2,6 +2,7 @@ use mediasoup::worker::{Worker, WorkerSettings};
 use mediasoup::worker_manager::WorkerManager;
 use roomler_ai_config::MediasoupSettings;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use tracing::{error, info};
 
 /// Pool of mediasoup workers with round-robin selection.
@@ -51,4 +52,19 @@ impl WorkerPool {
     pub fn worker_count(&self) -> usize {
         self.workers.len()
     }
+
+    /// Workers whose process is still running.
+    pub fn live_count(&self) -> usize {
+        self.workers.iter().filter(|w| !w.closed()).count()
+    }
+
+    /// Ask every worker for a dump, in pool order: `true` for each that
+    /// answered within `timeout`. A running worker that doesn't answer is
+    /// stuck.
+    pub async fn ping(&self, timeout: Duration) -> Vec<bool> {
+        let pings = self.workers.iter().map(|w| async move {
+            !w.closed() && matches!(tokio::time::timeout(timeout, w.dump()).await, Ok(Ok(_)))
+        });
+        futures::future::join_all(pings).await
+    }
 }
@@ -0,0 +1,140 @@
+//! Notification preferences: which notifications a user gets in a room and
+//! whether they may be alerted (push, email) right now.
+//!
+//! The level for a room is the first one set of: `mute_all` or a muted room
+//! (nothing), the room's `notification_override`, the member's tenant-wide
+//! override, the tenant's default. Quiet hours only hold back push and email.
+
+use chrono::{DateTime, Duration, Timelike, Utc};
+use roomler_ai_db::models::{
+    NotificationLevel, NotificationPrefs, QuietHours, RoomMember, TenantMember,
+};
+
+/// What a notification is about, as far as levels go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotifyKind {
+    /// The user was mentioned.
+    Mention,
+    /// Room activity: thread replies, calls.
+    Activity,
+}
+
+/// Whether `level` lets a notification of `kind` through.
+pub fn allows(level: &NotificationLevel, kind: NotifyKind) -> bool {
+    match level {
+        NotificationLevel::All => true,
+        NotificationLevel::Mentions => kind == NotifyKind::Mention,
+        NotificationLevel::Nothing => false,
+    }
+}
+
+/// The user's effective level in a room.
+pub fn room_level(
+    prefs: &NotificationPrefs,
+    room_member: Option<&RoomMember>,
+    tenant_member: Option<&TenantMember>,
+    tenant_default: &NotificationLevel,
+) -> NotificationLevel {
+    if prefs.mute_all || room_member.is_some_and(|m| m.is_muted) {
+        return NotificationLevel::Nothing;
+    }
+    room_member
+        .and_then(|m| m.notification_override.clone())
+        .or_else(|| tenant_member.and_then(|m| m.notification_override.clone()))
+        .unwrap_or_else(|| tenant_default.clone())
+}
+
+/// Minutes past midnight of an `HH:MM` time.
+pub fn parse_time(hhmm: &str) -> Option<u32> {
+    let (h, m) = hhmm.split_once(':')?;
+    if h.len() != 2 || m.len() != 2 {
+        return None;
+    }
+    let (h, m): (u32, u32) = (h.parse().ok()?, m.parse().ok()?);
+    (h < 24 && m < 60).then_some(h * 60 + m)
+}
+
+/// Whether `now` falls inside the quiet hours. A window with unparsable
+/// times, or that starts and ends at the same minute, is never quiet.
+pub fn in_quiet_hours(quiet: &QuietHours, now: DateTime<Utc>) -> bool {
+    let (Some(start), Some(end)) = (parse_time(&quiet.start), parse_time(&quiet.end)) else {
+        return false;
+    };
+    let local = now + Duration::minutes(i64::from(quiet.utc_offset_minutes));
+    let minute = local.hour() * 60 + local.minute();
+    if start <= end {
+        (start..end).contains(&minute)
+    } else {
+        minute >= start || minute < end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, h, m, 0).unwrap()
+    }
+
+    fn quiet(start: &str, end: &str, offset: i32) -> QuietHours {
+        QuietHours {
+            start: start.into(),
+            end: end.into(),
+            utc_offset_minutes: offset,
+        }
+    }
+
+    #[test]
+    fn levels() {
+        assert!(allows(&NotificationLevel::All, NotifyKind::Activity));
+        assert!(allows(&NotificationLevel::Mentions, NotifyKind::Mention));
+        assert!(!allows(&NotificationLevel::Mentions, NotifyKind::Activity));
+        assert!(!allows(&NotificationLevel::Nothing, NotifyKind::Mention));
+    }
+
+    #[test]
+    fn mute_all_wins() {
+        let prefs = NotificationPrefs {
+            mute_all: true,
+            ..Default::default()
+        };
+        let level = room_level(&prefs, None, None, &NotificationLevel::All);
+        assert_eq!(level, NotificationLevel::Nothing);
+        let level = room_level(
+            &NotificationPrefs::default(),
+            None,
+            None,
+            &NotificationLevel::Mentions,
+        );
+        assert_eq!(level, NotificationLevel::Mentions);
+    }
+
+    #[test]
+    fn times() {
+        assert_eq!(parse_time("07:30"), Some(450));
+        assert_eq!(parse_time("23:59"), Some(1439));
+        assert_eq!(parse_time("24:00"), None);
+        assert_eq!(parse_time("7:30"), None);
+        assert_eq!(parse_time("07-30"), None);
+    }
+
+    #[test]
+    fn quiet_windows() {
+        let night = quiet("22:00", "07:00", 0);
+        assert!(in_quiet_hours(&night, at(23, 0)));
+        assert!(in_quiet_hours(&night, at(6, 59)));
+        assert!(!in_quiet_hours(&night, at(7, 0)));
+        assert!(!in_quiet_hours(&night, at(12, 0)));
+
+        let lunch = quiet("12:00", "13:00", 0);
+        assert!(in_quiet_hours(&lunch, at(12, 30)));
+        assert!(!in_quiet_hours(&lunch, at(13, 0)));
+
+        // 21:30 UTC is 23:30 at UTC+2.
+        assert!(!in_quiet_hours(&night, at(21, 30)));
+        assert!(in_quiet_hours(&quiet("22:00", "07:00", 120), at(21, 30)));
+        assert!(!in_quiet_hours(&quiet("09:00", "09:00", 0), at(9, 0)));
+    }
+}
@@ -1,6 +1,7 @@
 use roomler_ai_config::OAuthSettings;
 use serde::Deserialize;
 use thiserror::Error;
+use tokio::sync::OnceCell;
 
 #[derive(Debug, Error)]
 pub enum OAuthError {
@@ -14,6 +15,10 @@ pub enum OAuthError {
     UserInfoFailed(String),
     #[error("Invalid state parameter")]
     InvalidState,
+    #[error("OIDC discovery failed: {0}")]
+    DiscoveryFailed(String),
+    #[error("The provider has not verified the account's email")]
+    UnverifiedEmail,
 }
 
 #[derive(Debug, Clone)]
@@ -25,6 +30,13 @@ pub struct OAuthUserInfo {
     pub avatar_url: Option<String>,
 }
 
+/// A provider the login page can offer, in display order.
+#[derive(Debug, Clone)]
+pub struct OAuthProviderInfo {
+    pub name: String,
+    pub label: String,
+}
+
 #[derive(Debug, Deserialize)]
 struct TokenResponse {
     access_token: String,
@@ -93,9 +105,44 @@ struct MicrosoftUser {
     user_principal_name: Option<String>,
 }
 
+/// Standard OIDC claims returned by the userinfo endpoint of the `custom`
+/// provider.
+#[derive(Debug, Deserialize)]
+struct OidcUser {
+    sub: String,
+    email: Option<String>,
+    /// Issuers that let users set their own email report it unverified;
+    /// only verified emails may sign in, as they link existing accounts.
+    email_verified: Option<bool>,
+    name: Option<String>,
+    preferred_username: Option<String>,
+    given_name: Option<String>,
+    family_name: Option<String>,
+    picture: Option<String>,
+}
+
+/// The subset of `/.well-known/openid-configuration` the login flow needs.
+#[derive(Debug, Deserialize)]
+struct OidcDiscovery {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    userinfo_endpoint: String,
+}
+
+/// Built-in providers with their login-page labels, in display order.
+const BUILTIN_PROVIDERS: [(&str, &str); 5] = [
+    ("google", "Google"),
+    ("facebook", "Facebook"),
+    ("github", "GitHub"),
+    ("linkedin", "LinkedIn"),
+    ("microsoft", "Microsoft"),
+];
+
 pub struct OAuthService {
     settings: OAuthSettings,
     client: reqwest::Client,
+    /// Discovery document of the `custom` provider, fetched on first use.
+    discovery: OnceCell<OidcDiscovery>,
 }
 
 impl OAuthService {
@@ -103,9 +150,52 @@ impl OAuthService {
         Self {
             settings,
             client: reqwest::Client::new(),
+            discovery: OnceCell::new(),
         }
     }
 
+    /// Providers with credentials configured, built-ins first and the
+    /// generic OIDC provider last.
+    pub fn providers(&self) -> Vec<OAuthProviderInfo> {
+        let builtin = BUILTIN_PROVIDERS
+            .iter()
+            .filter(|(name, _)| self.provider_config(name).is_ok())
+            .map(|(name, label)| OAuthProviderInfo {
+                name: name.to_string(),
+                label: label.to_string(),
+            });
+        let custom = self
+            .provider_config("custom")
+            .is_ok()
+            .then(|| OAuthProviderInfo {
+                name: "custom".to_string(),
+                label: self.settings.custom.label.clone(),
+            });
+        builtin.chain(custom).collect()
+    }
+
+    /// Resolves the `custom` provider's endpoints from its issuer, caching
+    /// the document for the lifetime of the service.
+    async fn oidc_discovery(&self) -> Result<&OidcDiscovery, OAuthError> {
+        self.discovery
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.settings.custom.issuer_url.trim_end_matches('/')
+                );
+                self.client
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|r| r.error_for_status())
+                    .map_err(|e| OAuthError::DiscoveryFailed(e.to_string()))?
+                    .json::<OidcDiscovery>()
+                    .await
+                    .map_err(|e| OAuthError::DiscoveryFailed(e.to_string()))
+            })
+            .await
+    }
+
     fn provider_config(&self, provider: &str) -> Result<(&str, &str), OAuthError> {
         let cfg = match provider {
             "google" => (
@@ -128,8 +218,15 @@ impl OAuthService {
                 &self.settings.microsoft.client_id,
                 &self.settings.microsoft.client_secret,
             ),
+            "custom" => (
+                &self.settings.custom.client_id,
+                &self.settings.custom.client_secret,
+            ),
             _ => return Err(OAuthError::UnknownProvider(provider.to_string())),
         };
+        if provider == "custom" && self.settings.custom.issuer_url.is_empty() {
+            return Err(OAuthError::ProviderNotConfigured(provider.to_string()));
+        }
         if cfg.0.is_empty() {
             return Err(OAuthError::ProviderNotConfigured(provider.to_string()));
         }
@@ -140,7 +237,7 @@ impl OAuthService {
         format!("{}/api/oauth/callback/{}", self.settings.base_url, provider)
     }
 
-    pub fn build_auth_url(&self, provider: &str, state: &str) -> Result<String, OAuthError> {
+    pub async fn build_auth_url(&self, provider: &str, state: &str) -> Result<String, OAuthError> {
         let (client_id, _) = self.provider_config(provider)?;
         let redirect_uri = self.callback_url(provider);
 
@@ -175,6 +272,23 @@ impl OAuthService {
                 urlencoding::encode(&redirect_uri),
                 urlencoding::encode(state)
             ),
+            "custom" => {
+                let discovery = self.oidc_discovery().await?;
+                let separator = if discovery.authorization_endpoint.contains('?') {
+                    '&'
+                } else {
+                    '?'
+                };
+                format!(
+                    "{}{}client_id={}&redirect_uri={}&response_type=code&scope={}&state={}",
+                    discovery.authorization_endpoint,
+                    separator,
+                    urlencoding::encode(client_id),
+                    urlencoding::encode(&redirect_uri),
+                    urlencoding::encode(&self.settings.custom.scopes),
+                    urlencoding::encode(state)
+                )
+            }
             _ => return Err(OAuthError::UnknownProvider(provider.to_string())),
         };
 
@@ -285,6 +399,27 @@ impl OAuthService {
                     .map_err(|e| OAuthError::TokenExchangeFailed(e.to_string()))?;
                 body.access_token
             }
+            "custom" => {
+                let discovery = self.oidc_discovery().await?;
+                let resp = self
+                    .client
+                    .post(&discovery.token_endpoint)
+                    .form(&[
+                        ("code", code),
+                        ("client_id", client_id),
+                        ("client_secret", client_secret),
+                        ("redirect_uri", &redirect_uri),
+                        ("grant_type", "authorization_code"),
+                    ])
+                    .send()
+                    .await
+                    .map_err(|e| OAuthError::TokenExchangeFailed(e.to_string()))?;
+                let body: TokenResponse = resp
+                    .json()
+                    .await
+                    .map_err(|e| OAuthError::TokenExchangeFailed(e.to_string()))?;
+                body.access_token
+            }
             _ => return Err(OAuthError::UnknownProvider(provider.to_string())),
         };
 
@@ -439,6 +574,39 @@ impl OAuthService {
                     avatar_url: None,
                 })
             }
+            "custom" => {
+                let discovery = self.oidc_discovery().await?;
+                let user: OidcUser = self
+                    .client
+                    .get(&discovery.userinfo_endpoint)
+                    .bearer_auth(access_token)
+                    .send()
+                    .await
+                    .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?
+                    .json()
+                    .await
+                    .map_err(|e| OAuthError::UserInfoFailed(e.to_string()))?;
+                let email = match (user.email, user.email_verified) {
+                    (Some(email), Some(true)) if !email.is_empty() => email,
+                    _ => return Err(OAuthError::UnverifiedEmail),
+                };
+                let name = user.name.or(user.preferred_username).unwrap_or_else(|| {
+                    format!(
+                        "{} {}",
+                        user.given_name.unwrap_or_default(),
+                        user.family_name.unwrap_or_default()
+                    )
+                    .trim()
+                    .to_string()
+                });
+                Ok(OAuthUserInfo {
+                    provider: "custom".to_string(),
+                    provider_id: user.sub,
+                    email,
+                    name,
+                    avatar_url: user.picture,
+                })
+            }
             _ => Err(OAuthError::UnknownProvider(provider.to_string())),
         }
     }
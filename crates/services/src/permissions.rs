@@ -0,0 +1,404 @@
+//! Effective permission resolution for a member in a tenant or room.
+//!
+//! Tenant permissions are the union of the member's role bits. In a room they
+//! are then narrowed or widened by `Room.permission_overwrites`, applied in
+//! order of specificity — `everyone`, then the member's roles (all role
+//! overwrites combined), then the member — each clearing its `deny` bits and
+//! setting its `allow` bits. The room's organizer and co-organizers (the
+//! creator, when no organizer is set) always get [`permissions::ORGANIZER`]
+//! on top, so an overwrite can't lock them out of their own room.
+//! `ADMINISTRATOR` short-circuits everything.
+//!
+//! Role and membership changes go through
+//! [`PermissionService::ensure_admin_remains`] first so a tenant can't be left
+//! without anyone holding `ADMINISTRATOR`.
+
+use std::sync::Arc;
+
+use bson::{doc, oid::ObjectId};
+use roomler_ai_db::models::{PermissionOverwrite, Role, Room, TenantMember, role::permissions};
+
+use crate::dao::{
+    base::{DaoError, DaoResult},
+    room::RoomDao,
+    tenant::TenantDao,
+};
+
+/// `PermissionOverwrite.target_type` values.
+pub const OVERWRITE_EVERYONE: &str = "everyone";
+pub const OVERWRITE_ROLE: &str = "role";
+pub const OVERWRITE_MEMBER: &str = "member";
+
+/// A role or membership change about to be applied, as seen by the
+/// last-admin check.
+#[derive(Debug, Clone, Copy)]
+pub enum RoleChange {
+    Unassign {
+        user_id: ObjectId,
+        role_id: ObjectId,
+    },
+    SetPermissions {
+        role_id: ObjectId,
+        permissions: u64,
+    },
+    DeleteRole(ObjectId),
+    RemoveMember(ObjectId),
+}
+
+pub struct PermissionService {
+    tenants: Arc<TenantDao>,
+    rooms: Arc<RoomDao>,
+}
+
+impl PermissionService {
+    pub fn new(tenants: Arc<TenantDao>, rooms: Arc<RoomDao>) -> Self {
+        Self { tenants, rooms }
+    }
+
+    /// The member's tenant-wide permissions. `Forbidden` for non-members.
+    pub async fn tenant_permissions(
+        &self,
+        tenant_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<u64> {
+        self.tenants
+            .get_member_permissions(tenant_id, user_id)
+            .await
+    }
+
+    /// The member's effective permissions in one room. `Forbidden` for
+    /// non-members of the tenant, `NotFound` for a room outside it or one
+    /// that has been deleted.
+    pub async fn room_permissions(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+    ) -> DaoResult<u64> {
+        let base = self.tenant_permissions(tenant_id, user_id).await?;
+        let room = self
+            .rooms
+            .base
+            .find_by_id_in_tenant(tenant_id, room_id)
+            .await?;
+        if room.deleted_at.is_some() {
+            return Err(DaoError::NotFound);
+        }
+        let role_ids = self.tenants.member_role_ids(tenant_id, user_id).await?;
+        Ok(resolve(base, &room, &role_ids, user_id))
+    }
+
+    /// `Ok` when the member holds `flag` in the room, `Forbidden` otherwise.
+    pub async fn require_room(
+        &self,
+        tenant_id: ObjectId,
+        room_id: ObjectId,
+        user_id: ObjectId,
+        flag: u64,
+    ) -> DaoResult<u64> {
+        let perms = self.room_permissions(tenant_id, room_id, user_id).await?;
+        if !permissions::has(perms, flag) {
+            return Err(DaoError::Forbidden(format!(
+                "Missing {} permission",
+                permissions::name(flag)
+            )));
+        }
+        Ok(perms)
+    }
+
+    /// `Forbidden` when `change` would take `ADMINISTRATOR` away from the
+    /// tenant's last member holding it. Tenants that have no administrator to
+    /// begin with are left alone.
+    pub async fn ensure_admin_remains(
+        &self,
+        tenant_id: ObjectId,
+        change: RoleChange,
+    ) -> DaoResult<()> {
+        let filter = doc! { "tenant_id": tenant_id };
+        let roles = self.tenants.roles.find_many(filter.clone(), None).await?;
+        let members = self.tenants.members.find_many(filter, None).await?;
+
+        if admin_count(&roles, &members, None) > 0
+            && admin_count(&roles, &members, Some(change)) == 0
+        {
+            return Err(DaoError::Forbidden(
+                "Cannot remove the last administrator".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Members holding `ADMINISTRATOR`, optionally with `change` applied.
+fn admin_count(roles: &[Role], members: &[TenantMember], change: Option<RoleChange>) -> usize {
+    let role_perms = |id: ObjectId| match change {
+        Some(RoleChange::DeleteRole(deleted)) if deleted == id => 0,
+        Some(RoleChange::SetPermissions {
+            role_id,
+            permissions: perms,
+        }) if role_id == id => perms,
+        _ => roles
+            .iter()
+            .find(|r| r.id == Some(id))
+            .map_or(0, |r| r.permissions),
+    };
+
+    members
+        .iter()
+        .filter(|m| !matches!(change, Some(RoleChange::RemoveMember(u)) if u == m.user_id))
+        .filter(|m| {
+            m.role_ids
+                .iter()
+                .filter(|rid| {
+                    !matches!(change, Some(RoleChange::Unassign { user_id, role_id })
+                        if user_id == m.user_id && role_id == **rid)
+                })
+                .any(|rid| role_perms(*rid) & permissions::ADMINISTRATOR != 0)
+        })
+        .count()
+}
+
+/// Apply `room`'s overwrites and organizer status to the member's tenant
+/// permissions.
+pub fn resolve(base: u64, room: &Room, role_ids: &[ObjectId], user_id: ObjectId) -> u64 {
+    if base & permissions::ADMINISTRATOR != 0 {
+        return permissions::ALL;
+    }
+
+    let mut perms = apply_overwrites(base, &room.permission_overwrites, role_ids, user_id);
+
+    let organizer = room.organizer_id.unwrap_or(room.creator_id);
+    if organizer == user_id || room.co_organizer_ids.contains(&user_id) {
+        perms |= permissions::ORGANIZER;
+    }
+    perms
+}
+
+fn apply_overwrites(
+    mut perms: u64,
+    overwrites: &[PermissionOverwrite],
+    role_ids: &[ObjectId],
+    user_id: ObjectId,
+) -> u64 {
+    let mut apply = |allow: u64, deny: u64| perms = (perms & !deny) | allow;
+
+    for ow in overwrites
+        .iter()
+        .filter(|o| o.target_type == OVERWRITE_EVERYONE)
+    {
+        apply(ow.allow, ow.deny);
+    }
+
+    let (allow, deny) = overwrites
+        .iter()
+        .filter(|o| o.target_type == OVERWRITE_ROLE && role_ids.contains(&o.target_id))
+        .fold((0, 0), |(a, d), o| (a | o.allow, d | o.deny));
+    apply(allow, deny);
+
+    for ow in overwrites
+        .iter()
+        .filter(|o| o.target_type == OVERWRITE_MEMBER && o.target_id == user_id)
+    {
+        apply(ow.allow, ow.deny);
+    }
+
+    perms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bson::DateTime;
+
+    fn room(creator: ObjectId, overwrites: Vec<PermissionOverwrite>) -> Room {
+        let now = DateTime::now();
+        Room {
+            id: Some(ObjectId::new()),
+            tenant_id: ObjectId::new(),
+            parent_id: None,
+            name: "general".into(),
+            path: "general".into(),
+            emoji: None,
+            topic: None,
+            purpose: None,
+            icon: None,
+            position: 0,
+            is_open: true,
+            is_archived: false,
+            is_read_only: false,
+            is_announcement: false,
+            is_default: false,
+            legal_hold: false,
+            permission_overwrites: overwrites,
+            tags: Vec::new(),
+            media_settings: None,
+            conference_settings: None,
+            conference_status: None,
+            meeting_code: None,
+            join_url: None,
+            organizer_id: None,
+            co_organizer_ids: Vec::new(),
+            creator_id: creator,
+            last_message_id: None,
+            last_activity_at: None,
+            member_count: 0,
+            message_count: 0,
+            participant_count: 0,
+            peak_participant_count: 0,
+            actual_start_time: None,
+            actual_end_time: None,
+            created_at: now,
+            updated_at: now,
+            deleted_at: None,
+        }
+    }
+
+    fn ow(target_type: &str, target_id: ObjectId, allow: u64, deny: u64) -> PermissionOverwrite {
+        PermissionOverwrite {
+            target_id,
+            target_type: target_type.into(),
+            allow,
+            deny,
+        }
+    }
+
+    #[test]
+    fn member_overwrite_beats_role_beats_everyone() {
+        let (user, role) = (ObjectId::new(), ObjectId::new());
+        let r = room(
+            ObjectId::new(),
+            vec![
+                ow(OVERWRITE_MEMBER, user, permissions::SEND_MESSAGES, 0),
+                ow(OVERWRITE_ROLE, role, 0, permissions::SEND_MESSAGES),
+                ow(
+                    OVERWRITE_EVERYONE,
+                    ObjectId::new(),
+                    permissions::SEND_MESSAGES,
+                    0,
+                ),
+            ],
+        );
+        let perms = resolve(permissions::DEFAULT_MEMBER, &r, &[role], user);
+        assert!(permissions::has(perms, permissions::SEND_MESSAGES));
+
+        // Without the member overwrite the role deny wins over everyone-allow.
+        let other = ObjectId::new();
+        let perms = resolve(permissions::DEFAULT_MEMBER, &r, &[role], other);
+        assert!(!permissions::has(perms, permissions::SEND_MESSAGES));
+    }
+
+    #[test]
+    fn role_overwrites_for_other_roles_are_ignored() {
+        let user = ObjectId::new();
+        let r = room(
+            ObjectId::new(),
+            vec![ow(
+                OVERWRITE_ROLE,
+                ObjectId::new(),
+                0,
+                permissions::SEND_MESSAGES,
+            )],
+        );
+        let perms = resolve(permissions::DEFAULT_MEMBER, &r, &[ObjectId::new()], user);
+        assert_eq!(perms, permissions::DEFAULT_MEMBER);
+    }
+
+    #[test]
+    fn organizer_and_administrator_cannot_be_locked_out() {
+        let creator = ObjectId::new();
+        let r = room(
+            creator,
+            vec![ow(OVERWRITE_EVERYONE, ObjectId::new(), 0, permissions::ALL)],
+        );
+        let perms = resolve(permissions::DEFAULT_MEMBER, &r, &[], creator);
+        assert!(permissions::has(perms, permissions::MANAGE_MEETINGS));
+        assert!(permissions::has(perms, permissions::MANAGE_CHANNELS));
+        assert!(!permissions::has(perms, permissions::SEND_MESSAGES));
+
+        let admin = ObjectId::new();
+        let perms = resolve(permissions::ADMINISTRATOR, &r, &[], admin);
+        assert_eq!(perms, permissions::ALL);
+    }
+
+    fn role(perms: u64) -> Role {
+        Role {
+            id: Some(ObjectId::new()),
+            tenant_id: ObjectId::new(),
+            name: "role".into(),
+            description: None,
+            color: None,
+            position: 0,
+            permissions: perms,
+            is_default: false,
+            is_managed: false,
+            is_mentionable: true,
+            is_hoisted: false,
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    fn member(role_ids: Vec<ObjectId>) -> TenantMember {
+        TenantMember {
+            id: Some(ObjectId::new()),
+            tenant_id: ObjectId::new(),
+            user_id: ObjectId::new(),
+            nickname: None,
+            role_ids,
+            joined_at: DateTime::now(),
+            is_pending: false,
+            is_muted: false,
+            notification_override: None,
+            invited_by: None,
+            last_seen_at: None,
+            search_keys: Vec::new(),
+            created_at: DateTime::now(),
+            updated_at: DateTime::now(),
+        }
+    }
+
+    #[test]
+    fn admin_count_applies_pending_change() {
+        let admin_role = role(permissions::ADMINISTRATOR);
+        let member_role = role(permissions::DEFAULT_MEMBER);
+        let (aid, mid) = (admin_role.id.unwrap(), member_role.id.unwrap());
+        let roles = vec![admin_role, member_role];
+        let admin = member(vec![aid, mid]);
+        let members = vec![admin.clone(), member(vec![mid])];
+
+        assert_eq!(admin_count(&roles, &members, None), 1);
+        let unassign = RoleChange::Unassign {
+            user_id: admin.user_id,
+            role_id: aid,
+        };
+        assert_eq!(admin_count(&roles, &members, Some(unassign)), 0);
+        // Dropping an unrelated role keeps the admin.
+        let unassign = RoleChange::Unassign {
+            user_id: admin.user_id,
+            role_id: mid,
+        };
+        assert_eq!(admin_count(&roles, &members, Some(unassign)), 1);
+        let demote = RoleChange::SetPermissions {
+            role_id: aid,
+            permissions: permissions::MANAGE_ROLES,
+        };
+        assert_eq!(admin_count(&roles, &members, Some(demote)), 0);
+        let promote = RoleChange::SetPermissions {
+            role_id: mid,
+            permissions: permissions::ADMINISTRATOR,
+        };
+        assert_eq!(admin_count(&roles, &members, Some(promote)), 2);
+        assert_eq!(
+            admin_count(&roles, &members, Some(RoleChange::DeleteRole(aid))),
+            0
+        );
+        assert_eq!(
+            admin_count(
+                &roles,
+                &members,
+                Some(RoleChange::RemoveMember(admin.user_id))
+            ),
+            0
+        );
+    }
+}
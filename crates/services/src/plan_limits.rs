@@ -0,0 +1,117 @@
+//! Plan limits enforced where a tenant grows: members at invite accept,
+//! call participants at call join, recording minutes at recording start and
+//! storage at upload. Callers measure current usage; [`check`] compares it
+//! with the plan.
+
+use roomler_ai_db::models::{Plan, PlanLimits};
+use serde::Serialize;
+
+const MB: u64 = 1024 * 1024;
+const GB: u64 = 1024 * MB;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Limit {
+    Members,
+    CallParticipants,
+    RecordingMinutes,
+    Storage,
+}
+
+impl Limit {
+    fn max(self, limits: &PlanLimits) -> u64 {
+        match self {
+            Limit::Members => u64::from(limits.max_members),
+            Limit::CallParticipants => u64::from(limits.video_max_participants),
+            Limit::RecordingMinutes => limits.recording_minutes,
+            Limit::Storage => limits.storage_bytes,
+        }
+    }
+}
+
+/// `current` plus what was asked for would exceed the plan's `max`.
+#[derive(Debug, Clone, Serialize)]
+pub struct LimitReached {
+    pub limit: Limit,
+    pub plan: Plan,
+    pub max: u64,
+    pub current: u64,
+}
+
+impl std::fmt::Display for LimitReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let plan = plan_name(&self.plan);
+        match self.limit {
+            Limit::Members => write!(f, "The {plan} plan allows {} members", self.max),
+            Limit::CallParticipants if self.max == 0 => {
+                write!(f, "The {plan} plan does not include calls")
+            }
+            Limit::CallParticipants => {
+                write!(f, "The {plan} plan allows {} call participants", self.max)
+            }
+            Limit::RecordingMinutes if self.max == 0 => {
+                write!(f, "The {plan} plan does not include recordings")
+            }
+            Limit::RecordingMinutes => {
+                write!(f, "The {plan} plan allows {} recording minutes", self.max)
+            }
+            Limit::Storage if self.max >= GB => {
+                write!(f, "The {plan} plan allows {} GB of storage", self.max / GB)
+            }
+            Limit::Storage => write!(f, "The {plan} plan allows {} MB of storage", self.max / MB),
+        }
+    }
+}
+
+/// Whether adding `adding` to `current` stays within the plan's `limit`.
+pub fn check(plan: &Plan, limit: Limit, current: u64, adding: u64) -> Result<(), LimitReached> {
+    let max = limit.max(&plan.limits());
+    if current.saturating_add(adding) > max {
+        return Err(LimitReached {
+            limit,
+            plan: plan.clone(),
+            max,
+            current,
+        });
+    }
+    Ok(())
+}
+
+fn plan_name(plan: &Plan) -> &'static str {
+    match plan {
+        Plan::Free => "Free",
+        Plan::Pro => "Pro",
+        Plan::Business => "Business",
+        Plan::Enterprise => "Enterprise",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_allow_up_to_the_plan_maximum() {
+        assert!(check(&Plan::Free, Limit::Members, 9, 1).is_ok());
+        let err = check(&Plan::Free, Limit::Members, 10, 1).unwrap_err();
+        assert_eq!(err.max, 10);
+        assert_eq!(err.to_string(), "The Free plan allows 10 members");
+
+        assert!(check(&Plan::Free, Limit::Storage, 99 * MB, MB).is_ok());
+        let err = check(&Plan::Free, Limit::Storage, 99 * MB, MB + 1).unwrap_err();
+        assert_eq!(err.to_string(), "The Free plan allows 100 MB of storage");
+    }
+
+    #[test]
+    fn unlimited_and_excluded_features() {
+        assert!(check(&Plan::Pro, Limit::Members, 10_000, 1).is_ok());
+        assert_eq!(
+            check(&Plan::Free, Limit::CallParticipants, 0, 1)
+                .unwrap_err()
+                .to_string(),
+            "The Free plan does not include calls"
+        );
+        assert!(check(&Plan::Business, Limit::RecordingMinutes, 2_999, 1).is_ok());
+        assert!(check(&Plan::Pro, Limit::RecordingMinutes, 0, 1).is_err());
+    }
+}
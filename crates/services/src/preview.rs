@@ -0,0 +1,172 @@
+use std::time::Duration;
+
+use base64::Engine;
+use roomler_ai_config::PreviewSettings;
+use serde::Deserialize;
+
+/// Content types the worker can render: PDF and Word documents.
+const PREVIEWABLE: &[&str] = &[
+    "application/pdf",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.oasis.opendocument.text",
+];
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Renders the first pages of documents to PNG through an external
+/// conversion worker. The worker gets the document as the POST body (with
+/// its `Content-Type`) and `?pages=&width=`, and answers
+/// `{ "pages": [{ "png": "<base64>", "width": u32, "height": u32 }] }`.
+///
+/// `from_settings` returns `None` when no worker is configured; attachments
+/// then have no previews.
+#[derive(Clone)]
+pub struct PreviewService {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    max_pages: u32,
+    width: u32,
+    timeout: Duration,
+}
+
+/// One rendered page.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageImage {
+    pub png: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkerReply {
+    pages: Vec<WorkerPage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkerPage {
+    png: String,
+    width: u32,
+    height: u32,
+}
+
+impl PreviewService {
+    pub fn from_settings(settings: &PreviewSettings) -> Option<Self> {
+        if settings.worker_url.is_empty() {
+            return None;
+        }
+        Some(Self {
+            client: reqwest::Client::new(),
+            url: settings.worker_url.clone(),
+            api_key: settings.api_key.clone(),
+            max_pages: settings.max_pages.max(1),
+            width: settings.width.max(1),
+            timeout: Duration::from_secs(settings.timeout_secs.max(1)),
+        })
+    }
+
+    /// Whether a file of this type gets page previews.
+    pub fn supports(content_type: &str) -> bool {
+        let essence = content_type.split(';').next().unwrap_or("").trim();
+        PREVIEWABLE.iter().any(|t| t.eq_ignore_ascii_case(essence))
+    }
+
+    /// Render up to `max_pages` pages of the document.
+    pub async fn render(&self, bytes: &[u8], content_type: &str) -> Result<Vec<PageImage>, String> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .query(&[("pages", self.max_pages), ("width", self.width)])
+            .header("content-type", content_type)
+            .body(bytes.to_vec())
+            .timeout(self.timeout);
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| format!("Conversion worker request failed: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("Conversion worker error {}", resp.status()));
+        }
+        let reply: WorkerReply = resp
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse conversion worker response: {}", e))?;
+        decode_pages(reply, self.max_pages as usize)
+    }
+}
+
+/// Decode the worker's pages, keeping at most `max_pages`; anything that
+/// isn't a PNG fails the whole render.
+fn decode_pages(reply: WorkerReply, max_pages: usize) -> Result<Vec<PageImage>, String> {
+    reply
+        .pages
+        .into_iter()
+        .take(max_pages)
+        .map(|page| {
+            let png = base64::engine::general_purpose::STANDARD
+                .decode(page.png.as_bytes())
+                .map_err(|e| format!("Invalid page image: {}", e))?;
+            if !png.starts_with(PNG_SIGNATURE) {
+                return Err("Conversion worker returned a page that isn't a PNG".to_string());
+            }
+            Ok(PageImage {
+                png,
+                width: page.width,
+                height: page.height,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(png: &[u8]) -> WorkerPage {
+        WorkerPage {
+            png: base64::engine::general_purpose::STANDARD.encode(png),
+            width: 480,
+            height: 679,
+        }
+    }
+
+    #[test]
+    fn supported_types() {
+        assert!(PreviewService::supports("application/pdf"));
+        assert!(PreviewService::supports("Application/PDF; charset=binary"));
+        assert!(PreviewService::supports(
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        ));
+        assert!(!PreviewService::supports("image/png"));
+        assert!(!PreviewService::supports("text/plain"));
+    }
+
+    #[test]
+    fn no_worker_no_service() {
+        let mut settings = PreviewSettings::default();
+        assert!(PreviewService::from_settings(&settings).is_none());
+        settings.worker_url = "http://convert.local/render".to_string();
+        assert!(PreviewService::from_settings(&settings).is_some());
+    }
+
+    #[test]
+    fn pages_are_decoded_and_capped() {
+        let png = [PNG_SIGNATURE, b"rest"].concat();
+        let reply = WorkerReply {
+            pages: vec![page(&png), page(&png), page(&png)],
+        };
+        let pages = decode_pages(reply, 2).unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].png, png);
+        assert_eq!((pages[0].width, pages[0].height), (480, 679));
+
+        let reply = WorkerReply {
+            pages: vec![page(b"GIF89a")],
+        };
+        assert!(decode_pages(reply, 2).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+use std::time::Duration;
+
+use roomler_ai_config::ScanSettings;
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// Bytes per chunk of a clamd `INSTREAM`; well under clamd's default
+/// `StreamMaxLength` chunking limits.
+const CLAMD_CHUNK: usize = 64 * 1024;
+
+/// Antivirus scanner for uploads. Two backends, picked from
+/// `ScanSettings::provider`:
+///   - `Clamd`: the `INSTREAM` command on clamd's TCP socket.
+///   - `Http`: an external API that gets the file as the POST body and
+///     answers `{ "clean": bool, "signature": string | null }`.
+///
+/// `from_settings` returns `None` when no provider is configured; uploads
+/// are then stored unscanned.
+#[derive(Clone)]
+pub struct ScanService {
+    backend: ScanBackend,
+    timeout: Duration,
+}
+
+#[derive(Clone)]
+enum ScanBackend {
+    Clamd {
+        addr: String,
+    },
+    Http {
+        client: reqwest::Client,
+        url: String,
+        api_key: String,
+    },
+}
+
+/// The outcome of a scan.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Clean,
+    /// Infected; the engine's name for what it found.
+    Infected(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpVerdict {
+    clean: bool,
+    signature: Option<String>,
+}
+
+impl ScanService {
+    pub fn from_settings(settings: &ScanSettings) -> Option<Self> {
+        let backend = match settings.provider.as_str() {
+            "clamav" => ScanBackend::Clamd {
+                addr: settings.clamd_addr.clone(),
+            },
+            "http" if !settings.api_url.is_empty() => ScanBackend::Http {
+                client: reqwest::Client::new(),
+                url: settings.api_url.clone(),
+                api_key: settings.api_key.clone(),
+            },
+            _ => return None,
+        };
+        Some(Self {
+            backend,
+            timeout: Duration::from_secs(settings.timeout_secs.max(1)),
+        })
+    }
+
+    /// Name of the engine, recorded with each result.
+    pub fn engine(&self) -> &'static str {
+        match self.backend {
+            ScanBackend::Clamd { .. } => "clamav",
+            ScanBackend::Http { .. } => "http",
+        }
+    }
+
+    pub async fn scan(&self, bytes: &[u8]) -> Result<Verdict, String> {
+        let scan = async {
+            match &self.backend {
+                ScanBackend::Clamd { addr } => clamd_instream(addr, bytes).await,
+                ScanBackend::Http {
+                    client,
+                    url,
+                    api_key,
+                } => {
+                    let mut request = client
+                        .post(url)
+                        .header("content-type", "application/octet-stream")
+                        .body(bytes.to_vec());
+                    if !api_key.is_empty() {
+                        request = request.bearer_auth(api_key);
+                    }
+                    let resp = request
+                        .send()
+                        .await
+                        .map_err(|e| format!("Scan API request failed: {}", e))?;
+                    if !resp.status().is_success() {
+                        return Err(format!("Scan API error {}", resp.status()));
+                    }
+                    let verdict: HttpVerdict = resp
+                        .json()
+                        .await
+                        .map_err(|e| format!("Failed to parse scan API response: {}", e))?;
+                    Ok(if verdict.clean {
+                        Verdict::Clean
+                    } else {
+                        Verdict::Infected(verdict.signature.unwrap_or_else(|| "unknown".into()))
+                    })
+                }
+            }
+        };
+        tokio::time::timeout(self.timeout, scan)
+            .await
+            .map_err(|_| "Scan timed out".to_string())?
+    }
+}
+
+/// Stream the bytes to clamd as length-prefixed chunks ended by a zero
+/// length, and read its null-terminated reply.
+async fn clamd_instream(addr: &str, bytes: &[u8]) -> Result<Verdict, String> {
+    let mut stream = TcpStream::connect(addr)
+        .await
+        .map_err(|e| format!("clamd connect to {} failed: {}", addr, e))?;
+    let io = |e: std::io::Error| format!("clamd I/O failed: {}", e);
+
+    stream.write_all(b"zINSTREAM\0").await.map_err(io)?;
+    for chunk in bytes.chunks(CLAMD_CHUNK) {
+        stream
+            .write_all(&(chunk.len() as u32).to_be_bytes())
+            .await
+            .map_err(io)?;
+        stream.write_all(chunk).await.map_err(io)?;
+    }
+    stream.write_all(&0u32.to_be_bytes()).await.map_err(io)?;
+
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await.map_err(io)?;
+    parse_clamd_reply(&String::from_utf8_lossy(&reply))
+}
+
+/// `stream: OK`, `stream: <signature> FOUND` or `... ERROR`.
+fn parse_clamd_reply(reply: &str) -> Result<Verdict, String> {
+    let reply = reply.trim_end_matches(['\0', '\n']).trim();
+    let result = reply.strip_prefix("stream:").unwrap_or(reply).trim();
+    if result == "OK" {
+        Ok(Verdict::Clean)
+    } else if let Some(signature) = result.strip_suffix(" FOUND") {
+        Ok(Verdict::Infected(signature.trim().to_string()))
+    } else {
+        Err(format!("clamd: {}", reply))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn clamd_replies() {
+        assert_eq!(parse_clamd_reply("stream: OK\0"), Ok(Verdict::Clean));
+        assert_eq!(
+            parse_clamd_reply("stream: Win.Test.EICAR_HDB-1 FOUND\0"),
+            Ok(Verdict::Infected("Win.Test.EICAR_HDB-1".to_string()))
+        );
+        assert!(parse_clamd_reply("INSTREAM size limit exceeded. ERROR\0").is_err());
+    }
+
+    #[test]
+    fn provider_selects_backend() {
+        let mut settings = ScanSettings::default();
+        assert!(ScanService::from_settings(&settings).is_none());
+        settings.provider = "http".to_string();
+        assert!(ScanService::from_settings(&settings).is_none());
+        settings.api_url = "http://scanner.local/scan".to_string();
+        assert_eq!(
+            ScanService::from_settings(&settings).unwrap().engine(),
+            "http"
+        );
+        settings.provider = "clamav".to_string();
+        assert_eq!(
+            ScanService::from_settings(&settings).unwrap().engine(),
+            "clamav"
+        );
+    }
+
+    #[tokio::test]
+    async fn instream_sends_framed_chunks() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let clamd = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut command = [0u8; 10];
+            socket.read_exact(&mut command).await.unwrap();
+            assert_eq!(&command, b"zINSTREAM\0");
+            let mut body = Vec::new();
+            loop {
+                let len = socket.read_u32().await.unwrap() as usize;
+                if len == 0 {
+                    break;
+                }
+                let mut chunk = vec![0; len];
+                socket.read_exact(&mut chunk).await.unwrap();
+                body.extend(chunk);
+            }
+            socket.write_all(b"stream: Eicar FOUND\0").await.unwrap();
+            body
+        });
+
+        let bytes = vec![7u8; CLAMD_CHUNK + 10];
+        assert_eq!(
+            clamd_instream(&addr, &bytes).await,
+            Ok(Verdict::Infected("Eicar".to_string()))
+        );
+        assert_eq!(clamd.await.unwrap(), bytes);
+    }
+}
@@ -1,27 +1,31 @@
 use mongodb::bson::{DateTime, doc, oid::ObjectId};
 use roomler_ai_config::StripeSettings;
-use roomler_ai_db::models::tenant::{BillingInfo, Plan, PlanLimits, SubscriptionStatus, Tenant};
+use roomler_ai_db::models::tenant::{
+    BillingInfo, Plan, PlanLimits, RateLimitOverrides, SubscriptionStatus, Tenant,
+};
 use serde::{Deserialize, Serialize};
 use tracing::{info, warn};
+use utoipa::ToSchema;
 
 // ---- Response / DTO types ------------------------------------------------
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct CheckoutResponse {
     pub url: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PortalResponse {
     pub url: String,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct PlanInfo {
     pub id: String,
     pub name: String,
     pub price_cents: u32,
     pub features: Vec<String>,
+    #[schema(value_type = Object)]
     pub limits: PlanLimits,
 }
 
@@ -29,6 +33,10 @@ pub struct PlanInfo {
 
 #[derive(Debug, Deserialize)]
 pub struct StripeEvent {
+    /// `evt_...`, unique per event; redeliveries repeat it.
+    pub id: String,
+    /// Unix seconds.
+    pub created: i64,
     #[serde(rename = "type")]
     pub event_type: String,
     pub data: StripeEventData,
@@ -53,6 +61,8 @@ pub enum StripeError {
     ApiError(String),
     #[error("Invalid webhook signature")]
     InvalidSignature,
+    #[error("Webhook timestamp outside the tolerance")]
+    StaleSignature,
     #[error("MongoDB error: {0}")]
     Mongo(#[from] mongodb::error::Error),
 }
@@ -255,6 +265,52 @@ impl StripeService {
         Ok(PortalResponse { url })
     }
 
+    // ---- Usage metering --------------------------------------------------
+
+    /// Report `value` of usage to the Stripe meter `event_name`. Stripe
+    /// drops a repeated `identifier`, so a retried report is not counted
+    /// twice.
+    pub async fn report_meter_event(
+        &self,
+        event_name: &str,
+        customer_id: &str,
+        value: u64,
+        timestamp: i64,
+        identifier: &str,
+    ) -> Result<(), StripeError> {
+        let value = value.to_string();
+        let timestamp = timestamp.to_string();
+        let params = [
+            ("event_name", event_name),
+            ("payload[stripe_customer_id]", customer_id),
+            ("payload[value]", value.as_str()),
+            ("timestamp", timestamp.as_str()),
+            ("identifier", identifier),
+        ];
+
+        let resp: serde_json::Value = self
+            .client
+            .post("https://api.stripe.com/v1/billing/meter_events")
+            .basic_auth(&self.settings.secret_key, None::<&str>)
+            .form(&params)
+            .send()
+            .await
+            .map_err(|e| StripeError::ApiError(e.to_string()))?
+            .json()
+            .await
+            .map_err(|e| StripeError::ApiError(e.to_string()))?;
+
+        if let Some(err) = resp.get("error") {
+            return Err(StripeError::ApiError(
+                err["message"]
+                    .as_str()
+                    .unwrap_or("Unknown Stripe error")
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     // ---- Plans (static) --------------------------------------------------
 
     pub fn get_plans() -> Vec<PlanInfo> {
@@ -294,7 +350,7 @@ impl StripeService {
                     "100 GB storage".into(),
                     "Video (100 participants)".into(),
                     "AI doc recognition".into(),
-                    "Recordings".into(),
+                    "Recordings (3,000 minutes)".into(),
                     "Priority support".into(),
                 ],
                 limits: Plan::Business.limits(),
@@ -304,11 +360,15 @@ impl StripeService {
 
     // ---- Webhook processing ----------------------------------------------
 
-    /// Verify the Stripe webhook signature using HMAC-SHA256.
+    /// Verify the Stripe webhook signature using HMAC-SHA256. The signed
+    /// timestamp must be within `tolerance_secs` of `now` (unix seconds), so
+    /// a captured request can't be replayed later; 0 skips that check.
     pub fn verify_signature(
         webhook_secret: &str,
         payload: &[u8],
         sig_header: &str,
+        now: i64,
+        tolerance_secs: u64,
     ) -> Result<(), StripeError> {
         use hmac::{Hmac, Mac};
         use sha2::Sha256;
@@ -330,6 +390,9 @@ impl StripeService {
         if signatures.is_empty() {
             return Err(StripeError::InvalidSignature);
         }
+        let signed_at: i64 = timestamp
+            .parse()
+            .map_err(|_| StripeError::InvalidSignature)?;
 
         // Build the signed payload: "{timestamp}.{body}"
         let signed_payload = format!("{timestamp}.{}", String::from_utf8_lossy(payload));
@@ -337,22 +400,36 @@ impl StripeService {
         let mut mac = Hmac::<Sha256>::new_from_slice(webhook_secret.as_bytes())
             .map_err(|_| StripeError::InvalidSignature)?;
         mac.update(signed_payload.as_bytes());
-        let expected = hex::encode(mac.finalize().into_bytes());
 
-        if signatures.iter().any(|s| s == &expected) {
-            Ok(())
-        } else {
-            Err(StripeError::InvalidSignature)
+        // Constant-time comparison against each candidate signature.
+        let valid = signatures.iter().any(|s| {
+            hex::decode(s)
+                .map(|sig| mac.clone().verify_slice(&sig).is_ok())
+                .unwrap_or(false)
+        });
+        if !valid {
+            return Err(StripeError::InvalidSignature);
         }
+        if tolerance_secs > 0 && now.abs_diff(signed_at) > tolerance_secs {
+            return Err(StripeError::StaleSignature);
+        }
+        Ok(())
     }
 
     /// Handle a verified webhook event, updating tenant billing state.
+    /// Stripe doesn't guarantee delivery order, so an event older than the
+    /// last one applied to the tenant (`billing.synced_at`) changes nothing.
     pub async fn handle_webhook_event(
         &self,
         db: &mongodb::Database,
         event: &StripeEvent,
     ) -> Result<(), StripeError> {
         let obj = &event.data.object;
+        let created = DateTime::from_millis(event.created.saturating_mul(1000));
+        let not_newer = doc! { "$or": [
+            { "billing.synced_at": null },
+            { "billing.synced_at": { "$lte": created } },
+        ] };
 
         match event.event_type.as_str() {
             "checkout.session.completed" => {
@@ -376,9 +453,11 @@ impl StripeService {
                 };
 
                 let collection = db.collection::<Tenant>(Tenant::COLLECTION);
+                let mut filter = not_newer.clone();
+                filter.insert("_id", tenant_id);
                 collection
                     .update_one(
-                        doc! { "_id": tenant_id },
+                        filter,
                         doc! {
                             "$set": {
                                 "plan": bson::to_bson(&plan).unwrap_or_default(),
@@ -388,6 +467,8 @@ impl StripeService {
                                     current_period_end: None,
                                     status: SubscriptionStatus::Active,
                                     cancel_at_period_end: false,
+                                    rate_limits: RateLimitOverrides::default(),
+                                    synced_at: Some(created),
                                 }).unwrap_or_default(),
                                 "updated_at": DateTime::now(),
                             }
@@ -423,17 +504,18 @@ impl StripeService {
                 let mut update = doc! {
                     "billing.status": bson::to_bson(&sub_status).unwrap_or_default(),
                     "billing.cancel_at_period_end": cancel_at_period_end,
+                    "billing.rate_limits": bson::to_bson(&rate_limit_overrides(obj)).unwrap_or_default(),
+                    "billing.synced_at": created,
                     "updated_at": DateTime::now(),
                 };
                 if let Some(pe) = period_end {
                     update.insert("billing.current_period_end", pe);
                 }
 
+                let mut filter = not_newer.clone();
+                filter.insert("billing.subscription_id", subscription_id);
                 collection
-                    .update_one(
-                        doc! { "billing.subscription_id": subscription_id },
-                        doc! { "$set": update },
-                    )
+                    .update_one(filter, doc! { "$set": update })
                     .await?;
 
                 info!(
@@ -447,14 +529,17 @@ impl StripeService {
                 let subscription_id = obj["id"].as_str().unwrap_or_default();
 
                 let collection = db.collection::<Tenant>(Tenant::COLLECTION);
+                let mut filter = not_newer.clone();
+                filter.insert("billing.subscription_id", subscription_id);
                 collection
                     .update_one(
-                        doc! { "billing.subscription_id": subscription_id },
+                        filter,
                         doc! {
                             "$set": {
                                 "plan": bson::to_bson(&Plan::Free).unwrap_or_default(),
                                 "billing.status": bson::to_bson(&SubscriptionStatus::Canceled).unwrap_or_default(),
                                 "billing.cancel_at_period_end": false,
+                                "billing.synced_at": created,
                                 "updated_at": DateTime::now(),
                             }
                         },
@@ -471,12 +556,15 @@ impl StripeService {
                 let subscription_id = obj["subscription"].as_str().unwrap_or_default();
 
                 let collection = db.collection::<Tenant>(Tenant::COLLECTION);
+                let mut filter = not_newer;
+                filter.insert("billing.subscription_id", subscription_id);
                 collection
                     .update_one(
-                        doc! { "billing.subscription_id": subscription_id },
+                        filter,
                         doc! {
                             "$set": {
                                 "billing.status": bson::to_bson(&SubscriptionStatus::PastDue).unwrap_or_default(),
+                                "billing.synced_at": created,
                                 "updated_at": DateTime::now(),
                             }
                         },
@@ -497,3 +585,85 @@ impl StripeService {
         Ok(())
     }
 }
+
+/// Read `rate_limit_*` keys from the subscription's price metadata (falling
+/// back to the legacy `plan` object). Operators tune a plan's limits by
+/// editing the price in the Stripe dashboard; the next
+/// `customer.subscription.updated` carries them over.
+fn rate_limit_overrides(subscription: &serde_json::Value) -> RateLimitOverrides {
+    let price_meta = &subscription["items"]["data"][0]["price"]["metadata"];
+    let plan_meta = &subscription["plan"]["metadata"];
+    let read = |key: &str| {
+        [price_meta, plan_meta].into_iter().find_map(|meta| {
+            meta[key]
+                .as_str()
+                .and_then(|v| v.trim().parse::<u32>().ok())
+        })
+    };
+    RateLimitOverrides {
+        messages_per_min: read("rate_limit_messages_per_min"),
+        uploads_per_min: read("rate_limit_uploads_per_min"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign(secret: &str, timestamp: i64, payload: &[u8]) -> String {
+        use hmac::{Hmac, Mac};
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{timestamp}.").as_bytes());
+        mac.update(payload);
+        format!(
+            "t={timestamp},v1={}",
+            hex::encode(mac.finalize().into_bytes())
+        )
+    }
+
+    #[test]
+    fn signature_must_match_and_be_recent() {
+        let body = br#"{"id":"evt_1"}"#;
+        let header = sign("whsec", 1_000, body);
+        assert!(StripeService::verify_signature("whsec", body, &header, 1_000, 300).is_ok());
+        assert!(StripeService::verify_signature("whsec", body, &header, 1_300, 300).is_ok());
+        assert!(matches!(
+            StripeService::verify_signature("whsec", body, &header, 1_301, 300),
+            Err(StripeError::StaleSignature)
+        ));
+        assert!(StripeService::verify_signature("whsec", body, &header, 99_999, 0).is_ok());
+        assert!(matches!(
+            StripeService::verify_signature("other", body, &header, 1_000, 300),
+            Err(StripeError::InvalidSignature)
+        ));
+        assert!(matches!(
+            StripeService::verify_signature("whsec", b"{}", &header, 1_000, 300),
+            Err(StripeError::InvalidSignature)
+        ));
+        assert!(
+            StripeService::verify_signature("whsec", body, "t=1000,v1=zz", 1_000, 300).is_err()
+        );
+    }
+
+    #[test]
+    fn reads_rate_limits_from_price_metadata() {
+        let sub = serde_json::json!({
+            "items": { "data": [{ "price": { "metadata": {
+                "rate_limit_messages_per_min": "500",
+                "rate_limit_uploads_per_min": "lots",
+            } } }] },
+            "plan": { "metadata": { "rate_limit_uploads_per_min": "90" } },
+        });
+        assert_eq!(
+            rate_limit_overrides(&sub),
+            RateLimitOverrides {
+                messages_per_min: Some(500),
+                uploads_per_min: Some(90),
+            }
+        );
+        assert_eq!(
+            rate_limit_overrides(&serde_json::json!({})),
+            RateLimitOverrides::default()
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use roomler_ai_config::TtsSettings;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+/// Text-to-speech for `media:speak`. Two backends, picked from
+/// `TtsSettings::provider`:
+///   - [`Piper`]: a local Piper binary with an ONNX voice model.
+///   - [`HttpTts`]: an OpenAI-compatible `/v1/audio/speech` API.
+///
+/// The audio comes back in a container FFmpeg reads (WAV), ready for
+/// `media::file_audio` to play into a call.
+#[async_trait]
+pub trait SpeechSynthesizer: Send + Sync {
+    /// Name of the backend, for logs.
+    fn name(&self) -> &'static str;
+
+    /// Speak `text` in `voice`, or the configured voice when `None`.
+    /// Backends with a single voice ignore it.
+    async fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Vec<u8>, String>;
+}
+
+/// The synthesizer `settings.provider` asks for; `None` when speech is off.
+pub fn from_settings(settings: &TtsSettings) -> Option<Arc<dyn SpeechSynthesizer>> {
+    let timeout = Duration::from_secs(settings.timeout_secs.max(1));
+    match settings.provider.as_str() {
+        "piper" if !settings.piper_model.is_empty() => Some(Arc::new(Piper {
+            path: settings.piper_path.clone(),
+            model: settings.piper_model.clone(),
+            timeout,
+        })),
+        "http" if !settings.api_url.is_empty() => Some(Arc::new(HttpTts {
+            client: reqwest::Client::new(),
+            url: settings.api_url.clone(),
+            api_key: settings.api_key.clone(),
+            model: settings.model.clone(),
+            voice: settings.voice.clone(),
+            timeout,
+        })),
+        _ => None,
+    }
+}
+
+/// Piper, run once per announcement: text on stdin, WAV to a temp file.
+pub struct Piper {
+    path: String,
+    model: String,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl SpeechSynthesizer for Piper {
+    fn name(&self) -> &'static str {
+        "piper"
+    }
+
+    async fn synthesize(&self, text: &str, _voice: Option<&str>) -> Result<Vec<u8>, String> {
+        let out = tempfile::Builder::new()
+            .suffix(".wav")
+            .tempfile()
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+        let run = async {
+            let mut child = Command::new(&self.path)
+                .arg("--model")
+                .arg(&self.model)
+                .arg("--output_file")
+                .arg(out.path())
+                .stdin(Stdio::piped())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .spawn()
+                .map_err(|e| format!("Failed to start {}: {}", self.path, e))?;
+            let mut stdin = child
+                .stdin
+                .take()
+                .ok_or_else(|| "Piper has no stdin".to_string())?;
+            stdin
+                .write_all(text.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to write to Piper: {}", e))?;
+            drop(stdin);
+            let status = child
+                .wait()
+                .await
+                .map_err(|e| format!("Piper failed: {}", e))?;
+            if !status.success() {
+                return Err(format!("Piper failed: {}", status));
+            }
+            tokio::fs::read(out.path())
+                .await
+                .map_err(|e| format!("Failed to read Piper output: {}", e))
+        };
+        tokio::time::timeout(self.timeout, run)
+            .await
+            .map_err(|_| "Speech synthesis timed out".to_string())?
+    }
+}
+
+/// An OpenAI-compatible speech API: POSTs `{ model, input, voice,
+/// response_format: "wav" }` and gets the audio back as the body.
+pub struct HttpTts {
+    client: reqwest::Client,
+    url: String,
+    api_key: String,
+    model: String,
+    voice: String,
+    timeout: Duration,
+}
+
+#[async_trait]
+impl SpeechSynthesizer for HttpTts {
+    fn name(&self) -> &'static str {
+        "http"
+    }
+
+    async fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Vec<u8>, String> {
+        let body = serde_json::json!({
+            "model": self.model,
+            "input": text,
+            "voice": voice.unwrap_or(&self.voice),
+            "response_format": "wav",
+        });
+        let mut request = self
+            .client
+            .post(&self.url)
+            .timeout(self.timeout)
+            .json(&body);
+        if !self.api_key.is_empty() {
+            request = request.bearer_auth(&self.api_key);
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| format!("Speech API request failed: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("Speech API error {}", resp.status()));
+        }
+        let audio = resp
+            .bytes()
+            .await
+            .map_err(|e| format!("Failed to read speech API response: {}", e))?;
+        Ok(audio.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provider_selects_backend() {
+        let mut settings = TtsSettings::default();
+        assert!(from_settings(&settings).is_none());
+        settings.provider = "piper".to_string();
+        assert!(from_settings(&settings).is_none());
+        settings.piper_model = "/voices/en_US-lessac-medium.onnx".to_string();
+        assert_eq!(from_settings(&settings).unwrap().name(), "piper");
+        settings.provider = "http".to_string();
+        assert!(from_settings(&settings).is_none());
+        settings.api_url = "http://tts.local/v1/audio/speech".to_string();
+        assert_eq!(from_settings(&settings).unwrap().name(), "http");
+    }
+}
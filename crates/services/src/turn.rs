@@ -0,0 +1,297 @@
+//! TURN relay credentials for conference clients.
+//!
+//! Credentials are coturn REST-API (`use-auth-secret`) pairs minted from
+//! `turn.shared_secret`, or the static `turn.username` / `turn.password`.
+//! With `turn.regions` set each region gets its own pair.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use hmac::{Hmac, Mac};
+use roomler_ai_config::TurnSettings;
+use sha1::Sha1;
+
+/// Lifetime of a minted credential.
+pub const CREDENTIAL_TTL_SECS: u64 = 86400;
+
+/// One TURN region parsed from `turn.regions`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TurnRegion {
+    pub name: String,
+    pub url: String,
+    /// ISO-3166 alpha-2 codes served by this region (upper-case).
+    pub countries: Vec<String>,
+}
+
+/// Parse `turn.regions`: comma-separated `name=turn:host:port@CC|CC|...`
+/// entries, e.g.
+/// `eu=turn:eu.turn.roomler.ai:3478@DE|AT|FR,us=turn:us.turn.roomler.ai:3478@US|CA`.
+/// The country list is optional; a region without one is only picked as the
+/// fallback. Malformed entries are skipped.
+pub fn parse_regions(raw: &str) -> Vec<TurnRegion> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|e| !e.is_empty())
+        .filter_map(|entry| {
+            let (name, rest) = entry.split_once('=')?;
+            let (url, countries) = match rest.rsplit_once('@') {
+                Some((url, cc)) => (
+                    url,
+                    cc.split('|')
+                        .map(|c| c.trim().to_ascii_uppercase())
+                        .filter(|c| !c.is_empty())
+                        .collect(),
+                ),
+                None => (rest, Vec::new()),
+            };
+            let name = name.trim();
+            let url = url.trim();
+            if name.is_empty() || !(url.starts_with("turn:") || url.starts_with("turns:")) {
+                return None;
+            }
+            Some(TurnRegion {
+                name: name.to_string(),
+                url: url.to_string(),
+                countries,
+            })
+        })
+        .collect()
+}
+
+/// Order regions nearest-first for a client: regions serving the client's
+/// country lead, the rest keep their configured order (the first configured
+/// region is the operator's default).
+pub fn order_for_country<'a>(
+    regions: &'a [TurnRegion],
+    country: Option<&str>,
+) -> Vec<&'a TurnRegion> {
+    let mut ordered: Vec<&TurnRegion> = regions.iter().collect();
+    if let Some(cc) = country {
+        // Stable sort keeps configuration order within each group.
+        ordered.sort_by_key(|r| !r.countries.iter().any(|c| c == cc));
+    }
+    ordered
+}
+
+/// Pick the regions handed to a client: the healthy ones, nearest first, at
+/// most `max`. If every region is down the client still gets them all —
+/// a relay that might answer beats none.
+pub fn select_regions(
+    ordered: Vec<&TurnRegion>,
+    is_healthy: impl Fn(&str) -> bool,
+    max: Option<usize>,
+) -> Vec<&TurnRegion> {
+    let healthy: Vec<&TurnRegion> = ordered
+        .iter()
+        .copied()
+        .filter(|r| is_healthy(&r.name))
+        .collect();
+    let mut selected = if healthy.is_empty() { ordered } else { healthy };
+    if let Some(max) = max.filter(|m| *m > 0) {
+        selected.truncate(max);
+    }
+    selected
+}
+
+/// Build TURN URLs with multiple transport variants. UDP TURN often fails
+/// behind NAT/firewalls, so include TCP and TLS fallbacks. Also emit
+/// `turn:HOST:443?transport=udp` because many corporate firewalls allow
+/// UDP/443 (QUIC) but block UDP/3478.
+fn expand_media_turn_url(url: &str) -> Vec<String> {
+    let mut urls: Vec<String> = vec![url.to_string()];
+    if url.starts_with("turn:") && !url.contains("?transport=") {
+        let turn_443 = url.replace(":3478", ":443");
+        urls.push(format!("{}?transport=udp", turn_443));
+        urls.push(format!("{}?transport=tcp", url));
+        // Derive TURNS (TLS) URL on port 5349
+        let turns_url = url.replacen("turn:", "turns:", 1).replace(":3478", ":5349");
+        urls.push(format!("{}?transport=tcp", turns_url));
+    }
+    urls
+}
+
+/// Mint a coturn REST-API (`use-auth-secret`) credential pair. The username
+/// carries the region so coturn's logs attribute relay allocations to it.
+fn mint_credential(secret: &str, user_hex: &str, region: Option<&str>) -> (String, String) {
+    let expiry = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+        + CREDENTIAL_TTL_SECS;
+    let username = match region {
+        Some(r) => format!("{}:{}:{}", expiry, user_hex, r),
+        None => format!("{}:{}", expiry, user_hex),
+    };
+    let mut mac =
+        Hmac::<Sha1>::new_from_slice(secret.as_bytes()).expect("HMAC key length is valid");
+    mac.update(username.as_bytes());
+    let credential = BASE64.encode(mac.finalize().into_bytes());
+    (username, credential)
+}
+
+/// The ICE servers for one client.
+#[derive(Debug, Clone, Default)]
+pub struct IceServers {
+    /// RTCIceServer-shaped entries; regional ones carry their `region`.
+    pub servers: Vec<serde_json::Value>,
+    /// The nearest region handed out, if `turn.regions` is set.
+    pub primary_region: Option<String>,
+}
+
+pub struct TurnService {
+    settings: TurnSettings,
+}
+
+impl TurnService {
+    pub fn new(settings: &TurnSettings) -> Self {
+        Self {
+            settings: settings.clone(),
+        }
+    }
+
+    /// The configured conference regions, in configuration order.
+    pub fn regions(&self) -> Vec<TurnRegion> {
+        self.settings
+            .regions
+            .as_deref()
+            .map(parse_regions)
+            .unwrap_or_default()
+    }
+
+    /// Build the ICE servers for a user. With `turn.regions` set, one entry
+    /// per healthy region, nearest-first for the client's country and capped
+    /// at `turn.max_regions`, each with its own credential; otherwise the
+    /// single `turn.url` entry, or none.
+    pub fn ice_servers(
+        &self,
+        user_hex: &str,
+        country: Option<&str>,
+        is_healthy: impl Fn(&str) -> bool,
+    ) -> IceServers {
+        let regions = self.regions();
+        if !regions.is_empty() {
+            let selected = select_regions(
+                order_for_country(&regions, country),
+                is_healthy,
+                self.settings.max_regions,
+            );
+            return IceServers {
+                primary_region: selected.first().map(|r| r.name.clone()),
+                servers: selected
+                    .into_iter()
+                    .map(|region| {
+                        let (username, credential) = self.credential(user_hex, Some(&region.name));
+                        serde_json::json!({
+                            "urls": expand_media_turn_url(&region.url),
+                            "username": username,
+                            "credential": credential,
+                            "region": region.name,
+                        })
+                    })
+                    .collect(),
+            };
+        }
+
+        let servers = match &self.settings.url {
+            Some(url) => {
+                let (username, credential) = self.credential(user_hex, None);
+                vec![serde_json::json!({
+                    "urls": expand_media_turn_url(url),
+                    "username": username,
+                    "credential": credential,
+                })]
+            }
+            None => vec![],
+        };
+        IceServers {
+            servers,
+            primary_region: None,
+        }
+    }
+
+    fn credential(&self, user_hex: &str, region: Option<&str>) -> (String, String) {
+        match &self.settings.shared_secret {
+            Some(secret) => mint_credential(secret, user_hex, region),
+            None => (
+                self.settings.username.clone().unwrap_or_default(),
+                self.settings.password.clone().unwrap_or_default(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_regions_with_and_without_countries() {
+        let regions = parse_regions(
+            "eu=turn:eu.example:3478@de|AT, us=turn:us.example:3478@US,ap=turn:ap.example:3478",
+        );
+        assert_eq!(regions.len(), 3);
+        assert_eq!(regions[0].name, "eu");
+        assert_eq!(regions[0].countries, vec!["DE", "AT"]);
+        assert_eq!(regions[2].url, "turn:ap.example:3478");
+        assert!(regions[2].countries.is_empty());
+    }
+
+    #[test]
+    fn skips_malformed_entries() {
+        let regions = parse_regions("noequals,eu=http://x:1@DE,=turn:x:3478,ok=turn:ok:3478");
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].name, "ok");
+    }
+
+    #[test]
+    fn orders_matching_region_first() {
+        let regions = parse_regions("eu=turn:eu:3478@DE,us=turn:us:3478@US,ap=turn:ap:3478@JP");
+        let ordered: Vec<&str> = order_for_country(&regions, Some("JP"))
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(ordered, vec!["ap", "eu", "us"]);
+
+        let fallback: Vec<&str> = order_for_country(&regions, Some("BR"))
+            .iter()
+            .map(|r| r.name.as_str())
+            .collect();
+        assert_eq!(fallback, vec!["eu", "us", "ap"]);
+    }
+
+    #[test]
+    fn drops_unhealthy_regions_and_caps_the_subset() {
+        let regions = parse_regions("eu=turn:eu:3478@DE,us=turn:us:3478@US,ap=turn:ap:3478@JP");
+        let names = |selected: Vec<&TurnRegion>| -> Vec<String> {
+            selected.iter().map(|r| r.name.clone()).collect()
+        };
+        let ordered = order_for_country(&regions, Some("DE"));
+        let selected = select_regions(ordered.clone(), |n| n != "eu", Some(1));
+        assert_eq!(names(selected), vec!["us"]);
+
+        // All down: hand out everything rather than nothing.
+        let selected = select_regions(ordered, |_| false, None);
+        assert_eq!(names(selected), vec!["eu", "us", "ap"]);
+    }
+
+    #[test]
+    fn mints_a_credential_per_region() {
+        let settings = TurnSettings {
+            url: None,
+            worker_urls: None,
+            regions: Some("eu=turn:eu:3478@DE,us=turn:us:3478@US".to_string()),
+            max_regions: None,
+            health_check_secs: 0,
+            username: None,
+            password: None,
+            shared_secret: Some("secret".to_string()),
+            force_relay: None,
+        };
+        let ice = TurnService::new(&settings).ice_servers("abc", Some("US"), |_| true);
+        assert_eq!(ice.primary_region.as_deref(), Some("us"));
+        assert_eq!(ice.servers.len(), 2);
+        let username = ice.servers[0]["username"].as_str().unwrap();
+        assert!(username.ends_with(":abc:us"), "{}", username);
+        assert_ne!(ice.servers[0]["credential"], ice.servers[1]["credential"]);
+    }
+}
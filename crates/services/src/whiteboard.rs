@@ -0,0 +1,173 @@
+//! In-memory whiteboard state for a room's call.
+//!
+//! Clients send ops (`add`, `update`, `delete`, `clear`); the server applies
+//! them to a [`Board`] in arrival order and stamps each with the next sequence
+//! number, so every participant replays the same history. Elements are opaque
+//! JSON objects identified by a string `id`; only the renderer in
+//! [`crate::export::whiteboard`] looks at their other fields.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// Upper bound on elements per board.
+pub const MAX_ELEMENTS: usize = 10_000;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WhiteboardOp {
+    /// Append an element, or replace one with the same id.
+    Add {
+        element: Value,
+    },
+    /// Shallow-merge fields into an existing element.
+    Update {
+        element: Value,
+    },
+    Delete {
+        element_id: String,
+    },
+    Clear,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Board {
+    /// Sequence number of the last applied op.
+    pub seq: i64,
+    /// Elements in z-order.
+    pub elements: Vec<Value>,
+}
+
+impl Board {
+    pub fn new(seq: i64, elements: Vec<Value>) -> Self {
+        Self { seq, elements }
+    }
+
+    /// Apply `op` and return its sequence number. A rejected op leaves the
+    /// board and its sequence untouched.
+    pub fn apply(&mut self, op: &WhiteboardOp) -> Result<i64, String> {
+        match op {
+            WhiteboardOp::Add { element } => {
+                let id = element_id(element)?;
+                match self.position(id) {
+                    Some(i) => self.elements[i] = element.clone(),
+                    None if self.elements.len() >= MAX_ELEMENTS => {
+                        return Err(format!("Boards are limited to {} elements", MAX_ELEMENTS));
+                    }
+                    None => self.elements.push(element.clone()),
+                }
+            }
+            WhiteboardOp::Update { element } => {
+                let id = element_id(element)?;
+                let i = self
+                    .position(id)
+                    .ok_or_else(|| format!("Unknown element {}", id))?;
+                if let (Some(target), Some(fields)) =
+                    (self.elements[i].as_object_mut(), element.as_object())
+                {
+                    for (k, v) in fields {
+                        target.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+            WhiteboardOp::Delete { element_id } => {
+                let i = self
+                    .position(element_id)
+                    .ok_or_else(|| format!("Unknown element {}", element_id))?;
+                self.elements.remove(i);
+            }
+            WhiteboardOp::Clear => self.elements.clear(),
+        }
+        self.seq += 1;
+        Ok(self.seq)
+    }
+
+    fn position(&self, id: &str) -> Option<usize> {
+        self.elements
+            .iter()
+            .position(|e| e.get("id").and_then(|v| v.as_str()) == Some(id))
+    }
+}
+
+fn element_id(element: &Value) -> Result<&str, String> {
+    if !element.is_object() {
+        return Err("Element must be an object".to_string());
+    }
+    element
+        .get("id")
+        .and_then(|v| v.as_str())
+        .filter(|id| !id.is_empty())
+        .ok_or_else(|| "Element id is required".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn op(v: Value) -> WhiteboardOp {
+        serde_json::from_value(v).unwrap()
+    }
+
+    #[test]
+    fn ops_are_sequenced_in_order() {
+        let mut board = Board::default();
+        let a = json!({ "type": "add", "element": { "id": "a", "kind": "rect", "x": 1 } });
+        let b = json!({ "type": "add", "element": { "id": "b", "kind": "text" } });
+        assert_eq!(board.apply(&op(a)), Ok(1));
+        assert_eq!(board.apply(&op(b)), Ok(2));
+        let update = json!({ "type": "update", "element": { "id": "a", "x": 5 } });
+        assert_eq!(board.apply(&op(update)), Ok(3));
+        assert_eq!(
+            board.elements[0],
+            json!({ "id": "a", "kind": "rect", "x": 5 })
+        );
+
+        assert_eq!(
+            board.apply(&op(json!({ "type": "delete", "element_id": "a" }))),
+            Ok(4)
+        );
+        assert_eq!(board.elements.len(), 1);
+        assert_eq!(board.apply(&op(json!({ "type": "clear" }))), Ok(5));
+        assert!(board.elements.is_empty());
+    }
+
+    #[test]
+    fn re_adding_an_element_replaces_it_in_place() {
+        let mut board = Board::default();
+        for id in ["a", "b"] {
+            board
+                .apply(&op(json!({ "type": "add", "element": { "id": id } })))
+                .unwrap();
+        }
+        board
+            .apply(&op(
+                json!({ "type": "add", "element": { "id": "a", "v": 2 } }),
+            ))
+            .unwrap();
+        assert_eq!(
+            board.elements,
+            vec![json!({ "id": "a", "v": 2 }), json!({ "id": "b" })]
+        );
+    }
+
+    #[test]
+    fn rejected_ops_do_not_advance_the_sequence() {
+        let mut board = Board::new(7, Vec::new());
+        assert!(
+            board
+                .apply(&op(json!({ "type": "update", "element": { "id": "x" } })))
+                .is_err()
+        );
+        assert!(
+            board
+                .apply(&op(json!({ "type": "delete", "element_id": "x" })))
+                .is_err()
+        );
+        assert!(
+            board
+                .apply(&op(json!({ "type": "add", "element": { "kind": "rect" } })))
+                .is_err()
+        );
+        assert_eq!(board.seq, 7);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+use std::time::Duration;
+
+async fn post_message(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    body: Value,
+) -> String {
+    let msg: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+            token,
+        )
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    msg["id"].as_str().unwrap().to_string()
+}
+
+async fn get_json(app: &TestApp, url: &str, token: &str) -> Value {
+    let resp = app.auth_get(url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "GET {}", url);
+    resp.json().await.unwrap()
+}
+
+fn ids_of(page: &Value) -> Vec<String> {
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|m| m["id"].as_str().unwrap().to_string())
+        .collect()
+}
+
+/// Posts five top-level messages (the first with one thread reply), ages
+/// the first three threads ten days and has the archiver move them.
+/// Returns the root ids oldest first and the reply id.
+async fn seed_archived(app: &TestApp, tid: &str, rid: &str, token: &str) -> (Vec<String>, String) {
+    let mut roots = Vec::new();
+    for i in 0..5 {
+        let body = serde_json::json!({ "content": format!("message {}", i) });
+        roots.push(post_message(app, tid, rid, token, body).await);
+    }
+    let reply = post_message(
+        app,
+        tid,
+        rid,
+        token,
+        serde_json::json!({ "content": "reply", "thread_id": roots[0] }),
+    )
+    .await;
+
+    let messages = app.db.collection::<bson::Document>("messages");
+    let base = bson::DateTime::now().timestamp_millis() - 10 * 86_400_000;
+    for (i, id) in roots[..3].iter().enumerate() {
+        let at = bson::DateTime::from_millis(base + i as i64 * 1000);
+        messages
+            .update_one(
+                doc! { "_id": ObjectId::parse_str(id).unwrap() },
+                doc! { "$set": { "created_at": at, "updated_at": at } },
+            )
+            .await
+            .unwrap();
+    }
+    let at = bson::DateTime::from_millis(base + 500);
+    messages
+        .update_one(
+            doc! { "_id": ObjectId::parse_str(&reply).unwrap() },
+            doc! { "$set": { "created_at": at, "updated_at": at } },
+        )
+        .await
+        .unwrap();
+    messages
+        .update_one(
+            doc! { "_id": ObjectId::parse_str(&roots[0]).unwrap() },
+            doc! { "$set": { "thread_metadata.last_reply_at": at } },
+        )
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_put(&format!("/api/tenant/{}/retention", tid), token)
+        .json(&serde_json::json!({ "archive_days": 7 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let archive = app.db.collection::<bson::Document>("messages_archive");
+    for _ in 0..50 {
+        if archive.count_documents(doc! {}).await.unwrap() == 4
+            && messages.count_documents(doc! {}).await.unwrap() == 2
+        {
+            return (roots, reply);
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    panic!("old threads were not archived");
+}
+
+#[tokio::test]
+async fn listing_merges_hot_and_archived_messages() {
+    let app = TestApp::spawn_with_settings(|s| s.archive.sweep_interval_secs = 1).await;
+    let tenant = app.seed_tenant("archive1").await;
+    let tid = &tenant.tenant_id;
+    let rid = &tenant.rooms[0].id;
+    let token = &tenant.member.access_token;
+    let (roots, _) = seed_archived(&app, tid, rid, &tenant.admin.access_token).await;
+    let newest_first: Vec<String> = roots.iter().rev().cloned().collect();
+    let list = format!("/api/tenant/{}/room/{}/message", tid, rid);
+
+    // Offset pages run across both collections.
+    let mut seen = Vec::new();
+    for page in 1..=3 {
+        let json = get_json(&app, &format!("{}?per_page=2&page={}", list, page), token).await;
+        assert_eq!(json["total"], 5);
+        assert_eq!(json["total_pages"], 3);
+        seen.extend(ids_of(&json));
+    }
+    assert_eq!(seen, newest_first);
+
+    // So does the keyset cursor, without gaps or repeats.
+    let mut seen = Vec::new();
+    let mut cursor = String::new();
+    loop {
+        let json = get_json(
+            &app,
+            &format!("{}?per_page=2&cursor={}", list, cursor),
+            token,
+        )
+        .await;
+        seen.extend(ids_of(&json));
+        match json["next_cursor"].as_str() {
+            Some(next) => cursor = next.to_string(),
+            None => break,
+        }
+    }
+    assert_eq!(seen, newest_first);
+
+    // Oldest first starts in the archive.
+    let json = get_json(&app, &format!("{}?sort=created_at&per_page=5", list), token).await;
+    assert_eq!(ids_of(&json), roots);
+}
+
+#[tokio::test]
+async fn archived_threads_are_readable_but_closed() {
+    let app = TestApp::spawn_with_settings(|s| s.archive.sweep_interval_secs = 1).await;
+    let tenant = app.seed_tenant("archive2").await;
+    let tid = &tenant.tenant_id;
+    let rid = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    let (roots, reply) = seed_archived(&app, tid, rid, token).await;
+
+    let replies = get_json(
+        &app,
+        &format!(
+            "/api/tenant/{}/room/{}/message/{}/thread",
+            tid, rid, roots[0]
+        ),
+        token,
+    )
+    .await;
+    assert_eq!(ids_of(&replies), vec![reply]);
+
+    let history = get_json(
+        &app,
+        &format!(
+            "/api/tenant/{}/room/{}/message/{}/history",
+            tid, rid, roots[1]
+        ),
+        token,
+    )
+    .await;
+    assert_eq!(history["content"], "message 1");
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room/{}/message", tid, rid), token)
+        .json(&serde_json::json!({ "content": "late", "thread_id": roots[0] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // Hot threads still take replies.
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/room/{}/message", tid, rid), token)
+        .json(&serde_json::json!({ "content": "fresh", "thread_id": roots[4] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn archive_days_are_validated() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("archive3").await;
+    let url = format!("/api/tenant/{}/retention", tenant.tenant_id);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "archive_days": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "archive_days": 90 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["archive_days"], 90);
+    assert!(body["message_days"].is_null());
+}
@@ -0,0 +1,359 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use reqwest::multipart;
+use serde_json::Value;
+
+/// Smallest byte string that passes the PNG signature check.
+const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+
+async fn upload_background(
+    app: &TestApp,
+    tenant_id: &str,
+    token: &str,
+    filename: &str,
+    bytes: &[u8],
+) -> reqwest::Response {
+    let part = multipart::Part::bytes(bytes.to_vec())
+        .file_name(filename.to_string())
+        .mime_str("application/octet-stream")
+        .unwrap();
+    app.client
+        .post(app.url(&format!("/api/tenant/{}/asset/background", tenant_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn list_backgrounds(app: &TestApp, tenant_id: &str, token: &str) -> Vec<Value> {
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/asset/background", tenant_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn background_library_lifecycle() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bg1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let resp = upload_background(&app, tid, admin, "beach.png", PNG).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let bg: Value = resp.json().await.unwrap();
+    // The type comes from the bytes, not the part header.
+    assert_eq!(bg["content_type"], "image/png");
+    assert!(bg.get("room_id").is_none());
+
+    // Every member can see and fetch the library.
+    let backgrounds = list_backgrounds(&app, tid, member).await;
+    assert_eq!(backgrounds.len(), 1);
+    assert_eq!(backgrounds[0]["id"], bg["id"]);
+    let bytes = app
+        .auth_get(bg["url"].as_str().unwrap(), member)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(&bytes[..], PNG);
+
+    // Backgrounds stay out of the tenant's file listing.
+    let files: Value = app
+        .auth_get(&format!("/api/tenant/{}/file", tid), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(files["total"], 0);
+
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/asset/background/{}",
+                tid,
+                bg["id"].as_str().unwrap()
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(list_backgrounds(&app, tid, member).await.is_empty());
+}
+
+#[tokio::test]
+async fn background_library_rules() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bg2").await;
+    let other = app.seed_tenant("bg2b").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    // Managing the library needs MANAGE_TENANT.
+    let resp = upload_background(&app, tid, &tenant.member.access_token, "a.png", PNG).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>";
+    let resp = upload_background(&app, tid, admin, "a.svg", svg).await;
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/asset/background", tid),
+            &other.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Ordinary files can't be deleted through the asset route.
+    let room_id = &tenant.rooms[0].id;
+    let part = multipart::Part::bytes(b"notes".to_vec())
+        .file_name("notes.txt")
+        .mime_str("text/plain")
+        .unwrap();
+    let file: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/room/{}/file/upload", tid, room_id)))
+        .header("Authorization", format!("Bearer {}", admin))
+        .multipart(multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/asset/background/{}",
+                tid,
+                file["id"].as_str().unwrap()
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+async fn upload_emoji(
+    app: &TestApp,
+    tenant_id: &str,
+    token: &str,
+    name: &str,
+    bytes: &[u8],
+) -> reqwest::Response {
+    let part = multipart::Part::bytes(bytes.to_vec())
+        .file_name("emoji.bin")
+        .mime_str("application/octet-stream")
+        .unwrap();
+    let form = multipart::Form::new()
+        .text("name", name.to_string())
+        .part("file", part);
+    app.client
+        .post(app.url(&format!("/api/tenant/{}/asset/emoji", tenant_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(form)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn emoji_pack_lifecycle() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("emoji1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let resp = upload_emoji(&app, tid, admin, "shipit", PNG).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let shipit: Value = resp.json().await.unwrap();
+    assert_eq!(shipit["name"], "shipit");
+    assert_eq!(shipit["is_animated"], false);
+    let resp = upload_emoji(&app, tid, admin, "party", b"GIF89a\x01\0\x01\0").await;
+    let party: Value = resp.json().await.unwrap();
+    assert_eq!(party["is_animated"], true);
+
+    // Members list the pack, by name, and can fetch the images.
+    let emojis: Vec<Value> = app
+        .auth_get(&format!("/api/tenant/{}/asset/emoji", tid), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let names: Vec<&str> = emojis.iter().map(|e| e["name"].as_str().unwrap()).collect();
+    assert_eq!(names, ["party", "shipit"]);
+    let bytes = app
+        .auth_get(shipit["url"].as_str().unwrap(), member)
+        .send()
+        .await
+        .unwrap()
+        .bytes()
+        .await
+        .unwrap();
+    assert_eq!(&bytes[..], PNG);
+
+    // Names are unique per tenant.
+    let resp = upload_emoji(&app, tid, admin, "shipit", PNG).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/asset/emoji/{}",
+                tid,
+                shipit["id"].as_str().unwrap()
+            ),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let emojis: Vec<Value> = app
+        .auth_get(&format!("/api/tenant/{}/asset/emoji", tid), member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(emojis.len(), 1);
+}
+
+#[tokio::test]
+async fn emoji_pack_rules() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("emoji2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    let resp = upload_emoji(&app, tid, &tenant.member.access_token, "nope", PNG).await;
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = upload_emoji(&app, tid, admin, "Bad-Name", PNG).await;
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = upload_emoji(&app, tid, admin, "svg", b"<svg/>").await;
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = upload_emoji(&app, tid, admin, "huge", &[PNG, &[0; 300 * 1024]].concat()).await;
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+#[tokio::test]
+async fn effects_state_is_relayed_and_replayed_to_joiners() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bg3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let bg: Value = upload_background(&app, tid, admin, "office.png", PNG)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tid), admin)
+        .json(&serde_json::json!({ "name": "Effects" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    for (token, action) in [(admin, "start"), (admin, "join"), (member, "join")] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tid, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let mut presenter = connect(&app, admin).await;
+    send(
+        &mut presenter,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut presenter, "media:transport_created").await;
+    send(
+        &mut presenter,
+        "media:effects_state",
+        serde_json::json!({ "room_id": room_id, "background": "blur" }),
+    )
+    .await;
+    // Let the server record it before the next connection joins.
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+
+    // A later joiner learns the current state on join.
+    let mut viewer = connect(&app, member).await;
+    send(
+        &mut viewer,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let replayed = next_of(&mut viewer, "media:effects_state").await;
+    assert_eq!(replayed["user_id"], tenant.admin.id.as_str());
+    assert_eq!(replayed["background"], "blur");
+    assert!(replayed["asset_id"].is_null());
+
+    send(
+        &mut presenter,
+        "media:effects_state",
+        serde_json::json!({ "room_id": room_id, "background": "image", "asset_id": bg["id"] }),
+    )
+    .await;
+    let update = next_of(&mut viewer, "media:effects_state").await;
+    assert_eq!(update["background"], "image");
+    assert_eq!(update["asset_id"], bg["id"]);
+
+    // Unknown assets are rejected back to the sender.
+    send(
+        &mut presenter,
+        "media:effects_state",
+        serde_json::json!({
+            "room_id": room_id,
+            "background": "image",
+            "asset_id": "000000000000000000000000",
+        }),
+    )
+    .await;
+    let error = next_of(&mut presenter, "media:error").await;
+    assert_eq!(error["message"], "Unknown background asset");
+}
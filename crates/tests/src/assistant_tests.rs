@@ -0,0 +1,251 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::Ws;
+use axum::{Json, Router, routing::post};
+use futures::{SinkExt, StreamExt};
+use serde_json::{Value, json};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+/// Read until a message of `msg_type` matching `pred` arrives.
+async fn next_matching(ws: &mut Ws, msg_type: &str, pred: impl Fn(&Value) -> bool) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let Ok(text) = msg.to_text() else { continue };
+            let Ok(parsed) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            if parsed["type"] == msg_type && pred(&parsed["data"]) {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no {} message", msg_type))
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+async fn enable_assistant(app: &TestApp, tenant_id: &str, token: &str) {
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/feature-flag/meeting_assistant", tenant_id),
+            token,
+        )
+        .json(&json!({ "mode": "on" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+async fn post_json(app: &TestApp, url: &str, token: &str, body: Value) -> reqwest::Response {
+    app.auth_post(url, token).json(&body).send().await.unwrap()
+}
+
+/// A chat completions API that records each prompt and answers it.
+async fn fake_chat(prompts: mpsc::UnboundedSender<String>) -> String {
+    let app = Router::new().route(
+        "/v1/chat/completions",
+        post(move |Json(body): Json<Value>| {
+            let prompts = prompts.clone();
+            async move {
+                let prompt = body["messages"][0]["content"].as_str().unwrap_or_default();
+                prompts.send(prompt.to_string()).unwrap();
+                Json(json!({
+                    "choices": [{ "message": { "role": "assistant", "content": " On Friday. " } }]
+                }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/v1/chat/completions", addr)
+}
+
+#[tokio::test]
+async fn assistant_answers_from_the_call_transcript() {
+    let (prompts_tx, mut prompts) = mpsc::unbounded_channel();
+    let api_url = fake_chat(prompts_tx).await;
+    let app = TestApp::spawn_with_settings(|s| {
+        s.assistant.provider = "http".into();
+        s.assistant.api_url = api_url;
+    })
+    .await;
+    let tenant = app.seed_tenant("assistant1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_url = format!("/api/tenant/{}/room/{}", tid, room_id);
+    enable_assistant(&app, tid, admin).await;
+
+    call_action(&app, tid, room_id, admin, "start").await;
+    call_action(&app, tid, room_id, admin, "join").await;
+    call_action(&app, tid, room_id, member, "join").await;
+    let resp = app
+        .auth_put(&format!("{}/call/transcription", room_url), member)
+        .json(&json!({ "opted_out": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let mut ws = connect(&app, admin).await;
+    let join = json!({ "type": "media:join", "data": { "room_id": room_id } });
+    ws.send(Message::Text(join.to_string().into()))
+        .await
+        .unwrap();
+    next_matching(&mut ws, "media:transport_created", |_| true).await;
+
+    // Only moderators feed the transcript
+    let transcript_url = format!("{}/call/transcript", room_url);
+    let segments = json!({ "segments": [
+        {
+            "user_id": tenant.admin.id,
+            "speaker_name": "Admin",
+            "text": "Let's ship on Friday",
+            "start_time": 1.0,
+            "end_time": 2.5,
+        },
+        {
+            "user_id": tenant.member.id,
+            "speaker_name": "Member",
+            "text": "Off the record",
+            "start_time": 3.0,
+            "end_time": 4.0,
+        },
+    ]});
+    let resp = post_json(&app, &transcript_url, member, segments.clone()).await;
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = post_json(&app, &transcript_url, admin, segments).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["accepted"], 2);
+    let line = next_matching(&mut ws, "media:transcript", |_| true).await;
+    assert_eq!(line["text"], "Let's ship on Friday");
+    assert_eq!(line["is_final"], true);
+    // The member opted out of transcription
+    let line = next_matching(&mut ws, "media:transcript", |_| true).await;
+    assert_eq!(line["user_id"], tenant.member.id.as_str());
+    assert_eq!(line["text"], "[not transcribed]");
+
+    let resp = post_json(
+        &app,
+        &format!("{}/call/message", room_url),
+        member,
+        json!({ "content": "/assistant ask When do we ship?" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let question: Value = resp.json().await.unwrap();
+    assert_eq!(question["author_type"], "user");
+
+    let prompt = tokio::time::timeout(std::time::Duration::from_secs(5), prompts.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(prompt.contains("Admin: Let's ship on Friday"));
+    assert!(prompt.contains("Member: [not transcribed]"));
+    assert!(!prompt.contains("Off the record"));
+    assert!(prompt.ends_with("When do we ship?"));
+
+    let answer = next_matching(&mut ws, "call:message:create", |m| {
+        m["author_type"] == "bot"
+    })
+    .await;
+    assert_eq!(answer["display_name"], "Assistant");
+    assert_eq!(answer["content"], "On Friday.");
+
+    // The answer is kept in the call's notes
+    let history_url = format!("{}/call/history", room_url);
+    let mut notes = Value::Null;
+    for _ in 0..50 {
+        let history: Value = app
+            .auth_get(&history_url, admin)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        notes = history["items"][0]["assistant_notes"].clone();
+        if notes.as_array().is_some_and(|n| !n.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    assert_eq!(notes[0]["asked_by"], tenant.member.id.as_str());
+    assert_eq!(notes[0]["question"], "When do we ship?");
+    assert_eq!(notes[0]["answer"], "On Friday.");
+}
+
+#[tokio::test]
+async fn assistant_commands_are_checked() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("assistant2").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let message_url = format!("/api/tenant/{}/room/{}/call/message", tid, room_id);
+    let ask = json!({ "content": "/assistant ask Anything?" });
+
+    // Off by default
+    let resp = post_json(&app, &message_url, admin, ask.clone()).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    enable_assistant(&app, tid, admin).await;
+    let resp = post_json(&app, &message_url, admin, ask.clone()).await;
+    assert_eq!(resp.status().as_u16(), 409);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["message"], "The meeting assistant is not configured");
+
+    let resp = post_json(
+        &app,
+        &message_url,
+        admin,
+        json!({ "content": "/assistant dance" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Other messages are left alone
+    let resp = post_json(
+        &app,
+        &message_url,
+        admin,
+        json!({ "content": "/assistants are people too" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = post_json(
+        &app,
+        &format!("/api/tenant/{}/room/{}/call/transcript", tid, room_id),
+        admin,
+        json!({ "segments": [] }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 409);
+}
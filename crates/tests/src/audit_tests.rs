@@ -0,0 +1,192 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+async fn audit(app: &TestApp, tenant_id: &str, token: &str, query: &str) -> reqwest::Response {
+    app.auth_get(&format!("/api/tenant/{}/audit{}", tenant_id, query), token)
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn room_delete_is_audited_with_snapshot() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audit1").await;
+    let room = &tenant.rooms[0];
+
+    let resp = app
+        .auth_delete(
+            &format!("/api/tenant/{}/room/{}", tenant.tenant_id, room.id),
+            &tenant.admin.access_token,
+        )
+        .header("User-Agent", "audit-test")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = audit(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "?filter[action]=room.delete",
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let items = body["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+
+    let entry = &items[0];
+    assert_eq!(entry["actor_id"], tenant.admin.id.as_str());
+    assert_eq!(entry["target_type"], "room");
+    assert_eq!(entry["target_id"], room.id.as_str());
+    assert_eq!(entry["user_agent"], "audit-test");
+    let name = entry["changes"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|c| c["field"] == "name")
+        .expect("name change recorded");
+    assert_eq!(name["old_value"], room.name.as_str());
+    assert!(name["new_value"].is_null());
+}
+
+#[tokio::test]
+async fn role_update_records_before_and_after() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audit2").await;
+
+    let role: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/role", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "mod", "permissions": 1 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let role_id = role["id"].as_str().unwrap();
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/role/{}", tenant.tenant_id, role_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "permissions": 3 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let body: Value = audit(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        &format!("?filter[target_id]={}", role_id),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let actions: Vec<&str> = body["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["action"].as_str().unwrap())
+        .collect();
+    // Newest first.
+    assert_eq!(actions, vec!["role.update", "role.create"]);
+
+    let changes = body["items"][0]["changes"].as_array().unwrap();
+    let perms = changes
+        .iter()
+        .find(|c| c["field"] == "permissions")
+        .expect("permissions change recorded");
+    assert_eq!(perms["old_value"], 1);
+    assert_eq!(perms["new_value"], 3);
+    assert!(
+        changes.iter().all(|c| c["field"] != "name"),
+        "unchanged fields are not recorded"
+    );
+}
+
+#[tokio::test]
+async fn member_remove_is_audited_with_reason() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audit3").await;
+
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/member/{}?reason=spam",
+                tenant.tenant_id, tenant.member.id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The removed user no longer has access to the tenant.
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let body: Value = audit(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "?filter[target_type]=member",
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let entry = &body["items"][0];
+    assert_eq!(entry["action"], "member.remove");
+    assert_eq!(entry["target_id"], tenant.member.id.as_str());
+    assert_eq!(entry["reason"], "spam");
+}
+
+#[tokio::test]
+async fn audit_log_requires_manage_tenant() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audit4").await;
+
+    let resp = audit(&app, &tenant.tenant_id, &tenant.member.access_token, "").await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Members can't remove other members either.
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/member/{}",
+                tenant.tenant_id, tenant.admin.id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = audit(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "?filter[ip]=1.2.3.4",
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 400);
+}
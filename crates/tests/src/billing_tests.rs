@@ -310,153 +310,236 @@ async fn webhook_rejects_invalid_signature() {
     assert_eq!(resp.status().as_u16(), 401);
 }
 
-#[tokio::test]
-async fn webhook_with_valid_signature_processes_checkout_completed() {
-    let app = TestApp::spawn_with_settings(|s| {
-        s.stripe.webhook_secret = "whsec_test_secret_for_billing_tests".to_string();
-    })
-    .await;
+const WEBHOOK_SECRET: &str = "whsec_test_secret_for_billing_tests";
 
-    let seeded = app.seed_tenant("webhook-checkout").await;
-
-    // Build the webhook payload
-    let payload = serde_json::json!({
-        "type": "checkout.session.completed",
-        "data": {
-            "object": {
-                "metadata": {
-                    "tenant_id": seeded.tenant_id,
-                    "plan": "pro",
-                },
-                "subscription": "sub_test_123",
-                "customer": "cus_test_456",
-            }
-        }
-    });
-    let payload_bytes = serde_json::to_vec(&payload).unwrap();
+async fn spawn_with_webhook_secret() -> TestApp {
+    TestApp::spawn_with_settings(|s| {
+        s.stripe.webhook_secret = WEBHOOK_SECRET.to_string();
+    })
+    .await
+}
 
-    // Compute valid HMAC-SHA256 signature
-    let timestamp = "1234567890";
-    let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-    let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-    let sig_header = format!("t={},v1={}", timestamp, sig);
+fn event(id: &str, created: i64, event_type: &str, object: Value) -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "id": id,
+        "created": created,
+        "type": event_type,
+        "data": { "object": object },
+    }))
+    .unwrap()
+}
 
-    let resp = app
-        .client
+/// POST `payload` signed at `signed_at` (unix seconds).
+async fn post_signed(app: &TestApp, payload: Vec<u8>, signed_at: i64) -> reqwest::Response {
+    let signed_payload = format!("{}.{}", signed_at, String::from_utf8_lossy(&payload));
+    let sig = compute_hmac_sha256(WEBHOOK_SECRET, &signed_payload);
+    app.client
         .post(app.url("/api/stripe/webhook"))
         .header("Content-Type", "application/json")
-        .header("stripe-signature", &sig_header)
-        .body(payload_bytes)
+        .header("stripe-signature", format!("t={},v1={}", signed_at, sig))
+        .body(payload)
         .send()
         .await
-        .unwrap();
+        .unwrap()
+}
 
+/// Deliver an event now and expect it to be acknowledged.
+async fn deliver(app: &TestApp, id: &str, created: i64, event_type: &str, object: Value) {
+    let now = chrono::Utc::now().timestamp();
+    let resp = post_signed(app, event(id, created, event_type, object), now).await;
     assert_eq!(resp.status().as_u16(), 200);
+}
 
-    // Verify tenant was upgraded to Pro in the database
+/// Wait for the billing worker to process the event.
+async fn wait_processed(app: &TestApp, id: &str) -> bson::Document {
+    use bson::doc;
+    for _ in 0..50 {
+        let record = app
+            .db
+            .collection::<bson::Document>("stripe_events")
+            .find_one(doc! { "event_id": id })
+            .await
+            .unwrap();
+        if let Some(record) = record
+            && record.get_str("status") == Ok("processed")
+        {
+            return record;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("Stripe event {} was not processed", id);
+}
+
+async fn tenant_doc(app: &TestApp, tenant_id: &str) -> bson::Document {
     use bson::{doc, oid::ObjectId};
-    let tenant_oid = ObjectId::parse_str(&seeded.tenant_id).unwrap();
-    let tenant: bson::Document = app
-        .db
+    app.db
         .collection::<bson::Document>("tenants")
-        .find_one(doc! { "_id": tenant_oid })
+        .find_one(doc! { "_id": ObjectId::parse_str(tenant_id).unwrap() })
         .await
         .unwrap()
-        .expect("Tenant not found in DB");
+        .expect("Tenant not found in DB")
+}
+
+async fn checkout(app: &TestApp, tenant_id: &str, plan: &str, sub: &str, created: i64) {
+    let id = format!("evt_checkout_{}", sub);
+    deliver(
+        app,
+        &id,
+        created,
+        "checkout.session.completed",
+        serde_json::json!({
+            "metadata": { "tenant_id": tenant_id, "plan": plan },
+            "subscription": sub,
+            "customer": format!("cus_{}", sub),
+        }),
+    )
+    .await;
+    wait_processed(app, &id).await;
+}
 
+#[tokio::test]
+async fn webhook_rejects_replayed_signature() {
+    let app = spawn_with_webhook_secret().await;
+    let payload = event(
+        "evt_old",
+        1_234_567_890,
+        "invoice.paid",
+        serde_json::json!({}),
+    );
+
+    // Correctly signed, but ten minutes ago.
+    let signed_at = chrono::Utc::now().timestamp() - 600;
+    let resp = post_signed(&app, payload, signed_at).await;
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["message"], "Webhook timestamp outside the tolerance");
+}
+
+#[tokio::test]
+async fn webhook_with_valid_signature_processes_checkout_completed() {
+    let app = spawn_with_webhook_secret().await;
+    let seeded = app.seed_tenant("webhook-checkout").await;
+    let now = chrono::Utc::now().timestamp();
+
+    checkout(&app, &seeded.tenant_id, "pro", "sub_test_123", now).await;
+
+    let tenant = tenant_doc(&app, &seeded.tenant_id).await;
     assert_eq!(tenant.get_str("plan").unwrap(), "pro");
 
-    // Verify billing info was set
     let billing = tenant
         .get_document("billing")
         .expect("billing should exist");
-    assert_eq!(billing.get_str("customer_id").unwrap(), "cus_test_456");
+    assert_eq!(billing.get_str("customer_id").unwrap(), "cus_sub_test_123");
     assert_eq!(billing.get_str("subscription_id").unwrap(), "sub_test_123");
     assert_eq!(billing.get_str("status").unwrap(), "active");
     assert_eq!(billing.get_bool("cancel_at_period_end").unwrap(), false);
+    assert!(billing.get_datetime("synced_at").is_ok());
 }
 
 #[tokio::test]
-async fn webhook_subscription_deleted_reverts_to_free() {
-    let app = TestApp::spawn_with_settings(|s| {
-        s.stripe.webhook_secret = "whsec_test_secret_for_billing_tests".to_string();
-    })
+async fn webhook_redelivery_is_processed_once() {
+    use bson::doc;
+
+    let app = spawn_with_webhook_secret().await;
+    let seeded = app.seed_tenant("webhook-dedupe").await;
+    let now = chrono::Utc::now().timestamp();
+    checkout(&app, &seeded.tenant_id, "pro", "sub_dup_123", now).await;
+
+    let object = serde_json::json!({ "subscription": "sub_dup_123" });
+    deliver(
+        &app,
+        "evt_dup",
+        now + 1,
+        "invoice.payment_failed",
+        object.clone(),
+    )
     .await;
+    let first = wait_processed(&app, "evt_dup").await;
+    deliver(&app, "evt_dup", now + 1, "invoice.payment_failed", object).await;
 
-    let seeded = app.seed_tenant("webhook-delete").await;
+    let count = app
+        .db
+        .collection::<bson::Document>("stripe_events")
+        .count_documents(doc! { "event_id": "evt_dup" })
+        .await
+        .unwrap();
+    assert_eq!(count, 1);
+    let again = wait_processed(&app, "evt_dup").await;
+    let attempts = again
+        .get("attempts")
+        .and_then(|a| a.as_i64().or(a.as_i32().map(i64::from)));
+    assert_eq!(attempts, Some(1));
+    assert_eq!(
+        first.get_datetime("processed_at").unwrap(),
+        again.get_datetime("processed_at").unwrap()
+    );
+}
 
-    // First, simulate a checkout.session.completed to set up billing
-    {
-        let payload = serde_json::json!({
-            "type": "checkout.session.completed",
-            "data": {
-                "object": {
-                    "metadata": {
-                        "tenant_id": seeded.tenant_id,
-                        "plan": "business",
-                    },
-                    "subscription": "sub_del_123",
-                    "customer": "cus_del_456",
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567890";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
+#[tokio::test]
+async fn webhook_ignores_events_older_than_the_last_applied() {
+    let app = spawn_with_webhook_secret().await;
+    let seeded = app.seed_tenant("webhook-order").await;
+    let now = chrono::Utc::now().timestamp();
+    checkout(
+        &app,
+        &seeded.tenant_id,
+        "business",
+        "sub_ord_123",
+        now - 100,
+    )
+    .await;
 
-    // Now send customer.subscription.deleted
-    {
-        let payload = serde_json::json!({
-            "type": "customer.subscription.deleted",
-            "data": {
-                "object": {
-                    "id": "sub_del_123",
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567891";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
+    deliver(
+        &app,
+        "evt_deleted",
+        now,
+        "customer.subscription.deleted",
+        serde_json::json!({ "id": "sub_ord_123" }),
+    )
+    .await;
+    wait_processed(&app, "evt_deleted").await;
+
+    // Created before the deletion, delivered after it.
+    deliver(
+        &app,
+        "evt_late_update",
+        now - 50,
+        "customer.subscription.updated",
+        serde_json::json!({
+            "id": "sub_ord_123",
+            "status": "active",
+            "cancel_at_period_end": true,
+        }),
+    )
+    .await;
+    wait_processed(&app, "evt_late_update").await;
 
-    // Verify tenant was reverted to Free plan
-    use bson::{doc, oid::ObjectId};
-    let tenant_oid = ObjectId::parse_str(&seeded.tenant_id).unwrap();
-    let tenant: bson::Document = app
-        .db
-        .collection::<bson::Document>("tenants")
-        .find_one(doc! { "_id": tenant_oid })
-        .await
-        .unwrap()
-        .expect("Tenant not found in DB");
+    let tenant = tenant_doc(&app, &seeded.tenant_id).await;
+    assert_eq!(tenant.get_str("plan").unwrap(), "free");
+    let billing = tenant.get_document("billing").unwrap();
+    assert_eq!(billing.get_str("status").unwrap(), "canceled");
+    assert_eq!(billing.get_bool("cancel_at_period_end").unwrap(), false);
+}
 
+#[tokio::test]
+async fn webhook_subscription_deleted_reverts_to_free() {
+    let app = spawn_with_webhook_secret().await;
+    let seeded = app.seed_tenant("webhook-delete").await;
+    let now = chrono::Utc::now().timestamp();
+    checkout(&app, &seeded.tenant_id, "business", "sub_del_123", now).await;
+
+    deliver(
+        &app,
+        "evt_del",
+        now + 1,
+        "customer.subscription.deleted",
+        serde_json::json!({ "id": "sub_del_123" }),
+    )
+    .await;
+    wait_processed(&app, "evt_del").await;
+
+    let tenant = tenant_doc(&app, &seeded.tenant_id).await;
     assert_eq!(tenant.get_str("plan").unwrap(), "free");
     let billing = tenant
         .get_document("billing")
@@ -466,88 +549,27 @@ async fn webhook_subscription_deleted_reverts_to_free() {
 
 #[tokio::test]
 async fn webhook_subscription_updated_sets_status() {
-    let app = TestApp::spawn_with_settings(|s| {
-        s.stripe.webhook_secret = "whsec_test_secret_for_billing_tests".to_string();
-    })
-    .await;
-
+    let app = spawn_with_webhook_secret().await;
     let seeded = app.seed_tenant("webhook-update").await;
+    let now = chrono::Utc::now().timestamp();
+    checkout(&app, &seeded.tenant_id, "pro", "sub_upd_123", now).await;
+
+    deliver(
+        &app,
+        "evt_upd",
+        now + 1,
+        "customer.subscription.updated",
+        serde_json::json!({
+            "id": "sub_upd_123",
+            "status": "active",
+            "cancel_at_period_end": true,
+            "current_period_end": 1700000000,
+        }),
+    )
+    .await;
+    wait_processed(&app, "evt_upd").await;
 
-    // Set up billing via checkout.session.completed
-    {
-        let payload = serde_json::json!({
-            "type": "checkout.session.completed",
-            "data": {
-                "object": {
-                    "metadata": {
-                        "tenant_id": seeded.tenant_id,
-                        "plan": "pro",
-                    },
-                    "subscription": "sub_upd_123",
-                    "customer": "cus_upd_456",
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567890";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
-
-    // Send customer.subscription.updated with cancel_at_period_end = true
-    {
-        let payload = serde_json::json!({
-            "type": "customer.subscription.updated",
-            "data": {
-                "object": {
-                    "id": "sub_upd_123",
-                    "status": "active",
-                    "cancel_at_period_end": true,
-                    "current_period_end": 1700000000,
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567892";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
-
-    // Verify billing info was updated
-    use bson::{doc, oid::ObjectId};
-    let tenant_oid = ObjectId::parse_str(&seeded.tenant_id).unwrap();
-    let tenant: bson::Document = app
-        .db
-        .collection::<bson::Document>("tenants")
-        .find_one(doc! { "_id": tenant_oid })
-        .await
-        .unwrap()
-        .expect("Tenant not found in DB");
-
+    let tenant = tenant_doc(&app, &seeded.tenant_id).await;
     let billing = tenant
         .get_document("billing")
         .expect("billing should exist");
@@ -557,85 +579,22 @@ async fn webhook_subscription_updated_sets_status() {
 
 #[tokio::test]
 async fn webhook_invoice_payment_failed_sets_past_due() {
-    let app = TestApp::spawn_with_settings(|s| {
-        s.stripe.webhook_secret = "whsec_test_secret_for_billing_tests".to_string();
-    })
-    .await;
-
+    let app = spawn_with_webhook_secret().await;
     let seeded = app.seed_tenant("webhook-pastdue").await;
+    let now = chrono::Utc::now().timestamp();
+    checkout(&app, &seeded.tenant_id, "pro", "sub_pd_123", now).await;
+
+    deliver(
+        &app,
+        "evt_pd",
+        now + 1,
+        "invoice.payment_failed",
+        serde_json::json!({ "subscription": "sub_pd_123" }),
+    )
+    .await;
+    wait_processed(&app, "evt_pd").await;
 
-    // Set up billing first
-    {
-        let payload = serde_json::json!({
-            "type": "checkout.session.completed",
-            "data": {
-                "object": {
-                    "metadata": {
-                        "tenant_id": seeded.tenant_id,
-                        "plan": "pro",
-                    },
-                    "subscription": "sub_pd_123",
-                    "customer": "cus_pd_456",
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567890";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
-
-    // Send invoice.payment_failed
-    {
-        let payload = serde_json::json!({
-            "type": "invoice.payment_failed",
-            "data": {
-                "object": {
-                    "subscription": "sub_pd_123",
-                }
-            }
-        });
-        let payload_bytes = serde_json::to_vec(&payload).unwrap();
-        let timestamp = "1234567893";
-        let signed_payload = format!("{}.{}", timestamp, String::from_utf8_lossy(&payload_bytes));
-        let sig = compute_hmac_sha256("whsec_test_secret_for_billing_tests", &signed_payload);
-        let sig_header = format!("t={},v1={}", timestamp, sig);
-
-        let resp = app
-            .client
-            .post(app.url("/api/stripe/webhook"))
-            .header("Content-Type", "application/json")
-            .header("stripe-signature", &sig_header)
-            .body(payload_bytes)
-            .send()
-            .await
-            .unwrap();
-        assert_eq!(resp.status().as_u16(), 200);
-    }
-
-    // Verify billing status is now past_due
-    use bson::{doc, oid::ObjectId};
-    let tenant_oid = ObjectId::parse_str(&seeded.tenant_id).unwrap();
-    let tenant: bson::Document = app
-        .db
-        .collection::<bson::Document>("tenants")
-        .find_one(doc! { "_id": tenant_oid })
-        .await
-        .unwrap()
-        .expect("Tenant not found in DB");
-
+    let tenant = tenant_doc(&app, &seeded.tenant_id).await;
     let billing = tenant
         .get_document("billing")
         .expect("billing should exist");
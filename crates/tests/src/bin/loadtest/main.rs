@@ -0,0 +1,162 @@
+//! Conference load test against a running server.
+//!
+//! Every participant is a real client: it signs up, joins the call over
+//! REST, does the media signaling over `/ws` and sets up both mediasoup
+//! transports the way mediasoup-client does, with a webrtc-rs peer
+//! connection per transport. It then sends a synthetic Opus stream and
+//! consumes everyone else's, so the server's Rust media path carries real
+//! RTP. Participants ramp in per the stages of a scenario file; see
+//! `crates/tests/scenarios/` and docs/testing.md.
+//!
+//! ```text
+//! cargo run --release -p roomler-ai-tests --bin loadtest -- scenario.toml [--json report.json]
+//! ```
+
+mod media;
+mod participant;
+mod report;
+mod scenario;
+mod sdp;
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Context;
+use clap::Parser;
+use roomler_ai_client::{Client, CreateRoomRequest};
+use tokio::sync::watch;
+use tracing::info;
+
+use crate::participant::Run;
+use crate::report::{Metrics, ServerSnapshot, StageReport};
+use crate::scenario::Scenario;
+
+#[derive(Parser)]
+#[command(about = "Ramp simulated participants into a call and report how it held up")]
+struct Args {
+    /// Scenario file (TOML).
+    scenario: PathBuf,
+    /// Also write the report as JSON here.
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| "loadtest=info".into()),
+        )
+        .init();
+    let args = Args::parse();
+    let scenario = Scenario::load(&args.scenario)?;
+
+    let organizer = Client::new(scenario.base_url.clone());
+    organizer
+        .login(&scenario.organizer.login, &scenario.organizer.password)
+        .await
+        .context("organizer login")?;
+    let tenant_id = match &scenario.organizer.tenant_id {
+        Some(id) => id.clone(),
+        None => {
+            organizer
+                .tenants()
+                .await?
+                .into_iter()
+                .next()
+                .context("the organizer has no tenant")?
+                .id
+        }
+    };
+    let tag = uuid::Uuid::new_v4().simple().to_string()[..8].to_string();
+    let room_id = match &scenario.organizer.room_id {
+        Some(id) => id.clone(),
+        None => {
+            organizer
+                .create_room(
+                    &tenant_id,
+                    &CreateRoomRequest {
+                        name: format!("loadtest-{tag}"),
+                        parent_id: None,
+                        is_open: true,
+                        media_settings: None,
+                    },
+                )
+                .await
+                .context("create room")?
+                .id
+        }
+    };
+    let call = organizer
+        .call_start(&tenant_id, &room_id)
+        .await
+        .context("start call")?;
+    info!(%tenant_id, %room_id, %tag, "call started");
+
+    let stages = scenario.stages.clone();
+    let run = Arc::new(Run {
+        scenario,
+        organizer: organizer.clone(),
+        tenant_id: tenant_id.clone(),
+        room_id: room_id.clone(),
+        tag,
+        rtp_capabilities: call.rtp_capabilities,
+        api: media::api()?,
+        metrics: Metrics::default(),
+    });
+
+    let (stop_tx, stop_rx) = watch::channel(false);
+    let mut participants = Vec::new();
+    let mut reports = Vec::new();
+    for (i, stage) in stages.iter().enumerate() {
+        let new = stage.participants - participants.len();
+        info!(
+            stage = i + 1,
+            participants = stage.participants,
+            "ramping up {new} over {:?}",
+            stage.ramp()
+        );
+        let gap = stage.ramp() / new.max(1) as u32;
+        for _ in 0..new {
+            let n = participants.len();
+            participants.push(tokio::spawn(participant::run(
+                run.clone(),
+                n,
+                stop_rx.clone(),
+            )));
+            tokio::time::sleep(gap).await;
+        }
+        tokio::time::sleep(stage.hold()).await;
+        let report = StageReport::new(
+            i + 1,
+            stage.participants,
+            run.metrics.take(),
+            ServerSnapshot::fetch(&organizer).await,
+        );
+        info!(
+            stage = i + 1,
+            joins_ok = report.joins_ok,
+            joins_failed = report.joins_failed,
+            consume_failed = report.consume_failed,
+            "stage done"
+        );
+        reports.push(report);
+    }
+
+    info!("stopping {} participants", participants.len());
+    let _ = stop_tx.send(true);
+    for participant in participants {
+        let _ = participant.await;
+    }
+    if let Err(e) = organizer.call_end(&tenant_id, &room_id).await {
+        info!("call end failed: {e}");
+    }
+
+    print!("{}", report::table(&reports));
+    if let Some(path) = args.json {
+        std::fs::write(&path, serde_json::to_vec_pretty(&reports)?)
+            .with_context(|| format!("write {}", path.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,194 @@
+//! Headless mediasoup-client: a webrtc-rs peer connection per transport,
+//! negotiated against the server's parameters with the SDP from [`sdp`].
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, anyhow};
+use bytes::Bytes;
+use serde_json::Value;
+use tokio::sync::mpsc;
+use webrtc::api::interceptor_registry::register_default_interceptors;
+use webrtc::api::media_engine::{MIME_TYPE_OPUS, MediaEngine};
+use webrtc::api::{API, APIBuilder};
+use webrtc::interceptor::registry::Registry;
+use webrtc::media::Sample;
+use webrtc::peer_connection::RTCPeerConnection;
+use webrtc::peer_connection::configuration::RTCConfiguration;
+use webrtc::peer_connection::sdp::session_description::RTCSessionDescription;
+use webrtc::rtp_transceiver::rtp_codec::RTCRtpCodecCapability;
+use webrtc::track::track_local::TrackLocal;
+use webrtc::track::track_local::track_local_static_sample::TrackLocalStaticSample;
+
+use crate::sdp::{self, Fingerprint, RemoteTrack, ServerTransport};
+
+/// An Opus frame of silence (TOC byte for a 20 ms CELT frame + an empty
+/// payload), so the stream costs the server nothing to forward.
+const OPUS_SILENCE: &[u8] = &[0xf8, 0xff, 0xfe];
+const FRAME: Duration = Duration::from_millis(20);
+
+pub fn api() -> anyhow::Result<API> {
+    let mut engine = MediaEngine::default();
+    engine.register_default_codecs()?;
+    let registry = register_default_interceptors(Registry::new(), &mut engine)?;
+    Ok(APIBuilder::new()
+        .with_media_engine(engine)
+        .with_interceptor_registry(registry)
+        .build())
+}
+
+/// The send transport with one synthetic Opus track.
+pub struct SendTransport {
+    pc: Arc<RTCPeerConnection>,
+    track: Arc<TrackLocalStaticSample>,
+    offer: String,
+    mid: String,
+}
+
+impl SendTransport {
+    /// Create the peer connection and apply the server's side. The returned
+    /// fingerprint goes to `media:connect_transport`.
+    pub async fn open(
+        api: &API,
+        server: &ServerTransport,
+        label: &str,
+    ) -> anyhow::Result<(Self, Fingerprint)> {
+        let pc = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await?);
+        let track = Arc::new(TrackLocalStaticSample::new(
+            RTCRtpCodecCapability {
+                mime_type: MIME_TYPE_OPUS.to_string(),
+                clock_rate: 48000,
+                channels: 2,
+                sdp_fmtp_line: "minptime=10;useinbandfec=1".to_string(),
+                rtcp_feedback: vec![],
+            },
+            format!("{label}-audio"),
+            label.to_string(),
+        ));
+        let sender = pc
+            .add_track(track.clone() as Arc<dyn TrackLocal + Send + Sync>)
+            .await?;
+        // Sender reports have to be read for the interceptors to run.
+        tokio::spawn(async move {
+            let mut buf = vec![0u8; 1500];
+            while sender.read(&mut buf).await.is_ok() {}
+        });
+
+        let offer = pc.create_offer(None).await?;
+        pc.set_local_description(offer).await?;
+        let offer = pc.local_description().await.context("no local offer")?.sdp;
+        let fingerprint = sdp::local_fingerprint(&offer).context("offer without fingerprint")?;
+        let answer = sdp::send_answer(server, &offer)?;
+        pc.set_remote_description(RTCSessionDescription::answer(answer)?)
+            .await?;
+        let mid = pc
+            .get_transceivers()
+            .await
+            .first()
+            .and_then(|t| t.mid())
+            .map(|mid| mid.to_string())
+            .ok_or_else(|| anyhow!("audio transceiver without a mid"))?;
+
+        Ok((
+            Self {
+                pc,
+                track,
+                offer,
+                mid,
+            },
+            fingerprint,
+        ))
+    }
+
+    /// `rtp_parameters` for `media:produce`.
+    pub fn rtp_parameters(&self) -> anyhow::Result<Value> {
+        sdp::opus_rtp_parameters(&self.offer, &self.mid)
+    }
+
+    /// Send silence every 20 ms until the transport is closed.
+    pub fn start_audio(&self) {
+        let track = self.track.clone();
+        tokio::spawn(async move {
+            let mut tick = tokio::time::interval(FRAME);
+            loop {
+                tick.tick().await;
+                let sample = Sample {
+                    data: Bytes::from_static(OPUS_SILENCE),
+                    duration: FRAME,
+                    ..Default::default()
+                };
+                if track.write_sample(&sample).await.is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    pub async fn close(&self) {
+        let _ = self.pc.close().await;
+    }
+}
+
+/// The recv transport. Every consumer is a new send-only section of the
+/// server's offer; the first RTP packet of each is reported by ssrc.
+pub struct RecvTransport {
+    pc: Arc<RTCPeerConnection>,
+    server: ServerTransport,
+    tracks: Vec<RemoteTrack>,
+    version: u64,
+}
+
+impl RecvTransport {
+    pub async fn open(
+        api: &API,
+        server: ServerTransport,
+        first_rtp: mpsc::UnboundedSender<u32>,
+    ) -> anyhow::Result<Self> {
+        let pc = Arc::new(api.new_peer_connection(RTCConfiguration::default()).await?);
+        pc.on_track(Box::new(move |track, _, _| {
+            let first_rtp = first_rtp.clone();
+            Box::pin(async move {
+                if track.read_rtp().await.is_ok() {
+                    let _ = first_rtp.send(track.ssrc());
+                }
+                // Keep reading so the jitter buffer doesn't back up.
+                while track.read_rtp().await.is_ok() {}
+            })
+        }));
+        Ok(Self {
+            pc,
+            server,
+            tracks: Vec::new(),
+            version: 0,
+        })
+    }
+
+    /// Renegotiate with `track` added. Returns the local fingerprint on the
+    /// first call, when the transport still has to be connected.
+    pub async fn add(&mut self, track: RemoteTrack) -> anyhow::Result<Option<Fingerprint>> {
+        self.tracks.push(track);
+        self.version += 1;
+        let offer = sdp::recv_offer(&self.server, &self.tracks, self.version);
+        self.pc
+            .set_remote_description(RTCSessionDescription::offer(offer)?)
+            .await?;
+        let answer = self.pc.create_answer(None).await?;
+        self.pc.set_local_description(answer).await?;
+        if self.version > 1 {
+            return Ok(None);
+        }
+        let answer = self
+            .pc
+            .local_description()
+            .await
+            .context("no local answer")?
+            .sdp;
+        Ok(Some(
+            sdp::local_fingerprint(&answer).context("answer without fingerprint")?,
+        ))
+    }
+
+    pub async fn close(&self) {
+        let _ = self.pc.close().await;
+    }
+}
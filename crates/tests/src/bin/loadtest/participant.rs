@@ -0,0 +1,322 @@
+//! One simulated participant: a fresh account that joins the call, sends
+//! synthetic audio and consumes everyone else until the run stops.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail};
+use reqwest::Method;
+use roomler_ai_client::{Client, RegisterRequest, WsClient};
+use serde_json::{Value, json};
+use tokio::sync::{mpsc, watch};
+use tokio::time::Instant;
+use tracing::{debug, warn};
+use webrtc::api::API;
+
+use crate::media::{RecvTransport, SendTransport};
+use crate::report::Metrics;
+use crate::scenario::Scenario;
+use crate::sdp::{self, RemoteTrack, ServerTransport};
+
+const PASSWORD: &str = "loadtest-Passw0rd!";
+
+/// What every participant of a run shares.
+pub struct Run {
+    pub scenario: Scenario,
+    /// Adds each participant to the tenant.
+    pub organizer: Client,
+    pub tenant_id: String,
+    pub room_id: String,
+    /// Makes the run's usernames unique.
+    pub tag: String,
+    pub rtp_capabilities: Value,
+    pub api: API,
+    pub metrics: Metrics,
+}
+
+struct Joined {
+    client: Client,
+    ws: WsClient,
+    send: ServerTransport,
+    recv: ServerTransport,
+    /// `media:new_producer` seen while joining.
+    producers: Vec<Value>,
+}
+
+/// Join participant `n`, then keep its media up until `stop` flips.
+pub async fn run(run: Arc<Run>, n: usize, mut stop: watch::Receiver<bool>) {
+    let started = Instant::now();
+    let joined = match join(&run, n).await {
+        Ok((joined, latency)) => {
+            run.metrics.join_ok(latency);
+            debug!(n, ?latency, "joined");
+            joined
+        }
+        Err(e) => {
+            warn!(n, "join failed after {:?}: {e:#}", started.elapsed());
+            run.metrics.join_failed(format!("join: {e:#}"));
+            return;
+        }
+    };
+    let Joined {
+        client,
+        mut ws,
+        send,
+        recv,
+        mut producers,
+    } = joined;
+
+    let send = if run.scenario.media.produce_audio {
+        match produce(&run, &mut ws, &send, &mut producers).await {
+            Ok(transport) => {
+                run.metrics.produce(Ok(()));
+                Some(transport)
+            }
+            Err(e) => {
+                warn!(n, "produce failed: {e:#}");
+                run.metrics.produce(Err(format!("produce: {e:#}")));
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let recv = if run.scenario.media.consume {
+        consume_until_stopped(&run, &mut ws, recv, producers, &mut stop).await
+    } else {
+        let _ = stop.wait_for(|stop| *stop).await;
+        None
+    };
+
+    if let Some(send) = send {
+        send.close().await;
+    }
+    if let Some(recv) = recv {
+        recv.close().await;
+    }
+    let _ = ws.media_leave(&run.room_id);
+    if let Err(e) = client.call_leave(&run.tenant_id, &run.room_id).await {
+        debug!(n, "call leave failed: {e}");
+    }
+}
+
+/// Sign up, join the room and the call, and get the transports. The
+/// latency covers the call join up to `media:transport_created`; the
+/// sign-up is harness setup.
+async fn join(run: &Run, n: usize) -> anyhow::Result<(Joined, Duration)> {
+    let scenario = &run.scenario;
+    let client = Client::new(scenario.base_url.clone());
+    let username = format!("lt{}{n}", run.tag);
+    client
+        .register(&RegisterRequest {
+            email: format!("{username}@loadtest.invalid"),
+            username,
+            display_name: format!("Loadtest {n}"),
+            password: PASSWORD.to_string(),
+            tenant_name: None,
+            tenant_slug: None,
+            invite_code: None,
+        })
+        .await?;
+    if client.access_token().is_none() {
+        bail!("sign-up was not auto-verified; run the server with auth.auto_verify");
+    }
+    let me = client.me().await?;
+    let _: Value = run
+        .organizer
+        .request(
+            Method::POST,
+            &format!("/api/tenant/{}/member", run.tenant_id),
+            Some(&json!({ "user_id": me.id })),
+        )
+        .await?;
+
+    let started = Instant::now();
+    client.join_room(&run.tenant_id, &run.room_id).await?;
+    client.call_join(&run.tenant_id, &run.room_id).await?;
+    let mut ws = client.connect_ws().await?;
+    ws.media_join(&run.room_id)?;
+    let mut producers = Vec::new();
+    let created = expect(
+        &mut ws,
+        "media:transport_created",
+        scenario.timeout(),
+        &mut producers,
+    )
+    .await?;
+    let latency = started.elapsed();
+    Ok((
+        Joined {
+            client,
+            ws,
+            send: ServerTransport::from_json(&created["send_transport"])?,
+            recv: ServerTransport::from_json(&created["recv_transport"])?,
+            producers,
+        },
+        latency,
+    ))
+}
+
+async fn produce(
+    run: &Run,
+    ws: &mut WsClient,
+    server: &ServerTransport,
+    producers: &mut Vec<Value>,
+) -> anyhow::Result<SendTransport> {
+    let (transport, fingerprint) = SendTransport::open(&run.api, server, "loadtest").await?;
+    ws.connect_transport(&run.room_id, &server.id, sdp::dtls_parameters(&fingerprint))?;
+    ws.produce(
+        &run.room_id,
+        "audio",
+        transport.rtp_parameters()?,
+        Some("microphone"),
+    )?;
+    if let Err(e) = expect(
+        ws,
+        "media:produce_result",
+        run.scenario.timeout(),
+        producers,
+    )
+    .await
+    {
+        transport.close().await;
+        return Err(e);
+    }
+    transport.start_audio();
+    Ok(transport)
+}
+
+/// Consume every producer announced until the run stops. A consume counts
+/// as a success once its first RTP packet arrives within the timeout.
+async fn consume_until_stopped(
+    run: &Run,
+    ws: &mut WsClient,
+    server: ServerTransport,
+    producers: Vec<Value>,
+    stop: &mut watch::Receiver<bool>,
+) -> Option<RecvTransport> {
+    let timeout = run.scenario.timeout();
+    let (rtp_tx, mut rtp_rx) = mpsc::unbounded_channel();
+    let mut transport = match RecvTransport::open(&run.api, server.clone(), rtp_tx).await {
+        Ok(transport) => transport,
+        Err(e) => {
+            run.metrics.consume(Err(format!("recv transport: {e:#}")));
+            let _ = stop.wait_for(|stop| *stop).await;
+            return None;
+        }
+    };
+    // Producer id -> when `media:consume` went out.
+    let mut requested: HashMap<String, Instant> = HashMap::new();
+    // Consumer ssrc -> when its consumer was created.
+    let mut flowing: HashMap<u32, Instant> = HashMap::new();
+    let request = |ws: &WsClient, requested: &mut HashMap<String, Instant>, data: &Value| {
+        let Some(producer_id) = data["producer_id"].as_str() else {
+            return;
+        };
+        if ws
+            .consume(&run.room_id, producer_id, run.rtp_capabilities.clone())
+            .is_ok()
+        {
+            requested.insert(producer_id.to_string(), Instant::now());
+        }
+    };
+    for data in &producers {
+        request(ws, &mut requested, data);
+    }
+
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = stop.changed() => break,
+            Some(ssrc) = rtp_rx.recv() => {
+                if flowing.remove(&ssrc).is_some() {
+                    run.metrics.consume(Ok(()));
+                }
+            }
+            event = ws.next_event() => {
+                let Some(event) = event else { break };
+                match event.kind.as_str() {
+                    "media:new_producer" => request(ws, &mut requested, &event.data),
+                    "media:consumer_created" => {
+                        let producer_id = event.data["producer_id"].as_str().unwrap_or_default();
+                        requested.remove(producer_id);
+                        match RemoteTrack::from_consumer(&event.data) {
+                            Ok(track) => {
+                                let ssrc = track.ssrc;
+                                match transport.add(track).await {
+                                    Ok(fingerprint) => {
+                                        if let Some(fingerprint) = fingerprint {
+                                            let _ = ws.connect_transport(
+                                                &run.room_id,
+                                                &server.id,
+                                                sdp::dtls_parameters(&fingerprint),
+                                            );
+                                        }
+                                        flowing.insert(ssrc, Instant::now());
+                                    }
+                                    Err(e) => run.metrics.consume(Err(format!("renegotiate: {e:#}"))),
+                                }
+                            }
+                            Err(e) => run.metrics.consume(Err(format!("consumer: {e:#}"))),
+                        }
+                    }
+                    // Errors don't say which request failed; charge the
+                    // oldest outstanding consume.
+                    "media:error" if !requested.is_empty() => {
+                        let oldest = requested
+                            .iter()
+                            .min_by_key(|(_, at)| **at)
+                            .map(|(id, _)| id.clone());
+                        if let Some(oldest) = oldest {
+                            requested.remove(&oldest);
+                        }
+                        run.metrics.consume(Err(format!(
+                            "consume: {}",
+                            event.data["message"].as_str().unwrap_or("media:error")
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+            _ = tick.tick() => {
+                let now = Instant::now();
+                let before = requested.len() + flowing.len();
+                requested.retain(|_, at| now - *at < timeout);
+                flowing.retain(|_, at| now - *at < timeout);
+                for _ in 0..before - requested.len() - flowing.len() {
+                    run.metrics.consume(Err(format!("consume: no media within {timeout:?}")));
+                }
+            }
+        }
+    }
+    Some(transport)
+}
+
+/// Wait for the next `kind` event, keeping `media:new_producer` events and
+/// failing on signaling errors.
+async fn expect(
+    ws: &mut WsClient,
+    kind: &str,
+    within: Duration,
+    producers: &mut Vec<Value>,
+) -> anyhow::Result<Value> {
+    let deadline = Instant::now() + within;
+    loop {
+        let event = tokio::time::timeout_at(deadline, ws.next_event())
+            .await
+            .map_err(|_| anyhow!("no {kind} within {within:?}"))?
+            .ok_or_else(|| anyhow!("WebSocket closed"))?;
+        match event.kind.as_str() {
+            k if k == kind => return Ok(event.data),
+            "media:error" => bail!(
+                "{}",
+                event.data["message"].as_str().unwrap_or("media:error")
+            ),
+            "media:room_full" => bail!("room full"),
+            "media:new_producer" => producers.push(event.data),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Per-stage results: what the participants saw and what the server says
+//! about itself.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use reqwest::Method;
+use roomler_ai_client::Client;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+/// Distinct error messages kept per stage.
+const ERROR_SAMPLES: usize = 5;
+
+/// Outcomes reported by participants since the last [`Metrics::take`].
+/// An outcome counts in the stage it completes in.
+#[derive(Default)]
+pub struct Metrics(Mutex<Outcomes>);
+
+#[derive(Debug, Default)]
+pub struct Outcomes {
+    joins_ok: u64,
+    joins_failed: u64,
+    join_latency: Vec<Duration>,
+    produce_ok: u64,
+    produce_failed: u64,
+    consume_ok: u64,
+    consume_failed: u64,
+    errors: Vec<String>,
+}
+
+impl Outcomes {
+    fn error(&mut self, error: String) {
+        if self.errors.len() < ERROR_SAMPLES && !self.errors.contains(&error) {
+            self.errors.push(error);
+        }
+    }
+}
+
+impl Metrics {
+    pub fn join_ok(&self, latency: Duration) {
+        let mut o = self.0.lock().unwrap();
+        o.joins_ok += 1;
+        o.join_latency.push(latency);
+    }
+
+    pub fn join_failed(&self, error: String) {
+        let mut o = self.0.lock().unwrap();
+        o.joins_failed += 1;
+        o.error(error);
+    }
+
+    pub fn produce(&self, result: Result<(), String>) {
+        let mut o = self.0.lock().unwrap();
+        match result {
+            Ok(()) => o.produce_ok += 1,
+            Err(e) => {
+                o.produce_failed += 1;
+                o.error(e);
+            }
+        }
+    }
+
+    pub fn consume(&self, result: Result<(), String>) {
+        let mut o = self.0.lock().unwrap();
+        match result {
+            Ok(()) => o.consume_ok += 1,
+            Err(e) => {
+                o.consume_failed += 1;
+                o.error(e);
+            }
+        }
+    }
+
+    pub fn take(&self) -> Outcomes {
+        std::mem::take(&mut *self.0.lock().unwrap())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageReport {
+    pub stage: usize,
+    pub participants: usize,
+    pub joins_ok: u64,
+    pub joins_failed: u64,
+    pub join_latency_ms: Option<Latency>,
+    pub produce_ok: u64,
+    pub produce_failed: u64,
+    pub consume_ok: u64,
+    pub consume_failed: u64,
+    pub errors: Vec<String>,
+    pub server: ServerSnapshot,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Latency {
+    pub avg: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub max: u64,
+}
+
+impl Latency {
+    fn of(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut ms: Vec<u64> = samples.iter().map(|d| d.as_millis() as u64).collect();
+        ms.sort_unstable();
+        let at = |p: usize| ms[(ms.len() * p).div_ceil(100).saturating_sub(1)];
+        Some(Self {
+            avg: ms.iter().sum::<u64>() / ms.len() as u64,
+            p50: at(50),
+            p95: at(95),
+            max: ms[ms.len() - 1],
+        })
+    }
+}
+
+/// The server's own view at the end of a stage. Each field is the endpoint's
+/// body, or `{"error": ...}` when it couldn't be read.
+#[derive(Debug, Serialize)]
+pub struct ServerSnapshot {
+    /// `GET /api/ws/stats`
+    pub ws: Value,
+    /// `GET /health/live`
+    pub live: Value,
+    /// `GET /health/ready`
+    pub ready: Value,
+}
+
+impl ServerSnapshot {
+    pub async fn fetch(client: &Client) -> Self {
+        let get = |path: &'static str| async move {
+            client
+                .request::<Value>(Method::GET, path, None)
+                .await
+                .unwrap_or_else(|e| json!({ "error": e.to_string() }))
+        };
+        Self {
+            ws: get("/api/ws/stats").await,
+            live: get("/health/live").await,
+            ready: get("/health/ready").await,
+        }
+    }
+}
+
+impl StageReport {
+    pub fn new(stage: usize, participants: usize, o: Outcomes, server: ServerSnapshot) -> Self {
+        Self {
+            stage,
+            participants,
+            joins_ok: o.joins_ok,
+            joins_failed: o.joins_failed,
+            join_latency_ms: Latency::of(&o.join_latency),
+            produce_ok: o.produce_ok,
+            produce_failed: o.produce_failed,
+            consume_ok: o.consume_ok,
+            consume_failed: o.consume_failed,
+            errors: o.errors,
+            server,
+        }
+    }
+}
+
+/// The text report: one row per stage, then the error samples.
+pub fn table(stages: &[StageReport]) -> String {
+    let mut out = format!(
+        "{:>5} | {:>12} | {:>11} | {:>24} | {:>9} | {:>9} | {:>8} | {:>6} | {:>5}\n",
+        "stage",
+        "participants",
+        "joins ok/x",
+        "join ms avg/p50/p95/max",
+        "produce",
+        "consume",
+        "ws conns",
+        "queued",
+        "stuck"
+    );
+    out.push_str(&format!("{}\n", "-".repeat(out.len() - 1)));
+    for s in stages {
+        let latency = s.join_latency_ms.as_ref().map_or_else(
+            || "-".to_string(),
+            |l| format!("{}/{}/{}/{}", l.avg, l.p50, l.p95, l.max),
+        );
+        let stat = |v: &Value| {
+            v.as_u64()
+                .map_or_else(|| "?".to_string(), |n| n.to_string())
+        };
+        out.push_str(&format!(
+            "{:>5} | {:>12} | {:>11} | {:>24} | {:>9} | {:>9} | {:>8} | {:>6} | {:>5}\n",
+            s.stage,
+            s.participants,
+            format!("{}/{}", s.joins_ok, s.joins_failed),
+            latency,
+            format!("{}/{}", s.produce_ok, s.produce_failed),
+            format!("{}/{}", s.consume_ok, s.consume_failed),
+            stat(&s.server.ws["connections"]),
+            stat(&s.server.ws["queued_messages"]),
+            stat(&s.server.live["stuck_workers"]),
+        ));
+    }
+    for s in stages.iter().filter(|s| !s.errors.is_empty()) {
+        out.push_str(&format!("\nstage {} errors:\n", s.stage));
+        for e in &s.errors {
+            out.push_str(&format!("  {e}\n"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn latency_percentiles() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        assert_eq!(
+            Latency::of(&samples),
+            Some(Latency {
+                avg: 50,
+                p50: 50,
+                p95: 95,
+                max: 100
+            })
+        );
+        assert_eq!(Latency::of(&[]), None);
+    }
+
+    #[test]
+    fn take_starts_a_new_stage_and_dedups_errors() {
+        let metrics = Metrics::default();
+        metrics.join_ok(Duration::from_millis(10));
+        metrics.join_failed("join: room full".into());
+        metrics.join_failed("join: room full".into());
+        metrics.consume(Ok(()));
+        let first = metrics.take();
+        assert_eq!((first.joins_ok, first.joins_failed), (1, 2));
+        assert_eq!(first.errors, ["join: room full"]);
+        assert_eq!(first.consume_ok, 1);
+        assert_eq!(metrics.take().joins_ok, 0);
+    }
+}
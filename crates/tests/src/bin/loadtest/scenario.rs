@@ -0,0 +1,163 @@
+//! Scenario files: who runs the call and how participants ramp in.
+
+use std::path::Path;
+use std::time::Duration;
+
+use anyhow::{Context, bail};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    /// Server origin, e.g. `http://localhost:5001`.
+    pub base_url: String,
+    pub organizer: Organizer,
+    #[serde(default)]
+    pub media: MediaPlan,
+    /// How long to wait for each signaling answer and for media to flow.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Ramps run in order; each one grows the call to its `participants`.
+    #[serde(rename = "stage")]
+    pub stages: Vec<Stage>,
+}
+
+/// The account that owns the call. Participants are registered on the fly
+/// and added to its tenant, so the server has to auto-verify sign-ups.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Organizer {
+    /// Username, or email when it contains an `@`.
+    pub login: String,
+    pub password: String,
+    /// The organizer's first tenant when absent.
+    pub tenant_id: Option<String>,
+    /// A fresh room is created when absent.
+    pub room_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MediaPlan {
+    /// Send a synthetic Opus stream from every participant.
+    #[serde(default = "yes")]
+    pub produce_audio: bool,
+    /// Consume every other participant's producers.
+    #[serde(default = "yes")]
+    pub consume: bool,
+}
+
+impl Default for MediaPlan {
+    fn default() -> Self {
+        Self {
+            produce_audio: true,
+            consume: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Stage {
+    /// Participants in the call once the ramp is done.
+    pub participants: usize,
+    /// Spread the new participants' joins evenly over this many seconds.
+    #[serde(default)]
+    pub ramp_secs: u64,
+    /// Stay at `participants` this long before the next stage.
+    #[serde(default)]
+    pub hold_secs: u64,
+}
+
+impl Stage {
+    pub fn ramp(&self) -> Duration {
+        Duration::from_secs(self.ramp_secs)
+    }
+
+    pub fn hold(&self) -> Duration {
+        Duration::from_secs(self.hold_secs)
+    }
+}
+
+fn yes() -> bool {
+    true
+}
+
+fn default_timeout_secs() -> u64 {
+    15
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("read {}", path.display()))?;
+        let scenario: Self =
+            toml::from_str(&text).with_context(|| format!("parse {}", path.display()))?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.stages.is_empty() {
+            bail!("a scenario needs at least one [[stage]]");
+        }
+        for pair in self.stages.windows(2) {
+            if pair[1].participants < pair[0].participants {
+                bail!(
+                    "stages only ramp up: {} participants after {}",
+                    pair[1].participants,
+                    pair[0].participants
+                );
+            }
+        }
+        if self.timeout_secs == 0 {
+            bail!("timeout_secs must be positive");
+        }
+        Ok(())
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> anyhow::Result<Scenario> {
+        let scenario: Scenario = toml::from_str(text)?;
+        scenario.validate()?;
+        Ok(scenario)
+    }
+
+    const ORGANIZER: &str = r#"
+        base_url = "http://localhost:5001"
+        [organizer]
+        login = "admin"
+        password = "secret"
+    "#;
+
+    #[test]
+    fn stages_default_to_full_media() {
+        let scenario = parse(&format!(
+            "{ORGANIZER}\n[[stage]]\nparticipants = 10\nramp_secs = 5\n\n[[stage]]\nparticipants = 20\nhold_secs = 30\n"
+        ))
+        .unwrap();
+        assert_eq!(scenario.stages.len(), 2);
+        assert_eq!(scenario.stages[1].hold(), Duration::from_secs(30));
+        assert!(scenario.media.produce_audio && scenario.media.consume);
+        assert_eq!(scenario.timeout(), Duration::from_secs(15));
+    }
+
+    #[test]
+    fn stages_must_ramp_up() {
+        assert!(parse(ORGANIZER).is_err());
+        assert!(
+            parse(&format!(
+                "{ORGANIZER}\n[[stage]]\nparticipants = 10\n\n[[stage]]\nparticipants = 5\n"
+            ))
+            .is_err()
+        );
+    }
+}
@@ -0,0 +1,456 @@
+//! SDP for talking to mediasoup transports, built the way mediasoup-client
+//! does it in a browser: the server's ICE and DTLS parameters become the
+//! remote side of a local peer connection. mediasoup is ICE-lite and only
+//! learns the client's address from its STUN checks, so no local candidates
+//! are sent back.
+
+use anyhow::{Context, anyhow};
+use serde_json::{Value, json};
+
+/// A server transport from `media:transport_created`.
+#[derive(Debug, Clone)]
+pub struct ServerTransport {
+    pub id: String,
+    ice_ufrag: String,
+    ice_pwd: String,
+    fingerprint: Fingerprint,
+    candidates: Vec<Candidate>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub algorithm: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone)]
+struct Candidate {
+    foundation: String,
+    priority: u64,
+    address: String,
+    port: u64,
+}
+
+/// A consumer from `media:consumer_created`, as the recv side needs it.
+#[derive(Debug, Clone)]
+pub struct RemoteTrack {
+    pub mid: String,
+    pub kind: String,
+    pub ssrc: u32,
+    pub rtp_parameters: Value,
+}
+
+impl ServerTransport {
+    pub fn from_json(v: &Value) -> anyhow::Result<Self> {
+        let str_of = |v: &Value, key: &str| {
+            v[key]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| anyhow!("transport without {key}"))
+        };
+        let ice = &v["ice_parameters"];
+        let fingerprint = v["dtls_parameters"]["fingerprints"]
+            .as_array()
+            .and_then(|fps| fps.iter().find(|fp| fp["algorithm"] == "sha-256"))
+            .context("transport without a sha-256 fingerprint")?;
+        // TCP candidates need an ICE-TCP capable client; UDP is what a
+        // browser uses when it can.
+        let candidates = v["ice_candidates"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+            .iter()
+            .filter(|c| c["protocol"] == "udp")
+            .filter_map(|c| {
+                Some(Candidate {
+                    foundation: c["foundation"].as_str()?.to_string(),
+                    priority: c["priority"].as_u64()?,
+                    // `ip` in older mediasoup releases
+                    address: c["address"].as_str().or(c["ip"].as_str())?.to_string(),
+                    port: c["port"].as_u64()?,
+                })
+            })
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return Err(anyhow!("transport without UDP candidates"));
+        }
+        Ok(Self {
+            id: str_of(v, "id")?,
+            ice_ufrag: str_of(ice, "usernameFragment")?,
+            ice_pwd: str_of(ice, "password")?,
+            fingerprint: Fingerprint {
+                algorithm: str_of(fingerprint, "algorithm")?,
+                value: str_of(fingerprint, "value")?,
+            },
+            candidates,
+        })
+    }
+
+    /// The transport attributes of one media section.
+    fn transport_lines(&self, out: &mut String, setup: &str) {
+        push(out, format!("a=ice-ufrag:{}", self.ice_ufrag));
+        push(out, format!("a=ice-pwd:{}", self.ice_pwd));
+        push(
+            out,
+            format!(
+                "a=fingerprint:{} {}",
+                self.fingerprint.algorithm, self.fingerprint.value
+            ),
+        );
+        push(out, format!("a=setup:{setup}"));
+        for c in &self.candidates {
+            push(
+                out,
+                format!(
+                    "a=candidate:{} 1 udp {} {} {} typ host",
+                    c.foundation, c.priority, c.address, c.port
+                ),
+            );
+        }
+        push(out, "a=end-of-candidates");
+    }
+}
+
+fn push(out: &mut String, line: impl AsRef<str>) {
+    out.push_str(line.as_ref());
+    out.push_str("\r\n");
+}
+
+fn session_header(out: &mut String, version: u64, mids: &[&str]) {
+    push(out, "v=0");
+    push(out, format!("o=mediasoup 10000 {version} IN IP4 0.0.0.0"));
+    push(out, "s=-");
+    push(out, "t=0 0");
+    push(out, "a=ice-lite");
+    push(out, format!("a=group:BUNDLE {}", mids.join(" ")));
+    push(out, "a=msid-semantic: WMS *");
+}
+
+/// One `m=` section of a local description.
+struct Section<'a> {
+    kind: &'a str,
+    lines: Vec<&'a str>,
+}
+
+fn sections(sdp: &str) -> Vec<Section<'_>> {
+    let mut out: Vec<Section> = Vec::new();
+    for line in sdp.lines() {
+        if let Some(m) = line.strip_prefix("m=") {
+            out.push(Section {
+                kind: m.split(' ').next().unwrap_or_default(),
+                lines: Vec::new(),
+            });
+        } else if let Some(section) = out.last_mut() {
+            section.lines.push(line);
+        }
+    }
+    out
+}
+
+impl Section<'_> {
+    fn attr(&self, name: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .find_map(|l| l.strip_prefix("a=")?.strip_prefix(name)?.strip_prefix(':'))
+    }
+
+    /// Payload type and `rtpmap` value of the first codec named `codec`.
+    fn codec(&self, codec: &str) -> Option<(u8, &str)> {
+        self.lines.iter().find_map(|l| {
+            let (pt, map) = l.strip_prefix("a=rtpmap:")?.split_once(' ')?;
+            map.to_ascii_lowercase()
+                .starts_with(&format!("{codec}/"))
+                .then(|| Some((pt.parse().ok()?, map)))
+                .flatten()
+        })
+    }
+
+    fn fmtp(&self, pt: u8) -> Option<&str> {
+        self.lines
+            .iter()
+            .find_map(|l| l.strip_prefix(&format!("a=fmtp:{pt} ")))
+    }
+}
+
+/// The DTLS fingerprint of a local description.
+pub fn local_fingerprint(sdp: &str) -> Option<Fingerprint> {
+    sdp.lines().find_map(|l| {
+        let (algorithm, value) = l.strip_prefix("a=fingerprint:")?.split_once(' ')?;
+        Some(Fingerprint {
+            algorithm: algorithm.to_ascii_lowercase(),
+            value: value.trim().to_string(),
+        })
+    })
+}
+
+/// `dtls_parameters` for `media:connect_transport`: the local side is
+/// always the DTLS client.
+pub fn dtls_parameters(fingerprint: &Fingerprint) -> Value {
+    json!({
+        "role": "client",
+        "fingerprints": [{ "algorithm": fingerprint.algorithm, "value": fingerprint.value }],
+    })
+}
+
+/// Answer a local offer of send-only audio sections with the send
+/// transport, each section receiving Opus.
+pub fn send_answer(server: &ServerTransport, offer: &str) -> anyhow::Result<String> {
+    let sections = sections(offer);
+    let mids = sections
+        .iter()
+        .map(|s| s.attr("mid").context("offer section without mid"))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    let mut out = String::new();
+    session_header(&mut out, 1, &mids);
+    for (section, mid) in sections.iter().zip(&mids) {
+        let (pt, rtpmap) = section.codec("opus").context("offer without Opus")?;
+        push(
+            &mut out,
+            format!("m={} 7 UDP/TLS/RTP/SAVPF {pt}", section.kind),
+        );
+        push(&mut out, "c=IN IP4 127.0.0.1");
+        server.transport_lines(&mut out, "passive");
+        push(&mut out, format!("a=mid:{mid}"));
+        push(&mut out, "a=recvonly");
+        push(&mut out, "a=rtcp-mux");
+        push(&mut out, "a=rtcp-rsize");
+        push(&mut out, format!("a=rtpmap:{pt} {rtpmap}"));
+        if let Some(fmtp) = section.fmtp(pt) {
+            push(&mut out, format!("a=fmtp:{pt} {fmtp}"));
+        }
+    }
+    Ok(out)
+}
+
+/// `rtp_parameters` for `media:produce` of the Opus section `mid` of a
+/// local offer.
+pub fn opus_rtp_parameters(offer: &str, mid: &str) -> anyhow::Result<Value> {
+    let sections = sections(offer);
+    let section = sections
+        .iter()
+        .find(|s| s.attr("mid") == Some(mid))
+        .with_context(|| format!("no section {mid} in offer"))?;
+    let (pt, rtpmap) = section.codec("opus").context("offer without Opus")?;
+    let mut rtpmap = rtpmap.split('/').skip(1);
+    let clock_rate: u32 = rtpmap.next().and_then(|c| c.parse().ok()).unwrap_or(48000);
+    let channels: u8 = rtpmap.next().and_then(|c| c.parse().ok()).unwrap_or(2);
+    let mut parameters = serde_json::Map::new();
+    for param in section.fmtp(pt).unwrap_or_default().split(';') {
+        if let Some((k, v)) = param.trim().split_once('=') {
+            let v = v.parse::<u32>().map_or_else(|_| json!(v), |n| json!(n));
+            parameters.insert(k.to_string(), v);
+        }
+    }
+    let (ssrc, cname) = section
+        .lines
+        .iter()
+        .find_map(|l| {
+            let (ssrc, attr) = l.strip_prefix("a=ssrc:")?.split_once(' ')?;
+            Some((ssrc.parse::<u32>().ok()?, attr.strip_prefix("cname:")?))
+        })
+        .context("offer section without an ssrc cname")?;
+    Ok(json!({
+        "mid": mid,
+        "codecs": [{
+            "mimeType": "audio/opus",
+            "payloadType": pt,
+            "clockRate": clock_rate,
+            "channels": channels,
+            "parameters": parameters,
+            "rtcpFeedback": [],
+        }],
+        "headerExtensions": [],
+        "encodings": [{ "ssrc": ssrc }],
+        "rtcp": { "cname": cname, "reducedSize": true },
+    }))
+}
+
+impl RemoteTrack {
+    pub fn from_consumer(data: &Value) -> anyhow::Result<Self> {
+        let rtp = &data["rtp_parameters"];
+        Ok(Self {
+            mid: rtp["mid"]
+                .as_str()
+                .context("consumer without a mid")?
+                .to_string(),
+            kind: data["kind"].as_str().unwrap_or("audio").to_string(),
+            ssrc: rtp["encodings"][0]["ssrc"]
+                .as_u64()
+                .and_then(|s| u32::try_from(s).ok())
+                .context("consumer without an ssrc")?,
+            rtp_parameters: rtp.clone(),
+        })
+    }
+}
+
+/// Offer every consumer of the recv transport, one send-only section each,
+/// as mediasoup-client does on each `consume`. `version` must grow with
+/// every renegotiation.
+pub fn recv_offer(server: &ServerTransport, tracks: &[RemoteTrack], version: u64) -> String {
+    let mids: Vec<&str> = tracks.iter().map(|t| t.mid.as_str()).collect();
+    let mut out = String::new();
+    session_header(&mut out, version, &mids);
+    for track in tracks {
+        let rtp = &track.rtp_parameters;
+        let codecs = rtp["codecs"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let pts: Vec<String> = codecs
+            .iter()
+            .filter_map(|c| c["payloadType"].as_u64())
+            .map(|pt| pt.to_string())
+            .collect();
+        push(
+            &mut out,
+            format!("m={} 7 UDP/TLS/RTP/SAVPF {}", track.kind, pts.join(" ")),
+        );
+        push(&mut out, "c=IN IP4 127.0.0.1");
+        server.transport_lines(&mut out, "actpass");
+        push(&mut out, format!("a=mid:{}", track.mid));
+        push(&mut out, "a=sendonly");
+        push(&mut out, "a=rtcp-mux");
+        push(&mut out, "a=rtcp-rsize");
+        for codec in codecs {
+            let pt = &codec["payloadType"];
+            let name = codec["mimeType"]
+                .as_str()
+                .and_then(|m| m.split_once('/'))
+                .map(|(_, name)| name)
+                .unwrap_or_default();
+            let mut rtpmap = format!("a=rtpmap:{pt} {name}/{}", codec["clockRate"]);
+            if let Some(channels) = codec["channels"].as_u64().filter(|c| *c > 1) {
+                rtpmap.push_str(&format!("/{channels}"));
+            }
+            push(&mut out, rtpmap);
+            let params: Vec<String> = codec["parameters"]
+                .as_object()
+                .into_iter()
+                .flatten()
+                .map(|(k, v)| match v {
+                    Value::String(s) => format!("{k}={s}"),
+                    v => format!("{k}={v}"),
+                })
+                .collect();
+            if !params.is_empty() {
+                push(&mut out, format!("a=fmtp:{pt} {}", params.join(";")));
+            }
+        }
+        let cname = rtp["rtcp"]["cname"].as_str().unwrap_or("mediasoup");
+        let stream = format!("stream-{}", track.mid);
+        let id = format!("track-{}", track.mid);
+        push(&mut out, format!("a=msid:{stream} {id}"));
+        let rtx = rtp["encodings"][0]["rtx"]["ssrc"].as_u64();
+        if let Some(rtx) = rtx {
+            push(&mut out, format!("a=ssrc-group:FID {} {rtx}", track.ssrc));
+        }
+        for ssrc in std::iter::once(u64::from(track.ssrc)).chain(rtx) {
+            push(&mut out, format!("a=ssrc:{ssrc} cname:{cname}"));
+            push(&mut out, format!("a=ssrc:{ssrc} msid:{stream} {id}"));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> ServerTransport {
+        ServerTransport::from_json(&json!({
+            "id": "t1",
+            "ice_parameters": { "usernameFragment": "uf", "password": "pw", "iceLite": true },
+            "ice_candidates": [
+                { "foundation": "udpcandidate", "priority": 1076302079u64, "address": "10.0.0.1",
+                  "protocol": "udp", "port": 40000, "type": "host" },
+                { "foundation": "tcpcandidate", "priority": 1076276479u64, "address": "10.0.0.1",
+                  "protocol": "tcp", "port": 40001, "type": "host", "tcpType": "passive" }
+            ],
+            "dtls_parameters": {
+                "role": "auto",
+                "fingerprints": [
+                    { "algorithm": "sha-1", "value": "AA" },
+                    { "algorithm": "sha-256", "value": "BB:CC" }
+                ]
+            }
+        }))
+        .unwrap()
+    }
+
+    const OFFER: &str = "v=0\r\no=- 1 2 IN IP4 0.0.0.0\r\ns=-\r\nt=0 0\r\n\
+        a=fingerprint:sha-256 DE:AD\r\na=group:BUNDLE 0\r\n\
+        m=audio 9 UDP/TLS/RTP/SAVPF 111 9\r\nc=IN IP4 0.0.0.0\r\na=setup:actpass\r\na=mid:0\r\n\
+        a=rtpmap:111 opus/48000/2\r\na=fmtp:111 minptime=10;useinbandfec=1\r\n\
+        a=rtpmap:9 G722/8000\r\na=ssrc:1234 cname:loadtest\r\na=ssrc:1234 msid:s t\r\na=sendonly\r\n";
+
+    #[test]
+    fn send_answer_mirrors_the_offer_with_server_ice_and_dtls() {
+        let answer = send_answer(&server(), OFFER).unwrap();
+        assert!(answer.contains("a=ice-lite\r\n"));
+        assert!(answer.contains("m=audio 7 UDP/TLS/RTP/SAVPF 111\r\n"));
+        assert!(answer.contains("a=fingerprint:sha-256 BB:CC\r\n"));
+        assert!(answer.contains("a=setup:passive\r\n"));
+        assert!(answer.contains("a=recvonly\r\n"));
+        assert!(answer.contains("a=fmtp:111 minptime=10;useinbandfec=1\r\n"));
+        assert!(
+            answer
+                .contains("a=candidate:udpcandidate 1 udp 1076302079 10.0.0.1 40000 typ host\r\n")
+        );
+        assert!(!answer.contains("tcpcandidate"));
+    }
+
+    #[test]
+    fn produce_parameters_come_from_the_offer() {
+        assert_eq!(
+            local_fingerprint(OFFER),
+            Some(Fingerprint {
+                algorithm: "sha-256".into(),
+                value: "DE:AD".into()
+            })
+        );
+        let rtp = opus_rtp_parameters(OFFER, "0").unwrap();
+        assert_eq!(rtp["codecs"][0]["payloadType"], 111);
+        assert_eq!(rtp["codecs"][0]["channels"], 2);
+        assert_eq!(rtp["codecs"][0]["parameters"]["useinbandfec"], 1);
+        assert_eq!(rtp["encodings"][0]["ssrc"], 1234);
+        assert_eq!(rtp["rtcp"]["cname"], "loadtest");
+    }
+
+    #[test]
+    fn recv_offer_has_a_section_per_consumer() {
+        let audio = RemoteTrack::from_consumer(&json!({
+            "id": "c1", "producer_id": "p1", "kind": "audio",
+            "rtp_parameters": {
+                "mid": "0",
+                "codecs": [{ "mimeType": "audio/opus", "payloadType": 100, "clockRate": 48000,
+                             "channels": 2, "parameters": { "useinbandfec": 1 } }],
+                "encodings": [{ "ssrc": 111 }],
+                "rtcp": { "cname": "peer" }
+            }
+        }))
+        .unwrap();
+        let video = RemoteTrack::from_consumer(&json!({
+            "kind": "video",
+            "rtp_parameters": {
+                "mid": "1",
+                "codecs": [
+                    { "mimeType": "video/VP8", "payloadType": 101, "clockRate": 90000, "parameters": {} },
+                    { "mimeType": "video/rtx", "payloadType": 102, "clockRate": 90000, "parameters": { "apt": 101 } }
+                ],
+                "encodings": [{ "ssrc": 222, "rtx": { "ssrc": 333 } }],
+                "rtcp": { "cname": "peer" }
+            }
+        }))
+        .unwrap();
+        let offer = recv_offer(&server(), &[audio, video], 3);
+        assert!(offer.contains("o=mediasoup 10000 3 IN IP4 0.0.0.0\r\n"));
+        assert!(offer.contains("a=group:BUNDLE 0 1\r\n"));
+        assert!(offer.contains("m=audio 7 UDP/TLS/RTP/SAVPF 100\r\n"));
+        assert!(offer.contains("a=rtpmap:100 opus/48000/2\r\n"));
+        assert!(offer.contains("m=video 7 UDP/TLS/RTP/SAVPF 101 102\r\n"));
+        assert!(offer.contains("a=fmtp:102 apt=101\r\n"));
+        assert!(offer.contains("a=ssrc-group:FID 222 333\r\n"));
+        assert!(offer.contains("a=ssrc:333 cname:peer\r\n"));
+        assert!(offer.contains("a=setup:actpass\r\n"));
+    }
+}
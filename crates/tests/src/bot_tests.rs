@@ -0,0 +1,209 @@
+use serde_json::Value;
+
+use crate::fixtures::seed::SeededTenant;
+use crate::fixtures::test_app::TestApp;
+
+/// Create a bot and issue it a token scoped to `room_ids`.
+async fn create_bot_with_token(
+    app: &TestApp,
+    tenant: &SeededTenant,
+    room_ids: &[&str],
+) -> (Value, Value) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/bot", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "Standup Bot" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let bot: Value = resp.json().await.unwrap();
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/bot/{}/token",
+                tenant.tenant_id,
+                bot["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "prod", "room_ids": room_ids }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let token: Value = resp.json().await.unwrap();
+    assert!(token["token"].is_string());
+    (bot, token)
+}
+
+#[tokio::test]
+async fn bot_token_posts_only_in_scoped_rooms() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bot1").await;
+    let (bot, token) = create_bot_with_token(&app, &tenant, &[&tenant.rooms[0].id]).await;
+    let bearer = token["token"].as_str().unwrap();
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/message",
+                tenant.tenant_id, tenant.rooms[0].id
+            ),
+            bearer,
+        )
+        .json(&serde_json::json!({ "content": "Standup in 5 minutes" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let msg: Value = resp.json().await.unwrap();
+    assert_eq!(msg["author_type"], "bot");
+    assert_eq!(msg["author_id"], bot["id"]);
+
+    // A room outside the token's scope.
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/message",
+                tenant.tenant_id, tenant.rooms[1].id
+            ),
+            bearer,
+        )
+        .json(&serde_json::json!({ "content": "hello?" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Endpoints outside the room read/post allowlist.
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/bot", tenant.tenant_id), bearer)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/role", tenant.tenant_id), bearer)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn bots_are_badged_in_member_lists() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bot2").await;
+    let (bot, _) = create_bot_with_token(&app, &tenant, &[&tenant.rooms[0].id]).await;
+
+    let members: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/member", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let items = members["items"].as_array().unwrap();
+    let entry = items
+        .iter()
+        .find(|m| m["user_id"] == bot["id"])
+        .expect("bot is a tenant member");
+    assert_eq!(entry["is_bot"], true);
+    assert!(
+        items
+            .iter()
+            .filter(|m| m["user_id"] != bot["id"])
+            .all(|m| m["is_bot"] == false)
+    );
+
+    // Issuing the token joined the bot to its room.
+    let room_members: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/member",
+                tenant.tenant_id, tenant.rooms[0].id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let entry = room_members["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["user_id"] == bot["id"])
+        .expect("bot joined the scoped room");
+    assert_eq!(entry["is_bot"], true);
+}
+
+#[tokio::test]
+async fn revoked_bot_token_is_rejected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bot3").await;
+    let (bot, token) = create_bot_with_token(&app, &tenant, &[&tenant.rooms[0].id]).await;
+    let bearer = token["token"].as_str().unwrap();
+    let messages = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+
+    let resp = app.auth_get(&messages, bearer).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_delete(
+            &format!(
+                "/api/tenant/{}/bot/{}/token/{}",
+                tenant.tenant_id,
+                bot["id"].as_str().unwrap(),
+                token["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app.auth_get(&messages, bearer).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+}
+
+#[tokio::test]
+async fn managing_bots_requires_manage_tenant() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bot4").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/bot", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "name": "rogue" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/bot", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -0,0 +1,261 @@
+use crate::fixtures::test_app::TestApp;
+use futures::StreamExt;
+use serde_json::Value;
+
+/// Create a room and return its id.
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+fn breakout_url(tenant_id: &str, room_id: &str) -> String {
+    format!("/api/tenant/{}/room/{}/call/breakout", tenant_id, room_id)
+}
+
+async fn open_breakouts(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    body: Value,
+) -> reqwest::Response {
+    app.auth_post(&breakout_url(tenant_id, room_id), token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn list_breakouts(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) -> Vec<Value> {
+    let resp = app
+        .auth_get(&breakout_url(tenant_id, room_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn breakout_auto_assigns_participants() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("breakout1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Workshop").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+
+    let resp = open_breakouts(
+        &app,
+        tid,
+        &room_id,
+        admin,
+        serde_json::json!({ "count": 1 }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 201);
+    let created: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(created.len(), 1);
+    assert_eq!(created[0]["name"], "Breakout 1");
+    // The organizer stays in the main room.
+    assert_eq!(
+        created[0]["user_ids"],
+        serde_json::json!([tenant.member.id.as_str()])
+    );
+
+    let listed = list_breakouts(&app, tid, &room_id, member).await;
+    assert_eq!(listed.len(), 1);
+    assert_eq!(listed[0]["id"], created[0]["id"]);
+}
+
+#[tokio::test]
+async fn breakout_manual_assignment_and_move() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("breakout2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Manual").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    let resp = open_breakouts(
+        &app,
+        tid,
+        &room_id,
+        admin,
+        serde_json::json!({
+            "rooms": [
+                { "name": "Design", "user_ids": [tenant.member.id] },
+                { "name": "Backend", "user_ids": [] },
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 201);
+    let created: Vec<Value> = resp.json().await.unwrap();
+    assert_eq!(created[0]["name"], "Design");
+    let backend_id = created[1]["id"].as_str().unwrap();
+
+    let resp = app
+        .auth_put(
+            &format!("{}/{}/participant", breakout_url(tid, &room_id), backend_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "user_id": tenant.member.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let listed = list_breakouts(&app, tid, &room_id, admin).await;
+    let design = listed.iter().find(|b| b["name"] == "Design").unwrap();
+    let backend = listed.iter().find(|b| b["name"] == "Backend").unwrap();
+    assert!(design["user_ids"].as_array().unwrap().is_empty());
+    assert_eq!(
+        backend["user_ids"],
+        serde_json::json!([tenant.member.id.as_str()])
+    );
+}
+
+#[tokio::test]
+async fn breakout_assignment_is_pushed_over_ws() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("breakout3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Notify").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+
+    let resp = open_breakouts(
+        &app,
+        tid,
+        &room_id,
+        admin,
+        serde_json::json!({ "rooms": [{ "user_ids": [tenant.member.id] }] }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 201);
+    let created: Vec<Value> = resp.json().await.unwrap();
+
+    let event = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            if parsed["type"] == "call:breakout_assigned" {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("no call:breakout_assigned event");
+    assert_eq!(event["data"]["room_id"], room_id.as_str());
+    assert_eq!(event["data"]["breakout_id"], created[0]["id"]);
+}
+
+#[tokio::test]
+async fn breakout_close_and_call_end_tear_down_rooms() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("breakout4").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Teardown").await;
+    let body = serde_json::json!({ "rooms": [{ "user_ids": [] }] });
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    let resp = open_breakouts(&app, tid, &room_id, admin, body.clone()).await;
+    assert_eq!(resp.status().as_u16(), 201);
+
+    let resp = app
+        .auth_delete(&breakout_url(tid, &room_id), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let closed: Value = resp.json().await.unwrap();
+    assert_eq!(closed["closed"], 1);
+    assert!(list_breakouts(&app, tid, &room_id, admin).await.is_empty());
+
+    // A new round can be opened once the previous one is closed, and ending
+    // the call closes it too.
+    let resp = open_breakouts(&app, tid, &room_id, admin, body).await;
+    assert_eq!(resp.status().as_u16(), 201);
+    call_action(&app, tid, &room_id, admin, "end").await;
+    assert!(list_breakouts(&app, tid, &room_id, admin).await.is_empty());
+}
+
+#[tokio::test]
+async fn breakout_rejects_invalid_requests() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("breakout5").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Rules").await;
+    let body = serde_json::json!({ "rooms": [{ "user_ids": [] }] });
+
+    // No call in progress
+    let resp = open_breakouts(&app, tid, &room_id, admin, body.clone()).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+
+    // Members can't run breakouts without MANAGE_MEETINGS
+    let resp = open_breakouts(&app, tid, &room_id, member, body.clone()).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Neither count nor rooms
+    let resp = open_breakouts(&app, tid, &room_id, admin, serde_json::json!({})).await;
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Same user in two rooms
+    let resp = open_breakouts(
+        &app,
+        tid,
+        &room_id,
+        admin,
+        serde_json::json!({
+            "rooms": [
+                { "user_ids": [tenant.member.id] },
+                { "user_ids": [tenant.member.id] },
+            ]
+        }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // Only one round open at a time
+    let resp = open_breakouts(&app, tid, &room_id, admin, body.clone()).await;
+    assert_eq!(resp.status().as_u16(), 201);
+    let resp = open_breakouts(&app, tid, &room_id, admin, body).await;
+    assert_eq!(resp.status().as_u16(), 409);
+}
@@ -0,0 +1,451 @@
+use crate::fixtures::test_app::TestApp;
+use axum::{
+    Form, Json, Router,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+
+/// What the fake Google Calendar saw.
+#[derive(Default)]
+struct Calls {
+    refreshes: usize,
+    /// Bearer token of each calendar API request.
+    bearers: Vec<String>,
+    created: Vec<Value>,
+    patched: Vec<Value>,
+    deleted: Vec<String>,
+}
+
+type Fake = Arc<Mutex<Calls>>;
+
+fn bearer(fake: &Fake, headers: &HeaderMap) {
+    let auth = headers["authorization"].to_str().unwrap();
+    fake.lock()
+        .unwrap()
+        .bearers
+        .push(auth.trim_start_matches("Bearer ").to_string());
+}
+
+/// Google's token, userinfo and event endpoints. The authorization code
+/// grants an already expired token, so the first calendar call refreshes
+/// it. The event's first attendee accepts and the others decline.
+async fn spawn_google(fake: Fake) -> String {
+    let app = Router::new()
+        .route(
+            "/token",
+            post(
+                |State(fake): State<Fake>, Form(form): Form<HashMap<String, String>>| async move {
+                    assert_eq!(form["client_id"], "test-google-id");
+                    assert_eq!(form["client_secret"], "test-google-secret");
+                    match form["grant_type"].as_str() {
+                        "authorization_code" => {
+                            assert_eq!(form["code"], "good-code");
+                            Json(json!({
+                                "access_token": "expired-token",
+                                "refresh_token": "refresh-token",
+                                "expires_in": 0,
+                            }))
+                        }
+                        _ => {
+                            assert_eq!(form["refresh_token"], "refresh-token");
+                            fake.lock().unwrap().refreshes += 1;
+                            Json(json!({ "access_token": "fresh-token", "expires_in": 3600 }))
+                        }
+                    }
+                },
+            ),
+        )
+        .route(
+            "/oauth2/v2/userinfo",
+            get(|| async { Json(json!({ "email": "organizer@gmail.com" })) }),
+        )
+        .route(
+            "/calendar/v3/calendars/primary/events",
+            post(
+                |State(fake): State<Fake>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                    bearer(&fake, &headers);
+                    fake.lock().unwrap().created.push(body);
+                    Json(json!({ "id": "event-1" }))
+                },
+            ),
+        )
+        .route(
+            "/calendar/v3/calendars/primary/events/{id}",
+            get(
+                |State(fake): State<Fake>, headers: HeaderMap, Path(id): Path<String>| async move {
+                    bearer(&fake, &headers);
+                    assert_eq!(id, "event-1");
+                    let calls = fake.lock().unwrap();
+                    let event = calls.patched.last().or(calls.created.last()).unwrap();
+                    let attendees: Vec<Value> = event["attendees"]
+                        .as_array()
+                        .unwrap()
+                        .iter()
+                        .enumerate()
+                        .map(|(i, a)| {
+                            let status = if i == 0 { "accepted" } else { "declined" };
+                            json!({ "email": a["email"], "responseStatus": status })
+                        })
+                        .collect();
+                    Json(json!({ "id": id, "attendees": attendees }))
+                },
+            )
+            .patch(
+                |State(fake): State<Fake>, headers: HeaderMap, Json(body): Json<Value>| async move {
+                    bearer(&fake, &headers);
+                    fake.lock().unwrap().patched.push(body);
+                    Json(json!({ "id": "event-1" }))
+                },
+            )
+            .delete(
+                |State(fake): State<Fake>, headers: HeaderMap, Path(id): Path<String>| async move {
+                    bearer(&fake, &headers);
+                    fake.lock().unwrap().deleted.push(id);
+                    StatusCode::NO_CONTENT
+                },
+            ),
+        )
+        .with_state(fake);
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+async fn spawn_app(fake: Fake) -> TestApp {
+    let google = spawn_google(fake).await;
+    TestApp::spawn_with_oauth_settings(|s| {
+        s.calendar.google_api_url = google.clone();
+        s.calendar.google_token_url = format!("{}/token", google);
+        s.calendar.dial_in_number = "+43 1 234 5678".to_string();
+        s.calendar.sync_interval_secs = 0;
+    })
+    .await
+}
+
+/// Connect `token`'s Google calendar through the consent flow.
+async fn connect_google(app: &TestApp, token: &str) {
+    let resp = app
+        .auth_post("/api/calendar/google/connect", token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let url = reqwest::Url::parse(body["url"].as_str().unwrap()).unwrap();
+    assert!(url.as_str().starts_with("https://accounts.google.com/"));
+    let query: HashMap<_, _> = url.query_pairs().into_owned().collect();
+    assert_eq!(query["access_type"], "offline");
+    assert!(query["scope"].contains("calendar.events"));
+    assert_eq!(
+        query["redirect_uri"],
+        "http://localhost:5001/api/calendar/callback/google"
+    );
+
+    let resp = app
+        .client
+        .get(app.url("/api/calendar/callback/google"))
+        .query(&[("code", "good-code"), ("state", query["state"].as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 303);
+    let location = resp.headers()["location"].to_str().unwrap();
+    assert!(location.ends_with("/profile/edit?calendar=google"));
+}
+
+#[tokio::test]
+async fn scheduled_call_syncs_with_google_calendar() {
+    let fake = Fake::default();
+    let app = spawn_app(fake.clone()).await;
+    let tenant = app.seed_tenant("calendar1").await;
+    let admin = &tenant.admin.access_token;
+    let schedule_url = format!(
+        "/api/tenant/{}/room/{}/call/schedule",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+
+    connect_google(&app, admin).await;
+    let connections: Value = app
+        .auth_get("/api/calendar/connection", admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(connections[0]["provider"], "google");
+    assert_eq!(connections[0]["account_email"], "organizer@gmail.com");
+
+    let resp = app
+        .auth_put(&schedule_url, admin)
+        .json(&json!({
+            "scheduled_start": "2030-03-04T15:00:00Z",
+            "scheduled_end": "2030-03-04T16:00:00Z",
+            "timezone": "Europe/Vienna",
+            "invitees": [tenant.member.email.to_uppercase(), "guest@example.com"],
+            "calendar": "google",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let schedule: Value = resp.json().await.unwrap();
+    let join_url = schedule["join_url"].as_str().unwrap().to_string();
+    assert!(join_url.starts_with(&format!("{}/join/", app.settings.app.frontend_url)));
+    assert_eq!(schedule["calendar_event"]["event_id"], "event-1");
+    assert!(schedule["calendar_event"]["error"].is_null());
+    assert_eq!(
+        schedule["invitees"][0]["email"],
+        tenant.member.email.to_lowercase()
+    );
+    assert_eq!(
+        schedule["invitees"][0]["user_id"],
+        tenant.member.id.as_str()
+    );
+    assert!(schedule["invitees"][1]["user_id"].is_null());
+    // The responses are read back right after the event is written
+    assert_eq!(schedule["invitees"][0]["response"], "accepted");
+    assert_eq!(schedule["invitees"][1]["response"], "declined");
+
+    {
+        let calls = fake.lock().unwrap();
+        // The expired token was refreshed once and used from then on
+        assert_eq!(calls.refreshes, 1);
+        assert!(calls.bearers.iter().all(|b| b == "fresh-token"));
+        let event = &calls.created[0];
+        assert_eq!(event["summary"], tenant.rooms[0].name.as_str());
+        assert_eq!(event["location"], join_url.as_str());
+        assert_eq!(event["start"]["dateTime"], "2030-03-04T15:00:00Z");
+        assert_eq!(event["start"]["timeZone"], "Europe/Vienna");
+        let description = event["description"].as_str().unwrap();
+        assert!(description.contains(&join_url));
+        assert!(description.contains("+43 1 234 5678"));
+        assert_eq!(event["attendees"].as_array().unwrap().len(), 2);
+    }
+
+    // Rescheduling updates the same event and keeps the responses
+    let resp = app
+        .auth_put(&schedule_url, admin)
+        .json(&json!({
+            "scheduled_start": "2030-03-05T09:00:00Z",
+            "scheduled_end": "2030-03-05T09:30:00Z",
+            "invitees": ["guest@example.com", tenant.member.email],
+            "calendar": "google",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let schedule: Value = resp.json().await.unwrap();
+    assert_eq!(schedule["scheduled_start"], "2030-03-05T09:00:00Z");
+    {
+        let calls = fake.lock().unwrap();
+        assert_eq!(calls.created.len(), 1);
+        assert_eq!(calls.patched.len(), 1);
+        assert_eq!(calls.patched[0]["end"]["dateTime"], "2030-03-05T09:30:00Z");
+    }
+
+    // The guest is first now, so the fake calendar has them accepting
+    let resp = app
+        .auth_post(&format!("{}/sync", schedule_url), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let schedule: Value = resp.json().await.unwrap();
+    assert_eq!(schedule["invitees"][0]["email"], "guest@example.com");
+    assert_eq!(schedule["invitees"][0]["response"], "accepted");
+    assert_eq!(schedule["invitees"][1]["response"], "declined");
+    assert!(!schedule["calendar_event"]["synced_at"].is_null());
+
+    let resp = app.auth_delete(&schedule_url, admin).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    assert_eq!(fake.lock().unwrap().deleted, vec!["event-1"]);
+    let schedule: Value = app
+        .auth_get(&schedule_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(schedule["scheduled_start"].is_null());
+    assert!(schedule["calendar_event"].is_null());
+
+    let resp = app
+        .auth_delete("/api/calendar/connection/google", admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 204);
+    let resp = app
+        .auth_delete("/api/calendar/connection/google", admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn scheduling_is_checked() {
+    let fake = Fake::default();
+    let app = spawn_app(fake.clone()).await;
+    let tenant = app.seed_tenant("calendar2").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let schedule_url = format!(
+        "/api/tenant/{}/room/{}/call/schedule",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let schedule = |body: Value, token: &str| app.auth_put(&schedule_url, token).json(&body).send();
+    let times = |extra: Value| {
+        let mut body = json!({
+            "scheduled_start": "2030-03-04T15:00:00Z",
+            "scheduled_end": "2030-03-04T16:00:00Z",
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        body
+    };
+
+    let resp = schedule(times(json!({})), member).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = schedule(
+        json!({
+            "scheduled_start": "2030-03-04T16:00:00Z",
+            "scheduled_end": "2030-03-04T15:00:00Z",
+        }),
+        admin,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = schedule(times(json!({ "invitees": ["nobody"] })), admin)
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = schedule(times(json!({ "calendar": "google" })), admin)
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["message"], "Connect your google calendar first");
+
+    // Without a calendar the call is only scheduled in the room
+    let resp = schedule(times(json!({ "invitees": ["guest@example.com"] })), admin)
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["invitees"][0]["response"], "needs_action");
+    assert!(body["calendar_event"].is_null());
+    assert!(fake.lock().unwrap().bearers.is_empty());
+
+    let resp = app
+        .auth_post(&format!("{}/sync", schedule_url), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // A state issued for another provider is refused
+    let resp = app
+        .auth_post("/api/calendar/microsoft/connect", admin)
+        .send()
+        .await
+        .unwrap();
+    let body: Value = resp.json().await.unwrap();
+    let url = reqwest::Url::parse(body["url"].as_str().unwrap()).unwrap();
+    let state = url
+        .query_pairs()
+        .find(|(k, _)| k == "state")
+        .unwrap()
+        .1
+        .into_owned();
+    let resp = app
+        .client
+        .get(app.url("/api/calendar/callback/google"))
+        .query(&[("code", "good-code"), ("state", state.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn consent_finishes_only_in_the_browser_that_started_it() {
+    let fake = Fake::default();
+    let app = spawn_app(fake).await;
+    let tenant = app.seed_tenant("calendar4").await;
+    let admin = &tenant.admin.access_token;
+
+    // The admin starts connecting and passes the URL on to the member
+    let body: Value = app
+        .auth_post("/api/calendar/google/connect", admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let url = reqwest::Url::parse(body["url"].as_str().unwrap()).unwrap();
+    let state = url
+        .query_pairs()
+        .find(|(k, _)| k == "state")
+        .unwrap()
+        .1
+        .into_owned();
+
+    // The member's browser consents, without the admin's nonce cookie
+    let resp = reqwest::Client::builder()
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+        .unwrap()
+        .get(app.url("/api/calendar/callback/google"))
+        .query(&[("code", "good-code"), ("state", state.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+    let connections: Value = app
+        .auth_get("/api/calendar/connection", admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(connections, json!([]));
+
+    // The browser that started it can finish it
+    let resp = app
+        .client
+        .get(app.url("/api/calendar/callback/google"))
+        .query(&[("code", "good-code"), ("state", state.as_str())])
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 303);
+}
+
+#[tokio::test]
+async fn calendar_needs_an_oauth_client() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("calendar3").await;
+    let resp = app
+        .auth_post("/api/calendar/google/connect", &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
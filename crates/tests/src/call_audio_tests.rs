@@ -0,0 +1,594 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use axum::{Json, Router, routing::post};
+use futures::StreamExt;
+use roomler_ai_config::MediaBackendKind;
+use roomler_ai_services::media::backend::mock;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::{Notify, mpsc};
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+async fn mute(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: &str,
+    token: &str,
+    muted: bool,
+) -> reqwest::Response {
+    app.auth_put(
+        &format!(
+            "/api/tenant/{}/room/{}/call/participant/{}/mute",
+            tenant_id, room_id, user_id
+        ),
+        token,
+    )
+    .json(&serde_json::json!({ "muted": muted }))
+    .send()
+    .await
+    .unwrap()
+}
+
+async fn push_to_talk(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    enabled: bool,
+) -> reqwest::Response {
+    app.auth_put(
+        &format!(
+            "/api/tenant/{}/room/{}/call/push-to-talk",
+            tenant_id, room_id
+        ),
+        token,
+    )
+    .json(&serde_json::json!({ "enabled": enabled }))
+    .send()
+    .await
+    .unwrap()
+}
+
+/// Upload a small audio file to the room; returns its id.
+async fn upload_audio(app: &TestApp, tenant_id: &str, token: &str, room_id: &str) -> String {
+    let part = reqwest::multipart::Part::bytes(b"ID3 intro".to_vec())
+        .file_name("intro.mp3")
+        .mime_str("audio/mpeg")
+        .unwrap();
+    let file: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/file/upload", tenant_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(
+            reqwest::multipart::Form::new()
+                .part("file", part)
+                .text("room_id", room_id.to_string()),
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    file["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn organizer_mute_follows_the_user_across_connections() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audio1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Webinar").await;
+
+    // No call yet
+    let resp = mute(&app, tid, &room_id, &tenant.member.id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+
+    // Members can't mute others without MANAGE_MEETINGS
+    let resp = mute(&app, tid, &room_id, &tenant.admin.id, member, true).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = mute(&app, tid, &room_id, &tenant.member.id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["connections"], 1);
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["user_id"], tenant.member.id.as_str());
+    assert_eq!(state["force_muted"], true);
+    assert_eq!(state["silenced"], true);
+
+    // Pressing push-to-talk doesn't get past the mute.
+    send(
+        &mut ws,
+        "media:ptt_active",
+        serde_json::json!({ "room_id": room_id, "active": true }),
+    )
+    .await;
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["ptt_active"], true);
+    assert_eq!(state["silenced"], true);
+
+    // A new connection of the muted user starts out muted.
+    ws.close(None).await.ok();
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["force_muted"], true);
+
+    let resp = mute(&app, tid, &room_id, &tenant.member.id, admin, false).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["force_muted"], false);
+    assert_eq!(state["silenced"], false);
+}
+
+#[tokio::test]
+async fn push_to_talk_silences_until_held() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("audio2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Town hall").await;
+
+    let resp = push_to_talk(&app, tid, &room_id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+
+    let resp = push_to_talk(&app, tid, &room_id, member, true).await;
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = push_to_talk(&app, tid, &room_id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let mode = next_of(&mut ws, "media:push_to_talk").await;
+    assert_eq!(mode["enabled"], true);
+
+    send(
+        &mut ws,
+        "media:ptt_active",
+        serde_json::json!({ "room_id": room_id, "active": true }),
+    )
+    .await;
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["ptt_active"], true);
+    assert_eq!(state["silenced"], false);
+
+    send(
+        &mut ws,
+        "media:ptt_active",
+        serde_json::json!({ "room_id": room_id, "active": false }),
+    )
+    .await;
+    let state = next_of(&mut ws, "media:audio_state").await;
+    assert_eq!(state["silenced"], true);
+
+    // Joiners learn the room's mode.
+    let mut late = connect(&app, admin).await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    send(
+        &mut late,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let mode = next_of(&mut late, "media:push_to_talk").await;
+    assert_eq!(mode["enabled"], true);
+}
+
+#[tokio::test]
+async fn shared_audio_ducks_while_someone_speaks() {
+    // The mock media layer takes no RTP, so the file is never decoded and
+    // the test says who is heard.
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.backend = MediaBackendKind::Mock).await;
+    let tenant = app.seed_tenant("audio3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Standup").await;
+
+    let file_id = upload_audio(&app, tid, admin, &room_id).await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut host = connect(&app, admin).await;
+    let mut guest = connect(&app, member).await;
+    send(
+        &mut host,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut host, "media:transport_created").await;
+    send(
+        &mut guest,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let caps = next_of(&mut guest, "media:router_capabilities").await["rtp_capabilities"].clone();
+
+    send(
+        &mut host,
+        "media:play_audio",
+        serde_json::json!({ "room_id": room_id, "file_id": file_id, "volume": 0.5 }),
+    )
+    .await;
+    let started = next_of(&mut guest, "media:audio_playback").await;
+    assert_eq!(started["action"], "start");
+    assert_eq!(started["ducking"], true);
+    assert_eq!(started["gain"], 0.5);
+    let playback_id = started["playback_id"].as_str().unwrap().to_string();
+    assert!(!playback_id.is_empty());
+    next_of(&mut host, "media:audio_playback").await;
+
+    // Everyone consumes the same server-side producer
+    let producer_id = started["producer_id"].as_str().unwrap().to_string();
+    send(
+        &mut guest,
+        "media:consume",
+        serde_json::json!({
+            "room_id": room_id,
+            "producer_id": producer_id,
+            "rtp_capabilities": caps,
+        }),
+    )
+    .await;
+    let consumer = next_of(&mut guest, "media:consumer_created").await;
+    assert_eq!(consumer["kind"], "audio");
+    // The music itself doesn't count as speech
+    assert!(!mock::set_heard(&producer_id, true));
+
+    send(
+        &mut guest,
+        "media:produce",
+        serde_json::json!({
+            "room_id": room_id,
+            "kind": "audio",
+            "rtp_parameters": {
+                "mid": "0",
+                "codecs": [{
+                    "mimeType": "audio/opus",
+                    "clockRate": 48000,
+                    "channels": 2,
+                    "payloadType": 111,
+                    "parameters": {},
+                    "rtcpFeedback": [],
+                }],
+                "headerExtensions": [],
+                "encodings": [{ "ssrc": 2222 }],
+                "rtcp": { "cname": "guest" },
+            },
+        }),
+    )
+    .await;
+    let voice = next_of(&mut guest, "media:produce_result").await["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    assert!(mock::set_heard(&voice, true));
+    let ducked = next_of(&mut host, "media:audio_playback").await;
+    assert_eq!(ducked["action"], "gain");
+    assert_eq!(ducked["ducked"], true);
+    assert!((ducked["gain"].as_f64().unwrap() - 0.1).abs() < 1e-9);
+
+    assert!(mock::set_heard(&voice, false));
+    let restored = next_of(&mut host, "media:audio_playback").await;
+    assert_eq!(restored["ducked"], false);
+    assert_eq!(restored["gain"], 0.5);
+
+    send(
+        &mut host,
+        "media:set_playback_volume",
+        serde_json::json!({
+            "room_id": room_id,
+            "playback_id": playback_id,
+            "volume": 0.8,
+            "ducking": false,
+        }),
+    )
+    .await;
+    let changed = next_of(&mut guest, "media:audio_playback").await;
+    assert_eq!(changed["action"], "gain");
+    assert_eq!(changed["ducking"], false);
+    assert_eq!(changed["gain"], 0.8);
+
+    send(
+        &mut host,
+        "media:stop_audio",
+        serde_json::json!({ "room_id": room_id, "playback_id": playback_id }),
+    )
+    .await;
+    let stopped = next_of(&mut guest, "media:audio_playback").await;
+    assert_eq!(stopped["action"], "stop");
+    assert_eq!(stopped["reason"], "stopped");
+}
+
+#[tokio::test]
+async fn shared_audio_that_cannot_be_decoded_stops() {
+    let app =
+        TestApp::spawn_with_settings(|s| s.mediasoup.ffmpeg_path = "/nonexistent/ffmpeg".into())
+            .await;
+    let tenant = app.seed_tenant("audio4").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Lesson").await;
+    let file_id = upload_audio(&app, tid, admin, &room_id).await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    let mut ws = connect(&app, admin).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+
+    send(
+        &mut ws,
+        "media:play_audio",
+        serde_json::json!({ "room_id": room_id, "file_id": file_id }),
+    )
+    .await;
+    let started = next_of(&mut ws, "media:audio_playback").await;
+    assert_eq!(started["action"], "start");
+    let stopped = next_of(&mut ws, "media:audio_playback").await;
+    assert_eq!(stopped["action"], "stop");
+    assert_eq!(stopped["playback_id"], started["playback_id"]);
+    assert_eq!(stopped["reason"], "failed");
+}
+
+#[tokio::test]
+async fn shared_audio_plays_only_files_the_player_could_download() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.backend = MediaBackendKind::Mock).await;
+    let tenant = app.seed_tenant("audio7").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = tenant.rooms[0].id.clone();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tid, room_id),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    // A room the member isn't in, a quarantined file and a deleted one
+    let private_room = create_room(&app, tid, admin, "Private").await;
+    let private_file = upload_audio(&app, tid, admin, &private_room).await;
+    let malware = upload_audio(&app, tid, admin, &room_id).await;
+    app.db
+        .collection::<bson::Document>("files")
+        .update_one(
+            bson::doc! { "_id": bson::oid::ObjectId::parse_str(&malware).unwrap() },
+            bson::doc! { "$set": { "scan_status": "malware" } },
+        )
+        .await
+        .unwrap();
+    let deleted = upload_audio(&app, tid, admin, &room_id).await;
+    let resp = app
+        .auth_delete(&format!("/api/tenant/{}/file/{}", tid, deleted), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let intro = upload_audio(&app, tid, admin, &room_id).await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut guest = connect(&app, member).await;
+    send(
+        &mut guest,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut guest, "media:transport_created").await;
+
+    for file_id in [&private_file, &malware, &deleted, &intro] {
+        send(
+            &mut guest,
+            "media:play_audio",
+            serde_json::json!({ "room_id": room_id, "file_id": file_id }),
+        )
+        .await;
+    }
+    let started = next_of(&mut guest, "media:audio_playback").await;
+    assert_eq!(started["action"], "start");
+    assert_eq!(started["file_id"], intro.as_str());
+    let playback_id = started["playback_id"].as_str().unwrap().to_string();
+
+    // Stopping takes a connection in the media room
+    let mut outsider = connect(&app, admin).await;
+    send(
+        &mut outsider,
+        "media:stop_audio",
+        serde_json::json!({ "room_id": room_id, "playback_id": playback_id }),
+    )
+    .await;
+    send(
+        &mut guest,
+        "media:set_playback_volume",
+        serde_json::json!({ "room_id": room_id, "playback_id": playback_id, "volume": 0.3 }),
+    )
+    .await;
+    let changed = next_of(&mut guest, "media:audio_playback").await;
+    assert_eq!(changed["action"], "gain");
+    assert_eq!(changed["volume"], 0.3);
+}
+
+#[tokio::test]
+async fn speak_needs_text_to_speech() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("speech1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Briefing").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    let mut ws = connect(&app, admin).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+
+    send(
+        &mut ws,
+        "media:speak",
+        serde_json::json!({ "room_id": room_id, "text": "Five minutes left" }),
+    )
+    .await;
+    let error = next_of(&mut ws, "media:error").await;
+    assert_eq!(error["message"], "Text-to-speech is not configured");
+}
+
+/// A speech API that records each request and answers once released.
+async fn fake_tts(requests: mpsc::UnboundedSender<Value>, release: Arc<Notify>) -> String {
+    let app = Router::new().route(
+        "/v1/audio/speech",
+        post(move |Json(body): Json<Value>| {
+            let requests = requests.clone();
+            let release = release.clone();
+            async move {
+                requests.send(body).unwrap();
+                release.notified().await;
+                b"RIFF not really a wave".to_vec()
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/v1/audio/speech", addr)
+}
+
+#[tokio::test]
+async fn announcements_are_spoken_into_the_call() {
+    let (requests_tx, mut requests) = mpsc::unbounded_channel();
+    let release = Arc::new(Notify::new());
+    let api_url = fake_tts(requests_tx, release.clone()).await;
+    let app = TestApp::spawn_with_settings(|s| {
+        s.tts.provider = "http".into();
+        s.tts.api_url = api_url;
+        s.mediasoup.ffmpeg_path = "/nonexistent/ffmpeg".into();
+    })
+    .await;
+    let tenant = app.seed_tenant("speech2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Workshop").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut host = connect(&app, admin).await;
+    let mut guest = connect(&app, member).await;
+    for ws in [&mut host, &mut guest] {
+        send(ws, "media:join", serde_json::json!({ "room_id": room_id })).await;
+        next_of(ws, "media:transport_created").await;
+    }
+
+    send(
+        &mut host,
+        "media:speak",
+        serde_json::json!({ "room_id": room_id, "text": " Five minutes left ", "voice": "nova" }),
+    )
+    .await;
+    let request = tokio::time::timeout(std::time::Duration::from_secs(5), requests.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(request["input"], "Five minutes left");
+    assert_eq!(request["voice"], "nova");
+    assert_eq!(request["model"], "tts-1");
+
+    // One announcement at a time
+    send(
+        &mut guest,
+        "media:speak",
+        serde_json::json!({ "room_id": room_id, "text": "Me too" }),
+    )
+    .await;
+    let error = next_of(&mut guest, "media:error").await;
+    assert_eq!(error["message"], "An announcement is already playing");
+
+    release.notify_one();
+    let started = next_of(&mut guest, "media:speech").await;
+    assert_eq!(started["action"], "start");
+    assert_eq!(started["text"], "Five minutes left");
+    assert_eq!(started["user_id"], tenant.admin.id.as_str());
+    assert!(!started["producer_id"].as_str().unwrap().is_empty());
+    let ended = next_of(&mut guest, "media:speech").await;
+    assert_eq!(ended["action"], "end");
+    assert_eq!(ended["speech_id"], started["speech_id"]);
+    assert_eq!(ended["reason"], "failed");
+}
@@ -0,0 +1,166 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+async fn timeline(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    query: &str,
+) -> reqwest::Response {
+    app.auth_get(
+        &format!(
+            "/api/tenant/{}/room/{}/call/debug{}",
+            tenant_id, room_id, query
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap()
+}
+
+/// Poll the timeline until it has `count` events; they are written in the
+/// background.
+async fn wait_for_events(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    count: usize,
+) -> Vec<Value> {
+    for _ in 0..50 {
+        let events: Vec<Value> = timeline(app, tenant_id, room_id, token, "")
+            .await
+            .json()
+            .await
+            .unwrap();
+        if events.len() >= count {
+            return events;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    panic!("fewer than {} call debug events", count);
+}
+
+#[tokio::test]
+async fn signaling_steps_show_up_in_the_call_debug_timeline() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("calldebug1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Support call").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+
+    // A failed step is recorded too.
+    send(
+        &mut ws,
+        "media:restart_ice",
+        serde_json::json!({ "room_id": room_id, "transport_id": "nope" }),
+    )
+    .await;
+    next_of(&mut ws, "media:error").await;
+    send(
+        &mut ws,
+        "media:leave",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+
+    let events = wait_for_events(&app, tid, &room_id, admin, 3).await;
+    let kinds: Vec<&str> = events.iter().map(|e| e["kind"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["join", "error", "leave"]);
+    assert!(
+        events
+            .iter()
+            .all(|e| e["user_id"] == tenant.member.id.as_str())
+    );
+    assert!(
+        events[1]["detail"]
+            .as_str()
+            .unwrap()
+            .starts_with("restart_ice failed")
+    );
+
+    // Filtered to one participant
+    let resp = timeline(
+        &app,
+        tid,
+        &room_id,
+        admin,
+        &format!("?user_id={}", tenant.admin.id),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let events: Vec<Value> = resp.json().await.unwrap();
+    assert!(events.is_empty());
+}
+
+#[tokio::test]
+async fn call_debug_timeline_is_for_tenant_admins() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("calldebug2").await;
+    let other = app.seed_tenant("calldebug3").await;
+    let tid = &tenant.tenant_id;
+    let room_id = create_room(&app, tid, &tenant.admin.access_token, "Private").await;
+
+    let resp = timeline(&app, tid, &room_id, &tenant.member.access_token, "").await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Another tenant's admin can't read it through their own tenant.
+    let resp = timeline(
+        &app,
+        &other.tenant_id,
+        &room_id,
+        &other.admin.access_token,
+        "",
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 404);
+}
@@ -0,0 +1,163 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+/// Create a room and return its id.
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+async fn history(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) -> Value {
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/call/history", tenant_id, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn call_history_keeps_each_call_separately() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callhist1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "History").await;
+
+    // First call: both users join, then the organizer ends it.
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    call_action(&app, tid, &room_id, admin, "end").await;
+
+    // Second call: a single participant, auto-ended when they leave.
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, admin, "leave").await;
+
+    let body = history(&app, tid, &room_id, admin).await;
+    assert_eq!(body["total"], 2);
+    let items = body["items"].as_array().unwrap();
+
+    // Newest first
+    let second = &items[0];
+    assert_eq!(second["peak_participants"], 1);
+    assert_eq!(second["participant_count"], 0);
+    assert!(second["ended_at"].is_string());
+    assert_eq!(second["participants"].as_array().unwrap().len(), 1);
+
+    let first = &items[1];
+    assert_eq!(first["started_by"], tenant.admin.id.as_str());
+    assert_eq!(first["peak_participants"], 2);
+    assert_eq!(first["participant_count"], 0);
+    assert!(first["ended_at"].is_string());
+    assert!(first["duration"].is_i64());
+    let participants = first["participants"].as_array().unwrap();
+    assert_eq!(participants.len(), 2);
+    assert!(participants.iter().all(|p| p["left_at"].is_string()));
+    assert!(participants.iter().all(|p| p["duration"].is_i64()));
+}
+
+#[tokio::test]
+async fn call_history_shows_active_call_and_dedupes_joins() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callhist2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Active").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    // Starting again while in progress reuses the session.
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+
+    let body = history(&app, tid, &room_id, admin).await;
+    assert_eq!(body["total"], 1);
+    let active = &body["items"][0];
+    assert!(active["ended_at"].is_null());
+    assert!(active["duration"].is_null());
+    assert_eq!(active["participant_count"], 1);
+    assert_eq!(active["peak_participants"], 1);
+    assert_eq!(active["participants"].as_array().unwrap().len(), 1);
+    assert!(active["participants"][0]["left_at"].is_null());
+}
+
+#[tokio::test]
+async fn call_history_links_recordings_made_during_the_call() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callhist3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Recorded").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    let recording: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/recording", tid, room_id),
+            admin,
+        )
+        .json(&serde_json::json!({ "recording_type": "video" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    call_action(&app, tid, &room_id, admin, "end").await;
+
+    let body = history(&app, tid, &room_id, admin).await;
+    assert_eq!(body["items"][0]["recording_ids"][0], recording["id"]);
+}
+
+#[tokio::test]
+async fn call_history_requires_tenant_membership() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("callhist4").await;
+    let other = app.seed_tenant("callhist4b").await;
+    let room_id = create_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Private",
+    )
+    .await;
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/call/history",
+                tenant.tenant_id, room_id
+            ),
+            &other.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
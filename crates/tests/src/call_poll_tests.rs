@@ -0,0 +1,339 @@
+use crate::fixtures::{
+    seed::SeededTenant,
+    test_app::TestApp,
+    ws::{Ws, next_of},
+};
+use futures::StreamExt;
+use serde_json::Value;
+
+/// Create a room and return its id.
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+/// Start a call that both the admin and the member have joined.
+async fn start_call(app: &TestApp, tenant: &SeededTenant, name: &str) -> String {
+    let tid = &tenant.tenant_id;
+    let room_id = create_room(app, tid, &tenant.admin.access_token, name).await;
+    call_action(app, tid, &room_id, &tenant.admin.access_token, "start").await;
+    call_action(app, tid, &room_id, &tenant.admin.access_token, "join").await;
+    call_action(app, tid, &room_id, &tenant.member.access_token, "join").await;
+    room_id
+}
+
+async fn create_poll(app: &TestApp, url: &str, token: &str, body: Value) -> reqwest::Response {
+    app.auth_post(url, token).json(&body).send().await.unwrap()
+}
+
+async fn vote(app: &TestApp, url: &str, poll_id: &str, token: &str, ids: &[u32]) -> u16 {
+    app.auth_post(&format!("{}/{}/vote", url, poll_id), token)
+        .json(&serde_json::json!({ "option_ids": ids }))
+        .send()
+        .await
+        .unwrap()
+        .status()
+        .as_u16()
+}
+
+async fn get_json(app: &TestApp, url: &str, token: &str) -> Value {
+    let resp = app.auth_get(url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+#[tokio::test]
+async fn poll_results_stay_hidden_until_revealed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("poll1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = start_call(&app, &tenant, "Polls").await;
+    let url = format!("/api/tenant/{}/room/{}/call/poll", tid, room_id);
+
+    let resp = create_poll(
+        &app,
+        &url,
+        admin,
+        serde_json::json!({ "question": "Lunch?", "options": ["Pizza", "Sushi"] }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 201);
+    let poll: Value = resp.json().await.unwrap();
+    let poll_id = poll["id"].as_str().unwrap();
+    assert_eq!(poll["results_visible"], false);
+
+    assert_eq!(vote(&app, &url, poll_id, member, &[1]).await, 200);
+    // One vote per user
+    assert_eq!(vote(&app, &url, poll_id, member, &[0]).await, 409);
+
+    let polls = get_json(&app, &url, member).await;
+    assert_eq!(polls[0]["voted"], true);
+    assert_eq!(polls[0]["total_votes"], 1);
+    assert!(polls[0]["options"][1]["votes"].is_null());
+
+    // Moderators always see the tallies.
+    let polls = get_json(&app, &url, admin).await;
+    assert_eq!(polls[0]["voted"], false);
+    assert_eq!(polls[0]["options"][1]["votes"], 1);
+
+    let resp = app
+        .auth_put(&format!("{}/{}", url, poll_id), admin)
+        .json(&serde_json::json!({ "results_visible": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let polls = get_json(&app, &url, member).await;
+    assert_eq!(polls[0]["options"][0]["votes"], 0);
+    assert_eq!(polls[0]["options"][1]["votes"], 1);
+
+    let resp = app
+        .auth_post(&format!("{}/{}/close", url, poll_id), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let closed: Value = resp.json().await.unwrap();
+    assert_eq!(closed["closed"], true);
+    assert_eq!(vote(&app, &url, poll_id, admin, &[0]).await, 409);
+}
+
+#[tokio::test]
+async fn poll_rules_are_enforced() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("poll2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    // No call in progress
+    let idle = create_room(&app, tid, admin, "Idle").await;
+    let resp = create_poll(
+        &app,
+        &format!("/api/tenant/{}/room/{}/call/poll", tid, idle),
+        admin,
+        serde_json::json!({ "question": "?", "options": ["a", "b"] }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let room_id = start_call(&app, &tenant, "Rules").await;
+    let url = format!("/api/tenant/{}/room/{}/call/poll", tid, room_id);
+    let two = serde_json::json!({ "question": "?", "options": ["a", "b"] });
+
+    // Only moderators run polls.
+    assert_eq!(
+        create_poll(&app, &url, member, two.clone())
+            .await
+            .status()
+            .as_u16(),
+        403
+    );
+    let one_option = serde_json::json!({ "question": "?", "options": ["a"] });
+    assert_eq!(
+        create_poll(&app, &url, admin, one_option)
+            .await
+            .status()
+            .as_u16(),
+        422
+    );
+
+    let single: Value = create_poll(&app, &url, admin, two)
+        .await
+        .json()
+        .await
+        .unwrap();
+    let single_id = single["id"].as_str().unwrap();
+    assert_eq!(vote(&app, &url, single_id, member, &[0, 1]).await, 422);
+    assert_eq!(vote(&app, &url, single_id, member, &[2]).await, 422);
+    assert_eq!(vote(&app, &url, single_id, member, &[]).await, 422);
+
+    let multi: Value = create_poll(
+        &app,
+        &url,
+        admin,
+        serde_json::json!({ "question": "?", "options": ["a", "b", "c"], "allow_multiple": true }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let multi_id = multi["id"].as_str().unwrap();
+    assert_eq!(vote(&app, &url, multi_id, member, &[0, 0]).await, 422);
+    assert_eq!(vote(&app, &url, multi_id, member, &[0, 2]).await, 200);
+
+    let polls = get_json(&app, &url, admin).await;
+    assert_eq!(polls[1]["total_votes"], 1);
+    assert_eq!(polls[1]["options"][0]["votes"], 1);
+    assert_eq!(polls[1]["options"][1]["votes"], 0);
+    assert_eq!(polls[1]["options"][2]["votes"], 1);
+}
+
+#[tokio::test]
+async fn poll_events_hide_tallies_from_participants() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("poll3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = start_call(&app, &tenant, "Live").await;
+    let url = format!("/api/tenant/{}/room/{}/call/poll", tid, room_id);
+
+    let mut organizer = connect(&app, admin).await;
+    let mut participant = connect(&app, member).await;
+
+    let poll: Value = create_poll(
+        &app,
+        &url,
+        admin,
+        serde_json::json!({ "question": "Ready?", "options": ["Yes", "No"] }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let created = next_of(&mut participant, "call:poll:create").await;
+    assert_eq!(created["room_id"], room_id.as_str());
+    assert_eq!(created["poll"]["id"], poll["id"]);
+
+    vote(&app, &url, poll["id"].as_str().unwrap(), member, &[0]).await;
+    let seen = next_of(&mut participant, "call:poll:update").await;
+    assert_eq!(seen["poll"]["total_votes"], 1);
+    assert!(seen["poll"]["options"][0]["votes"].is_null());
+
+    next_of(&mut organizer, "call:poll:create").await;
+    let seen = next_of(&mut organizer, "call:poll:update").await;
+    assert_eq!(seen["poll"]["options"][0]["votes"], 1);
+}
+
+#[tokio::test]
+async fn questions_are_ranked_by_upvotes() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("qa1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = start_call(&app, &tenant, "Q&A").await;
+    let url = format!("/api/tenant/{}/room/{}/call/question", tid, room_id);
+
+    let mut ws = connect(&app, admin).await;
+
+    let mut ids = Vec::new();
+    for content in ["First?", "Second?"] {
+        let resp = app
+            .auth_post(&url, member)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 201);
+        let q: Value = resp.json().await.unwrap();
+        ids.push(q["id"].as_str().unwrap().to_string());
+    }
+    let created = next_of(&mut ws, "call:question:create").await;
+    assert_eq!(created["question"]["content"], "First?");
+
+    // Upvoting twice counts once.
+    let upvote = format!("{}/{}/upvote", url, ids[1]);
+    for _ in 0..2 {
+        let resp = app.auth_post(&upvote, admin).send().await.unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+    app.auth_post(&upvote, member).send().await.unwrap();
+    let updated = next_of(&mut ws, "call:question:update").await;
+    assert_eq!(updated["question"]["upvotes"], 1);
+
+    let questions = get_json(&app, &url, admin).await;
+    assert_eq!(questions[0]["id"], ids[1].as_str());
+    assert_eq!(questions[0]["upvotes"], 2);
+    assert_eq!(questions[0]["upvoted"], true);
+    assert_eq!(questions[1]["upvotes"], 0);
+
+    let resp = app.auth_delete(&upvote, admin).send().await.unwrap();
+    let q: Value = resp.json().await.unwrap();
+    assert_eq!(q["upvotes"], 1);
+    assert_eq!(q["upvoted"], false);
+
+    // Only moderators mark questions answered.
+    let answer = format!("{}/{}/answer", url, ids[0]);
+    let resp = app.auth_post(&answer, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app.auth_post(&answer, admin).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let q: Value = resp.json().await.unwrap();
+    assert_eq!(q["answered"], true);
+}
+
+#[tokio::test]
+async fn call_history_includes_polls_and_questions() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("qa2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = start_call(&app, &tenant, "Recap").await;
+    let base = format!("/api/tenant/{}/room/{}/call", tid, room_id);
+    let poll_url = format!("{}/poll", base);
+
+    let poll: Value = create_poll(
+        &app,
+        &poll_url,
+        admin,
+        serde_json::json!({ "question": "Again next week?", "options": ["Yes", "No"] }),
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    vote(&app, &poll_url, poll["id"].as_str().unwrap(), member, &[0]).await;
+    app.auth_post(&format!("{}/question", base), member)
+        .json(&serde_json::json!({ "content": "Slides?" }))
+        .send()
+        .await
+        .unwrap();
+
+    call_action(&app, tid, &room_id, admin, "end").await;
+
+    // The call is over: nothing current, and the open poll was closed.
+    assert_eq!(
+        get_json(&app, &poll_url, member).await,
+        serde_json::json!([])
+    );
+    let history = get_json(&app, &format!("{}/history", base), member).await;
+    let session = &history["items"][0];
+    assert_eq!(session["polls"][0]["closed"], true);
+    assert_eq!(session["polls"][0]["options"][0]["votes"], 1);
+    assert_eq!(session["questions"][0]["content"], "Slides?");
+}
@@ -0,0 +1,151 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of_within, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn join(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+async fn start_call(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, body: Value) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/start", tenant_id, room_id),
+            token,
+        )
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn declining_a_ring_tells_the_caller() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ring1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut caller = connect(&app, &tenant.admin.access_token).await;
+    let mut callee = connect(&app, &tenant.member.access_token).await;
+    start_call(
+        &app,
+        tid,
+        room_id,
+        &tenant.admin.access_token,
+        serde_json::json!({ "ring": [tenant.member.id] }),
+    )
+    .await;
+
+    let ring = next_of_within(&mut callee, "call:ring", 3).await;
+    assert_eq!(ring["room_id"], room_id.as_str());
+    assert_eq!(ring["caller_id"], tenant.admin.id.as_str());
+
+    send(
+        &mut callee,
+        "call:decline",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let update = next_of_within(&mut caller, "call:ring_update", 3).await;
+    assert_eq!(update["user_id"], tenant.member.id.as_str());
+    assert_eq!(update["status"], "declined");
+    let cancelled = next_of_within(&mut callee, "call:ring_cancelled", 3).await;
+    assert_eq!(cancelled["reason"], "declined");
+}
+
+#[tokio::test]
+async fn unanswered_ring_becomes_a_missed_call() {
+    let app = TestApp::spawn_with_settings(|s| s.ws.ring_timeout_secs = 1).await;
+    let tenant = app.seed_tenant("ring2").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut caller = connect(&app, &tenant.admin.access_token).await;
+    let mut callee = connect(&app, &tenant.member.access_token).await;
+    start_call(
+        &app,
+        tid,
+        room_id,
+        &tenant.admin.access_token,
+        serde_json::json!({ "ring_all": true }),
+    )
+    .await;
+
+    next_of_within(&mut callee, "call:ring", 3).await;
+    let cancelled = next_of_within(&mut callee, "call:ring_cancelled", 5).await;
+    assert_eq!(cancelled["reason"], "timeout");
+    let update = next_of_within(&mut caller, "call:ring_update", 3).await;
+    assert_eq!(update["status"], "missed");
+
+    let resp = app
+        .auth_get("/api/notification", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    let items = json["items"].as_array().unwrap();
+    assert!(
+        items.iter().any(|n| n["notification_type"] == "missedcall"),
+        "Expected a missed-call notification, got {:?}",
+        items
+    );
+    assert!(
+        !items.iter().any(|n| n["notification_type"] == "call"),
+        "Rung members don't also get the call-started notification"
+    );
+}
+
+#[tokio::test]
+async fn ending_the_call_cancels_the_ring() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ring3").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut callee = connect(&app, &tenant.member.access_token).await;
+    start_call(
+        &app,
+        tid,
+        room_id,
+        &tenant.admin.access_token,
+        serde_json::json!({ "ring_all": true }),
+    )
+    .await;
+    next_of_within(&mut callee, "call:ring", 3).await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/end", tid, room_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let cancelled = next_of_within(&mut callee, "call:ring_cancelled", 3).await;
+    assert_eq!(cancelled["reason"], "ended");
+    let notification = next_of_within(&mut callee, "notification:new", 3).await;
+    assert_eq!(notification["notification_type"], "missed_call");
+}
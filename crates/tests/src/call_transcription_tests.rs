@@ -0,0 +1,128 @@
+use crate::fixtures::test_app::TestApp;
+use futures::StreamExt;
+use serde_json::{Value, json};
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+async fn set_opt_out(app: &TestApp, url: &str, token: &str, opted_out: bool) -> reqwest::Response {
+    app.auth_put(url, token)
+        .json(&json!({ "opted_out": opted_out }))
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn get_json(app: &TestApp, url: &str, token: &str) -> Value {
+    let resp = app.auth_get(url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn opted_out_participants_are_shown_and_not_transcribed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("transcopt1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_url = format!("/api/tenant/{}/room/{}", tid, room_id);
+    let opt_out_url = format!("{}/call/transcription", room_url);
+
+    let resp = set_opt_out(&app, &opt_out_url, member, true).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    call_action(&app, tid, room_id, admin, "start").await;
+    call_action(&app, tid, room_id, admin, "join").await;
+    call_action(&app, tid, room_id, member, "join").await;
+
+    // Only those who may join the call can opt out of it.
+    let outsider = app.seed_tenant("transcopt1b").await;
+    let resp = set_opt_out(&app, &opt_out_url, &outsider.admin.access_token, true).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let (mut ws, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/ws?token={}", app.addr, admin))
+            .await
+            .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+
+    let resp = set_opt_out(&app, &opt_out_url, member, true).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["opted_out_user_ids"],
+        json!([tenant.member.id.as_str()])
+    );
+
+    let event = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let Ok(parsed) = serde_json::from_str::<Value>(msg.to_text().unwrap_or("")) else {
+                continue;
+            };
+            if parsed["type"] == "call:transcription_opt_out" {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .expect("no call:transcription_opt_out event");
+    assert_eq!(event["user_id"], tenant.member.id.as_str());
+    assert_eq!(event["opted_out"], true);
+
+    let history = get_json(&app, &format!("{}/call/history", room_url), admin).await;
+    assert_eq!(
+        history["items"][0]["transcription_opt_outs"],
+        json!([tenant.member.id.as_str()])
+    );
+
+    let recording: Value = app
+        .auth_post(&format!("{}/recording", room_url), admin)
+        .json(&json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let transcript_url = format!(
+        "{}/recording/{}/transcript",
+        room_url,
+        recording["id"].as_str().unwrap()
+    );
+    let resp = app
+        .auth_put(&transcript_url, admin)
+        .json(&json!({ "segments": [
+            { "user_id": tenant.admin.id, "speaker_name": "Admin", "text": "Welcome", "start_time": 0.0, "end_time": 1.0 },
+            { "user_id": tenant.member.id, "speaker_name": "Member", "text": "My secret", "start_time": 1.0, "end_time": 2.0 },
+        ] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let transcript = get_json(&app, &transcript_url, admin).await;
+    let texts: Vec<&str> = transcript["segments"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|s| s["text"].as_str().unwrap())
+        .collect();
+    assert_eq!(texts, ["Welcome", "[not transcribed]"]);
+
+    let resp = set_opt_out(&app, &opt_out_url, member, false).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["opted_out_user_ids"], json!([]));
+}
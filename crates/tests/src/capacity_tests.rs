@@ -0,0 +1,148 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, media: Value) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": "All hands", "media_settings": media }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    action: &str,
+) -> reqwest::Response {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+        token,
+    )
+    .send()
+    .await
+    .unwrap()
+}
+
+/// Start a call and take its only full seat as the admin.
+async fn fill_room(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) -> Ws {
+    call_action(app, tenant_id, room_id, token, "start").await;
+    call_action(app, tenant_id, room_id, token, "join").await;
+    let mut ws = connect(app, token).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let created = next_of(&mut ws, "media:transport_created").await;
+    assert!(created["overflow"].is_null());
+    ws
+}
+
+#[tokio::test]
+async fn zero_cap_is_rejected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("cap0").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "Nobody",
+            "media_settings": { "max_participants": 0 },
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn full_room_refuses_joins() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("cap1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(
+        &app,
+        tid,
+        admin,
+        serde_json::json!({ "max_participants": 1 }),
+    )
+    .await;
+    let _admin_ws = fill_room(&app, tid, &room_id, admin).await;
+
+    // Another tab of a participant doesn't take a second seat.
+    let resp = call_action(&app, tid, &room_id, admin, "join").await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = call_action(&app, tid, &room_id, member, "join").await;
+    assert_eq!(resp.status().as_u16(), 409);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["error"], "room_full");
+
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let full = next_of(&mut ws, "media:room_full").await;
+    assert_eq!(full["room_id"], room_id.as_str());
+    assert_eq!(full["max_participants"], 1);
+}
+
+#[tokio::test]
+async fn overflow_joins_degraded() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("cap2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(
+        &app,
+        tid,
+        admin,
+        serde_json::json!({ "max_participants": 1, "overflow": "audio_only" }),
+    )
+    .await;
+    let _admin_ws = fill_room(&app, tid, &room_id, admin).await;
+
+    let resp = call_action(&app, tid, &room_id, member, "join").await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["overflow"], "audio_only");
+
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let created = next_of(&mut ws, "media:transport_created").await;
+    assert_eq!(created["overflow"], "audio_only");
+}
@@ -0,0 +1,132 @@
+use roomler_ai_client::{Client, ClientError, CreateMessageRequest, CreateRoomRequest, ListParams};
+
+use crate::fixtures::test_app::TestApp;
+
+async fn sdk_client(app: &TestApp, access_token: &str, refresh_token: &str) -> Client {
+    let client = Client::new(app.base_url.clone());
+    client.set_tokens(access_token, Some(refresh_token.to_string()));
+    client
+}
+
+fn create_room(name: &str) -> CreateRoomRequest {
+    CreateRoomRequest {
+        name: name.to_string(),
+        parent_id: None,
+        is_open: true,
+        media_settings: None,
+    }
+}
+
+#[tokio::test]
+async fn sdk_creates_room_and_pages_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sdkmsg").await;
+    let client = sdk_client(
+        &app,
+        &tenant.admin.access_token,
+        &tenant.admin.refresh_token,
+    )
+    .await;
+
+    let me = client.me().await.unwrap();
+    assert_eq!(me.id, tenant.admin.id);
+
+    let room = client
+        .create_room(&tenant.tenant_id, &create_room("SDK Room"))
+        .await
+        .unwrap();
+    assert_eq!(room.name, "SDK Room");
+
+    for i in 0..3 {
+        let msg = client
+            .send_message(
+                &tenant.tenant_id,
+                &room.id,
+                &CreateMessageRequest::text(format!("hello {i}")),
+            )
+            .await
+            .unwrap();
+        assert_eq!(msg.room_id, room.id);
+    }
+
+    let mut seen = Vec::new();
+    let mut params = ListParams::cursor().per_page(2);
+    loop {
+        let page = client
+            .messages(&tenant.tenant_id, &room.id, &params)
+            .await
+            .unwrap();
+        seen.extend(page.items.into_iter().map(|m| m.content));
+        match page.next_cursor {
+            Some(cursor) => params = params.after(cursor),
+            None => break,
+        }
+    }
+    seen.sort();
+    assert_eq!(seen, vec!["hello 0", "hello 1", "hello 2"]);
+}
+
+#[tokio::test]
+async fn sdk_surfaces_api_errors() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sdkerr").await;
+    let client = sdk_client(
+        &app,
+        &tenant.admin.access_token,
+        &tenant.admin.refresh_token,
+    )
+    .await;
+
+    let err = client
+        .messages(
+            &tenant.tenant_id,
+            &tenant.rooms[0].id,
+            &ListParams::default().sort("password"),
+        )
+        .await
+        .unwrap_err();
+    assert!(matches!(err, ClientError::Api { status: 400, .. }), "{err}");
+}
+
+#[tokio::test]
+async fn sdk_refreshes_token_on_401() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sdkrefresh").await;
+    let client = sdk_client(&app, "not-a-valid-token", &tenant.admin.refresh_token).await;
+
+    let tenants = client.tenants().await.unwrap();
+    assert!(tenants.iter().any(|t| t.id == tenant.tenant_id));
+    assert_ne!(client.access_token().as_deref(), Some("not-a-valid-token"));
+}
+
+#[tokio::test]
+async fn sdk_ws_media_join_gets_transports() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sdkws").await;
+    let client = sdk_client(
+        &app,
+        &tenant.admin.access_token,
+        &tenant.admin.refresh_token,
+    )
+    .await;
+
+    let room = client
+        .create_room(&tenant.tenant_id, &create_room("SDK Call"))
+        .await
+        .unwrap();
+    let started = client
+        .call_start(&tenant.tenant_id, &room.id)
+        .await
+        .unwrap();
+    assert!(started.started);
+
+    let mut ws = client.connect_ws().await.unwrap();
+    assert!(ws.wait_for("connected").await.is_some());
+    ws.media_join(&room.id).unwrap();
+    let created = ws.wait_for("media:transport_created").await.unwrap();
+    assert!(created.data["send_transport"]["id"].is_string());
+    assert!(created.data["recv_transport"]["id"].is_string());
+
+    ws.media_leave(&room.id).unwrap();
+    client.call_end(&tenant.tenant_id, &room.id).await.unwrap();
+}
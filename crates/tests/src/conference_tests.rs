@@ -981,6 +981,188 @@ async fn transport_created_no_turn_by_default() {
     ws.close(None).await.ok();
 }
 
+#[tokio::test]
+async fn ice_endpoint_serves_nearest_region_credentials() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.turn.regions =
+            Some("eu=turn:eu.example.com:3478@DE,us=turn:us.example.com:3478@US".to_string());
+        s.turn.shared_secret = Some("ice-test-secret".to_string());
+        s.turn.force_relay = None;
+    })
+    .await;
+    let tenant = app.seed_tenant("ice1").await;
+    let other = app.seed_tenant("ice2").await;
+    let url = format!(
+        "/api/tenant/{}/room/{}/ice",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .header("cf-ipcountry", "US")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let servers = body["ice_servers"].as_array().unwrap();
+    assert_eq!(servers.len(), 2);
+    assert_eq!(servers[0]["region"], "us");
+    assert_eq!(servers[0]["urls"][0], "turn:us.example.com:3478");
+    let username = servers[0]["username"].as_str().unwrap();
+    assert!(
+        username.ends_with(&format!(":{}:us", tenant.member.id)),
+        "username = {}",
+        username
+    );
+    assert!(!servers[0]["credential"].as_str().unwrap().is_empty());
+    assert_eq!(body["force_relay"], false);
+    assert_eq!(body["ttl_secs"], 86400);
+
+    // Not a member of the tenant.
+    let resp = app
+        .auth_get(&url, &other.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/ice",
+                tenant.tenant_id, other.rooms[0].id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn device_test_loops_back_and_expires() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("devtest1").await;
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.member.access_token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    ws.next().await;
+
+    let send = |msg: Value| Message::Text(serde_json::to_string(&msg).unwrap().into());
+    ws.send(send(serde_json::json!({
+        "type": "media:test_join",
+        "data": { "duration_secs": 2 }
+    })))
+    .await
+    .unwrap();
+
+    let ready = next_media_msg(&mut ws).await;
+    assert_eq!(ready["type"], "media:test_ready");
+    let test_id = ready["data"]["test_id"].as_str().unwrap().to_string();
+    assert!(ready["data"]["rtp_capabilities"]["codecs"].is_array());
+    assert!(ready["data"]["send_transport"]["id"].is_string());
+    assert!(ready["data"]["recv_transport"]["id"].is_string());
+    assert_eq!(ready["data"]["duration_secs"], 2);
+
+    ws.send(send(serde_json::json!({
+        "type": "media:test_ping",
+        "data": { "seq": 7 }
+    })))
+    .await
+    .unwrap();
+    let pong = next_media_msg(&mut ws).await;
+    assert_eq!(pong["type"], "media:test_pong");
+    assert_eq!(pong["data"]["seq"], 7);
+
+    ws.send(send(serde_json::json!({
+        "type": "media:test_stats",
+        "data": { "test_id": test_id }
+    })))
+    .await
+    .unwrap();
+    let stats = next_media_msg(&mut ws).await;
+    assert_eq!(stats["type"], "media:test_stats");
+    assert_eq!(stats["data"]["uplink_bps"], 0);
+
+    // A second test on the same connection is refused while one runs.
+    ws.send(send(serde_json::json!({ "type": "media:test_join" })))
+        .await
+        .unwrap();
+    let refused = next_media_msg(&mut ws).await;
+    assert_eq!(refused["type"], "media:error");
+
+    let ended = tokio::time::timeout(std::time::Duration::from_secs(5), next_media_msg(&mut ws))
+        .await
+        .expect("device test did not expire");
+    assert_eq!(ended["type"], "media:test_ended");
+    assert_eq!(ended["data"]["test_id"], test_id.as_str());
+    assert_eq!(ended["data"]["reason"], "expired");
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn connection_quality_is_reported_to_the_call() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("quality1").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Quality",
+    )
+    .await;
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let (mut ws1, _) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+    let (ws2, _) = ws_join_media(&app.addr, &tenant.member.access_token, &room_id).await;
+
+    let report = tokio::time::timeout(std::time::Duration::from_secs(12), async {
+        loop {
+            let msg = next_media_msg(&mut ws1).await;
+            if msg["type"] == "media:connection_quality" {
+                return msg;
+            }
+        }
+    })
+    .await
+    .expect("no media:connection_quality");
+
+    assert_eq!(report["data"]["room_id"], room_id.as_str());
+    let participants = report["data"]["participants"].as_array().unwrap();
+    assert_eq!(participants.len(), 2);
+    let users: Vec<&str> = participants
+        .iter()
+        .map(|p| p["user_id"].as_str().unwrap())
+        .collect();
+    assert!(users.contains(&tenant.admin.id.as_str()));
+    assert!(users.contains(&tenant.member.id.as_str()));
+    for p in participants {
+        // No media flowing yet: nothing lost, no round trip measured.
+        assert_eq!(p["score"], 5);
+        assert!(p["rtt"].is_null());
+        assert_eq!(p["loss"], 0.0);
+    }
+
+    drop(ws2);
+    ws1.close(None).await.ok();
+}
+
 // --- Connection-ID isolation tests ---
 
 /// Helper: connect WS, read "connected" message, send media:join, read router_capabilities + transport_created.
@@ -1264,7 +1446,8 @@ async fn same_user_media_leave_no_self_notification() {
 /// should each receive exactly one peer_left — not zero, not two.
 #[tokio::test]
 async fn same_user_disconnect_notifies_only_other_connections() {
-    let app = TestApp::spawn().await;
+    // No reconnect grace period: peer_left follows the disconnect.
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.reconnect_grace_secs = 0).await;
     let tenant = app.seed_tenant("echo2").await;
     let room_id = create_room_and_start_call(
         &app,
@@ -1623,7 +1806,8 @@ async fn transport_created_force_relay_false_by_default_disables_relay() {
 
 #[tokio::test]
 async fn ws_disconnect_notifies_peers_with_peer_left() {
-    let app = TestApp::spawn().await;
+    // No reconnect grace period: peer_left follows the disconnect.
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.reconnect_grace_secs = 0).await;
     let tenant = app.seed_tenant("connid3").await;
     let room_id = create_room_and_start_call(
         &app,
@@ -1672,3 +1856,372 @@ async fn ws_disconnect_notifies_peers_with_peer_left() {
 
     ws1.close(None).await.ok();
 }
+
+#[tokio::test]
+async fn e2ee_room_rotates_key_epoch_and_relays_envelopes() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("e2ee1").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "Encrypted Call",
+            "media_settings": {
+                "audio_enabled": true,
+                "video_enabled": true,
+                "e2ee_enabled": true,
+            },
+        }))
+        .send()
+        .await
+        .unwrap();
+    let room: Value = resp.json().await.unwrap();
+    assert_eq!(room["e2ee_enabled"], true);
+    let room_id = room["id"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/start",
+                tenant.tenant_id, room_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let started: Value = resp.json().await.unwrap();
+    assert_eq!(started["e2ee"], true);
+
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    // First joiner: epoch 1, alone in the room.
+    let (mut ws1, t1) = ws_join_media(&app.addr, &tenant.admin.access_token, &room_id).await;
+    assert_eq!(t1["data"]["e2ee"], true);
+    let rotate = next_media_msg(&mut ws1).await;
+    assert_eq!(rotate["type"], "media:key_rotate");
+    assert_eq!(rotate["data"]["epoch"], 1);
+    let conn1 = rotate["data"]["participants"][0]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    // Second joiner moves both participants to epoch 2.
+    let (mut ws2, _) = ws_join_media(&app.addr, &tenant.member.access_token, &room_id).await;
+    let rotate2 = next_media_msg(&mut ws2).await;
+    assert_eq!(rotate2["type"], "media:key_rotate");
+    assert_eq!(rotate2["data"]["epoch"], 2);
+    assert_eq!(rotate2["data"]["reason"], "join");
+    let rotate1 = next_media_msg(&mut ws1).await;
+    assert_eq!(rotate1["data"]["epoch"], 2);
+    let conn2 = rotate1["data"]["participants"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|c| c.as_str())
+        .find(|c| *c != conn1)
+        .unwrap()
+        .to_string();
+
+    // Stale-epoch envelopes are dropped; current ones reach only the target.
+    for epoch in [1, 2] {
+        ws1.send(Message::Text(
+            serde_json::to_string(&serde_json::json!({
+                "type": "media:key_distribute",
+                "data": {
+                    "room_id": room_id,
+                    "epoch": epoch,
+                    "keys": [{ "connection_id": conn2, "payload": format!("sealed-key-{epoch}") }],
+                }
+            }))
+            .unwrap()
+            .into(),
+        ))
+        .await
+        .unwrap();
+    }
+
+    let relayed = next_media_msg(&mut ws2).await;
+    assert_eq!(relayed["type"], "media:key_distribute");
+    assert_eq!(relayed["data"]["epoch"], 2);
+    assert_eq!(relayed["data"]["payload"], "sealed-key-2");
+    assert_eq!(relayed["data"]["from_connection_id"], conn1.as_str());
+    assert_eq!(relayed["data"]["from_user_id"], tenant.admin.id);
+
+    ws1.close(None).await.ok();
+    ws2.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn room_bitrate_caps_are_validated_and_applied() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("bwcap1").await;
+    let create = |caps: Value| {
+        app.auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "Capped Call",
+            "media_settings": caps,
+        }))
+        .send()
+    };
+
+    let resp = create(serde_json::json!({ "max_incoming_bitrate": 5_000 }))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let room: Value = create(serde_json::json!({
+        "video_enabled": true,
+        "max_incoming_bitrate": 1_500_000,
+        "max_outgoing_bitrate": 3_000_000,
+    }))
+    .await
+    .unwrap()
+    .json()
+    .await
+    .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+
+    for action in ["start", "join"] {
+        let resp = app
+            .auth_post(
+                &format!(
+                    "/api/tenant/{}/room/{}/call/{}",
+                    tenant.tenant_id, room_id, action
+                ),
+                &tenant.admin.access_token,
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    // Transports are created with the caps applied.
+    let (mut ws, transport) = ws_join_media(&app.addr, &tenant.admin.access_token, room_id).await;
+    assert!(transport["data"]["send_transport"]["id"].is_string());
+    assert!(transport["data"]["recv_transport"]["id"].is_string());
+
+    ws.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn restart_ice_returns_fresh_ice_parameters() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ice1").await;
+    let token = &tenant.admin.access_token;
+    let room_id = create_room_and_start_call(&app, &tenant.tenant_id, token, "ICE Restart").await;
+    app.auth_post(
+        &format!(
+            "/api/tenant/{}/room/{}/call/join",
+            tenant.tenant_id, room_id
+        ),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let (mut ws, transport) = ws_join_media(&app.addr, token, &room_id).await;
+    let send = &transport["data"]["send_transport"];
+    let transport_id = send["id"].as_str().unwrap();
+
+    ws.send(Message::Text(
+        serde_json::json!({
+            "type": "media:restart_ice",
+            "data": { "room_id": room_id, "transport_id": transport_id }
+        })
+        .to_string()
+        .into(),
+    ))
+    .await
+    .unwrap();
+    let restarted = next_media_msg(&mut ws).await;
+    assert_eq!(restarted["type"], "media:ice_restarted");
+    assert_eq!(restarted["data"]["transport_id"], transport_id);
+    let ice = &restarted["data"]["ice_parameters"];
+    assert!(ice["usernameFragment"].is_string());
+    assert_ne!(
+        ice["usernameFragment"],
+        send["ice_parameters"]["usernameFragment"]
+    );
+
+    // Only the caller's own transports can be restarted.
+    ws.send(Message::Text(
+        serde_json::json!({
+            "type": "media:restart_ice",
+            "data": {
+                "room_id": room_id,
+                "transport_id": "00000000-0000-0000-0000-000000000000",
+            }
+        })
+        .to_string()
+        .into(),
+    ))
+    .await
+    .unwrap();
+    let error = next_media_msg(&mut ws).await;
+    assert_eq!(error["type"], "media:error");
+
+    ws.close(None).await.ok();
+}
+
+/// Joins admin and member to a call's media and returns (admin WS, member
+/// WS, member's resume_token).
+async fn join_pair_for_reconnect(
+    app: &TestApp,
+    tenant: &crate::fixtures::seed::SeededTenant,
+    room_id: &str,
+) -> (
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    String,
+) {
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!(
+                "/api/tenant/{}/room/{}/call/join",
+                tenant.tenant_id, room_id
+            ),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+    let (ws_admin, _) = ws_join_media(&app.addr, &tenant.admin.access_token, room_id).await;
+    let (ws_member, transport) =
+        ws_join_media(&app.addr, &tenant.member.access_token, room_id).await;
+    let resume_token = transport["data"]["resume_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    (ws_admin, ws_member, resume_token)
+}
+
+async fn send_rejoin(
+    app: &TestApp,
+    token: &str,
+    resume_token: &str,
+) -> (
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>,
+    Value,
+) {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    ws.next().await;
+    ws.send(Message::Text(
+        serde_json::json!({
+            "type": "media:rejoin",
+            "data": { "resume_token": resume_token }
+        })
+        .to_string()
+        .into(),
+    ))
+    .await
+    .unwrap();
+    let reply = next_media_msg(&mut ws).await;
+    (ws, reply)
+}
+
+#[tokio::test]
+async fn dropped_connection_can_rejoin_within_grace_period() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.reconnect_grace_secs = 1).await;
+    let tenant = app.seed_tenant("rejoin1").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Rejoin",
+    )
+    .await;
+    let (mut ws_admin, ws_member, resume_token) =
+        join_pair_for_reconnect(&app, &tenant, &room_id).await;
+
+    // A blip: the peer hears nothing while the media is suspended.
+    drop(ws_member);
+    let quiet = tokio::time::timeout(std::time::Duration::from_millis(300), ws_admin.next()).await;
+    assert!(quiet.is_err(), "no peer_left during the grace period");
+
+    let (mut ws_member, rejoined) =
+        send_rejoin(&app, &tenant.member.access_token, &resume_token).await;
+    assert_eq!(rejoined["type"], "media:rejoined");
+    assert_eq!(rejoined["data"]["room_id"], room_id.as_str());
+    let new_token = rejoined["data"]["resume_token"].as_str().unwrap();
+    assert_ne!(new_token, resume_token);
+
+    let reconnected = next_media_msg(&mut ws_admin).await;
+    assert_eq!(reconnected["type"], "media:peer_reconnected");
+    assert_eq!(reconnected["data"]["user_id"], tenant.member.id);
+    assert_eq!(
+        reconnected["data"]["previous_connection_id"],
+        rejoined["data"]["previous_connection_id"]
+    );
+
+    // The reclaimed media outlives the old connection's grace period.
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+    let quiet = tokio::time::timeout(std::time::Duration::from_millis(300), ws_admin.next()).await;
+    assert!(quiet.is_err(), "reclaimed media must not expire");
+
+    ws_admin.close(None).await.ok();
+    ws_member.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn suspended_media_expires_after_grace_period() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.reconnect_grace_secs = 1).await;
+    let tenant = app.seed_tenant("rejoin2").await;
+    let room_id = create_room_and_start_call(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Rejoin Expiry",
+    )
+    .await;
+    let (mut ws_admin, ws_member, resume_token) =
+        join_pair_for_reconnect(&app, &tenant, &room_id).await;
+
+    // Someone else's token is useless.
+    let (mut ws_other, reply) = send_rejoin(&app, &tenant.admin.access_token, &resume_token).await;
+    assert_eq!(reply["type"], "media:error");
+    ws_other.close(None).await.ok();
+    // Media errors go to all of the user's connections.
+    assert_eq!(next_media_msg(&mut ws_admin).await["type"], "media:error");
+
+    drop(ws_member);
+    let left = tokio::time::timeout(
+        std::time::Duration::from_secs(3),
+        next_media_msg(&mut ws_admin),
+    )
+    .await
+    .expect("peer_left once the grace period expires");
+    assert_eq!(left["type"], "media:peer_left");
+    assert_eq!(left["data"]["user_id"], tenant.member.id);
+
+    let (mut ws_member, reply) =
+        send_rejoin(&app, &tenant.member.access_token, &resume_token).await;
+    assert_eq!(reply["type"], "media:error");
+    assert_eq!(reply["data"]["message"], "Unknown or expired resume_token");
+
+    ws_admin.close(None).await.ok();
+    ws_member.close(None).await.ok();
+}
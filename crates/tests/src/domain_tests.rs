@@ -0,0 +1,308 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+
+async fn status(resp: reqwest::RequestBuilder) -> u16 {
+    resp.send().await.unwrap().status().as_u16()
+}
+
+async fn role_id(app: &TestApp, tenant_id: &str, name: &str) -> String {
+    let role = app
+        .db
+        .collection::<bson::Document>("roles")
+        .find_one(doc! { "tenant_id": ObjectId::parse_str(tenant_id).unwrap(), "name": name })
+        .await
+        .unwrap()
+        .expect("role not found");
+    role.get_object_id("_id").unwrap().to_hex()
+}
+
+/// DNS is out of reach in tests, so mark the claim verified directly.
+async fn mark_verified(app: &TestApp, domain_id: &str) {
+    app.db
+        .collection::<bson::Document>("tenant_domains")
+        .update_one(
+            doc! { "_id": ObjectId::parse_str(domain_id).unwrap() },
+            doc! { "$set": { "verified_at": bson::DateTime::now() } },
+        )
+        .await
+        .unwrap();
+}
+
+async fn claim(app: &TestApp, tenant_id: &str, token: &str, body: Value) -> String {
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/domain", tenant_id), token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let domain: Value = resp.json().await.unwrap();
+    domain["id"].as_str().unwrap().to_string()
+}
+
+async fn joinable(app: &TestApp, token: &str) -> Vec<Value> {
+    let list: Value = app
+        .auth_get("/api/tenant/joinable", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    list.as_array().unwrap().clone()
+}
+
+#[tokio::test]
+async fn domain_claims_are_managed_by_tenant_admins() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("domain1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let url = format!("/api/tenant/{}/domain", tid);
+
+    let member_req = app
+        .auth_post(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "domain": "corp1.example" }));
+    assert_eq!(status(member_req).await, 403);
+
+    let bad = app
+        .auth_post(&url, admin)
+        .json(&serde_json::json!({ "domain": "not a domain" }));
+    assert_eq!(status(bad).await, 422);
+
+    let resp = app
+        .auth_post(&url, admin)
+        .json(&serde_json::json!({ "domain": "Corp1.Example." }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let domain: Value = resp.json().await.unwrap();
+    assert_eq!(domain["domain"], "corp1.example");
+    assert_eq!(domain["verified"], false);
+    assert_eq!(domain["join_mode"], "offer");
+    assert_eq!(domain["txt_name"], "_roomler-verify.corp1.example");
+    assert!(
+        domain["txt_value"]
+            .as_str()
+            .unwrap()
+            .starts_with("roomler-verify=")
+    );
+
+    let dup = app
+        .auth_post(&url, admin)
+        .json(&serde_json::json!({ "domain": "corp1.example" }));
+    assert_eq!(status(dup).await, 409);
+
+    let owner = role_id(&app, tid, "owner").await;
+    let admin_role = app
+        .auth_post(&url, admin)
+        .json(&serde_json::json!({ "domain": "other1.example", "role_id": owner }));
+    assert_eq!(status(admin_role).await, 422);
+
+    let list: Value = app
+        .auth_get(&url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+
+    let did = domain["id"].as_str().unwrap();
+    let moderator = role_id(&app, tid, "moderator").await;
+    let resp = app
+        .auth_put(&format!("{}/{}", url, did), admin)
+        .json(&serde_json::json!({ "join_mode": "auto", "role_id": moderator }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let updated: Value = resp.json().await.unwrap();
+    assert_eq!(updated["join_mode"], "auto");
+    assert_eq!(updated["role_id"], moderator.as_str());
+
+    assert_eq!(
+        status(app.auth_delete(&format!("{}/{}", url, did), admin)).await,
+        200
+    );
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?filter[action]=domain.create", tid),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn verified_domain_is_offered_to_matching_users() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("domain2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    let did = claim(
+        &app,
+        tid,
+        admin,
+        serde_json::json!({ "domain": "corp2.example" }),
+    )
+    .await;
+    let alice = app
+        .register_user(
+            "alice@corp2.example",
+            "alice2",
+            "Alice",
+            "Alice123!",
+            None,
+            None,
+        )
+        .await;
+    let join_url = format!("/api/tenant/{}/domain/join", tid);
+
+    // Nothing is offered until the domain is verified.
+    assert!(joinable(&app, &alice.access_token).await.is_empty());
+    assert_eq!(
+        status(app.auth_post(&join_url, &alice.access_token)).await,
+        403
+    );
+
+    mark_verified(&app, &did).await;
+    let offers = joinable(&app, &alice.access_token).await;
+    assert_eq!(offers.len(), 1);
+    assert_eq!(offers[0]["tenant_id"], tid.as_str());
+    assert_eq!(offers[0]["domain"], "corp2.example");
+
+    assert_eq!(
+        status(app.auth_post(&join_url, &alice.access_token)).await,
+        200
+    );
+    assert_eq!(
+        status(app.auth_post(&join_url, &alice.access_token)).await,
+        409
+    );
+    assert!(joinable(&app, &alice.access_token).await.is_empty());
+
+    let member = app
+        .db
+        .collection::<bson::Document>("tenant_members")
+        .find_one(doc! {
+            "tenant_id": ObjectId::parse_str(tid).unwrap(),
+            "user_id": ObjectId::parse_str(&alice.id).unwrap(),
+        })
+        .await
+        .unwrap()
+        .expect("membership not created");
+    let roles = member.get_array("role_ids").unwrap();
+    let member_role = role_id(&app, tid, "member").await;
+    assert_eq!(roles[0].as_object_id().unwrap().to_hex(), member_role);
+
+    let bob = app
+        .register_user(
+            "bob@elsewhere.example",
+            "bob2",
+            "Bob",
+            "Bob12345!",
+            None,
+            None,
+        )
+        .await;
+    assert!(joinable(&app, &bob.access_token).await.is_empty());
+    assert_eq!(
+        status(app.auth_post(&join_url, &bob.access_token)).await,
+        403
+    );
+}
+
+#[tokio::test]
+async fn auto_domain_joins_on_activation() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("domain3").await;
+    let tid = &tenant.tenant_id;
+    let moderator = role_id(&app, tid, "moderator").await;
+
+    let did = claim(
+        &app,
+        tid,
+        &tenant.admin.access_token,
+        serde_json::json!({
+            "domain": "corp3.example",
+            "join_mode": "auto",
+            "role_id": moderator,
+        }),
+    )
+    .await;
+    mark_verified(&app, &did).await;
+
+    let resp = app
+        .client
+        .post(app.url("/api/auth/register"))
+        .json(&serde_json::json!({
+            "email": "carol@corp3.example",
+            "username": "carol3",
+            "display_name": "Carol",
+            "password": "Carol123!",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    let user = app
+        .db
+        .collection::<bson::Document>("users")
+        .find_one(doc! { "email": "carol@corp3.example" })
+        .await
+        .unwrap()
+        .unwrap();
+    let user_id = user.get_object_id("_id").unwrap();
+    let members = app.db.collection::<bson::Document>("tenant_members");
+    let membership = doc! {
+        "tenant_id": ObjectId::parse_str(tid).unwrap(),
+        "user_id": user_id,
+    };
+    // Not before the email is verified.
+    assert!(
+        members
+            .find_one(membership.clone())
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    let code = app
+        .db
+        .collection::<bson::Document>("activation_codes")
+        .find_one(doc! { "user_id": user_id })
+        .await
+        .unwrap()
+        .unwrap();
+    let resp = app
+        .client
+        .post(app.url("/api/auth/activate"))
+        .json(&serde_json::json!({
+            "user_id": user_id.to_hex(),
+            "token": code.get_str("token").unwrap(),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let member = members
+        .find_one(membership)
+        .await
+        .unwrap()
+        .expect("auto-join did not add the user");
+    let roles = member.get_array("role_ids").unwrap();
+    assert_eq!(roles[0].as_object_id().unwrap().to_hex(), moderator);
+}
@@ -0,0 +1,308 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, next_of_within, send};
+use bson::oid::ObjectId;
+use futures::StreamExt;
+use mediasoup::prelude::MediaKind;
+use rcgen::{BasicConstraints, CertificateParams, ExtendedKeyUsagePurpose, IsCa, KeyPair};
+use roomler_ai_config::{ControlSettings, MediaBackendKind, MediasoupSettings};
+use roomler_ai_services::media::backend;
+use roomler_ai_services::media::room_manager::{RemoteProducer, RoomManager};
+use serde_json::Value;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// A CA and one pod identity for 127.0.0.1, shared by both pods, written
+/// out as `control.*` expects them.
+fn pki() -> (PathBuf, PathBuf, PathBuf) {
+    let ca_key = KeyPair::generate().unwrap();
+    let mut ca = CertificateParams::new(Vec::new()).unwrap();
+    ca.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    let ca_cert = ca.self_signed(&ca_key).unwrap();
+
+    let key = KeyPair::generate().unwrap();
+    let mut pod = CertificateParams::new(vec!["127.0.0.1".to_string()]).unwrap();
+    pod.extended_key_usages = vec![
+        ExtendedKeyUsagePurpose::ServerAuth,
+        ExtendedKeyUsagePurpose::ClientAuth,
+    ];
+    let cert = pod.signed_by(&key, &ca_cert, &ca_key).unwrap();
+
+    let dir = std::env::temp_dir().join(format!("roomler-control-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let paths = (dir.join("pod.crt"), dir.join("pod.key"), dir.join("ca.crt"));
+    std::fs::write(&paths.0, cert.pem()).unwrap();
+    std::fs::write(&paths.1, key.serialize_pem()).unwrap();
+    std::fs::write(&paths.2, ca_cert.pem()).unwrap();
+    paths
+}
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// A pod on the mock media backend, registered as `name` and reaching
+/// its peers' control planes on `peer_port`. `db_name` shares another
+/// pod's database.
+async fn spawn_pod(
+    name: &str,
+    db_name: Option<&str>,
+    pki: &(PathBuf, PathBuf, PathBuf),
+    peer_port: u16,
+) -> (TestApp, u16) {
+    let port = free_port();
+    let app = TestApp::spawn_with_settings(|s| {
+        s.mediasoup.backend = MediaBackendKind::Mock;
+        s.app.instance_url = Some(format!("http://127.0.0.1/{}", name));
+        if let Some(db_name) = db_name {
+            s.database.name = db_name.to_string();
+        }
+        s.control = ControlSettings {
+            listen_addr: format!("127.0.0.1:{}", port),
+            cert_path: pki.0.display().to_string(),
+            key_path: pki.1.display().to_string(),
+            ca_path: pki.2.display().to_string(),
+            peer_port,
+        };
+    })
+    .await;
+    (app, port)
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+#[tokio::test]
+async fn listeners_on_another_pod_get_the_call_over_a_pipe() {
+    let pki = pki();
+    let (owner, owner_port) = spawn_pod("pod-a", None, &pki, 0).await;
+    let (edge, _) = spawn_pod("pod-b", Some(owner.db.name()), &pki, owner_port).await;
+
+    let tenant = owner.seed_tenant("edgemedia1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room: Value = owner
+        .auth_post(&format!("/api/tenant/{}/room", tid), admin)
+        .json(&serde_json::json!({ "name": "Town hall" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+    owner
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/start", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+
+    let mut speaker = connect(&owner, admin).await;
+    send(
+        &mut speaker,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of(&mut speaker, "media:transport_created").await;
+    send(
+        &mut speaker,
+        "media:produce",
+        serde_json::json!({
+            "room_id": room_id,
+            "kind": "audio",
+            "rtp_parameters": {
+                "mid": "0",
+                "codecs": [{
+                    "mimeType": "audio/opus",
+                    "clockRate": 48000,
+                    "channels": 2,
+                    "payloadType": 111,
+                    "parameters": {},
+                    "rtcpFeedback": [],
+                }],
+                "headerExtensions": [],
+                "encodings": [{ "ssrc": 2222 }],
+                "rtcp": { "cname": "mock" },
+            },
+        }),
+    )
+    .await;
+    let produced = next_of(&mut speaker, "media:produce_result").await;
+    let producer_id = produced["id"].as_str().unwrap().to_string();
+
+    // Without listen_only the edge pod still redirects.
+    let mut listener = connect(&edge, member).await;
+    send(
+        &mut listener,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let redirect = next_of(&mut listener, "media:redirect").await;
+    assert_eq!(redirect["url"], "http://127.0.0.1/pod-a");
+
+    // A listener joins on the edge pod and gets the speaker piped over.
+    send(
+        &mut listener,
+        "media:join",
+        serde_json::json!({ "room_id": room_id, "listen_only": true }),
+    )
+    .await;
+    let caps = next_of(&mut listener, "media:router_capabilities").await;
+    next_of(&mut listener, "media:transport_created").await;
+    let announced = next_of(&mut listener, "media:new_producer").await;
+    assert_eq!(announced["producer_id"], producer_id.as_str());
+    assert_eq!(announced["user_id"], tenant.admin.id.as_str());
+
+    send(
+        &mut listener,
+        "media:consume",
+        serde_json::json!({
+            "room_id": room_id,
+            "producer_id": producer_id,
+            "rtp_capabilities": caps["rtp_capabilities"],
+        }),
+    )
+    .await;
+    let consumer = next_of(&mut listener, "media:consumer_created").await;
+    assert_eq!(consumer["producer_id"], producer_id.as_str());
+    assert_eq!(consumer["rtp_parameters"]["encodings"][0]["ssrc"], 2222);
+
+    // Listening only: the edge takes nothing from its joiners.
+    send(
+        &mut listener,
+        "media:produce",
+        serde_json::json!({
+            "room_id": room_id,
+            "kind": "audio",
+            "rtp_parameters": consumer["rtp_parameters"],
+        }),
+    )
+    .await;
+    next_of(&mut listener, "media:error").await;
+
+    // Closing the producer on the owner reaches the edge's listeners by
+    // the next sync.
+    send(
+        &mut speaker,
+        "media:producer_close",
+        serde_json::json!({ "room_id": room_id, "producer_id": producer_id }),
+    )
+    .await;
+    let closed = next_of_within(&mut listener, "media:producer_closed", 10).await;
+    assert_eq!(closed["producer_id"], producer_id.as_str());
+}
+
+/// A pod's media on real mediasoup workers, on its own port range.
+async fn media_pod(rtc_min_port: u16) -> RoomManager {
+    let settings = MediasoupSettings {
+        num_workers: 1,
+        listen_ip: "127.0.0.1".to_string(),
+        announced_ip: String::new(),
+        rtc_min_port,
+        rtc_max_port: rtc_min_port + 100,
+        reconnect_grace_secs: 0,
+        watchdog_secs: 0,
+        backend: MediaBackendKind::Mediasoup,
+        ffmpeg_path: "ffmpeg".to_string(),
+    };
+    RoomManager::new(backend::from_settings(&settings).await.unwrap())
+}
+
+/// An Opus RTP packet with a few bytes of payload.
+fn rtp_packet(ssrc: u32, seq: u16) -> Vec<u8> {
+    let mut packet = vec![0x80, 111];
+    packet.extend_from_slice(&seq.to_be_bytes());
+    packet.extend_from_slice(&(u32::from(seq) * 960).to_be_bytes());
+    packet.extend_from_slice(&ssrc.to_be_bytes());
+    packet.extend_from_slice(&[0xf8, 0xff, 0xfe]);
+    packet
+}
+
+#[tokio::test]
+async fn rtp_crosses_the_pipe_between_two_pods() {
+    let owner = media_pod(41000).await;
+    let edge = media_pod(41200).await;
+    let rid = ObjectId::new();
+    owner.create_room(rid).await.unwrap();
+    edge.create_edge(rid).await.unwrap();
+
+    let parameters = serde_json::from_value(serde_json::json!({
+        "mid": "0",
+        "codecs": [{
+            "mimeType": "audio/opus",
+            "clockRate": 48000,
+            "channels": 2,
+            "payloadType": 111,
+            "parameters": {},
+            "rtcpFeedback": [],
+        }],
+        "headerExtensions": [],
+        "encodings": [{ "ssrc": 3333 }],
+        "rtcp": { "cname": "pipe" },
+    }))
+    .unwrap();
+    let (producer_id, tx) = owner
+        .inject_producer(&rid, MediaKind::Audio, parameters)
+        .await
+        .unwrap();
+
+    // What the control plane does between the pods, called directly.
+    let remote = RemoteProducer {
+        producer_id,
+        user_id: ObjectId::new(),
+        connection_id: "speaker".to_string(),
+        source: "audio".to_string(),
+    };
+    let owner_ref = &owner;
+    edge.pipe_in(&rid, remote, |ip, port| async move {
+        owner_ref
+            .pipe_producer(&rid, producer_id, SocketAddr::new(ip.parse()?, port))
+            .await
+    })
+    .await
+    .unwrap();
+    assert_eq!(edge.piped_in_ids(&rid), vec![producer_id.to_string()]);
+
+    // Packets sent on the owner come out of the edge Router.
+    let mut tap = edge.create_rtp_tap(&rid, producer_id).await.unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(5), async {
+        let mut seq = 0u16;
+        loop {
+            tx.send(rtp_packet(3333, seq)).await.unwrap();
+            seq += 1;
+            tokio::select! {
+                packet = tap.recv() => return packet,
+                _ = tokio::time::sleep(Duration::from_millis(20)) => {}
+            }
+        }
+    })
+    .await
+    .expect("no RTP came across the pipe")
+    .unwrap();
+    assert_eq!(received[0] >> 6, 2);
+
+    // The owner drops its end with the producer.
+    assert!(owner.remove_injected(&rid, &producer_id.to_string()));
+    assert!(
+        edge.remove_piped_in(&rid, &producer_id.to_string())
+            .is_some()
+    );
+    assert!(edge.piped_in_ids(&rid).is_empty());
+}
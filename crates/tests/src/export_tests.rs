@@ -194,3 +194,126 @@ async fn list_background_tasks() {
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["total"], 2);
 }
+
+#[tokio::test]
+async fn room_archive_zip_contains_messages_files_and_history() {
+    use std::io::Read;
+
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("archive1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = tenant.rooms[0].id.clone();
+    let admin = &tenant.admin.access_token;
+
+    app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), admin)
+        .send()
+        .await
+        .unwrap();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+        admin,
+    )
+    .json(&serde_json::json!({ "content": "Keep <this> for the record" }))
+    .send()
+    .await
+    .unwrap();
+    let part = reqwest::multipart::Part::bytes(b"minutes".to_vec())
+        .file_name("minutes.txt")
+        .mime_str("text/plain")
+        .unwrap();
+    let file: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/room/{}/file/upload", tid, room_id)))
+        .header("Authorization", format!("Bearer {}", admin))
+        .multipart(reqwest::multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // Archives are an admin operation.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/export/room/{}", tid, room_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let json: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/export/room/{}", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let task_id = json["task_id"].as_str().unwrap().to_string();
+
+    let mut completed = false;
+    for _ in 0..20 {
+        tokio::time::sleep(tokio::time::Duration::from_millis(250)).await;
+        let json: Value = app
+            .auth_get(&format!("/api/tenant/{}/task/{}", tid, task_id), admin)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        match json["status"].as_str().unwrap() {
+            "Completed" => {
+                completed = true;
+                assert!(json["file_name"].as_str().unwrap().ends_with(".zip"));
+                break;
+            }
+            "Failed" => panic!("Archive task failed: {:?}", json["error"]),
+            _ => {}
+        }
+    }
+    assert!(completed, "Archive task did not complete within timeout");
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/task/{}/download", tid, task_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.headers()["content-type"], "application/zip");
+    let body = resp.bytes().await.unwrap();
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(body.to_vec())).unwrap();
+    let mut read = |name: &str| {
+        let mut text = String::new();
+        zip.by_name(name)
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        text
+    };
+
+    let messages: Value = serde_json::from_str(&read("messages.json")).unwrap();
+    assert!(
+        messages
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|m| m["content"] == "Keep <this> for the record")
+    );
+    assert!(read("messages.html").contains("Keep &lt;this&gt; for the record"));
+    let room: Value = serde_json::from_str(&read("room.json")).unwrap();
+    assert_eq!(room["id"], room_id.as_str());
+    let attachment = format!("attachments/{}-minutes.txt", file["id"].as_str().unwrap());
+    assert_eq!(read(&attachment), "minutes");
+    let calls: Value = serde_json::from_str(&read("calls.json")).unwrap();
+    assert!(calls.as_array().unwrap().is_empty());
+    assert_eq!(read("transcripts/call-chat.txt"), "");
+}
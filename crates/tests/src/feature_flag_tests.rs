@@ -0,0 +1,214 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::{Value, json};
+
+fn flags_url(tenant_id: &str) -> String {
+    format!("/api/tenant/{}/feature-flag", tenant_id)
+}
+
+async fn set_flag(
+    app: &TestApp,
+    tenant_id: &str,
+    token: &str,
+    key: &str,
+    body: Value,
+) -> reqwest::Response {
+    app.auth_put(&format!("{}/{}", flags_url(tenant_id), key), token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn list_flags(app: &TestApp, tenant_id: &str, token: &str) -> Vec<Value> {
+    let resp = app
+        .auth_get(&flags_url(tenant_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+fn flag<'a>(flags: &'a [Value], key: &str) -> &'a Value {
+    flags.iter().find(|f| f["key"] == key).unwrap()
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, body: Value) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    action: &str,
+) -> Value {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn members_list_flags_and_managers_set_them() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("flags1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let flags = list_flags(&app, tid, member).await;
+    let breakouts = flag(&flags, "breakout_rooms");
+    assert_eq!(breakouts["mode"], "default");
+    assert_eq!(breakouts["default"], true);
+    assert_eq!(breakouts["enabled"], true);
+
+    let resp = set_flag(
+        &app,
+        tid,
+        member,
+        "breakout_rooms",
+        json!({ "mode": "off" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = set_flag(&app, tid, admin, "no_such_flag", json!({ "mode": "on" })).await;
+    assert_eq!(resp.status().as_u16(), 404);
+
+    for body in [
+        json!({ "mode": "percentage" }),
+        json!({ "mode": "percentage", "percentage": 101 }),
+    ] {
+        let resp = set_flag(&app, tid, admin, "breakout_rooms", body).await;
+        assert_eq!(resp.status().as_u16(), 422);
+    }
+
+    let resp = set_flag(&app, tid, admin, "breakout_rooms", json!({ "mode": "off" })).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let updated: Value = resp.json().await.unwrap();
+    assert_eq!(updated["mode"], "off");
+    assert_eq!(updated["enabled"], false);
+
+    let flags = list_flags(&app, tid, member).await;
+    assert_eq!(flag(&flags, "breakout_rooms")["enabled"], false);
+
+    // Full and empty rollouts are the same for everyone.
+    for (percentage, enabled) in [(100, true), (0, false)] {
+        let resp = set_flag(
+            &app,
+            tid,
+            admin,
+            "breakout_rooms",
+            json!({ "mode": "percentage", "percentage": percentage }),
+        )
+        .await;
+        assert_eq!(resp.status().as_u16(), 200);
+        let flags = list_flags(&app, tid, member).await;
+        assert_eq!(flag(&flags, "breakout_rooms")["percentage"], percentage);
+        assert_eq!(flag(&flags, "breakout_rooms")["enabled"], enabled);
+    }
+
+    // Other tenants are unaffected.
+    let other = app.seed_tenant("flags1b").await;
+    let flags = list_flags(&app, &other.tenant_id, &other.member.access_token).await;
+    assert_eq!(flag(&flags, "breakout_rooms")["enabled"], true);
+}
+
+#[tokio::test]
+async fn disabled_breakout_rooms_are_refused() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("flags2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, json!({ "name": "Workshop" })).await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+
+    let breakout_url = format!("/api/tenant/{}/room/{}/call/breakout", tid, room_id);
+    set_flag(&app, tid, admin, "breakout_rooms", json!({ "mode": "off" })).await;
+    let resp = app
+        .auth_post(&breakout_url, admin)
+        .json(&json!({ "count": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    set_flag(&app, tid, admin, "breakout_rooms", json!({ "mode": "on" })).await;
+    let resp = app
+        .auth_post(&breakout_url, admin)
+        .json(&json!({ "count": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+}
+
+#[tokio::test]
+async fn disabled_e2ee_starts_unencrypted_calls() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("flags3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(
+        &app,
+        tid,
+        admin,
+        json!({
+            "name": "Encrypted Call",
+            "media_settings": { "audio_enabled": true, "video_enabled": true, "e2ee_enabled": true },
+        }),
+    )
+    .await;
+
+    set_flag(&app, tid, admin, "e2ee", json!({ "mode": "off" })).await;
+    let started = call_action(&app, tid, &room_id, admin, "start").await;
+    assert_eq!(started["e2ee"], false);
+}
+
+#[tokio::test]
+async fn deployment_default_applies_until_a_tenant_sets_the_flag() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.features.insert("breakout_rooms".to_string(), false);
+    })
+    .await;
+    let tenant = app.seed_tenant("flags4").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+
+    let flags = list_flags(&app, tid, admin).await;
+    assert_eq!(flag(&flags, "breakout_rooms")["default"], false);
+    assert_eq!(flag(&flags, "breakout_rooms")["enabled"], false);
+
+    set_flag(&app, tid, admin, "breakout_rooms", json!({ "mode": "on" })).await;
+    let flags = list_flags(&app, tid, admin).await;
+    assert_eq!(flag(&flags, "breakout_rooms")["enabled"], true);
+
+    set_flag(
+        &app,
+        tid,
+        admin,
+        "breakout_rooms",
+        json!({ "mode": "default" }),
+    )
+    .await;
+    let flags = list_flags(&app, tid, admin).await;
+    assert_eq!(flag(&flags, "breakout_rooms")["enabled"], false);
+}
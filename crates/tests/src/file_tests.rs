@@ -1,6 +1,11 @@
 use crate::fixtures::test_app::TestApp;
+use axum::{Json, Router, routing::post};
+use futures::StreamExt;
 use reqwest::multipart;
 use serde_json::Value;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::sync::Notify;
 
 #[tokio::test]
 async fn upload_file_to_room() {
@@ -288,3 +293,282 @@ async fn list_files_in_room() {
     let items = json["items"].as_array().unwrap();
     assert_eq!(items.len(), 2);
 }
+
+/// A clamd stand-in: answers `INSTREAM` with FOUND for bodies containing
+/// "EICAR", OK otherwise.
+async fn fake_clamd() -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap().to_string();
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            tokio::spawn(async move {
+                let mut command = [0u8; 10];
+                socket.read_exact(&mut command).await.unwrap();
+                let mut body = Vec::new();
+                loop {
+                    let len = socket.read_u32().await.unwrap() as usize;
+                    if len == 0 {
+                        break;
+                    }
+                    let mut chunk = vec![0; len];
+                    socket.read_exact(&mut chunk).await.unwrap();
+                    body.extend(chunk);
+                }
+                let reply: &[u8] = if body.windows(5).any(|w| w == b"EICAR") {
+                    b"stream: Eicar-Test-Signature FOUND\0"
+                } else {
+                    b"stream: OK\0"
+                };
+                socket.write_all(reply).await.unwrap();
+            });
+        }
+    });
+    addr
+}
+
+async fn upload_text(
+    app: &TestApp,
+    tenant_id: &str,
+    token: &str,
+    room_id: &str,
+    body: &[u8],
+) -> Value {
+    let part = multipart::Part::bytes(body.to_vec())
+        .file_name("notes.txt")
+        .mime_str("text/plain")
+        .unwrap();
+    app.client
+        .post(app.url(&format!(
+            "/api/tenant/{}/room/{}/file/upload",
+            tenant_id, room_id
+        )))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn uploads_are_quarantined_until_scanned() {
+    let clamd = fake_clamd().await;
+    let app = TestApp::spawn_with_settings(|s| {
+        s.scan.provider = "clamav".to_string();
+        s.scan.clamd_addr = clamd;
+    })
+    .await;
+    let tenant = app.seed_tenant("filescan").await;
+    let tid = tenant.tenant_id.as_str();
+    let token = &tenant.admin.access_token;
+    let room_id = tenant.rooms[0].id.clone();
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    ws.next().await;
+    let mut scan_results = std::collections::HashMap::new();
+
+    let clean = upload_text(&app, tid, token, &room_id, b"meeting notes").await;
+    let infected = upload_text(&app, tid, token, &room_id, b"X5O!P%@AP EICAR test").await;
+    assert_eq!(clean["scan_status"], "pending");
+    assert_eq!(infected["scan_status"], "pending");
+
+    tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        while scan_results.len() < 2 {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            if parsed["type"] == "file:scan_result" {
+                let data = parsed["data"].clone();
+                scan_results.insert(data["file_id"].as_str().unwrap().to_string(), data);
+            }
+        }
+    })
+    .await
+    .expect("no file:scan_result");
+
+    let clean_id = clean["id"].as_str().unwrap();
+    let infected_id = infected["id"].as_str().unwrap();
+    assert_eq!(scan_results[clean_id]["status"], "clean");
+    assert_eq!(scan_results[clean_id]["room_id"], room_id.as_str());
+    assert_eq!(scan_results[infected_id]["status"], "malware");
+    assert_eq!(
+        scan_results[infected_id]["signature"],
+        "Eicar-Test-Signature"
+    );
+
+    let download = |id: &str| {
+        app.auth_get(&format!("/api/tenant/{}/file/{}/download", tid, id), token)
+            .send()
+    };
+    assert_eq!(download(clean_id).await.unwrap().status().as_u16(), 200);
+    assert_eq!(download(infected_id).await.unwrap().status().as_u16(), 403);
+
+    // Only admins may override, and only to clean or malware
+    let scan_url = format!("/api/tenant/{}/file/{}/scan", tid, infected_id);
+    let resp = app
+        .auth_put(&scan_url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "status": "clean" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(&scan_url, token)
+        .json(&serde_json::json!({ "status": "pending" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_put(&scan_url, token)
+        .json(&serde_json::json!({ "status": "clean" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["scan_status"], "clean");
+    assert_eq!(download(infected_id).await.unwrap().status().as_u16(), 200);
+}
+
+const PAGE_PNG: &[u8] = b"\x89PNG\r\n\x1a\nfake page";
+
+/// A conversion worker stand-in: waits for `release`, then answers every
+/// render with three pages of `PAGE_PNG`.
+async fn fake_converter(release: Arc<Notify>) -> String {
+    use base64::Engine;
+    let app = Router::new().route(
+        "/render",
+        post(move || {
+            let release = release.clone();
+            async move {
+                release.notified().await;
+                let png = base64::engine::general_purpose::STANDARD.encode(PAGE_PNG);
+                let page = serde_json::json!({ "png": png, "width": 480, "height": 679 });
+                Json(serde_json::json!({ "pages": [page, page, page] }))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}/render", addr)
+}
+
+#[tokio::test]
+async fn pdf_attachments_get_page_previews() {
+    let release = Arc::new(Notify::new());
+    let worker = fake_converter(release.clone()).await;
+    let app = TestApp::spawn_with_settings(|s| {
+        s.preview.worker_url = worker;
+        s.preview.max_pages = 2;
+    })
+    .await;
+    let tenant = app.seed_tenant("filepreview").await;
+    let tid = tenant.tenant_id.as_str();
+    let token = &tenant.admin.access_token;
+    let room_id = tenant.rooms[0].id.clone();
+    app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, room_id), token)
+        .send()
+        .await
+        .unwrap();
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    ws.next().await;
+
+    let part = multipart::Part::bytes(b"%PDF-1.7 agenda".to_vec())
+        .file_name("agenda.pdf")
+        .mime_str("application/pdf")
+        .unwrap();
+    let file: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/room/{}/file/upload", tid, room_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(multipart::Form::new().part("file", part))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let file_id = file["id"].as_str().unwrap();
+    assert_eq!(file["thumbnails"].as_array().unwrap().len(), 0);
+
+    // Sent before the preview exists; picks it up once rendered
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "Agenda", "attachment_ids": [file_id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    release.notify_one();
+
+    let ready = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+            if parsed["type"] == "file:preview_ready" {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .expect("no file:preview_ready");
+    assert_eq!(ready["file_id"], file_id);
+    assert_eq!(ready["room_id"], room_id.as_str());
+    let page_url = format!("/api/tenant/{}/file/{}/preview/1", tid, file_id);
+    let thumbnails = ready["thumbnails"].as_array().unwrap();
+    assert_eq!(thumbnails.len(), 2);
+    assert_eq!(thumbnails[0]["size"], "page-1");
+    assert_eq!(thumbnails[0]["url"], page_url.as_str());
+    assert_eq!(thumbnails[0]["width"], 480);
+
+    let resp = app.auth_get(&page_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "image/png");
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), PAGE_PNG);
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/file/{}/preview/3", tid, file_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let file: Value = app
+        .auth_get(&format!("/api/tenant/{}/file/{}", tid, file_id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(file["thumbnails"].as_array().unwrap().len(), 2);
+
+    let messages: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        messages["items"][0]["attachments"][0]["thumbnail_url"],
+        page_url.as_str()
+    );
+}
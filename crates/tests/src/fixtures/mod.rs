@@ -1,2 +1,4 @@
 pub mod seed;
 pub mod test_app;
+#[cfg(test)]
+pub mod ws;
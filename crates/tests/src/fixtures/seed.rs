@@ -223,6 +223,11 @@ impl TestApp {
                 "notification_override": bson::Bson::Null,
                 "invited_by": bson::Bson::Null,
                 "last_seen_at": bson::Bson::Null,
+                "search_keys": roomler_ai_db::models::TenantMember::search_keys(
+                    &format!("{} Member", slug),
+                    &member.username,
+                    &member.email,
+                ),
                 "created_at": now,
                 "updated_at": now,
             };
@@ -1,7 +1,7 @@
 use mongodb::{Client, Database, options::ClientOptions};
 use roomler_ai_api::{build_router, state::AppState};
 use roomler_ai_config::Settings;
-use roomler_ai_db::indexes::ensure_indexes;
+use roomler_ai_db::migrations::migrate;
 use std::net::SocketAddr;
 use tokio::net::TcpListener;
 
@@ -32,6 +32,9 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        settings.database.tenant_audit = tenant_audit();
+        // Webhook and slash command receivers in tests listen on 127.0.0.1
+        settings.integrations.allow_private_networks = true;
 
         let client_options = ClientOptions::parse(&settings.database.url)
             .await
@@ -40,7 +43,7 @@ impl TestApp {
             Client::with_options(client_options).expect("Failed to create MongoDB client");
         let db = mongo_client.database(&db_name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
+        migrate(&db).await.expect("Failed to migrate database");
 
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
@@ -92,6 +95,9 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        settings.database.tenant_audit = tenant_audit();
+        // Webhook and slash command receivers in tests listen on 127.0.0.1
+        settings.integrations.allow_private_networks = true;
 
         // Apply caller's customizations
         mutator(&mut settings);
@@ -101,13 +107,15 @@ impl TestApp {
             .expect("Failed to parse MongoDB URL");
         let mongo_client =
             Client::with_options(client_options).expect("Failed to create MongoDB client");
-        let db = mongo_client.database(&db_name);
+        // The mutator may point a second pod at another app's database
+        let db = mongo_client.database(&settings.database.name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
+        migrate(&db).await.expect("Failed to migrate database");
 
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
             .expect("Failed to create AppState");
+        roomler_ai_api::control::spawn(app_state.clone());
         let app = build_router(app_state);
 
         let listener = TcpListener::bind("127.0.0.1:0")
@@ -142,6 +150,13 @@ impl TestApp {
     /// Spawn a test server with OAuth providers configured (fake client IDs).
     /// Uses a no-redirect reqwest client so we can inspect the 302/307 Location header.
     pub async fn spawn_with_oauth() -> Self {
+        Self::spawn_with_oauth_settings(|_| {}).await
+    }
+
+    /// Like [`TestApp::spawn_with_oauth`], with a `mutator` applied after the
+    /// fake provider credentials (e.g. to point the `custom` OIDC provider at
+    /// a local issuer).
+    pub async fn spawn_with_oauth_settings(mutator: impl FnOnce(&mut Settings)) -> Self {
         let db_name = format!("roomler_ai_test_{}", uuid::Uuid::new_v4().simple());
 
         let mut settings = Settings::load().unwrap_or_else(|_| test_settings());
@@ -149,6 +164,9 @@ impl TestApp {
             settings.database.url = url;
         }
         settings.database.name = db_name.clone();
+        settings.database.tenant_audit = tenant_audit();
+        // Webhook and slash command receivers in tests listen on 127.0.0.1
+        settings.integrations.allow_private_networks = true;
 
         // Configure fake OAuth provider credentials
         settings.oauth.base_url = "http://localhost:5001".to_string();
@@ -162,6 +180,7 @@ impl TestApp {
         settings.oauth.linkedin.client_secret = "test-linkedin-secret".to_string();
         settings.oauth.microsoft.client_id = "test-microsoft-id".to_string();
         settings.oauth.microsoft.client_secret = "test-microsoft-secret".to_string();
+        mutator(&mut settings);
 
         let client_options = ClientOptions::parse(&settings.database.url)
             .await
@@ -170,7 +189,7 @@ impl TestApp {
             Client::with_options(client_options).expect("Failed to create MongoDB client");
         let db = mongo_client.database(&db_name);
 
-        ensure_indexes(&db).await.expect("Failed to create indexes");
+        migrate(&db).await.expect("Failed to migrate database");
 
         let app_state = AppState::new(db.clone(), settings.clone())
             .await
@@ -234,6 +253,13 @@ impl Drop for TestApp {
     }
 }
 
+/// `database.tenant_audit` for every test app: `warn`, unless
+/// `ROOMLER__DATABASE__TENANT_AUDIT` picks another mode for the whole run
+/// (the mode is process-wide), e.g. `deny`.
+fn tenant_audit() -> String {
+    std::env::var("ROOMLER__DATABASE__TENANT_AUDIT").unwrap_or_else(|_| "warn".to_string())
+}
+
 fn test_settings() -> Settings {
     Settings {
         app: roomler_ai_config::AppSettings {
@@ -244,12 +270,16 @@ fn test_settings() -> Settings {
             static_dir: None,
             cors_origins: vec![],
             frontend_url: "http://localhost:5173".to_string(),
+            instance_url: None,
+            admin_emails: String::new(),
+            settings_reload_secs: 0,
         },
         database: roomler_ai_config::DatabaseSettings {
             url: "mongodb://localhost:27019".to_string(),
             name: "roomler_ai_test".to_string(),
             max_pool_size: Some(5),
             min_pool_size: Some(1),
+            tenant_audit: tenant_audit(),
         },
         jwt: roomler_ai_config::JwtSettings {
             secret: "test-secret-key-for-jwt-signing-minimum-32-chars".to_string(),
@@ -273,9 +303,16 @@ fn test_settings() -> Settings {
             announced_ip: "127.0.0.1".to_string(),
             rtc_min_port: 40000,
             rtc_max_port: 40100,
+            reconnect_grace_secs: 15,
+            watchdog_secs: 1,
+            backend: roomler_ai_config::MediaBackendKind::Mediasoup,
+            ffmpeg_path: "ffmpeg".to_string(),
         },
         turn: roomler_ai_config::TurnSettings {
             worker_urls: None,
+            regions: None,
+            max_regions: None,
+            health_check_secs: 0,
             url: None,
             username: None,
             password: None,
@@ -309,13 +346,23 @@ fn test_settings() -> Settings {
                 client_id: String::new(),
                 client_secret: String::new(),
             },
+            custom: roomler_ai_config::OidcProviderSettings {
+                issuer_url: String::new(),
+                client_id: String::new(),
+                client_secret: String::new(),
+                scopes: "openid email profile".to_string(),
+                label: "SSO".to_string(),
+            },
         },
         stripe: roomler_ai_config::StripeSettings {
             secret_key: String::new(),
             publishable_key: String::new(),
             webhook_secret: String::new(),
+            webhook_tolerance_secs: 300,
             price_pro: String::new(),
             price_business: String::new(),
+            enforce_limits: false,
+            report_usage: false,
         },
         giphy: roomler_ai_config::GiphySettings {
             api_key: String::new(),
@@ -334,5 +381,20 @@ fn test_settings() -> Settings {
             contact: "mailto:test@roomler.ai".to_string(),
         },
         auth: roomler_ai_config::AuthSettings::default(),
+        rate_limit: roomler_ai_config::RateLimitSettings::default(),
+        ws: roomler_ai_config::WsSettings::default(),
+        retention: roomler_ai_config::RetentionSettings::default(),
+        archive: roomler_ai_config::ArchiveSettings::default(),
+        usage: roomler_ai_config::UsageSettings::default(),
+        scan: roomler_ai_config::ScanSettings::default(),
+        preview: roomler_ai_config::PreviewSettings::default(),
+        tts: roomler_ai_config::TtsSettings::default(),
+        assistant: roomler_ai_config::AssistantSettings::default(),
+        calendar: roomler_ai_config::CalendarSettings::default(),
+        control: roomler_ai_config::ControlSettings::default(),
+        telemetry: roomler_ai_config::TelemetrySettings::default(),
+        storage: roomler_ai_config::StorageSettings::default(),
+        integrations: roomler_ai_config::IntegrationsSettings::default(),
+        features: Default::default(),
     }
 }
@@ -0,0 +1,39 @@
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+pub type Ws =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Send a `{ type, data }` event.
+pub async fn send(ws: &mut Ws, msg_type: &str, data: Value) {
+    let msg = serde_json::json!({ "type": msg_type, "data": data });
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// Read until a message of `msg_type` arrives, skipping everything else,
+/// and return its `data`. Panics after 5 seconds.
+pub async fn next_of(ws: &mut Ws, msg_type: &str) -> Value {
+    next_of_within(ws, msg_type, 5).await
+}
+
+/// [`next_of`] with its own timeout. Reading also lets the client answer
+/// the server's pings.
+pub async fn next_of_within(ws: &mut Ws, msg_type: &str, secs: u64) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(secs), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let Ok(text) = msg.to_text() else { continue };
+            let Ok(parsed) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            if parsed["type"] == msg_type {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("no {} message", msg_type))
+}
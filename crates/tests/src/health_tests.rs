@@ -0,0 +1,64 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+#[tokio::test]
+async fn ready_checks_dependencies() {
+    let app = TestApp::spawn().await;
+
+    let resp = app
+        .client
+        .get(app.url("/health/ready"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["status"], "ready");
+    assert_eq!(json["checks"]["mongo"]["status"], "ok");
+    assert!(json["checks"]["mongo"]["latency_ms"].is_u64());
+    assert_eq!(json["checks"]["mediasoup"]["status"], "ok");
+    assert_eq!(json["checks"]["mediasoup"]["live_workers"], 1);
+    assert_eq!(json["checks"]["mediasoup"]["workers"], 1);
+    // Redis is "skipped" when the pod started without it.
+    assert_ne!(json["checks"]["redis"]["status"], "fail");
+    assert_eq!(json["checks"]["asr"]["status"], "skipped");
+}
+
+#[tokio::test]
+async fn live_reports_the_worker_watchdog() {
+    let app = TestApp::spawn().await;
+    // The fixture pings the workers every second.
+    tokio::time::sleep(std::time::Duration::from_millis(1500)).await;
+
+    let resp = app
+        .client
+        .get(app.url("/health/live"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["status"], "ok");
+    assert_eq!(json["watchdog"], "ok");
+    assert_eq!(json["stuck_workers"], 0);
+    assert!(json["last_round_ms"].is_u64());
+}
+
+#[tokio::test]
+async fn live_without_watchdog_still_answers() {
+    let app = TestApp::spawn_with_settings(|s| s.mediasoup.watchdog_secs = 0).await;
+
+    let resp = app
+        .client
+        .get(app.url("/health/live"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["watchdog"], "disabled");
+    assert!(json["last_round_ms"].is_null());
+}
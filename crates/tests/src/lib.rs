@@ -1,27 +1,79 @@
 pub mod fixtures;
 
+#[cfg(test)]
+mod archive_tests;
+#[cfg(test)]
+mod asset_tests;
+#[cfg(test)]
+mod assistant_tests;
+#[cfg(test)]
+mod audit_tests;
 #[cfg(test)]
 mod auth_tests;
 #[cfg(test)]
+mod bot_tests;
+#[cfg(test)]
+mod breakout_tests;
+#[cfg(test)]
+mod calendar_tests;
+#[cfg(test)]
+mod call_audio_tests;
+#[cfg(test)]
+mod call_debug_tests;
+#[cfg(test)]
+mod call_history_tests;
+#[cfg(test)]
+mod call_poll_tests;
+#[cfg(test)]
+mod call_ring_tests;
+#[cfg(test)]
+mod call_transcription_tests;
+#[cfg(test)]
+mod capacity_tests;
+#[cfg(test)]
 mod channel_crud_tests;
 #[cfg(test)]
 mod channel_tests;
 #[cfg(test)]
+mod client_sdk_tests;
+#[cfg(test)]
 mod conference_message_tests;
 #[cfg(test)]
 mod conference_tests;
 #[cfg(test)]
+mod domain_tests;
+#[cfg(test)]
 mod export_tests;
 #[cfg(test)]
+mod feature_flag_tests;
+#[cfg(test)]
 mod file_tests;
 #[cfg(test)]
 mod message_tests;
 #[cfg(test)]
 mod multi_tenancy_tests;
 #[cfg(test)]
+mod presence_tests;
+#[cfg(test)]
+mod profile_tests;
+#[cfg(test)]
 mod reaction_tests;
 #[cfg(test)]
+mod recording_consent_tests;
+#[cfg(test)]
 mod recording_tests;
+#[cfg(test)]
+mod whiteboard_tests;
+#[cfg(test)]
+mod ws_batch_tests;
+#[cfg(test)]
+mod ws_keepalive_tests;
+#[cfg(test)]
+mod ws_sync_tests;
+#[cfg(test)]
+mod ws_tenant_tests;
+#[cfg(test)]
+mod ws_ticket_tests;
 
 #[cfg(test)]
 mod agent_crash_tests;
@@ -34,22 +86,56 @@ mod billing_tests;
 #[cfg(test)]
 mod cors_tests;
 #[cfg(test)]
+mod edge_media_tests;
+#[cfg(test)]
+mod health_tests;
+#[cfg(test)]
 mod invite_tests;
 #[cfg(test)]
 mod member_tests;
 #[cfg(test)]
+mod migration_tests;
+#[cfg(test)]
+mod mock_media_tests;
+#[cfg(test)]
 mod notification_tests;
 #[cfg(test)]
 mod oauth_tests;
 #[cfg(test)]
+mod openapi_tests;
+#[cfg(test)]
 mod pagination_tests;
 #[cfg(test)]
 mod pdf_export_tests;
 #[cfg(test)]
+mod permission_tests;
+#[cfg(test)]
+mod plan_limit_tests;
+#[cfg(test)]
 mod rate_limit_tests;
 #[cfg(test)]
 mod remote_control_tests;
 #[cfg(test)]
+mod retention_tests;
+#[cfg(test)]
 mod role_tests;
 #[cfg(test)]
+mod runtime_settings_tests;
+#[cfg(test)]
+mod scheduled_message_tests;
+#[cfg(test)]
+mod sidebar_tests;
+#[cfg(test)]
+mod soft_delete_tests;
+#[cfg(test)]
+mod storage_quota_tests;
+#[cfg(test)]
+mod thread_tests;
+#[cfg(test)]
 mod tunnel_tests;
+#[cfg(test)]
+mod usage_tests;
+#[cfg(test)]
+mod webhook_tests;
+#[cfg(test)]
+mod webinar_tests;
@@ -216,3 +216,75 @@ async fn create_message_with_everyone_mention() {
     );
     assert_eq!(msg["content"].as_str().unwrap(), "Attention @everyone!");
 }
+
+async fn search_members(app: &TestApp, tenant_id: &str, token: &str, query: &str) -> Value {
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/member/search?{}", tenant_id, query),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+fn names(results: &Value) -> Vec<&str> {
+    results
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["display_name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn member_search_matches_name_username_and_email_prefixes() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msearch").await;
+    let other = app.seed_tenant("msearchx").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.member.access_token;
+
+    // Both tenants have "msearch..." members; only this tenant's come back.
+    let results = search_members(&app, tid, token, "q=MSearch").await;
+    assert_eq!(names(&results), vec!["msearch Admin", "msearch Member"]);
+
+    let results = search_members(&app, tid, token, "q=adm").await;
+    assert_eq!(names(&results), vec!["msearch Admin"]);
+    assert_eq!(results[0]["roles"], serde_json::json!(["owner"]));
+    assert_eq!(results[0]["username"], "msearch_admin");
+    assert_eq!(results[0]["presence"], "offline");
+
+    let results = search_members(&app, tid, token, "q=member@msearch").await;
+    assert_eq!(names(&results), vec!["msearch Member"]);
+    assert_eq!(results[0]["roles"], serde_json::json!(["member"]));
+
+    let results = search_members(&app, tid, token, "q=.*").await;
+    assert!(results.as_array().unwrap().is_empty());
+    let results = search_members(&app, tid, token, "q=msearch&limit=1").await;
+    assert_eq!(results.as_array().unwrap().len(), 1);
+
+    // A rename is searchable straight away.
+    let resp = app
+        .auth_put("/api/user/me", token)
+        .json(&serde_json::json!({ "display_name": "Zed Quinn" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let results = search_members(&app, tid, token, "q=quin").await;
+    assert_eq!(names(&results), vec!["Zed Quinn"]);
+
+    let url = format!("/api/tenant/{}/member/search?q=", tid);
+    let resp = app.auth_get(&url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+    let url = format!("/api/tenant/{}/member/search?q=msearch", tid);
+    let resp = app
+        .auth_get(&url, &other.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -1,7 +1,16 @@
 use crate::fixtures::test_app::TestApp;
-use futures::{SinkExt, StreamExt};
+use crate::fixtures::ws::Ws;
+use futures::StreamExt;
 use serde_json::Value;
-use tokio_tungstenite::tungstenite::Message;
+
+async fn next_json(ws: &mut Ws) -> Value {
+    let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws.next())
+        .await
+        .expect("Timed out waiting for WS message")
+        .unwrap()
+        .unwrap();
+    serde_json::from_str(msg.to_text().unwrap()).unwrap()
+}
 
 #[tokio::test]
 async fn create_and_list_messages() {
@@ -172,7 +181,7 @@ async fn delete_message_soft_deletes() {
 }
 
 #[tokio::test]
-async fn message_broadcast_excludes_sender_reaches_member() {
+async fn message_broadcast_acks_nonce_and_reaches_senders_devices() {
     let app = TestApp::spawn().await;
     let tenant = app.seed_tenant("msgws").await;
     let room_id = &tenant.rooms[0].id;
@@ -215,11 +224,13 @@ async fn message_broadcast_excludes_sender_reaches_member() {
             &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
             &tenant.admin.access_token,
         )
-        .json(&serde_json::json!({ "content": "Hello from admin" }))
+        .json(&serde_json::json!({ "content": "Hello from admin", "nonce": "tmp-1" }))
         .send()
         .await
         .unwrap();
     assert_eq!(resp.status().as_u16(), 200);
+    let created: Value = resp.json().await.unwrap();
+    assert_eq!(created["nonce"], "tmp-1");
 
     // Member should receive message:create via WS
     let msg = tokio::time::timeout(std::time::Duration::from_secs(3), ws_member.next())
@@ -232,29 +243,226 @@ async fn message_broadcast_excludes_sender_reaches_member() {
     assert_eq!(parsed["type"], "message:create");
     assert_eq!(parsed["data"]["content"], "Hello from admin");
 
-    // Admin should NOT receive message:create (sender excluded from broadcast).
-    // Send a ping to flush any pending messages, then check.
-    ws_admin
-        .send(Message::Text(
-            serde_json::to_string(&serde_json::json!({ "type": "ping" }))
-                .unwrap()
-                .into(),
-        ))
+    // The admin's connection gets the ack mapping the nonce to the id, then
+    // the message itself, as the admin's other devices would.
+    let ack = next_json(&mut ws_admin).await;
+    assert_eq!(ack["type"], "message:ack");
+    assert_eq!(ack["data"]["nonce"], "tmp-1");
+    assert_eq!(ack["data"]["id"], created["id"]);
+    assert_eq!(ack["data"]["room_id"], room_id.as_str());
+
+    let echo = next_json(&mut ws_admin).await;
+    assert_eq!(echo["type"], "message:create");
+    assert_eq!(echo["data"]["id"], created["id"]);
+    assert_eq!(echo["data"]["nonce"], "tmp-1");
+
+    // Without a nonce there is nothing to acknowledge.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "content": "No nonce" }))
+        .send()
         .await
         .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let plain = next_json(&mut ws_admin).await;
+    assert_eq!(plain["type"], "message:create");
+    assert_eq!(plain["data"]["content"], "No nonce");
 
-    let msg = tokio::time::timeout(std::time::Duration::from_secs(2), ws_admin.next())
+    ws_admin.close(None).await.ok();
+    ws_member.close(None).await.ok();
+}
+
+#[tokio::test]
+async fn edit_history_is_recorded_and_restricted() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msghist").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+
+    let msg: Value = app
+        .auth_post(&base, &tenant.member.access_token)
+        .json(&serde_json::json!({ "content": "v1" }))
+        .send()
         .await
-        .expect("Timed out waiting for pong")
         .unwrap()
+        .json()
+        .await
         .unwrap();
+    let msg_path = format!("{}/{}", base, msg["id"].as_str().unwrap());
+    for content in ["v2", "v3"] {
+        let resp = app
+            .auth_put(&msg_path, &tenant.member.access_token)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
 
-    let parsed: Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
-    assert_eq!(
-        parsed["type"], "pong",
-        "Admin should receive pong, not message:create (sender excluded)"
+    let history: Value = app
+        .auth_get(
+            &format!("{}/history", msg_path),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(history["content"], "v3");
+    let edits: Vec<&str> = history["edits"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|e| e["content"].as_str().unwrap())
+        .collect();
+    assert_eq!(edits, vec!["v1", "v2"]);
+
+    // A plain member can't read someone else's history.
+    let admin_msg: Value = app
+        .auth_post(&base, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "content": "mine" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let resp = app
+        .auth_get(
+            &format!("{}/{}/history", base, admin_msg["id"].as_str().unwrap()),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn moderators_see_deleted_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgmod").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
     );
 
-    ws_admin.close(None).await.ok();
-    ws_member.close(None).await.ok();
+    let msg: Value = app
+        .auth_post(&base, &tenant.member.access_token)
+        .json(&serde_json::json!({ "content": "oops" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let msg_path = format!("{}/{}", base, msg["id"].as_str().unwrap());
+    let resp = app
+        .auth_delete(&msg_path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Members can't ask for deleted messages, nor read their own deleted history.
+    let resp = app
+        .auth_get(
+            &format!("{}?include_deleted=true", base),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_get(
+            &format!("{}/history", msg_path),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let json: Value = app
+        .auth_get(
+            &format!("{}?include_deleted=true", base),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let items = json["items"].as_array().unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0]["content"], "oops");
+    assert_eq!(items[0]["deleted_by"], tenant.member.id.as_str());
+    assert!(items[0]["deleted_at"].is_string());
+}
+
+#[tokio::test]
+async fn announcement_and_read_only_rooms_restrict_posting() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("msgann").await;
+    let room_path = format!(
+        "/api/tenant/{}/room/{}",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let base = format!("{}/message", room_path);
+    let post = |token: &str| {
+        app.auth_post(&base, token)
+            .json(&serde_json::json!({ "content": "hello" }))
+            .send()
+    };
+
+    let resp = app
+        .auth_put(&room_path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "is_announcement": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Only members who can manage the channel post in an announcement room.
+    let resp = post(&tenant.member.access_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["error"], "announcement_only");
+    let resp = post(&tenant.admin.access_token).await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let json: Value = app
+        .auth_get(&room_path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["is_announcement"], true);
+    assert_eq!(json["is_read_only"], false);
+
+    // A read-only room takes no posts at all.
+    let resp = app
+        .auth_put(&room_path, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "is_announcement": false, "is_read_only": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        let resp = post(token).await.unwrap();
+        assert_eq!(resp.status().as_u16(), 403);
+        let json: Value = resp.json().await.unwrap();
+        assert_eq!(json["error"], "room_read_only");
+    }
 }
@@ -0,0 +1,74 @@
+use crate::fixtures::test_app::TestApp;
+use bson::doc;
+use futures::TryStreamExt;
+use roomler_ai_db::migrations::{self, MIGRATIONS};
+
+async fn index_keys(app: &TestApp, collection: &str) -> Vec<bson::Document> {
+    app.db
+        .collection::<bson::Document>(collection)
+        .list_indexes()
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|i| {
+            let mut keys = i.keys;
+            if let Some(ttl) = i.options.and_then(|o| o.expire_after) {
+                keys.insert("expire_after_secs", ttl.as_secs() as i64);
+            }
+            keys
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn startup_records_every_migration_and_creates_its_indexes() {
+    let app = TestApp::spawn().await;
+
+    let recorded: Vec<bson::Document> = app
+        .db
+        .collection::<bson::Document>(migrations::COLLECTION)
+        .find(doc! {})
+        .sort(doc! { "_id": 1 })
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let versions: Vec<i64> = recorded.iter().map(|d| d.get_i64("_id").unwrap()).collect();
+    let expected: Vec<i64> = MIGRATIONS.iter().map(|m| i64::from(m.version)).collect();
+    assert_eq!(versions, expected);
+    assert_eq!(recorded[0].get_str("name").unwrap(), MIGRATIONS[0].name);
+    assert!(recorded[0].get_datetime("applied_at").is_ok());
+
+    let keys = index_keys(&app, "messages").await;
+    assert!(keys.contains(&doc! { "tenant_id": 1, "room_id": 1, "created_at": -1 }));
+    assert!(keys.contains(&doc! { "_fts": "text", "_ftsx": 1 }));
+
+    let keys = index_keys(&app, "invites").await;
+    assert!(keys.contains(&doc! { "expires_at": 1, "expire_after_secs": 30 * 86_400_i64 }));
+
+    let keys = index_keys(&app, "notifications").await;
+    assert!(keys.contains(&doc! { "created_at": 1, "expire_after_secs": 90 * 86_400_i64 }));
+}
+
+#[tokio::test]
+async fn migrate_is_idempotent() {
+    let app = TestApp::spawn().await;
+    let before = index_keys(&app, "messages").await.len();
+
+    let ran = migrations::migrate(&app.db).await.unwrap();
+    assert!(ran.is_empty());
+    assert_eq!(index_keys(&app, "messages").await.len(), before);
+
+    // A lost record only reruns that step, which is harmless.
+    app.db
+        .collection::<bson::Document>(migrations::COLLECTION)
+        .delete_one(doc! { "_id": 2_i64 })
+        .await
+        .unwrap();
+    let ran = migrations::migrate(&app.db).await.unwrap();
+    assert_eq!(ran, vec![2]);
+}
@@ -0,0 +1,235 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use roomler_ai_config::MediaBackendKind;
+use serde_json::Value;
+
+/// An app on the in-memory media backend: no mediasoup worker, no UDP.
+async fn spawn() -> TestApp {
+    TestApp::spawn_with_settings(|s| s.mediasoup.backend = MediaBackendKind::Mock).await
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+/// `media:join`, returning (router capabilities, transport_created data).
+async fn join(ws: &mut Ws, room_id: &str) -> (Value, Value) {
+    send(ws, "media:join", serde_json::json!({ "room_id": room_id })).await;
+    let caps = next_of(ws, "media:router_capabilities").await;
+    let transports = next_of(ws, "media:transport_created").await;
+    (caps["rtp_capabilities"].clone(), transports)
+}
+
+fn opus_parameters(ssrc: u32) -> Value {
+    serde_json::json!({
+        "mid": "0",
+        "codecs": [{
+            "mimeType": "audio/opus",
+            "clockRate": 48000,
+            "channels": 2,
+            "payloadType": 111,
+            "parameters": {},
+            "rtcpFeedback": [],
+        }],
+        "headerExtensions": [],
+        "encodings": [{ "ssrc": ssrc }],
+        "rtcp": { "cname": "mock" },
+    })
+}
+
+#[tokio::test]
+async fn produce_and_consume_over_the_mock_backend() {
+    let app = spawn().await;
+    let tenant = app.seed_tenant("mockmedia1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Standup").await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+
+    let mut speaker = connect(&app, admin).await;
+    let (caps, transports) = join(&mut speaker, &room_id).await;
+    assert!(caps["codecs"].is_array());
+    assert_ne!(
+        transports["send_transport"]["id"],
+        transports["recv_transport"]["id"]
+    );
+
+    send(
+        &mut speaker,
+        "media:produce",
+        serde_json::json!({
+            "room_id": room_id,
+            "kind": "audio",
+            "rtp_parameters": opus_parameters(1111),
+        }),
+    )
+    .await;
+    let produced = next_of(&mut speaker, "media:produce_result").await;
+    let producer_id = produced["id"].as_str().unwrap().to_string();
+
+    // A later joiner hears about the producer and consumes it.
+    let mut listener = connect(&app, member).await;
+    let (caps, _) = join(&mut listener, &room_id).await;
+    let announced = next_of(&mut listener, "media:new_producer").await;
+    assert_eq!(announced["producer_id"], producer_id.as_str());
+    assert_eq!(announced["user_id"], tenant.admin.id.as_str());
+    assert_eq!(announced["source"], "audio");
+
+    send(
+        &mut listener,
+        "media:consume",
+        serde_json::json!({
+            "room_id": room_id,
+            "producer_id": producer_id,
+            "rtp_capabilities": caps,
+        }),
+    )
+    .await;
+    let consumer = next_of(&mut listener, "media:consumer_created").await;
+    assert_eq!(consumer["producer_id"], producer_id.as_str());
+    assert_eq!(consumer["kind"], "audio");
+    assert_eq!(consumer["rtp_parameters"]["encodings"][0]["ssrc"], 1111);
+
+    // Closing the producer tells the listener, and it can't be consumed again.
+    send(
+        &mut speaker,
+        "media:producer_close",
+        serde_json::json!({ "room_id": room_id, "producer_id": producer_id }),
+    )
+    .await;
+    let closed = next_of(&mut listener, "media:producer_closed").await;
+    assert_eq!(closed["producer_id"], producer_id.as_str());
+    send(
+        &mut listener,
+        "media:consume",
+        serde_json::json!({
+            "room_id": room_id,
+            "producer_id": producer_id,
+            "rtp_capabilities": caps,
+        }),
+    )
+    .await;
+    let error = next_of(&mut listener, "media:error").await;
+    assert!(
+        error["message"]
+            .as_str()
+            .unwrap()
+            .contains("Cannot consume"),
+        "got {}",
+        error["message"]
+    );
+}
+
+#[tokio::test]
+async fn transport_connect_and_ice_restart_on_the_mock_backend() {
+    let app = spawn().await;
+    let tenant = app.seed_tenant("mockmedia2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Retro").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+
+    let mut ws = connect(&app, admin).await;
+    let (_, transports) = join(&mut ws, &room_id).await;
+    let send_transport = &transports["send_transport"];
+    let transport_id = send_transport["id"].as_str().unwrap();
+    assert_eq!(send_transport["ice_candidates"][0]["address"], "127.0.0.1");
+
+    send(
+        &mut ws,
+        "media:connect_transport",
+        serde_json::json!({
+            "room_id": room_id,
+            "transport_id": transport_id,
+            "dtls_parameters": {
+                "role": "client",
+                "fingerprints": send_transport["dtls_parameters"]["fingerprints"],
+            },
+        }),
+    )
+    .await;
+
+    send(
+        &mut ws,
+        "media:restart_ice",
+        serde_json::json!({ "room_id": room_id, "transport_id": transport_id }),
+    )
+    .await;
+    let restarted = next_of(&mut ws, "media:ice_restarted").await;
+    assert_eq!(restarted["transport_id"], transport_id);
+    assert_ne!(
+        restarted["ice_parameters"]["usernameFragment"],
+        send_transport["ice_parameters"]["usernameFragment"]
+    );
+
+    // The connect shows up in the call debug timeline with the states the
+    // backend reported.
+    let mut details = Vec::new();
+    for _ in 0..50 {
+        let events: Vec<Value> = app
+            .auth_get(
+                &format!("/api/tenant/{}/room/{}/call/debug", tid, room_id),
+                admin,
+            )
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        details = events
+            .iter()
+            .map(|e| format!("{} {}", e["kind"].as_str().unwrap(), e["detail"]))
+            .collect();
+        if details.len() >= 5 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    for expected in [
+        "ice_state \"send: connected\"",
+        "dtls_state \"send: connected\"",
+        "transport_connect",
+        "ice_restart",
+    ] {
+        assert!(
+            details.iter().any(|d| d.starts_with(expected)),
+            "no {} in {:?}",
+            expected,
+            details
+        );
+    }
+}
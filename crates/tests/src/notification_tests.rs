@@ -324,3 +324,213 @@ async fn notifications_are_user_scoped() {
         "Member should see at least 1 notification"
     );
 }
+
+#[tokio::test]
+async fn list_filter_cannot_widen_the_unread_scope() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("notif6").await;
+    let room_id = &tenant.rooms[0].id;
+    let member = &tenant.member.access_token;
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    send_mention_message(
+        &app,
+        &tenant.tenant_id,
+        room_id,
+        &tenant.admin.access_token,
+        &tenant.member.id,
+    )
+    .await;
+
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+
+    let resp = app
+        .auth_post("/api/notification/read-all", member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The unread list scopes on `is_read: false`; asking for read ones
+    // narrows it to nothing instead of replacing the scope.
+    let resp = app
+        .auth_get("/api/notification/unread?filter[is_read]=true", member)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["total"], 0);
+    assert!(json["items"].as_array().unwrap().is_empty());
+}
+
+async fn unread_count(app: &TestApp, token: &str) -> u64 {
+    let json: Value = app
+        .auth_get("/api/notification/unread-count", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    json["count"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn room_preferences_gate_notifications() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("notifprefs").await;
+    let room_id = &tenant.rooms[0].id;
+    let member = &tenant.member.access_token;
+    let prefs_path = format!(
+        "/api/tenant/{}/room/{}/notification-preferences",
+        tenant.tenant_id, room_id
+    );
+
+    // Not a member of the room yet.
+    let resp = app.auth_get(&prefs_path, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        member,
+    )
+    .send()
+    .await
+    .unwrap();
+    let json: Value = app
+        .auth_get(&prefs_path, member)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["level"], Value::Null);
+    assert_eq!(json["muted"], false);
+
+    // A room set to nothing gets no mention notifications.
+    let resp = app
+        .auth_put(&prefs_path, member)
+        .json(&serde_json::json!({ "level": "nothing" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    send_mention_message(
+        &app,
+        &tenant.tenant_id,
+        room_id,
+        &tenant.admin.access_token,
+        &tenant.member.id,
+    )
+    .await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(unread_count(&app, member).await, 0);
+
+    // Mentions-only lets them through again.
+    let resp = app
+        .auth_put(&prefs_path, member)
+        .json(&serde_json::json!({ "level": "mentions" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    send_mention_message(
+        &app,
+        &tenant.tenant_id,
+        room_id,
+        &tenant.admin.access_token,
+        &tenant.member.id,
+    )
+    .await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(unread_count(&app, member).await, 1);
+
+    // Muting everything wins over the room level.
+    let resp = app
+        .auth_put("/api/notification/preferences", member)
+        .json(&serde_json::json!({
+            "email": true,
+            "push": true,
+            "desktop": true,
+            "mute_all": true,
+            "quiet_hours": null,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    send_mention_message(
+        &app,
+        &tenant.tenant_id,
+        room_id,
+        &tenant.admin.access_token,
+        &tenant.member.id,
+    )
+    .await;
+    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+    assert_eq!(unread_count(&app, member).await, 1);
+}
+
+#[tokio::test]
+async fn user_preferences_round_trip_and_validate_quiet_hours() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("notifquiet").await;
+    let token = &tenant.member.access_token;
+
+    let json: Value = app
+        .auth_get("/api/notification/preferences", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["push"], true);
+    assert_eq!(json["mute_all"], false);
+    assert_eq!(json["quiet_hours"], Value::Null);
+
+    let body = |start: &str| {
+        serde_json::json!({
+            "email": false,
+            "push": true,
+            "desktop": true,
+            "mute_all": false,
+            "quiet_hours": { "start": start, "end": "07:00", "utc_offset_minutes": 120 },
+        })
+    };
+    let resp = app
+        .auth_put("/api/notification/preferences", token)
+        .json(&body("25:00"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_put("/api/notification/preferences", token)
+        .json(&body("22:00"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = app
+        .auth_get("/api/notification/preferences", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["email"], false);
+    assert_eq!(json["quiet_hours"]["start"], "22:00");
+    assert_eq!(json["quiet_hours"]["utc_offset_minutes"], 120);
+}
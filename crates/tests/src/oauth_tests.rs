@@ -1,5 +1,73 @@
 use crate::fixtures::test_app::TestApp;
-use serde_json::Value;
+use axum::{
+    Form, Json, Router,
+    http::HeaderMap,
+    routing::{get, post},
+};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use tokio::net::TcpListener;
+
+/// Minimal OIDC issuer: discovery document, a token endpoint that only
+/// accepts `code=good-code` (and `code=unverified-code`, whose user's email
+/// isn't verified), and a userinfo endpoint with standard claims.
+async fn spawn_issuer() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let issuer = format!("http://{}/realms/test", listener.local_addr().unwrap());
+    let discovery = json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{issuer}/protocol/openid-connect/auth"),
+        "token_endpoint": format!("{issuer}/protocol/openid-connect/token"),
+        "userinfo_endpoint": format!("{issuer}/protocol/openid-connect/userinfo"),
+    });
+    let app = Router::new()
+        .route(
+            "/realms/test/.well-known/openid-configuration",
+            get(move || async move { Json(discovery) }),
+        )
+        .route(
+            "/realms/test/protocol/openid-connect/token",
+            post(|Form(form): Form<HashMap<String, String>>| async move {
+                assert_eq!(form["grant_type"], "authorization_code");
+                assert_eq!(form["client_id"], "test-oidc-id");
+                assert_eq!(form["client_secret"], "test-oidc-secret");
+                match form["code"].as_str() {
+                    "good-code" => {
+                        Json(json!({ "access_token": "oidc-access", "token_type": "Bearer" }))
+                    }
+                    "unverified-code" => {
+                        Json(json!({ "access_token": "oidc-unverified", "token_type": "Bearer" }))
+                    }
+                    _ => Json(json!({ "error": "invalid_grant" })),
+                }
+            }),
+        )
+        .route(
+            "/realms/test/protocol/openid-connect/userinfo",
+            get(|headers: HeaderMap| async move {
+                let verified = headers["authorization"] == "Bearer oidc-access";
+                Json(json!({
+                    "sub": "kc-user-1",
+                    "email": "sso@test.com",
+                    "email_verified": verified,
+                    "preferred_username": "sso.user",
+                }))
+            }),
+        );
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    issuer
+}
+
+async fn spawn_with_custom_oidc() -> TestApp {
+    let issuer = spawn_issuer().await;
+    TestApp::spawn_with_oauth_settings(|s| {
+        s.oauth.custom.issuer_url = issuer;
+        s.oauth.custom.client_id = "test-oidc-id".to_string();
+        s.oauth.custom.client_secret = "test-oidc-secret".to_string();
+        s.oauth.custom.label = "Keycloak".to_string();
+    })
+    .await
+}
 
 #[tokio::test]
 async fn oauth_redirect_google_returns_302() {
@@ -207,3 +275,158 @@ async fn oauth_user_dao_does_not_duplicate_provider() {
     // Should still have only 1 oauth provider, not 2
     assert_eq!(user.oauth_providers.len(), 1);
 }
+
+#[tokio::test]
+async fn oauth_providers_lists_configured_providers() {
+    let app = spawn_with_custom_oidc().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/providers"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    let names: Vec<&str> = body
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|p| p["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "google",
+            "facebook",
+            "github",
+            "linkedin",
+            "microsoft",
+            "custom"
+        ]
+    );
+    assert_eq!(body[5]["label"], "Keycloak");
+}
+
+#[tokio::test]
+async fn oauth_custom_without_issuer_is_not_configured() {
+    let app = TestApp::spawn_with_oauth_settings(|s| {
+        s.oauth.custom.client_id = "test-oidc-id".to_string();
+    })
+    .await;
+
+    let body: Value = app
+        .client
+        .get(app.url("/api/oauth/providers"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(
+        body.as_array()
+            .unwrap()
+            .iter()
+            .all(|p| p["name"] != "custom")
+    );
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/custom"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn oauth_redirect_custom_uses_discovered_endpoint() {
+    let app = spawn_with_custom_oidc().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/custom"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 307);
+    let location = resp.headers().get("location").unwrap().to_str().unwrap();
+    assert!(location.contains("/realms/test/protocol/openid-connect/auth?"));
+    assert!(location.contains("client_id=test-oidc-id"));
+    assert!(location.contains("scope=openid%20email%20profile"));
+    assert!(location.contains("oauth%2Fcallback%2Fcustom"));
+}
+
+#[tokio::test]
+async fn oauth_callback_custom_creates_user_from_oidc_claims() {
+    let app = spawn_with_custom_oidc().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/callback/custom?code=good-code&state=s"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 302);
+    let location = resp.headers().get("location").unwrap().to_str().unwrap();
+    assert!(location.contains("/oauth/callback?token="));
+
+    let user = app
+        .db
+        .collection::<bson::Document>("users")
+        .find_one(bson::doc! { "email": "sso@test.com" })
+        .await
+        .unwrap()
+        .expect("user created");
+    assert_eq!(user.get_str("display_name").unwrap(), "sso.user");
+    let linked = user.get_array("oauth_providers").unwrap()[0]
+        .as_document()
+        .unwrap();
+    assert_eq!(linked.get_str("provider").unwrap(), "custom");
+    assert_eq!(linked.get_str("provider_id").unwrap(), "kc-user-1");
+}
+
+#[tokio::test]
+async fn oauth_callback_custom_rejected_code_returns_400() {
+    let app = spawn_with_custom_oidc().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/callback/custom?code=bad-code&state=s"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 400);
+}
+
+#[tokio::test]
+async fn oauth_callback_custom_unverified_email_returns_400() {
+    let app = spawn_with_custom_oidc().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/oauth/callback/custom?code=unverified-code&state=s"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(resp.status().as_u16(), 400);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["message"],
+        "The provider has not verified the account's email"
+    );
+    // No account was created or linked for the unverified email
+    let user = app
+        .db
+        .collection::<bson::Document>("users")
+        .find_one(bson::doc! { "email": "sso@test.com" })
+        .await
+        .unwrap();
+    assert!(user.is_none());
+}
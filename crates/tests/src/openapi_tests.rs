@@ -0,0 +1,48 @@
+use serde_json::Value;
+
+use crate::fixtures::test_app::TestApp;
+
+#[tokio::test]
+async fn openapi_document_covers_routes_and_ws_protocol() {
+    let app = TestApp::spawn().await;
+
+    let resp = app
+        .client
+        .get(app.url("/api/openapi.json"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let doc: Value = resp.json().await.unwrap();
+
+    assert!(doc["openapi"].as_str().unwrap().starts_with("3.1"));
+    let paths = doc["paths"].as_object().unwrap();
+    let messages = &paths["/api/tenant/{tenant_id}/room/{room_id}/message"];
+    assert_eq!(messages["get"]["operationId"], "message_list");
+    assert_eq!(messages["post"]["operationId"], "message_create");
+    assert!(paths.contains_key("/api/auth/login"));
+    assert!(paths.contains_key("/api/tenant/{tenant_id}/bot/{bot_id}/token"));
+    assert!(doc["components"]["schemas"]["CreateMessageRequest"].is_object());
+    assert!(doc["components"]["securitySchemes"]["bearer"].is_object());
+
+    // Public routes opt out of the global bearer requirement.
+    assert_eq!(
+        paths["/api/auth/login"]["post"]["security"],
+        serde_json::json!([{}])
+    );
+
+    let ws = &doc["x-websocket"];
+    assert_eq!(ws["path"], "/ws");
+    assert!(ws["client_messages"]["typing:start"].is_string());
+    assert!(ws["server_messages"]["message:create"].is_string());
+}
+
+#[tokio::test]
+async fn swagger_ui_is_served() {
+    let app = TestApp::spawn().await;
+
+    let resp = app.client.get(app.url("/api/docs/")).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let html = resp.text().await.unwrap();
+    assert!(html.contains("swagger"));
+}
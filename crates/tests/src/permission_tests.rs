@@ -0,0 +1,326 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+// Bits from `roomler_ai_db::models::role::permissions`.
+const SEND_MESSAGES: u64 = 1 << 7;
+const INVITE_MEMBERS: u64 = 1 << 6;
+const MANAGE_ROLES: u64 = 1 << 2;
+
+async fn set_overwrites(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    body: Value,
+) -> u16 {
+    app.auth_put(
+        &format!("/api/tenant/{}/room/{}/permission", tenant_id, room_id),
+        token,
+    )
+    .json(&serde_json::json!({ "overwrites": body }))
+    .send()
+    .await
+    .unwrap()
+    .status()
+    .as_u16()
+}
+
+async fn post_message(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+) -> reqwest::Response {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+        token,
+    )
+    .json(&serde_json::json!({ "content": "hello" }))
+    .send()
+    .await
+    .unwrap()
+}
+
+#[tokio::test]
+async fn room_overwrites_deny_then_allow_per_member() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("perm1").await;
+    let room = &tenant.rooms[0];
+
+    // Members can post by default.
+    let resp = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.member.access_token,
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Deny SEND_MESSAGES to everyone in the room.
+    let status = set_overwrites(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+        serde_json::json!([{ "target_type": "everyone", "deny": SEND_MESSAGES }]),
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    let resp = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.member.access_token,
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 403);
+    // Administrators bypass overwrites; other rooms are unaffected.
+    let resp = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = post_message(
+        &app,
+        &tenant.tenant_id,
+        &tenant.rooms[1].id,
+        &tenant.member.access_token,
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // A member overwrite wins over the everyone deny.
+    let status = set_overwrites(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+        serde_json::json!([
+            { "target_type": "everyone", "deny": SEND_MESSAGES },
+            { "target_type": "member", "target_id": tenant.member.id, "allow": SEND_MESSAGES },
+        ]),
+    )
+    .await;
+    assert_eq!(status, 200);
+    let resp = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.member.access_token,
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let perms: Value = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/permission",
+                tenant.tenant_id, room.id
+            ),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_ne!(perms["permissions"].as_u64().unwrap() & SEND_MESSAGES, 0);
+    assert_eq!(perms["overwrites"].as_array().unwrap().len(), 2);
+    assert!(perms["overwrites"][0]["target_id"].is_null());
+}
+
+#[tokio::test]
+async fn members_cannot_manage_rooms_or_overwrites() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("perm2").await;
+    let room = &tenant.rooms[0];
+    let room_path = format!("/api/tenant/{}/room/{}", tenant.tenant_id, room.id);
+
+    let resp = app
+        .auth_put(&room_path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "name": "hijacked" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_delete(&room_path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let status = set_overwrites(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.member.access_token,
+        serde_json::json!([{
+            "target_type": "member",
+            "target_id": tenant.member.id,
+            "allow": MANAGE_ROLES,
+        }]),
+    )
+    .await;
+    assert_eq!(status, 403);
+
+    let status = set_overwrites(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+        serde_json::json!([{ "target_type": "nobody" }]),
+    )
+    .await;
+    assert_eq!(status, 422);
+}
+
+#[tokio::test]
+async fn room_creator_manages_own_room() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("perm3").await;
+
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "name": "members-own", "is_open": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/room/{}", tenant.tenant_id, room_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "purpose": "mine" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn message_moderation_requires_manage_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("perm4").await;
+    let room = &tenant.rooms[0];
+
+    let admin_msg: Value = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let member_msg: Value = post_message(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.member.access_token,
+    )
+    .await
+    .json()
+    .await
+    .unwrap();
+    let msg_path = |id: &Value| {
+        format!(
+            "/api/tenant/{}/room/{}/message/{}",
+            tenant.tenant_id,
+            room.id,
+            id.as_str().unwrap()
+        )
+    };
+
+    // Members can neither pin nor delete someone else's message.
+    let resp = app
+        .auth_put(
+            &format!("{}/pin", msg_path(&admin_msg["id"])),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "pinned": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_delete(&msg_path(&admin_msg["id"]), &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // A moderator (here: the owner) can delete a member's message.
+    let resp = app
+        .auth_delete(&msg_path(&member_msg["id"]), &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn room_scoped_invite_uses_room_permissions() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("perm5").await;
+    let room = &tenant.rooms[0];
+    let invite_path = format!("/api/tenant/{}/invite", tenant.tenant_id);
+
+    let status = set_overwrites(
+        &app,
+        &tenant.tenant_id,
+        &room.id,
+        &tenant.admin.access_token,
+        serde_json::json!([{
+            "target_type": "member",
+            "target_id": tenant.member.id,
+            "allow": INVITE_MEMBERS,
+        }]),
+    )
+    .await;
+    assert_eq!(status, 200);
+
+    // Tenant-wide invites still need the tenant permission...
+    let resp = app
+        .auth_post(&invite_path, &tenant.member.access_token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // ...but the overwrite allows inviting into this room.
+    let resp = app
+        .auth_post(&invite_path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "room_id": room.id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let invite: Value = resp.json().await.unwrap();
+    assert_eq!(invite["room_id"], room.id.as_str());
+
+    let resp = app
+        .auth_post(&invite_path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "room_id": tenant.rooms[1].id }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
@@ -0,0 +1,171 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::Ws;
+use bson::{doc, oid::ObjectId};
+use futures::StreamExt;
+use reqwest::multipart;
+use serde_json::Value;
+
+async fn spawn() -> TestApp {
+    TestApp::spawn_with_settings(|s| s.stripe.enforce_limits = true).await
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+/// Read until a `billing:limit_reached` event arrives.
+async fn limit_event(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let Ok(text) = msg.to_text() else { continue };
+            let Ok(parsed) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            if parsed["type"] == "billing:limit_reached" {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .expect("no billing:limit_reached event")
+}
+
+async fn set_plan(app: &TestApp, tenant_id: &str, plan: &str) {
+    app.db
+        .collection::<bson::Document>("tenants")
+        .update_one(
+            doc! { "_id": ObjectId::parse_str(tenant_id).unwrap() },
+            doc! { "$set": { "plan": plan } },
+        )
+        .await
+        .unwrap();
+}
+
+async fn assert_plan_limit(resp: reqwest::Response, message: &str) {
+    assert_eq!(resp.status().as_u16(), 402);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "plan_limit");
+    assert_eq!(body["message"], message);
+}
+
+#[tokio::test]
+async fn free_plan_refuses_calls_recordings_and_extra_members() {
+    let app = spawn().await;
+    let tenant = app.seed_tenant("planfree").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    let mut ws = connect(&app, token).await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/join", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_plan_limit(resp, "The Free plan does not include calls").await;
+
+    let event = limit_event(&mut ws).await;
+    assert_eq!(event["tenant_id"], tid.as_str());
+    assert_eq!(event["limit"], "call_participants");
+    assert_eq!(event["plan"], "free");
+    assert_eq!(event["max"], 0);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/recording", tid, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "recording_type": "video" }))
+        .send()
+        .await
+        .unwrap();
+    assert_plan_limit(resp, "The Free plan does not include recordings").await;
+
+    // The seed has two members; fill the remaining eight seats.
+    let tenant_oid = ObjectId::parse_str(tid).unwrap();
+    let seats: Vec<bson::Document> = (0..8)
+        .map(|_| doc! { "tenant_id": tenant_oid, "user_id": ObjectId::new() })
+        .collect();
+    app.db
+        .collection::<bson::Document>("tenant_members")
+        .insert_many(seats)
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/member", tid), token)
+        .json(&serde_json::json!({ "user_id": ObjectId::new().to_hex() }))
+        .send()
+        .await
+        .unwrap();
+    assert_plan_limit(resp, "The Free plan allows 10 members").await;
+}
+
+#[tokio::test]
+async fn upgraded_plan_allows_calls_and_storage_quota_applies() {
+    let app = spawn().await;
+    let tenant = app.seed_tenant("planbiz").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    set_plan(&app, tid, "business").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/join", tid, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/recording", tid, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "recording_type": "video" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // Stored files already fill the 100 GB quota.
+    app.db
+        .collection::<bson::Document>("files")
+        .insert_one(doc! {
+            "tenant_id": ObjectId::parse_str(tid).unwrap(),
+            "size": 100_i64 * 1024 * 1024 * 1024,
+            "deleted_at": null,
+        })
+        .await
+        .unwrap();
+
+    let form = multipart::Form::new().part(
+        "file",
+        multipart::Part::bytes(b"one byte too many".to_vec())
+            .file_name("note.txt")
+            .mime_str("text/plain")
+            .unwrap(),
+    );
+    let resp = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/room/{}/file/upload", tid, room_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(form)
+        .send()
+        .await
+        .unwrap();
+    assert_plan_limit(resp, "The Business plan allows 100 GB of storage").await;
+}
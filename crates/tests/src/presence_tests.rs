@@ -0,0 +1,195 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of_within, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+/// The next JSON message, whatever its type.
+async fn next_json(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            if let Ok(text) = msg.to_text()
+                && let Ok(parsed) = serde_json::from_str::<Value>(text)
+            {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for WS message")
+}
+
+async fn join(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn presence_reaches_only_watchers_of_shared_rooms() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("presence1").await;
+    let other = app.seed_tenant("presence1b").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.admin.access_token).await;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut watcher = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut watcher,
+        "presence:subscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let snapshot = next_of_within(&mut watcher, "presence:snapshot", 3).await;
+    assert_eq!(snapshot["room_id"], room_id.as_str());
+    assert!(snapshot["typing"].as_array().unwrap().is_empty());
+
+    let mut admin = connect(&app, &tenant.admin.access_token).await;
+    send(
+        &mut admin,
+        "presence:update",
+        serde_json::json!({ "presence": "dnd" }),
+    )
+    .await;
+    let update = next_json(&mut watcher).await;
+    assert_eq!(update["type"], "presence:update");
+    assert_eq!(update["data"]["user_id"], tenant.admin.id.as_str());
+    assert_eq!(update["data"]["presence"], "dnd");
+
+    // A user of another tenant changing presence is none of our business.
+    let mut stranger = connect(&app, &other.admin.access_token).await;
+    send(
+        &mut stranger,
+        "presence:update",
+        serde_json::json!({ "presence": "online" }),
+    )
+    .await;
+    send(&mut watcher, "ping", serde_json::json!({})).await;
+    let reply = next_json(&mut watcher).await;
+    assert_eq!(reply["type"], "pong");
+
+    // A fresh subscription sees the admin in its snapshot.
+    send(
+        &mut watcher,
+        "presence:subscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let snapshot = next_of_within(&mut watcher, "presence:snapshot", 3).await;
+    let users = snapshot["users"].as_array().unwrap();
+    assert!(
+        users
+            .iter()
+            .any(|u| u["user_id"] == tenant.admin.id.as_str() && u["presence"] == "dnd")
+    );
+
+    // Invisible shows as offline.
+    send(
+        &mut admin,
+        "presence:update",
+        serde_json::json!({ "presence": "invisible" }),
+    )
+    .await;
+    let update = next_of_within(&mut watcher, "presence:update", 3).await;
+    assert_eq!(update["presence"], "offline");
+
+    // After unsubscribing, nothing more arrives.
+    send(
+        &mut watcher,
+        "presence:unsubscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    send(
+        &mut admin,
+        "presence:update",
+        serde_json::json!({ "presence": "online" }),
+    )
+    .await;
+    send(&mut watcher, "ping", serde_json::json!({})).await;
+    let reply = next_json(&mut watcher).await;
+    assert_eq!(reply["type"], "pong");
+}
+
+#[tokio::test]
+async fn non_members_cannot_watch_a_room() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("presence2").await;
+    let room_id = &tenant.rooms[0].id;
+
+    let mut ws = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut ws,
+        "presence:subscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    send(&mut ws, "ping", serde_json::json!({})).await;
+    let reply = next_json(&mut ws).await;
+    assert_eq!(reply["type"], "pong", "no snapshot for a non-member");
+}
+
+#[tokio::test]
+async fn silent_users_lapse_to_offline_and_typing_expires() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.ws.presence_ttl_secs = 2;
+        s.ws.idle_timeout_secs = 0;
+    })
+    .await;
+    let tenant = app.seed_tenant("presence3").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.admin.access_token).await;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut watcher = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut watcher,
+        "presence:subscribe",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    next_of_within(&mut watcher, "presence:snapshot", 3).await;
+
+    let mut admin = connect(&app, &tenant.admin.access_token).await;
+    send(
+        &mut admin,
+        "presence:update",
+        serde_json::json!({ "presence": "online" }),
+    )
+    .await;
+    let update = next_of_within(&mut watcher, "presence:update", 3).await;
+    assert_eq!(update["presence"], "online");
+    send(
+        &mut admin,
+        "typing:start",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let typing = next_of_within(&mut watcher, "typing:start", 3).await;
+    assert_eq!(typing["user_id"], tenant.admin.id.as_str());
+
+    // The admin goes quiet: their presence lapses, then their typing.
+    let update = next_of_within(&mut watcher, "presence:update", 5).await;
+    assert_eq!(update["user_id"], tenant.admin.id.as_str());
+    assert_eq!(update["presence"], "offline");
+    let stopped = next_of_within(&mut watcher, "typing:stop", 10).await;
+    assert_eq!(stopped["user_id"], tenant.admin.id.as_str());
+    drop(admin);
+}
@@ -0,0 +1,209 @@
+use crate::fixtures::test_app::TestApp;
+use bson::doc;
+use reqwest::multipart;
+use serde_json::Value;
+
+async fn status(resp: reqwest::RequestBuilder) -> u16 {
+    resp.send().await.unwrap().status().as_u16()
+}
+
+fn png(width: u32, height: u32) -> Vec<u8> {
+    let mut out = std::io::Cursor::new(Vec::new());
+    image::DynamicImage::new_rgb8(width, height)
+        .write_to(&mut out, image::ImageFormat::Png)
+        .unwrap();
+    out.into_inner()
+}
+
+async fn upload_avatar(
+    app: &TestApp,
+    token: &str,
+    bytes: Vec<u8>,
+    crop: &[(&str, &str)],
+) -> reqwest::Response {
+    let part = multipart::Part::bytes(bytes)
+        .file_name("me.png")
+        .mime_str("image/png")
+        .unwrap();
+    let mut form = multipart::Form::new().part("file", part);
+    for (name, value) in crop {
+        form = form.text(name.to_string(), value.to_string());
+    }
+    app.auth_post("/api/user/me/avatar", token)
+        .multipart(form)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn member_entry(app: &TestApp, tenant_id: &str, token: &str, user_id: &str) -> Value {
+    let page: Value = app
+        .auth_get(&format!("/api/tenant/{}/member", tenant_id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["user_id"] == user_id)
+        .cloned()
+        .expect("member not listed")
+}
+
+#[tokio::test]
+async fn profile_fields_and_status_reach_members_and_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("profile1").await;
+    let tid = &tenant.tenant_id;
+    let rid = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+
+    let bad_tz = app
+        .auth_put("/api/user/me", member)
+        .json(&serde_json::json!({ "timezone": "Mars Base" }));
+    assert_eq!(status(bad_tz).await, 422);
+    let update = app
+        .auth_put("/api/user/me", member)
+        .json(&serde_json::json!({ "pronouns": "they/them", "timezone": "Europe/Vienna" }));
+    assert_eq!(status(update).await, 200);
+
+    let past = app
+        .auth_put("/api/user/me/status", member)
+        .json(&serde_json::json!({ "text": "Away", "expires_at": "2020-01-01T00:00:00Z" }));
+    assert_eq!(status(past).await, 422);
+    let empty = app
+        .auth_put("/api/user/me/status", member)
+        .json(&serde_json::json!({ "text": "  " }));
+    assert_eq!(status(empty).await, 422);
+
+    let expires = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
+    let resp = app
+        .auth_put("/api/user/me/status", member)
+        .json(&serde_json::json!({
+            "text": "In a meeting",
+            "emoji": "\u{1f4c5}",
+            "expires_at": expires,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let profile: Value = resp.json().await.unwrap();
+    assert_eq!(profile["status"]["text"], "In a meeting");
+    assert_eq!(profile["pronouns"], "they/them");
+    assert_eq!(profile["timezone"], "Europe/Vienna");
+
+    let entry = member_entry(&app, tid, admin, &tenant.member.id).await;
+    assert_eq!(entry["pronouns"], "they/them");
+    assert_eq!(entry["status"]["emoji"], "\u{1f4c5}");
+
+    assert_eq!(
+        status(app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, rid), member)).await,
+        200
+    );
+    let messages_url = format!("/api/tenant/{}/room/{}/message", tid, rid);
+    let send = app
+        .auth_post(&messages_url, member)
+        .json(&serde_json::json!({ "content": "hi" }));
+    assert_eq!(status(send).await, 200);
+    let page: Value = app
+        .auth_get(&messages_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let message = page["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["author_id"] == tenant.member.id.as_str())
+        .unwrap()
+        .clone();
+    assert_eq!(message["author_status"]["text"], "In a meeting");
+
+    // An expired status is no longer shown.
+    app.db
+        .collection::<bson::Document>("users")
+        .update_one(
+            doc! { "email": tenant.member.email.as_str() },
+            doc! { "$set": { "status.expires_at": bson::DateTime::from_millis(0) } },
+        )
+        .await
+        .unwrap();
+    let entry = member_entry(&app, tid, admin, &tenant.member.id).await;
+    assert!(entry.get("status").is_none());
+
+    assert_eq!(
+        status(app.auth_delete("/api/user/me/status", member)).await,
+        200
+    );
+    let profile: Value = app
+        .auth_get(&format!("/api/user/{}", tenant.member.id), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(profile.get("status").is_none());
+}
+
+#[tokio::test]
+async fn avatar_upload_is_cropped_and_replaced() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("profile2").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.member.access_token;
+
+    let resp = upload_avatar(&app, token, b"not an image".to_vec(), &[]).await;
+    assert_eq!(resp.status().as_u16(), 422);
+    let resp = upload_avatar(&app, token, png(400, 200), &[("crop_x", "10")]).await;
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let crop = [("crop_x", "20"), ("crop_y", "0"), ("crop_size", "150")];
+    let resp = upload_avatar(&app, token, png(400, 200), &crop).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let profile: Value = resp.json().await.unwrap();
+    let first = profile["avatar"].as_str().unwrap().to_string();
+
+    let resp = app
+        .auth_get(&first, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "image/png");
+    let avatar = image::load_from_memory(&resp.bytes().await.unwrap()).unwrap();
+    assert_eq!((avatar.width(), avatar.height()), (256, 256));
+
+    let entry = member_entry(&app, tid, &tenant.admin.access_token, &tenant.member.id).await;
+    assert_eq!(entry["avatar"], first.as_str());
+
+    // A new upload retires the old picture.
+    let resp = upload_avatar(&app, token, png(100, 300), &[]).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let profile: Value = resp.json().await.unwrap();
+    assert_ne!(profile["avatar"], first.as_str());
+    assert_eq!(status(app.auth_get(&first, token)).await, 404);
+
+    assert_eq!(
+        status(app.auth_delete("/api/user/me/avatar", token)).await,
+        200
+    );
+    let profile: Value = app
+        .auth_get(&format!("/api/user/{}", tenant.member.id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(profile["avatar"].is_null());
+}
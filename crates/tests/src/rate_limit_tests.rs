@@ -49,3 +49,115 @@ async fn rate_limit_recovers_after_wait() {
         "Request should succeed after rate limit recovery"
     );
 }
+
+#[tokio::test]
+async fn auth_attempts_limited_per_ip_with_retry_after() {
+    let app = TestApp::spawn_with_settings(|s| s.rate_limit.auth_per_min = 3).await;
+
+    let mut statuses = Vec::new();
+    let mut retry_after = None;
+    for _ in 0..4 {
+        let resp = app
+            .client
+            .post(app.url("/api/auth/login"))
+            .json(&serde_json::json!({ "username": "nobody", "password": "wrong" }))
+            .send()
+            .await
+            .unwrap();
+        statuses.push(resp.status().as_u16());
+        if let Some(v) = resp.headers().get("retry-after") {
+            retry_after = v.to_str().ok().and_then(|v| v.parse::<u64>().ok());
+        }
+    }
+
+    assert_eq!(statuses, vec![401, 401, 401, 429]);
+    assert!(retry_after.is_some_and(|s| s >= 1), "missing Retry-After");
+}
+
+#[tokio::test]
+async fn message_create_limited_by_tenant_override() {
+    use bson::doc;
+
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ratemsg").await;
+    let tid = bson::oid::ObjectId::parse_str(&tenant.tenant_id).unwrap();
+    // Stand-in for a Stripe price carrying `rate_limit_messages_per_min`.
+    app.db
+        .collection::<bson::Document>("tenants")
+        .update_one(
+            doc! { "_id": tid },
+            doc! { "$set": { "billing": {
+                "customer_id": null,
+                "subscription_id": null,
+                "current_period_end": null,
+                "status": "active",
+                "cancel_at_period_end": false,
+                "rate_limits": { "messages_per_min": 2 },
+            } } },
+        )
+        .await
+        .unwrap();
+
+    let path = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let mut statuses = Vec::new();
+    for i in 0..3 {
+        let resp = app
+            .auth_post(&path, &tenant.admin.access_token)
+            .json(&serde_json::json!({ "content": format!("msg {i}") }))
+            .send()
+            .await
+            .unwrap();
+        statuses.push(resp.status().as_u16());
+    }
+    assert_eq!(statuses, vec![200, 200, 429]);
+
+    // Another member has their own allowance.
+    let resp = app
+        .auth_post(&path, &tenant.member.access_token)
+        .json(&serde_json::json!({ "content": "from member" }))
+        .send()
+        .await
+        .unwrap();
+    assert_ne!(resp.status().as_u16(), 429);
+}
+
+#[tokio::test]
+async fn ws_messages_throttled_per_connection() {
+    use futures::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite::Message;
+
+    let app = TestApp::spawn_with_settings(|s| s.rate_limit.ws_messages_per_sec = 1).await;
+    let tenant = app.seed_tenant("ratews").await;
+
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, tenant.admin.access_token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url).await.unwrap();
+    ws.next().await; // connected
+
+    // Burst is four seconds' worth: 4 pings answered, the rest dropped.
+    for _ in 0..10 {
+        ws.send(Message::Text(r#"{"type":"ping"}"#.into()))
+            .await
+            .unwrap();
+    }
+
+    let mut pongs = 0;
+    let mut limited = 0;
+    while let Ok(Some(Ok(msg))) =
+        tokio::time::timeout(std::time::Duration::from_millis(500), ws.next()).await
+    {
+        let parsed: serde_json::Value = serde_json::from_str(msg.to_text().unwrap()).unwrap();
+        match parsed["type"].as_str() {
+            Some("pong") => pongs += 1,
+            Some("rate_limited") => {
+                limited += 1;
+                assert!(parsed["data"]["retry_after_ms"].as_u64().is_some());
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(pongs, 4);
+    assert_eq!(limited, 1, "client is told once per throttled run");
+}
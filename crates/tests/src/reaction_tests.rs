@@ -109,6 +109,90 @@ async fn duplicate_reaction_fails() {
     assert_eq!(resp.status().as_u16(), 409);
 }
 
+#[tokio::test]
+async fn react_with_custom_emoji() {
+    let (app, tenant, room_id, message_id) = setup_with_message().await;
+    let tid = &tenant.tenant_id;
+    let reaction_url = format!(
+        "/api/tenant/{}/room/{}/message/{}/reaction",
+        tid, room_id, message_id
+    );
+
+    let part = reqwest::multipart::Part::bytes(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec())
+        .file_name("shipit.png")
+        .mime_str("image/png")
+        .unwrap();
+    let emoji: Value = app
+        .client
+        .post(app.url(&format!("/api/tenant/{}/asset/emoji", tid)))
+        .header(
+            "Authorization",
+            format!("Bearer {}", tenant.admin.access_token),
+        )
+        .multipart(
+            reqwest::multipart::Form::new()
+                .text("name", "shipit")
+                .part("file", part),
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+
+    // By name, or by id: the same reaction.
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "emoji": ":shipit:" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "custom_emoji_id": emoji["id"] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // Unknown custom emojis are rejected.
+    let resp = app
+        .auth_post(&reaction_url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "emoji": ":nope:" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let reactions = json["items"][0]["reaction_summary"].as_array().unwrap();
+    assert_eq!(reactions.len(), 1);
+    assert_eq!(reactions[0]["emoji"], ":shipit:");
+
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", reaction_url, ":shipit:"),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["removed"], true);
+}
+
 #[tokio::test]
 async fn remove_reaction_from_message() {
     let (app, tenant, room_id, message_id) = setup_with_message().await;
@@ -0,0 +1,203 @@
+use crate::fixtures::{
+    seed::SeededTenant,
+    test_app::TestApp,
+    ws::{Ws, next_of},
+};
+use futures::StreamExt;
+use serde_json::{Value, json};
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+/// Start a call in a new room that both the admin and the member have
+/// joined; returns the room's recording URL.
+async fn start_call(app: &TestApp, tenant: &SeededTenant, name: &str) -> String {
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tid), admin)
+        .json(&json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    call_action(app, tid, room_id, admin, "start").await;
+    call_action(app, tid, room_id, admin, "join").await;
+    call_action(app, tid, room_id, &tenant.member.access_token, "join").await;
+    format!("/api/tenant/{}/room/{}/recording", tid, room_id)
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let (mut ws, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/ws?token={}", app.addr, token))
+            .await
+            .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+/// Create a recording asking for consent; returns its id.
+async fn record_with_consent(app: &TestApp, url: &str, token: &str, on_decline: &str) -> String {
+    let resp = app
+        .auth_post(url, token)
+        .json(&json!({ "consent": { "on_decline": on_decline } }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let recording: Value = resp.json().await.unwrap();
+    assert_eq!(recording["status"], "AwaitingConsent");
+    recording["id"].as_str().unwrap().to_string()
+}
+
+async fn respond(app: &TestApp, url: &str, token: &str, accept: bool) -> reqwest::Response {
+    app.auth_post(url, token)
+        .json(&json!({ "accept": accept }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn recording_starts_once_everyone_consents() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rconsent1").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let url = start_call(&app, &tenant, "Consent").await;
+    let mut admin_ws = connect(&app, admin).await;
+    let mut member_ws = connect(&app, member).await;
+
+    let rec_id = record_with_consent(&app, &url, admin, "keep_unrecorded").await;
+    let asked = next_of(&mut member_ws, "call:recording_consent_request").await;
+    assert_eq!(asked["recording_id"], rec_id.as_str());
+    assert_eq!(asked["requested_by"], tenant.admin.id.as_str());
+    assert_eq!(asked["on_decline"], "keep_unrecorded");
+    assert_eq!(asked["timeout_secs"], 60);
+
+    // Nothing is recorded before consent.
+    let resp = app
+        .auth_put(&format!("{}/{}/file?duration_ms=1000", url, rec_id), admin)
+        .body(b"webm".to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let consent_url = format!("{}/{}/consent", url, rec_id);
+    let resp = respond(&app, &consent_url, member, true).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let consent: Value = resp.json().await.unwrap();
+    assert_eq!(consent["decision"], "accepted");
+    assert_eq!(consent["timed_out"], false);
+
+    let answer = next_of(&mut admin_ws, "call:recording_consent").await;
+    assert_eq!(answer["user_id"], tenant.member.id.as_str());
+    assert_eq!(answer["accepted"], true);
+    let started = next_of(&mut admin_ws, "call:recording_started").await;
+    assert_eq!(started["recording_id"], rec_id.as_str());
+    assert_eq!(started["unrecorded_user_ids"], json!([]));
+
+    // An answer is final.
+    let resp = respond(&app, &consent_url, member, false).await;
+    assert_eq!(resp.status().as_u16(), 409);
+
+    let resp = app.auth_get(&consent_url, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let consents: Value = app
+        .auth_get(&consent_url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let decisions: Vec<&str> = consents
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|c| c["decision"].as_str().unwrap())
+        .collect();
+    assert_eq!(decisions, ["accepted", "accepted"]);
+
+    let resp = app
+        .auth_put(&format!("{}/{}/file?duration_ms=1000", url, rec_id), admin)
+        .body(b"webm".to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn declining_keeps_the_participant_unrecorded() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rconsent2").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let url = start_call(&app, &tenant, "Unrecorded").await;
+    let mut admin_ws = connect(&app, admin).await;
+
+    let rec_id = record_with_consent(&app, &url, admin, "keep_unrecorded").await;
+    let consent_url = format!("{}/{}/consent", url, rec_id);
+    let resp = respond(&app, &consent_url, member, false).await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let started = next_of(&mut admin_ws, "call:recording_started").await;
+    assert_eq!(
+        started["unrecorded_user_ids"],
+        json!([tenant.member.id.as_str()])
+    );
+    let recordings: Value = app
+        .auth_get(&url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(recordings["items"][0]["status"], "Processing");
+}
+
+#[tokio::test]
+async fn declining_can_remove_the_participant_from_the_call() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rconsent3").await;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let url = start_call(&app, &tenant, "Removed").await;
+    let mut member_ws = connect(&app, member).await;
+
+    let rec_id = record_with_consent(&app, &url, admin, "remove").await;
+    let consent_url = format!("{}/{}/consent", url, rec_id);
+    let resp = respond(&app, &consent_url, member, false).await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let removed = next_of(&mut member_ws, "call:recording_removed").await;
+    assert_eq!(removed["recording_id"], rec_id.as_str());
+
+    let history: Value = app
+        .auth_get(&url.replace("/recording", "/call/history"), admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let call = &history["items"][0];
+    assert!(call["ended_at"].is_null());
+    assert_eq!(call["participant_count"], 1);
+}
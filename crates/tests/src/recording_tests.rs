@@ -151,3 +151,270 @@ async fn delete_recording() {
     let json: Value = resp.json().await.unwrap();
     assert_eq!(json["deleted"], true);
 }
+
+#[tokio::test]
+async fn recording_streams_ranges_and_aligns_transcript() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec4").await;
+    let token = &tenant.admin.access_token;
+    let room_id = tenant.rooms[0].id.as_str();
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording",
+        tenant.tenant_id, room_id
+    );
+
+    let rec: Value = app
+        .auth_post(&base, token)
+        .json(&serde_json::json!({ "recording_type": "video" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let rec_url = format!("{}/{}", base, rec["id"].as_str().unwrap());
+
+    // Not streamable until the file is uploaded
+    let resp = app
+        .auth_get(&format!("{}/stream", rec_url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // The recorder uploads the file; it starts 10 s into the call
+    let media: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+    let resp = app
+        .auth_put(
+            &format!("{}/file?duration_ms=60000&offset_ms=10000", rec_url),
+            token,
+        )
+        .header("Content-Type", "video/webm")
+        .body(media.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["status"], "Available");
+    assert_eq!(json["size"], 1000);
+    assert_eq!(json["duration"], 60);
+
+    let resp = app
+        .auth_put(&format!("{}/file?duration_ms=1000", rec_url), token)
+        .body(media.clone())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 409);
+
+    // Range requests
+    let resp = app
+        .auth_get(&format!("{}/stream", rec_url), token)
+        .header("Range", "bytes=100-199")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 206);
+    assert_eq!(resp.headers()["content-range"], "bytes 100-199/1000");
+    assert_eq!(resp.headers()["accept-ranges"], "bytes");
+    assert_eq!(resp.bytes().await.unwrap().as_ref(), &media[100..200]);
+
+    let resp = app
+        .auth_get(&format!("{}/stream", rec_url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.bytes().await.unwrap().len(), 1000);
+
+    let resp = app
+        .auth_get(&format!("{}/stream", rec_url), token)
+        .header("Range", "bytes=5000-")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 416);
+    assert_eq!(resp.headers()["content-range"], "bytes */1000");
+
+    // Transcript on the call clock comes back on the file's timeline
+    let resp = app
+        .auth_put(&format!("{}/transcript", rec_url), token)
+        .json(&serde_json::json!({ "segments": [
+            { "speaker_name": "Ann", "text": "second", "start_time": 25.5, "end_time": 28.0 },
+            { "speaker_name": "Ann", "text": "before", "start_time": 2.0, "end_time": 5.0 },
+            { "speaker_name": "Bob", "text": "first", "start_time": 9.0, "end_time": 12.0 },
+        ] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_get(&format!("{}/transcript", rec_url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["duration"], 60.0);
+    let segments = json["segments"].as_array().unwrap();
+    assert_eq!(segments.len(), 2);
+    assert_eq!(segments[0]["text"], "first");
+    assert_eq!(segments[0]["start"], 0.0);
+    assert_eq!(segments[0]["end"], 2.0);
+    assert_eq!(segments[1]["text"], "second");
+    assert_eq!(segments[1]["start"], 15.5);
+
+    let resp = app
+        .auth_put(&format!("{}/transcript", rec_url), token)
+        .json(&serde_json::json!({ "segments": [
+            { "speaker_name": "Ann", "text": "x", "start_time": 5.0, "end_time": 1.0 },
+        ] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn recording_is_chaptered_by_topic() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec5").await;
+    let token = &tenant.admin.access_token;
+    let room_id = tenant.rooms[0].id.as_str();
+    let base = format!(
+        "/api/tenant/{}/room/{}/recording",
+        tenant.tenant_id, room_id
+    );
+
+    let rec: Value = app
+        .auth_post(&base, token)
+        .json(&serde_json::json!({}))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let rec_url = format!("{}/{}", base, rec["id"].as_str().unwrap());
+
+    // Without a Claude key the transcript is split where the vocabulary shifts
+    let lines = [
+        (2.0, "The budget for the quarter is tight"),
+        (20.0, "Budget cuts hit the travel costs"),
+        (40.0, "Travel costs are over budget again"),
+        (60.0, "Let's freeze the budget for travel"),
+        (80.0, "Hiring: two engineers start in May"),
+        (100.0, "Interview engineers next week for hiring"),
+        (120.0, "The hiring panel needs engineers"),
+        (140.0, "Engineers onboarding after hiring"),
+    ];
+    let segments: Vec<Value> = lines
+        .iter()
+        .map(|(start, text)| {
+            serde_json::json!({
+                "speaker_name": "Ann",
+                "text": text,
+                "start_time": start,
+                "end_time": start + 15.0,
+            })
+        })
+        .collect();
+    app.auth_put(&format!("{}/transcript", rec_url), token)
+        .json(&serde_json::json!({ "segments": segments }))
+        .send()
+        .await
+        .unwrap();
+    app.auth_put(&format!("{}/file?duration_ms=160000", rec_url), token)
+        .body(vec![0u8; 64])
+        .send()
+        .await
+        .unwrap();
+
+    let mut chapters = Value::Null;
+    for _ in 0..50 {
+        let json: Value = app
+            .auth_get(&base, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        chapters = json["items"][0]["chapters"].clone();
+        if chapters.as_array().is_some_and(|c| !c.is_empty()) {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+    }
+    let chapters = chapters.as_array().expect("chapters listed");
+    assert_eq!(chapters.len(), 2);
+    assert_eq!(chapters[0]["start"], 0.0);
+    assert!(chapters[0]["title"].as_str().unwrap().starts_with("Budget"));
+    assert_eq!(chapters[1]["start"], 80.0);
+    assert!(chapters[1]["title"].as_str().unwrap().starts_with("Hiring"));
+}
+
+#[tokio::test]
+async fn recording_captions_follow_the_room_setting() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("rec6").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+
+    let mut urls = Vec::new();
+    for captions in [true, false] {
+        let room: Value = app
+            .auth_post(&format!("/api/tenant/{}/room", tid), token)
+            .json(&serde_json::json!({
+                "name": format!("Captions {}", captions),
+                "media_settings": { "captions": captions },
+            }))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let base = format!(
+            "/api/tenant/{}/room/{}/recording",
+            tid,
+            room["id"].as_str().unwrap()
+        );
+        let rec: Value = app
+            .auth_post(&base, token)
+            .json(&serde_json::json!({}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let rec_url = format!("{}/{}", base, rec["id"].as_str().unwrap());
+        let resp = app
+            .auth_put(&format!("{}/transcript", rec_url), token)
+            .json(&serde_json::json!({ "segments": [
+                { "speaker_name": "Ann", "text": "Hi <all>", "start_time": 1.5, "end_time": 4.0 },
+                { "speaker_name": "Bob", "text": "Hello", "start_time": 65.0, "end_time": 66.25 },
+            ] }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+        urls.push(format!("{}/captions", rec_url));
+    }
+
+    let resp = app.auth_get(&urls[0], token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(resp.headers()["content-type"], "text/vtt; charset=utf-8");
+    assert_eq!(
+        resp.text().await.unwrap(),
+        "WEBVTT\n\
+         \n1\n00:00:01.500 --> 00:00:04.000\n<v Ann>Hi &lt;all&gt;\n\
+         \n2\n00:01:05.000 --> 00:01:06.250\n<v Bob>Hello\n"
+    );
+
+    let resp = app.auth_get(&urls[1], token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
@@ -0,0 +1,210 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+use std::time::Duration;
+
+async fn post_message(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) -> String {
+    let msg: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "old news" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    msg["id"].as_str().unwrap().to_string()
+}
+
+async fn message_exists(app: &TestApp, id: &str) -> bool {
+    app.db
+        .collection::<bson::Document>("messages")
+        .find_one(doc! { "_id": ObjectId::parse_str(id).unwrap() })
+        .await
+        .unwrap()
+        .is_some()
+}
+
+#[tokio::test]
+async fn retention_policy_requires_manage_tenant_and_valid_days() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("retention1").await;
+    let url = format!("/api/tenant/{}/retention", tenant.tenant_id);
+
+    let resp = app
+        .auth_put(&url, &tenant.member.access_token)
+        .json(&serde_json::json!({ "message_days": 30 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "message_days": 0 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_put(&url, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "message_days": 30, "recording_days": 90 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let body: Value = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["message_days"], 30);
+    assert_eq!(body["recording_days"], 90);
+    assert!(body["transcript_days"].is_null());
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/audit?filter[action]=tenant.retention_update",
+                tenant.tenant_id
+            ),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    let audit: Value = resp.json().await.unwrap();
+    assert_eq!(audit["items"].as_array().unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn reaper_purges_expired_messages_except_pinned_and_held() {
+    let app = TestApp::spawn_with_settings(|s| s.retention.sweep_interval_secs = 1).await;
+    let tenant = app.seed_tenant("retention2").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let (open_room, held_room) = (&tenant.rooms[0].id, &tenant.rooms[1].id);
+
+    let expired = post_message(&app, tid, open_room, token).await;
+    let pinned = post_message(&app, tid, open_room, token).await;
+    let held = post_message(&app, tid, held_room, token).await;
+    let fresh = post_message(&app, tid, open_room, token).await;
+
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/room/{}/message/{}/pin",
+                tid, open_room, pinned
+            ),
+            token,
+        )
+        .json(&serde_json::json!({ "pinned": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/room/{}/legal-hold", tid, held_room),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_put(
+            &format!("/api/tenant/{}/room/{}/legal-hold", tid, held_room),
+            token,
+        )
+        .json(&serde_json::json!({ "enabled": true }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let room: Value = app
+        .auth_get(&format!("/api/tenant/{}/room/{}", tid, held_room), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(room["legal_hold"], true);
+
+    // Age everything but `fresh` past the limit.
+    let old =
+        bson::DateTime::from_millis(bson::DateTime::now().timestamp_millis() - 10 * 86_400_000);
+    let aged: Vec<ObjectId> = [&expired, &pinned, &held]
+        .iter()
+        .map(|id| ObjectId::parse_str(id).unwrap())
+        .collect();
+    app.db
+        .collection::<bson::Document>("messages")
+        .update_many(
+            doc! { "_id": { "$in": aged } },
+            doc! { "$set": { "created_at": old } },
+        )
+        .await
+        .unwrap();
+
+    let resp = app
+        .auth_put(&format!("/api/tenant/{}/retention", tid), token)
+        .json(&serde_json::json!({ "message_days": 7 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The purge is audited once its deletes are done.
+    let purge_audit = format!("/api/tenant/{}/audit?filter[action]=retention.purge", tid);
+    let mut entry = Value::Null;
+    for _ in 0..50 {
+        let audit: Value = app
+            .auth_get(&purge_audit, token)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        if let Some(first) = audit["items"].as_array().and_then(|i| i.first()) {
+            entry = first.clone();
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(entry["actor_type"], "system", "no purge was audited");
+    assert!(entry["actor_id"].is_null());
+    assert_eq!(entry["target_type"], "message");
+
+    assert!(!message_exists(&app, &expired).await);
+    assert!(message_exists(&app, &pinned).await);
+    assert!(message_exists(&app, &held).await);
+    assert!(message_exists(&app, &fresh).await);
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?filter[action]=room.legal_hold", tid),
+            token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["items"].as_array().unwrap().len(), 1);
+}
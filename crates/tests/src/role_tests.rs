@@ -256,3 +256,98 @@ async fn non_member_cannot_list_roles() {
         "Non-member should get 403 Forbidden when listing roles"
     );
 }
+
+#[tokio::test]
+async fn last_administrator_cannot_be_removed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("role7").await;
+    let base = format!("/api/tenant/{}/role", tenant.tenant_id);
+
+    let roles: Vec<Value> = app
+        .auth_get(&base, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let owner_role = roles.iter().find(|r| r["name"] == "owner").unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let owner_assignment = format!("{}/{}/assign/{}", base, owner_role, tenant.admin.id);
+
+    // The owner is the only administrator: unassigning or demoting is refused.
+    let resp = app
+        .auth_delete(&owner_assignment, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = app
+        .auth_put(
+            &format!("{}/{}", base, owner_role),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "permissions": 1 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Once a custom administrator role is held by someone else it works.
+    let role: Value = app
+        .auth_post(&base, &tenant.admin.access_token)
+        .json(&serde_json::json!({ "name": "co-admin", "permissions": 1u64 << 23 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let role_id = role["id"].as_str().unwrap();
+    let resp = app
+        .auth_post(
+            &format!("{}/{}/assign/{}", base, role_id, tenant.member.id),
+            &tenant.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let resp = app
+        .auth_delete(&owner_assignment, &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    // The co-admin is now the last administrator; its role can't be deleted.
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", base, role_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
+
+#[tokio::test]
+async fn unknown_permission_bits_are_rejected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("role8").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/role", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "bogus", "permissions": 1u64 << 40 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
@@ -0,0 +1,212 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::{Value, json};
+
+const OPERATOR: &str = "admin@rtset1.test";
+
+async fn put_settings(app: &TestApp, token: &str, values: Value) -> reqwest::Response {
+    app.auth_put("/api/admin/settings", token)
+        .json(&json!({ "values": values }))
+        .send()
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn only_operators_see_runtime_settings() {
+    let app = TestApp::spawn_with_settings(|s| s.app.admin_emails = OPERATOR.to_string()).await;
+    let tenant = app.seed_tenant("rtset1").await;
+
+    let resp = app
+        .auth_get("/api/admin/settings", &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+    let resp = put_settings(
+        &app,
+        &tenant.member.access_token,
+        json!({ "turn.url": "x" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get("/api/admin/settings", &tenant.admin.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["values"]["rate_limit.enabled"], true);
+    assert!(body["values"]["turn.url"].is_null());
+    assert_eq!(body["overrides"], json!({}));
+}
+
+#[tokio::test]
+async fn overrides_apply_at_once_and_persist() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.app.admin_emails = format!("someone@else.test, {}", OPERATOR.to_uppercase());
+        s.turn.shared_secret = None;
+    })
+    .await;
+    let tenant = app.seed_tenant("rtset1").await;
+    let token = &tenant.admin.access_token;
+    let ice_url = format!(
+        "/api/tenant/{}/room/{}/ice",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+
+    let resp = put_settings(
+        &app,
+        token,
+        json!({
+            "turn.url": "turn:live.example.com:3478",
+            "turn.password": "hunter2",
+            "features.new_composer": true,
+        }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["values"]["turn.url"], "turn:live.example.com:3478");
+    assert_eq!(body["values"]["features.new_composer"], true);
+    assert_eq!(body["overrides"]["turn.password"], "********");
+
+    // The next request sees the new TURN server.
+    let ice: Value = app
+        .auth_get(&ice_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let urls = ice["ice_servers"][0]["urls"].to_string();
+    assert!(urls.contains("live.example.com"), "{urls}");
+    assert_eq!(ice["ice_servers"][0]["credential"], "hunter2");
+
+    // Stored for the other pods.
+    let stored = app
+        .db
+        .collection::<bson::Document>("settings_overrides")
+        .find_one(bson::doc! {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.get_array("values").unwrap().len(), 3);
+
+    // `null` drops an override; the config value applies again.
+    let resp = put_settings(&app, token, json!({ "turn.url": null })).await;
+    let body: Value = resp.json().await.unwrap();
+    assert!(body["values"]["turn.url"].is_null());
+    assert!(body["overrides"].get("turn.url").is_none());
+    let ice: Value = app
+        .auth_get(&ice_url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(ice["ice_servers"], json!([]));
+}
+
+#[tokio::test]
+async fn rate_limit_override_takes_effect() {
+    let app = TestApp::spawn_with_settings(|s| s.app.admin_emails = OPERATOR.to_string()).await;
+    let tenant = app.seed_tenant("rtset1").await;
+
+    let resp = put_settings(
+        &app,
+        &tenant.admin.access_token,
+        json!({ "rate_limit.auth_per_min": 1 }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let mut statuses = Vec::new();
+    for _ in 0..2 {
+        let resp = app
+            .client
+            .post(app.url("/api/auth/login"))
+            .json(&json!({ "username": "nobody", "password": "wrong" }))
+            .send()
+            .await
+            .unwrap();
+        statuses.push(resp.status().as_u16());
+    }
+    assert_eq!(statuses[1], 429, "{statuses:?}");
+}
+
+#[tokio::test]
+async fn invalid_overrides_are_rejected() {
+    let app = TestApp::spawn_with_settings(|s| s.app.admin_emails = OPERATOR.to_string()).await;
+    let tenant = app.seed_tenant("rtset1").await;
+    let token = &tenant.admin.access_token;
+
+    for values in [
+        json!({ "jwt.secret": "x" }),
+        json!({ "turn.shared_secret": "x" }),
+        json!({ "rate_limit.auth_per_min": "lots" }),
+        json!({ "features.beta": "on" }),
+        // One bad key rejects the whole update.
+        json!({ "turn.url": "turn:ok.example.com", "database.url": "x" }),
+    ] {
+        let resp = put_settings(&app, token, values.clone()).await;
+        assert_eq!(resp.status().as_u16(), 422, "{values}");
+    }
+    let body: Value = app
+        .auth_get("/api/admin/settings", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["overrides"], json!({}));
+}
+
+#[tokio::test]
+async fn overrides_stored_by_another_pod_survive_an_update() {
+    let app = TestApp::spawn_with_settings(|s| s.app.admin_emails = OPERATOR.to_string()).await;
+    let tenant = app.seed_tenant("rtset1").await;
+    let token = &tenant.admin.access_token;
+
+    let resp = put_settings(&app, token, json!({ "features.beta": true })).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    // Another pod stores an override this one hasn't reloaded yet.
+    app.db
+        .collection::<bson::Document>("settings_overrides")
+        .update_one(
+            bson::doc! {},
+            bson::doc! { "$push": { "values": { "key": "features.gamma", "value": true } } },
+        )
+        .await
+        .unwrap();
+
+    let resp = put_settings(&app, token, json!({ "features.delta": true })).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(
+        body["overrides"],
+        json!({ "features.beta": true, "features.gamma": true, "features.delta": true })
+    );
+    assert_eq!(body["values"]["features.gamma"], true);
+
+    // A refused update stores nothing.
+    let resp = put_settings(
+        &app,
+        token,
+        json!({ "features.beta": null, "jwt.secret": "x" }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 422);
+    let stored = app
+        .db
+        .collection::<bson::Document>("settings_overrides")
+        .find_one(bson::doc! {})
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.get_array("values").unwrap().len(), 3);
+}
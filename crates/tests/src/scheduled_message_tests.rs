@@ -0,0 +1,161 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+fn in_seconds(secs: i64) -> String {
+    (chrono::Utc::now() + chrono::Duration::seconds(secs)).to_rfc3339()
+}
+
+async fn total(app: &TestApp, path: &str, token: &str) -> u64 {
+    let json: Value = app
+        .auth_get(path, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    json["total"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn scheduled_message_is_delivered_at_send_at() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sched1").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_post(&base, token)
+        .json(&serde_json::json!({ "content": "later", "send_at": in_seconds(2) }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 202);
+    let scheduled: Value = resp.json().await.unwrap();
+    assert_eq!(scheduled["content"], "later");
+
+    let pending: Vec<Value> = app
+        .auth_get(&format!("{}/scheduled", base), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(total(&app, &base, token).await, 0);
+
+    let mut delivered = false;
+    for _ in 0..20 {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        if total(&app, &base, token).await == 1 {
+            delivered = true;
+            break;
+        }
+    }
+    assert!(delivered, "scheduled message was not delivered");
+
+    let pending: Vec<Value> = app
+        .auth_get(&format!("{}/scheduled", base), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(pending.is_empty());
+}
+
+#[tokio::test]
+async fn scheduled_message_can_be_cancelled() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sched2").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let token = &tenant.admin.access_token;
+
+    let scheduled: Value = app
+        .auth_post(&base, token)
+        .json(&serde_json::json!({ "content": "never", "send_at": in_seconds(3600) }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let path = format!("{}/scheduled/{}", base, scheduled["id"].as_str().unwrap());
+
+    // Only the author can cancel.
+    let resp = app
+        .auth_delete(&path, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let resp = app.auth_delete(&path, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app.auth_delete(&path, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let resp = app
+        .auth_post(&base, token)
+        .json(&serde_json::json!({ "content": "bad", "send_at": "tomorrow" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+#[tokio::test]
+async fn silent_messages_skip_notifications_and_unread() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sched3").await;
+    let room_id = &tenant.rooms[0].id;
+    let base = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+        &tenant.member.access_token,
+    )
+    .send()
+    .await
+    .unwrap();
+
+    let msg: Value = app
+        .auth_post(&base, &tenant.admin.access_token)
+        .json(&serde_json::json!({
+            "content": "fyi",
+            "silent": true,
+            "mentions": { "users": [tenant.member.id] },
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(msg["is_silent"], true);
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+
+    let notifications = total(&app, "/api/notification", &tenant.member.access_token).await;
+    assert_eq!(notifications, 0);
+    let unread: Value = app
+        .auth_get(
+            &format!("{}/unread-count", base),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(unread["count"], 0);
+    // Still visible in the room.
+    assert_eq!(total(&app, &base, &tenant.member.access_token).await, 1);
+}
@@ -0,0 +1,112 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+fn names(section: &Value) -> Vec<&str> {
+    section
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["room"]["name"].as_str().unwrap())
+        .collect()
+}
+
+async fn fetch_sidebar(app: &TestApp, tenant_id: &str, token: &str) -> Value {
+    let resp = app
+        .auth_get(&format!("/api/tenant/{}/sidebar", tenant_id), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn sidebar_groups_pins_favorites_and_custom_order() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("sidebar").await;
+    let tid = &tenant.tenant_id;
+    let (general, engineering, random) = (&tenant.rooms[0], &tenant.rooms[1], &tenant.rooms[2]);
+    let admin = &tenant.admin.access_token;
+
+    // One unread message for the admin in #general.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tid, general.id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, general.id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "content": "hi" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let set_flags = |room_id: &str, token: &str, body: Value| {
+        app.auth_put(
+            &format!("/api/tenant/{}/room/{}/sidebar", tid, room_id),
+            token,
+        )
+        .json(&body)
+        .send()
+    };
+    let resp = set_flags(&random.id, admin, serde_json::json!({ "is_pinned": true }))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = set_flags(
+        &engineering.id,
+        admin,
+        serde_json::json!({ "is_favorite": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    // Only rooms the caller joined can be pinned.
+    let resp = set_flags(
+        &engineering.id,
+        &tenant.member.access_token,
+        serde_json::json!({ "is_pinned": true }),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let sidebar = fetch_sidebar(&app, tid, admin).await;
+    assert_eq!(names(&sidebar["pinned"]), ["random"]);
+    assert_eq!(names(&sidebar["favorites"]), ["engineering"]);
+    assert_eq!(names(&sidebar["rooms"]), ["general"]);
+    assert_eq!(sidebar["rooms"][0]["unread_count"], 1);
+    assert_eq!(sidebar["pinned"][0]["is_pinned"], true);
+
+    // The custom order beats the default by-name order.
+    let resp = set_flags(&random.id, admin, serde_json::json!({ "is_pinned": false }))
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .auth_put(&format!("/api/tenant/{}/sidebar/order", tid), admin)
+        .json(&serde_json::json!({ "room_ids": [random.id, general.id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let sidebar = fetch_sidebar(&app, tid, admin).await;
+    assert_eq!(names(&sidebar["rooms"]), ["random", "general"]);
+    assert_eq!(sidebar["rooms"][0]["position"], 0);
+
+    let resp = app
+        .auth_put(&format!("/api/tenant/{}/sidebar/order", tid), admin)
+        .json(&serde_json::json!({ "room_ids": [random.id, random.id] }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
@@ -0,0 +1,178 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+use std::time::Duration;
+
+async fn post_message(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) -> String {
+    let msg: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "oops" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    msg["id"].as_str().unwrap().to_string()
+}
+
+async fn status(resp: reqwest::RequestBuilder) -> u16 {
+    resp.send().await.unwrap().status().as_u16()
+}
+
+/// Push a soft delete `days` into the past.
+async fn age_deletion(app: &TestApp, collection: &str, id: &str, days: i64) {
+    let at =
+        bson::DateTime::from_millis(bson::DateTime::now().timestamp_millis() - days * 86_400_000);
+    app.db
+        .collection::<bson::Document>(collection)
+        .update_one(
+            doc! { "_id": ObjectId::parse_str(id).unwrap() },
+            doc! { "$set": { "deleted_at": at } },
+        )
+        .await
+        .unwrap();
+}
+
+async fn count(app: &TestApp, collection: &str, filter: bson::Document) -> u64 {
+    app.db
+        .collection::<bson::Document>(collection)
+        .count_documents(filter)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn deleted_room_is_hidden_until_restored() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("softdel1").await;
+    let tid = &tenant.tenant_id;
+    let rid = &tenant.rooms[2].id;
+    let admin = &tenant.admin.access_token;
+    let room_url = format!("/api/tenant/{}/room/{}", tid, rid);
+    let restore_url = format!("{}/restore", room_url);
+
+    assert_eq!(status(app.auth_post(&restore_url, admin)).await, 409);
+    assert_eq!(status(app.auth_delete(&room_url, admin)).await, 200);
+    assert_eq!(status(app.auth_get(&room_url, admin)).await, 404);
+
+    assert_eq!(
+        status(app.auth_post(&restore_url, &tenant.member.access_token)).await,
+        403
+    );
+    let resp = app.auth_post(&restore_url, admin).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let room: Value = resp.json().await.unwrap();
+    assert_eq!(room["name"], tenant.rooms[2].name);
+    assert_eq!(status(app.auth_get(&room_url, admin)).await, 200);
+
+    let audit: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/audit?filter[action]=room.restore", tid),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(audit["items"].as_array().unwrap().len(), 1);
+
+    // Past the grace window the room stays deleted.
+    assert_eq!(status(app.auth_delete(&room_url, admin)).await, 200);
+    age_deletion(&app, "rooms", rid, 31).await;
+    assert_eq!(status(app.auth_post(&restore_url, admin)).await, 409);
+}
+
+#[tokio::test]
+async fn message_restore_is_for_authors_and_moderators() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("softdel2").await;
+    let tid = &tenant.tenant_id;
+    let rid = &tenant.rooms[0].id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    assert_eq!(
+        status(app.auth_post(&format!("/api/tenant/{}/room/{}/join", tid, rid), member)).await,
+        200
+    );
+
+    let mid = post_message(&app, tid, rid, member).await;
+    let message_url = format!("/api/tenant/{}/room/{}/message/{}", tid, rid, mid);
+    let restore_url = format!("{}/restore", message_url);
+
+    // The author undoes their own delete.
+    assert_eq!(status(app.auth_delete(&message_url, member)).await, 200);
+    let resp = app.auth_post(&restore_url, member).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let message: Value = resp.json().await.unwrap();
+    assert_eq!(message["content"], "oops");
+    assert!(message["deleted_at"].is_null());
+    assert_eq!(status(app.auth_post(&restore_url, member)).await, 409);
+
+    // A moderator's delete is only undone by a moderator.
+    assert_eq!(status(app.auth_delete(&message_url, admin)).await, 200);
+    assert_eq!(status(app.auth_post(&restore_url, member)).await, 403);
+    assert_eq!(status(app.auth_post(&restore_url, admin)).await, 200);
+
+    assert_eq!(status(app.auth_delete(&message_url, member)).await, 200);
+    age_deletion(&app, "messages", &mid, 31).await;
+    assert_eq!(status(app.auth_post(&restore_url, member)).await, 409);
+}
+
+#[tokio::test]
+async fn reaper_purges_deletions_past_the_grace_window() {
+    let app = TestApp::spawn_with_settings(|s| s.retention.sweep_interval_secs = 1).await;
+    let tenant = app.seed_tenant("softdel3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let (rid, doomed_room) = (&tenant.rooms[0].id, &tenant.rooms[2].id);
+
+    let old = post_message(&app, tid, rid, admin).await;
+    let recent = post_message(&app, tid, rid, admin).await;
+    let in_room = post_message(&app, tid, doomed_room, admin).await;
+    let reaction_url = format!("/api/tenant/{}/room/{}/message/{}/reaction", tid, rid, old);
+    let resp = app
+        .auth_post(&reaction_url, admin)
+        .json(&serde_json::json!({ "emoji": "\u{1f44d}" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    for mid in [&old, &recent] {
+        let url = format!("/api/tenant/{}/room/{}/message/{}", tid, rid, mid);
+        assert_eq!(status(app.auth_delete(&url, admin)).await, 200);
+    }
+    let url = format!("/api/tenant/{}/room/{}", tid, doomed_room);
+    assert_eq!(status(app.auth_delete(&url, admin)).await, 200);
+    age_deletion(&app, "messages", &old, 31).await;
+    age_deletion(&app, "rooms", doomed_room, 31).await;
+
+    let old_oid = ObjectId::parse_str(&old).unwrap();
+    let room_oid = ObjectId::parse_str(doomed_room).unwrap();
+    for _ in 0..50 {
+        if count(&app, "messages", doc! { "_id": old_oid }).await == 0
+            && count(&app, "rooms", doc! { "_id": room_oid }).await == 0
+        {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(count(&app, "messages", doc! { "_id": old_oid }).await, 0);
+    assert_eq!(count(&app, "rooms", doc! { "_id": room_oid }).await, 0);
+    assert_eq!(
+        count(&app, "reactions", doc! { "message_id": old_oid }).await,
+        0
+    );
+    let in_room = ObjectId::parse_str(&in_room).unwrap();
+    assert_eq!(count(&app, "messages", doc! { "_id": in_room }).await, 0);
+
+    // Still inside its window.
+    let recent = ObjectId::parse_str(&recent).unwrap();
+    assert_eq!(count(&app, "messages", doc! { "_id": recent }).await, 1);
+}
@@ -0,0 +1,275 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::Ws;
+use futures::StreamExt;
+use reqwest::multipart;
+use roomler_ai_config::StorageQuotaMode;
+use serde_json::{Value, json};
+
+/// Upload `bytes` as a file of the room.
+async fn upload(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    token: &str,
+    bytes: &[u8],
+) -> reqwest::Response {
+    let part = multipart::Part::bytes(bytes.to_vec())
+        .file_name("quota.txt")
+        .mime_str("text/plain")
+        .unwrap();
+    let form = multipart::Form::new()
+        .part("file", part)
+        .text("room_id", room_id.to_string());
+    app.client
+        .post(app.url(&format!("/api/tenant/{}/file/upload", tenant_id)))
+        .header("Authorization", format!("Bearer {}", token))
+        .multipart(form)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn join_room(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+async fn storage(app: &TestApp, tenant_id: &str, token: &str) -> Value {
+    let tenant: Value = app
+        .auth_get(&format!("/api/tenant/{}", tenant_id), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    tenant["storage"].clone()
+}
+
+#[tokio::test]
+async fn uploads_past_the_quota_are_refused_and_deletes_free_space() {
+    let app = TestApp::spawn_with_settings(|s| s.storage.default_quota_bytes = 20).await;
+    let tenant = app.seed_tenant("squota1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    join_room(&app, tid, room_id, token).await;
+
+    let resp = upload(&app, tid, room_id, token, b"Hello, World!").await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let file: Value = resp.json().await.unwrap();
+    assert_eq!(
+        storage(&app, tid, token).await,
+        json!({ "used_bytes": 13, "quota_bytes": 20 })
+    );
+
+    let resp = upload(&app, tid, room_id, token, b"Hello, again!").await;
+    assert_eq!(resp.status().as_u16(), 507);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body["error"], "storage_quota");
+    assert_eq!(
+        body["details"],
+        json!({ "used_bytes": 13, "quota_bytes": 20, "adding_bytes": 13 })
+    );
+
+    let resp = app
+        .auth_delete(
+            &format!("/api/tenant/{}/file/{}", tid, file["id"].as_str().unwrap()),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(storage(&app, tid, token).await["used_bytes"], 0);
+
+    let resp = upload(&app, tid, room_id, token, b"Hello, again!").await;
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn concurrent_uploads_cannot_together_pass_the_quota() {
+    let app = TestApp::spawn_with_settings(|s| s.storage.default_quota_bytes = 20).await;
+    let tenant = app.seed_tenant("squota6").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    join_room(&app, tid, room_id, token).await;
+
+    let statuses: Vec<u16> = futures::future::join_all(
+        (0..5).map(|_| upload(&app, tid, room_id, token, b"Hello, World!")),
+    )
+    .await
+    .into_iter()
+    .map(|resp| resp.status().as_u16())
+    .collect();
+    assert_eq!(
+        statuses.iter().filter(|s| **s == 200).count(),
+        1,
+        "{statuses:?}"
+    );
+    assert_eq!(
+        statuses.iter().filter(|s| **s == 507).count(),
+        4,
+        "{statuses:?}"
+    );
+    assert_eq!(
+        storage(&app, tid, token).await,
+        json!({ "used_bytes": 13, "quota_bytes": 20 })
+    );
+}
+
+#[tokio::test]
+async fn recordings_count_towards_the_quota() {
+    let app = TestApp::spawn_with_settings(|s| s.storage.default_quota_bytes = 20).await;
+    let tenant = app.seed_tenant("squota4").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    join_room(&app, tid, room_id, token).await;
+
+    let base = format!("/api/tenant/{}/room/{}/recording", tid, room_id);
+    let rec: Value = app
+        .auth_post(&base, token)
+        .json(&json!({ "recording_type": "audio" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let rec_url = format!("{}/{}", base, rec["id"].as_str().unwrap());
+
+    let resp = app
+        .auth_put(&format!("{}/file?duration_ms=1000", rec_url), token)
+        .body(b"Hello, World!".to_vec())
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(storage(&app, tid, token).await["used_bytes"], 13);
+
+    // A file no longer fits next to the recording.
+    let resp = upload(&app, tid, room_id, token, b"Hello, again!").await;
+    assert_eq!(resp.status().as_u16(), 507);
+
+    let resp = app.auth_delete(&rec_url, token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(storage(&app, tid, token).await["used_bytes"], 0);
+}
+
+#[tokio::test]
+async fn warn_mode_stores_the_upload_and_notifies() {
+    let app = TestApp::spawn_with_settings(|s| {
+        s.storage.default_quota_bytes = 10;
+        s.storage.quota_mode = StorageQuotaMode::Warn;
+    })
+    .await;
+    let tenant = app.seed_tenant("squota2").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    join_room(&app, tid, room_id, token).await;
+
+    let (mut ws, _) =
+        tokio_tungstenite::connect_async(format!("ws://{}/ws?token={}", app.addr, token))
+            .await
+            .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+
+    let resp = upload(&app, tid, room_id, token, b"Hello, World!").await;
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let warning = next_warning(&mut ws).await;
+    assert_eq!(warning["tenant_id"], tid.as_str());
+    assert_eq!(warning["used_bytes"], 0);
+    assert_eq!(warning["quota_bytes"], 10);
+    assert_eq!(warning["adding_bytes"], 13);
+
+    let usage: Value = app
+        .auth_get(&format!("/api/tenant/{}/usage", tid), token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        usage["storage"],
+        json!({ "used_bytes": 13, "quota_bytes": 10 })
+    );
+}
+
+/// Read until a `storage:quota_warning` event arrives.
+async fn next_warning(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            let Ok(text) = msg.to_text() else { continue };
+            let Ok(parsed) = serde_json::from_str::<Value>(text) else {
+                continue;
+            };
+            if parsed["type"] == "storage:quota_warning" {
+                return parsed["data"].clone();
+            }
+        }
+    })
+    .await
+    .expect("no storage:quota_warning event")
+}
+
+#[tokio::test]
+async fn operators_set_a_tenants_quota() {
+    let app =
+        TestApp::spawn_with_settings(|s| s.app.admin_emails = "admin@squota3.test".to_string())
+            .await;
+    let tenant = app.seed_tenant("squota3").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let token = &tenant.admin.access_token;
+    let quota_url = format!("/api/admin/tenant/{}/storage-quota", tid);
+    join_room(&app, tid, room_id, token).await;
+    assert_eq!(
+        storage(&app, tid, token).await,
+        json!({ "used_bytes": 0, "quota_bytes": null })
+    );
+
+    let resp = app
+        .auth_put(&quota_url, &tenant.member.access_token)
+        .json(&json!({ "quota_bytes": 5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_put(&quota_url, token)
+        .json(&json!({ "quota_bytes": 5 }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let body: Value = resp.json().await.unwrap();
+    assert_eq!(body, json!({ "used_bytes": 0, "quota_bytes": 5 }));
+    let resp = upload(&app, tid, room_id, token, b"Hello, World!").await;
+    assert_eq!(resp.status().as_u16(), 507);
+
+    // 0 lifts the quota.
+    app.auth_put(&quota_url, token)
+        .json(&json!({ "quota_bytes": 0 }))
+        .send()
+        .await
+        .unwrap();
+    let resp = upload(&app, tid, room_id, token, b"Hello, World!").await;
+    assert_eq!(resp.status().as_u16(), 200);
+    assert_eq!(
+        storage(&app, tid, token).await,
+        json!({ "used_bytes": 13, "quota_bytes": null })
+    );
+}
@@ -0,0 +1,191 @@
+use crate::fixtures::test_app::TestApp;
+use serde_json::Value;
+
+async fn post(app: &TestApp, base: &str, token: &str, body: Value) -> Value {
+    let resp = app.auth_post(base, token).json(&body).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+async fn find_message(app: &TestApp, base: &str, token: &str, id: &str) -> Value {
+    let list: Value = app
+        .auth_get(base, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    list["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|m| m["id"] == id)
+        .cloned()
+        .expect("message listed")
+}
+
+async fn thread_notifications(app: &TestApp, token: &str) -> usize {
+    let json: Value = app
+        .auth_get("/api/notification", token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    json["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|n| n["notification_type"] == "threadreply")
+        .count()
+}
+
+#[tokio::test]
+async fn reply_count_tracks_create_and_delete() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("thread1").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let token = &tenant.admin.access_token;
+
+    let root = post(&app, &base, token, serde_json::json!({ "content": "root" })).await;
+    let root_id = root["id"].as_str().unwrap();
+    let first = post(
+        &app,
+        &base,
+        token,
+        serde_json::json!({ "content": "one", "thread_id": root_id }),
+    )
+    .await;
+    let second = post(
+        &app,
+        &base,
+        token,
+        serde_json::json!({ "content": "two", "thread_id": root_id }),
+    )
+    .await;
+
+    let msg = find_message(&app, &base, token, root_id).await;
+    assert_eq!(msg["is_thread_root"], true);
+    assert_eq!(msg["reply_count"], 2);
+    assert_eq!(msg["last_reply_at"], second["created_at"]);
+    // The root author follows automatically.
+    assert_eq!(msg["is_following"], true);
+
+    let resp = app
+        .auth_delete(
+            &format!("{}/{}", base, second["id"].as_str().unwrap()),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    let msg = find_message(&app, &base, token, root_id).await;
+    assert_eq!(msg["reply_count"], 1);
+    assert_eq!(msg["last_reply_at"], first["created_at"]);
+}
+
+#[tokio::test]
+async fn followers_are_notified_of_replies() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("thread2").await;
+    let room_id = &tenant.rooms[0].id;
+    let base = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room_id);
+    for token in [&tenant.admin.access_token, &tenant.member.access_token] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/join", tenant.tenant_id, room_id),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let root = post(
+        &app,
+        &base,
+        &tenant.admin.access_token,
+        serde_json::json!({ "content": "root" }),
+    )
+    .await;
+    let root_id = root["id"].as_str().unwrap();
+    let follow = format!("{}/{}/thread/follow", base, root_id);
+    let reply = serde_json::json!({ "content": "reply", "thread_id": root_id });
+
+    let resp = app
+        .auth_put(&follow, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    post(&app, &base, &tenant.admin.access_token, reply.clone()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(
+        thread_notifications(&app, &tenant.member.access_token).await,
+        1
+    );
+    // The replier isn't notified of their own reply.
+    assert_eq!(
+        thread_notifications(&app, &tenant.admin.access_token).await,
+        0
+    );
+
+    let resp = app
+        .auth_delete(&follow, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    post(&app, &base, &tenant.admin.access_token, reply.clone()).await;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(
+        thread_notifications(&app, &tenant.member.access_token).await,
+        1
+    );
+
+    // Replying re-follows; the root author hears about it.
+    post(&app, &base, &tenant.member.access_token, reply).await;
+    tokio::time::sleep(std::time::Duration::from_millis(300)).await;
+    assert_eq!(
+        thread_notifications(&app, &tenant.admin.access_token).await,
+        1
+    );
+}
+
+#[tokio::test]
+async fn only_thread_roots_can_be_followed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("thread3").await;
+    let base = format!(
+        "/api/tenant/{}/room/{}/message",
+        tenant.tenant_id, tenant.rooms[0].id
+    );
+    let token = &tenant.admin.access_token;
+
+    let root = post(&app, &base, token, serde_json::json!({ "content": "root" })).await;
+    let reply = post(
+        &app,
+        &base,
+        token,
+        serde_json::json!({ "content": "r", "thread_id": root["id"] }),
+    )
+    .await;
+
+    let resp = app
+        .auth_put(
+            &format!("{}/{}/thread/follow", base, reply["id"].as_str().unwrap()),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
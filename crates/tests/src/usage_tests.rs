@@ -0,0 +1,131 @@
+use crate::fixtures::test_app::TestApp;
+use bson::{doc, oid::ObjectId};
+use serde_json::Value;
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, action: &str, token: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+#[tokio::test]
+async fn usage_report_requires_manage_tenant_and_valid_range() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("usage1").await;
+    let url = format!("/api/tenant/{}/usage", tenant.tenant_id);
+    let token = &tenant.admin.access_token;
+
+    let resp = app
+        .auth_get(&url, &tenant.member.access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(&format!("{}?from=yesterday", url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 400);
+
+    let resp = app
+        .auth_get(&format!("{}?from=2026-03-02&to=2026-03-01", url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let resp = app
+        .auth_get(&format!("{}?from=2024-01-01&to=2026-01-01", url), token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let body: Value = app
+        .auth_get(&url, token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert!(body["days"].as_array().unwrap().is_empty());
+    assert_eq!(body["totals"]["call_minutes"], 0);
+}
+
+#[tokio::test]
+async fn call_minutes_are_booked_per_participant_per_day() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("usage2").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    let (admin, member) = (&tenant.admin.access_token, &tenant.member.access_token);
+
+    call_action(&app, tid, room_id, "start", admin).await;
+    call_action(&app, tid, room_id, "join", admin).await;
+    call_action(&app, tid, room_id, "join", member).await;
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    // The member leaves; ending the call closes the admin's entry.
+    call_action(&app, tid, room_id, "leave", member).await;
+    call_action(&app, tid, room_id, "end", admin).await;
+
+    let tenant_oid = ObjectId::parse_str(tid).unwrap();
+    let today = app
+        .db
+        .collection::<bson::Document>("usage_days")
+        .find_one(doc! { "tenant_id": tenant_oid })
+        .await
+        .unwrap()
+        .expect("no usage booked");
+    let call_secs = today.get_i64("call_secs").unwrap();
+    assert!((2..60).contains(&call_secs), "call_secs = {}", call_secs);
+
+    // An earlier day, already reported.
+    let earlier = (chrono::Utc::now().date_naive() - chrono::Duration::days(30)).to_string();
+    let now = bson::DateTime::now();
+    app.db
+        .collection::<bson::Document>("usage_days")
+        .insert_one(doc! {
+            "tenant_id": tenant_oid,
+            "date": &earlier,
+            "call_secs": 125_i64,
+            "storage_bytes": 4096_i64,
+            "reported_at": now,
+            "created_at": now,
+            "updated_at": now,
+        })
+        .await
+        .unwrap();
+
+    let date = today.get_str("date").unwrap();
+    let body: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/usage?from={}&to={}", tid, earlier, date),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let days = body["days"].as_array().unwrap();
+    assert_eq!(days.len(), 2);
+    assert_eq!(days[0]["date"], earlier.as_str());
+    assert_eq!(days[0]["call_minutes"], 3);
+    assert_eq!(days[0]["reported"], true);
+    assert_eq!(days[1]["date"], date);
+    assert_eq!(days[1]["call_minutes"], 1);
+    assert_eq!(days[1]["reported"], false);
+    // 125 s plus a few seconds, rounded up once.
+    assert_eq!(body["totals"]["call_minutes"], 3);
+    assert_eq!(body["totals"]["peak_storage_bytes"], 4096);
+}
@@ -0,0 +1,472 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use axum::{
+    Json, Router,
+    http::{HeaderMap, StatusCode},
+    routing::post,
+};
+use hmac::{Hmac, Mac};
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+
+use crate::fixtures::test_app::TestApp;
+
+/// A request received by [`spawn_receiver`].
+struct Received {
+    headers: HeaderMap,
+    body: String,
+}
+
+/// Local stand-in for an integration endpoint. Records every POST and
+/// answers with `reply`.
+async fn spawn_receiver(reply: Value) -> (String, mpsc::UnboundedReceiver<Received>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let app = Router::new().route(
+        "/",
+        post(move |headers: HeaderMap, body: String| {
+            let tx = tx.clone();
+            let reply = reply.clone();
+            async move {
+                let _ = tx.send(Received { headers, body });
+                Json(reply)
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/", addr), rx)
+}
+
+fn verify_signature(secret: &str, received: &Received) {
+    let timestamp = received.headers["x-roomler-timestamp"].to_str().unwrap();
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(format!("{}.{}", timestamp, received.body).as_bytes());
+    let expected = format!("sha256={}", hex::encode(mac.finalize().into_bytes()));
+    assert_eq!(received.headers["x-roomler-signature"], expected.as_str());
+}
+
+#[tokio::test]
+async fn outgoing_webhook_receives_signed_matching_messages() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("hook1").await;
+    let room = &tenant.rooms[0];
+    let (url, mut rx) = spawn_receiver(serde_json::json!({})).await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/webhook", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "deploy-bot",
+            "kind": "outgoing",
+            "room_ids": [room.id],
+            "keywords": ["Deploy"],
+            "url": url,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let hook: Value = resp.json().await.unwrap();
+    let secret = hook["secret"].as_str().unwrap();
+
+    let msg_path = format!("/api/tenant/{}/room/{}/message", tenant.tenant_id, room.id);
+    for content in ["just chatting", "please deploy now"] {
+        let resp = app
+            .auth_post(&msg_path, &tenant.member.access_token)
+            .json(&serde_json::json!({ "content": content }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    // Only the keyword match is delivered.
+    let received = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("webhook delivered")
+        .unwrap();
+    assert_eq!(received.headers["x-roomler-event"], "message.create");
+    verify_signature(secret, &received);
+    let event: Value = serde_json::from_str(&received.body).unwrap();
+    assert_eq!(event["event"], "message.create");
+    assert_eq!(event["message"]["content"], "please deploy now");
+    assert_eq!(event["message"]["author_id"], tenant.member.id.as_str());
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn incoming_webhook_posts_as_webhook_author() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("hook2").await;
+    let room = &tenant.rooms[0];
+
+    let hook: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/webhook", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "CI",
+            "kind": "incoming",
+            "room_ids": [room.id],
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let hook_path = hook["hook_path"].as_str().unwrap();
+
+    let resp = app
+        .client
+        .post(app.url(hook_path))
+        .json(&serde_json::json!({ "content": "build #42 passed", "username": "Jenkins" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let msg: Value = resp.json().await.unwrap();
+    assert_eq!(msg["author_type"], "webhook");
+    assert_eq!(msg["author_name"], "Jenkins");
+    assert_eq!(msg["author_id"], hook["id"]);
+
+    // A wrong token is indistinguishable from an unknown hook.
+    let resp = app
+        .client
+        .post(app.url(&format!("/api/hook/{}/wrong", hook["id"].as_str().unwrap())))
+        .json(&serde_json::json!({ "content": "nope" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    // Disabled hooks stop accepting posts.
+    let resp = app
+        .auth_put(
+            &format!(
+                "/api/tenant/{}/webhook/{}",
+                tenant.tenant_id,
+                hook["id"].as_str().unwrap()
+            ),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "is_active": false }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let resp = app
+        .client
+        .post(app.url(hook_path))
+        .json(&serde_json::json!({ "content": "after disable" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn slash_command_in_channel_reply_is_posted() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("hook3").await;
+    let room = &tenant.rooms[0];
+    let (url, mut rx) = spawn_receiver(serde_json::json!({
+        "text": "Berlin: 18°C",
+        "response_type": "in_channel",
+    }))
+    .await;
+
+    let cmd: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/command", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "command": "/Weather", "url": url }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(cmd["command"], "weather");
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/command", tenant.tenant_id, room.id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "text": "/weather berlin" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let result: Value = resp.json().await.unwrap();
+    assert_eq!(result["response_type"], "in_channel");
+    assert_eq!(result["message"]["author_type"], "bot");
+    assert_eq!(result["message"]["author_name"], "/weather");
+    assert_eq!(result["message"]["content"], "Berlin: 18°C");
+
+    let received = rx.recv().await.unwrap();
+    verify_signature(cmd["secret"].as_str().unwrap(), &received);
+    let invocation: Value = serde_json::from_str(&received.body).unwrap();
+    assert_eq!(invocation["command"], "/weather");
+    assert_eq!(invocation["text"], "berlin");
+    assert_eq!(invocation["user_id"], tenant.member.id.as_str());
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/command", tenant.tenant_id, room.id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({ "text": "/unknown" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+}
+
+#[tokio::test]
+async fn integrations_require_manage_tenant() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("hook4").await;
+
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/webhook", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "sneaky",
+            "kind": "outgoing",
+            "url": "http://example.com/",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = app
+        .auth_get(
+            &format!("/api/tenant/{}/command", tenant.tenant_id),
+            &tenant.member.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+
+    // Outgoing hooks need an http(s) URL.
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/webhook", tenant.tenant_id),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({
+            "name": "bad",
+            "kind": "outgoing",
+            "url": "file:///etc/passwd",
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+}
+
+/// Like [`spawn_receiver`], but answers the first POST with `500` to
+/// exercise retries.
+async fn spawn_flaky_receiver() -> (String, mpsc::UnboundedReceiver<(u16, Received)>) {
+    let (tx, rx) = mpsc::unbounded_channel();
+    let failed = Arc::new(AtomicBool::new(false));
+    let app = Router::new().route(
+        "/",
+        post(move |headers: HeaderMap, body: String| {
+            let tx = tx.clone();
+            let failed = failed.clone();
+            async move {
+                let status = if failed.swap(true, Ordering::SeqCst) {
+                    StatusCode::OK
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+                let _ = tx.send((status.as_u16(), Received { headers, body }));
+                status
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    (format!("http://{}/", addr), rx)
+}
+
+#[tokio::test]
+async fn outgoing_webhook_receives_call_events_with_retry() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("hook5").await;
+    let tid = tenant.tenant_id.as_str();
+    let token = &tenant.admin.access_token;
+    let room = &tenant.rooms[0];
+    let (url, mut rx) = spawn_flaky_receiver().await;
+
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/webhook", tid), token)
+        .json(&serde_json::json!({
+            "name": "crm",
+            "kind": "outgoing",
+            "events": ["call.started", "shout"],
+            "url": url,
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    let hook: Value = app
+        .auth_post(&format!("/api/tenant/{}/webhook", tid), token)
+        .json(&serde_json::json!({
+            "name": "crm",
+            "kind": "outgoing",
+            "events": ["call.started", "call.ended", "call.started"],
+            "url": url,
+        }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(
+        hook["events"],
+        serde_json::json!(["call.started", "call.ended"])
+    );
+    let secret = hook["secret"].as_str().unwrap();
+
+    // Not subscribed to messages
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, room.id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "standup in 5" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+
+    for action in ["start", "end"] {
+        let resp = app
+            .auth_post(
+                &format!("/api/tenant/{}/room/{}/call/{}", tid, room.id, action),
+                token,
+            )
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 200);
+    }
+
+    // The first delivery fails and is retried; call.ended isn't held up
+    let mut delivered = Vec::new();
+    let mut failed = None;
+    for _ in 0..3 {
+        let (status, r) = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("webhook delivered")
+            .unwrap();
+        verify_signature(secret, &r);
+        let event: Value = serde_json::from_str(&r.body).unwrap();
+        assert_eq!(event["room_id"], room.id.as_str());
+        if status == 500 {
+            failed = Some(event);
+        } else {
+            delivered.push(event["event"].as_str().unwrap().to_string());
+        }
+    }
+    let failed = failed.expect("first delivery failed");
+    assert_eq!(failed["event"], "call.started");
+    assert_eq!(failed["started_by"], tenant.admin.id.as_str());
+    delivered.sort();
+    assert_eq!(delivered, ["call.ended", "call.started"]);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(500), rx.recv())
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn integration_urls_into_private_networks_are_refused() {
+    let app = TestApp::spawn_with_settings(|s| s.integrations.allow_private_networks = false).await;
+    let tenant = app.seed_tenant("hook6").await;
+    let tid = &tenant.tenant_id;
+    let room = &tenant.rooms[0];
+    let token = &tenant.admin.access_token;
+    let (url, mut rx) = spawn_receiver(serde_json::json!({})).await;
+
+    for url in [
+        url.as_str(),
+        "http://169.254.169.254/latest/meta-data/",
+        "http://[::1]:8080/",
+    ] {
+        let resp = app
+            .auth_post(&format!("/api/tenant/{}/webhook", tid), token)
+            .json(&serde_json::json!({
+                "name": "inward",
+                "kind": "outgoing",
+                "room_ids": [room.id],
+                "url": url,
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(resp.status().as_u16(), 422, "{url}");
+    }
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/command", tid), token)
+        .json(&serde_json::json!({ "command": "/peek", "url": url }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 422);
+
+    // A host name is checked when it's resolved: localhost gets nothing
+    let resp = app
+        .auth_post(&format!("/api/tenant/{}/webhook", tid), token)
+        .json(&serde_json::json!({
+            "name": "by-name",
+            "kind": "outgoing",
+            "room_ids": [room.id],
+            "url": url.replace("127.0.0.1", "localhost"),
+        }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 201);
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tid, room.id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": "anyone there?" }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    assert!(
+        tokio::time::timeout(Duration::from_secs(2), rx.recv())
+            .await
+            .is_err()
+    );
+}
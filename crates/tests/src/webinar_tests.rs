@@ -0,0 +1,179 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, body: Value) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+async fn set_speaker(
+    app: &TestApp,
+    tenant_id: &str,
+    room_id: &str,
+    user_id: &str,
+    token: &str,
+    speaker: bool,
+) -> reqwest::Response {
+    let path = format!(
+        "/api/tenant/{}/room/{}/call/speaker/{}",
+        tenant_id, room_id, user_id
+    );
+    let req = if speaker {
+        app.auth_put(&path, token)
+    } else {
+        app.auth_delete(&path, token)
+    };
+    req.send().await.unwrap()
+}
+
+#[tokio::test]
+async fn attendees_cannot_produce_until_promoted() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("webinar1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(
+        &app,
+        tid,
+        admin,
+        serde_json::json!({ "name": "Webinar", "media_settings": { "webinar": true } }),
+    )
+    .await;
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+    let mut ws = connect(&app, member).await;
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let webinar = next_of(&mut ws, "media:webinar_state").await;
+    assert_eq!(webinar["speaker"], false);
+    assert_eq!(webinar["speakers"][0], tenant.admin.id.as_str());
+    assert_eq!(webinar["attendee_count"], 1);
+
+    // The server refuses an attendee's producer, whatever the client sends.
+    send(
+        &mut ws,
+        "media:produce",
+        serde_json::json!({
+            "room_id": room_id,
+            "kind": "audio",
+            "rtp_parameters": {
+                "mid": "0",
+                "codecs": [{
+                    "mimeType": "audio/opus",
+                    "clockRate": 48000,
+                    "channels": 2,
+                    "payloadType": 111,
+                    "parameters": {},
+                    "rtcpFeedback": [],
+                }],
+                "headerExtensions": [],
+                "encodings": [{ "ssrc": 4242 }],
+                "rtcp": { "cname": "attendee" },
+            },
+        }),
+    )
+    .await;
+    let error = next_of(&mut ws, "media:error").await;
+    assert!(
+        error["message"].as_str().unwrap().contains("Only speakers"),
+        "got {}",
+        error["message"]
+    );
+
+    // Members can't promote themselves.
+    let resp = set_speaker(&app, tid, &room_id, &tenant.member.id, member, true).await;
+    assert_eq!(resp.status().as_u16(), 403);
+
+    let resp = set_speaker(&app, tid, &room_id, &tenant.member.id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let update = next_of(&mut ws, "media:speaker_update").await;
+    assert_eq!(update["user_id"], tenant.member.id.as_str());
+    assert_eq!(update["speaker"], true);
+    assert_eq!(update["speaker_count"], 1);
+    assert_eq!(update["attendee_count"], 0);
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/call/webinar", tid, room_id),
+            member,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["enabled"], true);
+    assert_eq!(json["speakers"].as_array().unwrap().len(), 2);
+
+    let resp = set_speaker(&app, tid, &room_id, &tenant.member.id, admin, false).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let update = next_of(&mut ws, "media:speaker_update").await;
+    assert_eq!(update["speaker"], false);
+    assert_eq!(update["attendee_count"], 1);
+}
+
+#[tokio::test]
+async fn speakers_only_exist_in_webinars() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("webinar2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, serde_json::json!({ "name": "Standup" })).await;
+
+    let resp = set_speaker(&app, tid, &room_id, &tenant.member.id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 409, "no call yet");
+
+    call_action(&app, tid, &room_id, admin, "start").await;
+    let resp = set_speaker(&app, tid, &room_id, &tenant.member.id, admin, true).await;
+    assert_eq!(resp.status().as_u16(), 409, "not a webinar");
+
+    let json: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/call/webinar", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["enabled"], false);
+}
@@ -0,0 +1,265 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+/// Create a room and return its id.
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+async fn call_action(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, action: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tenant_id, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200, "call/{} failed", action);
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+fn add_rect(room_id: &str, id: &str) -> Value {
+    serde_json::json!({
+        "room_id": room_id,
+        "client_op_id": format!("c-{}", id),
+        "op": {
+            "type": "add",
+            "element": { "id": id, "kind": "rect", "x": 0, "y": 0, "width": 40, "height": 20 },
+        },
+    })
+}
+
+#[tokio::test]
+async fn whiteboard_ops_are_sequenced_and_relayed() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wb1").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let member = &tenant.member.access_token;
+    let room_id = create_room(&app, tid, admin, "Board").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+    call_action(&app, tid, &room_id, member, "join").await;
+
+    let mut drawer = connect(&app, admin).await;
+    let mut viewer = connect(&app, member).await;
+
+    send(&mut drawer, "whiteboard:op", add_rect(&room_id, "a")).await;
+    send(&mut drawer, "whiteboard:op", add_rect(&room_id, "b")).await;
+
+    // The sender gets its own op back as the acknowledgement.
+    let ack = next_of(&mut drawer, "whiteboard:op").await;
+    assert_eq!(ack["seq"], 1);
+    assert_eq!(ack["client_op_id"], "c-a");
+
+    let first = next_of(&mut viewer, "whiteboard:op").await;
+    let second = next_of(&mut viewer, "whiteboard:op").await;
+    assert_eq!(first["seq"], 1);
+    assert_eq!(first["user_id"], tenant.admin.id.as_str());
+    assert_eq!(first["op"]["element"]["id"], "a");
+    assert_eq!(second["seq"], 2);
+
+    // A late joiner catches up with a snapshot.
+    send(
+        &mut viewer,
+        "whiteboard:sync",
+        serde_json::json!({ "room_id": room_id }),
+    )
+    .await;
+    let snapshot = next_of(&mut viewer, "whiteboard:snapshot").await;
+    assert_eq!(snapshot["seq"], 2);
+    assert_eq!(snapshot["elements"].as_array().unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn whiteboard_rejects_invalid_ops() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wb2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Strict").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+
+    let mut ws = connect(&app, admin).await;
+    send(
+        &mut ws,
+        "whiteboard:op",
+        serde_json::json!({
+            "room_id": room_id,
+            "client_op_id": "x1",
+            "op": { "type": "delete", "element_id": "missing" },
+        }),
+    )
+    .await;
+    let error = next_of(&mut ws, "whiteboard:error").await;
+    assert_eq!(error["client_op_id"], "x1");
+
+    send(
+        &mut ws,
+        "whiteboard:op",
+        serde_json::json!({ "room_id": room_id, "op": { "type": "scribble" } }),
+    )
+    .await;
+    next_of(&mut ws, "whiteboard:error").await;
+
+    // Neither op took a sequence number.
+    send(&mut ws, "whiteboard:op", add_rect(&room_id, "a")).await;
+    let ack = next_of(&mut ws, "whiteboard:op").await;
+    assert_eq!(ack["seq"], 1);
+}
+
+#[tokio::test]
+async fn whiteboard_export_attaches_an_image_to_the_room() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wb3").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Export").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+
+    let url = format!("/api/tenant/{}/room/{}/whiteboard", tid, room_id);
+
+    // Nothing drawn yet
+    let resp = app
+        .auth_post(&format!("{}/export", url), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 404);
+
+    let mut ws = connect(&app, admin).await;
+    send(&mut ws, "whiteboard:op", add_rect(&room_id, "a")).await;
+    next_of(&mut ws, "whiteboard:op").await;
+
+    // GET includes ops not yet snapshotted.
+    let board: Value = app
+        .auth_get(&url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(board["seq"], 1);
+    assert_eq!(board["elements"][0]["id"], "a");
+
+    let resp = app
+        .auth_post(&format!("{}/export", url), admin)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    let file: Value = resp.json().await.unwrap();
+    assert_eq!(file["content_type"], "image/svg+xml");
+
+    let svg = app
+        .auth_get(file["url"].as_str().unwrap(), admin)
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(svg.starts_with("<svg"));
+    assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"40\" height=\"20\""));
+
+    let board: Value = app
+        .auth_get(&url, admin)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(board["export_file_id"], file["id"]);
+}
+
+#[tokio::test]
+async fn whiteboard_is_saved_and_exported_when_the_call_ends() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wb4").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let room_id = create_room(&app, tid, admin, "Final").await;
+    call_action(&app, tid, &room_id, admin, "start").await;
+    call_action(&app, tid, &room_id, admin, "join").await;
+
+    let mut ws = connect(&app, admin).await;
+    send(&mut ws, "whiteboard:op", add_rect(&room_id, "a")).await;
+    next_of(&mut ws, "whiteboard:op").await;
+
+    call_action(&app, tid, &room_id, admin, "end").await;
+
+    let saved = app
+        .db
+        .collection::<bson::Document>("whiteboards")
+        .find_one(bson::doc! { "room_id": bson::oid::ObjectId::parse_str(&room_id).unwrap() })
+        .await
+        .unwrap()
+        .expect("board not saved");
+    assert_eq!(saved.get_i64("seq").unwrap(), 1);
+
+    let board: Value = app
+        .auth_get(
+            &format!("/api/tenant/{}/room/{}/whiteboard", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(board["elements"].as_array().unwrap().len(), 1);
+    assert!(board["export_file_id"].is_string());
+}
+
+#[tokio::test]
+async fn whiteboard_requires_tenant_membership() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wb5").await;
+    let other = app.seed_tenant("wb5b").await;
+    let room_id = create_room(
+        &app,
+        &tenant.tenant_id,
+        &tenant.admin.access_token,
+        "Private",
+    )
+    .await;
+
+    let resp = app
+        .auth_get(
+            &format!(
+                "/api/tenant/{}/room/{}/whiteboard",
+                tenant.tenant_id, room_id
+            ),
+            &other.admin.access_token,
+        )
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 403);
+}
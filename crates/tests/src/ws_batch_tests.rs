@@ -0,0 +1,59 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::Ws;
+use futures::StreamExt;
+use serde_json::Value;
+
+/// The next text frame, parsed.
+async fn next_frame(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            if let Ok(text) = msg.to_text()
+                && let Ok(parsed) = serde_json::from_str::<Value>(text)
+            {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for WS frame")
+}
+
+#[tokio::test]
+async fn batching_connection_gets_array_frames() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wsbatch1").await;
+    let tid = &tenant.tenant_id;
+    let token = &tenant.admin.access_token;
+    let room_id = &tenant.rooms[0].id;
+
+    let ws_url = format!("ws://{}/ws?token={}&batch=true", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    let frame = next_frame(&mut ws).await;
+    assert_eq!(frame[0]["type"], "connected");
+
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/message", tid, room_id),
+        token,
+    )
+    .json(&serde_json::json!({ "content": "in a batch" }))
+    .send()
+    .await
+    .unwrap();
+    let frame = next_frame(&mut ws).await;
+    let events = frame.as_array().expect("array frame");
+    let created = events
+        .iter()
+        .find(|e| e["type"] == "message:create")
+        .expect("message:create in the frame");
+    assert_eq!(created["data"]["content"], "in a batch");
+
+    // Without `batch` frames stay single envelopes.
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut plain, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    assert_eq!(next_frame(&mut plain).await["type"], "connected");
+}
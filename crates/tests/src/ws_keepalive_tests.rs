@@ -0,0 +1,114 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of_within};
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn spawn(ping: u64, pong: u64, idle: u64) -> TestApp {
+    TestApp::spawn_with_settings(|s| {
+        s.ws.ping_interval_secs = ping;
+        s.ws.pong_timeout_secs = pong;
+        s.ws.idle_timeout_secs = idle;
+        s.mediasoup.reconnect_grace_secs = 0;
+    })
+    .await
+}
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn stats(app: &TestApp, token: &str) -> Value {
+    let resp = app.auth_get("/api/ws/stats", token).send().await.unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+    resp.json().await.unwrap()
+}
+
+#[tokio::test]
+async fn unresponsive_connection_is_reaped_and_leaves_the_call() {
+    let app = spawn(1, 1, 0).await;
+    let tenant = app.seed_tenant("keepalive1").await;
+    let tid = &tenant.tenant_id;
+    let room: Value = app
+        .auth_post(
+            &format!("/api/tenant/{}/room", tid),
+            &tenant.admin.access_token,
+        )
+        .json(&serde_json::json!({ "name": "Keepalive" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap();
+    for (token, action) in [
+        (&tenant.admin.access_token, "start"),
+        (&tenant.admin.access_token, "join"),
+        (&tenant.member.access_token, "join"),
+    ] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/call/{}", tid, room_id, action),
+            token,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let mut ghost = connect(&app, &tenant.admin.access_token).await;
+    let mut peer = connect(&app, &tenant.member.access_token).await;
+    for ws in [&mut ghost, &mut peer] {
+        ws.send(Message::Text(
+            serde_json::json!({ "type": "media:join", "data": { "room_id": room_id } })
+                .to_string()
+                .into(),
+        ))
+        .await
+        .unwrap();
+        next_of_within(ws, "media:transport_created", 5).await;
+    }
+
+    // The ghost stops reading, so it never answers a ping: a half-open
+    // connection as far as the server can tell.
+    let left = next_of_within(&mut peer, "media:peer_left", 6).await;
+    assert_eq!(left["user_id"], tenant.admin.id.as_str());
+
+    let counters = stats(&app, &tenant.member.access_token).await;
+    assert_eq!(counters["reaped_missed_pong"], 1);
+    assert_eq!(counters["reaped_idle"], 0);
+    drop(ghost);
+}
+
+#[tokio::test]
+async fn idle_connection_is_reaped() {
+    let app = spawn(1, 5, 2).await;
+    let tenant = app.seed_tenant("keepalive2").await;
+    let mut ws = connect(&app, &tenant.admin.access_token).await;
+
+    // The client answers pings but never sends a message.
+    let closed = tokio::time::timeout(std::time::Duration::from_secs(6), async {
+        loop {
+            match ws.next().await {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => return,
+                Some(Ok(_)) => {}
+            }
+        }
+    })
+    .await;
+    assert!(closed.is_ok(), "idle connection should be dropped");
+
+    let counters = stats(&app, &tenant.admin.access_token).await;
+    assert_eq!(counters["reaped_idle"], 1);
+    assert_eq!(counters["connections"], 0);
+    assert_eq!(counters["reaped_queue_overflow"], 0);
+    assert_eq!(counters["reaped_send_stalled"], 0);
+    assert_eq!(counters["queued_messages"], 0);
+    assert_eq!(counters["max_queue_depth"], 0);
+}
@@ -0,0 +1,167 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::Ws;
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::tungstenite::Message;
+
+async fn connect(app: &TestApp, token: &str) -> Ws {
+    let ws_url = format!("ws://{}/ws?token={}", app.addr, token);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    // Read "connected"
+    ws.next().await;
+    ws
+}
+
+async fn send(ws: &mut Ws, msg: Value) {
+    ws.send(Message::Text(msg.to_string().into()))
+        .await
+        .unwrap();
+}
+
+/// The next JSON message, whatever its type.
+async fn next_json(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            if let Ok(text) = msg.to_text()
+                && let Ok(parsed) = serde_json::from_str::<Value>(text)
+            {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for WS message")
+}
+
+async fn post_message(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, content: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": content }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+async fn join(app: &TestApp, tenant_id: &str, room_id: &str, token: &str) {
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn missed_room_events_are_replayed_on_sync() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wssync1").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.admin.access_token).await;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+
+    let mut ws = connect(&app, &tenant.member.access_token).await;
+    post_message(&app, tid, room_id, &tenant.admin.access_token, "seen").await;
+    let seen = next_json(&mut ws).await;
+    assert_eq!(seen["type"], "message:create");
+    assert_eq!(seen["room_id"], room_id.as_str());
+    let last_seq = seen["seq"].as_u64().expect("room events carry a seq");
+    ws.close(None).await.ok();
+
+    // Sent while the member is offline.
+    post_message(&app, tid, room_id, &tenant.admin.access_token, "missed 1").await;
+    post_message(&app, tid, room_id, &tenant.admin.access_token, "missed 2").await;
+
+    let mut ws = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut ws,
+        serde_json::json!({
+            "type": "sync",
+            "data": { "room_id": room_id, "last_seq": last_seq },
+        }),
+    )
+    .await;
+
+    let mut seq = last_seq;
+    for content in ["missed 1", "missed 2"] {
+        let event = next_json(&mut ws).await;
+        assert_eq!(event["type"], "message:create");
+        assert_eq!(event["data"]["content"], content);
+        let event_seq = event["seq"].as_u64().unwrap();
+        assert!(event_seq > seq, "replayed events come in seq order");
+        seq = event_seq;
+    }
+    let done = next_json(&mut ws).await;
+    assert_eq!(done["type"], "sync:done");
+    assert_eq!(done["data"]["room_id"], room_id.as_str());
+    assert_eq!(done["data"]["seq"], seq);
+
+    // Nothing missed since: just the marker.
+    send(
+        &mut ws,
+        serde_json::json!({
+            "type": "sync",
+            "data": { "room_id": room_id, "last_seq": seq },
+        }),
+    )
+    .await;
+    let done = next_json(&mut ws).await;
+    assert_eq!(done["type"], "sync:done");
+    assert_eq!(done["data"]["seq"], seq);
+}
+
+#[tokio::test]
+async fn unknown_gap_requires_resync() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wssync2").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.admin.access_token).await;
+    join(&app, tid, room_id, &tenant.member.access_token).await;
+    post_message(&app, tid, room_id, &tenant.admin.access_token, "hello").await;
+
+    let mut ws = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut ws,
+        serde_json::json!({
+            "type": "sync",
+            "data": { "room_id": room_id, "last_seq": 1_000_000 },
+        }),
+    )
+    .await;
+    let resync = next_json(&mut ws).await;
+    assert_eq!(resync["type"], "sync:resync_required");
+    assert_eq!(resync["data"]["room_id"], room_id.as_str());
+    assert!(resync["data"]["seq"].as_u64().unwrap() >= 1);
+}
+
+#[tokio::test]
+async fn non_members_cannot_sync() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wssync3").await;
+    let tid = &tenant.tenant_id;
+    let room_id = &tenant.rooms[0].id;
+    join(&app, tid, room_id, &tenant.admin.access_token).await;
+    post_message(&app, tid, room_id, &tenant.admin.access_token, "private").await;
+
+    let mut ws = connect(&app, &tenant.member.access_token).await;
+    send(
+        &mut ws,
+        serde_json::json!({
+            "type": "sync",
+            "data": { "room_id": room_id, "last_seq": 0 },
+        }),
+    )
+    .await;
+    send(&mut ws, serde_json::json!({ "type": "ping" })).await;
+    let reply = next_json(&mut ws).await;
+    assert_eq!(reply["type"], "pong", "sync from a non-member is ignored");
+}
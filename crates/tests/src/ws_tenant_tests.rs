@@ -0,0 +1,152 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+/// Connect and return the socket with its `connected` message.
+async fn connect(app: &TestApp, query: &str) -> (Ws, Value) {
+    let ws_url = format!("ws://{}/ws?{}", app.addr, query);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .expect("WS connect failed");
+    let connected = next_json(&mut ws).await;
+    assert_eq!(connected["type"], "connected");
+    (ws, connected)
+}
+
+async fn next_json(ws: &mut Ws) -> Value {
+    tokio::time::timeout(std::time::Duration::from_secs(3), async {
+        loop {
+            let msg = ws.next().await.unwrap().unwrap();
+            if let Ok(text) = msg.to_text()
+                && let Ok(parsed) = serde_json::from_str::<Value>(text)
+            {
+                return parsed;
+            }
+        }
+    })
+    .await
+    .expect("Timed out waiting for WS message")
+}
+
+/// A room in `tenant_id` that the token's user has joined.
+async fn joined_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name, "is_open": true }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let room_id = room["id"].as_str().unwrap().to_string();
+    app.auth_post(
+        &format!("/api/tenant/{}/room/{}/join", tenant_id, room_id),
+        token,
+    )
+    .send()
+    .await
+    .unwrap();
+    room_id
+}
+
+async fn post_message(app: &TestApp, tenant_id: &str, room_id: &str, token: &str, content: &str) {
+    let resp = app
+        .auth_post(
+            &format!("/api/tenant/{}/room/{}/message", tenant_id, room_id),
+            token,
+        )
+        .json(&serde_json::json!({ "content": content }))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 200);
+}
+
+#[tokio::test]
+async fn one_socket_receives_only_subscribed_tenants() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wsmux1").await;
+    let token = &tenant.admin.access_token;
+    let first = tenant.tenant_id.clone();
+    let second: Value = app
+        .auth_post("/api/tenant", token)
+        .json(&serde_json::json!({ "name": "wsmux1 Two", "slug": "wsmux1-two" }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let second = second["id"].as_str().unwrap().to_string();
+    let first_room = joined_room(&app, &first, token, "mux-first").await;
+    let second_room = joined_room(&app, &second, token, "mux-second").await;
+
+    let (mut ws, connected) = connect(&app, &format!("token={}&tenants={}", token, second)).await;
+    assert_eq!(connected["tenant_ids"], serde_json::json!([second]));
+
+    post_message(&app, &first, &first_room, token, "first 1").await;
+    post_message(&app, &second, &second_room, token, "second 1").await;
+    let event = next_json(&mut ws).await;
+    assert_eq!(event["type"], "message:create");
+    assert_eq!(event["tenant_id"], second.as_str());
+    assert_eq!(event["data"]["content"], "second 1");
+
+    // A tenant the user isn't in is ignored.
+    let stranger = bson::oid::ObjectId::new().to_hex();
+    send(
+        &mut ws,
+        "tenant:subscribe",
+        serde_json::json!({ "tenant_ids": [first, stranger] }),
+    )
+    .await;
+    let subscribed = next_json(&mut ws).await;
+    assert_eq!(subscribed["type"], "tenant:subscribed");
+    let mut expected = vec![first.clone(), second.clone()];
+    expected.sort();
+    assert_eq!(
+        subscribed["data"]["tenant_ids"],
+        serde_json::json!(expected)
+    );
+    post_message(&app, &first, &first_room, token, "first 2").await;
+    let event = next_json(&mut ws).await;
+    assert_eq!(event["tenant_id"], first.as_str());
+    assert_eq!(event["data"]["content"], "first 2");
+
+    send(
+        &mut ws,
+        "tenant:unsubscribe",
+        serde_json::json!({ "tenant_ids": [second] }),
+    )
+    .await;
+    let subscribed = next_json(&mut ws).await;
+    assert_eq!(subscribed["data"]["tenant_ids"], serde_json::json!([first]));
+    post_message(&app, &second, &second_room, token, "second 2").await;
+    post_message(&app, &first, &first_room, token, "first 3").await;
+    let event = next_json(&mut ws).await;
+    assert_eq!(event["data"]["content"], "first 3");
+
+    // Without `tenants` a connection hears every tenant, as before.
+    let (mut all, connected) = connect(&app, &format!("token={}", token)).await;
+    assert!(connected["tenant_ids"].is_null());
+    post_message(&app, &second, &second_room, token, "second 3").await;
+    let event = next_json(&mut all).await;
+    assert_eq!(event["tenant_id"], second.as_str());
+}
+
+#[tokio::test]
+async fn malformed_tenants_parameter_is_rejected() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("wsmux2").await;
+    let ws_url = format!(
+        "ws://{}/ws?token={}&tenants=not-an-id",
+        app.addr, tenant.member.access_token
+    );
+    match tokio_tungstenite::connect_async(&ws_url).await {
+        Err(tokio_tungstenite::tungstenite::Error::Http(resp)) => {
+            assert_eq!(resp.status().as_u16(), 400)
+        }
+        other => panic!("expected a 400, got {:?}", other.map(|_| ())),
+    }
+}
@@ -0,0 +1,124 @@
+use crate::fixtures::test_app::TestApp;
+use crate::fixtures::ws::{Ws, next_of, send};
+use futures::StreamExt;
+use serde_json::Value;
+
+async fn ticket(app: &TestApp, token: &str, body: Value) -> reqwest::Response {
+    app.auth_post("/api/auth/ws-ticket", token)
+        .json(&body)
+        .send()
+        .await
+        .unwrap()
+}
+
+async fn connect_with_ticket(app: &TestApp, ticket: &str) -> Result<Ws, String> {
+    let ws_url = format!("ws://{}/ws?ticket={}", app.addr, ticket);
+    let (mut ws, _) = tokio_tungstenite::connect_async(&ws_url)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Read "connected"
+    ws.next().await;
+    Ok(ws)
+}
+
+async fn create_room(app: &TestApp, tenant_id: &str, token: &str, name: &str) -> String {
+    let room: Value = app
+        .auth_post(&format!("/api/tenant/{}/room", tenant_id), token)
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    room["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn ticket_opens_one_connection() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ticket1").await;
+
+    let resp = app
+        .client
+        .post(app.url("/api/auth/ws-ticket"))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status().as_u16(), 401);
+
+    let resp = ticket(&app, &tenant.member.access_token, serde_json::json!({})).await;
+    assert_eq!(resp.status().as_u16(), 200);
+    let json: Value = resp.json().await.unwrap();
+    assert_eq!(json["expires_in"], 30);
+    assert!(json["media_rooms"].is_null());
+    let t = json["ticket"].as_str().unwrap();
+
+    let mut ws = connect_with_ticket(&app, t).await.unwrap();
+    send(&mut ws, "ping", serde_json::json!({})).await;
+    next_of(&mut ws, "pong").await;
+
+    // Replaying the ticket is refused.
+    assert!(connect_with_ticket(&app, t).await.is_err());
+    // An access token isn't a ticket.
+    assert!(
+        connect_with_ticket(&app, &tenant.member.access_token)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn media_ticket_is_limited_to_its_rooms() {
+    let app = TestApp::spawn().await;
+    let tenant = app.seed_tenant("ticket2").await;
+    let tid = &tenant.tenant_id;
+    let admin = &tenant.admin.access_token;
+    let allowed = create_room(&app, tid, admin, "Allowed").await;
+    let other = create_room(&app, tid, admin, "Other").await;
+
+    let resp = ticket(
+        &app,
+        admin,
+        serde_json::json!({ "media_rooms": ["not-an-id"] }),
+    )
+    .await;
+    assert_eq!(resp.status().as_u16(), 400);
+
+    for room_id in [&allowed, &other] {
+        app.auth_post(
+            &format!("/api/tenant/{}/room/{}/call/start", tid, room_id),
+            admin,
+        )
+        .send()
+        .await
+        .unwrap();
+    }
+
+    let json: Value = ticket(&app, admin, serde_json::json!({ "media_rooms": [allowed] }))
+        .await
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(json["media_rooms"][0], allowed.as_str());
+    let mut ws = connect_with_ticket(&app, json["ticket"].as_str().unwrap())
+        .await
+        .unwrap();
+
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": other }),
+    )
+    .await;
+    let err = next_of(&mut ws, "media:error").await;
+    assert!(err["message"].as_str().unwrap().contains("doesn't cover"));
+
+    send(
+        &mut ws,
+        "media:join",
+        serde_json::json!({ "room_id": allowed }),
+    )
+    .await;
+    next_of(&mut ws, "media:transport_created").await;
+}
@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct RegisterRequest {
+    pub email: String,
+    pub username: String,
+    pub display_name: String,
+    pub password: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_slug: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invite_code: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct AuthResponse {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_in: u64,
+    pub user: UserResponse,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub invite_tenant: Option<InviteTenantResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct InviteTenantResponse {
+    pub tenant_id: String,
+    pub tenant_name: String,
+    pub tenant_slug: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct UserResponse {
+    pub id: String,
+    pub email: String,
+    pub username: String,
+    pub display_name: String,
+    pub avatar: Option<String>,
+}
+
+/// Response shape for `POST /auth/register`. Always carries a
+/// `message`; when `ROOMLER__AUTH__AUTO_VERIFY=true` (e2e overlay)
+/// also returns access/refresh tokens + the user record so test
+/// helpers can chain register → authenticated API calls without
+/// an explicit login step. Production (auto_verify=false) returns
+/// only `message` — clients still call `/auth/login` after the
+/// email-link activation. Token fields skip-serialize when None
+/// so the prod payload stays a single `{ "message": "..." }`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct RegisterResponse {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub access_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_in: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<UserResponse>,
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct FileResponse {
+    pub id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+    pub url: String,
+    pub uploaded_by: String,
+    /// `pending` or `malware` files are quarantined: not downloadable.
+    #[serde(default)]
+    pub scan_status: String,
+    /// Page previews of PDF and Word documents, first page first.
+    #[serde(default)]
+    pub thumbnails: Vec<ThumbnailResponse>,
+    pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub room_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct ThumbnailResponse {
+    /// `page-1`, `page-2`, ...
+    pub size: String,
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
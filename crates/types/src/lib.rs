@@ -0,0 +1,46 @@
+//! Request and response bodies of the REST API, shared by the server
+//! (`roomler-ai-api`) and the Rust client (`roomler-ai-client`) so both
+//! sides serialize the same structs.
+//!
+//! Fields added after a type was first published carry `#[serde(default)]`,
+//! so newer clients still read older servers. With the `schema` feature
+//! every type derives `utoipa::ToSchema`.
+
+pub mod auth;
+pub mod file;
+pub mod message;
+pub mod notification;
+pub mod room;
+pub mod tenant;
+pub mod user;
+
+use serde::{Deserialize, Serialize};
+
+/// The envelope of every list endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct PaginatedResult<T> {
+    pub items: Vec<T>,
+    pub total: u64,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_pages: u64,
+    /// Cursor for the next page; only set in cursor mode while more items
+    /// remain.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+impl<T> PaginatedResult<T> {
+    /// Convert the items while keeping the pagination metadata.
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> PaginatedResult<U> {
+        PaginatedResult {
+            items: self.items.into_iter().map(f).collect(),
+            total: self.total,
+            page: self.page,
+            per_page: self.per_page,
+            total_pages: self.total_pages,
+            next_cursor: self.next_cursor,
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+
+use crate::user::UserStatusResponse;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct MentionRequest {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub everyone: bool,
+    #[serde(default)]
+    pub here: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct CreateMessageRequest {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thread_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub referenced_message_id: Option<String>,
+    /// Client-generated id for the optimistic copy; echoed in `message:ack`
+    /// and on the message.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mentions: Option<MentionRequest>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachment_ids: Vec<String>,
+    /// RFC 3339 delivery time. A future time stores the message and returns
+    /// `202` with the scheduled entry instead of posting it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_at: Option<String>,
+    /// Post without mention/thread notifications and without counting
+    /// towards unread.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub silent: bool,
+}
+
+impl CreateMessageRequest {
+    pub fn text(content: impl Into<String>) -> Self {
+        Self {
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct AttachmentResponse {
+    pub file_id: String,
+    pub filename: String,
+    pub content_type: String,
+    pub size: u64,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct ReactionSummaryResponse {
+    pub emoji: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct MessageResponse {
+    pub id: String,
+    pub room_id: String,
+    pub author_id: String,
+    pub author_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_avatar: Option<String>,
+    /// The author's custom status, while it lasts.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub author_status: Option<UserStatusResponse>,
+    /// `user`, `bot`, `webhook` or `system`; empty from older servers.
+    #[serde(default)]
+    pub author_type: String,
+    pub content: String,
+    pub message_type: String,
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub is_silent: bool,
+    pub is_edited: bool,
+    pub is_thread_root: bool,
+    pub thread_id: Option<String>,
+    pub referenced_message_id: Option<String>,
+    /// The `nonce` the author sent it with, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(default)]
+    pub reaction_summary: Vec<ReactionSummaryResponse>,
+    #[serde(default)]
+    pub attachments: Vec<AttachmentResponse>,
+    #[serde(default)]
+    pub is_read: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reply_count: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reply_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_reply_user_id: Option<String>,
+    /// Whether the viewer follows this thread; only set on thread roots.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub is_following: Option<bool>,
+    /// Only present on soft-deleted messages, which only moderators see.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_by: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct ScheduledMessageResponse {
+    pub id: String,
+    pub room_id: String,
+    pub content: String,
+    pub thread_id: Option<String>,
+    pub is_silent: bool,
+    pub send_at: String,
+    pub created_at: String,
+}
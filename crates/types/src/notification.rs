@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct NotificationResponse {
+    pub id: String,
+    pub notification_type: String,
+    pub title: String,
+    pub body: String,
+    pub link: Option<String>,
+    pub is_read: bool,
+    pub created_at: String,
+}
@@ -0,0 +1,60 @@
+use roomler_ai_db::models::MediaSettings;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct CreateRoomRequest {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(default)]
+    pub is_open: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schema(value_type = Option<Object>))]
+    pub media_settings: Option<MediaSettings>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct RoomResponse {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    pub parent_id: Option<String>,
+    pub is_open: bool,
+    /// Nobody may post.
+    #[serde(default)]
+    pub is_read_only: bool,
+    /// Only members with `MANAGE_CHANNELS` may post.
+    #[serde(default)]
+    pub is_announcement: bool,
+    pub member_count: u32,
+    pub message_count: u64,
+    pub has_media: bool,
+    /// Calls in this room use end-to-end encrypted media.
+    #[serde(default)]
+    pub e2ee_enabled: bool,
+    pub conference_status: Option<String>,
+    pub meeting_code: Option<String>,
+    pub participant_count: u32,
+    /// Exempt from the tenant's retention policy.
+    #[serde(default)]
+    pub legal_hold: bool,
+}
+
+/// Body of `POST .../call/start`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct CallStartResponse {
+    pub started: bool,
+    /// The router's RTP capabilities; `null` when another pod hosts the
+    /// conference.
+    #[cfg_attr(feature = "schema", schema(value_type = Object))]
+    pub rtp_capabilities: serde_json::Value,
+    /// Base URL of the pod hosting the conference, when the server runs
+    /// multi-pod; connect the media WS there.
+    #[serde(default)]
+    pub media_url: Option<String>,
+    #[serde(default)]
+    pub e2ee: bool,
+}
@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct TenantResponse {
+    pub id: String,
+    pub name: String,
+    pub slug: String,
+    pub owner_id: String,
+    pub plan: String,
+    /// File storage used against the tenant's quota.
+    #[serde(default)]
+    pub storage: StorageUsage,
+}
+
+/// A tenant's file storage: what it uses and what it may.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct StorageUsage {
+    pub used_bytes: u64,
+    /// `None` is unlimited.
+    pub quota_bytes: Option<u64>,
+}
@@ -0,0 +1,9 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(utoipa::ToSchema))]
+pub struct UserStatusResponse {
+    pub text: Option<String>,
+    pub emoji: Option<String>,
+    pub expires_at: Option<String>,
+}
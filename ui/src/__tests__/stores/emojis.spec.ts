@@ -0,0 +1,40 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest'
+import { setActivePinia, createPinia } from 'pinia'
+
+vi.mock('@/api/client', () => ({
+  api: {
+    get: vi.fn(),
+    post: vi.fn(),
+    put: vi.fn(),
+    delete: vi.fn(),
+    upload: vi.fn(),
+  },
+}))
+
+import { useEmojiStore } from '@/stores/emojis'
+import { api } from '@/api/client'
+
+const mockApi = vi.mocked(api)
+
+describe('useEmojiStore', () => {
+  beforeEach(() => {
+    setActivePinia(createPinia())
+    vi.clearAllMocks()
+  })
+
+  it('should fetch the tenant pack once and resolve :name: to its image', async () => {
+    mockApi.get.mockResolvedValue([
+      { id: 'e1', name: 'shipit', url: '/api/tenant/t1/file/f1/download', is_animated: false, created_at: '' },
+    ])
+    const store = useEmojiStore()
+
+    await store.fetchEmojis('t1')
+    await store.fetchEmojis('t1')
+
+    expect(mockApi.get).toHaveBeenCalledTimes(1)
+    expect(mockApi.get).toHaveBeenCalledWith('/tenant/t1/asset/emoji')
+    expect(store.imageFor(':shipit:')).toBe('/api/tenant/t1/file/f1/download')
+    expect(store.imageFor(':nope:')).toBeUndefined()
+    expect(store.imageFor('\u{1f44d}')).toBeUndefined()
+  })
+})
@@ -107,6 +107,8 @@ vi.stubGlobal('location', { protocol: 'http:', host: 'localhost:5000' })
 import { useWsStore } from '@/stores/ws'
 import { useMessageStore } from '@/stores/messages'
 import { useRoomStore } from '@/stores/rooms'
+import { useAuthStore } from '@/stores/auth'
+import { useSnackbar } from '@/composables/useSnackbar'
 
 describe('useWsStore', () => {
   beforeEach(() => {
@@ -289,6 +291,24 @@ describe('useWsStore', () => {
       expect(spy).not.toHaveBeenCalled()
     })
 
+    it('should NOT increment unread for own message:create from another device', () => {
+      const store = useWsStore()
+      store.connect('tok')
+      mockWsInstance.simulateOpen()
+
+      useAuthStore().user = { id: 'me' } as never
+      const roomStore = useRoomStore()
+      roomStore.setCurrent({ id: 'current-room' } as never)
+      const spy = vi.spyOn(roomStore, 'incrementUnread')
+
+      mockWsInstance.simulateMessage({
+        type: 'message:create',
+        data: { id: 'm1', room_id: 'other-room', author_id: 'me', content: 'hi' },
+      })
+
+      expect(spy).not.toHaveBeenCalled()
+    })
+
     it('should route room:call_started to roomStore.updateRoomCallStatus', () => {
       const store = useWsStore()
       store.connect('tok')
@@ -322,6 +342,28 @@ describe('useWsStore', () => {
     })
   })
 
+  describe('billing', () => {
+    it('should show billing:limit_reached in the snackbar', () => {
+      const store = useWsStore()
+      store.connect('tok')
+      mockWsInstance.simulateOpen()
+
+      const listener = vi.fn()
+      window.addEventListener('billing:limit_reached', listener)
+      mockWsInstance.simulateMessage({
+        type: 'billing:limit_reached',
+        data: { tenant_id: 't1', limit: 'members', message: 'The Free plan allows 10 members' },
+      })
+      window.removeEventListener('billing:limit_reached', listener)
+
+      const { state } = useSnackbar()
+      expect(state.show).toBe(true)
+      expect(state.color).toBe('warning')
+      expect(state.text).toContain('The Free plan allows 10 members')
+      expect(listener).toHaveBeenCalledOnce()
+    })
+  })
+
   describe('media handlers', () => {
     it('should register and invoke persistent media handlers', () => {
       const store = useWsStore()
@@ -351,6 +393,36 @@ describe('useWsStore', () => {
     })
   })
 
+  describe('batched frames', () => {
+    it('should ask for batching when connecting', () => {
+      const store = useWsStore()
+      store.connect('tok')
+
+      const url = (mockWsInstance as unknown as Record<string, unknown>).url as string
+      expect(url).toContain('batch=true')
+    })
+
+    it('should route each event of an array frame in order', () => {
+      const store = useWsStore()
+      store.connect('tok')
+      mockWsInstance.simulateOpen()
+
+      const messageStore = useMessageStore()
+      const spy = vi.spyOn(messageStore, 'addMessageFromWs')
+      const handler = vi.fn()
+      store.onRcMessage('rc:ice', handler)
+
+      mockWsInstance.simulateMessage([
+        { type: 'message:create', data: { id: 'm1', room_id: 'r1', content: 'one' } },
+        { t: 'rc:ice', session_id: 'x', candidate: {} },
+        { type: 'message:create', data: { id: 'm2', room_id: 'r1', content: 'two' } },
+      ])
+
+      expect(spy.mock.calls.map((c) => (c[0] as { id: string }).id)).toEqual(['m1', 'm2'])
+      expect(handler).toHaveBeenCalledTimes(1)
+    })
+  })
+
   describe('ping interval', () => {
     it('should send ping every 30 seconds after connecting', () => {
       const store = useWsStore()
@@ -1,41 +1,61 @@
 import { ref } from 'vue'
 import { useWsStore } from '@/stores/ws'
+import { useConferenceStore } from '@/stores/conference'
 
 interface PlaybackMessage {
-  action: 'start' | 'stop'
-  file_url?: string
+  action: 'start' | 'gain' | 'stop'
+  producer_id?: string
   file_id?: string
   filename?: string
   playback_id: string
   room_id: string
 }
 
+interface SpeechMessage {
+  action: 'start' | 'end'
+  producer_id?: string
+  speech_id: string
+  room_id: string
+  user_id?: string
+  text?: string
+}
+
 export function useAudioPlayback() {
   const wsStore = useWsStore()
-  const activePlayback = ref<{ id: string; audio: HTMLAudioElement } | null>(null)
+  const conferenceStore = useConferenceStore()
+  const activePlayback = ref<{ id: string; producerId: string; audio: HTMLAudioElement } | null>(
+    null,
+  )
   const isPlaying = ref(false)
+  const activeSpeech = ref<{
+    id: string
+    producerId: string
+    text: string
+    audio: HTMLAudioElement
+  } | null>(null)
 
-  function handlePlaybackMessage(data: PlaybackMessage) {
-    if (data.action === 'start' && data.file_url) {
+  async function handlePlaybackMessage(data: PlaybackMessage) {
+    if (data.action === 'start' && data.producer_id) {
       // Stop any existing playback first
       stopCurrentPlayback()
 
-      const audio = new Audio(data.file_url)
-      audio.addEventListener('ended', () => {
-        isPlaying.value = false
-        activePlayback.value = null
-      })
-      audio.addEventListener('error', (e) => {
-        console.error('Audio playback error:', e)
-        isPlaying.value = false
-        activePlayback.value = null
-      })
+      // Everyone consumes the same server-side producer, so the call hears it in sync.
+      // The server applies the playback's gain to the stream itself.
+      const audio = new Audio()
+      activePlayback.value = { id: data.playback_id, producerId: data.producer_id, audio }
+      isPlaying.value = true
+
+      const stream = await conferenceStore.consumePlayback(data.producer_id).catch(() => null)
+      if (activePlayback.value?.id !== data.playback_id) {
+        // Stopped while the consumer was being created
+        conferenceStore.closePlayback(data.producer_id)
+        return
+      }
+      if (!stream) return
+      audio.srcObject = stream
       audio.play().catch((err) => {
         console.error('Failed to start audio playback:', err)
       })
-
-      activePlayback.value = { id: data.playback_id, audio }
-      isPlaying.value = true
     } else if (data.action === 'stop') {
       if (activePlayback.value?.id === data.playback_id) {
         stopCurrentPlayback()
@@ -43,6 +63,37 @@ export function useAudioPlayback() {
     }
   }
 
+  async function handleSpeechMessage(data: SpeechMessage) {
+    if (data.action === 'start' && data.producer_id) {
+      stopCurrentSpeech()
+
+      // Announcements are server-side producers too, heard by everyone at once
+      const audio = new Audio()
+      activeSpeech.value = {
+        id: data.speech_id,
+        producerId: data.producer_id,
+        text: data.text ?? '',
+        audio,
+      }
+
+      const stream = await conferenceStore.consumePlayback(data.producer_id).catch(() => null)
+      if (activeSpeech.value?.id !== data.speech_id) {
+        // Ended while the consumer was being created
+        conferenceStore.closePlayback(data.producer_id)
+        return
+      }
+      if (!stream) return
+      audio.srcObject = stream
+      audio.play().catch((err) => {
+        console.error('Failed to start announcement:', err)
+      })
+    } else if (data.action === 'end') {
+      if (activeSpeech.value?.id === data.speech_id) {
+        stopCurrentSpeech()
+      }
+    }
+  }
+
   function requestPlay(roomId: string, fileId: string) {
     wsStore.send('media:play_audio', {
       room_id: roomId,
@@ -57,10 +108,28 @@ export function useAudioPlayback() {
     })
   }
 
+  function requestSpeak(roomId: string, text: string, voice?: string) {
+    wsStore.send('media:speak', {
+      room_id: roomId,
+      text,
+      voice,
+    })
+  }
+
+  function stopCurrentSpeech() {
+    if (activeSpeech.value) {
+      activeSpeech.value.audio.pause()
+      activeSpeech.value.audio.srcObject = null
+      conferenceStore.closePlayback(activeSpeech.value.producerId)
+      activeSpeech.value = null
+    }
+  }
+
   function stopCurrentPlayback() {
     if (activePlayback.value) {
       activePlayback.value.audio.pause()
-      activePlayback.value.audio.src = ''
+      activePlayback.value.audio.srcObject = null
+      conferenceStore.closePlayback(activePlayback.value.producerId)
       activePlayback.value = null
       isPlaying.value = false
     }
@@ -68,10 +137,14 @@ export function useAudioPlayback() {
 
   return {
     activePlayback,
+    activeSpeech,
     isPlaying,
     handlePlaybackMessage,
+    handleSpeechMessage,
     requestPlay,
     requestStop,
+    requestSpeak,
     stopCurrentPlayback,
+    stopCurrentSpeech,
   }
 }
@@ -278,13 +278,8 @@ export const useConferenceStore = defineStore('conference', () => {
     return stream
   }
 
-  async function consumeProducer(
-    producerId: string,
-    userId: string,
-    connectionId: string,
-    source: string,
-  ) {
-    if (!recvTransport.value || !device.value) return
+  async function createConsumer(producerId: string) {
+    if (!recvTransport.value || !device.value) return null
 
     const ws = useWsStore()
     const resultPromise = ws.waitForMessage('media:consumer_created')
@@ -304,6 +299,18 @@ export const useConferenceStore = defineStore('conference', () => {
     })
 
     consumers.set(consumer.id, consumer)
+    return { consumer, result }
+  }
+
+  async function consumeProducer(
+    producerId: string,
+    userId: string,
+    connectionId: string,
+    source: string,
+  ) {
+    const created = await createConsumer(producerId)
+    if (!created) return
+    const { consumer, result } = created
 
     const streamKey = source === 'screen' ? `${connectionId}:screen` : connectionId
     consumerStreamKey.set(consumer.id, streamKey)
@@ -334,6 +341,28 @@ export const useConferenceStore = defineStore('conference', () => {
       .catch((err) => console.error('[conference] consumeProducer failed:', err))
   }
 
+  /** Consume the server-side producer of a shared file playback. */
+  function consumePlayback(producerId: string): Promise<MediaStream | null> {
+    const stream = consumeChain.then(async () => {
+      const created = await createConsumer(producerId)
+      return created ? new MediaStream([created.consumer.track]) : null
+    })
+    consumeChain = stream.then(
+      () => undefined,
+      (err) => console.error('[conference] consumePlayback failed:', err),
+    )
+    return stream
+  }
+
+  function closePlayback(producerId: string) {
+    for (const [id, consumer] of consumers) {
+      if (consumer.producerId === producerId) {
+        consumer.close()
+        consumers.delete(id)
+      }
+    }
+  }
+
   function handlePeerLeft(data: { user_id: string; connection_id?: string }) {
     const connectionId = data.connection_id || data.user_id
     remoteStreams.delete(connectionId)
@@ -631,6 +660,8 @@ export const useConferenceStore = defineStore('conference', () => {
     toggleVideo,
     startScreenShare,
     stopScreenShare,
+    consumePlayback,
+    closePlayback,
     startActiveSpeaker,
     stopActiveSpeaker,
   }
@@ -0,0 +1,35 @@
+import { defineStore } from 'pinia'
+import { ref, computed } from 'vue'
+import { api } from '@/api/client'
+
+export interface CustomEmoji {
+  id: string
+  name: string
+  url: string
+  is_animated: boolean
+  created_at: string
+}
+
+export const useEmojiStore = defineStore('emojis', () => {
+  const emojis = ref<CustomEmoji[]>([])
+  const tenantId = ref<string | null>(null)
+
+  const byShortcode = computed(() => {
+    const map: Record<string, CustomEmoji> = {}
+    for (const e of emojis.value) map[`:${e.name}:`] = e
+    return map
+  })
+
+  async function fetchEmojis(tid: string) {
+    if (tenantId.value === tid) return
+    emojis.value = await api.get<CustomEmoji[]>(`/tenant/${tid}/asset/emoji`)
+    tenantId.value = tid
+  }
+
+  /** Image of a custom emoji reaction (`:name:`), if the tenant has one. */
+  function imageFor(emoji: string): string | undefined {
+    return byShortcode.value[emoji]?.url
+  }
+
+  return { emojis, fetchEmojis, imageFor }
+})
@@ -8,6 +8,8 @@ export interface Member {
   user_id: string
   nickname?: string | null
   display_name: string
+  /** Bot account (see `/tenant/{id}/bot`). */
+  is_bot?: boolean
   role_ids: string[]
   joined_at: string
 }
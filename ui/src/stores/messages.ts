@@ -98,11 +98,8 @@ export const useMessageStore = defineStore('messages', () => {
       `/tenant/${tenantId}/room/${roomId}/message`,
       body,
     )
-    if (threadId) {
-      threadMessages.value.push(msg)
-    } else {
-      messages.value.push(msg)
-    }
+    // The WS `message:create` echo may have added it already.
+    addMessageFromWs(msg)
     return msg
   }
 
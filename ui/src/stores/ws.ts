@@ -1,10 +1,12 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
+import { useAuthStore } from './auth'
 import { useRoomStore } from './rooms'
 import { useMessageStore } from './messages'
 import { useNotificationStore } from './notification'
 import { useTaskStore } from './tasks'
 import { useConferenceStore } from './conference'
+import { useSnackbar } from '@/composables/useSnackbar'
 
 type WsStatus = 'disconnected' | 'connecting' | 'connected'
 
@@ -37,7 +39,8 @@ export const useWsStore = defineStore('ws', () => {
     const protocol = location.protocol === 'https:' ? 'wss:' : 'ws:'
     // In dev mode, connect directly to the API server to bypass Vite proxy (which doesn't relay WS frames)
     const wsHost = import.meta.env.DEV ? 'localhost:5001' : location.host
-    socket = new WebSocket(`${protocol}//${wsHost}/ws?token=${token}`)
+    // batch=true: the server may send several events in one array frame.
+    socket = new WebSocket(`${protocol}//${wsHost}/ws?token=${token}&batch=true`)
 
     socket.onopen = () => {
       status.value = 'connected'
@@ -49,21 +52,19 @@ export const useWsStore = defineStore('ws', () => {
     }
 
     socket.onmessage = (event) => {
+      let parsed
       try {
-        const msg = JSON.parse(event.data)
-        if (msg.type?.startsWith('media:') || msg.type === 'connected') {
-          console.log('[WS] received:', msg.type)
-        }
-        // rc:* messages use the flat `{t, ...}` shape from the signalling
-        // protocol and don't go through the main dispatch.
-        if (typeof msg.t === 'string' && msg.t.startsWith('rc:')) {
-          const h = rcHandlers.get(msg.t)
-          if (h) h(msg)
-          return
-        }
-        handleMessage(msg)
+        parsed = JSON.parse(event.data)
       } catch {
         // ignore malformed messages
+        return
+      }
+      for (const msg of Array.isArray(parsed) ? parsed : [parsed]) {
+        try {
+          receive(msg)
+        } catch {
+          // one bad event doesn't drop the rest of its batch
+        }
       }
     }
 
@@ -78,6 +79,21 @@ export const useWsStore = defineStore('ws', () => {
     }
   }
 
+  // eslint-disable-next-line @typescript-eslint/no-explicit-any
+  function receive(msg: any) {
+    if (msg.type?.startsWith('media:') || msg.type === 'connected') {
+      console.log('[WS] received:', msg.type)
+    }
+    // rc:* messages use the flat `{t, ...}` shape from the signalling
+    // protocol and don't go through the main dispatch.
+    if (typeof msg.t === 'string' && msg.t.startsWith('rc:')) {
+      const h = rcHandlers.get(msg.t)
+      if (h) h(msg)
+      return
+    }
+    handleMessage(msg)
+  }
+
   function handleMessage(msg: { type: string; data?: unknown }) {
     const roomStore = useRoomStore()
     const messageStore = useMessageStore()
@@ -103,8 +119,10 @@ export const useWsStore = defineStore('ws', () => {
       case 'message:create': {
         messageStore.addMessageFromWs(msg.data as never)
         // Increment unread count if user is not viewing this room
-        const msgData = msg.data as { room_id?: string }
-        if (msgData.room_id) {
+        // Own messages arrive here too, from this and the user's other devices
+        const msgData = msg.data as { room_id?: string; author_id?: string }
+        const myId = useAuthStore().user?.id
+        if (msgData.room_id && !(myId && msgData.author_id === myId)) {
           const roomStore = useRoomStore()
           if (roomStore.current?.id !== msgData.room_id) {
             roomStore.incrementUnread(msgData.room_id)
@@ -136,6 +154,12 @@ export const useWsStore = defineStore('ws', () => {
         notificationStore.setUnreadCount(ncData.count)
         break
       }
+      case 'billing:limit_reached': {
+        const blData = msg.data as { tenant_id: string; limit: string; message: string }
+        useSnackbar().showSnackbar(`${blData.message}. Upgrade your plan to continue.`, 'warning', 8000)
+        window.dispatchEvent(new CustomEvent('billing:limit_reached', { detail: blData }))
+        break
+      }
       case 'room:call_started': {
         const csData = msg.data as { room_id: string; room_name: string; started_by: string }
         roomStore.updateRoomCallStatus(csData.room_id, 'in_progress')